@@ -0,0 +1,162 @@
+use std::hint::black_box;
+
+use criterion::BatchSize;
+use criterion::Criterion;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use nuttyverse_core::access::repository::AccessRepository;
+use nuttyverse_core::access::service::AccessService;
+use nuttyverse_core::content::repository::ContentRepository;
+use nuttyverse_core::content::service::ContentService;
+use nuttyverse_core::content::service::ContentServiceApi;
+use nuttyverse_core::models::ContentBlock;
+use nuttyverse_core::models::DissociatedNuttyId;
+use nuttyverse_core::models::FractionalIndex;
+use nuttyverse_core::models::NuttyId;
+use nuttyverse_core::models::children_view::ChildrenView;
+use nuttyverse_core::seed::DemoData;
+use nuttyverse_core::seed::SeedConfig;
+use sqlx::postgres::PgPoolOptions;
+use tokio::runtime::Runtime;
+
+fn fractional_index(c: &mut Criterion) {
+	let mut group = c.benchmark_group("fractional_index");
+
+	// Appending keeps splitting the gap before the end.
+	group.bench_function("append_100", |b| {
+		b.iter(|| {
+			let mut index = FractionalIndex::start();
+
+			for _ in 0..100 {
+				index = FractionalIndex::between(&index, &FractionalIndex::end()).unwrap();
+			}
+
+			black_box(index)
+		})
+	});
+
+	// Inserting at the front keeps splitting the gap after the start.
+	group.bench_function("prepend_100", |b| {
+		b.iter(|| {
+			let mut index = FractionalIndex::end();
+
+			for _ in 0..100 {
+				index = FractionalIndex::between(&FractionalIndex::start(), &index).unwrap();
+			}
+
+			black_box(index)
+		})
+	});
+
+	group.finish();
+}
+
+fn nutty_id(c: &mut Criterion) {
+	let mut group = c.benchmark_group("nutty_id");
+	let nutty_id = NuttyId::now();
+	let nid = nutty_id.nid();
+
+	group.bench_function("encode", |b| b.iter(|| black_box(&nutty_id).nid()));
+	group.bench_function("decode", |b| {
+		b.iter(|| DissociatedNuttyId::new(black_box(&nid)).unwrap())
+	});
+
+	group.finish();
+}
+
+/// Assemble contexts from a seeded tree. Skipped unless `DATABASE_URL` points
+/// at a migrated database. The seeded blocks are deleted afterwards.
+fn context_assembly(c: &mut Criterion) {
+	let Ok(database_url) = std::env::var("DATABASE_URL") else {
+		eprintln!("Skipping context assembly: DATABASE_URL is unset.");
+		return;
+	};
+
+	let runtime = Runtime::new().unwrap();
+
+	let pool = runtime
+		.block_on(
+			PgPoolOptions::new()
+				.max_connections(5)
+				.connect(&database_url),
+		)
+		.expect("Failed to connect to database");
+
+	let access_service = AccessService::new(AccessRepository::new(pool.clone()));
+	let service = ContentService::new(ContentRepository::new(pool), access_service);
+
+	let data = DemoData::generate(&SeedConfig {
+		pages: 100,
+		..SeedConfig::default()
+	})
+	.unwrap();
+
+	let roots: Vec<ContentBlock> = data
+		.roots
+		.iter()
+		.map(|&root| data.blocks[root].clone())
+		.collect();
+
+	// The busiest page, by direct children.
+	let busiest = roots
+		.iter()
+		.max_by_key(|root| {
+			data
+				.blocks
+				.iter()
+				.filter(|block| block.parent_id == Some(*root.nutty_id()))
+				.count()
+		})
+		.unwrap()
+		.nutty_id()
+		.dissociate();
+
+	runtime.block_on(async {
+		for block in data.blocks.clone() {
+			service.save_content_block(None, block).await.unwrap();
+		}
+	});
+
+	let mut group = c.benchmark_group("context");
+	let view = ChildrenView::default();
+	let reader_id = NuttyId::now();
+
+	group.bench_function("assemble", |b| {
+		b.to_async(&runtime).iter(|| async {
+			service
+				.get_content_block_context(&reader_id, &busiest, &view)
+				.await
+				.unwrap()
+		})
+	});
+
+	// Saving reindexes links and records a revision.
+	let paragraph = data
+		.blocks
+		.iter()
+		.find(|block| block.parent_id.is_some())
+		.unwrap()
+		.clone();
+
+	group.bench_function("save", |b| {
+		b.to_async(&runtime).iter_batched(
+			|| paragraph.clone(),
+			|block| async { service.save_content_block(None, block).await.unwrap() },
+			BatchSize::SmallInput,
+		)
+	});
+
+	group.finish();
+
+	runtime.block_on(async {
+		for root in roots {
+			service
+				.delete_content_block(&root.nutty_id().dissociate(), true)
+				.await
+				.unwrap();
+		}
+	});
+}
+
+criterion_group!(benches, fractional_index, nutty_id, context_assembly);
+criterion_main!(benches);
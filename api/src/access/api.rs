@@ -0,0 +1,193 @@
+use std::sync::Arc;
+
+use axum::Json;
+use axum::Router;
+use axum::extract::Query;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use serde::Deserialize;
+
+use crate::access::policy::AccessPolicy;
+use crate::access::service::AccessServiceError;
+use crate::models::NuttyId;
+use crate::utilities::api::response::Error;
+use crate::utilities::api::response::Response;
+use crate::utilities::api::session::Session;
+use crate::utilities::api::state::AppState;
+
+/// The permission required to export and apply access policies.
+const POLICY_PERMISSION: &str = "system:access:policy";
+
+/// The router for access control API endpoints.
+pub fn router(app_state: Arc<AppState>) -> Router {
+	Router::new()
+		.route(
+			"/access/policy",
+			get(export_policy_handler).put(apply_policy_handler),
+		)
+		.with_state(app_state)
+}
+
+/// Query parameters for exporting an access policy.
+#[derive(Debug, Deserialize)]
+pub struct ExportPolicyQuery {
+	/// `yaml` for a raw YAML document, instead of the JSON response.
+	format: Option<String>,
+}
+
+/// An API handler for exporting every role and permission, and which roles
+/// have which permissions, to apply to another environment.
+async fn export_policy_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Query(query): Query<ExportPolicyQuery>,
+) -> axum::response::Response {
+	let summary = "Failed to export access policy.";
+
+	if let Err(response) = require_policy_access(&state, navigator.nutty_id(), summary).await {
+		return response;
+	}
+
+	let policy = match state.access_service.export_access_policy().await {
+		Ok(policy) => policy,
+
+		Err(error) => {
+			return fail(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				AccessApiError::ExportPolicy(error),
+				summary,
+			);
+		}
+	};
+
+	match query.format.as_deref() {
+		None | Some("json") => (
+			StatusCode::OK,
+			Json(Response::Single { data: Some(policy) }),
+		)
+			.into_response(),
+
+		Some("yaml") => match policy.to_yaml() {
+			Ok(yaml) => (StatusCode::OK, [(CONTENT_TYPE, "application/yaml")], yaml).into_response(),
+
+			Err(error) => fail(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				AccessApiError::ExportPolicy(error.into()),
+				summary,
+			),
+		},
+
+		Some(format) => fail(
+			StatusCode::BAD_REQUEST,
+			AccessApiError::UnknownFormat(format.to_string()),
+			summary,
+		),
+	}
+}
+
+/// Query parameters for applying an access policy.
+#[derive(Debug, Deserialize)]
+pub struct ApplyPolicyQuery {
+	/// Whether to only list the changes, without making them.
+	#[serde(default)]
+	dry_run: bool,
+}
+
+/// An API handler for applying an access policy, written as YAML or JSON.
+/// Responds with the changes made, or that would be made on a dry run.
+async fn apply_policy_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Query(query): Query<ApplyPolicyQuery>,
+	body: String,
+) -> axum::response::Response {
+	let summary = "Failed to apply access policy.";
+
+	if let Err(response) = require_policy_access(&state, navigator.nutty_id(), summary).await {
+		return response;
+	}
+
+	let policy = match AccessPolicy::parse(&body) {
+		Ok(policy) => policy,
+
+		Err(error) => {
+			return fail(
+				StatusCode::BAD_REQUEST,
+				AccessApiError::ApplyPolicy(error.into()),
+				summary,
+			);
+		}
+	};
+
+	match state
+		.access_service
+		.apply_access_policy(&policy, query.dry_run)
+		.await
+	{
+		Ok(diff) => (StatusCode::OK, Json(Response::Single { data: Some(diff) })).into_response(),
+
+		Err(error) => {
+			let status = match &error {
+				AccessServiceError::InvalidPolicy(_) => StatusCode::BAD_REQUEST,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			fail(status, AccessApiError::ApplyPolicy(error), summary)
+		}
+	}
+}
+
+/// Check that a navigator may export and apply access policies, or make the
+/// response for why not.
+async fn require_policy_access(
+	state: &AppState,
+	navigator_id: &NuttyId,
+	summary: &str,
+) -> Result<(), axum::response::Response> {
+	match state
+		.access_service
+		.can_permission(navigator_id, POLICY_PERMISSION)
+		.await
+	{
+		Ok(true) => Ok(()),
+		Ok(false) => Err(fail(
+			StatusCode::FORBIDDEN,
+			AccessApiError::AccessDenied,
+			summary,
+		)),
+
+		Err(error) => Err(fail(
+			StatusCode::INTERNAL_SERVER_ERROR,
+			AccessApiError::AccessControl(error),
+			summary,
+		)),
+	}
+}
+
+fn fail(status: StatusCode, error: AccessApiError, summary: &str) -> axum::response::Response {
+	let error = Error::from_error(&error).with_summary(summary);
+	let errors = vec![error];
+
+	(status, Json(Response::<()>::Error { errors })).into_response()
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AccessApiError {
+	#[error("Access denied.")]
+	AccessDenied,
+
+	#[error("Failed to check access permissions: {0}")]
+	AccessControl(#[source] AccessServiceError),
+
+	#[error("Unknown format: '{0}' (expected 'json' or 'yaml')")]
+	UnknownFormat(String),
+
+	#[error("Failed to export access policy: {0}")]
+	ExportPolicy(#[source] AccessServiceError),
+
+	#[error("Failed to apply access policy: {0}")]
+	ApplyPolicy(#[source] AccessServiceError),
+}
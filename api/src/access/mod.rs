@@ -1,3 +1,5 @@
+pub mod api;
 pub mod models;
+pub mod policy;
 pub mod repository;
 pub mod service;
@@ -1,11 +1,119 @@
+use std::str::FromStr;
+
 use serde::Deserialize;
 use serde::Serialize;
+use sqlx::Decode;
+use sqlx::Encode;
 use sqlx::FromRow;
+use sqlx::Postgres;
+use sqlx::Type;
+use sqlx::postgres::PgTypeInfo;
+use sqlx::postgres::PgValueRef;
 use thiserror::Error;
 
 use crate::models::NuttyId;
 use crate::models::date_time_rfc_3339::DateTimeRfc3339;
 
+/// A kind of resource that roles can be granted on.
+///
+/// Adding a resource is a matter of adding a kind here and registering how
+/// to find its owner in [ResourceKind::owner_lookup].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+	ContentBlock,
+	Navigator,
+}
+
+/// How to find the owner of a resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OwnerLookup {
+	/// The owner is named by a column of the resource's table.
+	Column {
+		table: &'static str,
+		column: &'static str,
+	},
+
+	/// The resource owns itself.
+	Itself,
+}
+
+impl ResourceKind {
+	/// Get the stored representation of the kind.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ResourceKind::ContentBlock => "content_block",
+			ResourceKind::Navigator => "navigator",
+		}
+	}
+
+	/// Get how to find the owner of a resource of this kind.
+	pub fn owner_lookup(&self) -> OwnerLookup {
+		match self {
+			ResourceKind::ContentBlock => OwnerLookup::Column {
+				table: "content.blocks",
+				column: "owner_id",
+			},
+			ResourceKind::Navigator => OwnerLookup::Itself,
+		}
+	}
+}
+
+impl std::fmt::Display for ResourceKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+impl FromStr for ResourceKind {
+	type Err = AccessError;
+
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		match value {
+			"content_block" => Ok(ResourceKind::ContentBlock),
+			"navigator" => Ok(ResourceKind::Navigator),
+			_ => Err(AccessError::UnknownResourceKind(value.to_string())),
+		}
+	}
+}
+
+impl Type<Postgres> for ResourceKind {
+	fn type_info() -> PgTypeInfo {
+		PgTypeInfo::with_name("VARCHAR")
+	}
+
+	fn compatible(ty: &PgTypeInfo) -> bool {
+		<&str as Type<Postgres>>::compatible(ty)
+	}
+}
+
+impl Encode<'_, Postgres> for ResourceKind {
+	fn encode_by_ref(
+		&self,
+		buf: &mut <Postgres as sqlx::Database>::ArgumentBuffer<'_>,
+	) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+		<&str as Encode<Postgres>>::encode(self.as_str(), buf)
+	}
+}
+
+impl<'r> Decode<'r, Postgres> for ResourceKind {
+	fn decode(value: PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+		let value = <&str as Decode<Postgres>>::decode(value)?;
+		Ok(value.parse()?)
+	}
+}
+
+/// The maximum length of a role or permission name, in characters.
+pub const MAX_NAME_LENGTH: usize = 100;
+
+/// Check if a name can be given to a role or permission: not empty, not
+/// too long, and without whitespace.
+pub fn is_valid_name(name: &str) -> bool {
+	!name.is_empty()
+		&& name.chars().count() <= MAX_NAME_LENGTH
+		&& !name.chars().any(char::is_whitespace)
+}
+
 /// A permission that can be granted to roles.
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Permission {
@@ -20,6 +128,55 @@ pub struct Role {
 	description: String,
 }
 
+/// What was renamed by an [AccessRename].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RenameKind {
+	Role,
+	Permission,
+}
+
+impl RenameKind {
+	/// Get the stored representation of the kind.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			RenameKind::Role => "role",
+			RenameKind::Permission => "permission",
+		}
+	}
+}
+
+impl FromStr for RenameKind {
+	type Err = AccessError;
+
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		match value {
+			"role" => Ok(RenameKind::Role),
+			"permission" => Ok(RenameKind::Permission),
+			_ => Err(AccessError::UnknownRenameKind(value.to_string())),
+		}
+	}
+}
+
+/// A renamed role or permission, as recorded for auditing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessRename {
+	pub nutty_id: NuttyId,
+	pub kind: RenameKind,
+	pub old_name: String,
+	pub new_name: String,
+
+	/// The number of grants moved to the new name: role permissions, and
+	/// global and resource roles.
+	pub grant_count: i64,
+
+	/// The navigator that renamed it, unless it was renamed from the
+	/// command line.
+	pub renamed_by: Option<NuttyId>,
+
+	pub renamed_at: DateTimeRfc3339,
+}
+
 /// Associates a navigator with a global role.
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct NavigatorRole {
@@ -38,28 +195,99 @@ pub struct ResourceRole {
 	nutty_id: NuttyId,
 	navigator_id: Option<NuttyId>,
 	role_name: String,
-	resource_type: String,
+	resource_type: ResourceKind,
 	resource_id: NuttyId,
 	created_at: DateTimeRfc3339,
 	updated_at: DateTimeRfc3339,
 }
 
 impl ResourceRole {
+	/// Create a new resource role assignment.
+	pub fn new(
+		navigator_id: NuttyId,
+		role_name: String,
+		resource_type: ResourceKind,
+		resource_id: NuttyId,
+	) -> Self {
+		let now = DateTimeRfc3339::new(chrono::Utc::now().fixed_offset());
+
+		Self {
+			nutty_id: NuttyId::now(),
+			navigator_id: Some(navigator_id),
+			role_name,
+			resource_type,
+			resource_id,
+			created_at: now,
+			updated_at: now,
+		}
+	}
+
+	pub fn navigator_id(&self) -> Option<&NuttyId> {
+		self.navigator_id.as_ref()
+	}
+
+	pub fn resource_type(&self) -> ResourceKind {
+		self.resource_type
+	}
+
 	pub fn role_name(&self) -> &str {
 		&self.role_name
 	}
 
+	/// Move the assignment to a renamed role.
+	pub fn set_role_name(&mut self, role_name: String) {
+		self.role_name = role_name;
+	}
+
 	pub fn resource_id(&self) -> &NuttyId {
 		&self.resource_id
 	}
 }
 
+/// A resource role, as listed for a resource: granted on the resource
+/// itself, or inherited from one of its ancestors.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ResourceGrant {
+	#[sqlx(flatten)]
+	#[serde(flatten)]
+	role: ResourceRole,
+
+	/// The name of the navigator, if any (anonymous grants have none).
+	navigator_name: Option<String>,
+
+	/// Whether the role is granted on an ancestor of the resource.
+	inherited: bool,
+}
+
+impl ResourceGrant {
+	/// Create a new resource grant.
+	pub fn new(role: ResourceRole, navigator_name: Option<String>, inherited: bool) -> Self {
+		Self {
+			role,
+			navigator_name,
+			inherited,
+		}
+	}
+
+	pub fn role(&self) -> &ResourceRole {
+		&self.role
+	}
+
+	pub fn navigator_name(&self) -> Option<&str> {
+		self.navigator_name.as_deref()
+	}
+
+	pub fn is_inherited(&self) -> bool {
+		self.inherited
+	}
+}
+
 /// A permission check request.
 #[derive(Debug, Clone)]
 pub struct PermissionCheck {
 	navigator_id: Option<NuttyId>,
 	permission: String,
-	resource_type: Option<String>,
+	resource_type: Option<ResourceKind>,
 	resource_id: Option<NuttyId>,
 }
 
@@ -84,7 +312,7 @@ pub enum PermissionResult {
 pub struct PermissionCheckBuilder {
 	navigator_id: Option<NuttyId>,
 	permission: Option<String>,
-	resource_type: Option<String>,
+	resource_type: Option<ResourceKind>,
 	resource_id: Option<NuttyId>,
 }
 
@@ -99,7 +327,7 @@ impl PermissionCheckBuilder {
 		self
 	}
 
-	pub fn resource(mut self, resource_type: String, resource_id: NuttyId) -> Self {
+	pub fn resource(mut self, resource_type: ResourceKind, resource_id: NuttyId) -> Self {
 		self.resource_type = Some(resource_type);
 		self.resource_id = Some(resource_id);
 		self
@@ -132,8 +360,8 @@ impl PermissionCheck {
 		&self.permission
 	}
 
-	pub fn resource_type(&self) -> Option<&str> {
-		self.resource_type.as_deref()
+	pub fn resource_type(&self) -> Option<ResourceKind> {
+		self.resource_type
 	}
 
 	pub fn resource_id(&self) -> Option<&NuttyId> {
@@ -157,4 +385,10 @@ pub enum AccessError {
 
 	#[error("Invalid permission format: {0}")]
 	InvalidPermissionFormat(String),
+
+	#[error("Unknown resource kind: {0}")]
+	UnknownResourceKind(String),
+
+	#[error("Unknown rename kind: {0}")]
+	UnknownRenameKind(String),
 }
@@ -0,0 +1,343 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::access::models::is_valid_name;
+
+/// The roles and permissions of an environment, and which roles have which
+/// permissions, as a document to export from one environment and apply to
+/// another. Written as YAML or JSON:
+///
+/// ```yaml
+/// permissions:
+///   - name: content_blocks:read:own
+///     description: Can view own content blocks.
+/// roles:
+///   - name: block_owner
+///     description: Can view and edit own content blocks.
+///     permissions:
+///       - content_blocks:read:own
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessPolicy {
+	#[serde(default)]
+	pub permissions: Vec<PolicyPermission>,
+
+	#[serde(default)]
+	pub roles: Vec<PolicyRole>,
+}
+
+/// A permission, as written in an [AccessPolicy].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyPermission {
+	pub name: String,
+	pub description: String,
+}
+
+/// A role and its permissions, as written in an [AccessPolicy].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyRole {
+	pub name: String,
+	pub description: String,
+
+	#[serde(default)]
+	pub permissions: Vec<String>,
+}
+
+/// A change that applying an [AccessPolicy] makes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum AccessPolicyChange {
+	CreatePermission { name: String, description: String },
+	DescribePermission { name: String, description: String },
+	CreateRole { name: String, description: String },
+	DescribeRole { name: String, description: String },
+	GrantPermission { role: String, permission: String },
+	RevokePermission { role: String, permission: String },
+}
+
+/// The changes that applying an [AccessPolicy] made, or would make while
+/// previewing it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessPolicyDiff {
+	/// Whether the changes were made.
+	pub applied: bool,
+
+	pub changes: Vec<AccessPolicyChange>,
+}
+
+impl AccessPolicy {
+	/// Parse a policy from JSON, if it's an object, or otherwise YAML.
+	/// JSON is parsed on its own since YAML doesn't allow tab indentation.
+	pub fn parse(document: &str) -> Result<Self, AccessPolicyError> {
+		if document.trim_start().starts_with('{') {
+			serde_json::from_str(document).map_err(AccessPolicyError::Json)
+		} else {
+			serde_yaml_ng::from_str(document).map_err(AccessPolicyError::Yaml)
+		}
+	}
+
+	/// Write the policy as YAML.
+	pub fn to_yaml(&self) -> Result<String, AccessPolicyError> {
+		serde_yaml_ng::to_string(self).map_err(AccessPolicyError::Yaml)
+	}
+
+	/// Sort the roles, permissions, and each role's permissions by name, so
+	/// that exports of the same policy are the same.
+	pub fn sorted(mut self) -> Self {
+		self.permissions.sort_by(|a, b| a.name.cmp(&b.name));
+		self.roles.sort_by(|a, b| a.name.cmp(&b.name));
+
+		for role in &mut self.roles {
+			role.permissions.sort();
+			role.permissions.dedup();
+		}
+
+		self
+	}
+
+	/// Plan the changes that make the current policy match this one, in the
+	/// order to make them. Each listed role ends up with exactly its listed
+	/// permissions, but roles and permissions missing from this policy are
+	/// kept, since deleting a role would revoke it from every navigator.
+	pub fn diff(
+		&self,
+		current: &AccessPolicy,
+	) -> Result<Vec<AccessPolicyChange>, AccessPolicyError> {
+		self.validate(current)?;
+
+		let current_permissions: BTreeMap<_, _> = current
+			.permissions
+			.iter()
+			.map(|permission| (permission.name.as_str(), permission.description.as_str()))
+			.collect();
+
+		let current_roles: BTreeMap<_, _> = current
+			.roles
+			.iter()
+			.map(|role| (role.name.as_str(), role))
+			.collect();
+
+		let mut changes = vec![];
+
+		for permission in &self.permissions {
+			match current_permissions.get(permission.name.as_str()) {
+				None => changes.push(AccessPolicyChange::CreatePermission {
+					name: permission.name.clone(),
+					description: permission.description.clone(),
+				}),
+
+				Some(description) if *description != permission.description => {
+					changes.push(AccessPolicyChange::DescribePermission {
+						name: permission.name.clone(),
+						description: permission.description.clone(),
+					})
+				}
+
+				Some(_) => {}
+			}
+		}
+
+		for role in &self.roles {
+			let current_role = current_roles.get(role.name.as_str());
+
+			match current_role {
+				None => changes.push(AccessPolicyChange::CreateRole {
+					name: role.name.clone(),
+					description: role.description.clone(),
+				}),
+
+				Some(current_role) if current_role.description != role.description => {
+					changes.push(AccessPolicyChange::DescribeRole {
+						name: role.name.clone(),
+						description: role.description.clone(),
+					})
+				}
+
+				Some(_) => {}
+			}
+
+			let wanted: BTreeSet<_> = role.permissions.iter().collect();
+			let granted: BTreeSet<_> = current_role
+				.map(|current_role| current_role.permissions.iter().collect())
+				.unwrap_or_default();
+
+			for permission in wanted.difference(&granted) {
+				changes.push(AccessPolicyChange::GrantPermission {
+					role: role.name.clone(),
+					permission: permission.to_string(),
+				});
+			}
+
+			for permission in granted.difference(&wanted) {
+				changes.push(AccessPolicyChange::RevokePermission {
+					role: role.name.clone(),
+					permission: permission.to_string(),
+				});
+			}
+		}
+
+		Ok(changes)
+	}
+
+	/// Check that names are valid and unique, and that roles only have
+	/// permissions that exist, here or already.
+	fn validate(&self, current: &AccessPolicy) -> Result<(), AccessPolicyError> {
+		let mut permission_names = BTreeSet::new();
+		let mut role_names = BTreeSet::new();
+
+		for permission in &self.permissions {
+			if !is_valid_name(&permission.name) {
+				return Err(AccessPolicyError::InvalidName(permission.name.clone()));
+			}
+
+			if !permission_names.insert(permission.name.as_str()) {
+				return Err(AccessPolicyError::DuplicateName(permission.name.clone()));
+			}
+		}
+
+		for role in &self.roles {
+			if !is_valid_name(&role.name) {
+				return Err(AccessPolicyError::InvalidName(role.name.clone()));
+			}
+
+			if !role_names.insert(role.name.as_str()) {
+				return Err(AccessPolicyError::DuplicateName(role.name.clone()));
+			}
+
+			let unknown = role.permissions.iter().find(|permission| {
+				!permission_names.contains(permission.as_str())
+					&& !current
+						.permissions
+						.iter()
+						.any(|current| current.name == **permission)
+			});
+
+			if let Some(permission) = unknown {
+				return Err(AccessPolicyError::UnknownPermission {
+					role: role.name.clone(),
+					permission: permission.clone(),
+				});
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum AccessPolicyError {
+	#[error("Failed to parse access policy as JSON: {0}")]
+	Json(#[source] serde_json::Error),
+
+	#[error("Failed to parse or write access policy as YAML: {0}")]
+	Yaml(#[source] serde_yaml_ng::Error),
+
+	#[error("Invalid role or permission name: '{0}'")]
+	InvalidName(String),
+
+	#[error("Listed more than once: '{0}'")]
+	DuplicateName(String),
+
+	#[error("Role '{role}' has an unknown permission: '{permission}'")]
+	UnknownPermission { role: String, permission: String },
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_diff() {
+		let current = AccessPolicy::parse(
+			r#"
+				{
+					"permissions": [
+						{ "name": "blocks:read", "description": "Read blocks." },
+						{ "name": "blocks:write", "description": "Write blocks." }
+					],
+					"roles": [
+						{ "name": "viewer", "description": "Views.", "permissions": ["blocks:read"] },
+						{ "name": "editor", "description": "Edits.", "permissions": ["blocks:read", "blocks:write"] }
+					]
+				}
+			"#,
+		)
+		.unwrap();
+
+		let desired = AccessPolicy::parse(
+			"
+permissions:
+  - name: blocks:comment
+    description: Comment on blocks.
+  - name: blocks:write
+    description: Edit blocks.
+roles:
+  - name: editor
+    description: Edits.
+    permissions: [blocks:read, blocks:comment]
+  - name: commenter
+    description: Comments.
+    permissions: [blocks:comment]
+",
+		)
+		.unwrap();
+
+		// Assert: Only what differs changes, and unlisted roles are kept.
+		assert_eq!(
+			desired.diff(&current).unwrap(),
+			vec![
+				AccessPolicyChange::CreatePermission {
+					name: "blocks:comment".to_string(),
+					description: "Comment on blocks.".to_string(),
+				},
+				AccessPolicyChange::DescribePermission {
+					name: "blocks:write".to_string(),
+					description: "Edit blocks.".to_string(),
+				},
+				AccessPolicyChange::GrantPermission {
+					role: "editor".to_string(),
+					permission: "blocks:comment".to_string(),
+				},
+				AccessPolicyChange::RevokePermission {
+					role: "editor".to_string(),
+					permission: "blocks:write".to_string(),
+				},
+				AccessPolicyChange::CreateRole {
+					name: "commenter".to_string(),
+					description: "Comments.".to_string(),
+				},
+				AccessPolicyChange::GrantPermission {
+					role: "commenter".to_string(),
+					permission: "blocks:comment".to_string(),
+				},
+			]
+		);
+
+		// Assert: A policy makes no changes to itself, even once exported.
+		let exported = AccessPolicy::parse(&current.to_yaml().unwrap()).unwrap();
+		assert!(exported.diff(&current).unwrap().is_empty());
+
+		// Assert: Duplicate names and unknown permissions are rejected.
+		let mut duplicate = desired.clone();
+		duplicate.roles.push(desired.roles[0].clone());
+
+		assert!(matches!(
+			duplicate.diff(&current),
+			Err(AccessPolicyError::DuplicateName(name)) if name == "editor"
+		));
+
+		let mut unknown = desired.clone();
+		unknown.roles[0]
+			.permissions
+			.push("blocks:delete".to_string());
+
+		assert!(matches!(
+			unknown.diff(&current),
+			Err(AccessPolicyError::UnknownPermission { permission, .. }) if permission == "blocks:delete"
+		));
+	}
+}
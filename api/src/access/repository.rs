@@ -1,10 +1,24 @@
 use sqlx::PgPool;
+use sqlx::Postgres;
 use thiserror::Error;
 
+use crate::access::models::AccessRename;
+use crate::access::models::OwnerLookup;
 use crate::access::models::PermissionCheck;
 use crate::access::models::PermissionResult;
+use crate::access::models::RenameKind;
+use crate::access::models::ResourceGrant;
+use crate::access::models::ResourceKind;
 use crate::access::models::ResourceRole;
+use crate::access::policy::AccessPolicy;
+use crate::access::policy::AccessPolicyChange;
+use crate::access::policy::PolicyPermission;
+use crate::access::policy::PolicyRole;
 use crate::models::NuttyId;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+use crate::utilities::query_metrics::RecordQuery;
+use crate::utilities::repository::Repository;
+use crate::utilities::repository::TransactionExt;
 
 /// Repository for managing access control data.
 #[derive(Clone)]
@@ -35,21 +49,20 @@ impl AccessRepository {
 		let permission = check.permission();
 
 		// Special handling for ":own" permissions: must be owner to get permission.
-		if permission.ends_with(":own") {
-			if let (Some(resource_type), Some(resource_id)) =
+		if permission.ends_with(":own")
+			&& let (Some(resource_type), Some(resource_id)) =
 				(check.resource_type(), check.resource_id())
+		{
+			if self
+				.is_owner(navigator_id, resource_type, resource_id)
+				.await?
 			{
-				if self
-					.is_owner(navigator_id, resource_type, resource_id)
-					.await?
-				{
-					if self.has_global_permission(navigator_id, permission).await? {
-						return Ok(PermissionResult::GrantedOwnership);
-					}
-				} else {
-					// Not the owner: do not grant, even if they have the global ":own" permission.
-					return Ok(PermissionResult::Denied);
+				if self.has_global_permission(navigator_id, permission).await? {
+					return Ok(PermissionResult::GrantedOwnership);
 				}
+			} else {
+				// Not the owner: do not grant, even if they have the global ":own" permission.
+				return Ok(PermissionResult::Denied);
 			}
 		}
 
@@ -60,13 +73,11 @@ impl AccessRepository {
 
 		// Check resource-specific roles.
 		if let (Some(resource_type), Some(resource_id)) = (check.resource_type(), check.resource_id())
-		{
-			if self
+			&& self
 				.has_resource_permission(navigator_id, permission, resource_type, resource_id)
 				.await?
-			{
-				return Ok(PermissionResult::GrantedResource);
-			}
+		{
+			return Ok(PermissionResult::GrantedResource);
 		}
 
 		Ok(PermissionResult::Denied)
@@ -90,6 +101,7 @@ impl AccessRepository {
 			permission
 		)
 		.fetch_one(&self.pool)
+		.record_query("has_global_permission")
 		.await?;
 
 		Ok(result.exists)
@@ -100,7 +112,7 @@ impl AccessRepository {
 		&self,
 		navigator_id: &NuttyId,
 		permission: &str,
-		resource_type: &str,
+		resource_type: ResourceKind,
 		resource_id: &NuttyId,
 	) -> Result<bool, AccessRepositoryError> {
 		let result = sqlx::query!(
@@ -116,10 +128,11 @@ impl AccessRepository {
 			"#,
 			navigator_id.uuid(),
 			permission,
-			resource_type,
+			resource_type.as_str(),
 			resource_id.uuid()
 		)
 		.fetch_one(&self.pool)
+		.record_query("has_resource_permission")
 		.await?;
 
 		Ok(result.exists)
@@ -129,29 +142,23 @@ impl AccessRepository {
 	async fn is_owner(
 		&self,
 		navigator_id: &NuttyId,
-		resource_type: &str,
+		resource_type: ResourceKind,
 		resource_id: &NuttyId,
 	) -> Result<bool, AccessRepositoryError> {
-		if resource_type == "content_block" {
-			let result = sqlx::query!(
-				r#"
-					SELECT owner_id
-					FROM content.blocks
-					WHERE id = $1
-				"#,
-				resource_id.uuid()
-			)
-			.fetch_optional(&self.pool)
-			.await?;
-
-			if let Some(row) = result {
-				if let Some(owner_id) = row.owner_id {
-					return Ok(owner_id == *navigator_id.uuid());
-				}
+		match resource_type.owner_lookup() {
+			OwnerLookup::Column { table, column } => {
+				// The table and column come from the registry, never from input.
+				let owner_id: Option<Option<uuid::Uuid>> =
+					sqlx::query_scalar(&format!("SELECT {column} FROM {table} WHERE id = $1"))
+						.bind(resource_id.uuid())
+						.fetch_optional(&self.pool)
+						.record_query("is_owner")
+						.await?;
+
+				Ok(owner_id.flatten() == Some(*navigator_id.uuid()))
 			}
+			OwnerLookup::Itself => Ok(resource_id == navigator_id),
 		}
-
-		Ok(false)
 	}
 
 	/// Get all permissions for a navigator.
@@ -169,6 +176,7 @@ impl AccessRepository {
 			navigator_id.uuid()
 		)
 		.fetch_all(&self.pool)
+		.record_query("get_navigator_permissions")
 		.await?;
 
 		Ok(rows.into_iter().map(|row| row.permission_name).collect())
@@ -188,6 +196,7 @@ impl AccessRepository {
 		)
 		.bind(navigator_id.uuid())
 		.fetch_all(&self.pool)
+		.record_query("get_navigator_resource_roles")
 		.await?;
 
 		Ok(rows)
@@ -201,21 +210,28 @@ impl AccessRepository {
 	) -> Result<(), AccessRepositoryError> {
 		let nutty_id = NuttyId::now();
 
-		sqlx::query!(
-			r#"
-				INSERT INTO auth.navigator_roles (id, nutty_id, navigator_id, role_name)
-				VALUES ($1, $2, $3, $4)
-				ON CONFLICT (navigator_id, role_name) DO NOTHING
-			"#,
-			nutty_id.uuid(),
-			nutty_id.nid(),
-			navigator_id.uuid(),
-			role_name
-		)
-		.execute(&self.pool)
-		.await?;
-
-		Ok(())
+		self
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					sqlx::query!(
+						r#"
+							INSERT INTO auth.navigator_roles (id, nutty_id, navigator_id, role_name)
+							VALUES ($1, $2, $3, $4)
+							ON CONFLICT (navigator_id, role_name) DO NOTHING
+						"#,
+						nutty_id.uuid(),
+						nutty_id.nid(),
+						navigator_id.uuid(),
+						role_name
+					)
+					.execute(tx.as_executor())
+					.record_query("assign_global_role")
+					.await?;
+
+					Ok(())
+				})
+			})
+			.await
 	}
 
 	/// Assign a resource role to a navigator.
@@ -223,13 +239,123 @@ impl AccessRepository {
 		&self,
 		navigator_id: &NuttyId,
 		role_name: &str,
-		resource_type: &str,
+		resource_type: ResourceKind,
+		resource_id: &NuttyId,
+	) -> Result<(), AccessRepositoryError> {
+		let nutty_id = NuttyId::now();
+
+		self
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					sqlx::query!(
+						r#"
+							INSERT INTO auth.resource_roles (id, nutty_id, navigator_id, role_name, resource_type, resource_id)
+							VALUES ($1, $2, $3, $4, $5, $6)
+						"#,
+						nutty_id.uuid(),
+						nutty_id.nid(),
+						navigator_id.uuid(),
+						role_name,
+						resource_type.as_str(),
+						resource_id.uuid()
+					)
+					.execute(tx.as_executor())
+					.record_query("assign_resource_role")
+					.await?;
+
+					Ok(())
+				})
+			})
+			.await
+	}
+
+	/// Remove a global role from a navigator.
+	pub async fn remove_global_role(
+		&self,
+		navigator_id: &NuttyId,
+		role_name: &str,
+	) -> Result<(), AccessRepositoryError> {
+		self
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					sqlx::query!(
+						r#"
+							DELETE FROM auth.navigator_roles
+							WHERE navigator_id = $1 AND role_name = $2
+						"#,
+						navigator_id.uuid(),
+						role_name
+					)
+					.execute(tx.as_executor())
+					.record_query("remove_global_role")
+					.await?;
+
+					Ok(())
+				})
+			})
+			.await
+	}
+
+	/// Remove a resource role from a navigator.
+	pub async fn remove_resource_role(
+		&self,
+		navigator_id: &NuttyId,
+		role_name: &str,
+		resource_type: ResourceKind,
 		resource_id: &NuttyId,
 	) -> Result<(), AccessRepositoryError> {
+		self
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					sqlx::query!(
+						r#"
+							DELETE FROM auth.resource_roles
+							WHERE navigator_id = $1
+								AND role_name = $2
+								AND resource_type = $3
+								AND resource_id = $4
+						"#,
+						navigator_id.uuid(),
+						role_name,
+						resource_type.as_str(),
+						resource_id.uuid()
+					)
+					.execute(tx.as_executor())
+					.record_query("remove_resource_role")
+					.await?;
+
+					Ok(())
+				})
+			})
+			.await
+	}
+
+	/// Replace a navigator's roles on a resource with one role, in one
+	/// statement. Only the `replaced` roles are removed; any others are kept.
+	pub async fn replace_resource_role_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+		replaced: &[&str],
+		role_name: &str,
+		resource_type: ResourceKind,
+		resource_id: &NuttyId,
+	) -> Result<(), AccessRepositoryError>
+	where
+		E: sqlx::Executor<'e, Database = Postgres>,
+	{
 		let nutty_id = NuttyId::now();
+		let replaced: Vec<String> = replaced.iter().map(|name| name.to_string()).collect();
 
 		sqlx::query!(
 			r#"
+				WITH removed AS (
+					DELETE FROM auth.resource_roles
+					WHERE navigator_id = $3
+						AND role_name = ANY($7::TEXT[])
+						AND resource_type = $5
+						AND resource_id = $6
+				)
 				INSERT INTO auth.resource_roles (id, nutty_id, navigator_id, role_name, resource_type, resource_id)
 				VALUES ($1, $2, $3, $4, $5, $6)
 			"#,
@@ -237,67 +363,530 @@ impl AccessRepository {
 			nutty_id.nid(),
 			navigator_id.uuid(),
 			role_name,
-			resource_type,
-			resource_id.uuid()
+			resource_type.as_str(),
+			resource_id.uuid(),
+			&replaced,
 		)
-		.execute(&self.pool)
+		.execute(executor)
+		.record_query("replace_resource_role")
 		.await?;
 
 		Ok(())
 	}
 
-	/// Remove a global role from a navigator.
-	pub async fn remove_global_role(
+	/// Replace a navigator's roles on a resource with one role, in one
+	/// statement. Only the `replaced` roles are removed; any others are kept.
+	pub async fn replace_resource_role(
 		&self,
 		navigator_id: &NuttyId,
+		replaced: &[&str],
 		role_name: &str,
+		resource_type: ResourceKind,
+		resource_id: &NuttyId,
+	) -> Result<(), AccessRepositoryError> {
+		self
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					self
+						.replace_resource_role_tx(
+							tx.as_executor(),
+							navigator_id,
+							replaced,
+							role_name,
+							resource_type,
+							resource_id,
+						)
+						.await
+				})
+			})
+			.await
+	}
+
+	/// Get the roles granted on a resource, including those inherited from
+	/// its ancestors (for content blocks, up to the first that opts out of
+	/// inheritance), nearest first.
+	pub async fn get_resource_grants(
+		&self,
+		resource_type: ResourceKind,
+		resource_id: &NuttyId,
+	) -> Result<Vec<ResourceGrant>, AccessRepositoryError> {
+		let rows = sqlx::query_as(
+			r#"
+				WITH RECURSIVE ancestors AS (
+					SELECT $2::uuid AS id, 0 AS depth
+					UNION ALL
+					SELECT b.parent_id, a.depth + 1
+					FROM ancestors a
+					JOIN content.blocks b ON b.id = a.id
+					WHERE $1 = 'content_block' AND b.parent_id IS NOT NULL AND b.inherit_access
+				)
+				SELECT
+					rr.id, rr.navigator_id, rr.role_name, rr.resource_type, rr.resource_id,
+					rr.created_at, rr.updated_at,
+					n.name AS navigator_name,
+					a.depth > 0 AS inherited
+				FROM ancestors a
+				JOIN auth.resource_roles rr ON rr.resource_type = $1 AND rr.resource_id = a.id
+				LEFT JOIN auth.navigators n ON n.id = rr.navigator_id
+				ORDER BY a.depth, n.name NULLS FIRST, rr.role_name
+			"#,
+		)
+		.bind(resource_type)
+		.bind(resource_id.uuid())
+		.fetch_all(&self.pool)
+		.record_query("get_resource_grants")
+		.await?;
+
+		Ok(rows)
+	}
+
+	/// Rename a role, moving its permissions and grants to the new name,
+	/// and record the rename.
+	pub async fn rename_role(
+		&self,
+		old_name: &str,
+		new_name: &str,
+		renamed_by: Option<&NuttyId>,
+	) -> Result<AccessRename, AccessRepositoryError> {
+		self
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					let description = sqlx::query_scalar!(
+						"SELECT description FROM auth.roles WHERE name = $1 FOR UPDATE",
+						old_name,
+					)
+					.fetch_optional(tx.as_executor())
+					.record_query("lock_role")
+					.await?
+					.ok_or_else(|| AccessRepositoryError::RoleNotFound(old_name.to_string()))?;
+
+					let result = sqlx::query!(
+						r#"
+							INSERT INTO auth.roles (name, description)
+							VALUES ($1, $2)
+							ON CONFLICT (name) DO NOTHING
+						"#,
+						new_name,
+						description,
+					)
+					.execute(tx.as_executor())
+					.record_query("copy_role")
+					.await?;
+
+					if result.rows_affected() == 0 {
+						return Err(AccessRepositoryError::NameTaken(new_name.to_string()));
+					}
+
+					let mut grant_count = 0;
+
+					for query in [
+						sqlx::query!(
+							"UPDATE auth.role_permissions SET role_name = $2 WHERE role_name = $1",
+							old_name,
+							new_name,
+						),
+						sqlx::query!(
+							"UPDATE auth.navigator_roles SET role_name = $2 WHERE role_name = $1",
+							old_name,
+							new_name,
+						),
+						sqlx::query!(
+							"UPDATE auth.resource_roles SET role_name = $2 WHERE role_name = $1",
+							old_name,
+							new_name,
+						),
+					] {
+						let result = query
+							.execute(tx.as_executor())
+							.record_query("move_role_grants")
+							.await?;
+
+						grant_count += result.rows_affected() as i64;
+					}
+
+					sqlx::query!("DELETE FROM auth.roles WHERE name = $1", old_name)
+						.execute(tx.as_executor())
+						.record_query("delete_role")
+						.await?;
+
+					self
+						.record_rename_tx(
+							tx.as_executor(),
+							RenameKind::Role,
+							old_name,
+							new_name,
+							grant_count,
+							renamed_by,
+						)
+						.await
+				})
+			})
+			.await
+	}
+
+	/// Rename a permission, moving it to the new name in every role that
+	/// has it, and record the rename.
+	pub async fn rename_permission(
+		&self,
+		old_name: &str,
+		new_name: &str,
+		renamed_by: Option<&NuttyId>,
+	) -> Result<AccessRename, AccessRepositoryError> {
+		self
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					let description = sqlx::query_scalar!(
+						"SELECT description FROM auth.permissions WHERE name = $1 FOR UPDATE",
+						old_name,
+					)
+					.fetch_optional(tx.as_executor())
+					.record_query("lock_permission")
+					.await?
+					.ok_or_else(|| AccessRepositoryError::PermissionNotFound(old_name.to_string()))?;
+
+					let result = sqlx::query!(
+						r#"
+							INSERT INTO auth.permissions (name, description)
+							VALUES ($1, $2)
+							ON CONFLICT (name) DO NOTHING
+						"#,
+						new_name,
+						description,
+					)
+					.execute(tx.as_executor())
+					.record_query("copy_permission")
+					.await?;
+
+					if result.rows_affected() == 0 {
+						return Err(AccessRepositoryError::NameTaken(new_name.to_string()));
+					}
+
+					let result = sqlx::query!(
+						"UPDATE auth.role_permissions SET permission_name = $2 WHERE permission_name = $1",
+						old_name,
+						new_name,
+					)
+					.execute(tx.as_executor())
+					.record_query("move_permission_grants")
+					.await?;
+
+					sqlx::query!("DELETE FROM auth.permissions WHERE name = $1", old_name)
+						.execute(tx.as_executor())
+						.record_query("delete_permission")
+						.await?;
+
+					self
+						.record_rename_tx(
+							tx.as_executor(),
+							RenameKind::Permission,
+							old_name,
+							new_name,
+							result.rows_affected() as i64,
+							renamed_by,
+						)
+						.await
+				})
+			})
+			.await
+	}
+
+	/// List the renames of roles and permissions, most recent first.
+	pub async fn list_renames(
+		&self,
+		limit: i64,
+	) -> Result<Vec<AccessRename>, AccessRepositoryError> {
+		let records = sqlx::query!(
+			r#"
+				SELECT id, kind, old_name, new_name, grant_count, renamed_by, created_at
+				FROM auth.access_renames
+				ORDER BY created_at DESC
+				LIMIT $1
+			"#,
+			limit,
+		)
+		.fetch_all(&self.pool)
+		.record_query("list_access_renames")
+		.await?;
+
+		records
+			.into_iter()
+			.map(|record| {
+				Ok(AccessRename {
+					nutty_id: NuttyId::new(record.id),
+					kind: record
+						.kind
+						.parse()
+						.map_err(|_| AccessRepositoryError::UnknownRenameKind(record.kind))?,
+					old_name: record.old_name,
+					new_name: record.new_name,
+					grant_count: record.grant_count,
+					renamed_by: record.renamed_by.map(NuttyId::new),
+					renamed_at: DateTimeRfc3339::new(record.created_at.fixed_offset()),
+				})
+			})
+			.collect()
+	}
+
+	/// Get every role and permission, and which roles have which permissions.
+	pub async fn get_access_policy(&self) -> Result<AccessPolicy, AccessRepositoryError> {
+		let permissions = sqlx::query_as!(
+			PolicyPermission,
+			"SELECT name, description FROM auth.permissions ORDER BY name",
+		)
+		.fetch_all(&self.pool)
+		.record_query("list_permissions")
+		.await?;
+
+		let roles = sqlx::query_as!(
+			PolicyRole,
+			r#"
+				SELECT
+					r.name,
+					r.description,
+					COALESCE(
+						ARRAY_AGG(rp.permission_name ORDER BY rp.permission_name)
+							FILTER (WHERE rp.permission_name IS NOT NULL),
+						'{}'
+					) AS "permissions!"
+				FROM auth.roles r
+				LEFT JOIN auth.role_permissions rp ON rp.role_name = r.name
+				GROUP BY r.name, r.description
+				ORDER BY r.name
+			"#,
+		)
+		.fetch_all(&self.pool)
+		.record_query("list_roles_with_permissions")
+		.await?;
+
+		Ok(AccessPolicy { permissions, roles })
+	}
+
+	/// Make the changes of an access policy, all or none of them.
+	pub async fn apply_access_policy(
+		&self,
+		changes: &[AccessPolicyChange],
 	) -> Result<(), AccessRepositoryError> {
+		let mut permissions = vec![];
+		let mut roles = vec![];
+		let mut grants = vec![];
+		let mut revokes = vec![];
+
+		for change in changes.iter().cloned() {
+			match change {
+				AccessPolicyChange::CreatePermission { name, description }
+				| AccessPolicyChange::DescribePermission { name, description } => {
+					permissions.push((name, description))
+				}
+
+				AccessPolicyChange::CreateRole { name, description }
+				| AccessPolicyChange::DescribeRole { name, description } => roles.push((name, description)),
+
+				AccessPolicyChange::GrantPermission { role, permission } => {
+					grants.push((role, permission))
+				}
+
+				AccessPolicyChange::RevokePermission { role, permission } => {
+					revokes.push((role, permission))
+				}
+			}
+		}
+
+		let (permissions, roles, grants, revokes) = (&permissions, &roles, &grants, &revokes);
+
+		self
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					self
+						.upsert_permissions_tx(tx.as_executor(), permissions)
+						.await?;
+					self.upsert_roles_tx(tx.as_executor(), roles).await?;
+					self
+						.grant_role_permissions_tx(tx.as_executor(), grants)
+						.await?;
+					self
+						.revoke_role_permissions_tx(tx.as_executor(), revokes)
+						.await
+				})
+			})
+			.await
+	}
+
+	/// Record a rename for auditing.
+	async fn record_rename_tx<'e, E>(
+		&self,
+		executor: E,
+		kind: RenameKind,
+		old_name: &str,
+		new_name: &str,
+		grant_count: i64,
+		renamed_by: Option<&NuttyId>,
+	) -> Result<AccessRename, AccessRepositoryError>
+	where
+		E: sqlx::Executor<'e, Database = Postgres>,
+	{
+		let nutty_id = NuttyId::now();
+
+		let record = sqlx::query!(
+			r#"
+				INSERT INTO auth.access_renames (id, nutty_id, kind, old_name, new_name, grant_count, renamed_by)
+				VALUES ($1, $2, $3, $4, $5, $6, $7)
+				RETURNING created_at
+			"#,
+			nutty_id.uuid(),
+			nutty_id.nid(),
+			kind.as_str(),
+			old_name,
+			new_name,
+			grant_count,
+			renamed_by.map(|id| *id.uuid()),
+		)
+		.fetch_one(executor)
+		.record_query("record_access_rename")
+		.await?;
+
+		Ok(AccessRename {
+			nutty_id,
+			kind,
+			old_name: old_name.to_string(),
+			new_name: new_name.to_string(),
+			grant_count,
+			renamed_by: renamed_by.copied(),
+			renamed_at: DateTimeRfc3339::new(record.created_at.fixed_offset()),
+		})
+	}
+
+	/// Create or describe permissions, in bulk.
+	async fn upsert_permissions_tx<'e, E>(
+		&self,
+		executor: E,
+		permissions: &[(String, String)],
+	) -> Result<(), AccessRepositoryError>
+	where
+		E: sqlx::Executor<'e, Database = Postgres>,
+	{
+		let (names, descriptions): (Vec<_>, Vec<_>) = permissions.iter().cloned().unzip();
+
 		sqlx::query!(
 			r#"
-				DELETE FROM auth.navigator_roles
-				WHERE navigator_id = $1 AND role_name = $2
+				INSERT INTO auth.permissions (name, description)
+				SELECT * FROM UNNEST($1::TEXT[], $2::TEXT[])
+				ON CONFLICT (name) DO UPDATE SET description = EXCLUDED.description
 			"#,
-			navigator_id.uuid(),
-			role_name
+			&names,
+			&descriptions,
 		)
-		.execute(&self.pool)
+		.execute(executor)
+		.record_query("upsert_permissions")
 		.await?;
 
 		Ok(())
 	}
 
-	/// Remove a resource role from a navigator.
-	pub async fn remove_resource_role(
+	/// Create or describe roles, in bulk.
+	async fn upsert_roles_tx<'e, E>(
 		&self,
-		navigator_id: &NuttyId,
-		role_name: &str,
-		resource_type: &str,
-		resource_id: &NuttyId,
-	) -> Result<(), AccessRepositoryError> {
+		executor: E,
+		roles: &[(String, String)],
+	) -> Result<(), AccessRepositoryError>
+	where
+		E: sqlx::Executor<'e, Database = Postgres>,
+	{
+		let (names, descriptions): (Vec<_>, Vec<_>) = roles.iter().cloned().unzip();
+
 		sqlx::query!(
 			r#"
-				DELETE FROM auth.resource_roles
-				WHERE navigator_id = $1
-					AND role_name = $2
-					AND resource_type = $3
-					AND resource_id = $4
+				INSERT INTO auth.roles (name, description)
+				SELECT * FROM UNNEST($1::TEXT[], $2::TEXT[])
+				ON CONFLICT (name) DO UPDATE SET description = EXCLUDED.description
 			"#,
-			navigator_id.uuid(),
-			role_name,
-			resource_type,
-			resource_id.uuid()
+			&names,
+			&descriptions,
+		)
+		.execute(executor)
+		.record_query("upsert_roles")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Grant permissions to roles, in bulk.
+	async fn grant_role_permissions_tx<'e, E>(
+		&self,
+		executor: E,
+		grants: &[(String, String)],
+	) -> Result<(), AccessRepositoryError>
+	where
+		E: sqlx::Executor<'e, Database = Postgres>,
+	{
+		let (roles, permissions): (Vec<_>, Vec<_>) = grants.iter().cloned().unzip();
+
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.role_permissions (role_name, permission_name)
+				SELECT * FROM UNNEST($1::TEXT[], $2::TEXT[])
+				ON CONFLICT DO NOTHING
+			"#,
+			&roles,
+			&permissions,
 		)
-		.execute(&self.pool)
+		.execute(executor)
+		.record_query("grant_role_permissions")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Revoke permissions from roles, in bulk.
+	async fn revoke_role_permissions_tx<'e, E>(
+		&self,
+		executor: E,
+		revokes: &[(String, String)],
+	) -> Result<(), AccessRepositoryError>
+	where
+		E: sqlx::Executor<'e, Database = Postgres>,
+	{
+		let (roles, permissions): (Vec<_>, Vec<_>) = revokes.iter().cloned().unzip();
+
+		sqlx::query!(
+			r#"
+				DELETE FROM auth.role_permissions
+				WHERE (role_name, permission_name) IN (
+					SELECT * FROM UNNEST($1::TEXT[], $2::TEXT[])
+				)
+			"#,
+			&roles,
+			&permissions,
+		)
+		.execute(executor)
+		.record_query("revoke_role_permissions")
 		.await?;
 
 		Ok(())
 	}
 }
 
+impl Repository for AccessRepository {
+	fn pool(&self) -> &sqlx::Pool<Postgres> {
+		&self.pool
+	}
+}
+
 #[derive(Debug, Error)]
 pub enum AccessRepositoryError {
 	#[error("Database error: {0}")]
 	Database(#[from] sqlx::Error),
+
+	#[error("Role not found: '{0}'")]
+	RoleNotFound(String),
+
+	#[error("Permission not found: '{0}'")]
+	PermissionNotFound(String),
+
+	#[error("Name already taken: '{0}'")]
+	NameTaken(String),
+
+	#[error("Unknown rename kind: '{0}'")]
+	UnknownRenameKind(String),
 }
 
 #[cfg(test)]
@@ -504,7 +1093,12 @@ mod tests {
 
 		// Assign resource role to Alice.
 		repo
-			.assign_resource_role(&alice_id, "viewer", "content_block", &resource_id)
+			.assign_resource_role(
+				&alice_id,
+				"viewer",
+				ResourceKind::ContentBlock,
+				&resource_id,
+			)
 			.await
 			.expect("Failed to assign resource role");
 
@@ -512,7 +1106,7 @@ mod tests {
 		let check = PermissionCheck::builder()
 			.navigator(alice_id)
 			.permission("content_blocks:read:all".to_string())
-			.resource("content_block".to_string(), resource_id)
+			.resource(ResourceKind::ContentBlock, resource_id)
 			.try_build()
 			.expect("Failed to build permission check");
 
@@ -527,7 +1121,7 @@ mod tests {
 		let check = PermissionCheck::builder()
 			.navigator(bob_id)
 			.permission("content_blocks:read:all".to_string())
-			.resource("content_block".to_string(), resource_id)
+			.resource(ResourceKind::ContentBlock, resource_id)
 			.try_build()
 			.expect("Failed to build permission check");
 
@@ -573,7 +1167,7 @@ mod tests {
 		let check = PermissionCheck::builder()
 			.navigator(alice_id)
 			.permission("content_blocks:write:own".to_string())
-			.resource("content_block".to_string(), resource_id)
+			.resource(ResourceKind::ContentBlock, resource_id)
 			.try_build()
 			.expect("Failed to build permission check");
 
@@ -586,6 +1180,26 @@ mod tests {
 		// permission globally. She should get `GrantedOwnership` if she is the owner.
 		assert_eq!(result, PermissionResult::GrantedOwnership);
 
+		// Navigators own themselves, and nobody else.
+		for (resource_id, expected) in [
+			(alice_id, PermissionResult::GrantedOwnership),
+			(bob_id, PermissionResult::Denied),
+		] {
+			let check = PermissionCheck::builder()
+				.navigator(alice_id)
+				.permission("content_blocks:write:own".to_string())
+				.resource(ResourceKind::Navigator, resource_id)
+				.try_build()
+				.expect("Failed to build permission check");
+
+			let result = repo
+				.check_permission(&check)
+				.await
+				.expect("Failed to check permission");
+
+			assert_eq!(result, expected);
+		}
+
 		// Cleanup.
 		cleanup_test_data(&pool, &[alice_id, bob_id, charlie_id]).await;
 	}
@@ -660,14 +1274,24 @@ mod tests {
 
 		// Assign resource roles to Alice.
 		repo
-			.assign_resource_role(&alice_id, "viewer", "content_block", &resource_id)
+			.assign_resource_role(
+				&alice_id,
+				"viewer",
+				ResourceKind::ContentBlock,
+				&resource_id,
+			)
 			.await
 			.expect("Failed to assign resource role");
 
 		let resource_id_2 = NuttyId::now();
 
 		repo
-			.assign_resource_role(&alice_id, "editor", "content_block", &resource_id_2)
+			.assign_resource_role(
+				&alice_id,
+				"editor",
+				ResourceKind::ContentBlock,
+				&resource_id_2,
+			)
 			.await
 			.expect("Failed to assign second resource role");
 
@@ -750,7 +1374,12 @@ mod tests {
 
 		// Act: Assign resource role.
 		repo
-			.assign_resource_role(&alice_id, "viewer", "content_block", &resource_id)
+			.assign_resource_role(
+				&alice_id,
+				"viewer",
+				ResourceKind::ContentBlock,
+				&resource_id,
+			)
 			.await
 			.expect("Failed to assign resource role");
 
@@ -758,7 +1387,7 @@ mod tests {
 		let check = PermissionCheck::builder()
 			.navigator(alice_id)
 			.permission("content_blocks:read:all".to_string())
-			.resource("content_block".to_string(), resource_id)
+			.resource(ResourceKind::ContentBlock, resource_id)
 			.try_build()
 			.expect("Failed to build permission check");
 
@@ -771,7 +1400,12 @@ mod tests {
 
 		// Test idempotency. Assigning the same role again should not fail.
 		repo
-			.assign_resource_role(&alice_id, "viewer", "content_block", &resource_id)
+			.assign_resource_role(
+				&alice_id,
+				"viewer",
+				ResourceKind::ContentBlock,
+				&resource_id,
+			)
 			.await
 			.expect("Failed to assign resource role again");
 
@@ -779,6 +1413,53 @@ mod tests {
 		cleanup_test_data(&pool, &[alice_id, bob_id, charlie_id]).await;
 	}
 
+	#[tokio::test]
+	async fn test_replace_resource_role() {
+		// Arrange: Set up test data, with a role to replace and one to keep.
+		let pool = connect_to_test_database().await;
+		let repo = AccessRepository::new(pool.clone());
+		let (alice_id, bob_id, charlie_id, resource_id) = setup_test_data(&pool).await;
+
+		for role_name in ["viewer", "admin"] {
+			repo
+				.assign_resource_role(
+					&alice_id,
+					role_name,
+					ResourceKind::ContentBlock,
+					&resource_id,
+				)
+				.await
+				.expect("Failed to assign resource role");
+		}
+
+		// Act: Replace the viewer role with the editor role.
+		repo
+			.replace_resource_role(
+				&alice_id,
+				&["viewer", "editor"],
+				"editor",
+				ResourceKind::ContentBlock,
+				&resource_id,
+			)
+			.await
+			.expect("Failed to replace resource role");
+
+		// Assert: Only the replaced role is gone.
+		let mut role_names: Vec<_> = repo
+			.get_navigator_resource_roles(&alice_id)
+			.await
+			.expect("Failed to get resource roles")
+			.iter()
+			.map(|role| role.role_name().to_string())
+			.collect();
+
+		role_names.sort();
+		assert_eq!(role_names, ["admin", "editor"]);
+
+		// Cleanup.
+		cleanup_test_data(&pool, &[alice_id, bob_id, charlie_id]).await;
+	}
+
 	#[tokio::test]
 	async fn test_remove_global_role() {
 		// Arrange: Set up test data.
@@ -832,7 +1513,12 @@ mod tests {
 
 		// Assign resource role.
 		repo
-			.assign_resource_role(&alice_id, "viewer", "content_block", &resource_id)
+			.assign_resource_role(
+				&alice_id,
+				"viewer",
+				ResourceKind::ContentBlock,
+				&resource_id,
+			)
 			.await
 			.expect("Failed to assign resource role");
 
@@ -840,7 +1526,7 @@ mod tests {
 		let check = PermissionCheck::builder()
 			.navigator(alice_id)
 			.permission("content_blocks:read:all".to_string())
-			.resource("content_block".to_string(), resource_id)
+			.resource(ResourceKind::ContentBlock, resource_id)
 			.try_build()
 			.expect("Failed to build permission check");
 
@@ -853,7 +1539,12 @@ mod tests {
 
 		// Act: Remove resource role.
 		repo
-			.remove_resource_role(&alice_id, "viewer", "content_block", &resource_id)
+			.remove_resource_role(
+				&alice_id,
+				"viewer",
+				ResourceKind::ContentBlock,
+				&resource_id,
+			)
 			.await
 			.expect("Failed to remove resource role");
 
@@ -869,6 +1560,89 @@ mod tests {
 		cleanup_test_data(&pool, &[alice_id, bob_id, charlie_id]).await;
 	}
 
+	#[tokio::test]
+	async fn test_get_resource_grants() {
+		// Arrange: Set up test data, with a parent and child block.
+		let pool = connect_to_test_database().await;
+		let repo = AccessRepository::new(pool.clone());
+		let (alice_id, bob_id, charlie_id, _) = setup_test_data(&pool).await;
+		let parent_id = NuttyId::now();
+		let child_id = NuttyId::now();
+
+		for (block_id, block_parent_id) in [(parent_id, None), (child_id, Some(parent_id))] {
+			sqlx::query!(
+				r#"
+					INSERT INTO content.blocks (id, nutty_id, parent_id, f_index, content)
+					VALUES ($1, $2, $3, '!', '{"kind": "Paragraph", "markdown": ""}')
+				"#,
+				block_id.uuid(),
+				block_id.nid(),
+				block_parent_id.map(|id| *id.uuid()),
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to insert test block");
+		}
+
+		repo
+			.assign_resource_role(&alice_id, "editor", ResourceKind::ContentBlock, &child_id)
+			.await
+			.expect("Failed to assign resource role");
+
+		repo
+			.assign_resource_role(&bob_id, "viewer", ResourceKind::ContentBlock, &parent_id)
+			.await
+			.expect("Failed to assign resource role");
+
+		// Act: List the grants on the child block.
+		let grants = repo
+			.get_resource_grants(ResourceKind::ContentBlock, &child_id)
+			.await
+			.expect("Failed to get resource grants");
+
+		// Assert: Direct grants come first, then inherited ones.
+		let grants: Vec<_> = grants
+			.iter()
+			.map(|grant| {
+				(
+					grant.role().navigator_id().copied(),
+					grant.role().role_name().to_string(),
+					grant.navigator_name().map(str::to_string),
+					grant.is_inherited(),
+				)
+			})
+			.collect();
+
+		assert_eq!(
+			grants,
+			vec![
+				(
+					Some(alice_id),
+					"editor".to_string(),
+					Some(format!("alice_{}", alice_id.nid())),
+					false
+				),
+				(
+					Some(bob_id),
+					"viewer".to_string(),
+					Some(format!("bob_{}", bob_id.nid())),
+					true
+				),
+			]
+		);
+
+		// Cleanup.
+		sqlx::query!(
+			"DELETE FROM content.blocks WHERE id = ANY($1)",
+			&[*child_id.uuid(), *parent_id.uuid()],
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to cleanup test blocks");
+
+		cleanup_test_data(&pool, &[alice_id, bob_id, charlie_id]).await;
+	}
+
 	#[tokio::test]
 	async fn test_permission_check_builder() {
 		let navigator_id = NuttyId::now();
@@ -877,13 +1651,13 @@ mod tests {
 		let check = PermissionCheck::builder()
 			.navigator(navigator_id)
 			.permission("test:permission".to_string())
-			.resource("test_resource".to_string(), resource_id)
+			.resource(ResourceKind::ContentBlock, resource_id)
 			.try_build()
 			.expect("Failed to build permission check");
 
 		assert_eq!(check.navigator_id(), Some(&navigator_id));
 		assert_eq!(check.permission(), "test:permission");
-		assert_eq!(check.resource_type(), Some("test_resource"));
+		assert_eq!(check.resource_type(), Some(ResourceKind::ContentBlock));
 		assert_eq!(check.resource_id(), Some(&resource_id));
 
 		// Test build without permission (should fail).
@@ -909,7 +1683,7 @@ mod tests {
 
 		// Bob has resource-specific role.
 		repo
-			.assign_resource_role(&bob_id, "viewer", "content_block", &resource_id)
+			.assign_resource_role(&bob_id, "viewer", ResourceKind::ContentBlock, &resource_id)
 			.await
 			.expect("Failed to assign resource role");
 
@@ -920,7 +1694,7 @@ mod tests {
 		let check = PermissionCheck::builder()
 			.navigator(alice_id)
 			.permission("content_blocks:read:all".to_string())
-			.resource("content_block".to_string(), resource_id)
+			.resource(ResourceKind::ContentBlock, resource_id)
 			.try_build()
 			.expect("Failed to build permission check");
 
@@ -935,7 +1709,7 @@ mod tests {
 		let check = PermissionCheck::builder()
 			.navigator(bob_id)
 			.permission("content_blocks:read:all".to_string())
-			.resource("content_block".to_string(), resource_id)
+			.resource(ResourceKind::ContentBlock, resource_id)
 			.try_build()
 			.expect("Failed to build permission check");
 
@@ -950,7 +1724,7 @@ mod tests {
 		let check = PermissionCheck::builder()
 			.navigator(charlie_id)
 			.permission("content_blocks:read:all".to_string())
-			.resource("content_block".to_string(), resource_id)
+			.resource(ResourceKind::ContentBlock, resource_id)
 			.try_build()
 			.expect("Failed to build permission check");
 
@@ -1029,4 +1803,258 @@ mod tests {
 		// Cleanup.
 		cleanup_test_data(&pool, &[alice_id, bob_id, charlie_id]).await;
 	}
+
+	#[tokio::test]
+	async fn test_rename_role_and_permission() {
+		// Arrange: Set up a role and permission of this test's own, since
+		// other tests check the shared ones concurrently.
+		let pool = connect_to_test_database().await;
+		let repo = AccessRepository::new(pool.clone());
+		let (alice_id, bob_id, charlie_id, resource_id) = setup_test_data(&pool).await;
+
+		let suffix = alice_id.nid();
+		let viewer = format!("viewer_{suffix}");
+		let reader = format!("reader_{suffix}");
+		let read = format!("blocks:read:{suffix}");
+		let view = format!("blocks:view:{suffix}");
+
+		sqlx::query!(
+			"INSERT INTO auth.roles (name, description) VALUES ($1, 'Viewer')",
+			viewer,
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to insert test role");
+
+		sqlx::query!(
+			"INSERT INTO auth.permissions (name, description) VALUES ($1, 'Read')",
+			read,
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to insert test permission");
+
+		sqlx::query!(
+			"INSERT INTO auth.role_permissions (role_name, permission_name) VALUES ($1, $2)",
+			viewer,
+			read,
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to insert test role permission");
+
+		repo
+			.assign_global_role(&alice_id, &viewer)
+			.await
+			.expect("Failed to assign global role");
+
+		repo
+			.assign_resource_role(&bob_id, &viewer, ResourceKind::ContentBlock, &resource_id)
+			.await
+			.expect("Failed to assign resource role");
+
+		// Act: Rename the role, then the permission.
+		let rename = repo
+			.rename_role(&viewer, &reader, Some(&alice_id))
+			.await
+			.expect("Failed to rename role");
+
+		// Assert: The permission and both grants moved to the new name.
+		assert_eq!(rename.kind, RenameKind::Role);
+		assert_eq!(rename.grant_count, 3);
+		assert_eq!(rename.renamed_by, Some(alice_id));
+
+		let rename = repo
+			.rename_permission(&read, &view, None)
+			.await
+			.expect("Failed to rename permission");
+
+		assert_eq!(rename.grant_count, 1);
+
+		let permissions = repo
+			.get_navigator_permissions(&alice_id)
+			.await
+			.expect("Failed to get permissions");
+
+		assert!(permissions.contains(&view));
+		assert!(!permissions.contains(&read));
+
+		let roles = repo
+			.get_navigator_resource_roles(&bob_id)
+			.await
+			.expect("Failed to get resource roles");
+
+		assert_eq!(roles[0].role_name(), reader);
+
+		// Assert: Missing and taken names are rejected.
+		let result = repo.rename_role(&viewer, &reader, None).await;
+		assert!(matches!(
+			result,
+			Err(AccessRepositoryError::RoleNotFound(_))
+		));
+
+		let result = repo.rename_role(&reader, "admin", None).await;
+		assert!(matches!(result, Err(AccessRepositoryError::NameTaken(_))));
+
+		let result = repo.rename_permission(&read, &view, None).await;
+		assert!(matches!(
+			result,
+			Err(AccessRepositoryError::PermissionNotFound(_))
+		));
+
+		// Assert: Both renames were recorded.
+		let renames = repo
+			.list_renames(100)
+			.await
+			.expect("Failed to list renames");
+		assert!(renames.iter().any(|rename| rename.new_name == reader));
+		assert!(renames.iter().any(|rename| rename.new_name == view));
+
+		// Cleanup.
+		cleanup_test_data(&pool, &[alice_id, bob_id, charlie_id]).await;
+
+		sqlx::query!(
+			"DELETE FROM auth.access_renames WHERE new_name = ANY($1)",
+			&[reader.clone(), view.clone()],
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to cleanup renames");
+
+		sqlx::query!("DELETE FROM auth.roles WHERE name = $1", reader)
+			.execute(&pool)
+			.await
+			.expect("Failed to cleanup test role");
+
+		sqlx::query!("DELETE FROM auth.permissions WHERE name = $1", view)
+			.execute(&pool)
+			.await
+			.expect("Failed to cleanup test permission");
+	}
+
+	#[tokio::test]
+	async fn test_apply_access_policy() {
+		// Arrange: Name a role and permissions of this test's own.
+		let pool = connect_to_test_database().await;
+		let repo = AccessRepository::new(pool.clone());
+
+		let suffix = NuttyId::now().nid();
+		let editor = format!("editor_{suffix}");
+		let read = format!("blocks:read:{suffix}");
+		let write = format!("blocks:write:{suffix}");
+
+		let changes = vec![
+			AccessPolicyChange::CreatePermission {
+				name: read.clone(),
+				description: "Read".to_string(),
+			},
+			AccessPolicyChange::CreatePermission {
+				name: write.clone(),
+				description: "Write".to_string(),
+			},
+			AccessPolicyChange::CreateRole {
+				name: editor.clone(),
+				description: "Editor".to_string(),
+			},
+			AccessPolicyChange::GrantPermission {
+				role: editor.clone(),
+				permission: read.clone(),
+			},
+			AccessPolicyChange::GrantPermission {
+				role: editor.clone(),
+				permission: write.clone(),
+			},
+		];
+
+		// Act: Create them, then describe and revoke.
+		repo
+			.apply_access_policy(&changes)
+			.await
+			.expect("Failed to apply access policy");
+
+		repo
+			.apply_access_policy(&[
+				AccessPolicyChange::DescribeRole {
+					name: editor.clone(),
+					description: "Edits".to_string(),
+				},
+				AccessPolicyChange::RevokePermission {
+					role: editor.clone(),
+					permission: write.clone(),
+				},
+			])
+			.await
+			.expect("Failed to apply access policy");
+
+		// Assert: The policy has the role, with only its remaining permission.
+		let policy = repo
+			.get_access_policy()
+			.await
+			.expect("Failed to get access policy");
+
+		let role = policy
+			.roles
+			.iter()
+			.find(|role| role.name == editor)
+			.expect("Role not exported");
+
+		assert_eq!(role.description, "Edits");
+		assert_eq!(role.permissions, vec![read.clone()]);
+		assert!(
+			policy
+				.permissions
+				.iter()
+				.any(|permission| permission.name == write)
+		);
+
+		// Assert: Nothing is made if a grant fails.
+		let result = repo
+			.apply_access_policy(&[
+				AccessPolicyChange::CreatePermission {
+					name: format!("blocks:delete:{suffix}"),
+					description: "Delete".to_string(),
+				},
+				AccessPolicyChange::GrantPermission {
+					role: editor.clone(),
+					permission: format!("blocks:missing:{suffix}"),
+				},
+			])
+			.await;
+
+		assert!(matches!(result, Err(AccessRepositoryError::Database(_))));
+
+		let policy = repo
+			.get_access_policy()
+			.await
+			.expect("Failed to get access policy");
+
+		assert!(
+			!policy
+				.permissions
+				.iter()
+				.any(|permission| permission.name == format!("blocks:delete:{suffix}"))
+		);
+
+		// Cleanup.
+		sqlx::query!(
+			"DELETE FROM auth.role_permissions WHERE role_name = $1",
+			editor
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to cleanup test role permissions");
+
+		sqlx::query!("DELETE FROM auth.roles WHERE name = $1", editor)
+			.execute(&pool)
+			.await
+			.expect("Failed to cleanup test role");
+
+		sqlx::query!(
+			"DELETE FROM auth.permissions WHERE name = ANY($1)",
+			&[read, write],
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to cleanup test permissions");
+	}
 }
@@ -1,10 +1,30 @@
 use std::sync::Arc;
 
+use async_trait::async_trait;
+
+use super::models::AccessRename;
 use super::models::PermissionCheck;
 use super::models::PermissionResult;
+use super::models::ResourceGrant;
+use super::models::ResourceKind;
+use super::models::is_valid_name;
+use super::policy::AccessPolicy;
+use super::policy::AccessPolicyDiff;
 use super::repository::AccessRepository;
 use crate::models::NuttyId;
 
+/// The most renames listed at once.
+const MAX_LISTED_RENAMES: i64 = 100;
+
+/// Check a new name for a role or permission.
+pub(crate) fn validate_rename(old_name: &str, new_name: &str) -> Result<(), AccessServiceError> {
+	if new_name == old_name || !is_valid_name(new_name) {
+		return Err(AccessServiceError::InvalidName(new_name.to_string()));
+	}
+
+	Ok(())
+}
+
 /// Service for managing access control operations.
 #[derive(Clone)]
 pub struct AccessService {
@@ -18,8 +38,125 @@ impl AccessService {
 		}
 	}
 
+	/// Replace a navigator's roles on a resource with one role, within a
+	/// transaction of another service. Only the `replaced` roles are removed.
+	pub async fn replace_resource_role_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+		replaced: &[&str],
+		role_name: &str,
+		resource_type: ResourceKind,
+		resource_id: &NuttyId,
+	) -> Result<(), AccessServiceError>
+	where
+		E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+	{
+		self
+			.repository
+			.replace_resource_role_tx(
+				executor,
+				navigator_id,
+				replaced,
+				role_name,
+				resource_type,
+				resource_id,
+			)
+			.await
+			.map_err(AccessServiceError::Repository)
+	}
+}
+
+/// Access control operations.
+/// Implemented by [AccessService], and by an in-memory fake in the testkit.
+#[async_trait]
+pub trait AccessServiceApi: Send + Sync {
+	/// Get detailed permission check result.
+	async fn check(&self, check: &PermissionCheck) -> Result<PermissionResult, AccessServiceError>;
+
+	/// Grant a global role to a navigator.
+	async fn grant_global_role(
+		&self,
+		navigator_id: &NuttyId,
+		role_name: &str,
+	) -> Result<(), AccessServiceError>;
+
+	/// Grant a resource role to a navigator.
+	async fn grant_resource_role(
+		&self,
+		navigator_id: &NuttyId,
+		role_name: &str,
+		resource_type: ResourceKind,
+		resource_id: &NuttyId,
+	) -> Result<(), AccessServiceError>;
+
+	/// Revoke a global role from a navigator.
+	async fn revoke_global_role(
+		&self,
+		navigator_id: &NuttyId,
+		role_name: &str,
+	) -> Result<(), AccessServiceError>;
+
+	/// Revoke a resource role from a navigator.
+	async fn revoke_resource_role(
+		&self,
+		navigator_id: &NuttyId,
+		role_name: &str,
+		resource_type: ResourceKind,
+		resource_id: &NuttyId,
+	) -> Result<(), AccessServiceError>;
+
+	/// Get all permissions for a navigator.
+	async fn get_navigator_permissions(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<String>, AccessServiceError>;
+
+	/// Get the roles granted on a resource, including inherited ones.
+	async fn get_resource_grants(
+		&self,
+		resource_type: ResourceKind,
+		resource_id: &NuttyId,
+	) -> Result<Vec<ResourceGrant>, AccessServiceError>;
+
+	/// Rename a role everywhere it's granted, at once, and record who
+	/// renamed it. Names that the code checks for (e.g., "admin") must be
+	/// renamed there too.
+	async fn rename_role(
+		&self,
+		old_name: &str,
+		new_name: &str,
+		renamed_by: Option<&NuttyId>,
+	) -> Result<AccessRename, AccessServiceError>;
+
+	/// Rename a permission in every role that has it, at once, and record
+	/// who renamed it. Names that the code checks for must be renamed there
+	/// too.
+	async fn rename_permission(
+		&self,
+		old_name: &str,
+		new_name: &str,
+		renamed_by: Option<&NuttyId>,
+	) -> Result<AccessRename, AccessServiceError>;
+
+	/// List the most recent renames of roles and permissions.
+	async fn list_renames(&self) -> Result<Vec<AccessRename>, AccessServiceError>;
+
+	/// Export every role and permission, and which roles have which
+	/// permissions, sorted by name.
+	async fn export_access_policy(&self) -> Result<AccessPolicy, AccessServiceError>;
+
+	/// Make the current roles and permissions match a policy, or only list
+	/// the changes that would be made if previewing. Applying a policy
+	/// again changes nothing.
+	async fn apply_access_policy(
+		&self,
+		policy: &AccessPolicy,
+		dry_run: bool,
+	) -> Result<AccessPolicyDiff, AccessServiceError>;
+
 	/// Check if a navigator has a permission.
-	pub async fn can(&self, check: &PermissionCheck) -> Result<bool, AccessServiceError> {
+	async fn can(&self, check: &PermissionCheck) -> Result<bool, AccessServiceError> {
 		let result = self.check(check).await?;
 		Ok(matches!(
 			result,
@@ -29,20 +166,8 @@ impl AccessService {
 		))
 	}
 
-	/// Get detailed permission check result.
-	pub async fn check(
-		&self,
-		check: &PermissionCheck,
-	) -> Result<PermissionResult, AccessServiceError> {
-		self
-			.repository
-			.check_permission(check)
-			.await
-			.map_err(AccessServiceError::Repository)
-	}
-
 	/// Require a permission (returns error if not granted).
-	pub async fn require(&self, check: &PermissionCheck) -> Result<(), AccessServiceError> {
+	async fn require(&self, check: &PermissionCheck) -> Result<(), AccessServiceError> {
 		let result = self.check(check).await?;
 		match result {
 			PermissionResult::GrantedGlobal
@@ -59,7 +184,7 @@ impl AccessService {
 	}
 
 	/// Check if a navigator has a permission (convenience method).
-	pub async fn can_permission(
+	async fn can_permission(
 		&self,
 		navigator_id: &NuttyId,
 		permission: &str,
@@ -74,17 +199,17 @@ impl AccessService {
 	}
 
 	/// Check if a navigator has a permission on a specific resource (convenience method).
-	pub async fn can_on_resource(
+	async fn can_on_resource(
 		&self,
 		navigator_id: &NuttyId,
 		permission: &str,
-		resource_type: &str,
+		resource_type: ResourceKind,
 		resource_id: &NuttyId,
 	) -> Result<bool, AccessServiceError> {
 		let check = PermissionCheck::builder()
 			.navigator(*navigator_id)
 			.permission(permission.to_string())
-			.resource(resource_type.to_string(), *resource_id)
+			.resource(resource_type, *resource_id)
 			.try_build()
 			.map_err(AccessServiceError::from)?;
 
@@ -92,7 +217,7 @@ impl AccessService {
 	}
 
 	/// Require a permission (convenience method).
-	pub async fn require_permission(
+	async fn require_permission(
 		&self,
 		navigator_id: &NuttyId,
 		permission: &str,
@@ -107,25 +232,37 @@ impl AccessService {
 	}
 
 	/// Require a permission on a specific resource (convenience method).
-	pub async fn require_on_resource(
+	async fn require_on_resource(
 		&self,
 		navigator_id: &NuttyId,
 		permission: &str,
-		resource_type: &str,
+		resource_type: ResourceKind,
 		resource_id: &NuttyId,
 	) -> Result<(), AccessServiceError> {
 		let check = PermissionCheck::builder()
 			.navigator(*navigator_id)
 			.permission(permission.to_string())
-			.resource(resource_type.to_string(), *resource_id)
+			.resource(resource_type, *resource_id)
 			.try_build()
 			.map_err(AccessServiceError::from)?;
 
 		self.require(&check).await
 	}
+}
+
+#[async_trait]
+impl AccessServiceApi for AccessService {
+	/// Get detailed permission check result.
+	async fn check(&self, check: &PermissionCheck) -> Result<PermissionResult, AccessServiceError> {
+		self
+			.repository
+			.check_permission(check)
+			.await
+			.map_err(AccessServiceError::Repository)
+	}
 
 	/// Grant a global role to a navigator.
-	pub async fn grant_global_role(
+	async fn grant_global_role(
 		&self,
 		navigator_id: &NuttyId,
 		role_name: &str,
@@ -138,11 +275,11 @@ impl AccessService {
 	}
 
 	/// Grant a resource role to a navigator.
-	pub async fn grant_resource_role(
+	async fn grant_resource_role(
 		&self,
 		navigator_id: &NuttyId,
 		role_name: &str,
-		resource_type: &str,
+		resource_type: ResourceKind,
 		resource_id: &NuttyId,
 	) -> Result<(), AccessServiceError> {
 		self
@@ -153,7 +290,7 @@ impl AccessService {
 	}
 
 	/// Revoke a global role from a navigator.
-	pub async fn revoke_global_role(
+	async fn revoke_global_role(
 		&self,
 		navigator_id: &NuttyId,
 		role_name: &str,
@@ -166,11 +303,11 @@ impl AccessService {
 	}
 
 	/// Revoke a resource role from a navigator.
-	pub async fn revoke_resource_role(
+	async fn revoke_resource_role(
 		&self,
 		navigator_id: &NuttyId,
 		role_name: &str,
-		resource_type: &str,
+		resource_type: ResourceKind,
 		resource_id: &NuttyId,
 	) -> Result<(), AccessServiceError> {
 		self
@@ -181,7 +318,7 @@ impl AccessService {
 	}
 
 	/// Get all permissions for a navigator.
-	pub async fn get_navigator_permissions(
+	async fn get_navigator_permissions(
 		&self,
 		navigator_id: &NuttyId,
 	) -> Result<Vec<String>, AccessServiceError> {
@@ -191,6 +328,81 @@ impl AccessService {
 			.await
 			.map_err(AccessServiceError::Repository)
 	}
+
+	/// Get the roles granted on a resource, including inherited ones.
+	async fn get_resource_grants(
+		&self,
+		resource_type: ResourceKind,
+		resource_id: &NuttyId,
+	) -> Result<Vec<ResourceGrant>, AccessServiceError> {
+		self
+			.repository
+			.get_resource_grants(resource_type, resource_id)
+			.await
+			.map_err(AccessServiceError::Repository)
+	}
+
+	/// Rename a role everywhere it's granted.
+	async fn rename_role(
+		&self,
+		old_name: &str,
+		new_name: &str,
+		renamed_by: Option<&NuttyId>,
+	) -> Result<AccessRename, AccessServiceError> {
+		validate_rename(old_name, new_name)?;
+
+		self
+			.repository
+			.rename_role(old_name, new_name, renamed_by)
+			.await
+			.map_err(AccessServiceError::Repository)
+	}
+
+	/// Rename a permission in every role that has it.
+	async fn rename_permission(
+		&self,
+		old_name: &str,
+		new_name: &str,
+		renamed_by: Option<&NuttyId>,
+	) -> Result<AccessRename, AccessServiceError> {
+		validate_rename(old_name, new_name)?;
+
+		self
+			.repository
+			.rename_permission(old_name, new_name, renamed_by)
+			.await
+			.map_err(AccessServiceError::Repository)
+	}
+
+	/// List the most recent renames of roles and permissions.
+	async fn list_renames(&self) -> Result<Vec<AccessRename>, AccessServiceError> {
+		self
+			.repository
+			.list_renames(MAX_LISTED_RENAMES)
+			.await
+			.map_err(AccessServiceError::Repository)
+	}
+
+	async fn export_access_policy(&self) -> Result<AccessPolicy, AccessServiceError> {
+		let policy = self.repository.get_access_policy().await?;
+		Ok(policy.sorted())
+	}
+
+	async fn apply_access_policy(
+		&self,
+		policy: &AccessPolicy,
+		dry_run: bool,
+	) -> Result<AccessPolicyDiff, AccessServiceError> {
+		let current = self.repository.get_access_policy().await?;
+		let changes = policy.diff(&current)?;
+		let applied = !dry_run && !changes.is_empty();
+
+		if applied {
+			self.repository.apply_access_policy(&changes).await?;
+		}
+
+		Ok(AccessPolicyDiff { applied, changes })
+	}
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -201,6 +413,12 @@ pub enum AccessServiceError {
 	#[error("Permission check error: {0}")]
 	PermissionCheck(#[from] super::models::PermissionCheckError),
 
+	#[error("Invalid role or permission name: '{0}'")]
+	InvalidName(String),
+
+	#[error("Invalid access policy: {0}")]
+	InvalidPolicy(#[from] super::policy::AccessPolicyError),
+
 	#[error("Permission denied for navigator {navigator_id:?} on {permission} {resource:?}")]
 	PermissionDenied {
 		navigator_id: Option<String>,
@@ -395,7 +613,12 @@ mod tests {
 
 		// Assign resource role to Alice.
 		service
-			.grant_resource_role(&alice_id, "viewer", "content_block", &resource_id)
+			.grant_resource_role(
+				&alice_id,
+				"viewer",
+				ResourceKind::ContentBlock,
+				&resource_id,
+			)
 			.await
 			.expect("Failed to assign resource role");
 
@@ -405,7 +628,7 @@ mod tests {
 				&PermissionCheck::builder()
 					.navigator(alice_id)
 					.permission("content_blocks:read:resource".to_string())
-					.resource("content_block".to_string(), resource_id)
+					.resource(ResourceKind::ContentBlock, resource_id)
 					.try_build()
 					.unwrap(),
 			)
@@ -420,7 +643,7 @@ mod tests {
 				&PermissionCheck::builder()
 					.navigator(bob_id)
 					.permission("content_blocks:read:resource".to_string())
-					.resource("content_block".to_string(), resource_id)
+					.resource(ResourceKind::ContentBlock, resource_id)
 					.try_build()
 					.unwrap(),
 			)
@@ -557,7 +780,7 @@ mod tests {
 				&PermissionCheck::builder()
 					.navigator(bob_id)
 					.permission("content_blocks:read:resource".to_string())
-					.resource("content_block".to_string(), resource_id)
+					.resource(ResourceKind::ContentBlock, resource_id)
 					.try_build()
 					.unwrap(),
 			)
@@ -643,7 +866,12 @@ mod tests {
 
 		// Act: Grant resource role.
 		service
-			.grant_resource_role(&alice_id, "viewer", "content_block", &resource_id)
+			.grant_resource_role(
+				&alice_id,
+				"viewer",
+				ResourceKind::ContentBlock,
+				&resource_id,
+			)
 			.await
 			.expect("Failed to grant resource role");
 
@@ -653,7 +881,7 @@ mod tests {
 				&PermissionCheck::builder()
 					.navigator(alice_id)
 					.permission("content_blocks:read:resource".to_string())
-					.resource("content_block".to_string(), resource_id)
+					.resource(ResourceKind::ContentBlock, resource_id)
 					.try_build()
 					.unwrap(),
 			)
@@ -664,7 +892,12 @@ mod tests {
 
 		// Act: Revoke resource role.
 		service
-			.revoke_resource_role(&alice_id, "viewer", "content_block", &resource_id)
+			.revoke_resource_role(
+				&alice_id,
+				"viewer",
+				ResourceKind::ContentBlock,
+				&resource_id,
+			)
 			.await
 			.expect("Failed to revoke resource role");
 
@@ -674,7 +907,7 @@ mod tests {
 				&PermissionCheck::builder()
 					.navigator(alice_id)
 					.permission("content_blocks:read:resource".to_string())
-					.resource("content_block".to_string(), resource_id)
+					.resource(ResourceKind::ContentBlock, resource_id)
 					.try_build()
 					.unwrap(),
 			)
@@ -793,12 +1026,12 @@ mod tests {
 
 		// Act: Grant the same resource role twice.
 		service
-			.grant_resource_role(&bob_id, "viewer", "content_block", &resource_id)
+			.grant_resource_role(&bob_id, "viewer", ResourceKind::ContentBlock, &resource_id)
 			.await
 			.expect("Failed to grant resource role");
 
 		service
-			.grant_resource_role(&bob_id, "viewer", "content_block", &resource_id)
+			.grant_resource_role(&bob_id, "viewer", ResourceKind::ContentBlock, &resource_id)
 			.await
 			.expect("Failed to grant resource role again");
 
@@ -807,7 +1040,7 @@ mod tests {
 			.can_on_resource(
 				&bob_id,
 				"content_blocks:read:resource",
-				"content_block",
+				ResourceKind::ContentBlock,
 				&resource_id,
 			)
 			.await
@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use axum::Router;
+use axum::extract::DefaultBodyLimit;
+use axum::middleware;
+use axum::routing::get;
+
+use crate::access::api::router as access_router;
+use crate::content::api::router as content_router;
+use crate::ingest::api::router as ingest_router;
+use crate::integrations::chatbot::api::router as chatbot_router;
+use crate::integrations::webhooks::api::router as webhooks_router;
+use crate::moderation::api::router as moderation_router;
+use crate::navigator::api::router as navigator_router;
+use crate::system::api::router as system_router;
+use crate::utilities::api::body_limit::BodyLimits;
+use crate::utilities::api::body_limit::payload_too_large_middleware;
+use crate::utilities::api::read_only::read_only_middleware;
+use crate::utilities::api::state::AppState;
+use crate::utilities::circuit_breaker::circuit_breaker_middleware;
+use crate::utilities::request_metrics::request_metrics_middleware;
+use crate::utilities::request_transaction::request_transaction_middleware;
+use crate::utilities::row_level_security::row_level_security_middleware;
+
+/// The router for all API endpoints, with the shared middleware applied.
+pub fn router(app_state: Arc<AppState>, body_limits: BodyLimits) -> Router {
+	Router::new()
+		.route("/", get(|| async { "Hello world!" }))
+		.merge(content_router(app_state.clone()).layer(DefaultBodyLimit::max(body_limits.content)))
+		.merge(ingest_router(app_state.clone()).layer(DefaultBodyLimit::max(body_limits.content)))
+		.merge(chatbot_router(app_state.clone()).layer(DefaultBodyLimit::max(body_limits.content)))
+		.merge(webhooks_router(app_state.clone()).layer(DefaultBodyLimit::max(body_limits.content)))
+		.merge(moderation_router(app_state.clone()).layer(DefaultBodyLimit::max(body_limits.content)))
+		.merge(navigator_router(app_state.clone()).layer(DefaultBodyLimit::max(body_limits.auth)))
+		.merge(system_router(app_state.clone()).layer(DefaultBodyLimit::max(body_limits.system)))
+		.merge(access_router(app_state.clone()).layer(DefaultBodyLimit::max(body_limits.content)))
+		.layer(middleware::from_fn(request_transaction_middleware))
+		.layer(middleware::from_fn(circuit_breaker_middleware))
+		.layer(middleware::from_fn_with_state(
+			app_state.clone(),
+			read_only_middleware,
+		))
+		.layer(middleware::from_fn(payload_too_large_middleware))
+		.layer(middleware::from_fn(request_metrics_middleware))
+		.layer(middleware::from_fn(row_level_security_middleware))
+}
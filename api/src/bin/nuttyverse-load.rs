@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use nuttyverse_core::utilities::workload;
+use nuttyverse_core::utilities::workload::LatencyStats;
+use nuttyverse_core::utilities::workload::WorkloadConfig;
+
+/// Hammer a running server's save and context endpoints, e.g.:
+///
+/// `nuttyverse-load --url http://localhost:3000 --workers 8 --seconds 30`
+#[tokio::main]
+async fn main() {
+	let config = match WorkloadConfig::from_args(std::env::args().skip(1)) {
+		Ok(config) => config,
+		Err(error) => {
+			eprintln!("{error}");
+			eprintln!(
+				"Usage: nuttyverse-load [--url URL] [--name NAME] [--pass PASS] [--workers N] [--seconds N]"
+			);
+			std::process::exit(2);
+		}
+	};
+
+	println!(
+		"Running {} workers against {} for {}s…",
+		config.workers,
+		config.base_url,
+		config.duration.as_secs()
+	);
+
+	let report = workload::run(&config)
+		.await
+		.expect("Failed to run the workload");
+
+	println!(
+		"{:>10} {:>8} {:>10} {:>10} {:>10} {:>10}",
+		"", "count", "p50", "p95", "p99", "max"
+	);
+	print_stats("save", &report.saves);
+	print_stats("context", &report.contexts);
+	println!(
+		"{:.1} requests/s, {} errors",
+		report.throughput(),
+		report.errors
+	);
+}
+
+fn print_stats(name: &str, stats: &LatencyStats) {
+	let ms = |duration: Duration| format!("{:.2}ms", duration.as_secs_f64() * 1000.0);
+
+	println!(
+		"{name:>10} {:>8} {:>10} {:>10} {:>10} {:>10}",
+		stats.count,
+		ms(stats.p50),
+		ms(stats.p95),
+		ms(stats.p99),
+		ms(stats.max)
+	);
+}
@@ -0,0 +1,67 @@
+use nuttyverse_core::access::repository::AccessRepository;
+use nuttyverse_core::access::service::AccessService;
+use nuttyverse_core::content::repository::ContentRepository;
+use nuttyverse_core::content::service::ContentService;
+use nuttyverse_core::models::navigator::PasswordHashing;
+use nuttyverse_core::navigator::repository::NavigatorRepository;
+use nuttyverse_core::navigator::service::NavigatorService;
+use nuttyverse_core::seed::DEMO_PASSWORD;
+use nuttyverse_core::seed::DemoData;
+use nuttyverse_core::seed::SeedConfig;
+use sqlx::postgres::PgPoolOptions;
+
+/// Seed a fresh database with demo data, e.g.:
+///
+/// `nuttyverse-seed --seed 42 --pages 50 --depth 4 --paragraphs 5 --navigators 6`
+#[tokio::main]
+async fn main() {
+	let config = match SeedConfig::from_args(std::env::args().skip(1)) {
+		Ok(config) => config,
+		Err(error) => {
+			eprintln!("{error}");
+			eprintln!(
+				"Usage: nuttyverse-seed [--seed N] [--pages N] [--depth N] [--paragraphs N] [--navigators N]"
+			);
+			std::process::exit(2);
+		}
+	};
+
+	// Hash the demo passwords the way the server verifies them.
+	PasswordHashing::from_env()
+		.expect("Invalid Argon2 parameters")
+		.configure();
+
+	let database_url = std::env::var("DATABASE_URL")
+		.unwrap_or_else(|_| "postgres://nutty@localhost:5432/nuttyverse".to_string());
+
+	let database_pool = PgPoolOptions::new()
+		.max_connections(5)
+		.connect(&database_url)
+		.await
+		.expect("Failed to connect to database");
+
+	let access_service = AccessService::new(AccessRepository::new(database_pool.clone()));
+	let content_service = ContentService::new(
+		ContentRepository::new(database_pool.clone()),
+		access_service.clone(),
+	);
+	let navigator_service = NavigatorService::new(NavigatorRepository::new(database_pool));
+
+	println!("Generating demo data with seed {}…", config.seed);
+	let data = DemoData::generate(&config).expect("Failed to generate demo data");
+	let names: Vec<String> = data.navigators.iter().map(|n| n.name.clone()).collect();
+
+	let summary =
+		nuttyverse_core::seed::seed(data, &navigator_service, &content_service, &access_service)
+			.await
+			.expect("Failed to seed demo data");
+
+	println!(
+		"Seeded {} navigators and {} blocks.",
+		summary.navigators, summary.blocks
+	);
+	println!(
+		"Log in as {} with password {DEMO_PASSWORD:?}.",
+		names.join(", ")
+	);
+}
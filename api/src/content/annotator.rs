@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use crate::content::service::ContentServiceApi;
+use crate::content::service::ContentServiceError;
+use crate::models::ContentBlock;
+use crate::models::block_annotation::BlockAnnotation;
+use crate::models::block_annotation::parse_external_urls;
+use crate::utilities::api::link_checker::LinkChecker;
+
+/// The most links checked per block, so that link dumps don't tie up the
+/// checker.
+const MAX_CHECKED_LINKS: usize = 20;
+
+/// Annotate a saved block in the background. Nobody waits on the result, so
+/// failures are dropped; the next save tries again.
+pub fn spawn(service: Arc<dyn ContentServiceApi>, checker: LinkChecker, block: ContentBlock) {
+	tokio::spawn(async move {
+		let _ = annotate(service.as_ref(), &checker, &block).await;
+	});
+}
+
+/// Find the issues in a block, checking its external links, and replace its
+/// annotations with them. Returns whether they were saved, i.e. whether the
+/// block is unchanged since.
+pub async fn annotate(
+	service: &dyn ContentServiceApi,
+	checker: &LinkChecker,
+	block: &ContentBlock,
+) -> Result<bool, ContentServiceError> {
+	let mut annotations = BlockAnnotation::lint(block);
+
+	for url in parse_external_urls(&block.content)
+		.iter()
+		.take(MAX_CHECKED_LINKS)
+	{
+		if let Some(reason) = checker.check(url).await {
+			annotations.push(BlockAnnotation::broken_link(
+				*block.nutty_id(),
+				url,
+				&reason,
+			));
+		}
+	}
+
+	service.annotate_content_block(block, annotations).await
+}
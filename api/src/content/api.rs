@@ -1,40 +1,243 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use axum::Json;
 use axum::Router;
 use axum::extract::Path;
+use axum::extract::Query;
 use axum::extract::State;
+use axum::http::HeaderMap;
 use axum::http::StatusCode;
+use axum::http::header::CACHE_CONTROL;
+use axum::http::header::CONTENT_TYPE;
+use axum::http::header::LOCATION;
+use axum::response::IntoResponse;
+use axum::response::sse::KeepAlive;
+use axum::response::sse::Sse;
 use axum::routing::get;
+use axum::routing::post;
 use axum::routing::put;
+use chrono::DateTime;
+use chrono::Days;
+use chrono::NaiveDate;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
 
+use crate::access::models::ResourceGrant;
+use crate::content::annotator;
+use crate::content::events::ContentEventStream;
+use crate::content::events::HEARTBEAT_INTERVAL;
+use crate::content::events::MAX_STREAMS_PER_NAVIGATOR;
+use crate::content::html_import::HtmlImportError;
+use crate::content::og_image;
+use crate::content::og_image::OgImageError;
+use crate::content::repository::ContentRepositoryError;
 use crate::content::service::ContentServiceError;
+use crate::integrations::webhooks::delivery;
 use crate::models::ContentBlock;
+use crate::models::ContentCalendar;
 use crate::models::ContentContext;
 use crate::models::DissociatedNuttyId;
+use crate::models::NuttyId;
+use crate::models::ShareLevel;
+use crate::models::Task;
+use crate::models::access_request::AccessRequest;
+use crate::models::block_archival::BlockArchival;
+use crate::models::block_conversion::BlockConversion;
+use crate::models::block_conversion::ConvertibleKind;
+use crate::models::block_deletion::BlockDeletion;
+use crate::models::block_group::RootBlockGroup;
+use crate::models::block_merge::BlockMerge;
+use crate::models::block_merge::BlockMergeError;
+use crate::models::block_merge::MergeConflict;
+use crate::models::block_merge::MergeStrategy;
+use crate::models::block_split::BlockSplit;
+use crate::models::children_view::ChildrenView;
+use crate::models::children_view::ChildrenViewError;
+use crate::models::content_diff::ContentDiff;
+use crate::models::content_event::ContentEventFilter;
+use crate::models::content_filter::ContentFilter;
+use crate::models::content_filter::ContentFilterError;
+use crate::models::context_include::ContextInclude;
+use crate::models::context_include::ContextIncludeError;
+use crate::models::context_include::ContextPart;
+use crate::models::field_selection::FieldSelection;
+use crate::models::field_selection::FieldSelectionError;
+use crate::models::find_replace::TextMatch;
+use crate::models::linked_reference::LinkedReference;
 use crate::models::nutty_id::NuttyIdError;
+use crate::models::nutty_id::PERMALINK_BASE_URL;
+use crate::models::outline::OutlineEntry;
+use crate::models::ownership_transfer::OwnershipTransfer;
+use crate::models::property::PropertyDefinition;
+use crate::models::property::PropertyError;
+use crate::models::property::PropertyKind;
+use crate::models::search_language::LanguageUpdate;
+use crate::models::search_language::SearchLanguage;
+use crate::models::share_level::BlockCapability;
+use crate::models::site_settings::AccentColor;
+use crate::models::site_settings::SiteSettings;
+use crate::models::site_settings::SiteSettingsError;
+use crate::models::slug::Slug;
+use crate::models::slug::SlugError;
+use crate::models::slug::SlugTarget;
+use crate::models::sync_changes::SyncChanges;
+use crate::models::sync_changes::SyncCursor;
+use crate::models::sync_changes::SyncCursorError;
+use crate::models::task::TaskStatus;
+use crate::models::translation::Lang;
+use crate::models::translation::LocalizedBlock;
+use crate::models::translation::Translation;
+use crate::models::translation::TranslationError;
+use crate::navigator::service::NavigatorServiceError;
 use crate::utilities::api::response::Error;
 use crate::utilities::api::response::Response;
 use crate::utilities::api::session::Session;
 use crate::utilities::api::state::AppState;
+use crate::utilities::request_transaction::RequestTransaction;
 
 /// The router for content API endpoints.
 pub fn router(app_state: Arc<AppState>) -> Router {
 	Router::new()
-		.route("/content-block/{block_id}", put(content_block_handler))
+		.route(
+			"/content-block/{block_id}",
+			get(localized_block_handler)
+				.put(content_block_handler)
+				.delete(delete_content_block_handler),
+		)
 		.route(
 			"/content-block/{block_id}/context",
 			get(content_context_handler),
 		)
+		.route("/content-block/{block_id}/html", get(content_html_handler))
+		.route(
+			"/content-block/{block_id}/access",
+			get(content_access_handler),
+		)
+		.route(
+			"/content-block/{block_id}/share",
+			post(share_content_block_handler),
+		)
+		.route(
+			"/content-block/{block_id}/request-access",
+			post(request_access_handler),
+		)
+		.route(
+			"/content-block/{block_id}/access-requests",
+			get(access_requests_handler),
+		)
+		.route(
+			"/access/requests/{request_id}/approve",
+			post(approve_access_request_handler),
+		)
+		.route(
+			"/access/requests/{request_id}/deny",
+			post(deny_access_request_handler),
+		)
+		.route(
+			"/content-block/{block_id}/transfer",
+			post(transfer_ownership_handler),
+		)
+		.route(
+			"/content-block/{block_id}/language",
+			put(set_language_handler),
+		)
+		.route(
+			"/content-block/{block_id}/archive",
+			put(archive_handler).delete(unarchive_handler),
+		)
+		.route(
+			"/content-block/{block_id}/translations",
+			get(translations_handler)
+				.put(set_translation_handler)
+				.delete(remove_translation_handler),
+		)
+		.route("/content-block/{block_id}/diff", get(diff_handler))
+		.route(
+			"/content-block/{block_id}/publish-revision",
+			post(publish_revision_handler),
+		)
+		.route("/content-block/{block_id}/slug", put(set_slug_handler))
+		.route("/content-block/{block_id}/convert", post(convert_handler))
+		.route("/content-block/{block_id}/split", post(split_handler))
+		.route("/content-block/{block_id}/paste", post(paste_handler))
+		.route(
+			"/content-block/{block_id}/merge-with-next",
+			post(merge_with_next_handler),
+		)
+		.route(
+			"/content-block/{block_id}/reorder",
+			post(reorder_children_handler),
+		)
+		.route(
+			"/content-block/{block_id}/raw",
+			get(raw_content_handler).put(save_raw_content_handler),
+		)
+		.route("/permalink/{*path}", get(permalink_handler))
+		.route("/navigator/me/blocks", get(owned_blocks_handler))
+		.route(
+			"/site-settings",
+			get(get_site_settings_handler)
+				.put(save_site_settings_handler)
+				.delete(delete_site_settings_handler),
+		)
+		.route("/sync/checksums", get(checksums_handler))
+		.route("/sync/changes", get(changes_handler))
+		.route("/events/stream", get(event_stream_handler))
+		.route("/content", get(list_content_handler))
+		.route("/content/search", get(search_handler))
+		.route("/content/outline", get(outline_handler))
+		.route("/content/calendar", get(content_calendar_handler))
+		.route(
+			"/content/calendar/feed",
+			get(calendar_feed_handler).post(regenerate_calendar_feed_handler),
+		)
+		.route("/calendar.ics", get(calendar_ics_handler))
+		.route("/og/{file}", get(og_image_handler))
+		.route("/content/find-replace", post(find_replace_handler))
+		.route("/content/merge", post(merge_handler))
+		.route("/tasks", get(tasks_handler))
+		.route("/tasks/{block_id}/toggle", post(toggle_task_handler))
+		.route(
+			"/content/properties",
+			get(list_properties_handler).post(create_property_handler),
+		)
 		.with_state(app_state)
 }
 
+/// Query parameters for a content block's context.
+#[derive(Deserialize)]
+pub struct ContextQuery {
+	/// The property to sort children by, e.g. `properties.due`.
+	sort: Option<String>,
+
+	/// The property filters children must satisfy, e.g. `properties.status:eq:open`.
+	filter: Option<String>,
+
+	/// The fields to include in the response, e.g. `blocks.title,children_ids`.
+	fields: Option<String>,
+
+	/// The time to read the block's tree as of, in RFC 3339, e.g.
+	/// `2024-01-01T00:00:00Z`.
+	as_of: Option<String>,
+
+	/// The parts of the context to fetch, e.g. `ancestors,children`.
+	include: Option<String>,
+
+	/// Whether to include archived children.
+	#[serde(default)]
+	include_archived: bool,
+}
+
 /// An API handler for fetching the [BlockContext] for a given [ContentBlock].
 async fn content_context_handler(
 	State(state): State<Arc<AppState>>,
 	Session { navigator, .. }: Session,
 	Path(block_id): Path<String>,
-) -> (StatusCode, Json<Response<ContentContext>>) {
+	Query(query): Query<ContextQuery>,
+) -> (StatusCode, Json<Response<Value>>) {
 	let block_id = DissociatedNuttyId::new(&block_id);
 
 	let block_id = match block_id {
@@ -54,6 +257,78 @@ async fn content_context_handler(
 		}
 	};
 
+	let view = match ChildrenView::parse(query.sort.as_deref(), query.filter.as_deref()) {
+		Ok(view) => ChildrenView {
+			hide_archived: !query.include_archived,
+			..view
+		},
+
+		Err(error) => {
+			let summary = "Failed to query block context.";
+			let error = ContentApiError::InvalidChildrenView(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	let selection = match FieldSelection::parse(query.fields.as_deref()) {
+		Ok(selection) => selection,
+
+		Err(error) => {
+			let summary = "Failed to query block context.";
+			let error = ContentApiError::InvalidFieldSelection(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	let include = match ContextInclude::parse(query.include.as_deref()) {
+		Ok(include) => include,
+
+		Err(error) => {
+			let summary = "Failed to query block context.";
+			let error = ContentApiError::InvalidContextInclude(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	let as_of = match query.as_of.as_deref().map(DateTime::parse_from_rfc3339) {
+		None => None,
+		Some(Ok(as_of)) => Some(as_of),
+
+		Some(Err(error)) => {
+			let summary = "Failed to query block context.";
+			let error = ContentApiError::InvalidAsOf(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
 	// Check if the navigator has access to this content block.
 	let has_access = state
 		.content_service
@@ -64,19 +339,75 @@ async fn content_context_handler(
 		Ok(true) => {
 			// User has access to this content block.
 			// We can proceed with fetching the rest of the context.
-			let block_context = state
-				.content_service
-				.get_content_block_context(&block_id)
-				.await;
+			let block_context = match as_of {
+				Some(as_of) => {
+					state
+						.content_service
+						.get_content_block_context_as_of(&block_id, &as_of, &view)
+						.await
+				}
+
+				None if !include.is_default() => {
+					state
+						.content_service
+						.get_content_block_context_partial(&block_id, &include, &view)
+						.await
+				}
+
+				None => {
+					state
+						.content_service
+						.get_content_block_context(navigator.nutty_id(), &block_id, &view)
+						.await
+				}
+			};
+
+			let block_context = match block_context {
+				Ok(context) => hide_private_blocks(&state, navigator.nutty_id(), context).await,
+				Err(error) => Err(error),
+			};
+
+			let block_context = match block_context {
+				Ok(context) => show_published(&state, navigator.nutty_id(), context).await,
+				Err(error) => Err(error),
+			};
+
+			let block_context = match block_context {
+				Ok(mut context) => block_capabilities(&state, navigator.nutty_id(), &block_id)
+					.await
+					.map(|capabilities| {
+						context.set_capabilities(capabilities);
+						context
+					}),
+
+				Err(error) => Err(error),
+			};
+
+			let block_context = block_context.map(|block_context| {
+				serde_json::to_value(block_context).map(|context| selection.project(context))
+			});
 
 			match block_context {
-				Ok(block_context) => (
+				Ok(Ok(block_context)) => (
 					StatusCode::OK,
 					Json(Response::Single {
 						data: Some(block_context),
 					}),
 				),
 
+				Ok(Err(error)) => {
+					let summary = "Failed to query block context.";
+					let error = ContentApiError::SerializeBlockContext(error);
+					let error = Error::from_error(&error).with_summary(summary);
+
+					(
+						StatusCode::INTERNAL_SERVER_ERROR,
+						Json(Response::Error {
+							errors: vec![error],
+						}),
+					)
+				}
+
 				Err(error) => {
 					let summary = "Failed to query block context.";
 					let error = ContentApiError::QueryBlockContext(error);
@@ -122,18 +453,45 @@ async fn content_context_handler(
 	}
 }
 
-/// An API handler for upserting a [ContentBlock].
-async fn content_block_handler(
+/// Query parameters for rendering a content block.
+#[derive(Deserialize)]
+pub struct RenderQuery {
+	/// Whether to append the blocks linking to it as a "Linked references"
+	/// section, for exports that can't look backlinks up.
+	#[serde(default)]
+	linked_references: bool,
+}
+
+/// A content block rendered to sanitized HTML.
+#[derive(Serialize, Deserialize)]
+pub struct RenderedBlock {
+	pub block_id: NuttyId,
+	pub html: String,
+
+	/// The settings of the site that the block is published on, i.e. its
+	/// owner's.
+	pub site: Option<SiteSettings>,
+
+	/// The signed path of the block's preview image, unless disabled.
+	pub og_image: Option<String>,
+
+	/// The meta tags for the block's title and preview image, for the
+	/// page's `<head>`.
+	pub meta: String,
+}
+
+/// An API handler for rendering a [ContentBlock] to sanitized HTML.
+async fn content_html_handler(
 	State(state): State<Arc<AppState>>,
 	Session { navigator, .. }: Session,
 	Path(block_id): Path<String>,
-	Json(payload): Json<ContentBlock>,
-) -> (StatusCode, Json<Response<ContentBlock>>) {
-	// Parse the block ID.
+	Query(query): Query<RenderQuery>,
+) -> (StatusCode, Json<Response<RenderedBlock>>) {
 	let block_id = match DissociatedNuttyId::new(&block_id) {
 		Ok(id) => id,
+
 		Err(error) => {
-			let summary = "Failed to save content block.";
+			let summary = "Failed to render content block.";
 			let error = ContentApiError::LookupBlockContext(error);
 			let error = Error::from_error(&error).with_summary(summary);
 
@@ -146,43 +504,119 @@ async fn content_block_handler(
 		}
 	};
 
-	// Verify the block ID matches the payload.
-	if block_id.nid() != payload.nutty_id().nid() {
-		let error = ContentApiError::BlockIdMismatch(
-			"The block ID in the URL does not match the payload".to_string(),
-		);
-
-		let summary = "Failed to save content block.";
-		let error = Error::from_error(&error).with_summary(summary);
-
-		return (
-			StatusCode::BAD_REQUEST,
-			Json(Response::Error {
-				errors: vec![error],
-			}),
-		);
-	}
-
-	// Check if the navigator has write access to this content block.
+	// Check if the navigator has access to this content block.
 	let has_access = state
 		.content_service
-		.check_content_block_write_access(navigator.nutty_id(), &block_id)
+		.check_content_block_access(navigator.nutty_id(), &block_id)
 		.await;
 
 	match has_access {
 		Ok(true) => {
-			// User has write access to this content block.
-			// We can proceed with saving the block.
-			match state.content_service.save_content_block(payload).await {
-				Ok(content_block) => (
-					StatusCode::OK,
-					Json(Response::Single {
-						data: Some(content_block),
-					}),
-				),
+			let block_context = state
+				.content_service
+				.get_content_block_context(navigator.nutty_id(), &block_id, &ChildrenView::default())
+				.await;
+
+			match block_context {
+				Ok(context) => match context.block_cache().get(context.block_id()) {
+					Some(block) => {
+						let published = state
+							.content_service
+							.list_published_contents(navigator.nutty_id(), &[*block.nutty_id()])
+							.await;
+
+						let site = match block.owner_id() {
+							Some(owner_id) => state.content_service.get_site_settings(owner_id).await,
+							None => Ok(None),
+						};
+
+						let references = match query.linked_references {
+							true => {
+								state
+									.content_service
+									.list_linked_references(navigator.nutty_id(), &block_id)
+									.await
+							}
+
+							false => Ok(vec![]),
+						};
+
+						let revision = state
+							.content_service
+							.get_published_revision(&block_id)
+							.await;
+
+						let (content, site, references, revision) =
+							match (published, site, references, revision) {
+								(Ok(mut published), Ok(site), Ok(references), Ok(revision)) => (
+									published
+										.remove(block.nutty_id())
+										.unwrap_or_else(|| block.content.clone()),
+									site,
+									references,
+									revision,
+								),
+
+								(Err(error), _, _, _)
+								| (_, Err(error), _, _)
+								| (_, _, Err(error), _)
+								| (_, _, _, Err(error)) => {
+									let summary = "Failed to render content block.";
+									let error = ContentApiError::QueryBlockContext(error);
+									let error = Error::from_error(&error).with_summary(summary);
+
+									return (
+										StatusCode::INTERNAL_SERVER_ERROR,
+										Json(Response::Error {
+											errors: vec![error],
+										}),
+									);
+								}
+							};
+
+						let mut html = state.block_kinds.render_html(&content, &state.sanitizer);
+						html.push_str(&LinkedReference::render_html(&references));
+
+						// Preview images only draw published content.
+						let og_image = revision.and_then(|_| state.og_image_links.sign(&block_id));
+						let og_image_url = og_image
+							.as_ref()
+							.map(|path| format!("{PERMALINK_BASE_URL}{path}"));
+						let meta = og_image::meta_tags(
+							&OutlineEntry::title_of(&content),
+							og_image_url.as_deref(),
+						);
+
+						(
+							StatusCode::OK,
+							Json(Response::Single {
+								data: Some(RenderedBlock {
+									block_id: *context.block_id(),
+									html,
+									site,
+									og_image,
+									meta,
+								}),
+							}),
+						)
+					}
+
+					None => {
+						let summary = "Failed to render content block.";
+						let error = ContentApiError::BlockNotFound;
+						let error = Error::from_error(&error).with_summary(summary);
+
+						(
+							StatusCode::NOT_FOUND,
+							Json(Response::Error {
+								errors: vec![error],
+							}),
+						)
+					}
+				},
 
 				Err(error) => {
-					let summary = "Failed to save content block.";
+					let summary = "Failed to render content block.";
 					let error = ContentApiError::QueryBlockContext(error);
 					let error = Error::from_error(&error).with_summary(summary);
 
@@ -197,7 +631,6 @@ async fn content_block_handler(
 		}
 
 		Ok(false) => {
-			// User does not have write access to this content block.
 			let summary = "Access denied.";
 			let error = ContentApiError::AccessDenied;
 			let error = Error::from_error(&error).with_summary(summary);
@@ -211,7 +644,6 @@ async fn content_block_handler(
 		}
 
 		Err(error) => {
-			// Error occurred while checking access.
 			let summary = "Failed to check access permissions.";
 			let error = ContentApiError::AccessControl(error);
 			let error = Error::from_error(&error).with_summary(summary);
@@ -226,20 +658,4714 @@ async fn content_block_handler(
 	}
 }
 
-#[derive(Debug, thiserror::Error)]
-pub enum ContentApiError {
-	#[error("Unable to look up block context: {0}")]
-	LookupBlockContext(#[from] NuttyIdError),
+/// Show a navigator the published revisions of the blocks in a context that
+/// they can't edit, instead of the drafts. Editors, and everyone while
+/// nothing is published, see the drafts.
+async fn show_published(
+	state: &AppState,
+	navigator_id: &NuttyId,
+	mut context: ContentContext,
+) -> Result<ContentContext, ContentServiceError> {
+	let block_ids: Vec<NuttyId> = context.block_cache().keys().copied().collect();
 
-	#[error("Unable to query block context: {0}")]
-	QueryBlockContext(#[from] ContentServiceError),
+	let contents = state
+		.content_service
+		.list_published_contents(navigator_id, &block_ids)
+		.await?;
 
-	#[error("Block ID mismatch: {0}")]
-	BlockIdMismatch(String),
+	context.replace_contents(contents);
 
-	#[error("Access denied.")]
-	AccessDenied,
+	Ok(context)
+}
 
-	#[error("Failed to check access permissions: {0}")]
-	AccessControl(ContentServiceError),
+/// Remove the descendants that are hidden by moderation or opt out of
+/// inheriting access, and the restricted references and backlinks, that the
+/// navigator cannot view from a context. Query results and related blocks are
+/// already limited to the blocks that the navigator can read.
+async fn hide_private_blocks(
+	state: &AppState,
+	navigator_id: &NuttyId,
+	mut context: ContentContext,
+) -> Result<ContentContext, ContentServiceError> {
+	let can_review = state
+		.access_service
+		.can_permission(navigator_id, "moderation:review")
+		.await
+		.map_err(ContentServiceError::AccessControl)?;
+
+	if !can_review {
+		let hidden_ids = state
+			.content_service
+			.list_hidden_block_ids(&context.descendant_ids())
+			.await?;
+
+		for hidden_id in hidden_ids {
+			context.prune_subtree(&hidden_id);
+		}
+	}
+
+	for private_id in context.private_descendant_ids() {
+		// Skip blocks already pruned with a private ancestor.
+		if !context.block_cache().contains_key(&private_id) {
+			continue;
+		}
+
+		let has_access = match state
+			.content_service
+			.check_content_block_access(navigator_id, &private_id.dissociate())
+			.await
+		{
+			Ok(has_access) => has_access,
+
+			// Blocks in a past tree may have been deleted since, so their
+			// access can't be checked, and they're hidden.
+			Err(ContentServiceError::FetchContentBlock(ContentRepositoryError::QueryFailed(
+				sqlx::Error::RowNotFound,
+			))) => false,
+
+			Err(error) => return Err(error),
+		};
+
+		if !has_access {
+			context.prune_subtree(&private_id);
+		}
+	}
+
+	// Check the targets of the restricted links at once.
+	let readable_ids = state
+		.content_service
+		.list_readable_block_ids(navigator_id, &context.restricted_target_ids())
+		.await?;
+
+	context.retain_restricted_links(&readable_ids);
+
+	Ok(context)
+}
+
+/// List what a navigator who can read a content block can do with it.
+async fn block_capabilities(
+	state: &AppState,
+	navigator_id: &NuttyId,
+	block_id: &DissociatedNuttyId,
+) -> Result<Vec<BlockCapability>, ContentServiceError> {
+	let service = &state.content_service;
+	let mut capabilities = vec![BlockCapability::Read];
+
+	if service
+		.check_content_block_comment_access(navigator_id, block_id)
+		.await?
+	{
+		capabilities.push(BlockCapability::Comment);
+	}
+
+	if service
+		.check_content_block_write_access(navigator_id, block_id)
+		.await?
+	{
+		capabilities.push(BlockCapability::Edit);
+	}
+
+	Ok(capabilities)
+}
+
+/// An API handler for listing who has access to a [ContentBlock].
+async fn content_access_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+) -> (StatusCode, Json<Response<ResourceGrant>>) {
+	let block_id = match DissociatedNuttyId::new(&block_id) {
+		Ok(id) => id,
+
+		Err(error) => {
+			let summary = "Failed to list access grants.";
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	// Check if the navigator can manage access to this content block.
+	let has_access = state
+		.content_service
+		.check_content_block_manage_access(navigator.nutty_id(), &block_id)
+		.await;
+
+	match has_access {
+		Ok(true) => match state
+			.content_service
+			.get_content_block_grants(&block_id)
+			.await
+		{
+			Ok(grants) => (StatusCode::OK, Json(Response::Multiple { data: grants })),
+
+			Err(error) => {
+				let summary = "Failed to list access grants.";
+				let error = ContentApiError::ListGrants(error);
+				let error = Error::from_error(&error).with_summary(summary);
+
+				(
+					StatusCode::INTERNAL_SERVER_ERROR,
+					Json(Response::Error {
+						errors: vec![error],
+					}),
+				)
+			}
+		},
+
+		Ok(false) => {
+			let summary = "Access denied.";
+			let error = ContentApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+
+		Err(error) => {
+			let summary = "Failed to check access permissions.";
+			let error = ContentApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Request payload for sharing a content block with another navigator.
+#[derive(Serialize, Deserialize)]
+pub struct ShareRequest {
+	navigator_name: String,
+	level: ShareLevel,
+}
+
+/// An API handler for sharing a [ContentBlock] with another navigator.
+async fn share_content_block_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	transaction: RequestTransaction,
+	Path(block_id): Path<String>,
+	Json(payload): Json<ShareRequest>,
+) -> (StatusCode, Json<Response<ResourceGrant>>) {
+	let summary = "Failed to share content block.";
+
+	// Replacing a recipient's roles is saved all at once, or not at all.
+	transaction.begin();
+
+	let block_id = match DissociatedNuttyId::new(&block_id) {
+		Ok(id) => id,
+
+		Err(error) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	// Resolve the recipient by name.
+	let recipient = state
+		.navigator_service
+		.get_navigator_by_name(&payload.navigator_name)
+		.await;
+
+	let recipient = match recipient {
+		Ok(Some(recipient)) => recipient,
+
+		Ok(None) => {
+			let error = ContentApiError::RecipientNotFound;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::NOT_FOUND,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+
+		Err(error) => {
+			let error = ContentApiError::LookupRecipient(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	let grants = state
+		.content_service
+		.share_content_block(
+			navigator.nutty_id(),
+			&block_id,
+			recipient.nutty_id(),
+			payload.level,
+		)
+		.await;
+
+	match grants {
+		Ok(grants) => (StatusCode::OK, Json(Response::Multiple { data: grants })),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ShareDenied => StatusCode::FORBIDDEN,
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::ShareContentBlock(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Request payload for requesting access to a content block.
+#[derive(Serialize, Deserialize)]
+pub struct AccessRequestPayload {
+	#[serde(default)]
+	level: ShareLevel,
+}
+
+/// An API handler for requesting access to a [ContentBlock].
+async fn request_access_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+	Json(payload): Json<AccessRequestPayload>,
+) -> (StatusCode, Json<Response<AccessRequest>>) {
+	let summary = "Failed to request access.";
+
+	let block_id = match DissociatedNuttyId::new(&block_id) {
+		Ok(id) => id,
+
+		Err(error) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	let request = state
+		.content_service
+		.request_access(navigator.nutty_id(), &block_id, payload.level)
+		.await;
+
+	match request {
+		Ok(request) => (
+			StatusCode::CREATED,
+			Json(Response::Single {
+				data: Some(request),
+			}),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				ContentServiceError::BlockedByOwner => StatusCode::FORBIDDEN,
+				ContentServiceError::SaveAccessRequest(
+					ContentRepositoryError::AccessRequestPending,
+				) => StatusCode::CONFLICT,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::RequestAccess(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for listing the pending access requests for a [ContentBlock].
+async fn access_requests_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+) -> (StatusCode, Json<Response<AccessRequest>>) {
+	let block_id = match DissociatedNuttyId::new(&block_id) {
+		Ok(id) => id,
+
+		Err(error) => {
+			let summary = "Failed to list access requests.";
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	// Check if the navigator can manage access to this content block.
+	let has_access = state
+		.content_service
+		.check_content_block_manage_access(navigator.nutty_id(), &block_id)
+		.await;
+
+	match has_access {
+		Ok(true) => match state.content_service.list_access_requests(&block_id).await {
+			Ok(requests) => (StatusCode::OK, Json(Response::Multiple { data: requests })),
+
+			Err(error) => {
+				let summary = "Failed to list access requests.";
+				let error = ContentApiError::ListAccessRequests(error);
+				let error = Error::from_error(&error).with_summary(summary);
+
+				(
+					StatusCode::INTERNAL_SERVER_ERROR,
+					Json(Response::Error {
+						errors: vec![error],
+					}),
+				)
+			}
+		},
+
+		Ok(false) => {
+			let summary = "Access denied.";
+			let error = ContentApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+
+		Err(error) => {
+			let summary = "Failed to check access permissions.";
+			let error = ContentApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for approving an access request.
+async fn approve_access_request_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	transaction: RequestTransaction,
+	Path(request_id): Path<NuttyId>,
+) -> (StatusCode, Json<Response<AccessRequest>>) {
+	transaction.begin();
+	resolve_access_request(&state, navigator.nutty_id(), &request_id, true).await
+}
+
+/// An API handler for denying an access request.
+async fn deny_access_request_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	transaction: RequestTransaction,
+	Path(request_id): Path<NuttyId>,
+) -> (StatusCode, Json<Response<AccessRequest>>) {
+	transaction.begin();
+	resolve_access_request(&state, navigator.nutty_id(), &request_id, false).await
+}
+
+/// Approve or deny an access request on behalf of a navigator. Resolving it
+/// and granting the access it asked for are saved together.
+async fn resolve_access_request(
+	state: &AppState,
+	navigator_id: &NuttyId,
+	request_id: &NuttyId,
+	approve: bool,
+) -> (StatusCode, Json<Response<AccessRequest>>) {
+	let request = state
+		.content_service
+		.resolve_access_request(navigator_id, request_id, approve)
+		.await;
+
+	match request {
+		Ok(request) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(request),
+			}),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ManageDenied => StatusCode::FORBIDDEN,
+				ContentServiceError::AccessRequestNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let summary = "Failed to resolve access request.";
+			let error = ContentApiError::ResolveAccessRequest(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Request payload for transferring a content block to a new owner.
+#[derive(Serialize, Deserialize)]
+pub struct TransferOwnershipRequest {
+	new_owner_id: NuttyId,
+
+	#[serde(default)]
+	include_descendants: bool,
+}
+
+/// An API handler for transferring a content block to a new owner.
+async fn transfer_ownership_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+	Json(payload): Json<TransferOwnershipRequest>,
+) -> (StatusCode, Json<Response<OwnershipTransfer>>) {
+	let summary = "Failed to transfer ownership.";
+
+	let block_id = match DissociatedNuttyId::new(&block_id) {
+		Ok(id) => id,
+
+		Err(error) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	let transfers = state
+		.content_service
+		.transfer_ownership(
+			navigator.nutty_id(),
+			&block_id,
+			&payload.new_owner_id,
+			payload.include_descendants,
+		)
+		.await;
+
+	match transfers {
+		Ok(transfers) => (StatusCode::OK, Json(Response::Multiple { data: transfers })),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::TransferDenied => StatusCode::FORBIDDEN,
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				ContentServiceError::TransferOwnership(ContentRepositoryError::UnknownOwner) => {
+					StatusCode::BAD_REQUEST
+				}
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::TransferOwnership(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Query parameters for the content calendar.
+#[derive(Deserialize)]
+pub struct CalendarQuery {
+	/// The first date of the range.
+	from: NaiveDate,
+
+	/// The last date of the range, inclusive.
+	to: NaiveDate,
+}
+
+/// An API handler for fetching the date-linked blocks in a date range.
+async fn content_calendar_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Query(query): Query<CalendarQuery>,
+) -> (StatusCode, Json<Response<ContentCalendar>>) {
+	let calendar = state
+		.content_service
+		.get_content_calendar(navigator.nutty_id(), query.from, query.to)
+		.await;
+
+	match calendar {
+		Ok(calendar) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(calendar),
+			}),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::InvalidDateRange => StatusCode::BAD_REQUEST,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let summary = "Failed to query content calendar.";
+			let error = ContentApiError::QueryCalendar(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// How many days before today a calendar feed covers.
+const FEED_DAYS_BEFORE: u64 = 90;
+
+/// How many days after today a calendar feed covers.
+const FEED_DAYS_AFTER: u64 = 275;
+
+/// A navigator's calendar feed, for subscribing from calendar apps.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CalendarFeed {
+	/// The token that authenticates the feed.
+	pub token: String,
+
+	/// The feed's path, including its token.
+	pub path: String,
+}
+
+/// An API handler for getting the navigator's calendar feed.
+async fn calendar_feed_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+) -> (StatusCode, Json<Response<CalendarFeed>>) {
+	let version = state
+		.navigator_service
+		.get_feed_token_version(navigator.nutty_id())
+		.await
+		.and_then(|version| version.ok_or(NavigatorServiceError::NavigatorNotFound));
+
+	issue_calendar_feed(&state, navigator.nutty_id(), version)
+}
+
+/// An API handler for regenerating the navigator's calendar feed, revoking
+/// the feed's previous tokens.
+async fn regenerate_calendar_feed_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+) -> (StatusCode, Json<Response<CalendarFeed>>) {
+	let version = state
+		.navigator_service
+		.regenerate_feed_token(navigator.nutty_id())
+		.await;
+
+	issue_calendar_feed(&state, navigator.nutty_id(), version)
+}
+
+/// Issue the navigator's calendar feed at its current token version.
+fn issue_calendar_feed(
+	state: &AppState,
+	navigator_id: &NuttyId,
+	version: Result<i32, NavigatorServiceError>,
+) -> (StatusCode, Json<Response<CalendarFeed>>) {
+	let summary = "Failed to get calendar feed.";
+
+	let version = match version {
+		Ok(version) => version,
+
+		Err(error) => {
+			let error = ContentApiError::FeedToken(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	match state.feed_tokens.issue(navigator_id, version) {
+		Some(token) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(CalendarFeed {
+					path: format!("/calendar.ics?token={token}"),
+					token,
+				}),
+			}),
+		),
+
+		None => {
+			let error = ContentApiError::FeedsDisabled;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::SERVICE_UNAVAILABLE,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Query parameters for the calendar feed.
+#[derive(Deserialize)]
+pub struct CalendarFeedQuery {
+	/// The token from [calendar_feed_handler].
+	token: String,
+}
+
+/// An API handler for rendering a navigator's dated blocks as an iCalendar
+/// feed. Calendar apps can't hold a session, so a feed token authenticates
+/// the navigator instead, as long as they still exist and haven't regenerated
+/// the token since.
+async fn calendar_ics_handler(
+	State(state): State<Arc<AppState>>,
+	Query(query): Query<CalendarFeedQuery>,
+) -> axum::response::Response {
+	let summary = "Failed to render calendar feed.";
+
+	let Some((navigator_id, version)) = state.feed_tokens.verify(&query.token) else {
+		let error = ContentApiError::InvalidFeedToken;
+		let error = Error::from_error(&error).with_summary(summary);
+		let errors = vec![error];
+
+		return (
+			StatusCode::UNAUTHORIZED,
+			Json(Response::<()>::Error { errors }),
+		)
+			.into_response();
+	};
+
+	match state
+		.navigator_service
+		.get_feed_token_version(&navigator_id)
+		.await
+	{
+		Ok(Some(current)) if current == version => {}
+
+		// The token was rotated, or its navigator is gone.
+		Ok(_) => {
+			let error = ContentApiError::InvalidFeedToken;
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			return (
+				StatusCode::UNAUTHORIZED,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response();
+		}
+
+		Err(error) => {
+			let error = ContentApiError::FeedToken(error);
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			return (
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response();
+		}
+	}
+
+	let now = Utc::now();
+	let today = now.date_naive();
+	let from = today - Days::new(FEED_DAYS_BEFORE);
+	let to = today + Days::new(FEED_DAYS_AFTER);
+
+	match state
+		.content_service
+		.get_content_calendar(&navigator_id, from, to)
+		.await
+	{
+		Ok(calendar) => (
+			StatusCode::OK,
+			[(CONTENT_TYPE, "text/calendar; charset=utf-8")],
+			calendar.to_icalendar(now),
+		)
+			.into_response(),
+
+		Err(error) => {
+			let error = ContentApiError::QueryCalendar(error);
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response()
+		}
+	}
+}
+
+/// Query parameters for a preview image.
+#[derive(Deserialize)]
+pub struct OgImageQuery {
+	/// When the link from [RenderedBlock::og_image] expires, as a Unix
+	/// timestamp.
+	expires: i64,
+
+	/// The signature from [RenderedBlock::og_image].
+	signature: String,
+}
+
+/// An API handler for rendering a block's preview image, with its published
+/// title and breadcrumb, as a PNG. Link unfurls can't hold a session, so a
+/// signed link from rendering the block stands in for access to it.
+async fn og_image_handler(
+	State(state): State<Arc<AppState>>,
+	Path(file): Path<String>,
+	Query(query): Query<OgImageQuery>,
+) -> axum::response::Response {
+	let summary = "Failed to render preview image.";
+
+	let block_id = match DissociatedNuttyId::new(file.trim_end_matches(".png")) {
+		Ok(block_id) if file.ends_with(".png") => block_id,
+
+		Ok(_) => {
+			let error = ContentApiError::BlockNotFound;
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			return (
+				StatusCode::NOT_FOUND,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response();
+		}
+
+		Err(error) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response();
+		}
+	};
+
+	if !state
+		.og_image_links
+		.verify(&block_id, query.expires, &query.signature)
+	{
+		let error = ContentApiError::InvalidOgImageSignature;
+		let error = Error::from_error(&error).with_summary(summary);
+		let errors = vec![error];
+
+		return (
+			StatusCode::FORBIDDEN,
+			Json(Response::<()>::Error { errors }),
+		)
+			.into_response();
+	}
+
+	// Only published content is drawn, since the image is shown to anyone
+	// the link is shared with.
+	let title = match state
+		.content_service
+		.get_published_revision(&block_id)
+		.await
+	{
+		Ok(Some(revision)) => OutlineEntry::title_of(&revision.content),
+
+		Ok(None) => {
+			let error = ContentApiError::BlockNotFound;
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			return (
+				StatusCode::NOT_FOUND,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response();
+		}
+
+		Err(error) => {
+			let error = ContentApiError::QueryBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			return (
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response();
+		}
+	};
+
+	let include = ContextInclude {
+		parts: [ContextPart::Ancestors].into(),
+	};
+
+	let context = match state
+		.content_service
+		.get_content_block_context_partial(&block_id, &include, &ChildrenView::default())
+		.await
+	{
+		Ok(context) => context,
+
+		Err(ContentServiceError::ContentBlockNotFound) => {
+			let error = ContentApiError::BlockNotFound;
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			return (
+				StatusCode::NOT_FOUND,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response();
+		}
+
+		Err(error) => {
+			let error = ContentApiError::QueryBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			return (
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response();
+		}
+	};
+
+	let mut published = HashMap::new();
+
+	for ancestor_id in context.ancestor_ids() {
+		match state
+			.content_service
+			.get_published_revision(&ancestor_id.dissociate())
+			.await
+		{
+			Ok(Some(revision)) => {
+				published.insert(*ancestor_id, revision.content);
+			}
+
+			Ok(None) => {}
+
+			Err(error) => {
+				let error = ContentApiError::QueryBlockContext(error);
+				let error = Error::from_error(&error).with_summary(summary);
+				let errors = vec![error];
+
+				return (
+					StatusCode::INTERNAL_SERVER_ERROR,
+					Json(Response::<()>::Error { errors }),
+				)
+					.into_response();
+			}
+		}
+	}
+
+	let breadcrumb = og_image::breadcrumb(&context, &published);
+
+	match state.og_images.render(&title, &breadcrumb).await {
+		Ok(png) => (
+			StatusCode::OK,
+			[
+				(CONTENT_TYPE, "image/png"),
+				(CACHE_CONTROL, "public, max-age=86400"),
+			],
+			png,
+		)
+			.into_response(),
+
+		Err(error) => {
+			let error = ContentApiError::RenderOgImage(error);
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response()
+		}
+	}
+}
+
+/// Query parameters for a content diff.
+#[derive(Deserialize)]
+pub struct DiffQuery {
+	/// The revision to diff from.
+	from: Option<String>,
+
+	/// The revision to diff to, instead of the block's current content.
+	to: Option<String>,
+
+	/// Another block to diff the block's current content against, instead
+	/// of diffing revisions.
+	against: Option<String>,
+}
+
+/// An API handler for diffing a [ContentBlock] between two of its revisions,
+/// or against another block.
+async fn diff_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+	Query(query): Query<DiffQuery>,
+) -> (StatusCode, Json<Response<ContentDiff>>) {
+	let summary = "Failed to diff content.";
+
+	let parse = |id: Option<&String>| id.map(|id| DissociatedNuttyId::new(id)).transpose();
+
+	let (block_id, from, to, against) = match (
+		DissociatedNuttyId::new(&block_id),
+		parse(query.from.as_ref()),
+		parse(query.to.as_ref()),
+		parse(query.against.as_ref()),
+	) {
+		(Ok(block_id), Ok(from), Ok(to), Ok(against)) => (block_id, from, to, against),
+
+		(Err(error), ..) | (_, Err(error), ..) | (_, _, Err(error), _) | (.., Err(error)) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	if against.is_some() && (from.is_some() || to.is_some()) {
+		let error = ContentApiError::AmbiguousDiff;
+		let error = Error::from_error(&error).with_summary(summary);
+
+		return (
+			StatusCode::BAD_REQUEST,
+			Json(Response::Error {
+				errors: vec![error],
+			}),
+		);
+	}
+
+	// Check that the navigator can read every block being compared.
+	for block_id in [Some(block_id), against].into_iter().flatten() {
+		match state
+			.content_service
+			.check_content_block_access(navigator.nutty_id(), &block_id)
+			.await
+		{
+			Ok(true) => {}
+
+			Ok(false) => {
+				let error = ContentApiError::AccessDenied;
+				let error = Error::from_error(&error).with_summary(summary);
+
+				return (
+					StatusCode::FORBIDDEN,
+					Json(Response::Error {
+						errors: vec![error],
+					}),
+				);
+			}
+
+			Err(error) => {
+				let error = ContentApiError::AccessControl(error);
+				let error = Error::from_error(&error).with_summary(summary);
+
+				return (
+					StatusCode::INTERNAL_SERVER_ERROR,
+					Json(Response::Error {
+						errors: vec![error],
+					}),
+				);
+			}
+		}
+	}
+
+	let diff = match against {
+		Some(against) => {
+			state
+				.content_service
+				.diff_content_blocks(&block_id, &against)
+				.await
+		}
+
+		None => {
+			state
+				.content_service
+				.diff_content_block(&block_id, from.as_ref(), to.as_ref())
+				.await
+		}
+	};
+
+	match diff {
+		Ok(diff) => (StatusCode::OK, Json(Response::Single { data: Some(diff) })),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::MissingRevision => StatusCode::BAD_REQUEST,
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				ContentServiceError::RevisionNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::Diff(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Query parameters for the task list.
+#[derive(Deserialize)]
+pub struct TasksQuery {
+	/// Only list tasks with this status.
+	status: Option<TaskStatus>,
+
+	/// Only list tasks due before this date.
+	due_before: Option<NaiveDate>,
+}
+
+/// An API handler for listing the todos a navigator can access.
+async fn tasks_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Query(query): Query<TasksQuery>,
+) -> (StatusCode, Json<Response<Task>>) {
+	let tasks = state
+		.content_service
+		.list_tasks(navigator.nutty_id(), query.status, query.due_before)
+		.await;
+
+	match tasks {
+		Ok(tasks) => (StatusCode::OK, Json(Response::Multiple { data: tasks })),
+
+		Err(error) => {
+			let summary = "Failed to list tasks.";
+			let error = ContentApiError::ListTasks(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for toggling whether a todo is done.
+async fn toggle_task_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+) -> (StatusCode, Json<Response<ContentBlock>>) {
+	let block_id = match DissociatedNuttyId::new(&block_id) {
+		Ok(id) => id,
+
+		Err(error) => {
+			let summary = "Failed to toggle task.";
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	// Check if the navigator has write access to this content block.
+	let has_access = state
+		.content_service
+		.check_content_block_write_access(navigator.nutty_id(), &block_id)
+		.await;
+
+	match has_access {
+		Ok(true) => match state.content_service.toggle_todo(&block_id).await {
+			Ok(content_block) => (
+				StatusCode::OK,
+				Json(Response::Single {
+					data: Some(content_block),
+				}),
+			),
+
+			Err(error) => {
+				let status = match error {
+					ContentServiceError::NotATodo => StatusCode::BAD_REQUEST,
+					ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+					_ => StatusCode::INTERNAL_SERVER_ERROR,
+				};
+
+				let summary = "Failed to toggle task.";
+				let error = ContentApiError::ToggleTask(error);
+				let error = Error::from_error(&error).with_summary(summary);
+
+				(
+					status,
+					Json(Response::Error {
+						errors: vec![error],
+					}),
+				)
+			}
+		},
+
+		Ok(false) => {
+			let summary = "Access denied.";
+			let error = ContentApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+
+		Err(error) => {
+			let summary = "Failed to check access permissions.";
+			let error = ContentApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for listing the custom property definitions.
+async fn list_properties_handler(
+	State(state): State<Arc<AppState>>,
+	_: Session,
+) -> (StatusCode, Json<Response<PropertyDefinition>>) {
+	match state.content_service.list_property_definitions().await {
+		Ok(definitions) => (
+			StatusCode::OK,
+			Json(Response::Multiple { data: definitions }),
+		),
+
+		Err(error) => {
+			let summary = "Failed to list properties.";
+			let error = ContentApiError::ListProperties(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Request payload for a find and replace across a navigator's blocks.
+#[derive(Serialize, Deserialize)]
+pub struct FindReplaceRequest {
+	/// The regex pattern to find.
+	pattern: String,
+
+	/// The replacement, which may refer to capture groups as `$1`.
+	replacement: String,
+
+	/// Whether to only report the matches, without replacing them.
+	#[serde(default)]
+	dry_run: bool,
+}
+
+/// An API handler for finding and replacing text across the blocks that a
+/// navigator owns.
+async fn find_replace_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Json(payload): Json<FindReplaceRequest>,
+) -> (StatusCode, Json<Response<TextMatch>>) {
+	match state
+		.content_service
+		.find_and_replace(
+			navigator.nutty_id(),
+			&payload.pattern,
+			&payload.replacement,
+			payload.dry_run,
+		)
+		.await
+	{
+		Ok(matches) => (StatusCode::OK, Json(Response::Multiple { data: matches })),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::InvalidFindReplace(_) => StatusCode::BAD_REQUEST,
+				ContentServiceError::EditDenied => StatusCode::FORBIDDEN,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let summary = "Failed to find and replace.";
+			let error = ContentApiError::FindReplace(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// How a merge request combines the blocks' content.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategyKind {
+	#[default]
+	Concatenate,
+	ThreeWay,
+}
+
+/// Request payload for merging one content block into another.
+#[derive(Serialize, Deserialize)]
+pub struct MergeRequest {
+	/// The block to merge, which is deleted afterwards.
+	source_id: String,
+
+	/// The block to merge into.
+	target_id: String,
+
+	/// How to combine the blocks' content.
+	#[serde(default)]
+	strategy: MergeStrategyKind,
+
+	/// The revision that both blocks diverged from, for a three-way merge.
+	base_revision_id: Option<String>,
+}
+
+/// An API handler for merging one content block into another, such as a
+/// copy that diverged while offline. Conflicting changes are each reported
+/// as an error.
+async fn merge_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Json(payload): Json<MergeRequest>,
+) -> (StatusCode, Json<Response<BlockMerge>>) {
+	let summary = "Failed to merge content blocks.";
+
+	let base_revision_id = payload
+		.base_revision_id
+		.as_deref()
+		.map(DissociatedNuttyId::new)
+		.transpose();
+
+	let (source_id, target_id, base_revision_id) = match (
+		DissociatedNuttyId::new(&payload.source_id),
+		DissociatedNuttyId::new(&payload.target_id),
+		base_revision_id,
+	) {
+		(Ok(source_id), Ok(target_id), Ok(base_revision_id)) => {
+			(source_id, target_id, base_revision_id)
+		}
+
+		(Err(error), ..) | (_, Err(error), _) | (.., Err(error)) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	let strategy = match (payload.strategy, base_revision_id) {
+		(MergeStrategyKind::Concatenate, _) => MergeStrategy::Concatenate,
+		(MergeStrategyKind::ThreeWay, Some(base_revision_id)) => {
+			MergeStrategy::ThreeWay { base_revision_id }
+		}
+		(MergeStrategyKind::ThreeWay, None) => {
+			let error = ContentApiError::MissingBaseRevision;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	// Check that the navigator can write to both blocks.
+	for block_id in [&source_id, &target_id] {
+		match state
+			.content_service
+			.check_content_block_write_access(navigator.nutty_id(), block_id)
+			.await
+		{
+			Ok(true) => {}
+
+			Ok(false) => {
+				let error = ContentApiError::AccessDenied;
+				let error = Error::from_error(&error).with_summary(summary);
+
+				return (
+					StatusCode::FORBIDDEN,
+					Json(Response::Error {
+						errors: vec![error],
+					}),
+				);
+			}
+
+			Err(error) => {
+				let error = ContentApiError::AccessControl(error);
+				let error = Error::from_error(&error).with_summary(summary);
+
+				return (
+					StatusCode::INTERNAL_SERVER_ERROR,
+					Json(Response::Error {
+						errors: vec![error],
+					}),
+				);
+			}
+		}
+	}
+
+	match state
+		.content_service
+		.merge_blocks(&source_id, &target_id, &strategy)
+		.await
+	{
+		Ok(merge) => (StatusCode::OK, Json(Response::Single { data: Some(merge) })),
+
+		Err(ContentServiceError::InvalidMerge(BlockMergeError::Conflicts(conflicts))) => {
+			let errors = conflicts
+				.into_iter()
+				.map(|conflict| {
+					let error = ContentApiError::MergeConflict(conflict);
+					Error::from_error(&error).with_summary("Failed to merge content blocks.")
+				})
+				.collect();
+
+			(StatusCode::CONFLICT, Json(Response::Error { errors }))
+		}
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::InvalidMerge(_) => StatusCode::BAD_REQUEST,
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				ContentServiceError::RevisionNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::Merge(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Request payload for converting a content block to another kind.
+#[derive(Serialize, Deserialize)]
+pub struct ConvertRequest {
+	/// The kind to convert to, e.g. `Page`.
+	kind: ConvertibleKind,
+}
+
+/// An API handler for converting a content block to another kind, e.g. a
+/// paragraph into a page, without losing its identity or backlinks.
+async fn convert_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+	Json(payload): Json<ConvertRequest>,
+) -> (StatusCode, Json<Response<BlockConversion>>) {
+	let summary = "Failed to convert content block.";
+
+	let block_id = match DissociatedNuttyId::new(&block_id) {
+		Ok(block_id) => block_id,
+
+		Err(error) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	// Check if the navigator has write access to this content block.
+	match state
+		.content_service
+		.check_content_block_write_access(navigator.nutty_id(), &block_id)
+		.await
+	{
+		Ok(true) => {}
+
+		Ok(false) => {
+			let error = ContentApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	}
+
+	match state
+		.content_service
+		.convert_block(navigator.nutty_id(), &block_id, payload.kind)
+		.await
+	{
+		Ok(conversion) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(conversion),
+			}),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::InvalidConversion(_) => StatusCode::BAD_REQUEST,
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::Convert(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Request payload for splitting a paragraph.
+#[derive(Serialize, Deserialize)]
+pub struct SplitRequest {
+	/// Where to split the paragraph's text, in characters.
+	offset: usize,
+}
+
+/// An API handler for splitting a paragraph in two, as an editor does when
+/// a line is broken.
+async fn split_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+	Json(payload): Json<SplitRequest>,
+) -> (StatusCode, Json<Response<BlockSplit>>) {
+	let summary = "Failed to split paragraph.";
+
+	let block_id = match check_paragraph_write_access(&state, navigator.nutty_id(), &block_id).await
+	{
+		Ok(block_id) => block_id,
+
+		Err((status, error)) => {
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	match state
+		.content_service
+		.split_paragraph(navigator.nutty_id(), &block_id, payload.offset)
+		.await
+	{
+		Ok(split) => (StatusCode::OK, Json(Response::Single { data: Some(split) })),
+
+		Err(error) => {
+			let status = paragraph_error_status(&error);
+			let error = ContentApiError::EditParagraph(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for merging a paragraph with the one right after it, as
+/// an editor does when a line break is deleted.
+async fn merge_with_next_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+) -> (StatusCode, Json<Response<BlockMerge>>) {
+	let summary = "Failed to merge paragraphs.";
+
+	let block_id = match check_paragraph_write_access(&state, navigator.nutty_id(), &block_id).await
+	{
+		Ok(block_id) => block_id,
+
+		Err((status, error)) => {
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	match state
+		.content_service
+		.merge_with_next(navigator.nutty_id(), &block_id)
+		.await
+	{
+		Ok(merge) => (StatusCode::OK, Json(Response::Single { data: Some(merge) })),
+
+		Err(error) => {
+			let status = paragraph_error_status(&error);
+			let error = ContentApiError::EditParagraph(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Request payload for reordering a content block's children.
+#[derive(Serialize, Deserialize)]
+pub struct ReorderRequest {
+	/// The Nutty IDs of all of the block's children, in their new order.
+	child_ids: Vec<String>,
+}
+
+/// An API handler for reordering all of a content block's children at once,
+/// e.g. to sort them, rather than moving them one by one.
+async fn reorder_children_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+	Json(payload): Json<ReorderRequest>,
+) -> (StatusCode, Json<Response<ContentBlock>>) {
+	let summary = "Failed to reorder children.";
+
+	let child_ids = payload
+		.child_ids
+		.iter()
+		.map(|child_id| DissociatedNuttyId::new(child_id))
+		.collect::<Result<Vec<_>, _>>();
+
+	let (block_id, child_ids) = match (DissociatedNuttyId::new(&block_id), child_ids) {
+		(Ok(block_id), Ok(child_ids)) => (block_id, child_ids),
+
+		(Err(error), _) | (_, Err(error)) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	// Check if the navigator has write access to this content block.
+	match state
+		.content_service
+		.check_content_block_write_access(navigator.nutty_id(), &block_id)
+		.await
+	{
+		Ok(true) => {}
+
+		Ok(false) => {
+			let error = ContentApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	}
+
+	match state
+		.content_service
+		.reorder_children(&block_id, &child_ids)
+		.await
+	{
+		Ok(children) => (StatusCode::OK, Json(Response::Multiple { data: children })),
+
+		Err(error) => {
+			let status = match error {
+				// The client's view of the children is out of date.
+				ContentServiceError::ChildrenMismatch => StatusCode::CONFLICT,
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::ReorderChildren(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Request payload for pasting rich text.
+#[derive(Serialize, Deserialize)]
+pub struct PasteRequest {
+	/// The pasted HTML, as the clipboard holds it.
+	html: String,
+}
+
+/// An API handler for pasting rich text under a content block, converted
+/// into blocks after its last child.
+async fn paste_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+	Json(payload): Json<PasteRequest>,
+) -> (StatusCode, Json<Response<ContentBlock>>) {
+	let summary = "Failed to paste.";
+
+	let parent_id = match DissociatedNuttyId::new(&block_id) {
+		Ok(parent_id) => parent_id,
+
+		Err(error) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	// Check if the navigator has write access to the parent.
+	match state
+		.content_service
+		.check_content_block_write_access(navigator.nutty_id(), &parent_id)
+		.await
+	{
+		Ok(true) => {}
+
+		Ok(false) => {
+			let error = ContentApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	}
+
+	match state
+		.content_service
+		.paste_html(navigator.nutty_id(), &parent_id, &payload.html)
+		.await
+	{
+		Ok(blocks) => (
+			StatusCode::CREATED,
+			Json(Response::Multiple { data: blocks }),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::InvalidPaste(HtmlImportError::NothingToPaste) => {
+					StatusCode::BAD_REQUEST
+				}
+				ContentServiceError::InvalidPaste(HtmlImportError::TooManyBlocks) => {
+					StatusCode::PAYLOAD_TOO_LARGE
+				}
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::Paste(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Parse a paragraph's Nutty ID, and check that a navigator may edit it,
+/// for the paragraph handlers.
+async fn check_paragraph_write_access(
+	state: &AppState,
+	navigator_id: &NuttyId,
+	block_id: &str,
+) -> Result<DissociatedNuttyId, (StatusCode, ContentApiError)> {
+	let block_id = DissociatedNuttyId::new(block_id).map_err(|error| {
+		(
+			StatusCode::BAD_REQUEST,
+			ContentApiError::LookupBlockContext(error),
+		)
+	})?;
+
+	match state
+		.content_service
+		.check_content_block_write_access(navigator_id, &block_id)
+		.await
+	{
+		Ok(true) => Ok(block_id),
+		Ok(false) => Err((StatusCode::FORBIDDEN, ContentApiError::AccessDenied)),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			Err((status, ContentApiError::AccessControl(error)))
+		}
+	}
+}
+
+/// Get the status for an error from splitting or merging paragraphs.
+fn paragraph_error_status(error: &ContentServiceError) -> StatusCode {
+	match error {
+		ContentServiceError::NotAParagraph
+		| ContentServiceError::NoNextSibling
+		| ContentServiceError::InvalidSplit(_) => StatusCode::BAD_REQUEST,
+		ContentServiceError::EditDenied => StatusCode::FORBIDDEN,
+		ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+		_ => StatusCode::INTERNAL_SERVER_ERROR,
+	}
+}
+
+/// Query parameters for a content search.
+#[derive(Deserialize)]
+pub struct SearchQuery {
+	/// The search terms, e.g. `"exact phrase" -excluded`.
+	q: String,
+
+	/// Only search blocks in this language, e.g. `japanese`.
+	language: Option<SearchLanguage>,
+
+	/// Whether to search archived blocks too.
+	#[serde(default)]
+	include_archived: bool,
+}
+
+/// An API handler for searching the content blocks a navigator can access.
+async fn search_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Query(query): Query<SearchQuery>,
+) -> (StatusCode, Json<Response<ContentBlock>>) {
+	match state
+		.content_service
+		.search_content_blocks(
+			navigator.nutty_id(),
+			&query.q,
+			query.language,
+			query.include_archived,
+		)
+		.await
+	{
+		Ok(blocks) => (StatusCode::OK, Json(Response::Multiple { data: blocks })),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::EmptySearchQuery => StatusCode::BAD_REQUEST,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let summary = "Failed to search content.";
+			let error = ContentApiError::Search(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Query parameters for listing content blocks.
+#[derive(Deserialize)]
+pub struct ListContentQuery {
+	/// The kinds of blocks to list, e.g. `page,todo`.
+	kind: Option<String>,
+
+	/// Whose blocks to list; only `me` is supported.
+	owner: Option<String>,
+
+	/// Whether to list archived blocks too.
+	#[serde(default)]
+	include_archived: bool,
+}
+
+/// An API handler for listing the content blocks a navigator can access by
+/// kind and owner, most recently created first.
+async fn list_content_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Query(query): Query<ListContentQuery>,
+) -> (StatusCode, Json<Response<ContentBlock>>) {
+	let summary = "Failed to list content.";
+
+	let filter = ContentFilter::parse(
+		query.kind.as_deref(),
+		query.owner.as_deref(),
+		navigator.nutty_id(),
+		|kind| state.block_kinds.resolve(kind).map(str::to_string),
+	);
+
+	let filter = match filter {
+		Ok(filter) => ContentFilter {
+			include_archived: query.include_archived,
+			..filter
+		},
+
+		Err(error) => {
+			let error = ContentApiError::InvalidContentFilter(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	match state
+		.content_service
+		.list_content_blocks(navigator.nutty_id(), &filter)
+		.await
+	{
+		Ok(blocks) => (StatusCode::OK, Json(Response::Multiple { data: blocks })),
+
+		Err(error) => {
+			let error = ContentApiError::ListContent(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Query parameters for archiving or unarchiving a content block.
+#[derive(Deserialize)]
+pub struct ArchiveQuery {
+	/// Whether to archive or unarchive everything under the block too.
+	#[serde(default)]
+	subtree: bool,
+}
+
+/// An API handler for archiving a content block, optionally with everything
+/// under it. Archived blocks are kept, but left out of listings, search, and
+/// their parents' children unless `include_archived` is set.
+async fn archive_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+	Query(query): Query<ArchiveQuery>,
+) -> (StatusCode, Json<Response<BlockArchival>>) {
+	set_archived(&state, navigator.nutty_id(), &block_id, true, query.subtree).await
+}
+
+/// An API handler for unarchiving a content block, optionally with
+/// everything under it.
+async fn unarchive_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+	Query(query): Query<ArchiveQuery>,
+) -> (StatusCode, Json<Response<BlockArchival>>) {
+	set_archived(
+		&state,
+		navigator.nutty_id(),
+		&block_id,
+		false,
+		query.subtree,
+	)
+	.await
+}
+
+async fn set_archived(
+	state: &AppState,
+	navigator_id: &NuttyId,
+	block_id: &str,
+	archived: bool,
+	subtree: bool,
+) -> (StatusCode, Json<Response<BlockArchival>>) {
+	let summary = match archived {
+		true => "Failed to archive content block.",
+		false => "Failed to unarchive content block.",
+	};
+
+	let block_id = match DissociatedNuttyId::new(block_id) {
+		Ok(id) => id,
+
+		Err(error) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	// Check if the navigator has write access to this content block.
+	let has_access = state
+		.content_service
+		.check_content_block_write_access(navigator_id, &block_id)
+		.await;
+
+	match has_access {
+		Ok(true) => {}
+
+		Ok(false) => {
+			let error = ContentApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary("Access denied.");
+
+			return (
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	}
+
+	match state
+		.content_service
+		.set_content_block_archived(&block_id, archived, subtree)
+		.await
+	{
+		Ok(archival) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(archival),
+			}),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::SetArchived(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Request payload for setting a block's search language.
+#[derive(Serialize, Deserialize)]
+pub struct SetLanguageRequest {
+	/// The language to search the block and its descendants in, or `None`
+	/// to search them as English.
+	language: Option<SearchLanguage>,
+}
+
+/// An API handler for setting the search language of a content block and
+/// everything under it.
+async fn set_language_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+	Json(payload): Json<SetLanguageRequest>,
+) -> (StatusCode, Json<Response<LanguageUpdate>>) {
+	let summary = "Failed to set search language.";
+
+	let block_id = match DissociatedNuttyId::new(&block_id) {
+		Ok(id) => id,
+
+		Err(error) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	// Check if the navigator has write access to this content block.
+	let has_access = state
+		.content_service
+		.check_content_block_write_access(navigator.nutty_id(), &block_id)
+		.await;
+
+	match has_access {
+		Ok(true) => {}
+
+		Ok(false) => {
+			let error = ContentApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary("Access denied.");
+
+			return (
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	}
+
+	match state
+		.content_service
+		.set_content_block_language(&block_id, payload.language)
+		.await
+	{
+		Ok(updated_count) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(LanguageUpdate {
+					language: payload.language,
+					updated_count,
+				}),
+			}),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::SetLanguage(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Check that a navigator may read (or write) a content block, for the
+/// translation handlers.
+async fn check_translation_access(
+	state: &AppState,
+	navigator_id: &NuttyId,
+	block_id: &DissociatedNuttyId,
+	write: bool,
+) -> Result<(), (StatusCode, ContentApiError)> {
+	let has_access = match write {
+		true => {
+			state
+				.content_service
+				.check_content_block_write_access(navigator_id, block_id)
+				.await
+		}
+
+		false => {
+			state
+				.content_service
+				.check_content_block_access(navigator_id, block_id)
+				.await
+		}
+	};
+
+	match has_access {
+		Ok(true) => Ok(()),
+		Ok(false) => Err((StatusCode::FORBIDDEN, ContentApiError::AccessDenied)),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			Err((status, ContentApiError::AccessControl(error)))
+		}
+	}
+}
+
+/// Query parameters for fetching a content block.
+#[derive(Deserialize)]
+pub struct LocalizeQuery {
+	/// The preferred languages, most preferred first, e.g. `ja,en`.
+	lang: Option<String>,
+}
+
+/// An API handler for fetching a content block, in the preferred language
+/// if it's translated to it.
+async fn localized_block_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+	Query(query): Query<LocalizeQuery>,
+) -> (StatusCode, Json<Response<LocalizedBlock>>) {
+	let summary = "Failed to get content block.";
+
+	let block_id = match DissociatedNuttyId::new(&block_id) {
+		Ok(block_id) => block_id,
+
+		Err(error) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	let preferred = match query.lang.as_deref().map(Lang::parse_list) {
+		Some(Ok(preferred)) => preferred,
+
+		Some(Err(error)) => {
+			let error = ContentApiError::InvalidLang(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+		None => vec![],
+	};
+
+	if let Err((status, error)) =
+		check_translation_access(&state, navigator.nutty_id(), &block_id, false).await
+	{
+		let error = Error::from_error(&error).with_summary(summary);
+
+		return (
+			status,
+			Json(Response::Error {
+				errors: vec![error],
+			}),
+		);
+	}
+
+	match state
+		.content_service
+		.localize_content_block(navigator.nutty_id(), &block_id, &preferred)
+		.await
+	{
+		Ok(localized) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(localized),
+			}),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::Translation(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for listing the translations of a content block.
+async fn translations_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+) -> (StatusCode, Json<Response<Translation>>) {
+	let summary = "Failed to list translations.";
+
+	let block_id = match DissociatedNuttyId::new(&block_id) {
+		Ok(block_id) => block_id,
+
+		Err(error) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	if let Err((status, error)) =
+		check_translation_access(&state, navigator.nutty_id(), &block_id, false).await
+	{
+		let error = Error::from_error(&error).with_summary(summary);
+
+		return (
+			status,
+			Json(Response::Error {
+				errors: vec![error],
+			}),
+		);
+	}
+
+	match state
+		.content_service
+		.list_translations(navigator.nutty_id(), &block_id)
+		.await
+	{
+		Ok(translations) => (
+			StatusCode::OK,
+			Json(Response::Multiple { data: translations }),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::Translation(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Request payload for setting a block's language.
+#[derive(Serialize, Deserialize)]
+pub struct SetTranslationRequest {
+	/// The block's language, e.g. `ja`.
+	lang: String,
+
+	/// The block that this one translates, whose translations it joins.
+	translation_of: Option<String>,
+}
+
+/// An API handler for setting the language of a content block, optionally
+/// as a translation of another. Both blocks must be writable, since their
+/// readers are sent between them.
+async fn set_translation_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+	Json(payload): Json<SetTranslationRequest>,
+) -> (StatusCode, Json<Response<Translation>>) {
+	let summary = "Failed to set translation.";
+
+	let block_ids = std::iter::once(Some(block_id.as_str()))
+		.chain(std::iter::once(payload.translation_of.as_deref()))
+		.flatten()
+		.map(DissociatedNuttyId::new)
+		.collect::<Result<Vec<_>, _>>();
+
+	let block_ids = match block_ids {
+		Ok(block_ids) => block_ids,
+
+		Err(error) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	let lang = match Lang::parse(&payload.lang) {
+		Ok(lang) => lang,
+
+		Err(error) => {
+			let error = ContentApiError::InvalidLang(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	for block_id in &block_ids {
+		if let Err((status, error)) =
+			check_translation_access(&state, navigator.nutty_id(), block_id, true).await
+		{
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	}
+
+	match state
+		.content_service
+		.set_translation(navigator.nutty_id(), &block_ids[0], &lang, block_ids.get(1))
+		.await
+	{
+		Ok(translations) => (
+			StatusCode::OK,
+			Json(Response::Multiple { data: translations }),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				ContentServiceError::TranslationTaken => StatusCode::CONFLICT,
+				ContentServiceError::UntranslatedBlock => StatusCode::BAD_REQUEST,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::Translation(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for removing a content block from its translations.
+async fn remove_translation_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+) -> (StatusCode, Json<Response<()>>) {
+	let summary = "Failed to remove translation.";
+
+	let block_id = match DissociatedNuttyId::new(&block_id) {
+		Ok(block_id) => block_id,
+
+		Err(error) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	if let Err((status, error)) =
+		check_translation_access(&state, navigator.nutty_id(), &block_id, true).await
+	{
+		let error = Error::from_error(&error).with_summary(summary);
+
+		return (
+			status,
+			Json(Response::Error {
+				errors: vec![error],
+			}),
+		);
+	}
+
+	match state.content_service.remove_translation(&block_id).await {
+		Ok(()) => (StatusCode::OK, Json(Response::Single { data: None })),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::Translation(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Request payload for publishing a revision of a block.
+#[derive(Serialize, Deserialize)]
+pub struct PublishRevisionRequest {
+	/// The revision to publish, or `None` to publish the current content.
+	#[serde(default)]
+	revision_id: Option<String>,
+}
+
+/// A block's newly published revision.
+#[derive(Serialize, Deserialize)]
+pub struct PublishedRevision {
+	pub revision_id: NuttyId,
+}
+
+/// An API handler for publishing a revision of a content block, which
+/// navigators who can't edit it see when it's rendered.
+async fn publish_revision_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+	Json(payload): Json<PublishRevisionRequest>,
+) -> (StatusCode, Json<Response<PublishedRevision>>) {
+	let summary = "Failed to publish revision.";
+
+	let (block_id, revision_id) = match (
+		DissociatedNuttyId::new(&block_id),
+		payload
+			.revision_id
+			.as_deref()
+			.map(DissociatedNuttyId::new)
+			.transpose(),
+	) {
+		(Ok(block_id), Ok(revision_id)) => (block_id, revision_id),
+
+		(Err(error), _) | (_, Err(error)) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	// Check if the navigator has write access to this content block.
+	match state
+		.content_service
+		.check_content_block_write_access(navigator.nutty_id(), &block_id)
+		.await
+	{
+		Ok(true) => {}
+
+		Ok(false) => {
+			let error = ContentApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	}
+
+	match state
+		.content_service
+		.publish_revision(navigator.nutty_id(), &block_id, revision_id.as_ref())
+		.await
+	{
+		Ok(revision_id) => {
+			delivery::spawn_page_published(
+				state.webhook_service.clone(),
+				state.content_service.clone(),
+				state.webhook_sender.clone(),
+				*navigator.nutty_id(),
+				revision_id,
+				block_id,
+			);
+
+			(
+				StatusCode::OK,
+				Json(Response::Single {
+					data: Some(PublishedRevision { revision_id }),
+				}),
+			)
+		}
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound | ContentServiceError::RevisionNotFound => {
+					StatusCode::NOT_FOUND
+				}
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::PublishRevision(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Request payload for setting a block's slug.
+#[derive(Serialize, Deserialize)]
+pub struct SetSlugRequest {
+	slug: String,
+}
+
+/// A block's slug, as an alias of its permalink.
+#[derive(Serialize, Deserialize)]
+pub struct BlockSlug {
+	pub block_id: NuttyId,
+	pub slug: Slug,
+}
+
+/// An API handler for setting a content block's slug. Its previous slug
+/// redirects to the new one.
+async fn set_slug_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+	Json(payload): Json<SetSlugRequest>,
+) -> (StatusCode, Json<Response<BlockSlug>>) {
+	let summary = "Failed to set slug.";
+
+	let block_id = match DissociatedNuttyId::new(&block_id) {
+		Ok(block_id) => block_id,
+
+		Err(error) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	let slug = match Slug::parse(&payload.slug) {
+		Ok(slug) => slug,
+
+		Err(error) => {
+			let error = ContentApiError::InvalidSlug(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	// Check if the navigator has write access to this content block.
+	match state
+		.content_service
+		.check_content_block_write_access(navigator.nutty_id(), &block_id)
+		.await
+	{
+		Ok(true) => {}
+
+		Ok(false) => {
+			let error = ContentApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	}
+
+	match state
+		.content_service
+		.set_content_block_slug(navigator.nutty_id(), &block_id, &slug)
+		.await
+	{
+		Ok(block_id) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(BlockSlug { block_id, slug }),
+			}),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				ContentServiceError::SlugTaken => StatusCode::CONFLICT,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::SetSlug(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for getting the stored JSON of a content block's
+/// content, for scripting and debugging.
+async fn raw_content_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+) -> (StatusCode, Json<Response<Value>>) {
+	let summary = "Failed to get raw content.";
+
+	let block_id = match DissociatedNuttyId::new(&block_id) {
+		Ok(block_id) => block_id,
+
+		Err(error) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	// Check if the navigator has access to this content block.
+	match state
+		.content_service
+		.check_content_block_access(navigator.nutty_id(), &block_id)
+		.await
+	{
+		Ok(true) => {}
+
+		Ok(false) => {
+			let error = ContentApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	}
+
+	match state
+		.content_service
+		.get_raw_content(navigator.nutty_id(), &block_id)
+		.await
+	{
+		Ok(content) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(content),
+			}),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::RawContentDenied => StatusCode::FORBIDDEN,
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::RawContent(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for replacing a content block's content with raw JSON,
+/// which is validated against the block kinds before it's saved.
+async fn save_raw_content_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+	Json(payload): Json<Value>,
+) -> (StatusCode, Json<Response<Value>>) {
+	let summary = "Failed to save raw content.";
+
+	let block_id = match DissociatedNuttyId::new(&block_id) {
+		Ok(block_id) => block_id,
+
+		Err(error) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	// Check if the navigator has write access to this content block.
+	match state
+		.content_service
+		.check_content_block_write_access(navigator.nutty_id(), &block_id)
+		.await
+	{
+		Ok(true) => {}
+
+		Ok(false) => {
+			let error = ContentApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	}
+
+	match state
+		.content_service
+		.save_raw_content(navigator.nutty_id(), &block_id, payload)
+		.await
+	{
+		Ok(content) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(content),
+			}),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::RawContentDenied => StatusCode::FORBIDDEN,
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				ContentServiceError::InvalidRawContent(_)
+				| ContentServiceError::InvalidContent(_)
+				| ContentServiceError::ParseBlockQuery(_) => StatusCode::BAD_REQUEST,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::RawContent(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Query parameters for a subtree's checksums.
+#[derive(Deserialize)]
+pub struct ChecksumsQuery {
+	/// The NID of the subtree's root block.
+	pub root: String,
+}
+
+/// An API handler for listing the checksums of a subtree's blocks, so that
+/// sync clients can tell which of their blocks have drifted. Checksums are
+/// written as newline-delimited JSON arrays of NID, content hash, and
+/// update time, which clients can diff line by line.
+async fn checksums_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Query(query): Query<ChecksumsQuery>,
+) -> axum::response::Response {
+	let summary = "Failed to list checksums.";
+
+	let root_id = match DissociatedNuttyId::new(&query.root) {
+		Ok(root_id) => root_id,
+
+		Err(error) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response();
+		}
+	};
+
+	// Check if the navigator has access to the subtree's root.
+	match state
+		.content_service
+		.check_content_block_access(navigator.nutty_id(), &root_id)
+		.await
+	{
+		Ok(true) => {}
+
+		Ok(false) => {
+			let error = ContentApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			return (
+				StatusCode::FORBIDDEN,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response();
+		}
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			return (status, Json(Response::<()>::Error { errors })).into_response();
+		}
+	}
+
+	match state.content_service.list_block_checksums(&root_id).await {
+		Ok(checksums) => {
+			let body: String = checksums
+				.iter()
+				.map(|checksum| checksum.to_json_line() + "\n")
+				.collect();
+
+			(
+				StatusCode::OK,
+				[(CONTENT_TYPE, "application/x-ndjson")],
+				body,
+			)
+				.into_response()
+		}
+
+		Err(error) => {
+			let error = ContentApiError::ListChecksums(error);
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response()
+		}
+	}
+}
+
+/// Query parameters for a tree's outline.
+#[derive(Deserialize)]
+pub struct OutlineQuery {
+	/// The NID of the tree's root block.
+	pub root: String,
+
+	/// The NID of the root's child to continue after, to page through them.
+	pub after: Option<String>,
+
+	/// How many children of each block to outline.
+	pub limit: Option<i64>,
+}
+
+/// An API handler for outlining a tree of blocks that the navigator can
+/// read, without their content, for rendering a sidebar. Blocks are written
+/// depth first as newline-delimited JSON arrays of NID, title, depth, and
+/// child count. A block with more children than were outlined can be
+/// outlined on its own, or, for the root, continued after its last child.
+async fn outline_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Query(query): Query<OutlineQuery>,
+) -> axum::response::Response {
+	let summary = "Failed to outline content.";
+
+	let root_id = match DissociatedNuttyId::new(&query.root) {
+		Ok(root_id) => root_id,
+
+		Err(error) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response();
+		}
+	};
+
+	let after_id = match query.after.as_deref().map(DissociatedNuttyId::new) {
+		None => None,
+		Some(Ok(after_id)) => Some(after_id),
+
+		Some(Err(error)) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response();
+		}
+	};
+
+	let limit = query.limit.unwrap_or(OutlineEntry::DEFAULT_LIMIT);
+
+	if !(1..=OutlineEntry::MAX_LIMIT).contains(&limit) {
+		let error = ContentApiError::InvalidOutlineLimit(limit);
+		let error = Error::from_error(&error).with_summary(summary);
+		let errors = vec![error];
+
+		return (
+			StatusCode::BAD_REQUEST,
+			Json(Response::<()>::Error { errors }),
+		)
+			.into_response();
+	}
+
+	// Check if the navigator has access to the tree's root.
+	match state
+		.content_service
+		.check_content_block_access(navigator.nutty_id(), &root_id)
+		.await
+	{
+		Ok(true) => {}
+
+		Ok(false) => {
+			let error = ContentApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			return (
+				StatusCode::FORBIDDEN,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response();
+		}
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			return (status, Json(Response::<()>::Error { errors })).into_response();
+		}
+	}
+
+	match state
+		.content_service
+		.get_outline(navigator.nutty_id(), &root_id, after_id.as_ref(), limit)
+		.await
+	{
+		Ok(outline) => {
+			let body: String = outline
+				.iter()
+				.map(|entry| entry.to_json_line() + "\n")
+				.collect();
+
+			(
+				StatusCode::OK,
+				[(CONTENT_TYPE, "application/x-ndjson")],
+				body,
+			)
+				.into_response()
+		}
+
+		Err(error) => {
+			let error = ContentApiError::ListOutline(error);
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response()
+		}
+	}
+}
+
+/// Query parameters for pulling changes.
+#[derive(Deserialize)]
+pub struct ChangesQuery {
+	/// The cursor returned by the last pull, if any.
+	pub since: Option<String>,
+}
+
+/// An API handler for pulling the changes to the signed-in navigator's
+/// blocks and links since a cursor, deletions included as tombstones. Clients
+/// keep pulling with the returned cursor while there are more.
+async fn changes_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Query(query): Query<ChangesQuery>,
+) -> (StatusCode, Json<Response<SyncChanges>>) {
+	let summary = "Failed to pull changes.";
+
+	let since = match query.since.as_deref().map(SyncCursor::parse).transpose() {
+		Ok(since) => since,
+
+		Err(error) => {
+			let error = ContentApiError::InvalidSyncCursor(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	match state
+		.content_service
+		.list_changes(navigator.nutty_id(), since)
+		.await
+	{
+		Ok(changes) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(changes),
+			}),
+		),
+
+		Err(error) => {
+			let error = ContentApiError::ListChanges(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Query parameters for streaming content events.
+#[derive(Deserialize)]
+pub struct EventStreamQuery {
+	/// The NID of a block to only stream the events of it and the blocks
+	/// under it, if any.
+	pub root: Option<String>,
+}
+
+/// An API handler for streaming the changes to the signed-in navigator's
+/// blocks and links as server-sent events, the same changes that sync
+/// clients pull. Clients resume where they left off with `Last-Event-ID`;
+/// without it, only changes made from now on are sent.
+async fn event_stream_handler(
+	State(state): State<Arc<AppState>>,
+	Session { session, navigator }: Session,
+	Query(query): Query<EventStreamQuery>,
+	headers: HeaderMap,
+) -> axum::response::Response {
+	let summary = "Failed to stream events.";
+
+	let last_event_id = headers
+		.get("last-event-id")
+		.and_then(|value| value.to_str().ok());
+
+	let cursor = match last_event_id.map(SyncCursor::parse).transpose() {
+		Ok(cursor) => cursor.unwrap_or_else(SyncCursor::now),
+
+		Err(error) => {
+			let error = ContentApiError::InvalidSyncCursor(error);
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response();
+		}
+	};
+
+	let filter = match query.root.as_deref() {
+		None => ContentEventFilter::new(),
+
+		Some(root) => {
+			let root_id = match DissociatedNuttyId::new(root) {
+				Ok(root_id) => root_id,
+
+				Err(error) => {
+					let error = ContentApiError::LookupBlockContext(error);
+					let error = Error::from_error(&error).with_summary(summary);
+					let errors = vec![error];
+
+					return (
+						StatusCode::BAD_REQUEST,
+						Json(Response::<()>::Error { errors }),
+					)
+						.into_response();
+				}
+			};
+
+			match state
+				.content_service
+				.check_content_block_access(navigator.nutty_id(), &root_id)
+				.await
+			{
+				Ok(true) => {}
+
+				Ok(false) => {
+					let error = ContentApiError::AccessDenied;
+					let error = Error::from_error(&error).with_summary(summary);
+					let errors = vec![error];
+
+					return (
+						StatusCode::FORBIDDEN,
+						Json(Response::<()>::Error { errors }),
+					)
+						.into_response();
+				}
+
+				Err(error) => {
+					let status = match error {
+						ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+						_ => StatusCode::INTERNAL_SERVER_ERROR,
+					};
+
+					let error = ContentApiError::AccessControl(error);
+					let error = Error::from_error(&error).with_summary(summary);
+					let errors = vec![error];
+
+					return (status, Json(Response::<()>::Error { errors })).into_response();
+				}
+			}
+
+			let block_ids = match state.content_service.list_block_checksums(&root_id).await {
+				Ok(checksums) => checksums
+					.into_iter()
+					.map(|checksum| checksum.block_id)
+					.collect::<Vec<_>>(),
+
+				Err(error) => {
+					let error = ContentApiError::StreamEvents(error);
+					let error = Error::from_error(&error).with_summary(summary);
+					let errors = vec![error];
+
+					return (
+						StatusCode::INTERNAL_SERVER_ERROR,
+						Json(Response::<()>::Error { errors }),
+					)
+						.into_response();
+				}
+			};
+
+			let Some(root_id) = block_ids
+				.iter()
+				.find(|block_id| block_id.nid() == root_id.nid())
+			else {
+				let error = ContentApiError::StreamEvents(ContentServiceError::ContentBlockNotFound);
+				let error = Error::from_error(&error).with_summary(summary);
+				let errors = vec![error];
+
+				return (
+					StatusCode::NOT_FOUND,
+					Json(Response::<()>::Error { errors }),
+				)
+					.into_response();
+			};
+
+			ContentEventFilter::subtree(*root_id, block_ids.iter().copied())
+		}
+	};
+
+	let Some(slot) = state.event_streams.open(navigator.nutty_id()) else {
+		let error = ContentApiError::TooManyEventStreams;
+		let error = Error::from_error(&error).with_summary(summary);
+		let errors = vec![error];
+
+		return (
+			StatusCode::TOO_MANY_REQUESTS,
+			Json(Response::<()>::Error { errors }),
+		)
+			.into_response();
+	};
+
+	let stream = ContentEventStream::new(
+		state.content_service.clone(),
+		state.navigator_service.clone(),
+		*session.nutty_id(),
+		*navigator.nutty_id(),
+		filter,
+		cursor,
+		slot,
+	);
+
+	Sse::new(stream.into_stream())
+		.keep_alive(KeepAlive::new().interval(HEARTBEAT_INTERVAL))
+		.into_response()
+}
+
+/// The content block that a permalink leads to.
+#[derive(Serialize, Deserialize)]
+pub struct Permalink {
+	pub block_id: NuttyId,
+}
+
+/// An API handler for resolving a permalink path, either a NID or a slug,
+/// to a content block. Slugs that were replaced redirect to the block's
+/// current one.
+async fn permalink_handler(
+	State(state): State<Arc<AppState>>,
+	Path(path): Path<String>,
+) -> axum::response::Response {
+	let summary = "Failed to resolve permalink.";
+
+	match state.content_service.resolve_permalink(&path).await {
+		Ok(Some(SlugTarget {
+			moved_to: Some(current),
+			..
+		})) => (
+			StatusCode::MOVED_PERMANENTLY,
+			[(LOCATION, format!("/permalink/{current}"))],
+		)
+			.into_response(),
+
+		Ok(Some(SlugTarget { block_id, .. })) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(Permalink { block_id }),
+			}),
+		)
+			.into_response(),
+
+		Ok(None) => {
+			let error = ContentApiError::BlockNotFound;
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			(
+				StatusCode::NOT_FOUND,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response()
+		}
+
+		Err(error) => {
+			let error = ContentApiError::ResolvePermalink(error);
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response()
+		}
+	}
+}
+
+/// How owned blocks are grouped.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockGrouping {
+	/// By top-level ancestor.
+	#[default]
+	Root,
+}
+
+/// Query parameters for listing owned blocks.
+#[derive(Deserialize)]
+pub struct OwnedBlocksQuery {
+	#[serde(default)]
+	group_by: BlockGrouping,
+}
+
+/// An API handler for listing the blocks that the signed-in navigator owns,
+/// grouped by top-level ancestor.
+async fn owned_blocks_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Query(query): Query<OwnedBlocksQuery>,
+) -> (StatusCode, Json<Response<RootBlockGroup>>) {
+	let groups = match query.group_by {
+		BlockGrouping::Root => {
+			state
+				.content_service
+				.list_owned_blocks_by_root(navigator.nutty_id())
+				.await
+		}
+	};
+
+	match groups {
+		Ok(groups) => (StatusCode::OK, Json(Response::Multiple { data: groups })),
+
+		Err(error) => {
+			let summary = "Failed to list owned blocks.";
+			let error = ContentApiError::ListOwnedBlocks(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for getting the signed-in navigator's site settings.
+async fn get_site_settings_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+) -> (StatusCode, Json<Response<SiteSettings>>) {
+	let summary = "Failed to get site settings.";
+
+	match state
+		.content_service
+		.get_site_settings(navigator.nutty_id())
+		.await
+	{
+		Ok(Some(settings)) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(settings),
+			}),
+		),
+
+		Ok(None) => {
+			let error = ContentApiError::SiteSettingsNotFound;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::NOT_FOUND,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+
+		Err(error) => {
+			let error = ContentApiError::SiteSettings(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Request payload for saving site settings.
+#[derive(Serialize, Deserialize)]
+pub struct SiteSettingsRequest {
+	title: String,
+
+	#[serde(default)]
+	description: String,
+
+	/// A hex color, like "#ff8800".
+	#[serde(default)]
+	accent_color: Option<String>,
+
+	/// The pages linked from every page of the site, by NID or slug.
+	#[serde(default)]
+	navigation_roots: Vec<String>,
+}
+
+/// An API handler for saving the signed-in navigator's site settings. The
+/// navigator must be able to view each page the navigation links to.
+async fn save_site_settings_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Json(payload): Json<SiteSettingsRequest>,
+) -> (StatusCode, Json<Response<SiteSettings>>) {
+	let summary = "Failed to save site settings.";
+
+	let accent_color = match payload.accent_color.as_deref().map(AccentColor::parse) {
+		None => None,
+		Some(Ok(color)) => Some(color),
+
+		Some(Err(error)) => {
+			let error = ContentApiError::InvalidSiteSettings(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	let mut navigation_root_ids = vec![];
+
+	for path in &payload.navigation_roots {
+		let root_id = match state.content_service.resolve_permalink(path).await {
+			Ok(Some(target)) => target.block_id,
+
+			Ok(None) => {
+				let error = ContentApiError::BlockNotFound;
+				let error = Error::from_error(&error).with_summary(summary);
+
+				return (
+					StatusCode::NOT_FOUND,
+					Json(Response::Error {
+						errors: vec![error],
+					}),
+				);
+			}
+
+			Err(error) => {
+				let error = ContentApiError::SiteSettings(error);
+				let error = Error::from_error(&error).with_summary(summary);
+
+				return (
+					StatusCode::INTERNAL_SERVER_ERROR,
+					Json(Response::Error {
+						errors: vec![error],
+					}),
+				);
+			}
+		};
+
+		match state
+			.content_service
+			.check_content_block_access(navigator.nutty_id(), &root_id.dissociate())
+			.await
+		{
+			Ok(true) => navigation_root_ids.push(root_id),
+
+			Ok(false) => {
+				let error = ContentApiError::AccessDenied;
+				let error = Error::from_error(&error).with_summary(summary);
+
+				return (
+					StatusCode::FORBIDDEN,
+					Json(Response::Error {
+						errors: vec![error],
+					}),
+				);
+			}
+
+			Err(error) => {
+				let error = ContentApiError::AccessControl(error);
+				let error = Error::from_error(&error).with_summary(summary);
+
+				return (
+					StatusCode::INTERNAL_SERVER_ERROR,
+					Json(Response::Error {
+						errors: vec![error],
+					}),
+				);
+			}
+		}
+	}
+
+	let settings = SiteSettings {
+		title: payload.title,
+		description: payload.description,
+		accent_color,
+		navigation_root_ids,
+	};
+
+	match state
+		.content_service
+		.save_site_settings(navigator.nutty_id(), settings)
+		.await
+	{
+		Ok(settings) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(settings),
+			}),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::InvalidSiteSettings(_) => StatusCode::BAD_REQUEST,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::SiteSettings(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for deleting the signed-in navigator's site settings.
+async fn delete_site_settings_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+) -> (StatusCode, Json<Response<()>>) {
+	let summary = "Failed to delete site settings.";
+
+	match state
+		.content_service
+		.delete_site_settings(navigator.nutty_id())
+		.await
+	{
+		Ok(true) => (StatusCode::OK, Json(Response::Single { data: None })),
+
+		Ok(false) => {
+			let error = ContentApiError::SiteSettingsNotFound;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::NOT_FOUND,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+
+		Err(error) => {
+			let error = ContentApiError::SiteSettings(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Request payload for defining a custom property.
+#[derive(Serialize, Deserialize)]
+pub struct CreatePropertyRequest {
+	name: String,
+	kind: PropertyKind,
+
+	#[serde(default)]
+	options: Vec<String>,
+}
+
+/// An API handler for defining a custom property.
+async fn create_property_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Json(payload): Json<CreatePropertyRequest>,
+) -> (StatusCode, Json<Response<PropertyDefinition>>) {
+	let summary = "Failed to define property.";
+
+	let has_access = state
+		.access_service
+		.can_permission(navigator.nutty_id(), "content_properties:write")
+		.await;
+
+	match has_access {
+		Ok(true) => (),
+
+		Ok(false) => {
+			let error = ContentApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary("Access denied.");
+
+			return (
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+
+		Err(error) => {
+			let error = ContentApiError::AccessControl(ContentServiceError::AccessControl(error));
+			let error = Error::from_error(&error).with_summary("Failed to check access permissions.");
+
+			return (
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	}
+
+	let definition = match PropertyDefinition::new(&payload.name, payload.kind, payload.options) {
+		Ok(definition) => definition,
+
+		Err(error) => {
+			let error = ContentApiError::InvalidProperty(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	match state
+		.content_service
+		.create_property_definition(definition)
+		.await
+	{
+		Ok(definition) => (
+			StatusCode::CREATED,
+			Json(Response::Single {
+				data: Some(definition),
+			}),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::SavePropertyDefinition(
+					ContentRepositoryError::PropertyNameTaken,
+				) => StatusCode::CONFLICT,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::CreateProperty(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for upserting a [ContentBlock].
+async fn content_block_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+	Json(payload): Json<ContentBlock>,
+) -> (StatusCode, Json<Response<ContentBlock>>) {
+	// Parse the block ID.
+	let block_id = match DissociatedNuttyId::new(&block_id) {
+		Ok(id) => id,
+		Err(error) => {
+			let summary = "Failed to save content block.";
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	// Verify the block ID matches the payload.
+	if block_id.nid() != payload.nutty_id().nid() {
+		let error = ContentApiError::BlockIdMismatch(
+			"The block ID in the URL does not match the payload".to_string(),
+		);
+
+		let summary = "Failed to save content block.";
+		let error = Error::from_error(&error).with_summary(summary);
+
+		return (
+			StatusCode::BAD_REQUEST,
+			Json(Response::Error {
+				errors: vec![error],
+			}),
+		);
+	}
+
+	// Check if the navigator has write access to this content block.
+	let has_access = state
+		.content_service
+		.check_content_block_write_access(navigator.nutty_id(), &block_id)
+		.await;
+
+	match has_access {
+		Ok(true) => {
+			// User has write access to this content block.
+			// We can proceed with saving the block.
+			match state
+				.content_service
+				.save_content_block(Some(navigator.nutty_id()), payload)
+				.await
+			{
+				Ok(content_block) => {
+					annotator::spawn(
+						state.content_service.clone(),
+						state.link_checker.clone(),
+						content_block.clone(),
+					);
+
+					(
+						StatusCode::OK,
+						Json(Response::Single {
+							data: Some(content_block),
+						}),
+					)
+				}
+
+				Err(error) => {
+					let status = match error {
+						ContentServiceError::ParseBlockQuery(_) => StatusCode::BAD_REQUEST,
+						ContentServiceError::InvalidProperties(_) => StatusCode::BAD_REQUEST,
+						ContentServiceError::InvalidContent(_) => StatusCode::BAD_REQUEST,
+						_ => StatusCode::INTERNAL_SERVER_ERROR,
+					};
+
+					let summary = "Failed to save content block.";
+					let error = ContentApiError::QueryBlockContext(error);
+					let error = Error::from_error(&error).with_summary(summary);
+
+					(
+						status,
+						Json(Response::Error {
+							errors: vec![error],
+						}),
+					)
+				}
+			}
+		}
+
+		Ok(false) => {
+			// User does not have write access to this content block.
+			let summary = "Access denied.";
+			let error = ContentApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+
+		Err(error) => {
+			// Error occurred while checking access.
+			let summary = "Failed to check access permissions.";
+			let error = ContentApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Query parameters for deleting a content block.
+#[derive(Deserialize)]
+pub struct DeleteQuery {
+	/// Whether to convert tags referencing the deleted blocks to plain text.
+	#[serde(default)]
+	unlink: bool,
+}
+
+/// An API handler for deleting a [ContentBlock] and its descendants.
+/// The response counts the links left dangling (or unlinked) by the delete.
+async fn delete_content_block_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+	Query(query): Query<DeleteQuery>,
+) -> (StatusCode, Json<Response<BlockDeletion>>) {
+	let summary = "Failed to delete content block.";
+
+	let block_id = match DissociatedNuttyId::new(&block_id) {
+		Ok(id) => id,
+
+		Err(error) => {
+			let error = ContentApiError::LookupBlockContext(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	// Check if the navigator has write access to this content block.
+	let has_access = state
+		.content_service
+		.check_content_block_write_access(navigator.nutty_id(), &block_id)
+		.await;
+
+	match has_access {
+		Ok(true) => {}
+
+		Ok(false) => {
+			let error = ContentApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary("Access denied.");
+
+			return (
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	}
+
+	match state
+		.content_service
+		.delete_content_block(&block_id, query.unlink)
+		.await
+	{
+		Ok(deletion) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(deletion),
+			}),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = ContentApiError::DeleteContentBlock(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ContentApiError {
+	#[error("Unable to look up block context: {0}")]
+	LookupBlockContext(#[from] NuttyIdError),
+
+	#[error("Unable to query block context: {0}")]
+	QueryBlockContext(#[from] ContentServiceError),
+
+	#[error("Unable to query content calendar: {0}")]
+	QueryCalendar(ContentServiceError),
+
+	#[error("Unable to diff content: {0}")]
+	Diff(ContentServiceError),
+
+	#[error("Diff either against another block or between revisions, not both.")]
+	AmbiguousDiff,
+
+	#[error("Unable to merge content blocks: {0}")]
+	Merge(ContentServiceError),
+
+	#[error("Conflicting changes to {:?}: {:?} and {:?}", .0.base, .0.source, .0.target)]
+	MergeConflict(MergeConflict),
+
+	#[error("A three-way merge needs a base revision.")]
+	MissingBaseRevision,
+
+	#[error("Unable to convert content block: {0}")]
+	Convert(ContentServiceError),
+
+	#[error("Unable to edit paragraph: {0}")]
+	EditParagraph(ContentServiceError),
+
+	#[error("Unable to reorder children: {0}")]
+	ReorderChildren(ContentServiceError),
+
+	#[error("Unable to paste: {0}")]
+	Paste(ContentServiceError),
+
+	#[error("Calendar feeds are disabled.")]
+	FeedsDisabled,
+
+	#[error("Missing or invalid calendar feed token.")]
+	InvalidFeedToken,
+
+	#[error("Unable to access calendar feed token: {0}")]
+	FeedToken(NavigatorServiceError),
+
+	#[error("Invalid content filter: {0}")]
+	InvalidContentFilter(ContentFilterError),
+
+	#[error("Unable to list content: {0}")]
+	ListContent(ContentServiceError),
+
+	#[error("Missing or invalid preview image signature.")]
+	InvalidOgImageSignature,
+
+	#[error("Unable to render preview image: {0}")]
+	RenderOgImage(OgImageError),
+
+	#[error("Unable to delete content block: {0}")]
+	DeleteContentBlock(ContentServiceError),
+
+	#[error("Unable to transfer ownership: {0}")]
+	TransferOwnership(ContentServiceError),
+
+	#[error("Unable to list access grants: {0}")]
+	ListGrants(ContentServiceError),
+
+	#[error("Unable to share content block: {0}")]
+	ShareContentBlock(ContentServiceError),
+
+	#[error("Unable to look up navigator: {0}")]
+	LookupRecipient(NavigatorServiceError),
+
+	#[error("Navigator not found.")]
+	RecipientNotFound,
+
+	#[error("Unable to request access: {0}")]
+	RequestAccess(ContentServiceError),
+
+	#[error("Unable to list access requests: {0}")]
+	ListAccessRequests(ContentServiceError),
+
+	#[error("Unable to resolve access request: {0}")]
+	ResolveAccessRequest(ContentServiceError),
+
+	#[error("Unable to find and replace: {0}")]
+	FindReplace(ContentServiceError),
+
+	#[error("Unable to search content: {0}")]
+	Search(ContentServiceError),
+
+	#[error("Unable to set search language: {0}")]
+	SetLanguage(ContentServiceError),
+
+	#[error("Unable to archive content block: {0}")]
+	SetArchived(ContentServiceError),
+
+	#[error("Invalid language: {0}")]
+	InvalidLang(TranslationError),
+
+	#[error("Unable to manage translations: {0}")]
+	Translation(ContentServiceError),
+
+	#[error("Unable to publish revision: {0}")]
+	PublishRevision(ContentServiceError),
+
+	#[error("Invalid slug: {0}")]
+	InvalidSlug(SlugError),
+
+	#[error("Unable to set slug: {0}")]
+	SetSlug(ContentServiceError),
+
+	#[error("Unable to list checksums: {0}")]
+	ListChecksums(ContentServiceError),
+
+	#[error("Outline limit must be between 1 and {max}: {0}", max = OutlineEntry::MAX_LIMIT)]
+	InvalidOutlineLimit(i64),
+
+	#[error("Unable to outline content: {0}")]
+	ListOutline(ContentServiceError),
+
+	#[error("Invalid sync cursor: {0}")]
+	InvalidSyncCursor(SyncCursorError),
+
+	#[error("Unable to list changes: {0}")]
+	ListChanges(ContentServiceError),
+
+	#[error("Unable to stream events: {0}")]
+	StreamEvents(ContentServiceError),
+
+	#[error("Too many event streams are open, at most {MAX_STREAMS_PER_NAVIGATOR} are allowed")]
+	TooManyEventStreams,
+
+	#[error("Unable to access raw content: {0}")]
+	RawContent(ContentServiceError),
+
+	#[error("Unable to resolve permalink: {0}")]
+	ResolvePermalink(ContentServiceError),
+
+	#[error("Unable to list owned blocks: {0}")]
+	ListOwnedBlocks(ContentServiceError),
+
+	#[error("Unable to manage site settings: {0}")]
+	SiteSettings(ContentServiceError),
+
+	#[error("Invalid site settings: {0}")]
+	InvalidSiteSettings(SiteSettingsError),
+
+	#[error("Site settings not found.")]
+	SiteSettingsNotFound,
+
+	#[error("Unable to list tasks: {0}")]
+	ListTasks(ContentServiceError),
+
+	#[error("Unable to toggle task: {0}")]
+	ToggleTask(ContentServiceError),
+
+	#[error("Unable to list properties: {0}")]
+	ListProperties(ContentServiceError),
+
+	#[error("Unable to define property: {0}")]
+	CreateProperty(ContentServiceError),
+
+	#[error("Invalid property definition: {0}")]
+	InvalidProperty(PropertyError),
+
+	#[error("Invalid children view: {0}")]
+	InvalidChildrenView(ChildrenViewError),
+
+	#[error("Invalid field selection: {0}")]
+	InvalidFieldSelection(FieldSelectionError),
+
+	#[error("Invalid context parts: {0}")]
+	InvalidContextInclude(ContextIncludeError),
+
+	#[error("Invalid time to read as of: {0}")]
+	InvalidAsOf(chrono::ParseError),
+
+	#[error("Unable to serialize block context: {0}")]
+	SerializeBlockContext(serde_json::Error),
+
+	#[error("Block ID mismatch: {0}")]
+	BlockIdMismatch(String),
+
+	#[error("Content block not found.")]
+	BlockNotFound,
+
+	#[error("Access denied.")]
+	AccessDenied,
+
+	#[error("Failed to check access permissions: {0}")]
+	AccessControl(ContentServiceError),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::models::BlockContent;
+	use crate::models::FractionalIndex;
+	use crate::navigator::service::NavigatorServiceApi;
+	use crate::testkit;
+	use crate::testkit::FakeAccessService;
+	use crate::testkit::FakeContentService;
+	use crate::testkit::FakeNavigatorService;
+
+	/// Register and log in a navigator against the fakes.
+	async fn log_in(navigator_service: &FakeNavigatorService, name: &str) -> Session {
+		navigator_service
+			.register(name.to_string(), "password123".to_string())
+			.await
+			.unwrap();
+
+		let (navigator, session) = navigator_service
+			.login(
+				name.to_string(),
+				"password123".to_string(),
+				"test-agent".to_string(),
+				None,
+			)
+			.await
+			.unwrap();
+
+		Session { session, navigator }
+	}
+
+	/// Build an [AppState] whose content service holds the given blocks.
+	fn app_state(
+		access_service: FakeAccessService,
+		navigator_service: Arc<FakeNavigatorService>,
+		blocks: &[&ContentBlock],
+	) -> Arc<AppState> {
+		let access_service = Arc::new(access_service);
+		let mut content_service = FakeContentService::new(access_service.clone());
+
+		for block in blocks {
+			content_service = content_service.with_block((*block).clone());
+		}
+
+		testkit::app_state(access_service, Arc::new(content_service), navigator_service)
+	}
+
+	fn page(parent_id: Option<NuttyId>, title: &str) -> ContentBlock {
+		ContentBlock::now(
+			parent_id,
+			FractionalIndex::start(),
+			BlockContent::Page {
+				title: title.to_string(),
+			},
+		)
+	}
+
+	#[tokio::test]
+	async fn test_content_block_handler_requires_write_access() {
+		// Arrange: Seed a page that the navigator doesn't own.
+		let navigator_service = Arc::new(FakeNavigatorService::new());
+		let session = log_in(&navigator_service, "alice").await;
+		let block = page(None, "Parent");
+
+		let mut renamed = block.clone();
+		renamed.content = BlockContent::Page {
+			title: "Renamed".to_string(),
+		};
+
+		let state = app_state(
+			FakeAccessService::new(),
+			navigator_service.clone(),
+			&[&block],
+		);
+
+		// Act: Save the page without write access.
+		let (status, _) = content_block_handler(
+			State(state),
+			session.clone(),
+			Path(block.nutty_id().nid()),
+			Json(renamed.clone()),
+		)
+		.await;
+
+		// Assert: The request was denied.
+		assert_eq!(status, StatusCode::FORBIDDEN);
+
+		// Arrange: Grant write access to all blocks.
+		let access_service =
+			FakeAccessService::new().allow(session.navigator.nutty_id(), "content_blocks:write:all");
+		let state = app_state(access_service, navigator_service, &[&block]);
+
+		// Act: Save the page again.
+		let (status, Json(response)) = content_block_handler(
+			State(state),
+			session,
+			Path(block.nutty_id().nid()),
+			Json(renamed),
+		)
+		.await;
+
+		// Assert: The page was saved.
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(
+			response.extract_object().unwrap().content,
+			BlockContent::Page {
+				title: "Renamed".to_string()
+			}
+		);
+	}
+
+	#[tokio::test]
+	async fn test_content_block_handler_rejects_mismatched_block_id() {
+		// Arrange: Grant write access to all blocks.
+		let navigator_service = Arc::new(FakeNavigatorService::new());
+		let session = log_in(&navigator_service, "alice").await;
+
+		let access_service =
+			FakeAccessService::new().allow(session.navigator.nutty_id(), "content_blocks:write:all");
+		let state = app_state(access_service, navigator_service, &[]);
+
+		// Act: Save a block under another block's ID.
+		let (status, _) = content_block_handler(
+			State(state),
+			session,
+			Path(NuttyId::now().nid()),
+			Json(page(None, "Parent")),
+		)
+		.await;
+
+		// Assert: The request was rejected.
+		assert_eq!(status, StatusCode::BAD_REQUEST);
+	}
+
+	#[tokio::test]
+	async fn test_set_slug_handler() {
+		// Arrange: Seed a page that the navigator doesn't own.
+		let navigator_service = Arc::new(FakeNavigatorService::new());
+		let session = log_in(&navigator_service, "alice").await;
+		let block = page(None, "Parent");
+
+		let state = app_state(
+			FakeAccessService::new(),
+			navigator_service.clone(),
+			&[&block],
+		);
+
+		let set_slug = |state: &Arc<AppState>, slug: &str| {
+			set_slug_handler(
+				State(state.clone()),
+				session.clone(),
+				Path(block.nutty_id().nid()),
+				Json(SetSlugRequest {
+					slug: slug.to_string(),
+				}),
+			)
+		};
+
+		// Act: Set an invalid slug, then a valid one, without write access.
+		let (invalid_status, _) = set_slug(&state, "Not a slug").await;
+		let (denied_status, _) = set_slug(&state, "reading-list").await;
+
+		// Assert: The invalid slug was rejected before checking access.
+		assert_eq!(invalid_status, StatusCode::BAD_REQUEST);
+		assert_eq!(denied_status, StatusCode::FORBIDDEN);
+
+		// Arrange: Grant write access to all blocks.
+		let access_service =
+			FakeAccessService::new().allow(session.navigator.nutty_id(), "content_blocks:write:all");
+		let state = app_state(access_service, navigator_service.clone(), &[&block]);
+
+		// Act: Set the valid slug again.
+		let (status, Json(response)) = set_slug(&state, "reading-list").await;
+
+		// Assert: The slug now points at the page.
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(
+			response.extract_object().unwrap().block_id,
+			*block.nutty_id()
+		);
+	}
+
+	#[tokio::test]
+	async fn test_paste_handler() {
+		// Arrange: Seed a page that the navigator owns and may edit.
+		let navigator_service = Arc::new(FakeNavigatorService::new());
+		let session = log_in(&navigator_service, "alice").await;
+		let navigator_id = *session.navigator.nutty_id();
+
+		let block = ContentBlock::now_with_owner(
+			None,
+			navigator_id,
+			FractionalIndex::start(),
+			BlockContent::Page {
+				title: "Notes".to_string(),
+			},
+		);
+
+		let access_service =
+			FakeAccessService::new().allow(&navigator_id, "content_blocks:write:own");
+		let state = app_state(access_service, navigator_service, &[&block]);
+
+		let paste = |html: &str| {
+			paste_handler(
+				State(state.clone()),
+				session.clone(),
+				Path(block.nutty_id().nid()),
+				Json(PasteRequest {
+					html: html.to_string(),
+				}),
+			)
+		};
+
+		// Act: Paste nothing but whitespace, then two paragraphs.
+		let (empty_status, _) = paste("<p> </p>").await;
+		let (status, Json(response)) = paste("<p>Buy eggs</p><p>Buy milk</p>").await;
+
+		// Assert: Only the paragraphs were pasted, under the page.
+		assert_eq!(empty_status, StatusCode::BAD_REQUEST);
+		assert_eq!(status, StatusCode::CREATED);
+
+		let pasted = response.extract_objects();
+		assert_eq!(pasted.len(), 2);
+		assert!(
+			pasted
+				.iter()
+				.all(|pasted| pasted.parent_id == Some(*block.nutty_id()))
+		);
+	}
+
+	#[tokio::test]
+	async fn test_event_stream_handler_caps_streams() {
+		// Arrange: Sign in a navigator.
+		let navigator_service = Arc::new(FakeNavigatorService::new());
+		let session = log_in(&navigator_service, "alice").await;
+		let state = app_state(FakeAccessService::new(), navigator_service, &[]);
+
+		let open = || {
+			event_stream_handler(
+				State(state.clone()),
+				session.clone(),
+				Query(EventStreamQuery { root: None }),
+				HeaderMap::new(),
+			)
+		};
+
+		// Act: Open as many streams as allowed, then one more.
+		let mut streams = vec![];
+
+		for _ in 0..MAX_STREAMS_PER_NAVIGATOR {
+			streams.push(open().await);
+		}
+
+		let refused = open().await;
+
+		// Assert: Only the extra stream was refused.
+		assert!(
+			streams
+				.iter()
+				.all(|stream| stream.status() == StatusCode::OK)
+		);
+		assert_eq!(refused.status(), StatusCode::TOO_MANY_REQUESTS);
+
+		// Act: Close a stream, and open another.
+		streams.pop();
+		let reopened = open().await;
+
+		// Assert: The closed stream's slot was released.
+		assert_eq!(reopened.status(), StatusCode::OK);
+	}
 }
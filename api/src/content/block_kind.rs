@@ -0,0 +1,321 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use crate::content::sanitizer::Sanitizer;
+use crate::models::BlockContent;
+use crate::models::BlockQuery;
+use crate::models::NuttyTag;
+use crate::models::block_query::BlockQueryError;
+
+/// The kinds built into [BlockContent], each backed by one of its variants.
+const CORE_KINDS: [&str; 5] = ["Page", "Heading", "Paragraph", "Query", "Todo"];
+
+/// A kind of content block: how its content is validated, rendered, and
+/// linked to other blocks. Plugins implement this for their own kinds, whose
+/// content arrives as [BlockContent::Custom].
+pub trait BlockKind: Send + Sync {
+	/// The `kind` that content of this kind is tagged with, e.g. "Recipe".
+	fn name(&self) -> &str;
+
+	/// Check content before it's saved.
+	fn validate(&self, _content: &BlockContent) -> Result<(), BlockKindError> {
+		Ok(())
+	}
+
+	/// Render content to markdown.
+	fn render_markdown(&self, content: &BlockContent) -> String;
+
+	/// Render content to HTML, which must be sanitized. By default, the
+	/// markdown is rendered and cleaned.
+	fn render_html(&self, content: &BlockContent, sanitizer: &Sanitizer) -> String {
+		sanitizer.render(&self.render_markdown(content))
+	}
+
+	/// Extract the tags that content links to. By default, they're parsed
+	/// from the markdown.
+	fn extract_tags(&self, content: &BlockContent) -> Vec<NuttyTag> {
+		NuttyTag::parse_all(&self.render_markdown(content))
+	}
+}
+
+/// A built-in kind.
+struct CoreKind(&'static str);
+
+impl BlockKind for CoreKind {
+	fn name(&self) -> &str {
+		self.0
+	}
+
+	fn validate(&self, content: &BlockContent) -> Result<(), BlockKindError> {
+		match content {
+			BlockContent::Query { dsl } => BlockQuery::parse(dsl).map(|_| ()).map_err(Into::into),
+			BlockContent::Custom { kind, .. } => Err(BlockKindError::Malformed(kind.clone())),
+			_ => Ok(()),
+		}
+	}
+
+	fn render_markdown(&self, content: &BlockContent) -> String {
+		match content {
+			BlockContent::Page { title } => title.clone(),
+			BlockContent::Heading { markdown }
+			| BlockContent::Paragraph { markdown }
+			| BlockContent::Todo { markdown, .. } => markdown.clone(),
+			BlockContent::Query { dsl } => dsl.clone(),
+			BlockContent::Custom { .. } => String::new(),
+		}
+	}
+
+	fn render_html(&self, content: &BlockContent, sanitizer: &Sanitizer) -> String {
+		match content {
+			BlockContent::Page { title } => ammonia::clean_text(title),
+			BlockContent::Heading { markdown }
+			| BlockContent::Paragraph { markdown }
+			| BlockContent::Todo { markdown, .. } => sanitizer.render(markdown),
+			BlockContent::Query { .. } | BlockContent::Custom { .. } => String::new(),
+		}
+	}
+
+	fn extract_tags(&self, content: &BlockContent) -> Vec<NuttyTag> {
+		content.parse_target_tags()
+	}
+}
+
+/// The block kinds that content may take, by name. The core kinds are always
+/// registered; downstream crates register their own before serving.
+#[derive(Clone)]
+pub struct BlockKindRegistry {
+	kinds: BTreeMap<String, Arc<dyn BlockKind>>,
+}
+
+impl Default for BlockKindRegistry {
+	fn default() -> Self {
+		let kinds = CORE_KINDS
+			.into_iter()
+			.map(|name| {
+				(
+					name.to_string(),
+					Arc::new(CoreKind(name)) as Arc<dyn BlockKind>,
+				)
+			})
+			.collect();
+
+		Self { kinds }
+	}
+}
+
+impl BlockKindRegistry {
+	/// Register a kind, failing if one with its name already is.
+	pub fn register(&mut self, kind: impl BlockKind + 'static) -> Result<(), BlockKindError> {
+		let name = kind.name().to_string();
+
+		if self.kinds.contains_key(&name) {
+			return Err(BlockKindError::AlreadyRegistered(name));
+		}
+
+		self.kinds.insert(name, Arc::new(kind));
+		Ok(())
+	}
+
+	/// Get a kind by name.
+	pub fn get(&self, name: &str) -> Option<&dyn BlockKind> {
+		self.kinds.get(name).map(Arc::as_ref)
+	}
+
+	/// Get the registered name of a kind, ignoring case, e.g. "Todo" for
+	/// "todo".
+	pub fn resolve(&self, name: &str) -> Option<&str> {
+		self
+			.kinds
+			.keys()
+			.find(|kind| kind.eq_ignore_ascii_case(name))
+			.map(String::as_str)
+	}
+
+	/// Check content with its kind, failing if the kind isn't registered.
+	pub fn validate(&self, content: &BlockContent) -> Result<(), BlockKindError> {
+		self.kind_of(content)?.validate(content)
+	}
+
+	/// Render content to markdown. Content of unregistered kinds is empty.
+	pub fn render_markdown(&self, content: &BlockContent) -> String {
+		self
+			.kind_of(content)
+			.map_or_else(|_| String::new(), |kind| kind.render_markdown(content))
+	}
+
+	/// Render content to sanitized HTML. Content of unregistered kinds is
+	/// empty.
+	pub fn render_html(&self, content: &BlockContent, sanitizer: &Sanitizer) -> String {
+		self.kind_of(content).map_or_else(
+			|_| String::new(),
+			|kind| kind.render_html(content, sanitizer),
+		)
+	}
+
+	/// Extract the tags that content links to. Content of unregistered kinds
+	/// has none.
+	pub fn extract_tags(&self, content: &BlockContent) -> Vec<NuttyTag> {
+		self
+			.kind_of(content)
+			.map_or_else(|_| vec![], |kind| kind.extract_tags(content))
+	}
+
+	fn kind_of(&self, content: &BlockContent) -> Result<&dyn BlockKind, BlockKindError> {
+		self
+			.get(content.kind())
+			.ok_or_else(|| BlockKindError::Unknown(content.kind().to_string()))
+	}
+}
+
+impl fmt::Debug for BlockKindRegistry {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_set().entries(self.kinds.keys()).finish()
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum BlockKindError {
+	#[error("Unknown block kind: {0}")]
+	Unknown(String),
+
+	#[error("Block kind already registered: {0}")]
+	AlreadyRegistered(String),
+
+	#[error("Malformed {0} content")]
+	Malformed(String),
+
+	#[error("Invalid block query: {0}")]
+	InvalidQuery(#[from] BlockQueryError),
+
+	#[error("Invalid {kind} content: {reason}")]
+	Invalid { kind: String, reason: String },
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+
+	/// A plugin kind, e.g. from a downstream crate.
+	struct Recipe;
+
+	impl BlockKind for Recipe {
+		fn name(&self) -> &str {
+			"Recipe"
+		}
+
+		fn validate(&self, content: &BlockContent) -> Result<(), BlockKindError> {
+			match self.render_markdown(content).is_empty() {
+				true => Err(BlockKindError::Invalid {
+					kind: "Recipe".to_string(),
+					reason: "a recipe needs a dish".to_string(),
+				}),
+				false => Ok(()),
+			}
+		}
+
+		fn render_markdown(&self, content: &BlockContent) -> String {
+			let BlockContent::Custom { fields, .. } = content else {
+				return String::new();
+			};
+
+			fields
+				.get("dish")
+				.and_then(|dish| dish.as_str())
+				.unwrap_or_default()
+				.to_string()
+		}
+	}
+
+	fn content(value: serde_json::Value) -> BlockContent {
+		serde_json::from_value(value).unwrap()
+	}
+
+	#[test]
+	fn test_core_kinds() {
+		let registry = BlockKindRegistry::default();
+		let sanitizer = Sanitizer::default();
+
+		// Assert: Page titles render as text.
+		let page = BlockContent::Page {
+			title: "<script>alert(1)</script>".to_string(),
+		};
+
+		assert_eq!(
+			registry.render_html(&page, &sanitizer),
+			"&lt;script&gt;alert(1)&lt;&#47;script&gt;"
+		);
+
+		// Assert: Queries are validated, and malformed core content isn't
+		// mistaken for a plugin's.
+		let query = BlockContent::Query {
+			dsl: "title:foo".to_string(),
+		};
+
+		assert!(matches!(
+			registry.validate(&query),
+			Err(BlockKindError::InvalidQuery(_))
+		));
+
+		let malformed = content(json!({ "kind": "Paragraph", "text": "Hi" }));
+		assert!(matches!(
+			registry.validate(&malformed),
+			Err(BlockKindError::Malformed(_))
+		));
+
+		// Assert: Tags are extracted from markdown.
+		let paragraph = BlockContent::Paragraph {
+			markdown: "See [[abcdefg]].".to_string(),
+		};
+
+		assert_eq!(registry.extract_tags(&paragraph).len(), 1);
+	}
+
+	#[test]
+	fn test_plugin_kinds() {
+		let mut registry = BlockKindRegistry::default();
+		let sanitizer = Sanitizer::default();
+		let recipe = content(json!({ "kind": "Recipe", "dish": "Pho with [[abcdefg]]" }));
+
+		// Assert: Unregistered kinds are rejected.
+		assert!(matches!(
+			registry.validate(&recipe),
+			Err(BlockKindError::Unknown(_))
+		));
+
+		// Assert: Registered kinds validate, render, and link their content.
+		registry.register(Recipe).unwrap();
+		assert!(registry.validate(&recipe).is_ok());
+		assert!(
+			registry
+				.validate(&content(json!({ "kind": "Recipe" })))
+				.is_err()
+		);
+		assert_eq!(
+			registry.render_html(&recipe, &sanitizer),
+			"<p>Pho with [[abcdefg]]</p>\n"
+		);
+		assert_eq!(registry.extract_tags(&recipe).len(), 1);
+
+		// Assert: Kinds resolve to their registered names, ignoring case.
+		assert_eq!(registry.resolve("recipe"), Some("Recipe"));
+		assert_eq!(registry.resolve("TODO"), Some("Todo"));
+		assert_eq!(registry.resolve("Code"), None);
+
+		// Assert: Kinds can't be registered twice.
+		assert!(matches!(
+			registry.register(Recipe),
+			Err(BlockKindError::AlreadyRegistered(_))
+		));
+
+		// Assert: Plugin content round-trips as it was written.
+		assert_eq!(
+			serde_json::to_value(&recipe).unwrap(),
+			json!({ "kind": "Recipe", "dish": "Pho with [[abcdefg]]" })
+		);
+	}
+}
@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use axum::response::sse::Event;
+use futures_util::Stream;
+
+use crate::content::service::ContentServiceApi;
+use crate::models::NuttyId;
+use crate::models::content_event::ContentEventFilter;
+use crate::models::sync_changes::SyncCursor;
+use crate::navigator::service::NavigatorServiceApi;
+
+/// How long a stream waits between checks for new changes.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long a stream can be quiet before a heartbeat is sent, so that
+/// proxies don't close it.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// The most event streams that a navigator can hold open at once, across
+/// their devices and tabs.
+pub const MAX_STREAMS_PER_NAVIGATOR: usize = 4;
+
+/// Counts each navigator's open event streams, to cap them at
+/// [MAX_STREAMS_PER_NAVIGATOR].
+#[derive(Clone, Default)]
+pub struct EventStreams {
+	open: Arc<Mutex<HashMap<NuttyId, usize>>>,
+}
+
+impl EventStreams {
+	/// Reserve a stream for a navigator, unless they already hold as many as
+	/// allowed. The stream is released when the slot is dropped.
+	pub fn open(&self, navigator_id: &NuttyId) -> Option<StreamSlot> {
+		let mut open = self.open.lock().expect("Event stream counts poisoned");
+		let count = open.entry(*navigator_id).or_default();
+
+		if *count >= MAX_STREAMS_PER_NAVIGATOR {
+			return None;
+		}
+
+		*count += 1;
+
+		Some(StreamSlot {
+			open: self.open.clone(),
+			navigator_id: *navigator_id,
+		})
+	}
+}
+
+/// One of a navigator's open event streams, released on drop.
+pub struct StreamSlot {
+	open: Arc<Mutex<HashMap<NuttyId, usize>>>,
+	navigator_id: NuttyId,
+}
+
+impl Drop for StreamSlot {
+	fn drop(&mut self) {
+		let mut open = self.open.lock().expect("Event stream counts poisoned");
+
+		if let Some(count) = open.get_mut(&self.navigator_id) {
+			*count -= 1;
+
+			if *count == 0 {
+				open.remove(&self.navigator_id);
+			}
+		}
+	}
+}
+
+/// Streams a navigator's content changes as server-sent events, for clients
+/// that can't hold a WebSocket. Changes are pulled like sync clients pull
+/// them; after each page, a `checkpoint` event carries the cursor as its ID,
+/// so that a client reconnecting with `Last-Event-ID` resumes from there.
+/// Events after the last checkpoint may be sent again on resuming.
+///
+/// The session that opened the stream is checked again every heartbeat, and
+/// the stream ends once it's signed out or expired.
+pub struct ContentEventStream {
+	content_service: Arc<dyn ContentServiceApi>,
+	navigator_service: Arc<dyn NavigatorServiceApi>,
+	session_id: NuttyId,
+	navigator_id: NuttyId,
+	filter: ContentEventFilter,
+	cursor: SyncCursor,
+
+	/// Counts the stream against the navigator's open streams until dropped.
+	_slot: StreamSlot,
+
+	/// When the session was last checked.
+	session_checked_at: Instant,
+
+	/// Events pulled but not yet sent.
+	pending: VecDeque<Event>,
+
+	/// Whether more changes are waiting after the cursor.
+	has_more: bool,
+
+	/// Whether pulling failed or the session ended, which ends the stream.
+	ended: bool,
+}
+
+impl ContentEventStream {
+	/// Create a stream of a navigator's changes after a cursor, through a
+	/// filter, for as long as their session lasts.
+	pub fn new(
+		content_service: Arc<dyn ContentServiceApi>,
+		navigator_service: Arc<dyn NavigatorServiceApi>,
+		session_id: NuttyId,
+		navigator_id: NuttyId,
+		filter: ContentEventFilter,
+		cursor: SyncCursor,
+		slot: StreamSlot,
+	) -> Self {
+		Self {
+			content_service,
+			navigator_service,
+			session_id,
+			navigator_id,
+			filter,
+			cursor,
+			_slot: slot,
+			session_checked_at: Instant::now(),
+			pending: VecDeque::new(),
+			has_more: false,
+			ended: false,
+		}
+	}
+
+	/// Turn into a stream of events, which ends if pulling changes fails or
+	/// the session ends.
+	pub fn into_stream(self) -> impl Stream<Item = Result<Event, Infallible>> {
+		futures_util::stream::unfold(self, |mut stream| async move {
+			loop {
+				if let Some(event) = stream.pending.pop_front() {
+					return Some((Ok(event), stream));
+				}
+
+				if stream.ended {
+					return None;
+				}
+
+				if stream.session_checked_at.elapsed() >= HEARTBEAT_INTERVAL {
+					stream.check_session().await;
+					continue;
+				}
+
+				if !stream.has_more {
+					tokio::time::sleep(POLL_INTERVAL).await;
+				}
+
+				stream.pull().await;
+			}
+		})
+	}
+
+	/// Check that the session is still signed in, or end the stream.
+	async fn check_session(&mut self) {
+		self.session_checked_at = Instant::now();
+
+		let message = match self
+			.navigator_service
+			.get_session_by_id(&self.session_id)
+			.await
+		{
+			Ok(Some(session)) if !session.is_expired() => return,
+			Ok(_) => "The session has ended.",
+
+			Err(error) => {
+				eprintln!("Failed to check session for event stream: {error}");
+				"Failed to check the session."
+			}
+		};
+
+		self
+			.pending
+			.push_back(Event::default().event("error").data(message));
+
+		self.ended = true;
+	}
+
+	/// Pull the next page of changes into the pending events.
+	async fn pull(&mut self) {
+		let changes = match self
+			.content_service
+			.list_changes(&self.navigator_id, Some(self.cursor))
+			.await
+		{
+			Ok(changes) => changes,
+
+			Err(error) => {
+				eprintln!("Failed to pull changes for event stream: {error}");
+
+				let event = Event::default()
+					.event("error")
+					.data("Failed to pull changes.");
+
+				self.pending.push_back(event);
+				self.ended = true;
+				return;
+			}
+		};
+
+		for event in self.filter.events(&changes) {
+			match Event::default().event(event.name()).json_data(&event) {
+				Ok(sse_event) => self.pending.push_back(sse_event),
+				Err(error) => eprintln!("Failed to encode {} event: {error}", event.name()),
+			}
+		}
+
+		if changes.cursor != self.cursor {
+			let cursor = changes.cursor.to_string();
+
+			self.pending.push_back(
+				Event::default()
+					.event("checkpoint")
+					.id(&cursor)
+					.data(&cursor),
+			);
+		}
+
+		self.cursor = changes.cursor;
+		self.has_more = changes.has_more;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::navigator::service::NavigatorServiceApi;
+	use crate::testkit::FakeAccessService;
+	use crate::testkit::FakeContentService;
+	use crate::testkit::FakeNavigatorService;
+
+	#[tokio::test]
+	async fn test_content_event_stream_ends_with_session() {
+		// Arrange: Sign in a navigator, and open a stream of their changes.
+		let navigator_service = Arc::new(FakeNavigatorService::new());
+
+		navigator_service
+			.register("alice".to_string(), "password123".to_string())
+			.await
+			.unwrap();
+
+		let (navigator, session) = navigator_service
+			.login(
+				"alice".to_string(),
+				"password123".to_string(),
+				"test-agent".to_string(),
+				None,
+			)
+			.await
+			.unwrap();
+
+		let content_service = FakeContentService::new(Arc::new(FakeAccessService::new()));
+		let slot = EventStreams::default().open(navigator.nutty_id()).unwrap();
+
+		let mut stream = ContentEventStream::new(
+			Arc::new(content_service),
+			navigator_service.clone(),
+			*session.nutty_id(),
+			*navigator.nutty_id(),
+			ContentEventFilter::new(),
+			SyncCursor::now(),
+			slot,
+		);
+
+		// Act: Check the session while it's signed in.
+		stream.check_session().await;
+
+		// Assert: The stream goes on.
+		assert!(stream.pending.is_empty());
+		assert!(!stream.ended);
+
+		// Act: Sign out, and check the session again.
+		navigator_service.logout(session.nutty_id()).await.unwrap();
+		stream.check_session().await;
+
+		// Assert: The stream ends after an error event.
+		assert_eq!(stream.pending.len(), 1);
+		assert!(stream.ended);
+	}
+
+	#[test]
+	fn test_event_streams_cap() {
+		// Arrange: Open as many streams as a navigator is allowed.
+		let streams = EventStreams::default();
+		let navigator_id = NuttyId::now();
+
+		let mut slots: Vec<_> = (0..MAX_STREAMS_PER_NAVIGATOR)
+			.map(|_| streams.open(&navigator_id).unwrap())
+			.collect();
+
+		// Act & Assert: Another is refused, unless it's another navigator's.
+		assert!(streams.open(&navigator_id).is_none());
+		assert!(streams.open(&NuttyId::now()).is_some());
+
+		// Act & Assert: Dropping a slot makes room for another stream.
+		slots.pop();
+		assert!(streams.open(&navigator_id).is_some());
+	}
+}
@@ -0,0 +1,854 @@
+use std::cell::RefCell;
+
+use html5ever::tendril::StrTendril;
+use html5ever::tokenizer::BufferQueue;
+use html5ever::tokenizer::Tag;
+use html5ever::tokenizer::TagKind;
+use html5ever::tokenizer::Token;
+use html5ever::tokenizer::TokenSink;
+use html5ever::tokenizer::TokenSinkResult;
+use html5ever::tokenizer::Tokenizer;
+use thiserror::Error;
+
+use crate::models::BlockContent;
+use crate::models::ContentBlock;
+use crate::models::FractionalIndex;
+use crate::models::NuttyId;
+use crate::models::fractional_index::FractionalIndexError;
+
+/// The most blocks that one paste may create.
+pub const MAX_PASTED_BLOCKS: usize = 500;
+
+/// The most list items that a paste may nest. Items nested deeper are
+/// pasted as siblings of the deepest ones.
+pub const MAX_PASTE_DEPTH: usize = 16;
+
+/// Elements whose contents aren't pasted.
+const SKIPPED_TAGS: [&str; 6] = ["head", "noscript", "script", "style", "template", "title"];
+
+/// Elements that end the paragraph before them, and start another.
+const BLOCK_TAGS: [&str; 20] = [
+	"address",
+	"article",
+	"aside",
+	"blockquote",
+	"dd",
+	"div",
+	"dl",
+	"dt",
+	"figcaption",
+	"figure",
+	"footer",
+	"header",
+	"hr",
+	"main",
+	"nav",
+	"p",
+	"section",
+	"table",
+	"tbody",
+	"tr",
+];
+
+/// A block converted from pasted HTML, with the blocks under it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PastedBlock {
+	pub content: BlockContent,
+	pub children: Vec<PastedBlock>,
+}
+
+impl PastedBlock {
+	fn new(content: BlockContent) -> Self {
+		Self {
+			content,
+			children: vec![],
+		}
+	}
+}
+
+/// Convert pasted HTML into blocks. Top-level headings become pages, and
+/// the rest headings, with what follows them nested under them. List items
+/// become paragraphs, or todos if they have a checkbox, nested as their
+/// lists are. Preformatted text becomes a fenced code paragraph, and the
+/// rest of the text paragraphs, with its inline formatting as markdown.
+pub fn html_to_blocks(html: &str) -> Result<Vec<PastedBlock>, HtmlImportError> {
+	let mut converter = Converter::default();
+
+	for token in tokenize(html) {
+		converter.push(token);
+
+		// Stop as soon as the paste makes too many blocks, rather than
+		// convert the rest of it.
+		if converter.count > MAX_PASTED_BLOCKS {
+			return Err(HtmlImportError::TooManyBlocks);
+		}
+	}
+
+	let blocks = converter.finish();
+
+	if converter.count == 0 {
+		return Err(HtmlImportError::NothingToPaste);
+	}
+
+	if converter.count > MAX_PASTED_BLOCKS {
+		return Err(HtmlImportError::TooManyBlocks);
+	}
+
+	Ok(blocks)
+}
+
+/// Make the content blocks of pasted blocks for a navigator to own, under
+/// the given parent and after its last child, if it has any. Parents come
+/// before their children, in order.
+pub fn instantiate(
+	pasted: &[PastedBlock],
+	parent_id: NuttyId,
+	owner_id: NuttyId,
+	last_f_index: Option<&FractionalIndex>,
+) -> Result<Vec<ContentBlock>, FractionalIndexError> {
+	let f_indices = match last_f_index {
+		Some(last_f_index) => {
+			let mut previous = last_f_index.clone();
+			let mut f_indices = Vec::with_capacity(pasted.len());
+
+			for _ in pasted {
+				previous = FractionalIndex::between(&previous, &FractionalIndex::end())?;
+				f_indices.push(previous.clone());
+			}
+
+			f_indices
+		}
+
+		None => FractionalIndex::spread(pasted.len()),
+	};
+
+	let mut blocks = vec![];
+
+	for (block, f_index) in pasted.iter().zip(f_indices) {
+		instantiate_block(block, parent_id, owner_id, f_index, &mut blocks);
+	}
+
+	Ok(blocks)
+}
+
+fn instantiate_block(
+	pasted: &PastedBlock,
+	parent_id: NuttyId,
+	owner_id: NuttyId,
+	f_index: FractionalIndex,
+	blocks: &mut Vec<ContentBlock>,
+) {
+	let block =
+		ContentBlock::now_with_owner(Some(parent_id), owner_id, f_index, pasted.content.clone());
+	let block_id = *block.nutty_id();
+
+	blocks.push(block);
+
+	for (child, f_index) in pasted
+		.children
+		.iter()
+		.zip(FractionalIndex::spread(pasted.children.len()))
+	{
+		instantiate_block(child, block_id, owner_id, f_index, blocks);
+	}
+}
+
+/// Collects the tokens of some HTML.
+struct TokenCollector(RefCell<Vec<Token>>);
+
+impl TokenSink for TokenCollector {
+	type Handle = ();
+
+	fn process_token(&self, token: Token, _line_number: u64) -> TokenSinkResult<()> {
+		self.0.borrow_mut().push(token);
+		TokenSinkResult::Continue
+	}
+}
+
+fn tokenize(html: &str) -> Vec<Token> {
+	let input = BufferQueue::default();
+	input.push_back(StrTendril::from_slice(html));
+
+	let tokenizer = Tokenizer::new(TokenCollector(RefCell::new(vec![])), Default::default());
+	let _ = tokenizer.feed(&input);
+	tokenizer.end();
+
+	tokenizer.sink.0.take()
+}
+
+/// A heading that the blocks after it are nested under, until a heading of
+/// the same level or higher.
+struct OpenSection {
+	level: u8,
+	block: PastedBlock,
+}
+
+/// A list item, which takes its content from its first text, and nests
+/// what comes after it.
+#[derive(Default)]
+struct OpenItem {
+	content: Option<BlockContent>,
+	checked: Option<bool>,
+	children: Vec<PastedBlock>,
+}
+
+/// An inline element whose markdown is closed at its end tag.
+struct OpenInline {
+	name: String,
+
+	/// Where the element's text starts in the markdown.
+	opened_at: usize,
+
+	opening_length: usize,
+	closing: String,
+}
+
+/// Converts a stream of HTML tokens into blocks, collecting the text of
+/// each block as markdown until an element ends it.
+#[derive(Default)]
+struct Converter {
+	roots: Vec<PastedBlock>,
+	sections: Vec<OpenSection>,
+	items: Vec<OpenItem>,
+	inlines: Vec<OpenInline>,
+
+	/// The markdown of the block being collected.
+	markdown: String,
+
+	/// The text of the block being collected, without formatting.
+	text: String,
+
+	/// The level of the heading being collected, if it's one.
+	heading: Option<u8>,
+
+	/// The language of the code being collected, if it's preformatted.
+	code: Option<String>,
+
+	/// How many quotes the text is in.
+	quote_depth: usize,
+
+	/// How many skipped elements the text is in.
+	skip_depth: usize,
+
+	/// How many blocks have been emitted.
+	count: usize,
+}
+
+impl Converter {
+	fn push(&mut self, token: Token) {
+		match token {
+			Token::TagToken(tag) => match tag.kind {
+				TagKind::StartTag => self.start_tag(&tag),
+				TagKind::EndTag => self.end_tag(&tag),
+			},
+
+			Token::CharacterTokens(text) if self.skip_depth == 0 => self.characters(&text),
+
+			_ => {}
+		}
+	}
+
+	fn start_tag(&mut self, tag: &Tag) {
+		let name = &*tag.name;
+
+		if SKIPPED_TAGS.contains(&name) {
+			self.skip_depth += 1;
+			return;
+		}
+
+		if self.skip_depth > 0 {
+			return;
+		}
+
+		if let Some(language) = &mut self.code {
+			match name {
+				"br" => self.markdown.push('\n'),
+				"code" if language.is_empty() => *language = code_language(tag).unwrap_or_default(),
+				_ => {}
+			}
+
+			return;
+		}
+
+		match name {
+			"h1" | "h2" | "h3" | "h4" | "h5" | "h6" if self.items.is_empty() => {
+				self.flush();
+				self.heading = Some(heading_level(name));
+			}
+
+			"li" => {
+				self.flush();
+
+				if self.items.len() >= MAX_PASTE_DEPTH
+					&& let Some(item) = self.items.pop()
+				{
+					self.close_item(item);
+				}
+
+				self.items.push(OpenItem::default());
+			}
+
+			"ul" | "ol" => self.flush(),
+
+			"pre" => {
+				self.flush();
+				self.code = Some(code_language(tag).unwrap_or_default());
+			}
+
+			"blockquote" => {
+				self.flush();
+				self.quote_depth += 1;
+			}
+
+			"input"
+				if attribute(tag, "type").is_some_and(|kind| kind.eq_ignore_ascii_case("checkbox")) =>
+			{
+				if let Some(item) = self.items.last_mut() {
+					item.checked = Some(attribute(tag, "checked").is_some());
+				}
+			}
+
+			"br" => self.markdown.push('\n'),
+
+			"td" | "th" if !self.text.trim().is_empty() => self.append(" | ", " | "),
+
+			"img" => {
+				let alt = attribute(tag, "alt").unwrap_or_default();
+
+				if let Some(src) = attribute(tag, "src") {
+					self
+						.markdown
+						.push_str(&format!("![{}](<{}>)", escape(alt), src));
+					self.text.push_str(alt);
+				}
+			}
+
+			"b" | "strong" => self.open_inline(name, "**", "**"),
+			"i" | "em" => self.open_inline(name, "_", "_"),
+			"s" | "del" | "strike" => self.open_inline(name, "~~", "~~"),
+			"code" => self.open_inline(name, "`", "`"),
+
+			"a" => {
+				if let Some(href) = attribute(tag, "href") {
+					self.open_inline(name, "[", &format!("](<{href}>)"));
+				}
+			}
+
+			_ if BLOCK_TAGS.contains(&name) => self.flush(),
+
+			_ => {}
+		}
+	}
+
+	fn end_tag(&mut self, tag: &Tag) {
+		let name = &*tag.name;
+
+		if SKIPPED_TAGS.contains(&name) {
+			self.skip_depth = self.skip_depth.saturating_sub(1);
+			return;
+		}
+
+		if self.skip_depth > 0 {
+			return;
+		}
+
+		match name {
+			"pre" => self.close_code(),
+
+			_ if self.code.is_some() => {}
+
+			"h1" | "h2" | "h3" | "h4" | "h5" | "h6" if self.heading.is_some() => self.flush(),
+
+			"li" => {
+				self.flush();
+
+				if let Some(item) = self.items.pop() {
+					self.close_item(item);
+				}
+			}
+
+			"blockquote" => {
+				self.flush();
+				self.quote_depth = self.quote_depth.saturating_sub(1);
+			}
+
+			"b" | "strong" | "i" | "em" | "s" | "del" | "strike" | "code" | "a" => {
+				self.close_inline(name)
+			}
+
+			_ if BLOCK_TAGS.contains(&name) => self.flush(),
+
+			_ => {}
+		}
+	}
+
+	fn characters(&mut self, text: &str) {
+		if self.code.is_some() {
+			self.append(text, text);
+			return;
+		}
+
+		let mut collapsed = String::new();
+		let mut was_space = self.markdown.is_empty() || self.markdown.ends_with([' ', '\n']);
+
+		for c in text.chars() {
+			if c.is_whitespace() {
+				if !was_space {
+					collapsed.push(' ');
+				}
+
+				was_space = true;
+			} else {
+				collapsed.push(c);
+				was_space = false;
+			}
+		}
+
+		// Markdown formatting can't start with a space, so it goes before.
+		if let Some(rest) = collapsed.strip_prefix(' ')
+			&& let Some(first) = self
+				.inlines
+				.iter()
+				.position(|inline| inline.opened_at == self.markdown.len())
+		{
+			let position = self.inlines[first].opened_at - self.inlines[first].opening_length;
+			self.markdown.insert(position, ' ');
+
+			for inline in &mut self.inlines[first..] {
+				inline.opened_at += 1;
+			}
+
+			collapsed = rest.to_string();
+			self.text.push(' ');
+		}
+
+		self.append(&escape(&collapsed), &collapsed);
+	}
+
+	fn append(&mut self, markdown: &str, text: &str) {
+		self.markdown.push_str(markdown);
+		self.text.push_str(text);
+	}
+
+	fn open_inline(&mut self, name: &str, opening: &str, closing: &str) {
+		self.markdown.push_str(opening);
+
+		self.inlines.push(OpenInline {
+			name: name.to_string(),
+			opened_at: self.markdown.len(),
+			opening_length: opening.len(),
+			closing: closing.to_string(),
+		});
+	}
+
+	fn close_inline(&mut self, name: &str) {
+		let Some(position) = self.inlines.iter().rposition(|inline| inline.name == name) else {
+			return;
+		};
+
+		// Elements left open inside it are closed along with it.
+		let closed: Vec<_> = self.inlines.drain(position..).collect();
+
+		for inline in closed.into_iter().rev() {
+			// Empty elements are dropped, rather than leave stray markers.
+			if self.markdown.len() == inline.opened_at {
+				self
+					.markdown
+					.truncate(inline.opened_at - inline.opening_length);
+				continue;
+			}
+
+			// Markdown formatting can't end with a space, either.
+			let trailing_space = self.markdown.ends_with(' ');
+
+			if trailing_space {
+				self.markdown.pop();
+			}
+
+			self.markdown.push_str(&inline.closing);
+
+			if trailing_space {
+				self.markdown.push(' ');
+			}
+		}
+	}
+
+	/// End the preformatted block being collected, as fenced code.
+	fn close_code(&mut self) {
+		let Some(language) = self.code.take() else {
+			return;
+		};
+
+		let code = std::mem::take(&mut self.markdown);
+		let code = code.trim_matches('\n');
+		self.text.clear();
+
+		if !code.trim().is_empty() {
+			self.emit(PastedBlock::new(BlockContent::Paragraph {
+				markdown: format!("```{language}\n{code}\n```"),
+			}));
+		}
+	}
+
+	/// End the block being collected, if it has any text.
+	fn flush(&mut self) {
+		let heading = self.heading.take();
+		let markdown = std::mem::take(&mut self.markdown);
+		let text = std::mem::take(&mut self.text);
+		self.inlines.clear();
+
+		let markdown = markdown
+			.lines()
+			.map(str::trim)
+			.filter(|line| !line.is_empty())
+			.collect::<Vec<_>>()
+			.join("\n");
+
+		if markdown.is_empty() {
+			return;
+		}
+
+		if let Some(level) = heading {
+			self.close_sections(level);
+
+			let content = match level {
+				1 => BlockContent::Page {
+					title: text.split_whitespace().collect::<Vec<_>>().join(" "),
+				},
+				_ => BlockContent::Heading { markdown },
+			};
+
+			self.sections.push(OpenSection {
+				level,
+				block: PastedBlock::new(content),
+			});
+
+			return;
+		}
+
+		let markdown = match self.quote_depth {
+			0 => markdown,
+			depth => {
+				let prefix = "> ".repeat(depth);
+
+				markdown
+					.lines()
+					.map(|line| format!("{prefix}{line}"))
+					.collect::<Vec<_>>()
+					.join("\n")
+			}
+		};
+
+		match self.items.last_mut() {
+			Some(item) if item.content.is_none() => {
+				item.content = Some(match item.checked {
+					Some(done) => BlockContent::Todo { markdown, done },
+					None => BlockContent::Paragraph { markdown },
+				});
+			}
+
+			_ => self.emit(PastedBlock::new(BlockContent::Paragraph { markdown })),
+		}
+	}
+
+	/// Count a finished block, and place it.
+	fn emit(&mut self, block: PastedBlock) {
+		self.count += 1;
+		self.place(block);
+	}
+
+	/// Put a block under the innermost open item or section, or at the top
+	/// level.
+	fn place(&mut self, block: PastedBlock) {
+		if let Some(item) = self.items.last_mut() {
+			item.children.push(block);
+		} else if let Some(section) = self.sections.last_mut() {
+			section.block.children.push(block);
+		} else {
+			self.roots.push(block);
+		}
+	}
+
+	/// Emit a finished list item. Items without text of their own leave
+	/// their nested items to the list around them.
+	fn close_item(&mut self, item: OpenItem) {
+		match item.content {
+			Some(content) => self.emit(PastedBlock {
+				content,
+				children: item.children,
+			}),
+
+			None => {
+				for child in item.children {
+					self.place(child);
+				}
+			}
+		}
+	}
+
+	/// Close the sections at the given heading level or deeper.
+	fn close_sections(&mut self, level: u8) {
+		while self
+			.sections
+			.last()
+			.is_some_and(|section| section.level >= level)
+		{
+			if let Some(section) = self.sections.pop() {
+				self.emit(section.block);
+			}
+		}
+	}
+
+	fn finish(&mut self) -> Vec<PastedBlock> {
+		self.close_code();
+		self.flush();
+
+		while let Some(item) = self.items.pop() {
+			self.close_item(item);
+		}
+
+		self.close_sections(1);
+		std::mem::take(&mut self.roots)
+	}
+}
+
+fn heading_level(name: &str) -> u8 {
+	name[1..].parse().unwrap_or(6)
+}
+
+/// Get the value of a tag's attribute.
+fn attribute<'t>(tag: &'t Tag, name: &str) -> Option<&'t str> {
+	tag.attrs
+		.iter()
+		.find(|attribute| &*attribute.name.local == name)
+		.map(|attribute| &*attribute.value)
+}
+
+/// Get the language that a code element's class names, as in
+/// `language-rust`.
+fn code_language(tag: &Tag) -> Option<String> {
+	attribute(tag, "class")?
+		.split_whitespace()
+		.find_map(|class| {
+			class
+				.strip_prefix("language-")
+				.or_else(|| class.strip_prefix("lang-"))
+		})
+		.map(str::to_string)
+}
+
+/// Escape the characters that markdown would read as formatting.
+fn escape(text: &str) -> String {
+	let mut escaped = String::with_capacity(text.len());
+
+	for c in text.chars() {
+		if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '~') {
+			escaped.push('\\');
+		}
+
+		escaped.push(c);
+	}
+
+	escaped
+}
+
+#[derive(Debug, Error)]
+pub enum HtmlImportError {
+	#[error("The pasted HTML has no content")]
+	NothingToPaste,
+
+	#[error("The pasted HTML makes more than the {MAX_PASTED_BLOCKS} blocks allowed")]
+	TooManyBlocks,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn paragraph(markdown: &str) -> PastedBlock {
+		PastedBlock::new(BlockContent::Paragraph {
+			markdown: markdown.to_string(),
+		})
+	}
+
+	#[test]
+	fn test_html_to_blocks_nests_under_headings() {
+		let html = r#"
+			<h1>Trip <em>notes</em></h1>
+			<p>Intro</p>
+			<h2>Day one</h2>
+			<p>Arrived</p>
+			<h2>Day two</h2>
+			<p>Left</p>
+		"#;
+
+		let blocks = html_to_blocks(html).unwrap();
+
+		let heading = |markdown: &str, children| PastedBlock {
+			content: BlockContent::Heading {
+				markdown: markdown.to_string(),
+			},
+			children,
+		};
+
+		assert_eq!(
+			blocks,
+			vec![PastedBlock {
+				content: BlockContent::Page {
+					title: "Trip notes".to_string(),
+				},
+				children: vec![
+					paragraph("Intro"),
+					heading("Day one", vec![paragraph("Arrived")]),
+					heading("Day two", vec![paragraph("Left")]),
+				],
+			}]
+		);
+	}
+
+	#[test]
+	fn test_html_to_blocks_nests_lists() {
+		let html = r#"
+			<ul>
+				<li>Fruit
+					<ul>
+						<li>Apples</li>
+						<li><input type="checkbox" checked> Pears</li>
+					</ul>
+				</li>
+				<li><input type="checkbox"> Bread</li>
+			</ul>
+		"#;
+
+		let blocks = html_to_blocks(html).unwrap();
+
+		assert_eq!(
+			blocks,
+			vec![
+				PastedBlock {
+					content: BlockContent::Paragraph {
+						markdown: "Fruit".to_string(),
+					},
+					children: vec![
+						paragraph("Apples"),
+						PastedBlock::new(BlockContent::Todo {
+							markdown: "Pears".to_string(),
+							done: true,
+						}),
+					],
+				},
+				PastedBlock::new(BlockContent::Todo {
+					markdown: "Bread".to_string(),
+					done: false,
+				}),
+			]
+		);
+	}
+
+	#[test]
+	fn test_html_to_blocks_formats_inline_markdown() {
+		let html = concat!(
+			"<p>Some <b>bold</b>,<i> slanted</i>, <code>code</code>, and a ",
+			r#"<a href="https://example.com">link</a>. Not *markdown*.<b></b></p>"#,
+			"<blockquote>Quoted<br>twice</blockquote>",
+			"<script>alert(1)</script>",
+		);
+
+		let blocks = html_to_blocks(html).unwrap();
+
+		assert_eq!(
+			blocks,
+			vec![
+				paragraph(
+					"Some **bold**, _slanted_, `code`, and a [link](<https://example.com>). \
+					 Not \\*markdown\\*."
+				),
+				paragraph("> Quoted\n> twice"),
+			]
+		);
+	}
+
+	#[test]
+	fn test_html_to_blocks_fences_code() {
+		let html = "<pre><code class=\"language-rust\">fn main() {\n    *x &lt; 1;\n}\n</code></pre>";
+
+		let blocks = html_to_blocks(html).unwrap();
+
+		assert_eq!(
+			blocks,
+			vec![paragraph("```rust\nfn main() {\n    *x < 1;\n}\n```")]
+		);
+	}
+
+	#[test]
+	fn test_html_to_blocks_limits() {
+		assert!(matches!(
+			html_to_blocks("<p> </p><script>nothing</script>"),
+			Err(HtmlImportError::NothingToPaste)
+		));
+
+		let html = "<p>Block</p>".repeat(MAX_PASTED_BLOCKS + 1);
+
+		assert!(matches!(
+			html_to_blocks(&html),
+			Err(HtmlImportError::TooManyBlocks)
+		));
+	}
+
+	#[test]
+	fn test_html_to_blocks_limits_nesting() {
+		fn depth(blocks: &[PastedBlock]) -> usize {
+			blocks
+				.iter()
+				.map(|block| 1 + depth(&block.children))
+				.max()
+				.unwrap_or(0)
+		}
+
+		// Items nested past the limit are pasted as siblings.
+		let blocks = html_to_blocks(&"<li>Item".repeat(MAX_PASTE_DEPTH + 2)).unwrap();
+		assert_eq!(depth(&blocks), MAX_PASTE_DEPTH);
+
+		// Deeply nested items are counted without building the tree.
+		let html = "<li>a".repeat(50_000);
+
+		let converted = std::thread::Builder::new()
+			.stack_size(256 * 1024)
+			.spawn(move || html_to_blocks(&html).map(|_| ()))
+			.unwrap()
+			.join()
+			.unwrap();
+
+		assert!(matches!(converted, Err(HtmlImportError::TooManyBlocks)));
+	}
+
+	#[test]
+	fn test_instantiate() {
+		let parent_id = NuttyId::now();
+		let owner_id = NuttyId::now();
+		let last_f_index = FractionalIndex::spread(1).remove(0);
+
+		let pasted = vec![
+			PastedBlock {
+				content: BlockContent::Heading {
+					markdown: "Heading".to_string(),
+				},
+				children: vec![paragraph("Nested")],
+			},
+			paragraph("After"),
+		];
+
+		let blocks = instantiate(&pasted, parent_id, owner_id, Some(&last_f_index)).unwrap();
+
+		// Assert: Parents come before their children, all owned by the navigator.
+		assert_eq!(blocks.len(), 3);
+		assert!(blocks.iter().all(|block| block.owner_id == Some(owner_id)));
+		assert_eq!(blocks[0].parent_id, Some(parent_id));
+		assert_eq!(blocks[1].parent_id, Some(*blocks[0].nutty_id()));
+		assert_eq!(blocks[2].parent_id, Some(parent_id));
+
+		// Assert: The top-level blocks come after the existing children, in order.
+		assert!(blocks[0].f_index > last_f_index);
+		assert!(blocks[2].f_index > blocks[0].f_index);
+	}
+}
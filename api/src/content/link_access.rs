@@ -0,0 +1,23 @@
+/// What to do with links to blocks that their author can't read, which
+/// would otherwise reveal that the blocks exist.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LinkAccessPolicy {
+	/// Don't create the links.
+	#[default]
+	Drop,
+
+	/// Create the links, but mark them as restricted, so that they are only
+	/// shown to navigators who can read their targets.
+	Mark,
+}
+
+impl LinkAccessPolicy {
+	/// Read the policy from `CONTENT_LINK_ACCESS_POLICY` ("drop" or "mark"),
+	/// falling back to dropping the links.
+	pub fn from_env() -> Self {
+		match std::env::var("CONTENT_LINK_ACCESS_POLICY").as_deref() {
+			Ok("mark") => Self::Mark,
+			_ => Self::Drop,
+		}
+	}
+}
@@ -1,3 +1,10 @@
+pub mod annotator;
 pub mod api;
+pub mod block_kind;
+pub mod events;
+pub mod html_import;
+pub mod link_access;
+pub mod og_image;
 pub mod repository;
+pub mod sanitizer;
 pub mod service;
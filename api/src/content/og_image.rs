@@ -0,0 +1,450 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use resvg::tiny_skia::Pixmap;
+use resvg::tiny_skia::Transform;
+use resvg::usvg;
+use resvg::usvg::fontdb;
+use sha2::Digest;
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::models::BlockContent;
+use crate::models::ContentBlock;
+use crate::models::ContentContext;
+use crate::models::NuttyId;
+use crate::models::outline::OutlineEntry;
+
+/// The width of a preview image, in pixels.
+pub const OG_IMAGE_WIDTH: u32 = 1200;
+
+/// The height of a preview image, in pixels.
+pub const OG_IMAGE_HEIGHT: u32 = 630;
+
+/// The most characters on a line of a title before it wraps.
+const TITLE_LINE_CHARS: usize = 30;
+
+/// The most lines of a title before it's cut short.
+const TITLE_MAX_LINES: usize = 3;
+
+/// The most characters of a breadcrumb before it's cut short.
+const BREADCRUMB_MAX_CHARS: usize = 60;
+
+/// Renders the preview images that link unfurls show for public pages: a
+/// block's title and breadcrumb on a plain template. Rendered images are
+/// cached on disk, keyed by what's drawn on them, if a cache is configured.
+#[derive(Clone)]
+pub struct OgImages {
+	fonts: Arc<fontdb::Database>,
+	cache_dir: Option<PathBuf>,
+}
+
+impl OgImages {
+	/// Create a renderer with fonts and an optional cache directory.
+	pub fn new(fonts: fontdb::Database, cache_dir: Option<PathBuf>) -> Self {
+		Self {
+			fonts: Arc::new(fonts),
+			cache_dir,
+		}
+	}
+
+	/// Load the font from `OG_IMAGE_FONT_PATH`, or the system's fonts, and
+	/// cache images in `OG_IMAGE_CACHE_DIR`, if set.
+	pub fn from_env() -> Self {
+		let mut fonts = fontdb::Database::new();
+
+		match std::env::var("OG_IMAGE_FONT_PATH") {
+			Ok(path) => fonts
+				.load_font_file(&path)
+				.expect("Invalid OG_IMAGE_FONT_PATH"),
+			Err(_) => fonts.load_system_fonts(),
+		}
+
+		let family = fonts
+			.faces()
+			.flat_map(|face| face.families.iter())
+			.map(|(family, _)| family.clone())
+			.find(|family| family == "Arial")
+			.or_else(|| {
+				let face = fonts.faces().next()?;
+				face.families.first().map(|(family, _)| family.clone())
+			});
+
+		if let Some(family) = family {
+			fonts.set_sans_serif_family(family);
+		}
+
+		let cache_dir = std::env::var("OG_IMAGE_CACHE_DIR").ok().map(PathBuf::from);
+
+		Self::new(fonts, cache_dir)
+	}
+
+	/// Render a block's preview image as a PNG, or get it from the cache.
+	pub async fn render(&self, title: &str, breadcrumb: &[String]) -> Result<Vec<u8>, OgImageError> {
+		let svg = template(title, breadcrumb);
+		let renderer = self.clone();
+
+		tokio::task::spawn_blocking(move || renderer.render_cached(&svg))
+			.await
+			.map_err(|_| OgImageError::Cancelled)?
+	}
+
+	fn render_cached(&self, svg: &str) -> Result<Vec<u8>, OgImageError> {
+		let cache_path = self
+			.cache_dir
+			.as_ref()
+			.map(|dir| dir.join(format!("{:x}.png", Sha256::digest(svg.as_bytes()))));
+
+		if let Some(png) = cache_path
+			.as_ref()
+			.and_then(|path| std::fs::read(path).ok())
+		{
+			return Ok(png);
+		}
+
+		let png = self.render_svg(svg)?;
+
+		if let Some(path) = cache_path {
+			// The image was rendered anyway, so a cache that can't be
+			// written to only costs rendering it again.
+			if let Err(e) = write_cache(&path, &png) {
+				eprintln!("Failed to cache preview image at {}: {e}", path.display());
+			}
+		}
+
+		Ok(png)
+	}
+
+	fn render_svg(&self, svg: &str) -> Result<Vec<u8>, OgImageError> {
+		let options = usvg::Options {
+			fontdb: self.fonts.clone(),
+			..Default::default()
+		};
+
+		let tree = usvg::Tree::from_str(svg, &options).map_err(OgImageError::Template)?;
+		let mut pixmap = Pixmap::new(OG_IMAGE_WIDTH, OG_IMAGE_HEIGHT).ok_or(OgImageError::Canvas)?;
+
+		resvg::render(&tree, Transform::default(), &mut pixmap.as_mut());
+
+		pixmap
+			.encode_png()
+			.map_err(|e| OgImageError::Encode(e.to_string()))
+	}
+}
+
+impl Default for OgImages {
+	/// A renderer without fonts or a cache, which draws the template alone.
+	fn default() -> Self {
+		Self::new(fontdb::Database::new(), None)
+	}
+}
+
+impl std::fmt::Debug for OgImages {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("OgImages")
+			.field("fonts", &self.fonts.len())
+			.field("cache_dir", &self.cache_dir)
+			.finish()
+	}
+}
+
+/// Get the breadcrumb for a block's preview image: the published titles of
+/// the ancestors that it inherits access from, root first. It stops at the
+/// first one that isn't published, so that titles that were never meant to
+/// be shown aren't drawn.
+pub fn breadcrumb(
+	context: &ContentContext,
+	published: &HashMap<NuttyId, BlockContent>,
+) -> Vec<String> {
+	let Some(block) = context.block_cache().get(context.block_id()) else {
+		return vec![];
+	};
+
+	let ancestors: Vec<ContentBlock> = context
+		.ancestor_ids()
+		.iter()
+		.rev()
+		.filter_map(|ancestor_id| context.block_cache().get(ancestor_id))
+		.cloned()
+		.collect();
+
+	let mut breadcrumb: Vec<String> = block
+		.access_ancestors(&ancestors)
+		.iter()
+		.map_while(|ancestor| published.get(ancestor.nutty_id()))
+		.map(OutlineEntry::title_of)
+		.collect();
+
+	breadcrumb.reverse();
+	breadcrumb
+}
+
+/// Render the meta tags that link unfurls read a page's title and preview
+/// image from.
+pub fn meta_tags(title: &str, image_url: Option<&str>) -> String {
+	let mut meta = format!(
+		r#"<meta property="og:title" content="{}">"#,
+		escape_xml(title)
+	);
+
+	if let Some(image_url) = image_url {
+		meta.push_str(&format!(
+			r#"<meta property="og:image" content="{}"><meta property="og:image:width" content="{OG_IMAGE_WIDTH}"><meta property="og:image:height" content="{OG_IMAGE_HEIGHT}"><meta name="twitter:card" content="summary_large_image">"#,
+			escape_xml(image_url)
+		));
+	}
+
+	meta
+}
+
+/// Write a cached image, via a temporary file so that concurrent readers
+/// never see half of one.
+fn write_cache(path: &std::path::Path, png: &[u8]) -> std::io::Result<()> {
+	if let Some(dir) = path.parent() {
+		std::fs::create_dir_all(dir)?;
+	}
+
+	let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+	std::fs::write(&temp_path, png)?;
+	std::fs::rename(&temp_path, path)
+}
+
+/// Draw a title and breadcrumb on the preview image template, as SVG.
+fn template(title: &str, breadcrumb: &[String]) -> String {
+	let breadcrumb = truncate(&breadcrumb.join(" / "), BREADCRUMB_MAX_CHARS);
+
+	let title_lines: String = wrap(title, TITLE_LINE_CHARS, TITLE_MAX_LINES)
+		.iter()
+		.enumerate()
+		.map(|(i, line)| {
+			format!(
+				r#"<tspan x="80" dy="{}">{}</tspan>"#,
+				if i == 0 { 0 } else { 84 },
+				escape_xml(line)
+			)
+		})
+		.collect();
+
+	format!(
+		r##"<svg xmlns="http://www.w3.org/2000/svg" width="{OG_IMAGE_WIDTH}" height="{OG_IMAGE_HEIGHT}" viewBox="0 0 {OG_IMAGE_WIDTH} {OG_IMAGE_HEIGHT}">
+	<rect width="100%" height="100%" fill="#1c1917"/>
+	<rect x="0" y="0" width="16" height="100%" fill="#f59e0b"/>
+	<text x="80" y="120" font-family="sans-serif" font-size="32" fill="#a8a29e">{}</text>
+	<text x="80" y="260" font-family="sans-serif" font-size="72" font-weight="bold" fill="#fafaf9">{title_lines}</text>
+	<text x="80" y="560" font-family="sans-serif" font-size="28" fill="#f59e0b">Nuttyverse</text>
+</svg>"##,
+		escape_xml(&breadcrumb),
+	)
+}
+
+/// Wrap text into lines at word boundaries, cutting it short with an
+/// ellipsis if it takes more than the maximum number of lines.
+fn wrap(text: &str, line_chars: usize, max_lines: usize) -> Vec<String> {
+	let mut lines: Vec<String> = Vec::new();
+	let mut line = String::new();
+
+	for word in text.split_whitespace() {
+		let word = truncate(word, line_chars);
+
+		if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > line_chars {
+			lines.push(std::mem::take(&mut line));
+		}
+
+		if !line.is_empty() {
+			line.push(' ');
+		}
+
+		line.push_str(&word);
+	}
+
+	if !line.is_empty() {
+		lines.push(line);
+	}
+
+	if lines.len() > max_lines {
+		lines.truncate(max_lines);
+
+		if let Some(last) = lines.last_mut() {
+			let kept: String = last.chars().take(line_chars - 1).collect();
+			*last = format!("{kept}…");
+		}
+	}
+
+	lines
+}
+
+/// Cut text short with an ellipsis if it's longer than the maximum number
+/// of characters.
+fn truncate(text: &str, max_chars: usize) -> String {
+	if text.chars().count() <= max_chars {
+		return text.to_string();
+	}
+
+	let kept: String = text.chars().take(max_chars - 1).collect();
+	format!("{kept}…")
+}
+
+/// Escape text to be placed in XML.
+fn escape_xml(text: &str) -> String {
+	text
+		.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+		.replace('\'', "&apos;")
+}
+
+#[derive(Debug, Error)]
+pub enum OgImageError {
+	#[error("Failed to parse the preview image template: {0}")]
+	Template(#[source] usvg::Error),
+
+	#[error("Failed to allocate the preview image")]
+	Canvas,
+
+	#[error("Failed to encode the preview image: {0}")]
+	Encode(String),
+
+	#[error("Rendering the preview image was cancelled")]
+	Cancelled,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::models::FractionalIndex;
+
+	#[test]
+	fn test_template() {
+		// Assert: Titles wrap, and long ones are cut short.
+		let title = "The quick brown fox jumps over the lazy dog ".repeat(4);
+		let lines = wrap(&title, TITLE_LINE_CHARS, TITLE_MAX_LINES);
+
+		assert_eq!(lines.len(), TITLE_MAX_LINES);
+		assert!(
+			lines
+				.iter()
+				.all(|line| line.chars().count() <= TITLE_LINE_CHARS)
+		);
+		assert!(lines[2].ends_with('…'));
+
+		// Assert: Text is escaped.
+		let svg = template(
+			"<Fish & Chips>",
+			&["Recipes".to_string(), "\"UK\"".to_string()],
+		);
+
+		assert!(svg.contains("&lt;Fish &amp; Chips&gt;"));
+		assert!(svg.contains("Recipes / &quot;UK&quot;"));
+
+		// Assert: The image is only linked to if there is one.
+		let meta = meta_tags("Fish & Chips", None);
+		assert_eq!(
+			meta,
+			r#"<meta property="og:title" content="Fish &amp; Chips">"#
+		);
+
+		let meta = meta_tags("Fish & Chips", Some("https://nuttyver.se/og/abcdefg.png"));
+		assert!(
+			meta
+				.contains(r#"<meta property="og:image" content="https://nuttyver.se/og/abcdefg.png">"#)
+		);
+	}
+
+	#[test]
+	fn test_breadcrumb() {
+		let page = |parent: Option<&ContentBlock>, title: &str, inherit_access: bool| {
+			ContentBlock::now(
+				parent.map(|parent| *parent.nutty_id()),
+				FractionalIndex::start(),
+				BlockContent::Page {
+					title: title.to_string(),
+				},
+			)
+			.with_inherit_access(inherit_access)
+		};
+
+		let context = |blocks: &[&ContentBlock]| {
+			let (block, ancestors) = blocks.split_last().unwrap();
+
+			ancestors
+				.iter()
+				.fold(ContentContext::builder(), |builder, ancestor| {
+					builder.add_block_to_cache((*ancestor).clone())
+				})
+				.block_id(*block.nutty_id())
+				.ancestor_ids(
+					ancestors
+						.iter()
+						.map(|ancestor| *ancestor.nutty_id())
+						.collect(),
+				)
+				.add_block_to_cache((*block).clone())
+				.try_build()
+				.unwrap()
+		};
+
+		let published = |blocks: &[&ContentBlock]| {
+			blocks
+				.iter()
+				.map(|block| (*block.nutty_id(), block.content.clone()))
+				.collect::<HashMap<_, _>>()
+		};
+
+		let home = page(None, "Home", true);
+		let recipes = page(Some(&home), "Recipes", true);
+		let groceries = page(Some(&recipes), "Groceries", true);
+		let blocks = [&home, &recipes, &groceries];
+
+		// Assert: Published ancestors are drawn, root first.
+		assert_eq!(
+			breadcrumb(&context(&blocks), &published(&blocks)),
+			["Home", "Recipes"]
+		);
+
+		// Assert: The breadcrumb stops at an unpublished ancestor.
+		assert!(breadcrumb(&context(&blocks), &published(&[&home])).is_empty());
+
+		// Assert: Ancestors that access isn't inherited from aren't drawn.
+		let recipes = page(Some(&home), "Recipes", false);
+		let groceries = page(Some(&recipes), "Groceries", true);
+		let blocks = [&home, &recipes, &groceries];
+		assert_eq!(
+			breadcrumb(&context(&blocks), &published(&blocks)),
+			["Recipes"]
+		);
+
+		let groceries = page(Some(&recipes), "Groceries", false);
+		let blocks = [&home, &recipes, &groceries];
+		assert!(breadcrumb(&context(&blocks), &published(&blocks)).is_empty());
+	}
+
+	#[tokio::test]
+	async fn test_render() {
+		let cache_dir = std::env::temp_dir().join(format!("og-images-{}", uuid::Uuid::now_v7()));
+		let og_images = OgImages::new(fontdb::Database::new(), Some(cache_dir.clone()));
+
+		let png = og_images
+			.render("Groceries", &["Home".to_string()])
+			.await
+			.unwrap();
+
+		// Assert: The image is a PNG of the right size.
+		let pixmap = Pixmap::decode_png(&png).unwrap();
+		assert_eq!(pixmap.width(), OG_IMAGE_WIDTH);
+		assert_eq!(pixmap.height(), OG_IMAGE_HEIGHT);
+
+		// Assert: The image was cached, and is served from the cache.
+		assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 1);
+
+		let cached = og_images
+			.render("Groceries", &["Home".to_string()])
+			.await
+			.unwrap();
+
+		assert_eq!(cached, png);
+
+		std::fs::remove_dir_all(&cache_dir).unwrap();
+	}
+}
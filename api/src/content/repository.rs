@@ -1,15 +1,60 @@
+use std::collections::HashMap;
+
+use chrono::DateTime;
+use chrono::FixedOffset;
+use chrono::NaiveDate;
 use sqlx::Executor;
 use sqlx::Postgres;
+use sqlx::QueryBuilder;
 use thiserror::Error;
+use uuid::Uuid;
 
+use crate::models::BlockContent;
+use crate::models::BlockQuery;
 use crate::models::ContentBlock;
 use crate::models::ContentLink;
 use crate::models::DissociatedNuttyId;
 use crate::models::NuttyId;
+use crate::models::access_request::AccessRequest;
+use crate::models::access_request::AccessRequestStatus;
+use crate::models::block_annotation::BlockAnnotation;
+use crate::models::block_annotation::BlockAnnotationError;
+use crate::models::block_archival::BlockArchival;
+use crate::models::block_checksum::BlockChecksum;
+use crate::models::block_date::BlockDate;
+use crate::models::block_date::BlockDateError;
+use crate::models::block_group::RootBlockGroup;
+use crate::models::block_revision::BlockRevision;
+use crate::models::block_stats::BlockStats;
+use crate::models::canonical_json::canonical_hash;
+use crate::models::capture::Unfurl;
+use crate::models::children_view::ChildrenView;
 use crate::models::content_block::ContentBlockBuilderError;
 use crate::models::content_block::ContentBlockError;
+use crate::models::content_compression::CompressionStats;
+use crate::models::content_filter::ContentFilter;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
 use crate::models::fractional_index::FractionalIndexError;
+use crate::models::outline::OutlineEntry;
+use crate::models::ownership_transfer::OwnershipTransfer;
+use crate::models::property::PropertyDefinition;
+use crate::models::search_language::SearchLanguage;
+use crate::models::search_language::substring_pattern;
+use crate::models::site_settings::AccentColor;
+use crate::models::site_settings::SiteSettings;
+use crate::models::site_settings::SiteSettingsError;
+use crate::models::slug::Slug;
+use crate::models::slug::SlugError;
+use crate::models::slug::SlugTarget;
+use crate::models::sync_changes::Change;
+use crate::models::sync_changes::ChangeKind;
+use crate::models::sync_changes::SyncCursor;
+use crate::models::translation::Lang;
+use crate::models::translation::Translation;
+use crate::models::translation::TranslationError;
+use crate::utilities::query_metrics::RecordQuery;
 use crate::utilities::repository::Repository;
+use crate::utilities::repository::RetryPolicy;
 
 /// A repository for content blocks.
 /// Objects are stored in PostgreSQL.
@@ -17,12 +62,24 @@ use crate::utilities::repository::Repository;
 pub struct ContentRepository {
 	/// The PostgreSQL database pool.
 	pool: sqlx::Pool<Postgres>,
+
+	/// The policy for retrying transactions.
+	retry_policy: RetryPolicy,
 }
 
 impl ContentRepository {
 	/// Create a new content repository.
 	pub fn new(pool: sqlx::Pool<Postgres>) -> Self {
-		Self { pool }
+		Self {
+			pool,
+			retry_policy: RetryPolicy::default(),
+		}
+	}
+
+	/// Set the policy for retrying transactions.
+	pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+		self.retry_policy = retry_policy;
+		self
 	}
 
 	/// Resolve a [DissociatedNuttyId] into a [NuttyId].
@@ -43,6 +100,7 @@ impl ContentRepository {
 			id.nid(),
 		)
 		.fetch_one(executor)
+		.record_query("resolve_nutty_id")
 		.await?;
 
 		Ok(NuttyId::new(record.id))
@@ -75,6 +133,7 @@ impl ContentRepository {
 			&nids,
 		)
 		.fetch_all(executor)
+		.record_query("resolve_nutty_ids")
 		.await
 		.expect("Failed to resolve Nutty IDs");
 
@@ -104,13 +163,14 @@ impl ContentRepository {
 	{
 		Ok(sqlx::query_as(
 			r#"
-				SELECT id, owner_id, parent_id, f_index, content, created_at, updated_at
+				SELECT id, owner_id, parent_id, f_index, content, properties, inherit_access, created_at, updated_at
 				FROM content.blocks
 				WHERE nutty_id = $1
 			"#,
 		)
 		.bind(nutty_id.nid())
 		.fetch_optional(executor)
+		.record_query("get_content_block")
 		.await?)
 	}
 
@@ -122,7 +182,7 @@ impl ContentRepository {
 		self.get_content_block_tx(&self.pool, nutty_id).await
 	}
 
-	/// Get all ancestors of a content block.
+	/// Get all ancestors of a content block, nearest first.
 	pub async fn get_ancestor_blocks_tx<'e, E>(
 		&self,
 		executor: E,
@@ -142,7 +202,7 @@ impl ContentRepository {
 					FROM content.blocks p
 					JOIN ancestors a ON p.id = a.parent_id
 				)
-				SELECT id, owner_id, parent_id, f_index, content, created_at, updated_at
+				SELECT id, owner_id, parent_id, f_index, content, properties, inherit_access, created_at, updated_at
 				FROM ancestors
 				WHERE level > 0
 				ORDER BY level;
@@ -150,6 +210,7 @@ impl ContentRepository {
 		)
 		.bind(nutty_id.nid())
 		.fetch_all(executor)
+		.record_query("get_ancestor_blocks")
 		.await?)
 	}
 
@@ -181,7 +242,7 @@ impl ContentRepository {
 					FROM content.blocks c
 					JOIN descendants d ON c.parent_id = d.id
 				)
-				SELECT id, owner_id, parent_id, f_index, content, created_at, updated_at
+				SELECT id, owner_id, parent_id, f_index, content, properties, inherit_access, created_at, updated_at
 				FROM descendants
 				WHERE level > 0
 				ORDER BY level;
@@ -189,6 +250,7 @@ impl ContentRepository {
 		)
 		.bind(nutty_id.nid())
 		.fetch_all(executor)
+		.record_query("get_descendant_blocks")
 		.await?)
 	}
 
@@ -200,7 +262,259 @@ impl ContentRepository {
 		self.get_descendant_blocks_tx(&self.pool, nutty_id).await
 	}
 
-	/// Upsert a content block.
+	/// Get a content block as it was at a time, from its history. Versions
+	/// are presented as content blocks updated when they were saved.
+	pub async fn get_content_block_as_of_tx<'e, E>(
+		&self,
+		executor: E,
+		nutty_id: &DissociatedNuttyId,
+		as_of: &DateTime<FixedOffset>,
+	) -> Result<Option<ContentBlock>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT block_id AS id, owner_id, parent_id, f_index, content, properties, inherit_access, created_at, valid_from AS updated_at
+				FROM content.block_history
+				WHERE nutty_id = $1
+				AND valid_from <= $2 AND (valid_to IS NULL OR valid_to > $2)
+			"#,
+		)
+		.bind(nutty_id.nid())
+		.bind(as_of)
+		.fetch_optional(executor)
+		.record_query("get_content_block_as_of")
+		.await?)
+	}
+
+	/// Get the ancestors of a content block as they were at a time, nearest
+	/// first.
+	pub async fn get_ancestor_blocks_as_of_tx<'e, E>(
+		&self,
+		executor: E,
+		nutty_id: &DissociatedNuttyId,
+		as_of: &DateTime<FixedOffset>,
+	) -> Result<Vec<ContentBlock>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				WITH RECURSIVE ancestors AS (
+					SELECT h.*, 0 AS level
+					FROM content.block_history h
+					WHERE h.nutty_id = $1
+					AND h.valid_from <= $2 AND (h.valid_to IS NULL OR h.valid_to > $2)
+					UNION ALL
+					SELECT p.*, a.level + 1 AS level
+					FROM content.block_history p
+					JOIN ancestors a ON p.block_id = a.parent_id
+					WHERE p.valid_from <= $2 AND (p.valid_to IS NULL OR p.valid_to > $2)
+				)
+				SELECT block_id AS id, owner_id, parent_id, f_index, content, properties, inherit_access, created_at, valid_from AS updated_at
+				FROM ancestors
+				WHERE level > 0
+				ORDER BY level;
+			"#,
+		)
+		.bind(nutty_id.nid())
+		.bind(as_of)
+		.fetch_all(executor)
+		.record_query("get_ancestor_blocks_as_of")
+		.await?)
+	}
+
+	/// Get the descendants of a content block as they were at a time,
+	/// including those deleted since.
+	pub async fn get_descendant_blocks_as_of_tx<'e, E>(
+		&self,
+		executor: E,
+		nutty_id: &DissociatedNuttyId,
+		as_of: &DateTime<FixedOffset>,
+	) -> Result<Vec<ContentBlock>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				WITH RECURSIVE descendants AS (
+					SELECT h.*, 0 AS level
+					FROM content.block_history h
+					WHERE h.nutty_id = $1
+					AND h.valid_from <= $2 AND (h.valid_to IS NULL OR h.valid_to > $2)
+					UNION ALL
+					SELECT c.*, d.level + 1 AS level
+					FROM content.block_history c
+					JOIN descendants d ON c.parent_id = d.block_id
+					WHERE c.valid_from <= $2 AND (c.valid_to IS NULL OR c.valid_to > $2)
+				)
+				SELECT block_id AS id, owner_id, parent_id, f_index, content, properties, inherit_access, created_at, valid_from AS updated_at
+				FROM descendants
+				WHERE level > 0
+				ORDER BY level, f_index COLLATE "C";
+			"#,
+		)
+		.bind(nutty_id.nid())
+		.bind(as_of)
+		.fetch_all(executor)
+		.record_query("get_descendant_blocks_as_of")
+		.await?)
+	}
+
+	/// Get the Nutty IDs of the content blocks matching a [BlockQuery] that
+	/// a navigator can read, most recently created first.
+	pub async fn query_content_block_ids_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+		query: &BlockQuery,
+		exclude_id: &NuttyId,
+	) -> Result<Vec<NuttyId>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let mut builder = QueryBuilder::<Postgres>::new(
+			"SELECT blocks.id FROM content.blocks AS blocks WHERE blocks.id <> ",
+		);
+
+		builder.push_bind(*exclude_id.uuid());
+
+		// Match any of the given kinds.
+		if !query.kinds.is_empty() {
+			builder.push(" AND blocks.content->>'kind' = ANY(");
+			builder.push_bind(query.kinds.clone());
+			builder.push(")");
+		}
+
+		// Match blocks linking to every given tag.
+		for tag in &query.tags {
+			builder.push(
+				" AND EXISTS (
+					SELECT 1 FROM content.links AS links
+					JOIN content.blocks AS targets ON targets.id = links.target_id
+					WHERE links.source_id = blocks.id AND targets.nutty_id = ",
+			);
+			builder.push_bind(tag.nid());
+			builder.push(")");
+		}
+
+		// Match every date filter. Columns and operators come from a closed set.
+		for filter in &query.date_filters {
+			builder.push(format!(
+				" AND blocks.{} {} ",
+				filter.field.column(),
+				filter.comparison.operator()
+			));
+			builder.push_bind(filter.value);
+		}
+
+		// Match every custom property, using the GIN index.
+		if !query.properties.is_empty() {
+			builder.push(" AND blocks.properties @> ");
+			builder.push_bind(sqlx::types::Json(&query.properties));
+		}
+
+		// Only count the blocks that the navigator can read against the limit.
+		builder.push(" AND content.navigator_can_read(");
+		builder.push_bind(*navigator_id.uuid());
+		builder.push(", blocks.id, blocks.parent_id, blocks.owner_id, blocks.inherit_access)");
+
+		builder.push(" ORDER BY blocks.created_at DESC LIMIT ");
+		builder.push_bind(query.limit);
+
+		let ids: Vec<Uuid> = builder
+			.build_query_scalar()
+			.fetch_all(executor)
+			.record_query("query_content_block_ids")
+			.await?;
+
+		Ok(ids.into_iter().map(NuttyId::new).collect())
+	}
+
+	/// Get the Nutty IDs of the content blocks matching a [BlockQuery] that
+	/// a navigator can read, most recently created first.
+	pub async fn query_content_block_ids(
+		&self,
+		navigator_id: &NuttyId,
+		query: &BlockQuery,
+		exclude_id: &NuttyId,
+	) -> Result<Vec<NuttyId>, ContentRepositoryError> {
+		self
+			.query_content_block_ids_tx(&self.pool, navigator_id, query, exclude_id)
+			.await
+	}
+
+	/// Get the Nutty IDs of a content block's children matching a
+	/// [ChildrenView], in its order and then in fractional index order.
+	pub async fn list_child_ids_tx<'e, E>(
+		&self,
+		executor: E,
+		parent_id: &NuttyId,
+		view: &ChildrenView,
+	) -> Result<Vec<NuttyId>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let mut builder = QueryBuilder::<Postgres>::new(
+			"SELECT blocks.id FROM content.blocks AS blocks WHERE blocks.parent_id = ",
+		);
+
+		builder.push_bind(*parent_id.uuid());
+
+		if view.hide_archived {
+			builder.push(" AND (blocks.archived_at IS NULL OR EXISTS (SELECT 1 FROM content.blocks AS parent WHERE parent.id = ");
+			builder.push_bind(*parent_id.uuid());
+			builder.push(" AND parent.archived_at IS NOT NULL))");
+		}
+
+		// Match every filter, on values of the same type. Operators come from a closed set.
+		for filter in &view.filters {
+			builder.push(" AND jsonb_typeof(blocks.properties -> ");
+			builder.push_bind(filter.name.clone());
+			builder.push(") = jsonb_typeof(");
+			builder.push_bind(sqlx::types::Json(&filter.value));
+			builder.push(") AND blocks.properties -> ");
+			builder.push_bind(filter.name.clone());
+			builder.push(format!(" {} ", filter.operator.operator()));
+			builder.push_bind(sqlx::types::Json(&filter.value));
+		}
+
+		builder.push(" ORDER BY ");
+
+		if let Some(sort) = &view.sort {
+			builder.push("blocks.properties -> ");
+			builder.push_bind(sort.name.clone());
+
+			match sort.descending {
+				true => builder.push(" DESC NULLS LAST, "),
+				false => builder.push(" ASC NULLS LAST, "),
+			};
+		}
+
+		builder.push(r#"blocks.f_index COLLATE "C""#);
+
+		let ids: Vec<Uuid> = builder
+			.build_query_scalar()
+			.fetch_all(executor)
+			.record_query("list_child_ids")
+			.await?;
+
+		Ok(ids.into_iter().map(NuttyId::new).collect())
+	}
+
+	/// Get the Nutty IDs of a content block's children matching a
+	/// [ChildrenView], in its order and then in fractional index order.
+	pub async fn list_child_ids(
+		&self,
+		parent_id: &NuttyId,
+		view: &ChildrenView,
+	) -> Result<Vec<NuttyId>, ContentRepositoryError> {
+		self.list_child_ids_tx(&self.pool, parent_id, view).await
+	}
+
+	/// Upsert a content block, along with the page titles that it references
+	/// by title alone, so that renaming a page finds them.
 	pub async fn upsert_content_block_tx<'e, E>(
 		&self,
 		executor: E,
@@ -211,11 +525,23 @@ impl ContentRepository {
 	{
 		Ok(sqlx::query_as(
 			r#"
-				INSERT INTO content.blocks (id, nutty_id, owner_id, parent_id, f_index, content)
-				VALUES ($1, $2, $3, $4, $5, $6)
-				ON CONFLICT (id) DO UPDATE
-				SET parent_id = EXCLUDED.parent_id, content = EXCLUDED.content, f_index = EXCLUDED.f_index, owner_id = EXCLUDED.owner_id
-				RETURNING id, nutty_id, owner_id, parent_id, f_index, content, created_at, updated_at
+				WITH upserted AS (
+					INSERT INTO content.blocks (id, nutty_id, owner_id, parent_id, f_index, content, content_hash, properties, inherit_access, language)
+					VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, (SELECT language FROM content.blocks WHERE id = $4))
+					ON CONFLICT (id) DO UPDATE
+					SET parent_id = EXCLUDED.parent_id, content = EXCLUDED.content, content_hash = EXCLUDED.content_hash, f_index = EXCLUDED.f_index, owner_id = EXCLUDED.owner_id, properties = EXCLUDED.properties, inherit_access = EXCLUDED.inherit_access
+					RETURNING id, nutty_id, owner_id, parent_id, f_index, content, properties, inherit_access, created_at, updated_at
+				),
+				stale_title_references AS (
+					DELETE FROM content.title_references
+					WHERE block_id IN (SELECT id FROM upserted) AND NOT (title = ANY($10))
+				),
+				title_references AS (
+					INSERT INTO content.title_references (block_id, title)
+					SELECT id, UNNEST($10::text[]) FROM upserted
+					ON CONFLICT DO NOTHING
+				)
+				SELECT * FROM upserted
 			"#,
 		)
 		.bind(content_block.nutty_id().uuid())
@@ -224,7 +550,12 @@ impl ContentRepository {
 		.bind(content_block.parent_id.map(|id| *id.uuid()))
 		.bind(content_block.f_index.as_str())
 		.bind(content_block.serialize_content()?)
+		.bind(content_block.content_hash()?)
+		.bind(sqlx::types::Json(&content_block.properties))
+		.bind(content_block.inherit_access)
+		.bind(content_block.content.parse_title_references())
 		.fetch_one(executor)
+		.record_query("upsert_content_block")
 		.await?)
 	}
 
@@ -238,6 +569,74 @@ impl ContentRepository {
 			.await
 	}
 
+	/// Recompress a batch of blocks' content that Postgres compressed with
+	/// pglz, from before content was set to be compressed with lz4. The
+	/// content itself doesn't change. Returns the recompressed blocks' IDs.
+	pub async fn recompress_content_tx<'e, E>(
+		&self,
+		executor: E,
+		limit: i64,
+	) -> Result<Vec<NuttyId>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		// Concatenating an empty object detoasts the content, so that it's
+		// compressed again as it's stored.
+		let ids: Vec<Uuid> = sqlx::query_scalar(
+			r#"
+				UPDATE content.blocks
+				SET content = content || '{}'::jsonb
+				WHERE id IN (
+					SELECT id FROM content.blocks
+					WHERE pg_column_compression(content) = 'pglz'
+					ORDER BY id
+					LIMIT $1
+				)
+				RETURNING id
+			"#,
+		)
+		.bind(limit)
+		.fetch_all(executor)
+		.record_query("recompress_content")
+		.await?;
+
+		Ok(ids.into_iter().map(NuttyId::new).collect())
+	}
+
+	/// Measure how much the content of the given blocks is compressed at
+	/// rest. Blocks whose content isn't compressed aren't counted.
+	pub async fn get_compression_stats_tx<'e, E>(
+		&self,
+		executor: E,
+		ids: &[NuttyId],
+	) -> Result<CompressionStats, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let uuids: Vec<Uuid> = ids.iter().map(|id| *id.uuid()).collect();
+
+		let (compressed_count, original_bytes, compressed_bytes): (i64, i64, i64) = sqlx::query_as(
+			r#"
+				SELECT
+					COUNT(*),
+					COALESCE(SUM(OCTET_LENGTH(content::text)), 0)::bigint,
+					COALESCE(SUM(pg_column_size(content)), 0)::bigint
+				FROM content.blocks
+				WHERE id = ANY($1) AND pg_column_compression(content) IS NOT NULL
+			"#,
+		)
+		.bind(&uuids)
+		.fetch_one(executor)
+		.record_query("get_compression_stats")
+		.await?;
+
+		Ok(CompressionStats {
+			compressed_count: compressed_count as u64,
+			original_bytes: original_bytes as u64,
+			compressed_bytes: compressed_bytes as u64,
+		})
+	}
+
 	/// Delete a block of content by its identifier.
 	pub async fn delete_content_block_tx<'e, E>(
 		&self,
@@ -255,6 +654,7 @@ impl ContentRepository {
 			nutty_id.nid()
 		)
 		.execute(executor)
+		.record_query("delete_content_block")
 		.await?;
 
 		Ok(())
@@ -268,6 +668,30 @@ impl ContentRepository {
 		self.delete_content_block_tx(&self.pool, nutty_id).await
 	}
 
+	/// Delete several blocks of content at once, such as a block and its
+	/// descendants. Their links, dates, and access requests cascade.
+	pub async fn delete_content_blocks_tx<'e, E>(
+		&self,
+		executor: E,
+		nutty_ids: &[NuttyId],
+	) -> Result<(), ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		sqlx::query!(
+			r#"
+				DELETE FROM content.blocks
+				WHERE id = ANY($1)
+			"#,
+			&nutty_ids.iter().map(|id| *id.uuid()).collect::<Vec<_>>(),
+		)
+		.execute(executor)
+		.record_query("delete_content_blocks")
+		.await?;
+
+		Ok(())
+	}
+
 	/// Get a content link by its identifier.
 	pub async fn get_content_link_tx<'e, E>(
 		&self,
@@ -280,22 +704,26 @@ impl ContentRepository {
 		// Find the content link.
 		let record = sqlx::query!(
 			r#"
-				SELECT id, source_id, target_id
+				SELECT id, source_id, target_id, restricted
 				FROM content.links
 				WHERE nutty_id = $1
 			"#,
 			nutty_id.nid()
 		)
 		.fetch_optional(executor)
+		.record_query("get_content_link")
 		.await?;
 
 		match record {
 			// Found the content link!
-			Some(record) => Ok(Some(ContentLink::new(
-				NuttyId::new(record.id),
-				NuttyId::new(record.source_id),
-				NuttyId::new(record.target_id),
-			))),
+			Some(record) => Ok(Some(
+				ContentLink::new(
+					NuttyId::new(record.id),
+					NuttyId::new(record.source_id),
+					NuttyId::new(record.target_id),
+				)
+				.with_restricted(record.restricted),
+			)),
 
 			// It does not exist…
 			None => Ok(None),
@@ -321,13 +749,14 @@ impl ContentRepository {
 	{
 		let records = sqlx::query!(
 			r#"
-				SELECT id, source_id, target_id
+				SELECT id, source_id, target_id, restricted
 				FROM content.links
 				WHERE source_id = $1
 			"#,
 			nutty_id.uuid()
 		)
 		.fetch_all(executor)
+		.record_query("get_content_links_from")
 		.await?;
 
 		Ok(records
@@ -338,6 +767,7 @@ impl ContentRepository {
 					NuttyId::new(record.source_id),
 					NuttyId::new(record.target_id),
 				)
+				.with_restricted(record.restricted)
 			})
 			.collect())
 	}
@@ -350,6 +780,40 @@ impl ContentRepository {
 		self.get_content_links_from_tx(&self.pool, nutty_id).await
 	}
 
+	/// Get the content links with the given IDs.
+	pub async fn get_content_links_by_ids_tx<'e, E>(
+		&self,
+		executor: E,
+		ids: &[NuttyId],
+	) -> Result<Vec<ContentLink>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let records = sqlx::query!(
+			r#"
+				SELECT id, source_id, target_id, restricted
+				FROM content.links
+				WHERE id = ANY($1)
+			"#,
+			&ids.iter().map(|id| *id.uuid()).collect::<Vec<_>>(),
+		)
+		.fetch_all(executor)
+		.record_query("get_content_links_by_ids")
+		.await?;
+
+		Ok(records
+			.iter()
+			.map(|record| {
+				ContentLink::new(
+					NuttyId::new(record.id),
+					NuttyId::new(record.source_id),
+					NuttyId::new(record.target_id),
+				)
+				.with_restricted(record.restricted)
+			})
+			.collect())
+	}
+
 	/// Get all content links to a content block.
 	pub async fn get_content_links_to_tx<'e, E>(
 		&self,
@@ -361,13 +825,14 @@ impl ContentRepository {
 	{
 		let records = sqlx::query!(
 			r#"
-				SELECT id, source_id, target_id
+				SELECT id, source_id, target_id, restricted
 				FROM content.links
 				WHERE target_id = $1
 			"#,
 			nutty_id.uuid()
 		)
 		.fetch_all(executor)
+		.record_query("get_content_links_to")
 		.await?;
 
 		Ok(records
@@ -378,6 +843,7 @@ impl ContentRepository {
 					NuttyId::new(record.source_id),
 					NuttyId::new(record.target_id),
 				)
+				.with_restricted(record.restricted)
 			})
 			.collect())
 	}
@@ -390,53 +856,211 @@ impl ContentRepository {
 		self.get_content_links_to_tx(&self.pool, nutty_id).await
 	}
 
-	/// Upsert a content link between two content blocks.
-	pub async fn upsert_content_link_tx<'e, E>(
+	/// List the blocks that reference a page by its title alone ([[Title]])
+	/// and that a navigator can edit: blocks they own or can write, which
+	/// aren't hidden.
+	pub async fn list_title_referrers_tx<'e, E>(
 		&self,
 		executor: E,
-		link: ContentLink,
-	) -> Result<ContentLink, ContentRepositoryError>
+		navigator_id: &NuttyId,
+		title: &str,
+	) -> Result<Vec<ContentBlock>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT b.id, b.owner_id, b.parent_id, b.f_index, b.content, b.properties, b.inherit_access, b.created_at, b.updated_at
+				FROM content.title_references r
+				JOIN content.blocks b ON b.id = r.block_id
+				WHERE r.title = $2
+					AND NOT content.is_hidden(b.id)
+					AND (
+						b.owner_id = $1
+						OR content.navigator_can_write($1, b.id, b.parent_id, b.owner_id, b.inherit_access)
+					)
+				ORDER BY b.id
+			"#,
+		)
+		.bind(navigator_id.uuid())
+		.bind(title)
+		.fetch_all(executor)
+		.record_query("list_title_referrers")
+		.await?)
+	}
+
+	/// Check if a page other than the given one has a title, which makes
+	/// references to the title ambiguous.
+	pub async fn is_title_shared_tx<'e, E>(
+		&self,
+		executor: E,
+		page_id: &NuttyId,
+		title: &str,
+	) -> Result<bool, ContentRepositoryError>
 	where
 		E: Executor<'e, Database = Postgres>,
 	{
-		// Insert the content link.
 		let record = sqlx::query!(
 			r#"
-				INSERT INTO content.links (id, nutty_id, source_id, target_id)
-				VALUES ($1, $2, $3, $4)
-				ON CONFLICT (id) DO NOTHING
-				RETURNING id, nutty_id, source_id, target_id
+				SELECT EXISTS (
+					SELECT 1 FROM content.blocks
+					WHERE content->>'kind' = 'Page'
+						AND content->>'title' = $2
+						AND id <> $1
+				) AS "exists!"
 			"#,
-			link.nutty_id.uuid(),
-			link.nutty_id.nid(),
-			link.source_id.uuid(),
-			link.target_id.uuid()
+			page_id.uuid(),
+			title,
 		)
 		.fetch_one(executor)
+		.record_query("is_title_shared")
 		.await?;
 
-		// Get the updated content link.
-		let nutty_id = NuttyId::new(record.id);
-		let source_id = NuttyId::new(record.source_id);
-		let target_id = NuttyId::new(record.target_id);
+		Ok(record.exists)
+	}
 
-		Ok(ContentLink::new(nutty_id, source_id, target_id))
+	/// Get the blocks that a navigator can read most often linked to by the
+	/// same blocks as a content block (co-citation), most shared citations
+	/// first. Only citations from blocks that the navigator can read count.
+	pub async fn get_related_blocks_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+		nutty_id: &NuttyId,
+		limit: i64,
+	) -> Result<Vec<ContentBlock>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT blocks.id, owner_id, parent_id, f_index, content, properties, inherit_access, created_at, updated_at
+				FROM (
+					SELECT others.target_id, COUNT(DISTINCT others.source_id) AS citations
+					FROM content.links AS mine
+					JOIN content.links AS others ON others.source_id = mine.source_id
+					JOIN content.blocks AS sources ON sources.id = mine.source_id
+					WHERE mine.target_id = $1 AND others.target_id <> $1
+						AND content.navigator_can_read($3, sources.id, sources.parent_id, sources.owner_id, sources.inherit_access)
+					GROUP BY others.target_id
+				) AS related
+				JOIN content.blocks AS blocks ON blocks.id = related.target_id
+				WHERE content.navigator_can_read($3, blocks.id, blocks.parent_id, blocks.owner_id, blocks.inherit_access)
+				ORDER BY related.citations DESC, blocks.created_at DESC
+				LIMIT $2
+			"#,
+		)
+		.bind(nutty_id.uuid())
+		.bind(limit)
+		.bind(navigator_id.uuid())
+		.fetch_all(executor)
+		.record_query("get_related_blocks")
+		.await?)
 	}
 
-	/// Upsert a content link between two content blocks.
-	pub async fn upsert_content_link(
+	/// Get the blocks that a navigator can read most often linked to by the
+	/// same blocks as a content block (co-citation), most shared citations
+	/// first. Only citations from blocks that the navigator can read count.
+	pub async fn get_related_blocks(
 		&self,
-		link: ContentLink,
-	) -> Result<ContentLink, ContentRepositoryError> {
-		self.upsert_content_link_tx(&self.pool, link).await
+		navigator_id: &NuttyId,
+		nutty_id: &NuttyId,
+		limit: i64,
+	) -> Result<Vec<ContentBlock>, ContentRepositoryError> {
+		self
+			.get_related_blocks_tx(&self.pool, navigator_id, nutty_id, limit)
+			.await
 	}
 
-	/// Upsert multiple content links.
-	pub async fn upsert_content_links_tx<'e, E>(
+	/// Count the blocks linking to each of the given blocks, ignoring links
+	/// from within the given blocks. Blocks without inbound links are omitted.
+	pub async fn count_inbound_links_many_tx<'e, E>(
 		&self,
 		executor: E,
-		links: &[ContentLink],
-	) -> Result<Vec<ContentLink>, ContentRepositoryError>
+		nutty_ids: &[NuttyId],
+	) -> Result<HashMap<NuttyId, i64>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let records = sqlx::query!(
+			r#"
+				SELECT target_id, COUNT(DISTINCT source_id) AS "count!"
+				FROM content.links
+				WHERE target_id = ANY($1) AND NOT source_id = ANY($1)
+				GROUP BY target_id
+			"#,
+			&nutty_ids.iter().map(|id| *id.uuid()).collect::<Vec<_>>(),
+		)
+		.fetch_all(executor)
+		.record_query("count_inbound_links_many")
+		.await?;
+
+		Ok(records
+			.into_iter()
+			.map(|record| (NuttyId::new(record.target_id), record.count))
+			.collect())
+	}
+
+	/// Count the blocks linking to each of the given blocks, ignoring links
+	/// from within the given blocks. Blocks without inbound links are omitted.
+	pub async fn count_inbound_links_many(
+		&self,
+		nutty_ids: &[NuttyId],
+	) -> Result<HashMap<NuttyId, i64>, ContentRepositoryError> {
+		self
+			.count_inbound_links_many_tx(&self.pool, nutty_ids)
+			.await
+	}
+
+	/// Upsert a content link between two content blocks.
+	pub async fn upsert_content_link_tx<'e, E>(
+		&self,
+		executor: E,
+		link: ContentLink,
+	) -> Result<ContentLink, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		// Insert the content link.
+		let record = sqlx::query!(
+			r#"
+				INSERT INTO content.links (id, nutty_id, source_id, target_id, restricted)
+				VALUES ($1, $2, $3, $4, $5)
+				ON CONFLICT (id) DO NOTHING
+				RETURNING id, nutty_id, source_id, target_id, restricted
+			"#,
+			link.nutty_id.uuid(),
+			link.nutty_id.nid(),
+			link.source_id.uuid(),
+			link.target_id.uuid(),
+			link.restricted,
+		)
+		.fetch_one(executor)
+		.record_query("upsert_content_link")
+		.await?;
+
+		// Get the updated content link.
+		let nutty_id = NuttyId::new(record.id);
+		let source_id = NuttyId::new(record.source_id);
+		let target_id = NuttyId::new(record.target_id);
+
+		Ok(ContentLink::new(nutty_id, source_id, target_id).with_restricted(record.restricted))
+	}
+
+	/// Upsert a content link between two content blocks.
+	pub async fn upsert_content_link(
+		&self,
+		link: ContentLink,
+	) -> Result<ContentLink, ContentRepositoryError> {
+		self.upsert_content_link_tx(&self.pool, link).await
+	}
+
+	/// Upsert multiple content links.
+	pub async fn upsert_content_links_tx<'e, E>(
+		&self,
+		executor: E,
+		links: &[ContentLink],
+	) -> Result<Vec<ContentLink>, ContentRepositoryError>
 	where
 		E: Executor<'e, Database = Postgres>,
 	{
@@ -457,21 +1081,27 @@ impl ContentRepository {
 			.iter()
 			.map(|link| *link.target_id.uuid())
 			.collect::<Vec<_>>();
+		let restricted = links.iter().map(|link| link.restricted).collect::<Vec<_>>();
 
-		// Execute the bulk insert.
+		// Execute the bulk insert. Existing links are only updated when their
+		// author's access to the target has changed.
 		let records = sqlx::query!(
 			r#"
-				INSERT INTO content.links (id, nutty_id, source_id, target_id)
-				SELECT * FROM UNNEST($1::uuid[], $2::text[], $3::uuid[], $4::uuid[])
-				ON CONFLICT (source_id, target_id) DO NOTHING
-				RETURNING id, nutty_id, source_id, target_id
+				INSERT INTO content.links (id, nutty_id, source_id, target_id, restricted)
+				SELECT * FROM UNNEST($1::uuid[], $2::text[], $3::uuid[], $4::uuid[], $5::bool[])
+				ON CONFLICT (source_id, target_id) DO UPDATE
+				SET restricted = EXCLUDED.restricted
+				WHERE links.restricted <> EXCLUDED.restricted
+				RETURNING id, nutty_id, source_id, target_id, restricted
 			"#,
 			&ids,
 			&nids,
 			&source_ids,
 			&target_ids,
+			&restricted,
 		)
 		.fetch_all(executor)
+		.record_query("upsert_content_links")
 		.await?;
 
 		// Map the results.
@@ -483,6 +1113,7 @@ impl ContentRepository {
 					NuttyId::new(record.source_id),
 					NuttyId::new(record.target_id),
 				)
+				.with_restricted(record.restricted)
 			})
 			.collect())
 	}
@@ -512,6 +1143,7 @@ impl ContentRepository {
 			link.nutty_id.uuid()
 		)
 		.execute(executor)
+		.record_query("delete_content_link")
 		.await?;
 
 		Ok(())
@@ -539,7 +1171,7 @@ impl ContentRepository {
 			r#"
 				DELETE FROM content.links
 				WHERE source_id = $1
-				AND target_id <> ANY($2)
+				AND target_id <> ALL($2)
 			"#,
 			source_id.uuid(),
 			&target_ids
@@ -548,6 +1180,7 @@ impl ContentRepository {
 				.collect::<Vec<_>>()
 		)
 		.execute(executor)
+		.record_query("delete_orphaned_content_links")
 		.await?;
 
 		Ok(())
@@ -585,6 +1218,7 @@ impl ContentRepository {
 			target_id.uuid()
 		)
 		.fetch_one(executor)
+		.record_query("is_linked")
 		.await?;
 
 		Ok(record.exists)
@@ -598,124 +1232,2869 @@ impl ContentRepository {
 	) -> Result<bool, ContentRepositoryError> {
 		self.is_linked_tx(&self.pool, source_id, target_id).await
 	}
-}
 
-impl Repository for ContentRepository {
-	fn pool(&self) -> &sqlx::Pool<Postgres> {
-		&self.pool
+	/// Get the content blocks with the given IDs.
+	pub async fn get_content_blocks_by_ids_tx<'e, E>(
+		&self,
+		executor: E,
+		ids: &[NuttyId],
+	) -> Result<Vec<ContentBlock>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT id, owner_id, parent_id, f_index, content, properties, inherit_access, created_at, updated_at
+				FROM content.blocks
+				WHERE id = ANY($1)
+			"#,
+		)
+		.bind(ids.iter().map(|id| *id.uuid()).collect::<Vec<_>>())
+		.fetch_all(executor)
+		.record_query("get_content_blocks_by_ids")
+		.await?)
 	}
-}
 
-#[derive(Debug, Error)]
-pub enum ContentRepositoryError {
-	#[error("Unable to query content blocks: {0}")]
-	QueryFailed(#[from] sqlx::error::Error),
+	/// Get the content blocks with the given IDs.
+	pub async fn get_content_blocks_by_ids(
+		&self,
+		ids: &[NuttyId],
+	) -> Result<Vec<ContentBlock>, ContentRepositoryError> {
+		self.get_content_blocks_by_ids_tx(&self.pool, ids).await
+	}
 
-	#[error("Invalid content block builder state: {0}")]
-	InvalidContentBlockBuilder(#[from] ContentBlockBuilderError),
+	/// List the todo blocks that a navigator can read, oldest first,
+	/// optionally filtered by whether they are done and by a due date before
+	/// the given date.
+	pub async fn list_todo_blocks_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+		done: Option<bool>,
+		due_before: Option<NaiveDate>,
+		limit: i64,
+	) -> Result<Vec<ContentBlock>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT id, owner_id, parent_id, f_index, content, properties, inherit_access, created_at, updated_at
+				FROM content.blocks AS blocks
+				WHERE content->>'kind' = 'Todo'
+				AND ($1::boolean IS NULL OR (content->>'done')::boolean = $1)
+				AND ($2::date IS NULL OR EXISTS (
+					SELECT 1 FROM content.block_dates AS dates
+					WHERE dates.block_id = blocks.id AND dates.kind = 'due' AND dates.date < $2
+				))
+				AND content.navigator_can_read($4, id, parent_id, owner_id, inherit_access)
+				ORDER BY created_at
+				LIMIT $3
+			"#,
+		)
+		.bind(done)
+		.bind(due_before)
+		.bind(limit)
+		.bind(navigator_id.uuid())
+		.fetch_all(executor)
+		.record_query("list_todo_blocks")
+		.await?)
+	}
 
-	#[error("Invalid content block: {0}")]
-	InvalidContentBlock(#[from] ContentBlockError),
+	/// List the todo blocks that a navigator can read, oldest first,
+	/// optionally filtered by whether they are done and by a due date before
+	/// the given date.
+	pub async fn list_todo_blocks(
+		&self,
+		navigator_id: &NuttyId,
+		done: Option<bool>,
+		due_before: Option<NaiveDate>,
+		limit: i64,
+	) -> Result<Vec<ContentBlock>, ContentRepositoryError> {
+		self
+			.list_todo_blocks_tx(&self.pool, navigator_id, done, due_before, limit)
+			.await
+	}
 
-	#[error("Invalid index: {0}")]
-	InvalidFractionalIndex(#[from] FractionalIndexError),
-}
+	/// List the content blocks matching a [ContentFilter] that a navigator
+	/// can read, most recently created first, using the index on their kind
+	/// and owner.
+	pub async fn list_content_blocks_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+		filter: &ContentFilter,
+		limit: i64,
+	) -> Result<Vec<ContentBlock>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let mut builder = QueryBuilder::<Postgres>::new(
+			"SELECT id, owner_id, parent_id, f_index, content, properties, inherit_access, created_at, updated_at FROM content.blocks WHERE content.navigator_can_read(",
+		);
 
-#[cfg(test)]
-mod tests {
-	use sqlx::Pool;
-	use sqlx::Postgres;
-	use sqlx::postgres::PgPoolOptions;
+		builder.push_bind(*navigator_id.uuid());
+		builder.push(", id, parent_id, owner_id, inherit_access)");
 
-	use super::*;
-	use crate::models::BlockContent;
-	use crate::models::ContentBlock;
-	use crate::models::ContentLink;
-	use crate::models::DissociatedNuttyId;
-	use crate::models::FractionalIndex;
-	use crate::models::NuttyId;
+		// Match any of the given kinds.
+		if !filter.kinds.is_empty() {
+			builder.push(" AND content->>'kind' = ANY(");
+			builder.push_bind(filter.kinds.clone());
+			builder.push(")");
+		}
 
-	async fn connect_to_test_database() -> Pool<Postgres> {
-		let database_url = std::env::var("DATABASE_URL").unwrap();
+		if let Some(owner_id) = &filter.owner_id {
+			builder.push(" AND owner_id = ");
+			builder.push_bind(*owner_id.uuid());
+		}
 
-		PgPoolOptions::new()
-			.max_connections(5)
-			.connect(&database_url)
-			.await
-			.expect("Failed to connect to test database")
-	}
+		if !filter.include_archived {
+			builder.push(" AND archived_at IS NULL");
+		}
 
-	#[tokio::test]
-	async fn test_content_block_operations() {
-		// Arrange: Create a repository.
-		let pool = connect_to_test_database().await;
-		let repo = ContentRepository::new(pool);
+		builder.push(" ORDER BY created_at DESC LIMIT ");
+		builder.push_bind(limit);
 
-		// Arrange: Create a test content block.
-		let test_block = ContentBlock::now(
-			None,
-			FractionalIndex::start(),
-			BlockContent::Page {
-				title: "Test Page".to_string(),
-			},
-		);
+		Ok(builder
+			.build_query_as()
+			.fetch_all(executor)
+			.record_query("list_content_blocks")
+			.await?)
+	}
 
-		// Act: Save the test content block.
-		let saved_block = repo
-			.upsert_content_block(test_block.clone())
+	/// List the content blocks matching a [ContentFilter] that a navigator
+	/// can read, most recently created first.
+	pub async fn list_content_blocks(
+		&self,
+		navigator_id: &NuttyId,
+		filter: &ContentFilter,
+		limit: i64,
+	) -> Result<Vec<ContentBlock>, ContentRepositoryError> {
+		self
+			.list_content_blocks_tx(&self.pool, navigator_id, filter, limit)
 			.await
-			.expect("Failed to save content block");
+	}
 
-		// Assert: The saved content block matches the original.
-		assert_eq!(saved_block.nutty_id(), test_block.nutty_id());
-		assert_eq!(saved_block.parent_id, test_block.parent_id);
-		assert!(matches!(&saved_block.content, BlockContent::Page { title } if title == "Test Page"));
+	/// Search the markdown and titles of the content blocks that a navigator
+	/// can read, best matches first. Each block is searched in its own
+	/// language, optionally only those in the given language. Archived blocks
+	/// are left out unless included. Navigators who can't edit a block with a
+	/// published revision get that revision, when both it and the draft match.
+	pub async fn search_content_blocks_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+		query: &str,
+		language: Option<SearchLanguage>,
+		include_archived: bool,
+		limit: i64,
+	) -> Result<Vec<ContentBlock>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		// Each branch matches the predicate of its partial index.
+		Ok(sqlx::query_as(
+			r#"
+				SELECT
+					results.id, owner_id, parent_id, f_index,
+					COALESCE(published.content, results.content) AS content,
+					properties, inherit_access, created_at, updated_at
+				FROM (
+					SELECT *, ts_rank(to_tsvector('english', COALESCE(content->>'markdown', content->>'title', '')), websearch_to_tsquery('english', $1)) AS rank
+					FROM content.blocks
+					WHERE ($2::varchar IS NULL OR $2 = 'english')
+					AND content.search_language(language) = 'english'
+					AND ($5 OR archived_at IS NULL)
+					AND to_tsvector('english', COALESCE(content->>'markdown', content->>'title', '')) @@ websearch_to_tsquery('english', $1)
+					UNION ALL
+					SELECT *, ts_rank(to_tsvector('simple', COALESCE(content->>'markdown', content->>'title', '')), websearch_to_tsquery('simple', $1)) AS rank
+					FROM content.blocks
+					WHERE ($2::varchar IS NULL OR $2 = 'simple')
+					AND content.search_language(language) = 'simple'
+					AND ($5 OR archived_at IS NULL)
+					AND to_tsvector('simple', COALESCE(content->>'markdown', content->>'title', '')) @@ websearch_to_tsquery('simple', $1)
+					UNION ALL
+					SELECT *, 0.1::real AS rank
+					FROM content.blocks
+					WHERE ($2::varchar IS NULL OR $2 = 'japanese')
+					AND content.search_language(language) = 'japanese'
+					AND ($5 OR archived_at IS NULL)
+					AND COALESCE(content->>'markdown', content->>'title', '') ILIKE $3
+				) AS results
+				LEFT JOIN LATERAL (
+					SELECT r.content FROM content.block_revisions r
+					WHERE r.id = results.published_revision_id
+						AND NOT content.navigator_can_write($6, results.id, parent_id, owner_id, inherit_access)
+				) AS published ON TRUE
+				WHERE content.navigator_can_read($6, results.id, parent_id, owner_id, inherit_access)
+					AND (
+						published.content IS NULL
+						OR CASE content.search_language(results.language)
+							WHEN 'japanese' THEN
+								COALESCE(published.content->>'markdown', published.content->>'title', '') ILIKE $3
+							ELSE
+								to_tsvector(content.search_language(results.language)::regconfig, COALESCE(published.content->>'markdown', published.content->>'title', ''))
+									@@ websearch_to_tsquery(content.search_language(results.language)::regconfig, $1)
+						END
+					)
+				ORDER BY rank DESC, created_at DESC
+				LIMIT $4
+			"#,
+		)
+		.bind(query)
+		.bind(language.map(|language| language.as_str()))
+		.bind(substring_pattern(query))
+		.bind(limit)
+		.bind(include_archived)
+		.bind(navigator_id.uuid())
+		.fetch_all(executor)
+		.record_query("search_content_blocks")
+		.await?)
+	}
 
-		// Act: Query the content block.
-		let retrieved = repo
-			.get_content_block(&(*saved_block.nutty_id()).into())
+	/// Search the markdown and titles of the content blocks that a navigator
+	/// can read, best matches first.
+	pub async fn search_content_blocks(
+		&self,
+		navigator_id: &NuttyId,
+		query: &str,
+		language: Option<SearchLanguage>,
+		include_archived: bool,
+		limit: i64,
+	) -> Result<Vec<ContentBlock>, ContentRepositoryError> {
+		self
+			.search_content_blocks_tx(
+				&self.pool,
+				navigator_id,
+				query,
+				language,
+				include_archived,
+				limit,
+			)
 			.await
-			.expect("Failed to get content block")
-			.expect("Content block not found");
-
-		// Assert: The retrieved content block matches the original.
-		assert_eq!(retrieved.nutty_id(), test_block.nutty_id());
-		assert_eq!(retrieved.parent_id, test_block.parent_id);
-		assert!(matches!(retrieved.content, BlockContent::Page { title } if title == "Test Page"));
+	}
 
-		// Act: Update the content block.
-		let mut updated_block = test_block.clone();
+	/// Set the search language of a content block and its descendants, or
+	/// clear it with `None`. Translations keep the language they were set
+	/// with. Returns the number of blocks updated.
+	pub async fn set_block_language_tx<'e, E>(
+		&self,
+		executor: E,
+		nutty_id: &DissociatedNuttyId,
+		language: Option<SearchLanguage>,
+	) -> Result<u64, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let result = sqlx::query!(
+			r#"
+				WITH RECURSIVE subtree AS (
+					SELECT id FROM content.blocks WHERE nutty_id = $1
+					UNION ALL
+					SELECT c.id FROM content.blocks c
+					JOIN subtree s ON c.parent_id = s.id
+				)
+				UPDATE content.blocks
+				SET language = $2
+				WHERE id IN (SELECT id FROM subtree)
+					AND id NOT IN (SELECT block_id FROM content.block_translations)
+			"#,
+			nutty_id.nid(),
+			language.map(|language| language.tag()),
+		)
+		.execute(executor)
+		.record_query("set_block_language")
+		.await?;
 
-		updated_block.content = BlockContent::Page {
-			title: "Updated Page".to_string(),
-		};
+		Ok(result.rows_affected())
+	}
 
-		let updated = repo
-			.upsert_content_block(updated_block)
+	/// Set the search language of a content block and its descendants.
+	pub async fn set_block_language(
+		&self,
+		nutty_id: &DissociatedNuttyId,
+		language: Option<SearchLanguage>,
+	) -> Result<u64, ContentRepositoryError> {
+		self
+			.set_block_language_tx(&self.pool, nutty_id, language)
 			.await
-			.expect("Failed to update content block");
+	}
 
-		// Assert: The content block was updated.
-		assert_eq!(updated.nutty_id(), test_block.nutty_id());
-		assert_eq!(updated.parent_id, test_block.parent_id);
-		assert!(matches!(updated.content, BlockContent::Page { title } if title == "Updated Page"));
+	/// Archive or unarchive a content block, optionally with its descendants.
+	/// Blocks that were already archived keep the time they were archived.
+	/// Returns nothing if the block doesn't exist.
+	pub async fn set_block_archived_tx<'e, E>(
+		&self,
+		executor: E,
+		nutty_id: &DissociatedNuttyId,
+		archived: bool,
+		subtree: bool,
+	) -> Result<Option<BlockArchival>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let mut rows = sqlx::query!(
+			r#"
+				WITH RECURSIVE subtree AS (
+					SELECT id, 0 AS depth FROM content.blocks WHERE nutty_id = $1
+					UNION ALL
+					SELECT c.id, s.depth + 1 FROM content.blocks c
+					JOIN subtree s ON c.parent_id = s.id
+					WHERE $3
+				)
+				UPDATE content.blocks AS blocks
+				SET archived_at = CASE WHEN $2 THEN COALESCE(blocks.archived_at, NOW()) ELSE NULL END
+				FROM subtree
+				WHERE blocks.id = subtree.id
+				RETURNING blocks.id, subtree.depth AS "depth!"
+			"#,
+			nutty_id.nid(),
+			archived,
+			subtree,
+		)
+		.fetch_all(executor)
+		.record_query("set_block_archived")
+		.await?;
 
-		// Act: Delete the content block.
-		repo
-			.delete_content_block(&(*test_block.nutty_id()).into())
-			.await
-			.expect("Failed to delete content block");
+		// The block itself comes first, then its descendants by level.
+		rows.sort_by_key(|row| row.depth);
 
-		// Assert: The content block no longer exists.
-		let retrieved = repo
-			.get_content_block(&(*test_block.nutty_id()).into())
-			.await
-			.unwrap();
-		assert!(retrieved.is_none());
+		let updated_ids: Vec<NuttyId> = rows.into_iter().map(|row| NuttyId::new(row.id)).collect();
+
+		let Some(block_id) = updated_ids.first().copied() else {
+			return Ok(None);
+		};
+
+		Ok(Some(BlockArchival {
+			block_id,
+			archived,
+			updated_ids,
+		}))
 	}
 
-	#[tokio::test]
-	async fn test_content_link_operations() {
+	/// Archive or unarchive a content block, optionally with its descendants.
+	/// Returns nothing if the block doesn't exist.
+	pub async fn set_block_archived(
+		&self,
+		nutty_id: &DissociatedNuttyId,
+		archived: bool,
+		subtree: bool,
+	) -> Result<Option<BlockArchival>, ContentRepositoryError> {
+		self
+			.set_block_archived_tx(&self.pool, nutty_id, archived, subtree)
+			.await
+	}
+
+	/// Delete the dates of a content block.
+	pub async fn delete_block_dates_tx<'e, E>(
+		&self,
+		executor: E,
+		block_id: &NuttyId,
+	) -> Result<(), ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		sqlx::query!(
+			r#"
+				DELETE FROM content.block_dates
+				WHERE block_id = $1
+			"#,
+			block_id.uuid(),
+		)
+		.execute(executor)
+		.record_query("delete_block_dates")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Delete the dates of a content block.
+	pub async fn delete_block_dates(
+		&self,
+		block_id: &NuttyId,
+	) -> Result<(), ContentRepositoryError> {
+		self.delete_block_dates_tx(&self.pool, block_id).await
+	}
+
+	/// Insert block dates, ignoring any that already exist.
+	pub async fn insert_block_dates_tx<'e, E>(
+		&self,
+		executor: E,
+		dates: &[BlockDate],
+	) -> Result<(), ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let block_ids = dates
+			.iter()
+			.map(|date| *date.block_id.uuid())
+			.collect::<Vec<_>>();
+		let values = dates.iter().map(|date| date.date).collect::<Vec<_>>();
+		let kinds = dates
+			.iter()
+			.map(|date| date.kind.as_str().to_string())
+			.collect::<Vec<_>>();
+
+		sqlx::query!(
+			r#"
+				INSERT INTO content.block_dates (block_id, date, kind)
+				SELECT * FROM UNNEST($1::uuid[], $2::date[], $3::varchar[])
+				ON CONFLICT (block_id, date, kind) DO NOTHING
+			"#,
+			&block_ids,
+			&values,
+			&kinds,
+		)
+		.execute(executor)
+		.record_query("insert_block_dates")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Insert block dates, ignoring any that already exist.
+	pub async fn insert_block_dates(
+		&self,
+		dates: &[BlockDate],
+	) -> Result<(), ContentRepositoryError> {
+		self.insert_block_dates_tx(&self.pool, dates).await
+	}
+
+	/// List the dates of the blocks that a navigator can read within an
+	/// inclusive date range, in date order.
+	pub async fn list_block_dates_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+		from: NaiveDate,
+		to: NaiveDate,
+	) -> Result<Vec<BlockDate>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let records = sqlx::query!(
+			r#"
+				SELECT dates.block_id, dates.date, dates.kind
+				FROM content.block_dates AS dates
+				JOIN content.blocks AS blocks ON blocks.id = dates.block_id
+				WHERE dates.date BETWEEN $1 AND $2
+				AND content.navigator_can_read($3, blocks.id, blocks.parent_id, blocks.owner_id, blocks.inherit_access)
+				ORDER BY dates.date, dates.kind, dates.block_id
+			"#,
+			from,
+			to,
+			navigator_id.uuid(),
+		)
+		.fetch_all(executor)
+		.record_query("list_block_dates")
+		.await?;
+
+		records
+			.into_iter()
+			.map(|record| {
+				Ok(BlockDate::new(
+					NuttyId::new(record.block_id),
+					record.date,
+					record.kind.parse()?,
+				))
+			})
+			.collect()
+	}
+
+	/// List the dates of the blocks that a navigator can read within an
+	/// inclusive date range, in date order.
+	pub async fn list_block_dates(
+		&self,
+		navigator_id: &NuttyId,
+		from: NaiveDate,
+		to: NaiveDate,
+	) -> Result<Vec<BlockDate>, ContentRepositoryError> {
+		self
+			.list_block_dates_tx(&self.pool, navigator_id, from, to)
+			.await
+	}
+
+	/// Find a navigator's daily note for a date, preferring the oldest if
+	/// there are several.
+	pub async fn find_daily_note_tx<'e, E>(
+		&self,
+		executor: E,
+		owner_id: &NuttyId,
+		date: NaiveDate,
+	) -> Result<Option<NuttyId>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let block_id = sqlx::query_scalar!(
+			r#"
+				SELECT blocks.id
+				FROM content.block_dates AS dates
+				JOIN content.blocks AS blocks ON blocks.id = dates.block_id
+				WHERE dates.kind = 'daily'
+					AND dates.date = $1
+					AND blocks.owner_id = $2
+				ORDER BY blocks.created_at, blocks.id
+				LIMIT 1
+			"#,
+			date,
+			owner_id.uuid(),
+		)
+		.fetch_optional(executor)
+		.record_query("find_daily_note")
+		.await?;
+
+		Ok(block_id.map(NuttyId::new))
+	}
+
+	/// Find a navigator's daily note for a date, preferring the oldest if
+	/// there are several.
+	pub async fn find_daily_note(
+		&self,
+		owner_id: &NuttyId,
+		date: NaiveDate,
+	) -> Result<Option<NuttyId>, ContentRepositoryError> {
+		self.find_daily_note_tx(&self.pool, owner_id, date).await
+	}
+
+	/// List the custom property definitions, by name.
+	pub async fn list_property_definitions_tx<'e, E>(
+		&self,
+		executor: E,
+	) -> Result<Vec<PropertyDefinition>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT id, name, kind, options, created_at, updated_at
+				FROM content.property_definitions
+				ORDER BY name
+			"#,
+		)
+		.fetch_all(executor)
+		.record_query("list_property_definitions")
+		.await?)
+	}
+
+	/// List the custom property definitions, by name.
+	pub async fn list_property_definitions(
+		&self,
+	) -> Result<Vec<PropertyDefinition>, ContentRepositoryError> {
+		self.list_property_definitions_tx(&self.pool).await
+	}
+
+	/// Insert a custom property definition.
+	pub async fn insert_property_definition_tx<'e, E>(
+		&self,
+		executor: E,
+		definition: &PropertyDefinition,
+	) -> Result<PropertyDefinition, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		sqlx::query_as(
+			r#"
+				INSERT INTO content.property_definitions (id, nutty_id, name, kind, options)
+				VALUES ($1, $2, $3, $4, $5)
+				RETURNING id, name, kind, options, created_at, updated_at
+			"#,
+		)
+		.bind(definition.nutty_id().uuid())
+		.bind(definition.nutty_id().nid())
+		.bind(definition.name())
+		.bind(definition.kind().as_str())
+		.bind(sqlx::types::Json(definition.options()))
+		.fetch_one(executor)
+		.record_query("insert_property_definition")
+		.await
+		.map_err(|error| match &error {
+			sqlx::Error::Database(e) if e.constraint() == Some("property_definitions_name_key") => {
+				ContentRepositoryError::PropertyNameTaken
+			}
+			_ => ContentRepositoryError::QueryFailed(error),
+		})
+	}
+
+	/// Insert a custom property definition.
+	pub async fn insert_property_definition(
+		&self,
+		definition: &PropertyDefinition,
+	) -> Result<PropertyDefinition, ContentRepositoryError> {
+		self
+			.insert_property_definition_tx(&self.pool, definition)
+			.await
+	}
+
+	/// Create a new access request.
+	pub async fn create_access_request_tx<'e, E>(
+		&self,
+		executor: E,
+		request: &AccessRequest,
+	) -> Result<AccessRequest, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		sqlx::query_as(
+			r#"
+				INSERT INTO content.access_requests (id, nutty_id, block_id, navigator_id, level, status, created_at, updated_at)
+				VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+				RETURNING id, block_id, navigator_id, level, status, resolved_by, resolved_at, created_at, updated_at
+			"#,
+		)
+		.bind(request.nutty_id().uuid())
+		.bind(request.nutty_id().nid())
+		.bind(request.block_id().uuid())
+		.bind(request.navigator_id().uuid())
+		.bind(request.level())
+		.bind(request.status())
+		.bind(request.created_at())
+		.bind(request.created_at())
+		.fetch_one(executor)
+		.record_query("create_access_request")
+		.await
+		.map_err(|error| match &error {
+			sqlx::Error::Database(e)
+				if e.constraint() == Some("access_requests_pending_block_navigator_key") =>
+			{
+				ContentRepositoryError::AccessRequestPending
+			}
+			_ => ContentRepositoryError::QueryFailed(error),
+		})
+	}
+
+	/// Create a new access request.
+	pub async fn create_access_request(
+		&self,
+		request: &AccessRequest,
+	) -> Result<AccessRequest, ContentRepositoryError> {
+		self.create_access_request_tx(&self.pool, request).await
+	}
+
+	/// Get an access request by ID.
+	pub async fn get_access_request_tx<'e, E>(
+		&self,
+		executor: E,
+		id: &NuttyId,
+	) -> Result<Option<AccessRequest>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT id, block_id, navigator_id, level, status, resolved_by, resolved_at, created_at, updated_at
+				FROM content.access_requests
+				WHERE id = $1
+			"#,
+		)
+		.bind(id.uuid())
+		.fetch_optional(executor)
+		.record_query("get_access_request")
+		.await?)
+	}
+
+	/// Get an access request by ID.
+	pub async fn get_access_request(
+		&self,
+		id: &NuttyId,
+	) -> Result<Option<AccessRequest>, ContentRepositoryError> {
+		self.get_access_request_tx(&self.pool, id).await
+	}
+
+	/// List the pending access requests for a content block, oldest first.
+	pub async fn list_access_requests_tx<'e, E>(
+		&self,
+		executor: E,
+		block_id: &NuttyId,
+	) -> Result<Vec<AccessRequest>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT id, block_id, navigator_id, level, status, resolved_by, resolved_at, created_at, updated_at
+				FROM content.access_requests
+				WHERE block_id = $1 AND status = 'pending'
+				ORDER BY created_at ASC
+			"#,
+		)
+		.bind(block_id.uuid())
+		.fetch_all(executor)
+		.record_query("list_access_requests")
+		.await?)
+	}
+
+	/// List the pending access requests for a content block, oldest first.
+	pub async fn list_access_requests(
+		&self,
+		block_id: &NuttyId,
+	) -> Result<Vec<AccessRequest>, ContentRepositoryError> {
+		self.list_access_requests_tx(&self.pool, block_id).await
+	}
+
+	/// Resolve a pending access request. Returns `None` if there is no such
+	/// pending request.
+	pub async fn resolve_access_request_tx<'e, E>(
+		&self,
+		executor: E,
+		id: &NuttyId,
+		status: AccessRequestStatus,
+		resolved_by: &NuttyId,
+	) -> Result<Option<AccessRequest>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				UPDATE content.access_requests
+				SET status = $2, resolved_by = $3, resolved_at = NOW()
+				WHERE id = $1 AND status = 'pending'
+				RETURNING id, block_id, navigator_id, level, status, resolved_by, resolved_at, created_at, updated_at
+			"#,
+		)
+		.bind(id.uuid())
+		.bind(status)
+		.bind(resolved_by.uuid())
+		.fetch_optional(executor)
+		.record_query("resolve_access_request")
+		.await?)
+	}
+
+	/// Resolve a pending access request. Returns `None` if there is no such
+	/// pending request.
+	pub async fn resolve_access_request(
+		&self,
+		id: &NuttyId,
+		status: AccessRequestStatus,
+		resolved_by: &NuttyId,
+	) -> Result<Option<AccessRequest>, ContentRepositoryError> {
+		self
+			.resolve_access_request_tx(&self.pool, id, status, resolved_by)
+			.await
+	}
+
+	/// Transfer content blocks to a new owner, skipping blocks it already
+	/// owns. Returns the transfers that were made.
+	pub async fn transfer_ownership_tx<'e, E>(
+		&self,
+		executor: E,
+		block_ids: &[NuttyId],
+		new_owner_id: &NuttyId,
+	) -> Result<Vec<OwnershipTransfer>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let records = sqlx::query!(
+			r#"
+				WITH previous AS (
+					SELECT id, owner_id
+					FROM content.blocks
+					WHERE id = ANY($1) AND owner_id IS DISTINCT FROM $2
+					FOR UPDATE
+				)
+				UPDATE content.blocks AS blocks
+				SET owner_id = $2
+				FROM previous
+				WHERE blocks.id = previous.id
+				RETURNING blocks.id, previous.owner_id AS previous_owner_id
+			"#,
+			&block_ids.iter().map(|id| *id.uuid()).collect::<Vec<_>>(),
+			new_owner_id.uuid(),
+		)
+		.fetch_all(executor)
+		.record_query("transfer_ownership")
+		.await
+		.map_err(|error| match &error {
+			sqlx::Error::Database(e) if e.constraint() == Some("blocks_owner_id_fkey") => {
+				ContentRepositoryError::UnknownOwner
+			}
+			_ => ContentRepositoryError::QueryFailed(error),
+		})?;
+
+		Ok(records
+			.into_iter()
+			.map(|record| OwnershipTransfer {
+				block_id: NuttyId::new(record.id),
+				previous_owner_id: record.previous_owner_id.map(NuttyId::new),
+				new_owner_id: *new_owner_id,
+			})
+			.collect())
+	}
+
+	/// Move the resource roles that previous owners held on transferred
+	/// blocks to the new owners.
+	pub async fn reassign_owner_roles_tx<'e, E>(
+		&self,
+		executor: E,
+		transfers: &[OwnershipTransfer],
+	) -> Result<(), ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		// Unowned blocks had no owner roles to move.
+		let transfers: Vec<_> = transfers
+			.iter()
+			.filter_map(|transfer| Some((transfer, transfer.previous_owner_id?)))
+			.collect();
+
+		sqlx::query!(
+			r#"
+				UPDATE auth.resource_roles AS roles
+				SET navigator_id = transfers.new_owner_id
+				FROM UNNEST($1::uuid[], $2::uuid[], $3::uuid[])
+					AS transfers(block_id, previous_owner_id, new_owner_id)
+				WHERE roles.resource_type = 'content_block'
+				AND roles.resource_id = transfers.block_id
+				AND roles.navigator_id = transfers.previous_owner_id
+			"#,
+			&transfers
+				.iter()
+				.map(|(transfer, _)| *transfer.block_id.uuid())
+				.collect::<Vec<_>>(),
+			&transfers
+				.iter()
+				.map(|(_, previous_owner_id)| *previous_owner_id.uuid())
+				.collect::<Vec<_>>(),
+			&transfers
+				.iter()
+				.map(|(transfer, _)| *transfer.new_owner_id.uuid())
+				.collect::<Vec<_>>(),
+		)
+		.execute(executor)
+		.record_query("reassign_owner_roles")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Record ownership transfers, for auditing.
+	pub async fn record_ownership_transfers_tx<'e, E>(
+		&self,
+		executor: E,
+		transfers: &[OwnershipTransfer],
+		transferred_by: &NuttyId,
+	) -> Result<(), ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let ids: Vec<_> = transfers.iter().map(|_| NuttyId::now()).collect();
+
+		sqlx::query!(
+			r#"
+				INSERT INTO content.ownership_transfers
+					(id, nutty_id, block_id, previous_owner_id, new_owner_id, transferred_by)
+				SELECT *, $6::uuid FROM UNNEST($1::uuid[], $2::varchar[], $3::uuid[], $4::uuid[], $5::uuid[])
+			"#,
+			&ids.iter().map(|id| *id.uuid()).collect::<Vec<_>>(),
+			&ids.iter().map(|id| id.nid()).collect::<Vec<_>>(),
+			&transfers
+				.iter()
+				.map(|transfer| *transfer.block_id.uuid())
+				.collect::<Vec<_>>(),
+			&transfers
+				.iter()
+				.map(|transfer| transfer.previous_owner_id.map(|id| *id.uuid()))
+				.collect::<Vec<_>>() as &[Option<Uuid>],
+			&transfers
+				.iter()
+				.map(|transfer| *transfer.new_owner_id.uuid())
+				.collect::<Vec<_>>(),
+			transferred_by.uuid(),
+		)
+		.execute(executor)
+		.record_query("record_ownership_transfers")
+		.await?;
+
+		Ok(())
+	}
+
+	/// List a page of the blocks with markdown content that a navigator
+	/// owns, in ID order, starting after the given block.
+	pub async fn list_owned_markdown_blocks_tx<'e, E>(
+		&self,
+		executor: E,
+		owner_id: &NuttyId,
+		after: Option<&NuttyId>,
+		limit: i64,
+	) -> Result<Vec<ContentBlock>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT id, owner_id, parent_id, f_index, content, properties, inherit_access, created_at, updated_at
+				FROM content.blocks
+				WHERE owner_id = $1
+				AND content->>'kind' IN ('Heading', 'Paragraph', 'Todo')
+				AND ($2::uuid IS NULL OR id > $2)
+				ORDER BY id
+				LIMIT $3
+			"#,
+		)
+		.bind(owner_id.uuid())
+		.bind(after.map(|id| *id.uuid()))
+		.bind(limit)
+		.fetch_all(executor)
+		.record_query("list_owned_markdown_blocks")
+		.await?)
+	}
+
+	/// Get the block that a navigator's inbound emails are filed under.
+	pub async fn get_inbox_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+	) -> Result<Option<NuttyId>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let parent_id = sqlx::query_scalar!(
+			r#"
+				SELECT parent_id
+				FROM content.inboxes
+				WHERE navigator_id = $1
+			"#,
+			navigator_id.uuid(),
+		)
+		.fetch_optional(executor)
+		.record_query("get_inbox")
+		.await?;
+
+		Ok(parent_id.map(NuttyId::new))
+	}
+
+	/// Get the block that a navigator's inbound emails are filed under.
+	pub async fn get_inbox(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Option<NuttyId>, ContentRepositoryError> {
+		self.get_inbox_tx(&self.pool, navigator_id).await
+	}
+
+	/// Set the block that a navigator's inbound emails are filed under.
+	pub async fn set_inbox_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+		parent_id: &NuttyId,
+	) -> Result<(), ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		sqlx::query!(
+			r#"
+				INSERT INTO content.inboxes (navigator_id, parent_id)
+				VALUES ($1, $2)
+				ON CONFLICT (navigator_id) DO UPDATE
+				SET parent_id = EXCLUDED.parent_id
+			"#,
+			navigator_id.uuid(),
+			parent_id.uuid(),
+		)
+		.execute(executor)
+		.record_query("set_inbox")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Set the block that a navigator's inbound emails are filed under.
+	pub async fn set_inbox(
+		&self,
+		navigator_id: &NuttyId,
+		parent_id: &NuttyId,
+	) -> Result<(), ContentRepositoryError> {
+		self.set_inbox_tx(&self.pool, navigator_id, parent_id).await
+	}
+
+	/// Get the metadata of the page that a content block captured.
+	pub async fn get_unfurl_tx<'e, E>(
+		&self,
+		executor: E,
+		block_id: &NuttyId,
+	) -> Result<Option<Unfurl>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as!(
+			Unfurl,
+			r#"
+				SELECT url, title, description, site_name
+				FROM content.unfurls
+				WHERE block_id = $1
+			"#,
+			block_id.uuid(),
+		)
+		.fetch_optional(executor)
+		.record_query("get_unfurl")
+		.await?)
+	}
+
+	/// Get the metadata of the page that a content block captured.
+	pub async fn get_unfurl(
+		&self,
+		block_id: &NuttyId,
+	) -> Result<Option<Unfurl>, ContentRepositoryError> {
+		self.get_unfurl_tx(&self.pool, block_id).await
+	}
+
+	/// Save the metadata of the page that a content block captured.
+	pub async fn save_unfurl_tx<'e, E>(
+		&self,
+		executor: E,
+		block_id: &NuttyId,
+		unfurl: &Unfurl,
+	) -> Result<(), ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		sqlx::query!(
+			r#"
+				INSERT INTO content.unfurls (block_id, url, title, description, site_name)
+				VALUES ($1, $2, $3, $4, $5)
+				ON CONFLICT (block_id) DO UPDATE
+				SET url = EXCLUDED.url,
+					title = EXCLUDED.title,
+					description = EXCLUDED.description,
+					site_name = EXCLUDED.site_name
+			"#,
+			block_id.uuid(),
+			unfurl.url,
+			unfurl.title,
+			unfurl.description,
+			unfurl.site_name,
+		)
+		.execute(executor)
+		.record_query("save_unfurl")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Save the metadata of the page that a content block captured.
+	pub async fn save_unfurl(
+		&self,
+		block_id: &NuttyId,
+		unfurl: &Unfurl,
+	) -> Result<(), ContentRepositoryError> {
+		self.save_unfurl_tx(&self.pool, block_id, unfurl).await
+	}
+
+	/// Delete a content block's annotations.
+	pub async fn delete_annotations_tx<'e, E>(
+		&self,
+		executor: E,
+		block_id: &NuttyId,
+	) -> Result<(), ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		sqlx::query!(
+			r#"
+				DELETE FROM content.annotations
+				WHERE block_id = $1
+			"#,
+			block_id.uuid(),
+		)
+		.execute(executor)
+		.record_query("delete_annotations")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Insert block annotations, ignoring any that already exist.
+	pub async fn insert_annotations_tx<'e, E>(
+		&self,
+		executor: E,
+		annotations: &[BlockAnnotation],
+	) -> Result<(), ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let block_ids = annotations
+			.iter()
+			.map(|annotation| *annotation.block_id.uuid())
+			.collect::<Vec<_>>();
+		let kinds = annotations
+			.iter()
+			.map(|annotation| annotation.kind.as_str().to_string())
+			.collect::<Vec<_>>();
+		let messages = annotations
+			.iter()
+			.map(|annotation| annotation.message.clone())
+			.collect::<Vec<_>>();
+
+		sqlx::query!(
+			r#"
+				INSERT INTO content.annotations (block_id, kind, message)
+				SELECT * FROM UNNEST($1::uuid[], $2::varchar[], $3::text[])
+				ON CONFLICT (block_id, kind, message) DO NOTHING
+			"#,
+			&block_ids,
+			&kinds,
+			&messages,
+		)
+		.execute(executor)
+		.record_query("insert_annotations")
+		.await?;
+
+		Ok(())
+	}
+
+	/// List the annotations of the given content blocks.
+	pub async fn list_annotations_tx<'e, E>(
+		&self,
+		executor: E,
+		block_ids: &[NuttyId],
+	) -> Result<Vec<BlockAnnotation>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let block_ids = block_ids.iter().map(|id| *id.uuid()).collect::<Vec<_>>();
+
+		let records = sqlx::query!(
+			r#"
+				SELECT block_id, kind, message
+				FROM content.annotations
+				WHERE block_id = ANY($1)
+				ORDER BY block_id, kind, message
+			"#,
+			&block_ids,
+		)
+		.fetch_all(executor)
+		.record_query("list_annotations")
+		.await?;
+
+		records
+			.into_iter()
+			.map(|record| {
+				Ok(BlockAnnotation::new(
+					NuttyId::new(record.block_id),
+					record.kind.parse()?,
+					record.message,
+				))
+			})
+			.collect()
+	}
+
+	/// List the annotations of the given content blocks.
+	pub async fn list_annotations(
+		&self,
+		block_ids: &[NuttyId],
+	) -> Result<Vec<BlockAnnotation>, ContentRepositoryError> {
+		self.list_annotations_tx(&self.pool, block_ids).await
+	}
+
+	/// List the stats of the given content blocks.
+	pub async fn list_block_stats_tx<'e, E>(
+		&self,
+		executor: E,
+		block_ids: &[NuttyId],
+	) -> Result<Vec<BlockStats>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let block_ids = block_ids.iter().map(|id| *id.uuid()).collect::<Vec<_>>();
+
+		let records = sqlx::query!(
+			r#"
+				SELECT block_id, descendant_count, backlink_count
+				FROM content.block_stats
+				WHERE block_id = ANY($1)
+			"#,
+			&block_ids,
+		)
+		.fetch_all(executor)
+		.record_query("list_block_stats")
+		.await?;
+
+		Ok(records
+			.into_iter()
+			.map(|record| BlockStats {
+				block_id: NuttyId::new(record.block_id),
+				descendant_count: record.descendant_count,
+				backlink_count: record.backlink_count,
+			})
+			.collect())
+	}
+
+	/// List the stats of the given content blocks.
+	pub async fn list_block_stats(
+		&self,
+		block_ids: &[NuttyId],
+	) -> Result<Vec<BlockStats>, ContentRepositoryError> {
+		self.list_block_stats_tx(&self.pool, block_ids).await
+	}
+
+	/// List the changes to a navigator's blocks, and to the links from them,
+	/// after a cursor: saves and deletions alike, oldest first.
+	pub async fn list_changes_tx<'e, E>(
+		&self,
+		executor: E,
+		owner_id: &NuttyId,
+		after: Option<&SyncCursor>,
+		limit: i64,
+	) -> Result<Vec<Change>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let records = sqlx::query!(
+			r#"
+				SELECT
+					kind AS "kind!",
+					id AS "id!",
+					changed_at AS "changed_at!",
+					deleted AS "deleted!"
+				FROM (
+					SELECT 'block' AS kind, b.id, b.updated_at AS changed_at, FALSE AS deleted
+					FROM content.blocks b
+					WHERE b.owner_id = $1
+					UNION ALL
+					SELECT 'block', t.block_id, t.deleted_at, TRUE
+					FROM content.block_tombstones t
+					WHERE t.owner_id = $1
+					UNION ALL
+					SELECT 'link', k.id, k.created_at, FALSE
+					FROM content.links k
+					JOIN content.blocks s ON s.id = k.source_id
+					WHERE s.owner_id = $1
+					UNION ALL
+					SELECT 'link', t.link_id, t.deleted_at, TRUE
+					FROM content.link_tombstones t
+					WHERE t.owner_id = $1
+				) changes
+				WHERE $2::timestamptz IS NULL OR (changed_at, id) > ($2, $3)
+				ORDER BY changed_at, id
+				LIMIT $4
+			"#,
+			owner_id.uuid(),
+			after.map(|cursor| cursor.changed_at),
+			after.map(|cursor| cursor.id),
+			limit,
+		)
+		.fetch_all(executor)
+		.record_query("list_changes")
+		.await?;
+
+		Ok(records
+			.into_iter()
+			.filter_map(|record| {
+				Some(Change {
+					kind: ChangeKind::parse(&record.kind)?,
+					id: NuttyId::new(record.id),
+					changed_at: DateTimeRfc3339::new(record.changed_at.fixed_offset()),
+					deleted: record.deleted,
+				})
+			})
+			.collect())
+	}
+
+	/// List the outline of a content block and its descendants, depth
+	/// first, with at most `limit` children of each block. The root's
+	/// children may start after one of them, to page through them.
+	pub async fn list_outline_tx<'e, E>(
+		&self,
+		executor: E,
+		root_id: &DissociatedNuttyId,
+		after_id: Option<&DissociatedNuttyId>,
+		limit: i64,
+	) -> Result<Vec<OutlineEntry>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		// Each block's path of sort keys orders the outline depth first. The
+		// space before its ID sorts below any index digit, so that an index
+		// sorts before those it's a prefix of.
+		let records = sqlx::query!(
+			r#"
+				WITH RECURSIVE outline AS (
+					SELECT id, parent_id, inherit_access, 0 AS depth, ARRAY[]::text[] AS path
+					FROM content.blocks
+					WHERE nutty_id = $1
+					UNION ALL
+					SELECT id, parent_id, inherit_access, depth, path FROM (
+						SELECT
+							c.id,
+							c.parent_id,
+							c.inherit_access,
+							o.depth + 1 AS depth,
+							o.path || (c.f_index || ' ' || c.id::text) AS path,
+							ROW_NUMBER() OVER (
+								PARTITION BY c.parent_id
+								ORDER BY c.f_index COLLATE "C", c.id
+							) AS position
+						FROM content.blocks c
+						JOIN outline o ON c.parent_id = o.id
+						WHERE o.depth > 0
+						OR $2::text IS NULL
+						OR (c.f_index COLLATE "C", c.id) > (
+							SELECT f_index COLLATE "C", id FROM content.blocks WHERE nutty_id = $2
+						)
+					) children
+					WHERE position <= $3
+				)
+				SELECT
+					o.id AS "id!",
+					o.parent_id,
+					o.inherit_access AS "inherit_access!",
+					o.depth AS "depth!",
+					LEFT(SPLIT_PART(COALESCE(
+						CASE b.content->>'kind'
+							WHEN 'Page' THEN b.content->>'title'
+							WHEN 'Heading' THEN b.content->>'markdown'
+							WHEN 'Paragraph' THEN b.content->>'markdown'
+							WHEN 'Todo' THEN b.content->>'markdown'
+						END,
+						''
+					), E'\n', 1), $4) AS "title!",
+					(SELECT COUNT(*) FROM content.blocks c WHERE c.parent_id = o.id) AS "child_count!"
+				FROM outline o
+				JOIN content.blocks b ON b.id = o.id
+				ORDER BY o.path COLLATE "C"
+			"#,
+			root_id.nid(),
+			after_id.map(|after_id| after_id.nid()),
+			limit,
+			OutlineEntry::MAX_TITLE_CHARS,
+		)
+		.fetch_all(executor)
+		.record_query("list_outline")
+		.await?;
+
+		Ok(records
+			.into_iter()
+			.map(|record| OutlineEntry {
+				block_id: NuttyId::new(record.id),
+				parent_id: record.parent_id.map(NuttyId::new),
+				title: record.title,
+				depth: record.depth,
+				child_count: record.child_count,
+				inherit_access: record.inherit_access,
+			})
+			.collect())
+	}
+
+	/// List the outline of a content block and its descendants, depth first,
+	/// with at most `limit` children of each block.
+	pub async fn list_outline(
+		&self,
+		root_id: &DissociatedNuttyId,
+		after_id: Option<&DissociatedNuttyId>,
+		limit: i64,
+	) -> Result<Vec<OutlineEntry>, ContentRepositoryError> {
+		self
+			.list_outline_tx(&self.pool, root_id, after_id, limit)
+			.await
+	}
+
+	/// List the checksums of a content block and its descendants. Blocks
+	/// saved before content hashes were stored are hashed on the fly.
+	pub async fn list_block_checksums_tx<'e, E>(
+		&self,
+		executor: E,
+		root_id: &DissociatedNuttyId,
+	) -> Result<Vec<BlockChecksum>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let records = sqlx::query!(
+			r#"
+				WITH RECURSIVE subtree AS (
+					SELECT id FROM content.blocks WHERE nutty_id = $1
+					UNION ALL
+					SELECT c.id FROM content.blocks c
+					JOIN subtree s ON c.parent_id = s.id
+				)
+				SELECT
+					b.id,
+					b.content_hash,
+					CASE WHEN b.content_hash IS NULL THEN b.content END AS content,
+					b.updated_at
+				FROM content.blocks b
+				JOIN subtree USING (id)
+				ORDER BY b.id
+			"#,
+			root_id.nid(),
+		)
+		.fetch_all(executor)
+		.record_query("list_block_checksums")
+		.await?;
+
+		records
+			.into_iter()
+			.map(|record| {
+				let content_hash = match record.content_hash {
+					Some(content_hash) => content_hash,
+					None => canonical_hash(&record.content)
+						.map_err(ContentBlockError::CanonicalizationError)?,
+				};
+
+				Ok(BlockChecksum {
+					block_id: NuttyId::new(record.id),
+					content_hash,
+					updated_at: DateTimeRfc3339::new(record.updated_at.fixed_offset()),
+				})
+			})
+			.collect()
+	}
+
+	/// List the checksums of a content block and its descendants.
+	pub async fn list_block_checksums(
+		&self,
+		root_id: &DissociatedNuttyId,
+	) -> Result<Vec<BlockChecksum>, ContentRepositoryError> {
+		self.list_block_checksums_tx(&self.pool, root_id).await
+	}
+
+	/// Recount every block's stats from scratch, and repair those that had
+	/// drifted. Returns the repaired stats.
+	pub async fn repair_block_stats_tx<'e, E>(
+		&self,
+		executor: E,
+	) -> Result<Vec<BlockStats>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let records = sqlx::query!(
+			r#"
+				INSERT INTO content.block_stats (block_id, descendant_count, backlink_count)
+				SELECT c.block_id, c.descendant_count, c.backlink_count
+				FROM content.compute_block_stats() c
+				ON CONFLICT (block_id) DO UPDATE
+				SET
+					descendant_count = EXCLUDED.descendant_count,
+					backlink_count = EXCLUDED.backlink_count
+				WHERE block_stats.descendant_count <> EXCLUDED.descendant_count
+					OR block_stats.backlink_count <> EXCLUDED.backlink_count
+				RETURNING block_id, descendant_count, backlink_count
+			"#,
+		)
+		.fetch_all(executor)
+		.record_query("repair_block_stats")
+		.await?;
+
+		Ok(records
+			.into_iter()
+			.map(|record| BlockStats {
+				block_id: NuttyId::new(record.block_id),
+				descendant_count: record.descendant_count,
+				backlink_count: record.backlink_count,
+			})
+			.collect())
+	}
+
+	/// Count the content blocks.
+	pub async fn count_content_blocks_tx<'e, E>(
+		&self,
+		executor: E,
+	) -> Result<i64, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let count = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM content.blocks"#)
+			.fetch_one(executor)
+			.record_query("count_content_blocks")
+			.await?;
+
+		Ok(count)
+	}
+
+	/// Record revisions of content blocks, made by the given navigator.
+	/// Returns the Nutty IDs of the revisions, in order.
+	pub async fn record_block_revisions_tx<'e, E>(
+		&self,
+		executor: E,
+		revisions: &[BlockRevision],
+		revised_by: &NuttyId,
+	) -> Result<Vec<NuttyId>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let ids: Vec<_> = revisions.iter().map(|_| NuttyId::now()).collect();
+
+		let contents = |content: fn(&BlockRevision) -> &BlockContent| {
+			revisions
+				.iter()
+				.map(|revision| serde_json::to_value(content(revision)))
+				.collect::<Result<Vec<_>, _>>()
+				.map_err(ContentBlockError::SerializationError)
+		};
+
+		sqlx::query!(
+			r#"
+				INSERT INTO content.block_revisions
+					(id, nutty_id, block_id, previous_content, content, revised_by)
+				SELECT *, $6::uuid FROM UNNEST($1::uuid[], $2::varchar[], $3::uuid[], $4::jsonb[], $5::jsonb[])
+			"#,
+			&ids.iter().map(|id| *id.uuid()).collect::<Vec<_>>(),
+			&ids.iter().map(|id| id.nid()).collect::<Vec<_>>(),
+			&revisions
+				.iter()
+				.map(|revision| *revision.block_id.uuid())
+				.collect::<Vec<_>>(),
+			&contents(|revision| &revision.previous_content)?,
+			&contents(|revision| &revision.content)?,
+			revised_by.uuid(),
+		)
+		.execute(executor)
+		.record_query("record_block_revisions")
+		.await?;
+
+		Ok(ids)
+	}
+
+	/// Get a revision of a content block by its Nutty ID.
+	pub async fn get_block_revision_tx<'e, E>(
+		&self,
+		executor: E,
+		block_id: &DissociatedNuttyId,
+		revision_id: &DissociatedNuttyId,
+	) -> Result<Option<BlockRevision>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let row = sqlx::query!(
+			r#"
+				SELECT r.block_id, r.previous_content, r.content
+				FROM content.block_revisions r
+				JOIN content.blocks b ON b.id = r.block_id
+				WHERE r.nutty_id = $1 AND b.nutty_id = $2
+			"#,
+			revision_id.nid(),
+			block_id.nid(),
+		)
+		.fetch_optional(executor)
+		.record_query("get_block_revision")
+		.await?;
+
+		let Some(row) = row else {
+			return Ok(None);
+		};
+
+		let content =
+			|value| serde_json::from_value(value).map_err(ContentBlockError::DeserializationError);
+
+		Ok(Some(BlockRevision {
+			block_id: NuttyId::new(row.block_id),
+			previous_content: content(row.previous_content)?,
+			content: content(row.content)?,
+		}))
+	}
+
+	/// Get a revision of a content block by its Nutty ID.
+	pub async fn get_block_revision(
+		&self,
+		block_id: &DissociatedNuttyId,
+		revision_id: &DissociatedNuttyId,
+	) -> Result<Option<BlockRevision>, ContentRepositoryError> {
+		self
+			.get_block_revision_tx(&self.pool, block_id, revision_id)
+			.await
+	}
+
+	/// Publish a revision of a content block. Returns the Nutty ID of the
+	/// revision, or nothing if the block has no such revision.
+	pub async fn publish_revision_tx<'e, E>(
+		&self,
+		executor: E,
+		block_id: &DissociatedNuttyId,
+		revision_id: &DissociatedNuttyId,
+	) -> Result<Option<NuttyId>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let published_id = sqlx::query_scalar!(
+			r#"
+				UPDATE content.blocks b
+				SET published_revision_id = r.id
+				FROM content.block_revisions r
+				WHERE r.block_id = b.id AND r.nutty_id = $1 AND b.nutty_id = $2
+				RETURNING r.id
+			"#,
+			revision_id.nid(),
+			block_id.nid(),
+		)
+		.fetch_optional(executor)
+		.record_query("publish_revision")
+		.await?;
+
+		Ok(published_id.map(NuttyId::new))
+	}
+
+	/// Get the published revision of a content block, if it has one.
+	pub async fn get_published_revision_tx<'e, E>(
+		&self,
+		executor: E,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Option<BlockRevision>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let row = sqlx::query!(
+			r#"
+				SELECT r.block_id, r.previous_content, r.content
+				FROM content.blocks b
+				JOIN content.block_revisions r ON r.id = b.published_revision_id
+				WHERE b.nutty_id = $1
+			"#,
+			block_id.nid(),
+		)
+		.fetch_optional(executor)
+		.record_query("get_published_revision")
+		.await?;
+
+		let Some(row) = row else {
+			return Ok(None);
+		};
+
+		let content =
+			|value| serde_json::from_value(value).map_err(ContentBlockError::DeserializationError);
+
+		Ok(Some(BlockRevision {
+			block_id: NuttyId::new(row.block_id),
+			previous_content: content(row.previous_content)?,
+			content: content(row.content)?,
+		}))
+	}
+
+	/// Get the published revision of a content block, if it has one.
+	pub async fn get_published_revision(
+		&self,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Option<BlockRevision>, ContentRepositoryError> {
+		self.get_published_revision_tx(&self.pool, block_id).await
+	}
+
+	/// List the published content of the given content blocks that a
+	/// navigator can't edit, which they see instead of the drafts. Blocks
+	/// without a published revision are left out.
+	pub async fn list_published_contents_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+		ids: &[NuttyId],
+	) -> Result<HashMap<NuttyId, BlockContent>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let ids: Vec<Uuid> = ids.iter().map(|id| *id.uuid()).collect();
+
+		let rows = sqlx::query!(
+			r#"
+				SELECT b.id, r.content
+				FROM content.blocks b
+				JOIN content.block_revisions r ON r.id = b.published_revision_id
+				WHERE b.id = ANY($2)
+					AND NOT content.navigator_can_write($1, b.id, b.parent_id, b.owner_id, b.inherit_access)
+			"#,
+			navigator_id.uuid(),
+			&ids,
+		)
+		.fetch_all(executor)
+		.record_query("list_published_contents")
+		.await?;
+
+		rows
+			.into_iter()
+			.map(|row| {
+				let content = serde_json::from_value(row.content)
+					.map_err(ContentBlockError::DeserializationError)?;
+
+				Ok((NuttyId::new(row.id), content))
+			})
+			.collect()
+	}
+
+	/// List the published content of the given content blocks that a
+	/// navigator can't edit.
+	pub async fn list_published_contents(
+		&self,
+		navigator_id: &NuttyId,
+		ids: &[NuttyId],
+	) -> Result<HashMap<NuttyId, BlockContent>, ContentRepositoryError> {
+		self
+			.list_published_contents_tx(&self.pool, navigator_id, ids)
+			.await
+	}
+
+	/// Retire a content block's current slug, so that it redirects to the
+	/// block's next one.
+	pub async fn retire_slug_tx<'e, E>(
+		&self,
+		executor: E,
+		block_id: &NuttyId,
+	) -> Result<(), ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		sqlx::query!(
+			r#"
+				UPDATE content.slugs
+				SET is_current = FALSE
+				WHERE block_id = $1 AND is_current
+			"#,
+			block_id.uuid(),
+		)
+		.execute(executor)
+		.record_query("retire_slug")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Claim a slug as a content block's current one. A navigator may take
+	/// back their own retired slugs, but not anyone else's. Returns whether
+	/// the slug was claimed.
+	pub async fn claim_slug_tx<'e, E>(
+		&self,
+		executor: E,
+		slug: &Slug,
+		block_id: &NuttyId,
+		navigator_id: &NuttyId,
+	) -> Result<bool, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let claimed = sqlx::query!(
+			r#"
+				INSERT INTO content.slugs (path, block_id, navigator_id)
+				VALUES ($1, $2, $3)
+				ON CONFLICT (path) DO UPDATE
+				SET block_id = EXCLUDED.block_id, is_current = TRUE
+				WHERE slugs.navigator_id = EXCLUDED.navigator_id AND NOT slugs.is_current
+			"#,
+			slug.as_str(),
+			block_id.uuid(),
+			navigator_id.uuid(),
+		)
+		.execute(executor)
+		.record_query("claim_slug")
+		.await?;
+
+		Ok(claimed.rows_affected() > 0)
+	}
+
+	/// Resolve a slug to the content block it leads to, if any.
+	pub async fn resolve_slug_tx<'e, E>(
+		&self,
+		executor: E,
+		slug: &Slug,
+	) -> Result<Option<SlugTarget>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let row = sqlx::query!(
+			r#"
+				SELECT s.block_id, s.is_current, c.path AS "current_path?"
+				FROM content.slugs s
+				LEFT JOIN content.slugs c ON c.block_id = s.block_id AND c.is_current
+				WHERE s.path = $1
+			"#,
+			slug.as_str(),
+		)
+		.fetch_optional(executor)
+		.record_query("resolve_slug")
+		.await?;
+
+		let Some(row) = row else {
+			return Ok(None);
+		};
+
+		let moved_to = match (row.is_current, row.current_path) {
+			(false, Some(path)) => Some(Slug::parse(&path)?),
+			_ => None,
+		};
+
+		Ok(Some(SlugTarget {
+			block_id: NuttyId::new(row.block_id),
+			moved_to,
+		}))
+	}
+
+	/// Resolve a slug to the content block it leads to, if any.
+	pub async fn resolve_slug(
+		&self,
+		slug: &Slug,
+	) -> Result<Option<SlugTarget>, ContentRepositoryError> {
+		self.resolve_slug_tx(&self.pool, slug).await
+	}
+
+	/// Get a navigator's site settings, if they have any. Navigation roots
+	/// that were deleted are left out.
+	pub async fn get_site_settings_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+	) -> Result<Option<SiteSettings>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let row = sqlx::query!(
+			r#"
+				SELECT
+					s.title,
+					s.description,
+					s.accent_color,
+					ARRAY(
+						SELECT b.id
+						FROM unnest(s.navigation_root_ids) WITH ORDINALITY AS r(id, position)
+						JOIN content.blocks b ON b.id = r.id
+						ORDER BY r.position
+					) AS "navigation_root_ids!"
+				FROM content.site_settings s
+				WHERE s.navigator_id = $1
+			"#,
+			navigator_id.uuid(),
+		)
+		.fetch_optional(executor)
+		.record_query("get_site_settings")
+		.await?;
+
+		let Some(row) = row else {
+			return Ok(None);
+		};
+
+		Ok(Some(SiteSettings {
+			title: row.title,
+			description: row.description,
+			accent_color: row
+				.accent_color
+				.as_deref()
+				.map(AccentColor::parse)
+				.transpose()?,
+			navigation_root_ids: row
+				.navigation_root_ids
+				.into_iter()
+				.map(NuttyId::new)
+				.collect(),
+		}))
+	}
+
+	/// Get a navigator's site settings, if they have any.
+	pub async fn get_site_settings(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Option<SiteSettings>, ContentRepositoryError> {
+		self.get_site_settings_tx(&self.pool, navigator_id).await
+	}
+
+	/// Insert or replace a navigator's site settings.
+	pub async fn upsert_site_settings_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+		settings: &SiteSettings,
+	) -> Result<(), ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let navigation_root_ids: Vec<Uuid> = settings
+			.navigation_root_ids
+			.iter()
+			.map(|id| *id.uuid())
+			.collect();
+
+		sqlx::query!(
+			r#"
+				INSERT INTO content.site_settings (navigator_id, title, description, accent_color, navigation_root_ids)
+				VALUES ($1, $2, $3, $4, $5)
+				ON CONFLICT (navigator_id) DO UPDATE
+				SET
+					title = EXCLUDED.title,
+					description = EXCLUDED.description,
+					accent_color = EXCLUDED.accent_color,
+					navigation_root_ids = EXCLUDED.navigation_root_ids
+			"#,
+			navigator_id.uuid(),
+			settings.title,
+			settings.description,
+			settings.accent_color.as_ref().map(AccentColor::as_str),
+			&navigation_root_ids,
+		)
+		.execute(executor)
+		.record_query("upsert_site_settings")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Delete a navigator's site settings. Returns whether they had any.
+	pub async fn delete_site_settings_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+	) -> Result<bool, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let deleted = sqlx::query!(
+			"DELETE FROM content.site_settings WHERE navigator_id = $1",
+			navigator_id.uuid(),
+		)
+		.execute(executor)
+		.record_query("delete_site_settings")
+		.await?;
+
+		Ok(deleted.rows_affected() > 0)
+	}
+
+	/// Delete a navigator's site settings. Returns whether they had any.
+	pub async fn delete_site_settings(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<bool, ContentRepositoryError> {
+		self.delete_site_settings_tx(&self.pool, navigator_id).await
+	}
+
+	/// List the blocks that a navigator owns, grouped by top-level ancestor,
+	/// most recently modified group first. Each owned block's lineage is
+	/// walked up in one recursive query.
+	pub async fn list_owned_blocks_by_root_tx<'e, E>(
+		&self,
+		executor: E,
+		owner_id: &NuttyId,
+	) -> Result<Vec<RootBlockGroup>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let rows = sqlx::query!(
+			r#"
+				WITH RECURSIVE lineage AS (
+					SELECT b.id AS block_id, b.id, b.parent_id
+					FROM content.blocks b
+					WHERE b.owner_id = $1
+					UNION ALL
+					SELECT l.block_id, p.id, p.parent_id
+					FROM lineage l
+					JOIN content.blocks p ON p.id = l.parent_id
+				)
+				SELECT
+					l.id AS "root_id!",
+					COUNT(*) AS "block_count!",
+					MAX(b.updated_at) AS "last_modified_at!",
+					ARRAY_AGG(b.id ORDER BY b.updated_at DESC, b.id) AS "block_ids!"
+				FROM lineage l
+				JOIN content.blocks b ON b.id = l.block_id
+				WHERE l.parent_id IS NULL
+				GROUP BY l.id
+				ORDER BY MAX(b.updated_at) DESC, l.id
+			"#,
+			owner_id.uuid(),
+		)
+		.fetch_all(executor)
+		.record_query("list_owned_blocks_by_root")
+		.await?;
+
+		Ok(rows
+			.into_iter()
+			.map(|row| RootBlockGroup {
+				root_id: NuttyId::new(row.root_id),
+				block_count: row.block_count,
+				last_modified_at: DateTimeRfc3339::new(row.last_modified_at.fixed_offset()),
+				block_ids: row.block_ids.into_iter().map(NuttyId::new).collect(),
+			})
+			.collect())
+	}
+
+	/// List the blocks that a navigator owns, grouped by top-level ancestor,
+	/// most recently modified group first.
+	pub async fn list_owned_blocks_by_root(
+		&self,
+		owner_id: &NuttyId,
+	) -> Result<Vec<RootBlockGroup>, ContentRepositoryError> {
+		self
+			.list_owned_blocks_by_root_tx(&self.pool, owner_id)
+			.await
+	}
+
+	/// Check if a content block has been hidden by a moderator, itself or
+	/// through a hidden ancestor. Blocks that don't exist aren't hidden.
+	pub async fn is_hidden_tx<'e, E>(
+		&self,
+		executor: E,
+		nutty_id: &DissociatedNuttyId,
+	) -> Result<bool, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let record = sqlx::query!(
+			r#"
+				SELECT EXISTS (
+					SELECT 1 FROM content.blocks
+					WHERE nutty_id = $1 AND content.is_hidden(id)
+				) AS "exists!"
+			"#,
+			nutty_id.nid(),
+		)
+		.fetch_one(executor)
+		.record_query("is_hidden")
+		.await?;
+
+		Ok(record.exists)
+	}
+
+	/// Check if a content block has been hidden by a moderator, itself or
+	/// through a hidden ancestor.
+	pub async fn is_hidden(
+		&self,
+		nutty_id: &DissociatedNuttyId,
+	) -> Result<bool, ContentRepositoryError> {
+		self.is_hidden_tx(&self.pool, nutty_id).await
+	}
+
+	/// List which of the given content blocks a moderator has hidden directly,
+	/// rather than through an ancestor.
+	pub async fn list_hidden_ids_tx<'e, E>(
+		&self,
+		executor: E,
+		ids: &[NuttyId],
+	) -> Result<Vec<NuttyId>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let ids: Vec<Uuid> = ids.iter().map(|id| *id.uuid()).collect();
+
+		let rows = sqlx::query!(
+			r#"
+				SELECT id FROM content.blocks
+				WHERE id = ANY($1) AND moderation_status = 'hidden'
+			"#,
+			&ids,
+		)
+		.fetch_all(executor)
+		.record_query("list_hidden_ids")
+		.await?;
+
+		Ok(rows.into_iter().map(|row| NuttyId::new(row.id)).collect())
+	}
+
+	/// List which of the given content blocks a moderator has hidden directly,
+	/// rather than through an ancestor.
+	pub async fn list_hidden_ids(
+		&self,
+		ids: &[NuttyId],
+	) -> Result<Vec<NuttyId>, ContentRepositoryError> {
+		self.list_hidden_ids_tx(&self.pool, ids).await
+	}
+
+	/// List which of the given content blocks a navigator can read, by the
+	/// same rules as the access check, in one query.
+	pub async fn list_readable_ids_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+		ids: &[NuttyId],
+	) -> Result<Vec<NuttyId>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let ids: Vec<Uuid> = ids.iter().map(|id| *id.uuid()).collect();
+
+		let rows = sqlx::query!(
+			r#"
+				SELECT id FROM content.blocks
+				WHERE id = ANY($2)
+					AND content.navigator_can_read($1, id, parent_id, owner_id, inherit_access)
+			"#,
+			navigator_id.uuid(),
+			&ids,
+		)
+		.fetch_all(executor)
+		.record_query("list_readable_ids")
+		.await?;
+
+		Ok(rows.into_iter().map(|row| NuttyId::new(row.id)).collect())
+	}
+
+	/// List which of the given content blocks a navigator can read.
+	pub async fn list_readable_ids(
+		&self,
+		navigator_id: &NuttyId,
+		ids: &[NuttyId],
+	) -> Result<Vec<NuttyId>, ContentRepositoryError> {
+		self
+			.list_readable_ids_tx(&self.pool, navigator_id, ids)
+			.await
+	}
+
+	/// List which of the given content blocks a navigator can edit: blocks
+	/// they own or can write, which aren't hidden, in one query.
+	pub async fn list_editable_ids_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+		ids: &[NuttyId],
+	) -> Result<Vec<NuttyId>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let ids: Vec<Uuid> = ids.iter().map(|id| *id.uuid()).collect();
+
+		let rows = sqlx::query!(
+			r#"
+				SELECT id FROM content.blocks
+				WHERE id = ANY($2)
+					AND NOT content.is_hidden(id)
+					AND (
+						owner_id = $1
+						OR content.navigator_can_write($1, id, parent_id, owner_id, inherit_access)
+					)
+			"#,
+			navigator_id.uuid(),
+			&ids,
+		)
+		.fetch_all(executor)
+		.record_query("list_editable_ids")
+		.await?;
+
+		Ok(rows.into_iter().map(|row| NuttyId::new(row.id)).collect())
+	}
+
+	/// Check if a navigator has been blocked by another.
+	pub async fn is_blocked_by_tx<'e, E>(
+		&self,
+		executor: E,
+		blocker_id: &NuttyId,
+		navigator_id: &NuttyId,
+	) -> Result<bool, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let record = sqlx::query!(
+			r#"
+				SELECT EXISTS (
+					SELECT 1 FROM auth.navigator_blocks
+					WHERE blocker_id = $1 AND blocked_id = $2
+				) AS "exists!"
+			"#,
+			blocker_id.uuid(),
+			navigator_id.uuid(),
+		)
+		.fetch_one(executor)
+		.record_query("is_blocked_by")
+		.await?;
+
+		Ok(record.exists)
+	}
+
+	/// Check if a navigator has been blocked by another.
+	pub async fn is_blocked_by(
+		&self,
+		blocker_id: &NuttyId,
+		navigator_id: &NuttyId,
+	) -> Result<bool, ContentRepositoryError> {
+		self
+			.is_blocked_by_tx(&self.pool, blocker_id, navigator_id)
+			.await
+	}
+
+	/// List the translations of a content block, including itself, ordered
+	/// by language. A block without a language has no translations.
+	pub async fn list_translations_tx<'e, E>(
+		&self,
+		executor: E,
+		block_id: &NuttyId,
+	) -> Result<Vec<Translation>, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let records = sqlx::query!(
+			r#"
+				SELECT t.block_id, b.language AS "lang!"
+				FROM content.block_translations t
+				JOIN content.blocks b ON b.id = t.block_id
+				WHERE t.group_id = (
+					SELECT group_id FROM content.block_translations
+					WHERE block_id = $1
+				)
+				AND b.language IS NOT NULL
+				ORDER BY b.language
+			"#,
+			block_id.uuid(),
+		)
+		.fetch_all(executor)
+		.record_query("list_translations")
+		.await?;
+
+		records
+			.into_iter()
+			.map(|record| {
+				Ok(Translation {
+					block_id: NuttyId::new(record.block_id),
+					lang: Lang::parse(&record.lang)?,
+				})
+			})
+			.collect()
+	}
+
+	/// List the translations of a content block, including itself.
+	pub async fn list_translations(
+		&self,
+		block_id: &NuttyId,
+	) -> Result<Vec<Translation>, ContentRepositoryError> {
+		self.list_translations_tx(&self.pool, block_id).await
+	}
+
+	/// Lock the translations of a content block, if it has any, so that no
+	/// other transaction sets a language among them until this one ends.
+	pub async fn lock_translations_tx<'e, E>(
+		&self,
+		executor: E,
+		block_id: &NuttyId,
+	) -> Result<(), ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		sqlx::query!(
+			r#"
+				SELECT block_id FROM content.block_translations
+				WHERE group_id = (
+					SELECT group_id FROM content.block_translations
+					WHERE block_id = $1
+				)
+				FOR UPDATE
+			"#,
+			block_id.uuid(),
+		)
+		.fetch_all(executor)
+		.record_query("lock_translations")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Set the language of a content block, adding it to the translations
+	/// of another block if given. Otherwise, it stays among its current
+	/// translations, if any. Its descendants are searched in the language
+	/// too, unless they're translations themselves. Lock the translations
+	/// that it joins first, since a language is only taken once among them.
+	pub async fn set_translation_tx<'e, E>(
+		&self,
+		executor: E,
+		block_id: &NuttyId,
+		lang: &Lang,
+		translation_of: Option<&NuttyId>,
+	) -> Result<(), ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let updated = sqlx::query_scalar!(
+			r#"
+				WITH RECURSIVE target AS (
+					SELECT COALESCE(
+						(SELECT group_id FROM content.block_translations WHERE block_id = $2),
+						(SELECT group_id FROM content.block_translations WHERE block_id = $1),
+						$3
+					) AS group_id
+				),
+				translation AS (
+					INSERT INTO content.block_translations (block_id, group_id)
+					SELECT $1, group_id FROM target
+					WHERE NOT EXISTS (
+						SELECT 1 FROM content.block_translations t
+						JOIN content.blocks b ON b.id = t.block_id
+						WHERE t.group_id = (SELECT group_id FROM target)
+							AND t.block_id <> $1
+							AND b.language = $4
+					)
+					ON CONFLICT (block_id) DO UPDATE
+					SET group_id = EXCLUDED.group_id
+					RETURNING block_id
+				),
+				subtree AS (
+					SELECT id FROM content.blocks WHERE id = $1
+					UNION ALL
+					SELECT c.id FROM content.blocks c
+					JOIN subtree s ON c.parent_id = s.id
+					WHERE c.id NOT IN (SELECT block_id FROM content.block_translations)
+				)
+				UPDATE content.blocks
+				SET language = $4
+				WHERE id IN (SELECT id FROM subtree)
+					AND EXISTS (SELECT 1 FROM translation)
+				RETURNING id
+			"#,
+			block_id.uuid(),
+			translation_of.map(|id| *id.uuid()),
+			Uuid::now_v7(),
+			lang.as_str(),
+		)
+		.fetch_all(executor)
+		.record_query("set_translation")
+		.await?;
+
+		match updated.is_empty() {
+			true => Err(ContentRepositoryError::TranslationTaken),
+			false => Ok(()),
+		}
+	}
+
+	/// Set the language of a content block, adding it to the translations
+	/// of another block if given.
+	pub async fn set_translation(
+		&self,
+		block_id: &NuttyId,
+		lang: &Lang,
+		translation_of: Option<&NuttyId>,
+	) -> Result<(), ContentRepositoryError> {
+		self
+			.set_translation_tx(&self.pool, block_id, lang, translation_of)
+			.await
+	}
+
+	/// Remove a content block from its translations. It keeps its language,
+	/// to be searched in. Returns whether it had any.
+	pub async fn delete_translation_tx<'e, E>(
+		&self,
+		executor: E,
+		block_id: &NuttyId,
+	) -> Result<bool, ContentRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let result = sqlx::query!(
+			r#"
+				DELETE FROM content.block_translations
+				WHERE block_id = $1
+			"#,
+			block_id.uuid(),
+		)
+		.execute(executor)
+		.record_query("delete_translation")
+		.await?;
+
+		Ok(result.rows_affected() > 0)
+	}
+
+	/// Remove a content block from its translations.
+	pub async fn delete_translation(
+		&self,
+		block_id: &NuttyId,
+	) -> Result<bool, ContentRepositoryError> {
+		self.delete_translation_tx(&self.pool, block_id).await
+	}
+}
+
+impl Repository for ContentRepository {
+	fn pool(&self) -> &sqlx::Pool<Postgres> {
+		&self.pool
+	}
+
+	fn retry_policy(&self) -> RetryPolicy {
+		self.retry_policy
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum ContentRepositoryError {
+	#[error("Unable to query content blocks: {0}")]
+	QueryFailed(#[from] sqlx::error::Error),
+
+	#[error("Invalid content block builder state: {0}")]
+	InvalidContentBlockBuilder(#[from] ContentBlockBuilderError),
+
+	#[error("Invalid content block: {0}")]
+	InvalidContentBlock(#[from] ContentBlockError),
+
+	#[error("Invalid index: {0}")]
+	InvalidFractionalIndex(#[from] FractionalIndexError),
+
+	#[error("Invalid block date: {0}")]
+	InvalidBlockDate(#[from] BlockDateError),
+
+	#[error("Invalid block annotation: {0}")]
+	InvalidBlockAnnotation(#[from] BlockAnnotationError),
+
+	#[error("Invalid slug: {0}")]
+	InvalidSlug(#[from] SlugError),
+
+	#[error("Invalid site settings: {0}")]
+	InvalidSiteSettings(#[from] SiteSettingsError),
+
+	#[error("Property name already taken")]
+	PropertyNameTaken,
+
+	#[error("Invalid translation: {0}")]
+	InvalidTranslation(#[from] TranslationError),
+
+	#[error("Translation already exists in this language")]
+	TranslationTaken,
+
+	#[error("Unknown owner")]
+	UnknownOwner,
+
+	#[error("Access already requested")]
+	AccessRequestPending,
+}
+
+#[cfg(test)]
+mod tests {
+	use sqlx::Pool;
+	use sqlx::Postgres;
+	use sqlx::postgres::PgPoolOptions;
+
+	use super::*;
+	use crate::models::BlockContent;
+	use crate::models::ContentBlock;
+	use crate::models::ContentLink;
+	use crate::models::DissociatedNuttyId;
+	use crate::models::FractionalIndex;
+	use crate::models::NuttyId;
+
+	async fn connect_to_test_database() -> Pool<Postgres> {
+		let database_url = std::env::var("DATABASE_URL").unwrap();
+
+		PgPoolOptions::new()
+			.max_connections(5)
+			.connect(&database_url)
+			.await
+			.expect("Failed to connect to test database")
+	}
+
+	#[tokio::test]
+	async fn test_content_block_operations() {
+		// Arrange: Create a repository.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+
+		// Arrange: Create a test content block.
+		let test_block = ContentBlock::now(
+			None,
+			FractionalIndex::start(),
+			BlockContent::Page {
+				title: "Test Page".to_string(),
+			},
+		);
+
+		// Act: Save the test content block.
+		let saved_block = repo
+			.upsert_content_block(test_block.clone())
+			.await
+			.expect("Failed to save content block");
+
+		// Assert: The saved content block matches the original.
+		assert_eq!(saved_block.nutty_id(), test_block.nutty_id());
+		assert_eq!(saved_block.parent_id, test_block.parent_id);
+		assert!(matches!(&saved_block.content, BlockContent::Page { title } if title == "Test Page"));
+
+		// Act: Query the content block.
+		let retrieved = repo
+			.get_content_block(&(*saved_block.nutty_id()).into())
+			.await
+			.expect("Failed to get content block")
+			.expect("Content block not found");
+
+		// Assert: The retrieved content block matches the original.
+		assert_eq!(retrieved.nutty_id(), test_block.nutty_id());
+		assert_eq!(retrieved.parent_id, test_block.parent_id);
+		assert!(matches!(retrieved.content, BlockContent::Page { title } if title == "Test Page"));
+
+		// Act: Update the content block.
+		let mut updated_block = test_block.clone();
+
+		updated_block.content = BlockContent::Page {
+			title: "Updated Page".to_string(),
+		};
+
+		let updated = repo
+			.upsert_content_block(updated_block)
+			.await
+			.expect("Failed to update content block");
+
+		// Assert: The content block was updated.
+		assert_eq!(updated.nutty_id(), test_block.nutty_id());
+		assert_eq!(updated.parent_id, test_block.parent_id);
+		assert!(matches!(&updated.content, BlockContent::Page { title } if title == "Updated Page"));
+
+		// Assert: The stored content hash follows the content.
+		let content_hash: Option<String> =
+			sqlx::query_scalar("SELECT content_hash FROM content.blocks WHERE id = $1")
+				.bind(updated.nutty_id().uuid())
+				.fetch_one(&pool)
+				.await
+				.expect("Failed to get content hash");
+
+		assert_eq!(content_hash, Some(updated.content_hash().unwrap()));
+		assert_ne!(content_hash, Some(test_block.content_hash().unwrap()));
+
+		// Act: Delete the content block.
+		repo
+			.delete_content_block(&(*test_block.nutty_id()).into())
+			.await
+			.expect("Failed to delete content block");
+
+		// Assert: The content block no longer exists.
+		let retrieved = repo
+			.get_content_block(&(*test_block.nutty_id()).into())
+			.await
+			.unwrap();
+		assert!(retrieved.is_none());
+	}
+
+	#[tokio::test]
+	async fn test_content_link_operations() {
+		// Arrange: Create a repository.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool);
+
+		// Arrange: Create test content blocks.
+		let source_block = ContentBlock::now(
+			None,
+			FractionalIndex::start(),
+			BlockContent::Page {
+				title: "Source Page".to_string(),
+			},
+		);
+
+		let target_block = ContentBlock::now(
+			None,
+			FractionalIndex::between(&source_block.f_index, &FractionalIndex::end()).unwrap(),
+			BlockContent::Page {
+				title: "Target Page".to_string(),
+			},
+		);
+
+		// Act: Save the content blocks.
+		repo
+			.upsert_content_block(source_block.clone())
+			.await
+			.expect("Failed to save source block");
+
+		repo
+			.upsert_content_block(target_block.clone())
+			.await
+			.expect("Failed to save target block");
+
+		// Act: Create a content link between the blocks.
+		let link = ContentLink::now(*source_block.nutty_id(), *target_block.nutty_id());
+
+		repo
+			.upsert_content_link(link.clone())
+			.await
+			.expect("Failed to create content link");
+
+		// Assert: The blocks are linked.
+		assert!(
+			repo
+				.is_linked(
+					&source_block.nutty_id().clone(),
+					&target_block.nutty_id().clone()
+				)
+				.await
+				.expect("Failed to check link")
+		);
+
+		// Act: Get the content link by ID.
+		let retrieved_link = repo
+			.get_content_link(&link.nutty_id.into())
+			.await
+			.expect("Failed to get content link")
+			.expect("Content link not found");
+
+		// Assert: The retrieved link matches the original.
+		assert_eq!(retrieved_link.nutty_id, link.nutty_id);
+		assert_eq!(retrieved_link.source_id, *source_block.nutty_id());
+		assert_eq!(retrieved_link.target_id, *target_block.nutty_id());
+
+		// Act: Get all links from the source block.
+		let links_from = repo
+			.get_content_links_from(source_block.nutty_id())
+			.await
+			.expect("Failed to get links from source block");
+
+		// Assert: The links from source block match the original.
+		assert_eq!(links_from.len(), 1);
+		assert_eq!(links_from[0].nutty_id, link.nutty_id);
+		assert_eq!(links_from[0].source_id, *source_block.nutty_id());
+		assert_eq!(links_from[0].target_id, *target_block.nutty_id());
+
+		// Act: Get all links to the target block.
+		let links_to = repo
+			.get_content_links_to(target_block.nutty_id())
+			.await
+			.expect("Failed to get links to target block");
+
+		// Assert: The links to target block match the original.
+		assert_eq!(links_to.len(), 1);
+		assert_eq!(links_to[0].nutty_id, link.nutty_id);
+		assert_eq!(links_to[0].source_id, *source_block.nutty_id());
+		assert_eq!(links_to[0].target_id, *target_block.nutty_id());
+
+		// Act: Try to get a non-existent link.
+		let non_existent_link = repo
+			.get_content_link(&NuttyId::now().dissociate())
+			.await
+			.expect("Failed to check non-existent link");
+
+		// Assert: No link is found.
+		assert!(non_existent_link.is_none());
+
+		// Act: Try to get links from a non-existent source.
+		let no_links_from = repo
+			.get_content_links_from(&NuttyId::now())
+			.await
+			.expect("Failed to get links from non-existent source");
+
+		// Assert: No links are found.
+		assert!(no_links_from.is_empty());
+
+		// Act: Try to get links to a non-existent target.
+		let no_links_to = repo
+			.get_content_links_to(&NuttyId::now())
+			.await
+			.expect("Failed to get links to non-existent target");
+
+		// Assert: No links are found.
+		assert!(no_links_to.is_empty());
+
+		// Act: Delete the link.
+		repo
+			.delete_content_link(link)
+			.await
+			.expect("Failed to delete content link");
+
+		// Assert: The blocks are no longer linked.
+		assert!(
+			!repo
+				.is_linked(source_block.nutty_id(), target_block.nutty_id())
+				.await
+				.expect("Failed to check link")
+		);
+	}
+
+	#[tokio::test]
+	async fn test_resolve_nutty_ids() {
+		// Arrange: Create a repository.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool);
+
+		// Arrange: Create test content blocks.
+		let block_1 = ContentBlock::now(
+			None,
+			FractionalIndex::start(),
+			BlockContent::Page {
+				title: "Test Page 1".to_string(),
+			},
+		);
+
+		let block_2 = ContentBlock::now(
+			None,
+			FractionalIndex::between(&block_1.f_index, &FractionalIndex::end()).unwrap(),
+			BlockContent::Page {
+				title: "Test Page 2".to_string(),
+			},
+		);
+
+		// Act: Save the content blocks.
+		repo
+			.upsert_content_block(block_1.clone())
+			.await
+			.expect("Failed to save block1");
+
+		repo
+			.upsert_content_block(block_2.clone())
+			.await
+			.expect("Failed to save block2");
+
+		// Act: Create a mix of existing and non-existent Nutty IDs.
+		let ids = [
+			block_1.nutty_id().dissociate(),
+			DissociatedNuttyId::new(&block_2.nutty_id().nid())
+				.expect("Failed to create dissociated ID"),
+			NuttyId::now().dissociate(),
+			DissociatedNuttyId::new("1111111").expect("Failed to create dissociated ID"),
+		];
+
+		// Act: Resolve the IDs.
+		let resolved = repo.resolve_nutty_ids(ids.iter()).await;
+
+		// Assert: Only the IDs that exist in the database are returned.
+		assert_eq!(resolved.len(), 2);
+		assert!(resolved.contains(block_1.nutty_id()));
+		assert!(resolved.contains(block_2.nutty_id()));
+	}
+
+	#[tokio::test]
+	async fn test_delete_orphaned_content_links() {
+		// Arrange: Create a repository.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool);
+
+		// Arrange: Create test content blocks.
+		let source_block = ContentBlock::now(
+			None,
+			FractionalIndex::start(),
+			BlockContent::Page {
+				title: "Source Page".to_string(),
+			},
+		);
+
+		let target_block_1 = ContentBlock::now(
+			None,
+			FractionalIndex::between(&source_block.f_index, &FractionalIndex::end()).unwrap(),
+			BlockContent::Page {
+				title: "Target Page 1".to_string(),
+			},
+		);
+
+		let target_block_2 = ContentBlock::now(
+			None,
+			FractionalIndex::between(&target_block_1.f_index, &FractionalIndex::end()).unwrap(),
+			BlockContent::Page {
+				title: "Target Page 2".to_string(),
+			},
+		);
+
+		// Act: Save the content blocks.
+		repo
+			.upsert_content_block(source_block.clone())
+			.await
+			.expect("Failed to save source block");
+
+		repo
+			.upsert_content_block(target_block_1.clone())
+			.await
+			.expect("Failed to save target block 1");
+
+		repo
+			.upsert_content_block(target_block_2.clone())
+			.await
+			.expect("Failed to save target block 2");
+
+		// Act: Create content links between the blocks.
+		let link_1 = ContentLink::now(*source_block.nutty_id(), *target_block_1.nutty_id());
+		let link_2 = ContentLink::now(*source_block.nutty_id(), *target_block_2.nutty_id());
+
+		repo
+			.upsert_content_link(link_1.clone())
+			.await
+			.expect("Failed to create link 1");
+
+		repo
+			.upsert_content_link(link_2.clone())
+			.await
+			.expect("Failed to create link 2");
+
+		// Assert: Both links exist initially.
+		assert!(
+			repo
+				.is_linked(source_block.nutty_id(), target_block_1.nutty_id())
+				.await
+				.expect("Failed to check link 1")
+		);
+		assert!(
+			repo
+				.is_linked(source_block.nutty_id(), target_block_2.nutty_id())
+				.await
+				.expect("Failed to check link 2")
+		);
+
+		// Act: Delete orphaned links, keeping only link1.
+		repo
+			.delete_orphaned_content_links(source_block.nutty_id(), &[*target_block_1.nutty_id()])
+			.await
+			.expect("Failed to delete orphaned links");
+
+		// Assert: Only link1 remains.
+		assert!(
+			repo
+				.is_linked(source_block.nutty_id(), target_block_1.nutty_id())
+				.await
+				.expect("Failed to check link 1")
+		);
+		assert!(
+			!repo
+				.is_linked(source_block.nutty_id(), target_block_2.nutty_id())
+				.await
+				.expect("Failed to check link 2")
+		);
+	}
+
+	#[tokio::test]
+	async fn test_delete_orphaned_content_links_keeping_several() {
+		// Arrange: Create a repository.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool);
+
+		// Arrange: Create a source block linking to three targets.
+		let source_block = ContentBlock::now(
+			None,
+			FractionalIndex::start(),
+			BlockContent::Page {
+				title: "Source Page".to_string(),
+			},
+		);
+
+		repo
+			.upsert_content_block(source_block.clone())
+			.await
+			.expect("Failed to save source block");
+
+		let mut target_ids = Vec::new();
+		let mut f_index = source_block.f_index.clone();
+
+		for i in 1..=3 {
+			f_index = FractionalIndex::between(&f_index, &FractionalIndex::end()).unwrap();
+
+			let target_block = ContentBlock::now(
+				None,
+				f_index.clone(),
+				BlockContent::Page {
+					title: format!("Target Page {i}"),
+				},
+			);
+
+			repo
+				.upsert_content_block(target_block.clone())
+				.await
+				.expect("Failed to save target block");
+
+			repo
+				.upsert_content_link(ContentLink::now(
+					*source_block.nutty_id(),
+					*target_block.nutty_id(),
+				))
+				.await
+				.expect("Failed to create link");
+
+			target_ids.push(*target_block.nutty_id());
+		}
+
+		// Act: Keep the first two links.
+		repo
+			.delete_orphaned_content_links(source_block.nutty_id(), &target_ids[..2])
+			.await
+			.expect("Failed to delete orphaned links");
+
+		// Assert: Both kept links remain, and only the third is deleted.
+		for (i, target_id) in target_ids.iter().enumerate() {
+			let is_linked = repo
+				.is_linked(source_block.nutty_id(), target_id)
+				.await
+				.expect("Failed to check link");
+
+			assert_eq!(is_linked, i < 2, "link {i}");
+		}
+
+		// Act: Keep no links.
+		repo
+			.delete_orphaned_content_links(source_block.nutty_id(), &[])
+			.await
+			.expect("Failed to delete orphaned links");
+
+		// Assert: Every link is deleted.
+		for target_id in &target_ids {
+			assert!(
+				!repo
+					.is_linked(source_block.nutty_id(), target_id)
+					.await
+					.expect("Failed to check link")
+			);
+		}
+	}
+
+	#[tokio::test]
+	async fn test_upsert_content_links() {
 		// Arrange: Create a repository.
 		let pool = connect_to_test_database().await;
 		let repo = ContentRepository::new(pool);
@@ -729,11 +4108,19 @@ mod tests {
 			},
 		);
 
-		let target_block = ContentBlock::now(
+		let target_block_1 = ContentBlock::now(
+			None,
+			FractionalIndex::between(&source_block.f_index, &FractionalIndex::end()).unwrap(),
+			BlockContent::Page {
+				title: "Target Page 1".to_string(),
+			},
+		);
+
+		let target_block_2 = ContentBlock::now(
 			None,
-			FractionalIndex::between(&source_block.f_index, &FractionalIndex::end()).unwrap(),
+			FractionalIndex::between(&target_block_1.f_index, &FractionalIndex::end()).unwrap(),
 			BlockContent::Page {
-				title: "Target Page".to_string(),
+				title: "Target Page 2".to_string(),
 			},
 		);
 
@@ -744,347 +4131,895 @@ mod tests {
 			.expect("Failed to save source block");
 
 		repo
-			.upsert_content_block(target_block.clone())
+			.upsert_content_block(target_block_1.clone())
 			.await
-			.expect("Failed to save target block");
-
-		// Act: Create a content link between the blocks.
-		let link = ContentLink::now(*source_block.nutty_id(), *target_block.nutty_id());
+			.expect("Failed to save target block 1");
 
 		repo
-			.upsert_content_link(link.clone())
+			.upsert_content_block(target_block_2.clone())
 			.await
-			.expect("Failed to create content link");
+			.expect("Failed to save target block 2");
 
-		// Assert: The blocks are linked.
-		assert!(
-			repo
-				.is_linked(
-					&source_block.nutty_id().clone(),
-					&target_block.nutty_id().clone()
-				)
-				.await
-				.expect("Failed to check link")
-		);
+		// Act: Create multiple content links.
+		let links = vec![
+			ContentLink::now(*source_block.nutty_id(), *target_block_1.nutty_id()),
+			ContentLink::now(*source_block.nutty_id(), *target_block_2.nutty_id()),
+		];
 
-		// Act: Get the content link by ID.
-		let retrieved_link = repo
-			.get_content_link(&link.nutty_id.into())
+		// Act: Save the links in bulk.
+		let saved_links = repo
+			.upsert_content_links(&links)
 			.await
-			.expect("Failed to get content link")
-			.expect("Content link not found");
+			.expect("Failed to save content links");
 
-		// Assert: The retrieved link matches the original.
-		assert_eq!(retrieved_link.nutty_id, link.nutty_id);
-		assert_eq!(retrieved_link.source_id, *source_block.nutty_id());
-		assert_eq!(retrieved_link.target_id, *target_block.nutty_id());
+		// Assert: The correct number of links were saved.
+		assert_eq!(saved_links.len(), 2);
 
-		// Act: Get all links from the source block.
+		// Assert: Each link was saved correctly.
+		for saved_link in saved_links {
+			assert_eq!(saved_link.source_id, *source_block.nutty_id());
+			assert!(
+				saved_link.target_id == *target_block_1.nutty_id()
+					|| saved_link.target_id == *target_block_2.nutty_id()
+			);
+		}
+
+		// Act: Try to save duplicate links (should be ignored due to unique constraint).
+		let duplicate_links = vec![
+			ContentLink::now(*source_block.nutty_id(), *target_block_1.nutty_id()),
+			ContentLink::now(*source_block.nutty_id(), *target_block_2.nutty_id()),
+		];
+
+		let saved_duplicates = repo
+			.upsert_content_links(&duplicate_links)
+			.await
+			.expect("Failed to save duplicate links");
+
+		// Assert: No new links were created.
+		assert_eq!(saved_duplicates.len(), 0);
+
+		// Assert: The original links still exist.
 		let links_from = repo
 			.get_content_links_from(source_block.nutty_id())
 			.await
 			.expect("Failed to get links from source block");
 
-		// Assert: The links from source block match the original.
-		assert_eq!(links_from.len(), 1);
-		assert_eq!(links_from[0].nutty_id, link.nutty_id);
-		assert_eq!(links_from[0].source_id, *source_block.nutty_id());
-		assert_eq!(links_from[0].target_id, *target_block.nutty_id());
+		assert_eq!(links_from.len(), 2);
+	}
 
-		// Act: Get all links to the target block.
-		let links_to = repo
-			.get_content_links_to(target_block.nutty_id())
+	#[tokio::test]
+	async fn test_get_related_blocks() {
+		// Arrange: Create a repository, and a navigator who can read every block.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let reader_id = create_reader(&pool).await;
+
+		// Arrange: Create a block, two blocks cited alongside it, and one not.
+		let page = |title: &str| {
+			ContentBlock::now(
+				None,
+				FractionalIndex::start(),
+				BlockContent::Page {
+					title: title.to_string(),
+				},
+			)
+		};
+
+		let block = page("Block");
+		let often = page("Often Cited Alongside");
+		let once = page("Once Cited Alongside");
+		let unrelated = page("Unrelated");
+		let source_1 = page("Source 1");
+		let source_2 = page("Source 2");
+
+		for block in [&block, &often, &once, &unrelated, &source_1, &source_2] {
+			repo
+				.upsert_content_block(block.clone())
+				.await
+				.expect("Failed to save block");
+		}
+
+		let links = vec![
+			ContentLink::now(*source_1.nutty_id(), *block.nutty_id()),
+			ContentLink::now(*source_1.nutty_id(), *often.nutty_id()),
+			ContentLink::now(*source_1.nutty_id(), *once.nutty_id()),
+			ContentLink::now(*source_2.nutty_id(), *block.nutty_id()),
+			ContentLink::now(*source_2.nutty_id(), *often.nutty_id()),
+			ContentLink::now(*unrelated.nutty_id(), *often.nutty_id()),
+		];
+
+		repo
+			.upsert_content_links(&links)
 			.await
-			.expect("Failed to get links to target block");
+			.expect("Failed to save content links");
 
-		// Assert: The links to target block match the original.
-		assert_eq!(links_to.len(), 1);
-		assert_eq!(links_to[0].nutty_id, link.nutty_id);
-		assert_eq!(links_to[0].source_id, *source_block.nutty_id());
-		assert_eq!(links_to[0].target_id, *target_block.nutty_id());
+		// Act: Get the related blocks.
+		let related = repo
+			.get_related_blocks(&reader_id, block.nutty_id(), 10)
+			.await
+			.expect("Failed to get related blocks");
 
-		// Act: Try to get a non-existent link.
-		let non_existent_link = repo
-			.get_content_link(&NuttyId::now().dissociate())
+		// Assert: Co-cited blocks come most shared citations first.
+		let related_ids: Vec<_> = related.iter().map(|block| *block.nutty_id()).collect();
+		assert_eq!(related_ids, vec![*often.nutty_id(), *once.nutty_id()]);
+
+		// Act & Assert: The limit caps the related blocks.
+		let related = repo
+			.get_related_blocks(&reader_id, block.nutty_id(), 1)
 			.await
-			.expect("Failed to check non-existent link");
+			.expect("Failed to get related blocks");
 
-		// Assert: No link is found.
-		assert!(non_existent_link.is_none());
+		assert_eq!(related.len(), 1);
+	}
 
-		// Act: Try to get links from a non-existent source.
-		let no_links_from = repo
-			.get_content_links_from(&NuttyId::now())
+	#[tokio::test]
+	async fn test_get_related_blocks_access() {
+		// Arrange: Create a repository, and a navigator who can read their own blocks.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let owner_id = create_navigator(&pool, "block_owner").await;
+
+		// Arrange: Create the owner's pages, and private pages.
+		let page = |owner_id: Option<NuttyId>, title: &str| {
+			let mut block = ContentBlock::now(
+				None,
+				FractionalIndex::start(),
+				BlockContent::Page {
+					title: title.to_string(),
+				},
+			);
+
+			block.owner_id = owner_id;
+			block
+		};
+
+		let block = page(Some(owner_id), "Block");
+		let source = page(Some(owner_id), "Source");
+		let private_source = page(None, "Private Source");
+		let shared = page(Some(owner_id), "Cited Alongside");
+		let private = page(None, "Private, Cited Alongside");
+		let cited_privately = page(Some(owner_id), "Cited Alongside Privately");
+
+		for block in [
+			&block,
+			&source,
+			&private_source,
+			&shared,
+			&private,
+			&cited_privately,
+		] {
+			repo
+				.upsert_content_block(block.clone())
+				.await
+				.expect("Failed to save block");
+		}
+
+		let links = vec![
+			ContentLink::now(*source.nutty_id(), *block.nutty_id()),
+			ContentLink::now(*source.nutty_id(), *shared.nutty_id()),
+			ContentLink::now(*source.nutty_id(), *private.nutty_id()),
+			ContentLink::now(*private_source.nutty_id(), *block.nutty_id()),
+			ContentLink::now(*private_source.nutty_id(), *private.nutty_id()),
+			ContentLink::now(*private_source.nutty_id(), *cited_privately.nutty_id()),
+		];
+
+		repo
+			.upsert_content_links(&links)
+			.await
+			.expect("Failed to save content links");
+
+		// Act: Get the one most related block that the owner can read.
+		let related = repo
+			.get_related_blocks(&owner_id, block.nutty_id(), 1)
+			.await
+			.expect("Failed to get related blocks");
+
+		// Assert: The private page is filtered out before the limit, and the
+		// citations from the private source don't count.
+		let related_ids: Vec<_> = related.iter().map(|block| *block.nutty_id()).collect();
+		assert_eq!(related_ids, vec![*shared.nutty_id()]);
+
+		// Act & Assert: Without the limit, only the shared page is related.
+		let related = repo
+			.get_related_blocks(&owner_id, block.nutty_id(), 10)
+			.await
+			.expect("Failed to get related blocks");
+
+		assert_eq!(related.len(), 1);
+	}
+
+	#[tokio::test]
+	async fn test_list_owned_blocks_by_root() {
+		// Arrange: Create a repository, and two navigators.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let alice_id = NuttyId::now();
+		let bob_id = NuttyId::now();
+
+		for navigator_id in [alice_id, bob_id] {
+			sqlx::query(
+				"INSERT INTO auth.navigators (id, nutty_id, name, pass) VALUES ($1, $2, $3, 'hash')",
+			)
+			.bind(navigator_id.uuid())
+			.bind(navigator_id.nid())
+			.bind(format!("test_navigator_{}", navigator_id.nid()))
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+		}
+
+		// Arrange: Alice owns a page with a child, and a grandchild of Bob's page.
+		let block = |parent_id: Option<NuttyId>, owner_id| {
+			ContentBlock::now_with_owner(
+				parent_id,
+				owner_id,
+				FractionalIndex::start(),
+				BlockContent::Paragraph {
+					markdown: "Owned".to_string(),
+				},
+			)
+		};
+
+		let page = block(None, alice_id);
+		let child = block(Some(*page.nutty_id()), alice_id);
+		let bobs_page = block(None, bob_id);
+		let bobs_child = block(Some(*bobs_page.nutty_id()), bob_id);
+		let grandchild = block(Some(*bobs_child.nutty_id()), alice_id);
+
+		for block in [&page, &child, &bobs_page, &bobs_child, &grandchild] {
+			repo
+				.upsert_content_block(block.clone())
+				.await
+				.expect("Failed to save block");
+		}
+
+		// Act
+		let groups = repo
+			.list_owned_blocks_by_root(&alice_id)
+			.await
+			.expect("Failed to list owned blocks");
+
+		// Assert: Blocks are grouped under their top-level ancestor, whoever
+		// owns it, as the in-memory grouping does.
+		let all = [&page, &child, &bobs_page, &bobs_child, &grandchild];
+		let expected = RootBlockGroup::group_owned(all, &alice_id);
+
+		let summary = |groups: &[RootBlockGroup]| {
+			let mut summary: Vec<_> = groups
+				.iter()
+				.map(|group| {
+					let mut block_ids: Vec<_> = group.block_ids.iter().map(|id| *id.uuid()).collect();
+					block_ids.sort();
+					(*group.root_id.uuid(), group.block_count, block_ids)
+				})
+				.collect();
+
+			summary.sort();
+			summary
+		};
+
+		assert_eq!(groups.len(), 2);
+		assert_eq!(summary(&groups), summary(&expected));
+	}
+
+	#[tokio::test]
+	async fn test_block_stats() {
+		// Arrange: Create a repository.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool);
+
+		// Arrange: Create a page with a child and grandchild, and another
+		// page that the grandchild links to.
+		let block = |parent_id: Option<NuttyId>| {
+			ContentBlock::now(
+				parent_id,
+				FractionalIndex::start(),
+				BlockContent::Paragraph {
+					markdown: "Counted".to_string(),
+				},
+			)
+		};
+
+		let page = block(None);
+		let child = block(Some(*page.nutty_id()));
+		let grandchild = block(Some(*child.nutty_id()));
+		let other_page = block(None);
+
+		for block in [&page, &child, &grandchild, &other_page] {
+			repo
+				.upsert_content_block(block.clone())
+				.await
+				.expect("Failed to save block");
+		}
+
+		repo
+			.upsert_content_link(ContentLink::new(
+				NuttyId::now(),
+				*grandchild.nutty_id(),
+				*other_page.nutty_id(),
+			))
+			.await
+			.expect("Failed to save link");
+
+		let stats = async |repo: &ContentRepository| {
+			let stats = repo
+				.list_block_stats(&[*page.nutty_id(), *other_page.nutty_id()])
+				.await
+				.expect("Failed to list block stats");
+
+			let count = |id: &NuttyId| {
+				let stats = stats.iter().find(|stats| stats.block_id == *id).unwrap();
+				(stats.descendant_count, stats.backlink_count)
+			};
+
+			(count(page.nutty_id()), count(other_page.nutty_id()))
+		};
+
+		// Assert: Descendants are counted at any depth, and backlinks once.
+		assert_eq!(stats(&repo).await, ((2, 0), (0, 1)));
+
+		// Act: Move the child, with its grandchild, under the other page.
+		let mut moved = child.clone();
+		moved.parent_id = Some(*other_page.nutty_id());
+
+		repo
+			.upsert_content_block(moved)
+			.await
+			.expect("Failed to move block");
+
+		// Assert: Both pages were recounted.
+		assert_eq!(stats(&repo).await, ((0, 0), (2, 1)));
+
+		// Act: Delete the grandchild, and its link with it.
+		repo
+			.delete_content_block(&(*grandchild.nutty_id()).into())
+			.await
+			.expect("Failed to delete block");
+
+		// Assert: The other page lost a descendant and a backlink.
+		assert_eq!(stats(&repo).await, ((0, 0), (1, 0)));
+
+		// Assert: The incremental counts match fresh ones.
+		let repaired = repo
+			.repair_block_stats_tx(&repo.pool)
+			.await
+			.expect("Failed to repair block stats");
+
+		let ids = [page.nutty_id(), child.nutty_id(), other_page.nutty_id()];
+		assert!(repaired.iter().all(|stats| !ids.contains(&&stats.block_id)));
+
+		// Clean up.
+		for block in [&child, &page, &other_page] {
+			repo
+				.delete_content_block(&(*block.nutty_id()).into())
+				.await
+				.expect("Failed to delete block");
+		}
+	}
+
+	#[tokio::test]
+	async fn test_list_changes() {
+		// Arrange: Create a repository, and a navigator.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let owner_id = NuttyId::now();
+
+		sqlx::query(
+			"INSERT INTO auth.navigators (id, nutty_id, name, pass) VALUES ($1, $2, $3, 'hash')",
+		)
+		.bind(owner_id.uuid())
+		.bind(owner_id.nid())
+		.bind(format!("test_navigator_{}", owner_id.nid()))
+		.execute(&pool)
+		.await
+		.expect("Failed to create test navigator");
+
+		// Arrange: Save a page that links to another.
+		let block = |markdown: &str| {
+			ContentBlock::now_with_owner(
+				None,
+				owner_id,
+				FractionalIndex::start(),
+				BlockContent::Paragraph {
+					markdown: markdown.to_string(),
+				},
+			)
+		};
+
+		let page = block("Linking");
+		let other_page = block("Linked");
+
+		for block in [&page, &other_page] {
+			repo
+				.upsert_content_block(block.clone())
+				.await
+				.expect("Failed to save block");
+		}
+
+		let link = ContentLink::now(*page.nutty_id(), *other_page.nutty_id());
+
+		repo
+			.upsert_content_link(link.clone())
+			.await
+			.expect("Failed to save link");
+
+		let changes = async |after: Option<SyncCursor>, limit| {
+			repo
+				.list_changes_tx(&repo.pool, &owner_id, after.as_ref(), limit)
+				.await
+				.expect("Failed to list changes")
+		};
+
+		// Act: Pull the changes, one at a time.
+		let first = changes(None, 1).await;
+		let rest = changes(Some(first[0].cursor()), 10).await;
+
+		// Assert: Each change is pulled once.
+		let mut pulled: Vec<_> = first.iter().chain(&rest).map(|c| (c.kind, c.id)).collect();
+		pulled.sort_by_key(|(_, id)| *id.uuid());
+
+		let mut expected = vec![
+			(ChangeKind::Block, *page.nutty_id()),
+			(ChangeKind::Block, *other_page.nutty_id()),
+			(ChangeKind::Link, link.nutty_id),
+		];
+
+		expected.sort_by_key(|(_, id)| *id.uuid());
+		assert_eq!(pulled, expected);
+
+		// Act: Delete the page, and its link along with it.
+		let cursor = rest.last().unwrap().cursor();
+
+		repo
+			.delete_content_block(&page.nutty_id().dissociate())
+			.await
+			.expect("Failed to delete block");
+
+		let deleted = changes(Some(cursor), 10).await;
+
+		// Assert: Both deletions are pulled as tombstones.
+		let mut tombstones: Vec<_> = deleted
+			.iter()
+			.map(|change| (change.kind, change.id, change.deleted))
+			.collect();
+
+		tombstones.sort_by_key(|(kind, ..)| kind.as_str());
+
+		assert_eq!(
+			tombstones,
+			[
+				(ChangeKind::Block, *page.nutty_id(), true),
+				(ChangeKind::Link, link.nutty_id, true),
+			]
+		);
+
+		// Clean up.
+		repo
+			.delete_content_block(&other_page.nutty_id().dissociate())
+			.await
+			.expect("Failed to delete block");
+	}
+
+	#[tokio::test]
+	async fn test_translations() {
+		// Arrange: Create a repository, and an English note with two variants.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+
+		let block = |markdown: &str| {
+			ContentBlock::now(
+				None,
+				FractionalIndex::start(),
+				BlockContent::Paragraph {
+					markdown: markdown.to_string(),
+				},
+			)
+		};
+
+		let english = block("Hello");
+		let japanese = block("こんにちは");
+		let french = block("Bonjour");
+
+		for block in [&english, &japanese, &french] {
+			repo
+				.upsert_content_block(block.clone())
+				.await
+				.expect("Failed to save block");
+		}
+
+		let lang = |tag: &str| Lang::parse(tag).unwrap();
+
+		// Act: Set the note's language, and link its variants to it.
+		repo
+			.set_translation(english.nutty_id(), &lang("en"), None)
+			.await
+			.expect("Failed to set language");
+
+		for (variant, tag) in [(&japanese, "ja"), (&french, "fr")] {
+			repo
+				.set_translation(variant.nutty_id(), &lang(tag), Some(english.nutty_id()))
+				.await
+				.expect("Failed to link translation");
+		}
+
+		// Assert: Each variant lists all of them, by language.
+		let translations = repo
+			.list_translations(japanese.nutty_id())
 			.await
-			.expect("Failed to get links from non-existent source");
+			.expect("Failed to list translations");
 
-		// Assert: No links are found.
-		assert!(no_links_from.is_empty());
+		assert_eq!(
+			translations
+				.iter()
+				.map(|translation| (translation.block_id, translation.lang.as_str()))
+				.collect::<Vec<_>>(),
+			[
+				(*english.nutty_id(), "en"),
+				(*french.nutty_id(), "fr"),
+				(*japanese.nutty_id(), "ja"),
+			]
+		);
 
-		// Act: Try to get links to a non-existent target.
-		let no_links_to = repo
-			.get_content_links_to(&NuttyId::now())
-			.await
-			.expect("Failed to get links to non-existent target");
+		// Assert: The Japanese variant is searched in Japanese.
+		let search_language: String = sqlx::query_scalar(
+			"SELECT content.search_language(language) FROM content.blocks WHERE id = $1",
+		)
+		.bind(japanese.nutty_id().uuid())
+		.fetch_one(&pool)
+		.await
+		.expect("Failed to fetch search language");
 
-		// Assert: No links are found.
-		assert!(no_links_to.is_empty());
+		assert_eq!(search_language, "japanese");
 
-		// Act: Delete the link.
-		repo
-			.delete_content_link(link)
+		// Assert: A language is only taken once among translations.
+		let result = repo
+			.set_translation(french.nutty_id(), &lang("ja"), None)
+			.await;
+
+		assert!(matches!(
+			result,
+			Err(ContentRepositoryError::TranslationTaken)
+		));
+
+		// Act: Remove the French variant from the translations.
+		let deleted = repo
+			.delete_translation(french.nutty_id())
 			.await
-			.expect("Failed to delete content link");
+			.expect("Failed to delete translation");
 
-		// Assert: The blocks are no longer linked.
+		// Assert: It's gone, and has no translations of its own.
+		assert!(deleted);
+		assert_eq!(
+			repo
+				.list_translations(english.nutty_id())
+				.await
+				.expect("Failed to list translations")
+				.len(),
+			2
+		);
 		assert!(
-			!repo
-				.is_linked(source_block.nutty_id(), target_block.nutty_id())
+			repo
+				.list_translations(french.nutty_id())
 				.await
-				.expect("Failed to check link")
+				.expect("Failed to list translations")
+				.is_empty()
 		);
 	}
 
+	/// Create a navigator who can read every block.
+	async fn create_reader(pool: &Pool<Postgres>) -> NuttyId {
+		create_navigator(pool, "admin").await
+	}
+
+	/// Create a navigator with a global role.
+	async fn create_navigator(pool: &Pool<Postgres>, role_name: &str) -> NuttyId {
+		let navigator_id = NuttyId::now();
+		let role_id = NuttyId::now();
+
+		sqlx::query(
+			"INSERT INTO auth.navigators (id, nutty_id, name, pass) VALUES ($1, $2, $3, 'hash')",
+		)
+		.bind(navigator_id.uuid())
+		.bind(navigator_id.nid())
+		.bind(format!("test_navigator_{}", navigator_id.nid()))
+		.execute(pool)
+		.await
+		.expect("Failed to create test navigator");
+
+		sqlx::query(
+			"INSERT INTO auth.navigator_roles (id, nutty_id, navigator_id, role_name) VALUES ($1, $2, $3, $4)",
+		)
+		.bind(role_id.uuid())
+		.bind(role_id.nid())
+		.bind(navigator_id.uuid())
+		.bind(role_name)
+		.execute(pool)
+		.await
+		.expect("Failed to grant test role");
+
+		navigator_id
+	}
+
+	/// Markdown long enough that Postgres compresses it at rest.
+	fn long_markdown(first_line: &str) -> String {
+		format!("{first_line}\n{}", "All work and no play. ".repeat(200))
+	}
+
 	#[tokio::test]
-	async fn test_resolve_nutty_ids() {
+	async fn test_content_compression() {
 		// Arrange: Create a repository.
 		let pool = connect_to_test_database().await;
-		let repo = ContentRepository::new(pool);
+		let repo = ContentRepository::new(pool.clone());
+
+		let block = |markdown: String| {
+			ContentBlock::now(
+				None,
+				FractionalIndex::start(),
+				BlockContent::Paragraph { markdown },
+			)
+		};
 
-		// Arrange: Create test content blocks.
-		let block_1 = ContentBlock::now(
-			None,
-			FractionalIndex::start(),
-			BlockContent::Page {
-				title: "Test Page 1".to_string(),
-			},
-		);
+		// Act: Save a long and a short block.
+		let long = block(long_markdown("Long"));
+		let short = block("Short".to_string());
 
-		let block_2 = ContentBlock::now(
-			None,
-			FractionalIndex::between(&block_1.f_index, &FractionalIndex::end()).unwrap(),
-			BlockContent::Page {
-				title: "Test Page 2".to_string(),
-			},
-		);
+		for block in [&long, &short] {
+			repo
+				.upsert_content_block(block.clone())
+				.await
+				.expect("Failed to save block");
+		}
 
-		// Act: Save the content blocks.
-		repo
-			.upsert_content_block(block_1.clone())
+		// Assert: Only the long block is compressed, with lz4.
+		let compression = async |block: &ContentBlock| -> Option<String> {
+			sqlx::query_scalar(
+				"SELECT pg_column_compression(content) FROM content.blocks WHERE id = $1",
+			)
+			.bind(block.nutty_id().uuid())
+			.fetch_one(&pool)
 			.await
-			.expect("Failed to save block1");
+			.expect("Failed to fetch content compression")
+		};
 
-		repo
-			.upsert_content_block(block_2.clone())
+		assert_eq!(compression(&long).await.as_deref(), Some("lz4"));
+		assert_eq!(compression(&short).await, None);
+
+		// Assert: Only the long block is counted, smaller than its JSON.
+		let stats = repo
+			.get_compression_stats_tx(&pool, &[*long.nutty_id(), *short.nutty_id()])
 			.await
-			.expect("Failed to save block2");
+			.expect("Failed to get compression stats");
 
-		// Act: Create a mix of existing and non-existent Nutty IDs.
-		let ids = [
-			block_1.nutty_id().dissociate(),
-			DissociatedNuttyId::new(&block_2.nutty_id().nid())
-				.expect("Failed to create dissociated ID"),
-			NuttyId::now().dissociate(),
-			DissociatedNuttyId::new("1111111").expect("Failed to create dissociated ID"),
-		];
+		assert_eq!(stats.compressed_count, 1);
+		assert!(stats.compressed_bytes < stats.original_bytes);
 
-		// Act: Resolve the IDs.
-		let resolved = repo.resolve_nutty_ids(ids.iter()).await;
+		// Assert: There's no pglz content of these blocks to recompress.
+		let recompressed = repo
+			.recompress_content_tx(&pool, i64::MAX)
+			.await
+			.expect("Failed to recompress content");
 
-		// Assert: Only the IDs that exist in the database are returned.
-		assert_eq!(resolved.len(), 2);
-		assert!(resolved.contains(block_1.nutty_id()));
-		assert!(resolved.contains(block_2.nutty_id()));
+		assert!(!recompressed.contains(long.nutty_id()));
+
+		// Assert: Compressed content reads the same.
+		let fetched = repo
+			.get_content_block(&long.nutty_id().dissociate())
+			.await
+			.expect("Failed to get block")
+			.expect("Block not found");
+
+		assert_eq!(fetched.content, long.content);
 	}
 
 	#[tokio::test]
-	async fn test_delete_orphaned_content_links() {
-		// Arrange: Create a repository.
+	async fn test_search_long_content() {
+		// Arrange: Create a repository, a reader, and a long block.
 		let pool = connect_to_test_database().await;
-		let repo = ContentRepository::new(pool);
+		let repo = ContentRepository::new(pool.clone());
+		let reader_id = create_reader(&pool).await;
+		let word = format!("xyzzy{}", NuttyId::now().nid().to_lowercase());
 
-		// Arrange: Create test content blocks.
-		let source_block = ContentBlock::now(
+		let block = ContentBlock::now(
 			None,
 			FractionalIndex::start(),
-			BlockContent::Page {
-				title: "Source Page".to_string(),
+			BlockContent::Paragraph {
+				markdown: long_markdown(&word),
 			},
 		);
 
-		let target_block_1 = ContentBlock::now(
-			None,
-			FractionalIndex::between(&source_block.f_index, &FractionalIndex::end()).unwrap(),
-			BlockContent::Page {
-				title: "Target Page 1".to_string(),
-			},
+		repo
+			.upsert_content_block(block.clone())
+			.await
+			.expect("Failed to save block");
+
+		// Act
+		let results = repo
+			.search_content_blocks(&reader_id, &word, None, false, 10)
+			.await
+			.expect("Failed to search");
+
+		// Assert: The long block is found by a word in its markdown.
+		assert!(
+			results
+				.iter()
+				.any(|result| result.nutty_id() == block.nutty_id())
 		);
+	}
 
-		let target_block_2 = ContentBlock::now(
+	#[tokio::test]
+	async fn test_is_title_shared_long_title() {
+		// Arrange: Create a repository, and a page with a long title.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let title = long_markdown(&NuttyId::now().nid()).replace('\n', " ");
+
+		let page = ContentBlock::now(
 			None,
-			FractionalIndex::between(&target_block_1.f_index, &FractionalIndex::end()).unwrap(),
+			FractionalIndex::start(),
 			BlockContent::Page {
-				title: "Target Page 2".to_string(),
+				title: title.clone(),
 			},
 		);
 
-		// Act: Save the content blocks.
 		repo
-			.upsert_content_block(source_block.clone())
+			.upsert_content_block(page.clone())
 			.await
-			.expect("Failed to save source block");
+			.expect("Failed to save page");
 
-		repo
-			.upsert_content_block(target_block_1.clone())
+		// Act
+		let shared_with_other = repo
+			.is_title_shared_tx(&pool, &NuttyId::now(), &title)
 			.await
-			.expect("Failed to save target block 1");
+			.expect("Failed to check title");
 
-		repo
-			.upsert_content_block(target_block_2.clone())
+		let shared_with_itself = repo
+			.is_title_shared_tx(&pool, page.nutty_id(), &title)
 			.await
-			.expect("Failed to save target block 2");
+			.expect("Failed to check title");
 
-		// Act: Create content links between the blocks.
-		let link_1 = ContentLink::now(*source_block.nutty_id(), *target_block_1.nutty_id());
-		let link_2 = ContentLink::now(*source_block.nutty_id(), *target_block_2.nutty_id());
+		// Assert: Another page can't take the long title.
+		assert!(shared_with_other);
+		assert!(!shared_with_itself);
+	}
 
-		repo
-			.upsert_content_link(link_1.clone())
-			.await
-			.expect("Failed to create link 1");
+	#[tokio::test]
+	async fn test_list_todo_blocks_long_content() {
+		// Arrange: Create a repository, a reader, and a long done todo.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let reader_id = create_reader(&pool).await;
+
+		let todo = ContentBlock::now(
+			None,
+			FractionalIndex::start(),
+			BlockContent::Todo {
+				markdown: long_markdown("Long todo"),
+				done: true,
+			},
+		);
 
 		repo
-			.upsert_content_link(link_2.clone())
+			.upsert_content_block(todo.clone())
 			.await
-			.expect("Failed to create link 2");
+			.expect("Failed to save todo");
 
-		// Assert: Both links exist initially.
-		assert!(
-			repo
-				.is_linked(source_block.nutty_id(), target_block_1.nutty_id())
-				.await
-				.expect("Failed to check link 1")
-		);
-		assert!(
-			repo
-				.is_linked(source_block.nutty_id(), target_block_2.nutty_id())
-				.await
-				.expect("Failed to check link 2")
-		);
+		// Act
+		let done = repo
+			.list_todo_blocks(&reader_id, Some(true), None, i64::MAX)
+			.await
+			.expect("Failed to list done todos");
 
-		// Act: Delete orphaned links, keeping only link1.
-		repo
-			.delete_orphaned_content_links(source_block.nutty_id(), &[*target_block_1.nutty_id()])
+		let open = repo
+			.list_todo_blocks(&reader_id, Some(false), None, i64::MAX)
 			.await
-			.expect("Failed to delete orphaned links");
+			.expect("Failed to list open todos");
 
-		// Assert: Only link1 remains.
-		assert!(
-			repo
-				.is_linked(source_block.nutty_id(), target_block_1.nutty_id())
-				.await
-				.expect("Failed to check link 1")
-		);
-		assert!(
-			!repo
-				.is_linked(source_block.nutty_id(), target_block_2.nutty_id())
-				.await
-				.expect("Failed to check link 2")
-		);
+		// Assert: The long todo is filtered by whether it's done.
+		assert!(done.iter().any(|block| block.nutty_id() == todo.nutty_id()));
+		assert!(open.iter().all(|block| block.nutty_id() != todo.nutty_id()));
 	}
 
 	#[tokio::test]
-	async fn test_upsert_content_links() {
-		// Arrange: Create a repository.
+	async fn test_list_outline_long_content() {
+		// Arrange: Create a repository, and a page with a long child.
 		let pool = connect_to_test_database().await;
-		let repo = ContentRepository::new(pool);
+		let repo = ContentRepository::new(pool.clone());
 
-		// Arrange: Create test content blocks.
-		let source_block = ContentBlock::now(
+		let page = ContentBlock::now(
 			None,
 			FractionalIndex::start(),
 			BlockContent::Page {
-				title: "Source Page".to_string(),
-			},
-		);
-
-		let target_block_1 = ContentBlock::now(
-			None,
-			FractionalIndex::between(&source_block.f_index, &FractionalIndex::end()).unwrap(),
-			BlockContent::Page {
-				title: "Target Page 1".to_string(),
+				title: "Outlined".to_string(),
 			},
 		);
 
-		let target_block_2 = ContentBlock::now(
-			None,
-			FractionalIndex::between(&target_block_1.f_index, &FractionalIndex::end()).unwrap(),
-			BlockContent::Page {
-				title: "Target Page 2".to_string(),
+		let child = ContentBlock::now(
+			Some(*page.nutty_id()),
+			FractionalIndex::start(),
+			BlockContent::Paragraph {
+				markdown: long_markdown("First line"),
 			},
 		);
 
-		// Act: Save the content blocks.
-		repo
-			.upsert_content_block(source_block.clone())
-			.await
-			.expect("Failed to save source block");
+		for block in [&page, &child] {
+			repo
+				.upsert_content_block(block.clone())
+				.await
+				.expect("Failed to save block");
+		}
 
-		repo
-			.upsert_content_block(target_block_1.clone())
+		// Act
+		let outline = repo
+			.list_outline(&page.nutty_id().dissociate(), None, 10)
 			.await
-			.expect("Failed to save target block 1");
+			.expect("Failed to list outline");
 
-		repo
-			.upsert_content_block(target_block_2.clone())
-			.await
-			.expect("Failed to save target block 2");
+		// Assert: The long child is titled by its first line.
+		let entry = outline
+			.iter()
+			.find(|entry| entry.block_id == *child.nutty_id())
+			.expect("Child not in outline");
 
-		// Act: Create multiple content links.
-		let links = vec![
-			ContentLink::now(*source_block.nutty_id(), *target_block_1.nutty_id()),
-			ContentLink::now(*source_block.nutty_id(), *target_block_2.nutty_id()),
-		];
+		assert_eq!(entry.title, "First line");
+	}
 
-		// Act: Save the links in bulk.
-		let saved_links = repo
-			.upsert_content_links(&links)
-			.await
-			.expect("Failed to save content links");
+	#[tokio::test]
+	async fn test_list_block_checksums() {
+		// Arrange: Create a repository.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+
+		// Arrange: Create a page with a child, and an unrelated page.
+		let block = |parent_id: Option<NuttyId>, markdown: &str| {
+			ContentBlock::now(
+				parent_id,
+				FractionalIndex::start(),
+				BlockContent::Paragraph {
+					markdown: markdown.to_string(),
+				},
+			)
+		};
 
-		// Assert: The correct number of links were saved.
-		assert_eq!(saved_links.len(), 2);
+		let page = block(None, "Synced");
+		let child = block(Some(*page.nutty_id()), "Also synced");
+		let other_page = block(None, "Not synced");
 
-		// Assert: Each link was saved correctly.
-		for saved_link in saved_links {
-			assert_eq!(saved_link.source_id, *source_block.nutty_id());
-			assert!(
-				saved_link.target_id == *target_block_1.nutty_id()
-					|| saved_link.target_id == *target_block_2.nutty_id()
-			);
+		for block in [&page, &child, &other_page] {
+			repo
+				.upsert_content_block(block.clone())
+				.await
+				.expect("Failed to save block");
 		}
 
-		// Act: Try to save duplicate links (should be ignored due to unique constraint).
-		let duplicate_links = vec![
-			ContentLink::now(*source_block.nutty_id(), *target_block_1.nutty_id()),
-			ContentLink::now(*source_block.nutty_id(), *target_block_2.nutty_id()),
-		];
+		// Arrange: Forget the child's hash, as if it predated hashes.
+		sqlx::query("UPDATE content.blocks SET content_hash = NULL WHERE id = $1")
+			.bind(child.nutty_id().uuid())
+			.execute(&pool)
+			.await
+			.expect("Failed to clear hash");
 
-		let saved_duplicates = repo
-			.upsert_content_links(&duplicate_links)
+		// Act: List the page's checksums.
+		let checksums = repo
+			.list_block_checksums(&page.nutty_id().dissociate())
 			.await
-			.expect("Failed to save duplicate links");
+			.expect("Failed to list checksums");
 
-		// Assert: No new links were created.
-		assert_eq!(saved_duplicates.len(), 0);
+		// Assert: The page and its child are listed, hashed alike.
+		let hashes: HashMap<_, _> = checksums
+			.iter()
+			.map(|checksum| (checksum.block_id, checksum.content_hash.clone()))
+			.collect();
 
-		// Assert: The original links still exist.
-		let links_from = repo
-			.get_content_links_from(source_block.nutty_id())
-			.await
-			.expect("Failed to get links from source block");
+		assert_eq!(hashes.len(), 2);
 
-		assert_eq!(links_from.len(), 2);
+		for block in [&page, &child] {
+			assert_eq!(
+				hashes[block.nutty_id()],
+				block.content_hash().unwrap(),
+				"{:?}",
+				block.content
+			);
+		}
 	}
 
 	#[tokio::test]
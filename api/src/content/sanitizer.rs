@@ -0,0 +1,402 @@
+use std::collections::HashSet;
+use std::ops::Range;
+
+use pulldown_cmark::Event;
+use pulldown_cmark::Options;
+use pulldown_cmark::Parser;
+use pulldown_cmark::Tag;
+
+use crate::models::BlockContent;
+
+/// The markdown extensions used when rendering and cleaning content.
+const MARKDOWN_OPTIONS: Options = Options::ENABLE_STRIKETHROUGH;
+
+/// Tags whose contents are dropped along with them, which can never be allowed.
+const CONTENT_TAGS: [&str; 2] = ["script", "style"];
+
+/// Configuration for the content [Sanitizer].
+#[derive(Debug, Clone)]
+pub struct SanitizerConfig {
+	/// The HTML tags that may appear in rendered content.
+	pub allowed_tags: HashSet<String>,
+
+	/// The URL schemes that links and images may use. Relative URLs are always allowed.
+	pub allowed_url_schemes: HashSet<String>,
+
+	/// Whether to also clean markdown before it is saved.
+	pub sanitize_on_save: bool,
+}
+
+impl SanitizerConfig {
+	/// Read the configuration from `CONTENT_ALLOWED_TAGS` and
+	/// `CONTENT_ALLOWED_URL_SCHEMES` (comma-separated) and
+	/// `CONTENT_SANITIZE_ON_SAVE`, falling back to the defaults.
+	pub fn from_env() -> Self {
+		let defaults = Self::default();
+
+		let read_list = |name: &str, default: HashSet<String>| {
+			std::env::var(name)
+				.ok()
+				.map(|v| {
+					v.split(',')
+						.map(|item| item.trim().to_lowercase())
+						.filter(|item| !item.is_empty())
+						.collect()
+				})
+				.unwrap_or(default)
+		};
+
+		Self {
+			allowed_tags: read_list("CONTENT_ALLOWED_TAGS", defaults.allowed_tags),
+			allowed_url_schemes: read_list(
+				"CONTENT_ALLOWED_URL_SCHEMES",
+				defaults.allowed_url_schemes,
+			),
+			sanitize_on_save: std::env::var("CONTENT_SANITIZE_ON_SAVE")
+				.is_ok_and(|v| v == "1" || v == "true"),
+		}
+	}
+}
+
+impl Default for SanitizerConfig {
+	fn default() -> Self {
+		let allowed_tags = [
+			"a",
+			"blockquote",
+			"br",
+			"code",
+			"del",
+			"em",
+			"h1",
+			"h2",
+			"h3",
+			"h4",
+			"h5",
+			"h6",
+			"hr",
+			"img",
+			"li",
+			"ol",
+			"p",
+			"pre",
+			"strong",
+			"ul",
+		];
+
+		Self {
+			allowed_tags: allowed_tags.iter().map(|t| t.to_string()).collect(),
+			allowed_url_schemes: ["http", "https", "mailto"]
+				.iter()
+				.map(|s| s.to_string())
+				.collect(),
+			sanitize_on_save: false,
+		}
+	}
+}
+
+/// An allow-list based sanitizer for untrusted markdown.
+///
+/// Rendering always sanitizes the resulting HTML. Cleaning on save is a
+/// second line of defence that rewrites raw HTML and unsafe link
+/// destinations in the markdown itself.
+#[derive(Debug, Clone, Default)]
+pub struct Sanitizer {
+	config: SanitizerConfig,
+}
+
+impl Sanitizer {
+	/// Create a sanitizer with the given configuration.
+	pub fn new(config: SanitizerConfig) -> Self {
+		Self { config }
+	}
+
+	/// Whether markdown should be cleaned before it is saved.
+	pub fn sanitizes_on_save(&self) -> bool {
+		self.config.sanitize_on_save
+	}
+
+	/// Render markdown to sanitized HTML.
+	pub fn render(&self, markdown: &str) -> String {
+		let mut html = String::new();
+		pulldown_cmark::html::push_html(&mut html, Parser::new_ext(markdown, MARKDOWN_OPTIONS));
+		self.clean_html(&html)
+	}
+
+	/// Clean a block's markdown, leaving other content unchanged. Custom
+	/// content is cleaned as it's rendered instead.
+	pub fn clean_content(&self, content: &BlockContent) -> BlockContent {
+		match content {
+			BlockContent::Heading { markdown } => BlockContent::Heading {
+				markdown: self.clean_markdown(markdown),
+			},
+			BlockContent::Paragraph { markdown } => BlockContent::Paragraph {
+				markdown: self.clean_markdown(markdown),
+			},
+			BlockContent::Todo { markdown, done } => BlockContent::Todo {
+				markdown: self.clean_markdown(markdown),
+				done: *done,
+			},
+			BlockContent::Page { .. } | BlockContent::Query { .. } | BlockContent::Custom { .. } => {
+				content.clone()
+			}
+		}
+	}
+
+	/// Clean markdown by sanitizing raw HTML and dropping link and image
+	/// destinations with disallowed schemes. Other markdown is left as written.
+	pub fn clean_markdown(&self, markdown: &str) -> String {
+		let mut replacements: Vec<(Range<usize>, String)> = Vec::new();
+
+		for (event, range) in Parser::new_ext(markdown, MARKDOWN_OPTIONS).into_offset_iter() {
+			match event {
+				Event::Html(html) | Event::InlineHtml(html) => {
+					let cleaned = self.clean_fragment(&html);
+
+					if cleaned != *html {
+						replacements.push((range, cleaned));
+					}
+				}
+
+				Event::Start(Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. })
+					if !self.is_allowed_url(&dest_url) =>
+				{
+					// Drop just the destination when it is written literally,
+					// otherwise (e.g., entity-encoded) drop the whole link.
+					let range = match markdown[range.clone()].rfind(dest_url.as_ref()) {
+						Some(offset) => {
+							let start = range.start + offset;
+							start..start + dest_url.len()
+						}
+						None => range,
+					};
+
+					replacements.push((range, String::new()));
+				}
+
+				_ => {}
+			}
+		}
+
+		apply_replacements(markdown, replacements)
+	}
+
+	/// Sanitize a raw HTML fragment from markdown, which may be a lone
+	/// opening or closing tag that the surrounding markdown balances.
+	fn clean_fragment(&self, fragment: &str) -> String {
+		let trimmed = fragment.trim_end();
+
+		// Keep lone closing tags of allowed elements.
+		if let Some(name) = trimmed
+			.strip_prefix("</")
+			.and_then(|rest| rest.strip_suffix('>'))
+		{
+			let name = name.trim().to_lowercase();
+
+			return match self.config.allowed_tags.contains(&name) {
+				true => fragment.to_string(),
+				false => String::new(),
+			};
+		}
+
+		let cleaned = self.clean_html(fragment);
+
+		// Don't close lone opening tags.
+		let name: String = trimmed
+			.strip_prefix('<')
+			.unwrap_or_default()
+			.chars()
+			.take_while(char::is_ascii_alphanumeric)
+			.collect();
+
+		match cleaned.strip_suffix(&format!("</{}>", name.to_lowercase())) {
+			Some(opening) if !name.is_empty() && !trimmed.ends_with(&format!("</{name}>")) => {
+				opening.to_string()
+			}
+			_ => cleaned,
+		}
+	}
+
+	/// Sanitize an HTML fragment against the allow-list.
+	fn clean_html(&self, html: &str) -> String {
+		let tags = self
+			.config
+			.allowed_tags
+			.iter()
+			.map(String::as_str)
+			.filter(|tag| !CONTENT_TAGS.contains(tag))
+			.collect();
+
+		let url_schemes = self
+			.config
+			.allowed_url_schemes
+			.iter()
+			.map(String::as_str)
+			.collect();
+
+		ammonia::Builder::default()
+			.tags(tags)
+			.url_schemes(url_schemes)
+			.clean(html)
+			.to_string()
+	}
+
+	/// Check whether a URL is relative or uses an allowed scheme.
+	fn is_allowed_url(&self, url: &str) -> bool {
+		let url = url.trim_start_matches(|c: char| c.is_whitespace() || c.is_control());
+
+		let Some((scheme, _)) = url.split_once(':') else {
+			return true;
+		};
+
+		// A colon after a path, query, or fragment does not start a scheme.
+		if scheme.contains(['/', '?', '#']) {
+			return true;
+		}
+
+		self
+			.config
+			.allowed_url_schemes
+			.contains(&scheme.to_lowercase())
+	}
+}
+
+/// Apply non-overlapping replacements to a string, skipping any replacement
+/// that overlaps an earlier one.
+fn apply_replacements(source: &str, mut replacements: Vec<(Range<usize>, String)>) -> String {
+	replacements.sort_by_key(|(range, _)| range.start);
+
+	let mut output = String::with_capacity(source.len());
+	let mut cursor = 0;
+
+	for (range, replacement) in replacements {
+		if range.start < cursor {
+			continue;
+		}
+
+		output.push_str(&source[cursor..range.start]);
+		output.push_str(&replacement);
+		cursor = range.end;
+	}
+
+	output.push_str(&source[cursor..]);
+	output
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn render(markdown: &str) -> String {
+		Sanitizer::default().render(markdown)
+	}
+
+	#[test]
+	fn test_render_plain_markdown() {
+		assert_eq!(
+			render("Some **bold** text with [a link](https://example.com)."),
+			"<p>Some <strong>bold</strong> text with <a href=\"https://example.com\" rel=\"noopener noreferrer\">a link</a>.</p>\n"
+		);
+	}
+
+	#[test]
+	fn test_render_strips_scripts() {
+		let html = render("Hello <script>alert(1)</script> world\n\n<script>\nalert(2)\n</script>");
+		assert!(!html.contains("script"));
+		assert!(!html.contains("alert"));
+	}
+
+	#[test]
+	fn test_render_strips_event_handlers() {
+		let html =
+			render("<img src=\"x.png\" onerror=\"alert(1)\"> <b onmouseover=\"alert(1)\">hi</b>");
+		assert!(!html.contains("onerror"));
+		assert!(!html.contains("onmouseover"));
+		assert!(html.contains("<img src=\"x.png\">"));
+	}
+
+	#[test]
+	fn test_render_strips_unsafe_urls() {
+		for markdown in [
+			"[click](javascript:alert(1))",
+			"[click](JaVaScRiPt:alert(1))",
+			"[click](javascript&#58;alert(1))",
+			"[click](vbscript:msgbox(1))",
+			"![image](data:text/html;base64,PHNjcmlwdD4=)",
+			"<a href=\"javascript:alert(1)\">click</a>",
+			"[click][ref]\n\n[ref]: javascript:alert(1)",
+		] {
+			let html = render(markdown);
+			assert!(
+				!html.to_lowercase().contains("javascript"),
+				"{markdown}: {html}"
+			);
+			assert!(!html.contains("vbscript"), "{markdown}: {html}");
+			assert!(!html.contains("data:"), "{markdown}: {html}");
+		}
+	}
+
+	#[test]
+	fn test_render_escapes_link_titles() {
+		let html = render("[x](https://example.com \"a\\\" onmouseover=\\\"alert(1)\")");
+		assert!(!html.contains("\" onmouseover"), "{html}");
+	}
+
+	#[test]
+	fn test_render_strips_embeds() {
+		for markdown in [
+			"<iframe src=\"https://evil.example\"></iframe>",
+			"<object data=\"evil.swf\"></object>",
+			"<embed src=\"evil.swf\">",
+			"<svg><script>alert(1)</script></svg>",
+			"<style>body { display: none }</style>",
+		] {
+			let html = render(markdown);
+
+			for tag in ["iframe", "object", "embed", "svg", "script", "style"] {
+				assert!(!html.contains(tag), "{markdown}: {html}");
+			}
+		}
+	}
+
+	#[test]
+	fn test_custom_config() {
+		let sanitizer = Sanitizer::new(SanitizerConfig {
+			allowed_tags: ["p", "a"].iter().map(|t| t.to_string()).collect(),
+			allowed_url_schemes: ["https"].iter().map(|s| s.to_string()).collect(),
+			sanitize_on_save: true,
+		});
+
+		let html = sanitizer.render("**bold** [secure](https://a.example) [plain](http://a.example)");
+		assert!(!html.contains("<strong>"));
+		assert!(html.contains("href=\"https://a.example\""));
+		assert!(!html.contains("http://a.example"));
+	}
+
+	#[test]
+	fn test_clean_markdown() {
+		let sanitizer = Sanitizer::default();
+
+		// Ordinary markdown, including nutty tags, is unchanged.
+		let markdown =
+			"# Title\n\nSee [[abcdefg|Page]] and [docs](https://example.com) <em>now</em>.";
+		assert_eq!(sanitizer.clean_markdown(markdown), markdown);
+
+		// Raw HTML is sanitized.
+		assert_eq!(
+			sanitizer.clean_markdown("Hi <img src=\"x.png\" onerror=\"alert(1)\">!"),
+			"Hi <img src=\"x.png\">!"
+		);
+
+		// Unsafe destinations are dropped.
+		assert_eq!(
+			sanitizer.clean_markdown("[click](javascript:alert(1)) and ![i](https://a.example/i.png)"),
+			"[click]() and ![i](https://a.example/i.png)"
+		);
+
+		// Relative URLs are allowed.
+		assert_eq!(
+			sanitizer.clean_markdown("[page](/pages/one?x=a:b)"),
+			"[page](/pages/one?x=a:b)"
+		);
+	}
+}
@@ -1,12 +1,120 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::DateTime;
+use chrono::FixedOffset;
+use chrono::NaiveDate;
+use serde_json::Value;
+
+use crate::access::models::ResourceGrant;
+use crate::access::models::ResourceKind;
 use crate::access::service::AccessService;
+use crate::access::service::AccessServiceApi;
+use crate::content::block_kind::BlockKindError;
+use crate::content::block_kind::BlockKindRegistry;
+use crate::content::html_import;
+use crate::content::html_import::HtmlImportError;
+use crate::content::link_access::LinkAccessPolicy;
 use crate::content::repository::ContentRepository;
 use crate::content::repository::ContentRepositoryError;
+use crate::content::sanitizer::Sanitizer;
+use crate::models::BlockContent;
+use crate::models::BlockQuery;
 use crate::models::ContentBlock;
+use crate::models::ContentCalendar;
 use crate::models::ContentContext;
 use crate::models::ContentLink;
 use crate::models::DissociatedNuttyId;
+use crate::models::FractionalIndex;
+use crate::models::NuttyId;
+use crate::models::ShareLevel;
+use crate::models::Task;
+use crate::models::access_request::AccessRequest;
+use crate::models::access_request::AccessRequestStatus;
+use crate::models::block_annotation::BlockAnnotation;
+use crate::models::block_archival::BlockArchival;
+use crate::models::block_checksum::BlockChecksum;
+use crate::models::block_conversion;
+use crate::models::block_conversion::BlockConversion;
+use crate::models::block_conversion::BlockConversionError;
+use crate::models::block_conversion::ConvertibleKind;
+use crate::models::block_date::BlockDate;
+use crate::models::block_deletion::BlockDeletion;
+use crate::models::block_group::RootBlockGroup;
+use crate::models::block_merge;
+use crate::models::block_merge::BlockMerge;
+use crate::models::block_merge::BlockMergeError;
+use crate::models::block_merge::MergeStrategy;
+use crate::models::block_query::BlockQueryError;
+use crate::models::block_revision::BlockRevision;
+use crate::models::block_split;
+use crate::models::block_split::BlockSplit;
+use crate::models::block_split::BlockSplitError;
+use crate::models::block_stats::BlockStatsCheck;
+use crate::models::capture::Capture;
+use crate::models::capture::CaptureError;
+use crate::models::capture::CapturedPage;
+use crate::models::capture::PageSnapshot;
+use crate::models::children_view::ChildrenView;
+use crate::models::content_compression::CompressionStats;
+use crate::models::content_diff::ContentDiff;
+use crate::models::content_filter::ContentFilter;
+use crate::models::context_include::ContextInclude;
+use crate::models::context_include::ContextPart;
+use crate::models::find_replace::FindReplace;
+use crate::models::find_replace::FindReplaceError;
+use crate::models::find_replace::TextMatch;
+use crate::models::fractional_index::FractionalIndexError;
+use crate::models::incoming_email::IncomingEmail;
+use crate::models::incoming_email::IncomingEmailError;
+use crate::models::linked_reference::LinkedReference;
+use crate::models::outline::OutlineEntry;
+use crate::models::ownership_transfer::OwnershipTransfer;
+use crate::models::property::PropertyDefinition;
+use crate::models::property::PropertyError;
+use crate::models::property::validate_properties;
+use crate::models::search_language::SearchLanguage;
+use crate::models::site_settings::SiteSettings;
+use crate::models::site_settings::SiteSettingsError;
+use crate::models::slug::Slug;
+use crate::models::slug::SlugTarget;
+use crate::models::sync_changes::ChangeKind;
+use crate::models::sync_changes::SyncChanges;
+use crate::models::sync_changes::SyncCursor;
+use crate::models::sync_changes::Tombstone;
+use crate::models::task::Breadcrumb;
+use crate::models::task::TaskStatus;
+use crate::models::translation::Lang;
+use crate::models::translation::LocalizedBlock;
+use crate::models::translation::Translation;
+use crate::utilities::query_metrics::QueryMetrics;
 use crate::utilities::repository::Repository;
-use crate::utilities::repository::TransactionExt;
+use crate::utilities::repository::TxnContext;
+use crate::utilities::repository::lock_children_tx;
+use crate::utilities::row_level_security;
+
+/// The longest date range a content calendar can span, in days.
+pub const MAX_CALENDAR_DAYS: i64 = 366;
+
+/// The most todo blocks considered for a task list, before access checks.
+pub const MAX_TASKS: i64 = 500;
+
+/// The number of blocks searched at a time by a find and replace.
+pub const FIND_REPLACE_BATCH_SIZE: i64 = 200;
+
+/// The most search results considered, before access checks.
+pub const MAX_SEARCH_RESULTS: i64 = 100;
+
+/// The most blocks considered for a content listing, before access checks.
+pub const MAX_LISTED_BLOCKS: i64 = 500;
+
+/// The most related blocks included in a content context.
+pub const MAX_RELATED_BLOCKS: i64 = 10;
+
+/// The most changes pulled by a sync client at a time.
+pub const MAX_SYNC_CHANGES: i64 = 500;
 
 #[derive(Clone)]
 pub struct ContentService {
@@ -15,6 +123,15 @@ pub struct ContentService {
 
 	/// The access service to use for permission checking.
 	access_service: AccessService,
+
+	/// The sanitizer to clean markdown with before saving, if configured to.
+	sanitizer: Sanitizer,
+
+	/// The kinds that content may take.
+	block_kinds: Arc<BlockKindRegistry>,
+
+	/// What to do with links to blocks that their author can't read.
+	link_access_policy: LinkAccessPolicy,
 }
 
 impl ContentService {
@@ -23,400 +140,8734 @@ impl ContentService {
 		ContentService {
 			repository,
 			access_service,
+			sanitizer: Sanitizer::default(),
+			block_kinds: Arc::default(),
+			link_access_policy: LinkAccessPolicy::default(),
 		}
 	}
 
-	/// Get a content block's context.
-	pub async fn get_content_block_context(
-		&self,
-		nutty_id: &DissociatedNuttyId,
-	) -> Result<ContentContext, ContentServiceError> {
-		// Get the content block.
-		let content_block = self
-			.repository
-			.get_content_block(nutty_id)
-			.await
-			.map_err(ContentServiceError::FetchContentBlock)?
-			.ok_or(ContentServiceError::ContentBlockNotFound)?;
-
-		// Get the ancestor blocks.
-		let ancestors = self
-			.repository
-			.get_ancestor_blocks(nutty_id)
-			.await
-			.map_err(ContentServiceError::FetchAncestorBlocks)?;
-
-		// Get the descendant blocks.
-		let descendants = self
-			.repository
-			.get_descendant_blocks(nutty_id)
-			.await
-			.map_err(ContentServiceError::FetchDescendantBlocks)?;
-
-		// Get immediate children.
-		let children_ids = descendants
-			.iter()
-			.filter(|block| (block.parent_id.map(|i| i.nid()) == Some(nutty_id.nid())))
-			.map(|block| *block.nutty_id())
-			.collect::<Vec<_>>();
+	/// Use the given sanitizer for content being saved.
+	pub fn with_sanitizer(mut self, sanitizer: Sanitizer) -> Self {
+		self.sanitizer = sanitizer;
+		self
+	}
 
-		// Get outbound links (references).
-		let outbound_links = self
-			.repository
-			.get_content_links_from(content_block.nutty_id())
-			.await
-			.map_err(ContentServiceError::FetchOutboundLinks)?;
+	/// Accept content of the given kinds, e.g. with plugins registered.
+	pub fn with_block_kinds(mut self, block_kinds: Arc<BlockKindRegistry>) -> Self {
+		self.block_kinds = block_kinds;
+		self
+	}
 
-		// Get inbound links (backlinks).
-		let inbound_links = self
-			.repository
-			.get_content_links_to(content_block.nutty_id())
-			.await
-			.map_err(ContentServiceError::FetchInboundLinks)?;
+	/// Handle links to blocks that their author can't read with the given
+	/// policy.
+	pub fn with_link_access_policy(mut self, link_access_policy: LinkAccessPolicy) -> Self {
+		self.link_access_policy = link_access_policy;
+		self
+	}
 
-		// Build the block cache.
-		let mut block_cache = std::collections::HashMap::new();
+	/// Recompress the content that Postgres compressed with pglz, before it
+	/// was set to compress content with lz4, in batches, recording how much
+	/// was compressed in the query metrics. Recompressing a block counts as
+	/// updating it, so sync clients pull it again.
+	pub async fn recompress_content(
+		&self,
+		batch_size: i64,
+	) -> Result<CompressionStats, ContentServiceError> {
+		let mut stats = CompressionStats::default();
 
-		// Add the main content block to the cache.
-		block_cache.insert(*content_block.nutty_id(), content_block.clone());
+		loop {
+			let (ids, batch_stats) = self
+				.repository
+				.with_transaction(|tx| {
+					Box::pin(async move {
+						let ctx = &mut tx.context();
+
+						let ids = self
+							.repository
+							.recompress_content_tx(ctx.conn(), batch_size)
+							.await?;
+
+						let stats = self
+							.repository
+							.get_compression_stats_tx(ctx.conn(), &ids)
+							.await?;
+
+						Ok((ids, stats))
+					})
+				})
+				.await
+				.map_err(ContentServiceError::CompressContent)?;
 
-		// Add ancestor blocks to the cache.
-		for block in &ancestors {
-			block_cache.insert(*block.nutty_id(), block.clone());
-		}
+			// Recompressed content is no longer pglz, so batches run out.
+			if ids.is_empty() {
+				return Ok(stats);
+			}
 
-		// Add descendant blocks to the cache.
-		for block in &descendants {
-			block_cache.insert(*block.nutty_id(), block.clone());
+			QueryMetrics::global().record_compression(&batch_stats);
+			stats.merge(&batch_stats);
 		}
-
-		// Extract reference and backlink IDs.
-		let reference_ids = outbound_links.iter().map(|link| link.target_id).collect();
-		let backlink_ids = inbound_links.iter().map(|link| link.source_id).collect();
-
-		// Create the content context.
-		let context = ContentContext::builder()
-			.block_id(*content_block.nutty_id())
-			.parent_id(content_block.parent_id)
-			.children_ids(children_ids)
-			.reference_ids(reference_ids)
-			.backlink_ids(backlink_ids)
-			.block_cache(block_cache)
-			.try_build()
-			.map_err(|err| ContentServiceError::BuildContentContext(err.to_string()))?;
-
-		Ok(context)
 	}
 
-	/// Save a content block.
-	pub async fn save_content_block(
+	/// Get a content block, failing if it doesn't exist.
+	async fn get_content_block(
 		&self,
-		content_block: ContentBlock,
+		block_id: &DissociatedNuttyId,
 	) -> Result<ContentBlock, ContentServiceError> {
 		self
 			.repository
-			.with_transaction(|tx| {
-				Box::pin(async move {
-					// Save the content block.
-					let content_block = self
-						.repository
-						.upsert_content_block_tx(tx.as_executor(), content_block.clone())
-						.await
-						.map_err(ContentServiceError::SaveContentBlock)?;
-
-					// Parse tags from the content block.
-					let target_tags = content_block.content.parse_target_tags();
-
-					// Resolve [NuttyTag] references.
-					let target_ids = self
-						.repository
-						.resolve_nutty_ids_tx(
-							tx.as_executor(),
-							target_tags
-								.iter()
-								.map(|tag| tag.nutty_id())
-								.collect::<Vec<_>>(),
-						)
-						.await;
-
-					// Delete orphaned content links.
-					self
-						.repository
-						.delete_orphaned_content_links_tx(
-							tx.as_executor(),
-							content_block.nutty_id(),
-							&target_ids,
-						)
-						.await
-						.map_err(ContentServiceError::DeleteContentLinks)?;
-
-					// Create new content links.
-					let content_links: Vec<ContentLink> = target_ids
-						.iter()
-						.map(|target_id| ContentLink::now(*content_block.nutty_id(), *target_id))
-						.collect();
+			.get_content_block(block_id)
+			.await
+			.map_err(ContentServiceError::FetchContentBlock)?
+			.ok_or(ContentServiceError::ContentBlockNotFound)
+	}
 
-					// Save the content links.
-					self
-						.repository
-						.upsert_content_links_tx(tx.as_executor(), &content_links)
-						.await
-						.map_err(ContentServiceError::SaveContentLink)?;
+	/// Show a navigator the published revisions of the blocks they can't
+	/// edit, instead of the drafts.
+	async fn show_published(
+		&self,
+		navigator_id: &NuttyId,
+		blocks: &mut [ContentBlock],
+	) -> Result<(), ContentServiceError> {
+		let ids: Vec<NuttyId> = blocks.iter().map(|block| *block.nutty_id()).collect();
+		let mut contents = self.list_published_contents(navigator_id, &ids).await?;
+
+		for block in blocks {
+			if let Some(content) = contents.remove(block.nutty_id()) {
+				block.content = content;
+			}
+		}
 
-					// Return the saved content block.
-					Ok(content_block)
-				})
-			})
-			.await
+		Ok(())
 	}
 
-	/// Check if a navigator has access to a content block or any of its ancestors.
-	pub async fn check_content_block_access(
+	/// Check if a navigator has been blocked by a block's owner.
+	async fn is_blocked_by_owner(
 		&self,
-		navigator_id: &crate::models::NuttyId,
-		block_id: &DissociatedNuttyId,
+		navigator_id: &NuttyId,
+		block: &ContentBlock,
 	) -> Result<bool, ContentServiceError> {
-		// First, resolve the DissociatedNuttyId to a NuttyId.
-		let resolved_block_id = self
+		let Some(owner_id) = block.owner_id() else {
+			return Ok(false);
+		};
+
+		self
 			.repository
-			.resolve_nutty_id(block_id.clone())
+			.is_blocked_by(owner_id, navigator_id)
 			.await
-			.map_err(ContentServiceError::FetchContentBlock)?;
+			.map_err(ContentServiceError::FetchContentBlock)
+	}
 
-		// 1. Check if the navigator has global read permission.
-		let can_access_globally = self
+	/// Check that a navigator may read and write raw content.
+	async fn require_raw_content_permission(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<(), ContentServiceError> {
+		let can_access = self
 			.access_service
-			.can_permission(navigator_id, "content_blocks:read:all")
+			.can_permission(navigator_id, "content_blocks:raw")
 			.await
 			.map_err(ContentServiceError::AccessControl)?;
 
-		if can_access_globally {
-			return Ok(true);
+		match can_access {
+			true => Ok(()),
+			false => Err(ContentServiceError::RawContentDenied),
 		}
+	}
 
-		// 2. Check if the navigator has access to the requested block.
-		let can_access_block = self
-			.access_service
-			.can_on_resource(
-				navigator_id,
-				"content_blocks:read:resource",
-				"content_block",
-				&resolved_block_id,
-			)
+	/// Get a content block within a transaction, failing if it doesn't exist.
+	async fn get_existing_block_tx(
+		&self,
+		ctx: &mut TxnContext<'_>,
+		block_id: &DissociatedNuttyId,
+	) -> Result<ContentBlock, ContentServiceError> {
+		self
+			.repository
+			.get_content_block_tx(ctx.conn(), block_id)
 			.await
-			.map_err(ContentServiceError::AccessControl)?;
+			.map_err(ContentServiceError::FetchContentBlock)?
+			.ok_or(ContentServiceError::ContentBlockNotFound)
+	}
 
-		if can_access_block {
-			return Ok(true);
-		}
+	/// Keep the translations that a navigator can read. Hidden variants are
+	/// left out, as are those that were never shared with them.
+	async fn readable_translations(
+		&self,
+		navigator_id: &NuttyId,
+		translations: Vec<Translation>,
+	) -> Result<Vec<Translation>, ContentServiceError> {
+		let ids: Vec<NuttyId> = translations
+			.iter()
+			.map(|translation| translation.block_id)
+			.collect();
 
-		// 3. Check if the navigator has ownership permission.
-		let can_access_own = self
-			.access_service
-			.can_permission(navigator_id, "content_blocks:read:own")
-			.await
-			.map_err(ContentServiceError::AccessControl)?;
+		let readable_ids = self.list_readable_block_ids(navigator_id, &ids).await?;
 
-		if can_access_own {
-			// Check if the navigator owns the block.
-			let content_block = self
-				.repository
-				.get_content_block(block_id)
-				.await
-				.map_err(ContentServiceError::FetchContentBlock)?
-				.ok_or(ContentServiceError::ContentBlockNotFound)?;
+		Ok(translations
+			.into_iter()
+			.filter(|translation| readable_ids.contains(&translation.block_id))
+			.collect())
+	}
 
-			if let Some(owner_id) = content_block.owner_id {
-				if owner_id == *navigator_id {
-					return Ok(true);
-				}
-			}
-		}
+	/// Get the links into a content block within a transaction. Links to its
+	/// translations are backlinks to it, too.
+	async fn get_backlinks_tx(
+		&self,
+		ctx: &mut TxnContext<'_>,
+		block_id: &NuttyId,
+	) -> Result<Vec<ContentLink>, ContentServiceError> {
+		let mut inbound_links = self
+			.repository
+			.get_content_links_to_tx(ctx.conn(), block_id)
+			.await
+			.map_err(ContentServiceError::FetchInboundLinks)?;
 
-		// 4. Check if the navigator has access to any ancestor blocks.
-		let ancestors = self
+		let translations = self
 			.repository
-			.get_ancestor_blocks(block_id)
+			.list_translations_tx(ctx.conn(), block_id)
 			.await
-			.map_err(ContentServiceError::FetchAncestorBlocks)?;
+			.map_err(ContentServiceError::FetchTranslations)?;
 
-		for ancestor in &ancestors {
-			let can_access_ancestor = self
-				.access_service
-				.can_on_resource(
-					navigator_id,
-					"content_blocks:read:resource",
-					"content_block",
-					ancestor.nutty_id(),
-				)
+		for translation in &translations {
+			if translation.block_id == *block_id {
+				continue;
+			}
+
+			let links = self
+				.repository
+				.get_content_links_to_tx(ctx.conn(), &translation.block_id)
 				.await
-				.map_err(ContentServiceError::AccessControl)?;
+				.map_err(ContentServiceError::FetchInboundLinks)?;
 
-			if can_access_ancestor {
-				return Ok(true);
+			for link in links {
+				if !inbound_links.iter().any(|l| l.source_id == link.source_id) {
+					inbound_links.push(link);
+				}
 			}
 		}
 
-		Ok(false)
+		Ok(inbound_links)
 	}
 
-	/// Check if a navigator has write access to a content block or any of its ancestors.
-	pub async fn check_content_block_write_access(
+	/// Absorb a source block into a target block within a transaction: move
+	/// the source's children after the target's, point tags at the source to
+	/// the target, then delete the source. Returns the Nutty IDs of the moved
+	/// and the relinked blocks.
+	async fn absorb_block_tx(
 		&self,
-		navigator_id: &crate::models::NuttyId,
-		block_id: &DissociatedNuttyId,
-	) -> Result<bool, ContentServiceError> {
-		// First, resolve the DissociatedNuttyId to a NuttyId.
-		let resolved_block_id = self
+		ctx: &mut TxnContext<'_>,
+		source: &ContentBlock,
+		target: &ContentBlock,
+	) -> Result<(Vec<NuttyId>, Vec<NuttyId>), ContentServiceError> {
+		// Move the source's children after the target's.
+		lock_children_tx(ctx.conn(), target.nutty_id()).await?;
+
+		let target_child_ids = self
 			.repository
-			.resolve_nutty_id(block_id.clone())
+			.list_child_ids_tx(ctx.conn(), target.nutty_id(), &ChildrenView::default())
 			.await
-			.map_err(ContentServiceError::FetchContentBlock)?;
+			.map_err(ContentServiceError::FetchDescendantBlocks)?;
 
-		// 1. Check if the navigator has global write permission.
-		let can_write_globally = self
-			.access_service
-			.can_permission(navigator_id, "content_blocks:write:all")
+		let mut f_index = match target_child_ids.last() {
+			Some(child_id) => {
+				self
+					.get_existing_block_tx(ctx, &child_id.dissociate())
+					.await?
+					.f_index
+			}
+			None => FractionalIndex::start(),
+		};
+
+		let source_child_ids = self
+			.repository
+			.list_child_ids_tx(ctx.conn(), source.nutty_id(), &ChildrenView::default())
 			.await
-			.map_err(ContentServiceError::AccessControl)?;
+			.map_err(ContentServiceError::FetchDescendantBlocks)?;
 
-		if can_write_globally {
-			return Ok(true);
-		}
+		let mut moved_ids = vec![];
 
-		// 2. Check if the navigator has direct write access to the requested block.
-		let can_write_block = self
-			.access_service
-			.can_on_resource(
-				navigator_id,
-				"content_blocks:write",
-				"content_block",
-				&resolved_block_id,
-			)
-			.await
-			.map_err(ContentServiceError::AccessControl)?;
+		for child_id in source_child_ids {
+			let mut child = self
+				.get_existing_block_tx(ctx, &child_id.dissociate())
+				.await?;
 
-		if can_write_block {
-			return Ok(true);
-		}
+			f_index = FractionalIndex::between(&f_index, &FractionalIndex::end())
+				.map_err(ContentServiceError::OrderContentBlock)?;
 
-		// 3. Check if the navigator has ownership write permission.
-		let can_write_own = self
-			.access_service
-			.can_permission(navigator_id, "content_blocks:write:own")
-			.await
-			.map_err(ContentServiceError::AccessControl)?;
+			child.parent_id = Some(*target.nutty_id());
+			child.f_index = f_index.clone();
 
-		if can_write_own {
-			// Check if the navigator owns the block.
-			let content_block = self
+			self
 				.repository
-				.get_content_block(block_id)
+				.upsert_content_block_tx(ctx.conn(), child)
 				.await
-				.map_err(ContentServiceError::FetchContentBlock)?
-				.ok_or(ContentServiceError::ContentBlockNotFound)?;
+				.map_err(ContentServiceError::SaveContentBlock)?;
 
-			if let Some(owner_id) = content_block.owner_id {
-				if owner_id == *navigator_id {
-					return Ok(true);
-				}
-			}
+			moved_ids.push(child_id);
 		}
 
-		// 4. Check if the navigator has write access to any ancestor blocks.
-		let ancestors = self
+		// Point tags at the source to the target.
+		let inbound_links = self
 			.repository
-			.get_ancestor_blocks(block_id)
+			.get_content_links_to_tx(ctx.conn(), source.nutty_id())
 			.await
-			.map_err(ContentServiceError::FetchAncestorBlocks)?;
+			.map_err(ContentServiceError::FetchInboundLinks)?;
 
-		for ancestor in &ancestors {
-			let can_write_ancestor = self
-				.access_service
-				.can_on_resource(
-					navigator_id,
-					"content_blocks:write",
-					"content_block",
-					ancestor.nutty_id(),
-				)
-				.await
-				.map_err(ContentServiceError::AccessControl)?;
+		let mut relinked_ids = vec![];
 
-			if can_write_ancestor {
-				return Ok(true);
+		for link in inbound_links
+			.iter()
+			.filter(|link| link.source_id != *source.nutty_id())
+		{
+			let mut block = self
+				.get_existing_block_tx(ctx, &link.source_id.dissociate())
+				.await?;
+
+			let Some(content) = block.content.retarget_references(
+				&source.nutty_id().dissociate(),
+				&target.nutty_id().dissociate(),
+			) else {
+				continue;
+			};
+
+			// Saving replaces the block's links with those in its content,
+			// which were already there, so no author's access is checked.
+			block.content = content;
+			self.save_content_block_tx(ctx, None, block).await?;
+
+			if !relinked_ids.contains(&link.source_id) {
+				relinked_ids.push(link.source_id);
 			}
 		}
 
-		Ok(false)
+		self
+			.repository
+			.delete_content_blocks_tx(ctx.conn(), &[*source.nutty_id()])
+			.await
+			.map_err(ContentServiceError::DeleteContentBlock)?;
+
+		Ok((moved_ids, relinked_ids))
 	}
-}
 
-#[derive(Debug, thiserror::Error)]
-pub enum ContentServiceError {
-	#[error("Failed to save content block: {0}")]
-	SaveContentBlock(#[source] ContentRepositoryError),
+	/// Get the sibling right after a content block within a transaction, if
+	/// it has a parent and isn't its last child.
+	async fn next_sibling_tx(
+		&self,
+		ctx: &mut TxnContext<'_>,
+		block: &ContentBlock,
+	) -> Result<Option<ContentBlock>, ContentServiceError> {
+		let Some(parent_id) = block.parent_id else {
+			return Ok(None);
+		};
+
+		let sibling_ids = self
+			.repository
+			.list_child_ids_tx(ctx.conn(), &parent_id, &ChildrenView::default())
+			.await
+			.map_err(ContentServiceError::FetchDescendantBlocks)?;
 
-	#[error("Failed to save content link: {0}")]
-	SaveContentLink(#[source] ContentRepositoryError),
+		let next_id = sibling_ids
+			.iter()
+			.skip_while(|id| *id != block.nutty_id())
+			.nth(1);
+
+		match next_id {
+			Some(next_id) => Ok(Some(
+				self
+					.get_existing_block_tx(ctx, &next_id.dissociate())
+					.await?,
+			)),
+			None => Ok(None),
+		}
+	}
 
-	#[error("Failed to delete content links: {0}")]
-	DeleteContentLinks(#[source] ContentRepositoryError),
+	/// Get a revision of a content block, failing if it doesn't exist.
+	async fn get_block_revision(
+		&self,
+		block_id: &DissociatedNuttyId,
+		revision_id: &DissociatedNuttyId,
+	) -> Result<BlockRevision, ContentServiceError> {
+		self
+			.repository
+			.get_block_revision(block_id, revision_id)
+			.await
+			.map_err(ContentServiceError::FetchRevision)?
+			.ok_or(ContentServiceError::RevisionNotFound)
+	}
 
-	#[error("Database error: {0}")]
-	Database(#[from] sqlx::Error),
+	/// Save a content block within a transaction, replacing its links and
+	/// dates with those parsed from its content. Links to blocks that the
+	/// author, if any, can't read are handled by the [LinkAccessPolicy].
+	async fn save_content_block_tx(
+		&self,
+		ctx: &mut TxnContext<'_>,
+		author_id: Option<&NuttyId>,
+		content_block: ContentBlock,
+	) -> Result<ContentBlock, ContentServiceError> {
+		// Save the content block.
+		let content_block = self
+			.repository
+			.upsert_content_block_tx(ctx.conn(), content_block)
+			.await
+			.map_err(ContentServiceError::SaveContentBlock)?;
 
-	#[error("Content block not found")]
-	ContentBlockNotFound,
+		// Parse tags from the content block.
+		let target_tags = self.block_kinds.extract_tags(&content_block.content);
 
-	#[error("Failed to fetch content block: {0}")]
-	FetchContentBlock(#[source] ContentRepositoryError),
+		// Resolve [NuttyTag] references.
+		let target_ids = self
+			.repository
+			.resolve_nutty_ids_tx(
+				ctx.conn(),
+				target_tags
+					.iter()
+					.map(|tag| tag.nutty_id())
+					.collect::<Vec<_>>(),
+			)
+			.await;
 
-	#[error("Failed to fetch ancestor blocks: {0}")]
-	FetchAncestorBlocks(#[source] ContentRepositoryError),
+		// Find the targets that the author can't read, if saved on behalf of a
+		// navigator, so as not to reveal that they exist.
+		let restricted_ids = match author_id {
+			Some(author_id) => {
+				self
+					.find_unreadable_targets_tx(ctx, author_id, content_block.nutty_id(), &target_ids)
+					.await?
+			}
 
-	#[error("Failed to fetch descendant blocks: {0}")]
-	FetchDescendantBlocks(#[source] ContentRepositoryError),
+			None => HashSet::new(),
+		};
 
-	#[error("Failed to fetch outbound links: {0}")]
-	FetchOutboundLinks(#[source] ContentRepositoryError),
+		let target_ids = match self.link_access_policy {
+			LinkAccessPolicy::Drop => target_ids
+				.into_iter()
+				.filter(|target_id| !restricted_ids.contains(target_id))
+				.collect(),
+			LinkAccessPolicy::Mark => target_ids,
+		};
 
-	#[error("Failed to fetch inbound links: {0}")]
-	FetchInboundLinks(#[source] ContentRepositoryError),
+		// Delete orphaned content links.
+		self
+			.repository
+			.delete_orphaned_content_links_tx(ctx.conn(), content_block.nutty_id(), &target_ids)
+			.await
+			.map_err(ContentServiceError::DeleteContentLinks)?;
 
-	#[error("Failed to build content context: {0}")]
-	BuildContentContext(String),
+		// Create new content links.
+		let content_links: Vec<ContentLink> = target_ids
+			.iter()
+			.map(|target_id| {
+				ContentLink::now(*content_block.nutty_id(), *target_id)
+					.with_restricted(restricted_ids.contains(target_id))
+			})
+			.collect();
 
-	#[error("Access control error: {0}")]
-	AccessControl(#[source] crate::access::service::AccessServiceError),
-}
+		// Save the content links.
+		self
+			.repository
+			.upsert_content_links_tx(ctx.conn(), &content_links)
+			.await
+			.map_err(ContentServiceError::SaveContentLink)?;
 
-#[cfg(test)]
-mod tests {
-	use sqlx::Pool;
-	use sqlx::Postgres;
-	use sqlx::postgres::PgPoolOptions;
+		// Replace the block's dates.
+		let block_dates: Vec<BlockDate> = content_block
+			.content
+			.parse_dates()
+			.into_iter()
+			.map(|(date, kind)| BlockDate::new(*content_block.nutty_id(), date, kind))
+			.collect();
 
-	use super::*;
-	use crate::access::repository::AccessRepository;
-	use crate::access::service::AccessService;
-	use crate::content::repository::ContentRepository;
-	use crate::models::BlockContent;
-	use crate::models::ContentBlock;
+		self
+			.repository
+			.delete_block_dates_tx(ctx.conn(), content_block.nutty_id())
+			.await
+			.map_err(ContentServiceError::SaveBlockDates)?;
+
+		self
+			.repository
+			.insert_block_dates_tx(ctx.conn(), &block_dates)
+			.await
+			.map_err(ContentServiceError::SaveBlockDates)?;
+
+		// Return the saved content block.
+		Ok(content_block)
+	}
+
+	/// Find the link targets that an author can't read within a transaction.
+	/// Their own blocks are always readable, even if created earlier in the
+	/// transaction, and so is the linking block itself.
+	async fn find_unreadable_targets_tx(
+		&self,
+		ctx: &mut TxnContext<'_>,
+		author_id: &NuttyId,
+		source_id: &NuttyId,
+		target_ids: &[NuttyId],
+	) -> Result<HashSet<NuttyId>, ContentServiceError> {
+		if target_ids.is_empty() {
+			return Ok(HashSet::new());
+		}
+
+		let targets = self
+			.repository
+			.get_content_blocks_by_ids_tx(ctx.conn(), target_ids)
+			.await
+			.map_err(ContentServiceError::FetchContentBlock)?;
+
+		let checked_ids: Vec<NuttyId> = targets
+			.iter()
+			.filter(|target| target.nutty_id() != source_id && target.owner_id() != Some(author_id))
+			.map(|target| *target.nutty_id())
+			.collect();
+
+		if checked_ids.is_empty() {
+			return Ok(HashSet::new());
+		}
+
+		// Check every target at once, seeing every block in the transaction.
+		let readable_ids = self
+			.repository
+			.list_readable_ids_tx(ctx.conn(), author_id, &checked_ids)
+			.await
+			.map_err(ContentServiceError::FetchContentBlock)?;
+
+		Ok(checked_ids
+			.into_iter()
+			.filter(|id| !readable_ids.contains(id))
+			.collect())
+	}
+
+	/// Grant a navigator a [ShareLevel] on a content block, replacing any
+	/// level they were granted before, at once.
+	async fn grant_share_level(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &NuttyId,
+		level: ShareLevel,
+	) -> Result<(), ContentServiceError> {
+		self
+			.repository
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					self
+						.grant_share_level_tx(ctx, navigator_id, block_id, level)
+						.await
+				})
+			})
+			.await
+	}
+
+	/// Grant a navigator a [ShareLevel] on a content block within a
+	/// transaction, replacing any level they were granted before.
+	async fn grant_share_level_tx(
+		&self,
+		ctx: &mut TxnContext<'_>,
+		navigator_id: &NuttyId,
+		block_id: &NuttyId,
+		level: ShareLevel,
+	) -> Result<(), ContentServiceError> {
+		let replaced = ShareLevel::ALL.map(|level| level.role_name());
+
+		self
+			.access_service
+			.replace_resource_role_tx(
+				ctx.conn(),
+				navigator_id,
+				&replaced,
+				level.role_name(),
+				ResourceKind::ContentBlock,
+				block_id,
+			)
+			.await
+			.map_err(ContentServiceError::AccessControl)
+	}
+}
+
+/// Content operations.
+/// Implemented by [ContentService], and by an in-memory fake in the testkit.
+#[async_trait]
+pub trait ContentServiceApi: Send + Sync {
+	/// Get a content block's context for a navigator, with its children
+	/// presented in a [ChildrenView]. Query results and related blocks are
+	/// limited to the blocks that the navigator can read.
+	async fn get_content_block_context(
+		&self,
+		navigator_id: &NuttyId,
+		nutty_id: &DissociatedNuttyId,
+		view: &ChildrenView,
+	) -> Result<ContentContext, ContentServiceError>;
+
+	/// Get only some parts of a content block's context, skipping the
+	/// queries for the rest, with its children presented in a [ChildrenView].
+	async fn get_content_block_context_partial(
+		&self,
+		nutty_id: &DissociatedNuttyId,
+		include: &ContextInclude,
+		view: &ChildrenView,
+	) -> Result<ContentContext, ContentServiceError>;
+
+	/// Get a content block's context as it was at a time, from its history,
+	/// with its children presented in a [ChildrenView]. Only the tree is
+	/// reconstructed, without links, annotations or stats.
+	async fn get_content_block_context_as_of(
+		&self,
+		nutty_id: &DissociatedNuttyId,
+		as_of: &DateTime<FixedOffset>,
+		view: &ChildrenView,
+	) -> Result<ContentContext, ContentServiceError>;
+
+	/// Save a content block, on behalf of its author if any. Without one,
+	/// e.g. for seeds, links aren't checked for access.
+	async fn save_content_block(
+		&self,
+		author_id: Option<&NuttyId>,
+		content_block: ContentBlock,
+	) -> Result<ContentBlock, ContentServiceError>;
+
+	/// Rename a page that the navigator can write, and rewrite title
+	/// references in the blocks linking to it, or naming it by title, that
+	/// they can edit. Title links are left alone while another page has the
+	/// old title. Titles can't contain `[[` or `]]`, which would end a link.
+	/// Returns the renamed page followed by every rewritten block.
+	async fn rename_page(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		new_title: String,
+	) -> Result<Vec<ContentBlock>, ContentServiceError>;
+
+	/// Delete a content block and its descendants, counting the links into
+	/// them from other blocks. With `unlink`, tags in those blocks are first
+	/// converted to plain text. Callers must check for write access.
+	async fn delete_content_block(
+		&self,
+		block_id: &DissociatedNuttyId,
+		unlink: bool,
+	) -> Result<BlockDeletion, ContentServiceError>;
+
+	/// Check if a navigator has access to a content block or any of its ancestors.
+	async fn check_content_block_access(
+		&self,
+		navigator_id: &crate::models::NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<bool, ContentServiceError>;
+
+	/// Check if a navigator has write access to a content block or any of its ancestors.
+	async fn check_content_block_write_access(
+		&self,
+		navigator_id: &crate::models::NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<bool, ContentServiceError>;
+
+	/// List which of the given content blocks a moderator has hidden directly,
+	/// rather than through an ancestor.
+	async fn list_hidden_block_ids(
+		&self,
+		ids: &[NuttyId],
+	) -> Result<Vec<NuttyId>, ContentServiceError>;
+
+	/// List which of the given content blocks a navigator can read, checking
+	/// them all at once.
+	async fn list_readable_block_ids(
+		&self,
+		navigator_id: &NuttyId,
+		ids: &[NuttyId],
+	) -> Result<Vec<NuttyId>, ContentServiceError>;
+
+	/// Check if a navigator can comment on a content block: either they can
+	/// edit it, or they can read it and were granted the comment permission
+	/// on it or any of its ancestors.
+	async fn check_content_block_comment_access(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<bool, ContentServiceError>;
+
+	/// Check if a navigator can manage access to a content block or any of its ancestors.
+	async fn check_content_block_manage_access(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<bool, ContentServiceError>;
+
+	/// Transfer a content block, and optionally its descendants, to a new
+	/// owner. Requires the `content_blocks:transfer` permission. The previous
+	/// owners' roles on the blocks move to the new owner, and every transfer
+	/// is recorded for auditing.
+	async fn transfer_ownership(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		new_owner_id: &NuttyId,
+		include_descendants: bool,
+	) -> Result<Vec<OwnershipTransfer>, ContentServiceError>;
+
+	/// Get the roles granted on a content block, including those inherited
+	/// from its ancestors.
+	async fn get_content_block_grants(
+		&self,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Vec<ResourceGrant>, ContentServiceError>;
+
+	/// Share a content block with another navigator at a [ShareLevel],
+	/// replacing any level it was shared with before. Owners and navigators
+	/// who can manage access may share. Returns the block's updated grants.
+	async fn share_content_block(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		recipient_id: &NuttyId,
+		level: ShareLevel,
+	) -> Result<Vec<ResourceGrant>, ContentServiceError>;
+
+	/// Request access to a content block at a [ShareLevel], for review by
+	/// the navigators who manage it.
+	async fn request_access(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		level: ShareLevel,
+	) -> Result<AccessRequest, ContentServiceError>;
+
+	/// List the pending access requests for a content block, oldest first.
+	/// Callers must check that the navigator can manage the block.
+	async fn list_access_requests(
+		&self,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Vec<AccessRequest>, ContentServiceError>;
+
+	/// Approve or deny a pending access request. Approving grants the
+	/// requested level. Requires managing the requested block.
+	async fn resolve_access_request(
+		&self,
+		navigator_id: &NuttyId,
+		request_id: &NuttyId,
+		approve: bool,
+	) -> Result<AccessRequest, ContentServiceError>;
+
+	/// Get the date-linked blocks a navigator can access within an
+	/// inclusive date range.
+	async fn get_content_calendar(
+		&self,
+		navigator_id: &NuttyId,
+		from: NaiveDate,
+		to: NaiveDate,
+	) -> Result<ContentCalendar, ContentServiceError>;
+
+	/// Append a paragraph to the end of a navigator's daily note for a date,
+	/// creating the note if they don't have one yet.
+	async fn append_to_daily_note(
+		&self,
+		navigator_id: &NuttyId,
+		date: NaiveDate,
+		markdown: String,
+	) -> Result<ContentBlock, ContentServiceError>;
+
+	/// List the todos a navigator can access, optionally filtered by status
+	/// and by a due date before the given date.
+	async fn list_tasks(
+		&self,
+		navigator_id: &NuttyId,
+		status: Option<TaskStatus>,
+		due_before: Option<NaiveDate>,
+	) -> Result<Vec<Task>, ContentServiceError>;
+
+	/// Mark a todo as done, or as open again if it was done.
+	async fn toggle_todo(
+		&self,
+		block_id: &DissociatedNuttyId,
+	) -> Result<ContentBlock, ContentServiceError>;
+
+	/// Find and replace a regex pattern across the markdown of every block a
+	/// navigator owns, recording a revision for every changed block. With
+	/// `dry_run`, nothing is changed. Returns the matches, with snippets.
+	async fn find_and_replace(
+		&self,
+		owner_id: &NuttyId,
+		pattern: &str,
+		replacement: &str,
+		dry_run: bool,
+	) -> Result<Vec<TextMatch>, ContentServiceError>;
+
+	/// Diff a content block's content between two of its revisions, each
+	/// taken as the content it left behind. Without `from`, the change made
+	/// by `to` is diffed; without `to`, the block's current content is.
+	async fn diff_content_block(
+		&self,
+		block_id: &DissociatedNuttyId,
+		from: Option<&DissociatedNuttyId>,
+		to: Option<&DissociatedNuttyId>,
+	) -> Result<ContentDiff, ContentServiceError>;
+
+	/// Diff the current contents of two content blocks.
+	async fn diff_content_blocks(
+		&self,
+		from_id: &DissociatedNuttyId,
+		to_id: &DissociatedNuttyId,
+	) -> Result<ContentDiff, ContentServiceError>;
+
+	/// Publish a revision of a content block, which navigators who can't
+	/// edit the block see instead of its draft. Without a revision, the
+	/// current content is recorded as one, by the given navigator, and
+	/// published. Returns the Nutty ID of the published revision. Callers
+	/// must check for write access.
+	async fn publish_revision(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		revision_id: Option<&DissociatedNuttyId>,
+	) -> Result<NuttyId, ContentServiceError>;
+
+	/// Get the published revision of a content block, if it has one.
+	async fn get_published_revision(
+		&self,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Option<BlockRevision>, ContentServiceError>;
+
+	/// List the published content of the given content blocks that a
+	/// navigator can't edit, which every read path shows them instead of the
+	/// drafts. Blocks without a published revision are left out.
+	async fn list_published_contents(
+		&self,
+		navigator_id: &NuttyId,
+		ids: &[NuttyId],
+	) -> Result<HashMap<NuttyId, BlockContent>, ContentServiceError>;
+
+	/// Set a content block's slug, retiring its current one so that it
+	/// redirects to the new one. Returns the Nutty ID of the block. Callers
+	/// must check for write access.
+	async fn set_content_block_slug(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		slug: &Slug,
+	) -> Result<NuttyId, ContentServiceError>;
+
+	/// Resolve a permalink path, either a NID or a slug, to the content block
+	/// it leads to, if any.
+	async fn resolve_permalink(&self, path: &str)
+	-> Result<Option<SlugTarget>, ContentServiceError>;
+
+	/// List the blocks that a navigator owns, grouped by top-level ancestor,
+	/// most recently modified group first.
+	async fn list_owned_blocks_by_root(
+		&self,
+		owner_id: &NuttyId,
+	) -> Result<Vec<RootBlockGroup>, ContentServiceError>;
+
+	/// List the checksums of a content block and everything under it, for
+	/// sync clients to diff against. Callers must check for read access.
+	async fn list_block_checksums(
+		&self,
+		root_id: &DissociatedNuttyId,
+	) -> Result<Vec<BlockChecksum>, ContentServiceError>;
+
+	/// Get the outline of a content block and everything under it that a
+	/// navigator can read, depth first, with at most `limit` children of each
+	/// block. Callers must check for read access to the root.
+	async fn get_outline(
+		&self,
+		navigator_id: &NuttyId,
+		root_id: &DissociatedNuttyId,
+		after_id: Option<&DissociatedNuttyId>,
+		limit: i64,
+	) -> Result<Vec<OutlineEntry>, ContentServiceError>;
+
+	/// List the changes to the blocks that a navigator owns, and to the links
+	/// from them, since a cursor: the blocks and links saved, as they are now,
+	/// and tombstones for those deleted.
+	async fn list_changes(
+		&self,
+		navigator_id: &NuttyId,
+		since: Option<SyncCursor>,
+	) -> Result<SyncChanges, ContentServiceError>;
+
+	/// Get a navigator's site settings, if they have any.
+	async fn get_site_settings(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Option<SiteSettings>, ContentServiceError>;
+
+	/// Save a navigator's site settings, replacing any they had. Callers
+	/// must check for read access to the navigation roots.
+	async fn save_site_settings(
+		&self,
+		navigator_id: &NuttyId,
+		settings: SiteSettings,
+	) -> Result<SiteSettings, ContentServiceError>;
+
+	/// Delete a navigator's site settings. Returns whether they had any.
+	async fn delete_site_settings(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<bool, ContentServiceError>;
+
+	/// Get the stored JSON of a content block's content, for scripting and
+	/// debugging. Requires the `content_blocks:raw` permission. Callers must
+	/// check for read access.
+	async fn get_raw_content(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Value, ContentServiceError>;
+
+	/// Replace a content block's content with raw JSON, which must be valid
+	/// content of a registered kind, recording a revision by the navigator.
+	/// Requires the `content_blocks:raw` permission. Callers must check for
+	/// write access. Returns the saved JSON.
+	async fn save_raw_content(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		content: Value,
+	) -> Result<Value, ContentServiceError>;
+
+	/// Recount the stats of every content block, repairing those that had
+	/// drifted from their incremental updates.
+	async fn check_block_stats(&self) -> Result<BlockStatsCheck, ContentServiceError>;
+
+	/// Merge a source block into a target block: merge their content by a
+	/// [MergeStrategy], point tags at the source to the target, move the
+	/// source's children under the target, then delete the source. Callers
+	/// must check for write access to both.
+	async fn merge_blocks(
+		&self,
+		source_id: &DissociatedNuttyId,
+		target_id: &DissociatedNuttyId,
+		strategy: &MergeStrategy,
+	) -> Result<BlockMerge, ContentServiceError>;
+
+	/// Convert a content block to another kind, keeping its Nutty ID, and so
+	/// its links, and recording a revision by the navigator. Text that
+	/// doesn't fit, i.e. after a new page's title, is kept in a new first
+	/// child. Callers must check for write access.
+	async fn convert_block(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		target: ConvertibleKind,
+	) -> Result<BlockConversion, ContentServiceError>;
+
+	/// Split a paragraph in two at an offset, in characters, recording a
+	/// revision by the navigator. The text after the offset, and its links,
+	/// move to a new paragraph right after it. Callers must check for write
+	/// access.
+	async fn split_paragraph(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		offset: usize,
+	) -> Result<BlockSplit, ContentServiceError>;
+
+	/// Merge a paragraph with the paragraph right after it, recording a
+	/// revision by the navigator. The next paragraph's text, links and
+	/// children move to this one, and tags at it are pointed here, before
+	/// it's deleted. Callers must check for write access to the paragraph;
+	/// write access to the next one is checked here.
+	async fn merge_with_next(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<BlockMerge, ContentServiceError>;
+
+	/// Reorder all of a content block's children at once, given in their new
+	/// order, spreading their fractional indices evenly. The children given
+	/// must be exactly the block's children. Returns the children in order.
+	/// Callers must check for write access.
+	async fn reorder_children(
+		&self,
+		parent_id: &DissociatedNuttyId,
+		child_ids: &[DissociatedNuttyId],
+	) -> Result<Vec<ContentBlock>, ContentServiceError>;
+
+	/// Search the content blocks a navigator can access, best matches first.
+	/// Each block is searched in its own language, optionally only those in
+	/// the given language. Archived blocks are left out unless included.
+	async fn search_content_blocks(
+		&self,
+		navigator_id: &NuttyId,
+		query: &str,
+		language: Option<SearchLanguage>,
+		include_archived: bool,
+	) -> Result<Vec<ContentBlock>, ContentServiceError>;
+
+	/// List the content blocks matching a [ContentFilter] that a navigator
+	/// can access, most recently created first.
+	async fn list_content_blocks(
+		&self,
+		navigator_id: &NuttyId,
+		filter: &ContentFilter,
+	) -> Result<Vec<ContentBlock>, ContentServiceError>;
+
+	/// Set the search language of a content block and everything under it,
+	/// or clear it with `None`. Returns the number of blocks updated.
+	async fn set_content_block_language(
+		&self,
+		block_id: &DissociatedNuttyId,
+		language: Option<SearchLanguage>,
+	) -> Result<u64, ContentServiceError>;
+
+	/// Archive or unarchive a content block, and everything under it if
+	/// `subtree` is set. Archived blocks are kept, but left out of listings,
+	/// search, and their parents' children unless asked for.
+	/// Callers must check for write access.
+	async fn set_content_block_archived(
+		&self,
+		block_id: &DissociatedNuttyId,
+		archived: bool,
+		subtree: bool,
+	) -> Result<BlockArchival, ContentServiceError>;
+
+	/// Set the block that a navigator's inbound emails are filed under.
+	/// Returns the inbox block.
+	async fn set_inbox(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<ContentBlock, ContentServiceError>;
+
+	/// File an inbound email as a page under a navigator's inbox, owned by
+	/// the navigator. Returns the page.
+	async fn ingest_email(
+		&self,
+		navigator_id: &NuttyId,
+		email: &IncomingEmail,
+	) -> Result<ContentBlock, ContentServiceError>;
+
+	/// File a captured page under the navigator's inbox, along with the
+	/// page's metadata and text if a snapshot of it was taken.
+	async fn capture_page(
+		&self,
+		navigator_id: &NuttyId,
+		capture: &Capture,
+		snapshot: Option<&PageSnapshot>,
+	) -> Result<CapturedPage, ContentServiceError>;
+
+	/// Convert pasted HTML into blocks owned by the navigator, and save them
+	/// after the parent's last child. Callers must check for write access to
+	/// the parent.
+	async fn paste_html(
+		&self,
+		navigator_id: &NuttyId,
+		parent_id: &DissociatedNuttyId,
+		html: &str,
+	) -> Result<Vec<ContentBlock>, ContentServiceError>;
+
+	/// List the custom property definitions, by name.
+	async fn list_property_definitions(
+		&self,
+	) -> Result<Vec<PropertyDefinition>, ContentServiceError>;
+
+	/// Define a custom property that content blocks may carry.
+	async fn create_property_definition(
+		&self,
+		definition: PropertyDefinition,
+	) -> Result<PropertyDefinition, ContentServiceError>;
+
+	/// Replace a content block's annotations with those found in it, unless
+	/// its content has changed (or it was deleted) since. Returns whether
+	/// they were saved.
+	async fn annotate_content_block(
+		&self,
+		block: &ContentBlock,
+		annotations: Vec<BlockAnnotation>,
+	) -> Result<bool, ContentServiceError>;
+
+	/// List the translations of a content block that the navigator can
+	/// read, including itself.
+	async fn list_translations(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Vec<Translation>, ContentServiceError>;
+
+	/// Set the language of a content block, adding it to the translations
+	/// of another block if given. Returns the block's translations that the
+	/// navigator can read.
+	async fn set_translation(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		lang: &Lang,
+		translation_of: Option<&DissociatedNuttyId>,
+	) -> Result<Vec<Translation>, ContentServiceError>;
+
+	/// Remove a content block from its translations. It keeps its language.
+	async fn remove_translation(
+		&self,
+		block_id: &DissociatedNuttyId,
+	) -> Result<(), ContentServiceError>;
+
+	/// Get a content block in the first of the preferred languages that it's
+	/// translated to and that the navigator can read, or as it is otherwise.
+	async fn localize_content_block(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		preferred: &[Lang],
+	) -> Result<LocalizedBlock, ContentServiceError>;
+
+	/// List the blocks linking to a content block that the navigator can
+	/// read, oldest first, with excerpts of their text.
+	async fn list_linked_references(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Vec<LinkedReference>, ContentServiceError>;
+}
+
+#[async_trait]
+impl ContentServiceApi for ContentService {
+	/// Get a content block's context for a navigator, with its children
+	/// presented in a [ChildrenView]. Query results and related blocks are
+	/// limited to the blocks that the navigator can read.
+	async fn get_content_block_context(
+		&self,
+		navigator_id: &NuttyId,
+		nutty_id: &DissociatedNuttyId,
+		view: &ChildrenView,
+	) -> Result<ContentContext, ContentServiceError> {
+		// Read everything from one snapshot, so that a concurrent save can't
+		// leave links to blocks that aren't in the cache.
+		self
+			.repository
+			.with_snapshot(|tx| {
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					// Get the content block.
+					let content_block = self
+						.repository
+						.get_content_block_tx(ctx.conn(), nutty_id)
+						.await
+						.map_err(ContentServiceError::FetchContentBlock)?
+						.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+					// Get the ancestor blocks.
+					let ancestors = self
+						.repository
+						.get_ancestor_blocks_tx(ctx.conn(), nutty_id)
+						.await
+						.map_err(ContentServiceError::FetchAncestorBlocks)?;
+
+					// Get the descendant blocks.
+					let descendants = self
+						.repository
+						.get_descendant_blocks_tx(ctx.conn(), nutty_id)
+						.await
+						.map_err(ContentServiceError::FetchDescendantBlocks)?;
+
+					// Get immediate children, sorted and filtered in SQL for non-default views.
+					let children_ids = match view.is_default() {
+						true => descendants
+							.iter()
+							.filter(|block| block.parent_id.map(|i| i.nid()) == Some(nutty_id.nid()))
+							.map(|block| *block.nutty_id())
+							.collect::<Vec<_>>(),
+
+						false => self
+							.repository
+							.list_child_ids_tx(ctx.conn(), content_block.nutty_id(), view)
+							.await
+							.map_err(ContentServiceError::FetchDescendantBlocks)?,
+					};
+
+					// Get outbound links (references).
+					let outbound_links = self
+						.repository
+						.get_content_links_from_tx(ctx.conn(), content_block.nutty_id())
+						.await
+						.map_err(ContentServiceError::FetchOutboundLinks)?;
+
+					// Get inbound links (backlinks).
+					let inbound_links = self.get_backlinks_tx(ctx, content_block.nutty_id()).await?;
+
+					// Execute the block query, if any.
+					let query_result_ids = match &content_block.content {
+						BlockContent::Query { dsl } => {
+							let query =
+								BlockQuery::parse(dsl).map_err(ContentServiceError::ParseBlockQuery)?;
+
+							self
+								.repository
+								.query_content_block_ids_tx(
+									ctx.conn(),
+									navigator_id,
+									&query,
+									content_block.nutty_id(),
+								)
+								.await
+								.map_err(ContentServiceError::ExecuteBlockQuery)?
+						}
+
+						_ => vec![],
+					};
+
+					// Get the blocks cited alongside this one.
+					let related_blocks = self
+						.repository
+						.get_related_blocks_tx(
+							ctx.conn(),
+							navigator_id,
+							content_block.nutty_id(),
+							MAX_RELATED_BLOCKS,
+						)
+						.await
+						.map_err(ContentServiceError::FetchRelatedBlocks)?;
+
+					// Build the block cache.
+					let mut block_cache = std::collections::HashMap::new();
+
+					// Add the main content block to the cache.
+					block_cache.insert(*content_block.nutty_id(), content_block.clone());
+
+					// Add ancestor blocks to the cache.
+					for block in &ancestors {
+						block_cache.insert(*block.nutty_id(), block.clone());
+					}
+
+					// Add descendant blocks to the cache.
+					for block in &descendants {
+						block_cache.insert(*block.nutty_id(), block.clone());
+					}
+
+					// Add related blocks to the cache, except those already in the tree.
+					let mut related_ids = vec![];
+
+					for block in related_blocks {
+						if !block_cache.contains_key(block.nutty_id()) {
+							related_ids.push(*block.nutty_id());
+							block_cache.insert(*block.nutty_id(), block);
+						}
+					}
+
+					// Get the annotations of the cached blocks.
+					let annotations = self
+						.repository
+						.list_annotations_tx(ctx.conn(), &block_cache.keys().copied().collect::<Vec<_>>())
+						.await
+						.map_err(ContentServiceError::FetchAnnotations)?;
+
+					// Get the stats of the cached blocks.
+					let stats = self
+						.repository
+						.list_block_stats_tx(ctx.conn(), &block_cache.keys().copied().collect::<Vec<_>>())
+						.await
+						.map_err(ContentServiceError::FetchBlockStats)?;
+
+					// Ancestors come nearest first, by level, but are presented root first.
+					let ancestor_ids = ancestors
+						.iter()
+						.rev()
+						.map(|block| *block.nutty_id())
+						.collect();
+
+					// Extract reference and backlink IDs.
+					let reference_ids = outbound_links.iter().map(|link| link.target_id).collect();
+					let backlink_ids = inbound_links.iter().map(|link| link.source_id).collect();
+
+					let restricted_links = outbound_links
+						.iter()
+						.chain(&inbound_links)
+						.filter(|link| link.restricted)
+						.cloned()
+						.collect();
+
+					// Create the content context.
+					let context = ContentContext::builder()
+						.block_id(*content_block.nutty_id())
+						.parent_id(content_block.parent_id)
+						.ancestor_ids(ancestor_ids)
+						.children_ids(children_ids)
+						.reference_ids(reference_ids)
+						.backlink_ids(backlink_ids)
+						.restricted_links(restricted_links)
+						.query_result_ids(query_result_ids)
+						.related_ids(related_ids)
+						.block_cache(block_cache)
+						.annotations(annotations)
+						.stats(stats)
+						.try_build()
+						.map_err(|err| ContentServiceError::BuildContentContext(err.to_string()))?;
+
+					Ok(context)
+				})
+			})
+			.await
+	}
+
+	/// Get only some parts of a content block's context, skipping the
+	/// queries for the rest, with its children presented in a [ChildrenView].
+	async fn get_content_block_context_partial(
+		&self,
+		nutty_id: &DissociatedNuttyId,
+		include: &ContextInclude,
+		view: &ChildrenView,
+	) -> Result<ContentContext, ContentServiceError> {
+		self
+			.repository
+			.with_snapshot(|tx| {
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let content_block = self.get_existing_block_tx(ctx, nutty_id).await?;
+
+					let mut context = ContentContext::builder()
+						.block_id(*content_block.nutty_id())
+						.parent_id(content_block.parent_id)
+						.add_block_to_cache(content_block.clone());
+
+					if include.includes(ContextPart::Ancestors) {
+						let ancestors = self
+							.repository
+							.get_ancestor_blocks_tx(ctx.conn(), nutty_id)
+							.await
+							.map_err(ContentServiceError::FetchAncestorBlocks)?;
+
+						// Ancestors come nearest first, by level, but are presented root first.
+						context = context.ancestor_ids(
+							ancestors
+								.iter()
+								.rev()
+								.map(|block| *block.nutty_id())
+								.collect(),
+						);
+
+						for block in ancestors {
+							context = context.add_block_to_cache(block);
+						}
+					}
+
+					if include.includes(ContextPart::Children) {
+						let children_ids = self
+							.repository
+							.list_child_ids_tx(ctx.conn(), content_block.nutty_id(), view)
+							.await
+							.map_err(ContentServiceError::FetchDescendantBlocks)?;
+
+						let children = self
+							.repository
+							.get_content_blocks_by_ids_tx(ctx.conn(), &children_ids)
+							.await
+							.map_err(ContentServiceError::FetchDescendantBlocks)?;
+
+						context = context.children_ids(children_ids);
+
+						for block in children {
+							context = context.add_block_to_cache(block);
+						}
+					}
+
+					if include.includes(ContextPart::Links) {
+						let outbound_links = self
+							.repository
+							.get_content_links_from_tx(ctx.conn(), content_block.nutty_id())
+							.await
+							.map_err(ContentServiceError::FetchOutboundLinks)?;
+
+						let inbound_links = self.get_backlinks_tx(ctx, content_block.nutty_id()).await?;
+
+						let restricted_links = outbound_links
+							.iter()
+							.chain(&inbound_links)
+							.filter(|link| link.restricted)
+							.cloned()
+							.collect();
+
+						context = context
+							.reference_ids(outbound_links.iter().map(|link| link.target_id).collect())
+							.backlink_ids(inbound_links.iter().map(|link| link.source_id).collect())
+							.restricted_links(restricted_links);
+					}
+
+					context
+						.try_build()
+						.map_err(|err| ContentServiceError::BuildContentContext(err.to_string()))
+				})
+			})
+			.await
+	}
+
+	/// Get a content block's context as it was at a time, from its history,
+	/// with its children presented in a [ChildrenView].
+	async fn get_content_block_context_as_of(
+		&self,
+		nutty_id: &DissociatedNuttyId,
+		as_of: &DateTime<FixedOffset>,
+		view: &ChildrenView,
+	) -> Result<ContentContext, ContentServiceError> {
+		self
+			.repository
+			.with_snapshot(|tx| {
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let content_block = self
+						.repository
+						.get_content_block_as_of_tx(ctx.conn(), nutty_id, as_of)
+						.await
+						.map_err(ContentServiceError::FetchContentBlock)?
+						.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+					let ancestors = self
+						.repository
+						.get_ancestor_blocks_as_of_tx(ctx.conn(), nutty_id, as_of)
+						.await
+						.map_err(ContentServiceError::FetchAncestorBlocks)?;
+
+					// Descendants come in fractional index order within each level.
+					let descendants = self
+						.repository
+						.get_descendant_blocks_as_of_tx(ctx.conn(), nutty_id, as_of)
+						.await
+						.map_err(ContentServiceError::FetchDescendantBlocks)?;
+
+					let children_ids = view.apply(
+						descendants
+							.iter()
+							.filter(|block| block.parent_id == Some(*content_block.nutty_id())),
+					);
+
+					// Ancestors come nearest first, by level, but are presented root first.
+					let ancestor_ids = ancestors
+						.iter()
+						.rev()
+						.map(|block| *block.nutty_id())
+						.collect();
+
+					let block_cache = std::iter::once(&content_block)
+						.chain(&ancestors)
+						.chain(&descendants)
+						.map(|block| (*block.nutty_id(), block.clone()))
+						.collect();
+
+					ContentContext::builder()
+						.block_id(*content_block.nutty_id())
+						.parent_id(content_block.parent_id)
+						.ancestor_ids(ancestor_ids)
+						.children_ids(children_ids)
+						.block_cache(block_cache)
+						.try_build()
+						.map_err(|err| ContentServiceError::BuildContentContext(err.to_string()))
+				})
+			})
+			.await
+	}
+
+	/// Save a content block, on behalf of its author if any. Without one,
+	/// e.g. for seeds, links aren't checked for access.
+	async fn save_content_block(
+		&self,
+		author_id: Option<&NuttyId>,
+		mut content_block: ContentBlock,
+	) -> Result<ContentBlock, ContentServiceError> {
+		// Validate the content with its kind, including any block query.
+		self
+			.block_kinds
+			.validate(&content_block.content)
+			.map_err(|error| match error {
+				BlockKindError::InvalidQuery(error) => ContentServiceError::ParseBlockQuery(error),
+				error => ContentServiceError::InvalidContent(error),
+			})?;
+
+		// Clean untrusted markdown, if configured to.
+		if self.sanitizer.sanitizes_on_save() {
+			content_block.content = self.sanitizer.clean_content(&content_block.content);
+		}
+
+		// Validate the custom properties, if any.
+		if !content_block.properties.is_empty() {
+			let definitions = self
+				.repository
+				.list_property_definitions()
+				.await
+				.map_err(ContentServiceError::FetchPropertyDefinitions)?;
+
+			validate_properties(&definitions, &content_block.properties)
+				.map_err(ContentServiceError::InvalidProperties)?;
+		}
+
+		self
+			.repository
+			.with_transaction(|tx| {
+				let content_block = content_block.clone();
+				Box::pin(async move {
+					self
+						.save_content_block_tx(&mut tx.context(), author_id, content_block)
+						.await
+				})
+			})
+			.await
+	}
+
+	/// Rename a page that the navigator can write, and rewrite title
+	/// references in the blocks linking to it, or naming it by title, that
+	/// they can edit. Title links are left alone while another page has the
+	/// old title. Titles can't contain `[[` or `]]`, which would end a link.
+	/// Returns the renamed page followed by every rewritten block.
+	async fn rename_page(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		new_title: String,
+	) -> Result<Vec<ContentBlock>, ContentServiceError> {
+		if new_title.contains("[[") || new_title.contains("]]") {
+			return Err(ContentServiceError::InvalidPageTitle);
+		}
+
+		if !self
+			.check_content_block_write_access(navigator_id, block_id)
+			.await?
+		{
+			return Err(ContentServiceError::EditDenied);
+		}
+
+		self
+			.repository
+			.with_transaction(|tx| {
+				let new_title = new_title.clone();
+
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					// Get the page.
+					let mut page = self
+						.repository
+						.get_content_block_tx(ctx.conn(), block_id)
+						.await
+						.map_err(ContentServiceError::FetchContentBlock)?
+						.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+					let BlockContent::Page { title: old_title } = page.content.clone() else {
+						return Err(ContentServiceError::NotAPage);
+					};
+
+					// Save the page with its new title.
+					page.content = BlockContent::Page {
+						title: new_title.clone(),
+					};
+
+					let page = self
+						.repository
+						.upsert_content_block_tx(ctx.conn(), page)
+						.await
+						.map_err(ContentServiceError::SaveContentBlock)?;
+
+					let mut modified_blocks = vec![page.clone()];
+
+					if old_title == new_title {
+						return Ok(modified_blocks);
+					}
+
+					// Title links ([[Old Title]]) could mean another page with the
+					// same title, so they're only rewritten if there's none.
+					let is_title_shared = self
+						.repository
+						.is_title_shared_tx(ctx.conn(), page.nutty_id(), &old_title)
+						.await
+						.map_err(ContentServiceError::FetchContentBlock)?;
+
+					// Get inbound links (backlinks), from blocks the navigator can edit.
+					let inbound_links = self
+						.repository
+						.get_content_links_to_tx(ctx.conn(), page.nutty_id())
+						.await
+						.map_err(ContentServiceError::FetchInboundLinks)?;
+
+					let source_ids: Vec<NuttyId> =
+						inbound_links.iter().map(|link| link.source_id).collect();
+
+					let editable_ids = self
+						.repository
+						.list_editable_ids_tx(ctx.conn(), navigator_id, &source_ids)
+						.await
+						.map_err(ContentServiceError::FetchContentBlock)?;
+
+					let target_id = page.nutty_id().dissociate();
+
+					let rename = |content: &BlockContent| {
+						if is_title_shared {
+							content.rename_tag_display_text(&target_id, &old_title, &new_title)
+						} else {
+							content.rename_title_references(&target_id, &old_title, &new_title)
+						}
+					};
+
+					for link in inbound_links {
+						if !editable_ids.contains(&link.source_id) {
+							continue;
+						}
+
+						let mut source_block = self
+							.repository
+							.get_content_block_tx(ctx.conn(), &link.source_id.dissociate())
+							.await
+							.map_err(ContentServiceError::FetchContentBlock)?
+							.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+						let Some(content) = rename(&source_block.content) else {
+							continue;
+						};
+
+						// Links are keyed by Nutty ID, so they survive the rewrite.
+						source_block.content = content;
+
+						let source_block = self
+							.repository
+							.upsert_content_block_tx(ctx.conn(), source_block)
+							.await
+							.map_err(ContentServiceError::SaveContentBlock)?;
+
+						modified_blocks.push(source_block);
+					}
+
+					if is_title_shared {
+						return Ok(modified_blocks);
+					}
+
+					// Title links aren't links by Nutty ID, so they're found by
+					// the titles that blocks were saved with. Blocks rewritten
+					// above no longer reference the old title.
+					let title_referrers = self
+						.repository
+						.list_title_referrers_tx(ctx.conn(), navigator_id, &old_title)
+						.await
+						.map_err(ContentServiceError::FetchContentBlock)?;
+
+					for mut block in title_referrers {
+						let Some(content) = block
+							.content
+							.rename_title_references(&target_id, &old_title, &new_title)
+						else {
+							continue;
+						};
+
+						block.content = content;
+
+						let block = self
+							.repository
+							.upsert_content_block_tx(ctx.conn(), block)
+							.await
+							.map_err(ContentServiceError::SaveContentBlock)?;
+
+						modified_blocks.push(block);
+					}
+
+					Ok(modified_blocks)
+				})
+			})
+			.await
+	}
+
+	/// Delete a content block and its descendants, counting the links into
+	/// them from other blocks. With `unlink`, tags in those blocks are first
+	/// converted to plain text. Callers must check for write access.
+	async fn delete_content_block(
+		&self,
+		block_id: &DissociatedNuttyId,
+		unlink: bool,
+	) -> Result<BlockDeletion, ContentServiceError> {
+		self
+			.repository
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					// Get the block and its descendants.
+					let content_block = self
+						.repository
+						.get_content_block_tx(ctx.conn(), block_id)
+						.await
+						.map_err(ContentServiceError::FetchContentBlock)?
+						.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+					let descendants = self
+						.repository
+						.get_descendant_blocks_tx(ctx.conn(), block_id)
+						.await
+						.map_err(ContentServiceError::FetchDescendantBlocks)?;
+
+					let deleted_blocks: Vec<_> =
+						std::iter::once(content_block).chain(descendants).collect();
+
+					let deleted_ids: Vec<_> = deleted_blocks
+						.iter()
+						.map(|block| *block.nutty_id())
+						.collect();
+
+					// Count the links from blocks that remain.
+					let inbound_link_count = self
+						.repository
+						.count_inbound_links_many_tx(ctx.conn(), &deleted_ids)
+						.await
+						.map_err(ContentServiceError::FetchInboundLinks)?
+						.values()
+						.sum();
+
+					let mut unlinked_ids = vec![];
+
+					for target in deleted_blocks.iter().filter(|_| unlink) {
+						let inbound_links = self
+							.repository
+							.get_content_links_to_tx(ctx.conn(), target.nutty_id())
+							.await
+							.map_err(ContentServiceError::FetchInboundLinks)?;
+
+						// Bare tags to pages read as their title.
+						let target_id = target.nutty_id().dissociate();
+
+						let text = match &target.content {
+							BlockContent::Page { title } => title.clone(),
+							_ => target_id.nid(),
+						};
+
+						for link in inbound_links
+							.iter()
+							.filter(|link| !deleted_ids.contains(&link.source_id))
+						{
+							let mut source_block = self
+								.repository
+								.get_content_block_tx(ctx.conn(), &link.source_id.dissociate())
+								.await
+								.map_err(ContentServiceError::FetchContentBlock)?
+								.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+							let Some(content) = source_block.content.unlink_references(&target_id, &text)
+							else {
+								continue;
+							};
+
+							// The link itself cascades with the deleted block.
+							source_block.content = content;
+
+							self
+								.repository
+								.upsert_content_block_tx(ctx.conn(), source_block)
+								.await
+								.map_err(ContentServiceError::SaveContentBlock)?;
+
+							if !unlinked_ids.contains(&link.source_id) {
+								unlinked_ids.push(link.source_id);
+							}
+						}
+					}
+
+					self
+						.repository
+						.delete_content_blocks_tx(ctx.conn(), &deleted_ids)
+						.await
+						.map_err(ContentServiceError::DeleteContentBlock)?;
+
+					Ok(BlockDeletion {
+						block_id: deleted_ids[0],
+						deleted_ids,
+						inbound_link_count,
+						unlinked_ids,
+					})
+				})
+			})
+			.await
+	}
+
+	/// Check if a navigator has access to a content block or any of its ancestors.
+	async fn check_content_block_access(
+		&self,
+		navigator_id: &crate::models::NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<bool, ContentServiceError> {
+		// Access checks see every block, so that they can tell
+		// forbidden blocks from missing ones.
+		row_level_security::unrestricted(async {
+			// First, resolve the DissociatedNuttyId to a NuttyId.
+			let resolved_block_id = self
+				.repository
+				.resolve_nutty_id(*block_id)
+				.await
+				.map_err(ContentServiceError::FetchContentBlock)?;
+
+			// Hidden blocks, and the blocks under them, are only visible to
+			// moderators.
+			let is_hidden = self
+				.repository
+				.is_hidden(block_id)
+				.await
+				.map_err(ContentServiceError::FetchContentBlock)?;
+
+			if is_hidden {
+				return self
+					.access_service
+					.can_permission(navigator_id, "moderation:review")
+					.await
+					.map_err(ContentServiceError::AccessControl);
+			}
+
+			// 1. Check if the navigator has global read permission.
+			let can_access_globally = self
+				.access_service
+				.can_permission(navigator_id, "content_blocks:read:all")
+				.await
+				.map_err(ContentServiceError::AccessControl)?;
+
+			if can_access_globally {
+				return Ok(true);
+			}
+
+			// 2. Check if the navigator has access to the requested block.
+			let can_access_block = self
+				.access_service
+				.can_on_resource(
+					navigator_id,
+					"content_blocks:read:resource",
+					ResourceKind::ContentBlock,
+					&resolved_block_id,
+				)
+				.await
+				.map_err(ContentServiceError::AccessControl)?;
+
+			if can_access_block {
+				return Ok(true);
+			}
+
+			// Fetch the block, for its owner and whether it inherits access.
+			let content_block = self
+				.repository
+				.get_content_block(block_id)
+				.await
+				.map_err(ContentServiceError::FetchContentBlock)?
+				.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+			// 3. Check if the navigator has ownership permission.
+			let can_access_own = self
+				.access_service
+				.can_permission(navigator_id, "content_blocks:read:own")
+				.await
+				.map_err(ContentServiceError::AccessControl)?;
+
+			if can_access_own {
+				// Check if the navigator owns the block.
+				if let Some(owner_id) = content_block.owner_id
+					&& owner_id == *navigator_id
+				{
+					return Ok(true);
+				}
+			}
+
+			// 4. Check if the navigator has access to any ancestor blocks that
+			// the block inherits access from.
+			let ancestors = self
+				.repository
+				.get_ancestor_blocks(block_id)
+				.await
+				.map_err(ContentServiceError::FetchAncestorBlocks)?;
+
+			for ancestor in content_block.access_ancestors(&ancestors) {
+				let can_access_ancestor = self
+					.access_service
+					.can_on_resource(
+						navigator_id,
+						"content_blocks:read:resource",
+						ResourceKind::ContentBlock,
+						ancestor.nutty_id(),
+					)
+					.await
+					.map_err(ContentServiceError::AccessControl)?;
+
+				if can_access_ancestor {
+					return Ok(true);
+				}
+			}
+
+			Ok(false)
+		})
+		.await
+	}
+
+	/// Check if a navigator has write access to a content block or any of its ancestors.
+	async fn check_content_block_write_access(
+		&self,
+		navigator_id: &crate::models::NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<bool, ContentServiceError> {
+		// Access checks see every block, so that they can tell
+		// forbidden blocks from missing ones.
+		row_level_security::unrestricted(async {
+			// Hidden blocks, and the blocks under them, can only be changed by
+			// moderators, even their owners and global writers.
+			let is_hidden = self
+				.repository
+				.is_hidden(block_id)
+				.await
+				.map_err(ContentServiceError::FetchContentBlock)?;
+
+			if is_hidden {
+				return self
+					.access_service
+					.can_permission(navigator_id, "moderation:review")
+					.await
+					.map_err(ContentServiceError::AccessControl);
+			}
+
+			// 1. Check if the navigator has global write permission.
+			let can_write_globally = self
+				.access_service
+				.can_permission(navigator_id, "content_blocks:write:all")
+				.await
+				.map_err(ContentServiceError::AccessControl)?;
+
+			if can_write_globally {
+				return Ok(true);
+			}
+
+			// Resolve the DissociatedNuttyId to a NuttyId. This comes after the
+			// global check so that global writers can create new blocks.
+			let resolved_block_id = self
+				.repository
+				.resolve_nutty_id(*block_id)
+				.await
+				.map_err(ContentServiceError::FetchContentBlock)?;
+
+			// 2. Check if the navigator has direct write access to the requested block.
+			let can_write_block = self
+				.access_service
+				.can_on_resource(
+					navigator_id,
+					"content_blocks:write",
+					ResourceKind::ContentBlock,
+					&resolved_block_id,
+				)
+				.await
+				.map_err(ContentServiceError::AccessControl)?;
+
+			if can_write_block {
+				return Ok(true);
+			}
+
+			// Fetch the block, for its owner and whether it inherits access.
+			let content_block = self
+				.repository
+				.get_content_block(block_id)
+				.await
+				.map_err(ContentServiceError::FetchContentBlock)?
+				.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+			// 3. Check if the navigator has ownership write permission.
+			let can_write_own = self
+				.access_service
+				.can_permission(navigator_id, "content_blocks:write:own")
+				.await
+				.map_err(ContentServiceError::AccessControl)?;
+
+			if can_write_own {
+				// Check if the navigator owns the block.
+				if let Some(owner_id) = content_block.owner_id
+					&& owner_id == *navigator_id
+				{
+					return Ok(true);
+				}
+			}
+
+			// 4. Check if the navigator has write access to any ancestor blocks
+			// that the block inherits access from.
+			let ancestors = self
+				.repository
+				.get_ancestor_blocks(block_id)
+				.await
+				.map_err(ContentServiceError::FetchAncestorBlocks)?;
+
+			for ancestor in content_block.access_ancestors(&ancestors) {
+				let can_write_ancestor = self
+					.access_service
+					.can_on_resource(
+						navigator_id,
+						"content_blocks:write",
+						ResourceKind::ContentBlock,
+						ancestor.nutty_id(),
+					)
+					.await
+					.map_err(ContentServiceError::AccessControl)?;
+
+				if can_write_ancestor {
+					return Ok(true);
+				}
+			}
+
+			Ok(false)
+		})
+		.await
+	}
+
+	/// List which of the given content blocks a moderator has hidden directly.
+	async fn list_hidden_block_ids(
+		&self,
+		ids: &[NuttyId],
+	) -> Result<Vec<NuttyId>, ContentServiceError> {
+		row_level_security::unrestricted(self.repository.list_hidden_ids(ids))
+			.await
+			.map_err(ContentServiceError::FetchContentBlock)
+	}
+
+	async fn list_readable_block_ids(
+		&self,
+		navigator_id: &NuttyId,
+		ids: &[NuttyId],
+	) -> Result<Vec<NuttyId>, ContentServiceError> {
+		if ids.is_empty() {
+			return Ok(vec![]);
+		}
+
+		// Access checks see every block, so that ancestors aren't filtered.
+		row_level_security::unrestricted(self.repository.list_readable_ids(navigator_id, ids))
+			.await
+			.map_err(ContentServiceError::FetchContentBlock)
+	}
+
+	/// Check if a navigator can comment on a content block.
+	async fn check_content_block_comment_access(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<bool, ContentServiceError> {
+		// Access checks see every block, so that they can tell
+		// forbidden blocks from missing ones.
+		let content_block =
+			row_level_security::unrestricted(self.repository.get_content_block(block_id))
+				.await
+				.map_err(ContentServiceError::FetchContentBlock)?
+				.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		// 1. Navigators blocked by the block's owner can't comment at all.
+		if self
+			.is_blocked_by_owner(navigator_id, &content_block)
+			.await?
+		{
+			return Ok(false);
+		}
+
+		// 2. Editors can comment, too.
+		if self
+			.check_content_block_write_access(navigator_id, block_id)
+			.await?
+		{
+			return Ok(true);
+		}
+
+		// 3. Commenting on a block that can't be read, e.g. a hidden one, is
+		// never allowed.
+		if !self
+			.check_content_block_access(navigator_id, block_id)
+			.await?
+		{
+			return Ok(false);
+		}
+
+		row_level_security::unrestricted(async {
+			// 4. Check if the navigator can comment on the block or any of
+			// the ancestors it inherits access from.
+			let ancestors = self
+				.repository
+				.get_ancestor_blocks(block_id)
+				.await
+				.map_err(ContentServiceError::FetchAncestorBlocks)?;
+
+			let block_ids = std::iter::once(&content_block)
+				.chain(content_block.access_ancestors(&ancestors))
+				.map(|block| block.nutty_id());
+
+			for block_id in block_ids {
+				let can_comment_on_block = self
+					.access_service
+					.can_on_resource(
+						navigator_id,
+						"content_blocks:comment:resource",
+						ResourceKind::ContentBlock,
+						block_id,
+					)
+					.await
+					.map_err(ContentServiceError::AccessControl)?;
+
+				if can_comment_on_block {
+					return Ok(true);
+				}
+			}
+
+			Ok(false)
+		})
+		.await
+	}
+
+	/// Check if a navigator can manage access to a content block or any of its ancestors.
+	async fn check_content_block_manage_access(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<bool, ContentServiceError> {
+		// Access checks see every block, so that they can tell
+		// forbidden blocks from missing ones.
+		row_level_security::unrestricted(async {
+			// 1. Check if the navigator has global manage permission.
+			let can_manage_globally = self
+				.access_service
+				.can_permission(navigator_id, "content_blocks:manage:all")
+				.await
+				.map_err(ContentServiceError::AccessControl)?;
+
+			if can_manage_globally {
+				return Ok(true);
+			}
+
+			// 2. Check if the navigator can manage the block or any of the
+			// ancestors it inherits access from.
+			let content_block = self
+				.repository
+				.get_content_block(block_id)
+				.await
+				.map_err(ContentServiceError::FetchContentBlock)?
+				.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+			let ancestors = self
+				.repository
+				.get_ancestor_blocks(block_id)
+				.await
+				.map_err(ContentServiceError::FetchAncestorBlocks)?;
+
+			let block_ids = std::iter::once(&content_block)
+				.chain(content_block.access_ancestors(&ancestors))
+				.map(|block| block.nutty_id());
+
+			for block_id in block_ids {
+				let can_manage_block = self
+					.access_service
+					.can_on_resource(
+						navigator_id,
+						"content_blocks:manage:resource",
+						ResourceKind::ContentBlock,
+						block_id,
+					)
+					.await
+					.map_err(ContentServiceError::AccessControl)?;
+
+				if can_manage_block {
+					return Ok(true);
+				}
+			}
+
+			Ok(false)
+		})
+		.await
+	}
+
+	/// Transfer a content block, and optionally its descendants, to a new
+	/// owner. Requires the `content_blocks:transfer` permission. The previous
+	/// owners' roles on the blocks move to the new owner, and every transfer
+	/// is recorded for auditing.
+	async fn transfer_ownership(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		new_owner_id: &NuttyId,
+		include_descendants: bool,
+	) -> Result<Vec<OwnershipTransfer>, ContentServiceError> {
+		let can_transfer = self
+			.access_service
+			.can_permission(navigator_id, "content_blocks:transfer")
+			.await
+			.map_err(ContentServiceError::AccessControl)?;
+
+		if !can_transfer {
+			return Err(ContentServiceError::TransferDenied);
+		}
+
+		self
+			.repository
+			.with_transaction(|tx| {
+				let block_id = *block_id;
+				let new_owner_id = *new_owner_id;
+				let navigator_id = *navigator_id;
+
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let block = self
+						.repository
+						.get_content_block_tx(ctx.conn(), &block_id)
+						.await
+						.map_err(ContentServiceError::FetchContentBlock)?
+						.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+					let mut block_ids = vec![*block.nutty_id()];
+
+					if include_descendants {
+						let descendants = self
+							.repository
+							.get_descendant_blocks_tx(ctx.conn(), &block_id)
+							.await
+							.map_err(ContentServiceError::FetchDescendantBlocks)?;
+
+						block_ids.extend(descendants.iter().map(|block| *block.nutty_id()));
+					}
+
+					let transfers = self
+						.repository
+						.transfer_ownership_tx(ctx.conn(), &block_ids, &new_owner_id)
+						.await
+						.map_err(ContentServiceError::TransferOwnership)?;
+
+					self
+						.repository
+						.reassign_owner_roles_tx(ctx.conn(), &transfers)
+						.await
+						.map_err(ContentServiceError::TransferOwnership)?;
+
+					self
+						.repository
+						.record_ownership_transfers_tx(ctx.conn(), &transfers, &navigator_id)
+						.await
+						.map_err(ContentServiceError::TransferOwnership)?;
+
+					Ok(transfers)
+				})
+			})
+			.await
+	}
+
+	/// Get the roles granted on a content block, including those inherited
+	/// from its ancestors.
+	async fn get_content_block_grants(
+		&self,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Vec<ResourceGrant>, ContentServiceError> {
+		let resolved_block_id = self
+			.repository
+			.resolve_nutty_id(*block_id)
+			.await
+			.map_err(ContentServiceError::FetchContentBlock)?;
+
+		self
+			.access_service
+			.get_resource_grants(ResourceKind::ContentBlock, &resolved_block_id)
+			.await
+			.map_err(ContentServiceError::AccessControl)
+	}
+
+	/// Share a content block with another navigator at a [ShareLevel],
+	/// replacing any level it was shared with before. Owners and navigators
+	/// who can manage access may share. Returns the block's updated grants.
+	async fn share_content_block(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		recipient_id: &NuttyId,
+		level: ShareLevel,
+	) -> Result<Vec<ResourceGrant>, ContentServiceError> {
+		// Ownership is an access check, so it sees every block.
+		let block = row_level_security::unrestricted(self.repository.get_content_block(block_id))
+			.await
+			.map_err(ContentServiceError::FetchContentBlock)?
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		if !block.is_owned_by(navigator_id)
+			&& !self
+				.check_content_block_manage_access(navigator_id, block_id)
+				.await?
+		{
+			return Err(ContentServiceError::ShareDenied);
+		}
+
+		self
+			.grant_share_level(recipient_id, block.nutty_id(), level)
+			.await?;
+
+		self.get_content_block_grants(block_id).await
+	}
+
+	/// Request access to a content block at a [ShareLevel], for review by
+	/// the navigators who manage it.
+	async fn request_access(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		level: ShareLevel,
+	) -> Result<AccessRequest, ContentServiceError> {
+		// Navigators request access to blocks that they can't see yet.
+		let block = row_level_security::unrestricted(self.repository.get_content_block(block_id))
+			.await
+			.map_err(ContentServiceError::FetchContentBlock)?
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		if self.is_blocked_by_owner(navigator_id, &block).await? {
+			return Err(ContentServiceError::BlockedByOwner);
+		}
+
+		let request = AccessRequest::new(*block.nutty_id(), *navigator_id, level);
+
+		self
+			.repository
+			.create_access_request(&request)
+			.await
+			.map_err(ContentServiceError::SaveAccessRequest)
+	}
+
+	/// List the pending access requests for a content block, oldest first.
+	/// Callers must check that the navigator can manage the block.
+	async fn list_access_requests(
+		&self,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Vec<AccessRequest>, ContentServiceError> {
+		let resolved_block_id = self
+			.repository
+			.resolve_nutty_id(*block_id)
+			.await
+			.map_err(ContentServiceError::FetchContentBlock)?;
+
+		self
+			.repository
+			.list_access_requests(&resolved_block_id)
+			.await
+			.map_err(ContentServiceError::FetchAccessRequests)
+	}
+
+	/// Approve or deny a pending access request. Approving grants the
+	/// requested level. Requires managing the requested block.
+	async fn resolve_access_request(
+		&self,
+		navigator_id: &NuttyId,
+		request_id: &NuttyId,
+		approve: bool,
+	) -> Result<AccessRequest, ContentServiceError> {
+		let request = self
+			.repository
+			.get_access_request(request_id)
+			.await
+			.map_err(ContentServiceError::FetchAccessRequests)?
+			.filter(|request| request.status() == AccessRequestStatus::Pending)
+			.ok_or(ContentServiceError::AccessRequestNotFound)?;
+
+		if !self
+			.check_content_block_manage_access(navigator_id, &request.block_id().dissociate())
+			.await?
+		{
+			return Err(ContentServiceError::ManageDenied);
+		}
+
+		let status = if approve {
+			AccessRequestStatus::Approved
+		} else {
+			AccessRequestStatus::Denied
+		};
+
+		// Resolve the request and grant its level in one transaction, so that
+		// an approved request is never left without the access it asked for.
+		self
+			.repository
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let request = self
+						.repository
+						.resolve_access_request_tx(ctx.conn(), request_id, status, navigator_id)
+						.await
+						.map_err(ContentServiceError::SaveAccessRequest)?
+						.ok_or(ContentServiceError::AccessRequestNotFound)?;
+
+					if approve {
+						self
+							.grant_share_level_tx(
+								ctx,
+								request.navigator_id(),
+								request.block_id(),
+								request.level(),
+							)
+							.await?;
+					}
+
+					Ok(request)
+				})
+			})
+			.await
+	}
+
+	/// Get the date-linked blocks a navigator can access within an
+	/// inclusive date range.
+	async fn get_content_calendar(
+		&self,
+		navigator_id: &NuttyId,
+		from: NaiveDate,
+		to: NaiveDate,
+	) -> Result<ContentCalendar, ContentServiceError> {
+		// Bound the range, since every block in it is access checked.
+		if from > to || (to - from).num_days() >= MAX_CALENDAR_DAYS {
+			return Err(ContentServiceError::InvalidDateRange);
+		}
+
+		// Only the dates of blocks that the navigator can access are listed.
+		let block_dates = self
+			.repository
+			.list_block_dates(navigator_id, from, to)
+			.await
+			.map_err(ContentServiceError::FetchBlockDates)?;
+
+		let mut block_ids: Vec<NuttyId> = block_dates.iter().map(|date| date.block_id).collect();
+		block_ids.sort_by_key(|id| *id.uuid());
+		block_ids.dedup();
+
+		let blocks = self
+			.repository
+			.get_content_blocks_by_ids(&block_ids)
+			.await
+			.map_err(ContentServiceError::FetchContentBlock)?;
+
+		let block_cache: HashMap<_, _> = blocks
+			.into_iter()
+			.map(|block| (*block.nutty_id(), block))
+			.collect();
+
+		// Blocks deleted since their dates were listed are left out.
+		let entries = block_dates
+			.into_iter()
+			.filter(|date| block_cache.contains_key(&date.block_id))
+			.collect();
+
+		Ok(ContentCalendar::new(entries, block_cache))
+	}
+
+	async fn append_to_daily_note(
+		&self,
+		navigator_id: &NuttyId,
+		date: NaiveDate,
+		markdown: String,
+	) -> Result<ContentBlock, ContentServiceError> {
+		let mut content = BlockContent::Paragraph { markdown };
+
+		// Clean untrusted markdown, if configured to.
+		if self.sanitizer.sanitizes_on_save() {
+			content = self.sanitizer.clean_content(&content);
+		}
+
+		self
+			.repository
+			.with_transaction(|tx| {
+				let content = content.clone();
+
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let note_id = self
+						.repository
+						.find_daily_note_tx(ctx.conn(), navigator_id, date)
+						.await
+						.map_err(ContentServiceError::FetchBlockDates)?;
+
+					let (note_id, last_f_index) = match note_id {
+						Some(note_id) => {
+							lock_children_tx(ctx.conn(), &note_id).await?;
+
+							let child_ids = self
+								.repository
+								.list_child_ids_tx(ctx.conn(), &note_id, &ChildrenView::default())
+								.await
+								.map_err(ContentServiceError::FetchDescendantBlocks)?;
+
+							let last_child = match child_ids.last() {
+								Some(child_id) => self
+									.repository
+									.get_content_block_tx(ctx.conn(), &child_id.dissociate())
+									.await
+									.map_err(ContentServiceError::FetchContentBlock)?,
+								None => None,
+							};
+
+							(note_id, last_child.map(|child| child.f_index))
+						}
+
+						None => {
+							let note = ContentBlock::now_with_owner(
+								None,
+								*navigator_id,
+								FractionalIndex::start(),
+								BlockContent::Page {
+									title: date.format("%Y-%m-%d").to_string(),
+								},
+							);
+
+							let note = self
+								.save_content_block_tx(ctx, Some(navigator_id), note)
+								.await?;
+							(*note.nutty_id(), None)
+						}
+					};
+
+					let f_index = FractionalIndex::between(
+						&last_f_index.unwrap_or_else(FractionalIndex::start),
+						&FractionalIndex::end(),
+					)
+					.map_err(ContentServiceError::OrderContentBlock)?;
+
+					let block =
+						ContentBlock::now_with_owner(Some(note_id), *navigator_id, f_index, content);
+
+					self
+						.save_content_block_tx(ctx, Some(navigator_id), block)
+						.await
+				})
+			})
+			.await
+	}
+
+	/// List the todos a navigator can access, optionally filtered by status
+	/// and by a due date before the given date.
+	async fn list_tasks(
+		&self,
+		navigator_id: &NuttyId,
+		status: Option<TaskStatus>,
+		due_before: Option<NaiveDate>,
+	) -> Result<Vec<Task>, ContentServiceError> {
+		// Access is checked in the query, so that the limit counts only the
+		// todos the navigator can read.
+		let todos = self
+			.repository
+			.list_todo_blocks(
+				navigator_id,
+				status.map(|s| s.is_done()),
+				due_before,
+				MAX_TASKS,
+			)
+			.await
+			.map_err(ContentServiceError::FetchContentBlock)?;
+
+		let mut tasks = vec![];
+
+		for block in todos {
+			let block_id = block.nutty_id().dissociate();
+
+			let ancestors = self
+				.repository
+				.get_ancestor_blocks(&block_id)
+				.await
+				.map_err(ContentServiceError::FetchAncestorBlocks)?;
+
+			// Ancestors come nearest first; breadcrumbs read from the root.
+			let breadcrumbs = ancestors
+				.iter()
+				.rev()
+				.filter_map(|ancestor| match &ancestor.content {
+					BlockContent::Page { title } => Some(Breadcrumb {
+						block_id: *ancestor.nutty_id(),
+						title: title.clone(),
+					}),
+					_ => None,
+				})
+				.collect();
+
+			tasks.push(Task { block, breadcrumbs });
+		}
+
+		Ok(tasks)
+	}
+
+	/// Mark a todo as done, or as open again if it was done.
+	async fn toggle_todo(
+		&self,
+		block_id: &DissociatedNuttyId,
+	) -> Result<ContentBlock, ContentServiceError> {
+		self
+			.repository
+			.with_transaction(|tx| {
+				let block_id = *block_id;
+
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let mut block = self
+						.repository
+						.get_content_block_tx(ctx.conn(), &block_id)
+						.await
+						.map_err(ContentServiceError::FetchContentBlock)?
+						.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+					let BlockContent::Todo { done, .. } = &mut block.content else {
+						return Err(ContentServiceError::NotATodo);
+					};
+
+					*done = !*done;
+
+					self
+						.repository
+						.upsert_content_block_tx(ctx.conn(), block)
+						.await
+						.map_err(ContentServiceError::SaveContentBlock)
+				})
+			})
+			.await
+	}
+
+	/// Find and replace a regex pattern across the markdown of every block a
+	/// navigator owns, recording a revision for every changed block. With
+	/// `dry_run`, nothing is changed. Returns the matches, with snippets.
+	async fn find_and_replace(
+		&self,
+		owner_id: &NuttyId,
+		pattern: &str,
+		replacement: &str,
+		dry_run: bool,
+	) -> Result<Vec<TextMatch>, ContentServiceError> {
+		let find_replace =
+			FindReplace::new(pattern, replacement).map_err(ContentServiceError::InvalidFindReplace)?;
+
+		// The navigator must be able to write the blocks they own.
+		let can_write = self
+			.access_service
+			.can_permission(owner_id, "content_blocks:write:all")
+			.await
+			.map_err(ContentServiceError::AccessControl)?
+			|| self
+				.access_service
+				.can_permission(owner_id, "content_blocks:write:own")
+				.await
+				.map_err(ContentServiceError::AccessControl)?;
+
+		if !can_write {
+			return Err(ContentServiceError::EditDenied);
+		}
+
+		self
+			.repository
+			.with_transaction(|tx| {
+				let find_replace = &find_replace;
+
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let mut matches = vec![];
+					let mut after = None;
+
+					// Search the blocks in batches, saving each batch's changes.
+					loop {
+						let blocks = self
+							.repository
+							.list_owned_markdown_blocks_tx(
+								ctx.conn(),
+								owner_id,
+								after.as_ref(),
+								FIND_REPLACE_BATCH_SIZE,
+							)
+							.await
+							.map_err(ContentServiceError::FetchContentBlock)?;
+
+						let is_last_batch = (blocks.len() as i64) < FIND_REPLACE_BATCH_SIZE;
+						after = blocks.last().map(|block| *block.nutty_id());
+
+						let mut revisions = vec![];
+
+						for mut block in blocks {
+							let Some((content, block_matches)) = find_replace.apply(&block) else {
+								continue;
+							};
+
+							matches.extend(block_matches);
+
+							if dry_run {
+								continue;
+							}
+
+							let content = if self.sanitizer.sanitizes_on_save() {
+								self.sanitizer.clean_content(&content)
+							} else {
+								content
+							};
+
+							revisions.push(BlockRevision {
+								block_id: *block.nutty_id(),
+								previous_content: std::mem::replace(&mut block.content, content.clone()),
+								content,
+							});
+
+							// Replacements may add or remove tags, so links are saved too.
+							self
+								.save_content_block_tx(ctx, Some(owner_id), block)
+								.await?;
+						}
+
+						if !revisions.is_empty() {
+							self
+								.repository
+								.record_block_revisions_tx(ctx.conn(), &revisions, owner_id)
+								.await
+								.map_err(ContentServiceError::RecordRevisions)?;
+						}
+
+						if is_last_batch {
+							return Ok(matches);
+						}
+					}
+				})
+			})
+			.await
+	}
+
+	async fn diff_content_block(
+		&self,
+		block_id: &DissociatedNuttyId,
+		from: Option<&DissociatedNuttyId>,
+		to: Option<&DissociatedNuttyId>,
+	) -> Result<ContentDiff, ContentServiceError> {
+		let to_revision = match to {
+			Some(revision_id) => Some(self.get_block_revision(block_id, revision_id).await?),
+			None => None,
+		};
+
+		let from_content = match (from, &to_revision) {
+			(Some(revision_id), _) => {
+				self
+					.get_block_revision(block_id, revision_id)
+					.await?
+					.content
+			}
+			(None, Some(revision)) => revision.previous_content.clone(),
+			(None, None) => return Err(ContentServiceError::MissingRevision),
+		};
+
+		let to_content = match to_revision {
+			Some(revision) => revision.content,
+			None => self.get_content_block(block_id).await?.content,
+		};
+
+		Ok(ContentDiff::between(&from_content, &to_content))
+	}
+
+	async fn diff_content_blocks(
+		&self,
+		from_id: &DissociatedNuttyId,
+		to_id: &DissociatedNuttyId,
+	) -> Result<ContentDiff, ContentServiceError> {
+		let from = self.get_content_block(from_id).await?;
+		let to = self.get_content_block(to_id).await?;
+		Ok(ContentDiff::between(&from.content, &to.content))
+	}
+
+	async fn publish_revision(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		revision_id: Option<&DissociatedNuttyId>,
+	) -> Result<NuttyId, ContentServiceError> {
+		self
+			.repository
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let revision_id = match revision_id {
+						Some(revision_id) => *revision_id,
+
+						// Snapshot the draft, as a change from what was published.
+						None => {
+							let block = self.get_existing_block_tx(ctx, block_id).await?;
+
+							let published = self
+								.repository
+								.get_published_revision_tx(ctx.conn(), block_id)
+								.await
+								.map_err(ContentServiceError::FetchRevision)?;
+
+							let revision = BlockRevision {
+								block_id: *block.nutty_id(),
+								previous_content: published
+									.map_or_else(|| block.content.clone(), |published| published.content),
+								content: block.content,
+							};
+
+							let revision_ids = self
+								.repository
+								.record_block_revisions_tx(ctx.conn(), &[revision], navigator_id)
+								.await
+								.map_err(ContentServiceError::RecordRevisions)?;
+
+							revision_ids[0].dissociate()
+						}
+					};
+
+					self
+						.repository
+						.publish_revision_tx(ctx.conn(), block_id, &revision_id)
+						.await
+						.map_err(ContentServiceError::PublishRevision)?
+						.ok_or(ContentServiceError::RevisionNotFound)
+				})
+			})
+			.await
+	}
+
+	async fn get_published_revision(
+		&self,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Option<BlockRevision>, ContentServiceError> {
+		self
+			.repository
+			.get_published_revision(block_id)
+			.await
+			.map_err(ContentServiceError::FetchRevision)
+	}
+
+	async fn list_published_contents(
+		&self,
+		navigator_id: &NuttyId,
+		ids: &[NuttyId],
+	) -> Result<HashMap<NuttyId, BlockContent>, ContentServiceError> {
+		if ids.is_empty() {
+			return Ok(HashMap::new());
+		}
+
+		// Access checks see every block, so that ancestors aren't filtered.
+		row_level_security::unrestricted(self.repository.list_published_contents(navigator_id, ids))
+			.await
+			.map_err(ContentServiceError::FetchRevision)
+	}
+
+	async fn set_content_block_slug(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		slug: &Slug,
+	) -> Result<NuttyId, ContentServiceError> {
+		self
+			.repository
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let block = self.get_existing_block_tx(ctx, block_id).await?;
+
+					self
+						.repository
+						.retire_slug_tx(ctx.conn(), block.nutty_id())
+						.await
+						.map_err(ContentServiceError::SaveSlug)?;
+
+					let claimed = self
+						.repository
+						.claim_slug_tx(ctx.conn(), slug, block.nutty_id(), navigator_id)
+						.await
+						.map_err(ContentServiceError::SaveSlug)?;
+
+					match claimed {
+						true => Ok(*block.nutty_id()),
+						false => Err(ContentServiceError::SlugTaken),
+					}
+				})
+			})
+			.await
+	}
+
+	async fn resolve_permalink(
+		&self,
+		path: &str,
+	) -> Result<Option<SlugTarget>, ContentServiceError> {
+		if let Ok(block_id) = DissociatedNuttyId::new(path) {
+			let block = self
+				.repository
+				.get_content_block(&block_id)
+				.await
+				.map_err(ContentServiceError::FetchContentBlock)?;
+
+			return Ok(block.map(|block| SlugTarget {
+				block_id: *block.nutty_id(),
+				moved_to: None,
+			}));
+		}
+
+		let Ok(slug) = Slug::parse(path) else {
+			return Ok(None);
+		};
+
+		self
+			.repository
+			.resolve_slug(&slug)
+			.await
+			.map_err(ContentServiceError::ResolveSlug)
+	}
+
+	async fn list_owned_blocks_by_root(
+		&self,
+		owner_id: &NuttyId,
+	) -> Result<Vec<RootBlockGroup>, ContentServiceError> {
+		self
+			.repository
+			.list_owned_blocks_by_root(owner_id)
+			.await
+			.map_err(ContentServiceError::FetchOwnedBlocks)
+	}
+
+	async fn list_block_checksums(
+		&self,
+		root_id: &DissociatedNuttyId,
+	) -> Result<Vec<BlockChecksum>, ContentServiceError> {
+		self
+			.repository
+			.list_block_checksums(root_id)
+			.await
+			.map_err(ContentServiceError::ListBlockChecksums)
+	}
+
+	async fn get_outline(
+		&self,
+		navigator_id: &NuttyId,
+		root_id: &DissociatedNuttyId,
+		after_id: Option<&DissociatedNuttyId>,
+		limit: i64,
+	) -> Result<Vec<OutlineEntry>, ContentServiceError> {
+		let outline = self
+			.repository
+			.list_outline(root_id, after_id, limit)
+			.await
+			.map_err(ContentServiceError::ListOutline)?;
+
+		// Blocks that opt out of inheriting access are checked on their own,
+		// and left out with everything under them if they can't be read.
+		let mut readable = Vec::with_capacity(outline.len());
+		let mut unreadable_depth = None;
+
+		for entry in outline {
+			if unreadable_depth.is_some_and(|depth| entry.depth > depth) {
+				continue;
+			}
+
+			unreadable_depth = None;
+
+			if entry.depth > 0
+				&& !entry.inherit_access
+				&& !self
+					.check_content_block_access(navigator_id, &entry.block_id.dissociate())
+					.await?
+			{
+				unreadable_depth = Some(entry.depth);
+				continue;
+			}
+
+			readable.push(entry);
+		}
+
+		Ok(readable)
+	}
+
+	async fn list_changes(
+		&self,
+		navigator_id: &NuttyId,
+		since: Option<SyncCursor>,
+	) -> Result<SyncChanges, ContentServiceError> {
+		self
+			.repository
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					// Look one past the page, to tell whether there's more.
+					let mut changes = self
+						.repository
+						.list_changes_tx(
+							ctx.conn(),
+							navigator_id,
+							since.as_ref(),
+							MAX_SYNC_CHANGES + 1,
+						)
+						.await
+						.map_err(ContentServiceError::ListChanges)?;
+
+					let has_more = changes.len() > MAX_SYNC_CHANGES as usize;
+					changes.truncate(MAX_SYNC_CHANGES as usize);
+
+					let cursor = changes
+						.last()
+						.map(|change| change.cursor())
+						.or(since)
+						.unwrap_or_default();
+
+					let saved = |kind| {
+						changes
+							.iter()
+							.filter(|change| change.kind == kind && !change.deleted)
+							.map(|change| change.id)
+							.collect::<Vec<_>>()
+					};
+
+					let mut blocks = self
+						.repository
+						.get_content_blocks_by_ids_tx(ctx.conn(), &saved(ChangeKind::Block))
+						.await
+						.map_err(ContentServiceError::ListChanges)?;
+
+					// Blocks the navigator can't edit are pulled as published.
+					let mut published = self
+						.repository
+						.list_published_contents_tx(ctx.conn(), navigator_id, &saved(ChangeKind::Block))
+						.await
+						.map_err(ContentServiceError::ListChanges)?;
+
+					for block in &mut blocks {
+						if let Some(content) = published.remove(block.nutty_id()) {
+							block.content = content;
+						}
+					}
+
+					let links = self
+						.repository
+						.get_content_links_by_ids_tx(ctx.conn(), &saved(ChangeKind::Link))
+						.await
+						.map_err(ContentServiceError::ListChanges)?;
+
+					let tombstones = changes
+						.iter()
+						.filter(|change| change.deleted)
+						.map(|change| Tombstone {
+							kind: change.kind,
+							id: change.id,
+							deleted_at: change.changed_at,
+						})
+						.collect();
+
+					Ok(SyncChanges {
+						blocks,
+						links,
+						tombstones,
+						cursor,
+						has_more,
+					})
+				})
+			})
+			.await
+	}
+
+	async fn get_site_settings(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Option<SiteSettings>, ContentServiceError> {
+		self
+			.repository
+			.get_site_settings(navigator_id)
+			.await
+			.map_err(ContentServiceError::FetchSiteSettings)
+	}
+
+	async fn save_site_settings(
+		&self,
+		navigator_id: &NuttyId,
+		settings: SiteSettings,
+	) -> Result<SiteSettings, ContentServiceError> {
+		settings
+			.validate()
+			.map_err(ContentServiceError::InvalidSiteSettings)?;
+
+		self
+			.repository
+			.with_transaction(|tx| {
+				let settings = settings.clone();
+
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					self
+						.repository
+						.upsert_site_settings_tx(ctx.conn(), navigator_id, &settings)
+						.await
+						.map_err(ContentServiceError::SaveSiteSettings)?;
+
+					self
+						.repository
+						.get_site_settings_tx(ctx.conn(), navigator_id)
+						.await
+						.map_err(ContentServiceError::FetchSiteSettings)?
+						.ok_or(ContentServiceError::SiteSettingsNotFound)
+				})
+			})
+			.await
+	}
+
+	async fn delete_site_settings(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<bool, ContentServiceError> {
+		self
+			.repository
+			.delete_site_settings(navigator_id)
+			.await
+			.map_err(ContentServiceError::SaveSiteSettings)
+	}
+
+	async fn get_raw_content(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Value, ContentServiceError> {
+		self.require_raw_content_permission(navigator_id).await?;
+
+		let mut block = self.get_content_block(block_id).await?;
+		self
+			.show_published(navigator_id, std::slice::from_mut(&mut block))
+			.await?;
+
+		serde_json::to_value(&block.content).map_err(ContentServiceError::InvalidRawContent)
+	}
+
+	async fn save_raw_content(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		content: Value,
+	) -> Result<Value, ContentServiceError> {
+		self.require_raw_content_permission(navigator_id).await?;
+
+		let content: BlockContent =
+			serde_json::from_value(content).map_err(ContentServiceError::InvalidRawContent)?;
+
+		self
+			.block_kinds
+			.validate(&content)
+			.map_err(|error| match error {
+				BlockKindError::InvalidQuery(error) => ContentServiceError::ParseBlockQuery(error),
+				error => ContentServiceError::InvalidContent(error),
+			})?;
+
+		// Raw content is still cleaned, if configured to.
+		let content = if self.sanitizer.sanitizes_on_save() {
+			self.sanitizer.clean_content(&content)
+		} else {
+			content
+		};
+
+		self
+			.repository
+			.with_transaction(|tx| {
+				let content = content.clone();
+
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let mut block = self.get_existing_block_tx(ctx, block_id).await?;
+
+					let revision = BlockRevision {
+						block_id: *block.nutty_id(),
+						previous_content: std::mem::replace(&mut block.content, content.clone()),
+						content,
+					};
+
+					let block = self
+						.save_content_block_tx(ctx, Some(navigator_id), block)
+						.await?;
+
+					self
+						.repository
+						.record_block_revisions_tx(ctx.conn(), &[revision], navigator_id)
+						.await
+						.map_err(ContentServiceError::RecordRevisions)?;
+
+					serde_json::to_value(&block.content).map_err(ContentServiceError::InvalidRawContent)
+				})
+			})
+			.await
+	}
+
+	async fn check_block_stats(&self) -> Result<BlockStatsCheck, ContentServiceError> {
+		self
+			.repository
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let checked_count = self
+						.repository
+						.count_content_blocks_tx(ctx.conn())
+						.await
+						.map_err(ContentServiceError::CheckBlockStats)?;
+
+					let repaired = self
+						.repository
+						.repair_block_stats_tx(ctx.conn())
+						.await
+						.map_err(ContentServiceError::CheckBlockStats)?;
+
+					Ok(BlockStatsCheck {
+						checked_count,
+						repaired,
+					})
+				})
+			})
+			.await
+	}
+
+	async fn merge_blocks(
+		&self,
+		source_id: &DissociatedNuttyId,
+		target_id: &DissociatedNuttyId,
+		strategy: &MergeStrategy,
+	) -> Result<BlockMerge, ContentServiceError> {
+		self
+			.repository
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let source = self.get_existing_block_tx(ctx, source_id).await?;
+					let mut target = self.get_existing_block_tx(ctx, target_id).await?;
+
+					// Merging into the source's descendants would leave them parentless.
+					let target_ancestors = self
+						.repository
+						.get_ancestor_blocks_tx(ctx.conn(), target_id)
+						.await
+						.map_err(ContentServiceError::FetchAncestorBlocks)?;
+
+					if source.nutty_id() == target.nutty_id()
+						|| target_ancestors
+							.iter()
+							.any(|ancestor| ancestor.nutty_id() == source.nutty_id())
+					{
+						return Err(ContentServiceError::InvalidMerge(BlockMergeError::IntoSelf));
+					}
+
+					target.content = match strategy {
+						MergeStrategy::Concatenate => {
+							block_merge::concatenate(&source.content, &target.content)
+						}
+
+						MergeStrategy::ThreeWay { base_revision_id } => {
+							// The common revision may have been recorded on either block.
+							let mut base = None;
+
+							for block_id in [source_id, target_id] {
+								if base.is_none() {
+									base = self
+										.repository
+										.get_block_revision_tx(ctx.conn(), block_id, base_revision_id)
+										.await
+										.map_err(ContentServiceError::FetchRevision)?;
+								}
+							}
+
+							let base = base.ok_or(ContentServiceError::RevisionNotFound)?;
+
+							block_merge::merge_three_way(&base.content, &source.content, &target.content)
+								.map_err(ContentServiceError::InvalidMerge)?
+						}
+					};
+
+					// The merged content's links were already in the source or
+					// the target, so no author's access is checked.
+					let target = self.save_content_block_tx(ctx, None, target).await?;
+
+					let (moved_ids, relinked_ids) = self.absorb_block_tx(ctx, &source, &target).await?;
+
+					// The target may have been relinked, so get it afresh.
+					let block = self.get_existing_block_tx(ctx, target_id).await?;
+
+					Ok(BlockMerge {
+						block,
+						source_id: *source.nutty_id(),
+						relinked_ids,
+						moved_ids,
+					})
+				})
+			})
+			.await
+	}
+
+	async fn convert_block(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		target: ConvertibleKind,
+	) -> Result<BlockConversion, ContentServiceError> {
+		self
+			.repository
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let mut block = self.get_existing_block_tx(ctx, block_id).await?;
+
+					let converted = block_conversion::convert(&block.content, target)
+						.map_err(ContentServiceError::InvalidConversion)?;
+
+					let revision = BlockRevision {
+						block_id: *block.nutty_id(),
+						previous_content: std::mem::replace(
+							&mut block.content,
+							converted.content.clone(),
+						),
+						content: converted.content,
+					};
+
+					let block = self
+						.save_content_block_tx(ctx, Some(navigator_id), block)
+						.await?;
+
+					self
+						.repository
+						.record_block_revisions_tx(ctx.conn(), &[revision], navigator_id)
+						.await
+						.map_err(ContentServiceError::RecordRevisions)?;
+
+					let Some(content) = converted.overflow else {
+						return Ok(BlockConversion {
+							block,
+							overflow: None,
+						});
+					};
+
+					lock_children_tx(ctx.conn(), block.nutty_id()).await?;
+
+					// Put the overflow before the block's children, unless one is
+					// already at the very start.
+					let child_ids = self
+						.repository
+						.list_child_ids_tx(ctx.conn(), block.nutty_id(), &ChildrenView::default())
+						.await
+						.map_err(ContentServiceError::FetchDescendantBlocks)?;
+
+					let (first_child, last_child) = match (child_ids.first(), child_ids.last()) {
+						(Some(first_id), Some(last_id)) => (
+							Some(
+								self
+									.get_existing_block_tx(ctx, &first_id.dissociate())
+									.await?,
+							),
+							Some(
+								self
+									.get_existing_block_tx(ctx, &last_id.dissociate())
+									.await?,
+							),
+						),
+						_ => (None, None),
+					};
+
+					let f_index = match (first_child, last_child) {
+						(Some(first), _) if first.f_index != FractionalIndex::start() => {
+							FractionalIndex::between(&FractionalIndex::start(), &first.f_index)
+						}
+						(_, Some(last)) => {
+							FractionalIndex::between(&last.f_index, &FractionalIndex::end())
+						}
+						_ => FractionalIndex::between(&FractionalIndex::start(), &FractionalIndex::end()),
+					}
+					.map_err(ContentServiceError::OrderContentBlock)?;
+
+					let parent_id = Some(*block.nutty_id());
+
+					let child = match block.owner_id() {
+						Some(owner_id) => {
+							ContentBlock::now_with_owner(parent_id, *owner_id, f_index, content)
+						}
+						None => ContentBlock::now(parent_id, f_index, content),
+					};
+
+					let child = self
+						.save_content_block_tx(ctx, Some(navigator_id), child)
+						.await?;
+
+					Ok(BlockConversion {
+						block,
+						overflow: Some(child),
+					})
+				})
+			})
+			.await
+	}
+
+	async fn split_paragraph(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		offset: usize,
+	) -> Result<BlockSplit, ContentServiceError> {
+		self
+			.repository
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let mut block = self.get_existing_block_tx(ctx, block_id).await?;
+
+					let BlockContent::Paragraph { markdown } = &block.content else {
+						return Err(ContentServiceError::NotAParagraph);
+					};
+
+					let (head, tail) = block_split::split_markdown(markdown, offset)
+						.map_err(ContentServiceError::InvalidSplit)?;
+
+					// The new paragraph goes between the block and its next sibling.
+					if let Some(parent_id) = &block.parent_id {
+						lock_children_tx(ctx.conn(), parent_id).await?;
+					}
+
+					let next_f_index = match self.next_sibling_tx(ctx, &block).await? {
+						Some(next_sibling) => next_sibling.f_index,
+						None => FractionalIndex::end(),
+					};
+
+					let f_index = FractionalIndex::between(&block.f_index, &next_f_index)
+						.map_err(ContentServiceError::OrderContentBlock)?;
+
+					let content = BlockContent::Paragraph { markdown: head };
+
+					let revision = BlockRevision {
+						block_id: *block.nutty_id(),
+						previous_content: std::mem::replace(&mut block.content, content.clone()),
+						content,
+					};
+
+					// Saving replaces each block's links with those in its content.
+					let block = self
+						.save_content_block_tx(ctx, Some(navigator_id), block)
+						.await?;
+
+					self
+						.repository
+						.record_block_revisions_tx(ctx.conn(), &[revision], navigator_id)
+						.await
+						.map_err(ContentServiceError::RecordRevisions)?;
+
+					let content = BlockContent::Paragraph { markdown: tail };
+
+					let new_block = match block.owner_id() {
+						Some(owner_id) => {
+							ContentBlock::now_with_owner(block.parent_id, *owner_id, f_index, content)
+						}
+						None => ContentBlock::now(block.parent_id, f_index, content),
+					};
+
+					let new_block = self
+						.save_content_block_tx(ctx, Some(navigator_id), new_block)
+						.await?;
+
+					Ok(BlockSplit { block, new_block })
+				})
+			})
+			.await
+	}
+
+	async fn merge_with_next(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<BlockMerge, ContentServiceError> {
+		self
+			.repository
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let mut block = self.get_existing_block_tx(ctx, block_id).await?;
+
+					let next = self
+						.next_sibling_tx(ctx, &block)
+						.await?
+						.ok_or(ContentServiceError::NoNextSibling)?;
+
+					let (
+						BlockContent::Paragraph { markdown },
+						BlockContent::Paragraph {
+							markdown: next_markdown,
+						},
+					) = (&block.content, &next.content)
+					else {
+						return Err(ContentServiceError::NotAParagraph);
+					};
+
+					// The next paragraph is deleted, so it must be editable, too.
+					if !self
+						.check_content_block_write_access(navigator_id, &next.nutty_id().dissociate())
+						.await?
+					{
+						return Err(ContentServiceError::EditDenied);
+					}
+
+					let content = BlockContent::Paragraph {
+						markdown: format!("{markdown}{next_markdown}"),
+					};
+
+					let revision = BlockRevision {
+						block_id: *block.nutty_id(),
+						previous_content: std::mem::replace(&mut block.content, content.clone()),
+						content,
+					};
+
+					let block = self
+						.save_content_block_tx(ctx, Some(navigator_id), block)
+						.await?;
+
+					self
+						.repository
+						.record_block_revisions_tx(ctx.conn(), &[revision], navigator_id)
+						.await
+						.map_err(ContentServiceError::RecordRevisions)?;
+
+					let (moved_ids, relinked_ids) = self.absorb_block_tx(ctx, &next, &block).await?;
+
+					// The block may have been relinked, so get it afresh.
+					let block = self.get_existing_block_tx(ctx, block_id).await?;
+
+					Ok(BlockMerge {
+						block,
+						source_id: *next.nutty_id(),
+						relinked_ids,
+						moved_ids,
+					})
+				})
+			})
+			.await
+	}
+
+	async fn reorder_children(
+		&self,
+		parent_id: &DissociatedNuttyId,
+		child_ids: &[DissociatedNuttyId],
+	) -> Result<Vec<ContentBlock>, ContentServiceError> {
+		self
+			.repository
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let parent = self.get_existing_block_tx(ctx, parent_id).await?;
+					lock_children_tx(ctx.conn(), parent.nutty_id()).await?;
+
+					let current_ids = self
+						.repository
+						.list_child_ids_tx(ctx.conn(), parent.nutty_id(), &ChildrenView::default())
+						.await
+						.map_err(ContentServiceError::FetchDescendantBlocks)?;
+
+					// Each child must be given exactly once.
+					let mut given_nids: Vec<_> = child_ids.iter().map(|id| id.nid()).collect();
+					let mut current_nids: Vec<_> = current_ids.iter().map(|id| id.nid()).collect();
+					given_nids.sort();
+					current_nids.sort();
+
+					if given_nids != current_nids {
+						return Err(ContentServiceError::ChildrenMismatch);
+					}
+
+					let mut children = vec![];
+
+					for (child_id, f_index) in child_ids
+						.iter()
+						.zip(FractionalIndex::spread(child_ids.len()))
+					{
+						let mut child = self.get_existing_block_tx(ctx, child_id).await?;
+						child.f_index = f_index;
+
+						let child = self
+							.repository
+							.upsert_content_block_tx(ctx.conn(), child)
+							.await
+							.map_err(ContentServiceError::SaveContentBlock)?;
+
+						children.push(child);
+					}
+
+					Ok(children)
+				})
+			})
+			.await
+	}
+
+	/// List the custom property definitions, by name.
+	async fn search_content_blocks(
+		&self,
+		navigator_id: &NuttyId,
+		query: &str,
+		language: Option<SearchLanguage>,
+		include_archived: bool,
+	) -> Result<Vec<ContentBlock>, ContentServiceError> {
+		let query = query.trim();
+
+		if query.is_empty() {
+			return Err(ContentServiceError::EmptySearchQuery);
+		}
+
+		// Access is checked in the query, so that the best matches the
+		// navigator can read aren't crowded out by ones they can't.
+		self
+			.repository
+			.search_content_blocks(
+				navigator_id,
+				query,
+				language,
+				include_archived,
+				MAX_SEARCH_RESULTS,
+			)
+			.await
+			.map_err(ContentServiceError::SearchContentBlocks)
+	}
+
+	async fn list_content_blocks(
+		&self,
+		navigator_id: &NuttyId,
+		filter: &ContentFilter,
+	) -> Result<Vec<ContentBlock>, ContentServiceError> {
+		// Access is checked in the query, so that the limit counts only the
+		// blocks the navigator can read.
+		let mut blocks = self
+			.repository
+			.list_content_blocks(navigator_id, filter, MAX_LISTED_BLOCKS)
+			.await
+			.map_err(ContentServiceError::ListContentBlocks)?;
+
+		self.show_published(navigator_id, &mut blocks).await?;
+
+		Ok(blocks)
+	}
+
+	async fn set_content_block_language(
+		&self,
+		block_id: &DissociatedNuttyId,
+		language: Option<SearchLanguage>,
+	) -> Result<u64, ContentServiceError> {
+		let updated = self
+			.repository
+			.set_block_language(block_id, language)
+			.await
+			.map_err(ContentServiceError::SetLanguage)?;
+
+		if updated == 0 {
+			return Err(ContentServiceError::ContentBlockNotFound);
+		}
+
+		Ok(updated)
+	}
+
+	async fn set_content_block_archived(
+		&self,
+		block_id: &DissociatedNuttyId,
+		archived: bool,
+		subtree: bool,
+	) -> Result<BlockArchival, ContentServiceError> {
+		self
+			.repository
+			.set_block_archived(block_id, archived, subtree)
+			.await
+			.map_err(ContentServiceError::SetArchived)?
+			.ok_or(ContentServiceError::ContentBlockNotFound)
+	}
+
+	async fn set_inbox(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<ContentBlock, ContentServiceError> {
+		let inbox = self
+			.repository
+			.get_content_block(block_id)
+			.await
+			.map_err(ContentServiceError::FetchContentBlock)?
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		self
+			.repository
+			.set_inbox(navigator_id, inbox.nutty_id())
+			.await
+			.map_err(ContentServiceError::SaveInbox)?;
+
+		Ok(inbox)
+	}
+
+	async fn ingest_email(
+		&self,
+		navigator_id: &NuttyId,
+		email: &IncomingEmail,
+	) -> Result<ContentBlock, ContentServiceError> {
+		let parent_id = self
+			.repository
+			.get_inbox(navigator_id)
+			.await
+			.map_err(ContentServiceError::FetchInbox)?
+			.ok_or(ContentServiceError::InboxNotConfigured)?;
+
+		// Email is untrusted, so its markdown is cleaned regardless of config.
+		let blocks: Vec<ContentBlock> = email
+			.to_blocks(parent_id, *navigator_id)
+			.map_err(ContentServiceError::InvalidEmail)?
+			.into_iter()
+			.map(|mut block| {
+				block.content = self.sanitizer.clean_content(&block.content);
+				block
+			})
+			.collect();
+
+		self
+			.repository
+			.with_transaction(|tx| {
+				let blocks = blocks.clone();
+
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let mut saved = vec![];
+
+					for block in blocks {
+						saved.push(
+							self
+								.save_content_block_tx(ctx, Some(navigator_id), block)
+								.await?,
+						);
+					}
+
+					Ok(saved.remove(0))
+				})
+			})
+			.await
+	}
+
+	async fn capture_page(
+		&self,
+		navigator_id: &NuttyId,
+		capture: &Capture,
+		snapshot: Option<&PageSnapshot>,
+	) -> Result<CapturedPage, ContentServiceError> {
+		let parent_id = self
+			.repository
+			.get_inbox(navigator_id)
+			.await
+			.map_err(ContentServiceError::FetchInbox)?
+			.ok_or(ContentServiceError::InboxNotConfigured)?;
+
+		// Captures come from arbitrary pages, so they're always cleaned.
+		let blocks: Vec<ContentBlock> = capture
+			.to_blocks(parent_id, *navigator_id, snapshot)
+			.map_err(ContentServiceError::InvalidCapture)?
+			.into_iter()
+			.map(|mut block| {
+				block.content = self.sanitizer.clean_content(&block.content);
+				block
+			})
+			.collect();
+
+		let unfurl = snapshot.map(|snapshot| snapshot.unfurl.clone());
+
+		self
+			.repository
+			.with_transaction(|tx| {
+				let blocks = blocks.clone();
+				let unfurl = unfurl.clone();
+
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let mut saved = vec![];
+
+					for block in blocks {
+						saved.push(
+							self
+								.save_content_block_tx(ctx, Some(navigator_id), block)
+								.await?,
+						);
+					}
+
+					let block = saved.remove(0);
+
+					if let Some(unfurl) = &unfurl {
+						self
+							.repository
+							.save_unfurl_tx(ctx.conn(), block.nutty_id(), unfurl)
+							.await
+							.map_err(ContentServiceError::SaveUnfurl)?;
+					}
+
+					Ok(CapturedPage {
+						block,
+						unfurl,
+						archive: saved.pop(),
+					})
+				})
+			})
+			.await
+	}
+
+	async fn paste_html(
+		&self,
+		navigator_id: &NuttyId,
+		parent_id: &DissociatedNuttyId,
+		html: &str,
+	) -> Result<Vec<ContentBlock>, ContentServiceError> {
+		let pasted = html_import::html_to_blocks(html).map_err(ContentServiceError::InvalidPaste)?;
+
+		self
+			.repository
+			.with_transaction(|tx| {
+				let pasted = pasted.clone();
+
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let parent = self.get_existing_block_tx(ctx, parent_id).await?;
+					lock_children_tx(ctx.conn(), parent.nutty_id()).await?;
+
+					let child_ids = self
+						.repository
+						.list_child_ids_tx(ctx.conn(), parent.nutty_id(), &ChildrenView::default())
+						.await
+						.map_err(ContentServiceError::FetchDescendantBlocks)?;
+
+					let last_f_index = match child_ids.last() {
+						Some(child_id) => {
+							let child = self
+								.get_existing_block_tx(ctx, &child_id.dissociate())
+								.await?;
+							Some(child.f_index)
+						}
+						None => None,
+					};
+
+					let blocks = html_import::instantiate(
+						&pasted,
+						*parent.nutty_id(),
+						*navigator_id,
+						last_f_index.as_ref(),
+					)
+					.map_err(ContentServiceError::OrderContentBlock)?;
+
+					// Pastes come from arbitrary pages, so they're always cleaned.
+					let mut saved = vec![];
+
+					for mut block in blocks {
+						block.content = self.sanitizer.clean_content(&block.content);
+						saved.push(
+							self
+								.save_content_block_tx(ctx, Some(navigator_id), block)
+								.await?,
+						);
+					}
+
+					Ok(saved)
+				})
+			})
+			.await
+	}
+
+	async fn list_property_definitions(
+		&self,
+	) -> Result<Vec<PropertyDefinition>, ContentServiceError> {
+		self
+			.repository
+			.list_property_definitions()
+			.await
+			.map_err(ContentServiceError::FetchPropertyDefinitions)
+	}
+
+	/// Define a custom property that content blocks may carry.
+	async fn create_property_definition(
+		&self,
+		definition: PropertyDefinition,
+	) -> Result<PropertyDefinition, ContentServiceError> {
+		self
+			.repository
+			.insert_property_definition(&definition)
+			.await
+			.map_err(ContentServiceError::SavePropertyDefinition)
+	}
+
+	async fn annotate_content_block(
+		&self,
+		block: &ContentBlock,
+		annotations: Vec<BlockAnnotation>,
+	) -> Result<bool, ContentServiceError> {
+		let block_id = block.nutty_id().dissociate();
+
+		self
+			.repository
+			.with_transaction(|tx| {
+				let annotations = annotations.clone();
+
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let current = self
+						.repository
+						.get_content_block_tx(ctx.conn(), &block_id)
+						.await
+						.map_err(ContentServiceError::FetchContentBlock)?;
+
+					// Annotations of stale content would mislead editors.
+					if current.is_none_or(|current| current.content != block.content) {
+						return Ok(false);
+					}
+
+					self
+						.repository
+						.delete_annotations_tx(ctx.conn(), block.nutty_id())
+						.await
+						.map_err(ContentServiceError::SaveAnnotations)?;
+
+					self
+						.repository
+						.insert_annotations_tx(ctx.conn(), &annotations)
+						.await
+						.map_err(ContentServiceError::SaveAnnotations)?;
+
+					Ok(true)
+				})
+			})
+			.await
+	}
+
+	async fn list_translations(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Vec<Translation>, ContentServiceError> {
+		let block = self.get_content_block(block_id).await?;
+
+		let translations = self
+			.repository
+			.list_translations(block.nutty_id())
+			.await
+			.map_err(ContentServiceError::FetchTranslations)?;
+
+		self.readable_translations(navigator_id, translations).await
+	}
+
+	async fn set_translation(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		lang: &Lang,
+		translation_of: Option<&DissociatedNuttyId>,
+	) -> Result<Vec<Translation>, ContentServiceError> {
+		let translations = self
+			.repository
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+
+					let block = self.get_existing_block_tx(ctx, block_id).await?;
+
+					let original = match translation_of {
+						Some(original_id) => {
+							let original = self.get_existing_block_tx(ctx, original_id).await?;
+
+							// Variants join the original's translations, so it needs a language first.
+							let translations = self
+								.repository
+								.list_translations_tx(ctx.conn(), original.nutty_id())
+								.await
+								.map_err(ContentServiceError::FetchTranslations)?;
+
+							if translations.is_empty() {
+								return Err(ContentServiceError::UntranslatedBlock);
+							}
+
+							Some(*original.nutty_id())
+						}
+
+						None => None,
+					};
+
+					self
+						.repository
+						.lock_translations_tx(ctx.conn(), original.as_ref().unwrap_or(block.nutty_id()))
+						.await
+						.map_err(ContentServiceError::SaveTranslation)?;
+
+					self
+						.repository
+						.set_translation_tx(ctx.conn(), block.nutty_id(), lang, original.as_ref())
+						.await
+						.map_err(|error| match error {
+							ContentRepositoryError::TranslationTaken => {
+								ContentServiceError::TranslationTaken
+							}
+							error => ContentServiceError::SaveTranslation(error),
+						})?;
+
+					self
+						.repository
+						.list_translations_tx(ctx.conn(), block.nutty_id())
+						.await
+						.map_err(ContentServiceError::FetchTranslations)
+				})
+			})
+			.await?;
+
+		self.readable_translations(navigator_id, translations).await
+	}
+
+	async fn remove_translation(
+		&self,
+		block_id: &DissociatedNuttyId,
+	) -> Result<(), ContentServiceError> {
+		let block = self.get_content_block(block_id).await?;
+
+		let deleted = self
+			.repository
+			.delete_translation(block.nutty_id())
+			.await
+			.map_err(ContentServiceError::SaveTranslation)?;
+
+		match deleted {
+			true => Ok(()),
+			false => Err(ContentServiceError::ContentBlockNotFound),
+		}
+	}
+
+	async fn localize_content_block(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		preferred: &[Lang],
+	) -> Result<LocalizedBlock, ContentServiceError> {
+		let mut block = self.get_content_block(block_id).await?;
+
+		let translations = self
+			.repository
+			.list_translations(block.nutty_id())
+			.await
+			.map_err(ContentServiceError::FetchTranslations)?;
+
+		// Only variants the navigator can read are resolved, so that the next
+		// preferred language is tried when they can't read the best match.
+		let translations = self
+			.readable_translations(navigator_id, translations)
+			.await?;
+
+		if let Some(variant) = Translation::resolve(&translations, preferred)
+			&& variant.block_id != *block.nutty_id()
+		{
+			block = self
+				.get_content_block(&variant.block_id.dissociate())
+				.await?;
+		}
+
+		let lang = translations
+			.iter()
+			.find(|translation| translation.block_id == *block.nutty_id())
+			.map(|translation| translation.lang.clone());
+
+		self
+			.show_published(navigator_id, std::slice::from_mut(&mut block))
+			.await?;
+
+		Ok(LocalizedBlock {
+			block,
+			lang,
+			translations,
+		})
+	}
+
+	async fn list_linked_references(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Vec<LinkedReference>, ContentServiceError> {
+		let block = self.get_content_block(block_id).await?;
+
+		let links = self
+			.repository
+			.get_content_links_to(block.nutty_id())
+			.await
+			.map_err(ContentServiceError::FetchInboundLinks)?;
+
+		let source_ids: Vec<NuttyId> = links.iter().map(|link| link.source_id).collect();
+
+		let mut sources = self
+			.repository
+			.get_content_blocks_by_ids(&source_ids)
+			.await
+			.map_err(ContentServiceError::FetchContentBlock)?;
+
+		// Ties are broken by NID, so that the order is the same every time.
+		sources.sort_by(|a, b| {
+			a.created_at()
+				.partial_cmp(b.created_at())
+				.unwrap_or(std::cmp::Ordering::Equal)
+				.then_with(|| a.nutty_id().nid().cmp(&b.nutty_id().nid()))
+		});
+
+		let mut references = vec![];
+
+		for source in sources {
+			if self
+				.check_content_block_access(navigator_id, &source.nutty_id().dissociate())
+				.await?
+			{
+				let markdown = self.block_kinds.render_markdown(&source.content);
+				references.push(LinkedReference::new(*source.nutty_id(), &markdown));
+			}
+		}
+
+		Ok(references)
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ContentServiceError {
+	#[error("Failed to save content block: {0}")]
+	SaveContentBlock(#[source] ContentRepositoryError),
+
+	#[error("Failed to save content link: {0}")]
+	SaveContentLink(#[source] ContentRepositoryError),
+
+	#[error("Failed to delete content links: {0}")]
+	DeleteContentLinks(#[source] ContentRepositoryError),
+
+	#[error("Failed to delete content block: {0}")]
+	DeleteContentBlock(#[source] ContentRepositoryError),
+
+	#[error("Failed to save block dates: {0}")]
+	SaveBlockDates(#[source] ContentRepositoryError),
+
+	#[error("Failed to fetch block dates: {0}")]
+	FetchBlockDates(#[source] ContentRepositoryError),
+
+	#[error("Failed to save annotations: {0}")]
+	SaveAnnotations(#[source] ContentRepositoryError),
+
+	#[error("Failed to fetch annotations: {0}")]
+	FetchAnnotations(#[source] ContentRepositoryError),
+
+	#[error("Invalid date range; ranges span at most {MAX_CALENDAR_DAYS} days")]
+	InvalidDateRange,
+
+	#[error("Failed to fetch property definitions: {0}")]
+	FetchPropertyDefinitions(#[source] ContentRepositoryError),
+
+	#[error("Failed to save property definition: {0}")]
+	SavePropertyDefinition(#[source] ContentRepositoryError),
+
+	#[error("Invalid properties: {0}")]
+	InvalidProperties(#[source] PropertyError),
+
+	#[error("Invalid content: {0}")]
+	InvalidContent(#[source] BlockKindError),
+
+	#[error("Database error: {0}")]
+	Database(#[from] sqlx::Error),
+
+	#[error("Content block not found")]
+	ContentBlockNotFound,
+
+	#[error("Failed to fetch revision: {0}")]
+	FetchRevision(#[source] ContentRepositoryError),
+
+	#[error("Revision not found")]
+	RevisionNotFound,
+
+	#[error("Failed to publish revision: {0}")]
+	PublishRevision(#[source] ContentRepositoryError),
+
+	#[error("Slug already taken")]
+	SlugTaken,
+
+	#[error("Failed to fetch translations: {0}")]
+	FetchTranslations(#[source] ContentRepositoryError),
+
+	#[error("Failed to save translation: {0}")]
+	SaveTranslation(#[source] ContentRepositoryError),
+
+	#[error("A translation already exists in this language")]
+	TranslationTaken,
+
+	#[error("The block being translated has no language")]
+	UntranslatedBlock,
+
+	#[error("Failed to save slug: {0}")]
+	SaveSlug(#[source] ContentRepositoryError),
+
+	#[error("Failed to resolve slug: {0}")]
+	ResolveSlug(#[source] ContentRepositoryError),
+
+	#[error("Failed to fetch owned blocks: {0}")]
+	FetchOwnedBlocks(#[source] ContentRepositoryError),
+
+	#[error("Site settings not found")]
+	SiteSettingsNotFound,
+
+	#[error("Invalid site settings: {0}")]
+	InvalidSiteSettings(#[source] SiteSettingsError),
+
+	#[error("Failed to fetch site settings: {0}")]
+	FetchSiteSettings(#[source] ContentRepositoryError),
+
+	#[error("Failed to save site settings: {0}")]
+	SaveSiteSettings(#[source] ContentRepositoryError),
+
+	#[error("Failed to list block checksums: {0}")]
+	ListBlockChecksums(#[source] ContentRepositoryError),
+
+	#[error("Failed to list outline: {0}")]
+	ListOutline(#[source] ContentRepositoryError),
+
+	#[error("Failed to list changes: {0}")]
+	ListChanges(#[source] ContentRepositoryError),
+
+	#[error("The block's owner has blocked this navigator")]
+	BlockedByOwner,
+
+	#[error("Failed to fetch block stats: {0}")]
+	FetchBlockStats(#[source] ContentRepositoryError),
+
+	#[error("Failed to check block stats: {0}")]
+	CheckBlockStats(#[source] ContentRepositoryError),
+
+	#[error("Failed to compress content: {0}")]
+	CompressContent(#[source] ContentRepositoryError),
+
+	#[error("A revision to diff from or to is required")]
+	MissingRevision,
+
+	#[error("Unable to merge content blocks: {0}")]
+	InvalidMerge(#[source] BlockMergeError),
+
+	#[error("Unable to convert content block: {0}")]
+	InvalidConversion(#[source] BlockConversionError),
+
+	#[error("Content block is not a page")]
+	NotAPage,
+
+	#[error("Page titles can't contain [[ or ]]")]
+	InvalidPageTitle,
+
+	#[error("Content block is not a todo")]
+	NotATodo,
+
+	#[error("Content block is not a paragraph")]
+	NotAParagraph,
+
+	#[error("Content block has no next sibling")]
+	NoNextSibling,
+
+	#[error("The children given don't match the content block's children")]
+	ChildrenMismatch,
+
+	#[error("Unable to split paragraph: {0}")]
+	InvalidSplit(#[source] BlockSplitError),
+
+	#[error("Not allowed to transfer content blocks")]
+	TransferDenied,
+
+	#[error("Not allowed to access raw content")]
+	RawContentDenied,
+
+	#[error("Invalid raw content: {0}")]
+	InvalidRawContent(#[source] serde_json::Error),
+
+	#[error("Not allowed to edit content blocks")]
+	EditDenied,
+
+	#[error("Invalid find and replace: {0}")]
+	InvalidFindReplace(#[source] FindReplaceError),
+
+	#[error("Failed to record block revisions: {0}")]
+	RecordRevisions(#[source] ContentRepositoryError),
+
+	#[error("The search query is empty")]
+	EmptySearchQuery,
+
+	#[error("Failed to search content blocks: {0}")]
+	SearchContentBlocks(#[source] ContentRepositoryError),
+
+	#[error("Failed to list content blocks: {0}")]
+	ListContentBlocks(#[source] ContentRepositoryError),
+
+	#[error("Failed to fetch inbox: {0}")]
+	FetchInbox(#[source] ContentRepositoryError),
+
+	#[error("Failed to save inbox: {0}")]
+	SaveInbox(#[source] ContentRepositoryError),
+
+	#[error("No inbox is configured for the navigator")]
+	InboxNotConfigured,
+
+	#[error("Invalid email: {0}")]
+	InvalidEmail(#[source] IncomingEmailError),
+
+	#[error("Unable to order content block: {0}")]
+	OrderContentBlock(#[source] FractionalIndexError),
+
+	#[error("Invalid capture: {0}")]
+	InvalidCapture(#[source] CaptureError),
+
+	#[error("Invalid paste: {0}")]
+	InvalidPaste(#[source] HtmlImportError),
+
+	#[error("Failed to save unfurl: {0}")]
+	SaveUnfurl(#[source] ContentRepositoryError),
+
+	#[error("Failed to set search language: {0}")]
+	SetLanguage(#[source] ContentRepositoryError),
+
+	#[error("Failed to archive content block: {0}")]
+	SetArchived(#[source] ContentRepositoryError),
+
+	#[error("Not allowed to share content block")]
+	ShareDenied,
+
+	#[error("Not allowed to manage access to content block")]
+	ManageDenied,
+
+	#[error("Failed to fetch access requests: {0}")]
+	FetchAccessRequests(#[source] ContentRepositoryError),
+
+	#[error("Failed to save access request: {0}")]
+	SaveAccessRequest(#[source] ContentRepositoryError),
+
+	#[error("Pending access request not found")]
+	AccessRequestNotFound,
+
+	#[error("Failed to transfer ownership: {0}")]
+	TransferOwnership(#[source] ContentRepositoryError),
+
+	#[error("Failed to fetch content block: {0}")]
+	FetchContentBlock(#[source] ContentRepositoryError),
+
+	#[error("Failed to fetch ancestor blocks: {0}")]
+	FetchAncestorBlocks(#[source] ContentRepositoryError),
+
+	#[error("Failed to fetch descendant blocks: {0}")]
+	FetchDescendantBlocks(#[source] ContentRepositoryError),
+
+	#[error("Failed to fetch outbound links: {0}")]
+	FetchOutboundLinks(#[source] ContentRepositoryError),
+
+	#[error("Failed to fetch related blocks: {0}")]
+	FetchRelatedBlocks(#[source] ContentRepositoryError),
+
+	#[error("Failed to fetch inbound links: {0}")]
+	FetchInboundLinks(#[source] ContentRepositoryError),
+
+	#[error("Failed to parse block query: {0}")]
+	ParseBlockQuery(#[source] BlockQueryError),
+
+	#[error("Failed to execute block query: {0}")]
+	ExecuteBlockQuery(#[source] ContentRepositoryError),
+
+	#[error("Failed to build content context: {0}")]
+	BuildContentContext(String),
+
+	#[error("Access control error: {0}")]
+	AccessControl(#[source] crate::access::service::AccessServiceError),
+}
+
+#[cfg(test)]
+mod tests {
+	use sqlx::Pool;
+	use sqlx::Postgres;
+	use sqlx::postgres::PgPoolOptions;
+
+	use super::*;
+	use crate::access::repository::AccessRepository;
+	use crate::access::service::AccessService;
+	use crate::content::repository::ContentRepository;
+	use crate::models::BlockContent;
+	use crate::models::ContentBlock;
 	use crate::models::FractionalIndex;
 	use crate::models::NuttyId;
+	use crate::models::block_date::BlockDateKind;
+	use crate::models::content_diff::Change;
+	use crate::models::content_diff::ParagraphDiff;
+	use crate::models::property::BlockProperties;
+	use crate::models::property::PropertyKind;
+	use crate::moderation::repository::ModerationRepository;
+
+	async fn connect_to_test_database() -> Pool<Postgres> {
+		let database_url = std::env::var("DATABASE_URL").unwrap();
+
+		PgPoolOptions::new()
+			.max_connections(5)
+			.connect(&database_url)
+			.await
+			.expect("Failed to connect to test database")
+	}
+
+	#[tokio::test]
+	async fn test_get_content_block_context() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		// Arrange: A navigator to read as, who can't read any query results.
+		let navigator_id = NuttyId::now();
+
+		// Arrange: Create a hierarchy of content blocks.
+		let parent_block = ContentBlock::now(
+			None,
+			FractionalIndex::start(),
+			BlockContent::Page {
+				title: "Parent Page".to_string(),
+			},
+		);
+
+		let middle_block = ContentBlock::now(
+			Some(*parent_block.nutty_id()),
+			FractionalIndex::between(&FractionalIndex::start(), &FractionalIndex::end()).unwrap(),
+			BlockContent::Page {
+				title: "Middle Page".to_string(),
+			},
+		);
+
+		let child_block = ContentBlock::now(
+			Some(*middle_block.nutty_id()),
+			FractionalIndex::start(),
+			BlockContent::Page {
+				title: "Child Page".to_string(),
+			},
+		);
+
+		let sibling_block = ContentBlock::now(
+			Some(*middle_block.nutty_id()),
+			FractionalIndex::between(&child_block.f_index, &FractionalIndex::end()).unwrap(),
+			BlockContent::Page {
+				title: "Sibling Page".to_string(),
+			},
+		);
+
+		// Arrange: Create a reference block.
+		let reference_block = ContentBlock::now(
+			None,
+			FractionalIndex::end(),
+			BlockContent::Page {
+				title: "Reference Page".to_string(),
+			},
+		);
+
+		// Act: Save all the blocks.
+		service
+			.repository
+			.upsert_content_block(parent_block.clone())
+			.await
+			.expect("Failed to save parent block");
+
+		service
+			.repository
+			.upsert_content_block(middle_block.clone())
+			.await
+			.expect("Failed to save middle block");
+
+		service
+			.repository
+			.upsert_content_block(child_block.clone())
+			.await
+			.expect("Failed to save child block");
+
+		service
+			.repository
+			.upsert_content_block(sibling_block.clone())
+			.await
+			.expect("Failed to save sibling block");
+
+		service
+			.repository
+			.upsert_content_block(reference_block.clone())
+			.await
+			.expect("Failed to save reference block");
+
+		// Arrange: Create content links.
+		let link = ContentLink::now(*middle_block.nutty_id(), *reference_block.nutty_id());
+		service
+			.repository
+			.upsert_content_link(link)
+			.await
+			.expect("Failed to create link");
+
+		// Act: Get the context for the middle block.
+		let context = service
+			.get_content_block_context(
+				&navigator_id,
+				&middle_block.nutty_id().into(),
+				&ChildrenView::default(),
+			)
+			.await
+			.expect("Failed to get content context");
+
+		// Assert: The context has the correct block ID.
+		assert_eq!(context.block_id(), middle_block.nutty_id());
+
+		// Assert: The context has the correct parent ID.
+		assert_eq!(context.parent_id(), Some(parent_block.nutty_id()));
+
+		// Assert: The context has the correct children IDs.
+		let children_ids = context.children_ids();
+		assert_eq!(children_ids.len(), 2);
+		assert!(children_ids.contains(child_block.nutty_id()));
+		assert!(children_ids.contains(sibling_block.nutty_id()));
+
+		// Assert: The context has the correct reference IDs.
+		let reference_ids = context.reference_ids();
+		assert_eq!(reference_ids.len(), 1);
+		assert_eq!(reference_ids[0], *reference_block.nutty_id());
+
+		// Assert: The cache contains all the expected blocks.
+		let cache = context.block_cache();
+		assert!(cache.contains_key(parent_block.nutty_id()));
+		assert!(cache.contains_key(middle_block.nutty_id()));
+		assert!(cache.contains_key(child_block.nutty_id()));
+		assert!(cache.contains_key(sibling_block.nutty_id()));
+
+		// References may or may not be included automatically in the cache
+		// depending on implementation details.
+
+		// Assert: The context has the correct children IDs.
+		let children_ids = context.children_ids();
+		assert_eq!(children_ids.len(), 2);
+		assert!(children_ids.contains(child_block.nutty_id()));
+		assert!(children_ids.contains(sibling_block.nutty_id()));
+
+		// Get context for a child block to test different parent/children relationships.
+		let child_context = service
+			.get_content_block_context(
+				&navigator_id,
+				&child_block.nutty_id().into(),
+				&ChildrenView::default(),
+			)
+			.await
+			.expect("Failed to get child content context");
+
+		// Assert: The context has the correct parent ID.
+		assert_eq!(child_context.parent_id(), Some(middle_block.nutty_id()));
+
+		// Assert: The context has the ancestors, root first.
+		assert_eq!(
+			child_context.ancestor_ids(),
+			&[*parent_block.nutty_id(), *middle_block.nutty_id()]
+		);
+
+		// Assert: The context has no children.
+		assert_eq!(child_context.children_ids().len(), 0);
+
+		// Assert: The cache still contains ancestors and descendants.
+		let child_cache = child_context.block_cache();
+		assert!(child_cache.contains_key(parent_block.nutty_id()));
+		assert!(child_cache.contains_key(middle_block.nutty_id()));
+		assert!(child_cache.contains_key(child_block.nutty_id()));
+	}
+
+	#[tokio::test]
+	async fn test_save_content_block() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		// Arrange: Create test content blocks.
+		let source_block = ContentBlock::now(
+			None,
+			FractionalIndex::start(),
+			BlockContent::Page {
+				title: "Source Page".to_string(),
+			},
+		);
+
+		let target_block = ContentBlock::now(
+			None,
+			FractionalIndex::between(&source_block.f_index, &FractionalIndex::end()).unwrap(),
+			BlockContent::Page {
+				title: "Target Page".to_string(),
+			},
+		);
+
+		// Act: Save the target block first.
+		let saved_target = service
+			.save_content_block(None, target_block.clone())
+			.await
+			.expect("Failed to save target block");
+
+		// Act: Create a source block with a link to the target.
+		let source_with_link = ContentBlock::now(
+			None,
+			FractionalIndex::start(),
+			BlockContent::Paragraph {
+				markdown: format!("This links to [[{}]]", saved_target.nutty_id().nid()),
+			},
+		);
+
+		// Act: Save the source block.
+		let saved_source = service
+			.save_content_block(None, source_with_link.clone())
+			.await
+			.expect("Failed to save source block");
+
+		// Assert: The source block was saved correctly.
+		assert_eq!(saved_source.nutty_id(), source_with_link.nutty_id());
+		assert_eq!(saved_source.parent_id, source_with_link.parent_id);
+		assert!(matches!(
+			&saved_source.content,
+			BlockContent::Paragraph { markdown } if markdown == &format!("This links to [[{}]]", saved_target.nutty_id().nid())
+		));
+
+		// Assert: A link was created between the blocks.
+		let links = service
+			.repository
+			.get_content_links_from(saved_source.nutty_id())
+			.await
+			.expect("Failed to get links from source block");
+
+		assert_eq!(links.len(), 1);
+		assert_eq!(links[0].source_id, *saved_source.nutty_id());
+		assert_eq!(links[0].target_id, *saved_target.nutty_id());
+
+		// Act: Update the source block to remove the link.
+		let updated_source = ContentBlock::now(
+			None,
+			FractionalIndex::start(),
+			BlockContent::Paragraph {
+				markdown: "No more links".to_string(),
+			},
+		);
+
+		// Act: Save the updated source block.
+		let saved_updated = service
+			.save_content_block(None, updated_source.clone())
+			.await
+			.expect("Failed to save updated source block");
+
+		// Assert: The orphaned link was deleted.
+		let links = service
+			.repository
+			.get_content_links_from(saved_updated.nutty_id())
+			.await
+			.expect("Failed to get links from updated source block");
+
+		assert!(links.is_empty());
+	}
+
+	#[tokio::test]
+	async fn test_get_content_block_context_query() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		setup_test_data(&pool).await;
+
+		// Arrange: Create an admin who can read every query result.
+		let admin_id = NuttyId::now();
+		let admin_name = format!("test_navigator_{}", admin_id.nid());
+
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+				VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+			"#,
+			admin_id.uuid(),
+			admin_id.nid(),
+			admin_name,
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to create test navigator");
+
+		service
+			.access_service
+			.grant_global_role(&admin_id, "admin")
+			.await
+			.expect("Failed to grant global role");
+
+		// Arrange: Create a tag page and blocks that may or may not match.
+		let tag_page = service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					None,
+					FractionalIndex::start(),
+					BlockContent::Page {
+						title: "Tag Page".to_string(),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save tag page");
+
+		let nid = tag_page.nutty_id().nid();
+
+		let tagged_paragraph = service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					None,
+					FractionalIndex::start(),
+					BlockContent::Paragraph {
+						markdown: format!("Tagged with [[{nid}]]"),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save tagged paragraph");
+
+		service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					None,
+					FractionalIndex::start(),
+					BlockContent::Heading {
+						markdown: format!("Also tagged with [[{nid}]]"),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save tagged heading");
+
+		service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					None,
+					FractionalIndex::start(),
+					BlockContent::Paragraph {
+						markdown: "Not tagged".to_string(),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save untagged paragraph");
+
+		// Arrange: Create a query block.
+		let query_block = service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					None,
+					FractionalIndex::start(),
+					BlockContent::Query {
+						dsl: format!("kind:paragraph tag:{nid} created>=2000-01-01"),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save query block");
+
+		// Act: Get the context for the query block.
+		let context = service
+			.get_content_block_context(
+				&admin_id,
+				&query_block.nutty_id().into(),
+				&ChildrenView::default(),
+			)
+			.await
+			.expect("Failed to get content context");
+
+		// Assert: Only the tagged paragraph matches.
+		assert_eq!(context.query_result_ids(), &[*tagged_paragraph.nutty_id()]);
+
+		// Act: Try to save a query block with an invalid query.
+		let result = service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					None,
+					FractionalIndex::start(),
+					BlockContent::Query {
+						dsl: "kind:table".to_string(),
+					},
+				),
+			)
+			.await;
+
+		// Assert: The block was rejected.
+		assert!(matches!(
+			result,
+			Err(ContentServiceError::ParseBlockQuery(_))
+		));
+	}
+
+	#[tokio::test]
+	async fn test_get_content_block_context_query_access() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		setup_test_data(&pool).await;
+
+		// Arrange: Create two navigators who can read their own blocks.
+		let owner_id = NuttyId::now();
+		let other_id = NuttyId::now();
+
+		for navigator_id in [&owner_id, &other_id] {
+			let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				navigator_id.uuid(),
+				navigator_id.nid(),
+				navigator_name,
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+
+			service
+				.access_service
+				.grant_global_role(navigator_id, "block_owner")
+				.await
+				.expect("Failed to grant global role");
+		}
+
+		// Arrange: Create a tag page, and a tagged paragraph for each navigator.
+		let save = |owner_id: NuttyId, content: BlockContent| {
+			let service = &service;
+
+			async move {
+				let block =
+					ContentBlock::now_with_owner(None, owner_id, FractionalIndex::start(), content);
+
+				service
+					.save_content_block(None, block)
+					.await
+					.expect("Failed to save block")
+			}
+		};
+
+		let tag_page = save(
+			owner_id,
+			BlockContent::Page {
+				title: "Tag Page".to_string(),
+			},
+		)
+		.await;
+
+		let nid = tag_page.nutty_id().nid();
+
+		let own_paragraph = save(
+			owner_id,
+			BlockContent::Paragraph {
+				markdown: format!("Mine, tagged with [[{nid}]]"),
+			},
+		)
+		.await;
+
+		let private_paragraph = save(
+			other_id,
+			BlockContent::Paragraph {
+				markdown: format!("Private, tagged with [[{nid}]]"),
+			},
+		)
+		.await;
+
+		// Arrange: Query only the most recent paragraph.
+		let query_block = save(
+			owner_id,
+			BlockContent::Query {
+				dsl: format!("kind:paragraph tag:{nid} limit:1"),
+			},
+		)
+		.await;
+
+		// Act: Get the context for the query block, for each navigator.
+		let context = |navigator_id: NuttyId| {
+			let service = &service;
+			let query_id = query_block.nutty_id().dissociate();
+
+			async move {
+				service
+					.get_content_block_context(&navigator_id, &query_id, &ChildrenView::default())
+					.await
+					.expect("Failed to get content context")
+			}
+		};
+
+		// Assert: The other navigator's newer private paragraph is filtered out
+		// before the limit, so it doesn't take the only result.
+		assert_eq!(
+			context(owner_id).await.query_result_ids(),
+			&[*own_paragraph.nutty_id()]
+		);
+
+		// Assert: The other navigator can read only their own paragraph.
+		assert_eq!(
+			context(other_id).await.query_result_ids(),
+			&[*private_paragraph.nutty_id()]
+		);
+
+		// Cleanup: Delete the test data.
+		for block in [query_block, private_paragraph, own_paragraph, tag_page] {
+			service
+				.repository
+				.delete_content_block(&block.nutty_id().dissociate())
+				.await
+				.expect("Failed to delete block");
+		}
+
+		for navigator_id in [&owner_id, &other_id] {
+			sqlx::query!(
+				r#"DELETE FROM auth.navigators WHERE id = $1"#,
+				navigator_id.uuid()
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to cleanup test navigator");
+		}
+	}
+
+	#[tokio::test]
+	async fn test_save_content_block_properties() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		setup_test_data(&pool).await;
+
+		// Arrange: Create an admin who can read every query result.
+		let admin_id = NuttyId::now();
+		let admin_name = format!("test_navigator_{}", admin_id.nid());
+
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+				VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+			"#,
+			admin_id.uuid(),
+			admin_id.nid(),
+			admin_name,
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to create test navigator");
+
+		service
+			.access_service
+			.grant_global_role(&admin_id, "admin")
+			.await
+			.expect("Failed to grant global role");
+
+		// Arrange: Define a select property; definitions are global, so the
+		// name is unique to this test run.
+		let name = format!("status_{}", NuttyId::now().uuid().simple());
+
+		service
+			.create_property_definition(
+				PropertyDefinition::new(
+					&name,
+					PropertyKind::Select,
+					vec!["open".to_string(), "done".to_string()],
+				)
+				.unwrap(),
+			)
+			.await
+			.expect("Failed to define property");
+
+		let properties = |status: &str| -> BlockProperties {
+			BlockProperties::from([(name.clone(), serde_json::Value::from(status))])
+		};
+
+		let open_block = service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					None,
+					FractionalIndex::start(),
+					BlockContent::Paragraph {
+						markdown: "Open".to_string(),
+					},
+				)
+				.with_properties(properties("open")),
+			)
+			.await
+			.expect("Failed to save open block");
+
+		service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					None,
+					FractionalIndex::start(),
+					BlockContent::Paragraph {
+						markdown: "Done".to_string(),
+					},
+				)
+				.with_properties(properties("done")),
+			)
+			.await
+			.expect("Failed to save done block");
+
+		// Assert: Properties round-trip through the repository.
+		let saved = service
+			.repository
+			.get_content_block(&open_block.nutty_id().into())
+			.await
+			.unwrap()
+			.unwrap();
+
+		assert_eq!(saved.properties, properties("open"));
+
+		// Act: Query blocks by property.
+		let query_block = service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					None,
+					FractionalIndex::start(),
+					BlockContent::Query {
+						dsl: format!("prop:{name}=open"),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save query block");
+
+		let context = service
+			.get_content_block_context(
+				&admin_id,
+				&query_block.nutty_id().into(),
+				&ChildrenView::default(),
+			)
+			.await
+			.expect("Failed to get content context");
+
+		// Assert: Only the open block matches.
+		assert_eq!(context.query_result_ids(), &[*open_block.nutty_id()]);
+
+		// Act: Try to save a block with an invalid option.
+		let result = service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					None,
+					FractionalIndex::start(),
+					BlockContent::Paragraph {
+						markdown: "Blocked".to_string(),
+					},
+				)
+				.with_properties(properties("blocked")),
+			)
+			.await;
+
+		// Assert: The block was rejected.
+		assert!(matches!(
+			result,
+			Err(ContentServiceError::InvalidProperties(
+				PropertyError::InvalidValue(_)
+			))
+		));
+
+		// Act: Try to define the property again.
+		let result = service
+			.create_property_definition(
+				PropertyDefinition::new(&name, PropertyKind::Text, vec![]).unwrap(),
+			)
+			.await;
+
+		// Assert: The name is taken.
+		assert!(matches!(
+			result,
+			Err(ContentServiceError::SavePropertyDefinition(
+				ContentRepositoryError::PropertyNameTaken
+			))
+		));
+	}
+
+	#[tokio::test]
+	async fn test_get_content_block_context_children_view() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		// Arrange: A navigator to read as, who can't read any query results.
+		let navigator_id = NuttyId::now();
+
+		// Arrange: Define a date property, unique to this test run.
+		let due = format!("due_{}", NuttyId::now().uuid().simple());
+
+		service
+			.create_property_definition(
+				PropertyDefinition::new(&due, PropertyKind::Date, vec![]).unwrap(),
+			)
+			.await
+			.expect("Failed to define property");
+
+		// Arrange: Create a parent with children in fractional index order.
+		let parent = service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					None,
+					FractionalIndex::start(),
+					BlockContent::Page {
+						title: "Table".to_string(),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save parent");
+
+		let mut f_index = FractionalIndex::start();
+		let mut children = vec![];
+
+		for date in [Some("2025-09-01"), None, Some("2025-08-01")] {
+			let properties = date
+				.map(|date| BlockProperties::from([(due.clone(), serde_json::Value::from(date))]))
+				.unwrap_or_default();
+
+			let child = service
+				.save_content_block(
+					None,
+					ContentBlock::now(
+						Some(*parent.nutty_id()),
+						f_index.clone(),
+						BlockContent::Paragraph {
+							markdown: "Row".to_string(),
+						},
+					)
+					.with_properties(properties),
+				)
+				.await
+				.expect("Failed to save child");
+
+			f_index = FractionalIndex::between(&f_index, &FractionalIndex::end()).unwrap();
+			children.push(*child.nutty_id());
+		}
+
+		let context = |view: ChildrenView| {
+			let service = service.clone();
+			let parent_id = parent.nutty_id().dissociate();
+
+			async move {
+				service
+					.get_content_block_context(&navigator_id, &parent_id, &view)
+					.await
+					.expect("Failed to get content context")
+			}
+		};
+
+		// Assert: Sorting puts undated children last.
+		let view = ChildrenView::parse(Some(&format!("properties.{due}")), None).unwrap();
+
+		assert_eq!(
+			context(view).await.children_ids(),
+			&[children[2], children[0], children[1]]
+		);
+
+		// Assert: Filtering keeps only matching children.
+		let view =
+			ChildrenView::parse(None, Some(&format!("properties.{due}:lt:2025-08-15"))).unwrap();
+
+		assert_eq!(context(view).await.children_ids(), &[children[2]]);
+	}
+
+	#[tokio::test]
+	async fn test_transfer_ownership() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		// Arrange: Create an admin, the current owner, and the new owner.
+		let admin_id = NuttyId::now();
+		let owner_id = NuttyId::now();
+		let new_owner_id = NuttyId::now();
+
+		for navigator_id in [&admin_id, &owner_id, &new_owner_id] {
+			let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				navigator_id.uuid(),
+				navigator_id.nid(),
+				navigator_name,
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+		}
+
+		service
+			.access_service
+			.grant_global_role(&admin_id, "admin")
+			.await
+			.expect("Failed to grant global role");
+
+		// Arrange: Create an owned page with a child, and a role on the child.
+		let parent = service
+			.save_content_block(
+				None,
+				ContentBlock::now_with_owner(
+					None,
+					owner_id,
+					FractionalIndex::start(),
+					BlockContent::Page {
+						title: "Handover".to_string(),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save parent");
+
+		let child = service
+			.save_content_block(
+				None,
+				ContentBlock::now_with_owner(
+					Some(*parent.nutty_id()),
+					owner_id,
+					FractionalIndex::start(),
+					BlockContent::Paragraph {
+						markdown: "Notes".to_string(),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save child");
+
+		service
+			.access_service
+			.grant_resource_role(
+				&owner_id,
+				"admin",
+				ResourceKind::ContentBlock,
+				child.nutty_id(),
+			)
+			.await
+			.expect("Failed to grant resource role");
+
+		// Act: Try to transfer without permission.
+		let result = service
+			.transfer_ownership(
+				&owner_id,
+				&parent.nutty_id().dissociate(),
+				&new_owner_id,
+				true,
+			)
+			.await;
+
+		// Assert: The transfer was denied.
+		assert!(matches!(result, Err(ContentServiceError::TransferDenied)));
+
+		// Act: Transfer the subtree as the admin.
+		let transfers = service
+			.transfer_ownership(
+				&admin_id,
+				&parent.nutty_id().dissociate(),
+				&new_owner_id,
+				true,
+			)
+			.await
+			.expect("Failed to transfer ownership");
+
+		// Assert: Both blocks changed hands.
+		assert_eq!(transfers.len(), 2);
+
+		for block in [&parent, &child] {
+			let block = service
+				.repository
+				.get_content_block(&block.nutty_id().dissociate())
+				.await
+				.unwrap()
+				.unwrap();
+
+			assert_eq!(block.owner_id, Some(new_owner_id));
+		}
+
+		// Assert: The owner's role on the child moved to the new owner.
+		let grants = service
+			.access_service
+			.get_resource_grants(ResourceKind::ContentBlock, child.nutty_id())
+			.await
+			.expect("Failed to get grants");
+
+		assert!(
+			grants
+				.iter()
+				.filter(|grant| !grant.is_inherited())
+				.all(|grant| grant.role().navigator_id() == Some(&new_owner_id))
+		);
+
+		// Assert: The transfers were recorded.
+		let recorded = sqlx::query_scalar!(
+			r#"
+				SELECT COUNT(*) AS "count!"
+				FROM content.ownership_transfers
+				WHERE block_id = ANY($1) AND transferred_by = $2
+			"#,
+			&[*parent.nutty_id().uuid(), *child.nutty_id().uuid()],
+			admin_id.uuid(),
+		)
+		.fetch_one(&pool)
+		.await
+		.expect("Failed to count transfers");
+
+		assert_eq!(recorded, 2);
+
+		// Act: Transfer to the same owner again.
+		let transfers = service
+			.transfer_ownership(
+				&admin_id,
+				&parent.nutty_id().dissociate(),
+				&new_owner_id,
+				true,
+			)
+			.await
+			.expect("Failed to transfer ownership");
+
+		// Assert: Nothing changed hands.
+		assert!(transfers.is_empty());
+	}
+
+	#[tokio::test]
+	async fn test_raw_content() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		// Arrange: Create an admin and an owner.
+		let admin_id = NuttyId::now();
+		let owner_id = NuttyId::now();
+
+		for navigator_id in [&admin_id, &owner_id] {
+			let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				navigator_id.uuid(),
+				navigator_id.nid(),
+				navigator_name,
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+		}
+
+		service
+			.access_service
+			.grant_global_role(&admin_id, "admin")
+			.await
+			.expect("Failed to grant global role");
+
+		let block = service
+			.save_content_block(
+				None,
+				ContentBlock::now_with_owner(
+					None,
+					owner_id,
+					FractionalIndex::start(),
+					BlockContent::Paragraph {
+						markdown: "Before".to_string(),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save block");
+
+		let block_id = block.nutty_id().dissociate();
+
+		// Assert: Only navigators with the permission see raw content.
+		assert!(matches!(
+			service.get_raw_content(&owner_id, &block_id).await,
+			Err(ContentServiceError::RawContentDenied)
+		));
+
+		let raw = service
+			.get_raw_content(&admin_id, &block_id)
+			.await
+			.expect("Failed to get raw content");
+
+		assert_eq!(raw, serde_json::to_value(&block.content).unwrap());
+
+		// Assert: Content that doesn't fit the schema isn't saved.
+		let invalid = serde_json::json!({ "kind": "Paragraph", "markdown": 42 });
+
+		assert!(matches!(
+			service
+				.save_raw_content(&admin_id, &block_id, invalid)
+				.await,
+			Err(ContentServiceError::InvalidContent(_))
+		));
+
+		// Act: Save new raw content.
+		let content = BlockContent::Paragraph {
+			markdown: "After".to_string(),
+		};
+
+		let saved = service
+			.save_raw_content(
+				&admin_id,
+				&block_id,
+				serde_json::to_value(&content).unwrap(),
+			)
+			.await
+			.expect("Failed to save raw content");
+
+		// Assert: The block was saved, with a revision by the admin.
+		assert_eq!(saved, serde_json::to_value(&content).unwrap());
+
+		let block = service
+			.get_content_block(&block_id)
+			.await
+			.expect("Failed to get block");
+
+		assert_eq!(block.content, content);
+
+		let revised_by = sqlx::query_scalar!(
+			"SELECT revised_by FROM content.block_revisions WHERE block_id = $1",
+			block.nutty_id().uuid(),
+		)
+		.fetch_all(&pool)
+		.await
+		.expect("Failed to list revisions");
+
+		assert_eq!(revised_by, vec![Some(*admin_id.uuid())]);
+	}
+
+	#[tokio::test]
+	async fn test_share_content_block() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		// Arrange: Create an owner, a recipient, and a stranger.
+		let owner_id = NuttyId::now();
+		let recipient_id = NuttyId::now();
+		let stranger_id = NuttyId::now();
+
+		for navigator_id in [&owner_id, &recipient_id, &stranger_id] {
+			let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				navigator_id.uuid(),
+				navigator_id.nid(),
+				navigator_name,
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+		}
+
+		let page = service
+			.save_content_block(
+				None,
+				ContentBlock::now_with_owner(
+					None,
+					owner_id,
+					FractionalIndex::start(),
+					BlockContent::Page {
+						title: "Shared".to_string(),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save page");
+
+		let block_id = page.nutty_id().dissociate();
+
+		// Act: Try to share as a stranger.
+		let result = service
+			.share_content_block(&stranger_id, &block_id, &stranger_id, ShareLevel::Edit)
+			.await;
+
+		// Assert: The share was denied.
+		assert!(matches!(result, Err(ContentServiceError::ShareDenied)));
+
+		// Act: Share for viewing as the owner.
+		service
+			.share_content_block(&owner_id, &block_id, &recipient_id, ShareLevel::View)
+			.await
+			.expect("Failed to share content block");
+
+		// Assert: The recipient can read, but not write.
+		assert!(
+			service
+				.check_content_block_access(&recipient_id, &block_id)
+				.await
+				.unwrap()
+		);
+
+		assert!(
+			!service
+				.check_content_block_write_access(&recipient_id, &block_id)
+				.await
+				.unwrap()
+		);
+
+		assert!(
+			!service
+				.check_content_block_comment_access(&recipient_id, &block_id)
+				.await
+				.unwrap()
+		);
+
+		// Act: Share again for commenting.
+		service
+			.share_content_block(&owner_id, &block_id, &recipient_id, ShareLevel::Comment)
+			.await
+			.expect("Failed to share content block");
+
+		// Assert: The recipient can comment, but still not write.
+		assert!(
+			service
+				.check_content_block_comment_access(&recipient_id, &block_id)
+				.await
+				.unwrap()
+		);
+
+		assert!(
+			!service
+				.check_content_block_write_access(&recipient_id, &block_id)
+				.await
+				.unwrap()
+		);
+
+		// Act: Share again for editing.
+		let grants = service
+			.share_content_block(&owner_id, &block_id, &recipient_id, ShareLevel::Edit)
+			.await
+			.expect("Failed to share content block");
+
+		// Assert: The new level replaced the old one.
+		let recipient_grants: Vec<_> = grants
+			.iter()
+			.filter(|grant| grant.role().navigator_id() == Some(&recipient_id))
+			.collect();
+
+		assert_eq!(recipient_grants.len(), 1);
+		assert_eq!(recipient_grants[0].role().role_name(), "block_editor");
+
+		assert!(
+			service
+				.check_content_block_write_access(&recipient_id, &block_id)
+				.await
+				.unwrap()
+		);
+
+		// Assert: Editors can comment, too, but strangers can't.
+		assert!(
+			service
+				.check_content_block_comment_access(&recipient_id, &block_id)
+				.await
+				.unwrap()
+		);
+
+		assert!(
+			!service
+				.check_content_block_comment_access(&stranger_id, &block_id)
+				.await
+				.unwrap()
+		);
+
+		// Act: Block the recipient and the stranger as the owner.
+		for navigator_id in [&recipient_id, &stranger_id] {
+			sqlx::query("INSERT INTO auth.navigator_blocks (blocker_id, blocked_id) VALUES ($1, $2)")
+				.bind(owner_id.uuid())
+				.bind(navigator_id.uuid())
+				.execute(&pool)
+				.await
+				.expect("Failed to block navigator");
+		}
+
+		// Assert: Blocked navigators can't comment, even as editors, nor
+		// request access.
+		assert!(
+			!service
+				.check_content_block_comment_access(&recipient_id, &block_id)
+				.await
+				.unwrap()
+		);
+
+		let result = service
+			.request_access(&stranger_id, &block_id, ShareLevel::View)
+			.await;
+
+		assert!(matches!(result, Err(ContentServiceError::BlockedByOwner)));
+	}
+
+	#[tokio::test]
+	async fn test_access_requests() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		// Arrange: Create a manager, a requester, and a stranger.
+		let manager_id = NuttyId::now();
+		let requester_id = NuttyId::now();
+		let stranger_id = NuttyId::now();
+
+		for navigator_id in [&manager_id, &requester_id, &stranger_id] {
+			let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				navigator_id.uuid(),
+				navigator_id.nid(),
+				navigator_name,
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+		}
+
+		service
+			.access_service
+			.grant_global_role(&manager_id, "admin")
+			.await
+			.expect("Failed to grant global role");
+
+		let page = service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					None,
+					FractionalIndex::start(),
+					BlockContent::Page {
+						title: "Requested".to_string(),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save page");
+
+		let block_id = page.nutty_id().dissociate();
+
+		// Act: Request access twice.
+		let request = service
+			.request_access(&requester_id, &block_id, ShareLevel::Comment)
+			.await
+			.expect("Failed to request access");
+
+		let duplicate = service
+			.request_access(&requester_id, &block_id, ShareLevel::Edit)
+			.await;
+
+		// Assert: Only one request is pending.
+		assert!(matches!(
+			duplicate,
+			Err(ContentServiceError::SaveAccessRequest(
+				ContentRepositoryError::AccessRequestPending
+			))
+		));
+
+		let pending = service.list_access_requests(&block_id).await.unwrap();
+		assert_eq!(pending.len(), 1);
+		assert_eq!(pending[0].level(), ShareLevel::Comment);
+
+		// Act: Try to approve as a stranger.
+		let result = service
+			.resolve_access_request(&stranger_id, request.nutty_id(), true)
+			.await;
+
+		// Assert: The stranger cannot resolve it.
+		assert!(matches!(result, Err(ContentServiceError::ManageDenied)));
+
+		// Act: Approve as the manager.
+		let approved = service
+			.resolve_access_request(&manager_id, request.nutty_id(), true)
+			.await
+			.expect("Failed to approve access request");
+
+		// Assert: The request is resolved, and the level was granted.
+		assert_eq!(approved.status(), AccessRequestStatus::Approved);
+		assert_eq!(approved.resolved_by(), Some(&manager_id));
+
+		assert!(
+			service
+				.check_content_block_access(&requester_id, &block_id)
+				.await
+				.unwrap()
+		);
+
+		assert!(
+			service
+				.list_access_requests(&block_id)
+				.await
+				.unwrap()
+				.is_empty()
+		);
+
+		// Act: Deny a new request from the stranger.
+		let request = service
+			.request_access(&stranger_id, &block_id, ShareLevel::View)
+			.await
+			.expect("Failed to request access");
+
+		let denied = service
+			.resolve_access_request(&manager_id, request.nutty_id(), false)
+			.await
+			.expect("Failed to deny access request");
+
+		// Assert: Nothing was granted, and the request cannot be resolved again.
+		assert_eq!(denied.status(), AccessRequestStatus::Denied);
+
+		assert!(
+			!service
+				.check_content_block_access(&stranger_id, &block_id)
+				.await
+				.unwrap()
+		);
+
+		assert!(matches!(
+			service
+				.resolve_access_request(&manager_id, request.nutty_id(), true)
+				.await,
+			Err(ContentServiceError::AccessRequestNotFound)
+		));
+	}
+
+	#[tokio::test]
+	async fn test_inherit_access_opt_out() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		setup_test_data(&pool).await;
+
+		// Arrange: Create an owner and a viewer.
+		let owner_id = NuttyId::now();
+		let viewer_id = NuttyId::now();
+
+		for navigator_id in [&owner_id, &viewer_id] {
+			let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				navigator_id.uuid(),
+				navigator_id.nid(),
+				navigator_name,
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+		}
+
+		service
+			.access_service
+			.grant_global_role(&owner_id, "block_owner")
+			.await
+			.expect("Failed to grant global role");
+
+		// Arrange: Create a shared page with a private note, which has a child.
+		let page = service
+			.save_content_block(
+				None,
+				ContentBlock::now_with_owner(
+					None,
+					owner_id,
+					FractionalIndex::start(),
+					BlockContent::Page {
+						title: "Shared".to_string(),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save page");
+
+		let note = service
+			.save_content_block(
+				None,
+				ContentBlock::now_with_owner(
+					Some(*page.nutty_id()),
+					owner_id,
+					FractionalIndex::start(),
+					BlockContent::Paragraph {
+						markdown: "Private".to_string(),
+					},
+				)
+				.with_inherit_access(false),
+			)
+			.await
+			.expect("Failed to save note");
+
+		let detail = service
+			.save_content_block(
+				None,
+				ContentBlock::now_with_owner(
+					Some(*note.nutty_id()),
+					owner_id,
+					FractionalIndex::start(),
+					BlockContent::Paragraph {
+						markdown: "Detail".to_string(),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save detail");
+
+		assert!(!note.inherit_access);
+
+		service
+			.share_content_block(
+				&owner_id,
+				&page.nutty_id().dissociate(),
+				&viewer_id,
+				ShareLevel::View,
+			)
+			.await
+			.expect("Failed to share page");
+
+		let can_read = async |navigator_id: &NuttyId, block: &ContentBlock| {
+			service
+				.check_content_block_access(navigator_id, &block.nutty_id().dissociate())
+				.await
+				.unwrap()
+		};
+
+		// Assert: The page's grant stops at the private note.
+		assert!(can_read(&viewer_id, &page).await);
+		assert!(!can_read(&viewer_id, &note).await);
+		assert!(!can_read(&viewer_id, &detail).await);
+
+		// Assert: The owner can still read the private note.
+		assert!(can_read(&owner_id, &note).await);
+
+		// Assert: The note lists no grants inherited from the page.
+		let grants = service
+			.get_content_block_grants(&note.nutty_id().dissociate())
+			.await
+			.unwrap();
+
+		assert!(grants.iter().all(|grant| !grant.is_inherited()));
+
+		// Act: Share the private note itself.
+		service
+			.share_content_block(
+				&owner_id,
+				&note.nutty_id().dissociate(),
+				&viewer_id,
+				ShareLevel::View,
+			)
+			.await
+			.expect("Failed to share note");
+
+		// Assert: Its child inherits the note's grant.
+		assert!(can_read(&viewer_id, &note).await);
+		assert!(can_read(&viewer_id, &detail).await);
+	}
+
+	#[tokio::test]
+	async fn test_get_outline() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		setup_test_data(&pool).await;
+
+		// Arrange: Create an owner and a viewer.
+		let owner_id = NuttyId::now();
+		let viewer_id = NuttyId::now();
+
+		for navigator_id in [&owner_id, &viewer_id] {
+			let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				navigator_id.uuid(),
+				navigator_id.nid(),
+				navigator_name,
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+		}
+
+		service
+			.access_service
+			.grant_global_role(&owner_id, "block_owner")
+			.await
+			.expect("Failed to grant global role");
+
+		// Arrange: Create a shared page with an intro, a private note that
+		// has a child, and an outro.
+		let save = async |parent: Option<&ContentBlock>, f_index, content, inherit_access| {
+			service
+				.save_content_block(
+					None,
+					ContentBlock::now_with_owner(
+						parent.map(|parent| *parent.nutty_id()),
+						owner_id,
+						f_index,
+						content,
+					)
+					.with_inherit_access(inherit_access),
+				)
+				.await
+				.expect("Failed to save block")
+		};
+
+		let paragraph = |markdown: &str| BlockContent::Paragraph {
+			markdown: markdown.to_string(),
+		};
+
+		let page = BlockContent::Page {
+			title: "Shared".to_string(),
+		};
+
+		let page = save(None, FractionalIndex::start(), page, true).await;
+		let [intro_index, note_index, outro_index] = FractionalIndex::spread(3).try_into().unwrap();
+		let intro = save(Some(&page), intro_index, paragraph("Intro\nMore"), true).await;
+		let note = save(Some(&page), note_index, paragraph("Private"), false).await;
+		let outro = save(Some(&page), outro_index, paragraph("Outro"), true).await;
+		let detail = save(
+			Some(&note),
+			FractionalIndex::start(),
+			paragraph("Detail"),
+			true,
+		)
+		.await;
+
+		service
+			.share_content_block(
+				&owner_id,
+				&page.nutty_id().dissociate(),
+				&viewer_id,
+				ShareLevel::View,
+			)
+			.await
+			.expect("Failed to share page");
+
+		let outline = async |navigator_id: &NuttyId, after: Option<&ContentBlock>, limit| {
+			let after_id = after.map(|after| after.nutty_id().dissociate());
+
+			service
+				.get_outline(
+					navigator_id,
+					&page.nutty_id().dissociate(),
+					after_id.as_ref(),
+					limit,
+				)
+				.await
+				.expect("Failed to get outline")
+				.into_iter()
+				.map(|entry| (entry.block_id, entry.title, entry.depth, entry.child_count))
+				.collect::<Vec<_>>()
+		};
+
+		// Assert: The owner's outline is depth first, titled by first lines.
+		assert_eq!(
+			outline(&owner_id, None, 100).await,
+			vec![
+				(*page.nutty_id(), "Shared".to_string(), 0, 3),
+				(*intro.nutty_id(), "Intro".to_string(), 1, 0),
+				(*note.nutty_id(), "Private".to_string(), 1, 1),
+				(*detail.nutty_id(), "Detail".to_string(), 2, 0),
+				(*outro.nutty_id(), "Outro".to_string(), 1, 0),
+			]
+		);
+
+		// Assert: The viewer's outline leaves out the private note's subtree.
+		let ids = async |navigator_id, after, limit| {
+			outline(navigator_id, after, limit)
+				.await
+				.into_iter()
+				.map(|(block_id, ..)| block_id)
+				.collect::<Vec<_>>()
+		};
+
+		assert_eq!(
+			ids(&viewer_id, None, 100).await,
+			vec![*page.nutty_id(), *intro.nutty_id(), *outro.nutty_id()]
+		);
+
+		// Assert: The root's children are paged through, limited per level.
+		assert_eq!(
+			ids(&owner_id, None, 1).await,
+			vec![*page.nutty_id(), *intro.nutty_id()]
+		);
+		assert_eq!(
+			ids(&owner_id, Some(&intro), 1).await,
+			vec![*page.nutty_id(), *note.nutty_id(), *detail.nutty_id()]
+		);
+	}
+
+	#[tokio::test]
+	async fn test_get_content_block_context_as_of() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		let paragraph = |markdown: &str| BlockContent::Paragraph {
+			markdown: markdown.to_string(),
+		};
+
+		// Arrange: Create a page with two paragraphs.
+		let page = ContentBlock::now(
+			None,
+			FractionalIndex::start(),
+			BlockContent::Page {
+				title: "Then".to_string(),
+			},
+		);
+
+		let [first_index, second_index] = FractionalIndex::spread(2).try_into().unwrap();
+		let first = ContentBlock::now(Some(*page.nutty_id()), first_index, paragraph("First"));
+		let second = ContentBlock::now(Some(*page.nutty_id()), second_index, paragraph("Second"));
+
+		for block in [&page, &first, &second] {
+			service
+				.save_content_block(None, block.clone())
+				.await
+				.expect("Failed to save block");
+		}
+
+		let then = chrono::Utc::now().fixed_offset();
+
+		// Act: Retitle the page, delete a paragraph and add another.
+		let mut retitled = page.clone();
+		retitled.content = BlockContent::Page {
+			title: "Now".to_string(),
+		};
+
+		service
+			.save_content_block(None, retitled)
+			.await
+			.expect("Failed to retitle page");
+
+		service
+			.delete_content_block(&second.nutty_id().dissociate(), false)
+			.await
+			.expect("Failed to delete block");
+
+		let third = ContentBlock::now(
+			Some(*page.nutty_id()),
+			FractionalIndex::end(),
+			paragraph("Third"),
+		);
+
+		service
+			.save_content_block(None, third.clone())
+			.await
+			.expect("Failed to save block");
+
+		// Assert: The page is read as it was, with its children then.
+		let context = service
+			.get_content_block_context_as_of(
+				&page.nutty_id().dissociate(),
+				&then,
+				&ChildrenView::default(),
+			)
+			.await
+			.expect("Failed to get past context");
+
+		assert_eq!(
+			context.children_ids(),
+			[*first.nutty_id(), *second.nutty_id()]
+		);
+		assert_eq!(context.block_cache()[page.nutty_id()].content, page.content);
+		assert_eq!(
+			context.block_cache()[second.nutty_id()].content,
+			second.content
+		);
+
+		// Assert: Children read now include the new paragraph only.
+		let context = service
+			.get_content_block_context_as_of(
+				&page.nutty_id().dissociate(),
+				&chrono::Utc::now().fixed_offset(),
+				&ChildrenView::default(),
+			)
+			.await
+			.expect("Failed to get current context");
+
+		assert_eq!(
+			context.children_ids(),
+			[*first.nutty_id(), *third.nutty_id()]
+		);
+
+		// Assert: Blocks created since aren't found.
+		let result = service
+			.get_content_block_context_as_of(
+				&third.nutty_id().dissociate(),
+				&then,
+				&ChildrenView::default(),
+			)
+			.await;
+
+		assert!(matches!(
+			result,
+			Err(ContentServiceError::ContentBlockNotFound)
+		));
+	}
+
+	#[tokio::test]
+	async fn test_get_content_block_context_partial() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		let page = |title: &str| BlockContent::Page {
+			title: title.to_string(),
+		};
+
+		// Arrange: Create a page within a parent, with a child, linked from
+		// another page.
+		let parent = ContentBlock::now(None, FractionalIndex::start(), page("Parent"));
+		let block = ContentBlock::now(
+			Some(*parent.nutty_id()),
+			FractionalIndex::start(),
+			page("Block"),
+		);
+		let child = ContentBlock::now(
+			Some(*block.nutty_id()),
+			FractionalIndex::start(),
+			page("Child"),
+		);
+		let linking = ContentBlock::now(None, FractionalIndex::start(), page("Linking"));
+
+		for content_block in [&parent, &block, &child, &linking] {
+			service
+				.save_content_block(None, content_block.clone())
+				.await
+				.expect("Failed to save block");
+		}
+
+		service
+			.repository
+			.upsert_content_link(ContentLink::now(*linking.nutty_id(), *block.nutty_id()))
+			.await
+			.expect("Failed to save link");
+
+		let partial = async |include: &str| {
+			service
+				.get_content_block_context_partial(
+					&block.nutty_id().dissociate(),
+					&ContextInclude::parse(Some(include)).unwrap(),
+					&ChildrenView::default(),
+				)
+				.await
+				.expect("Failed to get partial context")
+		};
+
+		// Assert: Only the ancestors are fetched for breadcrumbs.
+		let context = partial("ancestors").await;
+
+		assert_eq!(context.ancestor_ids(), [*parent.nutty_id()]);
+		assert!(context.children_ids().is_empty());
+		assert!(context.backlink_ids().is_empty());
+		assert!(context.block_cache().contains_key(parent.nutty_id()));
+
+		// Assert: Only the children are fetched for the child list.
+		let context = partial("children").await;
+
+		assert!(context.ancestor_ids().is_empty());
+		assert_eq!(context.children_ids(), [*child.nutty_id()]);
+		assert!(context.block_cache().contains_key(child.nutty_id()));
+		assert!(!context.block_cache().contains_key(parent.nutty_id()));
+
+		// Assert: Only the links are fetched for links.
+		let context = partial("links").await;
+
+		assert!(context.children_ids().is_empty());
+		assert_eq!(context.backlink_ids(), [*linking.nutty_id()]);
+		assert_eq!(context.block_cache().len(), 1);
+	}
+
+	#[tokio::test]
+	async fn test_rename_page() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		setup_test_data(&pool).await;
+
+		// Arrange: Create the renamer and another navigator.
+		let owner_id = NuttyId::now();
+		let other_id = NuttyId::now();
+
+		for navigator_id in [&owner_id, &other_id] {
+			let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				navigator_id.uuid(),
+				navigator_id.nid(),
+				navigator_name,
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+
+			service
+				.access_service
+				.grant_global_role(navigator_id, "block_owner")
+				.await
+				.expect("Failed to grant global role");
+		}
+
+		// Arrange: Create a page, with titles unique to this run.
+		let old_title = format!("Old Title {}", owner_id.nid());
+		let new_title = format!("New Title {}", owner_id.nid());
+
+		let save = |owner_id: NuttyId, content: BlockContent| {
+			let service = &service;
+
+			async move {
+				service
+					.save_content_block(
+						None,
+						ContentBlock::now_with_owner(None, owner_id, FractionalIndex::start(), content),
+					)
+					.await
+					.expect("Failed to save block")
+			}
+		};
+
+		let page = save(
+			owner_id,
+			BlockContent::Page {
+				title: old_title.clone(),
+			},
+		)
+		.await;
+
+		let nid = page.nutty_id().nid();
+
+		// Arrange: Create blocks referencing it, by link and by title alone.
+		let referrer = save(
+			owner_id,
+			BlockContent::Paragraph {
+				markdown: format!("See [[{nid}|{old_title}]] or [[{old_title}]]"),
+			},
+		)
+		.await;
+
+		let bystander = save(
+			owner_id,
+			BlockContent::Paragraph {
+				markdown: format!("See [[{nid}|my favorite page]]"),
+			},
+		)
+		.await;
+
+		let title_referrer = save(
+			owner_id,
+			BlockContent::Todo {
+				markdown: format!("Read [[{old_title}]]"),
+				done: false,
+			},
+		)
+		.await;
+
+		// Arrange: Another navigator references it in their own blocks.
+		let other_referrer = save(
+			other_id,
+			BlockContent::Paragraph {
+				markdown: format!("See [[{nid}|{old_title}]] or [[{old_title}]]"),
+			},
+		)
+		.await;
+
+		// Act: Rename the page.
+		let modified_blocks = service
+			.rename_page(&owner_id, &page.nutty_id().into(), new_title.clone())
+			.await
+			.expect("Failed to rename page");
+
+		// Assert: Only the page and the renamer's referrers were modified,
+		// including the one that references the page by title alone.
+		let modified_ids: Vec<_> = modified_blocks
+			.iter()
+			.map(|block| *block.nutty_id())
+			.collect();
+
+		assert_eq!(
+			modified_ids,
+			vec![
+				*page.nutty_id(),
+				*referrer.nutty_id(),
+				*title_referrer.nutty_id()
+			]
+		);
+
+		let get = |block: &ContentBlock| {
+			let id = block.nutty_id().into();
+			let service = &service;
+
+			async move {
+				service
+					.repository
+					.get_content_block(&id)
+					.await
+					.expect("Failed to get block")
+					.expect("Block not found")
+					.content
+			}
+		};
+
+		// Assert: The page has its new title, and the references were rewritten.
+		assert!(matches!(
+			get(&page).await,
+			BlockContent::Page { title } if title == new_title
+		));
+
+		assert!(matches!(
+			get(&referrer).await,
+			BlockContent::Paragraph { markdown } if markdown == format!("See [[{nid}|{new_title}]] or [[{new_title}]]")
+		));
+
+		assert!(matches!(
+			get(&title_referrer).await,
+			BlockContent::Todo { markdown, .. } if markdown == format!("Read [[{new_title}]]")
+		));
+
+		// Assert: The bystander's custom display text was left alone.
+		assert!(matches!(
+			get(&bystander).await,
+			BlockContent::Paragraph { markdown } if markdown == format!("See [[{nid}|my favorite page]]")
+		));
+
+		// Assert: The other navigator's block was left alone.
+		assert!(matches!(
+			get(&other_referrer).await,
+			BlockContent::Paragraph { markdown } if markdown == format!("See [[{nid}|{old_title}]] or [[{old_title}]]")
+		));
+
+		// Arrange: Another page with the same title as the renamed one.
+		let namesake = save(
+			other_id,
+			BlockContent::Page {
+				title: new_title.clone(),
+			},
+		)
+		.await;
+
+		// Act: Rename the page again.
+		let renamed_title = format!("Renamed Title {}", owner_id.nid());
+
+		let modified_blocks = service
+			.rename_page(&owner_id, &page.nutty_id().into(), renamed_title.clone())
+			.await
+			.expect("Failed to rename page");
+
+		// Assert: Tags were rewritten, but title links were left alone,
+		// since they may mean the namesake.
+		assert_eq!(modified_blocks.len(), 2);
+
+		assert!(matches!(
+			get(&referrer).await,
+			BlockContent::Paragraph { markdown } if markdown == format!("See [[{nid}|{renamed_title}]] or [[{new_title}]]")
+		));
+
+		assert!(matches!(
+			get(&title_referrer).await,
+			BlockContent::Todo { markdown, .. } if markdown == format!("Read [[{new_title}]]")
+		));
+
+		// Act: Try to rename a block that isn't a page.
+		let result = service
+			.rename_page(&owner_id, &referrer.nutty_id().into(), "Nope".to_string())
+			.await;
+
+		// Assert: The rename was rejected.
+		assert!(matches!(result, Err(ContentServiceError::NotAPage)));
+
+		// Act: Try to rename the page to a title that would end a link.
+		let result = service
+			.rename_page(&owner_id, &page.nutty_id().into(), "Nope]] [[".to_string())
+			.await;
+
+		// Assert: The rename was rejected.
+		assert!(matches!(result, Err(ContentServiceError::InvalidPageTitle)));
+
+		// Act: Try to rename the page as the other navigator.
+		let result = service
+			.rename_page(&other_id, &page.nutty_id().into(), "Nope".to_string())
+			.await;
+
+		// Assert: The rename was rejected.
+		assert!(matches!(result, Err(ContentServiceError::EditDenied)));
+
+		// Cleanup: Delete the test blocks.
+		for block in [
+			&page,
+			&referrer,
+			&bystander,
+			&title_referrer,
+			&other_referrer,
+			&namesake,
+		] {
+			service
+				.repository
+				.delete_content_block(&block.nutty_id().into())
+				.await
+				.expect("Failed to delete block");
+		}
+	}
+
+	#[tokio::test]
+	async fn test_delete_content_block() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		// Arrange: Create a page with a child that links back to it.
+		let page = service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					None,
+					FractionalIndex::start(),
+					BlockContent::Page {
+						title: "Reading List".to_string(),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save page");
+
+		let page_nid = page.nutty_id().nid();
+
+		let child = service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					Some(*page.nutty_id()),
+					FractionalIndex::start(),
+					BlockContent::Paragraph {
+						markdown: format!("Part of [[{page_nid}]]"),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save child block");
+
+		let child_nid = child.nutty_id().nid();
+
+		// Arrange: Create a block elsewhere that links to both.
+		let referrer = service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					None,
+					FractionalIndex::start(),
+					BlockContent::Paragraph {
+						markdown: format!(
+							"Read [[{page_nid}]], starting at [[{child_nid}|chapter one]]."
+						),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save referrer block");
+
+		// Act: Delete the page, unlinking references to it.
+		let deletion = service
+			.delete_content_block(&page.nutty_id().into(), true)
+			.await
+			.expect("Failed to delete page");
+
+		// Assert: The page and its child were deleted.
+		assert_eq!(deletion.block_id, *page.nutty_id());
+		assert_eq!(
+			deletion.deleted_ids,
+			vec![*page.nutty_id(), *child.nutty_id()]
+		);
+
+		for block in [&page, &child] {
+			let block = service
+				.repository
+				.get_content_block(&block.nutty_id().into())
+				.await
+				.expect("Failed to get block");
+
+			assert!(block.is_none());
+		}
+
+		// Assert: Only links from outside the page were counted.
+		assert_eq!(deletion.inbound_link_count, 2);
+		assert_eq!(deletion.unlinked_ids, vec![*referrer.nutty_id()]);
+
+		// Assert: The referrer's tags were converted to plain text.
+		let referrer = service
+			.repository
+			.get_content_block(&referrer.nutty_id().into())
+			.await
+			.expect("Failed to get referrer block")
+			.expect("Referrer block not found");
+
+		assert!(matches!(
+			&referrer.content,
+			BlockContent::Paragraph { markdown } if markdown == "Read Reading List, starting at chapter one."
+		));
+
+		// Act: Try to delete the page again.
+		let result = service
+			.delete_content_block(&page.nutty_id().into(), false)
+			.await;
+
+		// Assert: The page is gone.
+		assert!(matches!(
+			result,
+			Err(ContentServiceError::ContentBlockNotFound)
+		));
+	}
+
+	#[tokio::test]
+	async fn test_find_and_replace() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		setup_test_data(&pool).await;
+
+		// Arrange: Create an owner, and a navigator who can't write.
+		let owner_id = NuttyId::now();
+		let stranger_id = NuttyId::now();
+
+		for navigator_id in [&owner_id, &stranger_id] {
+			let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				navigator_id.uuid(),
+				navigator_id.nid(),
+				navigator_name,
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+		}
+
+		service
+			.access_service
+			.grant_global_role(&owner_id, "block_owner")
+			.await
+			.expect("Failed to grant global role");
+
+		// Arrange: Create the owner's blocks, and someone else's.
+		let save = |owner_id: Option<NuttyId>, content: BlockContent| {
+			let service = &service;
+
+			async move {
+				let block = match owner_id {
+					Some(owner_id) => {
+						ContentBlock::now_with_owner(None, owner_id, FractionalIndex::start(), content)
+					}
+					None => ContentBlock::now(None, FractionalIndex::start(), content),
+				};
+
+				service
+					.save_content_block(None, block)
+					.await
+					.expect("Failed to save block")
+			}
+		};
+
+		let page = save(
+			Some(owner_id),
+			BlockContent::Page {
+				title: "Project Falcon".to_string(),
+			},
+		)
+		.await;
+
+		let todo = save(
+			Some(owner_id),
+			BlockContent::Todo {
+				markdown: "Ship Project Falcon".to_string(),
+				done: false,
+			},
+		)
+		.await;
+
+		let other = save(
+			None,
+			BlockContent::Paragraph {
+				markdown: "Project Falcon is not mine".to_string(),
+			},
+		)
+		.await;
+
+		// Act: Preview replacing the name.
+		let matches = service
+			.find_and_replace(&owner_id, r"Project (\w+)", "Operation $1", true)
+			.await
+			.expect("Failed to preview find and replace");
+
+		// Assert: Only the owner's markdown matched, and nothing changed.
+		assert_eq!(matches.len(), 1);
+		assert_eq!(matches[0].block_id, *todo.nutty_id());
+		assert_eq!(matches[0].snippet, "Ship Project Falcon");
+		assert_eq!(matches[0].replacement, "Operation Falcon");
+
+		let revision_count = |block_id: NuttyId| {
+			let pool = &pool;
+
+			async move {
+				sqlx::query_scalar!(
+					r#"SELECT COUNT(*) AS "count!" FROM content.block_revisions WHERE block_id = $1"#,
+					block_id.uuid(),
+				)
+				.fetch_one(pool)
+				.await
+				.expect("Failed to count revisions")
+			}
+		};
+
+		assert_eq!(revision_count(*todo.nutty_id()).await, 0);
+
+		// Act: Replace the name.
+		let matches = service
+			.find_and_replace(&owner_id, r"Project (\w+)", "Operation $1", false)
+			.await
+			.expect("Failed to find and replace");
+
+		// Assert: The todo was rewritten, with a revision; the rest were not.
+		assert_eq!(matches.len(), 1);
+
+		let get = |block: &ContentBlock| {
+			let service = &service;
+			let block_id = block.nutty_id().dissociate();
+
+			async move {
+				service
+					.repository
+					.get_content_block(&block_id)
+					.await
+					.expect("Failed to get block")
+					.expect("Block not found")
+			}
+		};
+
+		assert!(matches!(
+			get(&todo).await.content,
+			BlockContent::Todo { markdown, done: false } if markdown == "Ship Operation Falcon"
+		));
+
+		assert!(matches!(
+			get(&page).await.content,
+			BlockContent::Page { title } if title == "Project Falcon"
+		));
+
+		assert!(matches!(
+			get(&other).await.content,
+			BlockContent::Paragraph { markdown } if markdown == "Project Falcon is not mine"
+		));
+
+		assert_eq!(revision_count(*todo.nutty_id()).await, 1);
+
+		// Act & Assert: Invalid patterns and navigators who can't write are rejected.
+		let result = service
+			.find_and_replace(&owner_id, "(Project", "", true)
+			.await;
+
+		assert!(matches!(
+			result,
+			Err(ContentServiceError::InvalidFindReplace(_))
+		));
+
+		let result = service
+			.find_and_replace(&stranger_id, "Project", "Operation", true)
+			.await;
+
+		assert!(matches!(result, Err(ContentServiceError::EditDenied)));
+	}
+
+	#[tokio::test]
+	async fn test_diff_content_block() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		setup_test_data(&pool).await;
+
+		// Arrange: Create an owner with a todo, and revise it twice.
+		let owner_id = NuttyId::now();
+		let navigator_name = format!("test_navigator_{}", owner_id.nid());
+
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+				VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+			"#,
+			owner_id.uuid(),
+			owner_id.nid(),
+			navigator_name,
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to create test navigator");
+
+		service
+			.access_service
+			.grant_global_role(&owner_id, "block_owner")
+			.await
+			.expect("Failed to grant global role");
+
+		let todo = service
+			.save_content_block(
+				None,
+				ContentBlock::now_with_owner(
+					None,
+					owner_id,
+					FractionalIndex::start(),
+					BlockContent::Todo {
+						markdown: "Pack the tent\n\nBuy Falcon snacks".to_string(),
+						done: false,
+					},
+				),
+			)
+			.await
+			.expect("Failed to save block");
+
+		let block_id = todo.nutty_id().dissociate();
+
+		for (pattern, replacement) in [("Falcon", "Eagle"), ("Pack the tent", "Pack the tarp")] {
+			service
+				.find_and_replace(&owner_id, pattern, replacement, false)
+				.await
+				.expect("Failed to find and replace");
+		}
+
+		let revision_ids: Vec<DissociatedNuttyId> = sqlx::query_scalar!(
+			"SELECT nutty_id FROM content.block_revisions WHERE block_id = $1 ORDER BY created_at, id",
+			todo.nutty_id().uuid(),
+		)
+		.fetch_all(&pool)
+		.await
+		.expect("Failed to fetch revisions")
+		.iter()
+		.map(|nid| DissociatedNuttyId::new(nid).unwrap())
+		.collect();
+
+		let paragraphs = |diff: ContentDiff| {
+			diff
+				.paragraphs
+				.into_iter()
+				.filter(|paragraph| !matches!(paragraph, ParagraphDiff::Unchanged { .. }))
+				.collect::<Vec<_>>()
+		};
+
+		// Act & Assert: A revision on its own diffs the change it made.
+		let diff = service
+			.diff_content_block(&block_id, None, Some(&revision_ids[0]))
+			.await
+			.expect("Failed to diff revision");
+
+		assert_eq!(
+			paragraphs(diff),
+			vec![ParagraphDiff::Changed {
+				from: "Buy Falcon snacks".to_string(),
+				to: "Buy Eagle snacks".to_string(),
+			}]
+		);
+
+		// Act & Assert: Diffing from a revision compares with the current content.
+		let diff = service
+			.diff_content_block(&block_id, Some(&revision_ids[0]), None)
+			.await
+			.expect("Failed to diff revision");
+
+		assert_eq!(
+			paragraphs(diff),
+			vec![ParagraphDiff::Changed {
+				from: "Pack the tent".to_string(),
+				to: "Pack the tarp".to_string(),
+			}]
+		);
+
+		// Act & Assert: Revisions of other blocks aren't found.
+		let other = service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					None,
+					FractionalIndex::start(),
+					BlockContent::Paragraph {
+						markdown: "Pack the tarp".to_string(),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save block");
+
+		let result = service
+			.diff_content_block(&other.nutty_id().dissociate(), Some(&revision_ids[0]), None)
+			.await;
+
+		assert!(matches!(result, Err(ContentServiceError::RevisionNotFound)));
+
+		// Act & Assert: Blocks diff against each other's current content.
+		let diff = service
+			.diff_content_blocks(&block_id, &other.nutty_id().dissociate())
+			.await
+			.expect("Failed to diff blocks");
+
+		assert_eq!(
+			diff.kind,
+			Some(Change {
+				from: "Todo".to_string(),
+				to: "Paragraph".to_string(),
+			})
+		);
+		assert_eq!(
+			paragraphs(diff),
+			vec![ParagraphDiff::Removed {
+				text: "Buy Eagle snacks".to_string()
+			}]
+		);
+	}
+
+	#[tokio::test]
+	async fn test_publish_revision() {
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		// Arrange: Create a page, and publish its first draft.
+		let navigator_id = NuttyId::now();
+		let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+				VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+			"#,
+			navigator_id.uuid(),
+			navigator_id.nid(),
+			navigator_name,
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to create test navigator");
+
+		let page = |title: &str| {
+			ContentBlock::now(
+				None,
+				FractionalIndex::start(),
+				BlockContent::Page {
+					title: title.to_string(),
+				},
+			)
+		};
+
+		let draft = service
+			.save_content_block(None, page("First draft"))
+			.await
+			.expect("Failed to save block");
+
+		let block_id = draft.nutty_id().dissociate();
+
+		let first_id = service
+			.publish_revision(&navigator_id, &block_id, None)
+			.await
+			.expect("Failed to publish revision");
+
+		// Act: Keep editing the draft.
+		let mut edited = draft.clone();
+		edited.content = BlockContent::Page {
+			title: "Second draft".to_string(),
+		};
+
+		service
+			.save_content_block(None, edited)
+			.await
+			.expect("Failed to save block");
+
+		// Assert: The first draft stays published.
+		let published = service
+			.get_published_revision(&block_id)
+			.await
+			.expect("Failed to get published revision")
+			.expect("Nothing was published");
+
+		assert_eq!(published.content, draft.content);
+
+		// Act & Assert: Publishing the draft records it as a change from
+		// what was published.
+		let second_id = service
+			.publish_revision(&navigator_id, &block_id, None)
+			.await
+			.expect("Failed to publish revision");
+
+		let published = service
+			.get_published_revision(&block_id)
+			.await
+			.expect("Failed to get published revision")
+			.expect("Nothing was published");
+
+		assert_ne!(first_id, second_id);
+		assert_eq!(published.previous_content, draft.content);
+
+		// Act & Assert: Earlier revisions can be published again.
+		service
+			.publish_revision(&navigator_id, &block_id, Some(&first_id.dissociate()))
+			.await
+			.expect("Failed to publish revision");
+
+		let published = service
+			.get_published_revision(&block_id)
+			.await
+			.expect("Failed to get published revision")
+			.expect("Nothing was published");
+
+		assert_eq!(published.content, draft.content);
+
+		// Act & Assert: Revisions of other blocks can't be published.
+		let other = service
+			.save_content_block(None, page("Other"))
+			.await
+			.expect("Failed to save block");
+
+		let result = service
+			.publish_revision(
+				&navigator_id,
+				&other.nutty_id().dissociate(),
+				Some(&first_id.dissociate()),
+			)
+			.await;
+
+		assert!(matches!(result, Err(ContentServiceError::RevisionNotFound)));
+	}
+
+	#[tokio::test]
+	async fn test_published_revision_read_paths() {
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		// Arrange: Create an admin, and a reader who can read every block and
+		// its raw content, but edit none, not even their own.
+		let admin_id = NuttyId::now();
+		let reader_id = NuttyId::now();
+		let reader_role = format!("test_reader_{}", reader_id.nid());
+
+		for navigator_id in [&admin_id, &reader_id] {
+			let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				navigator_id.uuid(),
+				navigator_id.nid(),
+				navigator_name,
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+		}
+
+		sqlx::query!(
+			"INSERT INTO auth.roles (name, description) VALUES ($1, 'Test reader role')",
+			reader_role,
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to create test role");
+
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.role_permissions (role_name, permission_name)
+				VALUES ($1, 'content_blocks:read:all'), ($1, 'content_blocks:raw')
+			"#,
+			reader_role,
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to grant role permissions");
+
+		service
+			.access_service
+			.grant_global_role(&admin_id, "admin")
+			.await
+			.expect("Failed to grant global role");
+
+		service
+			.access_service
+			.grant_global_role(&reader_id, &reader_role)
+			.await
+			.expect("Failed to grant global role");
+
+		// Arrange: Publish the reader's page, then keep editing a draft.
+		let word = reader_id.nid().to_lowercase();
+		let page = |title: &str| BlockContent::Page {
+			title: format!("{title} {word}"),
+		};
+
+		let published = service
+			.save_content_block(
+				None,
+				ContentBlock::now_with_owner(
+					None,
+					reader_id,
+					FractionalIndex::start(),
+					page("Published"),
+				),
+			)
+			.await
+			.expect("Failed to save block");
+
+		let block_id = published.nutty_id().dissociate();
+
+		service
+			.publish_revision(&admin_id, &block_id, None)
+			.await
+			.expect("Failed to publish revision");
+
+		let mut draft = published.clone();
+		draft.content = page("Unreleased");
+
+		service
+			.save_content_block(None, draft.clone())
+			.await
+			.expect("Failed to save block");
+
+		// Act & Assert: The admin edits, and sees the draft.
+		let localized = service
+			.localize_content_block(&admin_id, &block_id, &[])
+			.await
+			.expect("Failed to localize block");
+
+		assert_eq!(localized.block.content, draft.content);
+
+		// Act & Assert: The reader sees the published page on every read path.
+		let localized = service
+			.localize_content_block(&reader_id, &block_id, &[])
+			.await
+			.expect("Failed to localize block");
+
+		assert_eq!(localized.block.content, published.content);
+
+		let raw = service
+			.get_raw_content(&reader_id, &block_id)
+			.await
+			.expect("Failed to get raw content");
+
+		assert_eq!(raw["title"], format!("Published {word}"));
+
+		let changes = service
+			.list_changes(&reader_id, None)
+			.await
+			.expect("Failed to list changes");
+
+		assert_eq!(changes.blocks.len(), 1);
+		assert_eq!(changes.blocks[0].content, published.content);
+
+		let filter = ContentFilter {
+			kinds: vec![],
+			owner_id: Some(reader_id),
+			include_archived: false,
+		};
+
+		let listed = service
+			.list_content_blocks(&reader_id, &filter)
+			.await
+			.expect("Failed to list blocks");
+
+		assert_eq!(listed.len(), 1);
+		assert_eq!(listed[0].content, published.content);
+
+		let results = service
+			.search_content_blocks(&reader_id, &word, None, false)
+			.await
+			.expect("Failed to search");
+
+		assert_eq!(results.len(), 1);
+		assert_eq!(results[0].content, published.content);
+
+		// Act & Assert: Only what the draft adds finds nothing for the reader.
+		let query = format!("unreleased {word}");
+
+		let results = service
+			.search_content_blocks(&reader_id, &query, None, false)
+			.await
+			.expect("Failed to search");
+
+		assert!(results.is_empty());
+
+		let results = service
+			.search_content_blocks(&admin_id, &query, None, false)
+			.await
+			.expect("Failed to search");
+
+		assert_eq!(results.len(), 1);
+		assert_eq!(results[0].content, draft.content);
+	}
+
+	#[tokio::test]
+	async fn test_set_content_block_slug() {
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		// Arrange: Alice and Bob each have a page.
+		let alice_id = NuttyId::now();
+		let bob_id = NuttyId::now();
+
+		for navigator_id in [alice_id, bob_id] {
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				navigator_id.uuid(),
+				navigator_id.nid(),
+				format!("test_navigator_{}", navigator_id.nid()),
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+		}
+
+		let mut pages = vec![];
+
+		for title in ["Alice", "Bob"] {
+			let page = service
+				.save_content_block(
+					None,
+					ContentBlock::now(
+						None,
+						FractionalIndex::start(),
+						BlockContent::Page {
+							title: title.to_string(),
+						},
+					),
+				)
+				.await
+				.expect("Failed to save block");
+
+			pages.push(page.nutty_id().dissociate());
+		}
+
+		let slug =
+			|name: &str| Slug::parse(&format!("{name}-{}", alice_id.nid().to_lowercase())).unwrap();
+
+		// Act & Assert: Alice gives her page a slug, which leads to it.
+		let alice_page = service
+			.set_content_block_slug(&alice_id, &pages[0], &slug("first"))
+			.await
+			.expect("Failed to set slug");
+
+		let target = service
+			.resolve_permalink(slug("first").as_str())
+			.await
+			.expect("Failed to resolve slug");
+
+		assert_eq!(
+			target,
+			Some(SlugTarget {
+				block_id: alice_page,
+				moved_to: None,
+			})
+		);
+
+		// Act & Assert: Changing the slug redirects the old one.
+		service
+			.set_content_block_slug(&alice_id, &pages[0], &slug("second"))
+			.await
+			.expect("Failed to set slug");
+
+		let target = service
+			.resolve_permalink(slug("first").as_str())
+			.await
+			.expect("Failed to resolve slug");
+
+		assert_eq!(
+			target,
+			Some(SlugTarget {
+				block_id: alice_page,
+				moved_to: Some(slug("second")),
+			})
+		);
+
+		// Assert: Bob can't take Alice's slugs, current or retired.
+		for name in ["first", "second"] {
+			let result = service
+				.set_content_block_slug(&bob_id, &pages[1], &slug(name))
+				.await;
+
+			assert!(matches!(result, Err(ContentServiceError::SlugTaken)));
+		}
+
+		// Act & Assert: Alice can take hers back.
+		service
+			.set_content_block_slug(&alice_id, &pages[0], &slug("first"))
+			.await
+			.expect("Failed to set slug");
+
+		let target = service
+			.resolve_permalink(slug("second").as_str())
+			.await
+			.expect("Failed to resolve slug")
+			.expect("Slug not found");
+
+		assert_eq!(target.moved_to, Some(slug("first")));
+
+		// Assert: NIDs lead to their blocks, and unknown slugs nowhere.
+		let target = service
+			.resolve_permalink(&pages[1].nid())
+			.await
+			.expect("Failed to resolve permalink")
+			.expect("Block not found");
+
+		assert_eq!(target.block_id.dissociate(), pages[1]);
+
+		let target = service
+			.resolve_permalink(slug("unknown").as_str())
+			.await
+			.expect("Failed to resolve slug");
+
+		assert_eq!(target, None);
+	}
+
+	#[tokio::test]
+	async fn test_save_site_settings() {
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		// Arrange: Create a navigator with two pages.
+		let navigator_id = NuttyId::now();
+		let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+				VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+			"#,
+			navigator_id.uuid(),
+			navigator_id.nid(),
+			navigator_name,
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to create test navigator");
+
+		let mut page_ids = vec![];
+
+		for title in ["About", "Blog"] {
+			let page = service
+				.save_content_block(
+					None,
+					ContentBlock::now_with_owner(
+						None,
+						navigator_id,
+						FractionalIndex::start(),
+						BlockContent::Page {
+							title: title.to_string(),
+						},
+					),
+				)
+				.await
+				.expect("Failed to save block");
+
+			page_ids.push(*page.nutty_id());
+		}
+
+		let settings = SiteSettings {
+			title: "Nuttyverse".to_string(),
+			description: "A garden of notes.".to_string(),
+			accent_color: None,
+			navigation_root_ids: vec![page_ids[1], page_ids[0]],
+		};
+
+		// Act & Assert: Settings are saved, and replaced.
+		let saved = service
+			.save_site_settings(&navigator_id, settings.clone())
+			.await
+			.expect("Failed to save site settings");
+
+		assert_eq!(saved, settings);
+
+		let renamed = SiteSettings {
+			title: "Nuttyverse 2".to_string(),
+			..settings.clone()
+		};
+
+		service
+			.save_site_settings(&navigator_id, renamed.clone())
+			.await
+			.expect("Failed to save site settings");
+
+		// Assert: Deleted pages drop out of the navigation, in order.
+		service
+			.delete_content_block(&page_ids[1].dissociate(), false)
+			.await
+			.expect("Failed to delete block");
+
+		let fetched = service
+			.get_site_settings(&navigator_id)
+			.await
+			.expect("Failed to get site settings")
+			.expect("No site settings");
+
+		assert_eq!(fetched.title, renamed.title);
+		assert_eq!(fetched.navigation_root_ids, vec![page_ids[0]]);
+
+		// Assert: Invalid settings aren't saved.
+		let untitled = SiteSettings {
+			title: String::new(),
+			..settings
+		};
+
+		let result = service.save_site_settings(&navigator_id, untitled).await;
+		assert!(matches!(
+			result,
+			Err(ContentServiceError::InvalidSiteSettings(_))
+		));
+
+		// Act & Assert: Settings can be deleted once.
+		for deleted in [true, false] {
+			let result = service
+				.delete_site_settings(&navigator_id)
+				.await
+				.expect("Failed to delete site settings");
+
+			assert_eq!(result, deleted);
+		}
+	}
+
+	#[tokio::test]
+	async fn test_merge_blocks() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo.clone(), access_service);
+
+		setup_test_data(&pool).await;
+
+		let owner_id = NuttyId::now();
+		let navigator_name = format!("test_navigator_{}", owner_id.nid());
+
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+				VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+			"#,
+			owner_id.uuid(),
+			owner_id.nid(),
+			navigator_name,
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to create test navigator");
+
+		service
+			.access_service
+			.grant_global_role(&owner_id, "block_owner")
+			.await
+			.expect("Failed to grant global role");
+
+		let save = |parent_id: Option<NuttyId>, content: BlockContent| {
+			let service = &service;
+
+			async move {
+				let block =
+					ContentBlock::now_with_owner(parent_id, owner_id, FractionalIndex::start(), content);
+
+				service
+					.save_content_block(None, block)
+					.await
+					.expect("Failed to save block")
+			}
+		};
+
+		let paragraph = |markdown: &str| BlockContent::Paragraph {
+			markdown: markdown.to_string(),
+		};
+
+		// Arrange: Create two copies of a note, each with a child, and a
+		// block that tags the source.
+		let source = save(None, paragraph("Intro\n\nMiddle")).await;
+		let target = save(None, paragraph("Intro\n\nMiddle")).await;
+		let source_child = save(Some(*source.nutty_id()), paragraph("Source child")).await;
+		let target_child = save(Some(*target.nutty_id()), paragraph("Target child")).await;
+
+		let referrer = save(
+			None,
+			paragraph(&format!("See [[{}|the note]].", source.nutty_id().nid())),
+		)
+		.await;
+
+		// Arrange: Revise both copies together, then edit them apart.
+		service
+			.find_and_replace(&owner_id, "Middle", "Center", false)
+			.await
+			.expect("Failed to find and replace");
+
+		let base_revision_id: String = sqlx::query_scalar!(
+			"SELECT nutty_id FROM content.block_revisions WHERE block_id = $1",
+			source.nutty_id().uuid(),
+		)
+		.fetch_one(&pool)
+		.await
+		.expect("Failed to fetch revision");
+
+		let base_revision_id = DissociatedNuttyId::new(&base_revision_id).unwrap();
+
+		let mut source = source;
+		source.content = paragraph("Intro, edited offline\n\nCenter");
+		let source = service.save_content_block(None, source).await.unwrap();
+
+		let mut target = target;
+		target.content = paragraph("Intro\n\nCenter\n\nOutro");
+		let target = service.save_content_block(None, target).await.unwrap();
+
+		let source_id = source.nutty_id().dissociate();
+		let target_id = target.nutty_id().dissociate();
+
+		// Act & Assert: A block can't be merged into its own descendants.
+		let result = service
+			.merge_blocks(
+				&source_id,
+				&source_child.nutty_id().dissociate(),
+				&MergeStrategy::Concatenate,
+			)
+			.await;
+
+		assert!(matches!(
+			result,
+			Err(ContentServiceError::InvalidMerge(BlockMergeError::IntoSelf))
+		));
+
+		// Act: Merge the source into the target, from their common revision.
+		let merge = service
+			.merge_blocks(
+				&source_id,
+				&target_id,
+				&MergeStrategy::ThreeWay { base_revision_id },
+			)
+			.await
+			.expect("Failed to merge blocks");
+
+		// Assert: Both copies' edits were kept.
+		assert!(matches!(
+			&merge.block.content,
+			BlockContent::Paragraph { markdown }
+				if markdown == "Intro, edited offline\n\nCenter\n\nOutro"
+		));
+
+		// Assert: The source's child follows the target's.
+		assert_eq!(merge.moved_ids, vec![*source_child.nutty_id()]);
+
+		let child_ids = repo
+			.list_child_ids_tx(&pool, target.nutty_id(), &ChildrenView::default())
+			.await
+			.unwrap();
+
+		assert_eq!(
+			child_ids,
+			vec![*target_child.nutty_id(), *source_child.nutty_id()]
+		);
+
+		// Assert: Tags at the source now point at the target.
+		assert_eq!(merge.relinked_ids, vec![*referrer.nutty_id()]);
+
+		let referrer = repo
+			.get_content_block(&referrer.nutty_id().dissociate())
+			.await
+			.unwrap()
+			.unwrap();
+
+		assert!(matches!(
+			&referrer.content,
+			BlockContent::Paragraph { markdown }
+				if *markdown == format!("See [[{}|the note]].", target_id.nid())
+		));
+
+		let inbound_links = repo.get_content_links_to(target.nutty_id()).await.unwrap();
+		assert_eq!(inbound_links.len(), 1);
+		assert_eq!(inbound_links[0].source_id, *referrer.nutty_id());
+
+		// Assert: The source is gone.
+		assert!(repo.get_content_block(&source_id).await.unwrap().is_none());
+	}
+
+	#[tokio::test]
+	async fn test_split_and_merge_paragraphs() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo.clone(), access_service);
+
+		setup_test_data(&pool).await;
+
+		let owner_id = NuttyId::now();
+		let navigator_name = format!("test_navigator_{}", owner_id.nid());
+
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+				VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+			"#,
+			owner_id.uuid(),
+			owner_id.nid(),
+			navigator_name,
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to create test navigator");
+
+		service
+			.access_service
+			.grant_global_role(&owner_id, "block_owner")
+			.await
+			.expect("Failed to grant global role");
+
+		let save = |parent_id: Option<NuttyId>, f_index: FractionalIndex, content: BlockContent| {
+			let service = &service;
+
+			async move {
+				let block = ContentBlock::now_with_owner(parent_id, owner_id, f_index, content);
+
+				service
+					.save_content_block(None, block)
+					.await
+					.expect("Failed to save block")
+			}
+		};
+
+		let paragraph = |markdown: String| BlockContent::Paragraph { markdown };
+
+		// Arrange: Create a page with a paragraph that tags two pages, and a
+		// paragraph after it.
+		let page = |title: &str| BlockContent::Page {
+			title: title.to_string(),
+		};
+
+		let parent = save(None, FractionalIndex::start(), page("Notes")).await;
+		let first_tagged = save(None, FractionalIndex::start(), page("First")).await;
+		let second_tagged = save(None, FractionalIndex::start(), page("Second")).await;
+
+		let [first_f_index, last_f_index] =
+			["M", "T"].map(|index| FractionalIndex::new(index.to_string()).unwrap());
+		let first_tag = format!("[[{}]]", first_tagged.nutty_id().nid());
+		let second_tag = format!("[[{}]]", second_tagged.nutty_id().nid());
+		let markdown = format!("See {first_tag}. And {second_tag}.");
+
+		let block = save(
+			Some(*parent.nutty_id()),
+			first_f_index,
+			paragraph(markdown.clone()),
+		)
+		.await;
+		let last = save(
+			Some(*parent.nutty_id()),
+			last_f_index,
+			paragraph("Last".to_string()),
+		)
+		.await;
+		let block_id = block.nutty_id().dissociate();
+
+		// Act & Assert: Tags can't be split.
+		let result = service.split_paragraph(&owner_id, &block_id, 6).await;
+
+		assert!(matches!(
+			result,
+			Err(ContentServiceError::InvalidSplit(
+				BlockSplitError::InsideTag(6)
+			))
+		));
+
+		// Act: Split the paragraph between its tags.
+		let offset = format!("See {first_tag}.").chars().count();
+
+		let split = service
+			.split_paragraph(&owner_id, &block_id, offset)
+			.await
+			.expect("Failed to split paragraph");
+
+		// Assert: Each half keeps its own text and links, in order.
+		assert_eq!(split.block.content, paragraph(format!("See {first_tag}.")));
+		assert_eq!(
+			split.new_block.content,
+			paragraph(format!(" And {second_tag}."))
+		);
+
+		let link_targets = |block_id: NuttyId| {
+			let repo = &repo;
+
+			async move {
+				repo
+					.get_content_links_from(&block_id)
+					.await
+					.unwrap()
+					.into_iter()
+					.map(|link| link.target_id)
+					.collect::<Vec<_>>()
+			}
+		};
+
+		assert_eq!(
+			link_targets(*block.nutty_id()).await,
+			vec![*first_tagged.nutty_id()]
+		);
+		assert_eq!(
+			link_targets(*split.new_block.nutty_id()).await,
+			vec![*second_tagged.nutty_id()]
+		);
+
+		let child_ids = repo
+			.list_child_ids_tx(&pool, parent.nutty_id(), &ChildrenView::default())
+			.await
+			.unwrap();
+
+		assert_eq!(
+			child_ids,
+			vec![
+				*block.nutty_id(),
+				*split.new_block.nutty_id(),
+				*last.nutty_id()
+			]
+		);
+
+		// Arrange: Tag the new paragraph.
+		let referrer = save(
+			None,
+			FractionalIndex::start(),
+			paragraph(format!("Cf. [[{}]]", split.new_block.nutty_id().nid())),
+		)
+		.await;
+
+		// Act: Merge the paragraph with the new one again.
+		let merge = service
+			.merge_with_next(&owner_id, &block_id)
+			.await
+			.expect("Failed to merge paragraphs");
+
+		// Assert: The text and links are whole again, and tags follow.
+		assert_eq!(merge.block.content, paragraph(markdown));
+		assert_eq!(merge.source_id, *split.new_block.nutty_id());
+		assert_eq!(merge.relinked_ids, vec![*referrer.nutty_id()]);
+
+		let mut targets = link_targets(*block.nutty_id()).await;
+		targets.sort_by_key(|id| *id.uuid());
+
+		let mut expected = vec![*first_tagged.nutty_id(), *second_tagged.nutty_id()];
+		expected.sort_by_key(|id| *id.uuid());
+
+		assert_eq!(targets, expected);
+		assert_eq!(
+			link_targets(*referrer.nutty_id()).await,
+			vec![*block.nutty_id()]
+		);
+
+		assert!(
+			repo
+				.get_content_block(&split.new_block.nutty_id().dissociate())
+				.await
+				.unwrap()
+				.is_none()
+		);
+
+		// Act & Assert: The last paragraph has nothing to merge with.
+		let result = service
+			.merge_with_next(&owner_id, &last.nutty_id().dissociate())
+			.await;
+
+		assert!(matches!(result, Err(ContentServiceError::NoNextSibling)));
+	}
+
+	#[tokio::test]
+	async fn test_reorder_children() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo.clone(), access_service);
+
+		setup_test_data(&pool).await;
+
+		// Arrange: Create a page with three children, in order.
+		let parent = ContentBlock::now(
+			None,
+			FractionalIndex::start(),
+			BlockContent::Page {
+				title: "Reordered".to_string(),
+			},
+		);
+
+		let parent = service.save_content_block(None, parent).await.unwrap();
+		let mut f_index = FractionalIndex::start();
+		let mut children = vec![];
+
+		for markdown in ["A", "B", "C"] {
+			f_index = FractionalIndex::between(&f_index, &FractionalIndex::end()).unwrap();
+
+			let child = ContentBlock::now(
+				Some(*parent.nutty_id()),
+				f_index.clone(),
+				BlockContent::Paragraph {
+					markdown: markdown.to_string(),
+				},
+			);
+
+			children.push(service.save_content_block(None, child).await.unwrap());
+		}
+
+		let parent_id = parent.nutty_id().dissociate();
+		let reversed: Vec<_> = children
+			.iter()
+			.rev()
+			.map(|child| child.nutty_id().dissociate())
+			.collect();
+
+		// Act & Assert: Every child must be given, once.
+		for child_ids in [&reversed[..2], &[reversed[0], reversed[0], reversed[1]]] {
+			let result = service.reorder_children(&parent_id, child_ids).await;
+			assert!(matches!(result, Err(ContentServiceError::ChildrenMismatch)));
+		}
+
+		// Act: Reverse the children.
+		let reordered = service
+			.reorder_children(&parent_id, &reversed)
+			.await
+			.expect("Failed to reorder children");
+
+		// Assert: The children are listed in their new order.
+		let reordered_ids: Vec<_> = reordered.iter().map(|child| *child.nutty_id()).collect();
+
+		let child_ids = repo
+			.list_child_ids_tx(&pool, parent.nutty_id(), &ChildrenView::default())
+			.await
+			.unwrap();
+
+		assert_eq!(child_ids, reordered_ids);
+		assert_eq!(
+			child_ids,
+			children
+				.iter()
+				.rev()
+				.map(|child| *child.nutty_id())
+				.collect::<Vec<_>>()
+		);
+	}
+
+	#[tokio::test]
+	async fn test_convert_block() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo.clone(), access_service);
+
+		setup_test_data(&pool).await;
+
+		let owner_id = NuttyId::now();
+		let navigator_name = format!("test_navigator_{}", owner_id.nid());
+
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+				VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+			"#,
+			owner_id.uuid(),
+			owner_id.nid(),
+			navigator_name,
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to create test navigator");
+
+		let save = |parent_id: Option<NuttyId>, f_index: FractionalIndex, markdown: String| {
+			let service = &service;
+
+			async move {
+				let content = BlockContent::Paragraph { markdown };
+				let block = ContentBlock::now_with_owner(parent_id, owner_id, f_index, content);
+
+				service
+					.save_content_block(None, block)
+					.await
+					.expect("Failed to save block")
+			}
+		};
+
+		// Arrange: Create a note with a child, and a block that tags it.
+		let middle =
+			FractionalIndex::between(&FractionalIndex::start(), &FractionalIndex::end()).unwrap();
+		let note = save(None, middle.clone(), "# Groceries\nEggs, milk".to_string()).await;
+		let child = save(Some(*note.nutty_id()), middle, "Bread".to_string()).await;
+
+		let referrer = save(
+			None,
+			FractionalIndex::start(),
+			format!("See [[{}|the list]].", note.nutty_id().nid()),
+		)
+		.await;
+
+		let note_id = note.nutty_id().dissociate();
+
+		// Act: Convert the note into a page.
+		let conversion = service
+			.convert_block(&owner_id, &note_id, ConvertibleKind::Page)
+			.await
+			.expect("Failed to convert block");
+
+		// Assert: The page is titled by the first line, and keeps its identity.
+		assert_eq!(conversion.block.nutty_id(), note.nutty_id());
+		assert!(matches!(
+			&conversion.block.content,
+			BlockContent::Page { title } if title == "Groceries"
+		));
+
+		// Assert: The rest of the text comes before the existing children.
+		let overflow = conversion.overflow.expect("Expected an overflow");
+
+		assert!(matches!(
+			&overflow.content,
+			BlockContent::Paragraph { markdown } if markdown == "Eggs, milk"
+		));
+
+		let child_ids = repo
+			.list_child_ids_tx(&pool, note.nutty_id(), &ChildrenView::default())
+			.await
+			.unwrap();
+
+		assert_eq!(child_ids, vec![*overflow.nutty_id(), *child.nutty_id()]);
+
+		// Assert: Backlinks and a revision were kept.
+		let inbound_links = repo.get_content_links_to(note.nutty_id()).await.unwrap();
+		assert_eq!(inbound_links.len(), 1);
+		assert_eq!(inbound_links[0].source_id, *referrer.nutty_id());
+
+		let revision_count = sqlx::query_scalar!(
+			r#"SELECT COUNT(*) AS "count!" FROM content.block_revisions WHERE block_id = $1"#,
+			note.nutty_id().uuid(),
+		)
+		.fetch_one(&pool)
+		.await
+		.unwrap();
+
+		assert_eq!(revision_count, 1);
+
+		// Act & Assert: Pages convert back, but not into pages again.
+		let conversion = service
+			.convert_block(&owner_id, &note_id, ConvertibleKind::Paragraph)
+			.await
+			.expect("Failed to convert block");
+
+		assert!(matches!(
+			&conversion.block.content,
+			BlockContent::Paragraph { markdown } if markdown == "Groceries"
+		));
+
+		let result = service
+			.convert_block(&owner_id, &note_id, ConvertibleKind::Paragraph)
+			.await;
+
+		assert!(matches!(
+			result,
+			Err(ContentServiceError::InvalidConversion(
+				BlockConversionError::Unsupported { .. }
+			))
+		));
+	}
+
+	#[tokio::test]
+	async fn test_search_content_blocks() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		setup_test_data(&pool).await;
+
+		// Arrange: Create an owner, and a navigator who can't read.
+		let owner_id = NuttyId::now();
+		let stranger_id = NuttyId::now();
+
+		for navigator_id in [&owner_id, &stranger_id] {
+			let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				navigator_id.uuid(),
+				navigator_id.nid(),
+				navigator_name,
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+		}
+
+		service
+			.access_service
+			.grant_global_role(&owner_id, "block_owner")
+			.await
+			.expect("Failed to grant global role");
+
+		// Arrange: Create an English paragraph, and a Japanese page whose
+		// paragraph takes its language.
+		let save = |parent_id: Option<NuttyId>, content: BlockContent| {
+			let service = &service;
+
+			async move {
+				let block =
+					ContentBlock::now_with_owner(parent_id, owner_id, FractionalIndex::start(), content);
+
+				service
+					.save_content_block(None, block)
+					.await
+					.expect("Failed to save block")
+			}
+		};
+
+		let english = save(
+			None,
+			BlockContent::Paragraph {
+				markdown: "The runners were running through Kyoto.".to_string(),
+			},
+		)
+		.await;
+
+		let page = save(
+			None,
+			BlockContent::Page {
+				title: "日記".to_string(),
+			},
+		)
+		.await;
+
+		let updated = service
+			.set_content_block_language(
+				&page.nutty_id().dissociate(),
+				Some(SearchLanguage::Japanese),
+			)
+			.await
+			.expect("Failed to set language");
+
+		assert_eq!(updated, 1);
+
+		let japanese = save(
+			Some(*page.nutty_id()),
+			BlockContent::Paragraph {
+				markdown: "京都で会議がありました。".to_string(),
+			},
+		)
+		.await;
+
+		let search = |navigator_id: NuttyId, query: &'static str, language| {
+			let service = &service;
+
+			async move {
+				service
+					.search_content_blocks(&navigator_id, query, language, false)
+					.await
+					.expect("Failed to search")
+					.iter()
+					.map(|block| *block.nutty_id())
+					.collect::<Vec<_>>()
+			}
+		};
+
+		// Act & Assert: English is stemmed.
+		let results = search(owner_id, "run", None).await;
+		assert!(results.contains(english.nutty_id()));
+		assert!(!results.contains(japanese.nutty_id()));
+
+		// Act & Assert: Japanese is matched by substring, in its own language.
+		let results = search(owner_id, "会議", None).await;
+		assert!(results.contains(japanese.nutty_id()));
+
+		let results = search(owner_id, "会議", Some(SearchLanguage::English)).await;
+		assert!(!results.contains(japanese.nutty_id()));
+
+		// Act & Assert: Clearing the language searches the page as English.
+		let updated = service
+			.set_content_block_language(&page.nutty_id().dissociate(), None)
+			.await
+			.expect("Failed to clear language");
+
+		assert_eq!(updated, 2);
+
+		let results = search(owner_id, "会議", Some(SearchLanguage::Japanese)).await;
+		assert!(!results.contains(japanese.nutty_id()));
+
+		// Act & Assert: Blocks the navigator can't read are left out.
+		let results = search(stranger_id, "run", None).await;
+		assert!(!results.contains(english.nutty_id()));
+
+		// Act & Assert: Better matches the navigator can't read don't crowd
+		// out the ones they can.
+		for _ in 0..MAX_SEARCH_RESULTS {
+			service
+				.repository
+				.upsert_content_block(ContentBlock::now_with_owner(
+					None,
+					stranger_id,
+					FractionalIndex::start(),
+					BlockContent::Paragraph {
+						markdown: "Kyoto runners, running in Kyoto.".to_string(),
+					},
+				))
+				.await
+				.expect("Failed to save block");
+		}
+
+		let results = search(owner_id, "kyoto", None).await;
+		assert!(results.contains(english.nutty_id()));
+
+		let result = service
+			.search_content_blocks(&owner_id, "  ", None, false)
+			.await;
+		assert!(matches!(result, Err(ContentServiceError::EmptySearchQuery)));
+	}
+
+	#[tokio::test]
+	async fn test_list_content_blocks() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		setup_test_data(&pool).await;
+
+		// Arrange: Create two owners.
+		let owner_id = NuttyId::now();
+		let other_id = NuttyId::now();
+
+		for navigator_id in [&owner_id, &other_id] {
+			let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				navigator_id.uuid(),
+				navigator_id.nid(),
+				navigator_name,
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+
+			service
+				.access_service
+				.grant_global_role(navigator_id, "block_owner")
+				.await
+				.expect("Failed to grant global role");
+		}
+
+		// Arrange: Give each owner a page and a todo.
+		let mut blocks = vec![];
+
+		for owner_id in [owner_id, other_id] {
+			for content in [
+				BlockContent::Page {
+					title: "Snippets".to_string(),
+				},
+				BlockContent::Todo {
+					markdown: "Tidy up".to_string(),
+					done: false,
+				},
+			] {
+				let block =
+					ContentBlock::now_with_owner(None, owner_id, FractionalIndex::start(), content);
+
+				blocks.push(
+					service
+						.save_content_block(None, block)
+						.await
+						.expect("Failed to save block"),
+				);
+			}
+		}
+
+		let list = |kinds: &[&str], owner: Option<NuttyId>| {
+			let service = &service;
+
+			let filter = ContentFilter {
+				kinds: kinds.iter().map(|kind| kind.to_string()).collect(),
+				owner_id: owner,
+				include_archived: false,
+			};
+
+			async move {
+				service
+					.list_content_blocks(&owner_id, &filter)
+					.await
+					.expect("Failed to list content blocks")
+					.iter()
+					.map(|block| *block.nutty_id())
+					.collect::<Vec<_>>()
+			}
+		};
+
+		// Act & Assert: Only the owner's todo is listed, of its blocks.
+		let results = list(&["Todo"], Some(owner_id)).await;
+		assert_eq!(results, vec![*blocks[1].nutty_id()]);
+
+		// Act & Assert: Blocks of any given kind are listed, newest first.
+		let results = list(&["Page", "Todo"], Some(owner_id)).await;
+		assert_eq!(results, vec![*blocks[1].nutty_id(), *blocks[0].nutty_id()]);
+
+		// Act & Assert: Blocks the navigator can't read are left out.
+		let results = list(&["Page"], None).await;
+		assert!(results.contains(blocks[0].nutty_id()));
+		assert!(!results.contains(blocks[2].nutty_id()));
+
+		// Act & Assert: Newer blocks the navigator can't read don't crowd out
+		// the ones they can.
+		for _ in 0..MAX_LISTED_BLOCKS {
+			service
+				.repository
+				.upsert_content_block(ContentBlock::now_with_owner(
+					None,
+					other_id,
+					FractionalIndex::start(),
+					BlockContent::Page {
+						title: "Snippets".to_string(),
+					},
+				))
+				.await
+				.expect("Failed to save block");
+		}
+
+		let results = list(&["Page"], None).await;
+		assert!(results.contains(blocks[0].nutty_id()));
+	}
+
+	#[tokio::test]
+	async fn test_list_tasks() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		setup_test_data(&pool).await;
+
+		// Arrange: Create two owners.
+		let owner_id = NuttyId::now();
+		let other_id = NuttyId::now();
+
+		for navigator_id in [&owner_id, &other_id] {
+			let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				navigator_id.uuid(),
+				navigator_id.nid(),
+				navigator_name,
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+
+			service
+				.access_service
+				.grant_global_role(navigator_id, "block_owner")
+				.await
+				.expect("Failed to grant global role");
+		}
+
+		// Arrange: Give each owner an open todo under a page.
+		let mut todos = vec![];
+
+		for owner_id in [owner_id, other_id] {
+			let page = service
+				.save_content_block(
+					None,
+					ContentBlock::now_with_owner(
+						None,
+						owner_id,
+						FractionalIndex::start(),
+						BlockContent::Page {
+							title: "Chores".to_string(),
+						},
+					),
+				)
+				.await
+				.expect("Failed to save page");
+
+			todos.push(
+				service
+					.save_content_block(
+						None,
+						ContentBlock::now_with_owner(
+							Some(*page.nutty_id()),
+							owner_id,
+							FractionalIndex::start(),
+							BlockContent::Todo {
+								markdown: "Water the plants".to_string(),
+								done: false,
+							},
+						),
+					)
+					.await
+					.expect("Failed to save todo"),
+			);
+		}
+
+		// Act: List the owner's open tasks.
+		let tasks = service
+			.list_tasks(&owner_id, Some(TaskStatus::Open), None)
+			.await
+			.expect("Failed to list tasks");
+
+		let task_ids = tasks
+			.iter()
+			.map(|task| *task.block.nutty_id())
+			.collect::<Vec<_>>();
+
+		// Assert: Only the owner's todo is listed, with its page as a breadcrumb.
+		assert!(task_ids.contains(todos[0].nutty_id()));
+		assert!(!task_ids.contains(todos[1].nutty_id()));
+
+		let task = tasks
+			.iter()
+			.find(|task| task.block.nutty_id() == todos[0].nutty_id())
+			.unwrap();
+
+		assert_eq!(task.breadcrumbs.len(), 1);
+		assert_eq!(task.breadcrumbs[0].title, "Chores");
+	}
+
+	#[tokio::test]
+	async fn test_set_content_block_archived() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		setup_test_data(&pool).await;
+
+		// Arrange: Create an owner.
+		let owner_id = NuttyId::now();
+		let navigator_name = format!("test_navigator_{}", owner_id.nid());
+
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+				VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+			"#,
+			owner_id.uuid(),
+			owner_id.nid(),
+			navigator_name,
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to create test navigator");
+
+		service
+			.access_service
+			.grant_global_role(&owner_id, "block_owner")
+			.await
+			.expect("Failed to grant global role");
+
+		// Arrange: Create a project under a page of projects, with notes.
+		let save = |parent_id: Option<NuttyId>, content: BlockContent| {
+			let service = &service;
+
+			async move {
+				let block =
+					ContentBlock::now_with_owner(parent_id, owner_id, FractionalIndex::start(), content);
+
+				service
+					.save_content_block(None, block)
+					.await
+					.expect("Failed to save block")
+			}
+		};
+
+		let projects = save(
+			None,
+			BlockContent::Page {
+				title: "Projects".to_string(),
+			},
+		)
+		.await;
+
+		let project = save(
+			Some(*projects.nutty_id()),
+			BlockContent::Page {
+				title: "Birdhouse".to_string(),
+			},
+		)
+		.await;
+
+		let notes = save(
+			Some(*project.nutty_id()),
+			BlockContent::Paragraph {
+				markdown: "Retrospective on the birdhouse".to_string(),
+			},
+		)
+		.await;
+
+		let view = ChildrenView {
+			hide_archived: true,
+			..ChildrenView::default()
+		};
+
+		let children = |block: &ContentBlock| {
+			let service = &service;
+			let block_id = block.nutty_id().dissociate();
+			let view = &view;
+
+			async move {
+				service
+					.get_content_block_context(&owner_id, &block_id, view)
+					.await
+					.expect("Failed to get context")
+					.children_ids()
+					.to_vec()
+			}
+		};
+
+		let search = |include_archived| {
+			let service = &service;
+
+			async move {
+				service
+					.search_content_blocks(&owner_id, "retrospective", None, include_archived)
+					.await
+					.expect("Failed to search")
+					.iter()
+					.map(|block| *block.nutty_id())
+					.collect::<Vec<_>>()
+			}
+		};
+
+		// Act: Archive the project and its notes.
+		let archival = service
+			.set_content_block_archived(&project.nutty_id().dissociate(), true, true)
+			.await
+			.expect("Failed to archive project");
+
+		// Assert: Both blocks were archived, the project first.
+		assert_eq!(
+			archival.updated_ids,
+			vec![*project.nutty_id(), *notes.nutty_id()]
+		);
+
+		// Assert: The project is hidden from its parent, but its own children
+		// are shown while it's archived too.
+		assert!(children(&projects).await.is_empty());
+		assert_eq!(children(&project).await, vec![*notes.nutty_id()]);
+
+		// Assert: The notes are only found when archived blocks are included.
+		assert!(!search(false).await.contains(notes.nutty_id()));
+		assert!(search(true).await.contains(notes.nutty_id()));
+
+		// Assert: The project is only listed when archived blocks are included.
+		let mut filter = ContentFilter {
+			kinds: vec!["Page".to_string()],
+			owner_id: Some(owner_id),
+			include_archived: false,
+		};
+
+		let listed = service
+			.list_content_blocks(&owner_id, &filter)
+			.await
+			.expect("Failed to list content blocks");
+
+		assert_eq!(listed.len(), 1);
+
+		filter.include_archived = true;
+
+		let listed = service
+			.list_content_blocks(&owner_id, &filter)
+			.await
+			.expect("Failed to list content blocks");
+
+		assert_eq!(listed.len(), 2);
+
+		// Act: Unarchive the project alone.
+		let archival = service
+			.set_content_block_archived(&project.nutty_id().dissociate(), false, false)
+			.await
+			.expect("Failed to unarchive project");
+
+		// Assert: The project is back, but its notes are still archived.
+		assert_eq!(archival.updated_ids, vec![*project.nutty_id()]);
+		assert_eq!(children(&projects).await, vec![*project.nutty_id()]);
+		assert!(children(&project).await.is_empty());
+
+		// Assert: Archiving a missing block fails.
+		let result = service
+			.set_content_block_archived(&NuttyId::now().dissociate(), true, false)
+			.await;
+
+		assert!(matches!(
+			result,
+			Err(ContentServiceError::ContentBlockNotFound)
+		));
+	}
+
+	#[tokio::test]
+	async fn test_localize_content_block() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		setup_test_data(&pool).await;
+
+		// Arrange: Create an owner, and a reader of only the English and
+		// French notes.
+		let owner_id = NuttyId::now();
+		let reader_id = NuttyId::now();
+
+		for navigator_id in [&owner_id, &reader_id] {
+			let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				navigator_id.uuid(),
+				navigator_id.nid(),
+				navigator_name,
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+		}
+
+		service
+			.access_service
+			.grant_global_role(&owner_id, "block_owner")
+			.await
+			.expect("Failed to grant global role");
+
+		// Arrange: Create an English note, its Japanese variant, and a block
+		// that links to the variant.
+		let save = |title: &str| {
+			let service = &service;
+
+			let block = ContentBlock::now_with_owner(
+				None,
+				owner_id,
+				FractionalIndex::start(),
+				BlockContent::Page {
+					title: title.to_string(),
+				},
+			);
+
+			async move {
+				service
+					.save_content_block(None, block)
+					.await
+					.expect("Failed to save block")
+			}
+		};
+
+		let english = save("Notes").await;
+		let japanese = save("ノート").await;
+		let french = save("Carnet").await;
+		let linking = save("Index").await;
+
+		service
+			.repository
+			.upsert_content_link(ContentLink::now(*linking.nutty_id(), *japanese.nutty_id()))
+			.await
+			.expect("Failed to save link");
+
+		for block in [&english, &french] {
+			service
+				.grant_share_level(&reader_id, block.nutty_id(), ShareLevel::View)
+				.await
+				.expect("Failed to share note");
+		}
+
+		let lang = |tag: &str| Lang::parse(tag).unwrap();
+
+		// Act: Set the note's language, then translate it.
+		service
+			.set_translation(
+				&owner_id,
+				&english.nutty_id().dissociate(),
+				&lang("en"),
+				None,
+			)
+			.await
+			.expect("Failed to set language");
+
+		for (variant, tag) in [(&japanese, "ja"), (&french, "fr")] {
+			service
+				.set_translation(
+					&owner_id,
+					&variant.nutty_id().dissociate(),
+					&lang(tag),
+					Some(&english.nutty_id().dissociate()),
+				)
+				.await
+				.expect("Failed to translate note");
+		}
+
+		// Assert: The variants are translations of one another, though the
+		// reader only sees those they can read.
+		let translations = |navigator_id| {
+			let service = &service;
+			let english = &english;
+
+			async move {
+				service
+					.list_translations(navigator_id, &english.nutty_id().dissociate())
+					.await
+					.expect("Failed to list translations")
+					.into_iter()
+					.map(|translation| translation.block_id)
+					.collect::<Vec<_>>()
+			}
+		};
+
+		assert_eq!(
+			translations(&owner_id).await,
+			[
+				*english.nutty_id(),
+				*french.nutty_id(),
+				*japanese.nutty_id()
+			]
+		);
+		assert_eq!(
+			translations(&reader_id).await,
+			[*english.nutty_id(), *french.nutty_id()]
+		);
+
+		// Assert: Only blocks with a language can be translated.
+		let result = service
+			.set_translation(
+				&owner_id,
+				&english.nutty_id().dissociate(),
+				&lang("de"),
+				Some(&save("Untranslated").await.nutty_id().dissociate()),
+			)
+			.await;
+
+		assert!(matches!(
+			result,
+			Err(ContentServiceError::UntranslatedBlock)
+		));
+
+		// Act & Assert: The owner gets the Japanese variant, by prefix.
+		let localized = service
+			.localize_content_block(
+				&owner_id,
+				&english.nutty_id().dissociate(),
+				&[lang("ja-JP")],
+			)
+			.await
+			.expect("Failed to localize note");
+
+		assert_eq!(localized.block.nutty_id(), japanese.nutty_id());
+		assert_eq!(localized.lang, Some(lang("ja")));
+
+		// Act & Assert: The reader falls back to the English note.
+		let localized = service
+			.localize_content_block(&reader_id, &english.nutty_id().dissociate(), &[lang("ja")])
+			.await
+			.expect("Failed to localize note");
+
+		assert_eq!(localized.block.nutty_id(), english.nutty_id());
+		assert_eq!(localized.lang, Some(lang("en")));
+		assert_eq!(localized.translations.len(), 2);
+
+		// Act & Assert: The reader gets their next preference they can read.
+		let localized = service
+			.localize_content_block(
+				&reader_id,
+				&english.nutty_id().dissociate(),
+				&[lang("ja"), lang("fr")],
+			)
+			.await
+			.expect("Failed to localize note");
+
+		assert_eq!(localized.block.nutty_id(), french.nutty_id());
+		assert_eq!(localized.lang, Some(lang("fr")));
+
+		// Act: Get the English note's context.
+		let context = service
+			.get_content_block_context(
+				&owner_id,
+				&english.nutty_id().dissociate(),
+				&ChildrenView::default(),
+			)
+			.await
+			.expect("Failed to get context");
+
+		// Assert: Links to the Japanese variant are backlinks to the note.
+		assert_eq!(context.backlink_ids(), [*linking.nutty_id()]);
+	}
+
+	#[tokio::test]
+	async fn test_list_linked_references() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		setup_test_data(&pool).await;
+
+		// Arrange: Create an owner, and a reader of only some blocks.
+		let owner_id = NuttyId::now();
+		let reader_id = NuttyId::now();
+
+		for navigator_id in [&owner_id, &reader_id] {
+			let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				navigator_id.uuid(),
+				navigator_id.nid(),
+				navigator_name,
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+		}
+
+		service
+			.access_service
+			.grant_global_role(&owner_id, "block_owner")
+			.await
+			.expect("Failed to grant global role");
+
+		// Arrange: Create a page, and two blocks that link to it.
+		let save = async |content| {
+			service
+				.save_content_block(
+					None,
+					ContentBlock::now_with_owner(None, owner_id, FractionalIndex::start(), content),
+				)
+				.await
+				.expect("Failed to save block")
+		};
+
+		let paragraph = |markdown: String| BlockContent::Paragraph { markdown };
+
+		let page = save(BlockContent::Page {
+			title: "Rust tips".to_string(),
+		})
+		.await;
 
-	async fn connect_to_test_database() -> Pool<Postgres> {
-		let database_url = std::env::var("DATABASE_URL").unwrap();
+		let tag = format!("[[{}|Rust tips]]", page.nutty_id().nid());
+		let shared = save(paragraph(format!("See {tag}\nfor more."))).await;
+		let private = save(paragraph(format!("Draft about {tag}"))).await;
 
-		PgPoolOptions::new()
-			.max_connections(5)
-			.connect(&database_url)
-			.await
-			.expect("Failed to connect to test database")
+		for block in [&page, &shared] {
+			service
+				.grant_share_level(&reader_id, block.nutty_id(), ShareLevel::View)
+				.await
+				.expect("Failed to share block");
+		}
+
+		let list = async |navigator_id: &NuttyId| {
+			service
+				.list_linked_references(navigator_id, &page.nutty_id().dissociate())
+				.await
+				.expect("Failed to list linked references")
+		};
+
+		// Assert: The owner sees every reference, oldest first.
+		assert_eq!(
+			list(&owner_id).await,
+			[
+				LinkedReference::new(*shared.nutty_id(), "See Rust tips for more."),
+				LinkedReference::new(*private.nutty_id(), "Draft about Rust tips"),
+			]
+		);
+
+		// Assert: The reader only sees references they can read.
+		assert_eq!(
+			list(&reader_id).await,
+			[LinkedReference::new(
+				*shared.nutty_id(),
+				"See Rust tips for more."
+			)]
+		);
 	}
 
 	#[tokio::test]
-	async fn test_get_content_block_context() {
+	async fn test_ingest_email() {
 		// Arrange: Create a repository and service.
 		let pool = connect_to_test_database().await;
 		let repo = ContentRepository::new(pool.clone());
@@ -424,147 +8875,281 @@ mod tests {
 		let access_service = AccessService::new(access_repo);
 		let service = ContentService::new(repo, access_service);
 
-		// Arrange: Create a hierarchy of content blocks.
-		let parent_block = ContentBlock::now(
-			None,
-			FractionalIndex::start(),
-			BlockContent::Page {
-				title: "Parent Page".to_string(),
-			},
-		);
-
-		let middle_block = ContentBlock::now(
-			Some(*parent_block.nutty_id()),
-			FractionalIndex::between(&FractionalIndex::start(), &FractionalIndex::end()).unwrap(),
-			BlockContent::Page {
-				title: "Middle Page".to_string(),
-			},
-		);
-
-		let child_block = ContentBlock::now(
-			Some(*middle_block.nutty_id()),
-			FractionalIndex::start(),
-			BlockContent::Page {
-				title: "Child Page".to_string(),
-			},
-		);
-
-		let sibling_block = ContentBlock::now(
-			Some(*middle_block.nutty_id()),
-			FractionalIndex::between(&child_block.f_index, &FractionalIndex::end()).unwrap(),
-			BlockContent::Page {
-				title: "Sibling Page".to_string(),
-			},
-		);
+		// Arrange: Create a navigator with an inbox, and one without.
+		let navigator_id = NuttyId::now();
+		let other_id = NuttyId::now();
+
+		for id in [&navigator_id, &other_id] {
+			let navigator_name = format!("test_navigator_{}", id.nid());
+
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				id.uuid(),
+				id.nid(),
+				navigator_name,
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+		}
 
-		// Arrange: Create a reference block.
-		let reference_block = ContentBlock::now(
-			None,
-			FractionalIndex::end(),
-			BlockContent::Page {
-				title: "Reference Page".to_string(),
-			},
-		);
+		let inbox = service
+			.save_content_block(
+				None,
+				ContentBlock::now_with_owner(
+					None,
+					navigator_id,
+					FractionalIndex::start(),
+					BlockContent::Page {
+						title: "Inbox".to_string(),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save inbox");
 
-		// Act: Save all the blocks.
 		service
-			.repository
-			.upsert_content_block(parent_block.clone())
+			.set_inbox(&navigator_id, &inbox.nutty_id().dissociate())
 			.await
-			.expect("Failed to save parent block");
+			.expect("Failed to set inbox");
+
+		let email = IncomingEmail {
+			from: "carol@example.com".to_string(),
+			to: "anyone@inbox.test".to_string(),
+			subject: "Hello".to_string(),
+			text: "Hi!<script>alert(1)</script>\n\nBye.".to_string(),
+			attachments: vec![],
+		};
+
+		// Act: Ingest the email.
+		let page = service
+			.ingest_email(&navigator_id, &email)
+			.await
+			.expect("Failed to ingest email");
 
-		service
+		// Assert: The page is filed under the inbox, with its body cleaned.
+		assert_eq!(page.parent_id, Some(*inbox.nutty_id()));
+		assert!(page.is_owned_by(&navigator_id));
+
+		let paragraphs = service
 			.repository
-			.upsert_content_block(middle_block.clone())
+			.get_descendant_blocks(&page.nutty_id().dissociate())
 			.await
-			.expect("Failed to save middle block");
+			.expect("Failed to get paragraphs");
 
-		service
-			.repository
-			.upsert_content_block(child_block.clone())
+		assert_eq!(paragraphs.len(), 3);
+		assert!(paragraphs.iter().all(|block| match &block.content {
+			BlockContent::Paragraph { markdown } => !markdown.contains("<script>"),
+			_ => false,
+		}));
+
+		// Act & Assert: Navigators without an inbox can't receive email.
+		let result = service.ingest_email(&other_id, &email).await;
+		assert!(matches!(
+			result,
+			Err(ContentServiceError::InboxNotConfigured)
+		));
+	}
+
+	#[tokio::test]
+	async fn test_capture_page() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		// Arrange: Create a navigator with an inbox.
+		let navigator_id = NuttyId::now();
+		let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+				VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+			"#,
+			navigator_id.uuid(),
+			navigator_id.nid(),
+			navigator_name,
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to create test navigator");
+
+		let inbox = service
+			.save_content_block(
+				None,
+				ContentBlock::now_with_owner(
+					None,
+					navigator_id,
+					FractionalIndex::start(),
+					BlockContent::Page {
+						title: "Inbox".to_string(),
+					},
+				),
+			)
 			.await
-			.expect("Failed to save child block");
+			.expect("Failed to save inbox");
 
 		service
-			.repository
-			.upsert_content_block(sibling_block.clone())
+			.set_inbox(&navigator_id, &inbox.nutty_id().dissociate())
 			.await
-			.expect("Failed to save sibling block");
+			.expect("Failed to set inbox");
+
+		let capture = Capture {
+			url: "https://example.com/tea".to_string(),
+			selection: String::new(),
+			note: "Read later<script>alert(1)</script>".to_string(),
+			archive: true,
+		};
+
+		let snapshot = PageSnapshot::from_html(
+			&capture.parse_url().unwrap(),
+			"<title>Tea</title><p>Steep for three minutes.</p>",
+		);
 
-		service
-			.repository
-			.upsert_content_block(reference_block.clone())
+		// Act: Capture the page.
+		let captured = service
+			.capture_page(&navigator_id, &capture, Some(&snapshot))
 			.await
-			.expect("Failed to save reference block");
+			.expect("Failed to capture page");
 
-		// Arrange: Create content links.
-		let link = ContentLink::now(*middle_block.nutty_id(), *reference_block.nutty_id());
-		service
+		// Assert: The link is filed under the inbox, cleaned, with its archive.
+		assert_eq!(captured.block.parent_id, Some(*inbox.nutty_id()));
+		assert!(matches!(
+			&captured.block.content,
+			BlockContent::Paragraph { markdown }
+				if markdown.starts_with("[Tea](") && !markdown.contains("<script>")
+		));
+
+		let archive = captured.archive.expect("Expected an archive");
+		assert_eq!(archive.parent_id, Some(*captured.block.nutty_id()));
+
+		// Assert: The unfurl is saved with the link.
+		let unfurl = service
 			.repository
-			.upsert_content_link(link)
+			.get_unfurl(captured.block.nutty_id())
 			.await
-			.expect("Failed to create link");
+			.expect("Failed to get unfurl");
 
-		// Act: Get the context for the middle block.
-		let context = service
-			.get_content_block_context(&middle_block.nutty_id().into())
+		assert_eq!(unfurl, Some(snapshot.unfurl));
+	}
+
+	#[tokio::test]
+	async fn test_paste_html() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		// Arrange: Save a page with a child.
+		let page = service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					None,
+					FractionalIndex::start(),
+					BlockContent::Page {
+						title: "Pasted into".to_string(),
+					},
+				),
+			)
 			.await
-			.expect("Failed to get content context");
+			.expect("Failed to save page");
+
+		let existing = service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					Some(*page.nutty_id()),
+					FractionalIndex::start(),
+					BlockContent::Paragraph {
+						markdown: "Already here".to_string(),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save child");
 
-		// Assert: The context has the correct block ID.
-		assert_eq!(context.block_id(), middle_block.nutty_id());
+		let navigator_id = NuttyId::now();
+		let navigator_name = format!("test_navigator_{}", navigator_id.nid());
 
-		// Assert: The context has the correct parent ID.
-		assert_eq!(context.parent_id(), Some(parent_block.nutty_id()));
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+				VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+			"#,
+			navigator_id.uuid(),
+			navigator_id.nid(),
+			navigator_name,
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to create test navigator");
 
-		// Assert: The context has the correct children IDs.
-		let children_ids = context.children_ids();
-		assert_eq!(children_ids.len(), 2);
-		assert!(children_ids.contains(child_block.nutty_id()));
-		assert!(children_ids.contains(sibling_block.nutty_id()));
+		let html = concat!(
+			"<h2>Steps</h2>",
+			"<ol><li>Boil</li><li>Steep<script>alert(1)</script></li></ol>",
+			"<pre><code>tea --brew</code></pre>",
+		);
 
-		// Assert: The context has the correct reference IDs.
-		let reference_ids = context.reference_ids();
-		assert_eq!(reference_ids.len(), 1);
-		assert_eq!(reference_ids[0], *reference_block.nutty_id());
+		// Act: Paste under the page.
+		let blocks = service
+			.paste_html(&navigator_id, &page.nutty_id().dissociate(), html)
+			.await
+			.expect("Failed to paste");
 
-		// Assert: The cache contains all the expected blocks.
-		let cache = context.block_cache();
-		assert!(cache.contains_key(parent_block.nutty_id()));
-		assert!(cache.contains_key(middle_block.nutty_id()));
-		assert!(cache.contains_key(child_block.nutty_id()));
-		assert!(cache.contains_key(sibling_block.nutty_id()));
+		// Assert: The heading goes after the existing child, with the rest under it.
+		assert_eq!(blocks.len(), 4);
+		assert_eq!(blocks[0].parent_id, Some(*page.nutty_id()));
+		assert!(blocks[0].f_index > existing.f_index);
+		assert!(
+			matches!(&blocks[0].content, BlockContent::Heading { markdown } if markdown == "Steps")
+		);
 
-		// References may or may not be included automatically in the cache
-		// depending on implementation details.
+		assert!(
+			blocks[1..]
+				.iter()
+				.all(|block| block.parent_id == Some(*blocks[0].nutty_id()))
+		);
 
-		// Assert: The context has the correct children IDs.
-		let children_ids = context.children_ids();
-		assert_eq!(children_ids.len(), 2);
-		assert!(children_ids.contains(child_block.nutty_id()));
-		assert!(children_ids.contains(sibling_block.nutty_id()));
+		assert!(matches!(
+			&blocks[3].content,
+			BlockContent::Paragraph { markdown } if markdown == "```\ntea --brew\n```"
+		));
 
-		// Get context for a child block to test different parent/children relationships.
-		let child_context = service
-			.get_content_block_context(&child_block.nutty_id().into())
+		// Assert: The blocks were saved, owned by the navigator.
+		let saved = service
+			.repository
+			.get_content_block(&blocks[2].nutty_id().dissociate())
 			.await
-			.expect("Failed to get child content context");
+			.expect("Failed to get pasted block")
+			.expect("Pasted block not saved");
 
-		// Assert: The context has the correct parent ID.
-		assert_eq!(child_context.parent_id(), Some(middle_block.nutty_id()));
+		assert_eq!(saved.owner_id, Some(navigator_id));
+		assert_eq!(saved.content, blocks[2].content);
 
-		// Assert: The context has no children.
-		assert_eq!(child_context.children_ids().len(), 0);
+		// Assert: Pastes without content are rejected.
+		let result = service
+			.paste_html(&navigator_id, &page.nutty_id().dissociate(), "<p></p>")
+			.await;
 
-		// Assert: The cache still contains ancestors and descendants.
-		let child_cache = child_context.block_cache();
-		assert!(child_cache.contains_key(parent_block.nutty_id()));
-		assert!(child_cache.contains_key(middle_block.nutty_id()));
-		assert!(child_cache.contains_key(child_block.nutty_id()));
+		assert!(matches!(
+			result,
+			Err(ContentServiceError::InvalidPaste(
+				HtmlImportError::NothingToPaste
+			))
+		));
 	}
 
 	#[tokio::test]
-	async fn test_save_content_block() {
+	async fn test_annotate_content_block() {
 		// Arrange: Create a repository and service.
 		let pool = connect_to_test_database().await;
 		let repo = ContentRepository::new(pool.clone());
@@ -572,86 +9157,107 @@ mod tests {
 		let access_service = AccessService::new(access_repo);
 		let service = ContentService::new(repo, access_service);
 
-		// Arrange: Create test content blocks.
-		let source_block = ContentBlock::now(
-			None,
-			FractionalIndex::start(),
-			BlockContent::Page {
-				title: "Source Page".to_string(),
-			},
-		);
+		// Arrange: A navigator to read as, who can't read any query results.
+		let navigator_id = NuttyId::now();
 
-		let target_block = ContentBlock::now(
-			None,
-			FractionalIndex::between(&source_block.f_index, &FractionalIndex::end()).unwrap(),
-			BlockContent::Page {
-				title: "Target Page".to_string(),
-			},
-		);
+		// Arrange: Save a page with an empty heading.
+		let page = service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					None,
+					FractionalIndex::start(),
+					BlockContent::Page {
+						title: "Annotated".to_string(),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save page");
+
+		let heading = service
+			.save_content_block(
+				None,
+				ContentBlock::now(
+					Some(*page.nutty_id()),
+					FractionalIndex::start(),
+					BlockContent::Heading {
+						markdown: "#".to_string(),
+					},
+				),
+			)
+			.await
+			.expect("Failed to save heading");
 
-		// Act: Save the target block first.
-		let saved_target = service
-			.save_content_block(target_block.clone())
+		// Act: Annotate the heading.
+		let annotations = BlockAnnotation::lint(&heading);
+		assert_eq!(annotations.len(), 1);
+
+		let saved = service
+			.annotate_content_block(&heading, annotations.clone())
 			.await
-			.expect("Failed to save target block");
+			.expect("Failed to annotate heading");
 
-		// Act: Create a source block with a link to the target.
-		let source_with_link = ContentBlock::now(
-			None,
-			FractionalIndex::start(),
-			BlockContent::Paragraph {
-				markdown: format!("This links to [[{}]]", saved_target.nutty_id().nid()),
-			},
-		);
+		// Assert: The annotations are surfaced in the page's context.
+		assert!(saved);
 
-		// Act: Save the source block.
-		let saved_source = service
-			.save_content_block(source_with_link.clone())
+		let context = service
+			.get_content_block_context(
+				&navigator_id,
+				&page.nutty_id().dissociate(),
+				&ChildrenView::default(),
+			)
 			.await
-			.expect("Failed to save source block");
+			.expect("Failed to get context");
 
-		// Assert: The source block was saved correctly.
-		assert_eq!(saved_source.nutty_id(), source_with_link.nutty_id());
-		assert_eq!(saved_source.parent_id, source_with_link.parent_id);
-		assert!(matches!(
-			&saved_source.content,
-			BlockContent::Paragraph { markdown } if markdown == &format!("This links to [[{}]]", saved_target.nutty_id().nid())
-		));
+		assert_eq!(
+			context.annotations().get(heading.nutty_id()),
+			Some(&annotations)
+		);
+		assert!(!context.annotations().contains_key(page.nutty_id()));
 
-		// Assert: A link was created between the blocks.
-		let links = service
-			.repository
-			.get_content_links_from(saved_source.nutty_id())
+		// Act: Fix the heading, then annotate its stale content.
+		let mut fixed = heading.clone();
+		fixed.content = BlockContent::Heading {
+			markdown: "# Notes".to_string(),
+		};
+
+		let fixed = service
+			.save_content_block(None, fixed)
 			.await
-			.expect("Failed to get links from source block");
+			.expect("Failed to save heading");
 
-		assert_eq!(links.len(), 1);
-		assert_eq!(links[0].source_id, *saved_source.nutty_id());
-		assert_eq!(links[0].target_id, *saved_target.nutty_id());
+		let saved = service
+			.annotate_content_block(&heading, annotations.clone())
+			.await
+			.expect("Failed to annotate heading");
 
-		// Act: Update the source block to remove the link.
-		let updated_source = ContentBlock::now(
-			None,
-			FractionalIndex::start(),
-			BlockContent::Paragraph {
-				markdown: "No more links".to_string(),
-			},
-		);
+		// Assert: Stale annotations aren't saved, but fresh ones replace them.
+		assert!(!saved);
 
-		// Act: Save the updated source block.
-		let saved_updated = service
-			.save_content_block(updated_source.clone())
+		let saved = service
+			.annotate_content_block(&fixed, BlockAnnotation::lint(&fixed))
 			.await
-			.expect("Failed to save updated source block");
+			.expect("Failed to annotate heading");
 
-		// Assert: The orphaned link was deleted.
-		let links = service
-			.repository
-			.get_content_links_from(saved_updated.nutty_id())
+		assert!(saved);
+
+		let context = service
+			.get_content_block_context(
+				&navigator_id,
+				&page.nutty_id().dissociate(),
+				&ChildrenView::default(),
+			)
 			.await
-			.expect("Failed to get links from updated source block");
+			.expect("Failed to get context");
 
-		assert!(links.is_empty());
+		assert!(context.annotations().is_empty());
+
+		// Cleanup: Delete the page, and its annotations with it.
+		service
+			.delete_content_block(&page.nutty_id().dissociate(), false)
+			.await
+			.expect("Failed to delete page");
 	}
 
 	#[tokio::test]
@@ -705,7 +9311,7 @@ mod tests {
 			.grant_resource_role(
 				&navigator_id,
 				"viewer",
-				"content_block",
+				ResourceKind::ContentBlock,
 				content_block.nutty_id(),
 			)
 			.await
@@ -820,7 +9426,7 @@ mod tests {
 			.grant_resource_role(
 				&navigator_id,
 				"viewer",
-				"content_block",
+				ResourceKind::ContentBlock,
 				parent_block.nutty_id(),
 			)
 			.await
@@ -948,6 +9554,310 @@ mod tests {
 		.expect("Failed to cleanup test navigator");
 	}
 
+	#[tokio::test]
+	async fn test_get_content_calendar() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		setup_test_data(&pool).await;
+
+		// Arrange: Create an admin and a navigator without permissions.
+		let admin_id = NuttyId::now();
+		let stranger_id = NuttyId::now();
+
+		for navigator_id in [&admin_id, &stranger_id] {
+			let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				navigator_id.uuid(),
+				navigator_id.nid(),
+				navigator_name,
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+		}
+
+		service
+			.access_service
+			.grant_global_role(&admin_id, "admin")
+			.await
+			.expect("Failed to grant global role");
+
+		// Arrange: Save a daily note with a task due two days later, in a
+		// year that no other test uses.
+		let daily_note = ContentBlock::now(
+			None,
+			FractionalIndex::start(),
+			BlockContent::Page {
+				title: "2191-03-01".to_string(),
+			},
+		);
+
+		let task = ContentBlock::now(
+			Some(*daily_note.nutty_id()),
+			FractionalIndex::start(),
+			BlockContent::Paragraph {
+				markdown: "Water the plants due:2191-03-03".to_string(),
+			},
+		);
+
+		for block in [&daily_note, &task] {
+			service
+				.save_content_block(None, block.clone())
+				.await
+				.expect("Failed to save test block");
+		}
+
+		let from = NaiveDate::from_ymd_opt(2191, 3, 1).unwrap();
+		let to = NaiveDate::from_ymd_opt(2191, 3, 31).unwrap();
+
+		// Act: Query the calendar.
+		let calendar = service
+			.get_content_calendar(&admin_id, from, to)
+			.await
+			.expect("Failed to get calendar");
+
+		// Assert: Both dates are listed in order, with their blocks.
+		let entries: Vec<_> = calendar
+			.entries()
+			.iter()
+			.map(|entry| (entry.block_id, entry.date, entry.kind))
+			.collect();
+
+		assert_eq!(
+			entries,
+			vec![
+				(*daily_note.nutty_id(), from, BlockDateKind::Daily),
+				(
+					*task.nutty_id(),
+					NaiveDate::from_ymd_opt(2191, 3, 3).unwrap(),
+					BlockDateKind::Due
+				),
+			]
+		);
+
+		assert!(calendar.block_cache().contains_key(task.nutty_id()));
+
+		// Assert: Navigators only see blocks they can access.
+		let calendar = service
+			.get_content_calendar(&stranger_id, from, to)
+			.await
+			.expect("Failed to get calendar");
+
+		assert!(calendar.entries().is_empty());
+
+		// Assert: Dates are replaced when a block is saved again.
+		let mut done = task.clone();
+		done.content = BlockContent::Paragraph {
+			markdown: "Watered the plants".to_string(),
+		};
+
+		service
+			.save_content_block(None, done)
+			.await
+			.expect("Failed to save test block");
+
+		let calendar = service
+			.get_content_calendar(&admin_id, from, to)
+			.await
+			.expect("Failed to get calendar");
+
+		assert_eq!(calendar.entries().len(), 1);
+
+		// Assert: Ranges are bounded.
+		assert!(matches!(
+			service.get_content_calendar(&admin_id, to, from).await,
+			Err(ContentServiceError::InvalidDateRange)
+		));
+
+		// Clean up.
+		for block in [&task, &daily_note] {
+			service
+				.repository
+				.delete_content_block(&block.nutty_id().dissociate())
+				.await
+				.expect("Failed to cleanup test block");
+		}
+
+		for navigator_id in [&admin_id, &stranger_id] {
+			sqlx::query!(
+				r#"DELETE FROM auth.navigators WHERE id = $1"#,
+				navigator_id.uuid()
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to cleanup test navigator");
+		}
+	}
+
+	#[tokio::test]
+	async fn test_check_content_block_access_hidden() {
+		// Test that hidden blocks are only visible to moderators.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_repo = AccessRepository::new(pool.clone());
+		let access_service = AccessService::new(access_repo);
+		let service = ContentService::new(repo, access_service);
+
+		// Set up test data (permissions, roles, etc.).
+		setup_test_data(&pool).await;
+
+		// Create an editor and a moderator in the database.
+		let editor_id = NuttyId::now();
+		let moderator_id = NuttyId::now();
+
+		for navigator_id in [&editor_id, &moderator_id] {
+			let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+			sqlx::query!(
+				r#"
+					INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+					VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+				"#,
+				navigator_id.uuid(),
+				navigator_id.nid(),
+				navigator_name,
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to create test navigator");
+		}
+
+		service
+			.access_service
+			.grant_global_role(&editor_id, "editor")
+			.await
+			.expect("Failed to grant global role");
+
+		service
+			.access_service
+			.grant_global_role(&moderator_id, "admin")
+			.await
+			.expect("Failed to grant global role");
+
+		// Create and hide a block.
+		let content_block = ContentBlock::now(
+			None,
+			FractionalIndex::start(),
+			BlockContent::Page {
+				title: "Test Page".to_string(),
+			},
+		);
+
+		service
+			.repository
+			.upsert_content_block(content_block.clone())
+			.await
+			.expect("Failed to save test block");
+
+		let child_block = ContentBlock::now(
+			Some(*content_block.nutty_id()),
+			FractionalIndex::start(),
+			BlockContent::Paragraph {
+				markdown: "Test Child".to_string(),
+			},
+		);
+
+		service
+			.repository
+			.upsert_content_block(child_block.clone())
+			.await
+			.expect("Failed to save test block");
+
+		ModerationRepository::new(pool.clone())
+			.set_block_hidden(content_block.nutty_id(), true)
+			.await
+			.expect("Failed to hide test block");
+
+		// Test that only the moderator can access the block.
+		let block_id_dissociated = DissociatedNuttyId::new(&content_block.nutty_id().nid()).unwrap();
+		let child_id_dissociated = DissociatedNuttyId::new(&child_block.nutty_id().nid()).unwrap();
+
+		assert!(
+			!service
+				.check_content_block_access(&editor_id, &block_id_dissociated)
+				.await
+				.expect("Failed to check access"),
+			"Editor should not see hidden blocks"
+		);
+
+		assert!(
+			service
+				.check_content_block_access(&moderator_id, &block_id_dissociated)
+				.await
+				.expect("Failed to check access"),
+			"Moderator should see hidden blocks"
+		);
+
+		// Test that the block's descendants are hidden too.
+		assert!(
+			!service
+				.check_content_block_access(&editor_id, &child_id_dissociated)
+				.await
+				.expect("Failed to check access"),
+			"Editor should not see blocks under hidden blocks"
+		);
+
+		assert!(
+			service
+				.check_content_block_access(&moderator_id, &child_id_dissociated)
+				.await
+				.expect("Failed to check access"),
+			"Moderator should see blocks under hidden blocks"
+		);
+
+		// Test that only the moderator can change the block and its descendants.
+		for block_id in [&block_id_dissociated, &child_id_dissociated] {
+			assert!(
+				!service
+					.check_content_block_write_access(&editor_id, block_id)
+					.await
+					.expect("Failed to check access"),
+				"Editor should not change hidden blocks"
+			);
+
+			assert!(
+				service
+					.check_content_block_write_access(&moderator_id, block_id)
+					.await
+					.expect("Failed to check access"),
+				"Moderator should change hidden blocks"
+			);
+		}
+
+		// Clean up.
+		service
+			.repository
+			.delete_content_block(&child_id_dissociated)
+			.await
+			.expect("Failed to cleanup test block");
+
+		service
+			.repository
+			.delete_content_block(&block_id_dissociated)
+			.await
+			.expect("Failed to cleanup test block");
+
+		for navigator_id in [&editor_id, &moderator_id] {
+			sqlx::query!(
+				r#"DELETE FROM auth.navigators WHERE id = $1"#,
+				navigator_id.uuid()
+			)
+			.execute(&pool)
+			.await
+			.expect("Failed to cleanup test navigator");
+		}
+	}
+
 	#[tokio::test]
 	async fn test_check_content_block_access_ownership() {
 		// Test that a user with ownership permission can access their own blocks.
@@ -1374,7 +10284,7 @@ mod tests {
 			.grant_resource_role(
 				&navigator_id,
 				"editor",
-				"content_block",
+				ResourceKind::ContentBlock,
 				content_block.nutty_id(),
 			)
 			.await
@@ -1571,7 +10481,7 @@ mod tests {
 			.grant_resource_role(
 				&navigator_id,
 				"editor",
-				"content_block",
+				ResourceKind::ContentBlock,
 				parent_block.nutty_id(),
 			)
 			.await
@@ -1831,6 +10741,183 @@ mod tests {
 	}
 
 	// Helper function to set up test data.
+	#[tokio::test]
+	async fn test_save_content_block_link_access() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = ContentRepository::new(pool.clone());
+		let access_service = AccessService::new(AccessRepository::new(pool.clone()));
+		let service = ContentService::new(repo.clone(), access_service.clone());
+
+		setup_test_data(&pool).await;
+
+		// Arrange: Create an author.
+		let author_id = NuttyId::now();
+
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.navigators (id, nutty_id, name, pass)
+				VALUES ($1, $2, $3, 'test_pass')
+			"#,
+			author_id.uuid(),
+			author_id.nid(),
+			format!("test_navigator_{}", author_id.nid()),
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to create test navigator");
+
+		// Arrange: Create a page shared with the author, and a private one.
+		let page = |title: &str| {
+			ContentBlock::now(
+				None,
+				FractionalIndex::start(),
+				BlockContent::Page {
+					title: title.to_string(),
+				},
+			)
+		};
+
+		let shared = repo
+			.upsert_content_block(page("Shared"))
+			.await
+			.expect("Failed to save shared page");
+
+		let private = repo
+			.upsert_content_block(page("Private"))
+			.await
+			.expect("Failed to save private page");
+
+		access_service
+			.grant_resource_role(
+				&author_id,
+				"viewer",
+				ResourceKind::ContentBlock,
+				shared.nutty_id(),
+			)
+			.await
+			.expect("Failed to grant resource role");
+
+		// Arrange: Write a block that tags both pages.
+		let block = ContentBlock::now_with_owner(
+			None,
+			author_id,
+			FractionalIndex::start(),
+			BlockContent::Paragraph {
+				markdown: format!(
+					"See [[{}]] and [[{}]].",
+					shared.nutty_id().nid(),
+					private.nutty_id().nid()
+				),
+			},
+		);
+
+		let save_as_author = |service: ContentService| {
+			let block = block.clone();
+
+			async move { service.save_content_block(Some(&author_id), block).await }
+		};
+
+		let links = || async {
+			repo
+				.get_content_links_from(block.nutty_id())
+				.await
+				.expect("Failed to get links")
+				.into_iter()
+				.map(|link| (link.target_id, link.restricted))
+				.collect::<HashMap<_, _>>()
+		};
+
+		// Act: Save the block on the author's behalf.
+		save_as_author(service.clone())
+			.await
+			.expect("Failed to save block");
+
+		// Assert: Only the shared page is linked.
+		assert_eq!(links().await, HashMap::from([(*shared.nutty_id(), false)]));
+
+		// Act: Save it again, marking links rather than dropping them.
+		save_as_author(
+			service
+				.clone()
+				.with_link_access_policy(LinkAccessPolicy::Mark),
+		)
+		.await
+		.expect("Failed to save block");
+
+		// Assert: Both pages are linked, with the private one restricted.
+		assert_eq!(
+			links().await,
+			HashMap::from([(*shared.nutty_id(), false), (*private.nutty_id(), true)])
+		);
+
+		// Act: Get the block's context, keeping the restricted references
+		// whose targets the author can read.
+		let mut context = service
+			.get_content_block_context(
+				&author_id,
+				&block.nutty_id().dissociate(),
+				&ChildrenView::default(),
+			)
+			.await
+			.expect("Failed to get content context");
+
+		assert_eq!(context.restricted_target_ids(), vec![*private.nutty_id()]);
+
+		let readable_ids = service
+			.list_readable_block_ids(&author_id, &context.restricted_target_ids())
+			.await
+			.expect("Failed to list readable blocks");
+
+		context.retain_restricted_links(&readable_ids);
+
+		// Assert: The private page isn't referenced for the author.
+		assert_eq!(context.reference_ids(), [*shared.nutty_id()]);
+
+		// Act: Get the private page's context, for a navigator who can read it.
+		let mut context = service
+			.get_content_block_context(
+				&author_id,
+				&private.nutty_id().dissociate(),
+				&ChildrenView::default(),
+			)
+			.await
+			.expect("Failed to get content context");
+
+		context.retain_restricted_links(&[*private.nutty_id()]);
+
+		// Assert: The restricted backlink is kept.
+		assert_eq!(context.backlink_ids(), [*block.nutty_id()]);
+
+		// Act: Save it again in the background, without an author.
+		service
+			.save_content_block(None, block.clone())
+			.await
+			.expect("Failed to save block");
+
+		// Assert: Nothing is restricted.
+		assert_eq!(
+			links().await,
+			HashMap::from([(*shared.nutty_id(), false), (*private.nutty_id(), false)])
+		);
+
+		// Cleanup: Delete the blocks and the author.
+		for block in [&block, &shared, &private] {
+			repo
+				.delete_content_block(&block.nutty_id().dissociate())
+				.await
+				.expect("Failed to delete test block");
+		}
+
+		sqlx::query!(
+			"DELETE FROM auth.navigators WHERE id = $1",
+			author_id.uuid()
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to delete test navigator");
+	}
+
 	async fn setup_test_data(pool: &sqlx::PgPool) {
 		// Insert test permissions.
 		sqlx::query!(
@@ -0,0 +1,298 @@
+use std::sync::Arc;
+
+use axum::Json;
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::routing::put;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::content::service::ContentServiceError;
+use crate::models::ContentBlock;
+use crate::models::DissociatedNuttyId;
+use crate::models::capture::Capture;
+use crate::models::capture::CaptureError;
+use crate::models::capture::CapturedPage;
+use crate::models::capture::PageSnapshot;
+use crate::models::incoming_email::IncomingEmail;
+use crate::models::nutty_id::NuttyIdError;
+use crate::navigator::service::NavigatorServiceError;
+use crate::utilities::api::response::Error;
+use crate::utilities::api::response::Response;
+use crate::utilities::api::session::Session;
+use crate::utilities::api::state::AppState;
+use crate::utilities::api::webhook::SIGNATURE_HEADER;
+
+/// The router for ingestion and capture API endpoints.
+pub fn router(app_state: Arc<AppState>) -> Router {
+	Router::new()
+		.route("/ingest/email", post(ingest_email_handler))
+		.route("/ingest/inbox", put(set_inbox_handler))
+		.route("/capture", post(capture_handler))
+		.with_state(app_state)
+}
+
+/// Build an error response.
+fn error_response<T>(
+	status: StatusCode,
+	summary: &str,
+	error: IngestApiError,
+) -> (StatusCode, Json<Response<T>>) {
+	let error = Error::from_error(&error).with_summary(summary);
+
+	(
+		status,
+		Json(Response::Error {
+			errors: vec![error],
+		}),
+	)
+}
+
+/// An API handler for filing an inbound email, posted by a mail service, as
+/// a page under the addressed navigator's inbox. The body must be signed
+/// with the shared ingestion secret.
+async fn ingest_email_handler(
+	State(state): State<Arc<AppState>>,
+	headers: HeaderMap,
+	body: Bytes,
+) -> (StatusCode, Json<Response<ContentBlock>>) {
+	let summary = "Failed to ingest email.";
+
+	let signature = headers
+		.get(SIGNATURE_HEADER)
+		.and_then(|value| value.to_str().ok())
+		.unwrap_or_default();
+
+	if !state.email_ingest_secret.verify(&body, signature) {
+		return error_response(
+			StatusCode::UNAUTHORIZED,
+			summary,
+			IngestApiError::InvalidSignature,
+		);
+	}
+
+	let email: IncomingEmail = match serde_json::from_slice(&body) {
+		Ok(email) => email,
+
+		Err(error) => {
+			return error_response(
+				StatusCode::BAD_REQUEST,
+				summary,
+				IngestApiError::InvalidEmail(error),
+			);
+		}
+	};
+
+	let Some(name) = email.recipient_name() else {
+		return error_response(
+			StatusCode::NOT_FOUND,
+			summary,
+			IngestApiError::UnknownRecipient,
+		);
+	};
+
+	let navigator = match state.navigator_service.get_navigator_by_name(name).await {
+		Ok(Some(navigator)) => navigator,
+
+		Ok(None) => {
+			return error_response(
+				StatusCode::NOT_FOUND,
+				summary,
+				IngestApiError::UnknownRecipient,
+			);
+		}
+
+		Err(error) => {
+			return error_response(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				summary,
+				IngestApiError::LookupRecipient(error),
+			);
+		}
+	};
+
+	match state
+		.content_service
+		.ingest_email(navigator.nutty_id(), &email)
+		.await
+	{
+		Ok(page) => (
+			StatusCode::CREATED,
+			Json(Response::Single { data: Some(page) }),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::InboxNotConfigured => StatusCode::CONFLICT,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			error_response(status, summary, IngestApiError::Ingest(error))
+		}
+	}
+}
+
+/// Request payload for setting a navigator's inbox.
+#[derive(Serialize, Deserialize)]
+pub struct SetInboxRequest {
+	/// The block to file inbound emails under.
+	block_id: String,
+}
+
+/// An API handler for setting the block that the navigator's inbound emails
+/// are filed under.
+async fn set_inbox_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Json(payload): Json<SetInboxRequest>,
+) -> (StatusCode, Json<Response<ContentBlock>>) {
+	let summary = "Failed to set inbox.";
+
+	let block_id = match DissociatedNuttyId::new(&payload.block_id) {
+		Ok(id) => id,
+
+		Err(error) => {
+			return error_response(
+				StatusCode::BAD_REQUEST,
+				summary,
+				IngestApiError::InvalidBlockId(error),
+			);
+		}
+	};
+
+	// Emails are filed as the navigator, so they must be able to write there.
+	match state
+		.content_service
+		.check_content_block_write_access(navigator.nutty_id(), &block_id)
+		.await
+	{
+		Ok(true) => {}
+
+		Ok(false) => {
+			return error_response(
+				StatusCode::FORBIDDEN,
+				"Access denied.",
+				IngestApiError::AccessDenied,
+			);
+		}
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			return error_response(status, summary, IngestApiError::AccessControl(error));
+		}
+	}
+
+	match state
+		.content_service
+		.set_inbox(navigator.nutty_id(), &block_id)
+		.await
+	{
+		Ok(inbox) => (StatusCode::OK, Json(Response::Single { data: Some(inbox) })),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			error_response(status, summary, IngestApiError::SetInbox(error))
+		}
+	}
+}
+
+/// An API handler for capturing a page from the browser (e.g., with a
+/// bookmarklet) into the navigator's inbox. The page is fetched to unfurl
+/// it and, if asked, to archive its text; when it can't be fetched, the
+/// capture is filed without them.
+async fn capture_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Json(capture): Json<Capture>,
+) -> (StatusCode, Json<Response<CapturedPage>>) {
+	let summary = "Failed to capture page.";
+
+	let url = match capture.parse_url() {
+		Ok(url) => url,
+
+		Err(error) => {
+			return error_response(
+				StatusCode::BAD_REQUEST,
+				summary,
+				IngestApiError::InvalidCapture(error),
+			);
+		}
+	};
+
+	let snapshot = state
+		.page_fetcher
+		.fetch(&url)
+		.await
+		.ok()
+		.map(|html| PageSnapshot::from_html(&url, &html));
+
+	match state
+		.content_service
+		.capture_page(navigator.nutty_id(), &capture, snapshot.as_ref())
+		.await
+	{
+		Ok(captured) => (
+			StatusCode::CREATED,
+			Json(Response::Single {
+				data: Some(captured),
+			}),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ContentServiceError::InboxNotConfigured => StatusCode::CONFLICT,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			error_response(status, summary, IngestApiError::Capture(error))
+		}
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum IngestApiError {
+	#[error("Missing or invalid webhook signature.")]
+	InvalidSignature,
+
+	#[error("Invalid email: {0}")]
+	InvalidEmail(serde_json::Error),
+
+	#[error("No navigator with the recipient's name.")]
+	UnknownRecipient,
+
+	#[error("Failed to look up recipient: {0}")]
+	LookupRecipient(NavigatorServiceError),
+
+	#[error("Failed to ingest email: {0}")]
+	Ingest(ContentServiceError),
+
+	#[error("Invalid block ID: {0}")]
+	InvalidBlockId(NuttyIdError),
+
+	#[error("Failed to set inbox: {0}")]
+	SetInbox(ContentServiceError),
+
+	#[error("Invalid capture: {0}")]
+	InvalidCapture(CaptureError),
+
+	#[error("Failed to capture page: {0}")]
+	Capture(ContentServiceError),
+
+	#[error("Access denied.")]
+	AccessDenied,
+
+	#[error("Failed to check access permissions: {0}")]
+	AccessControl(ContentServiceError),
+}
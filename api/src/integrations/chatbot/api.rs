@@ -0,0 +1,189 @@
+use std::sync::Arc;
+
+use axum::Json;
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::post;
+use chrono::Utc;
+use serde_json::json;
+
+use crate::integrations::chatbot::models::ChatCommand;
+use crate::integrations::chatbot::models::ChatMessage;
+use crate::integrations::chatbot::models::ChatbotError;
+use crate::integrations::chatbot::models::LinkCode;
+use crate::integrations::chatbot::platform::ChatPlatform;
+use crate::integrations::chatbot::service::ChatbotServiceError;
+use crate::utilities::api::response::Error;
+use crate::utilities::api::response::Response;
+use crate::utilities::api::session::Session;
+use crate::utilities::api::state::AppState;
+
+/// The reply to chat users who haven't linked their chat yet.
+const LINK_HINT: &str =
+	"Send /link followed by a code from your Nuttyverse settings to link this chat.";
+
+/// The router for chatbot API endpoints.
+pub fn router(app_state: Arc<AppState>) -> Router {
+	Router::new()
+		.route(
+			"/integrations/chatbot/link-code",
+			post(create_link_code_handler),
+		)
+		.route(
+			"/integrations/telegram/webhook",
+			post(telegram_webhook_handler),
+		)
+		.with_state(app_state)
+}
+
+/// Build an error response.
+fn error_response<T>(
+	status: StatusCode,
+	summary: &str,
+	error: ChatbotApiError,
+) -> (StatusCode, Json<Response<T>>) {
+	let error = Error::from_error(&error).with_summary(summary);
+
+	(
+		status,
+		Json(Response::Error {
+			errors: vec![error],
+		}),
+	)
+}
+
+/// An API handler for issuing a code that links a chat to the navigator.
+async fn create_link_code_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+) -> (StatusCode, Json<Response<LinkCode>>) {
+	match state
+		.chatbot_service
+		.create_link_code(navigator.nutty_id())
+		.await
+	{
+		Ok(link_code) => (
+			StatusCode::CREATED,
+			Json(Response::Single {
+				data: Some(link_code),
+			}),
+		),
+
+		Err(error) => error_response(
+			StatusCode::INTERNAL_SERVER_ERROR,
+			"Failed to create link code.",
+			ChatbotApiError::CreateLinkCode(error),
+		),
+	}
+}
+
+/// An API handler for updates from the Telegram bot's webhook.
+async fn telegram_webhook_handler(
+	State(state): State<Arc<AppState>>,
+	headers: HeaderMap,
+	body: Bytes,
+) -> axum::response::Response {
+	chat_webhook(&state, &state.telegram, &headers, &body).await
+}
+
+/// Handle a chat platform's webhook: link the sender with `/link <code>`,
+/// or add their message to today's daily note (in UTC), replying with its
+/// permalink. Failures are replied to in the chat rather than retried.
+async fn chat_webhook(
+	state: &AppState,
+	platform: &dyn ChatPlatform,
+	headers: &HeaderMap,
+	body: &[u8],
+) -> axum::response::Response {
+	let summary = "Failed to handle chat webhook.";
+
+	if !platform.verify(headers, body) {
+		return error_response::<()>(
+			StatusCode::UNAUTHORIZED,
+			summary,
+			ChatbotApiError::InvalidSecret,
+		)
+		.into_response();
+	}
+
+	let message = match platform.parse_message(body) {
+		Ok(Some(message)) => message,
+
+		// Acknowledge updates that aren't text messages.
+		Ok(None) => return Json(json!({})).into_response(),
+
+		Err(error) => {
+			return error_response::<()>(
+				StatusCode::BAD_REQUEST,
+				summary,
+				ChatbotApiError::InvalidPayload(error),
+			)
+			.into_response();
+		}
+	};
+
+	let reply = respond(state, platform, &message).await;
+	Json(platform.reply(&message, &reply)).into_response()
+}
+
+/// Carry out a chat message's command, returning the text to reply with.
+async fn respond(state: &AppState, platform: &dyn ChatPlatform, message: &ChatMessage) -> String {
+	let kind = platform.kind();
+
+	match message.command() {
+		ChatCommand::Link(None) => LINK_HINT.to_string(),
+
+		ChatCommand::Link(Some(code)) => {
+			match state
+				.chatbot_service
+				.link_chat_user(kind, &message.user_id, &code)
+				.await
+			{
+				Ok(_) => "Linked! Messages sent here are added to your daily note.".to_string(),
+				Err(ChatbotServiceError::InvalidLinkCode) => {
+					"That link code is invalid or has expired.".to_string()
+				}
+				Err(_) => "Something went wrong linking this chat. Please try again.".to_string(),
+			}
+		}
+
+		ChatCommand::Note(markdown) => {
+			let navigator_id = match state
+				.chatbot_service
+				.get_linked_navigator(kind, &message.user_id)
+				.await
+			{
+				Ok(Some(navigator_id)) => navigator_id,
+				Ok(None) => return LINK_HINT.to_string(),
+				Err(_) => return "Something went wrong. Please try again.".to_string(),
+			};
+
+			let today = Utc::now().date_naive();
+
+			match state
+				.content_service
+				.append_to_daily_note(&navigator_id, today, markdown)
+				.await
+			{
+				Ok(block) => format!("Added to your daily note: {}", block.nutty_id().permalink()),
+				Err(_) => "Something went wrong saving your note. Please try again.".to_string(),
+			}
+		}
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ChatbotApiError {
+	#[error("Missing or invalid webhook secret.")]
+	InvalidSecret,
+
+	#[error("{0}")]
+	InvalidPayload(ChatbotError),
+
+	#[error("Failed to create link code: {0}")]
+	CreateLinkCode(ChatbotServiceError),
+}
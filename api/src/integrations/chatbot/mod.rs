@@ -0,0 +1,6 @@
+pub mod api;
+pub mod models;
+pub mod platform;
+pub mod repository;
+pub mod service;
+pub mod telegram;
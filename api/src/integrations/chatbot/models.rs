@@ -0,0 +1,156 @@
+use std::str::FromStr;
+
+use chrono::Duration;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::models::NuttyId;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+
+/// How long a link code can be redeemed for, in minutes.
+pub const LINK_CODE_TTL_MINUTES: i64 = 15;
+
+/// A chat platform that navigators can take notes through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatPlatformKind {
+	Telegram,
+}
+
+impl ChatPlatformKind {
+	/// Get the stored representation of the platform.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ChatPlatformKind::Telegram => "telegram",
+		}
+	}
+}
+
+impl FromStr for ChatPlatformKind {
+	type Err = ChatbotError;
+
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		match value {
+			"telegram" => Ok(ChatPlatformKind::Telegram),
+			_ => Err(ChatbotError::UnknownPlatform(value.to_string())),
+		}
+	}
+}
+
+/// A text message sent to the bot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMessage {
+	/// The platform's ID for the chat, which replies are sent to.
+	pub chat_id: String,
+
+	/// The platform's ID for the sender.
+	pub user_id: String,
+
+	/// The message's text.
+	pub text: String,
+}
+
+/// What a chat message asks the bot to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChatCommand {
+	/// Link the sender to the navigator that issued the code, if any.
+	Link(Option<String>),
+
+	/// Add the text to the sender's daily note.
+	Note(String),
+}
+
+impl ChatMessage {
+	/// Parse the message as a command. `/start` is accepted for `/link`,
+	/// since that's what Telegram's deep links send.
+	pub fn command(&self) -> ChatCommand {
+		let text = self.text.trim();
+		let (first, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+
+		// Commands may be addressed to a bot, e.g. `/link@NuttyBot`.
+		let name = first.split('@').next().unwrap_or(first);
+
+		match name {
+			"/link" | "/start" => {
+				let code = rest.trim();
+				ChatCommand::Link((!code.is_empty()).then(|| code.to_string()))
+			}
+
+			_ => ChatCommand::Note(text.to_string()),
+		}
+	}
+}
+
+/// A code that links a chat user to the navigator it was issued to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkCode {
+	/// The code to send to the bot.
+	pub code: String,
+
+	/// When the code can no longer be redeemed.
+	pub expires_at: DateTimeRfc3339,
+}
+
+impl LinkCode {
+	/// Create a new code, expiring after [LINK_CODE_TTL_MINUTES].
+	pub fn new() -> Self {
+		let expires_at = Utc::now() + Duration::minutes(LINK_CODE_TTL_MINUTES);
+
+		Self {
+			// The NID is drawn from the UUID's random bits.
+			code: NuttyId::now().nid(),
+			expires_at: DateTimeRfc3339::new(expires_at.fixed_offset()),
+		}
+	}
+}
+
+impl Default for LinkCode {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum ChatbotError {
+	#[error("Unknown chat platform: {0}")]
+	UnknownPlatform(String),
+
+	#[error("Invalid webhook payload: {0}")]
+	InvalidPayload(serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn message(text: &str) -> ChatMessage {
+		ChatMessage {
+			chat_id: "1".to_string(),
+			user_id: "2".to_string(),
+			text: text.to_string(),
+		}
+	}
+
+	#[test]
+	fn test_chat_command() {
+		assert_eq!(
+			message("/link abc1234").command(),
+			ChatCommand::Link(Some("abc1234".to_string()))
+		);
+		assert_eq!(
+			message("/start@NuttyBot  abc1234 ").command(),
+			ChatCommand::Link(Some("abc1234".to_string()))
+		);
+		assert_eq!(message("/start").command(), ChatCommand::Link(None));
+		assert_eq!(
+			message(" Buy oat milk ").command(),
+			ChatCommand::Note("Buy oat milk".to_string())
+		);
+		assert_eq!(
+			message("/linked list").command(),
+			ChatCommand::Note("/linked list".to_string())
+		);
+	}
+}
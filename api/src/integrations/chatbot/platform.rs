@@ -0,0 +1,25 @@
+use axum::http::HeaderMap;
+use serde_json::Value;
+
+use crate::integrations::chatbot::models::ChatMessage;
+use crate::integrations::chatbot::models::ChatPlatformKind;
+use crate::integrations::chatbot::models::ChatbotError;
+
+/// A chat platform whose bot receives messages through a webhook, and
+/// replies in the webhook's response. Implemented by
+/// [Telegram](crate::integrations::chatbot::telegram::Telegram).
+pub trait ChatPlatform: Send + Sync {
+	/// Get the kind of platform.
+	fn kind(&self) -> ChatPlatformKind;
+
+	/// Check that a webhook request came from the platform. Every request
+	/// is rejected unless the platform is configured.
+	fn verify(&self, headers: &HeaderMap, body: &[u8]) -> bool;
+
+	/// Parse a webhook body into a text message, or `None` if it's an
+	/// update that the bot ignores.
+	fn parse_message(&self, body: &[u8]) -> Result<Option<ChatMessage>, ChatbotError>;
+
+	/// Build the webhook response that replies to a message.
+	fn reply(&self, message: &ChatMessage, text: &str) -> Value;
+}
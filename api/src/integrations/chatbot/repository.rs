@@ -0,0 +1,169 @@
+use sqlx::Executor;
+use sqlx::Postgres;
+use thiserror::Error;
+
+use crate::integrations::chatbot::models::ChatPlatformKind;
+use crate::integrations::chatbot::models::LinkCode;
+use crate::models::NuttyId;
+use crate::utilities::query_metrics::RecordQuery;
+use crate::utilities::repository::Repository;
+
+/// A repository for the chat users linked to navigators.
+/// Objects are stored in PostgreSQL.
+#[derive(Debug, Clone)]
+pub struct ChatbotRepository {
+	/// The PostgreSQL database pool.
+	pool: sqlx::Pool<Postgres>,
+}
+
+impl ChatbotRepository {
+	/// Create a new chatbot repository.
+	pub fn new(pool: sqlx::Pool<Postgres>) -> Self {
+		Self { pool }
+	}
+
+	/// Save a link code issued to a navigator.
+	pub async fn create_link_code_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+		link_code: &LinkCode,
+	) -> Result<(), ChatbotRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.chat_link_codes (code, navigator_id, expires_at)
+				VALUES ($1, $2, $3)
+			"#,
+			link_code.code,
+			navigator_id.uuid(),
+			link_code.expires_at.inner(),
+		)
+		.execute(executor)
+		.record_query("create_link_code")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Save a link code issued to a navigator.
+	pub async fn create_link_code(
+		&self,
+		navigator_id: &NuttyId,
+		link_code: &LinkCode,
+	) -> Result<(), ChatbotRepositoryError> {
+		self
+			.create_link_code_tx(&self.pool, navigator_id, link_code)
+			.await
+	}
+
+	/// Delete a link code, returning the navigator it was issued to if it
+	/// hadn't expired. Expired codes are cleared along the way.
+	pub async fn redeem_link_code_tx<'e, E>(
+		&self,
+		executor: E,
+		code: &str,
+	) -> Result<Option<NuttyId>, ChatbotRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let navigator_id = sqlx::query_scalar!(
+			r#"
+				WITH deleted AS (
+					DELETE FROM auth.chat_link_codes
+					WHERE code = $1 OR expires_at <= NOW()
+					RETURNING code, navigator_id, expires_at
+				)
+				SELECT navigator_id
+				FROM deleted
+				WHERE code = $1 AND expires_at > NOW()
+			"#,
+			code,
+		)
+		.fetch_optional(executor)
+		.record_query("redeem_link_code")
+		.await?;
+
+		Ok(navigator_id.map(NuttyId::new))
+	}
+
+	/// Link a chat user to a navigator, replacing any previous link.
+	pub async fn link_chat_user_tx<'e, E>(
+		&self,
+		executor: E,
+		platform: ChatPlatformKind,
+		chat_user_id: &str,
+		navigator_id: &NuttyId,
+	) -> Result<(), ChatbotRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.chat_links (platform, chat_user_id, navigator_id)
+				VALUES ($1, $2, $3)
+				ON CONFLICT (platform, chat_user_id) DO UPDATE
+				SET navigator_id = EXCLUDED.navigator_id
+			"#,
+			platform.as_str(),
+			chat_user_id,
+			navigator_id.uuid(),
+		)
+		.execute(executor)
+		.record_query("link_chat_user")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Get the navigator that a chat user is linked to.
+	pub async fn get_linked_navigator_tx<'e, E>(
+		&self,
+		executor: E,
+		platform: ChatPlatformKind,
+		chat_user_id: &str,
+	) -> Result<Option<NuttyId>, ChatbotRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let navigator_id = sqlx::query_scalar!(
+			r#"
+				SELECT navigator_id
+				FROM auth.chat_links
+				WHERE platform = $1 AND chat_user_id = $2
+			"#,
+			platform.as_str(),
+			chat_user_id,
+		)
+		.fetch_optional(executor)
+		.record_query("get_linked_navigator")
+		.await?;
+
+		Ok(navigator_id.map(NuttyId::new))
+	}
+
+	/// Get the navigator that a chat user is linked to.
+	pub async fn get_linked_navigator(
+		&self,
+		platform: ChatPlatformKind,
+		chat_user_id: &str,
+	) -> Result<Option<NuttyId>, ChatbotRepositoryError> {
+		self
+			.get_linked_navigator_tx(&self.pool, platform, chat_user_id)
+			.await
+	}
+}
+
+impl Repository for ChatbotRepository {
+	fn pool(&self) -> &sqlx::Pool<Postgres> {
+		&self.pool
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum ChatbotRepositoryError {
+	#[error("Database query failed: {0}")]
+	QueryFailed(#[from] sqlx::error::Error),
+}
@@ -0,0 +1,200 @@
+use async_trait::async_trait;
+
+use crate::integrations::chatbot::models::ChatPlatformKind;
+use crate::integrations::chatbot::models::LinkCode;
+use crate::integrations::chatbot::repository::ChatbotRepository;
+use crate::integrations::chatbot::repository::ChatbotRepositoryError;
+use crate::models::NuttyId;
+use crate::utilities::repository::Repository;
+
+#[derive(Clone)]
+pub struct ChatbotService {
+	repository: ChatbotRepository,
+}
+
+impl ChatbotService {
+	/// Create a new chatbot service with the given repository.
+	pub fn new(repository: ChatbotRepository) -> Self {
+		Self { repository }
+	}
+}
+
+/// Linking chat platform users to navigators.
+/// Implemented by [ChatbotService], and by an in-memory fake in the testkit.
+#[async_trait]
+pub trait ChatbotServiceApi: Send + Sync {
+	/// Issue a code that links the chat user who sends it to the navigator.
+	async fn create_link_code(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<LinkCode, ChatbotServiceError>;
+
+	/// Link a chat user to the navigator that a code was issued to, and
+	/// return that navigator. Each code can only be used once.
+	async fn link_chat_user(
+		&self,
+		platform: ChatPlatformKind,
+		chat_user_id: &str,
+		code: &str,
+	) -> Result<NuttyId, ChatbotServiceError>;
+
+	/// Get the navigator that a chat user is linked to.
+	async fn get_linked_navigator(
+		&self,
+		platform: ChatPlatformKind,
+		chat_user_id: &str,
+	) -> Result<Option<NuttyId>, ChatbotServiceError>;
+}
+
+#[async_trait]
+impl ChatbotServiceApi for ChatbotService {
+	async fn create_link_code(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<LinkCode, ChatbotServiceError> {
+		let link_code = LinkCode::new();
+
+		self
+			.repository
+			.create_link_code(navigator_id, &link_code)
+			.await
+			.map_err(ChatbotServiceError::SaveLinkCode)?;
+
+		Ok(link_code)
+	}
+
+	async fn link_chat_user(
+		&self,
+		platform: ChatPlatformKind,
+		chat_user_id: &str,
+		code: &str,
+	) -> Result<NuttyId, ChatbotServiceError> {
+		self
+			.repository
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					let navigator_id = self
+						.repository
+						.redeem_link_code_tx(tx.conn(), code)
+						.await
+						.map_err(ChatbotServiceError::RedeemLinkCode)?
+						.ok_or(ChatbotServiceError::InvalidLinkCode)?;
+
+					self
+						.repository
+						.link_chat_user_tx(tx.conn(), platform, chat_user_id, &navigator_id)
+						.await
+						.map_err(ChatbotServiceError::SaveLink)?;
+
+					Ok(navigator_id)
+				})
+			})
+			.await
+	}
+
+	async fn get_linked_navigator(
+		&self,
+		platform: ChatPlatformKind,
+		chat_user_id: &str,
+	) -> Result<Option<NuttyId>, ChatbotServiceError> {
+		self
+			.repository
+			.get_linked_navigator(platform, chat_user_id)
+			.await
+			.map_err(ChatbotServiceError::FetchLink)
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ChatbotServiceError {
+	#[error("Failed to save link code: {0}")]
+	SaveLinkCode(#[source] ChatbotRepositoryError),
+
+	#[error("Failed to redeem link code: {0}")]
+	RedeemLinkCode(#[source] ChatbotRepositoryError),
+
+	#[error("The link code is invalid or has expired")]
+	InvalidLinkCode,
+
+	#[error("Failed to save chat link: {0}")]
+	SaveLink(#[source] ChatbotRepositoryError),
+
+	#[error("Failed to fetch chat link: {0}")]
+	FetchLink(#[source] ChatbotRepositoryError),
+
+	#[error("Database error: {0}")]
+	Database(#[from] sqlx::Error),
+}
+
+#[cfg(test)]
+mod tests {
+	use sqlx::Pool;
+	use sqlx::Postgres;
+	use sqlx::postgres::PgPoolOptions;
+
+	use super::*;
+
+	async fn connect_to_test_database() -> Pool<Postgres> {
+		let database_url = std::env::var("DATABASE_URL").unwrap();
+
+		PgPoolOptions::new()
+			.max_connections(5)
+			.connect(&database_url)
+			.await
+			.expect("Failed to connect to test database")
+	}
+
+	#[tokio::test]
+	async fn test_link_chat_user() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let service = ChatbotService::new(ChatbotRepository::new(pool.clone()));
+
+		// Arrange: Create a navigator.
+		let navigator_id = NuttyId::now();
+		let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+				VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+			"#,
+			navigator_id.uuid(),
+			navigator_id.nid(),
+			navigator_name,
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to create test navigator");
+
+		let chat_user_id = NuttyId::now().nid();
+
+		// Act: Issue a code, and redeem it from a chat.
+		let link_code = service
+			.create_link_code(&navigator_id)
+			.await
+			.expect("Failed to create link code");
+
+		let linked_id = service
+			.link_chat_user(ChatPlatformKind::Telegram, &chat_user_id, &link_code.code)
+			.await
+			.expect("Failed to link chat user");
+
+		// Assert: The chat user is linked to the navigator.
+		assert_eq!(linked_id, navigator_id);
+
+		let linked_id = service
+			.get_linked_navigator(ChatPlatformKind::Telegram, &chat_user_id)
+			.await
+			.expect("Failed to get linked navigator");
+
+		assert_eq!(linked_id, Some(navigator_id));
+
+		// Assert: Codes can't be reused.
+		let result = service
+			.link_chat_user(ChatPlatformKind::Telegram, "someone_else", &link_code.code)
+			.await;
+
+		assert!(matches!(result, Err(ChatbotServiceError::InvalidLinkCode)));
+	}
+}
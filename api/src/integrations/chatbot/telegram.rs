@@ -0,0 +1,181 @@
+use std::fmt;
+
+use axum::http::HeaderMap;
+use serde::Deserialize;
+use serde_json::Value;
+use serde_json::json;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::integrations::chatbot::models::ChatMessage;
+use crate::integrations::chatbot::models::ChatPlatformKind;
+use crate::integrations::chatbot::models::ChatbotError;
+use crate::integrations::chatbot::platform::ChatPlatform;
+use crate::utilities::secrets::Secret;
+
+/// The header carrying the secret token given to Telegram's `setWebhook`.
+pub const SECRET_TOKEN_HEADER: &str = "x-telegram-bot-api-secret-token";
+
+/// A Telegram bot, receiving updates through a webhook registered with a
+/// secret token. Disabled unless a token is configured.
+#[derive(Clone, Default)]
+pub struct Telegram {
+	secret_token: Option<String>,
+}
+
+impl Telegram {
+	/// Create a bot that accepts updates carrying the secret token.
+	pub fn new(secret_token: Option<String>) -> Self {
+		Self {
+			secret_token: secret_token.filter(|token| !token.is_empty()),
+		}
+	}
+
+	/// Read the secret token from `TELEGRAM_WEBHOOK_SECRET`, if set.
+	pub fn from_env() -> Self {
+		Self::new(Secret::from_env("TELEGRAM_WEBHOOK_SECRET").map(Secret::into_string))
+	}
+}
+
+impl fmt::Debug for Telegram {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("Telegram([REDACTED])")
+	}
+}
+
+/// An update from Telegram, of which only new messages are read.
+#[derive(Debug, Deserialize)]
+struct Update {
+	message: Option<Message>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Message {
+	chat: Chat,
+	from: Option<User>,
+	text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Chat {
+	id: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+	id: i64,
+
+	#[serde(default)]
+	is_bot: bool,
+}
+
+impl ChatPlatform for Telegram {
+	fn kind(&self) -> ChatPlatformKind {
+		ChatPlatformKind::Telegram
+	}
+
+	fn verify(&self, headers: &HeaderMap, _body: &[u8]) -> bool {
+		let Some(expected) = &self.secret_token else {
+			return false;
+		};
+
+		let token = headers
+			.get(SECRET_TOKEN_HEADER)
+			.and_then(|value| value.to_str().ok())
+			.unwrap_or_default();
+
+		// Compare digests, so that the comparison takes the same time.
+		let expected = Sha256::digest(expected.as_bytes());
+		let token = Sha256::digest(token.as_bytes());
+
+		expected
+			.iter()
+			.zip(token.iter())
+			.fold(0, |difference, (a, b)| difference | (a ^ b))
+			== 0
+	}
+
+	fn parse_message(&self, body: &[u8]) -> Result<Option<ChatMessage>, ChatbotError> {
+		let update: Update = serde_json::from_slice(body).map_err(ChatbotError::InvalidPayload)?;
+
+		let message = update.message.and_then(|message| {
+			let from = message.from.filter(|user| !user.is_bot)?;
+			let text = message.text.filter(|text| !text.trim().is_empty())?;
+
+			Some(ChatMessage {
+				chat_id: message.chat.id.to_string(),
+				user_id: from.id.to_string(),
+				text,
+			})
+		});
+
+		Ok(message)
+	}
+
+	fn reply(&self, message: &ChatMessage, text: &str) -> Value {
+		json!({
+			"method": "sendMessage",
+			"chat_id": message.chat_id,
+			"text": text,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use axum::http::HeaderValue;
+
+	use super::*;
+
+	#[test]
+	fn test_verify() {
+		let mut headers = HeaderMap::new();
+		headers.insert(SECRET_TOKEN_HEADER, HeaderValue::from_static("s3cret"));
+
+		assert!(Telegram::new(Some("s3cret".to_string())).verify(&headers, b"{}"));
+		assert!(!Telegram::new(Some("other".to_string())).verify(&headers, b"{}"));
+		assert!(!Telegram::default().verify(&headers, b"{}"));
+		assert!(!Telegram::new(Some("s3cret".to_string())).verify(&HeaderMap::new(), b"{}"));
+	}
+
+	#[test]
+	fn test_parse_message() {
+		let telegram = Telegram::default();
+
+		let body = br#"{
+			"update_id": 1,
+			"message": {
+				"message_id": 7,
+				"chat": { "id": -42, "type": "private" },
+				"from": { "id": 99, "is_bot": false, "first_name": "Alice" },
+				"text": "Buy oat milk"
+			}
+		}"#;
+
+		let message = telegram.parse_message(body).unwrap().unwrap();
+
+		assert_eq!(
+			message,
+			ChatMessage {
+				chat_id: "-42".to_string(),
+				user_id: "99".to_string(),
+				text: "Buy oat milk".to_string(),
+			}
+		);
+
+		assert_eq!(
+			telegram.reply(&message, "Noted."),
+			json!({ "method": "sendMessage", "chat_id": "-42", "text": "Noted." })
+		);
+
+		// Updates without text, and messages from bots, are ignored.
+		let sticker =
+			br#"{ "update_id": 2, "message": { "chat": { "id": 1 }, "from": { "id": 2 } } }"#;
+		assert!(telegram.parse_message(sticker).unwrap().is_none());
+
+		let bot = br#"{ "update_id": 3, "message": { "chat": { "id": 1 }, "from": { "id": 2, "is_bot": true }, "text": "Hi" } }"#;
+		assert!(telegram.parse_message(bot).unwrap().is_none());
+
+		assert!(telegram.parse_message(b"not json").is_err());
+	}
+}
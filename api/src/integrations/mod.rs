@@ -0,0 +1,2 @@
+pub mod chatbot;
+pub mod webhooks;
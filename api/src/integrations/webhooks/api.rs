@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use axum::Json;
+use axum::Router;
+use axum::extract::Path;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::delete;
+use axum::routing::get;
+
+use crate::integrations::webhooks::models::WebhookSubscription;
+use crate::integrations::webhooks::models::WebhookSubscriptionRequest;
+use crate::integrations::webhooks::service::WebhookServiceError;
+use crate::models::NuttyId;
+use crate::utilities::api::response::Error;
+use crate::utilities::api::response::Response;
+use crate::utilities::api::session::Session;
+use crate::utilities::api::state::AppState;
+
+/// The router for webhook subscription API endpoints.
+pub fn router(app_state: Arc<AppState>) -> Router {
+	Router::new()
+		.route(
+			"/integrations/webhooks",
+			get(list_subscriptions_handler).post(create_subscription_handler),
+		)
+		.route(
+			"/integrations/webhooks/{subscription_id}",
+			delete(delete_subscription_handler),
+		)
+		.with_state(app_state)
+}
+
+/// Build an error response.
+fn error_response<T>(
+	status: StatusCode,
+	summary: &str,
+	error: WebhookApiError,
+) -> (StatusCode, Json<Response<T>>) {
+	let error = Error::from_error(&error).with_summary(summary);
+
+	(
+		status,
+		Json(Response::Error {
+			errors: vec![error],
+		}),
+	)
+}
+
+/// An API handler for listing the navigator's webhook subscriptions.
+async fn list_subscriptions_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+) -> (StatusCode, Json<Response<WebhookSubscription>>) {
+	match state
+		.webhook_service
+		.list_subscriptions(navigator.nutty_id())
+		.await
+	{
+		Ok(subscriptions) => (
+			StatusCode::OK,
+			Json(Response::Multiple {
+				data: subscriptions,
+			}),
+		),
+
+		Err(error) => error_response(
+			StatusCode::INTERNAL_SERVER_ERROR,
+			"Failed to list webhook subscriptions.",
+			WebhookApiError::ListSubscriptions(error),
+		),
+	}
+}
+
+/// An API handler for subscribing a URL to events. The response carries the
+/// secret that deliveries are signed with, which isn't shown again.
+async fn create_subscription_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Json(payload): Json<WebhookSubscriptionRequest>,
+) -> (StatusCode, Json<Response<WebhookSubscription>>) {
+	match state
+		.webhook_service
+		.create_subscription(navigator.nutty_id(), &payload)
+		.await
+	{
+		Ok(subscription) => (
+			StatusCode::CREATED,
+			Json(Response::Single {
+				data: Some(subscription),
+			}),
+		),
+
+		Err(error) => {
+			let status = match error {
+				WebhookServiceError::InvalidSubscription(_) => StatusCode::BAD_REQUEST,
+				WebhookServiceError::TooManySubscriptions => StatusCode::CONFLICT,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			error_response(
+				status,
+				"Failed to create webhook subscription.",
+				WebhookApiError::CreateSubscription(error),
+			)
+		}
+	}
+}
+
+/// An API handler for deleting one of the navigator's webhook subscriptions.
+async fn delete_subscription_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(subscription_id): Path<NuttyId>,
+) -> (StatusCode, Json<Response<()>>) {
+	match state
+		.webhook_service
+		.delete_subscription(navigator.nutty_id(), &subscription_id)
+		.await
+	{
+		Ok(()) => (StatusCode::OK, Json(Response::Single { data: None })),
+
+		Err(error) => {
+			let status = match error {
+				WebhookServiceError::SubscriptionNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			error_response(
+				status,
+				"Failed to delete webhook subscription.",
+				WebhookApiError::DeleteSubscription(error),
+			)
+		}
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookApiError {
+	#[error("Failed to list webhook subscriptions: {0}")]
+	ListSubscriptions(WebhookServiceError),
+
+	#[error("Failed to create webhook subscription: {0}")]
+	CreateSubscription(WebhookServiceError),
+
+	#[error("Failed to delete webhook subscription: {0}")]
+	DeleteSubscription(WebhookServiceError),
+}
@@ -0,0 +1,275 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::header::CONTENT_TYPE;
+use reqwest::header::HeaderMap;
+use reqwest::header::HeaderName;
+use reqwest::header::HeaderValue;
+use thiserror::Error;
+use url::Url;
+
+use crate::content::service::ContentServiceApi;
+use crate::integrations::webhooks::models::PagePublished;
+use crate::integrations::webhooks::models::WebhookEvent;
+use crate::integrations::webhooks::models::WebhookEventType;
+use crate::integrations::webhooks::models::WebhookSubscription;
+use crate::integrations::webhooks::service::WebhookServiceApi;
+use crate::models::BlockContent;
+use crate::models::DissociatedNuttyId;
+use crate::models::NuttyId;
+use crate::models::nutty_id::PERMALINK_BASE_URL;
+use crate::models::outline::OutlineEntry;
+use crate::utilities::api::webhook::SIGNATURE_HEADER;
+use crate::utilities::api::webhook::WebhookSecret;
+use crate::utilities::http::HttpClient;
+use crate::utilities::http::HttpError;
+use crate::utilities::http::HttpPolicy;
+
+/// The header naming a delivery's event type.
+pub const EVENT_HEADER: &str = "x-webhook-event";
+
+/// How long a subscriber has to accept a delivery.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Delivers events to the URLs subscribed to them. Each delivery is a JSON
+/// POST, signed with the subscription's secret like inbound webhooks are,
+/// and is attempted once.
+#[derive(Clone, Default)]
+pub struct WebhookSender {
+	/// The HTTP client, or `None` if delivery is disabled.
+	client: Option<HttpClient>,
+}
+
+impl WebhookSender {
+	/// Create an enabled webhook sender.
+	pub fn new() -> Self {
+		let client = HttpClient::new(HttpPolicy {
+			timeout: DELIVERY_TIMEOUT,
+			..Default::default()
+		});
+
+		Self {
+			client: Some(client),
+		}
+	}
+
+	/// Create a webhook sender unless `WEBHOOK_DELIVERY` is "0" or "false".
+	pub fn from_env() -> Self {
+		let disabled = std::env::var("WEBHOOK_DELIVERY").is_ok_and(|v| v == "0" || v == "false");
+
+		match disabled {
+			true => Self::default(),
+			false => Self::new(),
+		}
+	}
+
+	/// Check whether events are delivered at all.
+	pub fn is_enabled(&self) -> bool {
+		self.client.is_some()
+	}
+
+	/// Deliver an event to a subscription.
+	pub async fn send(
+		&self,
+		subscription: &WebhookSubscription,
+		event: &WebhookEvent,
+	) -> Result<(), WebhookDeliveryError> {
+		let Some(client) = &self.client else {
+			return Ok(());
+		};
+
+		let url = Url::parse(&subscription.url).map_err(WebhookDeliveryError::InvalidUrl)?;
+		let body = serde_json::to_vec(event).map_err(WebhookDeliveryError::Serialize)?;
+		let headers = signed_headers(subscription, event.event_type, &body);
+
+		client
+			.post(&url, headers, body)
+			.await
+			.and_then(|response| response.error_for_status())
+			.map_err(WebhookDeliveryError::Request)?;
+
+		Ok(())
+	}
+}
+
+/// Build the headers of a delivery: its content type, event type, and the
+/// body's signature, keyed by the subscription's secret as it was shown.
+fn signed_headers(
+	subscription: &WebhookSubscription,
+	event_type: WebhookEventType,
+	body: &[u8],
+) -> HeaderMap {
+	let mut headers = HeaderMap::new();
+
+	headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+	headers.insert(
+		HeaderName::from_static(EVENT_HEADER),
+		HeaderValue::from_static(event_type.as_str()),
+	);
+
+	let secret = subscription.secret.clone().map(String::into_bytes);
+
+	if let Some(signature) = WebhookSecret::new(secret).sign(body)
+		&& let Ok(signature) = HeaderValue::from_str(&signature)
+	{
+		headers.insert(HeaderName::from_static(SIGNATURE_HEADER), signature);
+	}
+
+	headers
+}
+
+/// Deliver a `page.published` event for a revision in the background, if
+/// the revision is of a page. Failures are logged rather than retried.
+pub fn spawn_page_published(
+	webhook_service: Arc<dyn WebhookServiceApi>,
+	content_service: Arc<dyn ContentServiceApi>,
+	sender: WebhookSender,
+	published_by: NuttyId,
+	revision_id: NuttyId,
+	block_id: DissociatedNuttyId,
+) {
+	if !sender.is_enabled() {
+		return;
+	}
+
+	tokio::spawn(async move {
+		let event = match page_published(
+			content_service.as_ref(),
+			published_by,
+			revision_id,
+			block_id,
+		)
+		.await
+		{
+			Some(event) => event,
+			None => return,
+		};
+
+		deliver(
+			webhook_service.as_ref(),
+			content_service.as_ref(),
+			&sender,
+			&event,
+		)
+		.await;
+	});
+}
+
+/// Build the `page.published` event for a block's published revision, or
+/// nothing if the block isn't a page.
+async fn page_published(
+	content_service: &dyn ContentServiceApi,
+	published_by: NuttyId,
+	revision_id: NuttyId,
+	block_id: DissociatedNuttyId,
+) -> Option<WebhookEvent> {
+	let revision = match content_service.get_published_revision(&block_id).await {
+		Ok(revision) => revision?,
+
+		Err(error) => {
+			eprintln!(
+				"Failed to fetch published revision of {}: {error}",
+				block_id.nid()
+			);
+			return None;
+		}
+	};
+
+	if !matches!(revision.content, BlockContent::Page { .. }) {
+		return None;
+	}
+
+	let block_id = revision.block_id;
+
+	Some(WebhookEvent::page_published(PagePublished {
+		block_id,
+		revision_id,
+		published_by,
+		title: OutlineEntry::title_of(&revision.content),
+		permalink: block_id.permalink(),
+		html_url: format!("{PERMALINK_BASE_URL}/content-block/{}/html", block_id.nid()),
+	}))
+}
+
+/// Send an event to every subscription that wants it and whose navigator
+/// can read the page.
+async fn deliver(
+	webhook_service: &dyn WebhookServiceApi,
+	content_service: &dyn ContentServiceApi,
+	sender: &WebhookSender,
+	event: &WebhookEvent,
+) {
+	let subscriptions = match webhook_service.list_subscribers(event.event_type).await {
+		Ok(subscriptions) => subscriptions,
+
+		Err(error) => {
+			eprintln!("Failed to list webhook subscribers: {error}");
+			return;
+		}
+	};
+
+	let block_id = event.data.block_id.dissociate();
+
+	for subscription in subscriptions
+		.iter()
+		.filter(|subscription| subscription.wants(event.event_type))
+	{
+		let can_read = content_service
+			.check_content_block_access(&subscription.navigator_id, &block_id)
+			.await
+			.unwrap_or(false);
+
+		if !can_read {
+			continue;
+		}
+
+		if let Err(error) = sender.send(subscription, event).await {
+			eprintln!(
+				"Failed to deliver {} webhook to {}: {error}",
+				event.event_type.as_str(),
+				subscription.url,
+			);
+		}
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum WebhookDeliveryError {
+	#[error("Invalid webhook URL: {0}")]
+	InvalidUrl(#[source] url::ParseError),
+
+	#[error("Failed to serialize webhook event: {0}")]
+	Serialize(#[source] serde_json::Error),
+
+	#[error("Webhook request failed: {0}")]
+	Request(#[source] HttpError),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::integrations::webhooks::models::WebhookSubscriptionRequest;
+
+	#[test]
+	fn test_signed_headers() {
+		let request = WebhookSubscriptionRequest {
+			url: "https://builder.example/hooks".to_string(),
+			event_types: vec![WebhookEventType::PagePublished],
+		};
+
+		let subscription = WebhookSubscription::new(NuttyId::now(), &request).unwrap();
+		let body = br#"{"type":"page.published"}"#;
+
+		let headers = signed_headers(&subscription, WebhookEventType::PagePublished, body);
+
+		// Assert: The event type is named, and the body is signed with the
+		// subscription's secret.
+		assert_eq!(headers[EVENT_HEADER], "page.published");
+		assert_eq!(headers[CONTENT_TYPE], "application/json");
+
+		let secret = WebhookSecret::new(subscription.secret.map(String::into_bytes));
+		let signature = headers[SIGNATURE_HEADER].to_str().unwrap();
+
+		assert!(secret.verify(body, signature));
+	}
+}
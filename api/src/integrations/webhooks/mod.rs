@@ -0,0 +1,5 @@
+pub mod api;
+pub mod delivery;
+pub mod models;
+pub mod repository;
+pub mod service;
@@ -0,0 +1,245 @@
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::rand_core::RngCore;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+use url::Url;
+
+use crate::models::NuttyId;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+
+/// The most webhook subscriptions that a navigator can have.
+pub const MAX_SUBSCRIPTIONS: usize = 10;
+
+/// The number of random bytes in a subscription's secret.
+const SECRET_BYTES: usize = 32;
+
+/// A kind of event that webhooks can be subscribed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WebhookEventType {
+	/// A page's revision was published.
+	#[serde(rename = "page.published")]
+	PagePublished,
+}
+
+impl WebhookEventType {
+	/// Every event type.
+	pub const ALL: [WebhookEventType; 1] = [WebhookEventType::PagePublished];
+
+	/// Get the event type's name, as it's stored and sent.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			WebhookEventType::PagePublished => "page.published",
+		}
+	}
+
+	/// Get an event type by its name.
+	pub fn parse(name: &str) -> Result<Self, WebhookError> {
+		Self::ALL
+			.into_iter()
+			.find(|candidate| candidate.as_str() == name)
+			.ok_or_else(|| WebhookError::UnknownEventType(name.to_string()))
+	}
+}
+
+/// A URL that a navigator has asked to be sent events at, filtered to the
+/// event types they want.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookSubscription {
+	pub id: NuttyId,
+	pub navigator_id: NuttyId,
+	pub url: String,
+	pub event_types: Vec<WebhookEventType>,
+	pub created_at: DateTimeRfc3339,
+
+	/// The secret that deliveries are signed with. It's only shown when the
+	/// subscription is created.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub secret: Option<String>,
+}
+
+impl WebhookSubscription {
+	/// Create a subscription for a navigator, with a new secret.
+	pub fn new(
+		navigator_id: NuttyId,
+		request: &WebhookSubscriptionRequest,
+	) -> Result<Self, WebhookError> {
+		let url = request.parse_url()?;
+
+		if request.event_types.is_empty() {
+			return Err(WebhookError::NoEventTypes);
+		}
+
+		let mut event_types = request.event_types.clone();
+		event_types.sort_by_key(WebhookEventType::as_str);
+		event_types.dedup();
+
+		let mut secret = [0u8; SECRET_BYTES];
+		OsRng.fill_bytes(&mut secret);
+
+		Ok(Self {
+			id: NuttyId::now(),
+			navigator_id,
+			url: url.to_string(),
+			event_types,
+			created_at: DateTimeRfc3339::new(Utc::now().fixed_offset()),
+			secret: Some(secret.iter().map(|byte| format!("{byte:02x}")).collect()),
+		})
+	}
+
+	/// Check whether the subscription wants an event type.
+	pub fn wants(&self, event_type: WebhookEventType) -> bool {
+		self.event_types.contains(&event_type)
+	}
+}
+
+/// Request payload for subscribing to webhook events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookSubscriptionRequest {
+	/// The URL to send events to.
+	pub url: String,
+
+	/// The event types to send.
+	pub event_types: Vec<WebhookEventType>,
+}
+
+impl WebhookSubscriptionRequest {
+	/// Parse the subscribed URL, which must be on the web.
+	pub fn parse_url(&self) -> Result<Url, WebhookError> {
+		let url = Url::parse(self.url.trim()).map_err(WebhookError::InvalidUrl)?;
+
+		match url.scheme() {
+			"http" | "https" if url.has_host() => Ok(url),
+			_ => Err(WebhookError::UnsupportedUrl(url.to_string())),
+		}
+	}
+}
+
+/// An event, as it's sent to the subscribed URLs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookEvent {
+	/// A unique ID for the event, which receivers can deduplicate by.
+	pub id: NuttyId,
+
+	#[serde(rename = "type")]
+	pub event_type: WebhookEventType,
+
+	pub occurred_at: DateTimeRfc3339,
+	pub data: PagePublished,
+}
+
+impl WebhookEvent {
+	/// Create an event for a published page.
+	pub fn page_published(data: PagePublished) -> Self {
+		Self {
+			id: NuttyId::now(),
+			event_type: WebhookEventType::PagePublished,
+			occurred_at: DateTimeRfc3339::new(Utc::now().fixed_offset()),
+			data,
+		}
+	}
+}
+
+/// The data of a `page.published` event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PagePublished {
+	/// The Nutty ID of the page.
+	pub block_id: NuttyId,
+
+	/// The Nutty ID of the published revision.
+	pub revision_id: NuttyId,
+
+	/// The navigator who published it.
+	pub published_by: NuttyId,
+
+	/// The page's title, as published.
+	pub title: String,
+
+	/// The page's permalink.
+	pub permalink: String,
+
+	/// Where the page's published HTML is rendered.
+	pub html_url: String,
+}
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+	#[error("Invalid URL: {0}")]
+	InvalidUrl(url::ParseError),
+
+	#[error("Only web URLs can be subscribed: {0}")]
+	UnsupportedUrl(String),
+
+	#[error("At least one event type must be subscribed to")]
+	NoEventTypes,
+
+	#[error("Unknown event type: '{0}'")]
+	UnknownEventType(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_new_subscription() {
+		let navigator_id = NuttyId::now();
+
+		let request = WebhookSubscriptionRequest {
+			url: " https://builder.example/hooks ".to_string(),
+			event_types: vec![
+				WebhookEventType::PagePublished,
+				WebhookEventType::PagePublished,
+			],
+		};
+
+		let subscription = WebhookSubscription::new(navigator_id, &request).unwrap();
+
+		// Assert: The URL is normalized, the event types deduplicated, and a
+		// secret generated.
+		assert_eq!(subscription.url, "https://builder.example/hooks");
+		assert_eq!(
+			subscription.event_types,
+			vec![WebhookEventType::PagePublished]
+		);
+		assert_eq!(subscription.secret.as_ref().map(String::len), Some(64));
+		assert!(subscription.wants(WebhookEventType::PagePublished));
+
+		// Assert: Only web URLs, with event types, can be subscribed.
+		let request = WebhookSubscriptionRequest {
+			url: "ftp://builder.example".to_string(),
+			event_types: vec![WebhookEventType::PagePublished],
+		};
+
+		assert!(matches!(
+			WebhookSubscription::new(navigator_id, &request),
+			Err(WebhookError::UnsupportedUrl(_))
+		));
+
+		let request = WebhookSubscriptionRequest {
+			url: "https://builder.example".to_string(),
+			event_types: vec![],
+		};
+
+		assert!(matches!(
+			WebhookSubscription::new(navigator_id, &request),
+			Err(WebhookError::NoEventTypes)
+		));
+	}
+
+	#[test]
+	fn test_event_type_names() {
+		for event_type in WebhookEventType::ALL {
+			let name = serde_json::to_value(event_type).unwrap();
+
+			assert_eq!(name, event_type.as_str());
+			assert_eq!(
+				WebhookEventType::parse(event_type.as_str()).unwrap(),
+				event_type
+			);
+		}
+
+		assert!(WebhookEventType::parse("page.deleted").is_err());
+	}
+}
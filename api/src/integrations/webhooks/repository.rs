@@ -0,0 +1,263 @@
+use chrono::DateTime;
+use chrono::Utc;
+use sqlx::Executor;
+use sqlx::Postgres;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::integrations::webhooks::models::WebhookEventType;
+use crate::integrations::webhooks::models::WebhookSubscription;
+use crate::models::NuttyId;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+use crate::utilities::query_metrics::RecordQuery;
+use crate::utilities::repository::Repository;
+
+/// A repository for the webhooks that navigators subscribe to.
+/// Objects are stored in PostgreSQL.
+#[derive(Debug, Clone)]
+pub struct WebhookRepository {
+	/// The PostgreSQL database pool.
+	pool: sqlx::Pool<Postgres>,
+}
+
+/// A stored subscription, before its event types are parsed.
+struct SubscriptionRow {
+	id: Uuid,
+	navigator_id: Uuid,
+	url: String,
+	event_types: Vec<String>,
+	secret: String,
+	created_at: DateTime<Utc>,
+}
+
+impl SubscriptionRow {
+	/// Convert into a subscription. Event types that are no longer known
+	/// are left out, and the secret is only kept if asked for.
+	fn into_subscription(self, with_secret: bool) -> WebhookSubscription {
+		WebhookSubscription {
+			id: NuttyId::new(self.id),
+			navigator_id: NuttyId::new(self.navigator_id),
+			url: self.url,
+			event_types: self
+				.event_types
+				.iter()
+				.filter_map(|name| WebhookEventType::parse(name).ok())
+				.collect(),
+			created_at: DateTimeRfc3339::new(self.created_at.fixed_offset()),
+			secret: with_secret.then_some(self.secret),
+		}
+	}
+}
+
+impl WebhookRepository {
+	/// Create a new webhook repository.
+	pub fn new(pool: sqlx::Pool<Postgres>) -> Self {
+		Self { pool }
+	}
+
+	/// Save a new subscription, with its secret.
+	pub async fn create_subscription_tx<'e, E>(
+		&self,
+		executor: E,
+		subscription: &WebhookSubscription,
+	) -> Result<(), WebhookRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let event_types: Vec<String> = subscription
+			.event_types
+			.iter()
+			.map(|event_type| event_type.as_str().to_string())
+			.collect();
+
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.webhook_subscriptions (
+					id, navigator_id, url, event_types, secret, created_at
+				)
+				VALUES ($1, $2, $3, $4, $5, $6)
+			"#,
+			subscription.id.uuid(),
+			subscription.navigator_id.uuid(),
+			subscription.url,
+			&event_types,
+			subscription.secret.as_deref().unwrap_or_default(),
+			subscription.created_at.inner(),
+		)
+		.execute(executor)
+		.record_query("create_webhook_subscription")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Lock a navigator's row until the transaction ends, so that
+	/// concurrent changes to their subscriptions happen one at a time.
+	pub async fn lock_navigator_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+	) -> Result<(), WebhookRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		sqlx::query!(
+			"SELECT id FROM auth.navigators WHERE id = $1 FOR UPDATE",
+			navigator_id.uuid(),
+		)
+		.fetch_optional(executor)
+		.record_query("lock_webhook_navigator")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Count a navigator's subscriptions.
+	pub async fn count_subscriptions_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+	) -> Result<usize, WebhookRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let count = sqlx::query_scalar!(
+			r#"
+				SELECT COUNT(*) AS "count!"
+				FROM auth.webhook_subscriptions
+				WHERE navigator_id = $1
+			"#,
+			navigator_id.uuid(),
+		)
+		.fetch_one(executor)
+		.record_query("count_webhook_subscriptions")
+		.await?;
+
+		Ok(count as usize)
+	}
+
+	/// List a navigator's subscriptions, oldest first, without their secrets.
+	pub async fn list_subscriptions_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<WebhookSubscription>, WebhookRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let rows = sqlx::query_as!(
+			SubscriptionRow,
+			r#"
+				SELECT id, navigator_id, url, event_types, secret, created_at
+				FROM auth.webhook_subscriptions
+				WHERE navigator_id = $1
+				ORDER BY created_at, id
+			"#,
+			navigator_id.uuid(),
+		)
+		.fetch_all(executor)
+		.record_query("list_webhook_subscriptions")
+		.await?;
+
+		Ok(rows
+			.into_iter()
+			.map(|row| row.into_subscription(false))
+			.collect())
+	}
+
+	/// List a navigator's subscriptions, oldest first, without their secrets.
+	pub async fn list_subscriptions(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<WebhookSubscription>, WebhookRepositoryError> {
+		self.list_subscriptions_tx(&self.pool, navigator_id).await
+	}
+
+	/// List every subscription to an event type, with their secrets, for
+	/// delivering it.
+	pub async fn list_subscriptions_for_event_tx<'e, E>(
+		&self,
+		executor: E,
+		event_type: WebhookEventType,
+	) -> Result<Vec<WebhookSubscription>, WebhookRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let rows = sqlx::query_as!(
+			SubscriptionRow,
+			r#"
+				SELECT id, navigator_id, url, event_types, secret, created_at
+				FROM auth.webhook_subscriptions
+				WHERE event_types @> ARRAY[$1]
+				ORDER BY created_at, id
+			"#,
+			event_type.as_str(),
+		)
+		.fetch_all(executor)
+		.record_query("list_webhook_subscriptions_for_event")
+		.await?;
+
+		Ok(rows
+			.into_iter()
+			.map(|row| row.into_subscription(true))
+			.collect())
+	}
+
+	/// List every subscription to an event type, with their secrets.
+	pub async fn list_subscriptions_for_event(
+		&self,
+		event_type: WebhookEventType,
+	) -> Result<Vec<WebhookSubscription>, WebhookRepositoryError> {
+		self
+			.list_subscriptions_for_event_tx(&self.pool, event_type)
+			.await
+	}
+
+	/// Delete one of a navigator's subscriptions. Returns whether it existed.
+	pub async fn delete_subscription_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+		subscription_id: &NuttyId,
+	) -> Result<bool, WebhookRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let result = sqlx::query!(
+			r#"
+				DELETE FROM auth.webhook_subscriptions
+				WHERE id = $1 AND navigator_id = $2
+			"#,
+			subscription_id.uuid(),
+			navigator_id.uuid(),
+		)
+		.execute(executor)
+		.record_query("delete_webhook_subscription")
+		.await?;
+
+		Ok(result.rows_affected() > 0)
+	}
+
+	/// Delete one of a navigator's subscriptions. Returns whether it existed.
+	pub async fn delete_subscription(
+		&self,
+		navigator_id: &NuttyId,
+		subscription_id: &NuttyId,
+	) -> Result<bool, WebhookRepositoryError> {
+		self
+			.delete_subscription_tx(&self.pool, navigator_id, subscription_id)
+			.await
+	}
+}
+
+impl Repository for WebhookRepository {
+	fn pool(&self) -> &sqlx::Pool<Postgres> {
+		&self.pool
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum WebhookRepositoryError {
+	#[error("Database query failed: {0}")]
+	QueryFailed(#[from] sqlx::error::Error),
+}
@@ -0,0 +1,280 @@
+use async_trait::async_trait;
+
+use crate::integrations::webhooks::models::MAX_SUBSCRIPTIONS;
+use crate::integrations::webhooks::models::WebhookError;
+use crate::integrations::webhooks::models::WebhookEventType;
+use crate::integrations::webhooks::models::WebhookSubscription;
+use crate::integrations::webhooks::models::WebhookSubscriptionRequest;
+use crate::integrations::webhooks::repository::WebhookRepository;
+use crate::integrations::webhooks::repository::WebhookRepositoryError;
+use crate::models::NuttyId;
+use crate::utilities::repository::Repository;
+
+#[derive(Clone)]
+pub struct WebhookService {
+	repository: WebhookRepository,
+}
+
+impl WebhookService {
+	/// Create a new webhook service with the given repository.
+	pub fn new(repository: WebhookRepository) -> Self {
+		Self { repository }
+	}
+}
+
+/// Managing the webhooks that navigators subscribe to.
+/// Implemented by [WebhookService], and by an in-memory fake in the testkit.
+#[async_trait]
+pub trait WebhookServiceApi: Send + Sync {
+	/// Subscribe a URL to events for a navigator. The returned subscription
+	/// carries the secret that its deliveries are signed with.
+	async fn create_subscription(
+		&self,
+		navigator_id: &NuttyId,
+		request: &WebhookSubscriptionRequest,
+	) -> Result<WebhookSubscription, WebhookServiceError>;
+
+	/// List a navigator's subscriptions, without their secrets.
+	async fn list_subscriptions(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<WebhookSubscription>, WebhookServiceError>;
+
+	/// Delete one of a navigator's subscriptions.
+	async fn delete_subscription(
+		&self,
+		navigator_id: &NuttyId,
+		subscription_id: &NuttyId,
+	) -> Result<(), WebhookServiceError>;
+
+	/// List every subscription to an event type, with their secrets, for
+	/// delivering it.
+	async fn list_subscribers(
+		&self,
+		event_type: WebhookEventType,
+	) -> Result<Vec<WebhookSubscription>, WebhookServiceError>;
+}
+
+#[async_trait]
+impl WebhookServiceApi for WebhookService {
+	async fn create_subscription(
+		&self,
+		navigator_id: &NuttyId,
+		request: &WebhookSubscriptionRequest,
+	) -> Result<WebhookSubscription, WebhookServiceError> {
+		let subscription = WebhookSubscription::new(*navigator_id, request)
+			.map_err(WebhookServiceError::InvalidSubscription)?;
+		let subscription = &subscription;
+
+		self
+			.repository
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					// Lock the navigator's row so that concurrent requests can't
+					// both slip under the limit.
+					self
+						.repository
+						.lock_navigator_tx(tx.conn(), navigator_id)
+						.await
+						.map_err(WebhookServiceError::SaveSubscription)?;
+
+					let count = self
+						.repository
+						.count_subscriptions_tx(tx.conn(), navigator_id)
+						.await
+						.map_err(WebhookServiceError::SaveSubscription)?;
+
+					if count >= MAX_SUBSCRIPTIONS {
+						return Err(WebhookServiceError::TooManySubscriptions);
+					}
+
+					self
+						.repository
+						.create_subscription_tx(tx.conn(), subscription)
+						.await
+						.map_err(WebhookServiceError::SaveSubscription)?;
+
+					Ok(subscription.clone())
+				})
+			})
+			.await
+	}
+
+	async fn list_subscriptions(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<WebhookSubscription>, WebhookServiceError> {
+		self
+			.repository
+			.list_subscriptions(navigator_id)
+			.await
+			.map_err(WebhookServiceError::FetchSubscriptions)
+	}
+
+	async fn delete_subscription(
+		&self,
+		navigator_id: &NuttyId,
+		subscription_id: &NuttyId,
+	) -> Result<(), WebhookServiceError> {
+		let deleted = self
+			.repository
+			.delete_subscription(navigator_id, subscription_id)
+			.await
+			.map_err(WebhookServiceError::DeleteSubscription)?;
+
+		if deleted {
+			Ok(())
+		} else {
+			Err(WebhookServiceError::SubscriptionNotFound)
+		}
+	}
+
+	async fn list_subscribers(
+		&self,
+		event_type: WebhookEventType,
+	) -> Result<Vec<WebhookSubscription>, WebhookServiceError> {
+		self
+			.repository
+			.list_subscriptions_for_event(event_type)
+			.await
+			.map_err(WebhookServiceError::FetchSubscriptions)
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookServiceError {
+	#[error("{0}")]
+	InvalidSubscription(#[source] WebhookError),
+
+	#[error("A navigator can have at most {MAX_SUBSCRIPTIONS} webhook subscriptions")]
+	TooManySubscriptions,
+
+	#[error("Webhook subscription not found")]
+	SubscriptionNotFound,
+
+	#[error("Failed to save webhook subscription: {0}")]
+	SaveSubscription(#[source] WebhookRepositoryError),
+
+	#[error("Failed to fetch webhook subscriptions: {0}")]
+	FetchSubscriptions(#[source] WebhookRepositoryError),
+
+	#[error("Failed to delete webhook subscription: {0}")]
+	DeleteSubscription(#[source] WebhookRepositoryError),
+
+	#[error("Database error: {0}")]
+	Database(#[from] sqlx::Error),
+}
+
+#[cfg(test)]
+mod tests {
+	use sqlx::Pool;
+	use sqlx::Postgres;
+	use sqlx::postgres::PgPoolOptions;
+
+	use super::*;
+
+	async fn connect_to_test_database() -> Pool<Postgres> {
+		let database_url = std::env::var("DATABASE_URL").unwrap();
+
+		PgPoolOptions::new()
+			.max_connections(5)
+			.connect(&database_url)
+			.await
+			.expect("Failed to connect to test database")
+	}
+
+	#[tokio::test]
+	async fn test_webhook_subscriptions() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let service = WebhookService::new(WebhookRepository::new(pool.clone()));
+
+		// Arrange: Create a navigator.
+		let navigator_id = NuttyId::now();
+		let navigator_name = format!("test_navigator_{}", navigator_id.nid());
+
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
+				VALUES ($1, $2, $3, 'test_pass', NOW(), NOW())
+			"#,
+			navigator_id.uuid(),
+			navigator_id.nid(),
+			navigator_name,
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to create test navigator");
+
+		let request = WebhookSubscriptionRequest {
+			url: "https://builder.example/hooks".to_string(),
+			event_types: vec![WebhookEventType::PagePublished],
+		};
+
+		// Act: Subscribe to published pages.
+		let subscription = service
+			.create_subscription(&navigator_id, &request)
+			.await
+			.expect("Failed to create subscription");
+
+		// Assert: The subscription is listed without its secret, and delivered
+		// with it.
+		let subscriptions = service
+			.list_subscriptions(&navigator_id)
+			.await
+			.expect("Failed to list subscriptions");
+
+		assert_eq!(subscriptions.len(), 1);
+		assert_eq!(subscriptions[0].id, subscription.id);
+		assert_eq!(subscriptions[0].secret, None);
+
+		let subscribers = service
+			.list_subscribers(WebhookEventType::PagePublished)
+			.await
+			.expect("Failed to list subscribers");
+
+		let subscriber = subscribers
+			.iter()
+			.find(|subscriber| subscriber.id == subscription.id)
+			.expect("Subscription should be a subscriber");
+
+		assert_eq!(subscriber.secret, subscription.secret);
+
+		// Assert: Navigators can only have so many subscriptions.
+		for _ in 1..MAX_SUBSCRIPTIONS {
+			service
+				.create_subscription(&navigator_id, &request)
+				.await
+				.expect("Failed to create subscription");
+		}
+
+		let result = service.create_subscription(&navigator_id, &request).await;
+		assert!(matches!(
+			result,
+			Err(WebhookServiceError::TooManySubscriptions)
+		));
+
+		// Act: Unsubscribe.
+		service
+			.delete_subscription(&navigator_id, &subscription.id)
+			.await
+			.expect("Failed to delete subscription");
+
+		// Assert: It's gone, and can't be deleted twice.
+		let subscriptions = service
+			.list_subscriptions(&navigator_id)
+			.await
+			.expect("Failed to list subscriptions");
+
+		assert!(subscriptions.iter().all(|s| s.id != subscription.id));
+
+		let result = service
+			.delete_subscription(&navigator_id, &subscription.id)
+			.await;
+
+		assert!(matches!(
+			result,
+			Err(WebhookServiceError::SubscriptionNotFound)
+		));
+	}
+}
@@ -1,5 +1,13 @@
 pub mod access;
+pub mod app;
 pub mod content;
+pub mod ingest;
+pub mod integrations;
 pub mod models;
+pub mod moderation;
 pub mod navigator;
+pub mod seed;
+pub mod system;
+#[cfg(any(test, feature = "testkit"))]
+pub mod testkit;
 pub mod utilities;
@@ -1,52 +1,456 @@
+use std::error::Error;
+use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
-use axum::Router;
-use axum::routing::get;
+use clap::Parser;
+use clap::Subcommand;
+use nuttyverse_core::access::models::RenameKind;
 use nuttyverse_core::access::repository::AccessRepository;
 use nuttyverse_core::access::service::AccessService;
-use nuttyverse_core::content::api::router as content_router;
+use nuttyverse_core::access::service::AccessServiceApi;
+use nuttyverse_core::app;
+use nuttyverse_core::content::block_kind::BlockKindRegistry;
+use nuttyverse_core::content::events::EventStreams;
+use nuttyverse_core::content::link_access::LinkAccessPolicy;
+use nuttyverse_core::content::og_image::OgImages;
 use nuttyverse_core::content::repository::ContentRepository;
+use nuttyverse_core::content::sanitizer::Sanitizer;
+use nuttyverse_core::content::sanitizer::SanitizerConfig;
 use nuttyverse_core::content::service::ContentService;
-use nuttyverse_core::navigator::api::router as navigator_router;
+use nuttyverse_core::content::service::ContentServiceApi;
+use nuttyverse_core::integrations::chatbot::repository::ChatbotRepository;
+use nuttyverse_core::integrations::chatbot::service::ChatbotService;
+use nuttyverse_core::integrations::chatbot::telegram::Telegram;
+use nuttyverse_core::integrations::webhooks::delivery::WebhookSender;
+use nuttyverse_core::integrations::webhooks::repository::WebhookRepository;
+use nuttyverse_core::integrations::webhooks::service::WebhookService;
+use nuttyverse_core::models::navigator::PasswordHashing;
+use nuttyverse_core::moderation::repository::ModerationRepository;
+use nuttyverse_core::moderation::service::ModerationService;
+use nuttyverse_core::navigator::onboarding::Onboarding;
 use nuttyverse_core::navigator::repository::NavigatorRepository;
 use nuttyverse_core::navigator::service::NavigatorService;
+use nuttyverse_core::navigator::service::NavigatorServiceApi;
+use nuttyverse_core::navigator::session_limit::SessionLimit;
+use nuttyverse_core::navigator::session_store::PostgresSessionStore;
+use nuttyverse_core::navigator::session_store::RedisSessionStore;
+use nuttyverse_core::navigator::session_store::WriteThroughSessionStore;
+use nuttyverse_core::seed::DEMO_PASSWORD;
+use nuttyverse_core::seed::DemoData;
+use nuttyverse_core::seed::SeedConfig;
+use nuttyverse_core::system::repository::SystemRepository;
+use nuttyverse_core::system::service::SystemService;
+use nuttyverse_core::utilities::api::body_limit::BodyLimits;
+use nuttyverse_core::utilities::api::export_link::ExportLinks;
+use nuttyverse_core::utilities::api::feed_token::FeedTokens;
+use nuttyverse_core::utilities::api::geo_ip::GeoIp;
+use nuttyverse_core::utilities::api::link_checker::LinkChecker;
+use nuttyverse_core::utilities::api::og_image_link::OgImageLinks;
+use nuttyverse_core::utilities::api::page_fetcher::PageFetcher;
+use nuttyverse_core::utilities::api::read_only::ReadOnlyMode;
 use nuttyverse_core::utilities::api::state::AppState;
+use nuttyverse_core::utilities::api::webhook::WebhookSecret;
+use nuttyverse_core::utilities::circuit_breaker::CircuitBreaker;
+use nuttyverse_core::utilities::circuit_breaker::CircuitBreakerConfig;
+use nuttyverse_core::utilities::migrations;
+use nuttyverse_core::utilities::query_metrics::QueryMetrics;
+use nuttyverse_core::utilities::request_metrics::DEFAULT_FLUSH_INTERVAL;
+use nuttyverse_core::utilities::request_metrics::RequestMetrics;
+use nuttyverse_core::utilities::row_level_security::RowLevelSecurity;
+use nuttyverse_core::utilities::secrets::Secret;
+use sqlx::PgPool;
 use sqlx::postgres::PgPoolOptions;
 
+/// The Nuttyverse server, and tools for operating it without psql.
+#[derive(Parser)]
+#[command(name = "nuttyverse")]
+struct Cli {
+	#[command(subcommand)]
+	command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+	/// Serve the API (the default).
+	Serve,
+
+	/// Apply the database migrations that haven't been yet.
+	Migrate {
+		/// The directory of dbmate migrations.
+		#[arg(long, default_value = "db/migrations")]
+		dir: PathBuf,
+	},
+
+	/// Seed a fresh database with demo data, e.g. `seed --seed 7 --pages 200`.
+	Seed {
+		/// Flags for the demo data: --seed, --pages, --depth, --paragraphs,
+		/// and --navigators.
+		#[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+		args: Vec<String>,
+	},
+
+	/// Register a navigator with the admin role.
+	CreateAdmin {
+		name: String,
+
+		#[arg(long, env = "ADMIN_PASSWORD", hide_env_values = true)]
+		password: String,
+	},
+
+	/// Grant a global role (e.g. "admin") to a navigator.
+	GrantRole { name: String, role: String },
+
+	/// Rename a role everywhere it's granted, e.g. `rename-role viewer reader`.
+	RenameRole { old_name: String, new_name: String },
+
+	/// Rename a permission in every role that has it.
+	RenamePermission { old_name: String, new_name: String },
+
+	/// Recount derived data, like block stats, repairing any that drifted.
+	VerifyIntegrity,
+
+	/// Recompress the content that Postgres compressed with pglz, before it
+	/// was set to compress content with lz4.
+	CompressContent {
+		/// The number of blocks to recompress per transaction.
+		#[arg(long, default_value_t = 500)]
+		batch_size: i64,
+	},
+}
+
 #[tokio::main]
 async fn main() {
+	let command = Cli::parse().command.unwrap_or(Command::Serve);
+
+	// Hash passwords the same way in every command, so that navigators
+	// registered by one can log in with another.
+	PasswordHashing::from_env()
+		.expect("Invalid Argon2 parameters")
+		.configure();
+
+	// Create the database connection pool.
+	let database_url = Secret::from_env("DATABASE_URL")
+		.map(Secret::into_string)
+		.unwrap_or_else(|| "postgres://nutty@localhost:5432/nuttyverse".to_string());
+
+	let result = match command {
+		Command::Serve => {
+			serve(&database_url).await;
+			Ok(())
+		}
+
+		Command::Migrate { dir } => migrate(&database_url, &dir).await,
+		Command::Seed { args } => seed(&database_url, args).await,
+		Command::CreateAdmin { name, password } => create_admin(&database_url, name, password).await,
+		Command::GrantRole { name, role } => grant_role(&database_url, &name, &role).await,
+		Command::RenameRole { old_name, new_name } => {
+			rename(&database_url, RenameKind::Role, &old_name, &new_name).await
+		}
+		Command::RenamePermission { old_name, new_name } => {
+			rename(&database_url, RenameKind::Permission, &old_name, &new_name).await
+		}
+		Command::VerifyIntegrity => verify_integrity(&database_url).await,
+		Command::CompressContent { batch_size } => compress_content(&database_url, batch_size).await,
+	};
+
+	if let Err(error) = result {
+		eprintln!("{error}");
+		std::process::exit(1);
+	}
+}
+
+/// Connect to the database, for maintenance commands.
+async fn connect(database_url: &str) -> PgPool {
+	PgPoolOptions::new()
+		.max_connections(2)
+		.connect(database_url)
+		.await
+		.expect("Failed to connect to database")
+}
+
+/// Apply pending migrations.
+async fn migrate(database_url: &str, directory: &Path) -> Result<(), Box<dyn Error>> {
+	let migrations = migrations::read_migrations(directory)?;
+	let applied = migrations::migrate(&connect(database_url).await, migrations).await?;
+
+	for migration in &applied {
+		println!("Applied {}_{}.", migration.version, migration.name);
+	}
+
+	println!("Applied {} migrations.", applied.len());
+	Ok(())
+}
+
+/// Seed the database with demo data.
+async fn seed(database_url: &str, args: Vec<String>) -> Result<(), Box<dyn Error>> {
+	let config = SeedConfig::from_args(args)?;
+	let database_pool = connect(database_url).await;
+
+	let access_service = AccessService::new(AccessRepository::new(database_pool.clone()));
+	let content_service = ContentService::new(
+		ContentRepository::new(database_pool.clone()),
+		access_service.clone(),
+	);
+	let navigator_service = NavigatorService::new(NavigatorRepository::new(database_pool));
+
+	println!("Generating demo data with seed {}…", config.seed);
+	let data = DemoData::generate(&config)?;
+	let names: Vec<String> = data.navigators.iter().map(|n| n.name.clone()).collect();
+
+	let summary =
+		nuttyverse_core::seed::seed(data, &navigator_service, &content_service, &access_service)
+			.await?;
+
+	println!(
+		"Seeded {} navigators and {} blocks.",
+		summary.navigators, summary.blocks
+	);
+	println!(
+		"Log in as {} with password {DEMO_PASSWORD:?}.",
+		names.join(", ")
+	);
+
+	Ok(())
+}
+
+/// Register a navigator, and make them an admin.
+async fn create_admin(
+	database_url: &str,
+	name: String,
+	password: String,
+) -> Result<(), Box<dyn Error>> {
+	let database_pool = connect(database_url).await;
+	let navigator_service = NavigatorService::new(NavigatorRepository::new(database_pool.clone()));
+	let access_service = AccessService::new(AccessRepository::new(database_pool));
+
+	let navigator = navigator_service.register(name, password).await?;
+
+	access_service
+		.grant_global_role(navigator.nutty_id(), "admin")
+		.await?;
+
+	println!(
+		"Created admin {} ({}).",
+		navigator.name(),
+		navigator.nutty_id()
+	);
+
+	Ok(())
+}
+
+/// Grant a global role to a navigator.
+async fn grant_role(database_url: &str, name: &str, role: &str) -> Result<(), Box<dyn Error>> {
+	let database_pool = connect(database_url).await;
+	let navigator_service = NavigatorService::new(NavigatorRepository::new(database_pool.clone()));
+	let access_service = AccessService::new(AccessRepository::new(database_pool));
+
+	let navigator = navigator_service
+		.get_navigator_by_name(name)
+		.await?
+		.ok_or_else(|| format!("Navigator not found: {name}"))?;
+
+	access_service
+		.grant_global_role(navigator.nutty_id(), role)
+		.await?;
+
+	println!("Granted {role} to {name}.");
+	Ok(())
+}
+
+/// Rename a role or permission everywhere it's granted.
+async fn rename(
+	database_url: &str,
+	kind: RenameKind,
+	old_name: &str,
+	new_name: &str,
+) -> Result<(), Box<dyn Error>> {
+	let access_service = AccessService::new(AccessRepository::new(connect(database_url).await));
+
+	let rename = match kind {
+		RenameKind::Role => access_service.rename_role(old_name, new_name, None).await?,
+		RenameKind::Permission => {
+			access_service
+				.rename_permission(old_name, new_name, None)
+				.await?
+		}
+	};
+
+	println!(
+		"Renamed {} {old_name} to {new_name}, moving {} grants.",
+		kind.as_str(),
+		rename.grant_count
+	);
+
+	Ok(())
+}
+
+/// Recount derived data, repairing any that drifted.
+async fn verify_integrity(database_url: &str) -> Result<(), Box<dyn Error>> {
+	let database_pool = connect(database_url).await;
+	let access_service = AccessService::new(AccessRepository::new(database_pool.clone()));
+	let content_service = ContentService::new(ContentRepository::new(database_pool), access_service);
+
+	let check = content_service.check_block_stats().await?;
+
+	for stats in &check.repaired {
+		println!(
+			"Repaired stats of {}: {} descendants, {} backlinks.",
+			stats.block_id, stats.descendant_count, stats.backlink_count
+		);
+	}
+
+	println!(
+		"Checked the stats of {} blocks; repaired {}.",
+		check.checked_count,
+		check.repaired.len()
+	);
+
+	Ok(())
+}
+
+/// Recompress the content of blocks compressed with pglz.
+async fn compress_content(database_url: &str, batch_size: i64) -> Result<(), Box<dyn Error>> {
+	let database_pool = connect(database_url).await;
+	let access_service = AccessService::new(AccessRepository::new(database_pool.clone()));
+	let content_service = ContentService::new(ContentRepository::new(database_pool), access_service);
+
+	let stats = content_service.recompress_content(batch_size).await?;
+
+	println!(
+		"Compressed {} blocks from {} to {} bytes.",
+		stats.compressed_count, stats.original_bytes, stats.compressed_bytes
+	);
+
+	Ok(())
+}
+
+/// Serve the API.
+async fn serve(database_url: &str) {
 	// リンクスタート〜！
 	println!("Starting the Nuttyverse server…");
 
 	// Create the database connection pool.
 	println!("Connecting to the Nuttyverse database…");
-	let database_url = std::env::var("DATABASE_URL")
-		.unwrap_or_else(|_| "postgres://nutty@localhost:5432/nuttyverse".to_string());
 
-	let database_pool = PgPoolOptions::new()
+	// Let Postgres enforce access to content blocks too, when requested.
+	let database_pool = RowLevelSecurity::from_env()
+		.configure(PgPoolOptions::new())
 		.max_connections(5)
-		.connect(&database_url)
+		.connect(database_url)
 		.await
 		.expect("Failed to connect to database");
 
+	// Log queries slower than the configured threshold.
+	if let Some(threshold) = std::env::var("SLOW_QUERY_THRESHOLD_MS")
+		.ok()
+		.and_then(|v| v.parse().ok())
+	{
+		QueryMetrics::global().set_slow_threshold(Duration::from_millis(threshold));
+	}
+
+	// Fail fast while the database is unreachable.
+	let circuit_breaker_defaults = CircuitBreakerConfig::default();
+
+	CircuitBreaker::global().configure(CircuitBreakerConfig {
+		failure_threshold: std::env::var("DATABASE_CIRCUIT_BREAKER_THRESHOLD")
+			.ok()
+			.and_then(|v| v.parse().ok())
+			.unwrap_or(circuit_breaker_defaults.failure_threshold),
+		cooldown: std::env::var("DATABASE_CIRCUIT_BREAKER_COOLDOWN_SECS")
+			.ok()
+			.and_then(|v| v.parse().ok())
+			.map(Duration::from_secs)
+			.unwrap_or(circuit_breaker_defaults.cooldown),
+	});
+
 	// Set up application state.
 	let content_repository = ContentRepository::new(database_pool.clone());
 	let access_repository = AccessRepository::new(database_pool.clone());
 	let access_service = AccessService::new(access_repository);
-	let content_service = ContentService::new(content_repository, access_service.clone());
+	let sanitizer = Sanitizer::new(SanitizerConfig::from_env());
+	let block_kinds = Arc::new(BlockKindRegistry::default());
+	let content_service = ContentService::new(content_repository.clone(), access_service.clone())
+		.with_sanitizer(sanitizer.clone())
+		.with_block_kinds(block_kinds.clone())
+		.with_link_access_policy(LinkAccessPolicy::from_env());
+	let moderation_repository = ModerationRepository::new(database_pool.clone());
+	let moderation_service = ModerationService::new(moderation_repository, content_repository);
+
+	// Start in read-only mode when requested (e.g., while migrating).
+	let read_only = std::env::var("READ_ONLY").is_ok_and(|v| v == "1" || v == "true");
+	let read_only_retry_after = std::env::var("READ_ONLY_RETRY_AFTER")
+		.ok()
+		.and_then(|v| v.parse().ok())
+		.unwrap_or(300);
+	let read_only = ReadOnlyMode::new(read_only, read_only_retry_after);
+
 	let navigator_repository = NavigatorRepository::new(database_pool.clone());
-	let navigator_service = NavigatorService::new(navigator_repository);
+	let mut navigator_service = NavigatorService::new(navigator_repository.clone())
+		.with_session_limit(SessionLimit::from_env())
+		.with_read_only(read_only.clone());
+
+	// Cache sessions in Redis, when configured, in front of Postgres.
+	if let Some(session_cache) = RedisSessionStore::from_env() {
+		navigator_service = navigator_service.with_session_store(WriteThroughSessionStore::new(
+			PostgresSessionStore::new(navigator_repository),
+			session_cache,
+		));
+	}
+
+	// Give new navigators starter content, when enabled.
+	if std::env::var("ONBOARDING_STARTER_CONTENT").is_ok_and(|v| v == "1" || v == "true") {
+		navigator_service = navigator_service.with_onboarding(Onboarding::new(
+			Arc::new(content_service.clone()),
+			Arc::new(access_service.clone()),
+		));
+	}
+
+	let chatbot_service = ChatbotService::new(ChatbotRepository::new(database_pool.clone()));
+	let system_service = SystemService::new(SystemRepository::new(database_pool.clone()));
+	let webhook_service = WebhookService::new(WebhookRepository::new(database_pool.clone()));
 
 	let app_state = Arc::new(AppState {
-		access_service,
-		content_service,
-		navigator_service,
+		access_service: Arc::new(access_service),
+		content_service: Arc::new(content_service),
+		navigator_service: Arc::new(navigator_service),
+		moderation_service: Arc::new(moderation_service),
+		chatbot_service: Arc::new(chatbot_service),
+		system_service: Arc::new(system_service),
+		webhook_service: Arc::new(webhook_service),
+		read_only,
+		geo_ip: GeoIp::from_env(),
+		sanitizer,
+		block_kinds,
+		email_ingest_secret: WebhookSecret::from_env("INGEST_EMAIL_SECRET"),
+		page_fetcher: PageFetcher::from_env(),
+		link_checker: LinkChecker::from_env(),
+		telegram: Telegram::from_env(),
+		webhook_sender: WebhookSender::from_env(),
+		feed_tokens: FeedTokens::from_env(),
+		export_links: ExportLinks::from_env(),
+		og_images: OgImages::from_env(),
+		og_image_links: OgImageLinks::from_env(),
+		event_streams: EventStreams::default(),
 	});
 
-	let router = Router::new()
-		.route("/", get(|| async { "Hello world!" }))
-		.merge(content_router(app_state.clone()))
-		.merge(navigator_router(app_state.clone()));
+	// Flush each navigator's request usage to the database periodically.
+	let request_stats_flush_interval = std::env::var("REQUEST_STATS_FLUSH_SECS")
+		.ok()
+		.and_then(|v| v.parse().ok())
+		.map(Duration::from_secs)
+		.unwrap_or(DEFAULT_FLUSH_INTERVAL);
+
+	RequestMetrics::global().spawn_flusher(
+		app_state.navigator_service.clone(),
+		request_stats_flush_interval,
+	);
+
+	// Limit request body sizes per group of routes.
+	let body_limits = BodyLimits::from_env();
+
+	let router = app::router(app_state, body_limits);
 
 	let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
 	println!("Listening @ 0.0.0.0:3000…");
@@ -0,0 +1,164 @@
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde::Serialize;
+use sqlx::Decode;
+use sqlx::Encode;
+use sqlx::FromRow;
+use sqlx::Postgres;
+use sqlx::Type;
+use sqlx::postgres::PgTypeInfo;
+use sqlx::postgres::PgValueRef;
+use thiserror::Error;
+
+use crate::models::NuttyId;
+use crate::models::ShareLevel;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+
+/// Where an access request is in its review.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessRequestStatus {
+	/// Waiting for a navigator who manages the block.
+	Pending,
+
+	/// Approved, and the requested level was granted.
+	Approved,
+
+	/// Denied, and nothing was granted.
+	Denied,
+}
+
+impl AccessRequestStatus {
+	/// Get the stored representation of the status.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			AccessRequestStatus::Pending => "pending",
+			AccessRequestStatus::Approved => "approved",
+			AccessRequestStatus::Denied => "denied",
+		}
+	}
+}
+
+impl FromStr for AccessRequestStatus {
+	type Err = AccessRequestError;
+
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		match value {
+			"pending" => Ok(AccessRequestStatus::Pending),
+			"approved" => Ok(AccessRequestStatus::Approved),
+			"denied" => Ok(AccessRequestStatus::Denied),
+			_ => Err(AccessRequestError::UnknownStatus(value.to_string())),
+		}
+	}
+}
+
+impl Type<Postgres> for AccessRequestStatus {
+	fn type_info() -> PgTypeInfo {
+		PgTypeInfo::with_name("VARCHAR")
+	}
+
+	fn compatible(ty: &PgTypeInfo) -> bool {
+		<&str as Type<Postgres>>::compatible(ty)
+	}
+}
+
+impl Encode<'_, Postgres> for AccessRequestStatus {
+	fn encode_by_ref(
+		&self,
+		buf: &mut <Postgres as sqlx::Database>::ArgumentBuffer<'_>,
+	) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+		<&str as Encode<Postgres>>::encode(self.as_str(), buf)
+	}
+}
+
+impl<'r> Decode<'r, Postgres> for AccessRequestStatus {
+	fn decode(value: PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+		let value = <&str as Decode<Postgres>>::decode(value)?;
+		Ok(value.parse()?)
+	}
+}
+
+/// A navigator's request for access to a content block they cannot view,
+/// reviewed by the navigators who manage the block.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AccessRequest {
+	#[sqlx(rename = "id")]
+	nutty_id: NuttyId,
+	block_id: NuttyId,
+	navigator_id: NuttyId,
+	level: ShareLevel,
+	status: AccessRequestStatus,
+	resolved_by: Option<NuttyId>,
+	resolved_at: Option<DateTimeRfc3339>,
+	created_at: DateTimeRfc3339,
+	updated_at: DateTimeRfc3339,
+}
+
+impl AccessRequest {
+	/// Create a new pending access request.
+	pub fn new(block_id: NuttyId, navigator_id: NuttyId, level: ShareLevel) -> Self {
+		let now = DateTimeRfc3339::new(chrono::Utc::now().fixed_offset());
+
+		Self {
+			nutty_id: NuttyId::now(),
+			block_id,
+			navigator_id,
+			level,
+			status: AccessRequestStatus::Pending,
+			resolved_by: None,
+			resolved_at: None,
+			created_at: now,
+			updated_at: now,
+		}
+	}
+
+	pub fn nutty_id(&self) -> &NuttyId {
+		&self.nutty_id
+	}
+
+	pub fn block_id(&self) -> &NuttyId {
+		&self.block_id
+	}
+
+	pub fn navigator_id(&self) -> &NuttyId {
+		&self.navigator_id
+	}
+
+	pub fn level(&self) -> ShareLevel {
+		self.level
+	}
+
+	pub fn status(&self) -> AccessRequestStatus {
+		self.status
+	}
+
+	pub fn resolved_by(&self) -> Option<&NuttyId> {
+		self.resolved_by.as_ref()
+	}
+
+	pub fn created_at(&self) -> &DateTimeRfc3339 {
+		&self.created_at
+	}
+
+	/// Approve or deny the request on behalf of a navigator who manages the block.
+	pub fn resolve(&mut self, resolver_id: NuttyId, approve: bool) {
+		let now = DateTimeRfc3339::new(chrono::Utc::now().fixed_offset());
+
+		self.status = if approve {
+			AccessRequestStatus::Approved
+		} else {
+			AccessRequestStatus::Denied
+		};
+
+		self.resolved_by = Some(resolver_id);
+		self.resolved_at = Some(now);
+		self.updated_at = now;
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum AccessRequestError {
+	#[error("Unknown access request status: {0}")]
+	UnknownStatus(String),
+}
@@ -0,0 +1,257 @@
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+use url::Url;
+
+use crate::models::BlockContent;
+use crate::models::ContentBlock;
+use crate::models::NuttyId;
+
+/// Bare and markdown-linked external URLs. Trailing punctuation is trimmed
+/// separately, since it usually ends the sentence rather than the URL.
+static EXTERNAL_URL: LazyLock<Regex> =
+	LazyLock::new(|| Regex::new(r#"https?://[^\s<>()\[\]"'`]+"#).unwrap());
+
+/// What kind of issue an annotation warns about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnotationKind {
+	/// An external URL couldn't be reached, or isn't there anymore.
+	BrokenLink,
+
+	/// A heading has no text.
+	EmptyHeading,
+
+	/// A code fence is opened but never closed.
+	UnclosedCodeFence,
+}
+
+impl AnnotationKind {
+	/// Get the stored representation of the kind.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			AnnotationKind::BrokenLink => "broken_link",
+			AnnotationKind::EmptyHeading => "empty_heading",
+			AnnotationKind::UnclosedCodeFence => "unclosed_code_fence",
+		}
+	}
+}
+
+impl FromStr for AnnotationKind {
+	type Err = BlockAnnotationError;
+
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		match value {
+			"broken_link" => Ok(AnnotationKind::BrokenLink),
+			"empty_heading" => Ok(AnnotationKind::EmptyHeading),
+			"unclosed_code_fence" => Ok(AnnotationKind::UnclosedCodeFence),
+			_ => Err(BlockAnnotationError::UnknownKind(value.to_string())),
+		}
+	}
+}
+
+/// An issue found in a content block after it was saved, shown to editors
+/// as a warning.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockAnnotation {
+	pub block_id: NuttyId,
+	pub kind: AnnotationKind,
+	pub message: String,
+}
+
+impl BlockAnnotation {
+	/// Create a new block annotation.
+	pub fn new(block_id: NuttyId, kind: AnnotationKind, message: impl Into<String>) -> Self {
+		Self {
+			block_id,
+			kind,
+			message: message.into(),
+		}
+	}
+
+	/// Annotate a broken link.
+	pub fn broken_link(block_id: NuttyId, url: &Url, reason: &str) -> Self {
+		Self::new(
+			block_id,
+			AnnotationKind::BrokenLink,
+			format!("{url} is broken: {reason}"),
+		)
+	}
+
+	/// Find the issues in a block that can be spotted without leaving the
+	/// server, i.e. everything except broken links.
+	pub fn lint(block: &ContentBlock) -> Vec<Self> {
+		let block_id = *block.nutty_id();
+		let mut annotations = vec![];
+
+		if let BlockContent::Heading { markdown } = &block.content
+			&& markdown.trim_start_matches('#').trim().is_empty()
+		{
+			annotations.push(Self::new(
+				block_id,
+				AnnotationKind::EmptyHeading,
+				"This heading is empty.",
+			));
+		}
+
+		if let Some(markdown) = markdown(&block.content)
+			&& has_unclosed_code_fence(markdown)
+		{
+			annotations.push(Self::new(
+				block_id,
+				AnnotationKind::UnclosedCodeFence,
+				"This code fence is never closed.",
+			));
+		}
+
+		annotations
+	}
+}
+
+/// Parse the external URLs in a content block's markdown, in order of first
+/// appearance.
+pub fn parse_external_urls(content: &BlockContent) -> Vec<Url> {
+	let Some(markdown) = markdown(content) else {
+		return vec![];
+	};
+
+	let mut urls: Vec<Url> = vec![];
+
+	for found in EXTERNAL_URL.find_iter(markdown) {
+		let trimmed = found
+			.as_str()
+			.trim_end_matches(['.', ',', ';', ':', '!', '?']);
+
+		if let Ok(url) = Url::parse(trimmed)
+			&& !urls.contains(&url)
+		{
+			urls.push(url);
+		}
+	}
+
+	urls
+}
+
+/// Get the markdown of a content block, if it has any.
+fn markdown(content: &BlockContent) -> Option<&str> {
+	match content {
+		BlockContent::Page { .. } | BlockContent::Query { .. } | BlockContent::Custom { .. } => None,
+		BlockContent::Heading { markdown } => Some(markdown),
+		BlockContent::Paragraph { markdown } => Some(markdown),
+		BlockContent::Todo { markdown, .. } => Some(markdown),
+	}
+}
+
+/// Check whether a code fence is left open. A fence is closed by a line of
+/// at least as many of the same character (``` or ~~~), as in CommonMark.
+fn has_unclosed_code_fence(markdown: &str) -> bool {
+	let mut open: Option<(char, usize)> = None;
+
+	for line in markdown.lines() {
+		let line = line.trim_start();
+
+		let Some(marker) = line.chars().next().filter(|c| *c == '`' || *c == '~') else {
+			continue;
+		};
+
+		let length = line.chars().take_while(|c| *c == marker).count();
+
+		if length < 3 {
+			continue;
+		}
+
+		open = match open {
+			None => Some((marker, length)),
+			Some((open_marker, open_length))
+				if open_marker == marker
+					&& length >= open_length
+					&& line[length..].trim().is_empty() =>
+			{
+				None
+			}
+			Some(fence) => Some(fence),
+		};
+	}
+
+	open.is_some()
+}
+
+#[derive(Debug, Error)]
+pub enum BlockAnnotationError {
+	#[error("Unknown annotation kind: {0}")]
+	UnknownKind(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::models::FractionalIndex;
+
+	fn block(content: BlockContent) -> ContentBlock {
+		ContentBlock::now(None, FractionalIndex::start(), content)
+	}
+
+	fn kinds(content: BlockContent) -> Vec<AnnotationKind> {
+		BlockAnnotation::lint(&block(content))
+			.into_iter()
+			.map(|annotation| annotation.kind)
+			.collect()
+	}
+
+	#[test]
+	fn test_lint() {
+		let heading = |markdown: &str| BlockContent::Heading {
+			markdown: markdown.to_string(),
+		};
+
+		let paragraph = |markdown: &str| BlockContent::Paragraph {
+			markdown: markdown.to_string(),
+		};
+
+		// Assert: Headings need text.
+		assert_eq!(kinds(heading("  ")), vec![AnnotationKind::EmptyHeading]);
+		assert_eq!(kinds(heading("## ")), vec![AnnotationKind::EmptyHeading]);
+		assert!(kinds(heading("Notes")).is_empty());
+
+		// Assert: Code fences must be closed by a long enough fence of the
+		// same character.
+		assert!(kinds(paragraph("```rust\nfn main() {}\n```")).is_empty());
+		assert!(kinds(paragraph("````\n```\n````")).is_empty());
+		assert!(kinds(paragraph("~~~\ncode\n~~~")).is_empty());
+
+		for markdown in ["```rust\nfn main() {}", "````\n```", "```\ncode\n~~~"] {
+			assert_eq!(
+				kinds(paragraph(markdown)),
+				vec![AnnotationKind::UnclosedCodeFence],
+				"{markdown:?}"
+			);
+		}
+
+		// Assert: Pages and queries have nothing to lint.
+		let page = BlockContent::Page {
+			title: String::new(),
+		};
+
+		assert!(kinds(page).is_empty());
+	}
+
+	#[test]
+	fn test_parse_external_urls() {
+		let content = BlockContent::Paragraph {
+			markdown: "See https://example.com/a, [docs](https://docs.rs/regex) and \
+				<https://example.com/a>. Not ftp://example.com or [[abcdefg]]."
+				.to_string(),
+		};
+
+		let urls: Vec<String> = parse_external_urls(&content)
+			.into_iter()
+			.map(String::from)
+			.collect();
+
+		assert_eq!(urls, vec!["https://example.com/a", "https://docs.rs/regex"]);
+	}
+}
@@ -0,0 +1,18 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::NuttyId;
+
+/// The outcome of archiving or unarchiving a content block, optionally
+/// along with its descendants.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockArchival {
+	/// The Nutty ID of the block.
+	pub block_id: NuttyId,
+
+	/// Whether the blocks are now archived.
+	pub archived: bool,
+
+	/// The Nutty IDs of every updated block, the block itself first.
+	pub updated_ids: Vec<NuttyId>,
+}
@@ -0,0 +1,54 @@
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::models::NuttyId;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+
+/// A content block's checksum, for sync clients to tell whether their copy
+/// has drifted before pulling the block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockChecksum {
+	pub block_id: NuttyId,
+
+	/// The hash of the block's canonical content JSON.
+	pub content_hash: String,
+
+	pub updated_at: DateTimeRfc3339,
+}
+
+impl BlockChecksum {
+	/// Write the checksum as a compact line of JSON: an array of the
+	/// block's NID, content hash, and update time, without a newline.
+	pub fn to_json_line(&self) -> String {
+		json!([self.block_id.nid(), self.content_hash, self.updated_at]).to_string()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::DateTime;
+
+	use super::*;
+
+	#[test]
+	fn test_to_json_line() {
+		let block_id = NuttyId::now();
+
+		let checksum = BlockChecksum {
+			block_id,
+			content_hash: "abc123".to_string(),
+			updated_at: DateTimeRfc3339::new(
+				DateTime::parse_from_rfc3339("2025-08-14T09:00:00+00:00").unwrap(),
+			),
+		};
+
+		assert_eq!(
+			checksum.to_json_line(),
+			format!(
+				r#"["{}","abc123","2025-08-14T09:00:00+00:00"]"#,
+				block_id.nid()
+			)
+		);
+	}
+}
@@ -1,5 +1,9 @@
+use chrono::NaiveDate;
+use regex::Regex;
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::Map;
+use serde_json::Value;
 use sqlx::Decode;
 use sqlx::Encode;
 use sqlx::FromRow;
@@ -9,17 +13,44 @@ use sqlx::Type;
 use sqlx::postgres::PgRow;
 use sqlx::postgres::PgTypeInfo;
 
+use crate::models::DissociatedNuttyId;
 use crate::models::NuttyTag;
+use crate::models::block_date::BlockDateKind;
 
 /// Not to be confused with [ContentBlock].
 /// `ContentBlockContent` it might have been named,
 /// but `BlockContent` is shorter and unclaimed.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "kind")]
 pub enum BlockContent {
-	Page { title: String },
-	Heading { markdown: String },
-	Paragraph { markdown: String },
+	Page {
+		title: String,
+	},
+	Heading {
+		markdown: String,
+	},
+	Paragraph {
+		markdown: String,
+	},
+	Query {
+		dsl: String,
+	},
+	Todo {
+		markdown: String,
+		done: bool,
+	},
+
+	/// Content of a kind registered as a plugin, e.g. `Recipe`, with the
+	/// rest of its fields kept as they were written. See [BlockKindRegistry].
+	///
+	/// [BlockKindRegistry]: crate::content::block_kind::BlockKindRegistry
+	#[serde(untagged)]
+	Custom {
+		kind: String,
+
+		#[serde(flatten)]
+		fields: Map<String, Value>,
+	},
 }
 
 impl FromRow<'_, PgRow> for BlockContent {
@@ -58,12 +89,385 @@ impl<'r> Decode<'r, sqlx::Postgres> for BlockContent {
 }
 
 impl BlockContent {
-	/// Parse the target [NuttyTag] list from the content block.
+	/// Get the name of the content's kind, e.g. "Paragraph".
+	pub fn kind(&self) -> &str {
+		match self {
+			BlockContent::Page { .. } => "Page",
+			BlockContent::Heading { .. } => "Heading",
+			BlockContent::Paragraph { .. } => "Paragraph",
+			BlockContent::Query { .. } => "Query",
+			BlockContent::Todo { .. } => "Todo",
+			BlockContent::Custom { kind, .. } => kind,
+		}
+	}
+
+	/// Parse the target [NuttyTag] list from the content block. Custom
+	/// content's tags are extracted by its [BlockKind] instead.
+	///
+	/// [BlockKind]: crate::content::block_kind::BlockKind
 	pub fn parse_target_tags(&self) -> Vec<NuttyTag> {
 		match self {
 			BlockContent::Page { .. } => vec![],
 			BlockContent::Heading { markdown } => NuttyTag::parse_all(markdown),
 			BlockContent::Paragraph { markdown } => NuttyTag::parse_all(markdown),
+			BlockContent::Query { .. } => vec![],
+			BlockContent::Todo { markdown, .. } => NuttyTag::parse_all(markdown),
+			BlockContent::Custom { .. } => vec![],
+		}
+	}
+
+	/// Parse the dates that a content block is linked to. Pages titled with
+	/// an ISO 8601 date are daily notes, and `due:YYYY-MM-DD` in markdown
+	/// marks a due date.
+	pub fn parse_dates(&self) -> Vec<(NaiveDate, BlockDateKind)> {
+		let parse_due_dates = |markdown: &str| {
+			let re = Regex::new(r"(?:^|\s)due:(\d{4}-\d{2}-\d{2})\b").unwrap();
+
+			let mut dates: Vec<_> = re
+				.captures_iter(markdown)
+				.filter_map(|captures| NaiveDate::parse_from_str(&captures[1], "%Y-%m-%d").ok())
+				.map(|date| (date, BlockDateKind::Due))
+				.collect();
+
+			dates.sort();
+			dates.dedup();
+			dates
+		};
+
+		match self {
+			BlockContent::Page { title } => NaiveDate::parse_from_str(title.trim(), "%Y-%m-%d")
+				.map(|date| vec![(date, BlockDateKind::Daily)])
+				.unwrap_or_default(),
+			BlockContent::Heading { markdown } => parse_due_dates(markdown),
+			BlockContent::Paragraph { markdown } => parse_due_dates(markdown),
+			BlockContent::Query { .. } => vec![],
+			BlockContent::Todo { markdown, .. } => parse_due_dates(markdown),
+			BlockContent::Custom { .. } => vec![],
 		}
 	}
+
+	/// Parse the page titles that the content references by title alone
+	/// ([[Title]]), rather than by Nutty ID.
+	pub fn parse_title_references(&self) -> Vec<String> {
+		let re = Regex::new(r"\[\[([^\[\]|]+)\]\]").unwrap();
+
+		let mut titles: Vec<String> = match self {
+			BlockContent::Heading { markdown }
+			| BlockContent::Paragraph { markdown }
+			| BlockContent::Todo { markdown, .. } => re
+				.captures_iter(markdown)
+				.map(|captures| captures[1].to_string())
+				.collect(),
+			BlockContent::Page { .. } | BlockContent::Query { .. } | BlockContent::Custom { .. } => {
+				vec![]
+			}
+		};
+
+		titles.sort();
+		titles.dedup();
+		titles
+	}
+
+	/// Rewrite tags to a renamed page whose display text mirrors the old
+	/// title ([[abcdefg|Old Title]]), returning the updated content if
+	/// anything changed. Title links are left alone.
+	pub fn rename_tag_display_text(
+		&self,
+		target_id: &DissociatedNuttyId,
+		old_title: &str,
+		new_title: &str,
+	) -> Option<BlockContent> {
+		let nid = target_id.nid();
+
+		self.rewrite_markdown(|markdown| {
+			markdown.replace(
+				&format!("[[{nid}|{old_title}]]"),
+				&format!("[[{nid}|{new_title}]]"),
+			)
+		})
+	}
+
+	/// Rewrite references to a renamed page, returning the updated content
+	/// if anything changed. Both title links ([[Old Title]]) and tags whose
+	/// display text mirrors the old title ([[abcdefg|Old Title]]) are updated.
+	pub fn rename_title_references(
+		&self,
+		target_id: &DissociatedNuttyId,
+		old_title: &str,
+		new_title: &str,
+	) -> Option<BlockContent> {
+		let nid = target_id.nid();
+
+		self.rewrite_markdown(|markdown| {
+			markdown
+				.replace(&format!("[[{old_title}]]"), &format!("[[{new_title}]]"))
+				.replace(
+					&format!("[[{nid}|{old_title}]]"),
+					&format!("[[{nid}|{new_title}]]"),
+				)
+		})
+	}
+
+	/// Convert tags referencing a deleted block to plain text, returning the
+	/// updated content if anything changed. Tags with display text keep it
+	/// ([[abcdefg|Notes]] becomes "Notes"), and bare tags ([[abcdefg]])
+	/// become the given text.
+	pub fn unlink_references(
+		&self,
+		target_id: &DissociatedNuttyId,
+		text: &str,
+	) -> Option<BlockContent> {
+		let re = Regex::new(&format!(
+			r"\[\[\s*{}\s*(?:\|([^]]+))?\]\]",
+			regex::escape(&target_id.nid())
+		))
+		.unwrap();
+
+		self.rewrite_markdown(|markdown| {
+			re.replace_all(markdown, |captures: &regex::Captures| {
+				captures
+					.get(1)
+					.map_or(text, |display| display.as_str().trim())
+					.to_string()
+			})
+			.into_owned()
+		})
+	}
+
+	/// Point tags referencing a merged block at the block it was merged into,
+	/// returning the updated content if anything changed. Display text is
+	/// kept ([[abcdefg|Notes]] becomes [[hijkmnp|Notes]]).
+	pub fn retarget_references(
+		&self,
+		source_id: &DissociatedNuttyId,
+		target_id: &DissociatedNuttyId,
+	) -> Option<BlockContent> {
+		let re = Regex::new(&format!(
+			r"\[\[\s*{}\s*(\|[^]]+)?\]\]",
+			regex::escape(&source_id.nid())
+		))
+		.unwrap();
+
+		let target = target_id.nid();
+
+		self.rewrite_markdown(|markdown| {
+			re.replace_all(markdown, |captures: &regex::Captures| {
+				let display = captures.get(1).map_or("", |display| display.as_str());
+				format!("[[{target}{display}]]")
+			})
+			.into_owned()
+		})
+	}
+
+	/// Rewrite the markdown of the content, returning the updated content if
+	/// anything changed.
+	pub fn rewrite_markdown(&self, rewrite: impl Fn(&str) -> String) -> Option<BlockContent> {
+		let rewritten = match self {
+			BlockContent::Page { .. } | BlockContent::Query { .. } | BlockContent::Custom { .. } => {
+				return None;
+			}
+			BlockContent::Heading { markdown } => BlockContent::Heading {
+				markdown: rewrite(markdown),
+			},
+			BlockContent::Paragraph { markdown } => BlockContent::Paragraph {
+				markdown: rewrite(markdown),
+			},
+			BlockContent::Todo { markdown, done } => BlockContent::Todo {
+				markdown: rewrite(markdown),
+				done: *done,
+			},
+		};
+
+		match (self, &rewritten) {
+			(BlockContent::Heading { markdown: a }, BlockContent::Heading { markdown: b })
+			| (BlockContent::Paragraph { markdown: a }, BlockContent::Paragraph { markdown: b })
+			| (BlockContent::Todo { markdown: a, .. }, BlockContent::Todo { markdown: b, .. })
+				if a == b =>
+			{
+				None
+			}
+			_ => Some(rewritten),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_dates() {
+		let date = |s| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+
+		// Daily notes are pages titled with a date.
+		let content = BlockContent::Page {
+			title: "2025-07-24".to_string(),
+		};
+
+		assert_eq!(
+			content.parse_dates(),
+			vec![(date("2025-07-24"), BlockDateKind::Daily)]
+		);
+
+		let content = BlockContent::Page {
+			title: "Not 2025-07-24".to_string(),
+		};
+
+		assert!(content.parse_dates().is_empty());
+
+		// Due dates are marked in markdown; invalid dates are ignored.
+		let content = BlockContent::Paragraph {
+			markdown: "due:2025-08-01 Write docs (due:2025-02-30) overdue:2025-01-01 due:2025-07-30"
+				.to_string(),
+		};
+
+		assert_eq!(
+			content.parse_dates(),
+			vec![
+				(date("2025-07-30"), BlockDateKind::Due),
+				(date("2025-08-01"), BlockDateKind::Due),
+			]
+		);
+	}
+
+	#[test]
+	fn test_rename_title_references() {
+		let target_id = DissociatedNuttyId::new("abcdefg").unwrap();
+
+		let content = BlockContent::Paragraph {
+			markdown: "See [[Old Title]] and [[abcdefg|Old Title]], not [[abcdefg|Other]]."
+				.to_string(),
+		};
+
+		let renamed = content
+			.rename_title_references(&target_id, "Old Title", "New Title")
+			.expect("Content should have been rewritten");
+
+		assert!(matches!(
+			renamed,
+			BlockContent::Paragraph { markdown }
+				if markdown == "See [[New Title]] and [[abcdefg|New Title]], not [[abcdefg|Other]]."
+		));
+	}
+
+	#[test]
+	fn test_parse_title_references() {
+		let content = BlockContent::Todo {
+			markdown: "Read [[Old Title]], [[abcdefg|Old Title]] and [[Other]], then [[Old Title]]"
+				.to_string(),
+			done: false,
+		};
+
+		assert_eq!(content.parse_title_references(), vec!["Old Title", "Other"]);
+
+		let content = BlockContent::Page {
+			title: "[[Old Title]]".to_string(),
+		};
+
+		assert!(content.parse_title_references().is_empty());
+	}
+
+	#[test]
+	fn test_rename_tag_display_text() {
+		let target_id = DissociatedNuttyId::new("abcdefg").unwrap();
+
+		let content = BlockContent::Paragraph {
+			markdown: "See [[Old Title]] and [[abcdefg|Old Title]].".to_string(),
+		};
+
+		let renamed = content
+			.rename_tag_display_text(&target_id, "Old Title", "New Title")
+			.expect("Content should have been rewritten");
+
+		assert!(matches!(
+			renamed,
+			BlockContent::Paragraph { markdown }
+				if markdown == "See [[Old Title]] and [[abcdefg|New Title]]."
+		));
+	}
+
+	#[test]
+	fn test_rename_title_references_unchanged() {
+		let target_id = DissociatedNuttyId::new("abcdefg").unwrap();
+
+		// No references to the old title.
+		let content = BlockContent::Heading {
+			markdown: "Nothing to see here: [[abcdefg]]".to_string(),
+		};
+
+		assert!(
+			content
+				.rename_title_references(&target_id, "Old Title", "New Title")
+				.is_none()
+		);
+
+		// Pages don't carry links.
+		let content = BlockContent::Page {
+			title: "[[Old Title]]".to_string(),
+		};
+
+		assert!(
+			content
+				.rename_title_references(&target_id, "Old Title", "New Title")
+				.is_none()
+		);
+	}
+
+	#[test]
+	fn test_unlink_references() {
+		let target_id = DissociatedNuttyId::new("abcdefg").unwrap();
+
+		let content = BlockContent::Todo {
+			markdown: "Read [[abcdefg]] and [[abcdefg|my notes]], then [[hijklmn]].".to_string(),
+			done: true,
+		};
+
+		let unlinked = content
+			.unlink_references(&target_id, "Reading List")
+			.expect("Content should have been rewritten");
+
+		assert!(matches!(
+			unlinked,
+			BlockContent::Todo { markdown, done: true }
+				if markdown == "Read Reading List and my notes, then [[hijklmn]]."
+		));
+
+		let content = BlockContent::Paragraph {
+			markdown: "Only [[hijklmn]] here.".to_string(),
+		};
+
+		assert!(
+			content
+				.unlink_references(&target_id, "Reading List")
+				.is_none()
+		);
+	}
+
+	#[test]
+	fn test_retarget_references() {
+		let source_id = DissociatedNuttyId::new("abcdefg").unwrap();
+		let target_id = DissociatedNuttyId::new("hijkmnp").unwrap();
+
+		let content = BlockContent::Paragraph {
+			markdown: "See [[abcdefg]] and [[ abcdefg |my notes]].".to_string(),
+		};
+
+		let retargeted = content
+			.retarget_references(&source_id, &target_id)
+			.expect("Content should have been rewritten");
+
+		assert!(matches!(
+			retargeted,
+			BlockContent::Paragraph { markdown }
+				if markdown == "See [[hijkmnp]] and [[hijkmnp|my notes]]."
+		));
+
+		assert!(
+			BlockContent::Paragraph {
+				markdown: "Only [[hijkmnp]] here.".to_string(),
+			}
+			.retarget_references(&source_id, &target_id)
+			.is_none()
+		);
+	}
 }
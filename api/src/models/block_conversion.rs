@@ -0,0 +1,199 @@
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::models::BlockContent;
+use crate::models::ContentBlock;
+
+/// A kind of content that blocks can be converted to and from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConvertibleKind {
+	Page,
+	Heading,
+	Paragraph,
+	Todo,
+}
+
+impl ConvertibleKind {
+	/// Get the kind of some content, if it's convertible.
+	pub fn of(content: &BlockContent) -> Option<Self> {
+		match content {
+			BlockContent::Page { .. } => Some(ConvertibleKind::Page),
+			BlockContent::Heading { .. } => Some(ConvertibleKind::Heading),
+			BlockContent::Paragraph { .. } => Some(ConvertibleKind::Paragraph),
+			BlockContent::Todo { .. } => Some(ConvertibleKind::Todo),
+			BlockContent::Query { .. } | BlockContent::Custom { .. } => None,
+		}
+	}
+
+	/// Get the name of the kind, e.g. "Paragraph".
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ConvertibleKind::Page => "Page",
+			ConvertibleKind::Heading => "Heading",
+			ConvertibleKind::Paragraph => "Paragraph",
+			ConvertibleKind::Todo => "Todo",
+		}
+	}
+}
+
+/// Content converted to another kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConvertedContent {
+	/// The converted content.
+	pub content: BlockContent,
+
+	/// The text after the first line, when only the first line fits, i.e.
+	/// when markdown becomes a page's title. It's kept in a new child.
+	pub overflow: Option<BlockContent>,
+}
+
+/// The outcome of converting a content block to another kind.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockConversion {
+	/// The converted block, which keeps its Nutty ID and links.
+	pub block: ContentBlock,
+
+	/// The child created for text that didn't fit, if any.
+	pub overflow: Option<ContentBlock>,
+}
+
+/// Convert content to another kind. Markdown becomes a page's title by its
+/// first line, without heading markers, and the rest overflows into a
+/// paragraph. A page's title becomes markdown as it is. Todos start undone.
+pub fn convert(
+	content: &BlockContent,
+	target: ConvertibleKind,
+) -> Result<ConvertedContent, BlockConversionError> {
+	let unsupported = || BlockConversionError::Unsupported {
+		from: content.kind().to_string(),
+		to: target.as_str(),
+	};
+
+	let text = match content {
+		BlockContent::Page { title } => title,
+		BlockContent::Heading { markdown } => markdown,
+		BlockContent::Paragraph { markdown } => markdown,
+		BlockContent::Todo { markdown, .. } => markdown,
+		BlockContent::Query { .. } | BlockContent::Custom { .. } => return Err(unsupported()),
+	};
+
+	if ConvertibleKind::of(content) == Some(target) {
+		return Err(unsupported());
+	}
+
+	let markdown = text.clone();
+
+	let converted = match target {
+		ConvertibleKind::Page => {
+			let (first_line, rest) = text.split_once('\n').unwrap_or((text, ""));
+			let title = first_line.trim().trim_start_matches('#').trim();
+
+			if title.is_empty() {
+				return Err(BlockConversionError::EmptyTitle);
+			}
+
+			let rest = rest.trim();
+
+			return Ok(ConvertedContent {
+				content: BlockContent::Page {
+					title: title.to_string(),
+				},
+				overflow: (!rest.is_empty()).then(|| BlockContent::Paragraph {
+					markdown: rest.to_string(),
+				}),
+			});
+		}
+
+		ConvertibleKind::Heading => BlockContent::Heading { markdown },
+		ConvertibleKind::Paragraph => BlockContent::Paragraph { markdown },
+		ConvertibleKind::Todo => BlockContent::Todo {
+			markdown,
+			done: false,
+		},
+	};
+
+	Ok(ConvertedContent {
+		content: converted,
+		overflow: None,
+	})
+}
+
+#[derive(Debug, Error)]
+pub enum BlockConversionError {
+	#[error("Can't convert {from} to {to}")]
+	Unsupported { from: String, to: &'static str },
+
+	#[error("A page needs a title, but the first line is empty")]
+	EmptyTitle,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn paragraph(markdown: &str) -> BlockContent {
+		BlockContent::Paragraph {
+			markdown: markdown.to_string(),
+		}
+	}
+
+	#[test]
+	fn test_convert() {
+		// Assert: The first line becomes the title, and the rest overflows.
+		let converted = convert(
+			&paragraph("# Groceries\n\nEggs, milk"),
+			ConvertibleKind::Page,
+		)
+		.unwrap();
+
+		assert_eq!(
+			converted.content,
+			BlockContent::Page {
+				title: "Groceries".to_string(),
+			}
+		);
+		assert_eq!(converted.overflow, Some(paragraph("Eggs, milk")));
+
+		// Assert: A single line doesn't overflow.
+		let converted = convert(&paragraph("Groceries"), ConvertibleKind::Page).unwrap();
+		assert_eq!(converted.overflow, None);
+
+		// Assert: Titles become markdown, and todos start undone.
+		let page = BlockContent::Page {
+			title: "Groceries".to_string(),
+		};
+
+		assert_eq!(
+			convert(&page, ConvertibleKind::Paragraph).unwrap().content,
+			paragraph("Groceries")
+		);
+		assert_eq!(
+			convert(&page, ConvertibleKind::Todo).unwrap().content,
+			BlockContent::Todo {
+				markdown: "Groceries".to_string(),
+				done: false,
+			}
+		);
+
+		// Assert: Empty titles, queries and conversions to the same kind fail.
+		assert!(matches!(
+			convert(&paragraph("\nEggs"), ConvertibleKind::Page),
+			Err(BlockConversionError::EmptyTitle)
+		));
+
+		let query = BlockContent::Query {
+			dsl: "tag:x".to_string(),
+		};
+
+		for (content, target) in [
+			(&query, ConvertibleKind::Page),
+			(&page, ConvertibleKind::Page),
+		] {
+			assert!(matches!(
+				convert(content, target),
+				Err(BlockConversionError::Unsupported { .. })
+			));
+		}
+	}
+}
@@ -0,0 +1,66 @@
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::models::NuttyId;
+
+/// What a date on a content block means.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockDateKind {
+	/// The block is the daily note for the date.
+	Daily,
+
+	/// The block is due on the date.
+	Due,
+}
+
+impl BlockDateKind {
+	/// Get the stored representation of the kind.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			BlockDateKind::Daily => "daily",
+			BlockDateKind::Due => "due",
+		}
+	}
+}
+
+impl FromStr for BlockDateKind {
+	type Err = BlockDateError;
+
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		match value {
+			"daily" => Ok(BlockDateKind::Daily),
+			"due" => Ok(BlockDateKind::Due),
+			_ => Err(BlockDateError::UnknownKind(value.to_string())),
+		}
+	}
+}
+
+/// A date extracted from a content block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockDate {
+	pub block_id: NuttyId,
+	pub date: NaiveDate,
+	pub kind: BlockDateKind,
+}
+
+impl BlockDate {
+	/// Create a new block date.
+	pub fn new(block_id: NuttyId, date: NaiveDate, kind: BlockDateKind) -> Self {
+		Self {
+			block_id,
+			date,
+			kind,
+		}
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum BlockDateError {
+	#[error("Unknown block date kind: {0}")]
+	UnknownKind(String),
+}
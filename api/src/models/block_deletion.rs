@@ -0,0 +1,21 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::NuttyId;
+
+/// The outcome of deleting a content block along with its descendants.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockDeletion {
+	/// The Nutty ID of the deleted block.
+	pub block_id: NuttyId,
+
+	/// The Nutty IDs of every deleted block, the block itself first.
+	pub deleted_ids: Vec<NuttyId>,
+
+	/// The number of links into the deleted blocks from blocks that remain.
+	/// Unless they were unlinked, those blocks are left with dangling tags.
+	pub inbound_link_count: i64,
+
+	/// The Nutty IDs of the blocks whose tags were converted to plain text.
+	pub unlinked_ids: Vec<NuttyId>,
+}
@@ -0,0 +1,131 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::ContentBlock;
+use crate::models::NuttyId;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+
+/// The blocks that a navigator owns under one top-level ancestor, which is
+/// the block itself for top-level blocks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RootBlockGroup {
+	/// The Nutty ID of the top-level ancestor.
+	pub root_id: NuttyId,
+
+	/// The number of owned blocks in the group.
+	pub block_count: i64,
+
+	/// When a block in the group was last modified.
+	pub last_modified_at: DateTimeRfc3339,
+
+	/// The Nutty IDs of the owned blocks, most recently modified first.
+	pub block_ids: Vec<NuttyId>,
+}
+
+impl RootBlockGroup {
+	/// Group the blocks that a navigator owns by top-level ancestor, most
+	/// recently modified group first, as the repository does. Ancestors are
+	/// looked up among the given blocks.
+	pub fn group_owned<'a>(
+		blocks: impl IntoIterator<Item = &'a ContentBlock>,
+		owner_id: &NuttyId,
+	) -> Vec<Self> {
+		let blocks: HashMap<NuttyId, &ContentBlock> = blocks
+			.into_iter()
+			.map(|block| (*block.nutty_id(), block))
+			.collect();
+
+		let mut groups: HashMap<NuttyId, Vec<&ContentBlock>> = HashMap::new();
+
+		for block in blocks.values().filter(|block| block.is_owned_by(owner_id)) {
+			let mut root = *block;
+
+			while let Some(parent) = root.parent_id.and_then(|id| blocks.get(&id)) {
+				root = parent;
+			}
+
+			groups.entry(*root.nutty_id()).or_default().push(block);
+		}
+
+		let mut groups: Vec<Self> = groups
+			.into_iter()
+			.map(|(root_id, mut owned)| {
+				owned.sort_by(|a, b| {
+					latest_first(a.updated_at(), b.updated_at())
+						.then_with(|| a.nutty_id().uuid().cmp(b.nutty_id().uuid()))
+				});
+
+				Self {
+					root_id,
+					block_count: owned.len() as i64,
+					last_modified_at: *owned[0].updated_at(),
+					block_ids: owned.iter().map(|block| *block.nutty_id()).collect(),
+				}
+			})
+			.collect();
+
+		groups.sort_by(|a, b| {
+			latest_first(&a.last_modified_at, &b.last_modified_at)
+				.then_with(|| a.root_id.uuid().cmp(b.root_id.uuid()))
+		});
+
+		groups
+	}
+}
+
+/// Order timestamps from latest to earliest.
+fn latest_first(a: &DateTimeRfc3339, b: &DateTimeRfc3339) -> Ordering {
+	b.partial_cmp(a).unwrap_or(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::models::BlockContent;
+	use crate::models::FractionalIndex;
+
+	#[test]
+	fn test_group_owned() {
+		let alice_id = NuttyId::now();
+		let bob_id = NuttyId::now();
+
+		let block = |parent_id: Option<NuttyId>, owner_id| {
+			ContentBlock::now_with_owner(
+				parent_id,
+				owner_id,
+				FractionalIndex::start(),
+				BlockContent::Paragraph {
+					markdown: String::new(),
+				},
+			)
+		};
+
+		// Arrange: Alice owns a page with a child, and a child of Bob's page.
+		let page = block(None, alice_id);
+		let child = block(Some(*page.nutty_id()), alice_id);
+		let bobs_page = block(None, bob_id);
+		let bobs_child = block(Some(*bobs_page.nutty_id()), bob_id);
+		let grandchild = block(Some(*bobs_child.nutty_id()), alice_id);
+
+		let blocks = [&page, &child, &bobs_page, &bobs_child, &grandchild];
+
+		// Act
+		let mut groups = RootBlockGroup::group_owned(blocks, &alice_id);
+		groups.sort_by_key(|group| *group.root_id.uuid());
+
+		// Assert: Blocks are grouped under their top-level ancestor, whoever
+		// owns it.
+		let mut expected = vec![(*page.nutty_id(), 2), (*bobs_page.nutty_id(), 1)];
+		expected.sort_by_key(|(root_id, _)| *root_id.uuid());
+
+		let counts: Vec<_> = groups
+			.iter()
+			.map(|group| (group.root_id, group.block_count))
+			.collect();
+
+		assert_eq!(counts, expected);
+	}
+}
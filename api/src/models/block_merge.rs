@@ -0,0 +1,228 @@
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::models::BlockContent;
+use crate::models::ContentBlock;
+use crate::models::DissociatedNuttyId;
+use crate::models::NuttyId;
+use crate::models::content_diff::common_paragraphs;
+
+/// How to merge a source block's content into a target block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeStrategy {
+	/// Append the source's text to the target's.
+	Concatenate,
+
+	/// Merge the changes both blocks made since a common revision, by
+	/// paragraph. Fails if both changed the same paragraphs differently.
+	ThreeWay {
+		base_revision_id: DissociatedNuttyId,
+	},
+}
+
+/// The outcome of merging a source block into a target block.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockMerge {
+	/// The target block, with the merged content.
+	pub block: ContentBlock,
+
+	/// The Nutty ID of the source block, which was deleted.
+	pub source_id: NuttyId,
+
+	/// The Nutty IDs of the blocks whose tags were pointed at the target.
+	pub relinked_ids: Vec<NuttyId>,
+
+	/// The Nutty IDs of the source's children, now under the target.
+	pub moved_ids: Vec<NuttyId>,
+}
+
+/// Paragraphs that the source and target changed differently since their
+/// common revision.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeConflict {
+	pub base: Vec<String>,
+	pub source: Vec<String>,
+	pub target: Vec<String>,
+}
+
+/// Append a source's text to a target's, keeping the target's kind. Pages
+/// are titled by the target alone.
+pub fn concatenate(source: &BlockContent, target: &BlockContent) -> BlockContent {
+	match (text(source), target) {
+		(_, BlockContent::Page { .. }) => target.clone(),
+		(source, _) if source.trim().is_empty() => target.clone(),
+		(source, target) => with_text(target, format!("{}\n\n{source}", text(target))),
+	}
+}
+
+/// Merge the changes that a source and target made to a common base, taking
+/// the target's kind. Paragraphs that only one side changed take that side's
+/// change.
+pub fn merge_three_way(
+	base: &BlockContent,
+	source: &BlockContent,
+	target: &BlockContent,
+) -> Result<BlockContent, BlockMergeError> {
+	let base = split(text(base));
+	let source = split(text(source));
+	let target_paragraphs = split(text(target));
+
+	// Paragraphs that neither side changed split the rest into chunks.
+	let source_pairs = common_paragraphs(&base, &source);
+	let target_pairs = common_paragraphs(&base, &target_paragraphs);
+
+	let stable = source_pairs.iter().filter_map(|&(o, s)| {
+		target_pairs
+			.iter()
+			.find(|&&(other, _)| other == o)
+			.map(|&(_, t)| (o, s, t))
+	});
+
+	let mut merged: Vec<&str> = vec![];
+	let mut conflicts = vec![];
+	let (mut o0, mut s0, mut t0) = (0, 0, 0);
+
+	// End with a sentinel, to merge the trailing chunk.
+	for (o, s, t) in stable.chain([(base.len(), source.len(), target_paragraphs.len())]) {
+		let (base_chunk, source_chunk, target_chunk) =
+			(&base[o0..o], &source[s0..s], &target_paragraphs[t0..t]);
+
+		if source_chunk == base_chunk || source_chunk == target_chunk {
+			merged.extend(target_chunk);
+		} else if target_chunk == base_chunk {
+			merged.extend(source_chunk);
+		} else {
+			let strings = |chunk: &[&str]| chunk.iter().map(|p| p.to_string()).collect();
+
+			conflicts.push(MergeConflict {
+				base: strings(base_chunk),
+				source: strings(source_chunk),
+				target: strings(target_chunk),
+			});
+		}
+
+		if o < base.len() {
+			merged.push(base[o]);
+		}
+
+		(o0, s0, t0) = (o + 1, s + 1, t + 1);
+	}
+
+	if !conflicts.is_empty() {
+		return Err(BlockMergeError::Conflicts(conflicts));
+	}
+
+	Ok(with_text(target, merged.join("\n\n")))
+}
+
+/// Get the text that a content's merged by: a page's title, or its markdown.
+/// Custom content has none, so merges keep the target's.
+fn text(content: &BlockContent) -> &str {
+	match content {
+		BlockContent::Page { title } => title,
+		BlockContent::Heading { markdown } => markdown,
+		BlockContent::Paragraph { markdown } => markdown,
+		BlockContent::Query { dsl } => dsl,
+		BlockContent::Todo { markdown, .. } => markdown,
+		BlockContent::Custom { .. } => "",
+	}
+}
+
+/// Replace a content's text, keeping its kind.
+fn with_text(content: &BlockContent, text: String) -> BlockContent {
+	match content {
+		BlockContent::Page { .. } => BlockContent::Page { title: text },
+		BlockContent::Heading { .. } => BlockContent::Heading { markdown: text },
+		BlockContent::Paragraph { .. } => BlockContent::Paragraph { markdown: text },
+		BlockContent::Query { .. } => BlockContent::Query { dsl: text },
+		BlockContent::Todo { done, .. } => BlockContent::Todo {
+			markdown: text,
+			done: *done,
+		},
+		BlockContent::Custom { .. } => content.clone(),
+	}
+}
+
+fn split(text: &str) -> Vec<&str> {
+	text
+		.split("\n\n")
+		.map(str::trim)
+		.filter(|paragraph| !paragraph.is_empty())
+		.collect()
+}
+
+#[derive(Debug, Error)]
+pub enum BlockMergeError {
+	#[error("Can't merge a block into itself or its descendants")]
+	IntoSelf,
+
+	#[error("{} conflicting change(s) since the common revision", .0.len())]
+	Conflicts(Vec<MergeConflict>),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn paragraph(markdown: &str) -> BlockContent {
+		BlockContent::Paragraph {
+			markdown: markdown.to_string(),
+		}
+	}
+
+	fn markdown(content: &BlockContent) -> &str {
+		text(content)
+	}
+
+	#[test]
+	fn test_concatenate() {
+		let merged = concatenate(&paragraph("Second"), &paragraph("First"));
+		assert_eq!(markdown(&merged), "First\n\nSecond");
+
+		// Assert: Pages keep the target's title.
+		let page = BlockContent::Page {
+			title: "Notes".to_string(),
+		};
+
+		assert_eq!(markdown(&concatenate(&paragraph("Second"), &page)), "Notes");
+		assert_eq!(
+			markdown(&concatenate(&paragraph(" "), &paragraph("First"))),
+			"First"
+		);
+	}
+
+	#[test]
+	fn test_merge_three_way() {
+		let base = paragraph("Intro\n\nMiddle\n\nOutro");
+		let source = paragraph("Intro, edited offline\n\nMiddle\n\nOutro");
+		let target = paragraph("Intro\n\nMiddle\n\nOutro\n\nAppendix");
+
+		// Assert: Changes on either side are kept.
+		let merged = merge_three_way(&base, &source, &target).unwrap();
+		assert_eq!(
+			markdown(&merged),
+			"Intro, edited offline\n\nMiddle\n\nOutro\n\nAppendix"
+		);
+
+		// Assert: Identical changes aren't conflicts.
+		let merged = merge_three_way(&base, &source, &source).unwrap();
+		assert_eq!(markdown(&merged), markdown(&source));
+
+		// Assert: Different changes to the same paragraph conflict.
+		let other = paragraph("Intro, edited online\n\nMiddle\n\nOutro");
+		let Err(BlockMergeError::Conflicts(conflicts)) = merge_three_way(&base, &source, &other)
+		else {
+			panic!("Expected conflicts");
+		};
+
+		assert_eq!(
+			conflicts,
+			vec![MergeConflict {
+				base: vec!["Intro".to_string()],
+				source: vec!["Intro, edited offline".to_string()],
+				target: vec!["Intro, edited online".to_string()],
+			}]
+		);
+	}
+}
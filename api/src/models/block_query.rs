@@ -0,0 +1,312 @@
+use chrono::DateTime;
+use chrono::FixedOffset;
+use chrono::NaiveDate;
+use thiserror::Error;
+
+use crate::models::nutty_id::DissociatedNuttyId;
+use crate::models::nutty_id::NuttyIdError;
+use crate::models::property::BlockProperties;
+use crate::models::property::parse_property_value;
+
+/// A parsed block query, as written in a [BlockContent::Query] block.
+///
+/// The query DSL is a whitespace-separated list of filters:
+///
+/// ```text
+/// kind:paragraph               — Match blocks of a given kind (repeatable, OR'd).
+/// tag:abcdefg                  — Match blocks linking to a Nutty ID (repeatable, AND'd).
+/// created>=2025-01-01          — Match blocks by creation date (>, >=, <, <=).
+/// updated<2025-02-01T00:00:00Z — Match blocks by update date (>, >=, <, <=).
+/// prop:status=open             — Match blocks by a custom property (repeatable, AND'd).
+/// limit:25                     — Limit the number of matching blocks.
+/// ```
+///
+/// [BlockContent::Query]: crate::models::BlockContent::Query
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockQuery {
+	/// The block kinds to match, if any.
+	pub kinds: Vec<String>,
+
+	/// The Nutty IDs that matching blocks must link to.
+	pub tags: Vec<DissociatedNuttyId>,
+
+	/// The date filters that matching blocks must satisfy.
+	pub date_filters: Vec<DateFilter>,
+
+	/// The custom properties that matching blocks must have.
+	pub properties: BlockProperties,
+
+	/// The maximum number of matching blocks.
+	pub limit: i64,
+}
+
+/// A comparison against one of a content block's timestamps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DateFilter {
+	pub field: DateField,
+	pub comparison: Comparison,
+	pub value: DateTime<FixedOffset>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DateField {
+	Created,
+	Updated,
+}
+
+impl DateField {
+	/// Get the column name of the timestamp.
+	pub fn column(&self) -> &'static str {
+		match self {
+			DateField::Created => "created_at",
+			DateField::Updated => "updated_at",
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+	GreaterThan,
+	GreaterThanOrEqual,
+	LessThan,
+	LessThanOrEqual,
+}
+
+impl Comparison {
+	/// Get the SQL operator of the comparison.
+	pub fn operator(&self) -> &'static str {
+		match self {
+			Comparison::GreaterThan => ">",
+			Comparison::GreaterThanOrEqual => ">=",
+			Comparison::LessThan => "<",
+			Comparison::LessThanOrEqual => "<=",
+		}
+	}
+}
+
+impl BlockQuery {
+	/// The default number of matching blocks.
+	pub const DEFAULT_LIMIT: i64 = 50;
+
+	/// The maximum number of matching blocks.
+	pub const MAX_LIMIT: i64 = 200;
+
+	/// Parse a block query from its DSL.
+	pub fn parse(dsl: &str) -> Result<Self, BlockQueryError> {
+		let mut query = BlockQuery {
+			kinds: vec![],
+			tags: vec![],
+			date_filters: vec![],
+			properties: BlockProperties::new(),
+			limit: Self::DEFAULT_LIMIT,
+		};
+
+		for term in dsl.split_whitespace() {
+			if let Some(kind) = term.strip_prefix("kind:") {
+				query.kinds.push(Self::parse_kind(kind)?);
+			} else if let Some(nid) = term.strip_prefix("tag:") {
+				query.tags.push(DissociatedNuttyId::new(nid)?);
+			} else if let Some(property) = term.strip_prefix("prop:") {
+				let (name, value) = property
+					.split_once('=')
+					.filter(|(name, value)| !name.is_empty() && !value.is_empty())
+					.ok_or_else(|| BlockQueryError::InvalidProperty(property.to_string()))?;
+
+				query
+					.properties
+					.insert(name.to_string(), parse_property_value(value));
+			} else if let Some(limit) = term.strip_prefix("limit:") {
+				query.limit = limit
+					.parse::<i64>()
+					.ok()
+					.filter(|limit| (1..=Self::MAX_LIMIT).contains(limit))
+					.ok_or_else(|| BlockQueryError::InvalidLimit(limit.to_string()))?;
+			} else if let Some(filter) = term.strip_prefix("created") {
+				query
+					.date_filters
+					.push(Self::parse_date_filter(DateField::Created, filter)?);
+			} else if let Some(filter) = term.strip_prefix("updated") {
+				query
+					.date_filters
+					.push(Self::parse_date_filter(DateField::Updated, filter)?);
+			} else {
+				return Err(BlockQueryError::UnknownTerm(term.to_string()));
+			}
+		}
+
+		Ok(query)
+	}
+
+	/// Map a DSL kind onto the serialized [BlockContent] kind.
+	///
+	/// [BlockContent]: crate::models::BlockContent
+	fn parse_kind(kind: &str) -> Result<String, BlockQueryError> {
+		match kind.to_lowercase().as_str() {
+			"page" => Ok("Page".to_string()),
+			"heading" => Ok("Heading".to_string()),
+			"paragraph" => Ok("Paragraph".to_string()),
+			"query" => Ok("Query".to_string()),
+			"todo" => Ok("Todo".to_string()),
+			_ => Err(BlockQueryError::UnknownKind(kind.to_string())),
+		}
+	}
+
+	/// Parse a date filter like `>=2025-01-01`.
+	fn parse_date_filter(field: DateField, filter: &str) -> Result<DateFilter, BlockQueryError> {
+		// Check the two-character operators first.
+		let (comparison, value) = if let Some(value) = filter.strip_prefix(">=") {
+			(Comparison::GreaterThanOrEqual, value)
+		} else if let Some(value) = filter.strip_prefix("<=") {
+			(Comparison::LessThanOrEqual, value)
+		} else if let Some(value) = filter.strip_prefix('>') {
+			(Comparison::GreaterThan, value)
+		} else if let Some(value) = filter.strip_prefix('<') {
+			(Comparison::LessThan, value)
+		} else {
+			return Err(BlockQueryError::InvalidComparison(filter.to_string()));
+		};
+
+		// Accept either a full RFC 3339 timestamp or a plain date (midnight UTC).
+		let value = DateTime::parse_from_rfc3339(value)
+			.ok()
+			.or_else(|| {
+				NaiveDate::parse_from_str(value, "%Y-%m-%d")
+					.ok()
+					.and_then(|date| date.and_hms_opt(0, 0, 0))
+					.map(|date| date.and_utc().fixed_offset())
+			})
+			.ok_or_else(|| BlockQueryError::InvalidDate(value.to_string()))?;
+
+		Ok(DateFilter {
+			field,
+			comparison,
+			value,
+		})
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum BlockQueryError {
+	#[error("Unknown query term: {0}")]
+	UnknownTerm(String),
+
+	#[error("Unknown block kind: {0}")]
+	UnknownKind(String),
+
+	#[error("Invalid tag: {0}")]
+	InvalidTag(#[from] NuttyIdError),
+
+	#[error("Invalid comparison: {0}")]
+	InvalidComparison(String),
+
+	#[error("Invalid date: {0}")]
+	InvalidDate(String),
+
+	#[error("Invalid limit: {0}")]
+	InvalidLimit(String),
+
+	#[error("Invalid property filter: {0}")]
+	InvalidProperty(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::Value;
+
+	use super::*;
+
+	#[test]
+	fn test_parse_query() {
+		let query =
+			BlockQuery::parse("kind:paragraph kind:Heading tag:abcdefg created>=2025-01-01 limit:10")
+				.unwrap();
+
+		assert_eq!(query.kinds, vec!["Paragraph", "Heading"]);
+		assert_eq!(query.tags.len(), 1);
+		assert_eq!(query.tags[0].nid(), "abcdefg");
+		assert_eq!(query.limit, 10);
+
+		assert_eq!(query.date_filters.len(), 1);
+		assert_eq!(query.date_filters[0].field, DateField::Created);
+		assert_eq!(
+			query.date_filters[0].comparison,
+			Comparison::GreaterThanOrEqual
+		);
+		assert_eq!(
+			query.date_filters[0].value.to_rfc3339(),
+			"2025-01-01T00:00:00+00:00"
+		);
+	}
+
+	#[test]
+	fn test_parse_property_filters() {
+		let query = BlockQuery::parse("prop:status=open prop:estimate=3").unwrap();
+
+		assert_eq!(query.properties["status"], Value::from("open"));
+		assert_eq!(query.properties["estimate"], Value::from(3));
+
+		assert!(matches!(
+			BlockQuery::parse("prop:status"),
+			Err(BlockQueryError::InvalidProperty(_))
+		));
+	}
+
+	#[test]
+	fn test_parse_empty_query() {
+		let query = BlockQuery::parse("  ").unwrap();
+
+		assert!(query.kinds.is_empty());
+		assert!(query.tags.is_empty());
+		assert!(query.date_filters.is_empty());
+		assert_eq!(query.limit, BlockQuery::DEFAULT_LIMIT);
+	}
+
+	#[test]
+	fn test_parse_date_filters() {
+		let query =
+			BlockQuery::parse("updated<2025-02-01T12:00:00+02:00 created>2024-12-31").unwrap();
+
+		assert_eq!(query.date_filters[0].field, DateField::Updated);
+		assert_eq!(query.date_filters[0].comparison, Comparison::LessThan);
+		assert_eq!(query.date_filters[1].field, DateField::Created);
+		assert_eq!(query.date_filters[1].comparison, Comparison::GreaterThan);
+	}
+
+	#[test]
+	fn test_parse_invalid_query() {
+		assert!(matches!(
+			BlockQuery::parse("title:foo"),
+			Err(BlockQueryError::UnknownTerm(_))
+		));
+
+		assert!(matches!(
+			BlockQuery::parse("kind:table"),
+			Err(BlockQueryError::UnknownKind(_))
+		));
+
+		assert!(matches!(
+			BlockQuery::parse("tag:nope"),
+			Err(BlockQueryError::InvalidTag(_))
+		));
+
+		assert!(matches!(
+			BlockQuery::parse("created=2025-01-01"),
+			Err(BlockQueryError::InvalidComparison(_))
+		));
+
+		assert!(matches!(
+			BlockQuery::parse("created>=yesterday"),
+			Err(BlockQueryError::InvalidDate(_))
+		));
+
+		assert!(matches!(
+			BlockQuery::parse("limit:0"),
+			Err(BlockQueryError::InvalidLimit(_))
+		));
+
+		assert!(matches!(
+			BlockQuery::parse("limit:1000"),
+			Err(BlockQueryError::InvalidLimit(_))
+		));
+	}
+}
@@ -0,0 +1,18 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::BlockContent;
+use crate::models::NuttyId;
+
+/// A change to a content block's content, recorded for bulk edits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockRevision {
+	/// The Nutty ID of the revised block.
+	pub block_id: NuttyId,
+
+	/// The content before the revision.
+	pub previous_content: BlockContent,
+
+	/// The content after the revision.
+	pub content: BlockContent,
+}
@@ -0,0 +1,74 @@
+use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::models::ContentBlock;
+
+/// The outcome of splitting a paragraph in two.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockSplit {
+	/// The paragraph, with the text before the split.
+	pub block: ContentBlock,
+
+	/// The new paragraph that follows it, with the text after the split.
+	pub new_block: ContentBlock,
+}
+
+/// Split markdown at an offset, in characters. Tags can't be split, so that
+/// each half keeps the links it had whole.
+pub fn split_markdown(markdown: &str, offset: usize) -> Result<(String, String), BlockSplitError> {
+	let index = match markdown.char_indices().nth(offset) {
+		Some((index, _)) => index,
+		None if offset == markdown.chars().count() => markdown.len(),
+		None => return Err(BlockSplitError::OutOfBounds(offset)),
+	};
+
+	// Matches [[…]] like [NuttyTag::parse_all].
+	//
+	// [NuttyTag::parse_all]: crate::models::NuttyTag::parse_all
+	let re = Regex::new(r"\[\[([^]]+)\]\]").unwrap();
+
+	if re
+		.find_iter(markdown)
+		.any(|tag| tag.start() < index && index < tag.end())
+	{
+		return Err(BlockSplitError::InsideTag(offset));
+	}
+
+	let (head, tail) = markdown.split_at(index);
+	Ok((head.to_string(), tail.to_string()))
+}
+
+#[derive(Debug, Error)]
+pub enum BlockSplitError {
+	#[error("Offset {0} is past the end of the text")]
+	OutOfBounds(usize),
+
+	#[error("Offset {0} is inside a tag")]
+	InsideTag(usize),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_split_markdown() {
+		let split = |offset| split_markdown("Café, see [[abc|notes]].", offset);
+
+		// Assert: Offsets count characters, and may be at either end.
+		assert_eq!(
+			split(5).unwrap(),
+			("Café,".to_string(), " see [[abc|notes]].".to_string())
+		);
+		assert_eq!(split(0).unwrap().0, "");
+		assert_eq!(split(24).unwrap().1, "");
+
+		// Assert: Offsets past the end, or inside tags, are rejected.
+		assert!(matches!(split(25), Err(BlockSplitError::OutOfBounds(25))));
+		assert!(matches!(split(12), Err(BlockSplitError::InsideTag(12))));
+		assert!(split(10).is_ok());
+		assert!(split(23).is_ok());
+	}
+}
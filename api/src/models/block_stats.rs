@@ -0,0 +1,27 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::NuttyId;
+
+/// Deep counts of a content block, kept up to date as blocks and links
+/// change rather than counted on demand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockStats {
+	pub block_id: NuttyId,
+
+	/// The number of blocks nested under the block, at any depth.
+	pub descendant_count: i64,
+
+	/// The number of blocks that link to the block.
+	pub backlink_count: i64,
+}
+
+/// The outcome of checking the block stats against fresh counts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockStatsCheck {
+	/// The number of blocks checked.
+	pub checked_count: i64,
+
+	/// The stats that had drifted, as repaired.
+	pub repaired: Vec<BlockStats>,
+}
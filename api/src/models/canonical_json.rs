@@ -0,0 +1,141 @@
+use serde::Serialize;
+use serde_json::Number;
+use serde_json::Value;
+use sha2::Digest;
+use sha2::Sha256;
+use thiserror::Error;
+
+/// The largest integer that a double represents exactly.
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+/// Serialize a value to canonical JSON, so that equal values always produce
+/// the same bytes:
+///
+/// ```text
+/// • Object keys are sorted by code point.
+/// • No whitespace is written between tokens.
+/// • Integral numbers are written without a fraction or exponent.
+/// • Strings only escape what JSON requires.
+/// ```
+pub fn to_canonical_json<T: Serialize>(value: &T) -> Result<String, CanonicalJsonError> {
+	let value = serde_json::to_value(value).map_err(CanonicalJsonError::Serialize)?;
+	let mut output = String::new();
+	write_value(&mut output, &value)?;
+	Ok(output)
+}
+
+/// Hash a value's canonical JSON with SHA-256, as lowercase hex.
+pub fn canonical_hash<T: Serialize>(value: &T) -> Result<String, CanonicalJsonError> {
+	let json = to_canonical_json(value)?;
+	Ok(format!("{:x}", Sha256::digest(json.as_bytes())))
+}
+
+fn write_value(output: &mut String, value: &Value) -> Result<(), CanonicalJsonError> {
+	match value {
+		Value::Null => output.push_str("null"),
+		Value::Bool(value) => output.push_str(if *value { "true" } else { "false" }),
+		Value::Number(number) => write_number(output, number),
+		Value::String(value) => write_string(output, value)?,
+
+		Value::Array(values) => {
+			output.push('[');
+
+			for (i, value) in values.iter().enumerate() {
+				if i > 0 {
+					output.push(',');
+				}
+
+				write_value(output, value)?;
+			}
+
+			output.push(']');
+		}
+
+		Value::Object(object) => {
+			let mut entries: Vec<_> = object.iter().collect();
+			entries.sort_by_key(|(key, _)| *key);
+
+			output.push('{');
+
+			for (i, (key, value)) in entries.into_iter().enumerate() {
+				if i > 0 {
+					output.push(',');
+				}
+
+				write_string(output, key)?;
+				output.push(':');
+				write_value(output, value)?;
+			}
+
+			output.push('}');
+		}
+	}
+
+	Ok(())
+}
+
+fn write_number(output: &mut String, number: &Number) {
+	match number.as_f64() {
+		// Write integral floats as integers, so that `1.0` and `1` agree.
+		Some(float) if number.is_f64() && float.fract() == 0.0 && float.abs() <= MAX_SAFE_INTEGER => {
+			output.push_str(&(float as i64).to_string());
+		}
+
+		_ => output.push_str(&number.to_string()),
+	}
+}
+
+fn write_string(output: &mut String, value: &str) -> Result<(), CanonicalJsonError> {
+	let value = serde_json::to_string(value).map_err(CanonicalJsonError::Serialize)?;
+	output.push_str(&value);
+	Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum CanonicalJsonError {
+	#[error("Unable to serialize value: {0}")]
+	Serialize(serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+	use crate::models::BlockContent;
+
+	#[test]
+	fn test_to_canonical_json() {
+		let value = json!({
+			"title": "Caf\u{e9} \"notes\"\n",
+			"estimate": 2.0,
+			"ratio": 0.5,
+			"nested": { "b": [1, true, null], "a": {} },
+		});
+
+		assert_eq!(
+			to_canonical_json(&value).unwrap(),
+			r#"{"estimate":2,"nested":{"a":{},"b":[1,true,null]},"ratio":0.5,"title":"Café \"notes\"\n"}"#
+		);
+	}
+
+	#[test]
+	fn test_canonical_json_round_trip() {
+		let content = BlockContent::Todo {
+			markdown: "Water the *plants*.".to_string(),
+			done: false,
+		};
+
+		let json = to_canonical_json(&content).unwrap();
+		let parsed: BlockContent = serde_json::from_str(&json).unwrap();
+
+		// Parsing and re-serializing yields the same bytes.
+		assert_eq!(to_canonical_json(&parsed).unwrap(), json);
+
+		// Key order and number formatting don't affect the hash.
+		let a = json!({ "a": 1, "b": 1.0 });
+		let b: Value = serde_json::from_str(r#"{ "b": 1, "a": 1.0 }"#).unwrap();
+		assert_eq!(canonical_hash(&a).unwrap(), canonical_hash(&b).unwrap());
+		assert_eq!(canonical_hash(&a).unwrap().len(), 64);
+	}
+}
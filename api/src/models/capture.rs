@@ -0,0 +1,405 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+use url::Url;
+
+use crate::models::BlockContent;
+use crate::models::ContentBlock;
+use crate::models::FractionalIndex;
+use crate::models::NuttyId;
+
+/// The longest unfurled title or description kept, in characters.
+const MAX_UNFURL_LENGTH: usize = 300;
+
+/// The longest snapshot of a page's text kept, in characters.
+const MAX_SNAPSHOT_LENGTH: usize = 20_000;
+
+/// Elements whose contents aren't part of a page's readable text.
+const NON_READABLE_TAGS: [&str; 11] = [
+	"aside", "footer", "form", "header", "nav", "noscript", "script", "style", "svg", "template",
+	"title",
+];
+
+static TITLE: LazyLock<Regex> =
+	LazyLock::new(|| Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap());
+
+static META: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?is)<meta\s[^>]*>").unwrap());
+
+static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| {
+	Regex::new(r#"(?is)([a-z][a-z0-9:_-]*)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap()
+});
+
+static BLOCK_BOUNDARY: LazyLock<Regex> = LazyLock::new(|| {
+	Regex::new(r"(?i)</?(?:p|div|br|h[1-6]|li|tr|pre|section|article|blockquote)\b[^>]*>").unwrap()
+});
+
+static BLANK_LINES: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\n\s*\n").unwrap());
+
+/// A page captured from the browser, e.g. by a bookmarklet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Capture {
+	/// The URL of the page.
+	pub url: String,
+
+	/// The text selected on the page, if any.
+	#[serde(default)]
+	pub selection: String,
+
+	/// A note about the page, if any.
+	#[serde(default)]
+	pub note: String,
+
+	/// Whether to archive a snapshot of the page's text.
+	#[serde(default)]
+	pub archive: bool,
+}
+
+/// The metadata that a page describes itself with.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Unfurl {
+	/// The page's URL.
+	pub url: String,
+
+	/// The page's title.
+	pub title: Option<String>,
+
+	/// The page's description.
+	pub description: Option<String>,
+
+	/// The name of the site the page belongs to.
+	pub site_name: Option<String>,
+}
+
+/// A page's metadata and readable text, as fetched when it was captured.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageSnapshot {
+	/// The page's metadata.
+	pub unfurl: Unfurl,
+
+	/// The page's text, with a blank line between paragraphs.
+	pub text: String,
+}
+
+/// The blocks filed for a [Capture].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturedPage {
+	/// The block linking to the page.
+	pub block: ContentBlock,
+
+	/// The page's metadata, if it was fetched.
+	pub unfurl: Option<Unfurl>,
+
+	/// The snapshot of the page's text, if it was archived.
+	pub archive: Option<ContentBlock>,
+}
+
+impl Capture {
+	/// Parse the captured URL, which must be a web page.
+	pub fn parse_url(&self) -> Result<Url, CaptureError> {
+		let url = Url::parse(self.url.trim()).map_err(CaptureError::InvalidUrl)?;
+
+		match url.scheme() {
+			"http" | "https" if url.has_host() => Ok(url),
+			_ => Err(CaptureError::UnsupportedUrl(url.to_string())),
+		}
+	}
+
+	/// Convert the capture into a paragraph linking to the page, under the
+	/// given parent, followed by a child holding the page's text if it was
+	/// to be archived and a snapshot was taken.
+	pub fn to_blocks(
+		&self,
+		parent_id: NuttyId,
+		owner_id: NuttyId,
+		snapshot: Option<&PageSnapshot>,
+	) -> Result<Vec<ContentBlock>, CaptureError> {
+		let url = self.parse_url()?;
+
+		let title = snapshot
+			.and_then(|snapshot| snapshot.unfurl.title.as_deref())
+			.unwrap_or(url.as_str());
+
+		let mut paragraphs = vec![format!("[{}](<{}>)", escape_link_text(title), url)];
+
+		let selection = self.selection.trim();
+
+		if !selection.is_empty() {
+			let quote: Vec<_> = selection
+				.lines()
+				.map(|line| format!("> {}", line.trim_end()))
+				.collect();
+
+			paragraphs.push(quote.join("\n"));
+		}
+
+		let note = self.note.trim();
+
+		if !note.is_empty() {
+			paragraphs.push(note.to_string());
+		}
+
+		let block = ContentBlock::now_with_owner(
+			Some(parent_id),
+			owner_id,
+			FractionalIndex::start(),
+			BlockContent::Paragraph {
+				markdown: paragraphs.join("\n\n"),
+			},
+		);
+
+		let archive = snapshot
+			.filter(|snapshot| self.archive && !snapshot.text.is_empty())
+			.map(|snapshot| {
+				ContentBlock::now_with_owner(
+					Some(*block.nutty_id()),
+					owner_id,
+					FractionalIndex::start(),
+					BlockContent::Paragraph {
+						markdown: snapshot.text.clone(),
+					},
+				)
+			});
+
+		Ok(std::iter::once(block).chain(archive).collect())
+	}
+}
+
+impl PageSnapshot {
+	/// Read a page's metadata and readable text from its HTML.
+	pub fn from_html(url: &Url, html: &str) -> Self {
+		let mut unfurl = Unfurl {
+			url: url.to_string(),
+			..Unfurl::default()
+		};
+		let mut description = None;
+
+		for meta in META.find_iter(html) {
+			let mut key = None;
+			let mut content = None;
+
+			for attribute in ATTRIBUTE.captures_iter(meta.as_str()) {
+				let value = attribute.get(2).or(attribute.get(3)).map(|m| m.as_str());
+
+				match attribute[1].to_ascii_lowercase().as_str() {
+					"property" | "name" => key = value.map(str::to_ascii_lowercase),
+					"content" => content = value.and_then(|value| clean_text(value, MAX_UNFURL_LENGTH)),
+					_ => {}
+				}
+			}
+
+			let (Some(key), Some(content)) = (key, content) else {
+				continue;
+			};
+
+			match key.as_str() {
+				"og:title" => unfurl.title = Some(content),
+				"og:description" => unfurl.description = Some(content),
+				"og:site_name" => unfurl.site_name = Some(content),
+				"description" => description = Some(content),
+				_ => {}
+			}
+		}
+
+		// Fall back to the plain title and description.
+		unfurl.title = unfurl.title.or_else(|| {
+			TITLE
+				.captures(html)
+				.and_then(|title| clean_text(&title[1], MAX_UNFURL_LENGTH))
+		});
+
+		unfurl.description = unfurl.description.or(description);
+
+		Self {
+			unfurl,
+			text: readable_text(html),
+		}
+	}
+}
+
+/// Extract a page's readable text, with a blank line between paragraphs.
+fn readable_text(html: &str) -> String {
+	let html = BLOCK_BOUNDARY.replace_all(html, "\n\n");
+
+	let text = ammonia::Builder::empty()
+		.clean_content_tags(HashSet::from(NON_READABLE_TAGS))
+		.clean(&html)
+		.to_string();
+
+	let paragraphs: Vec<_> = BLANK_LINES
+		.split(&unescape(&text))
+		.map(|paragraph| paragraph.split_whitespace().collect::<Vec<_>>().join(" "))
+		.filter(|paragraph| !paragraph.is_empty())
+		.collect();
+
+	truncate(&paragraphs.join("\n\n"), MAX_SNAPSHOT_LENGTH)
+}
+
+/// Collapse the whitespace in some HTML text and decode its entities, or
+/// return `None` if there's nothing left.
+fn clean_text(text: &str, max_length: usize) -> Option<String> {
+	let text = unescape(text)
+		.split_whitespace()
+		.collect::<Vec<_>>()
+		.join(" ");
+	(!text.is_empty()).then(|| truncate(&text, max_length))
+}
+
+/// Decode the entities that commonly appear in text and attribute values.
+fn unescape(text: &str) -> String {
+	text
+		.replace("&nbsp;", " ")
+		.replace("&lt;", "<")
+		.replace("&gt;", ">")
+		.replace("&quot;", "\"")
+		.replace("&#39;", "'")
+		.replace("&#x27;", "'")
+		.replace("&amp;", "&")
+}
+
+/// Cut some text down to at most the given number of characters.
+fn truncate(text: &str, max_length: usize) -> String {
+	match text.char_indices().nth(max_length) {
+		Some((end, _)) => format!("{}…", text[..end].trim_end()),
+		None => text.to_string(),
+	}
+}
+
+/// Escape the characters that would end a markdown link's text early.
+fn escape_link_text(text: &str) -> String {
+	let mut escaped = String::with_capacity(text.len());
+
+	for c in text.chars() {
+		if matches!(c, '\\' | '[' | ']') {
+			escaped.push('\\');
+		}
+
+		escaped.push(c);
+	}
+
+	escaped
+}
+
+#[derive(Debug, Error)]
+pub enum CaptureError {
+	#[error("Invalid URL: {0}")]
+	InvalidUrl(url::ParseError),
+
+	#[error("Only web pages can be captured: {0}")]
+	UnsupportedUrl(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const HTML: &str = r#"
+		<html>
+			<head>
+				<title>Fallback title</title>
+				<meta property="og:title" content="Tea &amp; Biscuits">
+				<meta name='description' content='A guide to   tea.'>
+				<meta property="og:site_name" content="Nutty Kitchen" />
+				<style>body { color: red; }</style>
+			</head>
+			<body>
+				<nav><a href="/">Home</a></nav>
+				<h1>Tea</h1>
+				<p>Steep for <b>three</b>
+				minutes.</p>
+				<script>alert("Hi!");</script>
+				<p>Then add milk &lt;optional&gt;.</p>
+				<footer>© 2025</footer>
+			</body>
+		</html>
+	"#;
+
+	fn capture(url: &str) -> Capture {
+		Capture {
+			url: url.to_string(),
+			selection: "Steep for three\nminutes.".to_string(),
+			note: " Try this ".to_string(),
+			archive: true,
+		}
+	}
+
+	#[test]
+	fn test_page_snapshot_from_html() {
+		let url = Url::parse("https://example.com/tea").unwrap();
+		let snapshot = PageSnapshot::from_html(&url, HTML);
+
+		assert_eq!(
+			snapshot.unfurl,
+			Unfurl {
+				url: "https://example.com/tea".to_string(),
+				title: Some("Tea & Biscuits".to_string()),
+				description: Some("A guide to tea.".to_string()),
+				site_name: Some("Nutty Kitchen".to_string()),
+			}
+		);
+
+		assert_eq!(
+			snapshot.text,
+			"Tea\n\nSteep for three minutes.\n\nThen add milk <optional>."
+		);
+
+		// The title tag is used when the page has no Open Graph title.
+		let snapshot = PageSnapshot::from_html(&url, "<title> Plain\n title </title>");
+		assert_eq!(snapshot.unfurl.title.as_deref(), Some("Plain title"));
+		assert_eq!(snapshot.text, "");
+	}
+
+	#[test]
+	fn test_capture_to_blocks() {
+		let parent_id = NuttyId::now();
+		let owner_id = NuttyId::now();
+		let url = Url::parse("https://example.com/tea").unwrap();
+		let snapshot = PageSnapshot::from_html(&url, HTML);
+
+		let blocks = capture("https://example.com/tea")
+			.to_blocks(parent_id, owner_id, Some(&snapshot))
+			.unwrap();
+
+		assert_eq!(blocks.len(), 2);
+		assert_eq!(blocks[0].parent_id, Some(parent_id));
+		assert_eq!(blocks[1].parent_id, Some(*blocks[0].nutty_id()));
+		assert!(blocks.iter().all(|block| block.is_owned_by(&owner_id)));
+
+		assert!(matches!(
+			&blocks[0].content,
+			BlockContent::Paragraph { markdown } if markdown
+				== "[Tea & Biscuits](<https://example.com/tea>)\n\n> Steep for three\n> minutes.\n\nTry this"
+		));
+
+		assert!(matches!(
+			&blocks[1].content,
+			BlockContent::Paragraph { markdown } if *markdown == snapshot.text
+		));
+
+		// Without a snapshot, the link falls back to the URL.
+		let mut unarchived = capture("https://example.com/[tea]");
+		unarchived.selection.clear();
+		unarchived.archive = false;
+
+		let blocks = unarchived
+			.to_blocks(parent_id, owner_id, Some(&snapshot))
+			.unwrap();
+
+		assert_eq!(blocks.len(), 1);
+
+		let blocks = unarchived.to_blocks(parent_id, owner_id, None).unwrap();
+
+		assert!(matches!(
+			&blocks[0].content,
+			BlockContent::Paragraph { markdown } if markdown
+				== concat!(r"[https://example.com/\[tea\]](<https://example.com/[tea]>)", "\n\nTry this")
+		));
+
+		assert!(capture("ftp://example.com/tea").parse_url().is_err());
+		assert!(capture("javascript:alert(1)").parse_url().is_err());
+		assert!(capture("not a url").parse_url().is_err());
+	}
+}
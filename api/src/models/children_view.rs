@@ -0,0 +1,327 @@
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::models::ContentBlock;
+use crate::models::NuttyId;
+use crate::models::property::BlockProperties;
+use crate::models::property::parse_property_value;
+
+/// How a content block's children are presented, as a table of their
+/// custom properties. Written as query parameters on the context endpoint:
+///
+/// ```text
+/// sort=properties.due                 — Sort by a property, ascending.
+/// sort=-properties.due                — Sort by a property, descending.
+/// filter=properties.status:eq:open    — Match a property (eq, ne, lt, lte, gt, gte).
+/// filter=properties.a:eq:1,properties.b:gt:2 — Match every filter.
+/// ```
+///
+/// Children missing the sorted property come last, and ties keep their
+/// fractional index order. Filters only match values of the same type.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChildrenView {
+	/// The property to sort children by, if any.
+	pub sort: Option<PropertySort>,
+
+	/// The property filters that children must satisfy.
+	pub filters: Vec<PropertyFilter>,
+
+	/// Whether to leave out archived children, unless the parent is archived
+	/// too. Past trees don't record archival, so this doesn't apply to them.
+	pub hide_archived: bool,
+}
+
+/// A sort on one of a content block's custom properties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertySort {
+	pub name: String,
+	pub descending: bool,
+}
+
+/// A comparison against one of a content block's custom properties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyFilter {
+	pub name: String,
+	pub operator: PropertyOperator,
+	pub value: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PropertyOperator {
+	Equal,
+	NotEqual,
+	LessThan,
+	LessThanOrEqual,
+	GreaterThan,
+	GreaterThanOrEqual,
+}
+
+impl PropertyOperator {
+	/// Get the SQL operator of the comparison.
+	pub fn operator(&self) -> &'static str {
+		match self {
+			PropertyOperator::Equal => "=",
+			PropertyOperator::NotEqual => "<>",
+			PropertyOperator::LessThan => "<",
+			PropertyOperator::LessThanOrEqual => "<=",
+			PropertyOperator::GreaterThan => ">",
+			PropertyOperator::GreaterThanOrEqual => ">=",
+		}
+	}
+
+	/// Check if an ordering satisfies the comparison.
+	fn accepts(&self, ordering: Ordering) -> bool {
+		match self {
+			PropertyOperator::Equal => ordering.is_eq(),
+			PropertyOperator::NotEqual => ordering.is_ne(),
+			PropertyOperator::LessThan => ordering.is_lt(),
+			PropertyOperator::LessThanOrEqual => ordering.is_le(),
+			PropertyOperator::GreaterThan => ordering.is_gt(),
+			PropertyOperator::GreaterThanOrEqual => ordering.is_ge(),
+		}
+	}
+}
+
+impl FromStr for PropertyOperator {
+	type Err = ChildrenViewError;
+
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		match value {
+			"eq" => Ok(PropertyOperator::Equal),
+			"ne" => Ok(PropertyOperator::NotEqual),
+			"lt" => Ok(PropertyOperator::LessThan),
+			"lte" => Ok(PropertyOperator::LessThanOrEqual),
+			"gt" => Ok(PropertyOperator::GreaterThan),
+			"gte" => Ok(PropertyOperator::GreaterThanOrEqual),
+			_ => Err(ChildrenViewError::InvalidFilter(value.to_string())),
+		}
+	}
+}
+
+impl PropertyFilter {
+	/// Check if a block's properties satisfy the filter.
+	pub fn matches(&self, properties: &BlockProperties) -> bool {
+		properties
+			.get(&self.name)
+			.and_then(|value| compare_same_type(value, &self.value))
+			.is_some_and(|ordering| self.operator.accepts(ordering))
+	}
+}
+
+impl ChildrenView {
+	/// Parse a view from its `sort` and `filter` query parameters.
+	pub fn parse(sort: Option<&str>, filter: Option<&str>) -> Result<Self, ChildrenViewError> {
+		let sort = sort
+			.filter(|sort| !sort.is_empty())
+			.map(|sort| {
+				let (descending, field) = match sort.strip_prefix('-') {
+					Some(field) => (true, field),
+					None => (false, sort),
+				};
+
+				Self::parse_property_name(field)
+					.map(|name| PropertySort { name, descending })
+					.ok_or_else(|| ChildrenViewError::InvalidSort(sort.to_string()))
+			})
+			.transpose()?;
+
+		let filters = filter
+			.filter(|filter| !filter.is_empty())
+			.map(|filter| filter.split(',').map(Self::parse_filter).collect())
+			.transpose()?
+			.unwrap_or_default();
+
+		Ok(Self {
+			sort,
+			filters,
+			hide_archived: false,
+		})
+	}
+
+	/// Check if the view leaves children unsorted and unfiltered.
+	pub fn is_default(&self) -> bool {
+		self.sort.is_none() && self.filters.is_empty() && !self.hide_archived
+	}
+
+	/// Filter and sort children in memory, as the repository does in SQL.
+	/// Children are expected in fractional index order.
+	pub fn apply<'a>(&self, children: impl IntoIterator<Item = &'a ContentBlock>) -> Vec<NuttyId> {
+		let mut children: Vec<_> = children
+			.into_iter()
+			.filter(|child| self.filters.iter().all(|f| f.matches(&child.properties)))
+			.collect();
+
+		if let Some(sort) = &self.sort {
+			// A stable sort keeps ties in their original order.
+			children.sort_by(|a, b| {
+				match (a.properties.get(&sort.name), b.properties.get(&sort.name)) {
+					(Some(a), Some(b)) if sort.descending => compare_values(b, a),
+					(Some(a), Some(b)) => compare_values(a, b),
+					(Some(_), None) => Ordering::Less,
+					(None, Some(_)) => Ordering::Greater,
+					(None, None) => Ordering::Equal,
+				}
+			});
+		}
+
+		children
+			.into_iter()
+			.map(|child| *child.nutty_id())
+			.collect()
+	}
+
+	fn parse_filter(filter: &str) -> Result<PropertyFilter, ChildrenViewError> {
+		let invalid = || ChildrenViewError::InvalidFilter(filter.to_string());
+
+		let mut parts = filter.splitn(3, ':');
+		let name = parts.next().and_then(Self::parse_property_name);
+		let operator = parts.next().map(str::parse::<PropertyOperator>);
+		let value = parts.next().filter(|value| !value.is_empty());
+
+		match (name, operator, value) {
+			(Some(name), Some(Ok(operator)), Some(value)) => Ok(PropertyFilter {
+				name,
+				operator,
+				value: parse_property_value(value),
+			}),
+			_ => Err(invalid()),
+		}
+	}
+
+	fn parse_property_name(field: &str) -> Option<String> {
+		field
+			.strip_prefix("properties.")
+			.filter(|name| !name.is_empty())
+			.map(str::to_string)
+	}
+}
+
+/// Compare two values of the same JSON type, as Postgres compares `jsonb`.
+fn compare_same_type(a: &Value, b: &Value) -> Option<Ordering> {
+	match (a, b) {
+		(Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+		(Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+		(Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+		_ => None,
+	}
+}
+
+/// Order any two values, as Postgres orders `jsonb`: by type, then by value.
+fn compare_values(a: &Value, b: &Value) -> Ordering {
+	let rank = |value: &Value| match value {
+		Value::Null => 0,
+		Value::String(_) => 1,
+		Value::Number(_) => 2,
+		Value::Bool(_) => 3,
+		Value::Array(_) => 4,
+		Value::Object(_) => 5,
+	};
+
+	compare_same_type(a, b).unwrap_or_else(|| rank(a).cmp(&rank(b)))
+}
+
+#[derive(Debug, Error)]
+pub enum ChildrenViewError {
+	#[error("Invalid sort; expected properties.<name> or -properties.<name>: {0}")]
+	InvalidSort(String),
+
+	#[error("Invalid filter; expected properties.<name>:<eq|ne|lt|lte|gt|gte>:<value>: {0}")]
+	InvalidFilter(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+	use crate::models::BlockContent;
+	use crate::models::FractionalIndex;
+
+	#[test]
+	fn test_parse_children_view() {
+		let view = ChildrenView::parse(
+			Some("-properties.due"),
+			Some("properties.status:eq:open,properties.estimate:gte:2"),
+		)
+		.unwrap();
+
+		assert_eq!(
+			view.sort,
+			Some(PropertySort {
+				name: "due".to_string(),
+				descending: true,
+			})
+		);
+
+		assert_eq!(
+			view.filters,
+			vec![
+				PropertyFilter {
+					name: "status".to_string(),
+					operator: PropertyOperator::Equal,
+					value: json!("open"),
+				},
+				PropertyFilter {
+					name: "estimate".to_string(),
+					operator: PropertyOperator::GreaterThanOrEqual,
+					value: json!(2),
+				},
+			]
+		);
+
+		assert!(ChildrenView::parse(None, Some("")).unwrap().is_default());
+
+		for (sort, filter) in [
+			(Some("due"), None),
+			(Some("properties."), None),
+			(None, Some("properties.status:is:open")),
+			(None, Some("properties.status:eq")),
+			(None, Some("status:eq:open")),
+		] {
+			assert!(ChildrenView::parse(sort, filter).is_err());
+		}
+	}
+
+	#[test]
+	fn test_apply_children_view() {
+		let child = |properties: Value| {
+			ContentBlock::now(
+				None,
+				FractionalIndex::start(),
+				BlockContent::Paragraph {
+					markdown: String::new(),
+				},
+			)
+			.with_properties(serde_json::from_value(properties).unwrap())
+		};
+
+		let late = child(json!({ "status": "open", "due": "2025-09-01" }));
+		let early = child(json!({ "status": "open", "due": "2025-08-01" }));
+		let undated = child(json!({ "status": "open" }));
+		let done = child(json!({ "status": "done", "due": "2025-07-01" }));
+		let children = [&undated, &late, &done, &early];
+
+		let view =
+			ChildrenView::parse(Some("properties.due"), Some("properties.status:eq:open")).unwrap();
+
+		assert_eq!(
+			view.apply(children),
+			vec![*early.nutty_id(), *late.nutty_id(), *undated.nutty_id()]
+		);
+
+		let view = ChildrenView::parse(Some("-properties.due"), None).unwrap();
+
+		assert_eq!(
+			view.apply(children),
+			vec![
+				*late.nutty_id(),
+				*early.nutty_id(),
+				*done.nutty_id(),
+				*undated.nutty_id()
+			]
+		);
+	}
+}
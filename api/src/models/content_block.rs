@@ -8,7 +8,10 @@ use thiserror::Error;
 use crate::models::BlockContent;
 use crate::models::FractionalIndex;
 use crate::models::NuttyId;
+use crate::models::canonical_json::CanonicalJsonError;
+use crate::models::canonical_json::canonical_hash;
 use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+use crate::models::property::BlockProperties;
 
 /// A block of content in the Nuttyverse.
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -20,6 +23,15 @@ pub struct ContentBlock {
 	pub f_index: FractionalIndex,
 	#[sqlx(json)]
 	pub content: BlockContent,
+	#[sqlx(json)]
+	#[serde(default)]
+	pub properties: BlockProperties,
+
+	/// Whether access granted on ancestors applies to this block. Blocks that
+	/// opt out are only reachable through their own grants or ownership.
+	#[serde(default = "inherit_access_default")]
+	pub inherit_access: bool,
+
 	created_at: DateTimeRfc3339,
 	updated_at: DateTimeRfc3339,
 }
@@ -41,6 +53,8 @@ impl ContentBlock {
 			parent_id,
 			f_index,
 			content,
+			properties: BlockProperties::new(),
+			inherit_access: true,
 			created_at,
 			updated_at,
 		}
@@ -99,12 +113,19 @@ impl ContentBlock {
 		self.owner_id.as_ref()
 	}
 
+	/// Get the creation time.
+	pub fn created_at(&self) -> &DateTimeRfc3339 {
+		&self.created_at
+	}
+
+	/// Get the last update time.
+	pub fn updated_at(&self) -> &DateTimeRfc3339 {
+		&self.updated_at
+	}
+
 	/// Check if the content block is owned by the given navigator.
 	pub fn is_owned_by(&self, navigator_id: &NuttyId) -> bool {
-		self
-			.owner_id
-			.as_ref()
-			.map_or(false, |owner| owner == navigator_id)
+		self.owner_id.as_ref() == Some(navigator_id)
 	}
 
 	/// Serialize content to a JSON value.
@@ -112,6 +133,11 @@ impl ContentBlock {
 		serde_json::to_value(self.content.clone()).map_err(ContentBlockError::SerializationError)
 	}
 
+	/// Hash the canonical JSON of the content, for deduplication and ETags.
+	pub fn content_hash(&self) -> Result<String, ContentBlockError> {
+		canonical_hash(&self.content).map_err(ContentBlockError::CanonicalizationError)
+	}
+
 	/// Deserialize content from a JSON value.
 	pub fn deserialize_content(
 		content: serde_json::Value,
@@ -119,12 +145,45 @@ impl ContentBlock {
 		serde_json::from_value(content).map_err(ContentBlockError::DeserializationError)
 	}
 
+	/// Set the block's custom properties.
+	pub fn with_properties(mut self, properties: BlockProperties) -> Self {
+		self.properties = properties;
+		self
+	}
+
+	/// Set whether the block inherits access from its ancestors.
+	pub fn with_inherit_access(mut self, inherit_access: bool) -> Self {
+		self.inherit_access = inherit_access;
+		self
+	}
+
+	/// Get the ancestors (nearest first) that the block inherits access from:
+	/// up to and including the first that opts out of inheritance, or none if
+	/// the block opts out itself.
+	pub fn access_ancestors<'a>(&self, ancestors: &'a [ContentBlock]) -> &'a [ContentBlock] {
+		if !self.inherit_access {
+			return &[];
+		}
+
+		match ancestors
+			.iter()
+			.position(|ancestor| !ancestor.inherit_access)
+		{
+			Some(boundary) => &ancestors[..=boundary],
+			None => ancestors,
+		}
+	}
+
 	/// Create a builder for a new content block.
 	pub fn builder() -> ContentBlockBuilder {
 		ContentBlockBuilder::default()
 	}
 }
 
+fn inherit_access_default() -> bool {
+	true
+}
+
 #[derive(Debug, Error)]
 pub enum ContentBlockError {
 	#[error("SerializationError: {0}")]
@@ -132,6 +191,9 @@ pub enum ContentBlockError {
 
 	#[error("DeserializationError: {0}")]
 	DeserializationError(serde_json::Error),
+
+	#[error("CanonicalizationError: {0}")]
+	CanonicalizationError(CanonicalJsonError),
 }
 
 /// A builder for creating new content blocks.
@@ -142,6 +204,8 @@ pub struct ContentBlockBuilder {
 	parent_id: Option<NuttyId>,
 	f_index: Option<FractionalIndex>,
 	content: Option<BlockContent>,
+	properties: BlockProperties,
+	inherit_access: Option<bool>,
 	created_at: Option<DateTimeRfc3339>,
 	updated_at: Option<DateTimeRfc3339>,
 }
@@ -177,6 +241,18 @@ impl ContentBlockBuilder {
 		self
 	}
 
+	/// Set the custom properties.
+	pub fn properties(mut self, properties: BlockProperties) -> Self {
+		self.properties = properties;
+		self
+	}
+
+	/// Set whether the block inherits access from its ancestors.
+	pub fn inherit_access(mut self, inherit_access: bool) -> Self {
+		self.inherit_access = Some(inherit_access);
+		self
+	}
+
 	/// Set the "created at" time.
 	pub fn created_at(mut self, created_at: DateTimeRfc3339) -> Self {
 		self.created_at = Some(created_at);
@@ -199,7 +275,10 @@ impl ContentBlockBuilder {
 			.content
 			.ok_or(ContentBlockBuilderError::MissingContent)?;
 
-		match (self.nutty_id, self.created_at, self.updated_at) {
+		let properties = self.properties;
+		let inherit_access = self.inherit_access.unwrap_or(true);
+
+		let content_block = match (self.nutty_id, self.created_at, self.updated_at) {
 			// Either create the content block with all timestamps …
 			(Some(nutty_id), Some(created_at), Some(updated_at)) => {
 				if updated_at < created_at {
@@ -224,7 +303,13 @@ impl ContentBlockBuilder {
 
 			// But, don't create the content block with partial timestamp context.
 			(_, _, _) => Err(ContentBlockBuilderError::PartialTimestampContext),
-		}
+		};
+
+		content_block.map(|block| {
+			block
+				.with_properties(properties)
+				.with_inherit_access(inherit_access)
+		})
 	}
 }
 
@@ -292,4 +377,25 @@ mod tests {
 		assert_eq!(block.owner_id(), Some(&owner_id));
 		assert!(block.is_owned_by(&owner_id));
 	}
+
+	#[test]
+	fn test_access_ancestors() {
+		let block = |inherit_access: bool| {
+			ContentBlock::now(
+				None,
+				FractionalIndex::start(),
+				BlockContent::Paragraph {
+					markdown: String::new(),
+				},
+			)
+			.with_inherit_access(inherit_access)
+		};
+
+		// Ancestors, nearest first, with a boundary in the middle.
+		let ancestors = [block(true), block(false), block(true)];
+
+		assert_eq!(block(true).access_ancestors(&ancestors).len(), 2);
+		assert!(block(false).access_ancestors(&ancestors).is_empty());
+		assert_eq!(block(true).access_ancestors(&ancestors[2..]).len(), 1);
+	}
 }
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use chrono::DateTime;
+use chrono::Utc;
+use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::BlockContent;
+use crate::models::ContentBlock;
+use crate::models::NuttyId;
+use crate::models::block_date::BlockDate;
+use crate::models::block_date::BlockDateKind;
+
+/// The longest iCalendar content line, in octets, before it's folded.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// The date-linked content blocks within a date range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentCalendar {
+	/// The dates within the range, in date order.
+	entries: Vec<BlockDate>,
+
+	/// The blocks that the entries refer to.
+	block_cache: HashMap<NuttyId, ContentBlock>,
+}
+
+impl ContentCalendar {
+	/// Create a calendar from its entries and their blocks.
+	pub fn new(entries: Vec<BlockDate>, block_cache: HashMap<NuttyId, ContentBlock>) -> Self {
+		Self {
+			entries,
+			block_cache,
+		}
+	}
+
+	pub fn entries(&self) -> &[BlockDate] {
+		&self.entries
+	}
+
+	pub fn block_cache(&self) -> &HashMap<NuttyId, ContentBlock> {
+		&self.block_cache
+	}
+
+	/// Render the calendar as an iCalendar (RFC 5545) feed, with an all-day
+	/// event for each entry that links back to its block.
+	pub fn to_icalendar(&self, now: DateTime<Utc>) -> String {
+		let mut lines = vec![
+			"BEGIN:VCALENDAR".to_string(),
+			"VERSION:2.0".to_string(),
+			"PRODID:-//Nuttyverse//Calendar//EN".to_string(),
+			"CALSCALE:GREGORIAN".to_string(),
+			"X-WR-CALNAME:Nuttyverse".to_string(),
+		];
+
+		let stamp = now.format("%Y%m%dT%H%M%SZ");
+
+		for entry in &self.entries {
+			let Some(block) = self.block_cache.get(&entry.block_id) else {
+				continue;
+			};
+
+			let summary = match entry.kind {
+				BlockDateKind::Daily => summarize(&block.content),
+				BlockDateKind::Due => format!("Due: {}", summarize(&block.content)),
+			};
+
+			let start = entry.date;
+			let end = start.succ_opt().unwrap_or(start);
+
+			lines.extend([
+				"BEGIN:VEVENT".to_string(),
+				format!(
+					"UID:{}-{}-{}@nuttyver.se",
+					entry.block_id.nid(),
+					entry.kind.as_str(),
+					start.format("%Y%m%d"),
+				),
+				format!("DTSTAMP:{stamp}"),
+				format!("DTSTART;VALUE=DATE:{}", start.format("%Y%m%d")),
+				format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")),
+				format!("SUMMARY:{}", escape_text(&summary)),
+				format!("URL:{}", entry.block_id.permalink()),
+				"END:VEVENT".to_string(),
+			]);
+		}
+
+		lines.push("END:VCALENDAR".to_string());
+
+		lines
+			.iter()
+			.map(|line| format!("{}\r\n", fold_line(line)))
+			.collect()
+	}
+}
+
+/// Summarize a block's content on one line, without its due dates.
+fn summarize(content: &BlockContent) -> String {
+	let text = match content {
+		BlockContent::Page { title } => title,
+		BlockContent::Heading { markdown } => markdown,
+		BlockContent::Paragraph { markdown } => markdown,
+		BlockContent::Query { dsl } => dsl,
+		BlockContent::Todo { markdown, .. } => markdown,
+		BlockContent::Custom { kind, .. } => kind,
+	};
+
+	let re = Regex::new(r"(?:^|\s)due:\d{4}-\d{2}-\d{2}\b").unwrap();
+	let text = re.replace_all(text, " ");
+	text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Escape a TEXT value.
+fn escape_text(text: &str) -> String {
+	text
+		.replace('\\', "\\\\")
+		.replace(';', "\\;")
+		.replace(',', "\\,")
+		.replace('\n', "\\n")
+}
+
+/// Fold a content line longer than [MAX_LINE_OCTETS], without splitting a
+/// character.
+fn fold_line(line: &str) -> String {
+	let mut folded = String::with_capacity(line.len());
+	let mut octets = 0;
+
+	for c in line.chars() {
+		if octets + c.len_utf8() > MAX_LINE_OCTETS {
+			folded.push_str("\r\n ");
+			octets = 1;
+		}
+
+		folded.push(c);
+		octets += c.len_utf8();
+	}
+
+	folded
+}
+
+#[cfg(test)]
+mod tests {
+	use chrono::NaiveDate;
+	use chrono::TimeZone;
+
+	use super::*;
+	use crate::models::FractionalIndex;
+
+	#[test]
+	fn test_to_icalendar() {
+		let page = ContentBlock::now(
+			None,
+			FractionalIndex::start(),
+			BlockContent::Page {
+				title: "2025-08-07".to_string(),
+			},
+		);
+
+		let todo = ContentBlock::now(
+			None,
+			FractionalIndex::start(),
+			BlockContent::Todo {
+				markdown: "Buy milk, eggs; and a very long list of other groceries due:2025-08-08"
+					.to_string(),
+				done: false,
+			},
+		);
+
+		let date = |day| NaiveDate::from_ymd_opt(2025, 8, day).unwrap();
+
+		let calendar = ContentCalendar::new(
+			vec![
+				BlockDate::new(*page.nutty_id(), date(7), BlockDateKind::Daily),
+				BlockDate::new(*todo.nutty_id(), date(8), BlockDateKind::Due),
+			],
+			HashMap::from([
+				(*page.nutty_id(), page.clone()),
+				(*todo.nutty_id(), todo.clone()),
+			]),
+		);
+
+		let now = Utc.with_ymd_and_hms(2025, 8, 7, 12, 0, 0).unwrap();
+		let ics = calendar.to_icalendar(now);
+
+		assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+		assert!(ics.ends_with("END:VCALENDAR\r\n"));
+		assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+		assert!(ics.contains("DTSTAMP:20250807T120000Z\r\n"));
+
+		// Assert: Daily notes are all-day events on their date.
+		assert!(ics.contains(&format!(
+			"UID:{}-daily-20250807@nuttyver.se",
+			page.nutty_id().nid()
+		)));
+		assert!(ics.contains("DTSTART;VALUE=DATE:20250807\r\nDTEND;VALUE=DATE:20250808\r\n"));
+		assert!(ics.contains("SUMMARY:2025-08-07\r\n"));
+		assert!(ics.contains(&format!("URL:{}\r\n", page.nutty_id().permalink())));
+
+		// Assert: Due todos are escaped, stripped of their due date, and folded.
+		let unfolded = ics.replace("\r\n ", "");
+		assert!(
+			unfolded.contains(
+				"SUMMARY:Due: Buy milk\\, eggs\\; and a very long list of other groceries\r\n"
+			)
+		);
+		assert!(ics.lines().all(|line| line.len() <= MAX_LINE_OCTETS));
+	}
+
+	#[test]
+	fn test_fold_line() {
+		let line = "é".repeat(40);
+		let folded = fold_line(&line);
+
+		// Assert: Lines are folded on character boundaries.
+		assert_eq!(folded, format!("{}\r\n {}", "é".repeat(37), "é".repeat(3)));
+		assert_eq!(fold_line("SUMMARY:short"), "SUMMARY:short");
+	}
+}
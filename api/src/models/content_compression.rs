@@ -0,0 +1,25 @@
+use serde::Serialize;
+
+/// How much content has been compressed. Postgres compresses content at
+/// rest itself, when it's big enough to be TOASTed, so this only counts the
+/// content that the `compress-content` backfill moved from pglz over to lz4.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CompressionStats {
+	/// The number of blocks whose content was compressed.
+	pub compressed_count: u64,
+
+	/// The size of their content as JSON, in bytes.
+	pub original_bytes: u64,
+
+	/// The size of their content as stored, in bytes.
+	pub compressed_bytes: u64,
+}
+
+impl CompressionStats {
+	/// Count the content counted in other stats.
+	pub fn merge(&mut self, other: &CompressionStats) {
+		self.compressed_count += other.compressed_count;
+		self.original_bytes += other.original_bytes;
+		self.compressed_bytes += other.compressed_bytes;
+	}
+}
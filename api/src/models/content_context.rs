@@ -3,8 +3,13 @@ use std::collections::HashMap;
 use serde::Serialize;
 use thiserror::Error;
 
+use crate::models::BlockContent;
 use crate::models::ContentBlock;
+use crate::models::ContentLink;
 use crate::models::NuttyId;
+use crate::models::block_annotation::BlockAnnotation;
+use crate::models::block_stats::BlockStats;
+use crate::models::share_level::BlockCapability;
 
 /// Represents the immediate context of a content block.
 ///
@@ -25,7 +30,13 @@ use crate::models::NuttyId;
 ///
 /// • The reference (outbound links) content blocks, if any.
 /// • The backlinked (inbound links) content blocks, if any.
+/// • The related (co-cited) content blocks, if any.
 /// ```
+///
+/// Query blocks additionally carry the Nutty IDs of the blocks matching their
+/// query, which are resolved server-side but not included in the cache.
+/// Cached blocks may carry annotations, i.e. warnings for editors, and stats,
+/// i.e. their deep counts of descendants and backlinks.
 #[derive(Debug, Clone, Serialize)]
 pub struct ContentContext {
 	/// The Nutty ID of the content block.
@@ -34,6 +45,10 @@ pub struct ContentContext {
 	/// The Nutty ID of the parent content block, if any.
 	parent_id: Option<NuttyId>,
 
+	/// A list of Nutty IDs of ancestor content blocks, from the root down to
+	/// the parent.
+	ancestor_ids: Vec<NuttyId>,
+
 	/// A list of Nutty IDs of child content blocks.
 	children_ids: Vec<NuttyId>,
 
@@ -43,30 +58,32 @@ pub struct ContentContext {
 	/// A list of Nutty IDs of content blocks that reference this block.
 	backlink_ids: Vec<NuttyId>,
 
+	/// The references and backlinks whose authors couldn't read their
+	/// targets, which are only shown to navigators who can.
+	#[serde(skip)]
+	restricted_links: Vec<ContentLink>,
+
+	/// A list of Nutty IDs of content blocks matching this block's query, if any.
+	query_result_ids: Vec<NuttyId>,
+
+	/// A list of Nutty IDs of content blocks cited alongside this block,
+	/// most often first.
+	related_ids: Vec<NuttyId>,
+
 	/// A cache of content blocks for quick access.
 	block_cache: HashMap<NuttyId, ContentBlock>,
+
+	/// Issues found in cached blocks, by block. Blocks without any are omitted.
+	annotations: HashMap<NuttyId, Vec<BlockAnnotation>>,
+
+	/// Deep counts of cached blocks, by block.
+	stats: HashMap<NuttyId, BlockStats>,
+
+	/// What the navigator viewing the context can do with the block.
+	capabilities: Vec<BlockCapability>,
 }
 
 impl ContentContext {
-	/// Create a new content context.
-	fn new(
-		block_id: NuttyId,
-		parent_id: Option<NuttyId>,
-		children_ids: Vec<NuttyId>,
-		reference_ids: Vec<NuttyId>,
-		backlink_ids: Vec<NuttyId>,
-		block_cache: HashMap<NuttyId, ContentBlock>,
-	) -> Self {
-		Self {
-			block_id,
-			parent_id,
-			children_ids,
-			reference_ids,
-			backlink_ids,
-			block_cache,
-		}
-	}
-
 	/// Get the block ID.
 	pub fn block_id(&self) -> &NuttyId {
 		&self.block_id
@@ -77,6 +94,11 @@ impl ContentContext {
 		self.parent_id.as_ref()
 	}
 
+	/// Get the ancestor IDs, root first.
+	pub fn ancestor_ids(&self) -> &[NuttyId] {
+		&self.ancestor_ids
+	}
+
 	/// Get the children IDs.
 	pub fn children_ids(&self) -> &[NuttyId] {
 		&self.children_ids
@@ -92,11 +114,130 @@ impl ContentContext {
 		&self.backlink_ids
 	}
 
+	/// Get the targets of the restricted references and backlinks.
+	pub fn restricted_target_ids(&self) -> Vec<NuttyId> {
+		self
+			.restricted_links
+			.iter()
+			.map(|link| link.target_id)
+			.collect()
+	}
+
+	/// Get the query result IDs.
+	pub fn query_result_ids(&self) -> &[NuttyId] {
+		&self.query_result_ids
+	}
+
+	/// Get the related IDs.
+	pub fn related_ids(&self) -> &[NuttyId] {
+		&self.related_ids
+	}
+
 	/// Get the block cache.
 	pub fn block_cache(&self) -> &HashMap<NuttyId, ContentBlock> {
 		&self.block_cache
 	}
 
+	/// Get the annotations.
+	pub fn annotations(&self) -> &HashMap<NuttyId, Vec<BlockAnnotation>> {
+		&self.annotations
+	}
+
+	/// Get the stats.
+	pub fn stats(&self) -> &HashMap<NuttyId, BlockStats> {
+		&self.stats
+	}
+
+	/// Get the capabilities of the navigator viewing the context.
+	pub fn capabilities(&self) -> &[BlockCapability] {
+		&self.capabilities
+	}
+
+	/// Set the capabilities of the navigator viewing the context.
+	pub fn set_capabilities(&mut self, capabilities: Vec<BlockCapability>) {
+		self.capabilities = capabilities;
+	}
+
+	/// Get the descendants in the cache.
+	pub fn descendant_ids(&self) -> Vec<NuttyId> {
+		self
+			.block_cache
+			.keys()
+			.filter(|id| self.is_descendant(id))
+			.copied()
+			.collect()
+	}
+
+	/// Get the descendants in the cache that opt out of inheriting access,
+	/// and so may be hidden from navigators who can view the block.
+	pub fn private_descendant_ids(&self) -> Vec<NuttyId> {
+		self
+			.block_cache
+			.values()
+			.filter(|block| !block.inherit_access && self.is_descendant(block.nutty_id()))
+			.map(|block| *block.nutty_id())
+			.collect()
+	}
+
+	/// Remove a descendant, and its own descendants, from the children and
+	/// the cache.
+	pub fn prune_subtree(&mut self, root_id: &NuttyId) {
+		let pruned: Vec<NuttyId> = self
+			.block_cache
+			.keys()
+			.filter(|id| self.ancestry(id).any(|ancestor_id| ancestor_id == *root_id))
+			.copied()
+			.collect();
+
+		for id in &pruned {
+			self.block_cache.remove(id);
+			self.annotations.remove(id);
+			self.stats.remove(id);
+		}
+
+		self.children_ids.retain(|id| !pruned.contains(id));
+	}
+
+	/// Remove the restricted references and backlinks whose targets aren't
+	/// among the given IDs.
+	pub fn retain_restricted_links(&mut self, target_ids: &[NuttyId]) {
+		for link in std::mem::take(&mut self.restricted_links) {
+			if target_ids.contains(&link.target_id) {
+				self.restricted_links.push(link);
+			} else if link.source_id == self.block_id {
+				self.reference_ids.retain(|id| *id != link.target_id);
+			} else {
+				self.backlink_ids.retain(|id| *id != link.source_id);
+			}
+		}
+	}
+
+	/// Replace the content of the cached blocks with the given contents,
+	/// e.g. with the published revisions of drafts.
+	pub fn replace_contents(&mut self, mut contents: HashMap<NuttyId, BlockContent>) {
+		for (id, block) in &mut self.block_cache {
+			if let Some(content) = contents.remove(id) {
+				block.content = content;
+			}
+		}
+	}
+
+	/// Check if a cached block is a descendant of the block.
+	fn is_descendant(&self, id: &NuttyId) -> bool {
+		self
+			.ancestry(id)
+			.skip(1)
+			.any(|ancestor_id| ancestor_id == self.block_id)
+	}
+
+	/// Walk from a cached block up through its cached ancestors, starting
+	/// with the block itself.
+	fn ancestry<'a>(&'a self, id: &NuttyId) -> impl Iterator<Item = NuttyId> + 'a {
+		std::iter::successors(Some(*id), |id| {
+			self.block_cache.get(id).and_then(|block| block.parent_id)
+		})
+	}
+
 	/// Create a builder for a new content context.
 	pub fn builder() -> ContentContextBuilder {
 		ContentContextBuilder::default()
@@ -108,10 +249,16 @@ impl ContentContext {
 pub struct ContentContextBuilder {
 	block_id: Option<NuttyId>,
 	parent_id: Option<NuttyId>,
+	ancestor_ids: Vec<NuttyId>,
 	children_ids: Vec<NuttyId>,
 	reference_ids: Vec<NuttyId>,
 	backlink_ids: Vec<NuttyId>,
+	restricted_links: Vec<ContentLink>,
+	query_result_ids: Vec<NuttyId>,
+	related_ids: Vec<NuttyId>,
 	block_cache: HashMap<NuttyId, ContentBlock>,
+	annotations: HashMap<NuttyId, Vec<BlockAnnotation>>,
+	stats: HashMap<NuttyId, BlockStats>,
 }
 
 impl ContentContextBuilder {
@@ -127,6 +274,12 @@ impl ContentContextBuilder {
 		self
 	}
 
+	/// Set the ancestor IDs, root first.
+	pub fn ancestor_ids(mut self, ancestor_ids: Vec<NuttyId>) -> Self {
+		self.ancestor_ids = ancestor_ids;
+		self
+	}
+
 	/// Set the children IDs.
 	pub fn children_ids(mut self, children_ids: Vec<NuttyId>) -> Self {
 		self.children_ids = children_ids;
@@ -163,6 +316,24 @@ impl ContentContextBuilder {
 		self
 	}
 
+	/// Set the restricted references and backlinks.
+	pub fn restricted_links(mut self, restricted_links: Vec<ContentLink>) -> Self {
+		self.restricted_links = restricted_links;
+		self
+	}
+
+	/// Set the query result IDs.
+	pub fn query_result_ids(mut self, query_result_ids: Vec<NuttyId>) -> Self {
+		self.query_result_ids = query_result_ids;
+		self
+	}
+
+	/// Set the related IDs.
+	pub fn related_ids(mut self, related_ids: Vec<NuttyId>) -> Self {
+		self.related_ids = related_ids;
+		self
+	}
+
 	/// Set the block cache.
 	pub fn block_cache(mut self, block_cache: HashMap<NuttyId, ContentBlock>) -> Self {
 		self.block_cache = block_cache;
@@ -175,25 +346,61 @@ impl ContentContextBuilder {
 		self
 	}
 
-	/// Build the content context, returning an error if required fields are not set.
+	/// Set the annotations, grouping them by block.
+	pub fn annotations(mut self, annotations: Vec<BlockAnnotation>) -> Self {
+		self.annotations.clear();
+
+		for annotation in annotations {
+			self
+				.annotations
+				.entry(annotation.block_id)
+				.or_default()
+				.push(annotation);
+		}
+
+		self
+	}
+
+	/// Set the stats.
+	pub fn stats(mut self, stats: Vec<BlockStats>) -> Self {
+		self.stats = stats
+			.into_iter()
+			.map(|stats| (stats.block_id, stats))
+			.collect();
+
+		self
+	}
+
+	/// Build the content context, returning an error if required fields are
+	/// not set, or if an ancestor isn't in the cache.
 	pub fn try_build(self) -> Result<ContentContext, ContentContextBuilderError> {
 		let block_id = self
 			.block_id
 			.ok_or(ContentContextBuilderError::MissingBlockId)?;
-		let parent_id = self.parent_id;
-		let children_ids = self.children_ids;
-		let reference_ids = self.reference_ids;
-		let backlink_ids = self.backlink_ids;
-		let block_cache = self.block_cache;
 
-		Ok(ContentContext::new(
+		if let Some(ancestor_id) = self
+			.ancestor_ids
+			.iter()
+			.find(|id| !self.block_cache.contains_key(id))
+		{
+			return Err(ContentContextBuilderError::UncachedAncestor(*ancestor_id));
+		}
+
+		Ok(ContentContext {
 			block_id,
-			parent_id,
-			children_ids,
-			reference_ids,
-			backlink_ids,
-			block_cache,
-		))
+			parent_id: self.parent_id,
+			ancestor_ids: self.ancestor_ids,
+			children_ids: self.children_ids,
+			reference_ids: self.reference_ids,
+			backlink_ids: self.backlink_ids,
+			restricted_links: self.restricted_links,
+			query_result_ids: self.query_result_ids,
+			related_ids: self.related_ids,
+			block_cache: self.block_cache,
+			annotations: self.annotations,
+			stats: self.stats,
+			capabilities: vec![],
+		})
 	}
 }
 
@@ -201,4 +408,47 @@ impl ContentContextBuilder {
 pub enum ContentContextBuilderError {
 	#[error("Block ID is required")]
 	MissingBlockId,
+
+	#[error("Ancestor is missing from the block cache: {0}")]
+	UncachedAncestor(NuttyId),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::models::BlockContent;
+	use crate::models::FractionalIndex;
+
+	#[test]
+	fn test_ancestor_ids() {
+		let root = ContentBlock::builder()
+			.f_index(FractionalIndex::start())
+			.content(BlockContent::Page {
+				title: "Root".to_string(),
+			})
+			.try_build()
+			.unwrap();
+
+		let block_id = NuttyId::now();
+
+		// Assert: Ancestors must be in the cache.
+		let result = ContentContext::builder()
+			.block_id(block_id)
+			.ancestor_ids(vec![*root.nutty_id()])
+			.try_build();
+
+		assert!(matches!(
+			result,
+			Err(ContentContextBuilderError::UncachedAncestor(id)) if id == *root.nutty_id()
+		));
+
+		let context = ContentContext::builder()
+			.block_id(block_id)
+			.ancestor_ids(vec![*root.nutty_id()])
+			.add_block_to_cache(root.clone())
+			.try_build()
+			.unwrap();
+
+		assert_eq!(context.ancestor_ids(), &[*root.nutty_id()]);
+	}
 }
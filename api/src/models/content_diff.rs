@@ -0,0 +1,269 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::BlockContent;
+
+/// A value before and after a change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Change<T> {
+	pub from: T,
+	pub to: T,
+}
+
+impl<T: PartialEq> Change<T> {
+	/// Create a change, unless the value is the same on both sides.
+	fn between(from: T, to: T) -> Option<Self> {
+		(from != to).then_some(Self { from, to })
+	}
+}
+
+/// How a paragraph differs between two contents.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ParagraphDiff {
+	Unchanged { text: String },
+	Added { text: String },
+	Removed { text: String },
+	Changed { from: String, to: String },
+}
+
+/// A structured diff between two block contents. Markdown is compared by
+/// paragraph, separated by blank lines.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentDiff {
+	/// The change in content kind, e.g. from `Paragraph` to `Todo`.
+	pub kind: Option<Change<String>>,
+
+	/// The change in page title, if either side is a page.
+	pub title: Option<Change<Option<String>>>,
+
+	/// The change in a todo's done state, if both sides are todos.
+	pub done: Option<Change<bool>>,
+
+	/// Every paragraph on either side, in order.
+	pub paragraphs: Vec<ParagraphDiff>,
+}
+
+impl ContentDiff {
+	/// Diff two block contents.
+	pub fn between(from: &BlockContent, to: &BlockContent) -> Self {
+		let done = match (from, to) {
+			(BlockContent::Todo { done: from, .. }, BlockContent::Todo { done: to, .. }) => {
+				Change::between(*from, *to)
+			}
+			_ => None,
+		};
+
+		Self {
+			kind: Change::between(from.kind().to_string(), to.kind().to_string()),
+			title: Change::between(title(from), title(to)),
+			done,
+			paragraphs: diff_paragraphs(&paragraphs(from), &paragraphs(to)),
+		}
+	}
+
+	/// Check whether the contents are the same.
+	pub fn is_empty(&self) -> bool {
+		self.kind.is_none()
+			&& self.title.is_none()
+			&& self.done.is_none()
+			&& self
+				.paragraphs
+				.iter()
+				.all(|paragraph| matches!(paragraph, ParagraphDiff::Unchanged { .. }))
+	}
+}
+
+fn title(content: &BlockContent) -> Option<String> {
+	match content {
+		BlockContent::Page { title } => Some(title.clone()),
+		_ => None,
+	}
+}
+
+/// Split a content's text into paragraphs. Pages and custom content have
+/// none.
+pub(crate) fn paragraphs(content: &BlockContent) -> Vec<&str> {
+	let text = match content {
+		BlockContent::Page { .. } | BlockContent::Custom { .. } => return vec![],
+		BlockContent::Heading { markdown } => markdown,
+		BlockContent::Paragraph { markdown } => markdown,
+		BlockContent::Query { dsl } => dsl,
+		BlockContent::Todo { markdown, .. } => markdown,
+	};
+
+	text
+		.split("\n\n")
+		.map(str::trim)
+		.filter(|paragraph| !paragraph.is_empty())
+		.collect()
+}
+
+/// Match up the paragraphs that two lists have in common, by their longest
+/// common subsequence. Returns the index pairs, in order.
+pub(crate) fn common_paragraphs(from: &[&str], to: &[&str]) -> Vec<(usize, usize)> {
+	// lengths[i][j] is the LCS length of from[i..] and to[j..].
+	let mut lengths = vec![vec![0; to.len() + 1]; from.len() + 1];
+
+	for i in (0..from.len()).rev() {
+		for j in (0..to.len()).rev() {
+			lengths[i][j] = match from[i] == to[j] {
+				true => lengths[i + 1][j + 1] + 1,
+				false => lengths[i + 1][j].max(lengths[i][j + 1]),
+			};
+		}
+	}
+
+	let mut pairs = vec![];
+	let (mut i, mut j) = (0, 0);
+
+	while i < from.len() && j < to.len() {
+		if from[i] == to[j] {
+			pairs.push((i, j));
+			i += 1;
+			j += 1;
+		} else if lengths[i + 1][j] >= lengths[i][j + 1] {
+			i += 1;
+		} else {
+			j += 1;
+		}
+	}
+
+	pairs
+}
+
+/// Diff two lists of paragraphs. Between unchanged paragraphs, removals and
+/// additions are paired up as changes.
+fn diff_paragraphs(from: &[&str], to: &[&str]) -> Vec<ParagraphDiff> {
+	let mut diffs = vec![];
+	let (mut i, mut j) = (0, 0);
+
+	// End with a sentinel pair, to flush the trailing paragraphs.
+	let pairs = common_paragraphs(from, to)
+		.into_iter()
+		.chain([(from.len(), to.len())]);
+
+	for (next_i, next_j) in pairs {
+		let removed = &from[i..next_i];
+		let added = &to[j..next_j];
+		let paired = removed.len().min(added.len());
+
+		for (from, to) in removed.iter().zip(added) {
+			diffs.push(ParagraphDiff::Changed {
+				from: from.to_string(),
+				to: to.to_string(),
+			});
+		}
+
+		for text in &removed[paired..] {
+			diffs.push(ParagraphDiff::Removed {
+				text: text.to_string(),
+			});
+		}
+
+		for text in &added[paired..] {
+			diffs.push(ParagraphDiff::Added {
+				text: text.to_string(),
+			});
+		}
+
+		if next_i < from.len() {
+			diffs.push(ParagraphDiff::Unchanged {
+				text: from[next_i].to_string(),
+			});
+		}
+
+		(i, j) = (next_i + 1, next_j + 1);
+	}
+
+	diffs
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn paragraph(markdown: &str) -> BlockContent {
+		BlockContent::Paragraph {
+			markdown: markdown.to_string(),
+		}
+	}
+
+	#[test]
+	fn test_diff_paragraphs() {
+		let from = paragraph("Intro\n\nOld middle\n\nDropped\n\nOutro");
+		let to = paragraph("Intro\n\nNew middle\n\nOutro\n\nAppendix");
+		let diff = ContentDiff::between(&from, &to);
+
+		assert_eq!(
+			diff.paragraphs,
+			vec![
+				ParagraphDiff::Unchanged {
+					text: "Intro".to_string()
+				},
+				ParagraphDiff::Changed {
+					from: "Old middle".to_string(),
+					to: "New middle".to_string(),
+				},
+				ParagraphDiff::Removed {
+					text: "Dropped".to_string()
+				},
+				ParagraphDiff::Unchanged {
+					text: "Outro".to_string()
+				},
+				ParagraphDiff::Added {
+					text: "Appendix".to_string()
+				},
+			]
+		);
+
+		assert_eq!(diff.kind, None);
+		assert!(!diff.is_empty());
+		assert!(ContentDiff::between(&from, &from).is_empty());
+	}
+
+	#[test]
+	fn test_diff_kind_and_title() {
+		let from = BlockContent::Page {
+			title: "Groceries".to_string(),
+		};
+
+		let to = BlockContent::Page {
+			title: "Shopping".to_string(),
+		};
+
+		// Assert: Retitled pages have a title change.
+		let diff = ContentDiff::between(&from, &to);
+		assert_eq!(
+			diff.title,
+			Some(Change {
+				from: Some("Groceries".to_string()),
+				to: Some("Shopping".to_string()),
+			})
+		);
+		assert!(diff.paragraphs.is_empty());
+
+		// Assert: Converting a paragraph to a todo changes its kind only.
+		let todo = BlockContent::Todo {
+			markdown: "Milk".to_string(),
+			done: true,
+		};
+
+		let diff = ContentDiff::between(&paragraph("Milk"), &todo);
+		assert_eq!(
+			diff.kind,
+			Some(Change {
+				from: "Paragraph".to_string(),
+				to: "Todo".to_string(),
+			})
+		);
+		assert_eq!(diff.title, None);
+		assert_eq!(diff.done, None);
+		assert_eq!(
+			diff.paragraphs,
+			vec![ParagraphDiff::Unchanged {
+				text: "Milk".to_string()
+			}]
+		);
+	}
+}
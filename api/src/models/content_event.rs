@@ -0,0 +1,254 @@
+use std::collections::HashSet;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::ContentBlock;
+use crate::models::ContentLink;
+use crate::models::NuttyId;
+use crate::models::sync_changes::ChangeKind;
+use crate::models::sync_changes::SyncChanges;
+
+/// A change to a navigator's content, as it's streamed to clients.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentEvent {
+	/// A block was created or updated, and is now as given.
+	BlockSaved { block: ContentBlock },
+
+	/// A block was deleted.
+	BlockDeleted { block_id: NuttyId },
+
+	/// A link was created.
+	LinkSaved { link: ContentLink },
+
+	/// A link was deleted.
+	LinkDeleted { link_id: NuttyId },
+}
+
+impl ContentEvent {
+	/// Get the event's name, as it's written in the stream.
+	pub fn name(&self) -> &'static str {
+		match self {
+			ContentEvent::BlockSaved { .. } => "block_saved",
+			ContentEvent::BlockDeleted { .. } => "block_deleted",
+			ContentEvent::LinkSaved { .. } => "link_saved",
+			ContentEvent::LinkDeleted { .. } => "link_deleted",
+		}
+	}
+}
+
+/// Picks the events of a subtree out of a navigator's changes, keeping track
+/// of which blocks are in it as they're created, moved, and deleted.
+#[derive(Debug, Clone, Default)]
+pub struct ContentEventFilter {
+	/// The subtree's root, which stays in it wherever it's moved.
+	root_id: Option<NuttyId>,
+
+	/// The blocks in the subtree, or none to let every event through.
+	subtree: Option<HashSet<NuttyId>>,
+}
+
+impl ContentEventFilter {
+	/// Create a filter that lets every event through.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Create a filter for a block's subtree, given the blocks in it.
+	pub fn subtree(root_id: NuttyId, block_ids: impl IntoIterator<Item = NuttyId>) -> Self {
+		let mut subtree: HashSet<_> = block_ids.into_iter().collect();
+		subtree.insert(root_id);
+
+		Self {
+			root_id: Some(root_id),
+			subtree: Some(subtree),
+		}
+	}
+
+	/// Turn a page of changes into events, in order: blocks saved, parents
+	/// before children, then links saved, then deletions. A saved block is in
+	/// the subtree if it or its parent already is, so a block moved out is
+	/// sent one last time. Deleted links can't be placed once they're gone,
+	/// so they're always sent.
+	pub fn events(&mut self, changes: &SyncChanges) -> Vec<ContentEvent> {
+		let mut events = vec![];
+		let mut blocks: Vec<_> = changes.blocks.iter().collect();
+
+		// A page's blocks aren't in tree order, so keep picking out the ones
+		// whose parents were picked until no more are.
+		loop {
+			let count = blocks.len();
+
+			blocks.retain(|block| {
+				let keep = self.keep_block(block);
+
+				if keep {
+					events.push(ContentEvent::BlockSaved {
+						block: (*block).clone(),
+					});
+				}
+
+				!keep
+			});
+
+			if blocks.len() == count {
+				break;
+			}
+		}
+
+		for link in &changes.links {
+			if self.contains(&link.source_id) {
+				events.push(ContentEvent::LinkSaved { link: link.clone() });
+			}
+		}
+
+		for tombstone in &changes.tombstones {
+			match tombstone.kind {
+				ChangeKind::Block => {
+					if self.remove(&tombstone.id) {
+						events.push(ContentEvent::BlockDeleted {
+							block_id: tombstone.id,
+						});
+					}
+				}
+
+				ChangeKind::Link => events.push(ContentEvent::LinkDeleted {
+					link_id: tombstone.id,
+				}),
+			}
+		}
+
+		events
+	}
+
+	/// Check if a saved block is, or was, in the subtree, and track whether
+	/// it still is.
+	fn keep_block(&mut self, block: &ContentBlock) -> bool {
+		let Some(subtree) = &mut self.subtree else {
+			return true;
+		};
+
+		let block_id = *block.nutty_id();
+
+		if self.root_id == Some(block_id) {
+			return true;
+		}
+
+		let was_in = subtree.remove(&block_id);
+		let is_in = block
+			.parent_id
+			.is_some_and(|parent_id| subtree.contains(&parent_id));
+
+		if is_in {
+			subtree.insert(block_id);
+		}
+
+		was_in || is_in
+	}
+
+	fn contains(&self, block_id: &NuttyId) -> bool {
+		self
+			.subtree
+			.as_ref()
+			.is_none_or(|subtree| subtree.contains(block_id))
+	}
+
+	fn remove(&mut self, block_id: &NuttyId) -> bool {
+		match &mut self.subtree {
+			None => true,
+			Some(subtree) => subtree.remove(block_id),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::models::BlockContent;
+	use crate::models::FractionalIndex;
+	use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+	use crate::models::sync_changes::SyncCursor;
+	use crate::models::sync_changes::Tombstone;
+
+	fn block(parent_id: Option<NuttyId>) -> ContentBlock {
+		ContentBlock::now(
+			parent_id,
+			FractionalIndex::start(),
+			BlockContent::Paragraph {
+				markdown: "Hello".to_string(),
+			},
+		)
+	}
+
+	fn changes(blocks: Vec<ContentBlock>, tombstones: Vec<Tombstone>) -> SyncChanges {
+		SyncChanges {
+			blocks,
+			links: vec![],
+			tombstones,
+			cursor: SyncCursor::default(),
+			has_more: false,
+		}
+	}
+
+	#[test]
+	fn test_subtree_events() {
+		let root = block(None);
+		let other = block(None);
+		let mut filter = ContentEventFilter::subtree(*root.nutty_id(), []);
+
+		// Assert: Blocks created under the subtree are sent, others aren't.
+		let child = block(Some(*root.nutty_id()));
+		let grandchild = block(Some(*child.nutty_id()));
+		let outsider = block(Some(*other.nutty_id()));
+
+		let events = filter.events(&changes(
+			vec![grandchild.clone(), outsider.clone(), child.clone()],
+			vec![],
+		));
+
+		assert_eq!(
+			events.iter().map(ContentEvent::name).collect::<Vec<_>>(),
+			vec!["block_saved", "block_saved"]
+		);
+
+		// Assert: A block moved out is sent once more, then no longer.
+		let mut moved = grandchild.clone();
+		moved.parent_id = Some(*other.nutty_id());
+
+		assert_eq!(
+			filter.events(&changes(vec![moved.clone()], vec![])).len(),
+			1
+		);
+		assert!(filter.events(&changes(vec![moved], vec![])).is_empty());
+
+		// Assert: The root stays in the subtree wherever it's moved.
+		let mut root = root.clone();
+		root.parent_id = Some(*other.nutty_id());
+		assert_eq!(filter.events(&changes(vec![root], vec![])).len(), 1);
+
+		// Assert: Only deletions in the subtree are sent.
+		let tombstone = |id: NuttyId| Tombstone {
+			kind: ChangeKind::Block,
+			id,
+			deleted_at: DateTimeRfc3339::new(chrono::Utc::now().fixed_offset()),
+		};
+
+		let events = filter.events(&changes(
+			vec![],
+			vec![
+				tombstone(*child.nutty_id()),
+				tombstone(*outsider.nutty_id()),
+			],
+		));
+
+		assert!(matches!(
+			events.as_slice(),
+			[ContentEvent::BlockDeleted { block_id }] if block_id == child.nutty_id()
+		));
+
+		// Assert: Without a subtree, every event is sent.
+		let events = ContentEventFilter::new().events(&changes(vec![outsider], vec![]));
+		assert_eq!(events.len(), 1);
+	}
+}
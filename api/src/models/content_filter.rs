@@ -0,0 +1,100 @@
+use thiserror::Error;
+
+use crate::models::NuttyId;
+
+/// A filter for listing content blocks by kind and owner. Written as the
+/// `kind` and `owner` query parameters on the content listing:
+///
+/// ```text
+/// kind=page            — Every page.
+/// kind=todo,Recipe     — Every todo and recipe.
+/// kind=Recipe&owner=me — The recipes that the navigator owns.
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContentFilter {
+	/// The kinds to match, by their registered names, or every kind if empty.
+	pub kinds: Vec<String>,
+
+	/// The navigator that matching blocks must be owned by, if any.
+	pub owner_id: Option<NuttyId>,
+
+	/// Whether to match archived blocks too.
+	pub include_archived: bool,
+}
+
+impl ContentFilter {
+	/// Parse a filter from the `kind` and `owner` query parameters. Kinds are
+	/// resolved to their registered names, and `owner=me` is the navigator.
+	pub fn parse(
+		kind: Option<&str>,
+		owner: Option<&str>,
+		navigator_id: &NuttyId,
+		resolve_kind: impl Fn(&str) -> Option<String>,
+	) -> Result<Self, ContentFilterError> {
+		let kinds = kind
+			.into_iter()
+			.flat_map(|kind| kind.split(','))
+			.map(str::trim)
+			.filter(|kind| !kind.is_empty())
+			.map(|kind| {
+				resolve_kind(kind).ok_or_else(|| ContentFilterError::UnknownKind(kind.to_string()))
+			})
+			.collect::<Result<_, _>>()?;
+
+		let owner_id = match owner {
+			None => None,
+			Some("me") => Some(*navigator_id),
+			Some(owner) => return Err(ContentFilterError::UnknownOwner(owner.to_string())),
+		};
+
+		Ok(Self {
+			kinds,
+			owner_id,
+			include_archived: false,
+		})
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum ContentFilterError {
+	#[error("Unknown block kind: '{0}'")]
+	UnknownKind(String),
+
+	#[error("Unknown owner: '{0}' (expected 'me')")]
+	UnknownOwner(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse() {
+		let navigator_id = NuttyId::now();
+		let resolve = |kind: &str| match kind.to_lowercase().as_str() {
+			"page" => Some("Page".to_string()),
+			"code" => Some("Code".to_string()),
+			_ => None,
+		};
+
+		// Assert: Without parameters, every block matches.
+		let filter = ContentFilter::parse(None, None, &navigator_id, resolve).unwrap();
+		assert_eq!(filter, ContentFilter::default());
+
+		// Assert: Kinds are resolved, and `me` is the navigator.
+		let filter =
+			ContentFilter::parse(Some("page, code"), Some("me"), &navigator_id, resolve).unwrap();
+		assert_eq!(filter.kinds, vec!["Page", "Code"]);
+		assert_eq!(filter.owner_id, Some(navigator_id));
+
+		// Assert: Unknown kinds and owners are rejected.
+		assert!(matches!(
+			ContentFilter::parse(Some("page,recipe"), None, &navigator_id, resolve),
+			Err(ContentFilterError::UnknownKind(kind)) if kind == "recipe"
+		));
+		assert!(matches!(
+			ContentFilter::parse(None, Some("you"), &navigator_id, resolve),
+			Err(ContentFilterError::UnknownOwner(owner)) if owner == "you"
+		));
+	}
+}
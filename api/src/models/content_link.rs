@@ -1,11 +1,18 @@
+use serde::Deserialize;
+use serde::Serialize;
+
 use crate::models::NuttyId;
 
 /// A link between two blocks of content.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContentLink {
 	pub nutty_id: NuttyId,
 	pub source_id: NuttyId,
 	pub target_id: NuttyId,
+
+	/// Whether the link's author couldn't read its target when saving it.
+	#[serde(default)]
+	pub restricted: bool,
 }
 
 impl ContentLink {
@@ -15,9 +22,16 @@ impl ContentLink {
 			nutty_id,
 			source_id,
 			target_id,
+			restricted: false,
 		}
 	}
 
+	/// Mark the link as made by an author who couldn't read its target.
+	pub fn with_restricted(mut self, restricted: bool) -> Self {
+		self.restricted = restricted;
+		self
+	}
+
 	/// Create a new content link with a generated identifier (UUIDv7).
 	pub fn now(source_id: NuttyId, target_id: NuttyId) -> Self {
 		Self::new(NuttyId::now(), source_id, target_id)
@@ -0,0 +1,102 @@
+use std::collections::BTreeSet;
+
+use thiserror::Error;
+
+/// A part of a content context that can be fetched on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ContextPart {
+	/// The ancestors, for breadcrumbs.
+	Ancestors,
+
+	/// The children, in the requested view.
+	Children,
+
+	/// The references and backlinks.
+	Links,
+}
+
+impl ContextPart {
+	/// Get the part named in an `include` query parameter.
+	pub fn parse(part: &str) -> Result<Self, ContextIncludeError> {
+		match part {
+			"ancestors" => Ok(ContextPart::Ancestors),
+			"children" => Ok(ContextPart::Children),
+			"links" => Ok(ContextPart::Links),
+			_ => Err(ContextIncludeError::UnknownPart(part.to_string())),
+		}
+	}
+}
+
+/// The parts of a content context to fetch. Written as the `include` query
+/// parameter on the context endpoint:
+///
+/// ```text
+/// include=ancestors       — Only the breadcrumbs.
+/// include=children        — Only the child list.
+/// include=ancestors,links — The breadcrumbs, references and backlinks.
+/// ```
+///
+/// The block itself is always included. Query results, related blocks,
+/// annotations and stats only come with the whole context.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContextInclude {
+	/// The included parts, or the whole context if empty.
+	pub parts: BTreeSet<ContextPart>,
+}
+
+impl ContextInclude {
+	/// Parse the parts from the `include` query parameter.
+	pub fn parse(include: Option<&str>) -> Result<Self, ContextIncludeError> {
+		let parts = include
+			.into_iter()
+			.flat_map(|include| include.split(','))
+			.map(str::trim)
+			.filter(|part| !part.is_empty())
+			.map(ContextPart::parse)
+			.collect::<Result<_, _>>()?;
+
+		Ok(Self { parts })
+	}
+
+	/// Check if the whole context is included.
+	pub fn is_default(&self) -> bool {
+		self.parts.is_empty()
+	}
+
+	/// Check if a part is included.
+	pub fn includes(&self, part: ContextPart) -> bool {
+		self.is_default() || self.parts.contains(&part)
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum ContextIncludeError {
+	#[error("Unknown context part: '{0}'")]
+	UnknownPart(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse() {
+		// Assert: Without parts, the whole context is included.
+		let include = ContextInclude::parse(None).unwrap();
+		assert!(include.is_default());
+		assert!(include.includes(ContextPart::Links));
+
+		// Assert: Only the listed parts are included.
+		let include = ContextInclude::parse(Some("ancestors, children,")).unwrap();
+		assert!(!include.is_default());
+		assert!(include.includes(ContextPart::Ancestors));
+		assert!(include.includes(ContextPart::Children));
+		assert!(!include.includes(ContextPart::Links));
+
+		// Assert: Unknown parts are rejected.
+		assert!(matches!(
+			ContextInclude::parse(Some("children,stats")),
+			Err(ContextIncludeError::UnknownPart(part)) if part == "stats"
+		));
+	}
+}
@@ -0,0 +1,133 @@
+use serde::Deserialize;
+use serde::Serialize;
+use sqlx::FromRow;
+
+/// The browser, operating system, and device type parsed from a user agent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct DeviceInfo {
+	browser: String,
+	os: String,
+	device: String,
+}
+
+/// Browser tokens, in match order. Order matters because most user agents
+/// also claim to be the browsers they are derived from (e.g., Edge → Chrome → Safari).
+const BROWSERS: &[(&str, &str)] = &[
+	("Edg/", "Edge"),
+	("OPR/", "Opera"),
+	("Firefox/", "Firefox"),
+	("FxiOS/", "Firefox"),
+	("CriOS/", "Chrome"),
+	("Chromium/", "Chromium"),
+	("Chrome/", "Chrome"),
+	("Safari/", "Safari"),
+	("curl/", "curl"),
+];
+
+/// Operating system tokens, in match order.
+const OPERATING_SYSTEMS: &[(&str, &str)] = &[
+	("iPhone", "iOS"),
+	("iPad", "iPadOS"),
+	("Android", "Android"),
+	("CrOS", "ChromeOS"),
+	("Windows", "Windows"),
+	("Mac OS X", "macOS"),
+	("Macintosh", "macOS"),
+	("Linux", "Linux"),
+];
+
+/// The fallback for anything we can't recognize.
+const UNKNOWN: &str = "Unknown";
+
+impl DeviceInfo {
+	/// Parse a user agent string.
+	pub fn parse(user_agent: &str) -> Self {
+		let find = |table: &[(&str, &str)]| {
+			table
+				.iter()
+				.find(|(token, _)| user_agent.contains(token))
+				.map_or(UNKNOWN, |(_, name)| name)
+				.to_string()
+		};
+
+		let device = if user_agent.contains("iPad") || user_agent.contains("Tablet") {
+			"Tablet"
+		} else if user_agent.contains("Mobi") || user_agent.contains("iPhone") {
+			"Mobile"
+		} else if user_agent.contains("Mozilla/") {
+			"Desktop"
+		} else {
+			UNKNOWN
+		};
+
+		Self {
+			browser: find(BROWSERS),
+			os: find(OPERATING_SYSTEMS),
+			device: device.to_string(),
+		}
+	}
+
+	/// Get the browser name.
+	pub fn browser(&self) -> &str {
+		&self.browser
+	}
+
+	/// Get the operating system name.
+	pub fn os(&self) -> &str {
+		&self.os
+	}
+
+	/// Get the device type (e.g., "Desktop", "Mobile").
+	pub fn device(&self) -> &str {
+		&self.device
+	}
+
+	/// Get a human-readable summary (e.g., "Firefox on Linux").
+	pub fn summary(&self) -> String {
+		format!("{} on {}", self.browser, self.os)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_user_agents() {
+		let cases = [
+			(
+				"Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0",
+				"Firefox on Linux",
+				"Desktop",
+			),
+			(
+				"Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0.0.0 Safari/537.36 Edg/126.0.0.0",
+				"Edge on Windows",
+				"Desktop",
+			),
+			(
+				"Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.5 Safari/605.1.15",
+				"Safari on macOS",
+				"Desktop",
+			),
+			(
+				"Mozilla/5.0 (iPhone; CPU iPhone OS 17_5 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.5 Mobile/15E148 Safari/604.1",
+				"Safari on iOS",
+				"Mobile",
+			),
+			(
+				"Mozilla/5.0 (Linux; Android 14; Pixel 8) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0.0.0 Mobile Safari/537.36",
+				"Chrome on Android",
+				"Mobile",
+			),
+			("curl/8.8.0", "curl on Unknown", "Unknown"),
+			("test-agent", "Unknown on Unknown", "Unknown"),
+		];
+
+		for (user_agent, summary, device) in cases {
+			let info = DeviceInfo::parse(user_agent);
+			assert_eq!(info.summary(), summary, "{user_agent}");
+			assert_eq!(info.device(), device, "{user_agent}");
+		}
+	}
+}
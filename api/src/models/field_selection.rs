@@ -0,0 +1,250 @@
+use std::collections::BTreeSet;
+
+use serde_json::Map;
+use serde_json::Value;
+use thiserror::Error;
+
+/// The fields of a content context that can be selected.
+const CONTEXT_FIELDS: [&str; 11] = [
+	"block_id",
+	"parent_id",
+	"children_ids",
+	"reference_ids",
+	"backlink_ids",
+	"query_result_ids",
+	"related_ids",
+	"block_cache",
+	"annotations",
+	"stats",
+	"capabilities",
+];
+
+/// The fields of a cached content block, or of its content, that can be selected.
+const BLOCK_FIELDS: [&str; 14] = [
+	"nutty_id",
+	"owner_id",
+	"parent_id",
+	"f_index",
+	"content",
+	"properties",
+	"inherit_access",
+	"created_at",
+	"updated_at",
+	"kind",
+	"title",
+	"markdown",
+	"dsl",
+	"done",
+];
+
+/// A sparse selection of a content context's fields. Written as the `fields`
+/// query parameter on the context endpoint:
+///
+/// ```text
+/// fields=children_ids              — Only the children's Nutty IDs.
+/// fields=blocks                    — Also every cached block, in full.
+/// fields=blocks.title,children_ids — Also the title of every cached block.
+/// ```
+///
+/// Block fields name a field of the block (e.g. `parent_id`) or of its
+/// content (e.g. `title`), which keeps the content's `kind` alongside it.
+/// The context's own `block_id` is always included.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FieldSelection {
+	/// The selected context fields, or every field if empty.
+	pub fields: BTreeSet<String>,
+
+	/// The selected fields of each cached block, or every field if empty.
+	pub block_fields: BTreeSet<String>,
+}
+
+impl FieldSelection {
+	/// Parse a selection from its `fields` query parameter.
+	pub fn parse(fields: Option<&str>) -> Result<Self, FieldSelectionError> {
+		let mut selection = Self::default();
+
+		for field in fields.into_iter().flat_map(|fields| fields.split(',')) {
+			let field = field.trim();
+
+			if field.is_empty() {
+				continue;
+			}
+
+			let (field, block_field) = match field.split_once('.') {
+				Some((field, block_field)) => (field, Some(block_field)),
+				None => (field, None),
+			};
+
+			let field = match field {
+				"blocks" => "block_cache",
+				field => field,
+			};
+
+			match (field, block_field) {
+				(field, None) if CONTEXT_FIELDS.contains(&field) => {}
+
+				("block_cache", Some(block_field)) if BLOCK_FIELDS.contains(&block_field) => {
+					selection.block_fields.insert(block_field.to_string());
+				}
+
+				_ => return Err(FieldSelectionError::UnknownField(field.to_string())),
+			}
+
+			selection.fields.insert(field.to_string());
+		}
+
+		Ok(selection)
+	}
+
+	/// Check if the selection includes every field.
+	pub fn is_default(&self) -> bool {
+		self.fields.is_empty()
+	}
+
+	/// Project a serialized content context onto the selected fields.
+	pub fn project(&self, context: Value) -> Value {
+		let Value::Object(context) = context else {
+			return context;
+		};
+
+		if self.is_default() {
+			return Value::Object(context);
+		}
+
+		let projected = context
+			.into_iter()
+			.filter(|(field, _)| field == "block_id" || self.fields.contains(field))
+			.map(|(field, value)| match (field.as_str(), value) {
+				("block_cache", Value::Object(blocks)) if !self.block_fields.is_empty() => {
+					let blocks = blocks
+						.into_iter()
+						.map(|(block_id, block)| (block_id, self.project_block(block)))
+						.collect();
+
+					(field, Value::Object(blocks))
+				}
+
+				(_, value) => (field, value),
+			})
+			.collect();
+
+		Value::Object(projected)
+	}
+
+	fn project_block(&self, block: Value) -> Value {
+		let Value::Object(mut block) = block else {
+			return block;
+		};
+
+		let mut projected = Map::new();
+		let mut content = Map::new();
+
+		let block_content = match block.remove("content") {
+			Some(Value::Object(block_content)) => block_content,
+			_ => Map::new(),
+		};
+
+		for field in &self.block_fields {
+			if field == "content" {
+				projected.insert(field.clone(), Value::Object(block_content.clone()));
+			} else if let Some(value) = block.remove(field) {
+				projected.insert(field.clone(), value);
+			} else if let Some(value) = block_content.get(field) {
+				content.insert(field.clone(), value.clone());
+
+				if let Some(kind) = block_content.get("kind") {
+					content.insert("kind".to_string(), kind.clone());
+				}
+			}
+		}
+
+		if !content.is_empty() && !projected.contains_key("content") {
+			projected.insert("content".to_string(), Value::Object(content));
+		}
+
+		Value::Object(projected)
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum FieldSelectionError {
+	#[error("Unknown field; expected a context field or blocks.<field>: {0}")]
+	UnknownField(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+
+	#[test]
+	fn test_parse_field_selection() {
+		let selection = FieldSelection::parse(Some("blocks.title,children_ids")).unwrap();
+
+		assert_eq!(
+			selection.fields,
+			BTreeSet::from(["block_cache".to_string(), "children_ids".to_string()])
+		);
+
+		assert_eq!(
+			selection.block_fields,
+			BTreeSet::from(["title".to_string()])
+		);
+
+		assert!(FieldSelection::parse(None).unwrap().is_default());
+		assert!(FieldSelection::parse(Some("")).unwrap().is_default());
+
+		for fields in ["blocks.color", "children", "children_ids.title"] {
+			assert!(FieldSelection::parse(Some(fields)).is_err());
+		}
+	}
+
+	#[test]
+	fn test_project_field_selection() {
+		let context = json!({
+			"block_id": "page",
+			"parent_id": null,
+			"children_ids": ["paragraph"],
+			"backlink_ids": [],
+			"block_cache": {
+				"page": {
+					"nutty_id": "page",
+					"owner_id": null,
+					"content": { "kind": "Page", "title": "Notes" },
+				},
+				"paragraph": {
+					"nutty_id": "paragraph",
+					"owner_id": null,
+					"content": { "kind": "Paragraph", "markdown": "Hello" },
+				},
+			},
+		});
+
+		let selection = FieldSelection::parse(Some("blocks.title,children_ids")).unwrap();
+
+		assert_eq!(
+			selection.project(context.clone()),
+			json!({
+				"block_id": "page",
+				"children_ids": ["paragraph"],
+				"block_cache": {
+					"page": { "content": { "kind": "Page", "title": "Notes" } },
+					"paragraph": {},
+				},
+			})
+		);
+
+		let selection = FieldSelection::parse(Some("blocks")).unwrap();
+
+		assert_eq!(
+			selection.project(context.clone()),
+			json!({
+				"block_id": "page",
+				"block_cache": context["block_cache"],
+			})
+		);
+
+		assert_eq!(FieldSelection::default().project(context.clone()), context);
+	}
+}
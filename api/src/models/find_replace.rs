@@ -0,0 +1,172 @@
+use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::models::BlockContent;
+use crate::models::ContentBlock;
+use crate::models::NuttyId;
+
+/// The characters of context kept on either side of a match's snippet.
+const SNIPPET_CONTEXT: usize = 24;
+
+/// A find and replace over the markdown of content blocks. Patterns are
+/// regular expressions, and replacements may refer to capture groups as
+/// `$1` or `${name}`. Page titles and queries are left alone.
+#[derive(Debug, Clone)]
+pub struct FindReplace {
+	pattern: Regex,
+	replacement: String,
+}
+
+/// A match of a [FindReplace] pattern in a content block's markdown.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextMatch {
+	/// The Nutty ID of the matching block.
+	pub block_id: NuttyId,
+
+	/// The match, with some of the markdown around it.
+	pub snippet: String,
+
+	/// The text the match is replaced with.
+	pub replacement: String,
+}
+
+impl FindReplace {
+	/// Create a find and replace from a pattern and its replacement.
+	pub fn new(pattern: &str, replacement: &str) -> Result<Self, FindReplaceError> {
+		if pattern.is_empty() {
+			return Err(FindReplaceError::EmptyPattern);
+		}
+
+		Ok(Self {
+			pattern: Regex::new(pattern).map_err(FindReplaceError::InvalidPattern)?,
+			replacement: replacement.to_string(),
+		})
+	}
+
+	/// Find the matches in a block's markdown, along with its content after
+	/// replacing them. Returns nothing if the replacement changes nothing.
+	pub fn apply(&self, block: &ContentBlock) -> Option<(BlockContent, Vec<TextMatch>)> {
+		let markdown = match &block.content {
+			BlockContent::Heading { markdown }
+			| BlockContent::Paragraph { markdown }
+			| BlockContent::Todo { markdown, .. } => markdown,
+			BlockContent::Page { .. } | BlockContent::Query { .. } | BlockContent::Custom { .. } => {
+				return None;
+			}
+		};
+
+		let content = block.content.rewrite_markdown(|markdown| {
+			self
+				.pattern
+				.replace_all(markdown, self.replacement.as_str())
+				.into_owned()
+		})?;
+
+		let matches = self
+			.pattern
+			.captures_iter(markdown)
+			.map(|captures| {
+				let found = captures.get(0).unwrap();
+				let mut replacement = String::new();
+				captures.expand(&self.replacement, &mut replacement);
+
+				TextMatch {
+					block_id: *block.nutty_id(),
+					snippet: snippet(markdown, found.start(), found.end()),
+					replacement,
+				}
+			})
+			.collect();
+
+		Some((content, matches))
+	}
+}
+
+/// Cut a match out of some text, with up to [SNIPPET_CONTEXT] characters of
+/// context on either side, trimmed of surrounding whitespace.
+fn snippet(text: &str, start: usize, end: usize) -> String {
+	let before = text[..start]
+		.char_indices()
+		.rev()
+		.nth(SNIPPET_CONTEXT - 1)
+		.map_or(0, |(i, _)| i);
+
+	let after = text[end..]
+		.char_indices()
+		.nth(SNIPPET_CONTEXT)
+		.map_or(text.len(), |(i, _)| end + i);
+
+	text[before..after].trim().to_string()
+}
+
+#[derive(Debug, Error)]
+pub enum FindReplaceError {
+	#[error("The pattern is empty")]
+	EmptyPattern,
+
+	#[error("Invalid pattern: {0}")]
+	InvalidPattern(regex::Error),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::models::FractionalIndex;
+
+	#[test]
+	fn test_find_replace() {
+		let block = ContentBlock::now(
+			None,
+			FractionalIndex::start(),
+			BlockContent::Todo {
+				markdown:
+					"Email Alice Smith, then call Bob Jones about the long-overdue quarterly report."
+						.to_string(),
+				done: false,
+			},
+		);
+
+		let find_replace = FindReplace::new(r"(\w+) (Smith|Jones)", "$2, $1").unwrap();
+		let (content, matches) = find_replace.apply(&block).unwrap();
+
+		assert!(matches!(
+			content,
+			BlockContent::Todo { markdown, done: false }
+				if markdown == "Email Smith, Alice, then call Jones, Bob about the long-overdue quarterly report."
+		));
+
+		assert_eq!(
+			matches
+				.iter()
+				.map(|m| (m.snippet.as_str(), m.replacement.as_str()))
+				.collect::<Vec<_>>(),
+			vec![
+				("Email Alice Smith, then call Bob Jones ab", "Smith, Alice"),
+				(
+					"Alice Smith, then call Bob Jones about the long-overdue",
+					"Jones, Bob"
+				),
+			]
+		);
+
+		// Replacements that change nothing, and pages, are skipped.
+		let find_replace = FindReplace::new("Alice", "Alice").unwrap();
+		assert!(find_replace.apply(&block).is_none());
+
+		let page = ContentBlock::now(
+			None,
+			FractionalIndex::start(),
+			BlockContent::Page {
+				title: "Alice".to_string(),
+			},
+		);
+
+		let find_replace = FindReplace::new("Alice", "Carol").unwrap();
+		assert!(find_replace.apply(&page).is_none());
+
+		assert!(FindReplace::new("", "Carol").is_err());
+		assert!(FindReplace::new("(Alice", "Carol").is_err());
+	}
+}
@@ -116,6 +116,37 @@ impl FractionalIndex {
 		Ok(Self(result))
 	}
 
+	/// Generates indices for a whole sequence at once, spread evenly between
+	/// the start and the end, so that later insertions stay short.
+	pub fn spread(count: usize) -> Vec<Self> {
+		let base = Self::BASE as u128;
+
+		// The first digit stays below the end's, so that no index reaches it.
+		let mut width = 1;
+		let mut range = base - 1;
+
+		while range <= count as u128 {
+			width += 1;
+			range *= base;
+		}
+
+		(1..=count as u128)
+			.map(|i| {
+				let mut value = range * i / (count as u128 + 1);
+				let mut digits = vec![Self::MIN_CHAR; width];
+
+				for digit in digits.iter_mut().rev() {
+					*digit = Self::MIN_CHAR + (value % base) as u8;
+					value /= base;
+				}
+
+				// Trailing minimum digits don't change the value.
+				let index = String::from_utf8(digits).expect("Digits are ASCII");
+				Self(index.trim_end_matches(Self::MIN_CHAR as char).to_string())
+			})
+			.collect()
+	}
+
 	/// Returns the string representation of the index.
 	pub fn as_str(&self) -> &str {
 		&self.0
@@ -178,6 +209,25 @@ mod tests {
 		.prop_map(|chars| chars.into_iter().collect())
 	}
 
+	#[test]
+	fn test_spread() {
+		for count in [0, 1, 2, 92, 93, 94, 1000] {
+			let indices = FractionalIndex::spread(count);
+			assert_eq!(indices.len(), count);
+
+			// Assert: Indices are in order, between the start and the end.
+			let bounded = std::iter::once(FractionalIndex::start())
+				.chain(indices)
+				.chain(std::iter::once(FractionalIndex::end()))
+				.collect::<Vec<_>>();
+
+			for pair in bounded.windows(2) {
+				assert!(pair[0] < pair[1], "{count}: {:?}", pair);
+				assert!(pair[0].as_str() < pair[1].as_str(), "{count}: {:?}", pair);
+			}
+		}
+	}
+
 	#[test]
 	fn test_between() {
 		let start = FractionalIndex::start();
@@ -246,16 +296,16 @@ mod tests {
 			let b = FractionalIndex::new(b).ok();
 			let c = FractionalIndex::new(c).ok();
 
-			if let (Some(a), Some(b), Some(c)) = (a, b, c) {
-				if a < b && b < c {
-					let between = FractionalIndex::between(&a, &c).unwrap();
-					prop_assert!(a < between);
-					prop_assert!(between < c);
+			if let (Some(a), Some(b), Some(c)) = (a, b, c)
+				&& a < b && b < c
+			{
+				let between = FractionalIndex::between(&a, &c).unwrap();
+				prop_assert!(a < between);
+				prop_assert!(between < c);
 
-					// Sanity check: Are we testing something?
-					// Run with `cargo test -- --nocapture` to see the output.
-					println!("In test_ordering_property: {} {} {}", a.as_str(), b.as_str(), c.as_str());
-				}
+				// Sanity check: Are we testing something?
+				// Run with `cargo test -- --nocapture` to see the output.
+				println!("In test_ordering_property: {} {} {}", a.as_str(), b.as_str(), c.as_str());
 			}
 		}
 
@@ -265,16 +315,16 @@ mod tests {
 			let a = FractionalIndex::new(a).ok();
 			let b = FractionalIndex::new(b).ok();
 
-			if let (Some(a), Some(b)) = (a, b) {
-				if a < b {
-					let between = FractionalIndex::between(&a, &b).unwrap();
-					prop_assert!(a < between);
-					prop_assert!(between < b);
-
-					// Sanity check: Are we testing something?
-					// Run with `cargo test -- --nocapture` to see the output.
-					println!("In test_monotonicity: {} {} {}", a.as_str(), b.as_str(), between.as_str());
-				}
+			if let (Some(a), Some(b)) = (a, b)
+				&& a < b
+			{
+				let between = FractionalIndex::between(&a, &b).unwrap();
+				prop_assert!(a < between);
+				prop_assert!(between < b);
+
+				// Sanity check: Are we testing something?
+				// Run with `cargo test -- --nocapture` to see the output.
+				println!("In test_monotonicity: {} {} {}", a.as_str(), b.as_str(), between.as_str());
 			}
 		}
 
@@ -286,16 +336,16 @@ mod tests {
 			let c = FractionalIndex::new(c).ok();
 			let d = FractionalIndex::new(d).ok();
 
-			if let (Some(ref a), Some(ref b), Some(ref c), Some(ref d)) = (a, b, c, d) {
-				if a < b && c < d && (a, b) != (c, d) {
-					let between1 = FractionalIndex::between(a, b).unwrap();
-					let between2 = FractionalIndex::between(c, d).unwrap();
-					prop_assert_ne!(between1, between2);
+			if let (Some(ref a), Some(ref b), Some(ref c), Some(ref d)) = (a, b, c, d)
+				&& a < b && c < d && (a, b) != (c, d)
+			{
+				let between1 = FractionalIndex::between(a, b).unwrap();
+				let between2 = FractionalIndex::between(c, d).unwrap();
+				prop_assert_ne!(between1, between2);
 
-					// Sanity check: Are we testing something?
-					// Run with `cargo test -- --nocapture` to see the output.
-					println!("In test_uniqueness: {} {} {} {}", a.as_str(), b.as_str(), c.as_str(), d.as_str());
-				}
+				// Sanity check: Are we testing something?
+				// Run with `cargo test -- --nocapture` to see the output.
+				println!("In test_uniqueness: {} {} {} {}", a.as_str(), b.as_str(), c.as_str(), d.as_str());
 			}
 		}
 
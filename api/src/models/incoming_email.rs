@@ -0,0 +1,213 @@
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::models::BlockContent;
+use crate::models::ContentBlock;
+use crate::models::FractionalIndex;
+use crate::models::NuttyId;
+use crate::models::fractional_index::FractionalIndexError;
+
+/// The title of a page filed from an email without a subject.
+const UNTITLED: &str = "(No subject)";
+
+/// An email received by a mail service, as posted to the ingestion webhook.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncomingEmail {
+	/// The sender's address.
+	pub from: String,
+
+	/// The recipient's address, e.g. `alice@inbox.nuttyver.se`.
+	pub to: String,
+
+	/// The subject line, if any.
+	#[serde(default)]
+	pub subject: String,
+
+	/// The plain text body.
+	#[serde(default)]
+	pub text: String,
+
+	/// The email's attachments.
+	#[serde(default)]
+	pub attachments: Vec<EmailAttachment>,
+}
+
+/// An attachment of an [IncomingEmail].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailAttachment {
+	/// The attachment's file name.
+	pub filename: String,
+
+	/// The attachment's MIME type.
+	pub content_type: String,
+}
+
+impl IncomingEmail {
+	/// Get the name of the navigator the email is addressed to: the local
+	/// part of the recipient's address, without any `+tag`.
+	pub fn recipient_name(&self) -> Option<&str> {
+		let address = self.to.trim();
+
+		// Accept `Alice <alice@…>` as well as a bare address.
+		let address = match (address.find('<'), address.rfind('>')) {
+			(Some(start), Some(end)) if start < end => &address[start + 1..end],
+			_ => address,
+		};
+
+		let (local, _) = address.split_once('@')?;
+		let name = local.split('+').next().unwrap_or(local);
+
+		(!name.is_empty()).then_some(name)
+	}
+
+	/// Convert the email into a page under the given parent, with a paragraph
+	/// per paragraph of its body. Attachments are listed by file name, since
+	/// there is nowhere to store their contents yet. Returns the page first.
+	pub fn to_blocks(
+		&self,
+		parent_id: NuttyId,
+		owner_id: NuttyId,
+	) -> Result<Vec<ContentBlock>, IncomingEmailError> {
+		let subject = self.subject.trim();
+		let title = if subject.is_empty() {
+			UNTITLED
+		} else {
+			subject
+		};
+
+		let page = ContentBlock::now_with_owner(
+			Some(parent_id),
+			owner_id,
+			FractionalIndex::start(),
+			BlockContent::Page {
+				title: title.to_string(),
+			},
+		);
+
+		let mut paragraphs = vec![format!("From: {}", self.from.trim())];
+
+		paragraphs.extend(
+			self
+				.text
+				.replace("\r\n", "\n")
+				.split("\n\n")
+				.map(str::trim)
+				.filter(|paragraph| !paragraph.is_empty())
+				.map(str::to_string),
+		);
+
+		if !self.attachments.is_empty() {
+			let names: Vec<_> = self
+				.attachments
+				.iter()
+				.map(|attachment| attachment.filename.as_str())
+				.collect();
+
+			paragraphs.push(format!("Attachments: {}", names.join(", ")));
+		}
+
+		let mut blocks = vec![page];
+		let mut f_index = FractionalIndex::start();
+
+		for markdown in paragraphs {
+			f_index = FractionalIndex::between(&f_index, &FractionalIndex::end())?;
+
+			blocks.push(ContentBlock::now_with_owner(
+				Some(*blocks[0].nutty_id()),
+				owner_id,
+				f_index.clone(),
+				BlockContent::Paragraph { markdown },
+			));
+		}
+
+		Ok(blocks)
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum IncomingEmailError {
+	#[error("Unable to order the email's paragraphs: {0}")]
+	FractionalIndex(#[from] FractionalIndexError),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn email(to: &str) -> IncomingEmail {
+		IncomingEmail {
+			from: "Bob <bob@example.com>".to_string(),
+			to: to.to_string(),
+			subject: "  Trip notes ".to_string(),
+			text: "Flights are booked.\r\n\r\n\r\nHotel is next.\n".to_string(),
+			attachments: vec![EmailAttachment {
+				filename: "itinerary.pdf".to_string(),
+				content_type: "application/pdf".to_string(),
+			}],
+		}
+	}
+
+	#[test]
+	fn test_recipient_name() {
+		assert_eq!(email("alice@inbox.test").recipient_name(), Some("alice"));
+		assert_eq!(
+			email("alice+trips@inbox.test").recipient_name(),
+			Some("alice")
+		);
+		assert_eq!(
+			email("Alice <alice@inbox.test>").recipient_name(),
+			Some("alice")
+		);
+		assert_eq!(email("inbox.test").recipient_name(), None);
+		assert_eq!(email("@inbox.test").recipient_name(), None);
+	}
+
+	#[test]
+	fn test_to_blocks() {
+		let parent_id = NuttyId::now();
+		let owner_id = NuttyId::now();
+		let blocks = email("alice@inbox.test")
+			.to_blocks(parent_id, owner_id)
+			.unwrap();
+
+		assert!(matches!(
+			&blocks[0].content,
+			BlockContent::Page { title } if title == "Trip notes"
+		));
+		assert_eq!(blocks[0].parent_id, Some(parent_id));
+
+		let markdown: Vec<_> = blocks[1..]
+			.iter()
+			.map(|block| match &block.content {
+				BlockContent::Paragraph { markdown } => markdown.as_str(),
+				_ => panic!("Expected a paragraph"),
+			})
+			.collect();
+
+		assert_eq!(
+			markdown,
+			vec![
+				"From: Bob <bob@example.com>",
+				"Flights are booked.",
+				"Hotel is next.",
+				"Attachments: itinerary.pdf",
+			]
+		);
+
+		// Paragraphs are ordered, under the page, and owned by the recipient.
+		for pair in blocks[1..].windows(2) {
+			assert!(pair[0].f_index.as_str() < pair[1].f_index.as_str());
+		}
+
+		for block in &blocks {
+			assert!(block.is_owned_by(&owner_id));
+		}
+
+		assert!(
+			blocks[1..]
+				.iter()
+				.all(|block| block.parent_id == Some(*blocks[0].nutty_id()))
+		);
+	}
+}
@@ -0,0 +1,99 @@
+use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::NuttyId;
+
+/// A block linking to another, with an excerpt of its text, for listing a
+/// page's backlinks where they can't be computed, e.g. in static exports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkedReference {
+	pub block_id: NuttyId,
+
+	/// The linking block's text, on one line, with tags replaced by their
+	/// display text.
+	pub excerpt: String,
+}
+
+impl LinkedReference {
+	/// The most characters of a block's text that are kept as its excerpt.
+	pub const MAX_EXCERPT_CHARS: usize = 160;
+
+	/// Make a reference to a block from its markdown.
+	pub fn new(block_id: NuttyId, markdown: &str) -> Self {
+		// Matches [[…]] like [NuttyTag::parse_all].
+		//
+		// [NuttyTag::parse_all]: crate::models::NuttyTag::parse_all
+		let re = Regex::new(r"\[\[([^]]+)\]\]").unwrap();
+
+		let text = re.replace_all(markdown, |captures: &regex::Captures| {
+			let tag = &captures[1];
+			tag.split_once('|')
+				.map_or(tag, |(_, text)| text)
+				.to_string()
+		});
+
+		let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+		let mut excerpt: String = text.chars().take(Self::MAX_EXCERPT_CHARS).collect();
+
+		if excerpt.len() < text.len() {
+			excerpt.push('…');
+		}
+
+		Self { block_id, excerpt }
+	}
+
+	/// Render references as a "Linked references" section of HTML, in the
+	/// order given. Without any, nothing is rendered.
+	pub fn render_html(references: &[LinkedReference]) -> String {
+		if references.is_empty() {
+			return String::new();
+		}
+
+		let items: String = references
+			.iter()
+			.map(|reference| {
+				format!(
+					"<li><a href=\"{}\">{}</a></li>",
+					reference.block_id.permalink(),
+					ammonia::clean_text(&reference.excerpt),
+				)
+			})
+			.collect();
+
+		format!(
+			"<section class=\"linked-references\"><h2>Linked references</h2><ul>{items}</ul></section>"
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_linked_reference() {
+		let block_id = NuttyId::now();
+
+		// Assert: Tags become their display text, and lines are joined.
+		let reference = LinkedReference::new(block_id, "See [[abcdefg|Rust tips]]\nand [[hijklmn]].");
+		assert_eq!(reference.excerpt, "See Rust tips and hijklmn.");
+
+		// Assert: Long text is cut short.
+		let reference = LinkedReference::new(block_id, &"é".repeat(200));
+		assert_eq!(
+			reference.excerpt.chars().count(),
+			LinkedReference::MAX_EXCERPT_CHARS + 1
+		);
+		assert!(reference.excerpt.ends_with('…'));
+
+		// Assert: Excerpts are escaped, and nothing renders without references.
+		let reference = LinkedReference::new(block_id, "<b>Bold</b>");
+		let html = LinkedReference::render_html(&[reference]);
+
+		assert!(html.contains("Linked references"));
+		assert!(html.contains(&block_id.permalink()));
+		assert!(html.contains("&lt;b&gt;Bold&lt;&#47;b&gt;"));
+		assert_eq!(LinkedReference::render_html(&[]), "");
+	}
+}
@@ -1,16 +1,59 @@
+pub mod access_request;
+pub mod block_annotation;
+pub mod block_archival;
+pub mod block_checksum;
 pub mod block_content;
+pub mod block_conversion;
+pub mod block_date;
+pub mod block_deletion;
+pub mod block_group;
+pub mod block_merge;
+pub mod block_query;
+pub mod block_revision;
+pub mod block_split;
+pub mod block_stats;
+pub mod canonical_json;
+pub mod capture;
+pub mod children_view;
 pub mod content_block;
+pub mod content_calendar;
+pub mod content_compression;
 pub mod content_context;
+pub mod content_diff;
+pub mod content_event;
+pub mod content_filter;
 pub mod content_link;
+pub mod context_include;
 pub mod date_time_rfc_3339;
+pub mod device;
+pub mod field_selection;
+pub mod find_replace;
 pub mod fractional_index;
+pub mod incoming_email;
+pub mod linked_reference;
 pub mod navigator;
+pub mod navigator_block;
+pub mod navigator_export;
 pub mod nutty_id;
 pub mod nutty_tag;
+pub mod onboarding;
+pub mod outline;
+pub mod ownership_transfer;
+pub mod property;
+pub mod request_usage;
+pub mod search_language;
 pub mod session;
+pub mod share_level;
+pub mod site_settings;
+pub mod slug;
+pub mod sync_changes;
+pub mod task;
+pub mod translation;
 
 pub use block_content::BlockContent;
+pub use block_query::BlockQuery;
 pub use content_block::ContentBlock;
+pub use content_calendar::ContentCalendar;
 pub use content_context::ContentContext;
 pub use content_link::ContentLink;
 pub use fractional_index::FractionalIndex;
@@ -18,3 +61,6 @@ pub use navigator::Navigator;
 pub use nutty_id::DissociatedNuttyId;
 pub use nutty_id::NuttyId;
 pub use nutty_tag::NuttyTag;
+pub use share_level::ShareLevel;
+pub use slug::Slug;
+pub use task::Task;
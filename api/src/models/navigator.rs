@@ -1,5 +1,11 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+use argon2::Algorithm;
 use argon2::Argon2;
+use argon2::Params;
 use argon2::PasswordHash;
+use argon2::Version;
 use argon2::password_hash::PasswordHasher;
 use argon2::password_hash::PasswordVerifier;
 use argon2::password_hash::SaltString;
@@ -13,6 +19,132 @@ use thiserror::Error;
 
 use crate::models::NuttyId;
 use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+use crate::utilities::secrets::Secret;
+
+/// The process-wide password hashing configuration.
+static PASSWORD_HASHING: OnceLock<PasswordHashing> = OnceLock::new();
+
+/// How passwords are hashed and verified.
+#[derive(Clone, Default)]
+pub struct PasswordHashing {
+	/// The Argon2 parameters for hashing new passwords.
+	pub params: Params,
+
+	/// The server-side secret mixed into every hash. Empty means no pepper.
+	pub pepper: Vec<u8>,
+
+	/// The pepper being rotated out, still accepted during verification.
+	/// An empty previous pepper accepts hashes made before a pepper was set.
+	pub previous_pepper: Option<Vec<u8>>,
+}
+
+impl fmt::Debug for PasswordHashing {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("PasswordHashing")
+			.field("params", &self.params)
+			.field("pepper", &"[REDACTED]")
+			.field(
+				"previous_pepper",
+				&self.previous_pepper.as_ref().map(|_| "[REDACTED]"),
+			)
+			.finish()
+	}
+}
+
+/// Which pepper verified a password.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PepperMatch {
+	Current,
+	Previous,
+}
+
+impl PasswordHashing {
+	/// Get the process-wide configuration (the Argon2 defaults, if unconfigured).
+	pub fn current() -> &'static PasswordHashing {
+		PASSWORD_HASHING.get_or_init(PasswordHashing::default)
+	}
+
+	/// Read the configuration from the environment: the Argon2 parameters
+	/// from `ARGON2_MEMORY_KIB`, `ARGON2_ITERATIONS`, and `ARGON2_PARALLELISM`,
+	/// and the peppers from `PASSWORD_PEPPER` and `PASSWORD_PREVIOUS_PEPPER`.
+	/// Set the previous pepper (possibly empty) while rotating peppers.
+	pub fn from_env() -> Result<Self, NavigatorError> {
+		let defaults = Params::default();
+		let param = |name: &str, default: u32| {
+			std::env::var(name)
+				.ok()
+				.and_then(|v| v.parse().ok())
+				.unwrap_or(default)
+		};
+
+		let params = Params::new(
+			param("ARGON2_MEMORY_KIB", defaults.m_cost()),
+			param("ARGON2_ITERATIONS", defaults.t_cost()),
+			param("ARGON2_PARALLELISM", defaults.p_cost()),
+			None,
+		)
+		.map_err(|e| NavigatorError::PasswordHashingError(e.to_string()))?;
+
+		Ok(Self {
+			params,
+			pepper: Secret::from_env("PASSWORD_PEPPER")
+				.map(Secret::into_bytes)
+				.unwrap_or_default(),
+			previous_pepper: Secret::from_env("PASSWORD_PREVIOUS_PEPPER").map(Secret::into_bytes),
+		})
+	}
+
+	/// Set the process-wide configuration.
+	/// Only takes effect if the configuration hasn't been used yet.
+	pub fn configure(self) -> bool {
+		PASSWORD_HASHING.set(self).is_ok()
+	}
+
+	/// Get an Argon2 hasher using the configured parameters and a pepper.
+	fn hasher<'k>(&self, pepper: &'k [u8]) -> Result<Argon2<'k>, NavigatorError> {
+		Argon2::new_with_secret(
+			pepper,
+			Algorithm::Argon2id,
+			Version::V0x13,
+			self.params.clone(),
+		)
+		.map_err(|e| NavigatorError::PasswordHashingError(e.to_string()))
+	}
+
+	/// Hash a password with the current pepper.
+	pub fn hash(&self, password: &str) -> Result<String, NavigatorError> {
+		let salt = SaltString::generate(&mut OsRng);
+
+		Ok(self
+			.hasher(&self.pepper)?
+			.hash_password(password.as_bytes(), &salt)
+			.map_err(|e| NavigatorError::PasswordHashingError(e.to_string()))?
+			.to_string())
+	}
+
+	/// Verify a password against a hash, trying the current pepper first.
+	pub fn verify(&self, hash: &str, password: &str) -> Option<PepperMatch> {
+		let parsed_hash = PasswordHash::new(hash).ok()?;
+
+		let verifies = |pepper: &[u8]| {
+			self.hasher(pepper).is_ok_and(|argon2| {
+				argon2
+					.verify_password(password.as_bytes(), &parsed_hash)
+					.is_ok()
+			})
+		};
+
+		if verifies(&self.pepper) {
+			Some(PepperMatch::Current)
+		} else if let Some(previous_pepper) = &self.previous_pepper
+			&& verifies(previous_pepper)
+		{
+			Some(PepperMatch::Previous)
+		} else {
+			None
+		}
+	}
+}
 
 /// A registered visitor wandering about in the Nuttyverse.
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -31,13 +163,7 @@ impl Navigator {
 	pub fn new(name: String, password: &str) -> Result<Self, NavigatorError> {
 		Navigator::validate_name(&name)?;
 
-		let salt = SaltString::generate(&mut OsRng);
-		let argon2 = Argon2::default();
-
-		let password_hash = argon2
-			.hash_password(password.as_bytes(), &salt)
-			.map_err(|e| NavigatorError::PasswordHashingError(e.to_string()))?
-			.to_string();
+		let password_hash = PasswordHashing::current().hash(password)?;
 
 		let nutty_id = NuttyId::now();
 		let timestamp = nutty_id.timestamp() as i64;
@@ -81,14 +207,55 @@ impl Navigator {
 
 	/// Verify a password attempt against the stored hash.
 	pub fn verify_password(&self, password: &str) -> bool {
-		let parsed_hash = match PasswordHash::new(&self.pass) {
-			Ok(hash) => hash,
-			Err(_) => return false,
+		self.verify_password_match(password).is_some()
+	}
+
+	/// Verify a password attempt, reporting which pepper it matched.
+	pub fn verify_password_match(&self, password: &str) -> Option<PepperMatch> {
+		PasswordHashing::current().verify(&self.pass, password)
+	}
+
+	/// Check if the stored hash was made with outdated parameters.
+	pub fn needs_rehash(&self) -> bool {
+		self.needs_rehash_with(&PasswordHashing::current().params)
+	}
+
+	/// Check if the stored hash was made with parameters other than the given ones.
+	pub fn needs_rehash_with(&self, params: &Params) -> bool {
+		let Ok(hash) = PasswordHash::new(&self.pass) else {
+			return true;
 		};
 
-		Argon2::default()
-			.verify_password(password.as_bytes(), &parsed_hash)
-			.is_ok()
+		let Ok(hash_params) = Params::try_from(&hash) else {
+			return true;
+		};
+
+		hash.algorithm != Algorithm::Argon2id.ident()
+			|| hash.version != Some(Version::V0x13.into())
+			|| hash_params.m_cost() != params.m_cost()
+			|| hash_params.t_cost() != params.t_cost()
+			|| hash_params.p_cost() != params.p_cost()
+	}
+
+	/// Describe the algorithm, version, and parameters of the stored hash
+	/// (e.g., "argon2id-v19-m19456-t2-p1").
+	pub fn hash_version(&self) -> String {
+		let Ok(hash) = PasswordHash::new(&self.pass) else {
+			return "unknown".to_string();
+		};
+
+		let version = hash.version.map(|v| format!("-v{v}")).unwrap_or_default();
+
+		match Params::try_from(&hash) {
+			Ok(params) => format!(
+				"{}{version}-m{}-t{}-p{}",
+				hash.algorithm,
+				params.m_cost(),
+				params.t_cost(),
+				params.p_cost()
+			),
+			Err(_) => format!("{}{version}", hash.algorithm),
+		}
 	}
 
 	/// Replace the existing name with a new name.
@@ -101,13 +268,7 @@ impl Navigator {
 
 	/// Replace the existing password with a new password.
 	pub fn update_password(&mut self, new_password: &str) -> Result<(), NavigatorError> {
-		let salt = SaltString::generate(&mut OsRng);
-		let argon2 = Argon2::default();
-
-		self.pass = argon2
-			.hash_password(new_password.as_bytes(), &salt)
-			.map_err(|e| NavigatorError::PasswordHashingError(e.to_string()))?
-			.to_string();
+		self.pass = PasswordHashing::current().hash(new_password)?;
 
 		Ok(())
 	}
@@ -209,13 +370,9 @@ impl NavigatorBuilder {
 				let pass = if self.password_is_hashed {
 					password
 				} else {
-					let salt = SaltString::generate(&mut OsRng);
-					let argon2 = Argon2::default();
-
-					argon2
-						.hash_password(password.as_bytes(), &salt)
+					PasswordHashing::current()
+						.hash(&password)
 						.map_err(|e| NavigatorBuilderError::PasswordHashingError(e.to_string()))?
-						.to_string()
 				};
 
 				Ok(Navigator {
@@ -286,6 +443,71 @@ pub enum NavigatorError {
 mod tests {
 	use super::*;
 
+	#[test]
+	fn test_pepper_rotation() {
+		// Arrange: Hash a password without a pepper.
+		let unpeppered = PasswordHashing::default();
+		let old_hash = unpeppered.hash("password123").unwrap();
+
+		// Arrange: Introduce a pepper, rotating out "no pepper".
+		let peppered = PasswordHashing {
+			pepper: b"new-pepper".to_vec(),
+			previous_pepper: Some(vec![]),
+			..Default::default()
+		};
+
+		let new_hash = peppered.hash("password123").unwrap();
+
+		// Assert: Both hashes verify, and the match reports the pepper used.
+		assert_eq!(
+			peppered.verify(&new_hash, "password123"),
+			Some(PepperMatch::Current)
+		);
+		assert_eq!(
+			peppered.verify(&old_hash, "password123"),
+			Some(PepperMatch::Previous)
+		);
+		assert_eq!(peppered.verify(&new_hash, "wrong"), None);
+
+		// Assert: Without the rotation, the old hash no longer verifies.
+		let rotated = PasswordHashing {
+			pepper: b"new-pepper".to_vec(),
+			..Default::default()
+		};
+
+		assert_eq!(rotated.verify(&old_hash, "password123"), None);
+
+		// Assert: Without the pepper, the new hash doesn't verify.
+		assert_eq!(unpeppered.verify(&new_hash, "password123"), None);
+	}
+
+	#[test]
+	fn test_needs_rehash() {
+		// Arrange: Hash a password with the default parameters.
+		let navigator = Navigator::new("rehash".to_string(), "password123").unwrap();
+
+		// Assert: The hash is current for the default parameters.
+		assert!(!navigator.needs_rehash_with(&Params::default()));
+		assert_eq!(navigator.hash_version(), "argon2id-v19-m19456-t2-p1");
+
+		// Assert: The hash is outdated for stronger parameters.
+		let stronger = Params::new(64 * 1024, 3, 1, None).unwrap();
+		assert!(navigator.needs_rehash_with(&stronger));
+
+		// Assert: Garbage hashes always need rehashing.
+		let garbage = Navigator::builder()
+			.nutty_id(NuttyId::now())
+			.name("garbage".to_string())
+			.password_hash("not-a-hash".to_string())
+			.created_at(*navigator.created_at())
+			.updated_at(*navigator.updated_at())
+			.try_build()
+			.unwrap();
+
+		assert!(garbage.needs_rehash_with(&Params::default()));
+		assert_eq!(garbage.hash_version(), "unknown");
+	}
+
 	#[test]
 	fn test_validate_name() {
 		// OK if name is valid.
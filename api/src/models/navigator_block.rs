@@ -0,0 +1,14 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::NuttyId;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+
+/// A navigator that another has blocked. Blocked navigators can't request
+/// access to, or comment on, the blocker's content.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockedNavigator {
+	pub navigator_id: NuttyId,
+	pub name: String,
+	pub blocked_at: DateTimeRfc3339,
+}
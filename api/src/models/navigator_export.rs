@@ -0,0 +1,220 @@
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde::Serialize;
+use sqlx::Decode;
+use sqlx::Encode;
+use sqlx::FromRow;
+use sqlx::Postgres;
+use sqlx::Type;
+use sqlx::postgres::PgTypeInfo;
+use sqlx::postgres::PgValueRef;
+use thiserror::Error;
+
+use crate::models::ContentBlock;
+use crate::models::Navigator;
+use crate::models::NuttyId;
+use crate::models::access_request::AccessRequest;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+use crate::models::ownership_transfer::OwnershipTransfer;
+use crate::models::session::Session;
+use crate::moderation::models::Report;
+
+/// Where an export is in its assembly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportStatus {
+	/// Waiting to be assembled.
+	Pending,
+
+	/// Assembled, and ready to download until it expires.
+	Ready,
+
+	/// Failed to assemble.
+	Failed,
+}
+
+impl ExportStatus {
+	/// Get the stored representation of the status.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ExportStatus::Pending => "pending",
+			ExportStatus::Ready => "ready",
+			ExportStatus::Failed => "failed",
+		}
+	}
+}
+
+impl FromStr for ExportStatus {
+	type Err = NavigatorExportError;
+
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		match value {
+			"pending" => Ok(ExportStatus::Pending),
+			"ready" => Ok(ExportStatus::Ready),
+			"failed" => Ok(ExportStatus::Failed),
+			_ => Err(NavigatorExportError::UnknownStatus(value.to_string())),
+		}
+	}
+}
+
+impl Type<Postgres> for ExportStatus {
+	fn type_info() -> PgTypeInfo {
+		PgTypeInfo::with_name("VARCHAR")
+	}
+
+	fn compatible(ty: &PgTypeInfo) -> bool {
+		<&str as Type<Postgres>>::compatible(ty)
+	}
+}
+
+impl Encode<'_, Postgres> for ExportStatus {
+	fn encode_by_ref(
+		&self,
+		buf: &mut <Postgres as sqlx::Database>::ArgumentBuffer<'_>,
+	) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+		<&str as Encode<Postgres>>::encode(self.as_str(), buf)
+	}
+}
+
+impl<'r> Decode<'r, Postgres> for ExportStatus {
+	fn decode(value: PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+		let value = <&str as Decode<Postgres>>::decode(value)?;
+		Ok(value.parse()?)
+	}
+}
+
+/// A navigator's request for an archive of their personal data.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NavigatorExport {
+	#[sqlx(rename = "id")]
+	nutty_id: NuttyId,
+	navigator_id: NuttyId,
+	status: ExportStatus,
+
+	/// Why the export failed, if it did.
+	error: Option<String>,
+
+	completed_at: Option<DateTimeRfc3339>,
+
+	/// When the archive stops being downloadable.
+	expires_at: DateTimeRfc3339,
+
+	created_at: DateTimeRfc3339,
+	updated_at: DateTimeRfc3339,
+}
+
+impl NavigatorExport {
+	/// Create a new pending export, which expires after a while.
+	pub fn new(navigator_id: NuttyId, ttl: chrono::Duration) -> Self {
+		let now = chrono::Utc::now().fixed_offset();
+
+		Self {
+			nutty_id: NuttyId::now(),
+			navigator_id,
+			status: ExportStatus::Pending,
+			error: None,
+			completed_at: None,
+			expires_at: DateTimeRfc3339::new(now + ttl),
+			created_at: DateTimeRfc3339::new(now),
+			updated_at: DateTimeRfc3339::new(now),
+		}
+	}
+
+	pub fn nutty_id(&self) -> &NuttyId {
+		&self.nutty_id
+	}
+
+	pub fn navigator_id(&self) -> &NuttyId {
+		&self.navigator_id
+	}
+
+	pub fn status(&self) -> ExportStatus {
+		self.status
+	}
+
+	pub fn error(&self) -> Option<&str> {
+		self.error.as_deref()
+	}
+
+	pub fn completed_at(&self) -> Option<&DateTimeRfc3339> {
+		self.completed_at.as_ref()
+	}
+
+	pub fn expires_at(&self) -> &DateTimeRfc3339 {
+		&self.expires_at
+	}
+
+	pub fn created_at(&self) -> &DateTimeRfc3339 {
+		&self.created_at
+	}
+
+	/// Check whether the archive can no longer be downloaded.
+	pub fn is_expired(&self) -> bool {
+		chrono::Utc::now().fixed_offset() > *self.expires_at.inner()
+	}
+
+	/// Mark the export as assembled.
+	pub fn complete(&mut self) {
+		let now = DateTimeRfc3339::new(chrono::Utc::now().fixed_offset());
+
+		self.status = ExportStatus::Ready;
+		self.completed_at = Some(now);
+		self.updated_at = now;
+	}
+
+	/// Mark the export as failed.
+	pub fn fail(&mut self, error: String) {
+		let now = DateTimeRfc3339::new(chrono::Utc::now().fixed_offset());
+
+		self.status = ExportStatus::Failed;
+		self.error = Some(error);
+		self.completed_at = Some(now);
+		self.updated_at = now;
+	}
+}
+
+/// A past name of a navigator.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NameChange {
+	pub old_name: String,
+	pub new_name: String,
+	pub created_at: DateTimeRfc3339,
+}
+
+/// A chat platform user linked to a navigator.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ChatLink {
+	pub platform: String,
+	pub chat_user_id: String,
+	pub created_at: DateTimeRfc3339,
+}
+
+/// Everything the Nuttyverse stores about a navigator. Secrets, like
+/// password and session token hashes, are left out.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportArchive {
+	pub exported_at: DateTimeRfc3339,
+	pub navigator: Navigator,
+	pub name_changes: Vec<NameChange>,
+	pub sessions: Vec<Session>,
+	pub chat_links: Vec<ChatLink>,
+
+	/// The content blocks that the navigator owns.
+	pub blocks: Vec<ContentBlock>,
+
+	/// The reports that the navigator filed.
+	pub reports: Vec<Report>,
+
+	/// The access that the navigator requested.
+	pub access_requests: Vec<AccessRequest>,
+
+	/// Ownership transfers to or from the navigator.
+	pub ownership_transfers: Vec<OwnershipTransfer>,
+}
+
+#[derive(Debug, Error)]
+pub enum NavigatorExportError {
+	#[error("Unknown export status: {0}")]
+	UnknownStatus(String),
+}
@@ -9,6 +9,9 @@ use sqlx::Type;
 use thiserror::Error;
 use uuid::Uuid;
 
+/// The base URL of permalinks.
+pub const PERMALINK_BASE_URL: &str = "https://nuttyver.se";
+
 /// A Nutty ID is a newtype wrapper around a UUID.
 ///
 /// It can be used to derive a short base-58 encoded string
@@ -62,6 +65,11 @@ impl NuttyId {
 	pub fn dissociate(&self) -> DissociatedNuttyId {
 		DissociatedNuttyId::new(&self.nid()).expect("the impossible")
 	}
+
+	/// Get the permalink for the NID.
+	pub fn permalink(&self) -> String {
+		format!("{PERMALINK_BASE_URL}/{}", self.nid())
+	}
 }
 
 impl From<Uuid> for NuttyId {
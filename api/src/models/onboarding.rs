@@ -0,0 +1,246 @@
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::models::BlockContent;
+use crate::models::ContentBlock;
+use crate::models::FractionalIndex;
+use crate::models::NuttyId;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+
+/// A step of a new navigator's onboarding, which they mark complete as they
+/// find their way around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingStep {
+	/// Read the welcome page.
+	ReadWelcome,
+
+	/// Create a page of their own.
+	CreatePage,
+
+	/// Share a block with another navigator.
+	ShareBlock,
+}
+
+impl OnboardingStep {
+	/// Every step, in the order they're presented.
+	pub const ALL: [OnboardingStep; 3] = [
+		OnboardingStep::ReadWelcome,
+		OnboardingStep::CreatePage,
+		OnboardingStep::ShareBlock,
+	];
+
+	/// Get the step's name, as it's stored and written in paths.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			OnboardingStep::ReadWelcome => "read_welcome",
+			OnboardingStep::CreatePage => "create_page",
+			OnboardingStep::ShareBlock => "share_block",
+		}
+	}
+
+	/// Get a step by its name.
+	pub fn parse(step: &str) -> Result<Self, OnboardingError> {
+		Self::ALL
+			.into_iter()
+			.find(|candidate| candidate.as_str() == step)
+			.ok_or_else(|| OnboardingError::UnknownStep(step.to_string()))
+	}
+}
+
+/// A step of a navigator's onboarding, and when they completed it, if they
+/// have.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OnboardingProgress {
+	pub step: OnboardingStep,
+	pub completed_at: Option<DateTimeRfc3339>,
+}
+
+impl OnboardingProgress {
+	/// List every step, in order, with the completed ones' times.
+	pub fn list(completed: &[(OnboardingStep, DateTimeRfc3339)]) -> Vec<Self> {
+		OnboardingStep::ALL
+			.into_iter()
+			.map(|step| OnboardingProgress {
+				step,
+				completed_at: completed
+					.iter()
+					.find(|(completed_step, _)| *completed_step == step)
+					.map(|(_, completed_at)| *completed_at),
+			})
+			.collect()
+	}
+}
+
+/// A block of a [StarterTemplate], with the blocks under it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateBlock {
+	pub content: BlockContent,
+	pub children: Vec<TemplateBlock>,
+}
+
+impl TemplateBlock {
+	/// Create a template block without children.
+	pub fn new(content: BlockContent) -> Self {
+		Self {
+			content,
+			children: vec![],
+		}
+	}
+
+	/// Put blocks under the template block.
+	pub fn with_children(mut self, children: Vec<TemplateBlock>) -> Self {
+		self.children = children;
+		self
+	}
+}
+
+/// The content that new navigators start with, instead of an empty tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StarterTemplate {
+	/// The top-level blocks.
+	pub roots: Vec<TemplateBlock>,
+}
+
+impl StarterTemplate {
+	/// Make the blocks of the template for a navigator to own, parents before
+	/// their children, in order.
+	pub fn instantiate(&self, owner_id: NuttyId) -> Vec<ContentBlock> {
+		let mut blocks = vec![];
+		instantiate_children(&self.roots, None, owner_id, &mut blocks);
+		blocks
+	}
+}
+
+fn instantiate_children(
+	templates: &[TemplateBlock],
+	parent_id: Option<NuttyId>,
+	owner_id: NuttyId,
+	blocks: &mut Vec<ContentBlock>,
+) {
+	for (template, f_index) in templates
+		.iter()
+		.zip(FractionalIndex::spread(templates.len()))
+	{
+		let block =
+			ContentBlock::now_with_owner(parent_id, owner_id, f_index, template.content.clone());
+		let block_id = *block.nutty_id();
+
+		blocks.push(block);
+		instantiate_children(&template.children, Some(block_id), owner_id, blocks);
+	}
+}
+
+impl Default for StarterTemplate {
+	/// A welcome page that walks through the onboarding steps.
+	fn default() -> Self {
+		let paragraph = |markdown: &str| {
+			TemplateBlock::new(BlockContent::Paragraph {
+				markdown: markdown.to_string(),
+			})
+		};
+
+		let todo = |markdown: &str| {
+			TemplateBlock::new(BlockContent::Todo {
+				markdown: markdown.to_string(),
+				done: false,
+			})
+		};
+
+		let welcome = TemplateBlock::new(BlockContent::Page {
+			title: "Welcome to the Nuttyverse".to_string(),
+		})
+		.with_children(vec![
+			paragraph(
+				"Everything here is a block: pages, headings, paragraphs, and todos. \
+				 Blocks nest under one another, so a page is just a block with others under it.",
+			),
+			paragraph("Link to any block by writing its ID in double brackets."),
+			TemplateBlock::new(BlockContent::Heading {
+				markdown: "Getting started".to_string(),
+			})
+			.with_children(vec![
+				todo("Read this page"),
+				todo("Create a page of your own"),
+				todo("Share a block with a friend"),
+			]),
+		]);
+
+		Self {
+			roots: vec![welcome],
+		}
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum OnboardingError {
+	#[error("Unknown onboarding step: '{0}'")]
+	UnknownStep(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_instantiate() {
+		let owner_id = NuttyId::now();
+		let blocks = StarterTemplate::default().instantiate(owner_id);
+
+		// Assert: Every block is owned by the navigator, under one welcome page.
+		assert!(blocks.iter().all(|block| block.owner_id == Some(owner_id)));
+		assert!(matches!(blocks[0].content, BlockContent::Page { .. }));
+		assert_eq!(
+			blocks
+				.iter()
+				.filter(|block| block.parent_id.is_none())
+				.count(),
+			1
+		);
+
+		// Assert: Parents come before their children, which are in order.
+		for (i, block) in blocks.iter().enumerate() {
+			if let Some(parent_id) = block.parent_id {
+				assert!(
+					blocks[..i]
+						.iter()
+						.any(|parent| *parent.nutty_id() == parent_id)
+				);
+			}
+		}
+
+		let todos: Vec<_> = blocks
+			.iter()
+			.filter(|block| matches!(block.content, BlockContent::Todo { .. }))
+			.collect();
+
+		assert_eq!(todos.len(), OnboardingStep::ALL.len());
+		assert!(
+			todos
+				.windows(2)
+				.all(|pair| pair[0].f_index < pair[1].f_index)
+		);
+	}
+
+	#[test]
+	fn test_progress() {
+		// Assert: Steps round-trip through their names.
+		for step in OnboardingStep::ALL {
+			assert_eq!(OnboardingStep::parse(step.as_str()).unwrap(), step);
+		}
+
+		assert!(matches!(
+			OnboardingStep::parse("read_manual"),
+			Err(OnboardingError::UnknownStep(_))
+		));
+
+		// Assert: Every step is listed, with the completed ones' times.
+		let completed_at = DateTimeRfc3339::new(chrono::Utc::now().fixed_offset());
+		let progress = OnboardingProgress::list(&[(OnboardingStep::CreatePage, completed_at)]);
+
+		assert_eq!(progress.len(), OnboardingStep::ALL.len());
+		assert_eq!(progress[0].completed_at, None);
+		assert_eq!(progress[1].completed_at, Some(completed_at));
+	}
+}
@@ -0,0 +1,97 @@
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::models::BlockContent;
+use crate::models::NuttyId;
+
+/// A content block in the outline of a tree, without its content, for
+/// rendering a sidebar before any blocks are fetched.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutlineEntry {
+	pub block_id: NuttyId,
+	pub parent_id: Option<NuttyId>,
+
+	/// A page's title, or the first line of a block's markdown.
+	pub title: String,
+
+	/// How far below the outline's root the block is; the root is at 0.
+	pub depth: i32,
+
+	/// How many children the block has, including any past the outline's
+	/// limit per level.
+	pub child_count: i64,
+
+	/// Whether the block inherits access from its ancestors.
+	#[serde(skip)]
+	pub inherit_access: bool,
+}
+
+impl OutlineEntry {
+	/// The most characters of a block's text that are kept as its title.
+	pub const MAX_TITLE_CHARS: i32 = 100;
+
+	/// How many children of each block are outlined, unless asked otherwise.
+	pub const DEFAULT_LIMIT: i64 = 100;
+
+	/// The most children of each block that can be outlined.
+	pub const MAX_LIMIT: i64 = 500;
+
+	/// Get the title of some content, like the outline query does.
+	pub fn title_of(content: &BlockContent) -> String {
+		let text = match content {
+			BlockContent::Page { title } => title,
+			BlockContent::Heading { markdown }
+			| BlockContent::Paragraph { markdown }
+			| BlockContent::Todo { markdown, .. } => markdown,
+			BlockContent::Query { .. } | BlockContent::Custom { .. } => "",
+		};
+
+		let first_line = text.split('\n').next().unwrap_or_default();
+		first_line
+			.chars()
+			.take(Self::MAX_TITLE_CHARS as usize)
+			.collect()
+	}
+
+	/// Write the entry as a compact line of JSON: an array of the block's
+	/// NID, title, depth, and child count, without a newline.
+	pub fn to_json_line(&self) -> String {
+		json!([
+			self.block_id.nid(),
+			self.title,
+			self.depth,
+			self.child_count
+		])
+		.to_string()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_outline_entry() {
+		let paragraph = BlockContent::Paragraph {
+			markdown: format!("{}\nSecond line", "a".repeat(120)),
+		};
+
+		// Assert: Titles are the first line, cut short.
+		assert_eq!(OutlineEntry::title_of(&paragraph), "a".repeat(100));
+
+		let entry = OutlineEntry {
+			block_id: NuttyId::now(),
+			parent_id: None,
+			title: "Groceries".to_string(),
+			depth: 0,
+			child_count: 3,
+			inherit_access: true,
+		};
+
+		assert_eq!(
+			entry.to_json_line(),
+			format!(r#"["{}","Groceries",0,3]"#, entry.block_id.nid())
+		);
+	}
+}
@@ -0,0 +1,17 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::NuttyId;
+
+/// A change of a content block's owner.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OwnershipTransfer {
+	/// The Nutty ID of the transferred block.
+	pub block_id: NuttyId,
+
+	/// The owner before the transfer, if any.
+	pub previous_owner_id: Option<NuttyId>,
+
+	/// The owner after the transfer.
+	pub new_owner_id: NuttyId,
+}
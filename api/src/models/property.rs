@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::FromRow;
+use thiserror::Error;
+
+use crate::models::NuttyId;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+
+/// A content block's custom properties, keyed by property name.
+pub type BlockProperties = BTreeMap<String, Value>;
+
+/// The maximum length of a property name, in characters.
+pub const MAX_PROPERTY_NAME_LENGTH: usize = 64;
+
+/// The type of a property's values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PropertyKind {
+	/// Any string.
+	Text,
+
+	/// Any JSON number.
+	Number,
+
+	/// An ISO 8601 date string (YYYY-MM-DD).
+	Date,
+
+	/// One of the definition's options.
+	Select,
+}
+
+impl PropertyKind {
+	/// Get the stored representation of the kind.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			PropertyKind::Text => "text",
+			PropertyKind::Number => "number",
+			PropertyKind::Date => "date",
+			PropertyKind::Select => "select",
+		}
+	}
+}
+
+impl FromStr for PropertyKind {
+	type Err = PropertyError;
+
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		match value {
+			"text" => Ok(PropertyKind::Text),
+			"number" => Ok(PropertyKind::Number),
+			"date" => Ok(PropertyKind::Date),
+			"select" => Ok(PropertyKind::Select),
+			_ => Err(PropertyError::UnknownKind(value.to_string())),
+		}
+	}
+}
+
+impl TryFrom<String> for PropertyKind {
+	type Error = PropertyError;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		value.parse()
+	}
+}
+
+/// The definition of a custom property that content blocks may carry.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PropertyDefinition {
+	#[sqlx(rename = "id")]
+	nutty_id: NuttyId,
+	name: String,
+	#[sqlx(try_from = "String")]
+	kind: PropertyKind,
+
+	/// The allowed values of a select property.
+	#[sqlx(json)]
+	options: Vec<String>,
+
+	created_at: DateTimeRfc3339,
+	updated_at: DateTimeRfc3339,
+}
+
+impl PropertyDefinition {
+	/// Create a new property definition. Names are 1–64 lowercase
+	/// alphanumerics and underscores, and only select properties have options.
+	pub fn new(name: &str, kind: PropertyKind, options: Vec<String>) -> Result<Self, PropertyError> {
+		let is_valid_name = !name.is_empty()
+			&& name.chars().count() <= MAX_PROPERTY_NAME_LENGTH
+			&& name
+				.chars()
+				.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+		if !is_valid_name {
+			return Err(PropertyError::InvalidName(name.to_string()));
+		}
+
+		match kind {
+			PropertyKind::Select if options.is_empty() => {
+				return Err(PropertyError::InvalidOptions(name.to_string()));
+			}
+			PropertyKind::Text | PropertyKind::Number | PropertyKind::Date if !options.is_empty() => {
+				return Err(PropertyError::InvalidOptions(name.to_string()));
+			}
+			_ => {}
+		}
+
+		let now = DateTimeRfc3339::new(chrono::Utc::now().fixed_offset());
+
+		Ok(Self {
+			nutty_id: NuttyId::now(),
+			name: name.to_string(),
+			kind,
+			options,
+			created_at: now,
+			updated_at: now,
+		})
+	}
+
+	pub fn nutty_id(&self) -> &NuttyId {
+		&self.nutty_id
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn kind(&self) -> PropertyKind {
+		self.kind
+	}
+
+	pub fn options(&self) -> &[String] {
+		&self.options
+	}
+
+	/// Check that a value has the definition's type.
+	pub fn validate(&self, value: &Value) -> Result<(), PropertyError> {
+		let is_valid = match self.kind {
+			PropertyKind::Text => value.is_string(),
+			PropertyKind::Number => value.is_number(),
+			PropertyKind::Date => value
+				.as_str()
+				.is_some_and(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok()),
+			PropertyKind::Select => value
+				.as_str()
+				.is_some_and(|option| self.options.iter().any(|o| o == option)),
+		};
+
+		match is_valid {
+			true => Ok(()),
+			false => Err(PropertyError::InvalidValue(self.name.clone())),
+		}
+	}
+}
+
+/// Parse a property value written in a query. Numbers match numeric
+/// properties; anything else matches as a string.
+pub fn parse_property_value(value: &str) -> Value {
+	value
+		.parse::<serde_json::Number>()
+		.map(Value::Number)
+		.unwrap_or_else(|_| Value::String(value.to_string()))
+}
+
+/// Check that every property is defined and has its definition's type.
+pub fn validate_properties(
+	definitions: &[PropertyDefinition],
+	properties: &BlockProperties,
+) -> Result<(), PropertyError> {
+	for (name, value) in properties {
+		definitions
+			.iter()
+			.find(|definition| definition.name() == name)
+			.ok_or_else(|| PropertyError::UnknownProperty(name.clone()))?
+			.validate(value)?;
+	}
+
+	Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum PropertyError {
+	#[error(
+		"Property names must be 1–{MAX_PROPERTY_NAME_LENGTH} lowercase alphanumerics or underscores: {0}"
+	)]
+	InvalidName(String),
+
+	#[error("Only select properties have options, and they must have at least one: {0}")]
+	InvalidOptions(String),
+
+	#[error("Unknown property kind: {0}")]
+	UnknownKind(String),
+
+	#[error("Unknown property: {0}")]
+	UnknownProperty(String),
+
+	#[error("Invalid value for property: {0}")]
+	InvalidValue(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+
+	#[test]
+	fn test_new_property_definition() {
+		assert!(PropertyDefinition::new("due", PropertyKind::Date, vec![]).is_ok());
+
+		assert!(matches!(
+			PropertyDefinition::new("Due Date", PropertyKind::Date, vec![]),
+			Err(PropertyError::InvalidName(_))
+		));
+
+		assert!(matches!(
+			PropertyDefinition::new("status", PropertyKind::Select, vec![]),
+			Err(PropertyError::InvalidOptions(_))
+		));
+
+		assert!(matches!(
+			PropertyDefinition::new("notes", PropertyKind::Text, vec!["a".to_string()]),
+			Err(PropertyError::InvalidOptions(_))
+		));
+	}
+
+	#[test]
+	fn test_validate_properties() {
+		let definitions = vec![
+			PropertyDefinition::new("notes", PropertyKind::Text, vec![]).unwrap(),
+			PropertyDefinition::new("estimate", PropertyKind::Number, vec![]).unwrap(),
+			PropertyDefinition::new("due", PropertyKind::Date, vec![]).unwrap(),
+			PropertyDefinition::new(
+				"status",
+				PropertyKind::Select,
+				vec!["open".to_string(), "done".to_string()],
+			)
+			.unwrap(),
+		];
+
+		let properties = |value: Value| -> BlockProperties { serde_json::from_value(value).unwrap() };
+
+		// Valid values of every kind.
+		assert!(
+			validate_properties(
+				&definitions,
+				&properties(json!({
+					"notes": "Call back",
+					"estimate": 2.5,
+					"due": "2025-08-01",
+					"status": "open",
+				}))
+			)
+			.is_ok()
+		);
+
+		// Invalid values.
+		for invalid in [
+			json!({ "notes": 1 }),
+			json!({ "estimate": "2" }),
+			json!({ "due": "2025-02-30" }),
+			json!({ "status": "blocked" }),
+		] {
+			assert!(matches!(
+				validate_properties(&definitions, &properties(invalid)),
+				Err(PropertyError::InvalidValue(_))
+			));
+		}
+
+		// Undefined properties.
+		assert!(matches!(
+			validate_properties(&definitions, &properties(json!({ "color": "red" }))),
+			Err(PropertyError::UnknownProperty(_))
+		));
+	}
+}
@@ -0,0 +1,263 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::models::NuttyId;
+
+/// The upper bounds of the request latency histogram buckets, in
+/// milliseconds. Anything slower than the last bound lands in an overflow
+/// bucket. Stored histograms are aligned with these, so only append to them.
+pub const LATENCY_BUCKET_BOUNDS_MS: &[f64] = &[
+	5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0,
+];
+
+/// The longest window that request usage can be ranked over.
+pub const MAX_USAGE_WINDOW: chrono::Duration = chrono::Duration::days(90);
+
+/// The requests made by a navigator: how many, how long they took, as a
+/// latency histogram, and how many bytes went each way.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequestUsage {
+	pub request_count: i64,
+	pub request_bytes: i64,
+	pub response_bytes: i64,
+	pub total_ms: f64,
+	pub max_ms: f64,
+
+	/// Counts per bucket, aligned with [LATENCY_BUCKET_BOUNDS_MS] plus an
+	/// overflow bucket.
+	pub latency_buckets: Vec<i64>,
+}
+
+impl Default for RequestUsage {
+	fn default() -> Self {
+		Self {
+			request_count: 0,
+			request_bytes: 0,
+			response_bytes: 0,
+			total_ms: 0.0,
+			max_ms: 0.0,
+			latency_buckets: vec![0; LATENCY_BUCKET_BOUNDS_MS.len() + 1],
+		}
+	}
+}
+
+impl RequestUsage {
+	/// Record a request.
+	pub fn record(&mut self, elapsed: Duration, request_bytes: u64, response_bytes: u64) {
+		let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
+
+		let bucket = LATENCY_BUCKET_BOUNDS_MS
+			.iter()
+			.position(|bound| elapsed_ms <= *bound)
+			.unwrap_or(LATENCY_BUCKET_BOUNDS_MS.len());
+
+		self.request_count += 1;
+		self.request_bytes += i64::try_from(request_bytes).unwrap_or(i64::MAX);
+		self.response_bytes += i64::try_from(response_bytes).unwrap_or(i64::MAX);
+		self.total_ms += elapsed_ms;
+		self.max_ms = self.max_ms.max(elapsed_ms);
+		self.latency_buckets[bucket] += 1;
+	}
+
+	/// Add another's requests to these.
+	pub fn merge(&mut self, other: &RequestUsage) {
+		self.request_count += other.request_count;
+		self.request_bytes += other.request_bytes;
+		self.response_bytes += other.response_bytes;
+		self.total_ms += other.total_ms;
+		self.max_ms = self.max_ms.max(other.max_ms);
+
+		if self.latency_buckets.len() < other.latency_buckets.len() {
+			self.latency_buckets.resize(other.latency_buckets.len(), 0);
+		}
+
+		for (count, other_count) in self.latency_buckets.iter_mut().zip(&other.latency_buckets) {
+			*count += other_count;
+		}
+	}
+
+	/// Estimate a latency percentile by nearest rank, as the upper bound of
+	/// the bucket it falls in, but no more than the slowest request.
+	pub fn percentile_ms(&self, percentile: u8) -> f64 {
+		if self.request_count <= 0 {
+			return 0.0;
+		}
+
+		let rank = ((i64::from(percentile) * self.request_count + 99) / 100).max(1);
+
+		let mut seen = 0;
+
+		for (bucket, count) in self.latency_buckets.iter().enumerate() {
+			seen += count;
+
+			if seen >= rank {
+				return LATENCY_BUCKET_BOUNDS_MS
+					.get(bucket)
+					.map_or(self.max_ms, |bound| bound.min(self.max_ms));
+			}
+		}
+
+		self.max_ms
+	}
+}
+
+/// A navigator's requests over a window, ranked against others'.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NavigatorUsage {
+	pub navigator_id: NuttyId,
+	pub name: String,
+	pub request_count: i64,
+	pub p95_ms: f64,
+	pub mean_ms: f64,
+	pub max_ms: f64,
+	pub request_bytes: i64,
+	pub response_bytes: i64,
+}
+
+impl NavigatorUsage {
+	/// Summarize a navigator's requests.
+	pub fn new(navigator_id: NuttyId, name: String, usage: &RequestUsage) -> Self {
+		Self {
+			navigator_id,
+			name,
+			request_count: usage.request_count,
+			p95_ms: usage.percentile_ms(95),
+			mean_ms: match usage.request_count {
+				0 => 0.0,
+				count => usage.total_ms / count as f64,
+			},
+			max_ms: usage.max_ms,
+			request_bytes: usage.request_bytes,
+			response_bytes: usage.response_bytes,
+		}
+	}
+
+	/// Rank navigators' usage, heaviest first, keeping the top `limit`.
+	pub fn rank(mut usage: Vec<Self>, sort: UsageSort, limit: usize) -> Vec<Self> {
+		usage.sort_by(|a, b| match sort {
+			UsageSort::Requests => b.request_count.cmp(&a.request_count),
+			UsageSort::Latency => b.p95_ms.total_cmp(&a.p95_ms),
+			UsageSort::Bytes => {
+				(b.request_bytes + b.response_bytes).cmp(&(a.request_bytes + a.response_bytes))
+			}
+		});
+
+		usage.truncate(limit);
+		usage
+	}
+}
+
+/// What to rank navigators' usage by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageSort {
+	/// The number of requests made.
+	#[default]
+	Requests,
+
+	/// The 95th percentile latency.
+	#[serde(rename = "p95")]
+	Latency,
+
+	/// The bytes sent and received.
+	Bytes,
+}
+
+/// Parse a usage window, written as a number of minutes, hours, or days,
+/// e.g. `30m`, `6h`, or `7d`.
+pub fn parse_window(window: &str) -> Result<chrono::Duration, RequestUsageError> {
+	let invalid = || RequestUsageError::InvalidWindow(window.to_string());
+
+	let (unit_index, _) = window.char_indices().last().ok_or_else(invalid)?;
+	let (count, unit) = window.split_at(unit_index);
+	let count: i64 = count.parse().map_err(|_| invalid())?;
+
+	let duration = match unit {
+		"m" => chrono::Duration::try_minutes(count),
+		"h" => chrono::Duration::try_hours(count),
+		"d" => chrono::Duration::try_days(count),
+		_ => None,
+	}
+	.ok_or_else(invalid)?;
+
+	if duration <= chrono::Duration::zero() || duration > MAX_USAGE_WINDOW {
+		return Err(invalid());
+	}
+
+	Ok(duration)
+}
+
+#[derive(Debug, Error)]
+pub enum RequestUsageError {
+	#[error("Invalid window: '{0}' (expected e.g. '30m', '6h', or '7d', up to 90 days)")]
+	InvalidWindow(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_request_usage() {
+		let mut usage = RequestUsage::default();
+
+		// Act: Record 95 fast requests and 5 slow ones.
+		for _ in 0..95 {
+			usage.record(Duration::from_millis(3), 100, 1000);
+		}
+
+		for _ in 0..5 {
+			usage.record(Duration::from_millis(700), 100, 1000);
+		}
+
+		// Assert: The 95th percentile is still fast; the 96th isn't.
+		assert_eq!(usage.request_count, 100);
+		assert_eq!(usage.percentile_ms(95), 5.0);
+		assert_eq!(usage.percentile_ms(96), 700.0);
+		assert_eq!(usage.response_bytes, 100_000);
+
+		// Assert: Percentiles don't exceed the slowest request.
+		let mut slow = RequestUsage::default();
+		slow.record(Duration::from_millis(30), 0, 0);
+		slow.record(Duration::from_secs(20), 0, 0);
+
+		assert_eq!(slow.percentile_ms(50), 50.0);
+		assert_eq!(slow.percentile_ms(95), 20_000.0);
+
+		// Assert: Merged usage adds up.
+		usage.merge(&slow);
+		assert_eq!(usage.request_count, 102);
+		assert_eq!(usage.max_ms, 20_000.0);
+		assert_eq!(usage.latency_buckets.iter().sum::<i64>(), 102);
+
+		// Assert: Navigators rank by what's asked for.
+		let quiet = NavigatorUsage::new(NuttyId::now(), "quiet".to_string(), &slow);
+		let busy = NavigatorUsage::new(NuttyId::now(), "busy".to_string(), &usage);
+
+		let ranked = NavigatorUsage::rank(vec![quiet.clone(), busy.clone()], UsageSort::Requests, 1);
+		assert_eq!(ranked, vec![busy.clone()]);
+
+		let ranked = NavigatorUsage::rank(vec![busy, quiet.clone()], UsageSort::Latency, 1);
+		assert_eq!(ranked, vec![quiet]);
+	}
+
+	#[test]
+	fn test_parse_window() {
+		assert_eq!(parse_window("30m").unwrap(), chrono::Duration::minutes(30));
+		assert_eq!(parse_window("6h").unwrap(), chrono::Duration::hours(6));
+		assert_eq!(parse_window("7d").unwrap(), chrono::Duration::days(7));
+
+		for window in ["", "h", "0h", "-1d", "1w", "91d", "１h", "1時"] {
+			assert!(
+				matches!(
+					parse_window(window),
+					Err(RequestUsageError::InvalidWindow(_))
+				),
+				"{window}"
+			);
+		}
+	}
+}
@@ -0,0 +1,133 @@
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+/// The language that a content block is searched in, by its language tag.
+/// A block without a language of its own is searched as
+/// [SearchLanguage::English].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchLanguage {
+	/// Full-text search, with English stemming and stop words.
+	#[default]
+	English,
+
+	/// Substring search, since Japanese isn't separated by whitespace.
+	Japanese,
+
+	/// Full-text search, without stemming or stop words.
+	Simple,
+}
+
+impl SearchLanguage {
+	/// Every search language.
+	pub const ALL: [SearchLanguage; 3] = [
+		SearchLanguage::English,
+		SearchLanguage::Japanese,
+		SearchLanguage::Simple,
+	];
+
+	/// Get the name of the language, as it's given in requests.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			SearchLanguage::English => "english",
+			SearchLanguage::Japanese => "japanese",
+			SearchLanguage::Simple => "simple",
+		}
+	}
+
+	/// Get the language tag that a block searched in the language is stored
+	/// with. Simple search isn't of any language in particular.
+	pub fn tag(&self) -> &'static str {
+		match self {
+			SearchLanguage::English => "en",
+			SearchLanguage::Japanese => "ja",
+			SearchLanguage::Simple => "und",
+		}
+	}
+
+	/// Get the Postgres text search configuration for the language, or
+	/// `None` if it's searched by substring.
+	pub fn text_search_config(&self) -> Option<&'static str> {
+		match self {
+			SearchLanguage::English => Some("english"),
+			SearchLanguage::Japanese => None,
+			SearchLanguage::Simple => Some("simple"),
+		}
+	}
+}
+
+impl FromStr for SearchLanguage {
+	type Err = SearchLanguageError;
+
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		match value {
+			"english" => Ok(SearchLanguage::English),
+			"japanese" => Ok(SearchLanguage::Japanese),
+			"simple" => Ok(SearchLanguage::Simple),
+			_ => Err(SearchLanguageError::UnknownLanguage(value.to_string())),
+		}
+	}
+}
+
+impl TryFrom<String> for SearchLanguage {
+	type Error = SearchLanguageError;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		value.parse()
+	}
+}
+
+/// The result of setting the search language of a block and its descendants.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageUpdate {
+	/// The language set, or `None` if it was cleared.
+	pub language: Option<SearchLanguage>,
+
+	/// The number of blocks updated.
+	pub updated_count: u64,
+}
+
+/// Build an `ILIKE` pattern that matches a query anywhere in the text.
+pub fn substring_pattern(query: &str) -> String {
+	let mut pattern = String::from("%");
+
+	for c in query.chars() {
+		if matches!(c, '\\' | '%' | '_') {
+			pattern.push('\\');
+		}
+
+		pattern.push(c);
+	}
+
+	pattern.push('%');
+	pattern
+}
+
+#[derive(Debug, Error)]
+pub enum SearchLanguageError {
+	#[error("Unknown search language: {0}")]
+	UnknownLanguage(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_search_language() {
+		for language in SearchLanguage::ALL {
+			assert_eq!(
+				language.as_str().parse::<SearchLanguage>().unwrap(),
+				language
+			);
+		}
+
+		assert!("klingon".parse::<SearchLanguage>().is_err());
+		assert_eq!(SearchLanguage::Japanese.tag(), "ja");
+		assert_eq!(SearchLanguage::Japanese.text_search_config(), None);
+		assert_eq!(substring_pattern("100%_ 完了"), r"%100\%\_ 完了%");
+	}
+}
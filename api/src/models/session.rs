@@ -1,12 +1,19 @@
+use std::fmt;
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::rand_core::RngCore;
 use chrono::Local;
 use chrono::TimeZone;
 use serde::Deserialize;
 use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
 use sqlx::FromRow;
 use thiserror::Error;
 
 use crate::models::NuttyId;
 use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+use crate::models::device::DeviceInfo;
 
 /// Represents an active [Navigator] login session.
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -17,6 +24,16 @@ pub struct Session {
 	navigator_id: NuttyId,
 	#[serde(skip_serializing)]
 	user_agent: String,
+	#[serde(skip)]
+	token_hash: String,
+	#[serde(skip)]
+	#[sqlx(skip)]
+	token: Option<SessionToken>,
+	#[serde(flatten)]
+	#[sqlx(flatten)]
+	device: DeviceInfo,
+	label: Option<String>,
+	country: Option<String>,
 	expires_at: DateTimeRfc3339,
 	created_at: DateTimeRfc3339,
 	updated_at: DateTimeRfc3339,
@@ -40,11 +57,18 @@ impl Session {
 			.into();
 
 		let expires_at = (*now.inner() + duration).into();
+		let token = SessionToken::generate();
+		let device = DeviceInfo::parse(&user_agent);
 
 		Ok(Self {
 			nutty_id,
 			navigator_id,
 			user_agent,
+			token_hash: token.hash(),
+			token: Some(token),
+			device,
+			label: None,
+			country: None,
 			expires_at,
 			created_at: now,
 			updated_at: now,
@@ -83,6 +107,55 @@ impl Session {
 		&self.user_agent
 	}
 
+	/// Get the SHA-256 hash of the session token.
+	pub fn token_hash(&self) -> &str {
+		&self.token_hash
+	}
+
+	/// Get the plaintext session token.
+	/// Only available on freshly created sessions, since it is never stored.
+	pub fn token(&self) -> Option<&SessionToken> {
+		self.token.as_ref()
+	}
+
+	/// Attach the plaintext session token.
+	pub fn with_token(mut self, token: Option<SessionToken>) -> Self {
+		self.token = token;
+		self
+	}
+
+	/// Set the country the session was created from (ISO 3166-1 alpha-2).
+	pub fn with_country(mut self, country: Option<String>) -> Self {
+		self.country = country;
+		self
+	}
+
+	/// Set the navigator-provided label.
+	pub fn with_label(mut self, label: Option<String>) -> Self {
+		self.label = label;
+		self
+	}
+
+	/// Get the device info parsed from the user agent.
+	pub fn device(&self) -> &DeviceInfo {
+		&self.device
+	}
+
+	/// Get the navigator-provided label (e.g., "work laptop").
+	pub fn label(&self) -> Option<&str> {
+		self.label.as_deref()
+	}
+
+	/// Get the country the session was created from.
+	pub fn country(&self) -> Option<&str> {
+		self.country.as_deref()
+	}
+
+	/// Check if a token belongs to this session, in constant time.
+	pub fn matches_token(&self, token: &SessionToken) -> bool {
+		constant_time_eq(self.token_hash.as_bytes(), token.hash().as_bytes())
+	}
+
 	/// Get the expiration time.
 	pub fn expires_at(&self) -> &DateTimeRfc3339 {
 		&self.expires_at
@@ -99,6 +172,67 @@ impl Session {
 	}
 }
 
+/// An opaque, high-entropy bearer token for a session.
+/// Only its SHA-256 hash is stored.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SessionToken(String);
+
+impl SessionToken {
+	/// The number of random bytes in a token.
+	const BYTES: usize = 32;
+
+	/// Generate a new random token.
+	pub fn generate() -> Self {
+		let mut bytes = [0u8; Self::BYTES];
+		OsRng.fill_bytes(&mut bytes);
+		Self(hex(&bytes))
+	}
+
+	/// Parse a token from its hex encoding (e.g., a cookie value).
+	pub fn parse(value: &str) -> Result<Self, SessionError> {
+		let is_valid = value.len() == Self::BYTES * 2
+			&& value
+				.bytes()
+				.all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+
+		if !is_valid {
+			return Err(SessionError::InvalidCookie);
+		}
+
+		Ok(Self(value.to_string()))
+	}
+
+	/// Get the hex encoding of the token.
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+
+	/// Hash the token for storage and lookup.
+	pub fn hash(&self) -> String {
+		hex(&Sha256::digest(self.0.as_bytes()))
+	}
+}
+
+impl fmt::Debug for SessionToken {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("SessionToken([REDACTED])")
+	}
+}
+
+/// Encode bytes as lowercase hex.
+fn hex(bytes: &[u8]) -> String {
+	bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Compare two byte strings without short-circuiting on the first mismatch.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+	if a.len() != b.len() {
+		return false;
+	}
+
+	a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
 #[derive(Debug, Error)]
 pub enum SessionError {
 	#[error("Invalid timestamp from Nutty ID: {timestamp}")]
@@ -110,6 +244,9 @@ pub enum SessionError {
 	#[error("Session expired")]
 	SessionExpired,
 
+	#[error("Session evicted by a newer login")]
+	SessionEvicted,
+
 	#[error("Missing cookie")]
 	MissingCookie,
 
@@ -126,6 +263,9 @@ pub struct SessionBuilder {
 	nutty_id: Option<NuttyId>,
 	navigator_id: Option<NuttyId>,
 	user_agent: Option<String>,
+	token_hash: Option<String>,
+	label: Option<String>,
+	country: Option<String>,
 	expires_at: Option<DateTimeRfc3339>,
 	created_at: Option<DateTimeRfc3339>,
 	updated_at: Option<DateTimeRfc3339>,
@@ -150,6 +290,24 @@ impl SessionBuilder {
 		self
 	}
 
+	/// Set the SHA-256 hash of the session token.
+	pub fn token_hash(mut self, token_hash: String) -> Self {
+		self.token_hash = Some(token_hash);
+		self
+	}
+
+	/// Set the label.
+	pub fn label(mut self, label: String) -> Self {
+		self.label = Some(label);
+		self
+	}
+
+	/// Set the country.
+	pub fn country(mut self, country: String) -> Self {
+		self.country = Some(country);
+		self
+	}
+
 	/// Set the expiration time.
 	pub fn expires_at(mut self, expires_at: DateTimeRfc3339) -> Self {
 		self.expires_at = Some(expires_at);
@@ -180,6 +338,10 @@ impl SessionBuilder {
 			.user_agent
 			.ok_or(SessionBuilderError::MissingUserAgent)?;
 
+		let token_hash = self
+			.token_hash
+			.ok_or(SessionBuilderError::MissingTokenHash)?;
+
 		let expires_at = self
 			.expires_at
 			.ok_or(SessionBuilderError::MissingExpiresAt)?;
@@ -199,7 +361,12 @@ impl SessionBuilder {
 		Ok(Session {
 			nutty_id,
 			navigator_id,
+			device: DeviceInfo::parse(&user_agent),
 			user_agent,
+			token_hash,
+			token: None,
+			label: self.label,
+			country: self.country,
 			expires_at,
 			created_at,
 			updated_at,
@@ -218,6 +385,9 @@ pub enum SessionBuilderError {
 	#[error("User agent is required")]
 	MissingUserAgent,
 
+	#[error("Token hash is required")]
+	MissingTokenHash,
+
 	#[error("Expiration time is required")]
 	MissingExpiresAt,
 
@@ -270,6 +440,7 @@ mod tests {
 		let nutty_id = NuttyId::now();
 		let navigator_id = NuttyId::now();
 		let user_agent = "test-agent".to_string();
+		let token_hash = SessionToken::generate().hash();
 		let timestamp = nutty_id.timestamp() as i64;
 
 		let now = Local
@@ -284,6 +455,7 @@ mod tests {
 			.nutty_id(nutty_id)
 			.navigator_id(navigator_id)
 			.user_agent(user_agent)
+			.token_hash(token_hash.clone())
 			.expires_at(expires_at)
 			.created_at(now.into())
 			.updated_at(now.into())
@@ -298,6 +470,7 @@ mod tests {
 		let nutty_id = NuttyId::now();
 		let navigator_id = NuttyId::now();
 		let user_agent = "test-agent".to_string();
+		let token_hash = SessionToken::generate().hash();
 		let timestamp = nutty_id.timestamp() as i64;
 
 		let now = Local
@@ -312,6 +485,7 @@ mod tests {
 		let result = Session::builder()
 			.navigator_id(navigator_id)
 			.user_agent(user_agent.clone())
+			.token_hash(token_hash.clone())
 			.expires_at(expires_at)
 			.created_at(now.into())
 			.updated_at(now.into())
@@ -323,6 +497,7 @@ mod tests {
 		let result = Session::builder()
 			.nutty_id(nutty_id)
 			.user_agent(user_agent.clone())
+			.token_hash(token_hash.clone())
 			.expires_at(expires_at)
 			.created_at(now.into())
 			.updated_at(now.into())
@@ -344,11 +519,24 @@ mod tests {
 
 		assert!(matches!(result, Err(SessionBuilderError::MissingUserAgent)));
 
+		// Test missing token_hash.
+		let result = Session::builder()
+			.nutty_id(nutty_id)
+			.navigator_id(navigator_id)
+			.user_agent(user_agent.clone())
+			.expires_at(expires_at)
+			.created_at(now.into())
+			.updated_at(now.into())
+			.try_build();
+
+		assert!(matches!(result, Err(SessionBuilderError::MissingTokenHash)));
+
 		// Test missing expires_at.
 		let result = Session::builder()
 			.nutty_id(nutty_id)
 			.navigator_id(navigator_id)
 			.user_agent(user_agent.clone())
+			.token_hash(token_hash.clone())
 			.created_at(now.into())
 			.updated_at(now.into())
 			.try_build();
@@ -360,6 +548,7 @@ mod tests {
 			.nutty_id(nutty_id)
 			.navigator_id(navigator_id)
 			.user_agent(user_agent.clone())
+			.token_hash(token_hash.clone())
 			.expires_at(expires_at)
 			.updated_at(now.into())
 			.try_build();
@@ -371,6 +560,7 @@ mod tests {
 			.nutty_id(nutty_id)
 			.navigator_id(navigator_id)
 			.user_agent(user_agent)
+			.token_hash(token_hash.clone())
 			.expires_at(expires_at)
 			.created_at(now.into())
 			.try_build();
@@ -383,6 +573,7 @@ mod tests {
 		let nutty_id = NuttyId::now();
 		let navigator_id = NuttyId::now();
 		let user_agent = "test-agent".to_string();
+		let token_hash = SessionToken::generate().hash();
 		let timestamp = nutty_id.timestamp() as i64;
 
 		let now = Local
@@ -398,6 +589,7 @@ mod tests {
 			.nutty_id(nutty_id)
 			.navigator_id(navigator_id)
 			.user_agent(user_agent)
+			.token_hash(token_hash.clone())
 			.expires_at(expires_at)
 			.created_at(now.into())
 			.updated_at(earlier)
@@ -405,4 +597,39 @@ mod tests {
 
 		assert!(matches!(result, Err(SessionBuilderError::InvalidUpdatedAt)));
 	}
+
+	#[test]
+	fn test_session_token() {
+		// Arrange: Create a new session.
+		let session = Session::new(
+			NuttyId::now(),
+			"test-agent".to_string(),
+			chrono::Duration::days(1),
+		)
+		.unwrap();
+
+		let token = session.token().unwrap().clone();
+
+		// Assert: Only the hash is kept for storage.
+		assert_eq!(token.as_str().len(), 64);
+		assert_ne!(session.token_hash(), token.as_str());
+		assert_eq!(session.token_hash(), token.hash());
+
+		// Assert: The token round-trips through a cookie value.
+		let parsed = SessionToken::parse(token.as_str()).unwrap();
+		assert!(session.matches_token(&parsed));
+
+		// Assert: Other tokens don't match.
+		assert!(!session.matches_token(&SessionToken::generate()));
+		assert!(SessionToken::parse("not-a-token").is_err());
+		assert!(SessionToken::parse(&NuttyId::now().to_string()).is_err());
+
+		// Assert: The token never leaks through Debug or serialization.
+		assert!(!format!("{session:?}").contains(token.as_str()));
+		assert!(
+			!serde_json::to_string(&session)
+				.unwrap()
+				.contains(token.as_str())
+		);
+	}
 }
@@ -0,0 +1,100 @@
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde::Serialize;
+use sqlx::Decode;
+use sqlx::Encode;
+use sqlx::Postgres;
+use sqlx::Type;
+use sqlx::postgres::PgTypeInfo;
+use sqlx::postgres::PgValueRef;
+use thiserror::Error;
+
+/// How much access a content block is shared with, as granted to another
+/// navigator. Each level includes the ones before it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShareLevel {
+	#[default]
+	View,
+	Comment,
+	Edit,
+}
+
+impl ShareLevel {
+	/// Every share level, from least to most access.
+	pub const ALL: [ShareLevel; 3] = [ShareLevel::View, ShareLevel::Comment, ShareLevel::Edit];
+
+	/// Get the name of the resource role granted for the level.
+	pub fn role_name(&self) -> &'static str {
+		match self {
+			ShareLevel::View => "block_viewer",
+			ShareLevel::Comment => "block_commenter",
+			ShareLevel::Edit => "block_editor",
+		}
+	}
+
+	/// Get the stored representation of the level.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ShareLevel::View => "view",
+			ShareLevel::Comment => "comment",
+			ShareLevel::Edit => "edit",
+		}
+	}
+}
+
+impl FromStr for ShareLevel {
+	type Err = ShareLevelError;
+
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		match value {
+			"view" => Ok(ShareLevel::View),
+			"comment" => Ok(ShareLevel::Comment),
+			"edit" => Ok(ShareLevel::Edit),
+			_ => Err(ShareLevelError::UnknownLevel(value.to_string())),
+		}
+	}
+}
+
+impl Type<Postgres> for ShareLevel {
+	fn type_info() -> PgTypeInfo {
+		PgTypeInfo::with_name("VARCHAR")
+	}
+
+	fn compatible(ty: &PgTypeInfo) -> bool {
+		<&str as Type<Postgres>>::compatible(ty)
+	}
+}
+
+impl Encode<'_, Postgres> for ShareLevel {
+	fn encode_by_ref(
+		&self,
+		buf: &mut <Postgres as sqlx::Database>::ArgumentBuffer<'_>,
+	) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+		<&str as Encode<Postgres>>::encode(self.as_str(), buf)
+	}
+}
+
+impl<'r> Decode<'r, Postgres> for ShareLevel {
+	fn decode(value: PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+		let value = <&str as Decode<Postgres>>::decode(value)?;
+		Ok(value.parse()?)
+	}
+}
+
+/// Something a navigator can do with a content block, whether through a
+/// share level, ownership, or a global role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlockCapability {
+	Read,
+	Comment,
+	Edit,
+}
+
+#[derive(Debug, Error)]
+pub enum ShareLevelError {
+	#[error("Unknown share level: {0}")]
+	UnknownLevel(String),
+}
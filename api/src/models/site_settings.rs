@@ -0,0 +1,191 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::models::NuttyId;
+
+/// The longest a site title may be, in characters.
+pub const MAX_TITLE_LENGTH: usize = 200;
+
+/// The longest a site description may be, in characters.
+pub const MAX_DESCRIPTION_LENGTH: usize = 2000;
+
+/// The most pages a site's navigation may link to.
+pub const MAX_NAVIGATION_ROOTS: usize = 20;
+
+/// How a navigator's published site presents itself, for the public
+/// renderers of their pages.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SiteSettings {
+	pub title: String,
+
+	#[serde(default)]
+	pub description: String,
+
+	#[serde(default)]
+	pub accent_color: Option<AccentColor>,
+
+	/// The pages linked from every page of the site, in order.
+	#[serde(default)]
+	pub navigation_root_ids: Vec<NuttyId>,
+}
+
+impl SiteSettings {
+	/// Check that the settings are within limits.
+	pub fn validate(&self) -> Result<(), SiteSettingsError> {
+		if self.title.trim().is_empty() {
+			return Err(SiteSettingsError::EmptyTitle);
+		}
+
+		if self.title.chars().count() > MAX_TITLE_LENGTH {
+			return Err(SiteSettingsError::TitleTooLong(MAX_TITLE_LENGTH));
+		}
+
+		if self.description.chars().count() > MAX_DESCRIPTION_LENGTH {
+			return Err(SiteSettingsError::DescriptionTooLong(
+				MAX_DESCRIPTION_LENGTH,
+			));
+		}
+
+		if self.navigation_root_ids.len() > MAX_NAVIGATION_ROOTS {
+			return Err(SiteSettingsError::TooManyNavigationRoots(
+				MAX_NAVIGATION_ROOTS,
+			));
+		}
+
+		for (i, root_id) in self.navigation_root_ids.iter().enumerate() {
+			if self.navigation_root_ids[..i].contains(root_id) {
+				return Err(SiteSettingsError::DuplicateNavigationRoot(root_id.nid()));
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// A site's accent color, as a hex color like "#ff8800".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct AccentColor(String);
+
+impl AccentColor {
+	/// Parse a hex color, in either case. It's stored in lowercase.
+	pub fn parse(value: &str) -> Result<Self, SiteSettingsError> {
+		let valid = value.len() == 7
+			&& value.starts_with('#')
+			&& value[1..].chars().all(|c| c.is_ascii_hexdigit());
+
+		match valid {
+			true => Ok(Self(value.to_ascii_lowercase())),
+			false => Err(SiteSettingsError::InvalidAccentColor(value.to_string())),
+		}
+	}
+
+	/// Get the hex color.
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+}
+
+impl Display for AccentColor {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.0)
+	}
+}
+
+impl TryFrom<String> for AccentColor {
+	type Error = SiteSettingsError;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		Self::parse(&value)
+	}
+}
+
+impl From<AccentColor> for String {
+	fn from(color: AccentColor) -> Self {
+		color.0
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum SiteSettingsError {
+	#[error("A site needs a title")]
+	EmptyTitle,
+
+	#[error("A site title can't be longer than {0} characters")]
+	TitleTooLong(usize),
+
+	#[error("A site description can't be longer than {0} characters")]
+	DescriptionTooLong(usize),
+
+	#[error("A site can't link to more than {0} pages in its navigation")]
+	TooManyNavigationRoots(usize),
+
+	#[error("Navigation links to {0} more than once")]
+	DuplicateNavigationRoot(String),
+
+	#[error("Invalid accent color '{0}': use a hex color like #ff8800")]
+	InvalidAccentColor(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn settings() -> SiteSettings {
+		SiteSettings {
+			title: "Nuttyverse".to_string(),
+			description: String::new(),
+			accent_color: None,
+			navigation_root_ids: vec![],
+		}
+	}
+
+	#[test]
+	fn test_validate() {
+		// Assert: A title is all that's needed.
+		assert!(settings().validate().is_ok());
+
+		let untitled = SiteSettings {
+			title: " ".to_string(),
+			..settings()
+		};
+
+		assert!(matches!(
+			untitled.validate(),
+			Err(SiteSettingsError::EmptyTitle)
+		));
+
+		// Assert: Navigation links to each page at most once.
+		let page_id = NuttyId::now();
+
+		let navigation = |navigation_root_ids| SiteSettings {
+			navigation_root_ids,
+			..settings()
+		};
+
+		assert!(navigation(vec![page_id, NuttyId::now()]).validate().is_ok());
+		assert!(matches!(
+			navigation(vec![page_id, page_id]).validate(),
+			Err(SiteSettingsError::DuplicateNavigationRoot(_))
+		));
+
+		let roots = (0..=MAX_NAVIGATION_ROOTS).map(|_| NuttyId::now()).collect();
+		assert!(matches!(
+			navigation(roots).validate(),
+			Err(SiteSettingsError::TooManyNavigationRoots(_))
+		));
+	}
+
+	#[test]
+	fn test_accent_color() {
+		assert_eq!(AccentColor::parse("#FF8800").unwrap().as_str(), "#ff8800");
+
+		for value in ["ff8800", "#f80", "#ff88001", "#gg8800"] {
+			assert!(AccentColor::parse(value).is_err(), "{value:?}");
+		}
+	}
+}
@@ -0,0 +1,153 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::models::DissociatedNuttyId;
+use crate::models::NuttyId;
+
+/// The longest a slug may be, in characters.
+pub const MAX_SLUG_LENGTH: usize = 200;
+
+/// A custom path that a navigator claims for a content block, as an alias
+/// of its permalink: https://nuttyver.se/notes/rust-tips.
+///
+/// Slugs are slash-separated segments of lowercase letters and digits,
+/// joined by single hyphens. A slug can't look like a NID, so that
+/// permalinks keep resolving to the blocks they were made for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Slug(String);
+
+impl Slug {
+	/// Parse a slug, ignoring leading and trailing slashes.
+	pub fn parse(path: &str) -> Result<Self, SlugError> {
+		let path = path.trim_matches('/');
+
+		if path.is_empty() {
+			return Err(SlugError::Empty);
+		}
+
+		if path.len() > MAX_SLUG_LENGTH {
+			return Err(SlugError::TooLong(MAX_SLUG_LENGTH));
+		}
+
+		for segment in path.split('/') {
+			let valid = !segment.is_empty()
+				&& segment.split('-').all(|word| {
+					!word.is_empty()
+						&& word
+							.chars()
+							.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+				});
+
+			if !valid {
+				return Err(SlugError::InvalidSegment(segment.to_string()));
+			}
+		}
+
+		if DissociatedNuttyId::new(path).is_ok() {
+			return Err(SlugError::NuttyId(path.to_string()));
+		}
+
+		Ok(Self(path.to_string()))
+	}
+
+	/// Get the slug as a path, without slashes around it.
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+}
+
+impl Display for Slug {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.0)
+	}
+}
+
+impl FromStr for Slug {
+	type Err = SlugError;
+
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		Self::parse(value)
+	}
+}
+
+impl TryFrom<String> for Slug {
+	type Error = SlugError;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		Self::parse(&value)
+	}
+}
+
+impl From<Slug> for String {
+	fn from(slug: Slug) -> Self {
+		slug.0
+	}
+}
+
+/// The content block that a slug leads to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlugTarget {
+	pub block_id: NuttyId,
+
+	/// The block's current slug, if the one resolved was replaced by it.
+	pub moved_to: Option<Slug>,
+}
+
+#[derive(Debug, Error)]
+pub enum SlugError {
+	#[error("A slug can't be empty")]
+	Empty,
+
+	#[error("A slug can't be longer than {0} characters")]
+	TooLong(usize),
+
+	#[error("Invalid slug segment '{0}': use lowercase letters and digits, joined by hyphens")]
+	InvalidSegment(String),
+
+	#[error("A slug can't look like a Nutty ID: '{0}'")]
+	NuttyId(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse() {
+		// Assert: Paths of hyphenated words are slugs.
+		for path in ["rust-tips", "notes/rust-tips", "/2025/week-32/", "a"] {
+			assert!(Slug::parse(path).is_ok(), "{path:?}");
+		}
+
+		assert_eq!(Slug::parse("/notes/").unwrap().as_str(), "notes");
+
+		// Assert: Anything else isn't.
+		assert!(matches!(Slug::parse("/"), Err(SlugError::Empty)));
+
+		for path in [
+			"Rust",
+			"rust--tips",
+			"-rust",
+			"notes//tips",
+			"notes/tips?",
+			"café",
+		] {
+			assert!(
+				matches!(Slug::parse(path), Err(SlugError::InvalidSegment(_))),
+				"{path:?}"
+			);
+		}
+
+		let long = "a".repeat(MAX_SLUG_LENGTH + 1);
+		assert!(matches!(Slug::parse(&long), Err(SlugError::TooLong(_))));
+
+		// Assert: Slugs can't shadow permalinks.
+		assert!(matches!(Slug::parse("abcdefg"), Err(SlugError::NuttyId(_))));
+	}
+}
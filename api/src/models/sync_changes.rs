@@ -0,0 +1,185 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use chrono::DateTime;
+use chrono::FixedOffset;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::ContentBlock;
+use crate::models::ContentLink;
+use crate::models::NuttyId;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+
+/// What changed: a content block or a link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+	Block,
+	Link,
+}
+
+impl ChangeKind {
+	/// Get the kind as it's written in queries.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ChangeKind::Block => "block",
+			ChangeKind::Link => "link",
+		}
+	}
+
+	/// Parse a kind as it's written in queries.
+	pub fn parse(kind: &str) -> Option<Self> {
+		match kind {
+			"block" => Some(ChangeKind::Block),
+			"link" => Some(ChangeKind::Link),
+			_ => None,
+		}
+	}
+}
+
+/// A change to a navigator's content, in the order that changes are pulled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+	pub kind: ChangeKind,
+	pub id: NuttyId,
+	pub changed_at: DateTimeRfc3339,
+
+	/// Whether the block or link was deleted, rather than saved.
+	pub deleted: bool,
+}
+
+impl Change {
+	/// Get the position just after this change.
+	pub fn cursor(&self) -> SyncCursor {
+		SyncCursor {
+			changed_at: *self.changed_at.inner(),
+			id: *self.id.uuid(),
+		}
+	}
+}
+
+/// A deleted block or link.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tombstone {
+	pub kind: ChangeKind,
+	pub id: NuttyId,
+	pub deleted_at: DateTimeRfc3339,
+}
+
+/// A page of a navigator's changes, pulled since a cursor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncChanges {
+	/// The blocks created or updated, as they are now.
+	pub blocks: Vec<ContentBlock>,
+
+	/// The links created.
+	pub links: Vec<ContentLink>,
+
+	/// The blocks and links deleted.
+	pub tombstones: Vec<Tombstone>,
+
+	/// Where to pull the next changes from.
+	pub cursor: SyncCursor,
+
+	/// Whether more changes are waiting after the cursor.
+	pub has_more: bool,
+}
+
+/// An opaque position in a navigator's changes: the time and ID of the last
+/// change pulled, ordered by both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SyncCursor {
+	pub changed_at: DateTime<FixedOffset>,
+	pub id: Uuid,
+}
+
+impl SyncCursor {
+	/// Parse a cursor, as `<microseconds>-<uuid>`.
+	pub fn parse(cursor: &str) -> Result<Self, SyncCursorError> {
+		let invalid = || SyncCursorError::Invalid(cursor.to_string());
+
+		let (micros, id) = cursor.split_once('-').ok_or_else(invalid)?;
+		let micros = micros.parse().map_err(|_| invalid())?;
+
+		Ok(Self {
+			changed_at: DateTime::from_timestamp_micros(micros)
+				.ok_or_else(invalid)?
+				.fixed_offset(),
+			id: Uuid::try_parse(id).map_err(|_| invalid())?,
+		})
+	}
+
+	/// The position of the changes made from now on.
+	pub fn now() -> Self {
+		Self {
+			changed_at: Utc::now().fixed_offset(),
+			id: Uuid::nil(),
+		}
+	}
+}
+
+impl Default for SyncCursor {
+	/// The start of a navigator's changes, before any were made.
+	fn default() -> Self {
+		Self {
+			changed_at: DateTime::UNIX_EPOCH.fixed_offset(),
+			id: Uuid::nil(),
+		}
+	}
+}
+
+impl Display for SyncCursor {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"{}-{}",
+			self.changed_at.timestamp_micros(),
+			self.id.simple()
+		)
+	}
+}
+
+impl TryFrom<String> for SyncCursor {
+	type Error = SyncCursorError;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		Self::parse(&value)
+	}
+}
+
+impl From<SyncCursor> for String {
+	fn from(cursor: SyncCursor) -> Self {
+		cursor.to_string()
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum SyncCursorError {
+	#[error("Invalid sync cursor: '{0}'")]
+	Invalid(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_cursor() {
+		let cursor = SyncCursor {
+			changed_at: DateTime::parse_from_rfc3339("2025-08-15T09:00:00.123456+00:00").unwrap(),
+			id: Uuid::now_v7(),
+		};
+
+		// Assert: Cursors survive being written and parsed.
+		assert_eq!(SyncCursor::parse(&cursor.to_string()).unwrap(), cursor);
+
+		for cursor in ["", "123", "abc-def", "123-not-a-uuid"] {
+			assert!(SyncCursor::parse(cursor).is_err(), "{cursor:?}");
+		}
+	}
+}
@@ -0,0 +1,34 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::ContentBlock;
+use crate::models::NuttyId;
+
+/// Whether a task is still to be done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+	Open,
+	Done,
+}
+
+impl TaskStatus {
+	/// Check whether the status is for finished tasks.
+	pub fn is_done(&self) -> bool {
+		matches!(self, TaskStatus::Done)
+	}
+}
+
+/// A page on the path from the root to a task.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Breadcrumb {
+	pub block_id: NuttyId,
+	pub title: String,
+}
+
+/// A todo block, with the pages it sits under (root first).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Task {
+	pub block: ContentBlock,
+	pub breadcrumbs: Vec<Breadcrumb>,
+}
@@ -0,0 +1,214 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::models::ContentBlock;
+use crate::models::NuttyId;
+
+/// The longest language tag that is stored.
+const MAX_LANG_LEN: usize = 35;
+
+/// A BCP 47 language tag, e.g. `en`, `ja` or `zh-Hant-TW`, in its
+/// conventional case: a lowercase language, a titlecase script and an
+/// uppercase region.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Lang(String);
+
+impl Lang {
+	/// Parse a language tag.
+	pub fn parse(tag: &str) -> Result<Self, TranslationError> {
+		let invalid = || TranslationError::InvalidLang(tag.to_string());
+
+		if tag.len() > MAX_LANG_LEN {
+			return Err(invalid());
+		}
+
+		let mut subtags = tag.split(['-', '_']);
+		let language = subtags.next().unwrap_or_default();
+
+		if !(2..=3).contains(&language.len()) || !language.bytes().all(|b| b.is_ascii_alphabetic()) {
+			return Err(invalid());
+		}
+
+		let mut normalized = language.to_ascii_lowercase();
+
+		for subtag in subtags {
+			if !(1..=8).contains(&subtag.len()) || !subtag.bytes().all(|b| b.is_ascii_alphanumeric()) {
+				return Err(invalid());
+			}
+
+			normalized.push('-');
+
+			match subtag.len() {
+				// A script, e.g. `Hant`.
+				4 if subtag.bytes().all(|b| b.is_ascii_alphabetic()) => {
+					normalized.push_str(&subtag[..1].to_ascii_uppercase());
+					normalized.push_str(&subtag[1..].to_ascii_lowercase());
+				}
+
+				// A region, e.g. `JP`.
+				2 => normalized.push_str(&subtag.to_ascii_uppercase()),
+
+				_ => normalized.push_str(&subtag.to_ascii_lowercase()),
+			}
+		}
+
+		Ok(Self(normalized))
+	}
+
+	/// Parse a comma-separated list of language tags, most preferred first,
+	/// e.g. `ja,en`.
+	pub fn parse_list(tags: &str) -> Result<Vec<Self>, TranslationError> {
+		tags.split(',').map(|tag| Self::parse(tag.trim())).collect()
+	}
+
+	/// Get the language tag.
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+
+	/// Get the tag, then each shorter tag it falls back to, e.g. `zh-Hant-TW`,
+	/// `zh-Hant` and `zh`.
+	pub fn fallbacks(&self) -> impl Iterator<Item = &str> {
+		let mut tag = Some(self.as_str());
+
+		std::iter::from_fn(move || {
+			let current = tag?;
+			tag = current.rsplit_once('-').map(|(prefix, _)| prefix);
+			Some(current)
+		})
+	}
+}
+
+impl Display for Lang {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&self.0)
+	}
+}
+
+impl TryFrom<String> for Lang {
+	type Error = TranslationError;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		Self::parse(&value)
+	}
+}
+
+impl From<Lang> for String {
+	fn from(lang: Lang) -> Self {
+		lang.0
+	}
+}
+
+/// A content block in a language, among the translations of one another.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Translation {
+	pub block_id: NuttyId,
+	pub lang: Lang,
+}
+
+impl Translation {
+	/// Pick the translation that best matches the preferred languages, most
+	/// preferred first. Each language falls back to shorter tags before the
+	/// next is tried, so `ja-JP` matches `ja`, then any `ja-*` variant.
+	pub fn resolve<'a>(translations: &'a [Translation], preferred: &[Lang]) -> Option<&'a Self> {
+		preferred.iter().find_map(|lang| {
+			let exact = lang.fallbacks().find_map(|tag| {
+				translations
+					.iter()
+					.find(|translation| translation.lang.as_str() == tag)
+			});
+
+			exact.or_else(|| {
+				let language = lang.fallbacks().last().unwrap_or_default();
+
+				translations
+					.iter()
+					.find(|translation| translation.lang.fallbacks().last() == Some(language))
+			})
+		})
+	}
+}
+
+/// A content block resolved to a preferred language.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalizedBlock {
+	/// The block in the preferred language, or the one asked for if it isn't
+	/// available.
+	pub block: ContentBlock,
+
+	/// The language of the block, if it's set.
+	pub lang: Option<Lang>,
+
+	/// Every translation of the block, including itself.
+	pub translations: Vec<Translation>,
+}
+
+#[derive(Debug, Error)]
+pub enum TranslationError {
+	#[error("Invalid language tag: '{0}'")]
+	InvalidLang(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn lang(tag: &str) -> Lang {
+		Lang::parse(tag).unwrap()
+	}
+
+	#[test]
+	fn test_lang() {
+		// Assert: Tags are normalized to their conventional case.
+		assert_eq!(lang("EN").as_str(), "en");
+		assert_eq!(lang("ja_jp").as_str(), "ja-JP");
+		assert_eq!(lang("zh-hant-tw").as_str(), "zh-Hant-TW");
+
+		for tag in ["", "e", "english", "en-", "ja-JP!", "en--US"] {
+			assert!(Lang::parse(tag).is_err(), "{tag:?}");
+		}
+
+		// Assert: Tags fall back to shorter tags.
+		assert_eq!(
+			lang("zh-Hant-TW").fallbacks().collect::<Vec<_>>(),
+			["zh-Hant-TW", "zh-Hant", "zh"]
+		);
+	}
+
+	#[test]
+	fn test_resolve() {
+		let translations = [
+			Translation {
+				block_id: NuttyId::now(),
+				lang: lang("en"),
+			},
+			Translation {
+				block_id: NuttyId::now(),
+				lang: lang("ja"),
+			},
+			Translation {
+				block_id: NuttyId::now(),
+				lang: lang("pt-BR"),
+			},
+		];
+
+		let resolve = |preferred: &str| {
+			Translation::resolve(&translations, &Lang::parse_list(preferred).unwrap())
+				.map(|translation| translation.lang.as_str())
+		};
+
+		// Assert: Languages match exactly, by prefix, or by their language.
+		assert_eq!(resolve("ja"), Some("ja"));
+		assert_eq!(resolve("ja-JP"), Some("ja"));
+		assert_eq!(resolve("pt"), Some("pt-BR"));
+
+		// Assert: Later preferences are tried in order.
+		assert_eq!(resolve("fr,en"), Some("en"));
+		assert_eq!(resolve("fr,de"), None);
+	}
+}
@@ -0,0 +1,282 @@
+use std::sync::Arc;
+
+use axum::Json;
+use axum::Router;
+use axum::extract::Path;
+use axum::extract::Query;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use axum::routing::put;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::access::service::AccessServiceError;
+use crate::content::service::ContentServiceError;
+use crate::models::DissociatedNuttyId;
+use crate::models::NuttyId;
+use crate::models::nutty_id::NuttyIdError;
+use crate::moderation::models::Report;
+use crate::moderation::models::ReportAction;
+use crate::moderation::models::ReportStatus;
+use crate::moderation::repository::ModerationRepositoryError;
+use crate::moderation::service::ModerationServiceError;
+use crate::utilities::api::response::Error;
+use crate::utilities::api::response::Response;
+use crate::utilities::api::session::Session;
+use crate::utilities::api::state::AppState;
+
+/// The permission required to review reports.
+const REVIEW_PERMISSION: &str = "moderation:review";
+
+/// The router for moderation API endpoints.
+pub fn router(app_state: Arc<AppState>) -> Router {
+	Router::new()
+		.route("/content-block/{block_id}/report", post(report_handler))
+		.route("/navigator/reports", get(own_reports_handler))
+		.route("/admin/reports", get(reports_handler))
+		.route("/admin/reports/{report_id}", put(resolve_handler))
+		.with_state(app_state)
+}
+
+/// Build an error response.
+fn error_response<T>(
+	status: StatusCode,
+	summary: &str,
+	error: ModerationApiError,
+) -> (StatusCode, Json<Response<T>>) {
+	let error = Error::from_error(&error).with_summary(summary);
+
+	(
+		status,
+		Json(Response::Error {
+			errors: vec![error],
+		}),
+	)
+}
+
+/// Request payload for reporting a content block.
+#[derive(Serialize, Deserialize)]
+pub struct ReportRequest {
+	reason: String,
+}
+
+/// An API handler for reporting a content block the navigator can view.
+async fn report_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(block_id): Path<String>,
+	Json(payload): Json<ReportRequest>,
+) -> (StatusCode, Json<Response<Report>>) {
+	let summary = "Failed to report content block.";
+
+	let block_id = match DissociatedNuttyId::new(&block_id) {
+		Ok(id) => id,
+		Err(error) => {
+			let error = ModerationApiError::InvalidBlockId(error);
+			return error_response(StatusCode::BAD_REQUEST, summary, error);
+		}
+	};
+
+	// Only viewers can report a block.
+	let has_access = state
+		.content_service
+		.check_content_block_access(navigator.nutty_id(), &block_id)
+		.await;
+
+	match has_access {
+		Ok(true) => {}
+
+		Ok(false) => {
+			let summary = "Access denied.";
+			return error_response(
+				StatusCode::FORBIDDEN,
+				summary,
+				ModerationApiError::AccessDenied,
+			);
+		}
+
+		Err(error) => {
+			let summary = "Failed to check access permissions.";
+			let error = ModerationApiError::ContentAccessControl(error);
+			return error_response(StatusCode::INTERNAL_SERVER_ERROR, summary, error);
+		}
+	}
+
+	match state
+		.moderation_service
+		.report(navigator.nutty_id(), &block_id, payload.reason)
+		.await
+	{
+		Ok(report) => (
+			StatusCode::CREATED,
+			Json(Response::Single { data: Some(report) }),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ModerationServiceError::InvalidReport(_) => StatusCode::BAD_REQUEST,
+				ModerationServiceError::ContentBlockNotFound => StatusCode::NOT_FOUND,
+				ModerationServiceError::SaveReport(ModerationRepositoryError::AlreadyReported) => {
+					StatusCode::CONFLICT
+				}
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			error_response(status, summary, ModerationApiError::Report(error))
+		}
+	}
+}
+
+/// An API handler for listing the current navigator's reports and their outcomes.
+async fn own_reports_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+) -> (StatusCode, Json<Response<Report>>) {
+	match state
+		.moderation_service
+		.list_own_reports(navigator.nutty_id())
+		.await
+	{
+		Ok(reports) => (StatusCode::OK, Json(Response::Multiple { data: reports })),
+
+		Err(error) => {
+			let summary = "Failed to list reports.";
+			let error = ModerationApiError::ListReports(error);
+			error_response(StatusCode::INTERNAL_SERVER_ERROR, summary, error)
+		}
+	}
+}
+
+/// Query parameters for the moderation queue.
+#[derive(Deserialize)]
+pub struct ReportsQuery {
+	/// The status to list, which defaults to open reports.
+	status: Option<ReportStatus>,
+}
+
+/// An API handler for listing the moderation queue.
+async fn reports_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Query(query): Query<ReportsQuery>,
+) -> (StatusCode, Json<Response<Report>>) {
+	if let Err(response) = require_review(&state, navigator.nutty_id()).await {
+		return response;
+	}
+
+	let status = query.status.unwrap_or(ReportStatus::Open);
+
+	match state.moderation_service.list_reports(status).await {
+		Ok(reports) => (StatusCode::OK, Json(Response::Multiple { data: reports })),
+
+		Err(error) => {
+			let summary = "Failed to list reports.";
+			let error = ModerationApiError::ListReports(error);
+			error_response(StatusCode::INTERNAL_SERVER_ERROR, summary, error)
+		}
+	}
+}
+
+/// Request payload for resolving a report.
+#[derive(Serialize, Deserialize)]
+pub struct ResolveRequest {
+	action: ReportAction,
+
+	/// A note for the reporter.
+	resolution: Option<String>,
+}
+
+/// An API handler for resolving a report.
+async fn resolve_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(report_id): Path<NuttyId>,
+	Json(payload): Json<ResolveRequest>,
+) -> (StatusCode, Json<Response<Report>>) {
+	if let Err(response) = require_review(&state, navigator.nutty_id()).await {
+		return response;
+	}
+
+	match state
+		.moderation_service
+		.resolve_report(
+			&report_id,
+			navigator.nutty_id(),
+			payload.action,
+			payload.resolution,
+		)
+		.await
+	{
+		Ok(report) => (
+			StatusCode::OK,
+			Json(Response::Single { data: Some(report) }),
+		),
+
+		Err(error) => {
+			let status = match error {
+				ModerationServiceError::ReportNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let summary = "Failed to resolve report.";
+			error_response(status, summary, ModerationApiError::Resolve(error))
+		}
+	}
+}
+
+/// Check that a navigator can review reports.
+async fn require_review<T>(
+	state: &AppState,
+	navigator_id: &NuttyId,
+) -> Result<(), (StatusCode, Json<Response<T>>)> {
+	let has_access = state
+		.access_service
+		.can_permission(navigator_id, REVIEW_PERMISSION)
+		.await;
+
+	match has_access {
+		Ok(true) => Ok(()),
+
+		Ok(false) => {
+			let summary = "Access denied.";
+			let error = ModerationApiError::AccessDenied;
+			Err(error_response(StatusCode::FORBIDDEN, summary, error))
+		}
+
+		Err(error) => {
+			let summary = "Failed to check access permissions.";
+			let error = ModerationApiError::AccessControl(error);
+			Err(error_response(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				summary,
+				error,
+			))
+		}
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ModerationApiError {
+	#[error("Invalid block ID: {0}")]
+	InvalidBlockId(#[from] NuttyIdError),
+
+	#[error("Failed to report content block: {0}")]
+	Report(ModerationServiceError),
+
+	#[error("Failed to list reports: {0}")]
+	ListReports(ModerationServiceError),
+
+	#[error("Failed to resolve report: {0}")]
+	Resolve(ModerationServiceError),
+
+	#[error("Access denied.")]
+	AccessDenied,
+
+	#[error("Failed to check access permissions: {0}")]
+	AccessControl(AccessServiceError),
+
+	#[error("Failed to check content access: {0}")]
+	ContentAccessControl(ContentServiceError),
+}
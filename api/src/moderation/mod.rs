@@ -0,0 +1,4 @@
+pub mod api;
+pub mod models;
+pub mod repository;
+pub mod service;
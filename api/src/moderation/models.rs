@@ -0,0 +1,241 @@
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde::Serialize;
+use sqlx::Decode;
+use sqlx::Encode;
+use sqlx::FromRow;
+use sqlx::Postgres;
+use sqlx::Type;
+use sqlx::postgres::PgTypeInfo;
+use sqlx::postgres::PgValueRef;
+use thiserror::Error;
+
+use crate::models::NuttyId;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+
+/// The maximum length of a report reason, in characters.
+pub const MAX_REPORT_REASON_LENGTH: usize = 1000;
+
+/// Where a report is in the moderation queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportStatus {
+	/// Waiting for a moderator.
+	Open,
+
+	/// Reviewed, and the content was left as is.
+	Dismissed,
+
+	/// Reviewed, and the content was hidden.
+	Actioned,
+}
+
+impl ReportStatus {
+	/// Get the stored representation of the status.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ReportStatus::Open => "open",
+			ReportStatus::Dismissed => "dismissed",
+			ReportStatus::Actioned => "actioned",
+		}
+	}
+}
+
+impl FromStr for ReportStatus {
+	type Err = ReportError;
+
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		match value {
+			"open" => Ok(ReportStatus::Open),
+			"dismissed" => Ok(ReportStatus::Dismissed),
+			"actioned" => Ok(ReportStatus::Actioned),
+			_ => Err(ReportError::UnknownStatus(value.to_string())),
+		}
+	}
+}
+
+impl Type<Postgres> for ReportStatus {
+	fn type_info() -> PgTypeInfo {
+		PgTypeInfo::with_name("VARCHAR")
+	}
+
+	fn compatible(ty: &PgTypeInfo) -> bool {
+		<&str as Type<Postgres>>::compatible(ty)
+	}
+}
+
+impl Encode<'_, Postgres> for ReportStatus {
+	fn encode_by_ref(
+		&self,
+		buf: &mut <Postgres as sqlx::Database>::ArgumentBuffer<'_>,
+	) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+		<&str as Encode<Postgres>>::encode(self.as_str(), buf)
+	}
+}
+
+impl<'r> Decode<'r, Postgres> for ReportStatus {
+	fn decode(value: PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+		let value = <&str as Decode<Postgres>>::decode(value)?;
+		Ok(value.parse()?)
+	}
+}
+
+/// How a moderator resolves a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportAction {
+	/// Leave the content visible.
+	Dismiss,
+
+	/// Hide the content from everyone but moderators.
+	Hide,
+}
+
+impl ReportAction {
+	/// Get the status of a report resolved with this action.
+	pub fn status(&self) -> ReportStatus {
+		match self {
+			ReportAction::Dismiss => ReportStatus::Dismissed,
+			ReportAction::Hide => ReportStatus::Actioned,
+		}
+	}
+}
+
+/// A viewer's report of abusive content.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Report {
+	#[sqlx(rename = "id")]
+	nutty_id: NuttyId,
+	block_id: NuttyId,
+	reporter_id: NuttyId,
+	reason: String,
+	status: ReportStatus,
+
+	/// The moderator's note to the reporter, if any.
+	resolution: Option<String>,
+
+	resolved_by: Option<NuttyId>,
+	resolved_at: Option<DateTimeRfc3339>,
+	created_at: DateTimeRfc3339,
+	updated_at: DateTimeRfc3339,
+}
+
+impl Report {
+	/// Create a new open report.
+	pub fn new(block_id: NuttyId, reporter_id: NuttyId, reason: &str) -> Result<Self, ReportError> {
+		let reason = reason.trim();
+
+		if reason.is_empty() || reason.chars().count() > MAX_REPORT_REASON_LENGTH {
+			return Err(ReportError::InvalidReason);
+		}
+
+		let now = DateTimeRfc3339::new(chrono::Utc::now().fixed_offset());
+
+		Ok(Self {
+			nutty_id: NuttyId::now(),
+			block_id,
+			reporter_id,
+			reason: reason.to_string(),
+			status: ReportStatus::Open,
+			resolution: None,
+			resolved_by: None,
+			resolved_at: None,
+			created_at: now,
+			updated_at: now,
+		})
+	}
+
+	pub fn nutty_id(&self) -> &NuttyId {
+		&self.nutty_id
+	}
+
+	pub fn block_id(&self) -> &NuttyId {
+		&self.block_id
+	}
+
+	pub fn reporter_id(&self) -> &NuttyId {
+		&self.reporter_id
+	}
+
+	pub fn reason(&self) -> &str {
+		&self.reason
+	}
+
+	pub fn status(&self) -> ReportStatus {
+		self.status
+	}
+
+	pub fn resolution(&self) -> Option<&str> {
+		self.resolution.as_deref()
+	}
+
+	pub fn resolved_by(&self) -> Option<&NuttyId> {
+		self.resolved_by.as_ref()
+	}
+
+	pub fn created_at(&self) -> &DateTimeRfc3339 {
+		&self.created_at
+	}
+
+	/// Resolve the report on behalf of a moderator.
+	pub fn resolve(
+		&mut self,
+		moderator_id: NuttyId,
+		action: ReportAction,
+		resolution: Option<String>,
+	) {
+		let now = DateTimeRfc3339::new(chrono::Utc::now().fixed_offset());
+
+		self.status = action.status();
+		self.resolution = resolution;
+		self.resolved_by = Some(moderator_id);
+		self.resolved_at = Some(now);
+		self.updated_at = now;
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum ReportError {
+	#[error("Report reasons must be 1–{MAX_REPORT_REASON_LENGTH} characters")]
+	InvalidReason,
+
+	#[error("Unknown report status: {0}")]
+	UnknownStatus(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_new_report() {
+		let block_id = NuttyId::now();
+		let reporter_id = NuttyId::now();
+
+		// Reasons are trimmed.
+		let report = Report::new(block_id, reporter_id, "  spam  ").unwrap();
+		assert_eq!(report.reason(), "spam");
+		assert_eq!(report.status(), ReportStatus::Open);
+
+		// Blank and overly long reasons are rejected.
+		assert!(Report::new(block_id, reporter_id, " ").is_err());
+		assert!(
+			Report::new(
+				block_id,
+				reporter_id,
+				&"x".repeat(MAX_REPORT_REASON_LENGTH + 1)
+			)
+			.is_err()
+		);
+
+		// Statuses round-trip through their stored form.
+		for status in [
+			ReportStatus::Open,
+			ReportStatus::Dismissed,
+			ReportStatus::Actioned,
+		] {
+			assert_eq!(status.as_str().parse::<ReportStatus>().unwrap(), status);
+		}
+	}
+}
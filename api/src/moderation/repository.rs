@@ -0,0 +1,244 @@
+use sqlx::Executor;
+use sqlx::Postgres;
+use thiserror::Error;
+
+use crate::models::NuttyId;
+use crate::moderation::models::Report;
+use crate::moderation::models::ReportStatus;
+use crate::utilities::query_metrics::RecordQuery;
+use crate::utilities::repository::Repository;
+
+/// A repository for abuse reports and content moderation.
+/// Objects are stored in PostgreSQL.
+#[derive(Debug, Clone)]
+pub struct ModerationRepository {
+	/// The PostgreSQL database pool.
+	pool: sqlx::Pool<Postgres>,
+}
+
+impl ModerationRepository {
+	/// Create a new moderation repository.
+	pub fn new(pool: sqlx::Pool<Postgres>) -> Self {
+		Self { pool }
+	}
+
+	/// Create a new report.
+	pub async fn create_report_tx<'e, E>(
+		&self,
+		executor: E,
+		report: Report,
+	) -> Result<Report, ModerationRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		sqlx::query_as(
+			r#"
+				INSERT INTO content.reports (id, nutty_id, block_id, reporter_id, reason, status, created_at, updated_at)
+				VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+				RETURNING id, block_id, reporter_id, reason, status, resolution, resolved_by, resolved_at, created_at, updated_at
+			"#,
+		)
+		.bind(report.nutty_id().uuid())
+		.bind(report.nutty_id().nid())
+		.bind(report.block_id().uuid())
+		.bind(report.reporter_id().uuid())
+		.bind(report.reason())
+		.bind(report.status())
+		.bind(report.created_at())
+		.bind(report.created_at())
+		.fetch_one(executor)
+		.record_query("create_report")
+		.await
+		.map_err(ModerationRepositoryError::from_write)
+	}
+
+	/// Create a new report.
+	pub async fn create_report(&self, report: Report) -> Result<Report, ModerationRepositoryError> {
+		self.create_report_tx(&self.pool, report).await
+	}
+
+	/// Get a report by ID.
+	pub async fn get_report_tx<'e, E>(
+		&self,
+		executor: E,
+		id: &NuttyId,
+	) -> Result<Option<Report>, ModerationRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT id, block_id, reporter_id, reason, status, resolution, resolved_by, resolved_at, created_at, updated_at
+				FROM content.reports
+				WHERE id = $1
+			"#,
+		)
+		.bind(id.uuid())
+		.fetch_optional(executor)
+		.record_query("get_report")
+		.await?)
+	}
+
+	/// Get a report by ID.
+	pub async fn get_report(
+		&self,
+		id: &NuttyId,
+	) -> Result<Option<Report>, ModerationRepositoryError> {
+		self.get_report_tx(&self.pool, id).await
+	}
+
+	/// List reports with a given status, oldest first.
+	pub async fn list_reports_tx<'e, E>(
+		&self,
+		executor: E,
+		status: ReportStatus,
+	) -> Result<Vec<Report>, ModerationRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT id, block_id, reporter_id, reason, status, resolution, resolved_by, resolved_at, created_at, updated_at
+				FROM content.reports
+				WHERE status = $1
+				ORDER BY created_at ASC
+			"#,
+		)
+		.bind(status)
+		.fetch_all(executor)
+		.record_query("list_reports")
+		.await?)
+	}
+
+	/// List reports with a given status, oldest first.
+	pub async fn list_reports(
+		&self,
+		status: ReportStatus,
+	) -> Result<Vec<Report>, ModerationRepositoryError> {
+		self.list_reports_tx(&self.pool, status).await
+	}
+
+	/// List a navigator's reports, most recent first.
+	pub async fn list_reports_by_reporter_id_tx<'e, E>(
+		&self,
+		executor: E,
+		reporter_id: &NuttyId,
+	) -> Result<Vec<Report>, ModerationRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT id, block_id, reporter_id, reason, status, resolution, resolved_by, resolved_at, created_at, updated_at
+				FROM content.reports
+				WHERE reporter_id = $1
+				ORDER BY created_at DESC
+			"#,
+		)
+		.bind(reporter_id.uuid())
+		.fetch_all(executor)
+		.record_query("list_reports_by_reporter_id")
+		.await?)
+	}
+
+	/// List a navigator's reports, most recent first.
+	pub async fn list_reports_by_reporter_id(
+		&self,
+		reporter_id: &NuttyId,
+	) -> Result<Vec<Report>, ModerationRepositoryError> {
+		self
+			.list_reports_by_reporter_id_tx(&self.pool, reporter_id)
+			.await
+	}
+
+	/// Resolve an open report. Returns `None` if there is no such open report.
+	pub async fn resolve_report_tx<'e, E>(
+		&self,
+		executor: E,
+		id: &NuttyId,
+		status: ReportStatus,
+		resolution: Option<&str>,
+		resolved_by: &NuttyId,
+	) -> Result<Option<Report>, ModerationRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				UPDATE content.reports
+				SET status = $2, resolution = $3, resolved_by = $4, resolved_at = NOW()
+				WHERE id = $1 AND status = 'open'
+				RETURNING id, block_id, reporter_id, reason, status, resolution, resolved_by, resolved_at, created_at, updated_at
+			"#,
+		)
+		.bind(id.uuid())
+		.bind(status)
+		.bind(resolution)
+		.bind(resolved_by.uuid())
+		.fetch_optional(executor)
+		.record_query("resolve_report")
+		.await?)
+	}
+
+	/// Hide (or unhide) a content block from everyone but moderators.
+	pub async fn set_block_hidden_tx<'e, E>(
+		&self,
+		executor: E,
+		block_id: &NuttyId,
+		hidden: bool,
+	) -> Result<(), ModerationRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		sqlx::query!(
+			r#"
+				UPDATE content.blocks
+				SET moderation_status = CASE WHEN $2 THEN 'hidden' ELSE 'visible' END
+				WHERE id = $1
+			"#,
+			block_id.uuid(),
+			hidden,
+		)
+		.execute(executor)
+		.record_query("set_block_hidden")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Hide (or unhide) a content block from everyone but moderators.
+	pub async fn set_block_hidden(
+		&self,
+		block_id: &NuttyId,
+		hidden: bool,
+	) -> Result<(), ModerationRepositoryError> {
+		self.set_block_hidden_tx(&self.pool, block_id, hidden).await
+	}
+}
+
+impl Repository for ModerationRepository {
+	fn pool(&self) -> &sqlx::Pool<Postgres> {
+		&self.pool
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum ModerationRepositoryError {
+	#[error("Database query failed: {0}")]
+	QueryFailed(#[from] sqlx::error::Error),
+
+	#[error("Already reported")]
+	AlreadyReported,
+}
+
+impl ModerationRepositoryError {
+	/// Map a failed report write, surfacing duplicate open reports.
+	fn from_write(error: sqlx::Error) -> Self {
+		match &error {
+			sqlx::Error::Database(e) if e.constraint() == Some("reports_open_block_reporter_key") => {
+				Self::AlreadyReported
+			}
+			_ => Self::QueryFailed(error),
+		}
+	}
+}
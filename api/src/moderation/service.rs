@@ -0,0 +1,322 @@
+use async_trait::async_trait;
+
+use crate::content::repository::ContentRepository;
+use crate::content::repository::ContentRepositoryError;
+use crate::models::DissociatedNuttyId;
+use crate::models::NuttyId;
+use crate::moderation::models::Report;
+use crate::moderation::models::ReportAction;
+use crate::moderation::models::ReportError;
+use crate::moderation::models::ReportStatus;
+use crate::moderation::repository::ModerationRepository;
+use crate::moderation::repository::ModerationRepositoryError;
+use crate::utilities::repository::Repository;
+
+#[derive(Clone)]
+pub struct ModerationService {
+	repository: ModerationRepository,
+	content_repository: ContentRepository,
+}
+
+impl ModerationService {
+	/// Create a new moderation service with the given repositories.
+	pub fn new(repository: ModerationRepository, content_repository: ContentRepository) -> Self {
+		Self {
+			repository,
+			content_repository,
+		}
+	}
+}
+
+/// Abuse reporting and the moderation queue.
+/// Implemented by [ModerationService], and by an in-memory fake in the testkit.
+#[async_trait]
+pub trait ModerationServiceApi: Send + Sync {
+	/// Report a content block. Callers must check that the reporter can view it.
+	async fn report(
+		&self,
+		reporter_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		reason: String,
+	) -> Result<Report, ModerationServiceError>;
+
+	/// List reports with a given status, oldest first.
+	async fn list_reports(
+		&self,
+		status: ReportStatus,
+	) -> Result<Vec<Report>, ModerationServiceError>;
+
+	/// List a navigator's own reports, most recent first, so that they can
+	/// see how each was resolved.
+	async fn list_own_reports(
+		&self,
+		reporter_id: &NuttyId,
+	) -> Result<Vec<Report>, ModerationServiceError>;
+
+	/// Resolve an open report, hiding the reported block if requested.
+	/// The resolution is a note for the reporter.
+	async fn resolve_report(
+		&self,
+		report_id: &NuttyId,
+		moderator_id: &NuttyId,
+		action: ReportAction,
+		resolution: Option<String>,
+	) -> Result<Report, ModerationServiceError>;
+}
+
+#[async_trait]
+impl ModerationServiceApi for ModerationService {
+	/// Report a content block. Callers must check that the reporter can view it.
+	async fn report(
+		&self,
+		reporter_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		reason: String,
+	) -> Result<Report, ModerationServiceError> {
+		let block = self
+			.content_repository
+			.get_content_block(block_id)
+			.await
+			.map_err(ModerationServiceError::FetchContentBlock)?
+			.ok_or(ModerationServiceError::ContentBlockNotFound)?;
+
+		let report = Report::new(*block.nutty_id(), *reporter_id, &reason)
+			.map_err(ModerationServiceError::InvalidReport)?;
+
+		self
+			.repository
+			.create_report(report)
+			.await
+			.map_err(ModerationServiceError::SaveReport)
+	}
+
+	/// List reports with a given status, oldest first.
+	async fn list_reports(
+		&self,
+		status: ReportStatus,
+	) -> Result<Vec<Report>, ModerationServiceError> {
+		self
+			.repository
+			.list_reports(status)
+			.await
+			.map_err(ModerationServiceError::FetchReports)
+	}
+
+	/// List a navigator's own reports, most recent first, so that they can
+	/// see how each was resolved.
+	async fn list_own_reports(
+		&self,
+		reporter_id: &NuttyId,
+	) -> Result<Vec<Report>, ModerationServiceError> {
+		self
+			.repository
+			.list_reports_by_reporter_id(reporter_id)
+			.await
+			.map_err(ModerationServiceError::FetchReports)
+	}
+
+	/// Resolve an open report, hiding the reported block if requested.
+	/// The resolution is a note for the reporter.
+	async fn resolve_report(
+		&self,
+		report_id: &NuttyId,
+		moderator_id: &NuttyId,
+		action: ReportAction,
+		resolution: Option<String>,
+	) -> Result<Report, ModerationServiceError> {
+		let resolution = resolution
+			.map(|resolution| resolution.trim().to_string())
+			.filter(|resolution| !resolution.is_empty());
+
+		self
+			.repository
+			.with_transaction(|tx| {
+				let resolution = resolution.clone();
+
+				Box::pin(async move {
+					// Resolve the report, if it's still open.
+					let report = self
+						.repository
+						.resolve_report_tx(
+							tx.conn(),
+							report_id,
+							action.status(),
+							resolution.as_deref(),
+							moderator_id,
+						)
+						.await
+						.map_err(ModerationServiceError::ResolveReport)?
+						.ok_or(ModerationServiceError::ReportNotFound)?;
+
+					// Hide the reported block.
+					if action == ReportAction::Hide {
+						self
+							.repository
+							.set_block_hidden_tx(tx.conn(), report.block_id(), true)
+							.await
+							.map_err(ModerationServiceError::ResolveReport)?;
+					}
+
+					Ok(report)
+				})
+			})
+			.await
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ModerationServiceError {
+	#[error("Invalid report: {0}")]
+	InvalidReport(#[source] ReportError),
+
+	#[error("Failed to fetch content block: {0}")]
+	FetchContentBlock(#[source] ContentRepositoryError),
+
+	#[error("Content block not found")]
+	ContentBlockNotFound,
+
+	#[error("Failed to save report: {0}")]
+	SaveReport(#[source] ModerationRepositoryError),
+
+	#[error("Failed to fetch reports: {0}")]
+	FetchReports(#[source] ModerationRepositoryError),
+
+	#[error("Open report not found")]
+	ReportNotFound,
+
+	#[error("Failed to resolve report: {0}")]
+	ResolveReport(#[source] ModerationRepositoryError),
+
+	#[error("Database error: {0}")]
+	Database(#[from] sqlx::Error),
+}
+
+#[cfg(test)]
+mod tests {
+	use sqlx::Pool;
+	use sqlx::Postgres;
+	use sqlx::postgres::PgPoolOptions;
+
+	use super::*;
+	use crate::models::BlockContent;
+	use crate::models::ContentBlock;
+	use crate::models::FractionalIndex;
+	use crate::models::Navigator;
+	use crate::navigator::repository::NavigatorRepository;
+
+	async fn connect_to_test_database() -> Pool<Postgres> {
+		let database_url = std::env::var("DATABASE_URL").unwrap();
+
+		PgPoolOptions::new()
+			.max_connections(5)
+			.connect(&database_url)
+			.await
+			.expect("Failed to connect to test database")
+	}
+
+	#[tokio::test]
+	async fn test_report_and_resolve() {
+		// Arrange: Create the repositories and service.
+		let pool = connect_to_test_database().await;
+		let content_repo = ContentRepository::new(pool.clone());
+		let navigator_repo = NavigatorRepository::new(pool.clone());
+		let service = ModerationService::new(ModerationRepository::new(pool), content_repo.clone());
+
+		// Arrange: Create a reporter, a moderator, and a block.
+		let reporter = navigator_repo
+			.create_navigator(Navigator::new("reporter".to_string(), "password123").unwrap())
+			.await
+			.expect("Failed to create reporter");
+
+		let moderator = navigator_repo
+			.create_navigator(Navigator::new("moderator".to_string(), "password123").unwrap())
+			.await
+			.expect("Failed to create moderator");
+
+		let block = content_repo
+			.upsert_content_block(ContentBlock::now(
+				None,
+				FractionalIndex::start(),
+				BlockContent::Page {
+					title: "Reported Page".to_string(),
+				},
+			))
+			.await
+			.expect("Failed to save block");
+
+		let block_id = block.nutty_id().into();
+
+		// Act: Report the block.
+		let report = service
+			.report(reporter.nutty_id(), &block_id, "spam".to_string())
+			.await
+			.expect("Failed to report block");
+
+		// Assert: The report is queued, and can't be duplicated while open.
+		let queue = service
+			.list_reports(ReportStatus::Open)
+			.await
+			.expect("Failed to list reports");
+
+		assert!(queue.iter().any(|r| r.nutty_id() == report.nutty_id()));
+
+		let duplicate = service
+			.report(reporter.nutty_id(), &block_id, "still spam".to_string())
+			.await;
+
+		assert!(matches!(
+			duplicate,
+			Err(ModerationServiceError::SaveReport(
+				ModerationRepositoryError::AlreadyReported
+			))
+		));
+
+		// Act: Resolve the report by hiding the block.
+		let resolved = service
+			.resolve_report(
+				report.nutty_id(),
+				moderator.nutty_id(),
+				ReportAction::Hide,
+				Some(" Removed. ".to_string()),
+			)
+			.await
+			.expect("Failed to resolve report");
+
+		// Assert: The block is hidden and the reporter can see the outcome.
+		assert_eq!(resolved.status(), ReportStatus::Actioned);
+		assert!(content_repo.is_hidden(&block_id).await.unwrap());
+
+		let own = service
+			.list_own_reports(reporter.nutty_id())
+			.await
+			.expect("Failed to list own reports");
+
+		assert_eq!(own.len(), 1);
+		assert_eq!(own[0].resolution(), Some("Removed."));
+
+		// Assert: Resolved reports can't be resolved again.
+		let again = service
+			.resolve_report(
+				report.nutty_id(),
+				moderator.nutty_id(),
+				ReportAction::Dismiss,
+				None,
+			)
+			.await;
+
+		assert!(matches!(again, Err(ModerationServiceError::ReportNotFound)));
+
+		// Cleanup: Delete the test data.
+		content_repo
+			.delete_content_block(&block_id)
+			.await
+			.expect("Failed to delete block");
+
+		for navigator in [reporter, moderator] {
+			navigator_repo
+				.delete_navigator(navigator.nutty_id())
+				.await
+				.expect("Failed to delete navigator");
+		}
+	}
+}
@@ -2,20 +2,33 @@ use std::sync::Arc;
 
 use axum::Json;
 use axum::Router;
+use axum::extract::Path;
+use axum::extract::Query;
 use axum::extract::State;
+use axum::http::HeaderMap;
 use axum::http::HeaderValue;
 use axum::http::StatusCode;
+use axum::http::header::CONTENT_DISPOSITION;
 use axum::http::header::SET_COOKIE;
 use axum::response::IntoResponse;
 use axum::routing::get;
 use axum::routing::post;
+use axum::routing::put;
 use axum_extra::TypedHeader;
 use axum_extra::headers::UserAgent;
 use cookie::Cookie;
 use cookie::SameSite;
 
 use crate::models::Navigator;
+use crate::models::NuttyId;
+use crate::models::navigator_block::BlockedNavigator;
+use crate::models::navigator_export::ExportStatus;
+use crate::models::navigator_export::NavigatorExport;
+use crate::models::onboarding::OnboardingError;
+use crate::models::onboarding::OnboardingProgress;
+use crate::models::onboarding::OnboardingStep;
 use crate::models::session::Session as SessionModel;
+use crate::navigator::repository::NavigatorRepositoryError;
 use crate::navigator::service::NavigatorServiceError;
 use crate::utilities::api::response::Error;
 use crate::utilities::api::response::Response;
@@ -29,6 +42,28 @@ pub fn router(app_state: Arc<AppState>) -> Router {
 		.route("/navigator/login", post(login_handler))
 		.route("/navigator/logout", post(logout_handler))
 		.route("/navigator/me", get(me_handler))
+		.route("/navigator/me/name", put(rename_handler))
+		.route("/navigator/sessions", get(sessions_handler))
+		.route(
+			"/navigator/sessions/{session_id}/label",
+			put(session_label_handler),
+		)
+		.route("/navigator/me/blocked", get(blocked_navigators_handler))
+		.route(
+			"/navigator/me/blocked/{name}",
+			put(block_navigator_handler).delete(unblock_navigator_handler),
+		)
+		.route("/navigator/me/onboarding", get(onboarding_handler))
+		.route(
+			"/navigator/me/onboarding/{step}",
+			post(complete_onboarding_step_handler),
+		)
+		.route("/navigator/me/export", post(request_export_handler))
+		.route("/navigator/me/export/{export_id}", get(export_handler))
+		.route(
+			"/navigator/exports/{export_id}/download",
+			get(download_export_handler),
+		)
 		.with_state(app_state)
 }
 
@@ -91,15 +126,24 @@ pub struct LoginResponse {
 async fn login_handler(
 	State(state): State<Arc<AppState>>,
 	TypedHeader(user_agent): TypedHeader<UserAgent>,
+	headers: HeaderMap,
 	Json(payload): Json<LoginRequest>,
 ) -> impl IntoResponse {
+	let country = state.geo_ip.country(&headers);
+
 	match state
 		.navigator_service
-		.login(payload.name, payload.pass, user_agent.to_string())
+		.login(payload.name, payload.pass, user_agent.to_string(), country)
 		.await
 	{
 		Ok((navigator, session)) => {
-			let cookie = Cookie::build(("session_id", session.nutty_id().to_string()))
+			let token = session
+				.token()
+				.expect("New sessions carry their token")
+				.as_str()
+				.to_string();
+
+			let cookie = Cookie::build(("session_id", token))
 				.same_site(SameSite::Strict)
 				.secure(true)
 				.http_only(true)
@@ -190,6 +234,501 @@ async fn me_handler(
 	})
 }
 
+/// Request payload for renaming a navigator.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct RenameRequest {
+	name: String,
+}
+
+/// An API handler for renaming the current navigator.
+async fn rename_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Json(payload): Json<RenameRequest>,
+) -> (StatusCode, Json<Response<Navigator>>) {
+	match state
+		.navigator_service
+		.rename(navigator.nutty_id(), payload.name)
+		.await
+	{
+		Ok(navigator) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(navigator),
+			}),
+		),
+
+		Err(error) => {
+			let status = match error {
+				NavigatorServiceError::InvalidName(_) => StatusCode::BAD_REQUEST,
+				NavigatorServiceError::UpdateNavigator(NavigatorRepositoryError::NameTaken) => {
+					StatusCode::CONFLICT
+				}
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let summary = "Failed to rename navigator.";
+			let error = NavigatorApiError::Rename(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for listing the navigators that the current navigator
+/// has blocked.
+async fn blocked_navigators_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+) -> (StatusCode, Json<Response<BlockedNavigator>>) {
+	match state
+		.navigator_service
+		.list_blocked_navigators(navigator.nutty_id())
+		.await
+	{
+		Ok(blocked) => (StatusCode::OK, Json(Response::Multiple { data: blocked })),
+
+		Err(error) => {
+			let summary = "Failed to list blocked navigators.";
+			let error = NavigatorApiError::Block(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for blocking a navigator by name.
+async fn block_navigator_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(name): Path<String>,
+) -> (StatusCode, Json<Response<()>>) {
+	let result = state
+		.navigator_service
+		.block_navigator(navigator.nutty_id(), &name)
+		.await;
+
+	block_response(result, "Failed to block navigator.")
+}
+
+/// An API handler for unblocking a navigator by name.
+async fn unblock_navigator_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(name): Path<String>,
+) -> (StatusCode, Json<Response<()>>) {
+	let result = state
+		.navigator_service
+		.unblock_navigator(navigator.nutty_id(), &name)
+		.await;
+
+	block_response(result, "Failed to unblock navigator.")
+}
+
+/// Respond to blocking or unblocking a navigator.
+fn block_response(
+	result: Result<(), NavigatorServiceError>,
+	summary: &str,
+) -> (StatusCode, Json<Response<()>>) {
+	match result {
+		Ok(()) => (StatusCode::OK, Json(Response::Single { data: None })),
+
+		Err(error) => {
+			let status = match error {
+				NavigatorServiceError::NavigatorNotFound => StatusCode::NOT_FOUND,
+				NavigatorServiceError::BlockSelf => StatusCode::BAD_REQUEST,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let error = NavigatorApiError::Block(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for listing the current navigator's onboarding steps.
+async fn onboarding_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+) -> (StatusCode, Json<Response<OnboardingProgress>>) {
+	let result = state
+		.navigator_service
+		.list_onboarding_steps(navigator.nutty_id())
+		.await;
+
+	onboarding_response(result, "Failed to list onboarding steps.")
+}
+
+/// An API handler for marking one of the current navigator's onboarding
+/// steps complete.
+async fn complete_onboarding_step_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(step): Path<String>,
+) -> (StatusCode, Json<Response<OnboardingProgress>>) {
+	let summary = "Failed to complete onboarding step.";
+
+	let step = match OnboardingStep::parse(&step) {
+		Ok(step) => step,
+
+		Err(error) => {
+			let error = NavigatorApiError::InvalidOnboardingStep(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	let result = state
+		.navigator_service
+		.complete_onboarding_step(navigator.nutty_id(), step)
+		.await;
+
+	onboarding_response(result, summary)
+}
+
+/// Respond with a navigator's onboarding steps.
+fn onboarding_response(
+	result: Result<Vec<OnboardingProgress>, NavigatorServiceError>,
+	summary: &str,
+) -> (StatusCode, Json<Response<OnboardingProgress>>) {
+	match result {
+		Ok(steps) => (StatusCode::OK, Json(Response::Multiple { data: steps })),
+
+		Err(error) => {
+			let error = NavigatorApiError::Onboarding(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// A session as listed to its navigator.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct SessionResponse {
+	id: NuttyId,
+
+	/// The label, or a summary of the device (e.g., "Firefox on Linux").
+	name: String,
+
+	/// Whether this is the session making the request.
+	current: bool,
+
+	#[serde(flatten)]
+	session: SessionModel,
+}
+
+impl SessionResponse {
+	fn new(session: SessionModel, current: &SessionModel) -> Self {
+		Self {
+			id: *session.nutty_id(),
+			name: session
+				.label()
+				.map_or_else(|| session.device().summary(), str::to_string),
+			current: session.nutty_id() == current.nutty_id(),
+			session,
+		}
+	}
+}
+
+/// An API handler for listing the current navigator's sessions.
+async fn sessions_handler(
+	State(state): State<Arc<AppState>>,
+	Session { session, navigator }: Session,
+) -> (StatusCode, Json<Response<SessionResponse>>) {
+	match state
+		.navigator_service
+		.list_sessions(navigator.nutty_id())
+		.await
+	{
+		Ok(sessions) => (
+			StatusCode::OK,
+			Json(Response::Multiple {
+				data: sessions
+					.into_iter()
+					.map(|s| SessionResponse::new(s, &session))
+					.collect(),
+			}),
+		),
+
+		Err(error) => {
+			let summary = "Failed to list sessions.";
+			let error = NavigatorApiError::ListSessions(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Request payload for labeling a session.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct SessionLabelRequest {
+	label: Option<String>,
+}
+
+/// An API handler for labeling one of the current navigator's sessions.
+async fn session_label_handler(
+	State(state): State<Arc<AppState>>,
+	Session { session, navigator }: Session,
+	Path(session_id): Path<NuttyId>,
+	Json(payload): Json<SessionLabelRequest>,
+) -> (StatusCode, Json<Response<SessionResponse>>) {
+	match state
+		.navigator_service
+		.label_session(&session_id, navigator.nutty_id(), payload.label)
+		.await
+	{
+		Ok(labeled) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(SessionResponse::new(labeled, &session)),
+			}),
+		),
+
+		Err(error) => {
+			let status = match error {
+				NavigatorServiceError::InvalidSessionLabel => StatusCode::BAD_REQUEST,
+				NavigatorServiceError::SessionNotFound => StatusCode::NOT_FOUND,
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let summary = "Failed to label session.";
+			let error = NavigatorApiError::LabelSession(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An export as shown to its navigator.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct ExportResponse {
+	#[serde(flatten)]
+	export: NavigatorExport,
+
+	/// A signed link to the archive, once it's ready.
+	download_url: Option<String>,
+}
+
+impl ExportResponse {
+	fn new(export: NavigatorExport, state: &AppState) -> Self {
+		let download_url = match export.status() == ExportStatus::Ready && !export.is_expired() {
+			true => state.export_links.sign(&export),
+			false => None,
+		};
+
+		Self {
+			export,
+			download_url,
+		}
+	}
+}
+
+/// An API handler for requesting an archive of the current navigator's data.
+/// The archive is assembled in the background; poll [export_handler] until
+/// it's ready.
+async fn request_export_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+) -> (StatusCode, Json<Response<ExportResponse>>) {
+	let summary = "Failed to request export.";
+
+	if !state.export_links.is_enabled() {
+		let error = NavigatorApiError::ExportsDisabled;
+		let error = Error::from_error(&error).with_summary(summary);
+
+		return (
+			StatusCode::SERVICE_UNAVAILABLE,
+			Json(Response::Error {
+				errors: vec![error],
+			}),
+		);
+	}
+
+	match state
+		.navigator_service
+		.request_export(navigator.nutty_id())
+		.await
+	{
+		Ok(export) => (
+			StatusCode::ACCEPTED,
+			Json(Response::Single {
+				data: Some(ExportResponse::new(export, &state)),
+			}),
+		),
+
+		Err(error) => {
+			let error = NavigatorApiError::Export(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for checking on one of the current navigator's exports.
+async fn export_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(export_id): Path<NuttyId>,
+) -> (StatusCode, Json<Response<ExportResponse>>) {
+	let summary = "Failed to get export.";
+
+	match state
+		.navigator_service
+		.get_export(&export_id, navigator.nutty_id())
+		.await
+	{
+		Ok(Some(export)) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(ExportResponse::new(export, &state)),
+			}),
+		),
+
+		Ok(None) => {
+			let error = NavigatorApiError::ExportNotFound;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::NOT_FOUND,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+
+		Err(error) => {
+			let error = NavigatorApiError::Export(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Query parameters for downloading an export.
+#[derive(serde::Deserialize)]
+pub struct DownloadExportQuery {
+	expires: i64,
+	signature: String,
+}
+
+/// An API handler for downloading an export's archive. The link from
+/// [export_handler] is signed, so no session is needed.
+async fn download_export_handler(
+	State(state): State<Arc<AppState>>,
+	Path(export_id): Path<NuttyId>,
+	Query(query): Query<DownloadExportQuery>,
+) -> axum::response::Response {
+	let summary = "Failed to download export.";
+
+	if !state
+		.export_links
+		.verify(&export_id, query.expires, &query.signature)
+	{
+		let error = NavigatorApiError::InvalidExportLink;
+		let error = Error::from_error(&error).with_summary(summary);
+		let errors = vec![error];
+
+		return (
+			StatusCode::FORBIDDEN,
+			Json(Response::<()>::Error { errors }),
+		)
+			.into_response();
+	}
+
+	match state.navigator_service.get_export_archive(&export_id).await {
+		Ok(Some((export, archive))) => {
+			let filename = format!("nuttyverse-export-{}.json", export.nutty_id().nid());
+			let disposition = format!("attachment; filename=\"{filename}\"");
+
+			(
+				StatusCode::OK,
+				[(CONTENT_DISPOSITION, disposition)],
+				Json(archive),
+			)
+				.into_response()
+		}
+
+		Ok(None) => {
+			let error = NavigatorApiError::ExportNotFound;
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			(
+				StatusCode::NOT_FOUND,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response()
+		}
+
+		Err(error) => {
+			let error = NavigatorApiError::Export(error);
+			let error = Error::from_error(&error).with_summary(summary);
+			let errors = vec![error];
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::<()>::Error { errors }),
+			)
+				.into_response()
+		}
+	}
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum NavigatorApiError {
 	#[error("Failed to register navigator: {0}")]
@@ -200,4 +739,34 @@ pub enum NavigatorApiError {
 
 	#[error("Failed to logout: {0}")]
 	Logout(NavigatorServiceError),
+
+	#[error("Failed to list sessions: {0}")]
+	ListSessions(NavigatorServiceError),
+
+	#[error("Failed to label session: {0}")]
+	LabelSession(NavigatorServiceError),
+
+	#[error("Failed to rename navigator: {0}")]
+	Rename(NavigatorServiceError),
+
+	#[error("Failed to block navigator: {0}")]
+	Block(NavigatorServiceError),
+
+	#[error("Failed to access onboarding steps: {0}")]
+	Onboarding(NavigatorServiceError),
+
+	#[error("Invalid onboarding step: {0}")]
+	InvalidOnboardingStep(OnboardingError),
+
+	#[error("Failed to export navigator: {0}")]
+	Export(NavigatorServiceError),
+
+	#[error("Exports are disabled")]
+	ExportsDisabled,
+
+	#[error("Export not found")]
+	ExportNotFound,
+
+	#[error("Invalid or expired export link")]
+	InvalidExportLink,
 }
@@ -1,3 +1,6 @@
 pub mod api;
+pub mod onboarding;
 pub mod repository;
 pub mod service;
+pub mod session_limit;
+pub mod session_store;
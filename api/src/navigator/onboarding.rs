@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use crate::access::service::AccessServiceApi;
+use crate::content::service::ContentServiceApi;
+use crate::models::ContentBlock;
+use crate::models::NuttyId;
+use crate::models::onboarding::StarterTemplate;
+use crate::navigator::service::NavigatorServiceError;
+
+/// The role that new navigators are granted, so that they can read and edit
+/// what they own, starting with their starter content.
+pub const STARTER_ROLE: &str = "block_owner";
+
+/// Gives new navigators starter content, instead of an empty tree, when they
+/// register.
+#[derive(Clone)]
+pub struct Onboarding {
+	content_service: Arc<dyn ContentServiceApi>,
+	access_service: Arc<dyn AccessServiceApi>,
+	template: StarterTemplate,
+}
+
+impl Onboarding {
+	/// Create onboarding with the default welcome page.
+	pub fn new(
+		content_service: Arc<dyn ContentServiceApi>,
+		access_service: Arc<dyn AccessServiceApi>,
+	) -> Self {
+		Self {
+			content_service,
+			access_service,
+			template: StarterTemplate::default(),
+		}
+	}
+
+	/// Start new navigators with the given template instead.
+	pub fn with_template(mut self, template: StarterTemplate) -> Self {
+		self.template = template;
+		self
+	}
+
+	/// Grant a navigator the starter role, then save their copy of the
+	/// template, parents first.
+	pub async fn provision(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<ContentBlock>, NavigatorServiceError> {
+		self
+			.access_service
+			.grant_global_role(navigator_id, STARTER_ROLE)
+			.await
+			.map_err(NavigatorServiceError::GrantStarterRole)?;
+
+		let mut blocks = vec![];
+
+		for block in self.template.instantiate(*navigator_id) {
+			let block = self
+				.content_service
+				.save_content_block(Some(navigator_id), block)
+				.await
+				.map_err(NavigatorServiceError::ProvisionStarterContent)?;
+
+			blocks.push(block);
+		}
+
+		Ok(blocks)
+	}
+}
+
+impl std::fmt::Debug for Onboarding {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Onboarding")
+			.field("template", &self.template)
+			.finish_non_exhaustive()
+	}
+}
@@ -1,13 +1,36 @@
+use chrono::DateTime;
+use chrono::Utc;
 use sqlx::Executor;
 use sqlx::Postgres;
+use sqlx::QueryBuilder;
 use thiserror::Error;
 
+use crate::models::ContentBlock;
 use crate::models::Navigator;
 use crate::models::NuttyId;
+use crate::models::access_request::AccessRequest;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
 use crate::models::navigator::NavigatorBuilderError;
 use crate::models::navigator::NavigatorError;
+use crate::models::navigator::PepperMatch;
+use crate::models::navigator_block::BlockedNavigator;
+use crate::models::navigator_export::ChatLink;
+use crate::models::navigator_export::NameChange;
+use crate::models::navigator_export::NavigatorExport;
+use crate::models::onboarding::OnboardingStep;
+use crate::models::ownership_transfer::OwnershipTransfer;
+use crate::models::request_usage::NavigatorUsage;
+use crate::models::request_usage::RequestUsage;
 use crate::models::session::Session;
 use crate::models::session::SessionBuilderError;
+use crate::moderation::models::Report;
+use crate::utilities::query_metrics::RecordQuery;
+use crate::utilities::repository::Repository;
+use crate::utilities::repository::TransactionExt;
+
+/// The most rows of request usage inserted per statement, to stay under
+/// Postgres's limit on bound parameters.
+const REQUEST_USAGE_BATCH_SIZE: usize = 1000;
 
 /// A repository for navigator accounts.
 /// Objects are stored in PostgreSQL.
@@ -32,7 +55,7 @@ impl NavigatorRepository {
 	where
 		E: Executor<'e, Database = Postgres>,
 	{
-		Ok(sqlx::query_as(
+		sqlx::query_as(
 			r#"
 				INSERT INTO auth.navigators (id, nutty_id, name, pass, created_at, updated_at)
 				VALUES ($1, $2, $3, $4, $5, $6)
@@ -46,7 +69,9 @@ impl NavigatorRepository {
 		.bind(navigator.created_at())
 		.bind(navigator.updated_at())
 		.fetch_one(executor)
-		.await?)
+		.record_query("create_navigator")
+		.await
+		.map_err(NavigatorRepositoryError::from_write)
 	}
 
 	/// Create a new navigator.
@@ -75,6 +100,7 @@ impl NavigatorRepository {
 		)
 		.bind(id.uuid())
 		.fetch_optional(executor)
+		.record_query("get_navigator_by_id")
 		.await?)
 	}
 
@@ -104,6 +130,7 @@ impl NavigatorRepository {
 		)
 		.bind(name)
 		.fetch_optional(executor)
+		.record_query("get_navigator_by_name")
 		.await?)
 	}
 
@@ -125,7 +152,7 @@ impl NavigatorRepository {
 		E: Executor<'e, Database = Postgres>,
 	{
 		// Update the navigator record.
-		Ok(sqlx::query_as(
+		sqlx::query_as(
 			r#"
 				UPDATE auth.navigators
 				SET name = $2, pass = $3
@@ -137,7 +164,9 @@ impl NavigatorRepository {
 		.bind(navigator.name())
 		.bind(navigator.pass())
 		.fetch_one(executor)
-		.await?)
+		.record_query("update_navigator")
+		.await
+		.map_err(NavigatorRepositoryError::from_write)
 	}
 
 	/// Update a navigator account.
@@ -203,6 +232,7 @@ impl NavigatorRepository {
 			id.uuid(),
 		)
 		.execute(executor)
+		.record_query("delete_navigator")
 		.await?;
 
 		Ok(())
@@ -213,13 +243,57 @@ impl NavigatorRepository {
 		self.delete_navigator_tx(&self.pool, id).await
 	}
 
-	/// Authenticate a navigator with name and password.
+	/// Record that a navigator was renamed, for auditing.
+	pub async fn record_rename_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+		old_name: &str,
+		new_name: &str,
+	) -> Result<(), NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let id = NuttyId::now();
+
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.navigator_renames (id, nutty_id, navigator_id, old_name, new_name)
+				VALUES ($1, $2, $3, $4, $5)
+			"#,
+			id.uuid(),
+			id.nid(),
+			navigator_id.uuid(),
+			old_name,
+			new_name,
+		)
+		.execute(executor)
+		.record_query("record_rename")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Record that a navigator was renamed, for auditing.
+	pub async fn record_rename(
+		&self,
+		navigator_id: &NuttyId,
+		old_name: &str,
+		new_name: &str,
+	) -> Result<(), NavigatorRepositoryError> {
+		self
+			.record_rename_tx(&self.pool, navigator_id, old_name, new_name)
+			.await
+	}
+
+	/// Authenticate a navigator with name and password, returning the
+	/// navigator and which pepper their password matched.
 	pub async fn authenticate_tx<'e, E>(
 		&self,
 		executor: E,
 		name: &str,
 		password: &str,
-	) -> Result<Option<Navigator>, NavigatorRepositoryError>
+	) -> Result<Option<(Navigator, PepperMatch)>, NavigatorRepositoryError>
 	where
 		E: Executor<'e, Database = Postgres>,
 	{
@@ -229,20 +303,19 @@ impl NavigatorRepository {
 			None => return Ok(None),
 		};
 
-		// Verify the password.
-		if navigator.verify_password(password) {
-			Ok(Some(navigator))
-		} else {
-			Ok(None)
-		}
+		// Verify the password, keeping which pepper it matched.
+		Ok(navigator
+			.verify_password_match(password)
+			.map(|matched| (navigator, matched)))
 	}
 
-	/// Authenticate a navigator with name and password.
+	/// Authenticate a navigator with name and password, returning the
+	/// navigator and which pepper their password matched.
 	pub async fn authenticate(
 		&self,
 		name: &str,
 		password: &str,
-	) -> Result<Option<Navigator>, NavigatorRepositoryError> {
+	) -> Result<Option<(Navigator, PepperMatch)>, NavigatorRepositoryError> {
 		self.authenticate_tx(&self.pool, name, password).await
 	}
 
@@ -255,22 +328,32 @@ impl NavigatorRepository {
 	where
 		E: Executor<'e, Database = Postgres>,
 	{
-		Ok(sqlx::query_as(
+		let created: Session = sqlx::query_as(
 			r#"
-				INSERT INTO auth.sessions (id, nutty_id, navigator_id, user_agent, expires_at, created_at, updated_at)
-				VALUES ($1, $2, $3, $4, $5, $6, $7)
-				RETURNING id, navigator_id, user_agent, expires_at, created_at, updated_at
+				INSERT INTO auth.sessions (id, nutty_id, navigator_id, user_agent, token_hash, browser, os, device, label, country, expires_at, created_at, updated_at)
+				VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+				RETURNING id, navigator_id, user_agent, token_hash, browser, os, device, label, country, expires_at, created_at, updated_at
 			"#,
 		)
 			.bind(session.nutty_id().uuid())
 			.bind(session.nutty_id().nid())
 			.bind(session.navigator_id().uuid())
 			.bind(session.user_agent())
+			.bind(session.token_hash())
+			.bind(session.device().browser())
+			.bind(session.device().os())
+			.bind(session.device().device())
+			.bind(session.label())
+			.bind(session.country())
 			.bind(session.expires_at())
 			.bind(session.created_at())
 			.bind(session.updated_at())
 		.fetch_one(executor)
-		.await?)
+		.record_query("create_session")
+		.await?;
+
+		// The plaintext token is never stored, so carry it over from the new session.
+		Ok(created.with_token(session.token().cloned()))
 	}
 
 	/// Create a new session for a navigator.
@@ -292,13 +375,14 @@ impl NavigatorRepository {
 	{
 		Ok(sqlx::query_as(
 			r#"
-				SELECT id, navigator_id, user_agent, expires_at, created_at, updated_at
+				SELECT id, navigator_id, user_agent, token_hash, browser, os, device, label, country, expires_at, created_at, updated_at
 				FROM auth.sessions
 				WHERE id = $1
 			"#,
 		)
 		.bind(id.uuid())
 		.fetch_optional(executor)
+		.record_query("get_session_by_id")
 		.await?)
 	}
 
@@ -310,6 +394,111 @@ impl NavigatorRepository {
 		self.get_session_by_id_tx(&self.pool, id).await
 	}
 
+	/// Get a session by the hash of its token.
+	pub async fn get_session_by_token_hash_tx<'e, E>(
+		&self,
+		executor: E,
+		token_hash: &str,
+	) -> Result<Option<Session>, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT id, navigator_id, user_agent, token_hash, browser, os, device, label, country, expires_at, created_at, updated_at
+				FROM auth.sessions
+				WHERE token_hash = $1
+			"#,
+		)
+		.bind(token_hash)
+		.fetch_optional(executor)
+		.record_query("get_session_by_token_hash")
+		.await?)
+	}
+
+	/// Get a session by the hash of its token.
+	pub async fn get_session_by_token_hash(
+		&self,
+		token_hash: &str,
+	) -> Result<Option<Session>, NavigatorRepositoryError> {
+		self
+			.get_session_by_token_hash_tx(&self.pool, token_hash)
+			.await
+	}
+
+	/// List a navigator's sessions, most recent first.
+	pub async fn list_sessions_by_navigator_id_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<Session>, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT id, navigator_id, user_agent, token_hash, browser, os, device, label, country, expires_at, created_at, updated_at
+				FROM auth.sessions
+				WHERE navigator_id = $1
+				ORDER BY created_at DESC
+			"#,
+		)
+		.bind(navigator_id.uuid())
+		.fetch_all(executor)
+		.record_query("list_sessions_by_navigator_id")
+		.await?)
+	}
+
+	/// List a navigator's sessions, most recent first.
+	pub async fn list_sessions_by_navigator_id(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<Session>, NavigatorRepositoryError> {
+		self
+			.list_sessions_by_navigator_id_tx(&self.pool, navigator_id)
+			.await
+	}
+
+	/// Set (or clear) the label of a navigator's session.
+	/// Returns `None` if the session does not belong to the navigator.
+	pub async fn update_session_label_tx<'e, E>(
+		&self,
+		executor: E,
+		id: &NuttyId,
+		navigator_id: &NuttyId,
+		label: Option<&str>,
+	) -> Result<Option<Session>, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				UPDATE auth.sessions
+				SET label = $3
+				WHERE id = $1 AND navigator_id = $2
+				RETURNING id, navigator_id, user_agent, token_hash, browser, os, device, label, country, expires_at, created_at, updated_at
+			"#,
+		)
+		.bind(id.uuid())
+		.bind(navigator_id.uuid())
+		.bind(label)
+		.fetch_optional(executor)
+		.record_query("update_session_label")
+		.await?)
+	}
+
+	/// Set (or clear) the label of a navigator's session.
+	pub async fn update_session_label(
+		&self,
+		id: &NuttyId,
+		navigator_id: &NuttyId,
+		label: Option<&str>,
+	) -> Result<Option<Session>, NavigatorRepositoryError> {
+		self
+			.update_session_label_tx(&self.pool, id, navigator_id, label)
+			.await
+	}
+
 	/// Delete a session by ID.
 	pub async fn delete_session_tx<'e, E>(
 		&self,
@@ -327,6 +516,7 @@ impl NavigatorRepository {
 			id.uuid(),
 		)
 		.execute(executor)
+		.record_query("delete_session")
 		.await?;
 
 		Ok(())
@@ -336,6 +526,779 @@ impl NavigatorRepository {
 	pub async fn delete_session(&self, id: &NuttyId) -> Result<(), NavigatorRepositoryError> {
 		self.delete_session_tx(&self.pool, id).await
 	}
+
+	/// Record that sessions were evicted to keep within the session limit.
+	pub async fn record_session_evictions_tx<'e, E>(
+		&self,
+		executor: E,
+		sessions: &[Session],
+	) -> Result<(), NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let token_hashes = sessions
+			.iter()
+			.map(|session| session.token_hash().to_string())
+			.collect::<Vec<_>>();
+		let navigator_ids = sessions
+			.iter()
+			.map(|session| *session.navigator_id().uuid())
+			.collect::<Vec<_>>();
+		let expires_at = sessions
+			.iter()
+			.map(|session| *session.expires_at().inner())
+			.collect::<Vec<_>>();
+
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.session_evictions (token_hash, navigator_id, expires_at)
+				SELECT * FROM UNNEST($1::text[], $2::uuid[], $3::timestamptz[])
+				ON CONFLICT (token_hash) DO NOTHING
+			"#,
+			&token_hashes,
+			&navigator_ids,
+			&expires_at,
+		)
+		.execute(executor)
+		.record_query("record_session_evictions")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Record that sessions were evicted to keep within the session limit.
+	pub async fn record_session_evictions(
+		&self,
+		sessions: &[Session],
+	) -> Result<(), NavigatorRepositoryError> {
+		self.record_session_evictions_tx(&self.pool, sessions).await
+	}
+
+	/// Check if the session with a token hash was evicted, and would still
+	/// be active otherwise.
+	pub async fn is_session_evicted_tx<'e, E>(
+		&self,
+		executor: E,
+		token_hash: &str,
+	) -> Result<bool, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let record = sqlx::query!(
+			r#"
+				SELECT EXISTS (
+					SELECT 1 FROM auth.session_evictions
+					WHERE token_hash = $1 AND expires_at > NOW()
+				) AS "exists!"
+			"#,
+			token_hash,
+		)
+		.fetch_one(executor)
+		.record_query("is_session_evicted")
+		.await?;
+
+		Ok(record.exists)
+	}
+
+	/// Check if the session with a token hash was evicted, and would still
+	/// be active otherwise.
+	pub async fn is_session_evicted(
+		&self,
+		token_hash: &str,
+	) -> Result<bool, NavigatorRepositoryError> {
+		self.is_session_evicted_tx(&self.pool, token_hash).await
+	}
+
+	/// Create a new export.
+	pub async fn create_export_tx<'e, E>(
+		&self,
+		executor: E,
+		export: &NavigatorExport,
+	) -> Result<NavigatorExport, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				INSERT INTO auth.navigator_exports (id, nutty_id, navigator_id, status, expires_at, created_at, updated_at)
+				VALUES ($1, $2, $3, $4, $5, $6, $6)
+				RETURNING id, navigator_id, status, error, completed_at, expires_at, created_at, updated_at
+			"#,
+		)
+		.bind(export.nutty_id().uuid())
+		.bind(export.nutty_id().nid())
+		.bind(export.navigator_id().uuid())
+		.bind(export.status())
+		.bind(export.expires_at())
+		.bind(export.created_at())
+		.fetch_one(executor)
+		.record_query("create_export")
+		.await?)
+	}
+
+	/// Create a new export.
+	pub async fn create_export(
+		&self,
+		export: &NavigatorExport,
+	) -> Result<NavigatorExport, NavigatorRepositoryError> {
+		self.create_export_tx(&self.pool, export).await
+	}
+
+	/// Get an export by ID.
+	pub async fn get_export_tx<'e, E>(
+		&self,
+		executor: E,
+		id: &NuttyId,
+	) -> Result<Option<NavigatorExport>, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT id, navigator_id, status, error, completed_at, expires_at, created_at, updated_at
+				FROM auth.navigator_exports
+				WHERE id = $1
+			"#,
+		)
+		.bind(id.uuid())
+		.fetch_optional(executor)
+		.record_query("get_export")
+		.await?)
+	}
+
+	/// Get an export by ID.
+	pub async fn get_export(
+		&self,
+		id: &NuttyId,
+	) -> Result<Option<NavigatorExport>, NavigatorRepositoryError> {
+		self.get_export_tx(&self.pool, id).await
+	}
+
+	/// Save a finished export, with its archive if it was assembled.
+	pub async fn finish_export_tx<'e, E>(
+		&self,
+		executor: E,
+		export: &NavigatorExport,
+		archive: Option<serde_json::Value>,
+	) -> Result<(), NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		sqlx::query(
+			r#"
+				UPDATE auth.navigator_exports
+				SET status = $2, error = $3, completed_at = $4, archive = $5
+				WHERE id = $1
+			"#,
+		)
+		.bind(export.nutty_id().uuid())
+		.bind(export.status())
+		.bind(export.error())
+		.bind(export.completed_at())
+		.bind(archive)
+		.execute(executor)
+		.record_query("finish_export")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Save a finished export, with its archive if it was assembled.
+	pub async fn finish_export(
+		&self,
+		export: &NavigatorExport,
+		archive: Option<serde_json::Value>,
+	) -> Result<(), NavigatorRepositoryError> {
+		self.finish_export_tx(&self.pool, export, archive).await
+	}
+
+	/// Get an export's archive, if it was assembled.
+	pub async fn get_export_archive_tx<'e, E>(
+		&self,
+		executor: E,
+		id: &NuttyId,
+	) -> Result<Option<serde_json::Value>, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let row = sqlx::query!(
+			r#"
+				SELECT archive
+				FROM auth.navigator_exports
+				WHERE id = $1
+			"#,
+			id.uuid(),
+		)
+		.fetch_optional(executor)
+		.record_query("get_export_archive")
+		.await?;
+
+		Ok(row.and_then(|row| row.archive))
+	}
+
+	/// Get an export's archive, if it was assembled.
+	pub async fn get_export_archive(
+		&self,
+		id: &NuttyId,
+	) -> Result<Option<serde_json::Value>, NavigatorRepositoryError> {
+		self.get_export_archive_tx(&self.pool, id).await
+	}
+
+	/// List a navigator's past names, oldest first.
+	pub async fn list_name_changes_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<NameChange>, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT old_name, new_name, created_at
+				FROM auth.navigator_renames
+				WHERE navigator_id = $1
+				ORDER BY created_at
+			"#,
+		)
+		.bind(navigator_id.uuid())
+		.fetch_all(executor)
+		.record_query("list_name_changes")
+		.await?)
+	}
+
+	/// List the chat users linked to a navigator.
+	pub async fn list_chat_links_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<ChatLink>, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT platform, chat_user_id, created_at
+				FROM auth.chat_links
+				WHERE navigator_id = $1
+				ORDER BY created_at
+			"#,
+		)
+		.bind(navigator_id.uuid())
+		.fetch_all(executor)
+		.record_query("list_chat_links")
+		.await?)
+	}
+
+	/// List the content blocks that a navigator owns, oldest first.
+	pub async fn list_owned_blocks_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<ContentBlock>, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT id, owner_id, parent_id, f_index, content, properties, inherit_access, created_at, updated_at
+				FROM content.blocks
+				WHERE owner_id = $1
+				ORDER BY created_at
+			"#,
+		)
+		.bind(navigator_id.uuid())
+		.fetch_all(executor)
+		.record_query("list_owned_blocks")
+		.await?)
+	}
+
+	/// List the reports that a navigator filed, oldest first.
+	pub async fn list_reports_by_reporter_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<Report>, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT id, block_id, reporter_id, reason, status, resolution, resolved_by, resolved_at, created_at, updated_at
+				FROM content.reports
+				WHERE reporter_id = $1
+				ORDER BY created_at
+			"#,
+		)
+		.bind(navigator_id.uuid())
+		.fetch_all(executor)
+		.record_query("list_reports_by_reporter")
+		.await?)
+	}
+
+	/// List the access that a navigator requested, oldest first.
+	pub async fn list_access_requests_by_navigator_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<AccessRequest>, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		Ok(sqlx::query_as(
+			r#"
+				SELECT id, block_id, navigator_id, level, status, resolved_by, resolved_at, created_at, updated_at
+				FROM content.access_requests
+				WHERE navigator_id = $1
+				ORDER BY created_at
+			"#,
+		)
+		.bind(navigator_id.uuid())
+		.fetch_all(executor)
+		.record_query("list_access_requests_by_navigator")
+		.await?)
+	}
+
+	/// List the ownership transfers to, from, or by a navigator, oldest first.
+	pub async fn list_ownership_transfers_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<OwnershipTransfer>, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let rows = sqlx::query!(
+			r#"
+				SELECT block_id, previous_owner_id, new_owner_id
+				FROM content.ownership_transfers
+				WHERE $1 IN (previous_owner_id, new_owner_id, transferred_by)
+				ORDER BY created_at
+			"#,
+			navigator_id.uuid(),
+		)
+		.fetch_all(executor)
+		.record_query("list_ownership_transfers")
+		.await?;
+
+		Ok(rows
+			.into_iter()
+			.map(|row| OwnershipTransfer {
+				block_id: NuttyId::new(row.block_id),
+				previous_owner_id: row.previous_owner_id.map(NuttyId::new),
+				new_owner_id: NuttyId::new(row.new_owner_id),
+			})
+			.collect())
+	}
+
+	/// Block a navigator on another's behalf. Blocking twice is a no-op.
+	pub async fn block_navigator_tx<'e, E>(
+		&self,
+		executor: E,
+		blocker_id: &NuttyId,
+		blocked_id: &NuttyId,
+	) -> Result<(), NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.navigator_blocks (blocker_id, blocked_id)
+				VALUES ($1, $2)
+				ON CONFLICT (blocker_id, blocked_id) DO NOTHING
+			"#,
+			blocker_id.uuid(),
+			blocked_id.uuid(),
+		)
+		.execute(executor)
+		.record_query("block_navigator")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Block a navigator on another's behalf. Blocking twice is a no-op.
+	pub async fn block_navigator(
+		&self,
+		blocker_id: &NuttyId,
+		blocked_id: &NuttyId,
+	) -> Result<(), NavigatorRepositoryError> {
+		self
+			.block_navigator_tx(&self.pool, blocker_id, blocked_id)
+			.await
+	}
+
+	/// Unblock a navigator. Returns whether they were blocked.
+	pub async fn unblock_navigator_tx<'e, E>(
+		&self,
+		executor: E,
+		blocker_id: &NuttyId,
+		blocked_id: &NuttyId,
+	) -> Result<bool, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let result = sqlx::query!(
+			r#"
+				DELETE FROM auth.navigator_blocks
+				WHERE blocker_id = $1 AND blocked_id = $2
+			"#,
+			blocker_id.uuid(),
+			blocked_id.uuid(),
+		)
+		.execute(executor)
+		.record_query("unblock_navigator")
+		.await?;
+
+		Ok(result.rows_affected() > 0)
+	}
+
+	/// Unblock a navigator. Returns whether they were blocked.
+	pub async fn unblock_navigator(
+		&self,
+		blocker_id: &NuttyId,
+		blocked_id: &NuttyId,
+	) -> Result<bool, NavigatorRepositoryError> {
+		self
+			.unblock_navigator_tx(&self.pool, blocker_id, blocked_id)
+			.await
+	}
+
+	/// List the navigators that a navigator has blocked, most recent first.
+	pub async fn list_blocked_navigators_tx<'e, E>(
+		&self,
+		executor: E,
+		blocker_id: &NuttyId,
+	) -> Result<Vec<BlockedNavigator>, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let records = sqlx::query!(
+			r#"
+				SELECT n.id, n.name, b.created_at
+				FROM auth.navigator_blocks b
+				JOIN auth.navigators n ON n.id = b.blocked_id
+				WHERE b.blocker_id = $1
+				ORDER BY b.created_at DESC, n.name
+			"#,
+			blocker_id.uuid(),
+		)
+		.fetch_all(executor)
+		.record_query("list_blocked_navigators")
+		.await?;
+
+		Ok(records
+			.into_iter()
+			.map(|record| BlockedNavigator {
+				navigator_id: NuttyId::new(record.id),
+				name: record.name,
+				blocked_at: DateTimeRfc3339::new(record.created_at.fixed_offset()),
+			})
+			.collect())
+	}
+
+	/// List the navigators that a navigator has blocked, most recent first.
+	pub async fn list_blocked_navigators(
+		&self,
+		blocker_id: &NuttyId,
+	) -> Result<Vec<BlockedNavigator>, NavigatorRepositoryError> {
+		self
+			.list_blocked_navigators_tx(&self.pool, blocker_id)
+			.await
+	}
+
+	/// Mark an onboarding step complete. Completing it twice keeps the
+	/// first time.
+	pub async fn complete_onboarding_step_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+		step: OnboardingStep,
+	) -> Result<(), NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		sqlx::query!(
+			r#"
+				INSERT INTO auth.navigator_onboarding (navigator_id, step)
+				VALUES ($1, $2)
+				ON CONFLICT (navigator_id, step) DO NOTHING
+			"#,
+			navigator_id.uuid(),
+			step.as_str(),
+		)
+		.execute(executor)
+		.record_query("complete_onboarding_step")
+		.await?;
+
+		Ok(())
+	}
+
+	/// Mark an onboarding step complete. Completing it twice keeps the
+	/// first time.
+	pub async fn complete_onboarding_step(
+		&self,
+		navigator_id: &NuttyId,
+		step: OnboardingStep,
+	) -> Result<(), NavigatorRepositoryError> {
+		self
+			.complete_onboarding_step_tx(&self.pool, navigator_id, step)
+			.await
+	}
+
+	/// Get the version of a navigator's calendar feed token, or nothing if
+	/// the navigator doesn't exist.
+	pub async fn get_feed_token_version_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+	) -> Result<Option<i32>, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let record = sqlx::query!(
+			r#"
+				SELECT feed_token_version
+				FROM auth.navigators
+				WHERE id = $1
+			"#,
+			navigator_id.uuid(),
+		)
+		.fetch_optional(executor)
+		.record_query("get_feed_token_version")
+		.await?;
+
+		Ok(record.map(|record| record.feed_token_version))
+	}
+
+	/// Get the version of a navigator's calendar feed token, or nothing if
+	/// the navigator doesn't exist.
+	pub async fn get_feed_token_version(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Option<i32>, NavigatorRepositoryError> {
+		self
+			.get_feed_token_version_tx(&self.pool, navigator_id)
+			.await
+	}
+
+	/// Bump the version of a navigator's calendar feed token, returning the
+	/// new version, or nothing if the navigator doesn't exist.
+	pub async fn bump_feed_token_version_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+	) -> Result<Option<i32>, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let record = sqlx::query!(
+			r#"
+				UPDATE auth.navigators
+				SET feed_token_version = feed_token_version + 1
+				WHERE id = $1
+				RETURNING feed_token_version
+			"#,
+			navigator_id.uuid(),
+		)
+		.fetch_optional(executor)
+		.record_query("bump_feed_token_version")
+		.await?;
+
+		Ok(record.map(|record| record.feed_token_version))
+	}
+
+	/// Bump the version of a navigator's calendar feed token, returning the
+	/// new version, or nothing if the navigator doesn't exist.
+	pub async fn bump_feed_token_version(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Option<i32>, NavigatorRepositoryError> {
+		self
+			.bump_feed_token_version_tx(&self.pool, navigator_id)
+			.await
+	}
+
+	/// Record the requests that navigators made since the last time. Usage of
+	/// navigators that have since been deleted is dropped.
+	pub async fn insert_request_usage_tx<'e, E>(
+		&self,
+		executor: E,
+		usage: &[(NuttyId, RequestUsage)],
+	) -> Result<(), NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		if usage.is_empty() {
+			return Ok(());
+		}
+
+		let mut builder = QueryBuilder::<Postgres>::new(
+			"INSERT INTO auth.navigator_request_stats (navigator_id, request_count, request_bytes, response_bytes, total_ms, max_ms, latency_buckets) SELECT * FROM (",
+		);
+
+		builder.push_values(usage, |mut row, (navigator_id, usage)| {
+			row.push_bind(*navigator_id.uuid())
+				.push_bind(usage.request_count)
+				.push_bind(usage.request_bytes)
+				.push_bind(usage.response_bytes)
+				.push_bind(usage.total_ms)
+				.push_bind(usage.max_ms)
+				.push_bind(usage.latency_buckets.clone());
+		});
+
+		builder.push(
+			") AS usage (navigator_id, request_count, request_bytes, response_bytes, total_ms, max_ms, latency_buckets) WHERE navigator_id IN (SELECT id FROM auth.navigators)",
+		);
+
+		builder
+			.build()
+			.execute(executor)
+			.record_query("insert_request_usage")
+			.await?;
+
+		Ok(())
+	}
+
+	/// Record the requests that navigators made since the last time, all or
+	/// nothing.
+	pub async fn insert_request_usage(
+		&self,
+		usage: &[(NuttyId, RequestUsage)],
+	) -> Result<(), NavigatorRepositoryError> {
+		self
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					for batch in usage.chunks(REQUEST_USAGE_BATCH_SIZE) {
+						self
+							.insert_request_usage_tx(tx.as_executor(), batch)
+							.await?;
+					}
+
+					Ok(())
+				})
+			})
+			.await
+	}
+
+	/// Sum the requests that each navigator made since a time, merging their
+	/// latency histograms bucket by bucket.
+	pub async fn list_request_usage_tx<'e, E>(
+		&self,
+		executor: E,
+		since: &DateTime<Utc>,
+	) -> Result<Vec<NavigatorUsage>, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let records = sqlx::query!(
+			r#"
+				WITH stats AS (
+					SELECT * FROM auth.navigator_request_stats
+					WHERE recorded_at >= $1
+				),
+				buckets AS (
+					SELECT stats.navigator_id, bucket.i, SUM(bucket.count)::BIGINT AS count
+					FROM stats, unnest(stats.latency_buckets) WITH ORDINALITY AS bucket(count, i)
+					GROUP BY stats.navigator_id, bucket.i
+				)
+				SELECT
+					n.id,
+					n.name,
+					SUM(stats.request_count)::BIGINT AS "request_count!",
+					SUM(stats.request_bytes)::BIGINT AS "request_bytes!",
+					SUM(stats.response_bytes)::BIGINT AS "response_bytes!",
+					SUM(stats.total_ms) AS "total_ms!",
+					MAX(stats.max_ms) AS "max_ms!",
+					ARRAY(
+						SELECT buckets.count FROM buckets
+						WHERE buckets.navigator_id = n.id
+						ORDER BY buckets.i
+					) AS "latency_buckets!"
+				FROM stats
+				JOIN auth.navigators n ON n.id = stats.navigator_id
+				GROUP BY n.id, n.name
+			"#,
+			since,
+		)
+		.fetch_all(executor)
+		.record_query("list_request_usage")
+		.await?;
+
+		Ok(records
+			.into_iter()
+			.map(|record| {
+				let usage = RequestUsage {
+					request_count: record.request_count,
+					request_bytes: record.request_bytes,
+					response_bytes: record.response_bytes,
+					total_ms: record.total_ms,
+					max_ms: record.max_ms,
+					latency_buckets: record.latency_buckets,
+				};
+
+				NavigatorUsage::new(NuttyId::new(record.id), record.name, &usage)
+			})
+			.collect())
+	}
+
+	/// Sum the requests that each navigator made since a time.
+	pub async fn list_request_usage(
+		&self,
+		since: &DateTime<Utc>,
+	) -> Result<Vec<NavigatorUsage>, NavigatorRepositoryError> {
+		self.list_request_usage_tx(&self.pool, since).await
+	}
+
+	/// List the onboarding steps that a navigator has completed, and when.
+	pub async fn list_onboarding_steps_tx<'e, E>(
+		&self,
+		executor: E,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<(OnboardingStep, DateTimeRfc3339)>, NavigatorRepositoryError>
+	where
+		E: Executor<'e, Database = Postgres>,
+	{
+		let records = sqlx::query!(
+			r#"
+				SELECT step, completed_at
+				FROM auth.navigator_onboarding
+				WHERE navigator_id = $1
+			"#,
+			navigator_id.uuid(),
+		)
+		.fetch_all(executor)
+		.record_query("list_onboarding_steps")
+		.await?;
+
+		// Steps that have since been retired are left out.
+		Ok(records
+			.into_iter()
+			.filter_map(|record| {
+				let step = OnboardingStep::parse(&record.step).ok()?;
+				Some((
+					step,
+					DateTimeRfc3339::new(record.completed_at.fixed_offset()),
+				))
+			})
+			.collect())
+	}
+
+	/// List the onboarding steps that a navigator has completed, and when.
+	pub async fn list_onboarding_steps(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<(OnboardingStep, DateTimeRfc3339)>, NavigatorRepositoryError> {
+		self
+			.list_onboarding_steps_tx(&self.pool, navigator_id)
+			.await
+	}
+}
+
+impl Repository for NavigatorRepository {
+	fn pool(&self) -> &sqlx::Pool<Postgres> {
+		&self.pool
+	}
 }
 
 #[derive(Debug, Error)]
@@ -354,6 +1317,21 @@ pub enum NavigatorRepositoryError {
 
 	#[error("Navigator not found")]
 	NavigatorNotFound,
+
+	#[error("Navigator name already taken")]
+	NameTaken,
+}
+
+impl NavigatorRepositoryError {
+	/// Map a failed navigator write, surfacing name conflicts.
+	fn from_write(error: sqlx::Error) -> Self {
+		match &error {
+			sqlx::Error::Database(e) if e.constraint() == Some("navigators_name_key") => {
+				Self::NameTaken
+			}
+			_ => Self::QueryFailed(error),
+		}
+	}
 }
 
 #[cfg(test)]
@@ -441,7 +1419,7 @@ mod tests {
 			.expect("Failed to update password");
 
 		// Assert: The password was updated.
-		let authenticated = repo
+		let (authenticated, _) = repo
 			.authenticate("updated_user", new_password)
 			.await
 			.expect("Failed to authenticate")
@@ -495,7 +1473,7 @@ mod tests {
 			.await
 			.expect("Failed to authenticate");
 
-		assert!(auth_result.is_some());
+		assert!(matches!(auth_result, Some((_, PepperMatch::Current))));
 
 		// Act & Assert: Failed authentication - wrong password.
 		let wrong_password = repo
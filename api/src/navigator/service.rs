@@ -1,78 +1,645 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use chrono::DateTime;
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::access::service::AccessServiceError;
+use crate::content::service::ContentServiceError;
+use crate::models::ContentBlock;
 use crate::models::Navigator;
 use crate::models::NuttyId;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
 use crate::models::navigator::NavigatorError;
+use crate::models::navigator::PepperMatch;
+use crate::models::navigator_block::BlockedNavigator;
+use crate::models::navigator_export::ExportArchive;
+use crate::models::navigator_export::ExportStatus;
+use crate::models::navigator_export::NavigatorExport;
+use crate::models::onboarding::OnboardingProgress;
+use crate::models::onboarding::OnboardingStep;
+use crate::models::request_usage::NavigatorUsage;
+use crate::models::request_usage::RequestUsage;
+use crate::models::request_usage::UsageSort;
 use crate::models::session::Session;
 use crate::models::session::SessionError;
+use crate::models::session::SessionToken;
+use crate::navigator::onboarding::Onboarding;
 use crate::navigator::repository::NavigatorRepository;
 use crate::navigator::repository::NavigatorRepositoryError;
+use crate::navigator::session_limit::SessionLimit;
+use crate::navigator::session_store::PostgresSessionStore;
+use crate::navigator::session_store::SessionStore;
+use crate::navigator::session_store::SessionStoreError;
+use crate::utilities::api::read_only::ReadOnlyMode;
+use crate::utilities::repository::Repository;
+
+/// The maximum length of a session label, in characters.
+const MAX_SESSION_LABEL_LENGTH: usize = 64;
+
+/// How long an export can be downloaded for, in hours.
+const EXPORT_TTL_HOURS: i64 = 24;
+
+/// Trim a session label, treating a blank one as no label.
+pub(crate) fn normalize_session_label(
+	label: Option<String>,
+) -> Result<Option<String>, NavigatorServiceError> {
+	let label = label
+		.map(|label| label.trim().to_string())
+		.filter(|label| !label.is_empty());
+
+	if label
+		.as_ref()
+		.is_some_and(|label| label.chars().count() > MAX_SESSION_LABEL_LENGTH)
+	{
+		return Err(NavigatorServiceError::InvalidSessionLabel);
+	}
+
+	Ok(label)
+}
 
 #[derive(Clone)]
 pub struct NavigatorService {
 	repository: NavigatorRepository,
+
+	/// Where sessions are looked up on every request.
+	session_store: Arc<dyn SessionStore>,
+
+	/// Password hash versions seen on successful logins.
+	hash_metrics: Arc<Mutex<PasswordHashMetrics>>,
+
+	/// What new navigators start with, if anything.
+	onboarding: Option<Onboarding>,
+
+	/// How many sessions each navigator may have at once.
+	session_limit: SessionLimit,
+
+	/// The API's read-only switch, under which logins only create sessions.
+	read_only: ReadOnlyMode,
+}
+
+/// Counts of successful logins by password hash version.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PasswordHashMetrics {
+	/// The number of logins per hash version (before any rehash).
+	logins_by_hash_version: HashMap<String, u64>,
+
+	/// The number of hashes upgraded to the current parameters.
+	rehashes: u64,
 }
 
 impl NavigatorService {
 	/// Create a new navigator service with the given repository.
 	pub fn new(repository: NavigatorRepository) -> Self {
-		NavigatorService { repository }
+		NavigatorService {
+			session_store: Arc::new(PostgresSessionStore::new(repository.clone())),
+			repository,
+			hash_metrics: Arc::new(Mutex::new(PasswordHashMetrics::default())),
+			onboarding: None,
+			session_limit: SessionLimit::default(),
+			read_only: ReadOnlyMode::new(false, 0),
+		}
+	}
+
+	/// Keep sessions in the given store, rather than only in Postgres.
+	pub fn with_session_store(mut self, session_store: impl SessionStore + 'static) -> Self {
+		self.session_store = Arc::new(session_store);
+		self
+	}
+
+	/// Give new navigators starter content when they register.
+	pub fn with_onboarding(mut self, onboarding: Onboarding) -> Self {
+		self.onboarding = Some(onboarding);
+		self
+	}
+
+	/// Limit how many sessions each navigator may have at once.
+	pub fn with_session_limit(mut self, session_limit: SessionLimit) -> Self {
+		self.session_limit = session_limit;
+		self
+	}
+
+	/// Share the API's read-only switch. While it's on, logins still create
+	/// sessions, so that navigators can sign in to switch it back off, but
+	/// don't rehash passwords or evict sessions.
+	pub fn with_read_only(mut self, read_only: ReadOnlyMode) -> Self {
+		self.read_only = read_only;
+		self
+	}
+
+	/// Evict a navigator's oldest sessions beyond the session limit. The
+	/// evictions are recorded first, so that the evicted devices are told why
+	/// they were signed out on their next request.
+	async fn evict_sessions_over_limit(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<(), NavigatorServiceError> {
+		if self.session_limit.max_sessions().is_none() {
+			return Ok(());
+		}
+
+		let sessions = self
+			.repository
+			.list_sessions_by_navigator_id(navigator_id)
+			.await
+			.map_err(NavigatorServiceError::EvictSessions)?;
+
+		let evicted = self
+			.session_limit
+			.evicted(&sessions)
+			.into_iter()
+			.cloned()
+			.collect::<Vec<_>>();
+
+		if evicted.is_empty() {
+			return Ok(());
+		}
+
+		self
+			.repository
+			.record_session_evictions(&evicted)
+			.await
+			.map_err(NavigatorServiceError::EvictSessions)?;
+
+		for session in &evicted {
+			self
+				.session_store
+				.delete_session(session)
+				.await
+				.map_err(NavigatorServiceError::SessionStore)?;
+		}
+
+		Ok(())
+	}
+
+	/// Rehash a navigator's password with the current parameters and pepper,
+	/// if needed and not in read-only mode. The pepper is the one that the
+	/// password matched when the navigator was authenticated.
+	async fn rehash_if_outdated(
+		&self,
+		mut navigator: Navigator,
+		password: &str,
+		matched: PepperMatch,
+	) -> Result<Navigator, NavigatorServiceError> {
+		let needs_rehash = navigator.needs_rehash() || matched == PepperMatch::Previous;
+
+		{
+			let mut metrics = self
+				.hash_metrics
+				.lock()
+				.expect("Hash metrics lock poisoned");
+
+			*metrics
+				.logins_by_hash_version
+				.entry(navigator.hash_version())
+				.or_default() += 1;
+
+			if needs_rehash && !self.read_only.is_enabled() {
+				metrics.rehashes += 1;
+			}
+		}
+
+		if !needs_rehash || self.read_only.is_enabled() {
+			return Ok(navigator);
+		}
+
+		navigator
+			.update_password(password)
+			.map_err(NavigatorServiceError::Rehash)?;
+
+		self
+			.repository
+			.update_navigator(navigator)
+			.await
+			.map_err(NavigatorServiceError::UpdateNavigator)
 	}
 
+	/// Assemble and save an export's archive. Nothing waits on this, so if
+	/// the export can't be saved, it stays pending.
+	async fn run_export(&self, mut export: NavigatorExport) {
+		let archive = self
+			.assemble_export(export.navigator_id())
+			.await
+			.and_then(|archive| {
+				serde_json::to_value(archive).map_err(NavigatorServiceError::SerializeExport)
+			});
+
+		let archive = match archive {
+			Ok(archive) => {
+				export.complete();
+				Some(archive)
+			}
+
+			Err(error) => {
+				export.fail(error.to_string());
+				None
+			}
+		};
+
+		let _ = self.repository.finish_export(&export, archive).await;
+	}
+
+	/// Gather everything stored about a navigator, in one snapshot.
+	async fn assemble_export(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<ExportArchive, NavigatorServiceError> {
+		self
+			.repository
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					let navigator = self
+						.repository
+						.get_navigator_by_id_tx(tx.conn(), navigator_id)
+						.await
+						.map_err(NavigatorServiceError::AssembleExport)?
+						.ok_or(NavigatorServiceError::NavigatorNotFound)?;
+
+					let name_changes = self
+						.repository
+						.list_name_changes_tx(tx.conn(), navigator_id)
+						.await
+						.map_err(NavigatorServiceError::AssembleExport)?;
+
+					let sessions = self
+						.repository
+						.list_sessions_by_navigator_id_tx(tx.conn(), navigator_id)
+						.await
+						.map_err(NavigatorServiceError::AssembleExport)?;
+
+					let chat_links = self
+						.repository
+						.list_chat_links_tx(tx.conn(), navigator_id)
+						.await
+						.map_err(NavigatorServiceError::AssembleExport)?;
+
+					let blocks = self
+						.repository
+						.list_owned_blocks_tx(tx.conn(), navigator_id)
+						.await
+						.map_err(NavigatorServiceError::AssembleExport)?;
+
+					let reports = self
+						.repository
+						.list_reports_by_reporter_tx(tx.conn(), navigator_id)
+						.await
+						.map_err(NavigatorServiceError::AssembleExport)?;
+
+					let access_requests = self
+						.repository
+						.list_access_requests_by_navigator_tx(tx.conn(), navigator_id)
+						.await
+						.map_err(NavigatorServiceError::AssembleExport)?;
+
+					let ownership_transfers = self
+						.repository
+						.list_ownership_transfers_tx(tx.conn(), navigator_id)
+						.await
+						.map_err(NavigatorServiceError::AssembleExport)?;
+
+					Ok(ExportArchive {
+						exported_at: DateTimeRfc3339::new(chrono::Utc::now().fixed_offset()),
+						navigator,
+						name_changes,
+						sessions,
+						chat_links,
+						blocks,
+						reports,
+						access_requests,
+						ownership_transfers,
+					})
+				})
+			})
+			.await
+	}
+}
+
+/// Navigator and session operations.
+/// Implemented by [NavigatorService], and by an in-memory fake in the testkit.
+#[async_trait]
+pub trait NavigatorServiceApi: Send + Sync {
+	/// Register a [Navigator].
+	async fn register(&self, name: String, pass: String)
+	-> Result<Navigator, NavigatorServiceError>;
+
+	/// Login a navigator with their name and password.
+	/// Returns a tuple of (Navigator, Session) if successful.
+	/// The session carries its plaintext token, which is never stored.
+	async fn login(
+		&self,
+		name: String,
+		password: String,
+		user_agent: String,
+		country: Option<String>,
+	) -> Result<(Navigator, Session), NavigatorServiceError>;
+
+	/// Get the password hash versions seen on successful logins.
+	fn password_hash_metrics(&self) -> PasswordHashMetrics;
+
+	/// Rename a navigator, recording the old name for auditing.
+	/// Sessions resolve their navigator on every request, so they see the new
+	/// name immediately.
+	async fn rename(
+		&self,
+		navigator_id: &NuttyId,
+		new_name: String,
+	) -> Result<Navigator, NavigatorServiceError>;
+
+	/// Logout a navigator by deleting their session.
+	async fn logout(&self, session_id: &NuttyId) -> Result<(), NavigatorServiceError>;
+
+	/// Get a navigator by ID.
+	async fn get_navigator_by_id(
+		&self,
+		id: &NuttyId,
+	) -> Result<Option<Navigator>, NavigatorServiceError>;
+
+	/// Get a navigator by name.
+	async fn get_navigator_by_name(
+		&self,
+		name: &str,
+	) -> Result<Option<Navigator>, NavigatorServiceError>;
+
+	/// Get a session by ID.
+	async fn get_session_by_id(
+		&self,
+		id: &NuttyId,
+	) -> Result<Option<Session>, NavigatorServiceError>;
+
+	/// List a navigator's sessions, most recent first.
+	async fn list_sessions(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<Session>, NavigatorServiceError>;
+
+	/// Name one of a navigator's sessions (e.g., "work laptop").
+	/// A blank label clears it.
+	async fn label_session(
+		&self,
+		id: &NuttyId,
+		navigator_id: &NuttyId,
+		label: Option<String>,
+	) -> Result<Session, NavigatorServiceError>;
+
+	/// Get a session by its token.
+	async fn get_session_by_token(
+		&self,
+		token: &SessionToken,
+	) -> Result<Option<Session>, NavigatorServiceError>;
+
+	/// Check if the session with a token was evicted by a newer login, to
+	/// tell its device why it was signed out.
+	async fn is_session_evicted(&self, token: &SessionToken) -> Result<bool, NavigatorServiceError>;
+
+	/// Request an archive of a navigator's personal data. It's assembled in
+	/// the background, so poll the export until it's ready.
+	async fn request_export(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<NavigatorExport, NavigatorServiceError>;
+
+	/// Get one of a navigator's exports.
+	async fn get_export(
+		&self,
+		id: &NuttyId,
+		navigator_id: &NuttyId,
+	) -> Result<Option<NavigatorExport>, NavigatorServiceError>;
+
+	/// Get an export and its archive, unless it isn't ready or has expired.
+	async fn get_export_archive(
+		&self,
+		id: &NuttyId,
+	) -> Result<Option<(NavigatorExport, serde_json::Value)>, NavigatorServiceError>;
+
+	/// Block a navigator by name, so that they can't request access to, or
+	/// comment on, the blocker's content.
+	async fn block_navigator(
+		&self,
+		blocker_id: &NuttyId,
+		name: &str,
+	) -> Result<(), NavigatorServiceError>;
+
+	/// Unblock a navigator by name.
+	async fn unblock_navigator(
+		&self,
+		blocker_id: &NuttyId,
+		name: &str,
+	) -> Result<(), NavigatorServiceError>;
+
+	/// List the navigators that a navigator has blocked, most recent first.
+	async fn list_blocked_navigators(
+		&self,
+		blocker_id: &NuttyId,
+	) -> Result<Vec<BlockedNavigator>, NavigatorServiceError>;
+
+	/// Give a navigator a copy of the starter content, which they own, and the
+	/// role to edit it. New navigators are provisioned when they register, if
+	/// onboarding is enabled.
+	async fn provision_starter_content(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<ContentBlock>, NavigatorServiceError>;
+
+	/// List every onboarding step, with when the navigator completed it.
+	async fn list_onboarding_steps(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<OnboardingProgress>, NavigatorServiceError>;
+
+	/// Mark an onboarding step complete, then list every step.
+	async fn complete_onboarding_step(
+		&self,
+		navigator_id: &NuttyId,
+		step: OnboardingStep,
+	) -> Result<Vec<OnboardingProgress>, NavigatorServiceError>;
+
+	/// Get the version of a navigator's calendar feed token, which is signed
+	/// into the token, or nothing if the navigator doesn't exist.
+	async fn get_feed_token_version(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Option<i32>, NavigatorServiceError>;
+
+	/// Regenerate a navigator's calendar feed token by bumping its version,
+	/// which revokes the tokens issued before. Returns the new version.
+	async fn regenerate_feed_token(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<i32, NavigatorServiceError>;
+
+	/// Record the requests that navigators made since the last time.
+	async fn record_request_usage(
+		&self,
+		usage: &[(NuttyId, RequestUsage)],
+	) -> Result<(), NavigatorServiceError>;
+
+	/// Rank the navigators that made requests since a time, heaviest first.
+	async fn rank_request_usage(
+		&self,
+		since: &DateTime<Utc>,
+		sort: UsageSort,
+		limit: usize,
+	) -> Result<Vec<NavigatorUsage>, NavigatorServiceError>;
+}
+
+#[async_trait]
+impl NavigatorServiceApi for NavigatorService {
 	/// Register a [Navigator].
-	pub async fn register(
+	async fn register(
 		&self,
 		name: String,
 		pass: String,
 	) -> Result<Navigator, NavigatorServiceError> {
 		let navigator = Navigator::new(name, &pass).map_err(NavigatorServiceError::Create)?;
 
-		self
+		let navigator = self
 			.repository
 			.create_navigator(navigator)
 			.await
-			.map_err(NavigatorServiceError::Insert)
+			.map_err(NavigatorServiceError::Insert)?;
+
+		// The navigator can still find their way around without starter
+		// content, so failing to provision it doesn't fail registration.
+		if self.onboarding.is_some()
+			&& let Err(e) = self.provision_starter_content(navigator.nutty_id()).await
+		{
+			eprintln!(
+				"Failed to provision starter content for {}: {e}",
+				navigator.nutty_id()
+			);
+		}
+
+		Ok(navigator)
 	}
 
 	/// Login a navigator with their name and password.
 	/// Returns a tuple of (Navigator, Session) if successful.
-	pub async fn login(
+	/// The session carries its plaintext token, which is never stored.
+	async fn login(
 		&self,
 		name: String,
 		password: String,
 		user_agent: String,
+		country: Option<String>,
 	) -> Result<(Navigator, Session), NavigatorServiceError> {
 		// Authenticate the navigator.
-		let navigator = self
+		let (navigator, matched) = self
 			.repository
 			.authenticate(&name, &password)
 			.await
 			.map_err(NavigatorServiceError::Insert)?
 			.ok_or(NavigatorServiceError::InvalidCredentials)?;
 
+		// Upgrade the password hash if it was made with outdated parameters.
+		let navigator = self
+			.rehash_if_outdated(navigator, &password, matched)
+			.await?;
+
 		// Create a new session.
 		let session = Session::new(*navigator.nutty_id(), user_agent, chrono::Duration::days(1))
-			.map_err(NavigatorServiceError::CreateSession)?;
+			.map_err(NavigatorServiceError::CreateSession)?
+			.with_country(country);
 
 		// Save the session.
 		let session = self
-			.repository
+			.session_store
 			.create_session(session)
 			.await
-			.map_err(NavigatorServiceError::Insert)?;
+			.map_err(NavigatorServiceError::SessionStore)?;
+
+		// Make room for it, evicting the oldest sessions, unless the session
+		// is the only write allowed right now.
+		if !self.read_only.is_enabled() {
+			self.evict_sessions_over_limit(navigator.nutty_id()).await?;
+		}
 
 		Ok((navigator, session))
 	}
 
-	/// Logout a navigator by deleting their session.
-	pub async fn logout(&self, session_id: &NuttyId) -> Result<(), NavigatorServiceError> {
+	/// Get the password hash versions seen on successful logins.
+	fn password_hash_metrics(&self) -> PasswordHashMetrics {
 		self
+			.hash_metrics
+			.lock()
+			.expect("Hash metrics lock poisoned")
+			.clone()
+	}
+
+	/// Rename a navigator, recording the old name for auditing.
+	/// Sessions resolve their navigator on every request, so they see the new
+	/// name immediately.
+	async fn rename(
+		&self,
+		navigator_id: &NuttyId,
+		new_name: String,
+	) -> Result<Navigator, NavigatorServiceError> {
+		self
+			.repository
+			.with_transaction(|tx| {
+				let new_name = new_name.clone();
+
+				Box::pin(async move {
+					// Get the navigator.
+					let mut navigator = self
+						.repository
+						.get_navigator_by_id_tx(tx.conn(), navigator_id)
+						.await
+						.map_err(NavigatorServiceError::UpdateNavigator)?
+						.ok_or(NavigatorServiceError::NavigatorNotFound)?;
+
+					let old_name = navigator.name().to_string();
+
+					if old_name == new_name {
+						return Ok(navigator);
+					}
+
+					navigator
+						.update_name(&new_name)
+						.map_err(NavigatorServiceError::InvalidName)?;
+
+					// Save the new name, which fails if it's taken.
+					let navigator = self
+						.repository
+						.update_navigator_tx(tx.conn(), navigator)
+						.await
+						.map_err(NavigatorServiceError::UpdateNavigator)?;
+
+					// Record the rename.
+					self
+						.repository
+						.record_rename_tx(tx.conn(), navigator_id, &old_name, &new_name)
+						.await
+						.map_err(NavigatorServiceError::UpdateNavigator)?;
+
+					Ok(navigator)
+				})
+			})
+			.await
+	}
+
+	/// Logout a navigator by deleting their session.
+	async fn logout(&self, session_id: &NuttyId) -> Result<(), NavigatorServiceError> {
+		let session = self
 			.repository
-			.delete_session(session_id)
+			.get_session_by_id(session_id)
 			.await
-			.map_err(NavigatorServiceError::DeleteSession)
+			.map_err(NavigatorServiceError::DeleteSession)?;
+
+		match session {
+			Some(session) => self
+				.session_store
+				.delete_session(&session)
+				.await
+				.map_err(NavigatorServiceError::SessionStore),
+			None => Ok(()),
+		}
 	}
 
 	/// Get a navigator by ID.
-	pub async fn get_navigator_by_id(
+	async fn get_navigator_by_id(
 		&self,
 		id: &NuttyId,
 	) -> Result<Option<Navigator>, NavigatorServiceError> {
@@ -83,8 +650,20 @@ impl NavigatorService {
 			.map_err(NavigatorServiceError::Insert)
 	}
 
+	/// Get a navigator by name.
+	async fn get_navigator_by_name(
+		&self,
+		name: &str,
+	) -> Result<Option<Navigator>, NavigatorServiceError> {
+		self
+			.repository
+			.get_navigator_by_name(name)
+			.await
+			.map_err(NavigatorServiceError::Insert)
+	}
+
 	/// Get a session by ID.
-	pub async fn get_session_by_id(
+	async fn get_session_by_id(
 		&self,
 		id: &NuttyId,
 	) -> Result<Option<Session>, NavigatorServiceError> {
@@ -94,6 +673,280 @@ impl NavigatorService {
 			.await
 			.map_err(NavigatorServiceError::Insert)
 	}
+
+	/// List a navigator's sessions, most recent first.
+	async fn list_sessions(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<Session>, NavigatorServiceError> {
+		self
+			.repository
+			.list_sessions_by_navigator_id(navigator_id)
+			.await
+			.map_err(NavigatorServiceError::Insert)
+	}
+
+	/// Name one of a navigator's sessions (e.g., "work laptop").
+	/// A blank label clears it.
+	async fn label_session(
+		&self,
+		id: &NuttyId,
+		navigator_id: &NuttyId,
+		label: Option<String>,
+	) -> Result<Session, NavigatorServiceError> {
+		let label = normalize_session_label(label)?;
+
+		let session = self
+			.repository
+			.update_session_label(id, navigator_id, label.as_deref())
+			.await
+			.map_err(NavigatorServiceError::Insert)?
+			.ok_or(NavigatorServiceError::SessionNotFound)?;
+
+		self
+			.session_store
+			.evict_session(&session)
+			.await
+			.map_err(NavigatorServiceError::SessionStore)?;
+
+		Ok(session)
+	}
+
+	/// Get a session by its token.
+	async fn get_session_by_token(
+		&self,
+		token: &SessionToken,
+	) -> Result<Option<Session>, NavigatorServiceError> {
+		self
+			.session_store
+			.get_session_by_token_hash(&token.hash())
+			.await
+			.map_err(NavigatorServiceError::SessionStore)
+	}
+
+	/// Check if the session with a token was evicted by a newer login, to
+	/// tell its device why it was signed out.
+	async fn is_session_evicted(&self, token: &SessionToken) -> Result<bool, NavigatorServiceError> {
+		self
+			.repository
+			.is_session_evicted(&token.hash())
+			.await
+			.map_err(NavigatorServiceError::EvictSessions)
+	}
+
+	/// Request an archive of a navigator's personal data. It's assembled in
+	/// the background, so poll the export until it's ready.
+	async fn request_export(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<NavigatorExport, NavigatorServiceError> {
+		let export = NavigatorExport::new(*navigator_id, chrono::Duration::hours(EXPORT_TTL_HOURS));
+
+		let export = self
+			.repository
+			.create_export(&export)
+			.await
+			.map_err(NavigatorServiceError::Export)?;
+
+		let service = self.clone();
+		let pending = export.clone();
+		tokio::spawn(async move { service.run_export(pending).await });
+
+		Ok(export)
+	}
+
+	/// Get one of a navigator's exports.
+	async fn get_export(
+		&self,
+		id: &NuttyId,
+		navigator_id: &NuttyId,
+	) -> Result<Option<NavigatorExport>, NavigatorServiceError> {
+		let export = self
+			.repository
+			.get_export(id)
+			.await
+			.map_err(NavigatorServiceError::Export)?;
+
+		Ok(export.filter(|export| export.navigator_id() == navigator_id))
+	}
+
+	/// Get an export and its archive, unless it isn't ready or has expired.
+	async fn get_export_archive(
+		&self,
+		id: &NuttyId,
+	) -> Result<Option<(NavigatorExport, serde_json::Value)>, NavigatorServiceError> {
+		let export = self
+			.repository
+			.get_export(id)
+			.await
+			.map_err(NavigatorServiceError::Export)?;
+
+		let Some(export) =
+			export.filter(|export| export.status() == ExportStatus::Ready && !export.is_expired())
+		else {
+			return Ok(None);
+		};
+
+		let archive = self
+			.repository
+			.get_export_archive(id)
+			.await
+			.map_err(NavigatorServiceError::Export)?;
+
+		Ok(archive.map(|archive| (export, archive)))
+	}
+
+	/// Block a navigator by name.
+	async fn block_navigator(
+		&self,
+		blocker_id: &NuttyId,
+		name: &str,
+	) -> Result<(), NavigatorServiceError> {
+		let blocked = self
+			.get_navigator_by_name(name)
+			.await?
+			.ok_or(NavigatorServiceError::NavigatorNotFound)?;
+
+		if blocked.nutty_id() == blocker_id {
+			return Err(NavigatorServiceError::BlockSelf);
+		}
+
+		self
+			.repository
+			.block_navigator(blocker_id, blocked.nutty_id())
+			.await
+			.map_err(NavigatorServiceError::Block)
+	}
+
+	/// Unblock a navigator by name.
+	async fn unblock_navigator(
+		&self,
+		blocker_id: &NuttyId,
+		name: &str,
+	) -> Result<(), NavigatorServiceError> {
+		let blocked = self
+			.get_navigator_by_name(name)
+			.await?
+			.ok_or(NavigatorServiceError::NavigatorNotFound)?;
+
+		let was_blocked = self
+			.repository
+			.unblock_navigator(blocker_id, blocked.nutty_id())
+			.await
+			.map_err(NavigatorServiceError::Block)?;
+
+		match was_blocked {
+			true => Ok(()),
+			false => Err(NavigatorServiceError::NavigatorNotFound),
+		}
+	}
+
+	/// List the navigators that a navigator has blocked.
+	async fn list_blocked_navigators(
+		&self,
+		blocker_id: &NuttyId,
+	) -> Result<Vec<BlockedNavigator>, NavigatorServiceError> {
+		self
+			.repository
+			.list_blocked_navigators(blocker_id)
+			.await
+			.map_err(NavigatorServiceError::Block)
+	}
+
+	/// Give a navigator a copy of the starter content.
+	async fn provision_starter_content(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<ContentBlock>, NavigatorServiceError> {
+		let onboarding = self
+			.onboarding
+			.as_ref()
+			.ok_or(NavigatorServiceError::OnboardingDisabled)?;
+
+		onboarding.provision(navigator_id).await
+	}
+
+	/// List every onboarding step, with when the navigator completed it.
+	async fn list_onboarding_steps(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<OnboardingProgress>, NavigatorServiceError> {
+		let completed = self
+			.repository
+			.list_onboarding_steps(navigator_id)
+			.await
+			.map_err(NavigatorServiceError::Onboarding)?;
+
+		Ok(OnboardingProgress::list(&completed))
+	}
+
+	/// Mark an onboarding step complete.
+	async fn complete_onboarding_step(
+		&self,
+		navigator_id: &NuttyId,
+		step: OnboardingStep,
+	) -> Result<Vec<OnboardingProgress>, NavigatorServiceError> {
+		self
+			.repository
+			.complete_onboarding_step(navigator_id, step)
+			.await
+			.map_err(NavigatorServiceError::Onboarding)?;
+
+		self.list_onboarding_steps(navigator_id).await
+	}
+
+	/// Get the version of a navigator's calendar feed token.
+	async fn get_feed_token_version(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Option<i32>, NavigatorServiceError> {
+		self
+			.repository
+			.get_feed_token_version(navigator_id)
+			.await
+			.map_err(NavigatorServiceError::FeedToken)
+	}
+
+	/// Regenerate a navigator's calendar feed token.
+	async fn regenerate_feed_token(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<i32, NavigatorServiceError> {
+		self
+			.repository
+			.bump_feed_token_version(navigator_id)
+			.await
+			.map_err(NavigatorServiceError::FeedToken)?
+			.ok_or(NavigatorServiceError::NavigatorNotFound)
+	}
+
+	/// Record the requests that navigators made since the last time.
+	async fn record_request_usage(
+		&self,
+		usage: &[(NuttyId, RequestUsage)],
+	) -> Result<(), NavigatorServiceError> {
+		self
+			.repository
+			.insert_request_usage(usage)
+			.await
+			.map_err(NavigatorServiceError::RequestUsage)
+	}
+
+	/// Rank the navigators that made requests since a time.
+	async fn rank_request_usage(
+		&self,
+		since: &DateTime<Utc>,
+		sort: UsageSort,
+		limit: usize,
+	) -> Result<Vec<NavigatorUsage>, NavigatorServiceError> {
+		let usage = self
+			.repository
+			.list_request_usage(since)
+			.await
+			.map_err(NavigatorServiceError::RequestUsage)?;
+
+		Ok(NavigatorUsage::rank(usage, sort, limit))
+	}
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -112,15 +965,90 @@ pub enum NavigatorServiceError {
 
 	#[error("Failed to delete session: {0}")]
 	DeleteSession(#[source] NavigatorRepositoryError),
+
+	#[error("Failed to access sessions: {0}")]
+	SessionStore(#[source] SessionStoreError),
+
+	#[error("Failed to evict sessions: {0}")]
+	EvictSessions(#[source] NavigatorRepositoryError),
+
+	#[error("Session not found")]
+	SessionNotFound,
+
+	#[error("Session labels must be at most {MAX_SESSION_LABEL_LENGTH} characters")]
+	InvalidSessionLabel,
+
+	#[error("Failed to rehash password: {0}")]
+	Rehash(#[source] NavigatorError),
+
+	#[error("Failed to update navigator: {0}")]
+	UpdateNavigator(#[source] NavigatorRepositoryError),
+
+	#[error("Invalid navigator name: {0}")]
+	InvalidName(#[source] NavigatorError),
+
+	#[error("Navigator not found")]
+	NavigatorNotFound,
+
+	#[error("Failed to save export: {0}")]
+	Export(#[source] NavigatorRepositoryError),
+
+	#[error("Failed to assemble export: {0}")]
+	AssembleExport(#[source] NavigatorRepositoryError),
+
+	#[error("Failed to serialize export: {0}")]
+	SerializeExport(#[source] serde_json::Error),
+
+	#[error("Failed to access blocked navigators: {0}")]
+	Block(#[source] NavigatorRepositoryError),
+
+	#[error("Navigators can't block themselves")]
+	BlockSelf,
+
+	#[error("Onboarding is disabled")]
+	OnboardingDisabled,
+
+	#[error("Failed to grant the starter role: {0}")]
+	GrantStarterRole(#[source] AccessServiceError),
+
+	#[error("Failed to provision starter content: {0}")]
+	ProvisionStarterContent(#[source] ContentServiceError),
+
+	#[error("Failed to access onboarding steps: {0}")]
+	Onboarding(#[source] NavigatorRepositoryError),
+
+	#[error("Failed to access request usage: {0}")]
+	RequestUsage(#[source] NavigatorRepositoryError),
+
+	#[error("Failed to access calendar feed token: {0}")]
+	FeedToken(#[source] NavigatorRepositoryError),
+
+	#[error("Database error: {0}")]
+	Database(#[from] sqlx::Error),
 }
 
 #[cfg(test)]
 mod tests {
+	use argon2::Algorithm;
+	use argon2::Argon2;
+	use argon2::Params;
+	use argon2::Version;
+	use argon2::password_hash::PasswordHasher;
+	use argon2::password_hash::SaltString;
+	use argon2::password_hash::rand_core::OsRng;
 	use sqlx::Pool;
 	use sqlx::Postgres;
 	use sqlx::postgres::PgPoolOptions;
 
 	use super::*;
+	use crate::access::repository::AccessRepository;
+	use crate::access::service::AccessService;
+	use crate::content::repository::ContentRepository;
+	use crate::content::service::ContentService;
+	use crate::content::service::ContentServiceApi;
+	use crate::models::BlockContent;
+	use crate::models::content_filter::ContentFilter;
+	use crate::models::request_usage::RequestUsage;
 
 	async fn connect_to_test_database() -> Pool<Postgres> {
 		let database_url = std::env::var("DATABASE_URL").unwrap();
@@ -200,8 +1128,8 @@ mod tests {
 		// Assert: Verify the error.
 		assert!(result_2.is_err());
 		match result_2.unwrap_err() {
-			NavigatorServiceError::Insert(NavigatorRepositoryError::QueryFailed(_)) => (),
-			_ => panic!("Expected QueryFailed error for duplicate name"),
+			NavigatorServiceError::Insert(NavigatorRepositoryError::NameTaken) => (),
+			_ => panic!("Expected NameTaken error for duplicate name"),
 		}
 
 		// Cleanup: Delete the test navigator.
@@ -218,29 +1146,196 @@ mod tests {
 		let repo = NavigatorRepository::new(pool);
 		let service = NavigatorService::new(repo.clone());
 
-		// Register a test navigator.
-		let navigator = service
-			.register("login_test".to_string(), "password123".to_string())
+		// Register a test navigator.
+		let navigator = service
+			.register("login_test".to_string(), "password123".to_string())
+			.await
+			.expect("Failed to register test navigator");
+
+		// Act: Login with correct credentials.
+		let result = service
+			.login(
+				"login_test".to_string(),
+				"password123".to_string(),
+				"test-agent".to_string(),
+				None,
+			)
+			.await;
+
+		// Assert: Verify the login was successful.
+		assert!(result.is_ok());
+		let (logged_in_navigator, session) = result.unwrap();
+		assert_eq!(logged_in_navigator.nutty_id(), navigator.nutty_id());
+		assert_eq!(session.user_agent(), "test-agent");
+		assert!(!session.is_expired());
+
+		// Cleanup: Delete the test navigator.
+		repo
+			.delete_navigator(navigator.nutty_id())
+			.await
+			.expect("Failed to delete test navigator");
+	}
+
+	#[tokio::test]
+	async fn test_login_rehashes_outdated_password() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = NavigatorRepository::new(pool);
+		let service = NavigatorService::new(repo.clone());
+
+		// Arrange: Insert a navigator whose hash uses weaker parameters.
+		let weak_params = Params::new(8 * 1024, 1, 1, None).unwrap();
+		let weak_hash = Argon2::new(Algorithm::Argon2id, Version::V0x13, weak_params)
+			.hash_password(b"password123", &SaltString::generate(&mut OsRng))
+			.unwrap()
+			.to_string();
+
+		let template = Navigator::new("rehash_test".to_string(), "unused").unwrap();
+
+		let navigator = Navigator::builder()
+			.nutty_id(*template.nutty_id())
+			.name("rehash_test".to_string())
+			.password_hash(weak_hash.clone())
+			.created_at(*template.created_at())
+			.updated_at(*template.updated_at())
+			.try_build()
+			.unwrap();
+
+		let navigator = repo
+			.create_navigator(navigator)
+			.await
+			.expect("Failed to create test navigator");
+
+		assert!(navigator.needs_rehash());
+
+		// Act: Login with correct credentials.
+		let (logged_in_navigator, _) = service
+			.login(
+				"rehash_test".to_string(),
+				"password123".to_string(),
+				"test-agent".to_string(),
+				None,
+			)
+			.await
+			.expect("Failed to login");
+
+		// Assert: The stored hash was upgraded and still verifies.
+		let stored = repo
+			.get_navigator_by_id(navigator.nutty_id())
+			.await
+			.expect("Failed to get navigator")
+			.expect("Navigator not found");
+
+		assert_ne!(stored.pass(), weak_hash);
+		assert_eq!(stored.pass(), logged_in_navigator.pass());
+		assert!(!stored.needs_rehash());
+		assert!(stored.verify_password("password123"));
+
+		// Assert: The login was counted under the old hash version.
+		let metrics = service.password_hash_metrics();
+		assert_eq!(metrics.rehashes, 1);
+		assert_eq!(
+			metrics
+				.logins_by_hash_version
+				.get("argon2id-v19-m8192-t1-p1"),
+			Some(&1)
+		);
+
+		// Cleanup: Delete the test navigator.
+		repo
+			.delete_navigator(navigator.nutty_id())
+			.await
+			.expect("Failed to delete test navigator");
+	}
+
+	#[tokio::test]
+	async fn test_login_while_read_only() {
+		// Arrange: Create a service allowing a single session, in read-only mode.
+		let pool = connect_to_test_database().await;
+		let repo = NavigatorRepository::new(pool);
+		let read_only = ReadOnlyMode::new(true, 0);
+		let service = NavigatorService::new(repo.clone())
+			.with_session_limit(SessionLimit::single())
+			.with_read_only(read_only.clone());
+
+		// Arrange: Insert a navigator whose hash uses weaker parameters.
+		let weak_params = Params::new(8 * 1024, 1, 1, None).unwrap();
+		let weak_hash = Argon2::new(Algorithm::Argon2id, Version::V0x13, weak_params)
+			.hash_password(b"password123", &SaltString::generate(&mut OsRng))
+			.unwrap()
+			.to_string();
+
+		let template = Navigator::new("read_only_login".to_string(), "unused").unwrap();
+
+		let navigator = Navigator::builder()
+			.nutty_id(*template.nutty_id())
+			.name("read_only_login".to_string())
+			.password_hash(weak_hash.clone())
+			.created_at(*template.created_at())
+			.updated_at(*template.updated_at())
+			.try_build()
+			.unwrap();
+
+		let navigator = repo
+			.create_navigator(navigator)
+			.await
+			.expect("Failed to create test navigator");
+
+		let login = || async {
+			service
+				.login(
+					"read_only_login".to_string(),
+					"password123".to_string(),
+					"test-agent".to_string(),
+					None,
+				)
+				.await
+				.expect("Failed to login")
+		};
+
+		// Act: Log in twice.
+		login().await;
+		let (_, second) = login().await;
+
+		// Assert: Both sessions were created, but nothing else was written.
+		let stored = repo
+			.get_navigator_by_id(navigator.nutty_id())
+			.await
+			.expect("Failed to get navigator")
+			.expect("Navigator not found");
+
+		let sessions = service
+			.list_sessions(navigator.nutty_id())
 			.await
-			.expect("Failed to register test navigator");
+			.expect("Failed to list sessions");
 
-		// Act: Login with correct credentials.
-		let result = service
-			.login(
-				"login_test".to_string(),
-				"password123".to_string(),
-				"test-agent".to_string(),
-			)
-			.await;
+		assert_eq!(stored.pass(), weak_hash);
+		assert_eq!(sessions.len(), 2);
+		assert_eq!(service.password_hash_metrics().rehashes, 0);
 
-		// Assert: Verify the login was successful.
-		assert!(result.is_ok());
-		let (logged_in_navigator, session) = result.unwrap();
-		assert_eq!(logged_in_navigator.nutty_id(), navigator.nutty_id());
-		assert_eq!(session.user_agent(), "test-agent");
-		assert!(!session.is_expired());
+		// Act: Switch read-only mode off, and log in again.
+		read_only.set_enabled(false);
+		login().await;
 
-		// Cleanup: Delete the test navigator.
+		// Assert: The password was rehashed and the older sessions evicted.
+		let stored = repo
+			.get_navigator_by_id(navigator.nutty_id())
+			.await
+			.expect("Failed to get navigator")
+			.expect("Navigator not found");
+
+		let sessions = service
+			.list_sessions(navigator.nutty_id())
+			.await
+			.expect("Failed to list sessions");
+
+		let token = second.token().expect("Missing session token");
+
+		assert_ne!(stored.pass(), weak_hash);
+		assert_eq!(sessions.len(), 1);
+		assert!(service.is_session_evicted(token).await.unwrap());
+
+		// Cleanup: Delete the test navigator, and their sessions with them.
 		repo
 			.delete_navigator(navigator.nutty_id())
 			.await
@@ -266,6 +1361,7 @@ mod tests {
 				"invalid_test".to_string(),
 				"wrong_password".to_string(),
 				"test-agent".to_string(),
+				None,
 			)
 			.await;
 
@@ -296,6 +1392,7 @@ mod tests {
 				"nonexistent".to_string(),
 				"password123".to_string(),
 				"test-agent".to_string(),
+				None,
 			)
 			.await;
 
@@ -362,6 +1459,62 @@ mod tests {
 			.expect("Failed to delete navigator");
 	}
 
+	#[tokio::test]
+	async fn test_regenerate_feed_token() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = NavigatorRepository::new(pool);
+		let service = NavigatorService::new(repo.clone());
+
+		let navigator = service
+			.register("feed_token_test".to_string(), "password123".to_string())
+			.await
+			.expect("Failed to register test navigator");
+
+		// Assert: New navigators start at the first version.
+		let version = service
+			.get_feed_token_version(navigator.nutty_id())
+			.await
+			.expect("Failed to get feed token version");
+
+		assert_eq!(version, Some(0));
+
+		// Act: Regenerate the token.
+		let regenerated = service
+			.regenerate_feed_token(navigator.nutty_id())
+			.await
+			.expect("Failed to regenerate feed token");
+
+		// Assert: The version is bumped.
+		let version = service
+			.get_feed_token_version(navigator.nutty_id())
+			.await
+			.expect("Failed to get feed token version");
+
+		assert_eq!(regenerated, 1);
+		assert_eq!(version, Some(1));
+
+		// Cleanup: Delete the test navigator.
+		repo
+			.delete_navigator(navigator.nutty_id())
+			.await
+			.expect("Failed to delete navigator");
+
+		// Assert: Deleted navigators have no version, nor can regenerate one.
+		let version = service
+			.get_feed_token_version(navigator.nutty_id())
+			.await
+			.expect("Failed to get feed token version");
+
+		assert_eq!(version, None);
+
+		let result = service.regenerate_feed_token(navigator.nutty_id()).await;
+		assert!(matches!(
+			result,
+			Err(NavigatorServiceError::NavigatorNotFound)
+		));
+	}
+
 	#[tokio::test]
 	async fn test_get_navigator_by_id() {
 		// Arrange: Create a repository and service.
@@ -470,4 +1623,561 @@ mod tests {
 			.await
 			.expect("Failed to delete test navigator");
 	}
+
+	#[tokio::test]
+	async fn test_list_and_label_sessions() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = NavigatorRepository::new(pool);
+		let service = NavigatorService::new(repo.clone());
+
+		// Arrange: Register two navigators.
+		let navigator = service
+			.register("label_test".to_string(), "password123".to_string())
+			.await
+			.expect("Failed to register test navigator");
+
+		let other = service
+			.register("label_test_other".to_string(), "password123".to_string())
+			.await
+			.expect("Failed to register other navigator");
+
+		// Act: Log in from Firefox on Linux, with a country.
+		let user_agent = "Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0";
+		let (_, session) = service
+			.login(
+				"label_test".to_string(),
+				"password123".to_string(),
+				user_agent.to_string(),
+				Some("DE".to_string()),
+			)
+			.await
+			.expect("Failed to login");
+
+		// Assert: The session is listed with its parsed device.
+		let sessions = service
+			.list_sessions(navigator.nutty_id())
+			.await
+			.expect("Failed to list sessions");
+
+		assert_eq!(sessions.len(), 1);
+		assert_eq!(sessions[0].device().summary(), "Firefox on Linux");
+		assert_eq!(sessions[0].device().device(), "Desktop");
+		assert_eq!(sessions[0].country(), Some("DE"));
+		assert_eq!(sessions[0].label(), None);
+
+		// Act: Label the session.
+		let labeled = service
+			.label_session(
+				session.nutty_id(),
+				navigator.nutty_id(),
+				Some("  work laptop ".to_string()),
+			)
+			.await
+			.expect("Failed to label session");
+
+		// Assert: The label was trimmed and saved.
+		assert_eq!(labeled.label(), Some("work laptop"));
+
+		// Assert: Other navigators can't label the session.
+		let result = service
+			.label_session(
+				session.nutty_id(),
+				other.nutty_id(),
+				Some("mine now".to_string()),
+			)
+			.await;
+
+		assert!(matches!(
+			result,
+			Err(NavigatorServiceError::SessionNotFound)
+		));
+
+		// Assert: Overly long labels are rejected.
+		let result = service
+			.label_session(
+				session.nutty_id(),
+				navigator.nutty_id(),
+				Some("x".repeat(MAX_SESSION_LABEL_LENGTH + 1)),
+			)
+			.await;
+
+		assert!(matches!(
+			result,
+			Err(NavigatorServiceError::InvalidSessionLabel)
+		));
+
+		// Act: Clear the label with a blank one.
+		let cleared = service
+			.label_session(
+				session.nutty_id(),
+				navigator.nutty_id(),
+				Some(" ".to_string()),
+			)
+			.await
+			.expect("Failed to clear label");
+
+		// Assert: The label was cleared.
+		assert_eq!(cleared.label(), None);
+
+		// Cleanup: Delete the test navigators.
+		for navigator in [navigator, other] {
+			repo
+				.delete_navigator(navigator.nutty_id())
+				.await
+				.expect("Failed to delete test navigator");
+		}
+	}
+
+	#[tokio::test]
+	async fn test_rename() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = NavigatorRepository::new(pool.clone());
+		let service = NavigatorService::new(repo.clone());
+
+		// Arrange: Register two navigators.
+		let navigator = service
+			.register("rename_test".to_string(), "password123".to_string())
+			.await
+			.expect("Failed to register test navigator");
+
+		let other = service
+			.register("rename_taken".to_string(), "password123".to_string())
+			.await
+			.expect("Failed to register other navigator");
+
+		// Act: Rename the navigator.
+		let renamed = service
+			.rename(navigator.nutty_id(), "rename_done".to_string())
+			.await
+			.expect("Failed to rename navigator");
+
+		// Assert: The navigator can log in with its new name.
+		assert_eq!(renamed.name(), "rename_done");
+		assert!(
+			repo
+				.authenticate("rename_done", "password123")
+				.await
+				.expect("Failed to authenticate")
+				.is_some()
+		);
+
+		// Assert: The rename was recorded.
+		let recorded: (String, String) = sqlx::query_as(
+			"SELECT old_name, new_name FROM auth.navigator_renames WHERE navigator_id = $1",
+		)
+		.bind(navigator.nutty_id().uuid())
+		.fetch_one(&pool)
+		.await
+		.expect("Failed to fetch rename");
+
+		assert_eq!(
+			recorded,
+			("rename_test".to_string(), "rename_done".to_string())
+		);
+
+		// Assert: Taken and invalid names are rejected.
+		let result = service
+			.rename(navigator.nutty_id(), "rename_taken".to_string())
+			.await;
+
+		assert!(matches!(
+			result,
+			Err(NavigatorServiceError::UpdateNavigator(
+				NavigatorRepositoryError::NameTaken
+			))
+		));
+
+		let result = service
+			.rename(navigator.nutty_id(), "Not Valid!".to_string())
+			.await;
+
+		assert!(matches!(result, Err(NavigatorServiceError::InvalidName(_))));
+
+		// Cleanup: Delete the test navigators.
+		for navigator in [navigator, other] {
+			repo
+				.delete_navigator(navigator.nutty_id())
+				.await
+				.expect("Failed to delete test navigator");
+		}
+	}
+
+	#[tokio::test]
+	async fn test_block_navigator() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = NavigatorRepository::new(pool);
+		let service = NavigatorService::new(repo.clone());
+
+		// Arrange: Register two navigators.
+		let navigator = service
+			.register("block_test".to_string(), "password123".to_string())
+			.await
+			.expect("Failed to register test navigator");
+
+		let other = service
+			.register("block_target".to_string(), "password123".to_string())
+			.await
+			.expect("Failed to register other navigator");
+
+		// Act: Block the other navigator, twice.
+		for _ in 0..2 {
+			service
+				.block_navigator(navigator.nutty_id(), "block_target")
+				.await
+				.expect("Failed to block navigator");
+		}
+
+		// Assert: They're blocked once.
+		let blocked = service
+			.list_blocked_navigators(navigator.nutty_id())
+			.await
+			.expect("Failed to list blocked navigators");
+
+		assert_eq!(blocked.len(), 1);
+		assert_eq!(blocked[0].navigator_id, *other.nutty_id());
+		assert_eq!(blocked[0].name, "block_target");
+
+		// Assert: Navigators can't block themselves, or nobody.
+		let result = service
+			.block_navigator(navigator.nutty_id(), "block_test")
+			.await;
+
+		assert!(matches!(result, Err(NavigatorServiceError::BlockSelf)));
+
+		let result = service
+			.block_navigator(navigator.nutty_id(), "block_nobody")
+			.await;
+
+		assert!(matches!(
+			result,
+			Err(NavigatorServiceError::NavigatorNotFound)
+		));
+
+		// Act: Unblock them.
+		service
+			.unblock_navigator(navigator.nutty_id(), "block_target")
+			.await
+			.expect("Failed to unblock navigator");
+
+		// Assert: They're no longer blocked, so can't be unblocked again.
+		let result = service
+			.unblock_navigator(navigator.nutty_id(), "block_target")
+			.await;
+
+		assert!(matches!(
+			result,
+			Err(NavigatorServiceError::NavigatorNotFound)
+		));
+
+		// Cleanup: Delete the test navigators.
+		for navigator in [navigator, other] {
+			repo
+				.delete_navigator(navigator.nutty_id())
+				.await
+				.expect("Failed to delete test navigator");
+		}
+	}
+
+	#[tokio::test]
+	async fn test_request_export() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = NavigatorRepository::new(pool);
+		let service = NavigatorService::new(repo.clone());
+
+		// Arrange: Register a navigator.
+		let navigator = service
+			.register("export_test".to_string(), "password123".to_string())
+			.await
+			.expect("Failed to register test navigator");
+
+		// Act: Request an export, and wait for it to be assembled.
+		let export = service
+			.request_export(navigator.nutty_id())
+			.await
+			.expect("Failed to request export");
+
+		assert_eq!(export.status(), ExportStatus::Pending);
+
+		let mut ready = None;
+
+		for _ in 0..50 {
+			let polled = service
+				.get_export(export.nutty_id(), navigator.nutty_id())
+				.await
+				.expect("Failed to get export")
+				.expect("Export not found");
+
+			if polled.status() != ExportStatus::Pending {
+				ready = Some(polled);
+				break;
+			}
+
+			tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+		}
+
+		let ready = ready.expect("Export was never assembled");
+		assert_eq!(ready.status(), ExportStatus::Ready);
+
+		// Assert: The archive holds the navigator, but not their secrets.
+		let (_, archive) = service
+			.get_export_archive(export.nutty_id())
+			.await
+			.expect("Failed to get export archive")
+			.expect("Export archive not found");
+
+		assert_eq!(archive["navigator"]["name"], "export_test");
+		assert!(archive["navigator"].get("pass").is_none());
+		assert!(archive["sessions"].is_array());
+
+		// Assert: Other navigators can't see the export.
+		let hidden = service
+			.get_export(export.nutty_id(), &NuttyId::now())
+			.await
+			.expect("Failed to get export");
+
+		assert!(hidden.is_none());
+
+		// Cleanup: Delete the test navigator, and its export with it.
+		repo
+			.delete_navigator(navigator.nutty_id())
+			.await
+			.expect("Failed to delete test navigator");
+	}
+
+	#[tokio::test]
+	async fn test_provision_starter_content() {
+		// Arrange: Create a service that onboards new navigators.
+		let pool = connect_to_test_database().await;
+		let repo = NavigatorRepository::new(pool.clone());
+		let access_service = AccessService::new(AccessRepository::new(pool.clone()));
+		let content_service =
+			ContentService::new(ContentRepository::new(pool.clone()), access_service.clone());
+		let service = NavigatorService::new(repo.clone()).with_onboarding(Onboarding::new(
+			Arc::new(content_service.clone()),
+			Arc::new(access_service),
+		));
+
+		// Act: Register a navigator.
+		let navigator = service
+			.register("onboarding_test".to_string(), "password123".to_string())
+			.await
+			.expect("Failed to register test navigator");
+
+		// Assert: They own a welcome page, and can read it.
+		let blocks = content_service
+			.list_content_blocks(
+				navigator.nutty_id(),
+				&ContentFilter {
+					kinds: vec!["Page".to_string()],
+					owner_id: Some(*navigator.nutty_id()),
+					include_archived: false,
+				},
+			)
+			.await
+			.expect("Failed to list content blocks");
+
+		assert_eq!(blocks.len(), 1);
+		assert!(matches!(
+			&blocks[0].content,
+			BlockContent::Page { title } if title == "Welcome to the Nuttyverse"
+		));
+
+		// Act: Complete a step, twice.
+		for _ in 0..2 {
+			service
+				.complete_onboarding_step(navigator.nutty_id(), OnboardingStep::ReadWelcome)
+				.await
+				.expect("Failed to complete onboarding step");
+		}
+
+		// Assert: Every step is listed, and only the completed one has a time.
+		let steps = service
+			.list_onboarding_steps(navigator.nutty_id())
+			.await
+			.expect("Failed to list onboarding steps");
+
+		assert_eq!(steps.len(), OnboardingStep::ALL.len());
+		assert_eq!(steps[0].step, OnboardingStep::ReadWelcome);
+		assert!(steps[0].completed_at.is_some());
+		assert!(steps[1..].iter().all(|step| step.completed_at.is_none()));
+
+		// Assert: Without onboarding, there's nothing to provision.
+		let result = NavigatorService::new(repo.clone())
+			.provision_starter_content(navigator.nutty_id())
+			.await;
+
+		assert!(matches!(
+			result,
+			Err(NavigatorServiceError::OnboardingDisabled)
+		));
+
+		// Cleanup: Delete the starter content and the test navigator.
+		sqlx::query!(
+			"DELETE FROM content.blocks WHERE owner_id = $1",
+			navigator.nutty_id().uuid(),
+		)
+		.execute(&pool)
+		.await
+		.expect("Failed to delete starter content");
+
+		repo
+			.delete_navigator(navigator.nutty_id())
+			.await
+			.expect("Failed to delete test navigator");
+	}
+
+	#[tokio::test]
+	async fn test_rank_request_usage() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let repo = NavigatorRepository::new(pool);
+		let service = NavigatorService::new(repo.clone());
+
+		// Arrange: Register two navigators.
+		let busy = service
+			.register("usage_busy".to_string(), "password123".to_string())
+			.await
+			.expect("Failed to register test navigator");
+
+		let slow = service
+			.register("usage_slow".to_string(), "password123".to_string())
+			.await
+			.expect("Failed to register other navigator");
+
+		// Arrange: The busy navigator makes many fast requests, across two
+		// flushes; the slow one makes a single slow request.
+		let mut fast = RequestUsage::default();
+
+		for _ in 0..10 {
+			fast.record(std::time::Duration::from_millis(3), 10, 1000);
+		}
+
+		let mut slower = RequestUsage::default();
+		slower.record(std::time::Duration::from_millis(700), 10, 10);
+
+		// Act: Record the usage, including some by a navigator that's gone.
+		let since = Utc::now() - chrono::Duration::minutes(1);
+
+		for usage in [
+			vec![
+				(*busy.nutty_id(), fast.clone()),
+				(NuttyId::now(), fast.clone()),
+			],
+			vec![(*busy.nutty_id(), fast), (*slow.nutty_id(), slower)],
+		] {
+			service
+				.record_request_usage(&usage)
+				.await
+				.expect("Failed to record request usage");
+		}
+
+		// Assert: The flushes add up per navigator, heaviest first.
+		let ranked = |sort| {
+			let service = &service;
+
+			async move {
+				service
+					.rank_request_usage(&since, sort, 100)
+					.await
+					.expect("Failed to rank request usage")
+					.into_iter()
+					.filter(|usage| usage.name.starts_with("usage_"))
+					.collect::<Vec<_>>()
+			}
+		};
+
+		let by_requests = ranked(UsageSort::Requests).await;
+		assert_eq!(by_requests.len(), 2);
+		assert_eq!(by_requests[0].navigator_id, *busy.nutty_id());
+		assert_eq!(by_requests[0].request_count, 20);
+		assert_eq!(by_requests[0].p95_ms, 3.0);
+		assert_eq!(by_requests[0].response_bytes, 20_000);
+
+		let by_latency = ranked(UsageSort::Latency).await;
+		assert_eq!(by_latency[0].name, "usage_slow");
+		assert_eq!(by_latency[0].p95_ms, 700.0);
+
+		// Cleanup: Delete the test navigators, and their usage with them.
+		for navigator in [busy, slow] {
+			repo
+				.delete_navigator(navigator.nutty_id())
+				.await
+				.expect("Failed to delete test navigator");
+		}
+	}
+
+	#[tokio::test]
+	async fn test_session_limit() {
+		// Arrange: Create a repository and a service allowing two sessions.
+		let pool = connect_to_test_database().await;
+		let repo = NavigatorRepository::new(pool);
+		let service = NavigatorService::new(repo.clone()).with_session_limit(SessionLimit::new(2));
+
+		// Arrange: Register a navigator.
+		let navigator = service
+			.register("session_cap".to_string(), "password123".to_string())
+			.await
+			.expect("Failed to register test navigator");
+
+		let login = |service: &NavigatorService| {
+			let service = service.clone();
+
+			async move {
+				let (_, session) = service
+					.login(
+						"session_cap".to_string(),
+						"password123".to_string(),
+						"test-agent".to_string(),
+						None,
+					)
+					.await
+					.expect("Failed to login");
+
+				session.token().cloned().expect("Missing session token")
+			}
+		};
+
+		// Act: Log in three times.
+		let first = login(&service).await;
+		let second = login(&service).await;
+		let third = login(&service).await;
+
+		// Assert: The oldest session was evicted, and is known to be.
+		let sessions = service
+			.list_sessions(navigator.nutty_id())
+			.await
+			.expect("Failed to list sessions");
+
+		assert_eq!(sessions.len(), 2);
+
+		for (token, is_evicted) in [(&first, true), (&second, false), (&third, false)] {
+			let session = service.get_session_by_token(token).await.unwrap();
+			assert_eq!(session.is_none(), is_evicted);
+			assert_eq!(service.is_session_evicted(token).await.unwrap(), is_evicted);
+		}
+
+		// Act: Log in again, allowing a single session.
+		let single = NavigatorService::new(repo.clone()).with_session_limit(SessionLimit::single());
+		let fourth = login(&single).await;
+
+		// Assert: Every other session was evicted.
+		let sessions = service
+			.list_sessions(navigator.nutty_id())
+			.await
+			.expect("Failed to list sessions");
+
+		assert_eq!(sessions.len(), 1);
+		assert!(service.is_session_evicted(&third).await.unwrap());
+		assert!(!service.is_session_evicted(&fourth).await.unwrap());
+
+		// Cleanup: Delete the test navigator, and their sessions with them.
+		repo
+			.delete_navigator(navigator.nutty_id())
+			.await
+			.expect("Failed to delete test navigator");
+	}
 }
@@ -0,0 +1,106 @@
+use crate::models::session::Session;
+
+/// How many sessions a navigator may have at once, by default.
+pub const DEFAULT_MAX_SESSIONS: usize = 5;
+
+/// How many active sessions each navigator may have at once. Logging in
+/// beyond the limit evicts their oldest sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionLimit {
+	max_sessions: Option<usize>,
+}
+
+impl SessionLimit {
+	/// Allow up to the given number of sessions per navigator.
+	pub fn new(max_sessions: usize) -> Self {
+		Self {
+			max_sessions: Some(max_sessions.max(1)),
+		}
+	}
+
+	/// Allow a single session per navigator, so that logging in signs out
+	/// every other device.
+	pub fn single() -> Self {
+		Self::new(1)
+	}
+
+	/// Allow any number of sessions.
+	pub fn unlimited() -> Self {
+		Self { max_sessions: None }
+	}
+
+	/// Read the limit from `SESSION_SINGLE` ("1" or "true" for a single
+	/// session) or `MAX_SESSIONS_PER_NAVIGATOR` (0 for no limit), falling
+	/// back to the default.
+	pub fn from_env() -> Self {
+		if std::env::var("SESSION_SINGLE").is_ok_and(|v| v == "1" || v == "true") {
+			return Self::single();
+		}
+
+		match std::env::var("MAX_SESSIONS_PER_NAVIGATOR")
+			.ok()
+			.and_then(|v| v.parse().ok())
+		{
+			Some(0) => Self::unlimited(),
+			Some(max_sessions) => Self::new(max_sessions),
+			None => Self::default(),
+		}
+	}
+
+	/// Get the most sessions allowed per navigator, if limited.
+	pub fn max_sessions(&self) -> Option<usize> {
+		self.max_sessions
+	}
+
+	/// Pick the sessions to evict from a navigator's sessions, listed most
+	/// recent first, to stay within the limit. Expired sessions don't count.
+	pub fn evicted<'s>(&self, sessions: &'s [Session]) -> Vec<&'s Session> {
+		let Some(max_sessions) = self.max_sessions else {
+			return vec![];
+		};
+
+		sessions
+			.iter()
+			.filter(|session| !session.is_expired())
+			.skip(max_sessions)
+			.collect()
+	}
+}
+
+impl Default for SessionLimit {
+	fn default() -> Self {
+		Self::new(DEFAULT_MAX_SESSIONS)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::models::NuttyId;
+
+	#[test]
+	fn test_evicted() {
+		let navigator_id = NuttyId::now();
+
+		let session = |days: i64| {
+			Session::new(
+				navigator_id,
+				"agent".to_string(),
+				chrono::Duration::days(days),
+			)
+			.unwrap()
+		};
+
+		// Arrange: Three active sessions, most recent first, and an expired one.
+		let sessions = vec![session(1), session(-1), session(1), session(1)];
+
+		// Assert: Only active sessions beyond the limit are evicted.
+		let evicted = SessionLimit::new(2).evicted(&sessions);
+		assert_eq!(evicted.len(), 1);
+		assert_eq!(evicted[0].nutty_id(), sessions[3].nutty_id());
+
+		assert_eq!(SessionLimit::single().evicted(&sessions).len(), 2);
+		assert!(SessionLimit::unlimited().evicted(&sessions).is_empty());
+		assert!(SessionLimit::default().evicted(&sessions).is_empty());
+	}
+}
@@ -0,0 +1,428 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Local;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::models::NuttyId;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+use crate::models::session::Session;
+use crate::navigator::repository::NavigatorRepository;
+use crate::navigator::repository::NavigatorRepositoryError;
+use crate::utilities::redis::RedisClient;
+use crate::utilities::redis::RedisError;
+use crate::utilities::secrets::Secret;
+
+/// How long sessions are cached for by default, in seconds.
+const DEFAULT_CACHE_TTL_SECS: u64 = 300;
+
+/// Where sessions are kept, for looking them up on every request.
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+	/// Save a new session.
+	async fn create_session(&self, session: Session) -> Result<Session, SessionStoreError>;
+
+	/// Get a session by the hash of its token.
+	async fn get_session_by_token_hash(
+		&self,
+		token_hash: &str,
+	) -> Result<Option<Session>, SessionStoreError>;
+
+	/// Delete a session.
+	async fn delete_session(&self, session: &Session) -> Result<(), SessionStoreError>;
+
+	/// Forget any copy of a session that was changed elsewhere. Stores that
+	/// hold the original have nothing to forget.
+	async fn evict_session(&self, _session: &Session) -> Result<(), SessionStoreError> {
+		Ok(())
+	}
+}
+
+/// Sessions kept in Postgres, with the rest of a navigator's data.
+#[derive(Clone)]
+pub struct PostgresSessionStore {
+	repository: NavigatorRepository,
+}
+
+impl PostgresSessionStore {
+	/// Create a session store backed by the given repository.
+	pub fn new(repository: NavigatorRepository) -> Self {
+		Self { repository }
+	}
+}
+
+#[async_trait]
+impl SessionStore for PostgresSessionStore {
+	async fn create_session(&self, session: Session) -> Result<Session, SessionStoreError> {
+		Ok(self.repository.create_session(session).await?)
+	}
+
+	async fn get_session_by_token_hash(
+		&self,
+		token_hash: &str,
+	) -> Result<Option<Session>, SessionStoreError> {
+		Ok(self
+			.repository
+			.get_session_by_token_hash(token_hash)
+			.await?)
+	}
+
+	async fn delete_session(&self, session: &Session) -> Result<(), SessionStoreError> {
+		Ok(self.repository.delete_session(session.nutty_id()).await?)
+	}
+}
+
+/// Sessions cached in Redis by the hash of their token, until they expire or
+/// the cache's TTL runs out, whichever comes first.
+#[derive(Debug)]
+pub struct RedisSessionStore {
+	client: RedisClient,
+	ttl: Duration,
+}
+
+impl RedisSessionStore {
+	/// Create a session store that caches sessions for the given TTL.
+	pub fn new(client: RedisClient, ttl: Duration) -> Self {
+		Self { client, ttl }
+	}
+
+	/// Connect to `SESSION_REDIS_URL`, if set, caching sessions for
+	/// `SESSION_CACHE_TTL_SECS` (5 minutes by default). The URL may hold a
+	/// password, so it's read as a secret, from `SESSION_REDIS_URL_FILE`
+	/// if that's set.
+	pub fn from_env() -> Option<Self> {
+		let url = Secret::from_env("SESSION_REDIS_URL")?;
+		let client = RedisClient::new(url.expose()).expect("Invalid SESSION_REDIS_URL");
+
+		let ttl = std::env::var("SESSION_CACHE_TTL_SECS")
+			.ok()
+			.map(|v| v.parse().expect("Invalid SESSION_CACHE_TTL_SECS"))
+			.unwrap_or(DEFAULT_CACHE_TTL_SECS);
+
+		Some(Self::new(client, Duration::from_secs(ttl)))
+	}
+
+	fn key(token_hash: &str) -> String {
+		format!("session:{token_hash}")
+	}
+}
+
+#[async_trait]
+impl SessionStore for RedisSessionStore {
+	async fn create_session(&self, session: Session) -> Result<Session, SessionStoreError> {
+		// Cache no longer than the session lives.
+		let remaining = (*session.expires_at().inner() - Local::now().fixed_offset())
+			.to_std()
+			.unwrap_or_default();
+
+		let ttl = self.ttl.min(remaining);
+
+		if ttl.as_secs() > 0 {
+			let value = serde_json::to_vec(&CachedSession::from(&session))
+				.map_err(|e| SessionStoreError::Malformed(e.to_string()))?;
+
+			self
+				.client
+				.set_ex(&Self::key(session.token_hash()), &value, ttl)
+				.await?;
+		}
+
+		Ok(session)
+	}
+
+	async fn get_session_by_token_hash(
+		&self,
+		token_hash: &str,
+	) -> Result<Option<Session>, SessionStoreError> {
+		let Some(value) = self.client.get(&Self::key(token_hash)).await? else {
+			return Ok(None);
+		};
+
+		let cached: CachedSession =
+			serde_json::from_slice(&value).map_err(|e| SessionStoreError::Malformed(e.to_string()))?;
+
+		cached.try_into().map(Some)
+	}
+
+	async fn delete_session(&self, session: &Session) -> Result<(), SessionStoreError> {
+		Ok(self.client.del(&Self::key(session.token_hash())).await?)
+	}
+
+	async fn evict_session(&self, session: &Session) -> Result<(), SessionStoreError> {
+		self.delete_session(session).await
+	}
+}
+
+/// A session as it's cached. Unlike its API representation, nothing is left
+/// out but the plaintext token, which is never stored.
+#[derive(Serialize, Deserialize)]
+struct CachedSession {
+	nutty_id: NuttyId,
+	navigator_id: NuttyId,
+	user_agent: String,
+	token_hash: String,
+	label: Option<String>,
+	country: Option<String>,
+	expires_at: DateTimeRfc3339,
+	created_at: DateTimeRfc3339,
+	updated_at: DateTimeRfc3339,
+}
+
+impl From<&Session> for CachedSession {
+	fn from(session: &Session) -> Self {
+		Self {
+			nutty_id: *session.nutty_id(),
+			navigator_id: *session.navigator_id(),
+			user_agent: session.user_agent().to_string(),
+			token_hash: session.token_hash().to_string(),
+			label: session.label().map(str::to_string),
+			country: session.country().map(str::to_string),
+			expires_at: *session.expires_at(),
+			created_at: *session.created_at(),
+			updated_at: *session.updated_at(),
+		}
+	}
+}
+
+impl TryFrom<CachedSession> for Session {
+	type Error = SessionStoreError;
+
+	fn try_from(cached: CachedSession) -> Result<Self, Self::Error> {
+		let session = Session::builder()
+			.nutty_id(cached.nutty_id)
+			.navigator_id(cached.navigator_id)
+			.user_agent(cached.user_agent)
+			.token_hash(cached.token_hash)
+			.expires_at(cached.expires_at)
+			.created_at(cached.created_at)
+			.updated_at(cached.updated_at)
+			.try_build()
+			.map_err(|e| SessionStoreError::Malformed(e.to_string()))?;
+
+		Ok(session
+			.with_label(cached.label)
+			.with_country(cached.country))
+	}
+}
+
+/// Sessions written through a cache to the store that holds the originals.
+/// Lookups try the cache first, and fill it on a miss. The cache is only an
+/// optimization: if it fails, sessions are still served from the store.
+#[derive(Clone)]
+pub struct WriteThroughSessionStore {
+	store: Arc<dyn SessionStore>,
+	cache: Arc<dyn SessionStore>,
+}
+
+impl WriteThroughSessionStore {
+	/// Create a session store that caches another's sessions.
+	pub fn new(store: impl SessionStore + 'static, cache: impl SessionStore + 'static) -> Self {
+		Self {
+			store: Arc::new(store),
+			cache: Arc::new(cache),
+		}
+	}
+
+	fn log_cache_error(error: SessionStoreError) {
+		eprintln!("Session cache unavailable, falling back to the session store: {error}");
+	}
+}
+
+#[async_trait]
+impl SessionStore for WriteThroughSessionStore {
+	async fn create_session(&self, session: Session) -> Result<Session, SessionStoreError> {
+		let session = self.store.create_session(session).await?;
+
+		if let Err(error) = self.cache.create_session(session.clone()).await {
+			Self::log_cache_error(error);
+		}
+
+		Ok(session)
+	}
+
+	async fn get_session_by_token_hash(
+		&self,
+		token_hash: &str,
+	) -> Result<Option<Session>, SessionStoreError> {
+		match self.cache.get_session_by_token_hash(token_hash).await {
+			Ok(Some(session)) => return Ok(Some(session)),
+			Ok(None) => {}
+			Err(error) => Self::log_cache_error(error),
+		}
+
+		let session = self.store.get_session_by_token_hash(token_hash).await?;
+
+		if let Some(session) = &session
+			&& let Err(error) = self.cache.create_session(session.clone()).await
+		{
+			Self::log_cache_error(error);
+		}
+
+		Ok(session)
+	}
+
+	async fn delete_session(&self, session: &Session) -> Result<(), SessionStoreError> {
+		self.store.delete_session(session).await?;
+
+		// A session left behind is only served until the cache's TTL runs out.
+		if let Err(error) = self.cache.delete_session(session).await {
+			Self::log_cache_error(error);
+		}
+
+		Ok(())
+	}
+
+	async fn evict_session(&self, session: &Session) -> Result<(), SessionStoreError> {
+		self.store.evict_session(session).await?;
+
+		if let Err(error) = self.cache.evict_session(session).await {
+			Self::log_cache_error(error);
+		}
+
+		Ok(())
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum SessionStoreError {
+	#[error("Failed to access sessions: {0}")]
+	Repository(#[from] NavigatorRepositoryError),
+
+	#[error("Failed to access cached sessions: {0}")]
+	Redis(#[from] RedisError),
+
+	#[error("Malformed cached session: {0}")]
+	Malformed(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use std::sync::Mutex;
+
+	use super::*;
+
+	/// Sessions kept in memory, by the hash of their token.
+	#[derive(Default)]
+	struct MemorySessionStore {
+		sessions: Mutex<HashMap<String, Session>>,
+	}
+
+	impl MemorySessionStore {
+		fn contains(&self, session: &Session) -> bool {
+			self
+				.sessions
+				.lock()
+				.unwrap()
+				.contains_key(session.token_hash())
+		}
+	}
+
+	#[async_trait]
+	impl SessionStore for Arc<MemorySessionStore> {
+		async fn create_session(&self, session: Session) -> Result<Session, SessionStoreError> {
+			self
+				.sessions
+				.lock()
+				.unwrap()
+				.insert(session.token_hash().to_string(), session.clone());
+
+			Ok(session)
+		}
+
+		async fn get_session_by_token_hash(
+			&self,
+			token_hash: &str,
+		) -> Result<Option<Session>, SessionStoreError> {
+			Ok(self.sessions.lock().unwrap().get(token_hash).cloned())
+		}
+
+		async fn delete_session(&self, session: &Session) -> Result<(), SessionStoreError> {
+			self.sessions.lock().unwrap().remove(session.token_hash());
+			Ok(())
+		}
+	}
+
+	fn session() -> Session {
+		Session::new(
+			NuttyId::now(),
+			"test-agent".to_string(),
+			chrono::Duration::days(1),
+		)
+		.unwrap()
+	}
+
+	#[tokio::test]
+	async fn test_write_through() {
+		// Arrange
+		let store = Arc::new(MemorySessionStore::default());
+		let cache = Arc::new(MemorySessionStore::default());
+		let sessions = WriteThroughSessionStore::new(store.clone(), cache.clone());
+
+		// Act: Create a session.
+		let session = sessions.create_session(session()).await.unwrap();
+
+		// Assert: It was written to both.
+		assert!(store.contains(&session));
+		assert!(cache.contains(&session));
+
+		// Act: Drop it from the cache, then look it up.
+		cache.delete_session(&session).await.unwrap();
+
+		let found = sessions
+			.get_session_by_token_hash(session.token_hash())
+			.await
+			.unwrap();
+
+		// Assert: It was found in the store, and cached again.
+		assert_eq!(found.unwrap().nutty_id(), session.nutty_id());
+		assert!(cache.contains(&session));
+
+		// Act: Delete it.
+		sessions.delete_session(&session).await.unwrap();
+
+		// Assert: It's gone from both.
+		assert!(!store.contains(&session));
+		assert!(!cache.contains(&session));
+	}
+
+	#[tokio::test]
+	async fn test_unavailable_cache() {
+		// Arrange: Cache in a Redis that isn't there.
+		let store = Arc::new(MemorySessionStore::default());
+		let client = RedisClient::new("redis://127.0.0.1:1").unwrap();
+		let cache = RedisSessionStore::new(client, Duration::from_secs(60));
+		let sessions = WriteThroughSessionStore::new(store.clone(), cache);
+
+		// Act
+		let session = sessions.create_session(session()).await.unwrap();
+
+		let found = sessions
+			.get_session_by_token_hash(session.token_hash())
+			.await
+			.unwrap();
+
+		// Assert: Sessions are still created and found.
+		assert!(store.contains(&session));
+		assert_eq!(found.unwrap().nutty_id(), session.nutty_id());
+	}
+
+	#[test]
+	fn test_cached_session() {
+		let session = session().with_label(Some("work laptop".to_string()));
+
+		// Act
+		let cached: Session = CachedSession::from(&session).try_into().unwrap();
+
+		// Assert: Everything but the plaintext token is kept.
+		assert_eq!(cached.nutty_id(), session.nutty_id());
+		assert_eq!(cached.token_hash(), session.token_hash());
+		assert_eq!(cached.user_agent(), session.user_agent());
+		assert_eq!(cached.label(), Some("work laptop"));
+		assert_eq!(cached.expires_at(), session.expires_at());
+		assert!(cached.token().is_none());
+	}
+}
@@ -0,0 +1,527 @@
+use thiserror::Error;
+
+use crate::access::models::ResourceKind;
+use crate::access::service::AccessServiceApi;
+use crate::access::service::AccessServiceError;
+use crate::content::service::ContentServiceApi;
+use crate::content::service::ContentServiceError;
+use crate::models::BlockContent;
+use crate::models::ContentBlock;
+use crate::models::FractionalIndex;
+use crate::models::fractional_index::FractionalIndexError;
+use crate::navigator::service::NavigatorServiceApi;
+use crate::navigator::service::NavigatorServiceError;
+
+/// The password of every demo navigator.
+pub const DEMO_PASSWORD: &str = "nuttyverse-demo";
+
+const NAMES: &[&str] = &[
+	"ada",
+	"alan",
+	"barbara",
+	"donald",
+	"edsger",
+	"frances",
+	"grace",
+	"hedy",
+	"john",
+	"katherine",
+	"linus",
+	"margaret",
+	"niklaus",
+	"radia",
+	"tony",
+	"yukihiro",
+];
+
+/// The share roles that the other navigators are granted.
+const SHARE_ROLES: &[&str] = &["block_viewer", "block_commenter", "block_editor"];
+
+const WORDS: &[&str] = &[
+	"orbit", "nebula", "garden", "lantern", "harbor", "compass", "meadow", "signal", "archive",
+	"comet", "thicket", "ledger", "summit", "current", "beacon", "atlas", "quiet", "bright",
+	"distant", "hidden", "northern", "gentle", "curious", "woven", "notes", "plans", "ideas",
+	"journal", "reading", "recipes", "travel", "projects",
+];
+
+/// A small, deterministic random number generator (SplitMix64), so that a
+/// seed generates the same data on every platform and release.
+#[derive(Debug, Clone)]
+pub struct SeedRng(u64);
+
+impl SeedRng {
+	pub fn new(seed: u64) -> Self {
+		Self(seed)
+	}
+
+	pub fn next_u64(&mut self) -> u64 {
+		self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+		let mut z = self.0;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+		z ^ (z >> 31)
+	}
+
+	/// Get a number in `0..n`. `n` must be positive.
+	pub fn below(&mut self, n: usize) -> usize {
+		(self.next_u64() % n as u64) as usize
+	}
+
+	/// Get `true` with a probability of `percent` in 100.
+	pub fn chance(&mut self, percent: u64) -> bool {
+		self.next_u64() % 100 < percent
+	}
+
+	/// Pick an item from a non-empty slice.
+	pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+		&items[self.below(items.len())]
+	}
+}
+
+/// What to generate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedConfig {
+	pub seed: u64,
+	pub pages: usize,
+	pub max_depth: usize,
+	pub paragraphs_per_page: usize,
+	pub navigators: usize,
+}
+
+impl Default for SeedConfig {
+	fn default() -> Self {
+		Self {
+			seed: 42,
+			pages: 50,
+			max_depth: 4,
+			paragraphs_per_page: 5,
+			navigators: 6,
+		}
+	}
+}
+
+impl SeedConfig {
+	/// Parse command-line flags, e.g. `--seed 7 --pages 200`. Missing flags
+	/// take their defaults.
+	pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, SeedError> {
+		let mut config = Self::default();
+		let mut args = args.into_iter();
+
+		while let Some(flag) = args.next() {
+			let value = args
+				.next()
+				.ok_or_else(|| SeedError::MissingValue(flag.clone()))?;
+
+			let parse = |value: &str| {
+				value
+					.parse::<usize>()
+					.map_err(|_| SeedError::InvalidValue(flag.clone(), value.to_string()))
+			};
+
+			match flag.as_str() {
+				"--seed" => {
+					config.seed = value
+						.parse()
+						.map_err(|_| SeedError::InvalidValue(flag.clone(), value.clone()))?
+				}
+				"--pages" => config.pages = parse(&value)?,
+				"--depth" => config.max_depth = parse(&value)?,
+				"--paragraphs" => config.paragraphs_per_page = parse(&value)?,
+				"--navigators" => config.navigators = parse(&value)?,
+				_ => return Err(SeedError::UnknownFlag(flag)),
+			}
+		}
+
+		if config.navigators == 0 || config.navigators > NAMES.len() {
+			return Err(SeedError::InvalidValue(
+				"--navigators".to_string(),
+				config.navigators.to_string(),
+			));
+		}
+
+		Ok(config)
+	}
+}
+
+/// A navigator's part in the demo data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DemoRole {
+	/// Owns every block, and administers the system.
+	Admin,
+
+	/// Has a share role on one top-level page.
+	Shared(&'static str),
+}
+
+/// A navigator to register.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DemoNavigator {
+	pub name: String,
+	pub role: DemoRole,
+
+	/// The index of the page that a shared role is granted on.
+	pub page: Option<usize>,
+}
+
+/// Generated demo data, ready to be saved.
+#[derive(Debug, Clone)]
+pub struct DemoData {
+	pub navigators: Vec<DemoNavigator>,
+
+	/// Every block, parents before their children.
+	pub blocks: Vec<ContentBlock>,
+
+	/// The indices of the top-level pages in `blocks`.
+	pub roots: Vec<usize>,
+}
+
+impl DemoData {
+	/// Generate demo data. Identifiers are fresh on every run, but the same
+	/// config always generates the same names, tree shape, and text.
+	pub fn generate(config: &SeedConfig) -> Result<Self, SeedError> {
+		let mut rng = SeedRng::new(config.seed);
+		let mut blocks: Vec<ContentBlock> = vec![];
+		let mut roots = vec![];
+
+		// Each page's index in `blocks`, depth, and last child's index.
+		let mut pages: Vec<(usize, usize, Option<FractionalIndex>)> = vec![];
+		let mut last_root: Option<FractionalIndex> = None;
+
+		for _ in 0..config.pages {
+			let parents: Vec<usize> = (0..pages.len())
+				.filter(|&page| pages[page].1 < config.max_depth)
+				.collect();
+
+			let parent = match parents.is_empty() || rng.chance(20) {
+				true => None,
+				false => Some(*rng.pick(&parents)),
+			};
+
+			let previous = match parent {
+				Some(parent) => &mut pages[parent].2,
+				None => &mut last_root,
+			};
+
+			let f_index = next_index(previous)?;
+			let title = title(&mut rng);
+
+			let page = ContentBlock::now(
+				parent.map(|parent| *blocks[pages[parent].0].nutty_id()),
+				f_index,
+				BlockContent::Page { title },
+			);
+
+			if parent.is_none() {
+				roots.push(blocks.len());
+			}
+
+			let depth = parent.map_or(0, |parent| pages[parent].1 + 1);
+			pages.push((blocks.len(), depth, None));
+			blocks.push(page);
+		}
+
+		// Fill the pages, linking back to earlier ones.
+		for page in 0..pages.len() {
+			let page_id = *blocks[pages[page].0].nutty_id();
+			let mut previous = pages[page].2.clone();
+
+			for _ in 0..config.paragraphs_per_page {
+				let markdown = paragraph(&mut rng, &blocks, &pages[..page]);
+
+				let content = match rng.below(10) {
+					0 => BlockContent::Heading {
+						markdown: title(&mut rng),
+					},
+					1 | 2 => BlockContent::Todo {
+						markdown,
+						done: rng.chance(50),
+					},
+					_ => BlockContent::Paragraph { markdown },
+				};
+
+				let f_index = next_index(&mut previous)?;
+				blocks.push(ContentBlock::now(Some(page_id), f_index, content));
+			}
+		}
+
+		let navigators = (0..config.navigators)
+			.map(|i| {
+				let name = format!("{}_{i}", NAMES[i]);
+
+				match i {
+					0 => DemoNavigator {
+						name,
+						role: DemoRole::Admin,
+						page: None,
+					},
+					_ => DemoNavigator {
+						name,
+						role: DemoRole::Shared(SHARE_ROLES[rng.below(SHARE_ROLES.len())]),
+						page: (!roots.is_empty()).then(|| *rng.pick(&roots)),
+					},
+				}
+			})
+			.collect();
+
+		Ok(Self {
+			navigators,
+			blocks,
+			roots,
+		})
+	}
+}
+
+/// How much demo data was saved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedSummary {
+	pub navigators: usize,
+	pub blocks: usize,
+}
+
+/// Save demo data. The admin owns every block, and the other navigators
+/// are granted their roles. Names clash with existing navigators, so seed a
+/// fresh database.
+pub async fn seed(
+	data: DemoData,
+	navigator_service: &dyn NavigatorServiceApi,
+	content_service: &dyn ContentServiceApi,
+	access_service: &dyn AccessServiceApi,
+) -> Result<SeedSummary, SeedError> {
+	let mut navigator_ids = vec![];
+
+	for navigator in &data.navigators {
+		let registered = navigator_service
+			.register(navigator.name.clone(), DEMO_PASSWORD.to_string())
+			.await
+			.map_err(SeedError::Register)?;
+
+		navigator_ids.push(*registered.nutty_id());
+	}
+
+	let owner_id = navigator_ids[0];
+	let block_count = data.blocks.len();
+	let mut block_ids = vec![];
+
+	for mut block in data.blocks {
+		block.owner_id = Some(owner_id);
+
+		let saved = content_service
+			.save_content_block(None, block)
+			.await
+			.map_err(SeedError::SaveBlock)?;
+
+		block_ids.push(*saved.nutty_id());
+	}
+
+	for (navigator, navigator_id) in data.navigators.iter().zip(&navigator_ids) {
+		match (navigator.role, navigator.page) {
+			(DemoRole::Admin, _) => access_service
+				.grant_global_role(navigator_id, "admin")
+				.await
+				.map_err(SeedError::GrantRole)?,
+
+			(DemoRole::Shared(role), Some(page)) => access_service
+				.grant_resource_role(
+					navigator_id,
+					role,
+					ResourceKind::ContentBlock,
+					&block_ids[page],
+				)
+				.await
+				.map_err(SeedError::GrantRole)?,
+
+			(DemoRole::Shared(_), None) => {}
+		}
+	}
+
+	Ok(SeedSummary {
+		navigators: navigator_ids.len(),
+		blocks: block_count,
+	})
+}
+
+/// Get the index after the previous sibling's, and remember it.
+fn next_index(previous: &mut Option<FractionalIndex>) -> Result<FractionalIndex, SeedError> {
+	let next = match previous {
+		Some(previous) => FractionalIndex::between(previous, &FractionalIndex::end())?,
+		None => FractionalIndex::start(),
+	};
+
+	*previous = Some(next.clone());
+	Ok(next)
+}
+
+fn title(rng: &mut SeedRng) -> String {
+	let (first, second) = (rng.pick(WORDS), rng.pick(WORDS));
+	let mut title = format!("{first} {second}");
+	title[..1].make_ascii_uppercase();
+	title
+}
+
+/// Write a paragraph, sometimes tagging one of the earlier pages.
+fn paragraph(
+	rng: &mut SeedRng,
+	blocks: &[ContentBlock],
+	pages: &[(usize, usize, Option<FractionalIndex>)],
+) -> String {
+	let length = 8 + rng.below(16);
+	let mut words: Vec<String> = (0..length).map(|_| rng.pick(WORDS).to_string()).collect();
+
+	if !pages.is_empty() && rng.chance(30) {
+		let target = &blocks[rng.pick(pages).0];
+
+		if let BlockContent::Page { title } = &target.content {
+			let position = rng.below(words.len());
+			words.insert(position, format!("[[{}|{title}]]", target.nutty_id().nid()));
+		}
+	}
+
+	let mut paragraph = words.join(" ");
+	paragraph[..1].make_ascii_uppercase();
+	paragraph.push('.');
+	paragraph
+}
+
+#[derive(Debug, Error)]
+pub enum SeedError {
+	#[error("Unknown flag: {0}")]
+	UnknownFlag(String),
+
+	#[error("Missing a value for {0}")]
+	MissingValue(String),
+
+	#[error("Invalid value for {0}: {1}")]
+	InvalidValue(String, String),
+
+	#[error("Failed to index blocks: {0}")]
+	Index(#[from] FractionalIndexError),
+
+	#[error("Failed to register navigator: {0}")]
+	Register(#[source] NavigatorServiceError),
+
+	#[error("Failed to save content block: {0}")]
+	SaveBlock(#[source] ContentServiceError),
+
+	#[error("Failed to grant role: {0}")]
+	GrantRole(#[source] AccessServiceError),
+}
+
+#[cfg(test)]
+mod tests {
+	use regex::Regex;
+
+	use super::*;
+	use crate::models::NuttyTag;
+
+	/// Everything but identifiers and timestamps.
+	fn shape(data: &DemoData) -> Vec<(Option<usize>, String, String)> {
+		let ids: Vec<_> = data.blocks.iter().map(|block| *block.nutty_id()).collect();
+		let tag = Regex::new(r"\[\[\w+\|").unwrap();
+
+		data
+			.blocks
+			.iter()
+			.map(|block| {
+				let parent = block
+					.parent_id
+					.map(|parent| ids.iter().position(|id| *id == parent).unwrap());
+
+				// Tags point at fresh IDs, so compare their display text only.
+				let content = serde_json::to_string(&block.content).unwrap();
+				let content = tag.replace_all(&content, "[[").to_string();
+
+				(parent, block.f_index.as_str().to_string(), content)
+			})
+			.collect()
+	}
+
+	#[test]
+	fn test_generate_is_deterministic() {
+		let config = SeedConfig::default();
+		let first = DemoData::generate(&config).unwrap();
+		let second = DemoData::generate(&config).unwrap();
+
+		assert_eq!(shape(&first), shape(&second));
+		assert_eq!(first.navigators, second.navigators);
+		assert_eq!(
+			first.blocks.len(),
+			config.pages * (1 + config.paragraphs_per_page)
+		);
+
+		let other = SeedConfig {
+			seed: 7,
+			..config.clone()
+		};
+
+		assert_ne!(shape(&first), shape(&DemoData::generate(&other).unwrap()));
+	}
+
+	#[test]
+	fn test_generate_tree() {
+		let config = SeedConfig {
+			pages: 200,
+			max_depth: 2,
+			..SeedConfig::default()
+		};
+
+		let data = DemoData::generate(&config).unwrap();
+		let depth = |block: &ContentBlock| {
+			let (mut depth, mut parent_id) = (0, block.parent_id);
+
+			while let Some(id) = parent_id {
+				let parent = data.blocks.iter().find(|b| *b.nutty_id() == id).unwrap();
+				parent_id = parent.parent_id;
+				depth += 1;
+			}
+
+			depth
+		};
+
+		// Assert: Pages are at most `max_depth` deep, and parents come first.
+		for (i, block) in data.blocks.iter().enumerate() {
+			if matches!(block.content, BlockContent::Page { .. }) {
+				assert!(depth(block) <= config.max_depth);
+			}
+
+			if let Some(parent) = block.parent_id {
+				assert!(data.blocks[..i].iter().any(|b| *b.nutty_id() == parent));
+			}
+		}
+
+		// Assert: Some paragraphs link to pages.
+		let links = data
+			.blocks
+			.iter()
+			.filter_map(|block| match &block.content {
+				BlockContent::Paragraph { markdown } => Some(NuttyTag::parse_all(markdown)),
+				_ => None,
+			})
+			.flatten()
+			.count();
+
+		assert!(links > 0);
+		assert_eq!(data.navigators[0].role, DemoRole::Admin);
+	}
+
+	#[test]
+	fn test_from_args() {
+		let args = ["--seed", "7", "--pages", "10"].map(String::from);
+		let config = SeedConfig::from_args(args).unwrap();
+
+		assert_eq!(config.seed, 7);
+		assert_eq!(config.pages, 10);
+		assert_eq!(config.navigators, SeedConfig::default().navigators);
+
+		let args = ["--pages"].map(String::from);
+		assert!(matches!(
+			SeedConfig::from_args(args),
+			Err(SeedError::MissingValue(_))
+		));
+
+		let args = ["--colour", "red"].map(String::from);
+		assert!(matches!(
+			SeedConfig::from_args(args),
+			Err(SeedError::UnknownFlag(_))
+		));
+	}
+}
@@ -0,0 +1,789 @@
+use std::sync::Arc;
+
+use axum::Json;
+use axum::Router;
+use axum::extract::Path;
+use axum::extract::Query;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use axum::routing::put;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::access::models::AccessRename;
+use crate::access::models::RenameKind;
+use crate::access::repository::AccessRepositoryError;
+use crate::access::service::AccessServiceError;
+use crate::content::service::ContentServiceError;
+use crate::models::NuttyId;
+use crate::models::block_stats::BlockStatsCheck;
+use crate::models::request_usage::NavigatorUsage;
+use crate::models::request_usage::RequestUsageError;
+use crate::models::request_usage::UsageSort;
+use crate::models::request_usage::parse_window;
+use crate::navigator::service::NavigatorServiceError;
+use crate::navigator::service::PasswordHashMetrics;
+use crate::system::models::SchemaTable;
+use crate::system::service::SystemServiceError;
+use crate::utilities::api::response::Error;
+use crate::utilities::api::response::Response;
+use crate::utilities::api::session::Session;
+use crate::utilities::api::state::AppState;
+use crate::utilities::circuit_breaker::CircuitBreaker;
+use crate::utilities::circuit_breaker::CircuitBreakerSummary;
+use crate::utilities::circuit_breaker::CircuitState;
+use crate::utilities::query_metrics::QueryMetrics;
+use crate::utilities::query_metrics::QueryMetricsSummary;
+
+/// The permission required to toggle read-only mode.
+const READ_ONLY_PERMISSION: &str = "system:read_only:write";
+
+/// The permission required to read query metrics.
+const METRICS_PERMISSION: &str = "system:metrics:read";
+
+/// The most navigators that request usage can be ranked for at once.
+const MAX_USAGE_LIMIT: usize = 100;
+
+/// The permission required to check the block stats.
+const BLOCK_STATS_PERMISSION: &str = "system:block_stats:check";
+
+/// The permission required to rename roles and permissions.
+const RENAME_PERMISSION: &str = "system:access:rename";
+
+/// The permission required to read the database schema.
+const SCHEMA_PERMISSION: &str = "system:schema:read";
+
+/// The router for system API endpoints.
+pub fn router(app_state: Arc<AppState>) -> Router {
+	Router::new()
+		.route("/healthz", get(health_handler))
+		.route("/readyz", get(readiness_handler))
+		.route("/admin/read-only", put(read_only_handler))
+		.route("/admin/slow-queries", get(slow_queries_handler))
+		.route("/admin/hash-versions", get(hash_versions_handler))
+		.route("/admin/request-usage", get(request_usage_handler))
+		.route("/admin/block-stats/check", post(check_block_stats_handler))
+		.route("/admin/roles/{name}/rename", post(rename_role_handler))
+		.route(
+			"/admin/permissions/{name}/rename",
+			post(rename_permission_handler),
+		)
+		.route("/admin/access-renames", get(access_renames_handler))
+		.route("/admin/schema", get(schema_handler))
+		.with_state(app_state)
+}
+
+/// The health of the API.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Health {
+	status: String,
+	read_only: bool,
+}
+
+impl Health {
+	fn from_state(state: &AppState) -> Self {
+		Self {
+			status: "ok".to_string(),
+			read_only: state.read_only.is_enabled(),
+		}
+	}
+}
+
+/// An API handler for checking the health of the API.
+async fn health_handler(
+	State(state): State<Arc<AppState>>,
+) -> (StatusCode, Json<Response<Health>>) {
+	(
+		StatusCode::OK,
+		Json(Response::Single {
+			data: Some(Health::from_state(&state)),
+		}),
+	)
+}
+
+/// The readiness of the API to serve requests, which it isn't while the
+/// database circuit is open or probing.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Readiness {
+	status: String,
+	database: CircuitBreakerSummary,
+}
+
+/// An API handler for checking if the API is ready to serve requests.
+async fn readiness_handler() -> (StatusCode, Json<Response<Readiness>>) {
+	let database = CircuitBreaker::global().summary();
+
+	let (status_code, status) = match database.state {
+		CircuitState::Closed => (StatusCode::OK, "ready"),
+		CircuitState::Open | CircuitState::HalfOpen => {
+			(StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+		}
+	};
+
+	(
+		status_code,
+		Json(Response::Single {
+			data: Some(Readiness {
+				status: status.to_string(),
+				database,
+			}),
+		}),
+	)
+}
+
+/// Request payload for toggling read-only mode.
+#[derive(Serialize, Deserialize)]
+pub struct ReadOnlyRequest {
+	enabled: bool,
+}
+
+/// An API handler for toggling read-only mode.
+async fn read_only_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Json(payload): Json<ReadOnlyRequest>,
+) -> (StatusCode, Json<Response<Health>>) {
+	let has_access = state
+		.access_service
+		.can_permission(navigator.nutty_id(), READ_ONLY_PERMISSION)
+		.await;
+
+	match has_access {
+		Ok(true) => {
+			state.read_only.set_enabled(payload.enabled);
+
+			(
+				StatusCode::OK,
+				Json(Response::Single {
+					data: Some(Health::from_state(&state)),
+				}),
+			)
+		}
+
+		Ok(false) => {
+			let summary = "Access denied.";
+			let error = SystemApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+
+		Err(error) => {
+			let summary = "Failed to check access permissions.";
+			let error = SystemApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for summarizing query latencies and slow queries.
+async fn slow_queries_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+) -> (StatusCode, Json<Response<QueryMetricsSummary>>) {
+	let has_access = state
+		.access_service
+		.can_permission(navigator.nutty_id(), METRICS_PERMISSION)
+		.await;
+
+	match has_access {
+		Ok(true) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(QueryMetrics::global().summary()),
+			}),
+		),
+
+		Ok(false) => {
+			let summary = "Access denied.";
+			let error = SystemApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+
+		Err(error) => {
+			let summary = "Failed to check access permissions.";
+			let error = SystemApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for summarizing password hash versions seen on login.
+async fn hash_versions_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+) -> (StatusCode, Json<Response<PasswordHashMetrics>>) {
+	let has_access = state
+		.access_service
+		.can_permission(navigator.nutty_id(), METRICS_PERMISSION)
+		.await;
+
+	match has_access {
+		Ok(true) => (
+			StatusCode::OK,
+			Json(Response::Single {
+				data: Some(state.navigator_service.password_hash_metrics()),
+			}),
+		),
+
+		Ok(false) => {
+			let summary = "Access denied.";
+			let error = SystemApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+
+		Err(error) => {
+			let summary = "Failed to check access permissions.";
+			let error = SystemApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for recounting the block stats, and repairing any that
+/// drifted.
+async fn check_block_stats_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+) -> (StatusCode, Json<Response<BlockStatsCheck>>) {
+	let has_access = state
+		.access_service
+		.can_permission(navigator.nutty_id(), BLOCK_STATS_PERMISSION)
+		.await;
+
+	match has_access {
+		Ok(true) => {}
+
+		Ok(false) => {
+			let summary = "Access denied.";
+			let error = SystemApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+
+		Err(error) => {
+			let summary = "Failed to check access permissions.";
+			let error = SystemApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	}
+
+	match state.content_service.check_block_stats().await {
+		Ok(check) => (StatusCode::OK, Json(Response::Single { data: Some(check) })),
+
+		Err(error) => {
+			let summary = "Failed to check block stats.";
+			let error = SystemApiError::CheckBlockStats(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// Request payload for renaming a role or permission.
+#[derive(Serialize, Deserialize)]
+pub struct RenameRequest {
+	name: String,
+}
+
+/// An API handler for renaming a role everywhere it's granted.
+async fn rename_role_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(name): Path<String>,
+	Json(payload): Json<RenameRequest>,
+) -> (StatusCode, Json<Response<AccessRename>>) {
+	rename(
+		&state,
+		navigator.nutty_id(),
+		RenameKind::Role,
+		&name,
+		&payload.name,
+	)
+	.await
+}
+
+/// An API handler for renaming a permission in every role that has it.
+async fn rename_permission_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Path(name): Path<String>,
+	Json(payload): Json<RenameRequest>,
+) -> (StatusCode, Json<Response<AccessRename>>) {
+	rename(
+		&state,
+		navigator.nutty_id(),
+		RenameKind::Permission,
+		&name,
+		&payload.name,
+	)
+	.await
+}
+
+/// Rename a role or permission on a navigator's behalf, if they may.
+async fn rename(
+	state: &AppState,
+	navigator_id: &NuttyId,
+	kind: RenameKind,
+	old_name: &str,
+	new_name: &str,
+) -> (StatusCode, Json<Response<AccessRename>>) {
+	let has_access = state
+		.access_service
+		.can_permission(navigator_id, RENAME_PERMISSION)
+		.await;
+
+	match has_access {
+		Ok(true) => {}
+
+		Ok(false) => {
+			let summary = "Access denied.";
+			let error = SystemApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+
+		Err(error) => {
+			let summary = "Failed to check access permissions.";
+			let error = SystemApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	}
+
+	let result = match kind {
+		RenameKind::Role => {
+			state
+				.access_service
+				.rename_role(old_name, new_name, Some(navigator_id))
+				.await
+		}
+
+		RenameKind::Permission => {
+			state
+				.access_service
+				.rename_permission(old_name, new_name, Some(navigator_id))
+				.await
+		}
+	};
+
+	match result {
+		Ok(rename) => (
+			StatusCode::OK,
+			Json(Response::Single { data: Some(rename) }),
+		),
+
+		Err(error) => {
+			let status = match &error {
+				AccessServiceError::InvalidName(_) => StatusCode::BAD_REQUEST,
+				AccessServiceError::Repository(
+					AccessRepositoryError::RoleNotFound(_)
+					| AccessRepositoryError::PermissionNotFound(_),
+				) => StatusCode::NOT_FOUND,
+				AccessServiceError::Repository(AccessRepositoryError::NameTaken(_)) => {
+					StatusCode::CONFLICT
+				}
+				_ => StatusCode::INTERNAL_SERVER_ERROR,
+			};
+
+			let summary = "Failed to rename.";
+			let error = SystemApiError::Rename(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				status,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for listing the most recent renames of roles and
+/// permissions.
+async fn access_renames_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+) -> (StatusCode, Json<Response<AccessRename>>) {
+	let has_access = state
+		.access_service
+		.can_permission(navigator.nutty_id(), RENAME_PERMISSION)
+		.await;
+
+	match has_access {
+		Ok(true) => {}
+
+		Ok(false) => {
+			let summary = "Access denied.";
+			let error = SystemApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+
+		Err(error) => {
+			let summary = "Failed to check access permissions.";
+			let error = SystemApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	}
+
+	match state.access_service.list_renames().await {
+		Ok(renames) => (StatusCode::OK, Json(Response::Multiple { data: renames })),
+
+		Err(error) => {
+			let summary = "Failed to list renames.";
+			let error = SystemApiError::Rename(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+#[derive(Deserialize)]
+pub struct RequestUsageQuery {
+	/// How far back to look, e.g. `30m`, `6h`, or `7d`, which defaults to a
+	/// day.
+	window: Option<String>,
+
+	/// What to rank navigators by, which defaults to their request count.
+	#[serde(default)]
+	sort: UsageSort,
+
+	/// How many navigators to list, which defaults to 20.
+	limit: Option<usize>,
+}
+
+/// An API handler for ranking the navigators that have made the heaviest
+/// use of the API over a window, for fairness analysis.
+async fn request_usage_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+	Query(query): Query<RequestUsageQuery>,
+) -> (StatusCode, Json<Response<NavigatorUsage>>) {
+	let has_access = state
+		.access_service
+		.can_permission(navigator.nutty_id(), METRICS_PERMISSION)
+		.await;
+
+	match has_access {
+		Ok(true) => {}
+
+		Ok(false) => {
+			let summary = "Access denied.";
+			let error = SystemApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+
+		Err(error) => {
+			let summary = "Failed to check access permissions.";
+			let error = SystemApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	}
+
+	let window = match parse_window(query.window.as_deref().unwrap_or("24h")) {
+		Ok(window) => window,
+
+		Err(error) => {
+			let summary = "Invalid window.";
+			let error = SystemApiError::InvalidWindow(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	};
+
+	let since = chrono::Utc::now() - window;
+	let limit = query.limit.unwrap_or(20).clamp(1, MAX_USAGE_LIMIT);
+
+	let usage = state
+		.navigator_service
+		.rank_request_usage(&since, query.sort, limit)
+		.await;
+
+	match usage {
+		Ok(usage) => (StatusCode::OK, Json(Response::Multiple { data: usage })),
+
+		Err(error) => {
+			let summary = "Failed to rank request usage.";
+			let error = SystemApiError::RequestUsage(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+/// An API handler for describing the tables, columns, and constraints of the
+/// database, as read from its live catalog.
+async fn schema_handler(
+	State(state): State<Arc<AppState>>,
+	Session { navigator, .. }: Session,
+) -> (StatusCode, Json<Response<SchemaTable>>) {
+	let has_access = state
+		.access_service
+		.can_permission(navigator.nutty_id(), SCHEMA_PERMISSION)
+		.await;
+
+	match has_access {
+		Ok(true) => {}
+
+		Ok(false) => {
+			let summary = "Access denied.";
+			let error = SystemApiError::AccessDenied;
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::FORBIDDEN,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+
+		Err(error) => {
+			let summary = "Failed to check access permissions.";
+			let error = SystemApiError::AccessControl(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			return (
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			);
+		}
+	}
+
+	match state.system_service.describe_schema().await {
+		Ok(tables) => (StatusCode::OK, Json(Response::Multiple { data: tables })),
+
+		Err(error) => {
+			let summary = "Failed to describe the schema.";
+			let error = SystemApiError::DescribeSchema(error);
+			let error = Error::from_error(&error).with_summary(summary);
+
+			(
+				StatusCode::INTERNAL_SERVER_ERROR,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		}
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SystemApiError {
+	#[error("Access denied.")]
+	AccessDenied,
+
+	#[error("Failed to check access permissions: {0}")]
+	AccessControl(#[source] AccessServiceError),
+
+	#[error("Failed to check block stats: {0}")]
+	CheckBlockStats(#[source] ContentServiceError),
+
+	#[error("Failed to rename: {0}")]
+	Rename(#[source] AccessServiceError),
+
+	#[error("{0}")]
+	InvalidWindow(#[source] RequestUsageError),
+
+	#[error("Failed to rank request usage: {0}")]
+	RequestUsage(#[source] NavigatorServiceError),
+
+	#[error("Failed to describe the schema: {0}")]
+	DescribeSchema(#[source] SystemServiceError),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::navigator::service::NavigatorServiceApi;
+	use crate::testkit;
+	use crate::testkit::FakeAccessService;
+	use crate::testkit::FakeContentService;
+	use crate::testkit::FakeNavigatorService;
+
+	#[tokio::test]
+	async fn test_read_only_handler_requires_permission() {
+		// Arrange: Register and log in a navigator against the fakes.
+		let navigator_service = Arc::new(FakeNavigatorService::new());
+
+		navigator_service
+			.register("admin".to_string(), "password123".to_string())
+			.await
+			.unwrap();
+
+		let (navigator, session) = navigator_service
+			.login(
+				"admin".to_string(),
+				"password123".to_string(),
+				"test-agent".to_string(),
+				None,
+			)
+			.await
+			.unwrap();
+
+		let access_service = Arc::new(FakeAccessService::new());
+		let content_service = Arc::new(FakeContentService::new(access_service.clone()));
+
+		let state = testkit::app_state(
+			access_service,
+			content_service.clone(),
+			navigator_service.clone(),
+		);
+
+		let extractor = Session {
+			session: session.clone(),
+			navigator: navigator.clone(),
+		};
+
+		// Act: Toggle read-only mode without the permission.
+		let (status, _) = read_only_handler(
+			State(state.clone()),
+			extractor.clone(),
+			Json(ReadOnlyRequest { enabled: true }),
+		)
+		.await;
+
+		// Assert: The request was denied.
+		assert_eq!(status, StatusCode::FORBIDDEN);
+		assert!(!state.read_only.is_enabled());
+
+		// Arrange: Grant the permission.
+		let access_service =
+			Arc::new(FakeAccessService::new().allow(navigator.nutty_id(), READ_ONLY_PERMISSION));
+		let state = testkit::app_state(access_service, content_service, navigator_service);
+
+		// Act: Toggle read-only mode again.
+		let (status, _) = read_only_handler(
+			State(state.clone()),
+			extractor,
+			Json(ReadOnlyRequest { enabled: true }),
+		)
+		.await;
+
+		// Assert: Read-only mode was enabled.
+		assert_eq!(status, StatusCode::OK);
+		assert!(state.read_only.is_enabled());
+	}
+}
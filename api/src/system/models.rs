@@ -0,0 +1,61 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+/// A table in the database, as described by the live catalog.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaTable {
+	/// The schema that the table is in.
+	pub schema: String,
+
+	/// The name of the table.
+	pub name: String,
+
+	/// The columns of the table, in their declared order.
+	pub columns: Vec<SchemaColumn>,
+
+	/// The constraints on the table, leaving out implicit not-null checks.
+	pub constraints: Vec<SchemaConstraint>,
+}
+
+/// A column of a [SchemaTable].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaColumn {
+	pub name: String,
+
+	/// The column's type. Arrays and user-defined types are named by their
+	/// underlying type, such as `_text` for `TEXT[]`.
+	pub data_type: String,
+
+	pub nullable: bool,
+
+	/// The expression that the column defaults to, if any.
+	pub default: Option<String>,
+}
+
+/// A constraint on a [SchemaTable].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaConstraint {
+	pub name: String,
+
+	/// The kind of constraint: `PRIMARY KEY`, `UNIQUE`, `FOREIGN KEY`,
+	/// or `CHECK`.
+	pub kind: String,
+
+	/// The constrained columns, for keys.
+	pub columns: Vec<String>,
+
+	/// What a foreign key references.
+	pub references: Option<SchemaReference>,
+
+	/// The condition of a check constraint.
+	pub check: Option<String>,
+}
+
+/// The columns that a foreign key references.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaReference {
+	/// The referenced table, qualified by its schema.
+	pub table: String,
+
+	pub columns: Vec<String>,
+}
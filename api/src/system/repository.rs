@@ -0,0 +1,183 @@
+use sqlx::Postgres;
+use thiserror::Error;
+
+use crate::system::models::SchemaColumn;
+use crate::system::models::SchemaConstraint;
+use crate::system::models::SchemaReference;
+use crate::system::models::SchemaTable;
+use crate::utilities::query_metrics::RecordQuery;
+use crate::utilities::repository::Repository;
+
+/// A repository for reading the database's own catalog.
+/// Objects are stored in PostgreSQL.
+#[derive(Debug, Clone)]
+pub struct SystemRepository {
+	/// The PostgreSQL database pool.
+	pool: sqlx::Pool<Postgres>,
+}
+
+impl SystemRepository {
+	/// Create a new system repository.
+	pub fn new(pool: sqlx::Pool<Postgres>) -> Self {
+		Self { pool }
+	}
+
+	/// Describe the tables in the given schemas from `information_schema`,
+	/// ordered by schema and name.
+	pub async fn describe_schema(
+		&self,
+		schemas: &[&str],
+	) -> Result<Vec<SchemaTable>, SystemRepositoryError> {
+		let schemas: Vec<String> = schemas.iter().map(|schema| schema.to_string()).collect();
+
+		let columns = sqlx::query!(
+			r#"
+				SELECT
+					c.table_schema::text AS "table_schema!",
+					c.table_name::text AS "table_name!",
+					c.column_name::text AS "name!",
+					CASE
+						WHEN c.data_type IN ('ARRAY', 'USER-DEFINED') THEN c.udt_name::text
+						ELSE c.data_type::text
+					END AS "data_type!",
+					c.is_nullable = 'YES' AS "nullable!",
+					c.column_default::text AS "default"
+				FROM information_schema.columns c
+				JOIN information_schema.tables t
+					ON t.table_schema = c.table_schema
+					AND t.table_name = c.table_name
+				WHERE c.table_schema = ANY($1)
+					AND t.table_type = 'BASE TABLE'
+				ORDER BY c.table_schema, c.table_name, c.ordinal_position
+			"#,
+			&schemas,
+		)
+		.fetch_all(&self.pool)
+		.record_query("describe_schema_columns")
+		.await?;
+
+		// Not-null columns show up as check constraints too, which the
+		// columns already describe.
+		let constraints = sqlx::query!(
+			r#"
+				SELECT
+					tc.table_schema::text AS "table_schema!",
+					tc.table_name::text AS "table_name!",
+					tc.constraint_name::text AS "name!",
+					tc.constraint_type::text AS "kind!",
+					ARRAY(
+						SELECT kcu.column_name::text
+						FROM information_schema.key_column_usage kcu
+						WHERE kcu.constraint_schema = tc.constraint_schema
+							AND kcu.constraint_name = tc.constraint_name
+							AND kcu.table_name = tc.table_name
+						ORDER BY kcu.ordinal_position
+					) AS "columns!",
+					(
+						SELECT DISTINCT pk.table_schema::text || '.' || pk.table_name::text
+						FROM information_schema.referential_constraints rc
+						JOIN information_schema.key_column_usage pk
+							ON pk.constraint_schema = rc.unique_constraint_schema
+							AND pk.constraint_name = rc.unique_constraint_name
+						WHERE rc.constraint_schema = tc.constraint_schema
+							AND rc.constraint_name = tc.constraint_name
+					) AS "referenced_table",
+					ARRAY(
+						SELECT pk.column_name::text
+						FROM information_schema.referential_constraints rc
+						JOIN information_schema.key_column_usage fk
+							ON fk.constraint_schema = rc.constraint_schema
+							AND fk.constraint_name = rc.constraint_name
+							AND fk.table_name = tc.table_name
+						JOIN information_schema.key_column_usage pk
+							ON pk.constraint_schema = rc.unique_constraint_schema
+							AND pk.constraint_name = rc.unique_constraint_name
+							AND pk.ordinal_position = fk.position_in_unique_constraint
+						WHERE rc.constraint_schema = tc.constraint_schema
+							AND rc.constraint_name = tc.constraint_name
+						ORDER BY fk.ordinal_position
+					) AS "referenced_columns!",
+					cc.check_clause::text AS "check_clause"
+				FROM information_schema.table_constraints tc
+				LEFT JOIN information_schema.check_constraints cc
+					ON cc.constraint_schema = tc.constraint_schema
+					AND cc.constraint_name = tc.constraint_name
+				WHERE tc.table_schema = ANY($1)
+					AND NOT (
+						tc.constraint_type = 'CHECK'
+						AND cc.check_clause LIKE '% IS NOT NULL'
+					)
+				ORDER BY tc.table_schema, tc.table_name, tc.constraint_type, tc.constraint_name
+			"#,
+			&schemas,
+		)
+		.fetch_all(&self.pool)
+		.record_query("describe_schema_constraints")
+		.await?;
+
+		let mut tables: Vec<SchemaTable> = Vec::new();
+
+		for column in columns {
+			let is_same_table = tables.last().is_some_and(|table| {
+				table.schema == column.table_schema && table.name == column.table_name
+			});
+
+			if !is_same_table {
+				tables.push(SchemaTable {
+					schema: column.table_schema,
+					name: column.table_name,
+					columns: Vec::new(),
+					constraints: Vec::new(),
+				});
+			}
+
+			if let Some(table) = tables.last_mut() {
+				table.columns.push(SchemaColumn {
+					name: column.name,
+					data_type: column.data_type,
+					nullable: column.nullable,
+					default: column.default,
+				});
+			}
+		}
+
+		for constraint in constraints {
+			let table = tables.iter_mut().find(|table| {
+				table.schema == constraint.table_schema && table.name == constraint.table_name
+			});
+
+			let Some(table) = table else {
+				continue;
+			};
+
+			let references = constraint
+				.referenced_table
+				.map(|referenced_table| SchemaReference {
+					table: referenced_table,
+					columns: constraint.referenced_columns,
+				});
+
+			table.constraints.push(SchemaConstraint {
+				name: constraint.name,
+				kind: constraint.kind,
+				columns: constraint.columns,
+				references,
+				check: constraint.check_clause,
+			});
+		}
+
+		Ok(tables)
+	}
+}
+
+impl Repository for SystemRepository {
+	fn pool(&self) -> &sqlx::Pool<Postgres> {
+		&self.pool
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum SystemRepositoryError {
+	#[error("Database query failed: {0}")]
+	QueryFailed(#[from] sqlx::error::Error),
+}
@@ -0,0 +1,126 @@
+use async_trait::async_trait;
+
+use crate::system::models::SchemaTable;
+use crate::system::repository::SystemRepository;
+use crate::system::repository::SystemRepositoryError;
+use crate::utilities::row_level_security;
+
+/// The schemas that hold the storage model documented to clients.
+const DOCUMENTED_SCHEMAS: [&str; 2] = ["auth", "content"];
+
+#[derive(Clone)]
+pub struct SystemService {
+	repository: SystemRepository,
+}
+
+impl SystemService {
+	/// Create a new system service with the given repository.
+	pub fn new(repository: SystemRepository) -> Self {
+		Self { repository }
+	}
+}
+
+/// Introspecting the running system.
+/// Implemented by [SystemService], and by a fake in the testkit.
+#[async_trait]
+pub trait SystemServiceApi: Send + Sync {
+	/// Describe the tables, columns, and constraints of the auth and content
+	/// schemas, as they are in the live database.
+	async fn describe_schema(&self) -> Result<Vec<SchemaTable>, SystemServiceError>;
+}
+
+#[async_trait]
+impl SystemServiceApi for SystemService {
+	async fn describe_schema(&self) -> Result<Vec<SchemaTable>, SystemServiceError> {
+		// The catalog only lists tables that the connecting role has
+		// privileges on, which the restricted role lacks for most.
+		row_level_security::unrestricted(self.repository.describe_schema(&DOCUMENTED_SCHEMAS))
+			.await
+			.map_err(SystemServiceError::DescribeSchema)
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SystemServiceError {
+	#[error("Failed to describe schema: {0}")]
+	DescribeSchema(#[source] SystemRepositoryError),
+}
+
+#[cfg(test)]
+mod tests {
+	use sqlx::Pool;
+	use sqlx::Postgres;
+	use sqlx::postgres::PgPoolOptions;
+
+	use super::*;
+
+	async fn connect_to_test_database() -> Pool<Postgres> {
+		let database_url = std::env::var("DATABASE_URL").unwrap();
+
+		PgPoolOptions::new()
+			.max_connections(5)
+			.connect(&database_url)
+			.await
+			.expect("Failed to connect to test database")
+	}
+
+	#[tokio::test]
+	async fn test_describe_schema() {
+		// Arrange: Create a repository and service.
+		let pool = connect_to_test_database().await;
+		let service = SystemService::new(SystemRepository::new(pool));
+
+		// Act: Describe the schema.
+		let tables = service
+			.describe_schema()
+			.await
+			.expect("Failed to describe schema");
+
+		// Assert: Only the documented schemas are described.
+		assert!(
+			tables
+				.iter()
+				.all(|table| DOCUMENTED_SCHEMAS.contains(&table.schema.as_str()))
+		);
+
+		// Assert: Columns are described in their declared order.
+		let navigators = tables
+			.iter()
+			.find(|table| table.schema == "auth" && table.name == "navigators")
+			.expect("Navigators table not described");
+
+		assert_eq!(navigators.columns[0].name, "id");
+		assert!(!navigators.columns[0].nullable);
+
+		let primary_key = navigators
+			.constraints
+			.iter()
+			.find(|constraint| constraint.kind == "PRIMARY KEY")
+			.expect("Primary key not described");
+
+		assert_eq!(primary_key.columns, vec!["id"]);
+
+		// Assert: Foreign keys name what they reference.
+		let links = tables
+			.iter()
+			.find(|table| table.schema == "content" && table.name == "links")
+			.expect("Links table not described");
+
+		let reference = links
+			.constraints
+			.iter()
+			.filter_map(|constraint| constraint.references.as_ref())
+			.find(|reference| reference.table == "content.blocks")
+			.expect("Foreign key not described");
+
+		assert_eq!(reference.columns, vec!["id"]);
+
+		// Assert: Not-null checks are left to the columns.
+		assert!(
+			tables
+				.iter()
+				.flat_map(|table| &table.constraints)
+				.all(|constraint| !constraint.name.ends_with("_not_null"))
+		);
+	}
+}
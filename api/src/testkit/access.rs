@@ -0,0 +1,441 @@
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::access::models::AccessRename;
+use crate::access::models::PermissionCheck;
+use crate::access::models::PermissionResult;
+use crate::access::models::RenameKind;
+use crate::access::models::ResourceGrant;
+use crate::access::models::ResourceKind;
+use crate::access::models::ResourceRole;
+use crate::access::policy::AccessPolicy;
+use crate::access::policy::AccessPolicyChange;
+use crate::access::policy::AccessPolicyDiff;
+use crate::access::policy::PolicyPermission;
+use crate::access::policy::PolicyRole;
+use crate::access::repository::AccessRepositoryError;
+use crate::access::service::AccessServiceApi;
+use crate::access::service::AccessServiceError;
+use crate::access::service::validate_rename;
+use crate::models::NuttyId;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+
+/// An in-memory [AccessServiceApi].
+/// Roles must be defined with [FakeAccessService::with_role] before granting them.
+#[derive(Default)]
+pub struct FakeAccessService {
+	state: Mutex<FakeAccessState>,
+}
+
+#[derive(Default)]
+struct FakeAccessState {
+	/// The permissions of each role.
+	role_permissions: HashMap<String, HashSet<String>>,
+
+	/// The global roles of each navigator.
+	global_roles: HashMap<NuttyId, HashSet<String>>,
+
+	/// The roles of each navigator on a resource, keyed by (navigator, type, resource).
+	resource_roles: HashMap<(NuttyId, ResourceKind, NuttyId), HashSet<String>>,
+
+	/// The descriptions of permissions, and of roles, set by access policies.
+	permission_descriptions: HashMap<String, String>,
+	role_descriptions: HashMap<String, String>,
+
+	/// Renames of roles and permissions, oldest first.
+	renames: Vec<AccessRename>,
+}
+
+impl FakeAccessService {
+	/// Create an access service that denies everything.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Define a role with the given permissions.
+	pub fn with_role(self, role_name: &str, permissions: &[&str]) -> Self {
+		self.lock().role_permissions.insert(
+			role_name.to_string(),
+			permissions.iter().map(|p| p.to_string()).collect(),
+		);
+
+		self
+	}
+
+	/// Grant a permission globally, via a role named after it.
+	pub fn allow(self, navigator_id: &NuttyId, permission: &str) -> Self {
+		{
+			let mut state = self.lock();
+
+			state
+				.role_permissions
+				.entry(permission.to_string())
+				.or_default()
+				.insert(permission.to_string());
+
+			state
+				.global_roles
+				.entry(*navigator_id)
+				.or_default()
+				.insert(permission.to_string());
+		}
+
+		self
+	}
+
+	fn lock(&self) -> std::sync::MutexGuard<'_, FakeAccessState> {
+		self.state.lock().expect("Fake access state poisoned")
+	}
+}
+
+impl FakeAccessState {
+	fn grants(&self, roles: Option<&HashSet<String>>, permission: &str) -> bool {
+		roles.into_iter().flatten().any(|role| {
+			self
+				.role_permissions
+				.get(role)
+				.is_some_and(|permissions| permissions.contains(permission))
+		})
+	}
+
+	/// Record a rename for auditing.
+	fn record_rename(
+		&mut self,
+		kind: RenameKind,
+		old_name: &str,
+		new_name: &str,
+		grant_count: i64,
+		renamed_by: Option<&NuttyId>,
+	) -> AccessRename {
+		let rename = AccessRename {
+			nutty_id: NuttyId::now(),
+			kind,
+			old_name: old_name.to_string(),
+			new_name: new_name.to_string(),
+			grant_count,
+			renamed_by: renamed_by.copied(),
+			renamed_at: DateTimeRfc3339::new(chrono::Utc::now().fixed_offset()),
+		};
+
+		self.renames.push(rename.clone());
+		rename
+	}
+}
+
+/// Move a role to its new name in a set of roles. Returns whether it was
+/// in the set.
+fn rename_in(roles: &mut HashSet<String>, old_name: &str, new_name: &str) -> bool {
+	let renamed = roles.remove(old_name);
+
+	if renamed {
+		roles.insert(new_name.to_string());
+	}
+
+	renamed
+}
+
+#[async_trait]
+impl AccessServiceApi for FakeAccessService {
+	async fn check(&self, check: &PermissionCheck) -> Result<PermissionResult, AccessServiceError> {
+		let state = self.lock();
+
+		let Some(navigator_id) = check.navigator_id() else {
+			return Ok(PermissionResult::Denied);
+		};
+
+		if state.grants(state.global_roles.get(navigator_id), check.permission()) {
+			return Ok(PermissionResult::GrantedGlobal);
+		}
+
+		if let (Some(resource_type), Some(resource_id)) = (check.resource_type(), check.resource_id())
+		{
+			let key = (*navigator_id, resource_type, *resource_id);
+
+			if state.grants(state.resource_roles.get(&key), check.permission()) {
+				return Ok(PermissionResult::GrantedResource);
+			}
+		}
+
+		Ok(PermissionResult::Denied)
+	}
+
+	async fn grant_global_role(
+		&self,
+		navigator_id: &NuttyId,
+		role_name: &str,
+	) -> Result<(), AccessServiceError> {
+		self
+			.lock()
+			.global_roles
+			.entry(*navigator_id)
+			.or_default()
+			.insert(role_name.to_string());
+
+		Ok(())
+	}
+
+	async fn grant_resource_role(
+		&self,
+		navigator_id: &NuttyId,
+		role_name: &str,
+		resource_type: ResourceKind,
+		resource_id: &NuttyId,
+	) -> Result<(), AccessServiceError> {
+		self
+			.lock()
+			.resource_roles
+			.entry((*navigator_id, resource_type, *resource_id))
+			.or_default()
+			.insert(role_name.to_string());
+
+		Ok(())
+	}
+
+	async fn revoke_global_role(
+		&self,
+		navigator_id: &NuttyId,
+		role_name: &str,
+	) -> Result<(), AccessServiceError> {
+		if let Some(roles) = self.lock().global_roles.get_mut(navigator_id) {
+			roles.remove(role_name);
+		}
+
+		Ok(())
+	}
+
+	async fn revoke_resource_role(
+		&self,
+		navigator_id: &NuttyId,
+		role_name: &str,
+		resource_type: ResourceKind,
+		resource_id: &NuttyId,
+	) -> Result<(), AccessServiceError> {
+		let key = (*navigator_id, resource_type, *resource_id);
+
+		if let Some(roles) = self.lock().resource_roles.get_mut(&key) {
+			roles.remove(role_name);
+		}
+
+		Ok(())
+	}
+
+	async fn get_navigator_permissions(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<String>, AccessServiceError> {
+		let state = self.lock();
+
+		let mut permissions: Vec<_> = state
+			.global_roles
+			.get(navigator_id)
+			.into_iter()
+			.flatten()
+			.filter_map(|role| state.role_permissions.get(role))
+			.flatten()
+			.cloned()
+			.collect::<HashSet<_>>()
+			.into_iter()
+			.collect();
+
+		permissions.sort();
+		Ok(permissions)
+	}
+
+	/// Only direct grants are listed, since resources have no hierarchy here.
+	async fn get_resource_grants(
+		&self,
+		resource_type: ResourceKind,
+		resource_id: &NuttyId,
+	) -> Result<Vec<ResourceGrant>, AccessServiceError> {
+		let state = self.lock();
+
+		let mut grants: Vec<_> = state
+			.resource_roles
+			.iter()
+			.filter(|((_, kind, id), _)| *kind == resource_type && id == resource_id)
+			.flat_map(|((navigator_id, kind, id), roles)| {
+				roles.iter().map(|role| {
+					let role = ResourceRole::new(*navigator_id, role.clone(), *kind, *id);
+					ResourceGrant::new(role, None, false)
+				})
+			})
+			.collect();
+
+		grants.sort_by(|a, b| a.role().role_name().cmp(b.role().role_name()));
+		Ok(grants)
+	}
+
+	async fn rename_role(
+		&self,
+		old_name: &str,
+		new_name: &str,
+		renamed_by: Option<&NuttyId>,
+	) -> Result<AccessRename, AccessServiceError> {
+		validate_rename(old_name, new_name)?;
+		let mut state = self.lock();
+
+		if state.role_permissions.contains_key(new_name) {
+			let error = AccessRepositoryError::NameTaken(new_name.to_string());
+			return Err(AccessServiceError::Repository(error));
+		}
+
+		let Some(permissions) = state.role_permissions.remove(old_name) else {
+			let error = AccessRepositoryError::RoleNotFound(old_name.to_string());
+			return Err(AccessServiceError::Repository(error));
+		};
+
+		let state = &mut *state;
+		let mut grant_count = permissions.len() as i64;
+		state
+			.role_permissions
+			.insert(new_name.to_string(), permissions);
+
+		for roles in state
+			.global_roles
+			.values_mut()
+			.chain(state.resource_roles.values_mut())
+		{
+			grant_count += rename_in(roles, old_name, new_name) as i64;
+		}
+
+		Ok(state.record_rename(
+			RenameKind::Role,
+			old_name,
+			new_name,
+			grant_count,
+			renamed_by,
+		))
+	}
+
+	async fn rename_permission(
+		&self,
+		old_name: &str,
+		new_name: &str,
+		renamed_by: Option<&NuttyId>,
+	) -> Result<AccessRename, AccessServiceError> {
+		validate_rename(old_name, new_name)?;
+		let mut state = self.lock();
+		let has_permission = |state: &FakeAccessState, name: &str| {
+			state
+				.role_permissions
+				.values()
+				.any(|permissions| permissions.contains(name))
+		};
+
+		if !has_permission(&state, old_name) {
+			let error = AccessRepositoryError::PermissionNotFound(old_name.to_string());
+			return Err(AccessServiceError::Repository(error));
+		}
+
+		if has_permission(&state, new_name) {
+			let error = AccessRepositoryError::NameTaken(new_name.to_string());
+			return Err(AccessServiceError::Repository(error));
+		}
+
+		let grant_count = state
+			.role_permissions
+			.values_mut()
+			.map(|permissions| rename_in(permissions, old_name, new_name) as i64)
+			.sum();
+
+		Ok(state.record_rename(
+			RenameKind::Permission,
+			old_name,
+			new_name,
+			grant_count,
+			renamed_by,
+		))
+	}
+
+	async fn list_renames(&self) -> Result<Vec<AccessRename>, AccessServiceError> {
+		Ok(self.lock().renames.iter().rev().cloned().collect())
+	}
+
+	async fn export_access_policy(&self) -> Result<AccessPolicy, AccessServiceError> {
+		let state = self.lock();
+
+		let permission_names: BTreeSet<&String> = state
+			.role_permissions
+			.values()
+			.flatten()
+			.chain(state.permission_descriptions.keys())
+			.collect();
+
+		let permissions = permission_names
+			.into_iter()
+			.map(|name| PolicyPermission {
+				name: name.clone(),
+				description: state
+					.permission_descriptions
+					.get(name)
+					.cloned()
+					.unwrap_or_default(),
+			})
+			.collect();
+
+		let roles = state
+			.role_permissions
+			.iter()
+			.map(|(name, permissions)| PolicyRole {
+				name: name.clone(),
+				description: state
+					.role_descriptions
+					.get(name)
+					.cloned()
+					.unwrap_or_default(),
+				permissions: permissions.iter().cloned().collect(),
+			})
+			.collect();
+
+		Ok(AccessPolicy { permissions, roles }.sorted())
+	}
+
+	async fn apply_access_policy(
+		&self,
+		policy: &AccessPolicy,
+		dry_run: bool,
+	) -> Result<AccessPolicyDiff, AccessServiceError> {
+		let current = self.export_access_policy().await?;
+		let changes = policy.diff(&current)?;
+		let applied = !dry_run && !changes.is_empty();
+
+		if applied {
+			let mut state = self.lock();
+
+			for change in changes.iter().cloned() {
+				match change {
+					AccessPolicyChange::CreatePermission { name, description }
+					| AccessPolicyChange::DescribePermission { name, description } => {
+						state.permission_descriptions.insert(name, description);
+					}
+
+					AccessPolicyChange::CreateRole { name, description }
+					| AccessPolicyChange::DescribeRole { name, description } => {
+						state.role_permissions.entry(name.clone()).or_default();
+						state.role_descriptions.insert(name, description);
+					}
+
+					AccessPolicyChange::GrantPermission { role, permission } => {
+						state
+							.role_permissions
+							.entry(role)
+							.or_default()
+							.insert(permission);
+					}
+
+					AccessPolicyChange::RevokePermission { role, permission } => {
+						if let Some(permissions) = state.role_permissions.get_mut(&role) {
+							permissions.remove(&permission);
+						}
+					}
+				}
+			}
+		}
+
+		Ok(AccessPolicyDiff { applied, changes })
+	}
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::integrations::chatbot::models::ChatPlatformKind;
+use crate::integrations::chatbot::models::LinkCode;
+use crate::integrations::chatbot::service::ChatbotServiceApi;
+use crate::integrations::chatbot::service::ChatbotServiceError;
+use crate::models::NuttyId;
+
+/// An in-memory [ChatbotServiceApi]. Link codes don't expire.
+#[derive(Default)]
+pub struct FakeChatbotService {
+	state: Mutex<FakeChatbotState>,
+}
+
+#[derive(Default)]
+struct FakeChatbotState {
+	codes: HashMap<String, NuttyId>,
+	links: HashMap<(ChatPlatformKind, String), NuttyId>,
+}
+
+impl FakeChatbotService {
+	/// Create a chatbot service with no links.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	fn lock(&self) -> std::sync::MutexGuard<'_, FakeChatbotState> {
+		self.state.lock().expect("Fake chatbot state poisoned")
+	}
+}
+
+#[async_trait]
+impl ChatbotServiceApi for FakeChatbotService {
+	async fn create_link_code(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<LinkCode, ChatbotServiceError> {
+		let link_code = LinkCode::new();
+		self
+			.lock()
+			.codes
+			.insert(link_code.code.clone(), *navigator_id);
+		Ok(link_code)
+	}
+
+	async fn link_chat_user(
+		&self,
+		platform: ChatPlatformKind,
+		chat_user_id: &str,
+		code: &str,
+	) -> Result<NuttyId, ChatbotServiceError> {
+		let mut state = self.lock();
+
+		let navigator_id = state
+			.codes
+			.remove(code)
+			.ok_or(ChatbotServiceError::InvalidLinkCode)?;
+
+		state
+			.links
+			.insert((platform, chat_user_id.to_string()), navigator_id);
+
+		Ok(navigator_id)
+	}
+
+	async fn get_linked_navigator(
+		&self,
+		platform: ChatPlatformKind,
+		chat_user_id: &str,
+	) -> Result<Option<NuttyId>, ChatbotServiceError> {
+		Ok(self
+			.lock()
+			.links
+			.get(&(platform, chat_user_id.to_string()))
+			.copied())
+	}
+}
@@ -0,0 +1,2077 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use chrono::DateTime;
+use chrono::FixedOffset;
+use chrono::NaiveDate;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::access::models::ResourceGrant;
+use crate::access::models::ResourceKind;
+use crate::access::service::AccessServiceApi;
+use crate::content::block_kind::BlockKindError;
+use crate::content::block_kind::BlockKindRegistry;
+use crate::content::html_import;
+use crate::content::repository::ContentRepositoryError;
+use crate::content::service::ContentServiceApi;
+use crate::content::service::ContentServiceError;
+use crate::content::service::MAX_CALENDAR_DAYS;
+use crate::content::service::MAX_SYNC_CHANGES;
+use crate::models::BlockContent;
+use crate::models::ContentBlock;
+use crate::models::ContentCalendar;
+use crate::models::ContentContext;
+use crate::models::DissociatedNuttyId;
+use crate::models::FractionalIndex;
+use crate::models::NuttyId;
+use crate::models::ShareLevel;
+use crate::models::Task;
+use crate::models::access_request::AccessRequest;
+use crate::models::access_request::AccessRequestStatus;
+use crate::models::block_annotation::BlockAnnotation;
+use crate::models::block_archival::BlockArchival;
+use crate::models::block_checksum::BlockChecksum;
+use crate::models::block_conversion;
+use crate::models::block_conversion::BlockConversion;
+use crate::models::block_conversion::ConvertibleKind;
+use crate::models::block_date::BlockDate;
+use crate::models::block_date::BlockDateKind;
+use crate::models::block_deletion::BlockDeletion;
+use crate::models::block_group::RootBlockGroup;
+use crate::models::block_merge;
+use crate::models::block_merge::BlockMerge;
+use crate::models::block_merge::BlockMergeError;
+use crate::models::block_merge::MergeStrategy;
+use crate::models::block_revision::BlockRevision;
+use crate::models::block_split;
+use crate::models::block_split::BlockSplit;
+use crate::models::block_stats::BlockStatsCheck;
+use crate::models::capture::Capture;
+use crate::models::capture::CapturedPage;
+use crate::models::capture::PageSnapshot;
+use crate::models::children_view::ChildrenView;
+use crate::models::content_diff::ContentDiff;
+use crate::models::content_filter::ContentFilter;
+use crate::models::context_include::ContextInclude;
+use crate::models::find_replace::FindReplace;
+use crate::models::find_replace::TextMatch;
+use crate::models::incoming_email::IncomingEmail;
+use crate::models::linked_reference::LinkedReference;
+use crate::models::outline::OutlineEntry;
+use crate::models::ownership_transfer::OwnershipTransfer;
+use crate::models::property::PropertyDefinition;
+use crate::models::property::validate_properties;
+use crate::models::search_language::SearchLanguage;
+use crate::models::site_settings::SiteSettings;
+use crate::models::slug::Slug;
+use crate::models::slug::SlugTarget;
+use crate::models::sync_changes::SyncChanges;
+use crate::models::sync_changes::SyncCursor;
+use crate::models::task::Breadcrumb;
+use crate::models::task::TaskStatus;
+use crate::models::translation::Lang;
+use crate::models::translation::LocalizedBlock;
+use crate::models::translation::Translation;
+
+/// An in-memory [ContentServiceApi].
+/// Links and block queries are not modelled, so contexts carry no references,
+/// backlinks, or query results.
+pub struct FakeContentService {
+	/// The stored content blocks, keyed by their dissociated Nutty ID.
+	blocks: Mutex<HashMap<String, ContentBlock>>,
+
+	/// The custom property definitions.
+	property_definitions: Mutex<Vec<PropertyDefinition>>,
+
+	/// The access requests, in creation order.
+	access_requests: Mutex<Vec<AccessRequest>>,
+
+	/// The search languages set on blocks, keyed by their dissociated Nutty ID.
+	languages: Mutex<HashMap<String, SearchLanguage>>,
+
+	/// The archived blocks, by their Nutty ID.
+	archived: Mutex<HashSet<NuttyId>>,
+
+	/// The blocks that navigators' inbound emails are filed under.
+	inboxes: Mutex<HashMap<NuttyId, NuttyId>>,
+
+	/// The annotations of blocks, keyed by their Nutty ID.
+	annotations: Mutex<HashMap<NuttyId, Vec<BlockAnnotation>>>,
+
+	/// The claimed slugs, with the navigator that claimed them.
+	slugs: Mutex<HashMap<Slug, FakeSlug>>,
+
+	/// The navigators' site settings.
+	site_settings: Mutex<HashMap<NuttyId, SiteSettings>>,
+
+	/// The (group ID, language) of translated blocks, keyed by their Nutty ID.
+	translations: Mutex<HashMap<NuttyId, (Uuid, Lang)>>,
+
+	/// The access service to use for permission checking.
+	access_service: Arc<dyn AccessServiceApi>,
+}
+
+/// A slug claimed in a [FakeContentService].
+struct FakeSlug {
+	block_id: NuttyId,
+	navigator_id: NuttyId,
+	is_current: bool,
+}
+
+impl FakeContentService {
+	/// Create an empty content service.
+	pub fn new(access_service: Arc<dyn AccessServiceApi>) -> Self {
+		Self {
+			blocks: Mutex::new(HashMap::new()),
+			property_definitions: Mutex::new(vec![]),
+			access_requests: Mutex::new(vec![]),
+			languages: Mutex::new(HashMap::new()),
+			archived: Mutex::new(HashSet::new()),
+			inboxes: Mutex::new(HashMap::new()),
+			annotations: Mutex::new(HashMap::new()),
+			slugs: Mutex::new(HashMap::new()),
+			site_settings: Mutex::new(HashMap::new()),
+			translations: Mutex::new(HashMap::new()),
+			access_service,
+		}
+	}
+
+	/// Seed the service with a content block.
+	pub fn with_block(self, block: ContentBlock) -> Self {
+		self.lock().insert(block.nutty_id().nid(), block);
+		self
+	}
+
+	fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, ContentBlock>> {
+		self.blocks.lock().expect("Fake content state poisoned")
+	}
+
+	fn definitions(&self) -> std::sync::MutexGuard<'_, Vec<PropertyDefinition>> {
+		self
+			.property_definitions
+			.lock()
+			.expect("Fake property definitions poisoned")
+	}
+
+	fn archived(&self) -> std::sync::MutexGuard<'_, HashSet<NuttyId>> {
+		self.archived.lock().expect("Fake archived blocks poisoned")
+	}
+
+	fn annotations(&self) -> std::sync::MutexGuard<'_, HashMap<NuttyId, Vec<BlockAnnotation>>> {
+		self.annotations.lock().expect("Fake annotations poisoned")
+	}
+
+	fn translations(&self) -> std::sync::MutexGuard<'_, HashMap<NuttyId, (Uuid, Lang)>> {
+		self
+			.translations
+			.lock()
+			.expect("Fake translations poisoned")
+	}
+
+	/// Get a block, failing if it doesn't exist.
+	fn existing_block(
+		&self,
+		block_id: &DissociatedNuttyId,
+	) -> Result<ContentBlock, ContentServiceError> {
+		self
+			.lock()
+			.get(&block_id.nid())
+			.cloned()
+			.ok_or(ContentServiceError::ContentBlockNotFound)
+	}
+
+	/// List the translations of a block, ordered by language.
+	fn translations_of(&self, block_id: &NuttyId) -> Vec<Translation> {
+		let translations = self.translations();
+
+		let Some((group_id, _)) = translations.get(block_id) else {
+			return vec![];
+		};
+
+		let mut group = translations
+			.iter()
+			.filter(|(_, (group, _))| group == group_id)
+			.map(|(block_id, (_, lang))| Translation {
+				block_id: *block_id,
+				lang: lang.clone(),
+			})
+			.collect::<Vec<_>>();
+
+		group.sort_by(|a, b| a.lang.as_str().cmp(b.lang.as_str()));
+		group
+	}
+
+	/// Keep the translations that a navigator can read.
+	async fn readable_translations(
+		&self,
+		navigator_id: &NuttyId,
+		translations: Vec<Translation>,
+	) -> Result<Vec<Translation>, ContentServiceError> {
+		let ids: Vec<NuttyId> = translations
+			.iter()
+			.map(|translation| translation.block_id)
+			.collect();
+
+		let readable_ids = self.list_readable_block_ids(navigator_id, &ids).await?;
+
+		Ok(translations
+			.into_iter()
+			.filter(|translation| readable_ids.contains(&translation.block_id))
+			.collect())
+	}
+
+	fn requests(&self) -> std::sync::MutexGuard<'_, Vec<AccessRequest>> {
+		self
+			.access_requests
+			.lock()
+			.expect("Fake access requests poisoned")
+	}
+
+	/// Grant a share level, replacing any level granted before.
+	async fn grant_share_level(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &NuttyId,
+		level: ShareLevel,
+	) -> Result<(), ContentServiceError> {
+		for previous in ShareLevel::ALL {
+			self
+				.access_service
+				.revoke_resource_role(
+					navigator_id,
+					previous.role_name(),
+					ResourceKind::ContentBlock,
+					block_id,
+				)
+				.await
+				.map_err(ContentServiceError::AccessControl)?;
+		}
+
+		self
+			.access_service
+			.grant_resource_role(
+				navigator_id,
+				level.role_name(),
+				ResourceKind::ContentBlock,
+				block_id,
+			)
+			.await
+			.map_err(ContentServiceError::AccessControl)
+	}
+
+	/// Check that a navigator may read and write raw content.
+	async fn require_raw_content_permission(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<(), ContentServiceError> {
+		let can_access = self
+			.access_service
+			.can_permission(navigator_id, "content_blocks:raw")
+			.await
+			.map_err(ContentServiceError::AccessControl)?;
+
+		match can_access {
+			true => Ok(()),
+			false => Err(ContentServiceError::RawContentDenied),
+		}
+	}
+
+	/// Get the sibling right after a block, if it has a parent.
+	fn next_sibling<'a>(
+		blocks: &'a HashMap<String, ContentBlock>,
+		block: &ContentBlock,
+	) -> Option<&'a ContentBlock> {
+		block.parent_id?;
+
+		blocks
+			.values()
+			.filter(|b| b.parent_id == block.parent_id && b.f_index.as_str() > block.f_index.as_str())
+			.min_by(|a, b| a.f_index.as_str().cmp(b.f_index.as_str()))
+	}
+
+	/// Get a block and its ancestors, nearest first.
+	fn block_and_ancestors(
+		&self,
+		block_id: &DissociatedNuttyId,
+	) -> Result<(ContentBlock, Vec<ContentBlock>), ContentServiceError> {
+		let blocks = self.lock();
+
+		let block = blocks
+			.get(&block_id.nid())
+			.cloned()
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		let mut ancestors = vec![];
+		let mut parent_id = block.parent_id;
+
+		while let Some(parent) = parent_id.and_then(|id| blocks.get(&id.nid())) {
+			ancestors.push(parent.clone());
+			parent_id = parent.parent_id;
+		}
+
+		Ok((block, ancestors))
+	}
+
+	/// Build a block's context, with its descendants and annotations.
+	fn context(
+		&self,
+		nutty_id: &DissociatedNuttyId,
+		view: &ChildrenView,
+	) -> Result<ContentContext, ContentServiceError> {
+		let (block, ancestors) = self.block_and_ancestors(nutty_id)?;
+		let blocks = self.lock();
+
+		// Collect descendants breadth-first.
+		let mut descendants: Vec<ContentBlock> = vec![];
+		let mut frontier = vec![*block.nutty_id()];
+
+		while let Some(parent_id) = frontier.pop() {
+			for child in blocks.values().filter(|b| b.parent_id == Some(parent_id)) {
+				frontier.push(*child.nutty_id());
+				descendants.push(child.clone());
+			}
+		}
+
+		let archived = self.archived();
+		let hide_archived = view.hide_archived && !archived.contains(block.nutty_id());
+
+		let mut children: Vec<_> = descendants
+			.iter()
+			.filter(|b| b.parent_id == Some(*block.nutty_id()))
+			.filter(|b| !hide_archived || !archived.contains(b.nutty_id()))
+			.collect();
+
+		children.sort_by(|a, b| a.f_index.as_str().cmp(b.f_index.as_str()));
+		let children_ids = view.apply(children);
+
+		let block_cache: HashMap<NuttyId, ContentBlock> = std::iter::once(&block)
+			.chain(&ancestors)
+			.chain(&descendants)
+			.map(|b| (*b.nutty_id(), b.clone()))
+			.collect();
+
+		let annotations = block_cache
+			.keys()
+			.filter_map(|id| self.annotations().get(id).cloned())
+			.flatten()
+			.collect();
+
+		ContentContext::builder()
+			.block_id(*block.nutty_id())
+			.parent_id(block.parent_id)
+			.ancestor_ids(ancestors.iter().rev().map(|b| *b.nutty_id()).collect())
+			.children_ids(children_ids)
+			.block_cache(block_cache)
+			.annotations(annotations)
+			.try_build()
+			.map_err(|err| ContentServiceError::BuildContentContext(err.to_string()))
+	}
+
+	/// Mirror [ContentService]'s access rules: global, resource (on the block
+	/// or an ancestor), or ownership.
+	///
+	/// [ContentService]: crate::content::service::ContentService
+	async fn check_access(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		mode: &str,
+	) -> Result<bool, ContentServiceError> {
+		let (block, ancestors) = self.block_and_ancestors(block_id)?;
+		let access = &self.access_service;
+
+		let resource_permission = match mode {
+			"read" => "content_blocks:read:resource",
+			"comment" => "content_blocks:comment:resource",
+			"manage" => "content_blocks:manage:resource",
+			_ => "content_blocks:write",
+		};
+
+		if access
+			.can_permission(navigator_id, &format!("content_blocks:{mode}:all"))
+			.await
+			.map_err(ContentServiceError::AccessControl)?
+		{
+			return Ok(true);
+		}
+
+		if block.is_owned_by(navigator_id)
+			&& access
+				.can_permission(navigator_id, &format!("content_blocks:{mode}:own"))
+				.await
+				.map_err(ContentServiceError::AccessControl)?
+		{
+			return Ok(true);
+		}
+
+		for block in std::iter::once(&block).chain(block.access_ancestors(&ancestors)) {
+			if access
+				.can_on_resource(
+					navigator_id,
+					resource_permission,
+					ResourceKind::ContentBlock,
+					block.nutty_id(),
+				)
+				.await
+				.map_err(ContentServiceError::AccessControl)?
+			{
+				return Ok(true);
+			}
+		}
+
+		Ok(false)
+	}
+}
+
+#[async_trait]
+impl ContentServiceApi for FakeContentService {
+	/// Block queries aren't run, so there are no results to check access to.
+	async fn get_content_block_context(
+		&self,
+		_navigator_id: &NuttyId,
+		nutty_id: &DissociatedNuttyId,
+		view: &ChildrenView,
+	) -> Result<ContentContext, ContentServiceError> {
+		self.context(nutty_id, view)
+	}
+
+	/// Everything is in memory, so the whole context is fetched.
+	async fn get_content_block_context_partial(
+		&self,
+		nutty_id: &DissociatedNuttyId,
+		_include: &ContextInclude,
+		view: &ChildrenView,
+	) -> Result<ContentContext, ContentServiceError> {
+		self.context(nutty_id, view)
+	}
+
+	/// History isn't kept, so blocks are read as they are now.
+	async fn get_content_block_context_as_of(
+		&self,
+		nutty_id: &DissociatedNuttyId,
+		_as_of: &DateTime<FixedOffset>,
+		view: &ChildrenView,
+	) -> Result<ContentContext, ContentServiceError> {
+		self.context(nutty_id, view)
+	}
+
+	async fn save_content_block(
+		&self,
+		_author_id: Option<&NuttyId>,
+		content_block: ContentBlock,
+	) -> Result<ContentBlock, ContentServiceError> {
+		// Only the core kinds are registered.
+		BlockKindRegistry::default()
+			.validate(&content_block.content)
+			.map_err(|error| match error {
+				BlockKindError::InvalidQuery(error) => ContentServiceError::ParseBlockQuery(error),
+				error => ContentServiceError::InvalidContent(error),
+			})?;
+
+		validate_properties(&self.definitions(), &content_block.properties)
+			.map_err(ContentServiceError::InvalidProperties)?;
+
+		self
+			.lock()
+			.insert(content_block.nutty_id().nid(), content_block.clone());
+
+		Ok(content_block)
+	}
+
+	async fn rename_page(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		new_title: String,
+	) -> Result<Vec<ContentBlock>, ContentServiceError> {
+		if new_title.contains("[[") || new_title.contains("]]") {
+			return Err(ContentServiceError::InvalidPageTitle);
+		}
+
+		if !self.check_access(navigator_id, block_id, "write").await? {
+			return Err(ContentServiceError::EditDenied);
+		}
+
+		let mut blocks = self.lock();
+
+		let page = blocks
+			.get_mut(&block_id.nid())
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		let BlockContent::Page { .. } = page.content else {
+			return Err(ContentServiceError::NotAPage);
+		};
+
+		page.content = BlockContent::Page { title: new_title };
+		Ok(vec![page.clone()])
+	}
+
+	async fn delete_content_block(
+		&self,
+		block_id: &DissociatedNuttyId,
+		_unlink: bool,
+	) -> Result<BlockDeletion, ContentServiceError> {
+		let mut blocks = self.lock();
+
+		let block = blocks
+			.get(&block_id.nid())
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		let mut deleted_ids = vec![*block.nutty_id()];
+		let mut i = 0;
+
+		while let Some(id) = deleted_ids.get(i).copied() {
+			deleted_ids.extend(
+				blocks
+					.values()
+					.filter(|b| b.parent_id == Some(id))
+					.map(|b| *b.nutty_id()),
+			);
+
+			i += 1;
+		}
+
+		for id in &deleted_ids {
+			blocks.remove(&id.nid());
+		}
+
+		// Without links, nothing links into the deleted blocks.
+		Ok(BlockDeletion {
+			block_id: deleted_ids[0],
+			deleted_ids,
+			inbound_link_count: 0,
+			unlinked_ids: vec![],
+		})
+	}
+
+	async fn check_content_block_access(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<bool, ContentServiceError> {
+		self.check_access(navigator_id, block_id, "read").await
+	}
+
+	async fn check_content_block_write_access(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<bool, ContentServiceError> {
+		self.check_access(navigator_id, block_id, "write").await
+	}
+
+	async fn list_hidden_block_ids(
+		&self,
+		_ids: &[NuttyId],
+	) -> Result<Vec<NuttyId>, ContentServiceError> {
+		Ok(vec![])
+	}
+
+	async fn list_readable_block_ids(
+		&self,
+		navigator_id: &NuttyId,
+		ids: &[NuttyId],
+	) -> Result<Vec<NuttyId>, ContentServiceError> {
+		let mut readable = vec![];
+
+		for id in ids {
+			if self
+				.check_access(navigator_id, &id.dissociate(), "read")
+				.await?
+			{
+				readable.push(*id);
+			}
+		}
+
+		Ok(readable)
+	}
+
+	async fn check_content_block_comment_access(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<bool, ContentServiceError> {
+		if self.check_access(navigator_id, block_id, "write").await? {
+			return Ok(true);
+		}
+
+		Ok(self.check_access(navigator_id, block_id, "read").await?
+			&& self.check_access(navigator_id, block_id, "comment").await?)
+	}
+
+	async fn check_content_block_manage_access(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<bool, ContentServiceError> {
+		self.check_access(navigator_id, block_id, "manage").await
+	}
+
+	async fn transfer_ownership(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		new_owner_id: &NuttyId,
+		include_descendants: bool,
+	) -> Result<Vec<OwnershipTransfer>, ContentServiceError> {
+		if !self
+			.access_service
+			.can_permission(navigator_id, "content_blocks:transfer")
+			.await
+			.map_err(ContentServiceError::AccessControl)?
+		{
+			return Err(ContentServiceError::TransferDenied);
+		}
+
+		let (block, _) = self.block_and_ancestors(block_id)?;
+		let mut blocks = self.lock();
+		let mut transfers = vec![];
+		let mut frontier = vec![*block.nutty_id()];
+
+		while let Some(id) = frontier.pop() {
+			if include_descendants {
+				frontier.extend(
+					blocks
+						.values()
+						.filter(|b| b.parent_id == Some(id))
+						.map(|b| *b.nutty_id()),
+				);
+			}
+
+			let block = blocks
+				.get_mut(&id.nid())
+				.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+			if block.owner_id != Some(*new_owner_id) {
+				transfers.push(OwnershipTransfer {
+					block_id: id,
+					previous_owner_id: block.owner_id.replace(*new_owner_id),
+					new_owner_id: *new_owner_id,
+				});
+			}
+		}
+
+		Ok(transfers)
+	}
+
+	async fn get_content_block_grants(
+		&self,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Vec<ResourceGrant>, ContentServiceError> {
+		let (block, ancestors) = self.block_and_ancestors(block_id)?;
+		let mut grants = vec![];
+
+		for (depth, block) in std::iter::once(&block)
+			.chain(block.access_ancestors(&ancestors))
+			.enumerate()
+		{
+			let direct = self
+				.access_service
+				.get_resource_grants(ResourceKind::ContentBlock, block.nutty_id())
+				.await
+				.map_err(ContentServiceError::AccessControl)?;
+
+			grants.extend(
+				direct
+					.into_iter()
+					.map(|grant| ResourceGrant::new(grant.role().clone(), None, depth > 0)),
+			);
+		}
+
+		Ok(grants)
+	}
+
+	async fn share_content_block(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		recipient_id: &NuttyId,
+		level: ShareLevel,
+	) -> Result<Vec<ResourceGrant>, ContentServiceError> {
+		let (block, _) = self.block_and_ancestors(block_id)?;
+
+		if !block.is_owned_by(navigator_id)
+			&& !self.check_access(navigator_id, block_id, "manage").await?
+		{
+			return Err(ContentServiceError::ShareDenied);
+		}
+
+		self
+			.grant_share_level(recipient_id, block.nutty_id(), level)
+			.await?;
+
+		self.get_content_block_grants(block_id).await
+	}
+
+	async fn request_access(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		level: ShareLevel,
+	) -> Result<AccessRequest, ContentServiceError> {
+		let (block, _) = self.block_and_ancestors(block_id)?;
+		let mut requests = self.requests();
+
+		if requests.iter().any(|request| {
+			request.status() == AccessRequestStatus::Pending
+				&& request.block_id() == block.nutty_id()
+				&& request.navigator_id() == navigator_id
+		}) {
+			return Err(ContentServiceError::SaveAccessRequest(
+				ContentRepositoryError::AccessRequestPending,
+			));
+		}
+
+		let request = AccessRequest::new(*block.nutty_id(), *navigator_id, level);
+		requests.push(request.clone());
+		Ok(request)
+	}
+
+	async fn list_access_requests(
+		&self,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Vec<AccessRequest>, ContentServiceError> {
+		let (block, _) = self.block_and_ancestors(block_id)?;
+
+		Ok(self
+			.requests()
+			.iter()
+			.filter(|request| {
+				request.block_id() == block.nutty_id()
+					&& request.status() == AccessRequestStatus::Pending
+			})
+			.cloned()
+			.collect())
+	}
+
+	async fn resolve_access_request(
+		&self,
+		navigator_id: &NuttyId,
+		request_id: &NuttyId,
+		approve: bool,
+	) -> Result<AccessRequest, ContentServiceError> {
+		let request = self
+			.requests()
+			.iter()
+			.find(|request| {
+				request.nutty_id() == request_id && request.status() == AccessRequestStatus::Pending
+			})
+			.cloned()
+			.ok_or(ContentServiceError::AccessRequestNotFound)?;
+
+		if !self
+			.check_access(navigator_id, &request.block_id().dissociate(), "manage")
+			.await?
+		{
+			return Err(ContentServiceError::ManageDenied);
+		}
+
+		let mut resolved = request.clone();
+		resolved.resolve(*navigator_id, approve);
+
+		if let Some(stored) = self
+			.requests()
+			.iter_mut()
+			.find(|stored| stored.nutty_id() == request_id)
+		{
+			*stored = resolved.clone();
+		}
+
+		if approve {
+			self
+				.grant_share_level(request.navigator_id(), request.block_id(), request.level())
+				.await?;
+		}
+
+		Ok(resolved)
+	}
+
+	async fn get_content_calendar(
+		&self,
+		navigator_id: &NuttyId,
+		from: NaiveDate,
+		to: NaiveDate,
+	) -> Result<ContentCalendar, ContentServiceError> {
+		if from > to || (to - from).num_days() >= MAX_CALENDAR_DAYS {
+			return Err(ContentServiceError::InvalidDateRange);
+		}
+
+		let blocks: Vec<ContentBlock> = self.lock().values().cloned().collect();
+		let mut entries = vec![];
+		let mut block_cache = HashMap::new();
+
+		for block in blocks {
+			let dates: Vec<BlockDate> = block
+				.content
+				.parse_dates()
+				.into_iter()
+				.filter(|(date, _)| *date >= from && *date <= to)
+				.map(|(date, kind)| BlockDate::new(*block.nutty_id(), date, kind))
+				.collect();
+
+			if dates.is_empty()
+				|| !self
+					.check_content_block_access(navigator_id, &block.nutty_id().dissociate())
+					.await?
+			{
+				continue;
+			}
+
+			entries.extend(dates);
+			block_cache.insert(*block.nutty_id(), block);
+		}
+
+		entries.sort_by_key(|date| (date.date, date.kind, *date.block_id.uuid()));
+		Ok(ContentCalendar::new(entries, block_cache))
+	}
+
+	/// Finds daily notes by title, without cleaning the markdown.
+	async fn append_to_daily_note(
+		&self,
+		navigator_id: &NuttyId,
+		date: NaiveDate,
+		markdown: String,
+	) -> Result<ContentBlock, ContentServiceError> {
+		let title = date.format("%Y-%m-%d").to_string();
+		let mut blocks = self.lock();
+
+		let note_id = blocks
+			.values()
+			.filter(|block| block.is_owned_by(navigator_id))
+			.filter(|block| matches!(&block.content, BlockContent::Page { title: t } if *t == title))
+			.map(|block| *block.nutty_id())
+			.min_by_key(|id| *id.uuid());
+
+		let note_id = note_id.unwrap_or_else(|| {
+			let note = ContentBlock::now_with_owner(
+				None,
+				*navigator_id,
+				FractionalIndex::start(),
+				BlockContent::Page { title },
+			);
+
+			let note_id = *note.nutty_id();
+			blocks.insert(note_id.nid(), note);
+			note_id
+		});
+
+		let last_f_index = blocks
+			.values()
+			.filter(|block| block.parent_id == Some(note_id))
+			.map(|block| block.f_index.clone())
+			.max_by(|a, b| a.as_str().cmp(b.as_str()))
+			.unwrap_or_else(FractionalIndex::start);
+
+		let f_index = FractionalIndex::between(&last_f_index, &FractionalIndex::end())
+			.map_err(ContentServiceError::OrderContentBlock)?;
+
+		let block = ContentBlock::now_with_owner(
+			Some(note_id),
+			*navigator_id,
+			f_index,
+			BlockContent::Paragraph { markdown },
+		);
+
+		blocks.insert(block.nutty_id().nid(), block.clone());
+		Ok(block)
+	}
+
+	async fn list_tasks(
+		&self,
+		navigator_id: &NuttyId,
+		status: Option<TaskStatus>,
+		due_before: Option<NaiveDate>,
+	) -> Result<Vec<Task>, ContentServiceError> {
+		let mut todos: Vec<ContentBlock> = self
+			.lock()
+			.values()
+			.filter(|block| match &block.content {
+				BlockContent::Todo { done, .. } => status.is_none_or(|s| s.is_done() == *done),
+				_ => false,
+			})
+			.filter(|block| {
+				due_before.is_none_or(|due_before| {
+					block
+						.content
+						.parse_dates()
+						.iter()
+						.any(|(date, kind)| *kind == BlockDateKind::Due && *date < due_before)
+				})
+			})
+			.cloned()
+			.collect();
+
+		todos.sort_by(|a, b| a.created_at().inner().cmp(b.created_at().inner()));
+
+		let mut tasks = vec![];
+
+		for block in todos {
+			let block_id = block.nutty_id().dissociate();
+
+			if !self.check_access(navigator_id, &block_id, "read").await? {
+				continue;
+			}
+
+			let (_, ancestors) = self.block_and_ancestors(&block_id)?;
+
+			let breadcrumbs = ancestors
+				.iter()
+				.rev()
+				.filter_map(|ancestor| match &ancestor.content {
+					BlockContent::Page { title } => Some(Breadcrumb {
+						block_id: *ancestor.nutty_id(),
+						title: title.clone(),
+					}),
+					_ => None,
+				})
+				.collect();
+
+			tasks.push(Task { block, breadcrumbs });
+		}
+
+		Ok(tasks)
+	}
+
+	async fn toggle_todo(
+		&self,
+		block_id: &DissociatedNuttyId,
+	) -> Result<ContentBlock, ContentServiceError> {
+		let mut blocks = self.lock();
+
+		let block = blocks
+			.get_mut(&block_id.nid())
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		let BlockContent::Todo { done, .. } = &mut block.content else {
+			return Err(ContentServiceError::NotATodo);
+		};
+
+		*done = !*done;
+		Ok(block.clone())
+	}
+
+	async fn find_and_replace(
+		&self,
+		owner_id: &NuttyId,
+		pattern: &str,
+		replacement: &str,
+		dry_run: bool,
+	) -> Result<Vec<TextMatch>, ContentServiceError> {
+		let find_replace =
+			FindReplace::new(pattern, replacement).map_err(ContentServiceError::InvalidFindReplace)?;
+
+		let can_write = self
+			.access_service
+			.can_permission(owner_id, "content_blocks:write:all")
+			.await
+			.map_err(ContentServiceError::AccessControl)?
+			|| self
+				.access_service
+				.can_permission(owner_id, "content_blocks:write:own")
+				.await
+				.map_err(ContentServiceError::AccessControl)?;
+
+		if !can_write {
+			return Err(ContentServiceError::EditDenied);
+		}
+
+		let mut blocks = self.lock();
+		let mut owned: Vec<_> = blocks
+			.values_mut()
+			.filter(|block| block.is_owned_by(owner_id))
+			.collect();
+
+		owned.sort_by_key(|block| *block.nutty_id().uuid());
+
+		let mut matches = vec![];
+
+		for block in owned {
+			if let Some((content, block_matches)) = find_replace.apply(block) {
+				matches.extend(block_matches);
+
+				if !dry_run {
+					block.content = content;
+				}
+			}
+		}
+
+		Ok(matches)
+	}
+
+	/// Revisions aren't recorded, so none are found.
+	async fn diff_content_block(
+		&self,
+		block_id: &DissociatedNuttyId,
+		from: Option<&DissociatedNuttyId>,
+		to: Option<&DissociatedNuttyId>,
+	) -> Result<ContentDiff, ContentServiceError> {
+		if !self.lock().contains_key(&block_id.nid()) {
+			return Err(ContentServiceError::ContentBlockNotFound);
+		}
+
+		match from.or(to) {
+			Some(_) => Err(ContentServiceError::RevisionNotFound),
+			None => Err(ContentServiceError::MissingRevision),
+		}
+	}
+
+	/// Revisions aren't recorded, so none can be published.
+	async fn publish_revision(
+		&self,
+		_navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		_revision_id: Option<&DissociatedNuttyId>,
+	) -> Result<NuttyId, ContentServiceError> {
+		match self.lock().contains_key(&block_id.nid()) {
+			true => Err(ContentServiceError::RevisionNotFound),
+			false => Err(ContentServiceError::ContentBlockNotFound),
+		}
+	}
+
+	/// Revisions aren't recorded, so none are published.
+	async fn get_published_revision(
+		&self,
+		_block_id: &DissociatedNuttyId,
+	) -> Result<Option<BlockRevision>, ContentServiceError> {
+		Ok(None)
+	}
+
+	/// Revisions aren't recorded, so none are published.
+	async fn list_published_contents(
+		&self,
+		_navigator_id: &NuttyId,
+		_ids: &[NuttyId],
+	) -> Result<HashMap<NuttyId, BlockContent>, ContentServiceError> {
+		Ok(HashMap::new())
+	}
+
+	async fn set_content_block_slug(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		slug: &Slug,
+	) -> Result<NuttyId, ContentServiceError> {
+		let block_id = *self
+			.lock()
+			.get(&block_id.nid())
+			.ok_or(ContentServiceError::ContentBlockNotFound)?
+			.nutty_id();
+
+		let mut slugs = self.slugs.lock().expect("Fake slugs poisoned");
+
+		if let Some(claimed) = slugs.get(slug)
+			&& (claimed.navigator_id != *navigator_id
+				|| (claimed.is_current && claimed.block_id != block_id))
+		{
+			return Err(ContentServiceError::SlugTaken);
+		}
+
+		for claimed in slugs.values_mut() {
+			if claimed.block_id == block_id {
+				claimed.is_current = false;
+			}
+		}
+
+		slugs.insert(
+			slug.clone(),
+			FakeSlug {
+				block_id,
+				navigator_id: *navigator_id,
+				is_current: true,
+			},
+		);
+
+		Ok(block_id)
+	}
+
+	async fn resolve_permalink(
+		&self,
+		path: &str,
+	) -> Result<Option<SlugTarget>, ContentServiceError> {
+		if let Ok(block_id) = DissociatedNuttyId::new(path) {
+			return Ok(self.lock().get(&block_id.nid()).map(|block| SlugTarget {
+				block_id: *block.nutty_id(),
+				moved_to: None,
+			}));
+		}
+
+		let Ok(slug) = Slug::parse(path) else {
+			return Ok(None);
+		};
+
+		let slugs = self.slugs.lock().expect("Fake slugs poisoned");
+
+		let Some(claimed) = slugs.get(&slug) else {
+			return Ok(None);
+		};
+
+		let moved_to = slugs
+			.iter()
+			.find(|(_, other)| other.block_id == claimed.block_id && other.is_current)
+			.map(|(current, _)| current.clone())
+			.filter(|_| !claimed.is_current);
+
+		Ok(Some(SlugTarget {
+			block_id: claimed.block_id,
+			moved_to,
+		}))
+	}
+
+	async fn list_owned_blocks_by_root(
+		&self,
+		owner_id: &NuttyId,
+	) -> Result<Vec<RootBlockGroup>, ContentServiceError> {
+		Ok(RootBlockGroup::group_owned(self.lock().values(), owner_id))
+	}
+
+	async fn list_block_checksums(
+		&self,
+		root_id: &DissociatedNuttyId,
+	) -> Result<Vec<BlockChecksum>, ContentServiceError> {
+		let blocks = self.lock();
+
+		let Some(root) = blocks.get(&root_id.nid()) else {
+			return Ok(vec![]);
+		};
+
+		let mut subtree = vec![root];
+		let mut i = 0;
+
+		while let Some(block) = subtree.get(i).copied() {
+			subtree.extend(
+				blocks
+					.values()
+					.filter(|b| b.parent_id == Some(*block.nutty_id())),
+			);
+
+			i += 1;
+		}
+
+		subtree.sort_by_key(|block| *block.nutty_id().uuid());
+
+		subtree
+			.into_iter()
+			.map(|block| {
+				Ok(BlockChecksum {
+					block_id: *block.nutty_id(),
+					content_hash: block
+						.content_hash()
+						.map_err(|error| ContentServiceError::ListBlockChecksums(error.into()))?,
+					updated_at: *block.updated_at(),
+				})
+			})
+			.collect()
+	}
+
+	async fn get_outline(
+		&self,
+		navigator_id: &NuttyId,
+		root_id: &DissociatedNuttyId,
+		after_id: Option<&DissociatedNuttyId>,
+		limit: i64,
+	) -> Result<Vec<OutlineEntry>, ContentServiceError> {
+		let (root, blocks) = {
+			let blocks = self.lock();
+
+			let Some(root) = blocks.get(&root_id.nid()).cloned() else {
+				return Ok(vec![]);
+			};
+
+			(root, blocks.values().cloned().collect::<Vec<_>>())
+		};
+
+		let after =
+			after_id.and_then(|after_id| blocks.iter().find(|b| b.nutty_id().nid() == after_id.nid()));
+
+		let children_of = |parent: &ContentBlock| {
+			let mut children: Vec<&ContentBlock> = blocks
+				.iter()
+				.filter(|block| block.parent_id == Some(*parent.nutty_id()))
+				.collect();
+
+			children.sort_by(|a, b| a.f_index.as_str().cmp(b.f_index.as_str()));
+			children
+		};
+
+		// Walk depth first, leaving out blocks that opt out of inheriting
+		// access, with everything under them, unless they can be read.
+		let mut outline = vec![];
+		let mut stack = vec![(&root, 0)];
+
+		while let Some((block, depth)) = stack.pop() {
+			if depth > 0
+				&& !block.inherit_access
+				&& !self
+					.check_access(navigator_id, &block.nutty_id().dissociate(), "read")
+					.await?
+			{
+				continue;
+			}
+
+			let children = children_of(block);
+
+			outline.push(OutlineEntry {
+				block_id: *block.nutty_id(),
+				parent_id: block.parent_id,
+				title: OutlineEntry::title_of(&block.content),
+				depth,
+				child_count: children.len() as i64,
+				inherit_access: block.inherit_access,
+			});
+
+			let shown: Vec<_> = children
+				.into_iter()
+				.filter(|child| {
+					depth > 0
+						|| after.is_none_or(|after| child.f_index.as_str() > after.f_index.as_str())
+				})
+				.take(limit as usize)
+				.map(|child| (child, depth + 1))
+				.collect();
+
+			stack.extend(shown.into_iter().rev());
+		}
+
+		Ok(outline)
+	}
+
+	async fn list_changes(
+		&self,
+		navigator_id: &NuttyId,
+		since: Option<SyncCursor>,
+	) -> Result<SyncChanges, ContentServiceError> {
+		// Deletions aren't remembered here, so only saved blocks are pulled.
+		let cursor = |block: &ContentBlock| SyncCursor {
+			changed_at: *block.updated_at().inner(),
+			id: *block.nutty_id().uuid(),
+		};
+
+		let mut blocks: Vec<_> = self
+			.lock()
+			.values()
+			.filter(|block| block.owner_id() == Some(navigator_id))
+			.filter(|block| since.is_none_or(|since| cursor(block) > since))
+			.cloned()
+			.collect();
+
+		blocks.sort_by_key(|block| cursor(block));
+
+		let has_more = blocks.len() > MAX_SYNC_CHANGES as usize;
+		blocks.truncate(MAX_SYNC_CHANGES as usize);
+
+		Ok(SyncChanges {
+			cursor: blocks.last().map(cursor).or(since).unwrap_or_default(),
+			blocks,
+			links: vec![],
+			tombstones: vec![],
+			has_more,
+		})
+	}
+
+	async fn get_site_settings(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Option<SiteSettings>, ContentServiceError> {
+		let settings = self
+			.site_settings
+			.lock()
+			.expect("Fake site settings poisoned");
+		Ok(settings.get(navigator_id).cloned())
+	}
+
+	async fn save_site_settings(
+		&self,
+		navigator_id: &NuttyId,
+		settings: SiteSettings,
+	) -> Result<SiteSettings, ContentServiceError> {
+		settings
+			.validate()
+			.map_err(ContentServiceError::InvalidSiteSettings)?;
+
+		self
+			.site_settings
+			.lock()
+			.expect("Fake site settings poisoned")
+			.insert(*navigator_id, settings.clone());
+
+		Ok(settings)
+	}
+
+	async fn delete_site_settings(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<bool, ContentServiceError> {
+		let mut settings = self
+			.site_settings
+			.lock()
+			.expect("Fake site settings poisoned");
+		Ok(settings.remove(navigator_id).is_some())
+	}
+
+	async fn get_raw_content(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Value, ContentServiceError> {
+		self.require_raw_content_permission(navigator_id).await?;
+
+		let blocks = self.lock();
+		let block = blocks
+			.get(&block_id.nid())
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		serde_json::to_value(&block.content).map_err(ContentServiceError::InvalidRawContent)
+	}
+
+	/// Revisions aren't kept, so none is recorded.
+	async fn save_raw_content(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		content: Value,
+	) -> Result<Value, ContentServiceError> {
+		self.require_raw_content_permission(navigator_id).await?;
+
+		let content: BlockContent =
+			serde_json::from_value(content).map_err(ContentServiceError::InvalidRawContent)?;
+
+		BlockKindRegistry::default()
+			.validate(&content)
+			.map_err(|error| match error {
+				BlockKindError::InvalidQuery(error) => ContentServiceError::ParseBlockQuery(error),
+				error => ContentServiceError::InvalidContent(error),
+			})?;
+
+		let mut blocks = self.lock();
+		let block = blocks
+			.get_mut(&block_id.nid())
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		block.content = content;
+		serde_json::to_value(&block.content).map_err(ContentServiceError::InvalidRawContent)
+	}
+
+	/// Stats are derived from the blocks on demand, so they never drift.
+	async fn check_block_stats(&self) -> Result<BlockStatsCheck, ContentServiceError> {
+		Ok(BlockStatsCheck {
+			checked_count: self.lock().len() as i64,
+			repaired: vec![],
+		})
+	}
+
+	async fn diff_content_blocks(
+		&self,
+		from_id: &DissociatedNuttyId,
+		to_id: &DissociatedNuttyId,
+	) -> Result<ContentDiff, ContentServiceError> {
+		let blocks = self.lock();
+
+		let content = |id: &DissociatedNuttyId| {
+			blocks
+				.get(&id.nid())
+				.map(|block| &block.content)
+				.ok_or(ContentServiceError::ContentBlockNotFound)
+		};
+
+		Ok(ContentDiff::between(content(from_id)?, content(to_id)?))
+	}
+
+	/// Revisions aren't recorded, so only concatenation is supported. Tags
+	/// in every other block are retargeted, and children are appended in no
+	/// particular order.
+	async fn merge_blocks(
+		&self,
+		source_id: &DissociatedNuttyId,
+		target_id: &DissociatedNuttyId,
+		strategy: &MergeStrategy,
+	) -> Result<BlockMerge, ContentServiceError> {
+		let mut blocks = self.lock();
+
+		let source = blocks
+			.get(&source_id.nid())
+			.cloned()
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		let target = blocks
+			.get(&target_id.nid())
+			.cloned()
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		// Walk up from the target, looking for the source.
+		let mut ancestor_id = Some(*target.nutty_id());
+
+		while let Some(id) = ancestor_id {
+			if id == *source.nutty_id() {
+				return Err(ContentServiceError::InvalidMerge(BlockMergeError::IntoSelf));
+			}
+
+			ancestor_id = blocks.get(&id.nid()).and_then(|block| block.parent_id);
+		}
+
+		let content = match strategy {
+			MergeStrategy::Concatenate => block_merge::concatenate(&source.content, &target.content),
+			MergeStrategy::ThreeWay { .. } => return Err(ContentServiceError::RevisionNotFound),
+		};
+
+		blocks.remove(&source_id.nid());
+
+		blocks
+			.get_mut(&target_id.nid())
+			.expect("The target was found above")
+			.content = content;
+
+		let mut moved_ids = vec![];
+		let mut relinked_ids = vec![];
+		let mut f_index = FractionalIndex::start();
+
+		for block in blocks.values_mut() {
+			if block.parent_id == Some(*source.nutty_id()) {
+				f_index = FractionalIndex::between(&f_index, &FractionalIndex::end())
+					.map_err(ContentServiceError::OrderContentBlock)?;
+
+				block.parent_id = Some(*target.nutty_id());
+				block.f_index = f_index.clone();
+				moved_ids.push(*block.nutty_id());
+			}
+
+			if let Some(content) = block.content.retarget_references(source_id, target_id) {
+				block.content = content;
+				relinked_ids.push(*block.nutty_id());
+			}
+		}
+
+		Ok(BlockMerge {
+			block: blocks[&target_id.nid()].clone(),
+			source_id: *source.nutty_id(),
+			relinked_ids,
+			moved_ids,
+		})
+	}
+
+	/// Revisions aren't recorded, and the overflow is always the last child.
+	async fn convert_block(
+		&self,
+		_navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		target: ConvertibleKind,
+	) -> Result<BlockConversion, ContentServiceError> {
+		let mut blocks = self.lock();
+
+		let block = blocks
+			.get_mut(&block_id.nid())
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		let converted = block_conversion::convert(&block.content, target)
+			.map_err(ContentServiceError::InvalidConversion)?;
+
+		block.content = converted.content;
+		let block = block.clone();
+
+		let overflow = converted.overflow.map(|content| {
+			let last_f_index = blocks
+				.values()
+				.filter(|b| b.parent_id == Some(*block.nutty_id()))
+				.map(|b| b.f_index.clone())
+				.max_by(|a, b| a.as_str().cmp(b.as_str()))
+				.unwrap_or_else(FractionalIndex::start);
+
+			let f_index = FractionalIndex::between(&last_f_index, &FractionalIndex::end())
+				.expect("Indices before the end leave room");
+
+			let child = ContentBlock::now(Some(*block.nutty_id()), f_index, content);
+			blocks.insert(child.nutty_id().nid(), child.clone());
+			child
+		});
+
+		Ok(BlockConversion { block, overflow })
+	}
+
+	/// Revisions aren't recorded.
+	async fn split_paragraph(
+		&self,
+		_navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		offset: usize,
+	) -> Result<BlockSplit, ContentServiceError> {
+		let mut blocks = self.lock();
+
+		let mut block = blocks
+			.get(&block_id.nid())
+			.cloned()
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		let BlockContent::Paragraph { markdown } = &block.content else {
+			return Err(ContentServiceError::NotAParagraph);
+		};
+
+		let (head, tail) = block_split::split_markdown(markdown, offset)
+			.map_err(ContentServiceError::InvalidSplit)?;
+
+		let next_f_index = Self::next_sibling(&blocks, &block)
+			.map(|next| next.f_index.clone())
+			.unwrap_or_else(FractionalIndex::end);
+
+		let f_index = FractionalIndex::between(&block.f_index, &next_f_index)
+			.map_err(ContentServiceError::OrderContentBlock)?;
+
+		block.content = BlockContent::Paragraph { markdown: head };
+		blocks.insert(block_id.nid(), block.clone());
+
+		let new_block = ContentBlock::now(
+			block.parent_id,
+			f_index,
+			BlockContent::Paragraph { markdown: tail },
+		);
+
+		blocks.insert(new_block.nutty_id().nid(), new_block.clone());
+
+		Ok(BlockSplit { block, new_block })
+	}
+
+	/// Revisions aren't recorded, and write access to the next paragraph
+	/// isn't checked.
+	async fn merge_with_next(
+		&self,
+		_navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<BlockMerge, ContentServiceError> {
+		let mut blocks = self.lock();
+
+		let block = blocks
+			.get(&block_id.nid())
+			.cloned()
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		let next = Self::next_sibling(&blocks, &block)
+			.cloned()
+			.ok_or(ContentServiceError::NoNextSibling)?;
+
+		let (
+			BlockContent::Paragraph { markdown },
+			BlockContent::Paragraph {
+				markdown: next_markdown,
+			},
+		) = (&block.content, &next.content)
+		else {
+			return Err(ContentServiceError::NotAParagraph);
+		};
+
+		let content = BlockContent::Paragraph {
+			markdown: format!("{markdown}{next_markdown}"),
+		};
+
+		blocks.remove(&next.nutty_id().nid());
+
+		blocks
+			.get_mut(&block_id.nid())
+			.expect("The block was found above")
+			.content = content;
+
+		let next_id = next.nutty_id().dissociate();
+		let mut moved_ids = vec![];
+		let mut relinked_ids = vec![];
+
+		for other in blocks.values_mut() {
+			if other.parent_id == Some(*next.nutty_id()) {
+				other.parent_id = Some(*block.nutty_id());
+				moved_ids.push(*other.nutty_id());
+			}
+
+			if let Some(content) = other.content.retarget_references(&next_id, block_id) {
+				other.content = content;
+				relinked_ids.push(*other.nutty_id());
+			}
+		}
+
+		Ok(BlockMerge {
+			block: blocks[&block_id.nid()].clone(),
+			source_id: *next.nutty_id(),
+			relinked_ids,
+			moved_ids,
+		})
+	}
+
+	async fn reorder_children(
+		&self,
+		parent_id: &DissociatedNuttyId,
+		child_ids: &[DissociatedNuttyId],
+	) -> Result<Vec<ContentBlock>, ContentServiceError> {
+		let mut blocks = self.lock();
+
+		let parent = blocks
+			.get(&parent_id.nid())
+			.cloned()
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		let mut given_nids: Vec<_> = child_ids.iter().map(|id| id.nid()).collect();
+
+		let mut current_nids: Vec<_> = blocks
+			.values()
+			.filter(|b| b.parent_id == Some(*parent.nutty_id()))
+			.map(|b| b.nutty_id().nid())
+			.collect();
+
+		given_nids.sort();
+		current_nids.sort();
+
+		if given_nids != current_nids {
+			return Err(ContentServiceError::ChildrenMismatch);
+		}
+
+		let f_indices = FractionalIndex::spread(child_ids.len());
+
+		Ok(child_ids
+			.iter()
+			.zip(f_indices)
+			.map(|(child_id, f_index)| {
+				let child = blocks
+					.get_mut(&child_id.nid())
+					.expect("The children were matched above");
+
+				child.f_index = f_index;
+				child.clone()
+			})
+			.collect())
+	}
+
+	/// Matches by case-insensitive substring in every language, newest first.
+	async fn search_content_blocks(
+		&self,
+		navigator_id: &NuttyId,
+		query: &str,
+		language: Option<SearchLanguage>,
+		include_archived: bool,
+	) -> Result<Vec<ContentBlock>, ContentServiceError> {
+		let query = query.trim().to_lowercase();
+
+		if query.is_empty() {
+			return Err(ContentServiceError::EmptySearchQuery);
+		}
+
+		let mut matches: Vec<ContentBlock> = {
+			let languages = self.languages.lock().expect("Fake languages poisoned");
+			let blocks = self.lock();
+			let archived = self.archived();
+
+			blocks
+				.values()
+				.filter(|block| include_archived || !archived.contains(block.nutty_id()))
+				.filter(|block| {
+					let block_language = languages
+						.get(&block.nutty_id().nid())
+						.copied()
+						.unwrap_or_default();
+
+					language.is_none_or(|language| language == block_language)
+				})
+				.filter(|block| match &block.content {
+					BlockContent::Heading { markdown }
+					| BlockContent::Paragraph { markdown }
+					| BlockContent::Todo { markdown, .. } => markdown.to_lowercase().contains(&query),
+					BlockContent::Page { title } => title.to_lowercase().contains(&query),
+					BlockContent::Query { .. } | BlockContent::Custom { .. } => false,
+				})
+				.cloned()
+				.collect()
+		};
+
+		matches.sort_by(|a, b| b.created_at().inner().cmp(a.created_at().inner()));
+
+		let mut results = vec![];
+
+		for block in matches {
+			if self
+				.check_access(navigator_id, &block.nutty_id().dissociate(), "read")
+				.await?
+			{
+				results.push(block);
+			}
+		}
+
+		Ok(results)
+	}
+
+	async fn list_content_blocks(
+		&self,
+		navigator_id: &NuttyId,
+		filter: &ContentFilter,
+	) -> Result<Vec<ContentBlock>, ContentServiceError> {
+		let mut matches: Vec<ContentBlock> = {
+			let blocks = self.lock();
+			let archived = self.archived();
+
+			blocks
+				.values()
+				.filter(|block| filter.include_archived || !archived.contains(block.nutty_id()))
+				.filter(|block| {
+					filter.kinds.is_empty()
+						|| filter.kinds.iter().any(|kind| kind == block.content.kind())
+				})
+				.filter(|block| {
+					filter
+						.owner_id
+						.is_none_or(|owner_id| block.owner_id == Some(owner_id))
+				})
+				.cloned()
+				.collect()
+		};
+
+		matches.sort_by(|a, b| b.created_at().inner().cmp(a.created_at().inner()));
+
+		let mut results = vec![];
+
+		for block in matches {
+			if self
+				.check_access(navigator_id, &block.nutty_id().dissociate(), "read")
+				.await?
+			{
+				results.push(block);
+			}
+		}
+
+		Ok(results)
+	}
+
+	async fn set_content_block_language(
+		&self,
+		block_id: &DissociatedNuttyId,
+		language: Option<SearchLanguage>,
+	) -> Result<u64, ContentServiceError> {
+		let blocks = self.lock();
+
+		let block = blocks
+			.get(&block_id.nid())
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		let mut subtree = vec![*block.nutty_id()];
+		let mut i = 0;
+
+		while let Some(id) = subtree.get(i).copied() {
+			subtree.extend(
+				blocks
+					.values()
+					.filter(|b| b.parent_id == Some(id))
+					.map(|b| *b.nutty_id()),
+			);
+
+			i += 1;
+		}
+
+		let mut languages = self.languages.lock().expect("Fake languages poisoned");
+
+		for id in &subtree {
+			match language {
+				Some(language) => languages.insert(id.nid(), language),
+				None => languages.remove(&id.nid()),
+			};
+		}
+
+		Ok(subtree.len() as u64)
+	}
+
+	async fn set_content_block_archived(
+		&self,
+		block_id: &DissociatedNuttyId,
+		archived: bool,
+		subtree: bool,
+	) -> Result<BlockArchival, ContentServiceError> {
+		let blocks = self.lock();
+
+		let block = blocks
+			.get(&block_id.nid())
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		let mut updated_ids = vec![*block.nutty_id()];
+		let mut i = 0;
+
+		while subtree && let Some(id) = updated_ids.get(i).copied() {
+			updated_ids.extend(
+				blocks
+					.values()
+					.filter(|b| b.parent_id == Some(id))
+					.map(|b| *b.nutty_id()),
+			);
+
+			i += 1;
+		}
+
+		let mut archived_ids = self.archived();
+
+		for id in &updated_ids {
+			match archived {
+				true => archived_ids.insert(*id),
+				false => archived_ids.remove(id),
+			};
+		}
+
+		Ok(BlockArchival {
+			block_id: *block.nutty_id(),
+			archived,
+			updated_ids,
+		})
+	}
+
+	async fn set_inbox(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<ContentBlock, ContentServiceError> {
+		let inbox = self
+			.lock()
+			.get(&block_id.nid())
+			.cloned()
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		self
+			.inboxes
+			.lock()
+			.expect("Fake inboxes poisoned")
+			.insert(*navigator_id, *inbox.nutty_id());
+
+		Ok(inbox)
+	}
+
+	/// Files the email without cleaning its markdown.
+	async fn ingest_email(
+		&self,
+		navigator_id: &NuttyId,
+		email: &IncomingEmail,
+	) -> Result<ContentBlock, ContentServiceError> {
+		let parent_id = self
+			.inboxes
+			.lock()
+			.expect("Fake inboxes poisoned")
+			.get(navigator_id)
+			.copied()
+			.ok_or(ContentServiceError::InboxNotConfigured)?;
+
+		let blocks = email
+			.to_blocks(parent_id, *navigator_id)
+			.map_err(ContentServiceError::InvalidEmail)?;
+
+		let mut stored = self.lock();
+
+		for block in &blocks {
+			stored.insert(block.nutty_id().nid(), block.clone());
+		}
+
+		Ok(blocks[0].clone())
+	}
+
+	/// Files the capture without cleaning its markdown.
+	async fn capture_page(
+		&self,
+		navigator_id: &NuttyId,
+		capture: &Capture,
+		snapshot: Option<&PageSnapshot>,
+	) -> Result<CapturedPage, ContentServiceError> {
+		let parent_id = self
+			.inboxes
+			.lock()
+			.expect("Fake inboxes poisoned")
+			.get(navigator_id)
+			.copied()
+			.ok_or(ContentServiceError::InboxNotConfigured)?;
+
+		let mut blocks = capture
+			.to_blocks(parent_id, *navigator_id, snapshot)
+			.map_err(ContentServiceError::InvalidCapture)?;
+
+		let mut stored = self.lock();
+
+		for block in &blocks {
+			stored.insert(block.nutty_id().nid(), block.clone());
+		}
+
+		Ok(CapturedPage {
+			block: blocks.remove(0),
+			unfurl: snapshot.map(|snapshot| snapshot.unfurl.clone()),
+			archive: blocks.pop(),
+		})
+	}
+
+	async fn paste_html(
+		&self,
+		navigator_id: &NuttyId,
+		parent_id: &DissociatedNuttyId,
+		html: &str,
+	) -> Result<Vec<ContentBlock>, ContentServiceError> {
+		let pasted = html_import::html_to_blocks(html).map_err(ContentServiceError::InvalidPaste)?;
+		let mut stored = self.lock();
+
+		let parent = stored
+			.get(&parent_id.nid())
+			.cloned()
+			.ok_or(ContentServiceError::ContentBlockNotFound)?;
+
+		let last_f_index = stored
+			.values()
+			.filter(|block| block.parent_id == Some(*parent.nutty_id()))
+			.map(|block| block.f_index.clone())
+			.max();
+
+		let blocks = html_import::instantiate(
+			&pasted,
+			*parent.nutty_id(),
+			*navigator_id,
+			last_f_index.as_ref(),
+		)
+		.map_err(ContentServiceError::OrderContentBlock)?;
+
+		for block in &blocks {
+			stored.insert(block.nutty_id().nid(), block.clone());
+		}
+
+		Ok(blocks)
+	}
+
+	async fn list_property_definitions(
+		&self,
+	) -> Result<Vec<PropertyDefinition>, ContentServiceError> {
+		let mut definitions = self.definitions().clone();
+		definitions.sort_by(|a, b| a.name().cmp(b.name()));
+		Ok(definitions)
+	}
+
+	async fn create_property_definition(
+		&self,
+		definition: PropertyDefinition,
+	) -> Result<PropertyDefinition, ContentServiceError> {
+		let mut definitions = self.definitions();
+
+		if definitions
+			.iter()
+			.any(|existing| existing.name() == definition.name())
+		{
+			return Err(ContentServiceError::SavePropertyDefinition(
+				ContentRepositoryError::PropertyNameTaken,
+			));
+		}
+
+		definitions.push(definition.clone());
+		Ok(definition)
+	}
+
+	async fn annotate_content_block(
+		&self,
+		block: &ContentBlock,
+		annotations: Vec<BlockAnnotation>,
+	) -> Result<bool, ContentServiceError> {
+		let current = self.lock().get(&block.nutty_id().nid()).cloned();
+
+		if current.is_none_or(|current| current.content != block.content) {
+			return Ok(false);
+		}
+
+		self.annotations().insert(*block.nutty_id(), annotations);
+		Ok(true)
+	}
+
+	async fn list_translations(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Vec<Translation>, ContentServiceError> {
+		let block = self.existing_block(block_id)?;
+		self
+			.readable_translations(navigator_id, self.translations_of(block.nutty_id()))
+			.await
+	}
+
+	async fn set_translation(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		lang: &Lang,
+		translation_of: Option<&DissociatedNuttyId>,
+	) -> Result<Vec<Translation>, ContentServiceError> {
+		let block_id = *self.existing_block(block_id)?.nutty_id();
+
+		let original_id = match translation_of {
+			Some(original_id) => Some(*self.existing_block(original_id)?.nutty_id()),
+			None => None,
+		};
+
+		{
+			let mut translations = self.translations();
+			let group_of = |id: &NuttyId| translations.get(id).map(|(group_id, _)| *group_id);
+
+			let group_id = match original_id {
+				Some(original_id) => {
+					group_of(&original_id).ok_or(ContentServiceError::UntranslatedBlock)?
+				}
+				None => group_of(&block_id).unwrap_or_else(Uuid::now_v7),
+			};
+
+			let is_taken = translations
+				.iter()
+				.any(|(id, (group, taken))| *id != block_id && *group == group_id && taken == lang);
+
+			if is_taken {
+				return Err(ContentServiceError::TranslationTaken);
+			}
+
+			translations.insert(block_id, (group_id, lang.clone()));
+		}
+
+		self
+			.readable_translations(navigator_id, self.translations_of(&block_id))
+			.await
+	}
+
+	async fn remove_translation(
+		&self,
+		block_id: &DissociatedNuttyId,
+	) -> Result<(), ContentServiceError> {
+		let block = self.existing_block(block_id)?;
+
+		match self.translations().remove(block.nutty_id()) {
+			Some(_) => Ok(()),
+			None => Err(ContentServiceError::ContentBlockNotFound),
+		}
+	}
+
+	async fn localize_content_block(
+		&self,
+		navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		preferred: &[Lang],
+	) -> Result<LocalizedBlock, ContentServiceError> {
+		let mut block = self.existing_block(block_id)?;
+
+		let translations = self
+			.readable_translations(navigator_id, self.translations_of(block.nutty_id()))
+			.await?;
+
+		if let Some(variant) = Translation::resolve(&translations, preferred)
+			&& variant.block_id != *block.nutty_id()
+		{
+			block = self.existing_block(&variant.block_id.dissociate())?;
+		}
+
+		let lang = translations
+			.iter()
+			.find(|translation| translation.block_id == *block.nutty_id())
+			.map(|translation| translation.lang.clone());
+
+		Ok(LocalizedBlock {
+			block,
+			lang,
+			translations,
+		})
+	}
+
+	/// Links aren't kept, so nothing links to any block.
+	async fn list_linked_references(
+		&self,
+		_navigator_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+	) -> Result<Vec<LinkedReference>, ContentServiceError> {
+		self.existing_block(block_id)?;
+		Ok(vec![])
+	}
+}
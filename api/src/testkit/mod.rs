@@ -0,0 +1,68 @@
+//! In-memory fakes of the services, for unit testing handlers without Postgres.
+//! Available to downstream crates with the `testkit` feature.
+
+mod access;
+mod chatbot;
+mod content;
+mod moderation;
+mod navigator;
+mod system;
+mod webhooks;
+
+use std::sync::Arc;
+
+pub use access::FakeAccessService;
+pub use chatbot::FakeChatbotService;
+pub use content::FakeContentService;
+pub use moderation::FakeModerationService;
+pub use navigator::FakeNavigatorService;
+pub use system::FakeSystemService;
+pub use webhooks::FakeWebhookService;
+
+use crate::content::events::EventStreams;
+use crate::content::og_image::OgImages;
+use crate::content::sanitizer::Sanitizer;
+use crate::integrations::chatbot::telegram::Telegram;
+use crate::integrations::webhooks::delivery::WebhookSender;
+use crate::utilities::api::export_link::ExportLinks;
+use crate::utilities::api::feed_token::FeedTokens;
+use crate::utilities::api::geo_ip::GeoIp;
+use crate::utilities::api::link_checker::LinkChecker;
+use crate::utilities::api::og_image_link::OgImageLinks;
+use crate::utilities::api::page_fetcher::PageFetcher;
+use crate::utilities::api::read_only::ReadOnlyMode;
+use crate::utilities::api::state::AppState;
+use crate::utilities::api::webhook::WebhookSecret;
+
+/// Build an [AppState] backed by the given fakes.
+pub fn app_state(
+	access_service: Arc<FakeAccessService>,
+	content_service: Arc<FakeContentService>,
+	navigator_service: Arc<FakeNavigatorService>,
+) -> Arc<AppState> {
+	let moderation_service = Arc::new(FakeModerationService::new(content_service.clone()));
+
+	Arc::new(AppState {
+		access_service,
+		content_service,
+		navigator_service,
+		moderation_service,
+		chatbot_service: Arc::new(FakeChatbotService::new()),
+		system_service: Arc::new(FakeSystemService::new()),
+		webhook_service: Arc::new(FakeWebhookService::new()),
+		read_only: ReadOnlyMode::new(false, 0),
+		geo_ip: GeoIp::default(),
+		sanitizer: Sanitizer::default(),
+		block_kinds: Arc::default(),
+		email_ingest_secret: WebhookSecret::default(),
+		page_fetcher: PageFetcher::default(),
+		link_checker: LinkChecker::default(),
+		telegram: Telegram::default(),
+		webhook_sender: WebhookSender::default(),
+		feed_tokens: FeedTokens::default(),
+		export_links: ExportLinks::default(),
+		og_images: OgImages::default(),
+		og_image_links: OgImageLinks::default(),
+		event_streams: EventStreams::default(),
+	})
+}
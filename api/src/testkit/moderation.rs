@@ -0,0 +1,116 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::content::service::ContentServiceApi;
+use crate::models::DissociatedNuttyId;
+use crate::models::NuttyId;
+use crate::models::children_view::ChildrenView;
+use crate::moderation::models::Report;
+use crate::moderation::models::ReportAction;
+use crate::moderation::models::ReportStatus;
+use crate::moderation::repository::ModerationRepositoryError;
+use crate::moderation::service::ModerationServiceApi;
+use crate::moderation::service::ModerationServiceError;
+
+/// An in-memory [ModerationServiceApi].
+/// Hiding a block is recorded on the report only; the content service is
+/// used to check that reported blocks exist.
+pub struct FakeModerationService {
+	reports: Mutex<Vec<Report>>,
+
+	/// The content service to look up reported blocks in.
+	content_service: Arc<dyn ContentServiceApi>,
+}
+
+impl FakeModerationService {
+	/// Create a moderation service with no reports.
+	pub fn new(content_service: Arc<dyn ContentServiceApi>) -> Self {
+		Self {
+			reports: Mutex::new(Vec::new()),
+			content_service,
+		}
+	}
+
+	fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Report>> {
+		self.reports.lock().expect("Fake moderation state poisoned")
+	}
+}
+
+#[async_trait]
+impl ModerationServiceApi for FakeModerationService {
+	async fn report(
+		&self,
+		reporter_id: &NuttyId,
+		block_id: &DissociatedNuttyId,
+		reason: String,
+	) -> Result<Report, ModerationServiceError> {
+		let context = self
+			.content_service
+			.get_content_block_context(reporter_id, block_id, &ChildrenView::default())
+			.await
+			.map_err(|_| ModerationServiceError::ContentBlockNotFound)?;
+
+		let report = Report::new(*context.block_id(), *reporter_id, &reason)
+			.map_err(ModerationServiceError::InvalidReport)?;
+
+		let mut reports = self.lock();
+
+		if reports.iter().any(|r| {
+			r.block_id() == report.block_id()
+				&& r.reporter_id() == reporter_id
+				&& r.status() == ReportStatus::Open
+		}) {
+			return Err(ModerationServiceError::SaveReport(
+				ModerationRepositoryError::AlreadyReported,
+			));
+		}
+
+		reports.push(report.clone());
+		Ok(report)
+	}
+
+	async fn list_reports(
+		&self,
+		status: ReportStatus,
+	) -> Result<Vec<Report>, ModerationServiceError> {
+		Ok(self
+			.lock()
+			.iter()
+			.filter(|report| report.status() == status)
+			.cloned()
+			.collect())
+	}
+
+	async fn list_own_reports(
+		&self,
+		reporter_id: &NuttyId,
+	) -> Result<Vec<Report>, ModerationServiceError> {
+		Ok(self
+			.lock()
+			.iter()
+			.rev()
+			.filter(|report| report.reporter_id() == reporter_id)
+			.cloned()
+			.collect())
+	}
+
+	async fn resolve_report(
+		&self,
+		report_id: &NuttyId,
+		moderator_id: &NuttyId,
+		action: ReportAction,
+		resolution: Option<String>,
+	) -> Result<Report, ModerationServiceError> {
+		let mut reports = self.lock();
+
+		let report = reports
+			.iter_mut()
+			.find(|report| report.nutty_id() == report_id && report.status() == ReportStatus::Open)
+			.ok_or(ModerationServiceError::ReportNotFound)?;
+
+		report.resolve(*moderator_id, action, resolution);
+		Ok(report.clone())
+	}
+}
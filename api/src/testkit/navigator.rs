@@ -0,0 +1,515 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use chrono::DateTime;
+use chrono::Utc;
+
+use crate::models::ContentBlock;
+use crate::models::Navigator;
+use crate::models::NuttyId;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+use crate::models::navigator_block::BlockedNavigator;
+use crate::models::navigator_export::ExportArchive;
+use crate::models::navigator_export::NavigatorExport;
+use crate::models::onboarding::OnboardingProgress;
+use crate::models::onboarding::OnboardingStep;
+use crate::models::request_usage::NavigatorUsage;
+use crate::models::request_usage::RequestUsage;
+use crate::models::request_usage::UsageSort;
+use crate::models::session::Session;
+use crate::models::session::SessionToken;
+use crate::navigator::repository::NavigatorRepositoryError;
+use crate::navigator::service::NavigatorServiceApi;
+use crate::navigator::service::NavigatorServiceError;
+use crate::navigator::service::PasswordHashMetrics;
+use crate::navigator::service::normalize_session_label;
+use crate::navigator::session_limit::SessionLimit;
+
+/// An in-memory [NavigatorServiceApi].
+#[derive(Default)]
+pub struct FakeNavigatorService {
+	state: Mutex<FakeNavigatorState>,
+}
+
+#[derive(Default)]
+struct FakeNavigatorState {
+	navigators: HashMap<NuttyId, Navigator>,
+	sessions: HashMap<NuttyId, Session>,
+	exports: HashMap<NuttyId, (NavigatorExport, serde_json::Value)>,
+
+	/// The (blocker ID, blocked ID, time) of each block.
+	blocks: Vec<(NuttyId, NuttyId, DateTimeRfc3339)>,
+
+	/// The (navigator ID, step, time) of each completed onboarding step.
+	onboarding: Vec<(NuttyId, OnboardingStep, DateTimeRfc3339)>,
+
+	/// The (navigator ID, usage, time) of each flush of request usage.
+	request_usage: Vec<(NuttyId, RequestUsage, DateTime<Utc>)>,
+
+	/// The sessions evicted by newer logins, within the default limit.
+	evicted_sessions: Vec<Session>,
+
+	/// The calendar feed token versions of navigators that regenerated theirs.
+	feed_token_versions: HashMap<NuttyId, i32>,
+}
+
+impl FakeNavigatorService {
+	/// Create an empty navigator service.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	fn lock(&self) -> std::sync::MutexGuard<'_, FakeNavigatorState> {
+		self.state.lock().expect("Fake navigator state poisoned")
+	}
+}
+
+#[async_trait]
+impl NavigatorServiceApi for FakeNavigatorService {
+	async fn register(
+		&self,
+		name: String,
+		pass: String,
+	) -> Result<Navigator, NavigatorServiceError> {
+		let mut state = self.lock();
+
+		if state.navigators.values().any(|n| n.name() == name) {
+			let error = NavigatorRepositoryError::NameTaken;
+			return Err(NavigatorServiceError::Insert(error));
+		}
+
+		let navigator = Navigator::new(name, &pass).map_err(NavigatorServiceError::Create)?;
+		state
+			.navigators
+			.insert(*navigator.nutty_id(), navigator.clone());
+
+		Ok(navigator)
+	}
+
+	async fn login(
+		&self,
+		name: String,
+		password: String,
+		user_agent: String,
+		country: Option<String>,
+	) -> Result<(Navigator, Session), NavigatorServiceError> {
+		let mut state = self.lock();
+
+		let navigator = state
+			.navigators
+			.values()
+			.find(|n| n.name() == name && n.verify_password(&password))
+			.cloned()
+			.ok_or(NavigatorServiceError::InvalidCredentials)?;
+
+		let session = Session::new(*navigator.nutty_id(), user_agent, chrono::Duration::days(1))
+			.map_err(NavigatorServiceError::CreateSession)?
+			.with_country(country);
+
+		state.sessions.insert(*session.nutty_id(), session.clone());
+
+		let mut sessions = state
+			.sessions
+			.values()
+			.filter(|s| s.navigator_id() == navigator.nutty_id())
+			.cloned()
+			.collect::<Vec<_>>();
+
+		sessions.sort_by(|a, b| b.created_at().partial_cmp(a.created_at()).unwrap());
+
+		for evicted in SessionLimit::default().evicted(&sessions) {
+			state.sessions.remove(evicted.nutty_id());
+			state.evicted_sessions.push(evicted.clone());
+		}
+
+		Ok((navigator, session))
+	}
+
+	fn password_hash_metrics(&self) -> PasswordHashMetrics {
+		PasswordHashMetrics::default()
+	}
+
+	async fn rename(
+		&self,
+		navigator_id: &NuttyId,
+		new_name: String,
+	) -> Result<Navigator, NavigatorServiceError> {
+		let mut state = self.lock();
+
+		if state
+			.navigators
+			.values()
+			.any(|n| n.name() == new_name && n.nutty_id() != navigator_id)
+		{
+			let error = NavigatorRepositoryError::NameTaken;
+			return Err(NavigatorServiceError::UpdateNavigator(error));
+		}
+
+		let navigator = state
+			.navigators
+			.get_mut(navigator_id)
+			.ok_or(NavigatorServiceError::NavigatorNotFound)?;
+
+		navigator
+			.update_name(&new_name)
+			.map_err(NavigatorServiceError::InvalidName)?;
+
+		Ok(navigator.clone())
+	}
+
+	async fn logout(&self, session_id: &NuttyId) -> Result<(), NavigatorServiceError> {
+		self.lock().sessions.remove(session_id);
+		Ok(())
+	}
+
+	async fn get_navigator_by_id(
+		&self,
+		id: &NuttyId,
+	) -> Result<Option<Navigator>, NavigatorServiceError> {
+		Ok(self.lock().navigators.get(id).cloned())
+	}
+
+	async fn get_navigator_by_name(
+		&self,
+		name: &str,
+	) -> Result<Option<Navigator>, NavigatorServiceError> {
+		Ok(self
+			.lock()
+			.navigators
+			.values()
+			.find(|navigator| navigator.name() == name)
+			.cloned())
+	}
+
+	async fn get_session_by_id(
+		&self,
+		id: &NuttyId,
+	) -> Result<Option<Session>, NavigatorServiceError> {
+		Ok(self.lock().sessions.get(id).cloned())
+	}
+
+	async fn list_sessions(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<Session>, NavigatorServiceError> {
+		let mut sessions: Vec<_> = self
+			.lock()
+			.sessions
+			.values()
+			.filter(|s| s.navigator_id() == navigator_id)
+			.cloned()
+			.collect();
+
+		sessions.sort_by(|a, b| {
+			b.created_at()
+				.partial_cmp(a.created_at())
+				.unwrap_or(std::cmp::Ordering::Equal)
+		});
+		Ok(sessions)
+	}
+
+	async fn label_session(
+		&self,
+		id: &NuttyId,
+		navigator_id: &NuttyId,
+		label: Option<String>,
+	) -> Result<Session, NavigatorServiceError> {
+		let label = normalize_session_label(label)?;
+		let mut state = self.lock();
+
+		let session = state
+			.sessions
+			.get_mut(id)
+			.filter(|s| s.navigator_id() == navigator_id)
+			.ok_or(NavigatorServiceError::SessionNotFound)?;
+
+		*session = session.clone().with_label(label);
+		Ok(session.clone())
+	}
+
+	async fn get_session_by_token(
+		&self,
+		token: &SessionToken,
+	) -> Result<Option<Session>, NavigatorServiceError> {
+		Ok(self
+			.lock()
+			.sessions
+			.values()
+			.find(|s| s.matches_token(token))
+			.cloned())
+	}
+
+	async fn is_session_evicted(&self, token: &SessionToken) -> Result<bool, NavigatorServiceError> {
+		Ok(self
+			.lock()
+			.evicted_sessions
+			.iter()
+			.any(|s| s.matches_token(token) && !s.is_expired()))
+	}
+
+	/// Exports are assembled immediately, from navigators and sessions only.
+	async fn request_export(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<NavigatorExport, NavigatorServiceError> {
+		let mut state = self.lock();
+
+		let navigator = state
+			.navigators
+			.get(navigator_id)
+			.cloned()
+			.ok_or(NavigatorServiceError::NavigatorNotFound)?;
+
+		let archive = ExportArchive {
+			exported_at: DateTimeRfc3339::new(chrono::Utc::now().fixed_offset()),
+			navigator,
+			name_changes: vec![],
+			sessions: state
+				.sessions
+				.values()
+				.filter(|s| s.navigator_id() == navigator_id)
+				.cloned()
+				.collect(),
+			chat_links: vec![],
+			blocks: vec![],
+			reports: vec![],
+			access_requests: vec![],
+			ownership_transfers: vec![],
+		};
+
+		let archive =
+			serde_json::to_value(archive).map_err(NavigatorServiceError::SerializeExport)?;
+
+		let mut export = NavigatorExport::new(*navigator_id, chrono::Duration::hours(24));
+		export.complete();
+
+		state
+			.exports
+			.insert(*export.nutty_id(), (export.clone(), archive));
+
+		Ok(export)
+	}
+
+	async fn get_export(
+		&self,
+		id: &NuttyId,
+		navigator_id: &NuttyId,
+	) -> Result<Option<NavigatorExport>, NavigatorServiceError> {
+		Ok(self
+			.lock()
+			.exports
+			.get(id)
+			.map(|(export, _)| export.clone())
+			.filter(|export| export.navigator_id() == navigator_id))
+	}
+
+	async fn get_export_archive(
+		&self,
+		id: &NuttyId,
+	) -> Result<Option<(NavigatorExport, serde_json::Value)>, NavigatorServiceError> {
+		Ok(self
+			.lock()
+			.exports
+			.get(id)
+			.filter(|(export, _)| !export.is_expired())
+			.cloned())
+	}
+
+	async fn block_navigator(
+		&self,
+		blocker_id: &NuttyId,
+		name: &str,
+	) -> Result<(), NavigatorServiceError> {
+		let blocked = self
+			.get_navigator_by_name(name)
+			.await?
+			.ok_or(NavigatorServiceError::NavigatorNotFound)?;
+
+		if blocked.nutty_id() == blocker_id {
+			return Err(NavigatorServiceError::BlockSelf);
+		}
+
+		let mut state = self.lock();
+		let blocked_id = *blocked.nutty_id();
+
+		if !state
+			.blocks
+			.iter()
+			.any(|(a, b, _)| a == blocker_id && *b == blocked_id)
+		{
+			let now = DateTimeRfc3339::new(chrono::Utc::now().fixed_offset());
+			state.blocks.push((*blocker_id, blocked_id, now));
+		}
+
+		Ok(())
+	}
+
+	async fn unblock_navigator(
+		&self,
+		blocker_id: &NuttyId,
+		name: &str,
+	) -> Result<(), NavigatorServiceError> {
+		let blocked = self
+			.get_navigator_by_name(name)
+			.await?
+			.ok_or(NavigatorServiceError::NavigatorNotFound)?;
+
+		let mut state = self.lock();
+		let count = state.blocks.len();
+
+		state
+			.blocks
+			.retain(|(a, b, _)| a != blocker_id || b != blocked.nutty_id());
+
+		match state.blocks.len() < count {
+			true => Ok(()),
+			false => Err(NavigatorServiceError::NavigatorNotFound),
+		}
+	}
+
+	async fn list_blocked_navigators(
+		&self,
+		blocker_id: &NuttyId,
+	) -> Result<Vec<BlockedNavigator>, NavigatorServiceError> {
+		let state = self.lock();
+
+		Ok(state
+			.blocks
+			.iter()
+			.rev()
+			.filter(|(a, ..)| a == blocker_id)
+			.filter_map(|(_, blocked_id, blocked_at)| {
+				let navigator = state.navigators.get(blocked_id)?;
+
+				Some(BlockedNavigator {
+					navigator_id: *blocked_id,
+					name: navigator.name().to_string(),
+					blocked_at: *blocked_at,
+				})
+			})
+			.collect())
+	}
+
+	async fn provision_starter_content(
+		&self,
+		_navigator_id: &NuttyId,
+	) -> Result<Vec<ContentBlock>, NavigatorServiceError> {
+		Err(NavigatorServiceError::OnboardingDisabled)
+	}
+
+	async fn list_onboarding_steps(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<OnboardingProgress>, NavigatorServiceError> {
+		let state = self.lock();
+
+		let completed: Vec<_> = state
+			.onboarding
+			.iter()
+			.filter(|(id, ..)| id == navigator_id)
+			.map(|(_, step, completed_at)| (*step, *completed_at))
+			.collect();
+
+		Ok(OnboardingProgress::list(&completed))
+	}
+
+	async fn complete_onboarding_step(
+		&self,
+		navigator_id: &NuttyId,
+		step: OnboardingStep,
+	) -> Result<Vec<OnboardingProgress>, NavigatorServiceError> {
+		{
+			let mut state = self.lock();
+
+			if !state
+				.onboarding
+				.iter()
+				.any(|(id, completed, _)| id == navigator_id && *completed == step)
+			{
+				let now = DateTimeRfc3339::new(chrono::Utc::now().fixed_offset());
+				state.onboarding.push((*navigator_id, step, now));
+			}
+		}
+
+		self.list_onboarding_steps(navigator_id).await
+	}
+
+	async fn get_feed_token_version(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Option<i32>, NavigatorServiceError> {
+		let state = self.lock();
+
+		Ok(state.navigators.contains_key(navigator_id).then(|| {
+			state
+				.feed_token_versions
+				.get(navigator_id)
+				.copied()
+				.unwrap_or_default()
+		}))
+	}
+
+	async fn regenerate_feed_token(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<i32, NavigatorServiceError> {
+		let mut state = self.lock();
+
+		if !state.navigators.contains_key(navigator_id) {
+			return Err(NavigatorServiceError::NavigatorNotFound);
+		}
+
+		let version = state.feed_token_versions.entry(*navigator_id).or_default();
+		*version += 1;
+		Ok(*version)
+	}
+
+	async fn record_request_usage(
+		&self,
+		usage: &[(NuttyId, RequestUsage)],
+	) -> Result<(), NavigatorServiceError> {
+		let mut state = self.lock();
+		let now = Utc::now();
+
+		for (navigator_id, usage) in usage {
+			if state.navigators.contains_key(navigator_id) {
+				state
+					.request_usage
+					.push((*navigator_id, usage.clone(), now));
+			}
+		}
+
+		Ok(())
+	}
+
+	async fn rank_request_usage(
+		&self,
+		since: &DateTime<Utc>,
+		sort: UsageSort,
+		limit: usize,
+	) -> Result<Vec<NavigatorUsage>, NavigatorServiceError> {
+		let state = self.lock();
+		let mut totals: HashMap<NuttyId, RequestUsage> = HashMap::new();
+
+		for (navigator_id, usage, recorded_at) in &state.request_usage {
+			if recorded_at >= since {
+				totals.entry(*navigator_id).or_default().merge(usage);
+			}
+		}
+
+		let usage = totals
+			.iter()
+			.filter_map(|(navigator_id, usage)| {
+				let navigator = state.navigators.get(navigator_id)?;
+				Some(NavigatorUsage::new(
+					*navigator_id,
+					navigator.name().to_string(),
+					usage,
+				))
+			})
+			.collect();
+
+		Ok(NavigatorUsage::rank(usage, sort, limit))
+	}
+}
@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+
+use crate::system::models::SchemaTable;
+use crate::system::service::SystemServiceApi;
+use crate::system::service::SystemServiceError;
+
+/// A [SystemServiceApi] that describes whatever tables it's given.
+#[derive(Default)]
+pub struct FakeSystemService {
+	tables: Vec<SchemaTable>,
+}
+
+impl FakeSystemService {
+	/// Create a system service that describes no tables.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Describe the given tables as the schema.
+	pub fn with_tables(mut self, tables: Vec<SchemaTable>) -> Self {
+		self.tables = tables;
+		self
+	}
+}
+
+#[async_trait]
+impl SystemServiceApi for FakeSystemService {
+	async fn describe_schema(&self) -> Result<Vec<SchemaTable>, SystemServiceError> {
+		Ok(self.tables.clone())
+	}
+}
@@ -0,0 +1,100 @@
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::integrations::webhooks::models::MAX_SUBSCRIPTIONS;
+use crate::integrations::webhooks::models::WebhookEventType;
+use crate::integrations::webhooks::models::WebhookSubscription;
+use crate::integrations::webhooks::models::WebhookSubscriptionRequest;
+use crate::integrations::webhooks::service::WebhookServiceApi;
+use crate::integrations::webhooks::service::WebhookServiceError;
+use crate::models::NuttyId;
+
+/// An in-memory [WebhookServiceApi].
+#[derive(Default)]
+pub struct FakeWebhookService {
+	subscriptions: Mutex<Vec<WebhookSubscription>>,
+}
+
+impl FakeWebhookService {
+	/// Create a webhook service with no subscriptions.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	fn lock(&self) -> std::sync::MutexGuard<'_, Vec<WebhookSubscription>> {
+		self
+			.subscriptions
+			.lock()
+			.expect("Fake webhook state poisoned")
+	}
+}
+
+#[async_trait]
+impl WebhookServiceApi for FakeWebhookService {
+	async fn create_subscription(
+		&self,
+		navigator_id: &NuttyId,
+		request: &WebhookSubscriptionRequest,
+	) -> Result<WebhookSubscription, WebhookServiceError> {
+		let subscription = WebhookSubscription::new(*navigator_id, request)
+			.map_err(WebhookServiceError::InvalidSubscription)?;
+
+		let mut subscriptions = self.lock();
+
+		let count = subscriptions
+			.iter()
+			.filter(|s| s.navigator_id == *navigator_id)
+			.count();
+
+		if count >= MAX_SUBSCRIPTIONS {
+			return Err(WebhookServiceError::TooManySubscriptions);
+		}
+
+		subscriptions.push(subscription.clone());
+		Ok(subscription)
+	}
+
+	async fn list_subscriptions(
+		&self,
+		navigator_id: &NuttyId,
+	) -> Result<Vec<WebhookSubscription>, WebhookServiceError> {
+		Ok(self
+			.lock()
+			.iter()
+			.filter(|s| s.navigator_id == *navigator_id)
+			.map(|s| WebhookSubscription {
+				secret: None,
+				..s.clone()
+			})
+			.collect())
+	}
+
+	async fn delete_subscription(
+		&self,
+		navigator_id: &NuttyId,
+		subscription_id: &NuttyId,
+	) -> Result<(), WebhookServiceError> {
+		let mut subscriptions = self.lock();
+		let before = subscriptions.len();
+
+		subscriptions.retain(|s| !(s.id == *subscription_id && s.navigator_id == *navigator_id));
+
+		match subscriptions.len() < before {
+			true => Ok(()),
+			false => Err(WebhookServiceError::SubscriptionNotFound),
+		}
+	}
+
+	async fn list_subscribers(
+		&self,
+		event_type: WebhookEventType,
+	) -> Result<Vec<WebhookSubscription>, WebhookServiceError> {
+		Ok(self
+			.lock()
+			.iter()
+			.filter(|s| s.wants(event_type))
+			.cloned()
+			.collect())
+	}
+}
@@ -0,0 +1,91 @@
+use axum::extract::Request;
+use axum::http::HeaderValue;
+use axum::http::StatusCode;
+use axum::http::header::CONTENT_TYPE;
+use axum::middleware::Next;
+use axum::response::IntoResponse;
+use serde::Serialize;
+
+/// Request body size limits, in bytes, per group of routes.
+#[derive(Debug, Clone, Copy)]
+pub struct BodyLimits {
+	/// The limit for authentication routes (e.g., registration, login).
+	pub auth: usize,
+
+	/// The limit for content routes.
+	pub content: usize,
+
+	/// The limit for system routes.
+	pub system: usize,
+}
+
+impl BodyLimits {
+	/// Read the limits from `BODY_LIMIT_{AUTH,CONTENT,SYSTEM}`, falling back to the defaults.
+	pub fn from_env() -> Self {
+		let defaults = Self::default();
+
+		let read = |name: &str, default: usize| {
+			std::env::var(name)
+				.ok()
+				.and_then(|v| v.parse().ok())
+				.unwrap_or(default)
+		};
+
+		Self {
+			auth: read("BODY_LIMIT_AUTH", defaults.auth),
+			content: read("BODY_LIMIT_CONTENT", defaults.content),
+			system: read("BODY_LIMIT_SYSTEM", defaults.system),
+		}
+	}
+}
+
+impl Default for BodyLimits {
+	fn default() -> Self {
+		Self {
+			auth: 16 * 1024,
+			content: 1024 * 1024,
+			system: 4 * 1024,
+		}
+	}
+}
+
+/// A problem details object (RFC 9457).
+#[derive(Debug, Serialize)]
+pub struct ProblemDetails {
+	#[serde(rename = "type")]
+	kind: String,
+	title: String,
+	status: u16,
+	detail: String,
+}
+
+/// Middleware that rewrites 413 responses into `application/problem+json`.
+pub async fn payload_too_large_middleware(
+	request: Request,
+	next: Next,
+) -> axum::response::Response {
+	let response = next.run(request).await;
+
+	if response.status() != StatusCode::PAYLOAD_TOO_LARGE {
+		return response;
+	}
+
+	let problem = ProblemDetails {
+		kind: "about:blank".to_string(),
+		title: "Payload Too Large".to_string(),
+		status: StatusCode::PAYLOAD_TOO_LARGE.as_u16(),
+		detail: "The request body exceeds the size limit for this route.".to_string(),
+	};
+
+	let body = serde_json::to_string(&problem).expect("Failed to serialize problem details");
+
+	(
+		StatusCode::PAYLOAD_TOO_LARGE,
+		[(
+			CONTENT_TYPE,
+			HeaderValue::from_static("application/problem+json"),
+		)],
+		body,
+	)
+		.into_response()
+}
@@ -0,0 +1,106 @@
+use std::fmt;
+
+use crate::models::NuttyId;
+use crate::models::navigator_export::NavigatorExport;
+use crate::utilities::api::webhook::WebhookSecret;
+
+/// Signs the links that export archives are downloaded through, so that
+/// downloads don't need a session. A link expires with its export. Every
+/// link is rejected unless a secret is configured.
+#[derive(Clone, Default)]
+pub struct ExportLinks {
+	secret: WebhookSecret,
+}
+
+impl ExportLinks {
+	/// Create a link signer from a secret.
+	pub fn new(secret: WebhookSecret) -> Self {
+		Self { secret }
+	}
+
+	/// Read the secret from `EXPORT_LINK_SECRET`, if set.
+	pub fn from_env() -> Self {
+		Self::new(WebhookSecret::from_env("EXPORT_LINK_SECRET"))
+	}
+
+	/// Check whether links can be signed.
+	pub fn is_enabled(&self) -> bool {
+		self.secret.sign(b"").is_some()
+	}
+
+	/// Get an export's download path, signed until the export expires.
+	/// Returns nothing if disabled.
+	pub fn sign(&self, export: &NavigatorExport) -> Option<String> {
+		let export_id = export.nutty_id();
+		let expires = export.expires_at().inner().timestamp();
+		let signature = self.secret.sign(&payload(export_id, expires))?;
+		let signature = signature.trim_start_matches("sha256=");
+
+		Some(format!(
+			"/navigator/exports/{export_id}/download?expires={expires}&signature={signature}"
+		))
+	}
+
+	/// Verify a download's signature, and that it hasn't expired.
+	pub fn verify(&self, export_id: &NuttyId, expires: i64, signature: &str) -> bool {
+		let signature = format!("sha256={signature}");
+
+		expires > chrono::Utc::now().timestamp()
+			&& self.secret.verify(&payload(export_id, expires), &signature)
+	}
+}
+
+impl fmt::Debug for ExportLinks {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("ExportLinks([REDACTED])")
+	}
+}
+
+/// The signed payload, scoped so that other uses of the secret can't be
+/// replayed as links.
+fn payload(export_id: &NuttyId, expires: i64) -> Vec<u8> {
+	format!("navigator-export:{}:{expires}", export_id.uuid().simple()).into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn query(link: &str, name: &str) -> String {
+		let (_, query) = link.split_once('?').unwrap();
+
+		query
+			.split('&')
+			.find_map(|pair| pair.strip_prefix(&format!("{name}=")))
+			.unwrap()
+			.to_string()
+	}
+
+	#[test]
+	fn test_verify_link() {
+		let links = ExportLinks::new(WebhookSecret::new(Some(b"hunter2".to_vec())));
+		let export = NavigatorExport::new(NuttyId::now(), chrono::Duration::hours(1));
+		let link = links.sign(&export).unwrap();
+
+		let expires: i64 = query(&link, "expires").parse().unwrap();
+		let signature = query(&link, "signature");
+		assert!(link.starts_with(&format!("/navigator/exports/{}/", export.nutty_id())));
+		assert!(links.verify(export.nutty_id(), expires, &signature));
+
+		// Assert: Links don't verify for other exports or expiry times.
+		assert!(!links.verify(&NuttyId::now(), expires, &signature));
+		assert!(!links.verify(export.nutty_id(), expires + 60, &signature));
+
+		// Assert: Expired links don't verify.
+		let expired = NavigatorExport::new(NuttyId::now(), chrono::Duration::hours(-1));
+		let link = links.sign(&expired).unwrap();
+		let expires: i64 = query(&link, "expires").parse().unwrap();
+		assert!(!links.verify(expired.nutty_id(), expires, &query(&link, "signature")));
+
+		// Assert: Nothing verifies while disabled.
+		let disabled = ExportLinks::default();
+		assert!(!disabled.is_enabled());
+		assert_eq!(disabled.sign(&export), None);
+		assert!(!disabled.verify(export.nutty_id(), expires, &signature));
+	}
+}
@@ -0,0 +1,100 @@
+use std::fmt;
+
+use uuid::Uuid;
+
+use crate::models::NuttyId;
+use crate::utilities::api::webhook::WebhookSecret;
+
+/// Signs the tokens that stand in for a session on a navigator's calendar
+/// feed, since calendar apps subscribe by URL alone. Tokens don't expire, but
+/// carry the version of the navigator's token, so that regenerating it
+/// revokes the ones before; rotating the secret revokes all of them. Every
+/// token is rejected unless a secret is configured.
+#[derive(Clone, Default)]
+pub struct FeedTokens {
+	secret: WebhookSecret,
+}
+
+impl FeedTokens {
+	/// Create a token signer from a secret.
+	pub fn new(secret: WebhookSecret) -> Self {
+		Self { secret }
+	}
+
+	/// Read the secret from `CALENDAR_FEED_SECRET`, if set.
+	pub fn from_env() -> Self {
+		Self::new(WebhookSecret::from_env("CALENDAR_FEED_SECRET"))
+	}
+
+	/// Issue a navigator's token at a version, as `<uuid>.<version>.<hex>`.
+	/// Returns nothing if disabled.
+	pub fn issue(&self, navigator_id: &NuttyId, version: i32) -> Option<String> {
+		let navigator_id = navigator_id.uuid().simple().to_string();
+		let version = version.to_string();
+		let signature = self.secret.sign(&payload(&navigator_id, &version))?;
+		let signature = signature.trim_start_matches("sha256=");
+		Some(format!("{navigator_id}.{version}.{signature}"))
+	}
+
+	/// Verify a token, returning the navigator that it was issued to and its
+	/// version. Callers must check that the version is still current.
+	pub fn verify(&self, token: &str) -> Option<(NuttyId, i32)> {
+		let mut parts = token.splitn(3, '.');
+		let navigator_id = parts.next()?;
+		let version = parts.next()?;
+		let signature = format!("sha256={}", parts.next()?);
+
+		if !self
+			.secret
+			.verify(&payload(navigator_id, version), &signature)
+		{
+			return None;
+		}
+
+		let navigator_id = Uuid::try_parse(navigator_id).ok().map(NuttyId::new)?;
+		let version = version.parse().ok()?;
+		Some((navigator_id, version))
+	}
+}
+
+impl fmt::Debug for FeedTokens {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("FeedTokens([REDACTED])")
+	}
+}
+
+/// The signed payload, scoped so that other uses of the secret can't be
+/// replayed as tokens.
+fn payload(navigator_id: &str, version: &str) -> Vec<u8> {
+	format!("calendar-feed:{navigator_id}:{version}").into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_verify_token() {
+		let tokens = FeedTokens::new(WebhookSecret::new(Some(b"hunter2".to_vec())));
+		let navigator_id = NuttyId::now();
+		let token = tokens.issue(&navigator_id, 1).unwrap();
+
+		assert_eq!(tokens.verify(&token), Some((navigator_id, 1)));
+
+		// Assert: Tokens for other navigators or versions don't verify.
+		let (_, signature) = token.rsplit_once('.').unwrap();
+		let forged = format!("{}.1.{signature}", NuttyId::now().uuid().simple());
+		assert_eq!(tokens.verify(&forged), None);
+		let forged = format!("{}.2.{signature}", navigator_id.uuid().simple());
+		assert_eq!(tokens.verify(&forged), None);
+		assert_eq!(
+			tokens.verify(&navigator_id.uuid().simple().to_string()),
+			None
+		);
+
+		// Assert: Nothing verifies while disabled.
+		let disabled = FeedTokens::default();
+		assert_eq!(disabled.issue(&navigator_id, 1), None);
+		assert_eq!(disabled.verify(&token), None);
+	}
+}
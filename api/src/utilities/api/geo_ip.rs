@@ -0,0 +1,64 @@
+use axum::http::HeaderMap;
+use axum::http::HeaderName;
+
+/// Resolves the country of a request from a header set by a trusted proxy
+/// (e.g., `CF-IPCountry`). Disabled unless a header is configured.
+#[derive(Debug, Clone, Default)]
+pub struct GeoIp {
+	/// The header carrying the ISO 3166-1 alpha-2 country code.
+	country_header: Option<HeaderName>,
+}
+
+impl GeoIp {
+	/// Create a resolver that reads the given header.
+	pub fn new(country_header: Option<HeaderName>) -> Self {
+		Self { country_header }
+	}
+
+	/// Read the header name from `GEOIP_COUNTRY_HEADER`, if set.
+	pub fn from_env() -> Self {
+		let country_header = std::env::var("GEOIP_COUNTRY_HEADER")
+			.ok()
+			.map(|v| HeaderName::try_from(v).expect("Invalid GEOIP_COUNTRY_HEADER"));
+
+		Self { country_header }
+	}
+
+	/// Resolve the country of a request, if enabled and known.
+	pub fn country(&self, headers: &HeaderMap) -> Option<String> {
+		let value = headers.get(self.country_header.as_ref()?)?.to_str().ok()?;
+
+		// Proxies use "XX" (or similar) for unknown locations.
+		let is_country_code = value.len() == 2
+			&& value.bytes().all(|b| b.is_ascii_alphabetic())
+			&& !value.eq_ignore_ascii_case("XX");
+
+		is_country_code.then(|| value.to_ascii_uppercase())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use axum::http::HeaderValue;
+
+	use super::*;
+
+	#[test]
+	fn test_country_from_header() {
+		let mut headers = HeaderMap::new();
+		headers.insert("cf-ipcountry", HeaderValue::from_static("us"));
+
+		// Assert: Nothing is resolved while disabled.
+		assert_eq!(GeoIp::default().country(&headers), None);
+
+		// Assert: The configured header is read and normalized.
+		let geo_ip = GeoIp::new(Some(HeaderName::from_static("cf-ipcountry")));
+		assert_eq!(geo_ip.country(&headers), Some("US".to_string()));
+
+		// Assert: Unknown or malformed values are ignored.
+		headers.insert("cf-ipcountry", HeaderValue::from_static("XX"));
+		assert_eq!(geo_ip.country(&headers), None);
+		headers.insert("cf-ipcountry", HeaderValue::from_static("T1X"));
+		assert_eq!(geo_ip.country(&headers), None);
+	}
+}
@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use url::Url;
+
+use crate::utilities::http::HttpClient;
+use crate::utilities::http::HttpError;
+use crate::utilities::http::HttpPolicy;
+
+/// How long to wait for a link before giving up on it.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Statuses that often mean a server refuses bots or HEAD requests, rather
+/// than that the page is gone.
+const INCONCLUSIVE_STATUSES: [StatusCode; 4] = [
+	StatusCode::UNAUTHORIZED,
+	StatusCode::FORBIDDEN,
+	StatusCode::METHOD_NOT_ALLOWED,
+	StatusCode::TOO_MANY_REQUESTS,
+];
+
+/// Checks external links in content blocks with HEAD requests. Links on
+/// loopback, private, or link-local addresses aren't checked. Disabled by
+/// default, in which case no link is reported broken.
+#[derive(Clone, Default)]
+pub struct LinkChecker {
+	/// The HTTP client, or `None` if checking is disabled.
+	client: Option<HttpClient>,
+}
+
+impl LinkChecker {
+	/// Create an enabled link checker.
+	pub fn new() -> Self {
+		let client = HttpClient::new(HttpPolicy {
+			timeout: CHECK_TIMEOUT,
+			..Default::default()
+		});
+
+		Self {
+			client: Some(client),
+		}
+	}
+
+	/// Create a link checker unless `LINK_CHECK` is "0" or "false".
+	pub fn from_env() -> Self {
+		let disabled = std::env::var("LINK_CHECK").is_ok_and(|v| v == "0" || v == "false");
+
+		match disabled {
+			true => Self::default(),
+			false => Self::new(),
+		}
+	}
+
+	/// Check a link, returning why it's broken if it clearly is. Timeouts,
+	/// refusals, unfollowed redirects, and links the client won't request
+	/// are inconclusive, so they aren't reported.
+	pub async fn check(&self, url: &Url) -> Option<String> {
+		let client = self.client.as_ref()?;
+
+		match client.head(url).await {
+			Ok(response) => {
+				let status = response.status();
+
+				let broken = (status.is_client_error() || status.is_server_error())
+					&& !INCONCLUSIVE_STATUSES.contains(&status);
+
+				broken.then(|| format!("it returned {status}"))
+			}
+
+			Err(HttpError::Request(error)) if error.is_timeout() || error.is_redirect() => None,
+			Err(HttpError::Request(_)) => Some("it couldn't be reached".to_string()),
+			Err(_) => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn test_check_guards() {
+		// Assert: Nothing is reported while disabled.
+		let url = Url::parse("https://example.invalid/").unwrap();
+		assert_eq!(LinkChecker::default().check(&url).await, None);
+
+		// Assert: Non-public hosts aren't checked.
+		let checker = LinkChecker::new();
+
+		for url in [
+			"http://localhost:1/",
+			"http://127.0.0.1:1/",
+			"http://10.0.0.8/",
+		] {
+			assert_eq!(checker.check(&Url::parse(url).unwrap()).await, None);
+		}
+	}
+}
@@ -1,3 +1,12 @@
+pub mod body_limit;
+pub mod export_link;
+pub mod feed_token;
+pub mod geo_ip;
+pub mod link_checker;
+pub mod og_image_link;
+pub mod page_fetcher;
+pub mod read_only;
 pub mod response;
 pub mod session;
 pub mod state;
+pub mod webhook;
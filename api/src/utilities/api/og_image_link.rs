@@ -0,0 +1,115 @@
+use std::fmt;
+
+use crate::models::DissociatedNuttyId;
+use crate::utilities::api::webhook::WebhookSecret;
+
+/// How long a preview image's link lasts.
+const LINK_TTL: i64 = 7 * 24 * 60 * 60;
+
+/// Signs the links that blocks' preview images are fetched through, since
+/// social media crawlers fetch them without a session. Links expire a week
+/// after the day they're signed on, so that a page's link is the same for a
+/// day at a time and caches can keep it. Every link is rejected unless a
+/// secret is configured.
+#[derive(Clone, Default)]
+pub struct OgImageLinks {
+	secret: WebhookSecret,
+}
+
+impl OgImageLinks {
+	/// Create a link signer from a secret.
+	pub fn new(secret: WebhookSecret) -> Self {
+		Self { secret }
+	}
+
+	/// Read the secret from `OG_IMAGE_SECRET`, if set.
+	pub fn from_env() -> Self {
+		Self::new(WebhookSecret::from_env("OG_IMAGE_SECRET"))
+	}
+
+	/// Get a block's preview image path, signed. Returns nothing if disabled.
+	pub fn sign(&self, block_id: &DissociatedNuttyId) -> Option<String> {
+		let now = chrono::Utc::now().timestamp();
+		let signed_on = now - now.rem_euclid(24 * 60 * 60);
+
+		self.sign_until(block_id, signed_on + LINK_TTL)
+	}
+
+	/// Get a block's preview image path, signed until a given time.
+	fn sign_until(&self, block_id: &DissociatedNuttyId, expires: i64) -> Option<String> {
+		let nid = block_id.nid();
+		let signature = self.secret.sign(&payload(&nid, expires))?;
+		let signature = signature.trim_start_matches("sha256=");
+
+		Some(format!(
+			"/og/{nid}.png?expires={expires}&signature={signature}"
+		))
+	}
+
+	/// Verify a preview image's signature, and that it hasn't expired.
+	pub fn verify(&self, block_id: &DissociatedNuttyId, expires: i64, signature: &str) -> bool {
+		let signature = format!("sha256={signature}");
+
+		expires > chrono::Utc::now().timestamp()
+			&& self
+				.secret
+				.verify(&payload(&block_id.nid(), expires), &signature)
+	}
+}
+
+impl fmt::Debug for OgImageLinks {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("OgImageLinks([REDACTED])")
+	}
+}
+
+/// The signed payload, scoped so that other uses of the secret can't be
+/// replayed as links.
+fn payload(nid: &str, expires: i64) -> Vec<u8> {
+	format!("og-image:{nid}:{expires}").into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::models::NuttyId;
+
+	fn query(link: &str, name: &str) -> String {
+		let (_, query) = link.split_once('?').unwrap();
+
+		query
+			.split('&')
+			.find_map(|pair| pair.strip_prefix(&format!("{name}=")))
+			.unwrap()
+			.to_string()
+	}
+
+	#[test]
+	fn test_verify_link() {
+		let links = OgImageLinks::new(WebhookSecret::new(Some(b"hunter2".to_vec())));
+		let block_id = NuttyId::now().dissociate();
+		let link = links.sign(&block_id).unwrap();
+
+		let expires: i64 = query(&link, "expires").parse().unwrap();
+		let signature = query(&link, "signature");
+		assert!(link.starts_with(&format!("/og/{}.png?", block_id.nid())));
+		assert!(links.verify(&block_id, expires, &signature));
+
+		// Assert: Links are the same for the rest of the day.
+		assert_eq!(links.sign(&block_id).unwrap(), link);
+
+		// Assert: Links don't verify for other blocks or expiry times.
+		assert!(!links.verify(&NuttyId::now().dissociate(), expires, &signature));
+		assert!(!links.verify(&block_id, expires + 60, &signature));
+
+		// Assert: Expired links don't verify.
+		let expired = chrono::Utc::now().timestamp() - 60;
+		let link = links.sign_until(&block_id, expired).unwrap();
+		assert!(!links.verify(&block_id, expired, &query(&link, "signature")));
+
+		// Assert: Nothing verifies while disabled.
+		let disabled = OgImageLinks::default();
+		assert_eq!(disabled.sign(&block_id), None);
+		assert!(!disabled.verify(&block_id, expires, &signature));
+	}
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use reqwest::header::CONTENT_TYPE;
+use thiserror::Error;
+use url::Url;
+
+use crate::utilities::http::HttpClient;
+use crate::utilities::http::HttpError;
+use crate::utilities::http::HttpPolicy;
+
+/// How long to wait for a page before giving up.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The largest page read, in bytes. Anything past it is ignored.
+const MAX_PAGE_SIZE: usize = 2 * 1024 * 1024;
+
+/// The default minimum time between fetches from the same host.
+const DEFAULT_MIN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Fetches captured pages, at most once per interval from each host. Pages
+/// on loopback, private, or link-local addresses are refused. Disabled by
+/// default, in which case every fetch fails.
+#[derive(Clone, Default)]
+pub struct PageFetcher {
+	/// The HTTP client, or `None` if fetching is disabled.
+	client: Option<HttpClient>,
+
+	/// The minimum time between fetches from the same host.
+	min_interval: Duration,
+
+	/// When each host was last fetched from.
+	last_fetched: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl PageFetcher {
+	/// Create a fetcher that waits at least `min_interval` between fetches
+	/// from the same host.
+	pub fn new(min_interval: Duration) -> Self {
+		let client = HttpClient::new(HttpPolicy {
+			timeout: FETCH_TIMEOUT,
+			max_response_size: MAX_PAGE_SIZE,
+			..Default::default()
+		});
+
+		Self {
+			client: Some(client),
+			min_interval,
+			last_fetched: Arc::default(),
+		}
+	}
+
+	/// Create a fetcher unless `CAPTURE_FETCH` is "0" or "false", waiting
+	/// `CAPTURE_FETCH_INTERVAL` seconds between fetches from the same host.
+	pub fn from_env() -> Self {
+		let disabled = std::env::var("CAPTURE_FETCH").is_ok_and(|v| v == "0" || v == "false");
+
+		if disabled {
+			return Self::default();
+		}
+
+		let min_interval = std::env::var("CAPTURE_FETCH_INTERVAL")
+			.ok()
+			.and_then(|v| v.parse().ok())
+			.map_or(DEFAULT_MIN_INTERVAL, Duration::from_secs);
+
+		Self::new(min_interval)
+	}
+
+	/// Fetch a page's HTML (or text), up to [MAX_PAGE_SIZE] bytes.
+	pub async fn fetch(&self, url: &Url) -> Result<String, PageFetchError> {
+		let client = self.client.as_ref().ok_or(PageFetchError::Disabled)?;
+		let host = client.check_url(url)?;
+
+		self.acquire(&host)?;
+
+		let response = client.get(url).await?.error_for_status()?;
+
+		let content_type = response
+			.header(CONTENT_TYPE)
+			.unwrap_or_default()
+			.to_ascii_lowercase();
+
+		if !content_type.starts_with("text/html") && !content_type.starts_with("text/plain") {
+			return Err(PageFetchError::UnsupportedContentType(content_type));
+		}
+
+		let body = response.body_prefix().await?;
+		Ok(String::from_utf8_lossy(&body).into_owned())
+	}
+
+	/// Record a fetch from a host, unless one was made too recently.
+	fn acquire(&self, host: &str) -> Result<(), PageFetchError> {
+		let mut last_fetched = self.last_fetched.lock().expect("Fetch times poisoned");
+		let now = Instant::now();
+
+		if last_fetched
+			.get(host)
+			.is_some_and(|last| now.duration_since(*last) < self.min_interval)
+		{
+			return Err(PageFetchError::RateLimited(host.to_string()));
+		}
+
+		// Forget hosts that can no longer limit a fetch.
+		last_fetched.retain(|_, last| now.duration_since(*last) < self.min_interval);
+		last_fetched.insert(host.to_string(), now);
+
+		Ok(())
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum PageFetchError {
+	#[error("Page fetching is disabled.")]
+	Disabled,
+
+	#[error("Too many fetches from {0}; try again later.")]
+	RateLimited(String),
+
+	#[error("Failed to fetch page: {0}")]
+	Http(#[from] HttpError),
+
+	#[error("Unsupported content type: {0:?}")]
+	UnsupportedContentType(String),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn test_fetch_guards() {
+		let url = Url::parse("https://example.com/").unwrap();
+
+		// Assert: Nothing is fetched while disabled.
+		assert!(matches!(
+			PageFetcher::default().fetch(&url).await,
+			Err(PageFetchError::Disabled)
+		));
+
+		// Assert: Non-public hosts are refused.
+		let fetcher = PageFetcher::new(Duration::from_secs(60));
+
+		for url in [
+			"http://localhost:3000/",
+			"http://127.0.0.1/",
+			"http://10.0.0.8/",
+			"http://169.254.169.254/latest/meta-data/",
+			"http://[::1]/",
+		] {
+			assert!(matches!(
+				fetcher.fetch(&Url::parse(url).unwrap()).await,
+				Err(PageFetchError::Http(HttpError::NonPublicHost(_)))
+			));
+		}
+
+		// Assert: A host is fetched from at most once per interval.
+		assert!(fetcher.acquire("example.com").is_ok());
+		assert!(matches!(
+			fetcher.acquire("example.com"),
+			Err(PageFetchError::RateLimited(_))
+		));
+		assert!(fetcher.acquire("example.org").is_ok());
+		assert!(
+			PageFetcher::new(Duration::ZERO)
+				.acquire("example.com")
+				.is_ok()
+		);
+	}
+}
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+use axum::Json;
+use axum::extract::Request;
+use axum::extract::State;
+use axum::http::HeaderValue;
+use axum::http::Method;
+use axum::http::StatusCode;
+use axum::http::header::RETRY_AFTER;
+use axum::middleware::Next;
+use axum::response::IntoResponse;
+
+use crate::utilities::api::response::Error;
+use crate::utilities::api::response::Response;
+use crate::utilities::api::state::AppState;
+
+/// Routes that keep accepting mutating requests in read-only mode,
+/// so that navigators can still sign in and switch the mode back off.
+/// Logging in only writes the new session then, and logging out only deletes
+/// one; see [NavigatorService::with_read_only].
+///
+/// [NavigatorService::with_read_only]: crate::navigator::service::NavigatorService::with_read_only
+const EXEMPT_PATHS: &[&str] = &["/admin/read-only", "/navigator/login", "/navigator/logout"];
+
+/// A global switch that puts the API into read-only mode (e.g., during migrations).
+#[derive(Debug, Clone)]
+pub struct ReadOnlyMode {
+	/// Whether read-only mode is enabled.
+	enabled: Arc<AtomicBool>,
+
+	/// The number of seconds clients should wait before retrying a rejected request.
+	retry_after: u64,
+}
+
+impl ReadOnlyMode {
+	/// Create a new read-only switch.
+	pub fn new(enabled: bool, retry_after: u64) -> Self {
+		Self {
+			enabled: Arc::new(AtomicBool::new(enabled)),
+			retry_after,
+		}
+	}
+
+	/// Check if read-only mode is enabled.
+	pub fn is_enabled(&self) -> bool {
+		self.enabled.load(Ordering::Relaxed)
+	}
+
+	/// Enable or disable read-only mode.
+	pub fn set_enabled(&self, enabled: bool) {
+		self.enabled.store(enabled, Ordering::Relaxed);
+	}
+
+	/// Get the number of seconds clients should wait before retrying.
+	pub fn retry_after(&self) -> u64 {
+		self.retry_after
+	}
+
+	/// Check if a request would be rejected in read-only mode.
+	pub fn rejects(&self, method: &Method, path: &str) -> bool {
+		let is_mutating = !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+
+		self.is_enabled() && is_mutating && !EXEMPT_PATHS.contains(&path)
+	}
+}
+
+/// Middleware that rejects mutating requests while read-only mode is enabled.
+pub async fn read_only_middleware(
+	State(state): State<Arc<AppState>>,
+	request: Request,
+	next: Next,
+) -> axum::response::Response {
+	if !state
+		.read_only
+		.rejects(request.method(), request.uri().path())
+	{
+		return next.run(request).await;
+	}
+
+	let summary = "The API is in read-only mode. Please try again later.";
+	let error = Error::from_error(&ReadOnlyError::ReadOnly).with_summary(summary);
+
+	(
+		StatusCode::SERVICE_UNAVAILABLE,
+		[(
+			RETRY_AFTER,
+			HeaderValue::from(state.read_only.retry_after()),
+		)],
+		Json(Response::<()>::Error {
+			errors: vec![error],
+		}),
+	)
+		.into_response()
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReadOnlyError {
+	#[error("The API is in read-only mode")]
+	ReadOnly,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_read_only_mode_rejects_mutations() {
+		// Arrange: Create a disabled switch.
+		let read_only = ReadOnlyMode::new(false, 60);
+
+		// Assert: Nothing is rejected while disabled.
+		assert!(!read_only.rejects(&Method::PUT, "/content-block/abcdefg"));
+
+		// Act: Enable read-only mode.
+		read_only.set_enabled(true);
+
+		// Assert: Mutations are rejected, reads are not.
+		assert!(read_only.rejects(&Method::PUT, "/content-block/abcdefg"));
+		assert!(read_only.rejects(&Method::POST, "/navigator"));
+		assert!(read_only.rejects(&Method::DELETE, "/content-block/abcdefg"));
+		assert!(!read_only.rejects(&Method::GET, "/content-block/abcdefg/context"));
+		assert!(!read_only.rejects(&Method::HEAD, "/healthz"));
+
+		// Assert: Exempt routes keep working.
+		assert!(!read_only.rejects(&Method::PUT, "/admin/read-only"));
+		assert!(!read_only.rejects(&Method::POST, "/navigator/login"));
+
+		// Assert: Clones share the same switch.
+		let clone = read_only.clone();
+		clone.set_enabled(false);
+		assert!(!read_only.is_enabled());
+	}
+}
@@ -5,13 +5,14 @@ use axum::extract::FromRequestParts;
 use axum::http::StatusCode;
 use axum::http::request::Parts;
 
-use crate::models::NuttyId;
 use crate::models::navigator::Navigator;
 use crate::models::session::Session as SessionModel;
 use crate::models::session::SessionError;
+use crate::models::session::SessionToken;
 use crate::utilities::api::response::Error;
 use crate::utilities::api::response::Response;
 use crate::utilities::api::state::AppState;
+use crate::utilities::row_level_security;
 
 #[derive(Debug, Clone)]
 pub struct Session {
@@ -36,7 +37,7 @@ impl FromRequestParts<Arc<AppState>> for Session {
 			.map(|v| v.trim())
 			.collect::<Vec<_>>();
 
-		let session_id = cookies
+		let session_token = cookies
 			.iter()
 			.find(|v| v.starts_with("session_id="))
 			.and_then(|v| v.strip_prefix("session_id="))
@@ -51,23 +52,21 @@ impl FromRequestParts<Arc<AppState>> for Session {
 				)
 			})?;
 
-		// Parse the session ID as a NuttyId.
-		let nutty_id =
-			serde_json::from_str::<NuttyId>(&format!("\"{session_id}\"")).map_err(|_| {
-				let error = Error::from_error(&SessionError::InvalidCookie)
-					.with_summary("Invalid session cookie.");
-				(
-					StatusCode::UNAUTHORIZED,
-					Json(Response::Error {
-						errors: vec![error],
-					}),
-				)
-			})?;
+		// Parse the session token.
+		let token = SessionToken::parse(session_token).map_err(|e| {
+			let error = Error::from_error(&e).with_summary("Invalid session cookie.");
+			(
+				StatusCode::UNAUTHORIZED,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			)
+		})?;
 
-		// Get the session from the database.
+		// Get the session from the database by the token's hash.
 		let session = state
 			.navigator_service
-			.get_session_by_id(&nutty_id)
+			.get_session_by_token(&token)
 			.await
 			.map_err(|e| {
 				let error = Error::from_error(&e).with_summary("Failed to retrieve session.");
@@ -77,18 +76,46 @@ impl FromRequestParts<Arc<AppState>> for Session {
 						errors: vec![error],
 					}),
 				)
-			})?
-			.ok_or_else(|| {
-				let error =
-					Error::from_error(&SessionError::SessionNotFound).with_summary("Session not found.");
-				(
-					StatusCode::UNAUTHORIZED,
-					Json(Response::Error {
-						errors: vec![error],
-					}),
-				)
 			})?;
 
+		let Some(session) = session else {
+			// Tell devices signed out by a newer login why, so that they don't
+			// mistake it for an expired session.
+			let is_evicted = state
+				.navigator_service
+				.is_session_evicted(&token)
+				.await
+				.unwrap_or(false);
+
+			let error = match is_evicted {
+				true => Error::from_error(&SessionError::SessionEvicted)
+					.with_summary("Signed out by a newer login elsewhere."),
+				false => {
+					Error::from_error(&SessionError::SessionNotFound).with_summary("Session not found.")
+				}
+			};
+
+			return Err((
+				StatusCode::UNAUTHORIZED,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			));
+		};
+
+		// Compare the stored hash in constant time.
+		if !session.matches_token(&token) {
+			let error =
+				Error::from_error(&SessionError::SessionNotFound).with_summary("Session not found.");
+
+			return Err((
+				StatusCode::UNAUTHORIZED,
+				Json(Response::Error {
+					errors: vec![error],
+				}),
+			));
+		}
+
 		// Check if the session is expired.
 		if session.is_expired() {
 			let error =
@@ -147,6 +174,9 @@ impl FromRequestParts<Arc<AppState>> for Session {
 				)
 			})?;
 
+		// Restrict the rest of the request's queries to the navigator's rows.
+		row_level_security::identify(*navigator.nutty_id());
+
 		Ok(Session { session, navigator })
 	}
 }
@@ -166,11 +196,32 @@ mod tests {
 	use super::*;
 	use crate::access::repository::AccessRepository;
 	use crate::access::service::AccessService;
+	use crate::content::events::EventStreams;
+	use crate::content::og_image::OgImages;
 	use crate::content::repository::ContentRepository;
+	use crate::content::sanitizer::Sanitizer;
 	use crate::content::service::ContentService;
+	use crate::integrations::chatbot::repository::ChatbotRepository;
+	use crate::integrations::chatbot::service::ChatbotService;
+	use crate::integrations::chatbot::telegram::Telegram;
+	use crate::integrations::webhooks::delivery::WebhookSender;
+	use crate::integrations::webhooks::repository::WebhookRepository;
+	use crate::integrations::webhooks::service::WebhookService;
+	use crate::moderation::repository::ModerationRepository;
+	use crate::moderation::service::ModerationService;
 	use crate::navigator::repository::NavigatorRepository;
 	use crate::navigator::service::NavigatorService;
+	use crate::system::repository::SystemRepository;
+	use crate::system::service::SystemService;
+	use crate::utilities::api::export_link::ExportLinks;
+	use crate::utilities::api::feed_token::FeedTokens;
+	use crate::utilities::api::geo_ip::GeoIp;
+	use crate::utilities::api::link_checker::LinkChecker;
+	use crate::utilities::api::og_image_link::OgImageLinks;
+	use crate::utilities::api::page_fetcher::PageFetcher;
+	use crate::utilities::api::read_only::ReadOnlyMode;
 	use crate::utilities::api::state::AppState;
+	use crate::utilities::api::webhook::WebhookSecret;
 
 	async fn connect_to_test_database() -> Pool<Postgres> {
 		let database_url = std::env::var("DATABASE_URL").unwrap();
@@ -192,11 +243,34 @@ mod tests {
 		let access_service = AccessService::new(access_repository);
 		let navigator_service = NavigatorService::new(navigator_repository.clone());
 		let content_service = ContentService::new(content_repository.clone(), access_service.clone());
+		let moderation_service = ModerationService::new(
+			ModerationRepository::new(pool.clone()),
+			content_repository.clone(),
+		);
+		let chatbot_service = ChatbotService::new(ChatbotRepository::new(pool.clone()));
 
 		let state = Arc::new(AppState {
-			navigator_service,
-			content_service,
-			access_service,
+			navigator_service: Arc::new(navigator_service),
+			content_service: Arc::new(content_service),
+			access_service: Arc::new(access_service),
+			moderation_service: Arc::new(moderation_service),
+			chatbot_service: Arc::new(chatbot_service),
+			system_service: Arc::new(SystemService::new(SystemRepository::new(pool.clone()))),
+			webhook_service: Arc::new(WebhookService::new(WebhookRepository::new(pool.clone()))),
+			read_only: ReadOnlyMode::new(false, 0),
+			geo_ip: GeoIp::default(),
+			sanitizer: Sanitizer::default(),
+			block_kinds: Arc::default(),
+			email_ingest_secret: WebhookSecret::default(),
+			page_fetcher: PageFetcher::default(),
+			link_checker: LinkChecker::default(),
+			telegram: Telegram::default(),
+			webhook_sender: WebhookSender::default(),
+			feed_tokens: FeedTokens::default(),
+			export_links: ExportLinks::default(),
+			og_images: OgImages::default(),
+			og_image_links: OgImageLinks::default(),
+			event_streams: EventStreams::default(),
 		});
 
 		// Create a test navigator.
@@ -222,7 +296,7 @@ mod tests {
 
 		// Create request parts with the session cookie.
 		let mut headers = HeaderMap::new();
-		let cookie = format!("session_id={}", session.nutty_id());
+		let cookie = format!("session_id={}", session.token().unwrap().as_str());
 		headers.insert("cookie", HeaderValue::from_str(&cookie).unwrap());
 		headers.insert("user-agent", HeaderValue::from_str("test-agent").unwrap());
 
@@ -263,11 +337,34 @@ mod tests {
 		let access_service = AccessService::new(access_repository);
 		let navigator_service = NavigatorService::new(navigator_repository.clone());
 		let content_service = ContentService::new(content_repository.clone(), access_service.clone());
+		let moderation_service = ModerationService::new(
+			ModerationRepository::new(pool.clone()),
+			content_repository.clone(),
+		);
+		let chatbot_service = ChatbotService::new(ChatbotRepository::new(pool.clone()));
 
 		let state = Arc::new(AppState {
-			navigator_service,
-			content_service,
-			access_service,
+			navigator_service: Arc::new(navigator_service),
+			content_service: Arc::new(content_service),
+			access_service: Arc::new(access_service),
+			moderation_service: Arc::new(moderation_service),
+			chatbot_service: Arc::new(chatbot_service),
+			system_service: Arc::new(SystemService::new(SystemRepository::new(pool.clone()))),
+			webhook_service: Arc::new(WebhookService::new(WebhookRepository::new(pool.clone()))),
+			read_only: ReadOnlyMode::new(false, 0),
+			geo_ip: GeoIp::default(),
+			sanitizer: Sanitizer::default(),
+			block_kinds: Arc::default(),
+			email_ingest_secret: WebhookSecret::default(),
+			page_fetcher: PageFetcher::default(),
+			link_checker: LinkChecker::default(),
+			telegram: Telegram::default(),
+			webhook_sender: WebhookSender::default(),
+			feed_tokens: FeedTokens::default(),
+			export_links: ExportLinks::default(),
+			og_images: OgImages::default(),
+			og_image_links: OgImageLinks::default(),
+			event_streams: EventStreams::default(),
 		});
 
 		// Create a test navigator.
@@ -295,7 +392,7 @@ mod tests {
 		{
 			// Create request with matching User-Agent.
 			let mut headers = HeaderMap::new();
-			let cookie = format!("session_id={}", session.nutty_id());
+			let cookie = format!("session_id={}", session.token().unwrap().as_str());
 			headers.insert("cookie", HeaderValue::from_str(&cookie).unwrap());
 			headers.insert(
 				"user-agent",
@@ -328,7 +425,7 @@ mod tests {
 		{
 			// Create request with different User-Agent.
 			let mut headers = HeaderMap::new();
-			let cookie = format!("session_id={}", session.nutty_id());
+			let cookie = format!("session_id={}", session.token().unwrap().as_str());
 			headers.insert("cookie", HeaderValue::from_str(&cookie).unwrap());
 			headers.insert(
 				"user-agent",
@@ -1,10 +1,48 @@
-use crate::access::service::AccessService;
-use crate::content::service::ContentService;
-use crate::navigator::service::NavigatorService;
+use std::sync::Arc;
+
+use crate::access::service::AccessServiceApi;
+use crate::content::block_kind::BlockKindRegistry;
+use crate::content::events::EventStreams;
+use crate::content::og_image::OgImages;
+use crate::content::sanitizer::Sanitizer;
+use crate::content::service::ContentServiceApi;
+use crate::integrations::chatbot::service::ChatbotServiceApi;
+use crate::integrations::chatbot::telegram::Telegram;
+use crate::integrations::webhooks::delivery::WebhookSender;
+use crate::integrations::webhooks::service::WebhookServiceApi;
+use crate::moderation::service::ModerationServiceApi;
+use crate::navigator::service::NavigatorServiceApi;
+use crate::system::service::SystemServiceApi;
+use crate::utilities::api::export_link::ExportLinks;
+use crate::utilities::api::feed_token::FeedTokens;
+use crate::utilities::api::geo_ip::GeoIp;
+use crate::utilities::api::link_checker::LinkChecker;
+use crate::utilities::api::og_image_link::OgImageLinks;
+use crate::utilities::api::page_fetcher::PageFetcher;
+use crate::utilities::api::read_only::ReadOnlyMode;
+use crate::utilities::api::webhook::WebhookSecret;
 
 #[derive(Clone)]
 pub struct AppState {
-	pub access_service: AccessService,
-	pub content_service: ContentService,
-	pub navigator_service: NavigatorService,
+	pub access_service: Arc<dyn AccessServiceApi>,
+	pub content_service: Arc<dyn ContentServiceApi>,
+	pub navigator_service: Arc<dyn NavigatorServiceApi>,
+	pub moderation_service: Arc<dyn ModerationServiceApi>,
+	pub chatbot_service: Arc<dyn ChatbotServiceApi>,
+	pub system_service: Arc<dyn SystemServiceApi>,
+	pub webhook_service: Arc<dyn WebhookServiceApi>,
+	pub read_only: ReadOnlyMode,
+	pub geo_ip: GeoIp,
+	pub sanitizer: Sanitizer,
+	pub block_kinds: Arc<BlockKindRegistry>,
+	pub email_ingest_secret: WebhookSecret,
+	pub page_fetcher: PageFetcher,
+	pub link_checker: LinkChecker,
+	pub telegram: Telegram,
+	pub webhook_sender: WebhookSender,
+	pub feed_tokens: FeedTokens,
+	pub export_links: ExportLinks,
+	pub og_images: OgImages,
+	pub og_image_links: OgImageLinks,
+	pub event_streams: EventStreams,
 }
@@ -0,0 +1,375 @@
+use std::future::Future;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use axum::Json;
+use axum::extract::Request;
+use axum::http::HeaderValue;
+use axum::http::StatusCode;
+use axum::http::header::RETRY_AFTER;
+use axum::middleware::Next;
+use axum::response::IntoResponse;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::utilities::api::response::Error;
+use crate::utilities::api::response::Response;
+
+/// Routes that keep working while the circuit is open, so that the API can
+/// report on its own health.
+const EXEMPT_PATHS: &[&str] = &["/healthz", "/readyz"];
+
+/// The process-wide circuit breaker around the database.
+static CIRCUIT_BREAKER: LazyLock<CircuitBreaker> = LazyLock::new(CircuitBreaker::default);
+
+/// Whether queries are let through to the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+	/// Queries run as usual.
+	Closed,
+
+	/// Queries fail fast until the cooldown ends.
+	Open,
+
+	/// One query is let through to probe the database; the rest fail fast
+	/// until it succeeds, or until the next cooldown ends.
+	HalfOpen,
+}
+
+/// A circuit breaker that stops waiting on the database after consecutive
+/// connection failures, so that requests fail fast instead of each waiting
+/// for the pool to time out.
+pub struct CircuitBreaker {
+	/// How the breaker is configured.
+	config: Mutex<CircuitBreakerConfig>,
+
+	/// Where the breaker is.
+	state: Mutex<CircuitBreakerState>,
+}
+
+/// How many failures trip a breaker, and how long it stays open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+	/// The number of consecutive connection failures that open the circuit.
+	pub failure_threshold: u32,
+
+	/// How long the circuit stays open before a query probes the database.
+	pub cooldown: Duration,
+}
+
+impl CircuitBreakerConfig {
+	/// The number of consecutive connection failures that open the circuit,
+	/// unless configured otherwise.
+	pub const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+
+	/// How long the circuit stays open, unless configured otherwise.
+	pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(10);
+}
+
+impl Default for CircuitBreakerConfig {
+	fn default() -> Self {
+		Self {
+			failure_threshold: Self::DEFAULT_FAILURE_THRESHOLD,
+			cooldown: Self::DEFAULT_COOLDOWN,
+		}
+	}
+}
+
+struct CircuitBreakerState {
+	state: CircuitState,
+	consecutive_failures: u32,
+
+	/// When the next probe is let through, unless the circuit is closed.
+	retry_at: Option<Instant>,
+
+	/// How many times the circuit has opened.
+	trip_count: u64,
+}
+
+/// A snapshot of a circuit breaker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitBreakerSummary {
+	pub state: CircuitState,
+	pub consecutive_failures: u32,
+	pub failure_threshold: u32,
+
+	/// How many seconds are left before the next probe, unless the circuit
+	/// is closed.
+	pub retry_after_secs: Option<u64>,
+
+	pub trip_count: u64,
+}
+
+impl CircuitBreaker {
+	/// Get the process-wide circuit breaker.
+	pub fn global() -> &'static CircuitBreaker {
+		&CIRCUIT_BREAKER
+	}
+
+	/// Set how many failures trip the breaker, and how long it stays open.
+	pub fn configure(&self, config: CircuitBreakerConfig) {
+		*self.config.lock().expect("Circuit breaker lock poisoned") = config;
+	}
+
+	/// Check if a query may run. While the circuit is open, the first query
+	/// after the cooldown is let through as a probe.
+	pub fn allow(&self) -> bool {
+		self.allow_at(Instant::now())
+	}
+
+	fn allow_at(&self, now: Instant) -> bool {
+		let cooldown = self.config().cooldown;
+		let mut state = self.lock();
+
+		match state.retry_at {
+			None => true,
+			Some(retry_at) if now < retry_at => false,
+
+			Some(_) => {
+				state.state = CircuitState::HalfOpen;
+				state.retry_at = Some(now + cooldown);
+				true
+			}
+		}
+	}
+
+	/// Record the outcome of a query. Only failures to reach the database
+	/// count towards opening the circuit; anything else closes it.
+	pub fn record(&self, error: Option<&sqlx::Error>) {
+		match error {
+			Some(error) if is_connection_failure(error) => self.record_failure_at(Instant::now()),
+			_ => self.record_success(),
+		}
+	}
+
+	fn record_success(&self) {
+		let mut state = self.lock();
+
+		state.state = CircuitState::Closed;
+		state.consecutive_failures = 0;
+		state.retry_at = None;
+	}
+
+	fn record_failure_at(&self, now: Instant) {
+		let config = self.config();
+		let mut state = self.lock();
+
+		state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+
+		let trips = match state.state {
+			CircuitState::Closed => state.consecutive_failures >= config.failure_threshold,
+			CircuitState::HalfOpen => true,
+			CircuitState::Open => false,
+		};
+
+		if trips {
+			if state.state == CircuitState::Closed {
+				state.trip_count += 1;
+				eprintln!(
+					"Opened the database circuit after {} consecutive connection failures",
+					state.consecutive_failures
+				);
+			}
+
+			state.state = CircuitState::Open;
+			state.retry_at = Some(now + config.cooldown);
+		}
+	}
+
+	/// Get how long requests should wait before retrying, unless the
+	/// circuit is closed.
+	pub fn retry_after(&self) -> Option<Duration> {
+		self
+			.lock()
+			.retry_at
+			.map(|retry_at| retry_at.saturating_duration_since(Instant::now()))
+	}
+
+	/// Check if the circuit is open and still cooling down, in which case
+	/// requests are turned away before they reach any query.
+	pub fn is_cooling_down(&self) -> bool {
+		let state = self.lock();
+
+		state.state == CircuitState::Open
+			&& state
+				.retry_at
+				.is_some_and(|retry_at| Instant::now() < retry_at)
+	}
+
+	/// Take a snapshot of the breaker.
+	pub fn summary(&self) -> CircuitBreakerSummary {
+		let config = self.config();
+		let state = self.lock();
+
+		CircuitBreakerSummary {
+			state: state.state,
+			consecutive_failures: state.consecutive_failures,
+			failure_threshold: config.failure_threshold,
+			retry_after_secs: state
+				.retry_at
+				.map(|retry_at| retry_after_secs(retry_at.saturating_duration_since(Instant::now()))),
+			trip_count: state.trip_count,
+		}
+	}
+
+	fn config(&self) -> CircuitBreakerConfig {
+		*self.config.lock().expect("Circuit breaker lock poisoned")
+	}
+
+	fn lock(&self) -> std::sync::MutexGuard<'_, CircuitBreakerState> {
+		self.state.lock().expect("Circuit breaker lock poisoned")
+	}
+}
+
+impl Default for CircuitBreaker {
+	fn default() -> Self {
+		Self {
+			config: Mutex::new(CircuitBreakerConfig::default()),
+			state: Mutex::new(CircuitBreakerState {
+				state: CircuitState::Closed,
+				consecutive_failures: 0,
+				retry_at: None,
+				trip_count: 0,
+			}),
+		}
+	}
+}
+
+/// Check if an error means the database couldn't be reached, as opposed to
+/// a query failing on its own.
+pub fn is_connection_failure(error: &sqlx::Error) -> bool {
+	matches!(
+		error,
+		sqlx::Error::PoolTimedOut
+			| sqlx::Error::PoolClosed
+			| sqlx::Error::Io(_)
+			| sqlx::Error::Tls(_)
+	)
+}
+
+/// Round a wait up to whole seconds, waiting at least one.
+fn retry_after_secs(wait: Duration) -> u64 {
+	(wait.as_secs() + u64::from(wait.subsec_nanos() > 0)).max(1)
+}
+
+pub trait GuardConnection<T>: Future<Output = Result<T, sqlx::Error>> + Sized {
+	/// Run this database call through the global [CircuitBreaker]. While the
+	/// circuit is open, it fails fast as if the pool had timed out.
+	fn guard_connection(self) -> impl Future<Output = Self::Output> {
+		async move {
+			let breaker = CircuitBreaker::global();
+
+			if !breaker.allow() {
+				return Err(sqlx::Error::PoolTimedOut);
+			}
+
+			let output = self.await;
+			breaker.record(output.as_ref().err());
+			output
+		}
+	}
+}
+
+impl<F, T> GuardConnection<T> for F where F: Future<Output = Result<T, sqlx::Error>> {}
+
+/// Middleware that turns requests away with a 503 while the database circuit
+/// is open, and turns the failures of requests caught by it into 503s too.
+pub async fn circuit_breaker_middleware(request: Request, next: Next) -> axum::response::Response {
+	let breaker = CircuitBreaker::global();
+
+	if EXEMPT_PATHS.contains(&request.uri().path()) {
+		return next.run(request).await;
+	}
+
+	if !breaker.is_cooling_down() {
+		let response = next.run(request).await;
+
+		if response.status() != StatusCode::INTERNAL_SERVER_ERROR {
+			return response;
+		}
+
+		if breaker.retry_after().is_none() {
+			return response;
+		}
+	}
+
+	let retry_after = retry_after_secs(breaker.retry_after().unwrap_or_default());
+
+	let summary = "The database is unavailable. Please try again later.";
+	let error = Error::from_error(&CircuitBreakerError::Open).with_summary(summary);
+
+	(
+		StatusCode::SERVICE_UNAVAILABLE,
+		[(RETRY_AFTER, HeaderValue::from(retry_after))],
+		Json(Response::<()>::Error {
+			errors: vec![error],
+		}),
+	)
+		.into_response()
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CircuitBreakerError {
+	#[error("The database circuit is open")]
+	Open,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_circuit_breaker() {
+		// Arrange: Create a breaker that trips after two failures.
+		let breaker = CircuitBreaker::default();
+		let cooldown = Duration::from_secs(10);
+
+		breaker.configure(CircuitBreakerConfig {
+			failure_threshold: 2,
+			cooldown,
+		});
+
+		let start = Instant::now();
+		let state = || breaker.summary().state;
+
+		// Assert: Query errors don't count, and successes reset the count.
+		breaker.record(Some(&sqlx::Error::RowNotFound));
+		breaker.record_failure_at(start);
+		breaker.record(None);
+		breaker.record_failure_at(start);
+		assert_eq!(state(), CircuitState::Closed);
+
+		// Act: Fail again, in a row.
+		breaker.record_failure_at(start);
+
+		// Assert: The circuit opened, and queries fail fast until the cooldown ends.
+		assert_eq!(state(), CircuitState::Open);
+		assert_eq!(breaker.summary().trip_count, 1);
+		assert!(!breaker.allow_at(start + cooldown / 2));
+
+		// Assert: One probe is let through after the cooldown.
+		assert!(breaker.allow_at(start + cooldown));
+		assert_eq!(state(), CircuitState::HalfOpen);
+		assert!(!breaker.allow_at(start + cooldown));
+
+		// Act: Fail the probe.
+		breaker.record_failure_at(start + cooldown);
+
+		// Assert: The circuit opened again, without counting another trip.
+		assert_eq!(state(), CircuitState::Open);
+		assert_eq!(breaker.summary().trip_count, 1);
+		assert!(!breaker.allow_at(start + cooldown));
+
+		// Act: Succeed with the next probe.
+		assert!(breaker.allow_at(start + cooldown * 2));
+		breaker.record(None);
+
+		// Assert: The circuit closed.
+		assert_eq!(state(), CircuitState::Closed);
+		assert!(breaker.allow());
+		assert_eq!(breaker.retry_after(), None);
+	}
+}
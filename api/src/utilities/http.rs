@@ -0,0 +1,584 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use reqwest::Client;
+use reqwest::RequestBuilder;
+use reqwest::Response;
+use reqwest::StatusCode;
+use reqwest::dns::Addrs;
+use reqwest::dns::Name;
+use reqwest::dns::Resolve;
+use reqwest::dns::Resolving;
+use reqwest::header::HeaderMap;
+use reqwest::header::HeaderName;
+use reqwest::redirect::Policy;
+use thiserror::Error;
+use url::Host;
+use url::Url;
+
+/// What outbound requests to user-controlled URLs may do.
+#[derive(Debug, Clone)]
+pub struct HttpPolicy {
+	/// The URL schemes that may be requested.
+	pub allowed_schemes: Vec<String>,
+
+	/// The ports that may be requested, after defaulting by scheme.
+	pub allowed_ports: Vec<u16>,
+
+	/// How long a request may take, reading the body included.
+	pub timeout: Duration,
+
+	/// The largest response body read, in bytes.
+	pub max_response_size: usize,
+
+	/// The most redirects followed for a request.
+	pub max_redirects: usize,
+
+	/// How many failures in a row open a destination's circuit.
+	pub failure_threshold: u32,
+
+	/// How long an open circuit refuses requests before letting one through.
+	pub open_duration: Duration,
+
+	/// The most destinations whose failures are tracked at once. Past it,
+	/// the least recently failed are forgotten.
+	pub max_breakers: usize,
+}
+
+impl Default for HttpPolicy {
+	fn default() -> Self {
+		Self {
+			allowed_schemes: vec!["http".to_string(), "https".to_string()],
+			allowed_ports: vec![80, 443, 8080, 8443],
+			timeout: Duration::from_secs(10),
+			max_response_size: 2 * 1024 * 1024,
+			max_redirects: 5,
+			failure_threshold: 5,
+			open_duration: Duration::from_secs(60),
+			max_breakers: 10_000,
+		}
+	}
+}
+
+/// An HTTP client for fetching user-controlled URLs. Requests are refused
+/// unless they're to an allowed scheme and port on a public address, which
+/// is checked again for every redirect and every name resolved, so that a
+/// name can't be pointed at an internal service after it was checked.
+/// Destinations that keep failing are left alone for a while.
+#[derive(Clone)]
+pub struct HttpClient {
+	client: Client,
+	policy: Arc<HttpPolicy>,
+
+	/// The circuit breakers of destinations that have recently failed.
+	breakers: Arc<Mutex<HashMap<String, Breaker>>>,
+}
+
+/// A destination's recent failures.
+#[derive(Debug)]
+struct Breaker {
+	failures: u32,
+	open_until: Option<Instant>,
+	last_failure: Instant,
+}
+
+impl HttpClient {
+	/// Create a client that enforces a policy.
+	pub fn new(policy: HttpPolicy) -> Self {
+		let policy = Arc::new(policy);
+		let redirect_policy = Arc::clone(&policy);
+
+		let client = Client::builder()
+			.timeout(policy.timeout)
+			.redirect(Policy::custom(move |attempt| {
+				if attempt.previous().len() >= redirect_policy.max_redirects {
+					attempt.error("Too many redirects")
+				} else if let Err(error) = check_url(&redirect_policy, attempt.url()) {
+					attempt.error(error)
+				} else {
+					attempt.follow()
+				}
+			}))
+			.dns_resolver(Arc::new(PublicResolver))
+			.no_proxy()
+			.user_agent(concat!("nuttyverse/", env!("CARGO_PKG_VERSION")))
+			.build()
+			.expect("Failed to build HTTP client");
+
+		Self {
+			client,
+			policy,
+			breakers: Arc::default(),
+		}
+	}
+
+	/// Check that a URL may be requested. Returns its host, which names
+	/// the destination for rate limits and circuit breakers.
+	pub fn check_url(&self, url: &Url) -> Result<String, HttpError> {
+		check_url(&self.policy, url)
+	}
+
+	/// Send a GET request.
+	pub async fn get(&self, url: &Url) -> Result<HttpResponse, HttpError> {
+		self.send(url, self.client.get(url.clone())).await
+	}
+
+	/// Send a HEAD request.
+	pub async fn head(&self, url: &Url) -> Result<HttpResponse, HttpError> {
+		self.send(url, self.client.head(url.clone())).await
+	}
+
+	/// Send a POST request with a body.
+	pub async fn post(
+		&self,
+		url: &Url,
+		headers: HeaderMap,
+		body: Vec<u8>,
+	) -> Result<HttpResponse, HttpError> {
+		let request = self.client.post(url.clone()).headers(headers).body(body);
+		self.send(url, request).await
+	}
+
+	/// Send a request to a URL, through its destination's circuit breaker.
+	async fn send(&self, url: &Url, request: RequestBuilder) -> Result<HttpResponse, HttpError> {
+		let host = self.check_url(url)?;
+		self.admit(&host)?;
+
+		match request.send().await {
+			Ok(response) => {
+				self.record(&host, !response.status().is_server_error());
+
+				Ok(HttpResponse {
+					response,
+					max_size: self.policy.max_response_size,
+				})
+			}
+
+			Err(error) => {
+				let error = HttpError::from_request(error);
+
+				if let HttpError::Request(error) = &error {
+					self.record(&host, !(error.is_connect() || error.is_timeout()));
+				}
+
+				Err(error)
+			}
+		}
+	}
+
+	/// Refuse a request to a destination whose circuit is open.
+	fn admit(&self, host: &str) -> Result<(), HttpError> {
+		let breakers = self.breakers.lock().expect("Circuit breakers poisoned");
+
+		let is_open = breakers
+			.get(host)
+			.and_then(|breaker| breaker.open_until)
+			.is_some_and(|open_until| Instant::now() < open_until);
+
+		match is_open {
+			true => Err(HttpError::CircuitOpen(host.to_string())),
+			false => Ok(()),
+		}
+	}
+
+	/// Record how a request to a destination went. A failure after the
+	/// circuit was opened, and let a request through, opens it again.
+	fn record(&self, host: &str, succeeded: bool) {
+		let mut breakers = self.breakers.lock().expect("Circuit breakers poisoned");
+
+		if succeeded {
+			breakers.remove(host);
+			return;
+		}
+
+		let now = Instant::now();
+
+		if !breakers.contains_key(host) && breakers.len() >= self.policy.max_breakers {
+			self.evict(&mut breakers, now);
+		}
+
+		let breaker = breakers.entry(host.to_string()).or_insert(Breaker {
+			failures: 0,
+			open_until: None,
+			last_failure: now,
+		});
+
+		breaker.failures += 1;
+		breaker.last_failure = now;
+
+		if breaker.failures >= self.policy.failure_threshold {
+			breaker.open_until = Some(now + self.policy.open_duration);
+		}
+	}
+
+	/// Make room for another circuit breaker, first forgetting destinations
+	/// that haven't failed for a while and aren't open, then the least
+	/// recently failed.
+	fn evict(&self, breakers: &mut HashMap<String, Breaker>, now: Instant) {
+		breakers.retain(|_, breaker| {
+			breaker
+				.open_until
+				.is_some_and(|open_until| now < open_until)
+				|| now.duration_since(breaker.last_failure) < self.policy.open_duration
+		});
+
+		while breakers.len() >= self.policy.max_breakers.max(1) {
+			let oldest = breakers
+				.iter()
+				.min_by_key(|(_, breaker)| breaker.last_failure)
+				.map(|(host, _)| host.clone());
+
+			match oldest {
+				Some(host) => breakers.remove(&host),
+				None => break,
+			};
+		}
+	}
+}
+
+/// A response whose body is read up to the policy's size limit.
+#[derive(Debug)]
+pub struct HttpResponse {
+	response: Response,
+	max_size: usize,
+}
+
+impl HttpResponse {
+	/// Get the response's status.
+	pub fn status(&self) -> StatusCode {
+		self.response.status()
+	}
+
+	/// Get a header's value, if it's set and readable.
+	pub fn header(&self, name: HeaderName) -> Option<&str> {
+		self
+			.response
+			.headers()
+			.get(name)
+			.and_then(|value| value.to_str().ok())
+	}
+
+	/// Fail unless the response's status is a success.
+	pub fn error_for_status(self) -> Result<Self, HttpError> {
+		let max_size = self.max_size;
+
+		self
+			.response
+			.error_for_status()
+			.map(|response| Self { response, max_size })
+			.map_err(HttpError::Request)
+	}
+
+	/// Read the body, failing if it's larger than the limit.
+	pub async fn body(self) -> Result<Vec<u8>, HttpError> {
+		let max_size = self.max_size;
+		let (body, truncated) = self.read(max_size).await?;
+
+		match truncated {
+			true => Err(HttpError::ResponseTooLarge(max_size)),
+			false => Ok(body),
+		}
+	}
+
+	/// Read the body up to the limit, ignoring anything past it.
+	pub async fn body_prefix(self) -> Result<Vec<u8>, HttpError> {
+		let max_size = self.max_size;
+		self.read(max_size).await.map(|(body, _)| body)
+	}
+
+	/// Read up to `max_size` bytes, and whether there were more.
+	async fn read(mut self, max_size: usize) -> Result<(Vec<u8>, bool), HttpError> {
+		let mut body = Vec::new();
+
+		while let Some(chunk) = self.response.chunk().await.map_err(HttpError::Request)? {
+			let remaining = max_size - body.len();
+			body.extend_from_slice(&chunk[..chunk.len().min(remaining)]);
+
+			if chunk.len() > remaining {
+				return Ok((body, true));
+			}
+		}
+
+		Ok((body, false))
+	}
+}
+
+/// Check a URL's scheme, host, and port against a policy. Hosts that are
+/// obviously non-public addresses are refused here; names that resolve to
+/// one are caught by [PublicResolver] when connecting.
+fn check_url(policy: &HttpPolicy, url: &Url) -> Result<String, HttpError> {
+	if !policy.allowed_schemes.iter().any(|s| s == url.scheme()) {
+		return Err(HttpError::DisallowedScheme(url.scheme().to_string()));
+	}
+
+	let host = public_host(url)?;
+
+	match url.port_or_known_default() {
+		Some(port) if policy.allowed_ports.contains(&port) => Ok(host),
+		port => Err(HttpError::DisallowedPort(port.unwrap_or_default())),
+	}
+}
+
+/// Get a URL's host, unless it obviously points at a non-public address.
+fn public_host(url: &Url) -> Result<String, HttpError> {
+	let address = match url.host() {
+		Some(Host::Domain(domain)) => {
+			let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+
+			if domain == "localhost" || domain.ends_with(".localhost") {
+				return Err(HttpError::NonPublicHost(domain));
+			}
+
+			return Ok(domain);
+		}
+
+		Some(Host::Ipv4(address)) => IpAddr::V4(address),
+		Some(Host::Ipv6(address)) => IpAddr::V6(address),
+		None => return Err(HttpError::NonPublicHost(url.to_string())),
+	};
+
+	match is_public(address) {
+		true => Ok(address.to_string()),
+		false => Err(HttpError::NonPublicHost(address.to_string())),
+	}
+}
+
+/// Check whether an address is reachable from the public internet, rather
+/// than loopback, private, link-local, or otherwise special-purpose.
+fn is_public(address: IpAddr) -> bool {
+	match address {
+		IpAddr::V4(v4) => {
+			let [a, b, c, _] = v4.octets();
+
+			!(v4.is_loopback()
+				|| v4.is_private()
+				|| v4.is_link_local()
+				|| v4.is_broadcast()
+				|| v4.is_multicast()
+				// "This network", 0.0.0.0/8, including the unspecified address.
+				|| a == 0
+				// Shared address space for carrier-grade NAT, 100.64.0.0/10.
+				|| (a == 100 && (b & 0xc0) == 64)
+				// Benchmarking, 198.18.0.0/15.
+				|| (a == 198 && (b & 0xfe) == 18)
+				// IETF protocol assignments, 192.0.0.0/24.
+				|| (a == 192 && b == 0 && c == 0)
+				// Reserved, 240.0.0.0/4.
+				|| a >= 240)
+		}
+
+		IpAddr::V6(v6) => match v6.to_ipv4_mapped().or_else(|| to_6to4_ipv4(&v6)) {
+			Some(v4) => is_public(IpAddr::V4(v4)),
+			None => {
+				let segments = v6.segments();
+
+				!(v6.is_loopback()
+					|| v6.is_unspecified()
+					|| v6.is_unique_local()
+					|| v6.is_unicast_link_local()
+					|| v6.is_multicast()
+					// IPv4-compatible addresses, ::a.b.c.d, which are deprecated.
+					|| segments[..6].iter().all(|segment| *segment == 0)
+					// NAT64, 64:ff9b::/96, which translates to any IPv4 address.
+					|| segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0])
+			}
+		},
+	}
+}
+
+/// Get the IPv4 address embedded in a 6to4 address, 2002:a.b.c.d::/48,
+/// which is routed to that IPv4 address.
+fn to_6to4_ipv4(address: &Ipv6Addr) -> Option<Ipv4Addr> {
+	match address.octets() {
+		[0x20, 0x02, a, b, c, d, ..] => Some(Ipv4Addr::new(a, b, c, d)),
+		_ => None,
+	}
+}
+
+/// Resolves names with the system resolver, refusing any name with a
+/// non-public address among its addresses.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+	fn resolve(&self, name: Name) -> Resolving {
+		let name = name.as_str().to_string();
+
+		Box::pin(async move {
+			let addresses: Vec<SocketAddr> =
+				tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+
+			if addresses.iter().any(|address| !is_public(address.ip())) {
+				return Err(HttpError::NonPublicHost(name).into());
+			}
+
+			Ok(Box::new(addresses.into_iter()) as Addrs)
+		})
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum HttpError {
+	#[error("Refusing to request a {0} URL.")]
+	DisallowedScheme(String),
+
+	#[error("Refusing to request port {0}.")]
+	DisallowedPort(u16),
+
+	#[error("Refusing to request a non-public host: {0}")]
+	NonPublicHost(String),
+
+	#[error("Too many failed requests to {0}; try again later.")]
+	CircuitOpen(String),
+
+	#[error("The response was larger than {0} bytes.")]
+	ResponseTooLarge(usize),
+
+	#[error("Request failed: {0}")]
+	Request(reqwest::Error),
+}
+
+impl HttpError {
+	/// Unwrap a request's failure, if the policy caused it.
+	fn from_request(error: reqwest::Error) -> Self {
+		let mut source = std::error::Error::source(&error);
+
+		while let Some(cause) = source {
+			if let Some(HttpError::NonPublicHost(host)) = cause.downcast_ref() {
+				return Self::NonPublicHost(host.clone());
+			}
+
+			source = cause.source();
+		}
+
+		Self::Request(error)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_check_url() {
+		let policy = HttpPolicy::default();
+		let check = |url: &str| check_url(&policy, &Url::parse(url).unwrap());
+
+		// Assert: Public hosts on allowed schemes and ports pass.
+		assert_eq!(check("https://Example.com./feed").unwrap(), "example.com");
+		assert!(check("http://93.184.215.14:8080/").is_ok());
+		assert!(check("http://[2002:5db8:d70e::1]/").is_ok());
+
+		// Assert: Anything else doesn't.
+		assert!(matches!(
+			check("ftp://example.com/"),
+			Err(HttpError::DisallowedScheme(_))
+		));
+		assert!(matches!(
+			check("http://example.com:22/"),
+			Err(HttpError::DisallowedPort(22))
+		));
+
+		for url in [
+			"http://localhost/",
+			"http://api.localhost/",
+			"http://127.0.0.1/",
+			"http://10.0.0.8/",
+			"http://169.254.169.254/latest/meta-data/",
+			"http://[::1]/",
+			"http://[::ffff:127.0.0.1]/",
+			"http://[fd00::1]/",
+			"http://0.1.2.3/",
+			"http://100.64.0.1/",
+			"http://100.127.255.254/",
+			"http://198.18.0.1/",
+			"http://198.19.255.254/",
+			"http://224.0.0.1/",
+			"http://[ff02::1]/",
+			"http://[64:ff9b::a9fe:a9fe]/",
+			"http://[::a9fe:a9fe]/",
+			"http://192.0.0.8/",
+			"http://240.0.0.1/",
+			"http://255.255.255.254/",
+			"http://[2002:7f00:1::]/",
+			"http://[2002:a9fe:a9fe::1]/",
+		] {
+			assert!(
+				matches!(check(url), Err(HttpError::NonPublicHost(_))),
+				"{url:?}"
+			);
+		}
+	}
+
+	#[tokio::test]
+	async fn test_public_resolver() {
+		// Assert: Names that resolve to a non-public address are refused.
+		let name = "localhost".parse().unwrap();
+
+		match PublicResolver.resolve(name).await {
+			Ok(_) => panic!("Resolved localhost"),
+			Err(error) => assert!(matches!(
+				error.downcast_ref(),
+				Some(HttpError::NonPublicHost(_))
+			)),
+		}
+	}
+
+	#[test]
+	fn test_circuit_breaker() {
+		let client = HttpClient::new(HttpPolicy {
+			failure_threshold: 2,
+			..Default::default()
+		});
+
+		// Assert: A destination's circuit opens after enough failures.
+		client.record("example.com", false);
+		assert!(client.admit("example.com").is_ok());
+
+		client.record("example.com", false);
+		assert!(matches!(
+			client.admit("example.com"),
+			Err(HttpError::CircuitOpen(_))
+		));
+		assert!(client.admit("example.org").is_ok());
+
+		// Assert: A success closes it again.
+		client.record("example.com", true);
+		assert!(client.admit("example.com").is_ok());
+
+		// Assert: An open circuit lets a request through once it's waited.
+		let client = HttpClient::new(HttpPolicy {
+			failure_threshold: 1,
+			open_duration: Duration::ZERO,
+			..Default::default()
+		});
+
+		client.record("example.com", false);
+		assert!(client.admit("example.com").is_ok());
+
+		// Assert: Only so many destinations are tracked, forgetting the least
+		// recently failed.
+		let client = HttpClient::new(HttpPolicy {
+			failure_threshold: 1,
+			max_breakers: 2,
+			..Default::default()
+		});
+
+		for host in ["example.com", "example.org", "example.net"] {
+			client.record(host, false);
+		}
+
+		assert_eq!(client.breakers.lock().unwrap().len(), 2);
+		assert!(client.admit("example.com").is_ok());
+		assert!(matches!(
+			client.admit("example.net"),
+			Err(HttpError::CircuitOpen(_))
+		));
+	}
+}
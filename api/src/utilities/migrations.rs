@@ -0,0 +1,167 @@
+use std::path::Path;
+
+use sqlx::PgPool;
+use thiserror::Error;
+
+/// A dbmate migration, e.g. `20250416034042_add_content_blocks_table.sql`.
+/// Only its `migrate:up` section is applied here; rolling back is left to
+/// dbmate itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+	/// The timestamp that orders the migration, and that dbmate records.
+	pub version: String,
+
+	/// The name of the migration, after its version.
+	pub name: String,
+
+	/// The SQL that applies the migration.
+	pub up: String,
+}
+
+impl Migration {
+	/// Parse a migration from its file name and contents.
+	pub fn parse(file_name: &str, contents: &str) -> Result<Self, MigrationError> {
+		let invalid = || MigrationError::InvalidFileName(file_name.to_string());
+
+		let (version, name) = file_name
+			.strip_suffix(".sql")
+			.and_then(|stem| stem.split_once('_'))
+			.ok_or_else(invalid)?;
+
+		if version.is_empty() || !version.bytes().all(|b| b.is_ascii_digit()) {
+			return Err(invalid());
+		}
+
+		let up = contents
+			.split_once("-- migrate:up")
+			.map(|(_, rest)| rest)
+			.ok_or_else(|| MigrationError::MissingUp(file_name.to_string()))?;
+
+		let up = up.split("-- migrate:down").next().unwrap_or_default();
+
+		Ok(Self {
+			version: version.to_string(),
+			name: name.to_string(),
+			up: up.trim().to_string(),
+		})
+	}
+}
+
+/// Read the migrations in a directory, in order.
+pub fn read_migrations(directory: &Path) -> Result<Vec<Migration>, MigrationError> {
+	let mut migrations = vec![];
+
+	for entry in std::fs::read_dir(directory)? {
+		let path = entry?.path();
+
+		let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+			continue;
+		};
+
+		if !file_name.ends_with(".sql") {
+			continue;
+		}
+
+		let contents = std::fs::read_to_string(&path)?;
+		migrations.push(Migration::parse(file_name, &contents)?);
+	}
+
+	migrations.sort_by(|a, b| a.version.cmp(&b.version));
+	Ok(migrations)
+}
+
+/// Apply the migrations that haven't been yet, each in a transaction, and
+/// record them where dbmate does. Returns the applied migrations.
+pub async fn migrate(
+	pool: &PgPool,
+	migrations: Vec<Migration>,
+) -> Result<Vec<Migration>, MigrationError> {
+	sqlx::query(
+		"CREATE TABLE IF NOT EXISTS public.schema_migrations (version VARCHAR(128) PRIMARY KEY)",
+	)
+	.execute(pool)
+	.await?;
+
+	let applied: Vec<String> = sqlx::query_scalar("SELECT version FROM public.schema_migrations")
+		.fetch_all(pool)
+		.await?;
+
+	let mut pending = vec![];
+
+	for migration in migrations {
+		if applied.contains(&migration.version) {
+			continue;
+		}
+
+		let mut tx = pool.begin().await?;
+
+		sqlx::raw_sql(&migration.up)
+			.execute(&mut *tx)
+			.await
+			.map_err(|error| MigrationError::Apply(migration.version.clone(), error))?;
+
+		sqlx::query("INSERT INTO public.schema_migrations (version) VALUES ($1)")
+			.bind(&migration.version)
+			.execute(&mut *tx)
+			.await?;
+
+		tx.commit().await?;
+		pending.push(migration);
+	}
+
+	Ok(pending)
+}
+
+#[derive(Debug, Error)]
+pub enum MigrationError {
+	#[error("Failed to read migrations: {0}")]
+	Read(#[from] std::io::Error),
+
+	#[error("Invalid migration file name: {0}")]
+	InvalidFileName(String),
+
+	#[error("Migration has no migrate:up section: {0}")]
+	MissingUp(String),
+
+	#[error("Failed to apply migration {0}: {1}")]
+	Apply(String, #[source] sqlx::Error),
+
+	#[error("Database error: {0}")]
+	Database(#[from] sqlx::Error),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse() {
+		let contents = "-- migrate:up\nCREATE TABLE t ();\n\n-- migrate:down\nDROP TABLE t;\n";
+
+		// Assert: Only the up section is kept.
+		let migration = Migration::parse("20250416034042_add_t.sql", contents).unwrap();
+		assert_eq!(migration.version, "20250416034042");
+		assert_eq!(migration.name, "add_t");
+		assert_eq!(migration.up, "CREATE TABLE t ();");
+
+		// Assert: Files that dbmate wouldn't apply are rejected.
+		assert!(matches!(
+			Migration::parse("add_t.sql", contents),
+			Err(MigrationError::InvalidFileName(_))
+		));
+		assert!(matches!(
+			Migration::parse("20250416034042_add_t.sql", "CREATE TABLE t ();"),
+			Err(MigrationError::MissingUp(_))
+		));
+	}
+
+	#[test]
+	fn test_read_migrations() {
+		let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("../db/migrations");
+		let migrations = read_migrations(&directory).unwrap();
+
+		// Assert: The repository's migrations parse, in order.
+		assert!(!migrations.is_empty());
+		assert!(migrations.is_sorted_by(|a, b| a.version < b.version));
+	}
+}
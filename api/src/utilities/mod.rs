@@ -1,2 +1,13 @@
 pub mod api;
+pub mod circuit_breaker;
+pub mod http;
+pub mod migrations;
+pub mod query_metrics;
+pub mod redis;
 pub mod repository;
+pub mod request_metrics;
+pub mod request_transaction;
+pub mod row_level_security;
+pub mod secrets;
+#[cfg(feature = "bench")]
+pub mod workload;
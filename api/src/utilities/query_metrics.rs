@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::models::content_compression::CompressionStats;
+use crate::models::date_time_rfc_3339::DateTimeRfc3339;
+use crate::utilities::circuit_breaker::CircuitBreaker;
+use crate::utilities::circuit_breaker::CircuitBreakerSummary;
+use crate::utilities::circuit_breaker::GuardConnection;
+
+/// The upper bounds of the latency histogram buckets, in milliseconds.
+/// Anything slower than the last bound lands in an overflow bucket.
+const BUCKET_BOUNDS_MS: &[f64] = &[1.0, 5.0, 10.0, 50.0, 100.0, 500.0, 1000.0, 5000.0];
+
+/// The number of slow queries to keep around.
+const SLOW_QUERY_CAPACITY: usize = 100;
+
+/// The process-wide query metrics.
+static QUERY_METRICS: LazyLock<QueryMetrics> = LazyLock::new(QueryMetrics::default);
+
+/// Per-query latency histograms, a log of slow queries, and how much content
+/// was recompressed at rest. Summaries include the state of the database's
+/// [CircuitBreaker], too.
+pub struct QueryMetrics {
+	/// The threshold above which queries are logged as slow, in milliseconds.
+	slow_threshold_ms: AtomicU64,
+
+	/// The recorded measurements.
+	state: Mutex<QueryMetricsState>,
+}
+
+#[derive(Default)]
+struct QueryMetricsState {
+	histograms: HashMap<&'static str, QueryHistogram>,
+	slow_queries: VecDeque<SlowQuery>,
+	compression: CompressionStats,
+}
+
+/// A latency histogram for a named query.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryHistogram {
+	name: &'static str,
+	count: u64,
+	total_ms: f64,
+	max_ms: f64,
+
+	/// Counts per bucket, aligned with `bucket_bounds_ms` plus an overflow bucket.
+	buckets: Vec<u64>,
+}
+
+/// A query that exceeded the slow query threshold.
+/// Bound parameters are never recorded.
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowQuery {
+	name: &'static str,
+	duration_ms: f64,
+	occurred_at: DateTimeRfc3339,
+}
+
+/// A snapshot of the query metrics.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryMetricsSummary {
+	slow_threshold_ms: u64,
+	bucket_bounds_ms: Vec<f64>,
+	queries: Vec<QueryHistogram>,
+	slow_queries: Vec<SlowQuery>,
+	compression: CompressionStats,
+	circuit_breaker: CircuitBreakerSummary,
+}
+
+impl QueryMetrics {
+	/// The default slow query threshold, in milliseconds.
+	pub const DEFAULT_SLOW_THRESHOLD_MS: u64 = 100;
+
+	/// Get the process-wide query metrics.
+	pub fn global() -> &'static QueryMetrics {
+		&QUERY_METRICS
+	}
+
+	/// Set the threshold above which queries are logged as slow.
+	pub fn set_slow_threshold(&self, threshold: Duration) {
+		let threshold = threshold.as_millis().try_into().unwrap_or(u64::MAX);
+		self.slow_threshold_ms.store(threshold, Ordering::Relaxed);
+	}
+
+	/// Record the latency of a named query.
+	pub fn record(&self, name: &'static str, elapsed: Duration) {
+		let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
+		let is_slow = elapsed_ms > self.slow_threshold_ms.load(Ordering::Relaxed) as f64;
+
+		let mut state = self.state.lock().expect("Query metrics lock poisoned");
+
+		let histogram = state
+			.histograms
+			.entry(name)
+			.or_insert_with(|| QueryHistogram {
+				name,
+				count: 0,
+				total_ms: 0.0,
+				max_ms: 0.0,
+				buckets: vec![0; BUCKET_BOUNDS_MS.len() + 1],
+			});
+
+		let bucket = BUCKET_BOUNDS_MS
+			.iter()
+			.position(|bound| elapsed_ms <= *bound)
+			.unwrap_or(BUCKET_BOUNDS_MS.len());
+
+		histogram.count += 1;
+		histogram.total_ms += elapsed_ms;
+		histogram.max_ms = histogram.max_ms.max(elapsed_ms);
+		histogram.buckets[bucket] += 1;
+
+		if is_slow {
+			eprintln!("Slow query `{name}` took {elapsed_ms:.1} ms (bound parameters redacted)");
+
+			if state.slow_queries.len() == SLOW_QUERY_CAPACITY {
+				state.slow_queries.pop_front();
+			}
+
+			state.slow_queries.push_back(SlowQuery {
+				name,
+				duration_ms: elapsed_ms,
+				occurred_at: DateTimeRfc3339::new(chrono::Utc::now().fixed_offset()),
+			});
+		}
+	}
+
+	/// Record content being recompressed.
+	pub fn record_compression(&self, stats: &CompressionStats) {
+		let mut state = self.state.lock().expect("Query metrics lock poisoned");
+		state.compression.merge(stats);
+	}
+
+	/// Take a snapshot of the query metrics, slowest queries first.
+	pub fn summary(&self) -> QueryMetricsSummary {
+		let state = self.state.lock().expect("Query metrics lock poisoned");
+
+		let mut queries: Vec<_> = state.histograms.values().cloned().collect();
+		queries.sort_by(|a, b| b.max_ms.total_cmp(&a.max_ms));
+
+		let mut slow_queries: Vec<_> = state.slow_queries.iter().cloned().collect();
+		slow_queries.sort_by(|a, b| b.duration_ms.total_cmp(&a.duration_ms));
+
+		QueryMetricsSummary {
+			slow_threshold_ms: self.slow_threshold_ms.load(Ordering::Relaxed),
+			bucket_bounds_ms: BUCKET_BOUNDS_MS.to_vec(),
+			queries,
+			slow_queries,
+			compression: state.compression,
+			circuit_breaker: CircuitBreaker::global().summary(),
+		}
+	}
+}
+
+impl Default for QueryMetrics {
+	fn default() -> Self {
+		Self {
+			slow_threshold_ms: AtomicU64::new(Self::DEFAULT_SLOW_THRESHOLD_MS),
+			state: Mutex::new(QueryMetricsState::default()),
+		}
+	}
+}
+
+pub trait RecordQuery<T>: Future<Output = Result<T, sqlx::Error>> + Sized {
+	/// Record the latency of this query in the global [QueryMetrics], and
+	/// run it through the global [CircuitBreaker].
+	fn record_query(self, name: &'static str) -> impl Future<Output = Self::Output> {
+		async move {
+			let start = Instant::now();
+			let output = self.guard_connection().await;
+			QueryMetrics::global().record(name, start.elapsed());
+			output
+		}
+	}
+}
+
+impl<F, T> RecordQuery<T> for F where F: Future<Output = Result<T, sqlx::Error>> {}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_record_query_metrics() {
+		// Arrange: Create metrics with a 50 ms slow query threshold.
+		let metrics = QueryMetrics::default();
+		metrics.set_slow_threshold(Duration::from_millis(50));
+
+		// Act: Record a fast and a slow query.
+		metrics.record("get_content_block", Duration::from_millis(3));
+		metrics.record("get_content_block", Duration::from_millis(7));
+		metrics.record("get_descendant_blocks", Duration::from_millis(700));
+
+		let summary = metrics.summary();
+
+		// Assert: The slowest query comes first.
+		assert_eq!(summary.queries.len(), 2);
+		assert_eq!(summary.queries[0].name, "get_descendant_blocks");
+		assert_eq!(summary.queries[0].buckets[6], 1);
+
+		// Assert: The fast query was bucketed.
+		let fast = &summary.queries[1];
+		assert_eq!(fast.count, 2);
+		assert_eq!(fast.buckets[1], 1);
+		assert_eq!(fast.buckets[2], 1);
+		assert!((fast.total_ms - 10.0).abs() < 0.001);
+
+		// Assert: Only the slow query was logged.
+		assert_eq!(summary.slow_queries.len(), 1);
+		assert_eq!(summary.slow_queries[0].name, "get_descendant_blocks");
+	}
+
+	#[test]
+	fn test_slow_query_log_is_bounded() {
+		let metrics = QueryMetrics::default();
+		metrics.set_slow_threshold(Duration::ZERO);
+
+		for _ in 0..SLOW_QUERY_CAPACITY + 10 {
+			metrics.record("is_linked", Duration::from_millis(1));
+		}
+
+		assert_eq!(metrics.summary().slow_queries.len(), SLOW_QUERY_CAPACITY);
+	}
+}
@@ -1,45 +1,298 @@
 use std::future::Future;
+use std::hash::BuildHasher;
+use std::hash::Hasher;
+use std::hash::RandomState;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::ops::DerefMut;
 use std::pin::Pin;
+use std::time::Duration;
 
 use sqlx::Executor;
+use sqlx::PgConnection;
 use sqlx::Pool;
 use sqlx::Postgres;
 use sqlx::Transaction;
 
+use crate::models::NuttyId;
+use crate::utilities::circuit_breaker::GuardConnection;
+use crate::utilities::query_metrics::RecordQuery;
+use crate::utilities::request_transaction::RequestTransaction;
+
+/// How to retry transactions that fail with transient errors.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+	/// The maximum number of attempts, including the first one.
+	pub max_attempts: u32,
+
+	/// The base delay of the exponential backoff.
+	pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+	/// Get the jittered delay before a given retry (1-indexed).
+	///
+	/// The delay is sampled uniformly from [0, base_delay × 2^(retry - 1)]
+	/// ("full jitter"), so that competing transactions spread out.
+	pub fn backoff(&self, retry: u32) -> Duration {
+		let ceiling = self
+			.base_delay
+			.saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)));
+
+		// RandomState is randomly seeded, which is good enough for jitter.
+		let random = RandomState::new().build_hasher().finish();
+		let fraction = (random % 1_000) as u32;
+
+		ceiling * fraction / 1_000
+	}
+}
+
+impl Default for RetryPolicy {
+	fn default() -> Self {
+		Self {
+			max_attempts: 3,
+			base_delay: Duration::from_millis(10),
+		}
+	}
+}
+
 pub trait Repository: Send + Sync {
 	/// Provide access to the database connection pool.
 	fn pool(&self) -> &Pool<Postgres>;
 
+	/// Provide the policy for retrying transactions.
+	fn retry_policy(&self) -> RetryPolicy {
+		RetryPolicy::default()
+	}
+
 	/// Execute a function within a transaction.
+	///
+	/// If the transaction fails with a serialization failure or a deadlock,
+	/// it is rolled back and retried with jittered exponential backoff, so
+	/// the function may be called more than once.
+	///
+	/// Within a request whose [RequestTransaction] has begun, the function
+	/// runs in a savepoint of the request's transaction instead, which is
+	/// committed when the request ends.
 	fn with_transaction<'r, F, R, E>(
 		&'r self,
 		execute_transaction_body: F,
 	) -> Pin<Box<dyn Future<Output = Result<R, E>> + Send + 'r>>
 	where
-		F: for<'c> FnOnce(
-				&'c mut Transaction<'r, Postgres>,
+		F: for<'c> Fn(
+				&'c mut ScopedTransaction<'r>,
 			) -> Pin<Box<dyn Future<Output = Result<R, E>> + Send + 'c>>
 			+ Send
+			+ Sync
 			+ 'r,
 		R: Send + 'r,
-		E: From<sqlx::Error> + Send + 'r,
+		E: From<sqlx::Error> + std::error::Error + Send + 'static,
 	{
 		Box::pin(async move {
-			let mut tx = self.pool().begin().await?;
+			let policy = self.retry_policy();
+			let mut attempt = 1;
+
+			loop {
+				let result = self.try_transaction(&execute_transaction_body).await;
+
+				match result {
+					Err(e) if attempt < policy.max_attempts && is_retriable(&e) => {
+						tokio::time::sleep(policy.backoff(attempt)).await;
+						attempt += 1;
+					}
+
+					result => return result,
+				}
+			}
+		})
+	}
+
+	/// Execute a function within a transaction, once.
+	fn try_transaction<'r, 'f, F, R, E>(
+		&'r self,
+		execute_transaction_body: &'f F,
+	) -> Pin<Box<dyn Future<Output = Result<R, E>> + Send + 'f>>
+	where
+		'r: 'f,
+		F: for<'c> Fn(
+				&'c mut ScopedTransaction<'r>,
+			) -> Pin<Box<dyn Future<Output = Result<R, E>> + Send + 'c>>
+			+ Send
+			+ Sync
+			+ 'r,
+		R: Send + 'r,
+		E: From<sqlx::Error> + Send + 'static,
+	{
+		Box::pin(async move {
+			if let Some(request_transaction) = RequestTransaction::current() {
+				return request_transaction
+					.run(self.pool(), execute_transaction_body)
+					.await;
+			}
+
+			let mut tx = ScopedTransaction::new(self.pool().begin().guard_connection().await?);
 			let result = execute_transaction_body(&mut tx).await;
 
 			match result {
 				Ok(r) => {
-					tx.commit().await?;
+					tx.into_inner().commit().await?;
 					Ok(r)
 				}
 				Err(e) => {
-					let _ = tx.rollback().await;
+					let _ = tx.into_inner().rollback().await;
 					Err(e)
 				}
 			}
 		})
 	}
+
+	/// Execute a function within a read-only transaction that sees a single
+	/// snapshot of the database, so that separate queries agree with one
+	/// another even as others save changes.
+	///
+	/// It doesn't join the request's transaction, so it doesn't see writes
+	/// made earlier in the request that haven't been committed yet.
+	fn with_snapshot<'r, F, R, E>(
+		&'r self,
+		execute_snapshot_body: F,
+	) -> Pin<Box<dyn Future<Output = Result<R, E>> + Send + 'r>>
+	where
+		F: for<'c> FnOnce(
+				&'c mut ScopedTransaction<'r>,
+			) -> Pin<Box<dyn Future<Output = Result<R, E>> + Send + 'c>>
+			+ Send
+			+ 'r,
+		R: Send + 'r,
+		E: From<sqlx::Error> + Send + 'static,
+	{
+		Box::pin(async move {
+			let mut tx = ScopedTransaction::new(self.pool().begin().guard_connection().await?);
+
+			tx.execute("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+				.await?;
+
+			let result = execute_snapshot_body(&mut tx).await;
+
+			// There's nothing to commit, either way.
+			let _ = tx.into_inner().rollback().await;
+
+			result
+		})
+	}
+}
+
+/// The transaction that the body of [Repository::with_transaction] runs in,
+/// which is either its own or the current request's. It's tied to the
+/// repository's lifetime, so that the body may borrow from it.
+pub struct ScopedTransaction<'r> {
+	transaction: Transaction<'static, Postgres>,
+	repository: PhantomData<&'r ()>,
+}
+
+impl ScopedTransaction<'_> {
+	/// Lend a transaction to a body.
+	pub(crate) fn new(transaction: Transaction<'static, Postgres>) -> Self {
+		Self {
+			transaction,
+			repository: PhantomData,
+		}
+	}
+
+	/// Take the transaction back, to commit or roll it back.
+	pub(crate) fn into_inner(self) -> Transaction<'static, Postgres> {
+		self.transaction
+	}
+
+	/// Get the transaction's connection, to run a query on.
+	pub fn conn(&mut self) -> &mut PgConnection {
+		&mut self.transaction
+	}
+
+	/// Lend the transaction to service-internal helpers.
+	pub fn context(&mut self) -> TxnContext<'_> {
+		TxnContext::new(&mut self.transaction)
+	}
+}
+
+impl Deref for ScopedTransaction<'_> {
+	type Target = Transaction<'static, Postgres>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.transaction
+	}
+}
+
+impl DerefMut for ScopedTransaction<'_> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.transaction
+	}
+}
+
+/// A transaction lent to service-internal helpers, so that they can write
+/// blocks, links, revisions, and so on together through the `*_tx` methods
+/// of any repository.
+///
+/// Only the body given to [Repository::with_transaction] needs boxing; the
+/// helpers it calls are plain `async fn`s that take a `&mut TxnContext<'_>`,
+/// which reborrows implicitly, so it can be passed down any number of calls.
+pub struct TxnContext<'t> {
+	conn: &'t mut PgConnection,
+}
+
+impl<'t> TxnContext<'t> {
+	/// Lend a transaction.
+	pub fn new(transaction: &'t mut Transaction<'_, Postgres>) -> Self {
+		Self {
+			conn: &mut **transaction,
+		}
+	}
+
+	/// Get the transaction's connection, to run a query on.
+	pub fn conn(&mut self) -> &mut PgConnection {
+		self.conn
+	}
+}
+
+/// Check if an error was caused by a serialization failure (40001)
+/// or a deadlock (40P01), in which case the transaction can be retried.
+pub fn is_retriable(error: &(dyn std::error::Error + 'static)) -> bool {
+	let mut current = Some(error);
+
+	while let Some(error) = current {
+		if let Some(sqlx::Error::Database(db_error)) = error.downcast_ref::<sqlx::Error>()
+			&& matches!(db_error.code().as_deref(), Some("40001" | "40P01"))
+		{
+			return true;
+		}
+
+		current = error.source();
+	}
+
+	false
+}
+
+/// Lock a parent's children until the transaction ends, so that operations
+/// that order new or moved children after reading their siblings' fractional
+/// indexes take turns, instead of computing the same index.
+///
+/// The lock is a Postgres advisory lock, which only excludes others that
+/// take it; it doesn't lock any rows.
+pub async fn lock_children_tx<'e, E>(executor: E, parent_id: &NuttyId) -> Result<(), sqlx::Error>
+where
+	E: Executor<'e, Database = Postgres>,
+{
+	sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+		.bind(children_lock_key(parent_id))
+		.execute(executor)
+		.record_query("lock_children")
+		.await?;
+
+	Ok(())
+}
+
+/// The key of a parent's children lock, namespaced from other advisory locks.
+fn children_lock_key(parent_id: &NuttyId) -> String {
+	format!("content.children:{}", parent_id.nid())
 }
 
 pub trait TransactionExt<'t> {
@@ -60,3 +313,271 @@ impl<'t> TransactionExt<'t> for Transaction<'_, Postgres> {
 		&mut **self
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::sync::atomic::AtomicU32;
+	use std::sync::atomic::Ordering;
+
+	use sqlx::postgres::PgPoolOptions;
+
+	use super::*;
+
+	struct TestRepository {
+		pool: Pool<Postgres>,
+	}
+
+	impl Repository for TestRepository {
+		fn pool(&self) -> &Pool<Postgres> {
+			&self.pool
+		}
+	}
+
+	async fn connect_to_test_database() -> Pool<Postgres> {
+		let database_url = std::env::var("DATABASE_URL").unwrap();
+
+		PgPoolOptions::new()
+			.max_connections(5)
+			.connect(&database_url)
+			.await
+			.expect("Failed to connect to test database")
+	}
+
+	/// Raise a Postgres error with the given SQLSTATE code.
+	async fn raise(tx: &mut Transaction<'_, Postgres>, code: &str) -> Result<(), sqlx::Error> {
+		let statement =
+			format!("DO $$ BEGIN RAISE EXCEPTION 'test' USING ERRCODE = '{code}'; END $$");
+		tx.as_executor().execute(statement.as_str()).await?;
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_with_transaction_retries_serialization_failures() {
+		// Arrange: Create a repository.
+		let pool = connect_to_test_database().await;
+		let repository = TestRepository { pool };
+		let attempts = AtomicU32::new(0);
+
+		// Act: Fail with a serialization failure on the first attempt only.
+		let result: Result<u32, sqlx::Error> = repository
+			.with_transaction(|tx| {
+				let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+
+				Box::pin(async move {
+					if attempt == 1 {
+						raise(tx, "40001").await?;
+					}
+
+					Ok(attempt)
+				})
+			})
+			.await;
+
+		// Assert: The transaction succeeded on the second attempt.
+		assert_eq!(result.unwrap(), 2);
+		assert_eq!(attempts.load(Ordering::SeqCst), 2);
+	}
+
+	#[tokio::test]
+	async fn test_with_transaction_gives_up_after_max_attempts() {
+		// Arrange: Create a repository.
+		let pool = connect_to_test_database().await;
+		let repository = TestRepository { pool };
+		let attempts = AtomicU32::new(0);
+
+		// Act: Deadlock on every attempt.
+		let result: Result<(), sqlx::Error> = repository
+			.with_transaction(|tx| {
+				attempts.fetch_add(1, Ordering::SeqCst);
+				Box::pin(async move { raise(tx, "40P01").await })
+			})
+			.await;
+
+		// Assert: The error surfaces after the maximum number of attempts.
+		assert!(is_retriable(&result.unwrap_err()));
+		assert_eq!(
+			attempts.load(Ordering::SeqCst),
+			RetryPolicy::default().max_attempts
+		);
+	}
+
+	#[tokio::test]
+	async fn test_with_transaction_does_not_retry_other_errors() {
+		// Arrange: Create a repository.
+		let pool = connect_to_test_database().await;
+		let repository = TestRepository { pool };
+		let attempts = AtomicU32::new(0);
+
+		// Act: Fail with a unique violation.
+		let result: Result<(), sqlx::Error> = repository
+			.with_transaction(|tx| {
+				attempts.fetch_add(1, Ordering::SeqCst);
+				Box::pin(async move { raise(tx, "23505").await })
+			})
+			.await;
+
+		// Assert: The transaction was attempted once.
+		assert!(!is_retriable(&result.unwrap_err()));
+		assert_eq!(attempts.load(Ordering::SeqCst), 1);
+	}
+
+	#[tokio::test]
+	async fn test_txn_context() {
+		// Arrange: Create a repository, and a helper that writes through a
+		// lent transaction.
+		let pool = connect_to_test_database().await;
+		let repository = TestRepository { pool };
+		let navigator_id = NuttyId::now();
+
+		async fn insert_navigator(
+			ctx: &mut TxnContext<'_>,
+			navigator_id: &NuttyId,
+		) -> Result<(), sqlx::Error> {
+			sqlx::query(
+				"INSERT INTO auth.navigators (id, nutty_id, name, pass) VALUES ($1, $2, $3, 'hash')",
+			)
+			.bind(navigator_id.uuid())
+			.bind(navigator_id.nid())
+			.bind(format!("test_navigator_{}", navigator_id.nid()))
+			.execute(ctx.conn())
+			.await?;
+
+			Ok(())
+		}
+
+		// Act: Write through the helper, see it within the transaction, then
+		// fail the transaction.
+		let result: Result<(), sqlx::Error> = repository
+			.with_transaction(|tx| {
+				Box::pin(async move {
+					let ctx = &mut tx.context();
+					insert_navigator(ctx, &navigator_id).await?;
+
+					let exists: bool =
+						sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM auth.navigators WHERE id = $1)")
+							.bind(navigator_id.uuid())
+							.fetch_one(ctx.conn())
+							.await?;
+
+					assert!(exists);
+					raise(tx, "23505").await
+				})
+			})
+			.await;
+
+		// Assert: The helper's write was rolled back with the transaction.
+		assert!(result.is_err());
+
+		let exists: bool =
+			sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM auth.navigators WHERE id = $1)")
+				.bind(navigator_id.uuid())
+				.fetch_one(&repository.pool)
+				.await
+				.unwrap();
+
+		assert!(!exists);
+	}
+
+	#[tokio::test]
+	async fn test_with_snapshot() {
+		// Arrange: Create a repository.
+		let pool = connect_to_test_database().await;
+		let repository = TestRepository { pool };
+		let pool = &repository.pool;
+		let navigator_id = crate::models::NuttyId::now();
+
+		let count = "SELECT COUNT(*) FROM auth.navigators";
+
+		// Act: Create a navigator between two reads of a snapshot.
+		let (before, after, write) = repository
+			.with_snapshot(|tx| {
+				Box::pin(async move {
+					let before: i64 = sqlx::query_scalar(count)
+						.fetch_one(tx.as_executor())
+						.await?;
+
+					sqlx::query(
+						"INSERT INTO auth.navigators (id, nutty_id, name, pass) VALUES ($1, $2, $3, 'hash')",
+					)
+					.bind(navigator_id.uuid())
+					.bind(navigator_id.nid())
+					.bind(format!("test_navigator_{}", navigator_id.nid()))
+					.execute(pool)
+					.await?;
+
+					let after: i64 = sqlx::query_scalar(count)
+						.fetch_one(tx.as_executor())
+						.await?;
+					let write = tx
+						.as_executor()
+						.execute("CREATE TEMPORARY TABLE t (x INT)")
+						.await;
+
+					Ok::<_, sqlx::Error>((before, after, write))
+				})
+			})
+			.await
+			.unwrap();
+
+		// Assert: The snapshot didn't see the navigator, nor allow writes.
+		assert_eq!(before, after);
+		assert!(write.is_err());
+
+		sqlx::query("DELETE FROM auth.navigators WHERE id = $1")
+			.bind(navigator_id.uuid())
+			.execute(&repository.pool)
+			.await
+			.unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_lock_children() {
+		// Arrange: Connect to the database.
+		let pool = connect_to_test_database().await;
+		let parent_id = NuttyId::now();
+		let other_parent_id = NuttyId::now();
+
+		let try_lock = |parent_id: NuttyId| {
+			let pool = &pool;
+
+			async move {
+				let mut tx = pool.begin().await.unwrap();
+
+				sqlx::query_scalar::<_, bool>(
+					"SELECT pg_try_advisory_xact_lock(hashtextextended($1, 0))",
+				)
+				.bind(children_lock_key(&parent_id))
+				.fetch_one(tx.as_executor())
+				.await
+				.unwrap()
+			}
+		};
+
+		// Act: Lock a parent's children.
+		let mut tx = pool.begin().await.unwrap();
+		lock_children_tx(tx.as_executor(), &parent_id)
+			.await
+			.unwrap();
+
+		// Assert: Other transactions can't lock them, but can lock others'.
+		assert!(!try_lock(parent_id).await);
+		assert!(try_lock(other_parent_id).await);
+
+		// Assert: The lock is released when the transaction ends.
+		tx.rollback().await.unwrap();
+		assert!(try_lock(parent_id).await);
+	}
+
+	#[test]
+	fn test_backoff_is_bounded() {
+		let policy = RetryPolicy {
+			max_attempts: 5,
+			base_delay: Duration::from_millis(10),
+		};
+
+		for retry in 1..5 {
+			let ceiling = Duration::from_millis(10 * 2u64.pow(retry - 1));
+			assert!(policy.backoff(retry) <= ceiling);
+		}
+	}
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use axum::body::HttpBody;
+use axum::extract::Request;
+use axum::middleware::Next;
+
+use crate::models::NuttyId;
+use crate::models::request_usage::RequestUsage;
+use crate::navigator::service::NavigatorServiceApi;
+use crate::utilities::row_level_security;
+
+/// How often request usage is flushed to the database, by default.
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The process-wide request metrics.
+static REQUEST_METRICS: LazyLock<RequestMetrics> = LazyLock::new(RequestMetrics::default);
+
+/// The requests that each signed-in navigator has made since the last
+/// flush, so that operators can see who the heaviest users are. Requests
+/// without a session aren't counted.
+#[derive(Default)]
+pub struct RequestMetrics {
+	usage: Mutex<HashMap<NuttyId, RequestUsage>>,
+}
+
+impl RequestMetrics {
+	/// Get the process-wide request metrics.
+	pub fn global() -> &'static RequestMetrics {
+		&REQUEST_METRICS
+	}
+
+	/// Record a request made by a navigator.
+	pub fn record(
+		&self,
+		navigator_id: NuttyId,
+		elapsed: Duration,
+		request_bytes: u64,
+		response_bytes: u64,
+	) {
+		self
+			.lock()
+			.entry(navigator_id)
+			.or_default()
+			.record(elapsed, request_bytes, response_bytes);
+	}
+
+	/// Take the usage recorded since the last time, leaving none.
+	pub fn take(&self) -> Vec<(NuttyId, RequestUsage)> {
+		std::mem::take(&mut *self.lock()).into_iter().collect()
+	}
+
+	/// Put back usage that couldn't be flushed, to try again next time.
+	pub fn restore(&self, usage: Vec<(NuttyId, RequestUsage)>) {
+		let mut current = self.lock();
+
+		for (navigator_id, usage) in usage {
+			current.entry(navigator_id).or_default().merge(&usage);
+		}
+	}
+
+	/// Flush the recorded usage to the database every interval, for as long
+	/// as the server runs.
+	pub fn spawn_flusher(&'static self, service: Arc<dyn NavigatorServiceApi>, interval: Duration) {
+		tokio::spawn(async move {
+			let mut ticker = tokio::time::interval(interval);
+			ticker.tick().await;
+
+			loop {
+				ticker.tick().await;
+				self.flush(service.as_ref()).await;
+			}
+		});
+	}
+
+	/// Flush the recorded usage to the database. Usage that fails to save is
+	/// kept for the next flush.
+	pub async fn flush(&self, service: &dyn NavigatorServiceApi) {
+		let usage = self.take();
+
+		if usage.is_empty() {
+			return;
+		}
+
+		if let Err(e) = service.record_request_usage(&usage).await {
+			eprintln!("Failed to flush request usage: {e}");
+			self.restore(usage);
+		}
+	}
+
+	fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<NuttyId, RequestUsage>> {
+		self.usage.lock().expect("Request metrics lock poisoned")
+	}
+}
+
+/// Middleware that records the latency and size of each request in the
+/// global [RequestMetrics], once a session identifies who made it. Latency
+/// is measured until the response starts, and streamed bodies only count
+/// the bytes known up front.
+pub async fn request_metrics_middleware(request: Request, next: Next) -> axum::response::Response {
+	let start = Instant::now();
+	let request_bytes = body_bytes(request.body());
+	let response = next.run(request).await;
+
+	if let Some(navigator_id) = row_level_security::identified() {
+		let response_bytes = body_bytes(response.body());
+
+		RequestMetrics::global().record(navigator_id, start.elapsed(), request_bytes, response_bytes);
+	}
+
+	response
+}
+
+/// The size of a body, or as much of it as is known up front.
+fn body_bytes(body: &impl HttpBody) -> u64 {
+	let size_hint = body.size_hint();
+	size_hint.exact().unwrap_or(size_hint.lower())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_take_and_restore() {
+		let metrics = RequestMetrics::default();
+		let navigator_id = NuttyId::now();
+
+		// Act: Record two requests.
+		metrics.record(navigator_id, Duration::from_millis(3), 10, 100);
+		metrics.record(navigator_id, Duration::from_millis(30), 10, 100);
+
+		// Assert: They're taken together, leaving none.
+		let usage = metrics.take();
+		assert_eq!(usage.len(), 1);
+		assert_eq!(usage[0].1.request_count, 2);
+		assert!(metrics.take().is_empty());
+
+		// Assert: Restored usage merges with what's recorded since.
+		metrics.record(navigator_id, Duration::from_millis(3), 10, 100);
+		metrics.restore(usage);
+
+		let usage = metrics.take();
+		assert_eq!(usage[0].1.request_count, 3);
+		assert_eq!(usage[0].1.response_bytes, 300);
+	}
+}
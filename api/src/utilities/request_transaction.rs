@@ -0,0 +1,319 @@
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+use axum::Json;
+use axum::extract::FromRequestParts;
+use axum::extract::Request;
+use axum::http::StatusCode;
+use axum::http::request::Parts;
+use axum::middleware::Next;
+use axum::response::IntoResponse;
+use sqlx::Executor;
+use sqlx::Pool;
+use sqlx::Postgres;
+use sqlx::Transaction;
+use tokio::sync::Mutex;
+
+use crate::utilities::api::response::Error;
+use crate::utilities::api::response::Response;
+use crate::utilities::circuit_breaker::GuardConnection;
+use crate::utilities::repository::ScopedTransaction;
+
+tokio::task_local! {
+	/// The transaction of the current request.
+	static REQUEST_TRANSACTION: RequestTransaction;
+}
+
+/// A transaction that spans a whole API request, for handlers whose service
+/// calls must succeed or fail together, e.g. resolving an access request
+/// and granting the access it asked for.
+///
+/// Handlers opt in by extracting it and calling [RequestTransaction::begin].
+/// From then on, every [Repository::with_transaction] in the request runs in
+/// a savepoint of it, and it's committed if the response is successful, or
+/// rolled back otherwise. Queries made straight on a pool aren't part of it,
+/// so they don't see its writes until the request ends.
+///
+/// [Repository::with_transaction]: crate::utilities::repository::Repository::with_transaction
+#[derive(Clone, Default)]
+pub struct RequestTransaction {
+	/// Whether the handler opted in.
+	is_begun: Arc<AtomicBool>,
+
+	/// The transaction, once the first repository transaction joins it.
+	transaction: Arc<Mutex<Option<Transaction<'static, Postgres>>>>,
+}
+
+impl RequestTransaction {
+	/// Run the rest of the request's repository transactions in this one.
+	/// Nothing is sent to the database until the first of them runs.
+	pub fn begin(&self) {
+		self.is_begun.store(true, Ordering::Relaxed);
+	}
+
+	/// Get the current request's transaction, if its handler began it.
+	pub fn current() -> Option<Self> {
+		REQUEST_TRANSACTION
+			.try_with(Self::clone)
+			.ok()
+			.filter(|transaction| transaction.is_begun.load(Ordering::Relaxed))
+	}
+
+	/// Run a repository transaction's body in a savepoint, so that it's
+	/// undone on its own if it fails. Bodies take turns, so they mustn't
+	/// run repository transactions of their own.
+	pub(crate) fn run<'r, 'f, F, R, E>(
+		&'f self,
+		pool: &'f Pool<Postgres>,
+		body: &'f F,
+	) -> Pin<Box<dyn Future<Output = Result<R, E>> + Send + 'f>>
+	where
+		'r: 'f,
+		F: for<'c> Fn(
+				&'c mut ScopedTransaction<'r>,
+			) -> Pin<Box<dyn Future<Output = Result<R, E>> + Send + 'c>>
+			+ Send
+			+ Sync
+			+ 'r,
+		R: Send + 'r,
+		E: From<sqlx::Error> + Send + 'static,
+	{
+		Box::pin(async move {
+			let mut transaction = self.transaction.lock().await;
+
+			let mut tx = ScopedTransaction::new(match transaction.take() {
+				Some(tx) => tx,
+				None => pool.begin().guard_connection().await?,
+			});
+
+			let result = savepoint(&mut tx, body).await;
+
+			*transaction = Some(tx.into_inner());
+			result
+		})
+	}
+
+	/// Commit the transaction, or roll it back, if it was used.
+	pub async fn finish(&self, commit: bool) -> Result<(), sqlx::Error> {
+		let Some(tx) = self.transaction.lock().await.take() else {
+			return Ok(());
+		};
+
+		match commit {
+			true => tx.commit().await,
+			false => tx.rollback().await,
+		}
+	}
+}
+
+/// Run a body in a savepoint of a transaction, undoing it if it fails.
+fn savepoint<'r, 'f, F, R, E>(
+	tx: &'f mut ScopedTransaction<'r>,
+	body: &'f F,
+) -> Pin<Box<dyn Future<Output = Result<R, E>> + Send + 'f>>
+where
+	'r: 'f,
+	F: for<'c> Fn(
+			&'c mut ScopedTransaction<'r>,
+		) -> Pin<Box<dyn Future<Output = Result<R, E>> + Send + 'c>>
+		+ Send
+		+ Sync
+		+ 'r,
+	R: Send + 'r,
+	E: From<sqlx::Error> + Send + 'static,
+{
+	Box::pin(async move {
+		tx.execute("SAVEPOINT request_transaction").await?;
+
+		match body(tx).await {
+			Ok(r) => {
+				tx.execute("RELEASE SAVEPOINT request_transaction").await?;
+
+				Ok(r)
+			}
+
+			Err(e) => {
+				let _ = tx
+					.execute("ROLLBACK TO SAVEPOINT request_transaction")
+					.await;
+
+				Err(e)
+			}
+		}
+	})
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for RequestTransaction {
+	type Rejection = Infallible;
+
+	async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+		// Outside of the middleware, there's no request to span, so beginning
+		// the transaction does nothing.
+		Ok(parts.extensions.get::<Self>().cloned().unwrap_or_default())
+	}
+}
+
+/// Middleware that gives each request a [RequestTransaction], and ends it
+/// according to the response's status.
+pub async fn request_transaction_middleware(
+	mut request: Request,
+	next: Next,
+) -> axum::response::Response {
+	let transaction = RequestTransaction::default();
+	request.extensions_mut().insert(transaction.clone());
+
+	let response = REQUEST_TRANSACTION
+		.scope(transaction.clone(), next.run(request))
+		.await;
+
+	let status = response.status();
+	let commit = status.is_success() || status.is_redirection();
+
+	let Err(error) = transaction.finish(commit).await else {
+		return response;
+	};
+
+	let error = RequestTransactionError::Finish(error);
+	let error = Error::from_error(&error).with_summary("Failed to save changes.");
+
+	(
+		StatusCode::INTERNAL_SERVER_ERROR,
+		Json(Response::<()>::Error {
+			errors: vec![error],
+		}),
+	)
+		.into_response()
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RequestTransactionError {
+	#[error("Failed to end request transaction: {0}")]
+	Finish(#[source] sqlx::Error),
+}
+
+#[cfg(test)]
+mod tests {
+	use sqlx::postgres::PgPoolOptions;
+
+	use super::*;
+	use crate::models::NuttyId;
+	use crate::utilities::repository::Repository;
+	use crate::utilities::repository::TransactionExt;
+
+	struct TestRepository {
+		pool: Pool<Postgres>,
+	}
+
+	impl Repository for TestRepository {
+		fn pool(&self) -> &Pool<Postgres> {
+			&self.pool
+		}
+	}
+
+	impl TestRepository {
+		/// Create a navigator, failing afterwards if asked to.
+		async fn create_navigator(
+			&self,
+			navigator_id: NuttyId,
+			fail: bool,
+		) -> Result<(), sqlx::Error> {
+			self
+				.with_transaction(|tx| {
+					Box::pin(async move {
+						sqlx::query(
+							"INSERT INTO auth.navigators (id, nutty_id, name, pass) VALUES ($1, $2, $3, 'hash')",
+						)
+						.bind(navigator_id.uuid())
+						.bind(navigator_id.nid())
+						.bind(format!("test_navigator_{}", navigator_id.nid()))
+						.execute(tx.as_executor())
+						.await?;
+
+						match fail {
+							true => Err(sqlx::Error::RowNotFound),
+							false => Ok(()),
+						}
+					})
+				})
+				.await
+		}
+
+		/// Check if a navigator exists, outside of any transaction.
+		async fn exists(&self, navigator_id: NuttyId) -> bool {
+			sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM auth.navigators WHERE id = $1)")
+				.bind(navigator_id.uuid())
+				.fetch_one(&self.pool)
+				.await
+				.unwrap()
+		}
+	}
+
+	async fn connect_to_test_database() -> Pool<Postgres> {
+		let database_url = std::env::var("DATABASE_URL").unwrap();
+
+		PgPoolOptions::new()
+			.max_connections(5)
+			.connect(&database_url)
+			.await
+			.expect("Failed to connect to test database")
+	}
+
+	#[tokio::test]
+	async fn test_request_transaction() {
+		// Arrange: Create a repository.
+		let pool = connect_to_test_database().await;
+		let repository = TestRepository { pool };
+		let [kept, failed, rolled_back, unjoined] = [(); 4].map(|_| NuttyId::now());
+
+		// Act: Create navigators in a request that succeeds, one of them failing.
+		let transaction = RequestTransaction::default();
+
+		REQUEST_TRANSACTION
+			.scope(transaction.clone(), async {
+				transaction.begin();
+
+				repository.create_navigator(kept, false).await.unwrap();
+				assert!(repository.create_navigator(failed, true).await.is_err());
+
+				// Assert: Nothing is visible before the request ends.
+				assert!(!repository.exists(kept).await);
+			})
+			.await;
+
+		transaction.finish(true).await.unwrap();
+
+		// Assert: Only the navigator whose transaction succeeded was kept.
+		assert!(repository.exists(kept).await);
+		assert!(!repository.exists(failed).await);
+
+		// Act: Create navigators in a request that fails, one without beginning.
+		let transaction = RequestTransaction::default();
+
+		REQUEST_TRANSACTION
+			.scope(transaction.clone(), async {
+				repository.create_navigator(unjoined, false).await.unwrap();
+
+				transaction.begin();
+				repository
+					.create_navigator(rolled_back, false)
+					.await
+					.unwrap();
+			})
+			.await;
+
+		transaction.finish(false).await.unwrap();
+
+		// Assert: Only the navigator created before beginning was kept.
+		assert!(repository.exists(unjoined).await);
+		assert!(!repository.exists(rolled_back).await);
+
+		sqlx::query("DELETE FROM auth.navigators WHERE id = ANY($1)")
+			.bind(vec![*kept.uuid(), *unjoined.uuid()])
+			.execute(&repository.pool)
+			.await
+			.unwrap();
+	}
+}
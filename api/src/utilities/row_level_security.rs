@@ -0,0 +1,242 @@
+use std::cell::Cell;
+
+use axum::extract::Request;
+use axum::middleware::Next;
+use sqlx::Executor;
+use sqlx::PgConnection;
+use sqlx::postgres::PgPoolOptions;
+
+use crate::models::NuttyId;
+
+/// The role that connections switch to for signed-in navigators, which the
+/// schema's row-level security policies apply to.
+const NAVIGATOR_ROLE: &str = "nuttyverse_navigator";
+
+tokio::task_local! {
+	/// The navigator that the current request is made by, once identified.
+	static NAVIGATOR_ID: Cell<Option<NuttyId>>;
+}
+
+/// A defense-in-depth mode in which Postgres enforces access to content
+/// blocks, too. Connections made while serving a signed-in navigator switch
+/// to a restricted role that only sees the rows they could access through
+/// the API. Requests without a session, and background tasks, aren't
+/// restricted. Disabled by default, since it costs a round-trip per query.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RowLevelSecurity {
+	enabled: bool,
+}
+
+impl RowLevelSecurity {
+	/// Create a row-level security switch.
+	pub fn new(enabled: bool) -> Self {
+		Self { enabled }
+	}
+
+	/// Enable row-level security if `ROW_LEVEL_SECURITY` is "1" or "true".
+	pub fn from_env() -> Self {
+		Self::new(std::env::var("ROW_LEVEL_SECURITY").is_ok_and(|v| v == "1" || v == "true"))
+	}
+
+	/// Check if row-level security is enabled.
+	pub fn is_enabled(&self) -> bool {
+		self.enabled
+	}
+
+	/// Configure a pool to set up every connection it hands out for the
+	/// current request, if enabled.
+	pub fn configure(&self, options: PgPoolOptions) -> PgPoolOptions {
+		if !self.enabled {
+			return options;
+		}
+
+		// New connections skip `before_acquire`, so both are needed.
+		options
+			.after_connect(|connection, _| Box::pin(restrict(connection)))
+			.before_acquire(|connection, _| {
+				Box::pin(async move { restrict(connection).await.map(|_| true) })
+			})
+	}
+}
+
+/// Run a request, so that the navigator making it can be identified.
+pub async fn scope<F: Future>(future: F) -> F::Output {
+	NAVIGATOR_ID.scope(Cell::new(None), future).await
+}
+
+/// Identify the navigator that the current request is made by. Does nothing
+/// outside of a request.
+pub fn identify(navigator_id: NuttyId) {
+	let _ = NAVIGATOR_ID.try_with(|current| current.set(Some(navigator_id)));
+}
+
+/// Get the navigator that the current request is made by, if identified.
+pub fn identified() -> Option<NuttyId> {
+	NAVIGATOR_ID.try_with(Cell::get).ok().flatten()
+}
+
+/// Run a future without the current request's restrictions, for checks that
+/// need to see the rows they decide on.
+pub async fn unrestricted<F: Future>(future: F) -> F::Output {
+	scope(future).await
+}
+
+/// Middleware that scopes each request for [identify].
+pub async fn row_level_security_middleware(
+	request: Request,
+	next: Next,
+) -> axum::response::Response {
+	scope(next.run(request)).await
+}
+
+/// Switch a connection to the restricted role for the current navigator,
+/// or back to the connecting role if there isn't one.
+async fn restrict(connection: &mut PgConnection) -> Result<(), sqlx::Error> {
+	let navigator_id = NAVIGATOR_ID.try_with(Cell::get).ok().flatten();
+
+	let (navigator_id, role) = match navigator_id {
+		Some(navigator_id) => (navigator_id.uuid().to_string(), NAVIGATOR_ROLE),
+		None => (String::new(), "none"),
+	};
+
+	connection
+		.execute(
+			sqlx::query(
+				"SELECT set_config('app.navigator_id', $1, false), set_config('role', $2, false)",
+			)
+			.bind(navigator_id)
+			.bind(role),
+		)
+		.await?;
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use sqlx::PgPool;
+
+	use super::*;
+	use crate::models::NuttyId;
+
+	/// Connect to the test database with row-level security enabled.
+	async fn connect_to_test_database() -> PgPool {
+		let database_url = std::env::var("DATABASE_URL").unwrap();
+
+		RowLevelSecurity::new(true)
+			.configure(PgPoolOptions::new().max_connections(2))
+			.connect(&database_url)
+			.await
+			.expect("Failed to connect to test database")
+	}
+
+	/// Count the blocks with the given IDs that the current connection sees.
+	async fn count_visible(pool: &PgPool, block_ids: &[NuttyId]) -> i64 {
+		let block_ids: Vec<_> = block_ids.iter().map(|id| *id.uuid()).collect();
+
+		sqlx::query_scalar("SELECT COUNT(*) FROM content.blocks WHERE id = ANY($1)")
+			.bind(block_ids)
+			.fetch_one(pool)
+			.await
+			.unwrap()
+	}
+
+	#[tokio::test]
+	async fn test_row_level_security() {
+		// Arrange: Alice owns a page with a child, which Bob is shared.
+		let pool = connect_to_test_database().await;
+		let alice_id = NuttyId::now();
+		let bob_id = NuttyId::now();
+		let eve_id = NuttyId::now();
+		let page_id = NuttyId::now();
+		let child_id = NuttyId::now();
+		let grant_id = NuttyId::now();
+
+		for navigator_id in [alice_id, bob_id, eve_id] {
+			sqlx::query(
+				"INSERT INTO auth.navigators (id, nutty_id, name, pass) VALUES ($1, $2, $3, 'hash')",
+			)
+			.bind(navigator_id.uuid())
+			.bind(navigator_id.nid())
+			.bind(format!("rls_{}", navigator_id.nid()))
+			.execute(&pool)
+			.await
+			.unwrap();
+		}
+
+		for (block_id, parent_id) in [(page_id, None), (child_id, Some(page_id))] {
+			sqlx::query(
+				r#"
+					INSERT INTO content.blocks (id, nutty_id, parent_id, f_index, content, owner_id)
+					VALUES ($1, $2, $3, 'a0', '{"kind": "Page", "title": "RLS"}', $4)
+				"#,
+			)
+			.bind(block_id.uuid())
+			.bind(block_id.nid())
+			.bind(parent_id.map(|id: NuttyId| *id.uuid()))
+			.bind(alice_id.uuid())
+			.execute(&pool)
+			.await
+			.unwrap();
+		}
+
+		sqlx::query(
+			r#"
+				INSERT INTO auth.resource_roles (id, nutty_id, navigator_id, role_name, resource_type, resource_id)
+				VALUES ($1, $2, $3, 'block_viewer', 'content_block', $4)
+			"#,
+		)
+		.bind(grant_id.uuid())
+		.bind(grant_id.nid())
+		.bind(bob_id.uuid())
+		.bind(page_id.uuid())
+		.execute(&pool)
+		.await
+		.unwrap();
+
+		let blocks = [page_id, child_id];
+
+		// Assert: Background tasks and anonymous requests see every block.
+		assert_eq!(count_visible(&pool, &blocks).await, 2);
+		assert_eq!(scope(count_visible(&pool, &blocks)).await, 2);
+
+		// Assert: Bob sees the page he was shared, and the child that
+		// inherits access from it, but can't edit them.
+		scope(async {
+			identify(bob_id);
+			assert_eq!(count_visible(&pool, &blocks).await, 2);
+
+			let updated = sqlx::query("UPDATE content.blocks SET f_index = 'a1' WHERE id = $1")
+				.bind(child_id.uuid())
+				.execute(&pool)
+				.await
+				.unwrap();
+
+			assert_eq!(updated.rows_affected(), 0);
+		})
+		.await;
+
+		// Assert: Eve sees neither, even though nothing checked her access.
+		scope(async {
+			identify(eve_id);
+			assert_eq!(count_visible(&pool, &blocks).await, 0);
+		})
+		.await;
+
+		// Assert: Connections go back to seeing everything after a request.
+		assert_eq!(count_visible(&pool, &blocks).await, 2);
+
+		// Clean up.
+		sqlx::query("DELETE FROM content.blocks WHERE id = ANY($1)")
+			.bind([*child_id.uuid(), *page_id.uuid()])
+			.execute(&pool)
+			.await
+			.unwrap();
+
+		sqlx::query("DELETE FROM auth.navigators WHERE id = ANY($1)")
+			.bind([*alice_id.uuid(), *bob_id.uuid(), *eve_id.uuid()])
+			.execute(&pool)
+			.await
+			.unwrap();
+	}
+}
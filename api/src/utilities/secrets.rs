@@ -0,0 +1,210 @@
+use std::fmt;
+use std::io::ErrorKind;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use std::process::Stdio;
+
+use thiserror::Error;
+
+/// The command that decrypts encrypted secret files, e.g.
+/// `age --decrypt --identity /run/keys/nuttyverse.txt`.
+const DECRYPT_COMMAND: &str = "SECRETS_DECRYPT_COMMAND";
+
+/// The extensions of secret files that are decrypted before use.
+const ENCRYPTED_EXTENSIONS: [&str; 2] = ["age", "enc"];
+
+/// A secret read from the environment, which is redacted when debugged.
+///
+/// A secret named `NAME` is read from the file at `NAME_FILE`, if it's set,
+/// so that it needn't sit in the environment. Files ending in `.age` or
+/// `.enc` are piped through `SECRETS_DECRYPT_COMMAND`, which may call out
+/// to age or a KMS. Otherwise, it's read from `NAME` itself.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+	/// Read a secret, if it's set, panicking if it can't be read. Secrets
+	/// are read at startup, where a missing secret file is a misconfiguration.
+	pub fn from_env(name: &str) -> Option<Self> {
+		Self::read(name, |name| std::env::var(name).ok())
+			.unwrap_or_else(|error| panic!("Failed to read secret {name}: {error}"))
+	}
+
+	/// Read a secret, looking up variables with `var`.
+	pub fn read(
+		name: &str,
+		var: impl Fn(&str) -> Option<String>,
+	) -> Result<Option<Self>, SecretError> {
+		let file_name = format!("{name}_FILE");
+
+		let Some(path) = var(&file_name) else {
+			return Ok(var(name).map(Self));
+		};
+
+		if var(name).is_some() {
+			return Err(SecretError::Ambiguous(file_name));
+		}
+
+		let path = Path::new(&path);
+		let contents = std::fs::read(path)?;
+
+		let is_encrypted = path
+			.extension()
+			.and_then(|extension| extension.to_str())
+			.is_some_and(|extension| ENCRYPTED_EXTENSIONS.contains(&extension));
+
+		let contents = match is_encrypted {
+			true => {
+				let command = var(DECRYPT_COMMAND).ok_or(SecretError::NoDecryptCommand)?;
+				decrypt(&command, &contents)?
+			}
+
+			false => contents,
+		};
+
+		let secret = String::from_utf8(contents).map_err(|_| SecretError::NotUtf8)?;
+
+		// Editors and `echo` leave a trailing newline that isn't part of it.
+		Ok(Some(Self(
+			secret.trim_end_matches(['\r', '\n']).to_string(),
+		)))
+	}
+
+	/// Get the secret's value.
+	pub fn expose(&self) -> &str {
+		&self.0
+	}
+
+	/// Take the secret's value.
+	pub fn into_string(self) -> String {
+		self.0
+	}
+
+	/// Take the secret's value as bytes.
+	pub fn into_bytes(self) -> Vec<u8> {
+		self.0.into_bytes()
+	}
+}
+
+impl fmt::Debug for Secret {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("Secret([REDACTED])")
+	}
+}
+
+/// Decrypt a secret by piping it through a shell command.
+fn decrypt(command: &str, ciphertext: &[u8]) -> Result<Vec<u8>, SecretError> {
+	let mut child = Command::new("sh")
+		.args(["-c", command])
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.spawn()?;
+
+	let mut stdin = child.stdin.take().expect("Decrypt command has no stdin");
+
+	// A command that fails early may not read everything; its status says why.
+	if let Err(error) = stdin.write_all(ciphertext)
+		&& error.kind() != ErrorKind::BrokenPipe
+	{
+		return Err(error.into());
+	}
+
+	drop(stdin);
+
+	let output = child.wait_with_output()?;
+
+	match output.status.success() {
+		true => Ok(output.stdout),
+		false => Err(SecretError::Decrypt(output.status.to_string())),
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum SecretError {
+	#[error("Set either {0} or the secret itself, not both")]
+	Ambiguous(String),
+
+	#[error("Failed to read secret file: {0}")]
+	Io(#[from] std::io::Error),
+
+	#[error("Encrypted secret files need SECRETS_DECRYPT_COMMAND to be set")]
+	NoDecryptCommand,
+
+	#[error("Failed to decrypt secret: {0}")]
+	Decrypt(String),
+
+	#[error("Secrets must be UTF-8")]
+	NotUtf8,
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+
+	use super::*;
+
+	fn read(vars: &[(&str, &str)]) -> Result<Option<Secret>, SecretError> {
+		let vars: HashMap<_, _> = vars.iter().copied().collect();
+		Secret::read("PEPPER", |name| vars.get(name).map(|v| v.to_string()))
+	}
+
+	#[test]
+	fn test_read() {
+		let directory = std::env::temp_dir().join(format!("secrets-{}", std::process::id()));
+		std::fs::create_dir_all(&directory).unwrap();
+
+		let plain = directory.join("pepper");
+		std::fs::write(&plain, "hunter2\n").unwrap();
+
+		let encrypted = directory.join("pepper.age");
+		std::fs::write(&encrypted, "2retnuh").unwrap();
+
+		let plain = plain.to_str().unwrap();
+		let encrypted = encrypted.to_str().unwrap();
+
+		// Assert: Secrets come from the environment or from files.
+		assert_eq!(read(&[]).unwrap(), None);
+		assert_eq!(
+			read(&[("PEPPER", "hunter2")]).unwrap().unwrap().expose(),
+			"hunter2"
+		);
+		assert_eq!(
+			read(&[("PEPPER_FILE", plain)]).unwrap().unwrap().expose(),
+			"hunter2"
+		);
+		assert!(matches!(
+			read(&[("PEPPER", "hunter2"), ("PEPPER_FILE", plain)]),
+			Err(SecretError::Ambiguous(_))
+		));
+		assert!(matches!(
+			read(&[("PEPPER_FILE", "/nonexistent/pepper")]),
+			Err(SecretError::Io(_))
+		));
+
+		// Assert: Encrypted files are decrypted by the configured command.
+		assert!(matches!(
+			read(&[("PEPPER_FILE", encrypted)]),
+			Err(SecretError::NoDecryptCommand)
+		));
+		assert_eq!(
+			read(&[("PEPPER_FILE", encrypted), (DECRYPT_COMMAND, "rev")])
+				.unwrap()
+				.unwrap()
+				.expose(),
+			"hunter2"
+		);
+		assert!(matches!(
+			read(&[("PEPPER_FILE", encrypted), (DECRYPT_COMMAND, "false")]),
+			Err(SecretError::Decrypt(_))
+		));
+
+		std::fs::remove_dir_all(&directory).unwrap();
+	}
+
+	#[test]
+	fn test_redacted() {
+		let secret = Secret("hunter2".to_string());
+		assert_eq!(format!("{secret:?}"), "Secret([REDACTED])");
+	}
+}
@@ -0,0 +1,354 @@
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use cookie::Cookie;
+use reqwest::StatusCode;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::header::COOKIE;
+use reqwest::header::SET_COOKIE;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::models::BlockContent;
+use crate::models::ContentBlock;
+use crate::models::FractionalIndex;
+use crate::seed::DEMO_PASSWORD;
+
+/// What to run against a server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkloadConfig {
+	pub base_url: String,
+
+	/// A navigator that can write new top-level blocks, e.g. the admin that
+	/// `nuttyverse-seed` registers.
+	pub name: String,
+	pub pass: String,
+
+	pub workers: usize,
+	pub duration: Duration,
+}
+
+impl Default for WorkloadConfig {
+	fn default() -> Self {
+		Self {
+			base_url: "http://localhost:3000".to_string(),
+			name: "ada_0".to_string(),
+			pass: DEMO_PASSWORD.to_string(),
+			workers: 8,
+			duration: Duration::from_secs(30),
+		}
+	}
+}
+
+impl WorkloadConfig {
+	/// Parse command-line flags, e.g. `--workers 16 --seconds 60`. Missing
+	/// flags take their defaults.
+	pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, WorkloadError> {
+		let mut config = Self::default();
+		let mut args = args.into_iter();
+
+		while let Some(flag) = args.next() {
+			let value = args
+				.next()
+				.ok_or_else(|| WorkloadError::MissingValue(flag.clone()))?;
+
+			let parse = |value: &str| {
+				value
+					.parse::<u64>()
+					.ok()
+					.filter(|value| *value > 0)
+					.ok_or_else(|| WorkloadError::InvalidValue(flag.clone(), value.to_string()))
+			};
+
+			match flag.as_str() {
+				"--url" => config.base_url = value.trim_end_matches('/').to_string(),
+				"--name" => config.name = value,
+				"--pass" => config.pass = value,
+				"--workers" => config.workers = parse(&value)? as usize,
+				"--seconds" => config.duration = Duration::from_secs(parse(&value)?),
+				_ => return Err(WorkloadError::UnknownFlag(flag)),
+			}
+		}
+
+		Ok(config)
+	}
+}
+
+/// Latencies of one kind of request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyStats {
+	pub count: usize,
+	pub p50: Duration,
+	pub p95: Duration,
+	pub p99: Duration,
+	pub max: Duration,
+}
+
+impl LatencyStats {
+	/// Summarize samples by nearest-rank percentiles.
+	pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+		if samples.is_empty() {
+			return Self::default();
+		}
+
+		samples.sort();
+
+		let percentile = |p: usize| {
+			let rank = (p * samples.len()).div_ceil(100).max(1);
+			samples[rank - 1]
+		};
+
+		Self {
+			count: samples.len(),
+			p50: percentile(50),
+			p95: percentile(95),
+			p99: percentile(99),
+			max: samples[samples.len() - 1],
+		}
+	}
+}
+
+/// The outcome of a workload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkloadReport {
+	pub saves: LatencyStats,
+	pub contexts: LatencyStats,
+	pub errors: usize,
+	pub elapsed: Duration,
+}
+
+impl WorkloadReport {
+	/// Get the successful requests per second.
+	pub fn throughput(&self) -> f64 {
+		(self.saves.count + self.contexts.count) as f64 / self.elapsed.as_secs_f64()
+	}
+}
+
+/// Run a synthetic workload: each worker saves its own paragraph on a shared
+/// page, then assembles the page's context, until time is up. The page is
+/// deleted afterwards.
+pub async fn run(config: &WorkloadConfig) -> Result<WorkloadReport, WorkloadError> {
+	let client = Client::login(config).await?;
+
+	let page = ContentBlock::now(
+		None,
+		FractionalIndex::start(),
+		BlockContent::Page {
+			title: "Workload".to_string(),
+		},
+	);
+
+	client.save(&page).await?;
+
+	let client = Arc::new(client);
+	let started = Instant::now();
+	let mut workers = vec![];
+
+	for worker in 0..config.workers {
+		let client = client.clone();
+		let page = page.clone();
+		let deadline = started + config.duration;
+
+		workers.push(tokio::spawn(async move {
+			client.work(worker, &page, deadline).await
+		}));
+	}
+
+	let (mut saves, mut contexts, mut errors) = (vec![], vec![], 0);
+
+	for worker in workers {
+		let samples = worker.await.map_err(|_| WorkloadError::WorkerPanicked)?;
+		saves.extend(samples.saves);
+		contexts.extend(samples.contexts);
+		errors += samples.errors;
+	}
+
+	let elapsed = started.elapsed();
+	client.delete(&page).await?;
+
+	Ok(WorkloadReport {
+		saves: LatencyStats::from_samples(saves),
+		contexts: LatencyStats::from_samples(contexts),
+		errors,
+		elapsed,
+	})
+}
+
+/// A worker's raw latencies.
+#[derive(Default)]
+struct Samples {
+	saves: Vec<Duration>,
+	contexts: Vec<Duration>,
+	errors: usize,
+}
+
+/// An HTTP client with a session.
+struct Client {
+	http: reqwest::Client,
+	base_url: String,
+	session: String,
+}
+
+impl Client {
+	async fn login(config: &WorkloadConfig) -> Result<Self, WorkloadError> {
+		// Sessions record the device, so requests need a user agent.
+		let http = reqwest::Client::builder()
+			.user_agent("nuttyverse-load")
+			.build()?;
+
+		let response = http
+			.post(format!("{}/navigator/login", config.base_url))
+			.header(CONTENT_TYPE, "application/json")
+			.body(json!({ "name": config.name, "pass": config.pass }).to_string())
+			.send()
+			.await?;
+
+		if !response.status().is_success() {
+			return Err(WorkloadError::Status("login", response.status()));
+		}
+
+		let session = response
+			.headers()
+			.get_all(SET_COOKIE)
+			.iter()
+			.filter_map(|header| Cookie::parse(header.to_str().ok()?.to_string()).ok())
+			.find(|cookie| cookie.name() == "session_id")
+			.ok_or(WorkloadError::MissingSession)?;
+
+		Ok(Self {
+			http,
+			base_url: config.base_url.clone(),
+			session: format!("session_id={}", session.value()),
+		})
+	}
+
+	async fn save(&self, block: &ContentBlock) -> Result<(), WorkloadError> {
+		let response = self
+			.http
+			.put(self.block_url(block))
+			.header(COOKIE, &self.session)
+			.header(CONTENT_TYPE, "application/json")
+			.body(serde_json::to_vec(block).map_err(WorkloadError::Serialize)?)
+			.send()
+			.await?;
+
+		match response.status().is_success() {
+			true => Ok(()),
+			false => Err(WorkloadError::Status("save", response.status())),
+		}
+	}
+
+	async fn context(&self, block: &ContentBlock) -> Result<(), WorkloadError> {
+		let response = self
+			.http
+			.get(format!("{}/context", self.block_url(block)))
+			.header(COOKIE, &self.session)
+			.send()
+			.await?;
+
+		match response.status().is_success() {
+			true => Ok(()),
+			false => Err(WorkloadError::Status("context", response.status())),
+		}
+	}
+
+	async fn delete(&self, block: &ContentBlock) -> Result<(), WorkloadError> {
+		let response = self
+			.http
+			.delete(format!("{}?unlink=true", self.block_url(block)))
+			.header(COOKIE, &self.session)
+			.send()
+			.await?;
+
+		match response.status().is_success() {
+			true => Ok(()),
+			false => Err(WorkloadError::Status("delete", response.status())),
+		}
+	}
+
+	/// Alternate between saving and reading until the deadline.
+	async fn work(&self, worker: usize, page: &ContentBlock, deadline: Instant) -> Samples {
+		let mut samples = Samples::default();
+		let mut paragraph = ContentBlock::now(
+			Some(*page.nutty_id()),
+			FractionalIndex::start(),
+			BlockContent::Paragraph {
+				markdown: String::new(),
+			},
+		);
+
+		for round in 0.. {
+			if Instant::now() >= deadline {
+				break;
+			}
+
+			paragraph.content = BlockContent::Paragraph {
+				markdown: format!("Worker {worker}, round {round}."),
+			};
+
+			let started = Instant::now();
+			match self.save(&paragraph).await {
+				Ok(()) => samples.saves.push(started.elapsed()),
+				Err(_) => samples.errors += 1,
+			}
+
+			let started = Instant::now();
+			match self.context(page).await {
+				Ok(()) => samples.contexts.push(started.elapsed()),
+				Err(_) => samples.errors += 1,
+			}
+		}
+
+		samples
+	}
+
+	fn block_url(&self, block: &ContentBlock) -> String {
+		format!("{}/content-block/{}", self.base_url, block.nutty_id().nid())
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum WorkloadError {
+	#[error("Unknown flag: {0}")]
+	UnknownFlag(String),
+
+	#[error("Missing a value for {0}")]
+	MissingValue(String),
+
+	#[error("Invalid value for {0}: {1}")]
+	InvalidValue(String, String),
+
+	#[error("Request failed: {0}")]
+	Request(#[from] reqwest::Error),
+
+	#[error("Failed to {0}: {1}")]
+	Status(&'static str, StatusCode),
+
+	#[error("The server didn't set a session cookie")]
+	MissingSession,
+
+	#[error("Failed to serialize content block: {0}")]
+	Serialize(#[source] serde_json::Error),
+
+	#[error("A worker panicked")]
+	WorkerPanicked,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_latency_stats() {
+		let samples = (1..=100).map(Duration::from_millis).collect();
+		let stats = LatencyStats::from_samples(samples);
+
+		assert_eq!(stats.count, 100);
+		assert_eq!(stats.p50, Duration::from_millis(50));
+		assert_eq!(stats.p95, Duration::from_millis(95));
+		assert_eq!(stats.p99, Duration::from_millis(99));
+		assert_eq!(stats.max, Duration::from_millis(100));
+		assert_eq!(LatencyStats::from_samples(vec![]), LatencyStats::default());
+	}
+}
@@ -0,0 +1,2311 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestClient;
+use common::TestServer;
+use nuttyverse_core::access::models::AccessRename;
+use nuttyverse_core::access::policy::AccessPolicy;
+use nuttyverse_core::access::policy::AccessPolicyDiff;
+use nuttyverse_core::access::policy::PolicyRole;
+use nuttyverse_core::content::api::CalendarFeed;
+use nuttyverse_core::integrations::chatbot::models::LinkCode;
+use nuttyverse_core::integrations::chatbot::telegram::SECRET_TOKEN_HEADER;
+use nuttyverse_core::models::BlockContent;
+use nuttyverse_core::models::ContentBlock;
+use nuttyverse_core::models::FractionalIndex;
+use nuttyverse_core::models::NuttyId;
+use nuttyverse_core::models::block_conversion::BlockConversion;
+use nuttyverse_core::models::block_group::RootBlockGroup;
+use nuttyverse_core::models::block_merge::BlockMerge;
+use nuttyverse_core::models::block_split::BlockSplit;
+use nuttyverse_core::models::capture::CapturedPage;
+use nuttyverse_core::models::content_diff::ContentDiff;
+use nuttyverse_core::models::content_diff::ParagraphDiff;
+use nuttyverse_core::models::find_replace::TextMatch;
+use nuttyverse_core::models::navigator_block::BlockedNavigator;
+use nuttyverse_core::models::nutty_id::PERMALINK_BASE_URL;
+use nuttyverse_core::models::onboarding::OnboardingProgress;
+use nuttyverse_core::models::onboarding::OnboardingStep;
+use nuttyverse_core::models::sync_changes::ChangeKind;
+use nuttyverse_core::models::sync_changes::SyncChanges;
+use nuttyverse_core::models::translation::LocalizedBlock;
+use nuttyverse_core::models::translation::Translation;
+use nuttyverse_core::utilities::api::body_limit::BodyLimits;
+use reqwest::Method;
+use serde::Deserialize;
+use serde_json::Value;
+use serde_json::json;
+
+/// The parts of a content context that these flows check.
+#[derive(Deserialize)]
+struct Context {
+	parent_id: Option<NuttyId>,
+	children_ids: Vec<NuttyId>,
+	reference_ids: Vec<NuttyId>,
+	backlink_ids: Vec<NuttyId>,
+}
+
+/// The parts of a listed session that these flows check.
+#[derive(Deserialize)]
+struct SessionSummary {
+	id: String,
+	current: bool,
+}
+
+/// The parts of a task that these flows check.
+#[derive(Deserialize)]
+struct TaskSummary {
+	breadcrumbs: Vec<Breadcrumb>,
+}
+
+#[derive(Deserialize)]
+struct Breadcrumb {
+	title: String,
+}
+
+/// The parts of an access request that these flows check.
+#[derive(Deserialize)]
+struct AccessRequestSummary {
+	nutty_id: NuttyId,
+	status: String,
+}
+
+/// The parts of a report that these flows check.
+#[derive(Deserialize)]
+struct ReportSummary {
+	nutty_id: NuttyId,
+	status: String,
+	resolution: Option<String>,
+}
+
+fn page(title: &str) -> ContentBlock {
+	ContentBlock::now(
+		None,
+		FractionalIndex::start(),
+		BlockContent::Page {
+			title: title.to_string(),
+		},
+	)
+}
+
+fn block_path(block: &ContentBlock) -> String {
+	format!("/content-block/{}", block.nutty_id().nid())
+}
+
+/// Share a block with a navigator, by name.
+async fn share(client: &TestClient, block: &ContentBlock, navigator_name: &str, level: &str) {
+	let share = json!({ "navigator_name": navigator_name, "level": level });
+
+	let (status, _) = client
+		.post::<_, Value>(&format!("{}/share", block_path(block)), &share)
+		.await;
+	assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_context_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	// Alice creates a page, a tag, and a paragraph under the page that links to the tag.
+	let parent = page("Parent");
+	let tag = page("Tag");
+
+	let paragraph = ContentBlock::now(
+		Some(*parent.nutty_id()),
+		FractionalIndex::start(),
+		BlockContent::Paragraph {
+			markdown: format!("See [[{}]].", tag.nutty_id().nid()),
+		},
+	);
+
+	for block in [&parent, &tag, &paragraph] {
+		let (status, _) = alice.put::<_, Value>(&block_path(block), block).await;
+		assert_eq!(status, StatusCode::OK);
+	}
+
+	// The paragraph's context includes its parent and reference.
+	let (status, context) = alice
+		.get::<Context>(&format!("{}/context", block_path(&paragraph)))
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let context = context.extract_object().unwrap();
+	assert_eq!(context.parent_id, Some(*parent.nutty_id()));
+	assert_eq!(context.reference_ids, vec![*tag.nutty_id()]);
+
+	// The page sees its child, and the tag sees its backlink.
+	let (_, context) = alice
+		.get::<Context>(&format!("{}/context", block_path(&parent)))
+		.await;
+	assert_eq!(
+		context.extract_object().unwrap().children_ids,
+		vec![*paragraph.nutty_id()]
+	);
+
+	// Navigation only needs the page's children and their titles.
+	let (status, context) = alice
+		.get::<Value>(&format!(
+			"{}/context?fields=blocks.title,children_ids",
+			block_path(&parent)
+		))
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let context = context.extract_object().unwrap();
+	let parent_key = json!(parent.nutty_id());
+	assert!(context.get("backlink_ids").is_none());
+	assert_eq!(
+		context["block_cache"][parent_key.as_str().unwrap()],
+		json!({ "content": { "kind": "Page", "title": "Parent" } })
+	);
+
+	let (status, _) = alice
+		.get::<Value>(&format!(
+			"{}/context?fields=blocks.color",
+			block_path(&parent)
+		))
+		.await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	let (_, context) = alice
+		.get::<Context>(&format!("{}/context", block_path(&tag)))
+		.await;
+	assert_eq!(
+		context.extract_object().unwrap().backlink_ids,
+		vec![*paragraph.nutty_id()]
+	);
+
+	// Saved blocks are annotated in the background, and editors see the
+	// warnings in the context.
+	let heading = ContentBlock::now(
+		Some(*parent.nutty_id()),
+		FractionalIndex::start(),
+		BlockContent::Heading {
+			markdown: String::new(),
+		},
+	);
+
+	let (status, _) = alice.put::<_, Value>(&block_path(&heading), &heading).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let heading_key = json!(heading.nutty_id());
+	let mut annotations = Value::Null;
+
+	for _ in 0..50 {
+		let (_, context) = alice
+			.get::<Value>(&format!(
+				"{}/context?fields=annotations",
+				block_path(&parent)
+			))
+			.await;
+
+		annotations =
+			context.extract_object().unwrap()["annotations"][heading_key.as_str().unwrap()].clone();
+
+		if !annotations.is_null() {
+			break;
+		}
+
+		tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+	}
+
+	assert_eq!(annotations[0]["kind"], "empty_heading");
+
+	// The context carries deep counts of the cached blocks.
+	let (_, context) = alice
+		.get::<Value>(&format!("{}/context?fields=stats", block_path(&parent)))
+		.await;
+
+	let parent_stats = &context.extract_object().unwrap()["stats"][parent_key.as_str().unwrap()];
+	assert!(parent_stats["descendant_count"].as_i64().unwrap() >= 2);
+
+	// Only admins can recount the stats, which match the incremental ones.
+	let (status, _) = bob
+		.post::<_, Value>("/admin/block-stats/check", &json!({}))
+		.await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, check) = alice
+		.post::<_, Value>("/admin/block-stats/check", &json!({}))
+		.await;
+	assert_eq!(status, StatusCode::OK);
+	assert!(
+		check.extract_object().unwrap()["checked_count"]
+			.as_i64()
+			.unwrap()
+			> 0
+	);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_raw_content_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let heading = ContentBlock::now(
+		None,
+		FractionalIndex::start(),
+		BlockContent::Heading {
+			markdown: "Heading".to_string(),
+		},
+	);
+
+	let (status, _) = alice.put::<_, Value>(&block_path(&heading), &heading).await;
+	assert_eq!(status, StatusCode::OK);
+
+	// Admins can read and write the raw content JSON, within the schema.
+	let raw_path = format!("{}/raw", block_path(&heading));
+
+	let (status, _) = bob.get::<Value>(&raw_path).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, raw) = alice.get::<Value>(&raw_path).await;
+	assert_eq!(status, StatusCode::OK);
+	assert_eq!(raw.extract_object().unwrap()["kind"], "Heading");
+
+	let (status, _) = alice
+		.put::<_, Value>(&raw_path, &json!({ "kind": "Unknown" }))
+		.await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	let (status, raw) = alice
+		.put::<_, Value>(&raw_path, &json!({ "kind": "Heading", "markdown": "Raw" }))
+		.await;
+	assert_eq!(status, StatusCode::OK);
+	assert_eq!(raw.extract_object().unwrap()["markdown"], "Raw");
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_checksums_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let parent = page("Parent");
+
+	let heading = ContentBlock::now(
+		Some(*parent.nutty_id()),
+		FractionalIndex::start(),
+		BlockContent::Heading {
+			markdown: "Heading".to_string(),
+		},
+	);
+
+	for block in [&parent, &heading] {
+		let (status, _) = alice.put::<_, Value>(&block_path(block), block).await;
+		assert_eq!(status, StatusCode::OK);
+	}
+
+	// Sync clients get a line per block in the subtree, to diff against.
+	let checksums_path = format!("/sync/checksums?root={}", parent.nutty_id().nid());
+
+	let (status, _) = bob.get::<Value>(&checksums_path).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, content_type, checksums) = alice.get_session_text(&checksums_path).await;
+	assert_eq!(status, StatusCode::OK);
+	assert_eq!(content_type, "application/x-ndjson");
+
+	let lines: Vec<Vec<String>> = checksums
+		.lines()
+		.map(|line| serde_json::from_str(line).unwrap())
+		.collect();
+
+	for block in [&parent, &heading] {
+		assert!(lines.iter().any(|line| line[0] == block.nutty_id().nid()));
+	}
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_rendering_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let parent = page("Parent");
+	let (status, _) = alice.put::<_, Value>(&block_path(&parent), &parent).await;
+	assert_eq!(status, StatusCode::OK);
+
+	// Rendered HTML is sanitized.
+	let script = ContentBlock::now(
+		Some(*parent.nutty_id()),
+		FractionalIndex::start(),
+		BlockContent::Paragraph {
+			markdown: "[Hi](javascript:alert(1)) <script>alert(2)</script>".to_string(),
+		},
+	);
+
+	alice.put::<_, Value>(&block_path(&script), &script).await;
+
+	let (status, rendered) = alice
+		.get::<Value>(&format!("{}/html", block_path(&script)))
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let rendered = rendered.extract_object().unwrap();
+	assert_eq!(
+		rendered["html"],
+		"<p><a rel=\"noopener noreferrer\">Hi</a> </p>\n"
+	);
+
+	// Unpublished blocks don't link to a preview image.
+	assert_eq!(rendered["og_image"], Value::Null);
+
+	// Once published, the rendered block links to its preview image, which
+	// is served to anyone with the signed link.
+	let publish_path = format!("{}/publish-revision", block_path(&script));
+	let (status, _) = alice.post::<_, Value>(&publish_path, &json!({})).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let (_, rendered) = alice
+		.get::<Value>(&format!("{}/html", block_path(&script)))
+		.await;
+	let rendered = rendered.extract_object().unwrap();
+
+	let og_image = rendered["og_image"].as_str().unwrap();
+	let og_image_attr = og_image.replace('&', "&amp;");
+	assert!(rendered["meta"].as_str().unwrap().contains(&og_image_attr));
+
+	let (status, content_type, _) = alice.get_text(og_image).await;
+	assert_eq!(status, StatusCode::OK);
+	assert_eq!(content_type, "image/png");
+
+	let (expires, _) = og_image
+		.split_once("expires=")
+		.and_then(|(_, query)| query.split_once('&'))
+		.unwrap();
+	let forged = format!(
+		"/og/{}.png?expires={expires}&signature=00",
+		script.nutty_id().nid()
+	);
+	let (status, _, _) = alice.get_text(&forged).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	// Blocks of kinds that no plugin registered are rejected.
+	let recipe = ContentBlock::now(
+		Some(*parent.nutty_id()),
+		FractionalIndex::start(),
+		BlockContent::Page {
+			title: String::new(),
+		},
+	);
+
+	let mut body = serde_json::to_value(&recipe).unwrap();
+	body["content"] = json!({ "kind": "Recipe", "dish": "Pho" });
+
+	let (status, _) = alice.put::<_, Value>(&block_path(&recipe), &body).await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_task_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let parent = page("Parent");
+	let (status, _) = alice.put::<_, Value>(&block_path(&parent), &parent).await;
+	assert_eq!(status, StatusCode::OK);
+
+	// Todos are rolled up as tasks, with their breadcrumbs.
+	let todo = ContentBlock::now(
+		Some(*parent.nutty_id()),
+		FractionalIndex::start(),
+		BlockContent::Todo {
+			markdown: "Review the draft due:2025-01-15".to_string(),
+			done: false,
+		},
+	);
+
+	alice.put::<_, Value>(&block_path(&todo), &todo).await;
+
+	let (status, tasks) = alice
+		.get::<TaskSummary>("/tasks?status=open&due_before=2025-02-01")
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let tasks = tasks.extract_objects();
+	assert_eq!(tasks.len(), 1);
+	assert_eq!(tasks[0].breadcrumbs[0].title, "Parent");
+
+	let toggle_path = format!("/tasks/{}/toggle", todo.nutty_id().nid());
+
+	let (status, _) = bob.post::<_, Value>(&toggle_path, &json!({})).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, _) = alice.post::<_, Value>(&toggle_path, &json!({})).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let (_, tasks) = alice.get::<TaskSummary>("/tasks?status=open").await;
+	assert!(tasks.extract_objects().is_empty());
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_property_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let todo = ContentBlock::now(
+		None,
+		FractionalIndex::start(),
+		BlockContent::Todo {
+			markdown: "Review the draft".to_string(),
+			done: false,
+		},
+	);
+
+	let (status, _) = alice.put::<_, Value>(&block_path(&todo), &todo).await;
+	assert_eq!(status, StatusCode::OK);
+
+	// Admins define properties, and blocks are validated against them.
+	let property = json!({ "name": "priority", "kind": "number" });
+
+	let (status, _) = bob.post::<_, Value>("/content/properties", &property).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, _) = alice
+		.post::<_, Value>("/content/properties", &property)
+		.await;
+	assert_eq!(status, StatusCode::CREATED);
+
+	let (status, _) = alice
+		.post::<_, Value>("/content/properties", &property)
+		.await;
+	assert_eq!(status, StatusCode::CONFLICT);
+
+	let prioritized = todo
+		.clone()
+		.with_properties([("priority".to_string(), json!("high"))].into());
+
+	let (status, _) = alice
+		.put::<_, Value>(&block_path(&todo), &prioritized)
+		.await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	let prioritized = todo
+		.clone()
+		.with_properties([("priority".to_string(), json!(1))].into());
+
+	let (status, _) = alice
+		.put::<_, Value>(&block_path(&todo), &prioritized)
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_sharing_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let parent = page("Parent");
+	let (status, _) = alice.put::<_, Value>(&block_path(&parent), &parent).await;
+	assert_eq!(status, StatusCode::OK);
+
+	// Only navigators who manage a block can see who has access to it.
+	let access_path = format!("{}/access", block_path(&parent));
+
+	let (status, _) = alice.get::<Value>(&access_path).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let (status, _) = bob.get::<Value>(&access_path).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	// Bob can neither read nor write Alice's blocks.
+	let (status, _) = bob
+		.get::<Value>(&format!("{}/context", block_path(&parent)))
+		.await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, _) = bob.put::<_, Value>(&block_path(&parent), &parent).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	// Alice shares the block with Bob by name, for viewing only.
+	let share_path = format!("{}/share", block_path(&parent));
+	let share = json!({ "navigator_name": "bobby", "level": "view" });
+
+	let (status, _) = bob.post::<_, Value>(&share_path, &share).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, _) = alice
+		.post::<_, Value>(
+			&share_path,
+			&json!({ "navigator_name": "nobody_at_all", "level": "view" }),
+		)
+		.await;
+	assert_eq!(status, StatusCode::NOT_FOUND);
+
+	let (status, _) = alice.post::<_, Value>(&share_path, &share).await;
+	assert_eq!(status, StatusCode::OK);
+
+	// Contexts list what the viewer can do with the block.
+	let capabilities_path = format!("{}/context?fields=capabilities", block_path(&parent));
+
+	let (status, context) = bob.get::<Value>(&capabilities_path).await;
+	assert_eq!(status, StatusCode::OK);
+	assert_eq!(
+		context.extract_object().unwrap()["capabilities"],
+		json!(["read"])
+	);
+
+	let (status, _) = bob.put::<_, Value>(&block_path(&parent), &parent).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_access_request_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let parent = page("Parent");
+	let (status, _) = alice.put::<_, Value>(&block_path(&parent), &parent).await;
+	assert_eq!(status, StatusCode::OK);
+
+	share(&alice, &parent, "bobby", "view").await;
+
+	// Bob requests edit access, which Alice approves.
+	let request_path = format!("{}/request-access", block_path(&parent));
+	let edit = json!({ "level": "edit" });
+
+	let (status, request) = bob
+		.post::<_, AccessRequestSummary>(&request_path, &edit)
+		.await;
+	assert_eq!(status, StatusCode::CREATED);
+
+	let request_id = request.extract_object().unwrap().nutty_id;
+
+	let (status, _) = bob.post::<_, Value>(&request_path, &edit).await;
+	assert_eq!(status, StatusCode::CONFLICT);
+
+	let requests_path = format!("{}/access-requests", block_path(&parent));
+
+	let (status, _) = bob.get::<Value>(&requests_path).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, requests) = alice.get::<AccessRequestSummary>(&requests_path).await;
+	assert_eq!(status, StatusCode::OK);
+	assert_eq!(requests.extract_objects().len(), 1);
+
+	let approve_path = format!("/access/requests/{request_id}/approve");
+
+	let (status, _) = bob.post::<_, Value>(&approve_path, &json!({})).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, request) = alice
+		.post::<_, AccessRequestSummary>(&approve_path, &json!({}))
+		.await;
+	assert_eq!(status, StatusCode::OK);
+	assert_eq!(request.extract_object().unwrap().status, "approved");
+
+	let (status, _) = alice.post::<_, Value>(&approve_path, &json!({})).await;
+	assert_eq!(status, StatusCode::NOT_FOUND);
+
+	let (status, _) = bob.put::<_, Value>(&block_path(&parent), &parent).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let capabilities_path = format!("{}/context?fields=capabilities", block_path(&parent));
+
+	let (_, context) = bob.get::<Value>(&capabilities_path).await;
+	assert_eq!(
+		context.extract_object().unwrap()["capabilities"],
+		json!(["read", "comment", "edit"])
+	);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_inherit_access_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let parent = page("Parent");
+	let (status, _) = alice.put::<_, Value>(&block_path(&parent), &parent).await;
+	assert_eq!(status, StatusCode::OK);
+
+	share(&alice, &parent, "bobby", "edit").await;
+
+	// A private note that opts out of inheriting access is hidden from Bob.
+	let note = ContentBlock::now(
+		Some(*parent.nutty_id()),
+		FractionalIndex::start(),
+		BlockContent::Paragraph {
+			markdown: "Private".to_string(),
+		},
+	)
+	.with_inherit_access(false);
+
+	let (status, _) = alice.put::<_, Value>(&block_path(&note), &note).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let context_path = format!("{}/context", block_path(&parent));
+
+	let (_, context) = alice.get::<Context>(&context_path).await;
+	assert!(
+		context
+			.extract_object()
+			.unwrap()
+			.children_ids
+			.contains(note.nutty_id())
+	);
+
+	let (status, context) = bob.get::<Context>(&context_path).await;
+	assert_eq!(status, StatusCode::OK);
+	assert!(
+		!context
+			.extract_object()
+			.unwrap()
+			.children_ids
+			.contains(note.nutty_id())
+	);
+
+	let (status, _) = bob
+		.get::<Value>(&format!("{}/context", block_path(&note)))
+		.await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, _) = bob.delete::<Value>(&block_path(&note)).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_publishing_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let parent = page("Parent");
+	let (status, _) = alice.put::<_, Value>(&block_path(&parent), &parent).await;
+	assert_eq!(status, StatusCode::OK);
+
+	share(&alice, &parent, "bobby", "view").await;
+
+	// Alice publishes the page, and keeps editing a draft that Bob can't see.
+	let publish_path = format!("{}/publish-revision", block_path(&parent));
+
+	let (status, _) = bob.post::<_, Value>(&publish_path, &json!({})).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, _) = alice.post::<_, Value>(&publish_path, &json!({})).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let mut draft = parent.clone();
+	draft.content = BlockContent::Page {
+		title: "Draft".to_string(),
+	};
+
+	let (status, _) = alice.put::<_, Value>(&block_path(&draft), &draft).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let html_path = format!("{}/html", block_path(&parent));
+
+	for (client, html) in [(&alice, "Draft"), (&bob, "Parent")] {
+		let (status, rendered) = client.get::<Value>(&html_path).await;
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(rendered.extract_object().unwrap()["html"], html);
+	}
+
+	// The block, its context, and search show Bob the published page too.
+	let (status, localized) = bob.get::<LocalizedBlock>(&block_path(&parent)).await;
+	assert_eq!(status, StatusCode::OK);
+	assert_eq!(
+		localized.extract_object().unwrap().block.content,
+		parent.content
+	);
+
+	let (status, context) = bob
+		.get::<Value>(&format!("{}/context", block_path(&parent)))
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let parent_key = json!(parent.nutty_id());
+	assert_eq!(
+		context.extract_object().unwrap()["block_cache"][parent_key.as_str().unwrap()]["content"],
+		json!({ "kind": "Page", "title": "Parent" })
+	);
+
+	for (client, found) in [(&alice, true), (&bob, false)] {
+		let (status, results) = client.get::<ContentBlock>("/content/search?q=draft").await;
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(
+			results
+				.extract_objects()
+				.iter()
+				.any(|block| block.nutty_id() == parent.nutty_id()),
+			found
+		);
+	}
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_slug_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let parent = page("Parent");
+	let (status, _) = alice.put::<_, Value>(&block_path(&parent), &parent).await;
+	assert_eq!(status, StatusCode::OK);
+
+	share(&alice, &parent, "bobby", "view").await;
+
+	// Alice gives the page a slug, then renames it; the old slug redirects.
+	let slug_path = format!("{}/slug", block_path(&parent));
+
+	let (status, _) = alice
+		.put::<_, Value>(&slug_path, &json!({ "slug": "Not a slug" }))
+		.await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	let (status, _) = bob
+		.put::<_, Value>(&slug_path, &json!({ "slug": "bobs" }))
+		.await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	for slug in ["parent", "renamed-parent"] {
+		let (status, _) = alice
+			.put::<_, Value>(&slug_path, &json!({ "slug": slug }))
+			.await;
+		assert_eq!(status, StatusCode::OK);
+	}
+
+	for path in [
+		"parent".to_string(),
+		"renamed-parent".to_string(),
+		parent.nutty_id().nid(),
+	] {
+		let (status, permalink) = bob.get::<Value>(&format!("/permalink/{path}")).await;
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(
+			permalink.extract_object().unwrap()["block_id"],
+			json!(parent.nutty_id())
+		);
+	}
+
+	let (status, _) = bob.get::<Value>("/permalink/unknown-page").await;
+	assert_eq!(status, StatusCode::NOT_FOUND);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_site_settings_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let parent = page("Parent");
+	let (status, _) = alice.put::<_, Value>(&block_path(&parent), &parent).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let (status, _) = alice
+		.put::<_, Value>(
+			&format!("{}/slug", block_path(&parent)),
+			&json!({ "slug": "parent" }),
+		)
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	// Alice sets up her site, which pages she publishes render with.
+	let (status, _) = alice.get::<Value>("/site-settings").await;
+	assert_eq!(status, StatusCode::NOT_FOUND);
+
+	let settings = |accent_color: &str, navigation_roots: Vec<String>| {
+		json!({
+			"title": "Nuttyverse",
+			"accent_color": accent_color,
+			"navigation_roots": navigation_roots,
+		})
+	};
+
+	for invalid in [
+		settings("orange", vec![]),
+		settings(
+			"#FF8800",
+			vec!["parent".to_string(), parent.nutty_id().nid()],
+		),
+	] {
+		let (status, _) = alice.put::<_, Value>("/site-settings", &invalid).await;
+		assert_eq!(status, StatusCode::BAD_REQUEST);
+	}
+
+	let (status, saved) = alice
+		.put::<_, Value>(
+			"/site-settings",
+			&settings("#FF8800", vec!["parent".to_string()]),
+		)
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let saved = saved.extract_object().unwrap();
+	assert_eq!(saved["accent_color"], "#ff8800");
+	assert_eq!(saved["navigation_root_ids"], json!([parent.nutty_id()]));
+
+	let home = ContentBlock::now_with_owner(
+		None,
+		alice_id,
+		FractionalIndex::start(),
+		BlockContent::Page {
+			title: "Home".to_string(),
+		},
+	);
+
+	let (status, _) = alice.put::<_, Value>(&block_path(&home), &home).await;
+	assert_eq!(status, StatusCode::OK);
+
+	for (block, site_title) in [(&home, json!("Nuttyverse")), (&parent, Value::Null)] {
+		let (status, rendered) = alice
+			.get::<Value>(&format!("{}/html", block_path(block)))
+			.await;
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(
+			rendered.extract_object().unwrap()["site"]["title"],
+			site_title
+		);
+	}
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_sync_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let parent = page("Parent");
+
+	let home = ContentBlock::now_with_owner(
+		None,
+		alice_id,
+		FractionalIndex::start(),
+		BlockContent::Page {
+			title: "Home".to_string(),
+		},
+	);
+
+	for block in [&parent, &home] {
+		let (status, _) = alice.put::<_, Value>(&block_path(block), block).await;
+		assert_eq!(status, StatusCode::OK);
+	}
+
+	// Alice's home page is the only block she owns.
+	let (status, groups) = alice
+		.get::<RootBlockGroup>("/navigator/me/blocks?group_by=root")
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let groups = groups.extract_objects();
+	assert_eq!(groups.len(), 1);
+	assert_eq!(groups[0].root_id, *home.nutty_id());
+	assert_eq!(groups[0].block_ids, vec![*home.nutty_id()]);
+
+	// Sync clients pull what changed in Alice's blocks since their cursor,
+	// deletions included.
+	let (status, changes) = alice.get::<SyncChanges>("/sync/changes").await;
+	assert_eq!(status, StatusCode::OK);
+
+	let changes = changes.extract_object().unwrap();
+	assert_eq!(changes.blocks.len(), 1);
+	assert_eq!(changes.blocks[0].nutty_id(), home.nutty_id());
+	assert!(!changes.has_more);
+
+	let scratch = ContentBlock::now_with_owner(
+		Some(*home.nutty_id()),
+		alice_id,
+		FractionalIndex::start(),
+		BlockContent::Paragraph {
+			markdown: "Scratch".to_string(),
+		},
+	);
+
+	alice.put::<_, Value>(&block_path(&scratch), &scratch).await;
+
+	let (status, _) = alice.delete::<Value>(&block_path(&scratch)).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let (status, changes) = alice
+		.get::<SyncChanges>(&format!("/sync/changes?since={}", changes.cursor))
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let changes = changes.extract_object().unwrap();
+	assert!(changes.blocks.is_empty());
+	assert_eq!(changes.tombstones.len(), 1);
+	assert_eq!(changes.tombstones[0].kind, ChangeKind::Block);
+	assert_eq!(changes.tombstones[0].id, *scratch.nutty_id());
+
+	let (status, _) = alice.get::<Value>("/sync/changes?since=nope").await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	// The same changes stream as server-sent events, under a subtree.
+	let stream_path = format!("/events/stream?root={}", home.nutty_id().nid());
+	let (status, stream) = alice.events(&stream_path, None).await;
+	assert_eq!(status, StatusCode::OK);
+	let mut stream = stream.unwrap();
+
+	let streamed = ContentBlock::now_with_owner(
+		Some(*home.nutty_id()),
+		alice_id,
+		FractionalIndex::start(),
+		BlockContent::Paragraph {
+			markdown: "Streamed".to_string(),
+		},
+	);
+
+	alice
+		.put::<_, Value>(&block_path(&streamed), &streamed)
+		.await;
+
+	let event = stream.next().await;
+	assert_eq!(event.event, "block_saved");
+	assert!(event.data.contains(&streamed.nutty_id().nid()));
+
+	let checkpoint = stream.next().await;
+	assert_eq!(checkpoint.event, "checkpoint");
+	assert!(checkpoint.id.is_some());
+	drop(stream);
+
+	// Reconnecting with the last event ID resumes from there.
+	let cursor = changes.cursor.to_string();
+	let (_, stream) = alice.events("/events/stream", Some(&cursor)).await;
+	let event = stream.unwrap().next().await;
+	assert_eq!(event.event, "block_saved");
+	assert!(event.data.contains(&streamed.nutty_id().nid()));
+
+	let (status, _) = alice.events("/events/stream", Some("nope")).await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_navigator_block_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let home = ContentBlock::now_with_owner(
+		None,
+		alice_id,
+		FractionalIndex::start(),
+		BlockContent::Page {
+			title: "Home".to_string(),
+		},
+	);
+
+	let (status, _) = alice.put::<_, Value>(&block_path(&home), &home).await;
+	assert_eq!(status, StatusCode::OK);
+
+	// Navigators that Alice blocks can't request access to her blocks.
+	let (status, _) = alice
+		.put::<_, Value>("/navigator/me/blocked/bobby", &json!({}))
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let (_, blocked) = alice.get::<BlockedNavigator>("/navigator/me/blocked").await;
+	assert_eq!(blocked.extract_objects()[0].name, "bobby");
+
+	let home_request_path = format!("{}/request-access", block_path(&home));
+	let view = json!({ "level": "view" });
+
+	let (status, _) = bob.post::<_, Value>(&home_request_path, &view).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, _) = alice.delete::<Value>("/navigator/me/blocked/bobby").await;
+	assert_eq!(status, StatusCode::OK);
+
+	let (status, _) = alice.delete::<Value>("/navigator/me/blocked/bobby").await;
+	assert_eq!(status, StatusCode::NOT_FOUND);
+
+	let (status, _) = bob.post::<_, Value>(&home_request_path, &view).await;
+	assert_eq!(status, StatusCode::CREATED);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_translation_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let home = ContentBlock::now_with_owner(
+		None,
+		alice_id,
+		FractionalIndex::start(),
+		BlockContent::Page {
+			title: "Home".to_string(),
+		},
+	);
+
+	// Alice translates her home page to Japanese, which readers who prefer
+	// Japanese are given instead.
+	let japanese = ContentBlock::now_with_owner(
+		Some(*home.nutty_id()),
+		alice_id,
+		FractionalIndex::start(),
+		BlockContent::Paragraph {
+			markdown: "ホーム".to_string(),
+		},
+	);
+
+	for block in [&home, &japanese] {
+		let (status, _) = alice.put::<_, Value>(&block_path(block), block).await;
+		assert_eq!(status, StatusCode::OK);
+	}
+
+	let home_translations_path = format!("{}/translations", block_path(&home));
+	let japanese_translations_path = format!("{}/translations", block_path(&japanese));
+
+	let (status, _) = alice
+		.put::<_, Value>(&home_translations_path, &json!({ "lang": "en" }))
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let translate = json!({ "lang": "ja", "translation_of": home.nutty_id().nid() });
+
+	let (status, translations) = alice
+		.put::<_, Translation>(&japanese_translations_path, &translate)
+		.await;
+	assert_eq!(status, StatusCode::OK);
+	assert_eq!(translations.extract_objects().len(), 2);
+
+	let taken = json!({ "lang": "en", "translation_of": home.nutty_id().nid() });
+
+	let (status, _) = alice
+		.put::<_, Value>(&japanese_translations_path, &taken)
+		.await;
+	assert_eq!(status, StatusCode::CONFLICT);
+
+	let (status, localized) = alice
+		.get::<LocalizedBlock>(&format!("{}?lang=ja-JP,en", block_path(&home)))
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let localized = localized.extract_object().unwrap();
+	assert_eq!(localized.block.nutty_id(), japanese.nutty_id());
+	assert_eq!(localized.lang.as_ref().unwrap().as_str(), "ja");
+
+	let (status, _) = alice
+		.get::<Value>(&format!("{}?lang=japanese!", block_path(&home)))
+		.await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	let (status, _) = bob
+		.get::<Value>(&format!("{}?lang=ja", block_path(&home)))
+		.await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, _) = alice.delete::<Value>(&japanese_translations_path).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let (_, translations) = alice.get::<Translation>(&home_translations_path).await;
+	assert_eq!(translations.extract_objects().len(), 1);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_deletion_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let parent = page("Parent");
+	let tag = page("Tag");
+
+	let paragraph = ContentBlock::now(
+		Some(*parent.nutty_id()),
+		FractionalIndex::start(),
+		BlockContent::Paragraph {
+			markdown: format!("See [[{}]].", tag.nutty_id().nid()),
+		},
+	);
+
+	for block in [&parent, &tag, &paragraph] {
+		let (status, _) = alice.put::<_, Value>(&block_path(block), block).await;
+		assert_eq!(status, StatusCode::OK);
+	}
+
+	// Deleting the tag warns about its backlink, which is unlinked.
+	let (status, deletion) = alice
+		.delete::<Value>(&format!("{}?unlink=true", block_path(&tag)))
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let deletion = deletion.extract_object().unwrap();
+	assert_eq!(deletion["inbound_link_count"], 1);
+	assert_eq!(deletion["unlinked_ids"], json!([paragraph.nutty_id()]));
+
+	let (status, _) = alice
+		.get::<Value>(&format!("{}/context", block_path(&tag)))
+		.await;
+	assert_ne!(status, StatusCode::OK);
+
+	let (_, context) = alice
+		.get::<Value>(&format!("{}/context", block_path(&paragraph)))
+		.await;
+	let context = context.extract_object().unwrap();
+	let paragraph_key = json!(paragraph.nutty_id());
+	assert_eq!(
+		context["block_cache"][paragraph_key.as_str().unwrap()]["content"]["markdown"],
+		"See Tag."
+	);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_find_replace_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	// Alice previews, then applies, a find and replace over her own blocks.
+	let draft = ContentBlock::now_with_owner(
+		None,
+		alice_id,
+		FractionalIndex::start(),
+		BlockContent::Paragraph {
+			markdown: "Draft of the Falcon plan.".to_string(),
+		},
+	);
+
+	let (status, _) = alice.put::<_, Value>(&block_path(&draft), &draft).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let find_replace = json!({ "pattern": "Falcon", "replacement": "Osprey", "dry_run": true });
+
+	let (status, matches) = alice
+		.post::<_, TextMatch>("/content/find-replace", &find_replace)
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let matches = matches.extract_objects();
+	assert_eq!(matches.len(), 1);
+	assert_eq!(matches[0].replacement, "Osprey");
+
+	let find_replace = json!({ "pattern": "Falcon", "replacement": "Osprey" });
+
+	let (status, _) = alice
+		.post::<_, Value>("/content/find-replace", &find_replace)
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let (_, context) = alice
+		.get::<Value>(&format!("{}/context", block_path(&draft)))
+		.await;
+	let context = context.extract_object().unwrap();
+	let draft_key = json!(draft.nutty_id());
+	assert_eq!(
+		context["block_cache"][draft_key.as_str().unwrap()]["content"]["markdown"],
+		"Draft of the Osprey plan."
+	);
+
+	let find_replace = json!({ "pattern": "(Falcon", "replacement": "Osprey" });
+
+	let (status, _) = alice
+		.post::<_, Value>("/content/find-replace", &find_replace)
+		.await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_diff_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let parent = page("Parent");
+
+	let draft = ContentBlock::now_with_owner(
+		None,
+		alice_id,
+		FractionalIndex::start(),
+		BlockContent::Paragraph {
+			markdown: "Draft of the Osprey plan.".to_string(),
+		},
+	);
+
+	for block in [&parent, &draft] {
+		let (status, _) = alice.put::<_, Value>(&block_path(block), block).await;
+		assert_eq!(status, StatusCode::OK);
+	}
+
+	// Alice diffs the draft against the Parent page.
+	let diff_path = format!(
+		"{}/diff?against={}",
+		block_path(&draft),
+		parent.nutty_id().nid()
+	);
+
+	let (status, diff) = alice.get::<ContentDiff>(&diff_path).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let diff = diff.extract_object().unwrap();
+	assert_eq!(diff.title.as_ref().unwrap().to, Some("Parent".to_string()));
+	assert_eq!(
+		diff.paragraphs,
+		vec![ParagraphDiff::Removed {
+			text: "Draft of the Osprey plan.".to_string()
+		}]
+	);
+
+	let (status, _) = bob.get::<Value>(&diff_path).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, _) = alice
+		.get::<Value>(&format!("{}/diff", block_path(&draft)))
+		.await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_search_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let draft = ContentBlock::now_with_owner(
+		None,
+		alice_id,
+		FractionalIndex::start(),
+		BlockContent::Paragraph {
+			markdown: "Draft of the Osprey plan.".to_string(),
+		},
+	);
+
+	let (status, _) = alice.put::<_, Value>(&block_path(&draft), &draft).await;
+	assert_eq!(status, StatusCode::OK);
+
+	// Alice searches her draft, stemmed, then as Japanese once she sets it so.
+	let (status, results) = alice.get::<ContentBlock>("/content/search?q=plans").await;
+	assert_eq!(status, StatusCode::OK);
+	assert!(
+		results
+			.extract_objects()
+			.iter()
+			.any(|block| block.nutty_id() == draft.nutty_id())
+	);
+
+	let language_path = format!("{}/language", block_path(&draft));
+
+	let (status, _) = bob
+		.put::<_, Value>(&language_path, &json!({ "language": "japanese" }))
+		.await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, update) = alice
+		.put::<_, Value>(&language_path, &json!({ "language": "japanese" }))
+		.await;
+	assert_eq!(status, StatusCode::OK);
+	assert_eq!(update.extract_object().unwrap()["updated_count"], 1);
+
+	let (_, results) = alice
+		.get::<ContentBlock>("/content/search?q=Osprey%20pl&language=japanese")
+		.await;
+	assert!(
+		results
+			.extract_objects()
+			.iter()
+			.any(|block| block.nutty_id() == draft.nutty_id())
+	);
+
+	let (status, _) = alice.get::<Value>("/content/search?q=%20").await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	// Alice lists her paragraphs; Bob has none, and unknown kinds are rejected.
+	for (client, listed) in [(&alice, true), (&bob, false)] {
+		let (status, results) = client
+			.get::<ContentBlock>("/content?kind=paragraph&owner=me")
+			.await;
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(
+			results
+				.extract_objects()
+				.iter()
+				.any(|block| block.nutty_id() == draft.nutty_id()),
+			listed
+		);
+	}
+
+	let (status, _) = alice.get::<Value>("/content?kind=code").await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_archive_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let parent = page("Parent");
+
+	let note = ContentBlock::now(
+		Some(*parent.nutty_id()),
+		FractionalIndex::start(),
+		BlockContent::Paragraph {
+			markdown: "Private".to_string(),
+		},
+	);
+
+	for block in [&parent, &note] {
+		let (status, _) = alice.put::<_, Value>(&block_path(block), block).await;
+		assert_eq!(status, StatusCode::OK);
+	}
+
+	// Alice archives the note, which hides it until she asks for it.
+	let archive_path = format!("{}/archive?subtree=true", block_path(&note));
+
+	let (status, _) = bob.put::<_, Value>(&archive_path, &json!({})).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, archival) = alice.put::<_, Value>(&archive_path, &json!({})).await;
+	assert_eq!(status, StatusCode::OK);
+	assert_eq!(
+		archival.extract_object().unwrap()["updated_ids"],
+		json!([note.nutty_id()])
+	);
+
+	let context_path = format!("{}/context", block_path(&parent));
+	let note_id = *note.nutty_id();
+
+	let shows_note = |path: String| {
+		let alice = &alice;
+
+		async move {
+			let (status, context) = alice.get::<Context>(&path).await;
+			assert_eq!(status, StatusCode::OK);
+
+			context
+				.extract_object()
+				.unwrap()
+				.children_ids
+				.contains(&note_id)
+		}
+	};
+
+	assert!(!shows_note(context_path.clone()).await);
+	assert!(shows_note(format!("{context_path}?include_archived=true")).await);
+
+	for (path, found) in [
+		("/content/search?q=private", false),
+		("/content/search?q=private&include_archived=true", true),
+	] {
+		let (status, results) = alice.get::<ContentBlock>(path).await;
+		assert_eq!(status, StatusCode::OK);
+		assert_eq!(
+			results
+				.extract_objects()
+				.iter()
+				.any(|block| block.nutty_id() == note.nutty_id()),
+			found
+		);
+	}
+
+	let (status, _) = alice.delete::<Value>(&archive_path).await;
+	assert_eq!(status, StatusCode::OK);
+	assert!(shows_note(context_path.clone()).await);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_ingest_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let parent = page("Parent");
+	let (status, _) = alice.put::<_, Value>(&block_path(&parent), &parent).await;
+	assert_eq!(status, StatusCode::OK);
+
+	// Alice files emails under the parent page; Bob has no inbox.
+	let inbox = json!({ "block_id": parent.nutty_id().nid() });
+
+	let (status, _) = alice.put::<_, Value>("/ingest/inbox", &inbox).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let email = |to: &str| {
+		json!({
+			"from": "carol@example.com",
+			"to": to,
+			"subject": "Reading list",
+			"text": "Start with the classics.",
+		})
+	};
+
+	let mail_service = server.client();
+	let secret = common::email_ingest_secret();
+
+	let (status, _) = mail_service
+		.post::<_, Value>("/ingest/email", &email("alice@inbox.test"))
+		.await;
+	assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+	let (status, filed) = mail_service
+		.post_signed::<_, ContentBlock>("/ingest/email", &email("alice+books@inbox.test"), &secret)
+		.await;
+	assert_eq!(status, StatusCode::CREATED);
+
+	let filed = filed.extract_object().unwrap();
+	assert_eq!(filed.parent_id, Some(*parent.nutty_id()));
+	assert!(filed.is_owned_by(&alice_id));
+
+	let (status, _) = mail_service
+		.post_signed::<_, Value>("/ingest/email", &email("bobby@inbox.test"), &secret)
+		.await;
+	assert_eq!(status, StatusCode::CONFLICT);
+
+	let (status, _) = mail_service
+		.post_signed::<_, Value>("/ingest/email", &email("nobody@inbox.test"), &secret)
+		.await;
+	assert_eq!(status, StatusCode::NOT_FOUND);
+
+	// Pages captured from the browser are filed in the inbox too. Fetching is
+	// disabled here, so they're filed without an unfurl or archive.
+	let capture = json!({
+		"url": "https://example.com/classics",
+		"selection": "Start here.",
+		"archive": true,
+	});
+
+	let (status, captured) = alice.post::<_, CapturedPage>("/capture", &capture).await;
+	assert_eq!(status, StatusCode::CREATED);
+
+	let captured = captured.extract_object().unwrap();
+	assert_eq!(captured.block.parent_id, Some(*parent.nutty_id()));
+	assert!(captured.unfurl.is_none() && captured.archive.is_none());
+
+	let (status, _) = alice
+		.post::<_, Value>("/capture", &json!({ "url": "javascript:alert(1)" }))
+		.await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_merge_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	// Alice merges a duplicate page into the original, and its backlinks follow.
+	let original = page("Reading List");
+	let duplicate = page("Reading List (copy)");
+	let mention = ContentBlock::now(
+		Some(*duplicate.nutty_id()),
+		FractionalIndex::start(),
+		BlockContent::Paragraph {
+			markdown: format!("Filed under [[{}]].", duplicate.nutty_id().nid()),
+		},
+	);
+
+	for block in [&original, &duplicate, &mention] {
+		let (status, _) = alice.put::<_, Value>(&block_path(block), block).await;
+		assert_eq!(status, StatusCode::OK);
+	}
+
+	let merge = json!({
+		"source_id": duplicate.nutty_id().nid(),
+		"target_id": original.nutty_id().nid(),
+	});
+
+	let (status, _) = bob.post::<_, Value>("/content/merge", &merge).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, merged) = alice.post::<_, BlockMerge>("/content/merge", &merge).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let merged = merged.extract_object().unwrap();
+	assert_eq!(merged.moved_ids, vec![*mention.nutty_id()]);
+	assert_eq!(merged.relinked_ids, vec![*mention.nutty_id()]);
+
+	let (_, context) = alice
+		.get::<Context>(&format!("{}/context", block_path(&original)))
+		.await;
+	let context = context.extract_object().unwrap();
+	assert!(context.children_ids.contains(mention.nutty_id()));
+	assert!(context.backlink_ids.contains(mention.nutty_id()));
+
+	let (status, _) = alice
+		.get::<Value>(&format!("{}/context", block_path(&duplicate)))
+		.await;
+	assert_ne!(status, StatusCode::OK);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_convert_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let original = page("Reading List");
+	let mention = ContentBlock::now(
+		Some(*original.nutty_id()),
+		FractionalIndex::start(),
+		BlockContent::Paragraph {
+			markdown: "Filed under the reading list.".to_string(),
+		},
+	);
+
+	for block in [&original, &mention] {
+		let (status, _) = alice.put::<_, Value>(&block_path(block), block).await;
+		assert_eq!(status, StatusCode::OK);
+	}
+
+	// Alice promotes the mention to a page, which keeps its identity.
+	let convert_path = format!("{}/convert", block_path(&mention));
+
+	let (status, _) = bob
+		.post::<_, Value>(&convert_path, &json!({ "kind": "Page" }))
+		.await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, converted) = alice
+		.post::<_, BlockConversion>(&convert_path, &json!({ "kind": "Page" }))
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let converted = converted.extract_object().unwrap();
+	assert_eq!(converted.block.nutty_id(), mention.nutty_id());
+	assert!(matches!(converted.block.content, BlockContent::Page { .. }));
+	assert!(converted.overflow.is_none());
+
+	let (status, _) = alice
+		.post::<_, Value>(&convert_path, &json!({ "kind": "Page" }))
+		.await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_split_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let original = page("Reading List");
+
+	// Alice breaks a line in a paragraph, then deletes the break again.
+	let line = ContentBlock::now(
+		Some(*original.nutty_id()),
+		FractionalIndex::start(),
+		BlockContent::Paragraph {
+			markdown: "Read this. Then that.".to_string(),
+		},
+	);
+
+	for block in [&original, &line] {
+		let (status, _) = alice.put::<_, Value>(&block_path(block), block).await;
+		assert_eq!(status, StatusCode::OK);
+	}
+
+	let (status, split) = alice
+		.post::<_, BlockSplit>(
+			&format!("{}/split", block_path(&line)),
+			&json!({ "offset": 10 }),
+		)
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let split = split.extract_object().unwrap();
+	assert!(matches!(
+		split.new_block.content,
+		BlockContent::Paragraph { ref markdown } if markdown == " Then that."
+	));
+
+	let merge_path = format!("{}/merge-with-next", block_path(&line));
+
+	let (status, _) = bob.post::<_, Value>(&merge_path, &json!({})).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, merged) = alice.post::<_, BlockMerge>(&merge_path, &json!({})).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let merged = merged.extract_object().unwrap();
+	assert_eq!(merged.source_id, *split.new_block.nutty_id());
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_reorder_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+	server.assign_global_role(&alice_id, "admin").await;
+
+	let original = page("Reading List");
+
+	let [mention, line] = ["Filed under the reading list.", "Read this."].map(|markdown| {
+		ContentBlock::now(
+			Some(*original.nutty_id()),
+			FractionalIndex::start(),
+			BlockContent::Paragraph {
+				markdown: markdown.to_string(),
+			},
+		)
+	});
+
+	for block in [&original, &mention, &line] {
+		let (status, _) = alice.put::<_, Value>(&block_path(block), block).await;
+		assert_eq!(status, StatusCode::OK);
+	}
+
+	// Alice moves the line above the mention, listing every child at once.
+	let reorder_path = format!("{}/reorder", block_path(&original));
+	let reorder = json!({ "child_ids": [line.nutty_id().nid(), mention.nutty_id().nid()] });
+
+	let (status, _) = bob.post::<_, Value>(&reorder_path, &reorder).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, _) = alice
+		.post::<_, Value>(
+			&reorder_path,
+			&json!({ "child_ids": [line.nutty_id().nid()] }),
+		)
+		.await;
+	assert_eq!(status, StatusCode::CONFLICT);
+
+	let (status, _) = alice.post::<_, Value>(&reorder_path, &reorder).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let (_, context) = alice
+		.get::<Context>(&format!("{}/context", block_path(&original)))
+		.await;
+	let context = context.extract_object().unwrap();
+	assert_eq!(
+		context.children_ids,
+		vec![*line.nutty_id(), *mention.nutty_id()]
+	);
+
+	// The sidebar outlines the tree in that order, a line per block.
+	let outline_path = format!("/content/outline?root={}", original.nutty_id().nid());
+
+	let (status, _) = bob.get::<Value>(&outline_path).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, _) = alice.get::<Value>(&format!("{outline_path}&limit=0")).await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	let (status, content_type, outline) = alice.get_session_text(&outline_path).await;
+	assert_eq!(status, StatusCode::OK);
+	assert_eq!(content_type, "application/x-ndjson");
+
+	let lines: Vec<Vec<Value>> = outline
+		.lines()
+		.map(|line| serde_json::from_str(line).unwrap())
+		.collect();
+
+	let ids: Vec<_> = lines.iter().map(|line| line[0].clone()).collect();
+	assert_eq!(
+		ids,
+		[&original, &line, &mention].map(|block| json!(block.nutty_id().nid()))
+	);
+	assert_eq!(lines[0][2..], [json!(0), json!(2)]);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_session_flow() {
+	let server = TestServer::spawn_with_onboarding().await;
+	let client = server.client();
+
+	// Anonymous requests are rejected.
+	let (status, _) = client.get::<Value>("/navigator/me").await;
+	assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+	// Register and log in.
+	let credentials = json!({ "name": "carol", "pass": "password123" });
+	client.post::<_, Value>("/navigator", &credentials).await;
+	client
+		.post::<_, Value>("/navigator/login", &credentials)
+		.await;
+
+	// The current session is listed and can be labeled.
+	let (status, sessions) = client.get::<SessionSummary>("/navigator/sessions").await;
+	assert_eq!(status, StatusCode::OK);
+
+	let sessions = sessions.extract_objects();
+	assert_eq!(sessions.len(), 1);
+	assert!(sessions[0].current);
+
+	let session_id = &sessions[0].id;
+
+	let (status, session) = client
+		.put::<_, Value>(
+			&format!("/navigator/sessions/{session_id}/label"),
+			&json!({ "label": "Laptop" }),
+		)
+		.await;
+	assert_eq!(status, StatusCode::OK);
+	assert_eq!(session.extract_object().unwrap()["label"], "Laptop");
+
+	// New navigators start with a welcome page of their own.
+	let (status, pages) = client
+		.get::<ContentBlock>("/content?kind=page&owner=me")
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let pages = pages.extract_objects();
+	assert_eq!(pages.len(), 1);
+	assert!(matches!(
+		&pages[0].content,
+		BlockContent::Page { title } if title == "Welcome to the Nuttyverse"
+	));
+
+	// Onboarding steps are listed, and can be completed.
+	let (status, steps) = client
+		.get::<OnboardingProgress>("/navigator/me/onboarding")
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let steps = steps.extract_objects();
+	assert_eq!(steps.len(), OnboardingStep::ALL.len());
+	assert!(steps.iter().all(|step| step.completed_at.is_none()));
+
+	let (status, steps) = client
+		.post::<_, OnboardingProgress>("/navigator/me/onboarding/read_welcome", &json!({}))
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let steps = steps.extract_objects();
+	assert_eq!(steps[0].step, OnboardingStep::ReadWelcome);
+	assert!(steps[0].completed_at.is_some());
+
+	let (status, _) = client
+		.post::<_, Value>("/navigator/me/onboarding/read_manual", &json!({}))
+		.await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	// An export of the navigator's data is assembled in the background.
+	let (status, export) = client
+		.post::<_, Value>("/navigator/me/export", &json!({}))
+		.await;
+	assert_eq!(status, StatusCode::ACCEPTED);
+
+	let export_id = export.extract_object().unwrap()["nutty_id"]
+		.as_str()
+		.unwrap()
+		.to_string();
+
+	let mut download_url = None;
+
+	for _ in 0..50 {
+		let (status, export) = client
+			.get::<Value>(&format!("/navigator/me/export/{export_id}"))
+			.await;
+		assert_eq!(status, StatusCode::OK);
+
+		if let Some(url) = export.extract_object().unwrap()["download_url"].as_str() {
+			download_url = Some(url.to_string());
+			break;
+		}
+
+		tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+	}
+
+	// The signed link downloads the archive without a session.
+	let download_url = download_url.expect("Export was never assembled");
+	let (status, _, archive) = client.get_text(&download_url).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let archive: Value = serde_json::from_str(&archive).unwrap();
+	assert_eq!(archive["navigator"]["name"], "carol");
+	assert_eq!(archive["sessions"][0]["label"], "Laptop");
+
+	// Tampered links are rejected.
+	let tampered = format!("{}0", download_url.trim_end_matches(|c: char| c != '='));
+	let (status, _, _) = client.get_text(&tampered).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	// Logging out clears the cookie and ends the session.
+	let (status, _) = client
+		.post::<_, Value>("/navigator/logout", &json!({}))
+		.await;
+	assert_eq!(status, StatusCode::OK);
+	assert!(!client.has_cookie("session_id"));
+
+	let (status, _) = client.get::<Value>("/navigator/me").await;
+	assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_moderation_flow() {
+	let server = TestServer::spawn().await;
+	let (alice, alice_id) = server.register("alice").await;
+	let (bob, _) = server.register("bobby").await;
+
+	// Make Alice an admin, and therefore a moderator.
+	server.assign_global_role(&alice_id, "admin").await;
+
+	// Alice creates a page, which she can view and report; Bob can do neither.
+	let spam = page("Spam");
+	let (status, _) = alice.put::<_, Value>(&block_path(&spam), &spam).await;
+	assert_eq!(status, StatusCode::OK);
+
+	let report_path = format!("{}/report", block_path(&spam));
+	let reason = json!({ "reason": "Unsolicited advertising." });
+
+	let (status, _) = bob.post::<_, Value>(&report_path, &reason).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, report) = alice.post::<_, ReportSummary>(&report_path, &reason).await;
+	assert_eq!(status, StatusCode::CREATED);
+	let report_id = report.extract_object().unwrap().nutty_id;
+
+	// Reports are not duplicated while open.
+	let (status, _) = alice.post::<_, Value>(&report_path, &reason).await;
+	assert_eq!(status, StatusCode::CONFLICT);
+
+	// Only moderators can review the queue.
+	let (status, _) = bob.get::<Value>("/admin/reports").await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, queue) = alice.get::<ReportSummary>("/admin/reports").await;
+	assert_eq!(status, StatusCode::OK);
+	assert_eq!(queue.extract_objects().len(), 1);
+
+	// Resolve the report by hiding the page, which moderators can still see.
+	let (status, _) = alice
+		.put::<_, Value>(
+			&format!("/admin/reports/{report_id}"),
+			&json!({ "action": "hide", "resolution": "Removed as spam." }),
+		)
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let (status, _) = alice
+		.get::<Value>(&format!("{}/context", block_path(&spam)))
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let (_, queue) = alice.get::<ReportSummary>("/admin/reports").await;
+	assert!(queue.extract_objects().is_empty());
+
+	// The reporter sees the outcome.
+	let (_, reports) = alice.get::<ReportSummary>("/navigator/reports").await;
+	let reports = reports.extract_objects();
+	assert_eq!(reports[0].status, "actioned");
+	assert_eq!(reports[0].resolution.as_deref(), Some("Removed as spam."));
+
+	// Only admins rename roles, and grants move to the new name with them.
+	let rename = json!({ "name": "administrator" });
+
+	let (status, _) = bob
+		.post::<_, Value>("/admin/roles/admin/rename", &rename)
+		.await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, renamed) = alice
+		.post::<_, AccessRename>("/admin/roles/admin/rename", &rename)
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let renamed = renamed.extract_object().unwrap();
+	assert_eq!(renamed.renamed_by, Some(alice_id));
+	assert!(renamed.grant_count > 1);
+
+	let (status, _) = alice.get::<Value>("/admin/reports").await;
+	assert_eq!(status, StatusCode::OK);
+
+	// Renames are recorded; missing and taken names are rejected.
+	let (status, renames) = alice.get::<AccessRename>("/admin/access-renames").await;
+	assert_eq!(status, StatusCode::OK);
+	assert_eq!(renames.extract_objects(), vec![renamed]);
+
+	let (status, _) = alice
+		.post::<_, Value>("/admin/roles/admin/rename", &rename)
+		.await;
+	assert_eq!(status, StatusCode::NOT_FOUND);
+
+	let (status, _) = alice
+		.post::<_, Value>(
+			"/admin/roles/block_viewer/rename",
+			&json!({ "name": "block_editor" }),
+		)
+		.await;
+	assert_eq!(status, StatusCode::CONFLICT);
+
+	// Only admins export the access policy, which has the renamed role.
+	let (status, _) = bob.get::<Value>("/access/policy").await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, content_type, yaml) = alice.get_session_text("/access/policy?format=yaml").await;
+	assert_eq!(status, StatusCode::OK);
+	assert_eq!(content_type, "application/yaml");
+
+	let mut policy = AccessPolicy::parse(&yaml).unwrap();
+	assert!(policy.roles.iter().any(|role| role.name == "administrator"));
+
+	// Previewing a new role lists its changes without making them.
+	policy.roles.push(PolicyRole {
+		name: "curator".to_string(),
+		description: "Can view all content blocks.".to_string(),
+		permissions: vec!["content_blocks:read:all".to_string()],
+	});
+	let policy = policy.to_yaml().unwrap();
+
+	let (status, diff) = alice
+		.put_text::<AccessPolicyDiff>("/access/policy?dry_run=true", &policy)
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let diff = diff.extract_object().unwrap();
+	assert!(!diff.applied);
+	assert_eq!(diff.changes.len(), 2);
+
+	let (_, exported) = alice.get::<AccessPolicy>("/access/policy").await;
+	let exported = exported.extract_object().unwrap();
+	assert!(!exported.roles.iter().any(|role| role.name == "curator"));
+
+	// Applying it makes them, and applying it again changes nothing.
+	let (_, applied) = alice
+		.put_text::<AccessPolicyDiff>("/access/policy", &policy)
+		.await;
+	let applied = applied.extract_object().unwrap();
+	assert!(applied.applied);
+	assert_eq!(applied.changes, diff.changes);
+
+	let (_, reapplied) = alice
+		.put_text::<AccessPolicyDiff>("/access/policy", &policy)
+		.await;
+	assert!(reapplied.extract_object().unwrap().changes.is_empty());
+
+	// Policies larger than the system routes' limit round-trip too.
+	let (_, exported) = alice.get::<AccessPolicy>("/access/policy").await;
+	let mut large = exported.extract_object().unwrap().clone();
+	let permissions: Vec<String> = large.permissions.iter().map(|p| p.name.clone()).collect();
+
+	for i in 0..20 {
+		large.roles.push(PolicyRole {
+			name: format!("auditor_{i}"),
+			description: "Can do everything, for auditing.".to_string(),
+			permissions: permissions.clone(),
+		});
+	}
+
+	let large = large.to_yaml().unwrap();
+	assert!(large.len() > BodyLimits::default().system);
+
+	let (status, applied) = alice
+		.put_text::<AccessPolicyDiff>("/access/policy", &large)
+		.await;
+	assert_eq!(status, StatusCode::OK);
+	assert!(applied.extract_object().unwrap().applied);
+
+	let (_, exported) = alice.get::<AccessPolicy>("/access/policy").await;
+	let exported = exported.extract_object().unwrap();
+	assert!(exported.roles.iter().any(|role| role.name == "auditor_19"));
+
+	// Roles can't have permissions that don't exist.
+	let (status, _) = alice
+		.put_text::<Value>(
+			"/access/policy",
+			"roles: [{ name: curator, description: Curates., permissions: [content_blocks:curate] }]",
+		)
+		.await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_chatbot_flow() {
+	let server = TestServer::spawn().await;
+	let (dave, dave_id) = server.register("dave").await;
+	let telegram = server.client();
+
+	// Make Dave an admin, so that he can read back his notes.
+	server.assign_global_role(&dave_id, "admin").await;
+
+	let webhook = "/integrations/telegram/webhook";
+	let secret = (SECRET_TOKEN_HEADER, common::TELEGRAM_SECRET);
+
+	let update = |text: &str| {
+		json!({
+			"update_id": 1,
+			"message": {
+				"chat": { "id": 42 },
+				"from": { "id": 7, "is_bot": false },
+				"text": text,
+			},
+		})
+	};
+
+	// Updates without the secret token are rejected.
+	let (status, _) = telegram
+		.post_raw(webhook, &update("Hi"), (SECRET_TOKEN_HEADER, "wrong"))
+		.await;
+	assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+	// Unlinked chat users are told how to link.
+	let (status, reply) = telegram
+		.post_raw(webhook, &update("Buy oat milk"), secret)
+		.await;
+	assert_eq!(status, StatusCode::OK);
+	assert_eq!(reply["method"], "sendMessage");
+	assert_eq!(reply["chat_id"], "42");
+	assert!(reply["text"].as_str().unwrap().contains("/link"));
+
+	// Dave links the chat with a code.
+	let (status, link_code) = dave
+		.post::<_, LinkCode>("/integrations/chatbot/link-code", &json!({}))
+		.await;
+	assert_eq!(status, StatusCode::CREATED);
+
+	let code = &link_code.extract_object().unwrap().code;
+
+	let (_, reply) = telegram
+		.post_raw(webhook, &update(&format!("/link {code}")), secret)
+		.await;
+	assert!(reply["text"].as_str().unwrap().starts_with("Linked!"));
+
+	let (_, reply) = telegram
+		.post_raw(webhook, &update(&format!("/link {code}")), secret)
+		.await;
+	assert!(reply["text"].as_str().unwrap().contains("invalid"));
+
+	// Messages are added to today's daily note, which is created on demand.
+	let mut note_ids = vec![];
+
+	for text in ["Buy oat milk", "Call the vet"] {
+		let (_, reply) = telegram.post_raw(webhook, &update(text), secret).await;
+		let reply = reply["text"].as_str().unwrap();
+		assert!(reply.contains(PERMALINK_BASE_URL));
+
+		let nid = reply.rsplit('/').next().unwrap();
+		let (status, context) = dave
+			.get::<Context>(&format!("/content-block/{nid}/context"))
+			.await;
+		assert_eq!(status, StatusCode::OK);
+
+		note_ids.push(context.extract_object().unwrap().parent_id.unwrap());
+	}
+
+	assert_eq!(note_ids[0], note_ids[1]);
+
+	let (_, note) = dave
+		.get::<Context>(&format!("/content-block/{}/context", note_ids[0].nid()))
+		.await;
+	assert_eq!(note.extract_object().unwrap().children_ids.len(), 2);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_calendar_feed_flow() {
+	let server = TestServer::spawn().await;
+	let (erin, erin_id) = server.register("erin").await;
+	let calendar_app = server.client();
+	server.assign_global_role(&erin_id, "admin").await;
+
+	// Erin writes a daily note with a todo that's due tomorrow.
+	let today = chrono::Utc::now().date_naive();
+	let tomorrow = today.succ_opt().unwrap();
+	let note = page(&today.to_string());
+
+	let todo = ContentBlock::now(
+		Some(*note.nutty_id()),
+		FractionalIndex::start(),
+		BlockContent::Todo {
+			markdown: format!("Renew passport due:{tomorrow}"),
+			done: false,
+		},
+	);
+
+	for block in [&note, &todo] {
+		let (status, _) = erin.put::<_, Value>(&block_path(block), block).await;
+		assert_eq!(status, StatusCode::OK);
+	}
+
+	// Erin gets the feed's URL.
+	let (status, feed) = erin.get::<CalendarFeed>("/content/calendar/feed").await;
+	assert_eq!(status, StatusCode::OK);
+
+	let feed = feed.extract_object().unwrap();
+	assert!(feed.path.ends_with(&feed.token));
+
+	// The feed can't be read without a valid token.
+	let (status, _, _) = calendar_app.get_text("/calendar.ics?token=nope").await;
+	assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+	// A calendar app subscribes with the token alone.
+	let (status, content_type, ics) = calendar_app.get_text(&feed.path).await;
+	assert_eq!(status, StatusCode::OK);
+	assert!(content_type.starts_with("text/calendar"));
+
+	let date = |date: chrono::NaiveDate| date.format("%Y%m%d").to_string();
+	assert!(ics.contains(&format!("SUMMARY:{today}\r\n")));
+	assert!(ics.contains(&format!("DTSTART;VALUE=DATE:{}\r\n", date(today))));
+	assert!(ics.contains("SUMMARY:Due: Renew passport\r\n"));
+	assert!(ics.contains(&format!("DTSTART;VALUE=DATE:{}\r\n", date(tomorrow))));
+	assert!(ics.contains(&format!("URL:{}\r\n", todo.nutty_id().permalink())));
+
+	// Erin regenerates the feed, which revokes the old token.
+	let (status, regenerated) = erin
+		.post::<_, CalendarFeed>("/content/calendar/feed", &json!({}))
+		.await;
+	assert_eq!(status, StatusCode::OK);
+
+	let regenerated = regenerated.extract_object().unwrap();
+	assert_ne!(regenerated.token, feed.token);
+
+	let (status, _, _) = calendar_app.get_text(&feed.path).await;
+	assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+	let (status, _, _) = calendar_app.get_text(&regenerated.path).await;
+	assert_eq!(status, StatusCode::OK);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_paste_flow() {
+	let server = TestServer::spawn().await;
+	let (grace, grace_id) = server.register("grace").await;
+	let (heidi, _) = server.register("heidi").await;
+	server.assign_global_role(&grace_id, "admin").await;
+
+	// Grace creates a page to paste into.
+	let notes = page("Notes");
+	let (status, _) = grace.put::<_, Value>(&block_path(&notes), &notes).await;
+	assert_eq!(status, StatusCode::OK);
+
+	// Grace pastes rich text under the page, which becomes blocks.
+	let paste_path = format!("{}/paste", block_path(&notes));
+	let paste = json!({ "html": "<p>Buy eggs</p><p>Buy <b>milk</b></p>" });
+
+	let (status, pasted) = grace.post::<_, ContentBlock>(&paste_path, &paste).await;
+	assert_eq!(status, StatusCode::CREATED);
+
+	let pasted = pasted.extract_objects();
+	assert!(!pasted.is_empty());
+	assert!(
+		pasted
+			.iter()
+			.all(|block| block.parent_id == Some(*notes.nutty_id()))
+	);
+
+	let (_, context) = grace
+		.get::<Context>(&format!("{}/context", block_path(&notes)))
+		.await;
+	assert_eq!(
+		context.extract_object().unwrap().children_ids.len(),
+		pasted.len()
+	);
+
+	// Heidi can't paste into Grace's page, and nothing is pasted from nothing.
+	let (status, _) = heidi.post::<_, Value>(&paste_path, &paste).await;
+	assert_eq!(status, StatusCode::FORBIDDEN);
+
+	let (status, _) = grace
+		.post::<_, Value>(&paste_path, &json!({ "html": "<p> </p>" }))
+		.await;
+	assert_eq!(status, StatusCode::BAD_REQUEST);
+
+	server.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_body_limit_flow() {
+	let server = TestServer::spawn().await;
+	let frank = server.client();
+	let limits = BodyLimits::default();
+
+	let assert_too_large = |(status, content_type, problem): (StatusCode, String, Value)| {
+		assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+		assert_eq!(content_type, "application/problem+json");
+		assert_eq!(problem["status"], 413);
+		assert_eq!(problem["title"], "Payload Too Large");
+	};
+
+	// Registering with an oversized body is rejected by the auth routes' limit.
+	let credentials = json!({ "name": "frank", "pass": "a".repeat(limits.auth) });
+	assert_too_large(
+		frank
+			.send_raw(Method::POST, "/navigator", &credentials)
+			.await,
+	);
+
+	let credentials = json!({ "name": "frank", "pass": "password123" });
+	frank.post::<_, Value>("/navigator", &credentials).await;
+	frank
+		.post::<_, Value>("/navigator/login", &credentials)
+		.await;
+
+	// Saving an oversized block is rejected by the content routes' limit.
+	let block = ContentBlock::now(
+		None,
+		FractionalIndex::start(),
+		BlockContent::Paragraph {
+			markdown: "a".repeat(limits.content),
+		},
+	);
+
+	assert_too_large(
+		frank
+			.send_raw(Method::PUT, &block_path(&block), &block)
+			.await,
+	);
+
+	server.shutdown().await;
+}
@@ -0,0 +1,555 @@
+//! An end-to-end harness that serves the full router on an ephemeral port,
+//! backed by a freshly migrated temporary database.
+
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use axum::http::StatusCode;
+use cookie::Cookie;
+use nuttyverse_core::access::repository::AccessRepository;
+use nuttyverse_core::access::service::AccessService;
+use nuttyverse_core::app;
+use nuttyverse_core::content::events::EventStreams;
+use nuttyverse_core::content::og_image::OgImages;
+use nuttyverse_core::content::repository::ContentRepository;
+use nuttyverse_core::content::sanitizer::Sanitizer;
+use nuttyverse_core::content::service::ContentService;
+use nuttyverse_core::integrations::chatbot::repository::ChatbotRepository;
+use nuttyverse_core::integrations::chatbot::service::ChatbotService;
+use nuttyverse_core::integrations::chatbot::telegram::Telegram;
+use nuttyverse_core::integrations::webhooks::delivery::WebhookSender;
+use nuttyverse_core::integrations::webhooks::repository::WebhookRepository;
+use nuttyverse_core::integrations::webhooks::service::WebhookService;
+use nuttyverse_core::models::NuttyId;
+use nuttyverse_core::moderation::repository::ModerationRepository;
+use nuttyverse_core::moderation::service::ModerationService;
+use nuttyverse_core::navigator::onboarding::Onboarding;
+use nuttyverse_core::navigator::repository::NavigatorRepository;
+use nuttyverse_core::navigator::service::NavigatorService;
+use nuttyverse_core::system::repository::SystemRepository;
+use nuttyverse_core::system::service::SystemService;
+use nuttyverse_core::utilities::api::body_limit::BodyLimits;
+use nuttyverse_core::utilities::api::export_link::ExportLinks;
+use nuttyverse_core::utilities::api::feed_token::FeedTokens;
+use nuttyverse_core::utilities::api::geo_ip::GeoIp;
+use nuttyverse_core::utilities::api::link_checker::LinkChecker;
+use nuttyverse_core::utilities::api::og_image_link::OgImageLinks;
+use nuttyverse_core::utilities::api::page_fetcher::PageFetcher;
+use nuttyverse_core::utilities::api::read_only::ReadOnlyMode;
+use nuttyverse_core::utilities::api::response::Response;
+use nuttyverse_core::utilities::api::state::AppState;
+use nuttyverse_core::utilities::api::webhook::SIGNATURE_HEADER;
+use nuttyverse_core::utilities::api::webhook::WebhookSecret;
+use reqwest::Method;
+use reqwest::RequestBuilder;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::header::COOKIE;
+use reqwest::header::SET_COOKIE;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use serde_json::json;
+use sqlx::PgPool;
+use sqlx::postgres::PgConnectOptions;
+use sqlx::postgres::PgPoolOptions;
+
+/// A running API server with its own temporary database.
+pub struct TestServer {
+	address: SocketAddr,
+	pool: PgPool,
+	admin_pool: PgPool,
+	database_name: String,
+}
+
+impl TestServer {
+	/// Create and migrate a temporary database, then serve the router on an
+	/// ephemeral port.
+	pub async fn spawn() -> Self {
+		Self::spawn_with(false).await
+	}
+
+	/// Spawn a server that gives new navigators starter content.
+	pub async fn spawn_with_onboarding() -> Self {
+		Self::spawn_with(true).await
+	}
+
+	async fn spawn_with(onboarding: bool) -> Self {
+		let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+		let admin_pool = PgPoolOptions::new()
+			.max_connections(1)
+			.connect(&database_url)
+			.await
+			.expect("Failed to connect to database");
+
+		// Create the temporary database.
+		let database_name = format!("nuttyverse_test_{}", uuid::Uuid::now_v7().simple());
+
+		sqlx::query(&format!(r#"CREATE DATABASE "{database_name}""#))
+			.execute(&admin_pool)
+			.await
+			.expect("Failed to create temporary database");
+
+		let options: PgConnectOptions = database_url.parse().expect("Invalid DATABASE_URL");
+
+		let pool = PgPoolOptions::new()
+			.max_connections(5)
+			.connect_with(options.database(&database_name))
+			.await
+			.expect("Failed to connect to temporary database");
+
+		migrate(&pool).await;
+
+		// Wire up the services like `main` does.
+		let access_service = AccessService::new(AccessRepository::new(pool.clone()));
+		let content_repository = ContentRepository::new(pool.clone());
+		let content_service = ContentService::new(content_repository.clone(), access_service.clone());
+		let moderation_service =
+			ModerationService::new(ModerationRepository::new(pool.clone()), content_repository);
+		let read_only = ReadOnlyMode::new(false, 0);
+		let mut navigator_service = NavigatorService::new(NavigatorRepository::new(pool.clone()))
+			.with_read_only(read_only.clone());
+
+		if onboarding {
+			navigator_service = navigator_service.with_onboarding(Onboarding::new(
+				Arc::new(content_service.clone()),
+				Arc::new(access_service.clone()),
+			));
+		}
+		let chatbot_service = ChatbotService::new(ChatbotRepository::new(pool.clone()));
+		let system_service = SystemService::new(SystemRepository::new(pool.clone()));
+		let webhook_service = WebhookService::new(WebhookRepository::new(pool.clone()));
+
+		let app_state = Arc::new(AppState {
+			access_service: Arc::new(access_service),
+			content_service: Arc::new(content_service),
+			navigator_service: Arc::new(navigator_service),
+			moderation_service: Arc::new(moderation_service),
+			chatbot_service: Arc::new(chatbot_service),
+			system_service: Arc::new(system_service),
+			webhook_service: Arc::new(webhook_service),
+			read_only,
+			geo_ip: GeoIp::default(),
+			sanitizer: Sanitizer::default(),
+			block_kinds: Arc::default(),
+			email_ingest_secret: email_ingest_secret(),
+			page_fetcher: PageFetcher::default(),
+			link_checker: LinkChecker::default(),
+			telegram: Telegram::new(Some(TELEGRAM_SECRET.to_string())),
+			webhook_sender: WebhookSender::default(),
+			feed_tokens: FeedTokens::new(WebhookSecret::new(Some(
+				b"test_calendar_feed_secret".to_vec(),
+			))),
+			export_links: ExportLinks::new(WebhookSecret::new(Some(
+				b"test_export_link_secret".to_vec(),
+			))),
+			og_images: OgImages::default(),
+			og_image_links: OgImageLinks::new(WebhookSecret::new(Some(
+				b"test_og_image_secret".to_vec(),
+			))),
+			event_streams: EventStreams::default(),
+		});
+
+		let router = app::router(app_state, BodyLimits::default());
+
+		let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+			.await
+			.expect("Failed to bind ephemeral port");
+
+		let address = listener.local_addr().unwrap();
+
+		tokio::spawn(async move {
+			axum::serve(listener, router).await.unwrap();
+		});
+
+		Self {
+			address,
+			pool,
+			admin_pool,
+			database_name,
+		}
+	}
+
+	/// Create a client with an empty cookie jar.
+	pub fn client(&self) -> TestClient {
+		TestClient {
+			http: reqwest::Client::builder()
+				.user_agent("nuttyverse-e2e")
+				.build()
+				.unwrap(),
+			base_url: format!("http://{}", self.address),
+			cookies: Mutex::new(BTreeMap::new()),
+		}
+	}
+
+	/// Register a navigator, and log them in with a new client.
+	pub async fn register(&self, name: &str) -> (TestClient, NuttyId) {
+		let client = self.client();
+		let credentials = json!({ "name": name, "pass": "password123" });
+
+		let (status, _) = client.post::<_, Value>("/navigator", &credentials).await;
+		assert_eq!(status, StatusCode::CREATED);
+
+		let (status, _) = client
+			.post::<_, Value>("/navigator/login", &credentials)
+			.await;
+		assert_eq!(status, StatusCode::OK);
+		assert!(client.has_cookie("session_id"));
+
+		let (status, me) = client.get::<Value>("/navigator/me").await;
+		assert_eq!(status, StatusCode::OK);
+
+		let navigator_id =
+			serde_json::from_value(me.extract_object().unwrap()["nutty_id"].clone()).unwrap();
+
+		(client, navigator_id)
+	}
+
+	/// Assign a global role to a navigator, bypassing the API.
+	pub async fn assign_global_role(&self, navigator_id: &NuttyId, role_name: &str) {
+		AccessRepository::new(self.pool.clone())
+			.assign_global_role(navigator_id, role_name)
+			.await
+			.expect("Failed to assign global role");
+	}
+
+	/// Drop the temporary database.
+	pub async fn shutdown(self) {
+		self.pool.close().await;
+
+		sqlx::query(&format!(
+			r#"DROP DATABASE IF EXISTS "{}" WITH (FORCE)"#,
+			self.database_name
+		))
+		.execute(&self.admin_pool)
+		.await
+		.expect("Failed to drop temporary database");
+	}
+}
+
+/// The secret that the server expects inbound emails to be signed with.
+pub fn email_ingest_secret() -> WebhookSecret {
+	WebhookSecret::new(Some(b"test_email_ingest_secret".to_vec()))
+}
+
+/// The secret token that the server expects Telegram updates to carry.
+pub const TELEGRAM_SECRET: &str = "test_telegram_secret";
+
+/// Apply the `migrate:up` section of each dbmate migration, in order.
+async fn migrate(pool: &PgPool) {
+	let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("../db/migrations");
+
+	let mut paths: Vec<_> = std::fs::read_dir(&directory)
+		.expect("Failed to read migrations")
+		.map(|entry| entry.unwrap().path())
+		.filter(|path| path.extension().is_some_and(|ext| ext == "sql"))
+		.collect();
+
+	paths.sort();
+
+	for path in paths {
+		let migration = std::fs::read_to_string(&path).unwrap();
+		let up = migration.split("-- migrate:down").next().unwrap();
+
+		sqlx::raw_sql(up)
+			.execute(pool)
+			.await
+			.unwrap_or_else(|error| panic!("Failed to apply {}: {error}", path.display()));
+	}
+}
+
+/// An HTTP client that keeps the cookies the server sets.
+pub struct TestClient {
+	http: reqwest::Client,
+	base_url: String,
+	cookies: Mutex<BTreeMap<String, String>>,
+}
+
+impl TestClient {
+	/// Send a GET request.
+	pub async fn get<T: DeserializeOwned>(&self, path: &str) -> (StatusCode, Response<T>) {
+		self.send(self.http.get(self.url(path))).await
+	}
+
+	/// Send a POST request with a JSON body.
+	pub async fn post<B: Serialize, T: DeserializeOwned>(
+		&self,
+		path: &str,
+		body: &B,
+	) -> (StatusCode, Response<T>) {
+		self.send(self.http.post(self.url(path)).json(body)).await
+	}
+
+	/// Send a PUT request with a JSON body.
+	pub async fn put<B: Serialize, T: DeserializeOwned>(
+		&self,
+		path: &str,
+		body: &B,
+	) -> (StatusCode, Response<T>) {
+		self.send(self.http.put(self.url(path)).json(body)).await
+	}
+
+	/// Open a stream of server-sent events, resuming after an event if given.
+	/// Returns the response's status, and the stream if it was opened.
+	pub async fn events(
+		&self,
+		path: &str,
+		last_event_id: Option<&str>,
+	) -> (StatusCode, Option<EventStream>) {
+		let mut request = self.with_cookies(self.http.get(self.url(path)));
+
+		if let Some(last_event_id) = last_event_id {
+			request = request.header("Last-Event-ID", last_event_id);
+		}
+
+		let response = request.send().await.expect("Failed to send request");
+		let status = response.status();
+
+		let stream = status.is_success().then(|| EventStream {
+			response,
+			buffer: String::new(),
+		});
+
+		(status, stream)
+	}
+
+	/// Send a PUT request with a text body, such as a YAML document.
+	pub async fn put_text<T: DeserializeOwned>(
+		&self,
+		path: &str,
+		body: &str,
+	) -> (StatusCode, Response<T>) {
+		let request = self
+			.http
+			.put(self.url(path))
+			.header(CONTENT_TYPE, "application/yaml")
+			.body(body.to_string());
+
+		self.send(request).await
+	}
+
+	/// Send a POST request with a JSON body, signed as a webhook would.
+	pub async fn post_signed<B: Serialize, T: DeserializeOwned>(
+		&self,
+		path: &str,
+		body: &B,
+		secret: &WebhookSecret,
+	) -> (StatusCode, Response<T>) {
+		let body = serde_json::to_vec(body).unwrap();
+		let signature = secret.sign(&body).unwrap_or_default();
+
+		let request = self
+			.http
+			.post(self.url(path))
+			.header(CONTENT_TYPE, "application/json")
+			.header(SIGNATURE_HEADER, signature)
+			.body(body);
+
+		self.send(request).await
+	}
+
+	/// Send a POST request with a JSON body and an extra header, returning
+	/// the raw JSON response rather than an API response.
+	pub async fn post_raw<B: Serialize>(
+		&self,
+		path: &str,
+		body: &B,
+		header: (&str, &str),
+	) -> (StatusCode, Value) {
+		let response = self
+			.http
+			.post(self.url(path))
+			.header(header.0, header.1)
+			.json(body)
+			.send()
+			.await
+			.expect("Failed to send request");
+
+		let status = response.status();
+		let body = response.json().await.expect("Failed to parse response");
+		(status, body)
+	}
+
+	/// Send a GET request without cookies, returning the body's content type
+	/// and text.
+	pub async fn get_text(&self, path: &str) -> (StatusCode, String, String) {
+		let response = self
+			.http
+			.get(self.url(path))
+			.send()
+			.await
+			.expect("Failed to send request");
+
+		let status = response.status();
+
+		let content_type = response
+			.headers()
+			.get(CONTENT_TYPE)
+			.and_then(|value| value.to_str().ok())
+			.unwrap_or_default()
+			.to_string();
+
+		let body = response.text().await.expect("Failed to read response");
+		(status, content_type, body)
+	}
+
+	/// Send a GET request with cookies, returning the body's content type
+	/// and text.
+	pub async fn get_session_text(&self, path: &str) -> (StatusCode, String, String) {
+		let response = self
+			.with_cookies(self.http.get(self.url(path)))
+			.send()
+			.await
+			.expect("Failed to send request");
+
+		let status = response.status();
+
+		let content_type = response
+			.headers()
+			.get(CONTENT_TYPE)
+			.and_then(|value| value.to_str().ok())
+			.unwrap_or_default()
+			.to_string();
+
+		let body = response.text().await.expect("Failed to read response");
+		(status, content_type, body)
+	}
+
+	/// Send a request with a JSON body and cookies, returning the body's
+	/// content type and JSON rather than an API response, e.g. for problem
+	/// details.
+	pub async fn send_raw<B: Serialize>(
+		&self,
+		method: Method,
+		path: &str,
+		body: &B,
+	) -> (StatusCode, String, Value) {
+		let response = self
+			.with_cookies(self.http.request(method, self.url(path)))
+			.json(body)
+			.send()
+			.await
+			.expect("Failed to send request");
+
+		let status = response.status();
+
+		let content_type = response
+			.headers()
+			.get(CONTENT_TYPE)
+			.and_then(|value| value.to_str().ok())
+			.unwrap_or_default()
+			.to_string();
+
+		let body = response.json().await.expect("Failed to parse response");
+		(status, content_type, body)
+	}
+
+	/// Send a DELETE request.
+	pub async fn delete<T: DeserializeOwned>(&self, path: &str) -> (StatusCode, Response<T>) {
+		self.send(self.http.delete(self.url(path))).await
+	}
+
+	/// Check whether the server has set a cookie.
+	pub fn has_cookie(&self, name: &str) -> bool {
+		self.cookies.lock().unwrap().contains_key(name)
+	}
+
+	fn url(&self, path: &str) -> String {
+		format!("{}{path}", self.base_url)
+	}
+
+	fn with_cookies(&self, request: RequestBuilder) -> RequestBuilder {
+		let cookies = self
+			.cookies
+			.lock()
+			.unwrap()
+			.iter()
+			.map(|(name, value)| format!("{name}={value}"))
+			.collect::<Vec<_>>()
+			.join("; ");
+
+		if cookies.is_empty() {
+			request
+		} else {
+			request.header(COOKIE, cookies)
+		}
+	}
+
+	async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> (StatusCode, Response<T>) {
+		let request = self.with_cookies(request);
+		let response = request.send().await.expect("Failed to send request");
+		let status = response.status();
+
+		// Keep the jar in sync, ignoring attributes like `Secure` that only
+		// matter to browsers.
+		for header in response.headers().get_all(SET_COOKIE) {
+			let cookie = Cookie::parse(header.to_str().unwrap().to_string()).unwrap();
+			let mut cookies = self.cookies.lock().unwrap();
+
+			if cookie.value().is_empty() || cookie.max_age().is_some_and(|age| age.is_zero()) {
+				cookies.remove(cookie.name());
+			} else {
+				cookies.insert(cookie.name().to_string(), cookie.value().to_string());
+			}
+		}
+
+		let body = response.json().await.expect("Failed to parse response");
+		(status, body)
+	}
+}
+
+/// A server-sent event.
+#[derive(Debug, Default)]
+pub struct ServerEvent {
+	pub event: String,
+	pub id: Option<String>,
+	pub data: String,
+}
+
+/// A stream of server-sent events being received.
+pub struct EventStream {
+	response: reqwest::Response,
+	buffer: String,
+}
+
+impl EventStream {
+	/// Wait for the next event, skipping heartbeats. Panics if none comes
+	/// within a few seconds.
+	pub async fn next(&mut self) -> ServerEvent {
+		tokio::time::timeout(std::time::Duration::from_secs(10), async {
+			loop {
+				while let Some(end) = self.buffer.find("\n\n") {
+					let block: String = self.buffer.drain(..end + 2).collect();
+					let mut event = ServerEvent::default();
+
+					for line in block.lines() {
+						match line.split_once(':') {
+							Some(("event", value)) => event.event = value.trim().to_string(),
+							Some(("id", value)) => event.id = Some(value.trim().to_string()),
+							Some(("data", value)) => event.data.push_str(value.trim()),
+							_ => {}
+						}
+					}
+
+					if !event.event.is_empty() {
+						return event;
+					}
+				}
+
+				let chunk = self
+					.response
+					.chunk()
+					.await
+					.expect("Failed to read event stream")
+					.expect("Event stream ended");
+
+				self.buffer.push_str(&String::from_utf8_lossy(&chunk));
+			}
+		})
+		.await
+		.expect("Timed out waiting for an event")
+	}
+}
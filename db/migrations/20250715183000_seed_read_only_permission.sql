@@ -0,0 +1,10 @@
+-- migrate:up
+INSERT INTO auth.permissions (name, description) VALUES
+('system:read_only:write', 'Can toggle read-only mode.');
+
+INSERT INTO auth.role_permissions (role_name, permission_name) VALUES
+('admin', 'system:read_only:write');
+
+-- migrate:down
+DELETE FROM auth.role_permissions WHERE permission_name = 'system:read_only:write';
+DELETE FROM auth.permissions WHERE name = 'system:read_only:write';
@@ -0,0 +1,10 @@
+-- migrate:up
+INSERT INTO auth.permissions (name, description) VALUES
+('system:metrics:read', 'Can view query metrics and slow queries.');
+
+INSERT INTO auth.role_permissions (role_name, permission_name) VALUES
+('admin', 'system:metrics:read');
+
+-- migrate:down
+DELETE FROM auth.role_permissions WHERE permission_name = 'system:metrics:read';
+DELETE FROM auth.permissions WHERE name = 'system:metrics:read';
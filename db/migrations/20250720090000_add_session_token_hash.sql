@@ -0,0 +1,12 @@
+-- migrate:up
+-- Existing sessions are keyed by their raw ID and have no token, so sign everyone out.
+DELETE FROM auth.sessions;
+
+ALTER TABLE auth.sessions ADD COLUMN token_hash TEXT NOT NULL;
+
+CREATE UNIQUE INDEX sessions_token_hash_idx ON auth.sessions(token_hash);
+
+-- migrate:down
+DROP INDEX IF EXISTS auth.sessions_token_hash_idx;
+
+ALTER TABLE auth.sessions DROP COLUMN token_hash;
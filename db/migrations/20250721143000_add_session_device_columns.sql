@@ -0,0 +1,15 @@
+-- migrate:up
+ALTER TABLE auth.sessions
+	ADD COLUMN browser TEXT NOT NULL DEFAULT 'Unknown',
+	ADD COLUMN os TEXT NOT NULL DEFAULT 'Unknown',
+	ADD COLUMN device TEXT NOT NULL DEFAULT 'Unknown',
+	ADD COLUMN label TEXT,
+	ADD COLUMN country TEXT;
+
+-- migrate:down
+ALTER TABLE auth.sessions
+	DROP COLUMN country,
+	DROP COLUMN label,
+	DROP COLUMN device,
+	DROP COLUMN os,
+	DROP COLUMN browser;
@@ -0,0 +1,15 @@
+-- migrate:up
+CREATE TABLE auth.navigator_renames (
+	id UUID PRIMARY KEY,
+	nutty_id VARCHAR(7) NOT NULL,
+	navigator_id UUID NOT NULL,
+	old_name VARCHAR(255) NOT NULL,
+	new_name VARCHAR(255) NOT NULL,
+	created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	CONSTRAINT navigator_renames_navigator_id_fkey FOREIGN KEY (navigator_id) REFERENCES auth.navigators(id) ON DELETE CASCADE
+);
+
+CREATE INDEX navigator_renames_navigator_id_idx ON auth.navigator_renames(navigator_id);
+
+-- migrate:down
+DROP TABLE IF EXISTS auth.navigator_renames;
@@ -0,0 +1,45 @@
+-- migrate:up
+ALTER TABLE content.blocks
+ADD COLUMN moderation_status VARCHAR(16) NOT NULL DEFAULT 'visible'
+CHECK (moderation_status IN ('visible', 'hidden'));
+
+CREATE TABLE content.reports (
+	id UUID PRIMARY KEY,
+	nutty_id VARCHAR(7) NOT NULL,
+	block_id UUID NOT NULL REFERENCES content.blocks(id) ON DELETE CASCADE,
+	reporter_id UUID NOT NULL REFERENCES auth.navigators(id) ON DELETE CASCADE,
+	reason TEXT NOT NULL,
+	status VARCHAR(16) NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'dismissed', 'actioned')),
+	resolution TEXT,
+	resolved_by UUID REFERENCES auth.navigators(id) ON DELETE SET NULL,
+	resolved_at TIMESTAMP WITH TIME ZONE,
+	created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX reports_nutty_id_idx ON content.reports(nutty_id);
+CREATE INDEX reports_status_idx ON content.reports(status, created_at);
+CREATE INDEX reports_reporter_id_idx ON content.reports(reporter_id);
+
+-- A navigator can only have one open report per block.
+CREATE UNIQUE INDEX reports_open_block_reporter_key
+ON content.reports(block_id, reporter_id)
+WHERE status = 'open';
+
+CREATE TRIGGER update_content_reports_updated_at
+BEFORE UPDATE ON content.reports
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+INSERT INTO auth.permissions (name, description) VALUES
+('moderation:review', 'Can review reports and hide content.');
+
+INSERT INTO auth.role_permissions (role_name, permission_name) VALUES
+('admin', 'moderation:review');
+
+-- migrate:down
+DELETE FROM auth.role_permissions WHERE permission_name = 'moderation:review';
+DELETE FROM auth.permissions WHERE name = 'moderation:review';
+DROP TRIGGER IF EXISTS update_content_reports_updated_at ON content.reports;
+DROP TABLE IF EXISTS content.reports;
+ALTER TABLE content.blocks DROP COLUMN IF EXISTS moderation_status;
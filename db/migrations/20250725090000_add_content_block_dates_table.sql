@@ -0,0 +1,14 @@
+-- migrate:up
+CREATE TABLE content.block_dates (
+	block_id UUID NOT NULL,
+	date DATE NOT NULL,
+	kind VARCHAR(16) NOT NULL,
+	CONSTRAINT block_dates_pkey PRIMARY KEY (block_id, date, kind),
+	CONSTRAINT block_dates_block_id_fkey FOREIGN KEY (block_id) REFERENCES content.blocks(id) ON DELETE CASCADE,
+	CONSTRAINT block_dates_kind_check CHECK (kind IN ('daily', 'due'))
+);
+
+CREATE INDEX block_dates_date_idx ON content.block_dates(date);
+
+-- migrate:down
+DROP TABLE IF EXISTS content.block_dates;
@@ -0,0 +1,34 @@
+-- migrate:up
+ALTER TABLE content.blocks ADD COLUMN properties JSONB NOT NULL DEFAULT '{}';
+
+CREATE INDEX blocks_properties_idx ON content.blocks USING GIN (properties jsonb_path_ops);
+
+CREATE TABLE content.property_definitions (
+	id UUID PRIMARY KEY,
+	nutty_id VARCHAR(7) NOT NULL,
+	name VARCHAR(64) NOT NULL,
+	kind VARCHAR(16) NOT NULL,
+	options JSONB NOT NULL DEFAULT '[]',
+	created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	CONSTRAINT property_definitions_name_key UNIQUE (name),
+	CONSTRAINT property_definitions_kind_check CHECK (kind IN ('text', 'number', 'date', 'select'))
+);
+
+CREATE TRIGGER update_content_property_definitions_updated_at
+BEFORE UPDATE ON content.property_definitions
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+INSERT INTO auth.permissions (name, description) VALUES
+('content_properties:write', 'Can define content block properties.');
+
+INSERT INTO auth.role_permissions (role_name, permission_name) VALUES
+('admin', 'content_properties:write');
+
+-- migrate:down
+DELETE FROM auth.role_permissions WHERE permission_name = 'content_properties:write';
+DELETE FROM auth.permissions WHERE name = 'content_properties:write';
+DROP TABLE IF EXISTS content.property_definitions;
+DROP INDEX IF EXISTS content.blocks_properties_idx;
+ALTER TABLE content.blocks DROP COLUMN IF EXISTS properties;
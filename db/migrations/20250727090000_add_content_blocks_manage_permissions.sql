@@ -0,0 +1,11 @@
+-- migrate:up
+INSERT INTO auth.permissions (name, description) VALUES
+('content_blocks:manage:all', 'Can manage access to all content blocks.'),
+('content_blocks:manage:resource', 'Can manage access to a content block and its descendants.');
+
+INSERT INTO auth.role_permissions (role_name, permission_name) VALUES
+('admin', 'content_blocks:manage:all');
+
+-- migrate:down
+DELETE FROM auth.role_permissions WHERE permission_name LIKE 'content_blocks:manage:%';
+DELETE FROM auth.permissions WHERE name LIKE 'content_blocks:manage:%';
@@ -0,0 +1,27 @@
+-- migrate:up
+CREATE TABLE content.ownership_transfers (
+	id UUID PRIMARY KEY,
+	nutty_id VARCHAR(7) NOT NULL,
+	block_id UUID NOT NULL,
+	previous_owner_id UUID,
+	new_owner_id UUID NOT NULL,
+	transferred_by UUID,
+	created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	CONSTRAINT ownership_transfers_block_id_fkey FOREIGN KEY (block_id) REFERENCES content.blocks(id) ON DELETE CASCADE,
+	CONSTRAINT ownership_transfers_previous_owner_id_fkey FOREIGN KEY (previous_owner_id) REFERENCES auth.navigators(id) ON DELETE SET NULL,
+	CONSTRAINT ownership_transfers_new_owner_id_fkey FOREIGN KEY (new_owner_id) REFERENCES auth.navigators(id) ON DELETE CASCADE,
+	CONSTRAINT ownership_transfers_transferred_by_fkey FOREIGN KEY (transferred_by) REFERENCES auth.navigators(id) ON DELETE SET NULL
+);
+
+CREATE INDEX ownership_transfers_block_id_idx ON content.ownership_transfers(block_id);
+
+INSERT INTO auth.permissions (name, description) VALUES
+('content_blocks:transfer', 'Can transfer ownership of content blocks.');
+
+INSERT INTO auth.role_permissions (role_name, permission_name) VALUES
+('admin', 'content_blocks:transfer');
+
+-- migrate:down
+DELETE FROM auth.role_permissions WHERE permission_name = 'content_blocks:transfer';
+DELETE FROM auth.permissions WHERE name = 'content_blocks:transfer';
+DROP TABLE IF EXISTS content.ownership_transfers;
@@ -0,0 +1,26 @@
+-- migrate:up
+INSERT INTO auth.permissions (name, description) VALUES
+('content_blocks:read:resource', 'Can view a content block and its descendants.'),
+('content_blocks:comment:resource', 'Can comment on a content block and its descendants.'),
+('content_blocks:write', 'Can edit a content block and its descendants.')
+ON CONFLICT (name) DO NOTHING;
+
+INSERT INTO auth.roles (name, description) VALUES
+('block_viewer', 'Can view a shared content block.'),
+('block_commenter', 'Can view and comment on a shared content block.'),
+('block_editor', 'Can view, comment on, and edit a shared content block.')
+ON CONFLICT (name) DO NOTHING;
+
+INSERT INTO auth.role_permissions (role_name, permission_name) VALUES
+('block_viewer', 'content_blocks:read:resource'),
+('block_commenter', 'content_blocks:read:resource'),
+('block_commenter', 'content_blocks:comment:resource'),
+('block_editor', 'content_blocks:read:resource'),
+('block_editor', 'content_blocks:comment:resource'),
+('block_editor', 'content_blocks:write')
+ON CONFLICT (role_name, permission_name) DO NOTHING;
+
+-- migrate:down
+DELETE FROM auth.role_permissions WHERE role_name IN ('block_viewer', 'block_commenter', 'block_editor');
+DELETE FROM auth.roles WHERE name IN ('block_viewer', 'block_commenter', 'block_editor');
+DELETE FROM auth.permissions WHERE name = 'content_blocks:comment:resource';
@@ -0,0 +1,30 @@
+-- migrate:up
+CREATE TABLE content.access_requests (
+	id UUID PRIMARY KEY,
+	nutty_id VARCHAR(7) NOT NULL,
+	block_id UUID NOT NULL REFERENCES content.blocks(id) ON DELETE CASCADE,
+	navigator_id UUID NOT NULL REFERENCES auth.navigators(id) ON DELETE CASCADE,
+	level VARCHAR(16) NOT NULL CHECK (level IN ('view', 'comment', 'edit')),
+	status VARCHAR(16) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'denied')),
+	resolved_by UUID REFERENCES auth.navigators(id) ON DELETE SET NULL,
+	resolved_at TIMESTAMP WITH TIME ZONE,
+	created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX access_requests_nutty_id_idx ON content.access_requests(nutty_id);
+CREATE INDEX access_requests_block_id_idx ON content.access_requests(block_id, status);
+
+-- A navigator can only have one pending request per block.
+CREATE UNIQUE INDEX access_requests_pending_block_navigator_key
+ON content.access_requests(block_id, navigator_id)
+WHERE status = 'pending';
+
+CREATE TRIGGER update_content_access_requests_updated_at
+BEFORE UPDATE ON content.access_requests
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+-- migrate:down
+DROP TRIGGER IF EXISTS update_content_access_requests_updated_at ON content.access_requests;
+DROP TABLE IF EXISTS content.access_requests;
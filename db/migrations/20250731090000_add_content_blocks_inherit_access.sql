@@ -0,0 +1,5 @@
+-- migrate:up
+ALTER TABLE content.blocks ADD COLUMN inherit_access BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- migrate:down
+ALTER TABLE content.blocks DROP COLUMN IF EXISTS inherit_access;
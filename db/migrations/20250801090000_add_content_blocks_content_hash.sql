@@ -0,0 +1,9 @@
+-- migrate:up
+ALTER TABLE content.blocks ADD COLUMN content_hash TEXT;
+
+CREATE INDEX blocks_content_hash_idx ON content.blocks(content_hash);
+
+-- migrate:down
+DROP INDEX IF EXISTS content.blocks_content_hash_idx;
+
+ALTER TABLE content.blocks DROP COLUMN IF EXISTS content_hash;
@@ -0,0 +1,17 @@
+-- migrate:up
+CREATE TABLE content.block_revisions (
+	id UUID PRIMARY KEY,
+	nutty_id VARCHAR(7) NOT NULL,
+	block_id UUID NOT NULL,
+	previous_content JSONB NOT NULL,
+	content JSONB NOT NULL,
+	revised_by UUID,
+	created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	CONSTRAINT block_revisions_block_id_fkey FOREIGN KEY (block_id) REFERENCES content.blocks(id) ON DELETE CASCADE,
+	CONSTRAINT block_revisions_revised_by_fkey FOREIGN KEY (revised_by) REFERENCES auth.navigators(id) ON DELETE SET NULL
+);
+
+CREATE INDEX block_revisions_block_id_idx ON content.block_revisions(block_id);
+
+-- migrate:down
+DROP TABLE IF EXISTS content.block_revisions;
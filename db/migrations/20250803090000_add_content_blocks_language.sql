@@ -0,0 +1,28 @@
+-- migrate:up
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Blocks without a language are searched as English.
+ALTER TABLE content.blocks ADD COLUMN language VARCHAR(16)
+CHECK (language IN ('english', 'japanese', 'simple'));
+
+CREATE INDEX blocks_english_search_idx ON content.blocks
+USING GIN (to_tsvector('english', COALESCE(content->>'markdown', content->>'title', '')))
+WHERE COALESCE(language, 'english') = 'english';
+
+CREATE INDEX blocks_simple_search_idx ON content.blocks
+USING GIN (to_tsvector('simple', COALESCE(content->>'markdown', content->>'title', '')))
+WHERE language = 'simple';
+
+-- Japanese isn't separated by whitespace, so it's matched by substring
+-- instead. Where pg_bigm is installed, gin_bigm_ops can replace
+-- gin_trgm_ops here to also serve one and two character queries.
+CREATE INDEX blocks_japanese_search_idx ON content.blocks
+USING GIN ((COALESCE(content->>'markdown', content->>'title', '')) gin_trgm_ops)
+WHERE language = 'japanese';
+
+-- migrate:down
+DROP INDEX IF EXISTS content.blocks_japanese_search_idx;
+DROP INDEX IF EXISTS content.blocks_simple_search_idx;
+DROP INDEX IF EXISTS content.blocks_english_search_idx;
+
+ALTER TABLE content.blocks DROP COLUMN IF EXISTS language;
@@ -0,0 +1,17 @@
+-- migrate:up
+-- The page that each navigator's inbound emails are filed under.
+CREATE TABLE content.inboxes (
+	navigator_id UUID PRIMARY KEY REFERENCES auth.navigators(id) ON DELETE CASCADE,
+	parent_id UUID NOT NULL REFERENCES content.blocks(id) ON DELETE CASCADE,
+	created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE TRIGGER update_content_inboxes_updated_at
+BEFORE UPDATE ON content.inboxes
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+-- migrate:down
+DROP TRIGGER IF EXISTS update_content_inboxes_updated_at ON content.inboxes;
+DROP TABLE IF EXISTS content.inboxes;
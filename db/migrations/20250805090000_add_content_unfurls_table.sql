@@ -0,0 +1,20 @@
+-- migrate:up
+-- The metadata of pages captured as content blocks, as fetched at capture.
+CREATE TABLE content.unfurls (
+	block_id UUID PRIMARY KEY REFERENCES content.blocks(id) ON DELETE CASCADE,
+	url TEXT NOT NULL,
+	title TEXT,
+	description TEXT,
+	site_name TEXT,
+	created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE TRIGGER update_content_unfurls_updated_at
+BEFORE UPDATE ON content.unfurls
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+-- migrate:down
+DROP TRIGGER IF EXISTS update_content_unfurls_updated_at ON content.unfurls;
+DROP TABLE IF EXISTS content.unfurls;
@@ -0,0 +1,32 @@
+-- migrate:up
+-- Chat platform users linked to navigators, e.g. through a Telegram bot.
+CREATE TABLE auth.chat_links (
+	platform TEXT NOT NULL,
+	chat_user_id TEXT NOT NULL,
+	navigator_id UUID NOT NULL REFERENCES auth.navigators(id) ON DELETE CASCADE,
+	created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	PRIMARY KEY (platform, chat_user_id),
+	CONSTRAINT chat_links_platform_check CHECK (platform IN ('telegram'))
+);
+
+CREATE INDEX chat_links_navigator_id_idx ON auth.chat_links(navigator_id);
+
+CREATE TRIGGER update_auth_chat_links_updated_at
+BEFORE UPDATE ON auth.chat_links
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+-- Short-lived codes that a navigator sends to a bot to link their chat user.
+CREATE TABLE auth.chat_link_codes (
+	code TEXT PRIMARY KEY,
+	navigator_id UUID NOT NULL REFERENCES auth.navigators(id) ON DELETE CASCADE,
+	expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+	created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+-- migrate:down
+DROP TABLE IF EXISTS auth.chat_link_codes;
+DROP TRIGGER IF EXISTS update_auth_chat_links_updated_at ON auth.chat_links;
+DROP INDEX IF EXISTS auth.chat_links_navigator_id_idx;
+DROP TABLE IF EXISTS auth.chat_links;
@@ -0,0 +1,25 @@
+-- migrate:up
+-- Archives of a navigator's personal data, assembled in the background.
+CREATE TABLE auth.navigator_exports (
+	id UUID PRIMARY KEY,
+	nutty_id VARCHAR(7) NOT NULL,
+	navigator_id UUID NOT NULL REFERENCES auth.navigators(id) ON DELETE CASCADE,
+	status VARCHAR(16) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'ready', 'failed')),
+	archive JSONB,
+	error TEXT,
+	completed_at TIMESTAMP WITH TIME ZONE,
+	expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+	created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX navigator_exports_navigator_id_idx ON auth.navigator_exports(navigator_id, created_at);
+
+CREATE TRIGGER update_auth_navigator_exports_updated_at
+BEFORE UPDATE ON auth.navigator_exports
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+-- migrate:down
+DROP TRIGGER IF EXISTS update_auth_navigator_exports_updated_at ON auth.navigator_exports;
+DROP TABLE IF EXISTS auth.navigator_exports;
@@ -0,0 +1,14 @@
+-- migrate:up
+-- Issues found in content blocks after they're saved, e.g. broken links.
+CREATE TABLE content.annotations (
+	block_id UUID NOT NULL,
+	kind VARCHAR(32) NOT NULL,
+	message TEXT NOT NULL,
+	created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	CONSTRAINT annotations_pkey PRIMARY KEY (block_id, kind, message),
+	CONSTRAINT annotations_block_id_fkey FOREIGN KEY (block_id) REFERENCES content.blocks(id) ON DELETE CASCADE,
+	CONSTRAINT annotations_kind_check CHECK (kind IN ('broken_link', 'empty_heading', 'unclosed_code_fence'))
+);
+
+-- migrate:down
+DROP TABLE IF EXISTS content.annotations;
@@ -0,0 +1,160 @@
+-- migrate:up
+-- Row-level security for content blocks, as a second line of defense behind
+-- the API's access checks. With ROW_LEVEL_SECURITY enabled, the API switches
+-- to the nuttyverse_navigator role for requests made by a signed-in
+-- navigator, with app.navigator_id set to theirs. Other roles, like the
+-- owner of the tables, aren't restricted.
+DO $$
+BEGIN
+	CREATE ROLE nuttyverse_navigator NOLOGIN;
+EXCEPTION WHEN duplicate_object OR unique_violation THEN
+	-- Roles are shared by every database in the cluster.
+	NULL;
+END
+$$;
+
+DO $$
+BEGIN
+	EXECUTE format('GRANT nuttyverse_navigator TO %I', current_user);
+EXCEPTION WHEN unique_violation THEN
+	NULL;
+END
+$$;
+
+GRANT USAGE ON SCHEMA auth, content TO nuttyverse_navigator;
+GRANT SELECT, INSERT, UPDATE, DELETE ON ALL TABLES IN SCHEMA auth, content TO nuttyverse_navigator;
+
+ALTER DEFAULT PRIVILEGES IN SCHEMA auth, content
+GRANT SELECT, INSERT, UPDATE, DELETE ON TABLES TO nuttyverse_navigator;
+
+-- The navigator that the current request is made by, if any.
+CREATE FUNCTION auth.current_navigator_id()
+RETURNS UUID
+LANGUAGE sql STABLE
+AS $$
+	SELECT NULLIF(current_setting('app.navigator_id', TRUE), '')::UUID
+$$;
+
+-- Check if the current navigator has a permission through a global role.
+CREATE FUNCTION auth.has_global_permission(permission TEXT)
+RETURNS BOOLEAN
+LANGUAGE sql STABLE
+AS $$
+	SELECT EXISTS (
+		SELECT 1 FROM auth.navigator_roles nr
+		JOIN auth.role_permissions rp ON rp.role_name = nr.role_name
+		WHERE nr.navigator_id = auth.current_navigator_id()
+			AND rp.permission_name = permission
+	)
+$$;
+
+-- Check if the current navigator can read or write a content block, by the
+-- same rules as the API: a global permission, ownership, or a resource role
+-- on the block or an ancestor that it inherits access from. Ancestors are
+-- looked up as the function's owner, so that they aren't filtered too.
+CREATE FUNCTION content.navigator_can(
+	action TEXT,
+	target_id UUID,
+	target_parent_id UUID,
+	target_owner_id UUID,
+	target_inherit_access BOOLEAN
+)
+RETURNS BOOLEAN
+LANGUAGE sql STABLE SECURITY DEFINER
+SET search_path = pg_catalog, pg_temp
+AS $$
+	WITH RECURSIVE ancestors AS (
+		SELECT target_id AS id, target_parent_id AS parent_id, target_inherit_access AS inherit_access
+		UNION ALL
+		SELECT b.id, b.parent_id, b.inherit_access
+		FROM ancestors a
+		JOIN content.blocks b ON b.id = a.parent_id
+		WHERE a.inherit_access
+	)
+	SELECT auth.current_navigator_id() IS NOT NULL AND (
+		auth.has_global_permission('content_blocks:' || action || ':all')
+		OR (
+			target_owner_id = auth.current_navigator_id()
+			AND auth.has_global_permission('content_blocks:' || action || ':own')
+		)
+		OR EXISTS (
+			SELECT 1 FROM ancestors a
+			JOIN auth.resource_roles rr ON rr.resource_type = 'content_block' AND rr.resource_id = a.id
+			JOIN auth.role_permissions rp ON rp.role_name = rr.role_name
+			WHERE rr.navigator_id = auth.current_navigator_id()
+				AND rp.permission_name = CASE action
+					WHEN 'read' THEN 'content_blocks:read:resource'
+					ELSE 'content_blocks:write'
+				END
+		)
+	)
+$$;
+
+ALTER TABLE content.blocks ENABLE ROW LEVEL SECURITY;
+
+-- Hidden blocks are only visible to moderators, who also see the rest so
+-- that they can review reports.
+CREATE POLICY blocks_select ON content.blocks
+FOR SELECT TO nuttyverse_navigator
+USING (
+	auth.has_global_permission('moderation:review')
+	OR (
+		moderation_status <> 'hidden'
+		AND content.navigator_can('read', id, parent_id, owner_id, inherit_access)
+	)
+);
+
+CREATE POLICY blocks_insert ON content.blocks
+FOR INSERT TO nuttyverse_navigator
+WITH CHECK (content.navigator_can('write', id, parent_id, owner_id, inherit_access));
+
+-- Moderators hide blocks, and transfers hand blocks to other owners.
+CREATE POLICY blocks_update ON content.blocks
+FOR UPDATE TO nuttyverse_navigator
+USING (
+	content.navigator_can('write', id, parent_id, owner_id, inherit_access)
+	OR auth.has_global_permission('moderation:review')
+	OR auth.has_global_permission('content_blocks:transfer')
+)
+WITH CHECK (
+	content.navigator_can('write', id, parent_id, owner_id, inherit_access)
+	OR auth.has_global_permission('moderation:review')
+	OR auth.has_global_permission('content_blocks:transfer')
+);
+
+CREATE POLICY blocks_delete ON content.blocks
+FOR DELETE TO nuttyverse_navigator
+USING (content.navigator_can('write', id, parent_id, owner_id, inherit_access));
+
+-- Revisions hold past content, so they're visible with their blocks.
+ALTER TABLE content.block_revisions ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY block_revisions_all ON content.block_revisions
+FOR ALL TO nuttyverse_navigator
+USING (EXISTS (SELECT 1 FROM content.blocks b WHERE b.id = block_id));
+
+-- Other roles keep seeing every row.
+CREATE POLICY blocks_unrestricted ON content.blocks
+USING (current_user <> 'nuttyverse_navigator');
+
+CREATE POLICY block_revisions_unrestricted ON content.block_revisions
+USING (current_user <> 'nuttyverse_navigator');
+
+-- migrate:down
+DROP POLICY IF EXISTS block_revisions_unrestricted ON content.block_revisions;
+DROP POLICY IF EXISTS blocks_unrestricted ON content.blocks;
+DROP POLICY IF EXISTS block_revisions_all ON content.block_revisions;
+ALTER TABLE content.block_revisions DISABLE ROW LEVEL SECURITY;
+DROP POLICY IF EXISTS blocks_delete ON content.blocks;
+DROP POLICY IF EXISTS blocks_update ON content.blocks;
+DROP POLICY IF EXISTS blocks_insert ON content.blocks;
+DROP POLICY IF EXISTS blocks_select ON content.blocks;
+ALTER TABLE content.blocks DISABLE ROW LEVEL SECURITY;
+DROP FUNCTION IF EXISTS content.navigator_can(TEXT, UUID, UUID, UUID, BOOLEAN);
+DROP FUNCTION IF EXISTS auth.has_global_permission(TEXT);
+DROP FUNCTION IF EXISTS auth.current_navigator_id();
+ALTER DEFAULT PRIVILEGES IN SCHEMA auth, content
+REVOKE SELECT, INSERT, UPDATE, DELETE ON TABLES FROM nuttyverse_navigator;
+REVOKE ALL ON ALL TABLES IN SCHEMA auth, content FROM nuttyverse_navigator;
+REVOKE USAGE ON SCHEMA auth, content FROM nuttyverse_navigator;
+-- The role is left in place, since other databases in the cluster may use it.
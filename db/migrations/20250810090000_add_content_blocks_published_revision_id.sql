@@ -0,0 +1,9 @@
+-- migrate:up
+-- The revision that navigators who can't edit a block see, while its
+-- editors keep working on the draft.
+ALTER TABLE content.blocks
+ADD COLUMN published_revision_id UUID
+REFERENCES content.block_revisions(id) ON DELETE SET NULL;
+
+-- migrate:down
+ALTER TABLE content.blocks DROP COLUMN IF EXISTS published_revision_id;
@@ -0,0 +1,26 @@
+-- migrate:up
+-- Custom paths that navigators claim for blocks, as aliases of their
+-- permalinks. A block's earlier slugs are kept, to redirect to its current
+-- one.
+CREATE TABLE content.slugs (
+	path VARCHAR(200) PRIMARY KEY,
+	block_id UUID NOT NULL,
+	navigator_id UUID NOT NULL,
+	is_current BOOLEAN NOT NULL DEFAULT TRUE,
+	created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	CONSTRAINT slugs_block_id_fkey FOREIGN KEY (block_id) REFERENCES content.blocks(id) ON DELETE CASCADE,
+	CONSTRAINT slugs_navigator_id_fkey FOREIGN KEY (navigator_id) REFERENCES auth.navigators(id) ON DELETE CASCADE
+);
+
+-- A block has at most one current slug.
+CREATE UNIQUE INDEX slugs_current_block_id_key ON content.slugs(block_id) WHERE is_current;
+
+CREATE TRIGGER update_content_slugs_updated_at
+BEFORE UPDATE ON content.slugs
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+-- migrate:down
+DROP TRIGGER IF EXISTS update_content_slugs_updated_at ON content.slugs;
+DROP TABLE IF EXISTS content.slugs;
@@ -0,0 +1,21 @@
+-- migrate:up
+-- How a navigator's published site presents itself. Navigation roots are the
+-- pages linked from every page of the site, in order.
+CREATE TABLE content.site_settings (
+	navigator_id UUID PRIMARY KEY REFERENCES auth.navigators(id) ON DELETE CASCADE,
+	title VARCHAR(200) NOT NULL,
+	description TEXT NOT NULL DEFAULT '',
+	accent_color VARCHAR(7),
+	navigation_root_ids UUID[] NOT NULL DEFAULT '{}',
+	created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE TRIGGER update_content_site_settings_updated_at
+BEFORE UPDATE ON content.site_settings
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+-- migrate:down
+DROP TRIGGER IF EXISTS update_content_site_settings_updated_at ON content.site_settings;
+DROP TABLE IF EXISTS content.site_settings;
@@ -0,0 +1,156 @@
+-- migrate:up
+-- Deep counts of content blocks, maintained by triggers as blocks are
+-- saved, moved, and deleted, and as links come and go. The counts can drift
+-- if a single statement moves a block along with its descendants, so an
+-- admin job checks them against content.compute_block_stats().
+CREATE TABLE content.block_stats (
+	block_id UUID PRIMARY KEY REFERENCES content.blocks(id) ON DELETE CASCADE,
+	descendant_count BIGINT NOT NULL DEFAULT 0,
+	backlink_count BIGINT NOT NULL DEFAULT 0
+);
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON content.block_stats TO nuttyverse_navigator;
+
+-- Compute every block's deep counts from scratch.
+CREATE FUNCTION content.compute_block_stats()
+RETURNS TABLE (block_id UUID, descendant_count BIGINT, backlink_count BIGINT)
+LANGUAGE sql STABLE
+AS $$
+	WITH RECURSIVE lineage AS (
+		SELECT b.id AS descendant_id, b.parent_id AS ancestor_id
+		FROM content.blocks b
+		WHERE b.parent_id IS NOT NULL
+		UNION ALL
+		SELECT l.descendant_id, p.parent_id
+		FROM lineage l
+		JOIN content.blocks p ON p.id = l.ancestor_id
+		WHERE p.parent_id IS NOT NULL
+	)
+	SELECT b.id, COALESCE(d.count, 0), COALESCE(k.count, 0)
+	FROM content.blocks b
+	LEFT JOIN (
+		SELECT l.ancestor_id, COUNT(*) AS count FROM lineage l GROUP BY l.ancestor_id
+	) d ON d.ancestor_id = b.id
+	LEFT JOIN (
+		SELECT k.target_id, COUNT(*) AS count FROM content.links k GROUP BY k.target_id
+	) k ON k.target_id = b.id
+$$;
+
+-- Add to the descendant counts of a block and its ancestors. Ancestors are
+-- looked up as the function's owner, so that row-level security doesn't
+-- hide them.
+CREATE FUNCTION content.adjust_descendant_counts(start_id UUID, delta BIGINT)
+RETURNS VOID
+LANGUAGE sql VOLATILE SECURITY DEFINER
+SET search_path = pg_catalog, pg_temp
+AS $$
+	WITH RECURSIVE ancestors AS (
+		SELECT b.id, b.parent_id FROM content.blocks b WHERE b.id = start_id
+		UNION ALL
+		SELECT p.id, p.parent_id
+		FROM ancestors a
+		JOIN content.blocks p ON p.id = a.parent_id
+	)
+	UPDATE content.block_stats s
+	SET descendant_count = s.descendant_count + delta
+	FROM ancestors a
+	WHERE s.block_id = a.id
+$$;
+
+-- Deletions run before the row is gone, so that its ancestors can still be
+-- found. Descendants deleted in the same statement after their ancestor
+-- find nothing, since it was already counted with them.
+CREATE FUNCTION content.maintain_block_stats()
+RETURNS TRIGGER
+LANGUAGE plpgsql SECURITY DEFINER
+SET search_path = pg_catalog, pg_temp
+AS $$
+DECLARE
+	subtree_size BIGINT;
+BEGIN
+	IF TG_OP = 'INSERT' THEN
+		INSERT INTO content.block_stats (block_id) VALUES (NEW.id) ON CONFLICT DO NOTHING;
+		PERFORM content.adjust_descendant_counts(NEW.parent_id, 1);
+		RETURN NEW;
+	END IF;
+
+	SELECT 1 + s.descendant_count INTO subtree_size
+	FROM content.block_stats s
+	WHERE s.block_id = OLD.id;
+
+	subtree_size := COALESCE(subtree_size, 1);
+	PERFORM content.adjust_descendant_counts(OLD.parent_id, -subtree_size);
+
+	IF TG_OP = 'DELETE' THEN
+		RETURN OLD;
+	END IF;
+
+	PERFORM content.adjust_descendant_counts(NEW.parent_id, subtree_size);
+	RETURN NEW;
+END
+$$;
+
+CREATE TRIGGER maintain_content_blocks_stats_on_insert
+AFTER INSERT ON content.blocks
+FOR EACH ROW
+EXECUTE FUNCTION content.maintain_block_stats();
+
+CREATE TRIGGER maintain_content_blocks_stats_on_move
+AFTER UPDATE OF parent_id ON content.blocks
+FOR EACH ROW
+WHEN (OLD.parent_id IS DISTINCT FROM NEW.parent_id)
+EXECUTE FUNCTION content.maintain_block_stats();
+
+CREATE TRIGGER maintain_content_blocks_stats_on_delete
+BEFORE DELETE ON content.blocks
+FOR EACH ROW
+EXECUTE FUNCTION content.maintain_block_stats();
+
+CREATE FUNCTION content.maintain_backlink_counts()
+RETURNS TRIGGER
+LANGUAGE plpgsql SECURITY DEFINER
+SET search_path = pg_catalog, pg_temp
+AS $$
+BEGIN
+	IF TG_OP IN ('UPDATE', 'DELETE') THEN
+		UPDATE content.block_stats
+		SET backlink_count = backlink_count - 1
+		WHERE block_id = OLD.target_id;
+	END IF;
+
+	IF TG_OP IN ('INSERT', 'UPDATE') THEN
+		UPDATE content.block_stats
+		SET backlink_count = backlink_count + 1
+		WHERE block_id = NEW.target_id;
+	END IF;
+
+	RETURN NULL;
+END
+$$;
+
+CREATE TRIGGER maintain_content_links_backlink_counts
+AFTER INSERT OR DELETE OR UPDATE OF target_id ON content.links
+FOR EACH ROW
+EXECUTE FUNCTION content.maintain_backlink_counts();
+
+INSERT INTO content.block_stats (block_id, descendant_count, backlink_count)
+SELECT * FROM content.compute_block_stats();
+
+INSERT INTO auth.permissions (name, description) VALUES
+('system:block_stats:check', 'Can check and repair the deep counts of content blocks.');
+
+INSERT INTO auth.role_permissions (role_name, permission_name) VALUES
+('admin', 'system:block_stats:check');
+
+-- migrate:down
+DELETE FROM auth.role_permissions WHERE permission_name = 'system:block_stats:check';
+DELETE FROM auth.permissions WHERE name = 'system:block_stats:check';
+DROP TRIGGER IF EXISTS maintain_content_links_backlink_counts ON content.links;
+DROP FUNCTION IF EXISTS content.maintain_backlink_counts();
+DROP TRIGGER IF EXISTS maintain_content_blocks_stats_on_delete ON content.blocks;
+DROP TRIGGER IF EXISTS maintain_content_blocks_stats_on_move ON content.blocks;
+DROP TRIGGER IF EXISTS maintain_content_blocks_stats_on_insert ON content.blocks;
+DROP FUNCTION IF EXISTS content.maintain_block_stats();
+DROP FUNCTION IF EXISTS content.adjust_descendant_counts(UUID, BIGINT);
+DROP FUNCTION IF EXISTS content.compute_block_stats();
+DROP TABLE IF EXISTS content.block_stats;
@@ -0,0 +1,10 @@
+-- migrate:up
+INSERT INTO auth.permissions (name, description) VALUES
+('content_blocks:raw', 'Can read and write the raw JSON content of content blocks.');
+
+INSERT INTO auth.role_permissions (role_name, permission_name) VALUES
+('admin', 'content_blocks:raw');
+
+-- migrate:down
+DELETE FROM auth.role_permissions WHERE permission_name = 'content_blocks:raw';
+DELETE FROM auth.permissions WHERE name = 'content_blocks:raw';
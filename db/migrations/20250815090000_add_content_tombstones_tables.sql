@@ -0,0 +1,112 @@
+-- migrate:up
+-- Tombstones of deleted blocks and links, so that sync clients pulling
+-- changes since a point in time hear about removals too. A block's
+-- tombstone is kept per owner, so that a block transferred to another
+-- navigator is removed from its previous owner's clients.
+CREATE TABLE content.block_tombstones (
+	block_id UUID NOT NULL,
+	owner_id UUID NOT NULL REFERENCES auth.navigators(id) ON DELETE CASCADE,
+	deleted_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	PRIMARY KEY (block_id, owner_id)
+);
+
+CREATE INDEX block_tombstones_owner_id_deleted_at_idx ON content.block_tombstones(owner_id, deleted_at, block_id);
+
+-- A link's tombstone belongs to the owner of its source block.
+CREATE TABLE content.link_tombstones (
+	link_id UUID PRIMARY KEY,
+	source_id UUID NOT NULL,
+	target_id UUID NOT NULL,
+	owner_id UUID NOT NULL REFERENCES auth.navigators(id) ON DELETE CASCADE,
+	deleted_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX link_tombstones_owner_id_deleted_at_idx ON content.link_tombstones(owner_id, deleted_at, link_id);
+
+GRANT SELECT ON content.block_tombstones, content.link_tombstones TO nuttyverse_navigator;
+
+-- Links weren't timestamped, so existing ones count as created now.
+ALTER TABLE content.links ADD COLUMN created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL;
+
+CREATE INDEX blocks_owner_id_updated_at_idx ON content.blocks(owner_id, updated_at, id);
+
+-- Deletions run before the row is gone, so that links deleted along with
+-- the block can still find their owner in its tombstone.
+CREATE FUNCTION content.record_block_tombstone()
+RETURNS TRIGGER
+LANGUAGE plpgsql SECURITY DEFINER
+SET search_path = pg_catalog, pg_temp
+AS $$
+BEGIN
+	-- A deleted navigator's blocks are orphaned, but nobody's left to tell.
+	IF EXISTS (SELECT 1 FROM auth.navigators n WHERE n.id = OLD.owner_id) THEN
+		INSERT INTO content.block_tombstones (block_id, owner_id)
+		VALUES (OLD.id, OLD.owner_id)
+		ON CONFLICT (block_id, owner_id) DO UPDATE SET deleted_at = EXCLUDED.deleted_at;
+	END IF;
+
+	IF TG_OP = 'DELETE' THEN
+		RETURN OLD;
+	END IF;
+
+	RETURN NEW;
+END
+$$;
+
+CREATE TRIGGER record_content_blocks_tombstone_on_delete
+BEFORE DELETE ON content.blocks
+FOR EACH ROW
+EXECUTE FUNCTION content.record_block_tombstone();
+
+CREATE TRIGGER record_content_blocks_tombstone_on_transfer
+BEFORE UPDATE OF owner_id ON content.blocks
+FOR EACH ROW
+WHEN (OLD.owner_id IS DISTINCT FROM NEW.owner_id)
+EXECUTE FUNCTION content.record_block_tombstone();
+
+CREATE FUNCTION content.record_link_tombstone()
+RETURNS TRIGGER
+LANGUAGE plpgsql SECURITY DEFINER
+SET search_path = pg_catalog, pg_temp
+AS $$
+DECLARE
+	source_owner_id UUID;
+BEGIN
+	SELECT b.owner_id INTO source_owner_id
+	FROM content.blocks b
+	WHERE b.id = OLD.source_id;
+
+	-- The source may have been deleted in the same statement.
+	IF source_owner_id IS NULL THEN
+		SELECT t.owner_id INTO source_owner_id
+		FROM content.block_tombstones t
+		WHERE t.block_id = OLD.source_id
+		ORDER BY t.deleted_at DESC
+		LIMIT 1;
+	END IF;
+
+	IF EXISTS (SELECT 1 FROM auth.navigators n WHERE n.id = source_owner_id) THEN
+		INSERT INTO content.link_tombstones (link_id, source_id, target_id, owner_id)
+		VALUES (OLD.id, OLD.source_id, OLD.target_id, source_owner_id)
+		ON CONFLICT (link_id) DO UPDATE SET deleted_at = EXCLUDED.deleted_at;
+	END IF;
+
+	RETURN NULL;
+END
+$$;
+
+CREATE TRIGGER record_content_links_tombstone
+AFTER DELETE ON content.links
+FOR EACH ROW
+EXECUTE FUNCTION content.record_link_tombstone();
+
+-- migrate:down
+DROP TRIGGER IF EXISTS record_content_links_tombstone ON content.links;
+DROP FUNCTION IF EXISTS content.record_link_tombstone();
+DROP TRIGGER IF EXISTS record_content_blocks_tombstone_on_transfer ON content.blocks;
+DROP TRIGGER IF EXISTS record_content_blocks_tombstone_on_delete ON content.blocks;
+DROP FUNCTION IF EXISTS content.record_block_tombstone();
+DROP INDEX IF EXISTS content.blocks_owner_id_updated_at_idx;
+ALTER TABLE content.links DROP COLUMN IF EXISTS created_at;
+DROP TABLE IF EXISTS content.link_tombstones;
+DROP TABLE IF EXISTS content.block_tombstones;
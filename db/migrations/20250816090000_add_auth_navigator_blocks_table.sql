@@ -0,0 +1,17 @@
+-- migrate:up
+-- Navigators that another navigator has blocked from interacting with
+-- their content, e.g. from requesting access to it or commenting on it.
+CREATE TABLE auth.navigator_blocks (
+	blocker_id UUID NOT NULL REFERENCES auth.navigators(id) ON DELETE CASCADE,
+	blocked_id UUID NOT NULL REFERENCES auth.navigators(id) ON DELETE CASCADE,
+	created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	PRIMARY KEY (blocker_id, blocked_id),
+	CONSTRAINT navigator_blocks_self_check CHECK (blocker_id <> blocked_id)
+);
+
+CREATE INDEX navigator_blocks_blocked_id_idx ON auth.navigator_blocks(blocked_id);
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON auth.navigator_blocks TO nuttyverse_navigator;
+
+-- migrate:down
+DROP TABLE IF EXISTS auth.navigator_blocks;
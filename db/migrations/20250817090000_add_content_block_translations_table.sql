@@ -0,0 +1,84 @@
+-- migrate:up
+-- A block's language is a BCP 47 tag, e.g. `ja` or `zh-Hant-TW`, both for
+-- its translations and for how it's searched. Search languages are stored as
+-- the tags `en`, `ja`, and `und` for simple search.
+DROP INDEX content.blocks_japanese_search_idx;
+DROP INDEX content.blocks_simple_search_idx;
+DROP INDEX content.blocks_english_search_idx;
+
+ALTER TABLE content.blocks DROP CONSTRAINT blocks_language_check;
+ALTER TABLE content.blocks ALTER COLUMN language TYPE VARCHAR(35);
+
+UPDATE content.blocks
+SET language = CASE language
+	WHEN 'english' THEN 'en'
+	WHEN 'japanese' THEN 'ja'
+	ELSE 'und'
+END
+WHERE language IS NOT NULL;
+
+-- Get the search language of a block's language. Blocks without a language
+-- are searched as English.
+CREATE FUNCTION content.search_language(language VARCHAR)
+RETURNS VARCHAR
+LANGUAGE sql IMMUTABLE
+AS $$
+	SELECT CASE
+		WHEN language IS NULL OR language = 'en' OR language LIKE 'en-%' THEN 'english'
+		WHEN language = 'ja' OR language LIKE 'ja-%' THEN 'japanese'
+		ELSE 'simple'
+	END
+$$;
+
+CREATE INDEX blocks_english_search_idx ON content.blocks
+USING GIN (to_tsvector('english', COALESCE(content->>'markdown', content->>'title', '')))
+WHERE content.search_language(language) = 'english';
+
+CREATE INDEX blocks_simple_search_idx ON content.blocks
+USING GIN (to_tsvector('simple', COALESCE(content->>'markdown', content->>'title', '')))
+WHERE content.search_language(language) = 'simple';
+
+CREATE INDEX blocks_japanese_search_idx ON content.blocks
+USING GIN ((COALESCE(content->>'markdown', content->>'title', '')) gin_trgm_ops)
+WHERE content.search_language(language) = 'japanese';
+
+-- The groups of blocks that are translations of one another, e.g. the
+-- English and Japanese variants of the same note. Each variant is in its
+-- own language.
+CREATE TABLE content.block_translations (
+	block_id UUID PRIMARY KEY REFERENCES content.blocks(id) ON DELETE CASCADE,
+	group_id UUID NOT NULL
+);
+
+CREATE INDEX block_translations_group_id_idx ON content.block_translations(group_id);
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON content.block_translations TO nuttyverse_navigator;
+
+-- migrate:down
+DROP TABLE IF EXISTS content.block_translations;
+
+DROP INDEX IF EXISTS content.blocks_japanese_search_idx;
+DROP INDEX IF EXISTS content.blocks_simple_search_idx;
+DROP INDEX IF EXISTS content.blocks_english_search_idx;
+
+UPDATE content.blocks
+SET language = content.search_language(language)
+WHERE language IS NOT NULL;
+
+DROP FUNCTION IF EXISTS content.search_language(VARCHAR);
+
+ALTER TABLE content.blocks ALTER COLUMN language TYPE VARCHAR(16);
+ALTER TABLE content.blocks ADD CONSTRAINT blocks_language_check
+CHECK (language IN ('english', 'japanese', 'simple'));
+
+CREATE INDEX blocks_english_search_idx ON content.blocks
+USING GIN (to_tsvector('english', COALESCE(content->>'markdown', content->>'title', '')))
+WHERE COALESCE(language, 'english') = 'english';
+
+CREATE INDEX blocks_simple_search_idx ON content.blocks
+USING GIN (to_tsvector('simple', COALESCE(content->>'markdown', content->>'title', '')))
+WHERE language = 'simple';
+
+CREATE INDEX blocks_japanese_search_idx ON content.blocks
+USING GIN ((COALESCE(content->>'markdown', content->>'title', '')) gin_trgm_ops)
+WHERE language = 'japanese';
@@ -0,0 +1,8 @@
+-- migrate:up
+-- Compress big content with lz4 when it's TOASTed, which is faster than the
+-- default pglz. Postgres decompresses it transparently, so it stays readable
+-- by every query. Content stored before this keeps pglz until it's rewritten.
+ALTER TABLE content.blocks ALTER COLUMN content SET COMPRESSION lz4;
+
+-- migrate:down
+ALTER TABLE content.blocks ALTER COLUMN content SET COMPRESSION default;
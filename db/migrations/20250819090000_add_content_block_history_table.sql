@@ -0,0 +1,76 @@
+-- migrate:up
+-- Every version of every content block, valid from when it was saved until
+-- it was replaced or deleted, for reading a tree as it was at a time.
+-- Blocks that existed before this table are recorded as of their last
+-- update, so earlier times don't include them.
+CREATE TABLE content.block_history (
+	id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+	block_id UUID NOT NULL,
+	nutty_id VARCHAR(7) NOT NULL,
+	owner_id UUID,
+	parent_id UUID,
+	f_index TEXT NOT NULL,
+	content JSONB NOT NULL,
+	properties JSONB NOT NULL,
+	inherit_access BOOLEAN NOT NULL,
+	created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+	valid_from TIMESTAMP WITH TIME ZONE NOT NULL,
+	valid_to TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX block_history_nutty_id_idx ON content.block_history(nutty_id, valid_from);
+CREATE INDEX block_history_parent_id_idx ON content.block_history(parent_id, valid_from);
+CREATE INDEX block_history_block_id_idx ON content.block_history(block_id) WHERE valid_to IS NULL;
+
+INSERT INTO content.block_history (
+	block_id, nutty_id, owner_id, parent_id, f_index, content, properties,
+	inherit_access, created_at, valid_from
+)
+SELECT
+	id, nutty_id, owner_id, parent_id, f_index, content, properties,
+	inherit_access, created_at, updated_at
+FROM content.blocks;
+
+-- Close the current version of a block, and open a new one unless it was
+-- deleted. Versions saved in the same transaction are valid for no time.
+CREATE FUNCTION content.record_block_history()
+RETURNS TRIGGER AS $$
+BEGIN
+	IF TG_OP IN ('UPDATE', 'DELETE') THEN
+		UPDATE content.block_history
+		SET valid_to = NOW()
+		WHERE block_id = OLD.id AND valid_to IS NULL;
+	END IF;
+
+	IF TG_OP IN ('INSERT', 'UPDATE') THEN
+		INSERT INTO content.block_history (
+			block_id, nutty_id, owner_id, parent_id, f_index, content, properties,
+			inherit_access, created_at, valid_from
+		)
+		VALUES (
+			NEW.id, NEW.nutty_id, NEW.owner_id, NEW.parent_id, NEW.f_index, NEW.content,
+			NEW.properties, NEW.inherit_access, NEW.created_at, NOW()
+		);
+	END IF;
+
+	RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER record_content_blocks_history_on_insert_or_delete
+AFTER INSERT OR DELETE ON content.blocks
+FOR EACH ROW
+EXECUTE FUNCTION content.record_block_history();
+
+CREATE TRIGGER record_content_blocks_history_on_update
+AFTER UPDATE OF owner_id, parent_id, f_index, content, properties, inherit_access ON content.blocks
+FOR EACH ROW
+EXECUTE FUNCTION content.record_block_history();
+
+GRANT SELECT, INSERT, UPDATE ON content.block_history TO nuttyverse_navigator;
+
+-- migrate:down
+DROP TRIGGER IF EXISTS record_content_blocks_history_on_update ON content.blocks;
+DROP TRIGGER IF EXISTS record_content_blocks_history_on_insert_or_delete ON content.blocks;
+DROP FUNCTION IF EXISTS content.record_block_history;
+DROP TABLE IF EXISTS content.block_history;
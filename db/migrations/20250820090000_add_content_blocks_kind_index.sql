@@ -0,0 +1,8 @@
+-- migrate:up
+-- Lists blocks by kind, optionally of one owner, newest first, without
+-- reading every block's content.
+CREATE INDEX blocks_kind_owner_id_created_at_idx
+ON content.blocks ((content->>'kind'), owner_id, created_at DESC);
+
+-- migrate:down
+DROP INDEX IF EXISTS content.blocks_kind_owner_id_created_at_idx;
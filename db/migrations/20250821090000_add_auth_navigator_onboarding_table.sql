@@ -0,0 +1,27 @@
+-- migrate:up
+-- The onboarding steps that navigators have completed.
+CREATE TABLE auth.navigator_onboarding (
+	navigator_id UUID NOT NULL REFERENCES auth.navigators(id) ON DELETE CASCADE,
+	step VARCHAR(32) NOT NULL,
+	completed_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL,
+	PRIMARY KEY (navigator_id, step)
+);
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON auth.navigator_onboarding TO nuttyverse_navigator;
+
+-- The role that new navigators are granted with their starter content, so
+-- that they can read and edit what they own.
+INSERT INTO auth.roles (name, description) VALUES
+('block_owner', 'Can view, create, update, and delete own content blocks.')
+ON CONFLICT (name) DO NOTHING;
+
+INSERT INTO auth.role_permissions (role_name, permission_name) VALUES
+('block_owner', 'content_blocks:read:own'),
+('block_owner', 'content_blocks:write:own')
+ON CONFLICT (role_name, permission_name) DO NOTHING;
+
+-- migrate:down
+DELETE FROM auth.role_permissions WHERE role_name = 'block_owner';
+DELETE FROM auth.navigator_roles WHERE role_name = 'block_owner';
+DELETE FROM auth.roles WHERE name = 'block_owner';
+DROP TABLE IF EXISTS auth.navigator_onboarding;
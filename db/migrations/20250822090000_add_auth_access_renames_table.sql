@@ -0,0 +1,27 @@
+-- migrate:up
+-- An audit log of renamed roles and permissions.
+CREATE TABLE auth.access_renames (
+	id UUID PRIMARY KEY,
+	nutty_id VARCHAR(7) NOT NULL,
+	kind VARCHAR(20) NOT NULL CHECK (kind IN ('role', 'permission')),
+	old_name VARCHAR(100) NOT NULL,
+	new_name VARCHAR(100) NOT NULL,
+	grant_count BIGINT NOT NULL,
+	renamed_by UUID REFERENCES auth.navigators(id) ON DELETE SET NULL,
+	created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX access_renames_created_at_idx ON auth.access_renames(created_at DESC);
+
+GRANT SELECT, INSERT, UPDATE, DELETE ON auth.access_renames TO nuttyverse_navigator;
+
+INSERT INTO auth.permissions (name, description) VALUES
+('system:access:rename', 'Can rename roles and permissions.');
+
+INSERT INTO auth.role_permissions (role_name, permission_name) VALUES
+('admin', 'system:access:rename');
+
+-- migrate:down
+DELETE FROM auth.role_permissions WHERE permission_name = 'system:access:rename';
+DELETE FROM auth.permissions WHERE name = 'system:access:rename';
+DROP TABLE IF EXISTS auth.access_renames;
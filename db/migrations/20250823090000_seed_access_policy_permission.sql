@@ -0,0 +1,10 @@
+-- migrate:up
+INSERT INTO auth.permissions (name, description) VALUES
+('system:access:policy', 'Can export and apply access policies.');
+
+INSERT INTO auth.role_permissions (role_name, permission_name) VALUES
+('admin', 'system:access:policy');
+
+-- migrate:down
+DELETE FROM auth.role_permissions WHERE permission_name = 'system:access:policy';
+DELETE FROM auth.permissions WHERE name = 'system:access:policy';
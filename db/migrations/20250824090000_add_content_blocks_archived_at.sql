@@ -0,0 +1,7 @@
+-- migrate:up
+-- Archived blocks are kept, but left out of listings, search, and their
+-- parents' children unless asked for.
+ALTER TABLE content.blocks ADD COLUMN archived_at TIMESTAMP WITH TIME ZONE;
+
+-- migrate:down
+ALTER TABLE content.blocks DROP COLUMN IF EXISTS archived_at;
@@ -0,0 +1,23 @@
+-- migrate:up
+-- The requests that navigators made between flushes of the API's request
+-- metrics, as latency histograms, to find the heaviest users over a window.
+-- Only the server writes them, outside of any navigator's request.
+CREATE TABLE auth.navigator_request_stats (
+	id BIGSERIAL PRIMARY KEY,
+	navigator_id UUID NOT NULL REFERENCES auth.navigators(id) ON DELETE CASCADE,
+	request_count BIGINT NOT NULL,
+	request_bytes BIGINT NOT NULL,
+	response_bytes BIGINT NOT NULL,
+	total_ms DOUBLE PRECISION NOT NULL,
+	max_ms DOUBLE PRECISION NOT NULL,
+	latency_buckets BIGINT[] NOT NULL,
+	recorded_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX navigator_request_stats_recorded_at_idx
+ON auth.navigator_request_stats (recorded_at);
+
+GRANT SELECT ON auth.navigator_request_stats TO nuttyverse_navigator;
+
+-- migrate:down
+DROP TABLE IF EXISTS auth.navigator_request_stats;
@@ -0,0 +1,12 @@
+-- migrate:up
+-- Links whose author couldn't read the target when saving them, kept when
+-- the link access policy marks them rather than dropping them.
+ALTER TABLE content.links ADD COLUMN restricted BOOLEAN NOT NULL DEFAULT false;
+
+-- Links are updated now, so give their update trigger its column.
+ALTER TABLE content.links
+ADD COLUMN updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP;
+
+-- migrate:down
+ALTER TABLE content.links DROP COLUMN IF EXISTS updated_at;
+ALTER TABLE content.links DROP COLUMN IF EXISTS restricted;
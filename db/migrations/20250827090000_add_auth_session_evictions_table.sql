@@ -0,0 +1,15 @@
+-- migrate:up
+-- Sessions evicted to keep navigators within the session limit, so that the
+-- evicted devices can be told why they were signed out. They're only worth
+-- keeping until the sessions would have expired anyway.
+CREATE TABLE auth.session_evictions (
+	token_hash TEXT PRIMARY KEY,
+	navigator_id UUID NOT NULL REFERENCES auth.navigators(id) ON DELETE CASCADE,
+	expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+	evicted_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX session_evictions_expires_at_idx ON auth.session_evictions(expires_at);
+
+-- migrate:down
+DROP TABLE IF EXISTS auth.session_evictions;
@@ -0,0 +1,10 @@
+-- migrate:up
+INSERT INTO auth.permissions (name, description) VALUES
+('system:schema:read', 'Can read the documentation of the database schema.');
+
+INSERT INTO auth.role_permissions (role_name, permission_name) VALUES
+('admin', 'system:schema:read');
+
+-- migrate:down
+DELETE FROM auth.role_permissions WHERE permission_name = 'system:schema:read';
+DELETE FROM auth.permissions WHERE name = 'system:schema:read';
@@ -0,0 +1,22 @@
+-- migrate:up
+-- The URLs that navigators have asked to be sent events at, and which
+-- events they want. Deliveries are signed with the subscription's secret.
+CREATE TABLE auth.webhook_subscriptions (
+	id UUID PRIMARY KEY,
+	navigator_id UUID NOT NULL REFERENCES auth.navigators(id) ON DELETE CASCADE,
+	url TEXT NOT NULL,
+	event_types TEXT[] NOT NULL,
+	secret TEXT NOT NULL,
+	created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX webhook_subscriptions_navigator_id_idx
+ON auth.webhook_subscriptions (navigator_id);
+
+CREATE INDEX webhook_subscriptions_event_types_idx
+ON auth.webhook_subscriptions USING GIN (event_types);
+
+GRANT SELECT, INSERT, DELETE ON auth.webhook_subscriptions TO nuttyverse_navigator;
+
+-- migrate:down
+DROP TABLE IF EXISTS auth.webhook_subscriptions;
@@ -0,0 +1,115 @@
+-- migrate:up
+-- Check if a content block is hidden by moderation, itself or through a
+-- hidden ancestor. Ancestors are looked up as the function's owner, so that
+-- they aren't filtered too.
+CREATE FUNCTION content.is_hidden(target_id UUID)
+RETURNS BOOLEAN
+LANGUAGE sql STABLE SECURITY DEFINER
+SET search_path = pg_catalog, pg_temp
+AS $$
+	WITH RECURSIVE lineage AS (
+		SELECT id, parent_id, moderation_status
+		FROM content.blocks
+		WHERE id = target_id
+		UNION ALL
+		SELECT b.id, b.parent_id, b.moderation_status
+		FROM lineage l
+		JOIN content.blocks b ON b.id = l.parent_id
+	)
+	SELECT EXISTS (SELECT 1 FROM lineage WHERE moderation_status = 'hidden')
+$$;
+
+-- Hidden blocks, and their descendants, are only visible to moderators, and
+-- only moderators can change them.
+DROP POLICY blocks_select ON content.blocks;
+
+CREATE POLICY blocks_select ON content.blocks
+FOR SELECT TO nuttyverse_navigator
+USING (
+	auth.has_global_permission('moderation:review')
+	OR (
+		NOT content.is_hidden(id)
+		AND content.navigator_can('read', id, parent_id, owner_id, inherit_access)
+	)
+);
+
+DROP POLICY blocks_insert ON content.blocks;
+
+CREATE POLICY blocks_insert ON content.blocks
+FOR INSERT TO nuttyverse_navigator
+WITH CHECK (
+	content.navigator_can('write', id, parent_id, owner_id, inherit_access)
+	AND (parent_id IS NULL OR NOT content.is_hidden(parent_id))
+);
+
+DROP POLICY blocks_update ON content.blocks;
+
+CREATE POLICY blocks_update ON content.blocks
+FOR UPDATE TO nuttyverse_navigator
+USING (
+	(
+		content.navigator_can('write', id, parent_id, owner_id, inherit_access)
+		AND NOT content.is_hidden(id)
+	)
+	OR auth.has_global_permission('moderation:review')
+	OR auth.has_global_permission('content_blocks:transfer')
+)
+WITH CHECK (
+	content.navigator_can('write', id, parent_id, owner_id, inherit_access)
+	OR auth.has_global_permission('moderation:review')
+	OR auth.has_global_permission('content_blocks:transfer')
+);
+
+DROP POLICY blocks_delete ON content.blocks;
+
+CREATE POLICY blocks_delete ON content.blocks
+FOR DELETE TO nuttyverse_navigator
+USING (
+	content.navigator_can('write', id, parent_id, owner_id, inherit_access)
+	AND (
+		NOT content.is_hidden(id)
+		OR auth.has_global_permission('moderation:review')
+	)
+);
+
+-- migrate:down
+DROP POLICY IF EXISTS blocks_delete ON content.blocks;
+
+CREATE POLICY blocks_delete ON content.blocks
+FOR DELETE TO nuttyverse_navigator
+USING (content.navigator_can('write', id, parent_id, owner_id, inherit_access));
+
+DROP POLICY IF EXISTS blocks_update ON content.blocks;
+
+CREATE POLICY blocks_update ON content.blocks
+FOR UPDATE TO nuttyverse_navigator
+USING (
+	content.navigator_can('write', id, parent_id, owner_id, inherit_access)
+	OR auth.has_global_permission('moderation:review')
+	OR auth.has_global_permission('content_blocks:transfer')
+)
+WITH CHECK (
+	content.navigator_can('write', id, parent_id, owner_id, inherit_access)
+	OR auth.has_global_permission('moderation:review')
+	OR auth.has_global_permission('content_blocks:transfer')
+);
+
+DROP POLICY IF EXISTS blocks_insert ON content.blocks;
+
+CREATE POLICY blocks_insert ON content.blocks
+FOR INSERT TO nuttyverse_navigator
+WITH CHECK (content.navigator_can('write', id, parent_id, owner_id, inherit_access));
+
+DROP POLICY IF EXISTS blocks_select ON content.blocks;
+
+CREATE POLICY blocks_select ON content.blocks
+FOR SELECT TO nuttyverse_navigator
+USING (
+	auth.has_global_permission('moderation:review')
+	OR (
+		moderation_status <> 'hidden'
+		AND content.navigator_can('read', id, parent_id, owner_id, inherit_access)
+	)
+);
+
+DROP FUNCTION IF EXISTS content.is_hidden(UUID);
@@ -0,0 +1,63 @@
+-- migrate:up
+-- Check if a navigator has a permission through a global role. Unlike
+-- auth.has_global_permission, the navigator is given rather than taken from
+-- the current request, for queries that filter on a navigator's behalf
+-- without row-level security.
+CREATE FUNCTION auth.navigator_has_permission(viewer_id UUID, permission TEXT)
+RETURNS BOOLEAN
+LANGUAGE sql STABLE
+AS $$
+	SELECT EXISTS (
+		SELECT 1 FROM auth.navigator_roles nr
+		JOIN auth.role_permissions rp ON rp.role_name = nr.role_name
+		WHERE nr.navigator_id = viewer_id
+			AND rp.permission_name = permission
+	)
+$$;
+
+-- Check if a navigator can read a content block, by the same rules as the
+-- API's access check: hidden blocks, and the blocks under them, only by
+-- moderators; others by a global permission, ownership, or a resource role
+-- on the block or an ancestor that it inherits access from.
+CREATE FUNCTION content.navigator_can_read(
+	viewer_id UUID,
+	target_id UUID,
+	target_parent_id UUID,
+	target_owner_id UUID,
+	target_inherit_access BOOLEAN
+)
+RETURNS BOOLEAN
+LANGUAGE sql STABLE SECURITY DEFINER
+SET search_path = pg_catalog, pg_temp
+AS $$
+	WITH RECURSIVE ancestors AS (
+		SELECT target_id AS id, target_parent_id AS parent_id, target_inherit_access AS inherit_access
+		UNION ALL
+		SELECT b.id, b.parent_id, b.inherit_access
+		FROM ancestors a
+		JOIN content.blocks b ON b.id = a.parent_id
+		WHERE a.inherit_access
+	)
+	SELECT CASE
+		WHEN content.is_hidden(target_id) THEN
+			auth.navigator_has_permission(viewer_id, 'moderation:review')
+		ELSE
+			auth.navigator_has_permission(viewer_id, 'content_blocks:read:all')
+			OR auth.navigator_has_permission(viewer_id, 'content_blocks:read:resource')
+			OR (
+				target_owner_id = viewer_id
+				AND auth.navigator_has_permission(viewer_id, 'content_blocks:read:own')
+			)
+			OR EXISTS (
+				SELECT 1 FROM ancestors a
+				JOIN auth.resource_roles rr ON rr.resource_type = 'content_block' AND rr.resource_id = a.id
+				JOIN auth.role_permissions rp ON rp.role_name = rr.role_name
+				WHERE rr.navigator_id = viewer_id
+					AND rp.permission_name = 'content_blocks:read:resource'
+			)
+	END
+$$;
+
+-- migrate:down
+DROP FUNCTION IF EXISTS content.navigator_can_read(UUID, UUID, UUID, UUID, BOOLEAN);
+DROP FUNCTION IF EXISTS auth.navigator_has_permission(UUID, TEXT);
@@ -0,0 +1,8 @@
+-- migrate:up
+-- The version of a navigator's calendar feed token, which is signed into the
+-- token. Regenerating the token bumps it, revoking the tokens before.
+ALTER TABLE auth.navigators
+ADD COLUMN feed_token_version INTEGER NOT NULL DEFAULT 0;
+
+-- migrate:down
+ALTER TABLE auth.navigators DROP COLUMN IF EXISTS feed_token_version;
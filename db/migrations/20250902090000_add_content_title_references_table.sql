@@ -0,0 +1,58 @@
+-- migrate:up
+-- The page titles that blocks reference by title alone ([[Title]]), so that
+-- renaming a page finds them without reading every block. Kept up to date
+-- whenever a block is saved.
+CREATE TABLE content.title_references (
+	block_id UUID NOT NULL,
+	title TEXT NOT NULL,
+	CONSTRAINT title_references_pkey PRIMARY KEY (block_id, title),
+	CONSTRAINT title_references_block_id_fkey FOREIGN KEY (block_id) REFERENCES content.blocks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX title_references_title_idx ON content.title_references(title);
+
+INSERT INTO content.title_references (block_id, title)
+SELECT DISTINCT id, m[1]
+FROM content.blocks, regexp_matches(content->>'markdown', '\[\[([^][|]+)\]\]', 'g') AS m
+ON CONFLICT DO NOTHING;
+
+-- Check if a navigator can write a content block, by the same rules as the
+-- API's access check: a global permission, ownership, or a resource role on
+-- the block or an ancestor that it inherits access from. Hidden blocks are
+-- left to callers.
+CREATE FUNCTION content.navigator_can_write(
+	viewer_id UUID,
+	target_id UUID,
+	target_parent_id UUID,
+	target_owner_id UUID,
+	target_inherit_access BOOLEAN
+)
+RETURNS BOOLEAN
+LANGUAGE sql STABLE SECURITY DEFINER
+SET search_path = pg_catalog, pg_temp
+AS $$
+	WITH RECURSIVE ancestors AS (
+		SELECT target_id AS id, target_parent_id AS parent_id, target_inherit_access AS inherit_access
+		UNION ALL
+		SELECT b.id, b.parent_id, b.inherit_access
+		FROM ancestors a
+		JOIN content.blocks b ON b.id = a.parent_id
+		WHERE a.inherit_access
+	)
+	SELECT auth.navigator_has_permission(viewer_id, 'content_blocks:write:all')
+		OR (
+			target_owner_id = viewer_id
+			AND auth.navigator_has_permission(viewer_id, 'content_blocks:write:own')
+		)
+		OR EXISTS (
+			SELECT 1 FROM ancestors a
+			JOIN auth.resource_roles rr ON rr.resource_type = 'content_block' AND rr.resource_id = a.id
+			JOIN auth.role_permissions rp ON rp.role_name = rr.role_name
+			WHERE rr.navigator_id = viewer_id
+				AND rp.permission_name = 'content_blocks:write'
+		)
+$$;
+
+-- migrate:down
+DROP FUNCTION IF EXISTS content.navigator_can_write(UUID, UUID, UUID, UUID, BOOLEAN);
+DROP TABLE IF EXISTS content.title_references;